@@ -0,0 +1,239 @@
+//! Benchmarks for the database paths that run on every sync and every
+//! scroll of the mail list
+//!
+//! Each group works on an in-memory database migrated to the current
+//! schema and seeded with `MAILBOX_SIZE` messages spread over a realistic
+//! set of labels, so query plans and FTS index sizes match a mid-sized
+//! account. Writes are rolled back, or deleted again before the next
+//! iteration, keeping the mailbox the same size from one to the next.
+//!
+//! Run with `make bench`; see the Makefile for recording the local
+//! baseline it compares against.
+
+use std::cell::RefCell;
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use rusqlite::Connection;
+use serde_json::json;
+
+use tauri_app_lib::database::operations::archive_operations;
+use tauri_app_lib::database::operations::backfill_operations::{self, StoredMessage};
+use tauri_app_lib::database::operations::label_cache_operations::{self, CachedLabel, LabelCounts};
+use tauri_app_lib::database::operations::message_header_operations::{self, HeaderFilter, HeaderSort};
+use tauri_app_lib::database::operations::search_operations::{self, SearchDomain};
+use tauri_app_lib::database::operations::sync_policy_operations::{LabelSyncPolicy, SyncContent};
+use tauri_app_lib::database::operations::workspace_operations::HiddenIds;
+use tauri_app_lib::database::schema::run_migrations;
+use tauri_app_lib::services::gmail::api_service::ProcessedGmailMessage;
+use tauri_app_lib::services::gmail::sync_service;
+
+const ACCOUNT: &str = "bench";
+const MAILBOX_SIZE: usize = 5_000;
+/// Messages handled per sync batch, Gmail's batch call limit
+const SYNC_BATCH: usize = 100;
+
+const USER_LABELS: [&str; 12] = [
+    "Label_Receipts", "Label_Travel", "Label_Family", "Label_Work", "Label_Newsletters", "Label_Finance",
+    "Label_Projects", "Label_Hiring", "Label_Support", "Label_Events", "Label_Archive2023", "Label_Reading",
+];
+const WORDS: [&str; 16] = [
+    "budget", "invoice", "meeting", "travel", "schedule", "report", "review", "launch",
+    "dinner", "contract", "quarterly", "shipping", "update", "offsite", "proposal", "agenda",
+];
+
+/// A parsed message as the sync stores it; `n` varies the content
+fn message(n: usize) -> ProcessedGmailMessage {
+    let word = |i: usize| WORDS[(n * 7 + i * 3) % WORDS.len()];
+    let mut labels = vec!["INBOX".to_string(), USER_LABELS[n % USER_LABELS.len()].to_string()];
+    if n.is_multiple_of(3) {
+        labels.push("UNREAD".to_string());
+    }
+    if n.is_multiple_of(17) {
+        labels.push("STARRED".to_string());
+    }
+    let body = (0..60).map(word).collect::<Vec<_>>().join(" ");
+
+    serde_json::from_value(json!({
+        "id": format!("msg{:06}", n),
+        "thread_id": format!("thr{:06}", n / 3),
+        "parsed_content": {
+            "message_id": format!("msg{:06}", n),
+            "thread_id": format!("thr{:06}", n / 3),
+            "subject": format!("{} {} #{}", word(0), word(1), n),
+            "from": { "email": format!("sender{}@example.com", n % 250), "name": format!("Sender {}", n % 250) },
+            "to": [{ "email": "bench@example.com", "name": null }],
+            "cc": [],
+            "bcc": [],
+            "reply_to": null,
+            "date": "Wed, 1 May 2024 10:00:00 +0000",
+            "body_text": body,
+            "body_html": format!("<div>{}</div>", body),
+            "attachments": [],
+            "headers": {},
+            "is_multipart": true,
+            "content_type": "multipart/alternative",
+            "size_estimate": null,
+        },
+        "labels": labels,
+        "snippet": body.chars().take(120).collect::<String>(),
+        // A message every ten minutes, going back from May 2024
+        "internal_date": (1_714_557_600_000i64 - n as i64 * 600_000).to_string(),
+        "size_estimate": 4096,
+    }))
+    .expect("bench message")
+}
+
+fn stored(message: &ProcessedGmailMessage) -> StoredMessage {
+    StoredMessage {
+        account_id: ACCOUNT.to_string(),
+        message_id: message.id.clone(),
+        thread_id: message.thread_id.clone(),
+        label_ids: message.labels.clone(),
+        internal_date: message.internal_date.as_deref().and_then(|d| d.parse().ok()),
+        content: "full".to_string(),
+        message_data: serde_json::to_value(message).expect("bench message data"),
+    }
+}
+
+/// A migrated database holding `MAILBOX_SIZE` messages and their labels
+fn mailbox() -> Connection {
+    let mut conn = Connection::open_in_memory().expect("in-memory database");
+    run_migrations(&conn).expect("migrations");
+
+    let tx = conn.transaction().expect("seed transaction");
+    for n in 0..MAILBOX_SIZE {
+        backfill_operations::store_message(&tx, &stored(&message(n))).expect("seed message");
+    }
+    tx.commit().expect("seed commit");
+
+    let labels: Vec<CachedLabel> = ["INBOX", "UNREAD", "STARRED"]
+        .into_iter()
+        .chain(USER_LABELS)
+        .map(|id| CachedLabel { label_id: id.to_string(), name: id.to_string(), ..CachedLabel::default() })
+        .collect();
+    label_cache_operations::replace_labels(&mut conn, ACCOUNT, &labels).expect("seed labels");
+    conn
+}
+
+fn message_cache(c: &mut Criterion) {
+    let conn = mailbox();
+    let incoming: Vec<StoredMessage> = (MAILBOX_SIZE..MAILBOX_SIZE + SYNC_BATCH).map(|n| stored(&message(n))).collect();
+    let mut group = c.benchmark_group("message_cache");
+
+    group.throughput(Throughput::Elements(SYNC_BATCH as u64));
+    group.bench_function("insert_batch", |b| {
+        b.iter(|| {
+            let tx = conn.unchecked_transaction().unwrap();
+            for message in &incoming {
+                backfill_operations::store_message(&tx, message).unwrap();
+            }
+            tx.rollback().unwrap();
+        })
+    });
+
+    group.throughput(Throughput::Elements(50));
+    let inbox = HeaderFilter { label_id: Some("INBOX".to_string()), ..HeaderFilter::default() };
+    group.bench_function("header_window_first", |b| {
+        b.iter(|| message_header_operations::get_headers(&conn, ACCOUNT, &inbox, HeaderSort::DateDesc, 50, None).unwrap())
+    });
+    // The cursor of the window ending around row 4,000
+    let mut deep_cursor = None;
+    for _ in 0..8 {
+        deep_cursor = message_header_operations::get_headers(&conn, ACCOUNT, &inbox, HeaderSort::DateDesc, 500, deep_cursor.as_deref())
+            .unwrap()
+            .next_cursor;
+    }
+    group.bench_function("header_window_deep", |b| {
+        b.iter(|| message_header_operations::get_headers(&conn, ACCOUNT, &inbox, HeaderSort::DateDesc, 50, deep_cursor.as_deref()).unwrap())
+    });
+    let unread = HeaderFilter { unread_only: true, ..HeaderFilter::default() };
+    group.bench_function("header_window_unread_by_sender", |b| {
+        b.iter(|| message_header_operations::get_headers(&conn, ACCOUNT, &unread, HeaderSort::Sender, 50, None).unwrap())
+    });
+    group.finish();
+}
+
+fn fts_search(c: &mut Criterion) {
+    let conn = mailbox();
+    let mut group = c.benchmark_group("fts_search");
+
+    for (name, query) in [("one_word", "budget"), ("two_words", "quarterly invoice"), ("rare_word", "sender17")] {
+        let query = search_operations::fts_query(query);
+        group.bench_function(name, |b| {
+            b.iter(|| search_operations::search_domain(&conn, SearchDomain::Mail, black_box(&query), Some(ACCOUNT), &HiddenIds::default(), 50).unwrap())
+        });
+    }
+    let prefix = search_operations::fts_prefix_query("quarterly inv");
+    group.bench_function("prefix", |b| {
+        b.iter(|| search_operations::search_domain(&conn, SearchDomain::Mail, black_box(&prefix), Some(ACCOUNT), &HiddenIds::default(), 50).unwrap())
+    });
+    group.finish();
+}
+
+fn label_counts(c: &mut Criterion) {
+    let mut conn = mailbox();
+    let mut group = c.benchmark_group("label_counts");
+
+    group.bench_function("aggregate_from_store", |b| {
+        b.iter(|| archive_operations::list_archive_folders(&conn, ACCOUNT).unwrap())
+    });
+
+    let counts: Vec<LabelCounts> = archive_operations::list_archive_folders(&conn, ACCOUNT)
+        .unwrap()
+        .into_iter()
+        .map(|folder| LabelCounts {
+            label_id: folder.label,
+            messages_total: Some(folder.message_count),
+            messages_unread: Some(folder.message_count / 3),
+            threads_total: Some(folder.message_count / 3),
+            threads_unread: Some(folder.message_count / 9),
+        })
+        .collect();
+    group.bench_function("store_refreshed_counts", |b| {
+        b.iter(|| label_cache_operations::update_counts(&mut conn, ACCOUNT, black_box(&counts), 1_714_557_600).unwrap())
+    });
+    group.bench_function("read_cached_labels", |b| {
+        b.iter(|| label_cache_operations::get_labels(&conn, ACCOUNT).unwrap())
+    });
+    group.finish();
+}
+
+fn sync_batch(c: &mut Criterion) {
+    let conn = RefCell::new(mailbox());
+    let fetched: Vec<ProcessedGmailMessage> = (MAILBOX_SIZE..MAILBOX_SIZE + SYNC_BATCH).map(message).collect();
+    let policy = |label: &str, enabled: bool, content: SyncContent| LabelSyncPolicy {
+        label_id: label.to_string(),
+        enabled,
+        max_age_days: None,
+        content,
+    };
+    let policies = vec![
+        policy("INBOX", true, SyncContent::Full),
+        policy("Label_Newsletters", true, SyncContent::Headers),
+        policy("Label_Archive2023", false, SyncContent::Headers),
+    ];
+    let mut group = c.benchmark_group("sync_batch");
+    group.throughput(Throughput::Elements(SYNC_BATCH as u64));
+
+    // The database half of a sync page, as `sync_message_batch` runs it
+    // once the messages are fetched
+    let ids: Vec<String> = fetched.iter().map(|message| message.id.clone()).collect();
+    group.bench_function("apply_policies_and_store", |b| {
+        b.iter_batched(
+            || {
+                backfill_operations::delete_messages(&conn.borrow(), ACCOUNT, &ids).unwrap();
+                fetched.clone()
+            },
+            |batch| {
+                let mut conn = conn.borrow_mut();
+                black_box(sync_service::unstored_ids(&conn, ACCOUNT, ids.clone()).unwrap());
+                sync_service::store_fetched(&mut conn, ACCOUNT, &policies, batch).unwrap()
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, message_cache, fts_search, label_counts, sync_batch);
+criterion_main!(benches);
@@ -0,0 +1,159 @@
+# Backend strings shown to the user. Dates use chrono format strings.
+
+## Errors
+
+error-gmail-auth = Die Gmail-Anmeldung ist fehlgeschlagen. Bitte melde dich erneut an.
+error-gmail-api = Gmail ist vorübergehend nicht erreichbar. Bitte versuche es später noch einmal.
+error-database-connection = Die Verbindung zur Datenbank ist fehlgeschlagen. Bitte starte die Anwendung neu.
+error-network = Die Netzwerkverbindung ist fehlgeschlagen. Bitte prüfe deine Internetverbindung.
+error-rate-limit = Zu viele Anfragen. Bitte warte einen Moment und versuche es dann erneut.
+error-invalid-input = Ungültige Eingabe. Bitte prüfe deine Angaben und versuche es erneut.
+error-unexpected = Ein unerwarteter Fehler ist aufgetreten. Bitte versuche es erneut.
+
+## Daily digest
+
+digest-date-format = %A, %-d. %B
+digest-short-date-format = %-d. %b
+digest-title = Tagesübersicht für { $date }
+digest-important-mail = { $count ->
+    [one] { $count } wichtige E-Mail
+   *[other] { $count } wichtige E-Mails
+}
+digest-events = { $count ->
+    [one] { $count } Termin
+   *[other] { $count } Termine
+}
+digest-tasks-due = { $count ->
+    [one] { $count } fällige Aufgabe
+   *[other] { $count } fällige Aufgaben
+}
+digest-follow-ups = { $count ->
+    [one] { $count } Nachverfolgung
+   *[other] { $count } Nachverfolgungen
+}
+digest-all-day = Ganztägig
+digest-overdue = (überfällig seit { $date })
+digest-none = (keine)
+digest-mail-heading = Wichtige ungelesene E-Mails
+digest-mail-empty = Nichts offen.
+digest-events-heading = Heutige Termine
+digest-events-empty = Heute keine Termine.
+digest-tasks-heading = Fällige Aufgaben
+digest-tasks-empty = Nichts fällig.
+digest-follow-ups-heading = Nachverfolgen
+digest-follow-ups-empty = Keine markierten Nachrichten.
+
+## Model memory checks
+
+resource-fits-gpu = Passt in { $gpu } Grafikspeicher
+resource-tight-gpu = Braucht etwa { $required }, die GPU hat aber nur { $gpu }; ein Teil läuft langsam auf der CPU
+resource-fits-cpu = Läuft auf der CPU innerhalb von { $available } Arbeitsspeicher
+resource-tight-cpu = Braucht etwa { $required } von { $system } Arbeitsspeicher; andere Programme können langsamer werden
+resource-too-large = Braucht etwa { $required }, dieser Rechner hat aber nur { $system } Arbeitsspeicher
+
+## Recipient checks
+
+recipient-invalid = „{ $email }“ ist keine gültige E-Mail-Adresse
+recipient-typo = Meinten Sie { $suggestion } statt { $email }?
+recipient-disposable = { $domain } ist ein Wegwerf-Maildienst; Antworten werden womöglich nie gelesen
+recipient-no-mail-server = { $domain } nimmt keine E-Mails an
+
+## Attachment reminder
+
+attachment-reminder = Ihre Nachricht erwähnt „{ $phrase }“, hat aber keinen Anhang. Trotzdem senden?
+
+## Out-of-office checks
+
+away-date-format = %-d. %b
+away-auto-reply-until = { $name } ist anscheinend bis { $date } abwesend
+away-auto-reply = { $name } ist anscheinend abwesend (automatische Antwort vom { $date })
+away-calendar-until = Der Kalender von { $name } ist bis { $date } blockiert
+
+## Notes
+
+note-long-date-format = %A, %-d. %B %Y
+daily-note-agenda-heading = Termine
+daily-note-agenda-empty = Heute keine Termine.
+daily-note-tasks-heading = Fällige Aufgaben
+daily-note-tasks-empty = Nichts fällig.
+daily-note-notes-heading = Notizen
+
+## Task activity
+
+task-activity-heading = Verlauf
+task-activity-date-format = %-d. %b
+task-activity-created = Erstellt
+task-activity-deleted = Gelöscht
+task-activity-completed = Erledigt
+task-activity-reopened = Wieder geöffnet
+task-activity-renamed = Umbenannt in „{ $title }“
+task-activity-due = fällig am { $date }
+task-activity-due-cleared = Fälligkeitsdatum entfernt
+task-activity-notes = Notizen bearbeitet
+task-activity-updated = Geändert
+task-activity-comment = { $author }: { $body }
+
+## Travel buffers
+
+travel-buffer-to = Anfahrt: { $place }
+travel-buffer-from = Rückfahrt: { $place }
+
+## Availability
+
+availability-day-off = Kein Arbeitstag
+availability-working = Arbeitszeit { $start }–{ $end }
+availability-block = { $label } { $start }–{ $end }
+availability-break = Pause
+availability-no-meetings = Keine Meetings
+
+## Triage
+
+triage-task-notes = E-Mail von { $sender }: { $subject }
+triage-no-subject = (kein Betreff)
+
+## Notifications
+
+vip-test-sender = VIP-Test
+vip-test-subject = Test-Benachrichtigung für VIPs
+vip-test-snippet = So werden E-Mails von deinen VIPs angekündigt.
+
+## Timeline
+
+timeline-day-format = %A, %-d. %B
+timeline-untitled = Ohne Titel
+timeline-no-subject = (kein Betreff)
+timeline-sent-to = An { $recipients }
+timeline-created = Erstellt
+timeline-edited = Bearbeitet
+timeline-deleted = Gelöscht
+timeline-labels-changed = Labels geändert
+timeline-chat-messages = { $count ->
+    [one] { $count } Nachricht
+   *[other] { $count } Nachrichten
+}
+
+## Thread export
+
+export-date = Datum
+export-to = An
+export-cc = Cc
+export-attachments = Anhänge
+export-attachment = Anhang
+export-no-subject = (Kein Betreff)
+export-message-count = { $count ->
+    [one] { $count } Nachricht
+   *[other] { $count } Nachrichten
+}
+
+## Replies and forwards
+
+reply-date-format = %a, %-d. %b %Y um %H:%M
+reply-attribution = Am { $date } schrieb { $sender }:
+reply-attribution-undated = { $sender } schrieb:
+reply-unknown-sender = jemand
+forward-header = ---------- Weitergeleitete Nachricht ---------
+forward-from = Von
+forward-date = Datum
+forward-subject = Betreff
+forward-to = An
+forward-cc = Cc
@@ -0,0 +1,159 @@
+# Backend strings shown to the user. Dates use chrono format strings.
+
+## Errors
+
+error-gmail-auth = Gmail authentication failed. Please sign in again.
+error-gmail-api = Gmail service is temporarily unavailable. Please try again later.
+error-database-connection = Database connection failed. Please restart the application.
+error-network = Network connection failed. Please check your internet connection.
+error-rate-limit = Rate limit exceeded. Please wait a moment before trying again.
+error-invalid-input = Invalid input provided. Please check your data and try again.
+error-unexpected = An unexpected error occurred. Please try again.
+
+## Daily digest
+
+digest-date-format = %A, %B %-d
+digest-short-date-format = %b %-d
+digest-title = Daily digest for { $date }
+digest-important-mail = { $count ->
+    [one] { $count } important email
+   *[other] { $count } important emails
+}
+digest-events = { $count ->
+    [one] { $count } event
+   *[other] { $count } events
+}
+digest-tasks-due = { $count ->
+    [one] { $count } task due
+   *[other] { $count } tasks due
+}
+digest-follow-ups = { $count ->
+    [one] { $count } follow-up
+   *[other] { $count } follow-ups
+}
+digest-all-day = All day
+digest-overdue = (overdue since { $date })
+digest-none = (none)
+digest-mail-heading = Important unread mail
+digest-mail-empty = Nothing waiting.
+digest-events-heading = Today's calendar
+digest-events-empty = No events today.
+digest-tasks-heading = Tasks due
+digest-tasks-empty = Nothing due.
+digest-follow-ups-heading = Follow-ups
+digest-follow-ups-empty = No starred messages.
+
+## Model memory checks
+
+resource-fits-gpu = Fits in { $gpu } of GPU memory
+resource-tight-gpu = Needs about { $required } but the GPU has { $gpu }; part of it will run slowly on the CPU
+resource-fits-cpu = Runs on the CPU within { $available } of memory
+resource-tight-cpu = Needs about { $required } of { $system } memory; other apps may slow down
+resource-too-large = Needs about { $required } but this machine has { $system } of memory
+
+## Recipient checks
+
+recipient-invalid = "{ $email }" is not a valid email address
+recipient-typo = Did you mean { $suggestion } instead of { $email }?
+recipient-disposable = { $domain } is a disposable mail service; replies may never be read
+recipient-no-mail-server = { $domain } does not accept email
+
+## Attachment reminder
+
+attachment-reminder = Your message mentions "{ $phrase }" but has no attachment. Send anyway?
+
+## Out-of-office checks
+
+away-date-format = %b %-d
+away-auto-reply-until = { $name } appears to be OOO until { $date }
+away-auto-reply = { $name } appears to be OOO (automatic reply on { $date })
+away-calendar-until = { $name }'s calendar is blocked until { $date }
+
+## Notes
+
+note-long-date-format = %A, %B %-d, %Y
+daily-note-agenda-heading = Agenda
+daily-note-agenda-empty = No events today.
+daily-note-tasks-heading = Tasks due
+daily-note-tasks-empty = Nothing due.
+daily-note-notes-heading = Notes
+
+## Task activity
+
+task-activity-heading = Activity
+task-activity-date-format = %b %-d
+task-activity-created = Created
+task-activity-deleted = Deleted
+task-activity-completed = Completed
+task-activity-reopened = Reopened
+task-activity-renamed = Renamed to "{ $title }"
+task-activity-due = due { $date }
+task-activity-due-cleared = Due date removed
+task-activity-notes = notes edited
+task-activity-updated = Updated
+task-activity-comment = { $author }: { $body }
+
+## Travel buffers
+
+travel-buffer-to = Travel to { $place }
+travel-buffer-from = Travel from { $place }
+
+## Availability
+
+availability-day-off = Not a working day
+availability-working = Working { $start }–{ $end }
+availability-block = { $label } { $start }–{ $end }
+availability-break = Break
+availability-no-meetings = No meetings
+
+## Triage
+
+triage-task-notes = Email from { $sender }: { $subject }
+triage-no-subject = (no subject)
+
+## Notifications
+
+vip-test-sender = VIP test
+vip-test-subject = Test VIP alert
+vip-test-snippet = This is how mail from your VIPs will be announced.
+
+## Timeline
+
+timeline-day-format = %A, %B %-d
+timeline-untitled = Untitled
+timeline-no-subject = (no subject)
+timeline-sent-to = To { $recipients }
+timeline-created = Created
+timeline-edited = Edited
+timeline-deleted = Deleted
+timeline-labels-changed = Labels changed
+timeline-chat-messages = { $count ->
+    [one] { $count } message
+   *[other] { $count } messages
+}
+
+## Thread export
+
+export-date = Date
+export-to = To
+export-cc = Cc
+export-attachments = Attachments
+export-attachment = attachment
+export-no-subject = (No subject)
+export-message-count = { $count ->
+    [one] { $count } message
+   *[other] { $count } messages
+}
+
+## Replies and forwards
+
+reply-date-format = %a, %b %-d, %Y at %-I:%M %p
+reply-attribution = On { $date }, { $sender } wrote:
+reply-attribution-undated = { $sender } wrote:
+reply-unknown-sender = someone
+forward-header = ---------- Forwarded message ---------
+forward-from = From
+forward-date = Date
+forward-subject = Subject
+forward-to = To
+forward-cc = Cc
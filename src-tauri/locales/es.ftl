@@ -0,0 +1,159 @@
+# Backend strings shown to the user. Dates use chrono format strings.
+
+## Errors
+
+error-gmail-auth = No se pudo iniciar sesión en Gmail. Vuelve a iniciar sesión.
+error-gmail-api = Gmail no está disponible en este momento. Inténtalo de nuevo más tarde.
+error-database-connection = No se pudo conectar con la base de datos. Reinicia la aplicación.
+error-network = Falló la conexión de red. Comprueba tu conexión a internet.
+error-rate-limit = Se superó el límite de solicitudes. Espera un momento antes de volver a intentarlo.
+error-invalid-input = Los datos no son válidos. Revísalos e inténtalo de nuevo.
+error-unexpected = Se produjo un error inesperado. Inténtalo de nuevo.
+
+## Daily digest
+
+digest-date-format = %A, %-d de %B
+digest-short-date-format = %-d %b
+digest-title = Resumen diario del { $date }
+digest-important-mail = { $count ->
+    [one] { $count } correo importante
+   *[other] { $count } correos importantes
+}
+digest-events = { $count ->
+    [one] { $count } evento
+   *[other] { $count } eventos
+}
+digest-tasks-due = { $count ->
+    [one] { $count } tarea pendiente
+   *[other] { $count } tareas pendientes
+}
+digest-follow-ups = { $count ->
+    [one] { $count } seguimiento
+   *[other] { $count } seguimientos
+}
+digest-all-day = Todo el día
+digest-overdue = (vencida desde el { $date })
+digest-none = (ninguno)
+digest-mail-heading = Correo importante sin leer
+digest-mail-empty = Nada pendiente.
+digest-events-heading = Calendario de hoy
+digest-events-empty = No hay eventos hoy.
+digest-tasks-heading = Tareas pendientes
+digest-tasks-empty = No vence nada.
+digest-follow-ups-heading = Seguimientos
+digest-follow-ups-empty = No hay mensajes destacados.
+
+## Model memory checks
+
+resource-fits-gpu = Cabe en { $gpu } de memoria de la GPU
+resource-tight-gpu = Necesita unos { $required }, pero la GPU tiene { $gpu }; una parte se ejecutará lentamente en la CPU
+resource-fits-cpu = Se ejecuta en la CPU con { $available } de memoria
+resource-tight-cpu = Necesita unos { $required } de { $system } de memoria; otras aplicaciones pueden ir más lentas
+resource-too-large = Necesita unos { $required }, pero este equipo tiene { $system } de memoria
+
+## Recipient checks
+
+recipient-invalid = «{ $email }» no es una dirección de correo válida
+recipient-typo = ¿Quería decir { $suggestion } en lugar de { $email }?
+recipient-disposable = { $domain } es un servicio de correo desechable; puede que nadie lea las respuestas
+recipient-no-mail-server = { $domain } no acepta correo
+
+## Attachment reminder
+
+attachment-reminder = Su mensaje menciona «{ $phrase }» pero no lleva ningún adjunto. ¿Enviar de todos modos?
+
+## Out-of-office checks
+
+away-date-format = %-d %b
+away-auto-reply-until = Parece que { $name } está fuera de la oficina hasta el { $date }
+away-auto-reply = Parece que { $name } está fuera de la oficina (respuesta automática del { $date })
+away-calendar-until = El calendario de { $name } está bloqueado hasta el { $date }
+
+## Notes
+
+note-long-date-format = %A, %-d de %B de %Y
+daily-note-agenda-heading = Agenda
+daily-note-agenda-empty = No hay eventos hoy.
+daily-note-tasks-heading = Tareas pendientes
+daily-note-tasks-empty = Nada pendiente.
+daily-note-notes-heading = Notas
+
+## Task activity
+
+task-activity-heading = Actividad
+task-activity-date-format = %-d %b
+task-activity-created = Creada
+task-activity-deleted = Eliminada
+task-activity-completed = Completada
+task-activity-reopened = Reabierta
+task-activity-renamed = Renombrada a «{ $title }»
+task-activity-due = vence el { $date }
+task-activity-due-cleared = Fecha de vencimiento eliminada
+task-activity-notes = notas editadas
+task-activity-updated = Actualizada
+task-activity-comment = { $author }: { $body }
+
+## Travel buffers
+
+travel-buffer-to = Viaje a { $place }
+travel-buffer-from = Viaje desde { $place }
+
+## Availability
+
+availability-day-off = Día no laborable
+availability-working = Trabajo { $start }–{ $end }
+availability-block = { $label } { $start }–{ $end }
+availability-break = Pausa
+availability-no-meetings = Sin reuniones
+
+## Triage
+
+triage-task-notes = Correo de { $sender }: { $subject }
+triage-no-subject = (sin asunto)
+
+## Notifications
+
+vip-test-sender = Prueba VIP
+vip-test-subject = Aviso VIP de prueba
+vip-test-snippet = Así se anunciarán los correos de tus VIP.
+
+## Timeline
+
+timeline-day-format = %A, %-d de %B
+timeline-untitled = Sin título
+timeline-no-subject = (sin asunto)
+timeline-sent-to = Para { $recipients }
+timeline-created = Creado
+timeline-edited = Editado
+timeline-deleted = Eliminado
+timeline-labels-changed = Etiquetas cambiadas
+timeline-chat-messages = { $count ->
+    [one] { $count } mensaje
+   *[other] { $count } mensajes
+}
+
+## Thread export
+
+export-date = Fecha
+export-to = Para
+export-cc = Cc
+export-attachments = Archivos adjuntos
+export-attachment = archivo adjunto
+export-no-subject = (Sin asunto)
+export-message-count = { $count ->
+    [one] { $count } mensaje
+   *[other] { $count } mensajes
+}
+
+## Replies and forwards
+
+reply-date-format = %a, %-d %b %Y a las %H:%M
+reply-attribution = El { $date }, { $sender } escribió:
+reply-attribution-undated = { $sender } escribió:
+reply-unknown-sender = alguien
+forward-header = ---------- Mensaje reenviado ---------
+forward-from = De
+forward-date = Fecha
+forward-subject = Asunto
+forward-to = Para
+forward-cc = CC
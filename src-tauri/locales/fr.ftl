@@ -0,0 +1,159 @@
+# Backend strings shown to the user. Dates use chrono format strings.
+
+## Errors
+
+error-gmail-auth = L'authentification Gmail a échoué. Veuillez vous reconnecter.
+error-gmail-api = Gmail est temporairement indisponible. Veuillez réessayer plus tard.
+error-database-connection = La connexion à la base de données a échoué. Veuillez redémarrer l'application.
+error-network = La connexion réseau a échoué. Vérifiez votre connexion internet.
+error-rate-limit = Trop de requêtes. Patientez un instant avant de réessayer.
+error-invalid-input = Données invalides. Vérifiez vos informations et réessayez.
+error-unexpected = Une erreur inattendue s'est produite. Veuillez réessayer.
+
+## Daily digest
+
+digest-date-format = %A %-d %B
+digest-short-date-format = %-d %b
+digest-title = Résumé du { $date }
+digest-important-mail = { $count ->
+    [one] { $count } e-mail important
+   *[other] { $count } e-mails importants
+}
+digest-events = { $count ->
+    [one] { $count } événement
+   *[other] { $count } événements
+}
+digest-tasks-due = { $count ->
+    [one] { $count } tâche à faire
+   *[other] { $count } tâches à faire
+}
+digest-follow-ups = { $count ->
+    [one] { $count } suivi
+   *[other] { $count } suivis
+}
+digest-all-day = Toute la journée
+digest-overdue = (en retard depuis le { $date })
+digest-none = (aucun)
+digest-mail-heading = E-mails importants non lus
+digest-mail-empty = Rien en attente.
+digest-events-heading = Agenda du jour
+digest-events-empty = Aucun événement aujourd'hui.
+digest-tasks-heading = Tâches à faire
+digest-tasks-empty = Rien à faire.
+digest-follow-ups-heading = Suivis
+digest-follow-ups-empty = Aucun message suivi.
+
+## Model memory checks
+
+resource-fits-gpu = Tient dans { $gpu } de mémoire GPU
+resource-tight-gpu = Nécessite environ { $required } mais le GPU n'a que { $gpu } ; une partie s'exécutera lentement sur le CPU
+resource-fits-cpu = S'exécute sur le CPU dans { $available } de mémoire
+resource-tight-cpu = Nécessite environ { $required } sur { $system } de mémoire ; les autres applications peuvent ralentir
+resource-too-large = Nécessite environ { $required } mais cet ordinateur n'a que { $system } de mémoire
+
+## Recipient checks
+
+recipient-invalid = « { $email } » n'est pas une adresse e-mail valide
+recipient-typo = Vouliez-vous dire { $suggestion } au lieu de { $email } ?
+recipient-disposable = { $domain } est un service d'e-mail jetable ; les réponses risquent de ne jamais être lues
+recipient-no-mail-server = { $domain } n'accepte pas d'e-mails
+
+## Attachment reminder
+
+attachment-reminder = Votre message mentionne « { $phrase } » mais n'a aucune pièce jointe. Envoyer quand même ?
+
+## Out-of-office checks
+
+away-date-format = %-d %b
+away-auto-reply-until = { $name } semble absent·e jusqu'à { $date }
+away-auto-reply = { $name } semble absent·e (réponse automatique du { $date })
+away-calendar-until = L'agenda de { $name } est bloqué jusqu'à { $date }
+
+## Notes
+
+note-long-date-format = %A %-d %B %Y
+daily-note-agenda-heading = Agenda
+daily-note-agenda-empty = Aucun événement aujourd'hui.
+daily-note-tasks-heading = Tâches à faire
+daily-note-tasks-empty = Rien à faire.
+daily-note-notes-heading = Notes
+
+## Task activity
+
+task-activity-heading = Activité
+task-activity-date-format = %-d %b
+task-activity-created = Créée
+task-activity-deleted = Supprimée
+task-activity-completed = Terminée
+task-activity-reopened = Rouverte
+task-activity-renamed = Renommée en « { $title } »
+task-activity-due = échéance le { $date }
+task-activity-due-cleared = Échéance supprimée
+task-activity-notes = notes modifiées
+task-activity-updated = Modifiée
+task-activity-comment = { $author } : { $body }
+
+## Travel buffers
+
+travel-buffer-to = Trajet vers { $place }
+travel-buffer-from = Trajet depuis { $place }
+
+## Availability
+
+availability-day-off = Jour non travaillé
+availability-working = Travail { $start }–{ $end }
+availability-block = { $label } { $start }–{ $end }
+availability-break = Pause
+availability-no-meetings = Pas de réunions
+
+## Triage
+
+triage-task-notes = E-mail de { $sender } : { $subject }
+triage-no-subject = (sans objet)
+
+## Notifications
+
+vip-test-sender = Test VIP
+vip-test-subject = Alerte VIP de test
+vip-test-snippet = Voici comment les e-mails de vos VIP seront annoncés.
+
+## Timeline
+
+timeline-day-format = %A %-d %B
+timeline-untitled = Sans titre
+timeline-no-subject = (sans objet)
+timeline-sent-to = À { $recipients }
+timeline-created = Créé
+timeline-edited = Modifié
+timeline-deleted = Supprimé
+timeline-labels-changed = Libellés modifiés
+timeline-chat-messages = { $count ->
+    [one] { $count } message
+   *[other] { $count } messages
+}
+
+## Thread export
+
+export-date = Date
+export-to = À
+export-cc = Cc
+export-attachments = Pièces jointes
+export-attachment = pièce jointe
+export-no-subject = (Sans objet)
+export-message-count = { $count ->
+    [one] { $count } message
+   *[other] { $count } messages
+}
+
+## Replies and forwards
+
+reply-date-format = %a %-d %b %Y à %H:%M
+reply-attribution = Le { $date }, { $sender } a écrit :
+reply-attribution-undated = { $sender } a écrit :
+reply-unknown-sender = quelqu'un
+forward-header = ---------- Message transféré ---------
+forward-from = De
+forward-date = Date
+forward-subject = Objet
+forward-to = À
+forward-cc = Cc
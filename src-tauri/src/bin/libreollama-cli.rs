@@ -0,0 +1,337 @@
+//! LibreOllama command-line companion
+//!
+//! Runs common operations headlessly against the same database and services
+//! the desktop app uses, for automation and debugging without the GUI:
+//!
+//! ```text
+//! libreollama-cli sync --account <id>
+//! libreollama-cli export-notes [--out <dir>] [--format md|json]
+//! libreollama-cli list-tasks --account <id>
+//! libreollama-cli send-email --account <id> --to <addr> --template <file> [--var key=value]...
+//! ```
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use tauri_app_lib::commands::rate_limiter::{RateLimitConfig, RateLimiter};
+use tauri_app_lib::database::{self, operations::note_operations, DatabaseManager};
+use tauri_app_lib::services::gmail::api_service::{EmailAddress, GmailApiService};
+use tauri_app_lib::services::gmail::auth_service::GmailAuthService;
+use tauri_app_lib::services::gmail::compose_service::{ComposeRequest, GmailComposeService, MessageImportance};
+use tauri_app_lib::services::gmail::sync_service::{GmailSyncService, SyncStatus};
+use tauri_app_lib::services::google::{
+    calendar_service::GoogleCalendarService, subtask_sync, task_recurrence, tasks_service::GoogleTasksService,
+};
+use tauri_app_lib::services::sync::conflicts;
+
+const USAGE: &str = "Usage:
+  libreollama-cli sync --account <id>
+  libreollama-cli export-notes [--out <dir>] [--format md|json]
+  libreollama-cli list-tasks --account <id>
+  libreollama-cli send-email --account <id> --to <addr> --template <file> [--var key=value]...";
+
+/// Parsed `--flag value` options; `--var` may repeat
+#[derive(Default)]
+struct Options {
+    values: HashMap<String, String>,
+    vars: HashMap<String, String>,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Options::default();
+        let mut iter = args.iter();
+        while let Some(flag) = iter.next() {
+            let name = flag
+                .strip_prefix("--")
+                .ok_or_else(|| format!("Unexpected argument: {}", flag))?;
+            let value = iter.next().ok_or_else(|| format!("Missing value for --{}", name))?;
+
+            if name == "var" {
+                let (key, val) = value
+                    .split_once('=')
+                    .ok_or_else(|| format!("Invalid --var '{}', expected key=value", value))?;
+                options.vars.insert(key.to_string(), val.to_string());
+            } else {
+                options.values.insert(name.to_string(), value.clone());
+            }
+        }
+        Ok(options)
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(|v| v.as_str())
+    }
+
+    fn require(&self, name: &str) -> Result<&str, String> {
+        self.get(name).ok_or_else(|| format!("Missing required option --{}", name))
+    }
+}
+
+/// The services the desktop app wires up in `setup`, built without Tauri
+struct Services {
+    db_manager: Arc<DatabaseManager>,
+    auth_service: Arc<GmailAuthService>,
+    rate_limiter: Arc<RateLimiter>,
+}
+
+impl Services {
+    async fn init() -> Result<Self, String> {
+        let db_manager = Arc::new(
+            database::init_database()
+                .await
+                .map_err(|e| format!("Database initialization failed: {}", e))?,
+        );
+
+        // Must match the key used by the desktop app so stored tokens decrypt
+        let encryption_key = [0u8; 32];
+        let auth_service = Arc::new(
+            GmailAuthService::new(db_manager.clone(), encryption_key)
+                .map_err(|e| format!("Failed to create auth service: {}", e))?,
+        );
+        let rate_limiter = Arc::new(RateLimiter::new(RateLimitConfig::default()));
+
+        Ok(Self { db_manager, auth_service, rate_limiter })
+    }
+
+    fn tasks(&self) -> GoogleTasksService {
+        GoogleTasksService::new(self.auth_service.clone(), self.db_manager.clone())
+    }
+
+    fn calendar(&self) -> GoogleCalendarService {
+        GoogleCalendarService::new(self.auth_service.clone(), self.db_manager.clone())
+    }
+
+    fn gmail(&self) -> GmailApiService {
+        GmailApiService::new(self.auth_service.clone(), self.db_manager.clone(), self.rate_limiter.clone())
+    }
+
+    fn gmail_sync(&self) -> GmailSyncService {
+        GmailSyncService::headless(self.db_manager.clone(), Arc::new(self.gmail()))
+    }
+
+    fn compose(&self) -> GmailComposeService {
+        GmailComposeService::new(self.auth_service.clone(), self.db_manager.clone(), self.rate_limiter.clone())
+    }
+}
+
+async fn run_sync(services: &Services, options: &Options) -> Result<(), String> {
+    let account_id = options.require("account")?;
+    let tasks_service = services.tasks();
+    let gmail = services.gmail();
+
+    let flushed = conflicts::flush_pending_changes(services.db_manager.clone(), &tasks_service, &services.calendar(), &gmail, account_id)
+        .await
+        .map_err(|e| format!("Failed to flush pending changes: {}", e))?;
+    println!(
+        "Pending changes: {} applied, {} merged, {} conflicts, {} failed",
+        flushed.applied, flushed.auto_merged, flushed.conflicts, flushed.failed
+    );
+
+    let gmail_sync = services.gmail_sync();
+    let gmail_state = gmail_sync
+        .get_sync_state(account_id)
+        .await
+        .map_err(|e| format!("Failed to get Gmail sync state: {}", e))?;
+    if matches!(gmail_state.map(|state| state.sync_status), Some(SyncStatus::Paused)) {
+        println!("Gmail: sync paused");
+    } else {
+        let synced = gmail_sync
+            .perform_incremental_sync(account_id)
+            .await
+            .map_err(|e| format!("Failed to sync Gmail: {}", e))?;
+        println!(
+            "Gmail: {} messages synced, {} failed ({} sync)",
+            synced.messages_processed,
+            synced.messages_failed,
+            synced.sync_type.as_str()
+        );
+        for error in &synced.errors {
+            eprintln!("  {}", error);
+        }
+    }
+
+    let lists = tasks_service
+        .get_task_lists(account_id)
+        .await
+        .map_err(|e| format!("Failed to get task lists: {}", e))?;
+    for list in &lists {
+        let tasks = tasks_service
+            .get_tasks(account_id, &list.id)
+            .await
+            .map_err(|e| format!("Failed to get tasks: {}", e))?;
+        let mut conn = services
+            .db_manager
+            .get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+        let summary = subtask_sync::reconcile_remote_hierarchy(&mut conn, &list.id, &tasks)
+            .map_err(|e| format!("Failed to reconcile subtasks: {}", e))?;
+        println!(
+            "{}: {} tasks ({} subtasks added, {} updated, {} removed)",
+            list.title,
+            tasks.len(),
+            summary.inserted_local,
+            summary.updated_local,
+            summary.removed_local
+        );
+    }
+
+    let spawned = task_recurrence::process_recurring_tasks(&tasks_service, services.db_manager.clone(), account_id)
+        .await
+        .map_err(|e| format!("Failed to process recurring tasks: {}", e))?;
+    println!("Recurring tasks: {} new occurrences", spawned.len());
+
+    let receipts = services
+        .compose()
+        .process_receipts(account_id)
+        .await
+        .map_err(|e| format!("Failed to process receipts: {}", e))?;
+    println!(
+        "Receipts: {} read, {} delivery reports ({} unmatched)",
+        receipts.read_receipts, receipts.delivery_reports, receipts.unmatched
+    );
+
+    Ok(())
+}
+
+async fn run_export_notes(services: &Services, options: &Options) -> Result<(), String> {
+    let out_dir = PathBuf::from(options.get("out").unwrap_or("notes-export"));
+    let format = options.get("format").unwrap_or("md");
+
+    let conn = services
+        .db_manager
+        .get_connection()
+        .map_err(|e| format!("Failed to get database connection: {}", e))?;
+    let notes = note_operations::get_all_notes(&conn).map_err(|e| format!("Failed to load notes: {}", e))?;
+
+    std::fs::create_dir_all(&out_dir).map_err(|e| format!("Failed to create {}: {}", out_dir.display(), e))?;
+
+    match format {
+        "json" => {
+            let path = out_dir.join("notes.json");
+            let json = serde_json::to_string_pretty(&notes).map_err(|e| format!("Failed to serialize notes: {}", e))?;
+            std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        }
+        "md" => {
+            for note in &notes {
+                let slug: String = note
+                    .title
+                    .chars()
+                    .map(|c| if c.is_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+                    .collect();
+                let path = out_dir.join(format!("{}-{}.md", note.id, slug.trim_matches('-')));
+                let body = format!("# {}\n\n{}\n", note.title, note.content);
+                std::fs::write(&path, body).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            }
+        }
+        other => return Err(format!("Unknown export format '{}', expected md or json", other)),
+    }
+
+    println!("Exported {} notes to {}", notes.len(), out_dir.display());
+    Ok(())
+}
+
+async fn run_list_tasks(services: &Services, options: &Options) -> Result<(), String> {
+    let account_id = options.require("account")?;
+    let tasks_service = services.tasks();
+
+    let lists = tasks_service
+        .get_task_lists(account_id)
+        .await
+        .map_err(|e| format!("Failed to get task lists: {}", e))?;
+    for list in &lists {
+        println!("{}", list.title);
+        let tasks = tasks_service
+            .get_tasks(account_id, &list.id)
+            .await
+            .map_err(|e| format!("Failed to get tasks: {}", e))?;
+        for task in tasks {
+            let mark = if task.status == "completed" { "x" } else { " " };
+            let indent = if task.parent.is_some() { "    " } else { "  " };
+            let due = task.due.map(|d| format!(" (due {})", d)).unwrap_or_default();
+            println!("{}[{}] {}{}", indent, mark, task.title, due);
+        }
+    }
+    Ok(())
+}
+
+/// Substitute `{{key}}` placeholders in a template
+fn render_template(template: &str, vars: &HashMap<String, String>) -> String {
+    vars.iter().fold(template.to_string(), |text, (key, value)| {
+        text.replace(&format!("{{{{{}}}}}", key), value)
+    })
+}
+
+async fn run_send_email(services: &Services, options: &Options) -> Result<(), String> {
+    let account_id = options.require("account")?;
+    let to = options.require("to")?;
+    let template_path = options.require("template")?;
+
+    let template = std::fs::read_to_string(template_path)
+        .map_err(|e| format!("Failed to read template {}: {}", template_path, e))?;
+    let rendered = render_template(&template, &options.vars);
+
+    // The first line may carry the subject as "Subject: ..."
+    let (subject, body) = match rendered.split_once('\n') {
+        Some((first, rest)) if first.starts_with("Subject:") => {
+            (first.trim_start_matches("Subject:").trim().to_string(), rest.trim_start().to_string())
+        }
+        _ => (options.get("subject").unwrap_or_default().to_string(), rendered),
+    };
+
+    let request = ComposeRequest {
+        account_id: account_id.to_string(),
+        to: vec![EmailAddress { email: to.to_string(), name: None }],
+        cc: None,
+        bcc: None,
+        subject,
+        body_text: Some(body),
+        body_html: None,
+        attachments: None,
+        reply_to_message_id: None,
+        in_reply_to: None,
+        references: None,
+        thread_id: None,
+        importance: MessageImportance::Normal,
+        delivery_receipt: false,
+        read_receipt: false,
+        schedule_send: None,
+        from_alias: None,
+    };
+
+    let response = services
+        .compose()
+        .send_message(&request)
+        .await
+        .map_err(|e| format!("Failed to send email: {}", e))?;
+    println!("Sent message {} (thread {})", response.message_id, response.thread_id);
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some(command) = args.first() else {
+        eprintln!("{}", USAGE);
+        std::process::exit(2);
+    };
+
+    let result = async {
+        let options = Options::parse(&args[1..])?;
+        let services = Services::init().await?;
+        match command.as_str() {
+            "sync" => run_sync(&services, &options).await,
+            "export-notes" => run_export_notes(&services, &options).await,
+            "list-tasks" => run_list_tasks(&services, &options).await,
+            "send-email" => run_send_email(&services, &options).await,
+            other => Err(format!("Unknown command '{}'\n\n{}", other, USAGE)),
+        }
+    }
+    .await;
+
+    if let Err(e) = result {
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
+    }
+}
@@ -5,6 +5,7 @@ use tauri::State;
 // Import database modules
 use crate::database::models::{Agent as DbAgent, AgentExecution as DbAgentExecution};
 use crate::database::operations;
+use crate::errors::{CommandResult, LibreOllamaError};
 
 // Data structures for agent functionality (compatible with frontend)
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -93,12 +94,19 @@ impl From<DbAgentExecution> for AgentExecution {
     }
 }
 
+/// Agent IDs reach the frontend as strings
+fn parse_agent_id(agent_id: &str) -> CommandResult<i32> {
+    agent_id.parse().map_err(|_| {
+        LibreOllamaError::InvalidInput { message: "Invalid agent ID".to_string(), field: Some("agent_id".to_string()) }.into()
+    })
+}
+
 // Tauri commands for agent functionality
 #[tauri::command]
 pub async fn create_agent(
     request: CreateAgentRequest,
     db_manager: State<'_, std::sync::Arc<crate::database::DatabaseManager>>,
-) -> Result<Agent, String> {
+) -> CommandResult<Agent> {
     let parameters = serde_json::json!({"model": request.model});
     
     // The DbAgent creation is synchronous and can stay here.
@@ -138,9 +146,7 @@ pub async fn create_agent(
             parameters
         )
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
 
     let mut final_agent: Agent = db_agent.into();
     final_agent.id = created_agent_id.to_string();
@@ -149,15 +155,13 @@ pub async fn create_agent(
 }
 
 #[tauri::command]
-pub async fn get_agents(db_manager: State<'_, crate::database::DatabaseManager>) -> Result<Vec<Agent>, String> {
+pub async fn get_agents(db_manager: State<'_, crate::database::DatabaseManager>) -> CommandResult<Vec<Agent>> {
     let db_manager_clone = db_manager.inner().clone();
     let db_agents = tokio::task::spawn_blocking(move || {
         let conn = db_manager_clone.get_connection()?;
         operations::agent_operations::get_all_agents(&conn)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
     
     let agents: Vec<Agent> = db_agents.into_iter().map(|agent| agent.into()).collect();
     
@@ -168,17 +172,15 @@ pub async fn get_agents(db_manager: State<'_, crate::database::DatabaseManager>)
 pub async fn get_agent(
     agent_id: String,
     db_manager: State<'_, std::sync::Arc<crate::database::DatabaseManager>>,
-) -> Result<Agent, String> {
-    let agent_id_int = agent_id.parse().map_err(|_| "Invalid agent ID".to_string())?;
+) -> CommandResult<Agent> {
+    let agent_id_int = parse_agent_id(&agent_id)?;
     let db_manager_clone = db_manager.inner().clone();
 
     let db_agent = tokio::task::spawn_blocking(move || {
         let conn = db_manager_clone.get_connection()?;
         operations::agent_operations::get_agent(&conn, agent_id_int)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?
+    .await??
     .ok_or("Agent not found")?;
     
     Ok(db_agent.into())
@@ -189,17 +191,15 @@ pub async fn update_agent(
     agent_id: String,
     request: UpdateAgentRequest,
     db_manager: State<'_, std::sync::Arc<crate::database::DatabaseManager>>,
-) -> Result<Agent, String> {
-    let agent_id_int = agent_id.parse().map_err(|_| "Invalid agent ID".to_string())?;
+) -> CommandResult<Agent> {
+    let agent_id_int = parse_agent_id(&agent_id)?;
     let db_manager_clone = db_manager.inner().clone();
     
     let mut db_agent = tokio::task::spawn_blocking(move || {
         let conn = db_manager_clone.get_connection()?;
         operations::agent_operations::get_agent(&conn, agent_id_int)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?
+    .await??
     .ok_or("Agent not found")?;
     
     if let Some(name) = request.name { db_agent.name = name; }
@@ -244,9 +244,7 @@ pub async fn update_agent(
             None  // Use default max_tokens
         )
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
     
     Ok(result_agent)
 }
@@ -255,29 +253,25 @@ pub async fn update_agent(
 pub async fn delete_agent(
     agent_id: String,
     db_manager: State<'_, std::sync::Arc<crate::database::DatabaseManager>>,
-) -> Result<bool, String> {
-    let agent_id_int = agent_id.parse().map_err(|_| "Invalid agent ID".to_string())?;
+) -> CommandResult<bool> {
+    let agent_id_int = parse_agent_id(&agent_id)?;
     
     let db_manager_clone_check = db_manager.inner().clone();
     let agent_exists = tokio::task::spawn_blocking(move || {
         let conn = db_manager_clone_check.get_connection()?;
         operations::agent_operations::get_agent(&conn, agent_id_int)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?
+    .await??
     .is_some();
     
-    if !agent_exists { return Err("Agent not found".to_string()); }
+    if !agent_exists { return Err("Agent not found".into()); }
     
     let db_manager_clone_delete = db_manager.inner().clone();
     tokio::task::spawn_blocking(move || {
         let conn = db_manager_clone_delete.get_connection()?;
         operations::agent_operations::delete_agent(&conn, agent_id_int)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
     
     Ok(true)
 }
@@ -287,20 +281,18 @@ pub async fn execute_agent(
     agent_id: String,
     input: String,
     db_manager: State<'_, std::sync::Arc<crate::database::DatabaseManager>>,
-) -> Result<AgentExecution, String> {
-    let agent_id_int = agent_id.parse().map_err(|_| "Invalid agent ID".to_string())?;
+) -> CommandResult<AgentExecution> {
+    let agent_id_int = parse_agent_id(&agent_id)?;
     let db_manager_clone = db_manager.inner().clone();
     
     let db_agent = tokio::task::spawn_blocking(move || {
         let conn = db_manager_clone.get_connection()?;
         operations::agent_operations::get_agent(&conn, agent_id_int)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?
+    .await??
     .ok_or("Agent not found")?;
     
-    if !db_agent.is_active { return Err("Agent is not active".to_string()); }
+    if !db_agent.is_active { return Err("Agent is not active".into()); }
     
     // Since agent execution logic is not fully implemented, this remains mostly synchronous.
     // However, the initial agent fetch is now correctly asynchronous.
@@ -322,17 +314,15 @@ pub async fn execute_agent(
 pub async fn get_agent_executions(
     agent_id: String,
     db_manager: State<'_, std::sync::Arc<crate::database::DatabaseManager>>,
-) -> Result<Vec<AgentExecution>, String> {
-    let agent_id_int = agent_id.parse().map_err(|_| "Invalid agent ID".to_string())?;
+) -> CommandResult<Vec<AgentExecution>> {
+    let agent_id_int = parse_agent_id(&agent_id)?;
     let db_manager_clone = db_manager.inner().clone();
 
     tokio::task::spawn_blocking(move || {
         let conn = db_manager_clone.get_connection()?;
         operations::agent_operations::get_agent(&conn, agent_id_int)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?
+    .await??
     .ok_or("Agent not found")?;
     
     // The actual fetching of executions would also need to be a spawn_blocking call
@@ -0,0 +1,46 @@
+//! Application Lock Commands
+//!
+//! Setting the lock passphrase and locking or unlocking the app. While the
+//! app is locked only `get_app_lock_status`, `unlock_application` and
+//! `lock_application` run; see `services::app_lock`.
+
+use anyhow::Context;
+use tauri::State;
+
+use crate::services::app_lock::{AppLock, AppLockStatus};
+use crate::errors::CommandResult;
+
+#[tauri::command]
+pub async fn get_app_lock_status(app_lock: State<'_, AppLock>) -> CommandResult<AppLockStatus> {
+    Ok(app_lock.status().context("Failed to read lock status")?)
+}
+
+/// Set the lock passphrase, or change it given the current one
+#[tauri::command]
+pub async fn set_app_lock_passphrase(
+    current_passphrase: Option<String>,
+    new_passphrase: String,
+    app_lock: State<'_, AppLock>,
+) -> CommandResult<AppLockStatus> {
+    Ok(app_lock
+        .set_passphrase(current_passphrase.as_deref(), &new_passphrase)
+        .context("Failed to set the lock passphrase")?)
+}
+
+/// Remove the lock passphrase, turning the lock off
+#[tauri::command]
+pub async fn remove_app_lock_passphrase(current_passphrase: String, app_lock: State<'_, AppLock>) -> CommandResult<AppLockStatus> {
+    Ok(app_lock
+        .remove_passphrase(&current_passphrase)
+        .context("Failed to remove the lock passphrase")?)
+}
+
+#[tauri::command]
+pub async fn lock_application(app_lock: State<'_, AppLock>) -> CommandResult<AppLockStatus> {
+    Ok(app_lock.lock("manual").context("Failed to lock")?)
+}
+
+#[tauri::command]
+pub async fn unlock_application(passphrase: String, app_lock: State<'_, AppLock>) -> CommandResult<AppLockStatus> {
+    Ok(app_lock.unlock(&passphrase).context("Failed to unlock")?)
+}
@@ -2,10 +2,25 @@
 //!
 //! This module provides Tauri command handlers for Google Calendar API operations.
 
+use anyhow::Context;
 use serde::{Deserialize, Serialize};
 use chrono::Utc;
 use std::sync::Arc;
-use tauri::State;
+use tauri::{AppHandle, State};
+use crate::utils::http_client::http_client;
+use crate::commands::rate_limiter::RequestPriority;
+use crate::services::request_scheduler;
+use crate::database::operations::imported_calendar_operations;
+use crate::database::operations::cache_delta_operations::{NewCacheDelta, CHANGE_ADDED, CHANGE_REMOVED, CHANGE_UPDATED};
+use crate::database::operations::workspace_operations::WorkspaceKind;
+use crate::services::availability::{self, AvailabilityPurpose};
+use crate::services::events::EventBus;
+use crate::services::ics;
+use crate::services::time_service::TimeService;
+use crate::services::workspaces;
+use crate::utils::time;
+use super::travel_buffers::{self, EventChange};
+use crate::errors::{CommandResult, LibreOllamaError};
 
 // Define the calendar structures that match the frontend types
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -181,17 +196,18 @@ pub struct EventsResponse {
 pub async fn get_calendars(
     account_id: String,
     auth_service: State<'_, Arc<crate::services::gmail::auth_service::GmailAuthService>>,
-) -> Result<Vec<GoogleCalendar>, String> {
+    db_manager: State<'_, Arc<crate::database::DatabaseManager>>,
+) -> CommandResult<Vec<GoogleCalendar>> {
     println!("📅 [CALENDAR-API] Getting calendars for account: {}", account_id);
     
     // Get access token
     let tokens = auth_service.get_account_tokens(&account_id).await
-        .map_err(|e| format!("Failed to get tokens: {}", e))?
+        .context("Failed to get tokens")?
         .ok_or("No tokens found for account")?;
 
     // Make API call to Google Calendar
-    let client = reqwest::Client::new();
-    let response = client
+    let client = http_client();
+    let request = client
         .get("https://www.googleapis.com/calendar/v3/users/me/calendarList")
         .query(&[
             ("minAccessRole", "reader"),  // Include all calendars where user has at least read access
@@ -199,17 +215,17 @@ pub async fn get_calendars(
             ("showDeleted", "false"),     // Exclude deleted calendars
             ("maxResults", "250")         // Get more calendars
         ])
-        .bearer_auth(&tokens.access_token)
-        .send()
+        .bearer_auth(&tokens.access_token);
+    let response = request_scheduler::send(Some(&account_id), RequestPriority::High, request)
         .await
-        .map_err(|e| format!("API request failed: {}", e))?;
+        .context("API request failed")?;
 
     if !response.status().is_success() {
-        return Err(format!("Calendar API failed: {}", response.status()));
+        return Err(LibreOllamaError::GoogleCalendarApi { message: format!("Calendar API failed: {}", response.status()), status_code: Some(response.status().as_u16()) }.into());
     }
 
     let calendar_list: serde_json::Value = response.json().await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+        .context("Failed to parse response")?;
 
     let mut calendars = Vec::new();
     if let Some(items) = calendar_list.get("items").and_then(|v| v.as_array()) {
@@ -233,6 +249,26 @@ pub async fn get_calendars(
     }
 
     println!("✅ [CALENDAR-API] Retrieved {} calendars", calendars.len());
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    // ICS imports and webcal subscriptions are listed beside Google's
+    let imported = imported_calendar_operations::list_calendars(&conn).context("Failed to list imported calendars")?;
+    calendars.extend(imported.into_iter().map(|calendar| GoogleCalendar {
+        id: calendar.id,
+        summary: calendar.name,
+        description: None,
+        time_zone: None,
+        color_id: None,
+        background_color: None,
+        foreground_color: None,
+        selected: Some(true),
+        access_role: Some("reader".to_string()),
+        default_reminders: None,
+        notification_settings: None,
+        primary: Some(false),
+        deleted: Some(false),
+    }));
+    let scope = workspaces::scope(&conn).context("Failed to load workspace")?;
+    scope.retain(WorkspaceKind::Calendar, &mut calendars, |calendar| &calendar.id);
     Ok(calendars)
 }
 
@@ -247,7 +283,8 @@ pub async fn get_calendar_events(
     show_deleted: Option<bool>,
     single_events: Option<bool>,
     auth_service: State<'_, Arc<crate::services::gmail::auth_service::GmailAuthService>>,
-) -> Result<EventsResponse, String> {
+    db_manager: State<'_, Arc<crate::database::DatabaseManager>>,
+) -> CommandResult<EventsResponse> {
     let time_min = time_min.unwrap_or_else(|| {
         Utc::now()
             .checked_sub_signed(chrono::Duration::days(365))
@@ -264,15 +301,19 @@ pub async fn get_calendar_events(
     let show_deleted = show_deleted.unwrap_or(false);
     let single_events = single_events.unwrap_or(true);
 
+    if calendar_id.starts_with(ics::CALENDAR_ID_PREFIX) {
+        return imported_events(&db_manager, &calendar_id, &time_min, &time_max);
+    }
+
     println!("📆 [CALENDAR-API] Getting events for calendar: {} (account: {})", calendar_id, account_id);
 
     // Get access token
     let tokens = auth_service.get_account_tokens(&account_id).await
-        .map_err(|e| format!("Failed to get tokens: {}", e))?
+        .context("Failed to get tokens")?
         .ok_or("No tokens found for account")?;
 
     // Make API call to Google Calendar Events
-    let client = reqwest::Client::new();
+    let client = http_client();
     // URL-encode the calendar ID to handle special characters
     let encoded_calendar_id = urlencoding::encode(&calendar_id);
     let mut url = format!("https://www.googleapis.com/calendar/v3/calendars/{}/events", encoded_calendar_id);
@@ -281,22 +322,22 @@ pub async fn get_calendar_events(
     
     println!("📆 [CALENDAR-API] Requesting URL: {}", url);
 
-    let response = client
+    let request = client
         .get(&url)
-        .bearer_auth(&tokens.access_token)
-        .send()
+        .bearer_auth(&tokens.access_token);
+    let response = request_scheduler::send(Some(&account_id), RequestPriority::High, request)
         .await
-        .map_err(|e| format!("API request failed: {}", e))?;
+        .context("API request failed")?;
 
     if !response.status().is_success() {
         let status = response.status();
         let error_body = response.text().await.unwrap_or_else(|_| "No error details".to_string());
         println!("❌ [CALENDAR-API] Error response: {} - {}", status, error_body);
-        return Err(format!("Calendar Events API failed: {} - {}", status, error_body));
+        return Err(LibreOllamaError::GoogleCalendarApi { message: format!("Calendar Events API failed: {} - {}", status, error_body), status_code: Some(status.as_u16()) }.into());
     }
 
     let events_data: serde_json::Value = response.json().await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+        .context("Failed to parse response")?;
 
     // Parse events from response
     let mut events = Vec::new();
@@ -364,28 +405,184 @@ pub async fn get_calendar_events(
     };
 
     println!("✅ [CALENDAR-API] Retrieved {} events", response.items.len());
+    if let Err(e) = index_events_for_search(&db_manager, &account_id, &calendar_id, &time_min, &time_max, &response.items) {
+        eprintln!("⚠️ [CALENDAR-API] Search index update skipped: {}", e);
+    }
     Ok(response)
 }
 
+/// Events of an imported calendar in Google's shape. Recurring events
+/// come once with their rules, as with `singleEvents=false`; the search
+/// index already has them from the import.
+fn imported_events(
+    db_manager: &crate::database::DatabaseManager,
+    calendar_id: &str,
+    time_min: &str,
+    time_max: &str,
+) -> CommandResult<EventsResponse> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    let calendar = imported_calendar_operations::get_calendar(&conn, calendar_id)
+        .context("Failed to load imported calendar")?
+        .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("imported calendar {}", calendar_id) })?;
+    let events = imported_calendar_operations::list_events(&conn, calendar_id, Some(time_min), Some(time_max))
+        .context("Failed to list imported events")?;
+    let zone = TimeService::load(&conn).context("Failed to read the time zone")?.zone();
+
+    let items = events
+        .into_iter()
+        .map(|event| {
+            let time = |date_time: &Option<String>, date: &Option<String>| {
+                serde_json::json!({ "dateTime": date_time, "date": date, "timeZone": event.time_zone })
+            };
+            serde_json::from_value(serde_json::json!({
+                "id": event.uid,
+                "iCalUID": event.uid.split('#').next(),
+                "summary": event.summary,
+                "description": event.description,
+                "location": event.location,
+                "status": event.status.as_deref().unwrap_or("confirmed"),
+                "start": time(&event.start_date_time, &event.start_date),
+                "end": time(&event.end_date_time, &event.end_date),
+                "recurrence": (!event.recurrence.is_empty()).then_some(&event.recurrence),
+            }))
+        })
+        .collect::<Result<Vec<GoogleCalendarEvent>, _>>()
+        .context("Failed to convert imported events")?;
+
+    Ok(EventsResponse {
+        kind: "calendar#events".to_string(),
+        etag: String::new(),
+        summary: calendar.name,
+        description: None,
+        updated: calendar.refreshed_at,
+        time_zone: zone.name().to_string(),
+        access_role: "reader".to_string(),
+        default_reminders: Vec::new(),
+        next_page_token: None,
+        next_sync_token: None,
+        items,
+    })
+}
+
+/// Keep the fetched window of a calendar searchable offline. Recurring
+/// events are only indexed when expanded into single events.
+fn index_events_for_search(
+    db_manager: &crate::database::DatabaseManager,
+    account_id: &str,
+    calendar_id: &str,
+    time_min: &str,
+    time_max: &str,
+    events: &[GoogleCalendarEvent],
+) -> anyhow::Result<()> {
+    use crate::database::operations::search_operations::{self, NewSearchItem};
+
+    let (Some(window_start), Some(window_end)) =
+        (search_operations::utc_item_time(time_min), search_operations::utc_item_time(time_max))
+    else {
+        return Ok(());
+    };
+    let indexed: Vec<(&GoogleCalendarEvent, String, Option<String>)> = events
+        .iter()
+        .filter(|event| !event.id.is_empty() && event.status.as_deref() != Some("cancelled"))
+        .map(|event| {
+            let body = [event.description.as_deref(), event.location.as_deref()]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join("\n");
+            let start = event
+                .start
+                .as_ref()
+                .and_then(|start| start.date_time.as_deref().or(start.date.as_deref()))
+                .and_then(search_operations::utc_item_time);
+            (event, body, start)
+        })
+        .collect();
+    let items: Vec<NewSearchItem> = indexed
+        .iter()
+        .map(|(event, body, start)| NewSearchItem {
+            item_id: &event.id,
+            container_id: calendar_id,
+            title: event.summary.as_deref().unwrap_or("(No title)"),
+            body,
+            item_time: start.as_deref(),
+            status: event.status.as_deref(),
+            completed_at: None,
+            all_day: event.start.as_ref().is_some_and(|start| start.date_time.is_none() && start.date.is_some()),
+        })
+        .collect();
+
+    let mut conn = db_manager.get_connection()?;
+    search_operations::replace_event_items(&mut conn, account_id, calendar_id, &window_start, &window_end, &items)
+}
+
+/// Google reads a dateTime without an offset in the time's `timeZone` and
+/// rejects it without one, so such times get the user's zone
+fn pin_time_zones(event: &mut GoogleCalendarEvent, db_manager: &crate::database::DatabaseManager) -> CommandResult<()> {
+    let zone = TimeService::from_db(db_manager).context("Failed to read the time zone")?.zone();
+    for time in [event.start.as_mut(), event.end.as_mut()].into_iter().flatten() {
+        let floating = time.date_time.as_deref().is_some_and(|t| chrono::DateTime::parse_from_rfc3339(t).is_err());
+        if floating && time.time_zone.is_none() {
+            time.time_zone = Some(zone.name().to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Warn when a timed event goes against the availability profile. Events
+/// with guests are meetings; others are only kept out of breaks and
+/// non-working time.
+fn warn_on_availability(event: &GoogleCalendarEvent, db_manager: &crate::database::DatabaseManager, event_bus: &EventBus) {
+    let (Some(start), Some(end)) = (event.start.as_ref(), event.end.as_ref()) else {
+        return;
+    };
+    let (Some(start_time), Some(end_time)) = (start.date_time.as_deref(), end.date_time.as_deref()) else {
+        return;
+    };
+    let purpose = if event.attendees.as_ref().is_some_and(|attendees| !attendees.is_empty()) {
+        AvailabilityPurpose::Meeting
+    } else {
+        AvailabilityPurpose::Focus
+    };
+    let conflicts = db_manager.get_connection().map_err(LibreOllamaError::from).and_then(|conn| {
+        let zone = TimeService::load(&conn)?.zone();
+        match (
+            time::zoned_to_utc(start_time, start.time_zone.as_deref(), &zone),
+            time::zoned_to_utc(end_time, end.time_zone.as_deref(), &zone),
+        ) {
+            (Some(start), Some(end)) => availability::check(&conn, start, end, purpose),
+            _ => Ok(Vec::new()),
+        }
+    });
+    match conflicts {
+        Ok(conflicts) => event_bus.availability_conflicts("event", &event.id, event.summary.as_deref(), purpose, conflicts),
+        Err(e) => eprintln!("⚠️ [CALENDAR-API] Availability check skipped for {}: {}", event.id, e),
+    }
+}
+
 /// Create a new calendar event
 #[tauri::command]
 pub async fn create_calendar_event(
     account_id: String,
     calendar_id: String,
     event_data: GoogleCalendarEvent,
+    app: AppHandle,
     auth_service: State<'_, Arc<crate::services::gmail::auth_service::GmailAuthService>>,
-) -> Result<GoogleCalendarEvent, String> {
+    db_manager: State<'_, Arc<crate::database::DatabaseManager>>,
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<GoogleCalendarEvent> {
     println!("📅 [CALENDAR-API] Creating event '{}' in calendar: {} (account: {})", 
              event_data.summary.as_deref().unwrap_or("No Title"), calendar_id, account_id);
 
     // Get access token
     let tokens = auth_service.get_account_tokens(&account_id).await
-        .map_err(|e| format!("Failed to get tokens: {}", e))?
+        .context("Failed to get tokens")?
         .ok_or("No tokens found for account")?;
 
     // Prepare event data for API (remove id field as it's generated by Google)
     let mut api_event = event_data.clone();
     api_event.id = String::new(); // Clear ID for creation
+    pin_time_zones(&mut api_event, &db_manager)?;
     
     // Debug: Print the event being sent
     println!("📤 [CALENDAR-API] Sending event to Google Calendar:");
@@ -407,7 +604,7 @@ pub async fn create_calendar_event(
 
     // Convert to JSON and manually remove the id field if empty
     let mut json_value = serde_json::to_value(&api_event)
-        .map_err(|e| format!("Failed to convert to JSON: {}", e))?;
+        .context("Failed to convert to JSON")?;
     
     // Remove null fields and empty id field from JSON
     if let Some(obj) = json_value.as_object_mut() {
@@ -456,14 +653,14 @@ pub async fn create_calendar_event(
     }
     
     // Make API call to Google Calendar
-    let client = reqwest::Client::new();
-    let response = client
+    let client = http_client();
+    let request = client
         .post(format!("https://www.googleapis.com/calendar/v3/calendars/{}/events", calendar_id))
         .bearer_auth(&tokens.access_token)
-        .json(&json_value)
-        .send()
+        .json(&json_value);
+    let response = request_scheduler::send(Some(&account_id), RequestPriority::High, request)
         .await
-        .map_err(|e| format!("API request failed: {}", e))?;
+        .context("API request failed")?;
 
     if !response.status().is_success() {
         let status = response.status();
@@ -476,13 +673,19 @@ pub async fn create_calendar_event(
             println!("❌ [CALENDAR-API] Final JSON sent to Google:\n{}", final_json);
         }
         
-        return Err(format!("Failed to create event: {}", error_text));
+        return Err(LibreOllamaError::GoogleCalendarApi { message: format!("Failed to create event: {}", error_text), status_code: Some(status.as_u16()) }.into());
     }
 
     let created_event: GoogleCalendarEvent = response.json().await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+        .context("Failed to parse response")?;
 
     println!("✅ [CALENDAR-API] Event created successfully: {}", created_event.id);
+    event_bus.cache_changed(&[
+        NewCacheDelta::event(&account_id, &created_event.id, CHANGE_ADDED).with_data(serde_json::json!({ "calendar_id": calendar_id })),
+    ]);
+    warn_on_availability(&created_event, &db_manager, &event_bus);
+    let change = EventChange::Created(created_event.clone());
+    travel_buffers::follow_event_change(&app, tokens.access_token, account_id, calendar_id, change);
     Ok(created_event)
 }
 
@@ -492,36 +695,48 @@ pub async fn update_calendar_event(
     account_id: String,
     calendar_id: String,
     event_id: String,
-    event_data: GoogleCalendarEvent,
+    mut event_data: GoogleCalendarEvent,
+    app: AppHandle,
     auth_service: State<'_, Arc<crate::services::gmail::auth_service::GmailAuthService>>,
-) -> Result<GoogleCalendarEvent, String> {
+    db_manager: State<'_, Arc<crate::database::DatabaseManager>>,
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<GoogleCalendarEvent> {
     println!("📅 [CALENDAR-API] Updating event {} in calendar: {} (account: {})", 
              event_id, calendar_id, account_id);
 
     // Get access token
     let tokens = auth_service.get_account_tokens(&account_id).await
-        .map_err(|e| format!("Failed to get tokens: {}", e))?
+        .context("Failed to get tokens")?
         .ok_or("No tokens found for account")?;
 
+    pin_time_zones(&mut event_data, &db_manager)?;
+
     // Make API call to Google Calendar
-    let client = reqwest::Client::new();
-    let response = client
+    let client = http_client();
+    let request = client
         .put(format!("https://www.googleapis.com/calendar/v3/calendars/{}/events/{}", calendar_id, event_id))
         .bearer_auth(&tokens.access_token)
-        .json(&event_data)
-        .send()
+        .json(&event_data);
+    let response = request_scheduler::send(Some(&account_id), RequestPriority::High, request)
         .await
-        .map_err(|e| format!("API request failed: {}", e))?;
+        .context("API request failed")?;
 
     if !response.status().is_success() {
+        let status = response.status();
         let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("Failed to update event: {}", error_text));
+        return Err(LibreOllamaError::GoogleCalendarApi { message: format!("Failed to update event: {}", error_text), status_code: Some(status.as_u16()) }.into());
     }
 
     let updated_event: GoogleCalendarEvent = response.json().await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+        .context("Failed to parse response")?;
 
     println!("✅ [CALENDAR-API] Event updated successfully: {}", event_id);
+    event_bus.cache_changed(&[
+        NewCacheDelta::event(&account_id, &event_id, CHANGE_UPDATED).with_data(serde_json::json!({ "calendar_id": calendar_id })),
+    ]);
+    warn_on_availability(&updated_event, &db_manager, &event_bus);
+    let change = EventChange::Updated(updated_event.clone());
+    travel_buffers::follow_event_change(&app, tokens.access_token, account_id, calendar_id, change);
     Ok(updated_event)
 }
 
@@ -531,30 +746,37 @@ pub async fn delete_calendar_event(
     account_id: String,
     calendar_id: String,
     event_id: String,
+    app: AppHandle,
     auth_service: State<'_, Arc<crate::services::gmail::auth_service::GmailAuthService>>,
-) -> Result<(), String> {
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<()> {
     println!("📅 [CALENDAR-API] Deleting event {} from calendar: {} (account: {})", 
              event_id, calendar_id, account_id);
 
     // Get access token
     let tokens = auth_service.get_account_tokens(&account_id).await
-        .map_err(|e| format!("Failed to get tokens: {}", e))?
+        .context("Failed to get tokens")?
         .ok_or("No tokens found for account")?;
 
     // Make API call to Google Calendar
-    let client = reqwest::Client::new();
-    let response = client
+    let client = http_client();
+    let request = client
         .delete(format!("https://www.googleapis.com/calendar/v3/calendars/{}/events/{}", calendar_id, event_id))
-        .bearer_auth(&tokens.access_token)
-        .send()
+        .bearer_auth(&tokens.access_token);
+    let response = request_scheduler::send(Some(&account_id), RequestPriority::High, request)
         .await
-        .map_err(|e| format!("API request failed: {}", e))?;
+        .context("API request failed")?;
 
     if !response.status().is_success() {
+        let status = response.status();
         let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("Failed to delete event: {}", error_text));
+        return Err(LibreOllamaError::GoogleCalendarApi { message: format!("Failed to delete event: {}", error_text), status_code: Some(status.as_u16()) }.into());
     }
 
     println!("✅ [CALENDAR-API] Event deleted successfully: {}", event_id);
+    event_bus.cache_changed(&[
+        NewCacheDelta::event(&account_id, &event_id, CHANGE_REMOVED).with_data(serde_json::json!({ "calendar_id": calendar_id })),
+    ]);
+    travel_buffers::follow_event_change(&app, tokens.access_token, account_id, calendar_id, EventChange::Deleted(event_id));
     Ok(())
 } 
\ No newline at end of file
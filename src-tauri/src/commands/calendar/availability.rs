@@ -0,0 +1,92 @@
+//! Availability Commands
+//!
+//! Read and change the availability profile, globally or for one
+//! workspace, and check a stretch of time against it.
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::State;
+
+use crate::database::DatabaseManager;
+use crate::services::availability::{self, AvailabilityConflict, AvailabilityProfile, AvailabilityPurpose, AVAILABILITY_SETTING};
+use crate::services::settings::SettingsService;
+use crate::errors::CommandResult;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AvailabilitySettings {
+    pub profile: AvailabilityProfile,
+    /// Workspace whose own profile this is; the global profile when absent
+    pub workspace_id: Option<i64>,
+}
+
+fn parse_time(value: &str, field: &str) -> CommandResult<DateTime<Utc>> {
+    let time = DateTime::parse_from_rfc3339(value).with_context(|| format!("Invalid {}", field))?;
+    Ok(time.with_timezone(&Utc))
+}
+
+/// The profile of a workspace, falling back to the global one, or with no
+/// workspace given the profile in effect for the active workspace
+#[tauri::command]
+pub async fn get_availability_profile(
+    workspace_id: Option<i64>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<AvailabilitySettings> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    let (profile, workspace_id) = match workspace_id {
+        Some(id) => match availability::workspace_profile(&conn, id)? {
+            Some(profile) => (profile, Some(id)),
+            None => (availability::global_profile(&conn)?, None),
+        },
+        None => availability::effective_profile(&conn)?,
+    };
+    Ok(AvailabilitySettings { profile, workspace_id })
+}
+
+/// Save the global profile, or a workspace's own profile when a workspace
+/// is given; no profile for a workspace makes it use the global one again
+#[tauri::command]
+pub async fn set_availability_profile(
+    profile: Option<AvailabilityProfile>,
+    workspace_id: Option<i64>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    settings_service: State<'_, SettingsService>,
+) -> CommandResult<()> {
+    match workspace_id {
+        Some(id) => {
+            let conn = db_manager.get_connection()
+                .context("Failed to get database connection")?;
+            availability::save_workspace_profile(&conn, id, profile.as_ref())?;
+        }
+        None => match profile {
+            Some(profile) => {
+                settings_service.set(AVAILABILITY_SETTING, &serde_json::to_value(&profile)?)?;
+            }
+            None => {
+                settings_service.reset(AVAILABILITY_SETTING)?;
+            }
+        },
+    }
+    Ok(())
+}
+
+/// Ways a meeting or time block (RFC 3339 times) would go against the
+/// availability in effect
+#[tauri::command]
+pub async fn check_availability(
+    start: String,
+    end: String,
+    purpose: AvailabilityPurpose,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<AvailabilityConflict>> {
+    let start = parse_time(&start, "start")?;
+    let end = parse_time(&end, "end")?;
+    if end < start {
+        return Err("The time must end after it starts".into());
+    }
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(availability::check(&conn, start, end, purpose)?)
+}
@@ -0,0 +1,88 @@
+//! Imported Calendar Commands
+//!
+//! ICS files and webcal subscriptions, kept locally beside the Google
+//! calendars. See `services::ics`.
+
+use anyhow::Context;
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+
+use crate::database::operations::imported_calendar_operations::{self, ImportedCalendar, ImportedEvent};
+use crate::database::DatabaseManager;
+use crate::services::file_access;
+use crate::services::ics;
+use crate::errors::CommandResult;
+
+/// Import an ICS file; importing the same file again replaces its events.
+/// The file must have been picked in a file dialog or opened with the app.
+#[tauri::command]
+pub async fn import_ics_file(
+    path: String,
+    app: AppHandle,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<ImportedCalendar> {
+    let path = file_access::permitted_path(&app, &path)?;
+    let db_manager = db_manager.inner().clone();
+    Ok(tokio::task::spawn_blocking(move || ics::import_file(&db_manager, &path))
+        .await
+        .context("Import task failed")?
+        .context("Failed to import calendar")?)
+}
+
+/// Subscribe to a `webcal:` or https calendar, refreshing it if already
+/// subscribed
+#[tauri::command]
+pub async fn subscribe_webcal_calendar(
+    url: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<ImportedCalendar> {
+    Ok(ics::subscribe(db_manager.inner(), &url)
+        .await
+        .context("Failed to subscribe to calendar")?)
+}
+
+#[tauri::command]
+pub async fn refresh_imported_calendar(
+    calendar_id: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<ImportedCalendar> {
+    Ok(ics::refresh(db_manager.inner(), &calendar_id)
+        .await
+        .context("Failed to refresh calendar")?)
+}
+
+#[tauri::command]
+pub async fn get_imported_calendars(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<ImportedCalendar>> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(imported_calendar_operations::list_calendars(&conn)
+        .context("Failed to list imported calendars")?)
+}
+
+/// Events of an imported calendar in a time range; recurring events are
+/// returned once with their recurrence rules
+#[tauri::command]
+pub async fn get_imported_events(
+    calendar_id: String,
+    time_min: Option<String>,
+    time_max: Option<String>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<ImportedEvent>> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(imported_calendar_operations::list_events(&conn, &calendar_id, time_min.as_deref(), time_max.as_deref())
+        .context("Failed to list imported events")?)
+}
+
+#[tauri::command]
+pub async fn delete_imported_calendar(
+    calendar_id: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<bool> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(imported_calendar_operations::delete_calendar(&conn, &calendar_id)
+        .context("Failed to delete imported calendar")?)
+}
@@ -0,0 +1,164 @@
+//! Meeting Slot Commands
+//!
+//! Suggest meeting times from free/busy data and the availability
+//! profile, and keep the working hours in that profile.
+
+use anyhow::Context;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::State;
+
+use crate::database::DatabaseManager;
+use crate::services::gmail::auth_service::GmailAuthService;
+use crate::services::availability::{self, WorkingHours, AVAILABILITY_SETTING};
+use crate::services::google::meeting_slots::{self, InviteDraft, MeetingSlot, SlotSearch};
+use crate::services::settings::SettingsService;
+use crate::services::google::GoogleEndpoints;
+use crate::services::time_service::TimeService;
+use crate::errors::{CommandResult, LibreOllamaError};
+
+const MAX_SEARCH_DAYS: i64 = 62;
+
+/// Ask for an invite email proposing the best slot. Templates use
+/// `{{title}}`, `{{names}}`, `{{slot}}`, `{{duration}}` and `{{alternatives}}`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct InviteOptions {
+    pub title: Option<String>,
+    pub subject_template: Option<String>,
+    pub body_template: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MeetingSlotSuggestions {
+    pub slots: Vec<MeetingSlot>,
+    pub working_hours: WorkingHours,
+    pub invite: Option<InviteDraft>,
+}
+
+fn parse_time(value: &str, field: &str) -> CommandResult<DateTime<Utc>> {
+    let time = DateTime::parse_from_rfc3339(value).with_context(|| format!("Invalid {}", field))?;
+    Ok(time.with_timezone(&Utc))
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MeetingSlotRequest {
+    pub attendees: Vec<String>,
+    pub duration_minutes: i64,
+    /// RFC 3339
+    pub range_start: String,
+    /// RFC 3339
+    pub range_end: String,
+    /// Consult attendee calendars too (default true)
+    pub check_attendees: Option<bool>,
+    pub limit: Option<usize>,
+    pub invite: Option<InviteOptions>,
+}
+
+/// Find candidate meeting times in the requested range, best first
+#[tauri::command]
+pub async fn find_meeting_slots(
+    account_id: String,
+    request: MeetingSlotRequest,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    auth_service: State<'_, Arc<GmailAuthService>>,
+) -> CommandResult<MeetingSlotSuggestions> {
+    let MeetingSlotRequest { attendees, duration_minutes, range_start, range_end, check_attendees, limit, invite } = request;
+    if !(5..=8 * 60).contains(&duration_minutes) {
+        return Err("Meeting duration must be between 5 minutes and 8 hours".into());
+    }
+    let range_start = parse_time(&range_start, "range_start")?.max(Utc::now());
+    let range_end = parse_time(&range_end, "range_end")?;
+    if range_end <= range_start {
+        return Err("The search range must end in the future, after it starts".into());
+    }
+    if range_end - range_start > Duration::days(MAX_SEARCH_DAYS) {
+        return Err(LibreOllamaError::InvalidInput {
+            message: format!("The search range can be at most {} days", MAX_SEARCH_DAYS),
+            field: Some("range_end".to_string()),
+        }
+        .into());
+    }
+
+    // Working hours and blocks are wall-clock times in the user's zone
+    let (profile, zone) = {
+        let conn = db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        let zone = TimeService::load(&conn)?.zone();
+        (availability::load_profile(&conn)?, zone)
+    };
+
+    let attendees: Vec<String> = attendees
+        .into_iter()
+        .map(|email| email.trim().to_lowercase())
+        .filter(|email| !email.is_empty())
+        .collect();
+    let queried: &[String] = if check_attendees.unwrap_or(true) { &attendees } else { &[] };
+
+    println!(
+        "📅 [MEETING-SLOTS] Finding {} minute slots for {} attendees (account: {})",
+        duration_minutes,
+        attendees.len(),
+        account_id
+    );
+
+    let tokens = auth_service
+        .validate_and_refresh_tokens(&db_manager, &account_id)
+        .await
+        .context("Failed to get tokens")?;
+    let mut calendars = meeting_slots::query_free_busy(&GoogleEndpoints::default(), &account_id, &tokens.access_token, queried, range_start, range_end)
+        .await?;
+    let own = calendars.remove(0);
+
+    let search = SlotSearch {
+        range_start,
+        range_end,
+        duration: Duration::minutes(duration_minutes),
+        limit: limit.unwrap_or(10).clamp(1, 50),
+    };
+    let slots = meeting_slots::rank_slots(&zone, &search, &profile, &own, &calendars)?;
+
+    let invite = match invite {
+        Some(options) => meeting_slots::draft_invite(
+            &zone,
+            &attendees,
+            &slots,
+            options.title.as_deref(),
+            options.subject_template.as_deref(),
+            options.body_template.as_deref(),
+        )
+        ?,
+        None => None,
+    };
+
+    println!("✅ [MEETING-SLOTS] Found {} candidate slots", slots.len());
+    Ok(MeetingSlotSuggestions { slots, working_hours: profile.working_hours, invite })
+}
+
+/// Working hours in effect, from the active workspace's profile if it has one
+#[tauri::command]
+pub async fn get_working_hours(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<WorkingHours> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(availability::load_profile(&conn)?.working_hours)
+}
+
+/// Set the working hours of the global availability profile
+#[tauri::command]
+pub async fn set_working_hours(
+    working_hours: WorkingHours,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    settings_service: State<'_, SettingsService>,
+) -> CommandResult<()> {
+    working_hours.validate()?;
+    let mut profile = {
+        let conn = db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        availability::global_profile(&conn)?
+    };
+    profile.working_hours = working_hours;
+    settings_service.set(AVAILABILITY_SETTING, &serde_json::to_value(&profile)?)?;
+    Ok(())
+}
@@ -1,3 +1,11 @@
 pub mod api;
+pub mod availability;
+pub mod ics;
+pub mod meeting_slots;
+pub mod travel_buffers;
 
-pub use api::*; 
\ No newline at end of file
+pub use api::*;
+pub use availability::*;
+pub use ics::*;
+pub use meeting_slots::*;
+pub use travel_buffers::*;
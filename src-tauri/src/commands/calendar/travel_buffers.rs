@@ -0,0 +1,170 @@
+//! Travel Buffer Commands
+//!
+//! Suggest and create travel-time blocks around events with a physical
+//! location, keep them in step when their event changes, and keep the
+//! travel buffer preference.
+
+use anyhow::Context;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, State};
+
+use super::api::GoogleCalendarEvent;
+use crate::commands::rate_limiter::RequestPriority;
+use crate::database::operations::cache_delta_operations::{NewCacheDelta, CHANGE_ADDED, CHANGE_REMOVED};
+use crate::database::DatabaseManager;
+use crate::services::events::EventBus;
+use crate::services::gmail::auth_service::GmailAuthService;
+use crate::services::google::travel_buffers::{self, TravelBufferSettings, TravelBufferSuggestion, TravelBufferSuggestions};
+use crate::services::google::GoogleEndpoints;
+use crate::services::request_scheduler;
+use crate::errors::CommandResult;
+
+fn load_settings(db_manager: &DatabaseManager) -> CommandResult<TravelBufferSettings> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(travel_buffers::load_settings(&conn).context("Failed to load travel buffer settings")?)
+}
+
+fn announce(event_bus: &EventBus, account_id: &str, calendar_id: &str, created: &[GoogleCalendarEvent]) {
+    let deltas: Vec<NewCacheDelta> = created
+        .iter()
+        .map(|event| {
+            NewCacheDelta::event(account_id, &event.id, CHANGE_ADDED).with_data(serde_json::json!({ "calendar_id": calendar_id }))
+        })
+        .collect();
+    event_bus.cache_changed(&deltas);
+}
+
+/// Travel buffers an event could use, leaving out ones already created
+#[tauri::command]
+pub async fn suggest_travel_buffers(
+    account_id: String,
+    event_id: String,
+    calendar_id: Option<String>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    auth_service: State<'_, Arc<GmailAuthService>>,
+) -> CommandResult<TravelBufferSuggestions> {
+    let calendar_id = calendar_id.unwrap_or_else(|| "primary".to_string());
+    let settings = load_settings(&db_manager)?;
+    let tokens = auth_service
+        .validate_and_refresh_tokens(&db_manager, &account_id)
+        .await
+        .context("Failed to get tokens")?;
+    Ok(travel_buffers::suggest(&GoogleEndpoints::default(), &account_id, &tokens.access_token, &calendar_id, &event_id, &settings)
+        .await?)
+}
+
+/// Create the chosen travel buffers next to their event
+#[tauri::command]
+pub async fn create_travel_buffers(
+    account_id: String,
+    event_id: String,
+    calendar_id: Option<String>,
+    suggestions: Vec<TravelBufferSuggestion>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    auth_service: State<'_, Arc<GmailAuthService>>,
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<Vec<GoogleCalendarEvent>> {
+    let calendar_id = calendar_id.unwrap_or_else(|| "primary".to_string());
+    let tokens = auth_service
+        .validate_and_refresh_tokens(&db_manager, &account_id)
+        .await
+        .context("Failed to get tokens")?;
+    let endpoints = GoogleEndpoints::default();
+    let created =
+        travel_buffers::insert_buffers(&endpoints, &account_id, &tokens.access_token, &calendar_id, &event_id, &suggestions)
+            .await?;
+    println!("✅ [TRAVEL-BUFFERS] Created {} travel buffers for event {}", created.len(), event_id);
+    announce(&event_bus, &account_id, &calendar_id, &created);
+    Ok(created)
+}
+
+/// What happened to an event whose travel buffers may need redoing
+pub(crate) enum EventChange {
+    Created(GoogleCalendarEvent),
+    Updated(GoogleCalendarEvent),
+    Deleted(String),
+}
+
+/// Keep an event's travel buffers in step with it, in the background so
+/// routing and Calendar calls do not hold up the change itself. New events
+/// get buffers when the user asked for that; a moved event has its buffers
+/// redone and a deleted one loses them. Buffers that would overlap another
+/// event are left out. Failures are logged.
+pub(crate) fn follow_event_change(app: &AppHandle, access_token: String, account_id: String, calendar_id: String, change: EventChange) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(request_scheduler::with_priority(RequestPriority::Low, async move {
+        let db_manager = app.state::<Arc<DatabaseManager>>();
+        let settings = match load_settings(&db_manager) {
+            Ok(settings) => settings,
+            Err(e) => return eprintln!("⚠️ [TRAVEL-BUFFERS] {}", e),
+        };
+        let endpoints = GoogleEndpoints::default();
+        let (event_id, event, keep_existing) = match &change {
+            EventChange::Created(event) => (event.id.clone(), Some(event), false),
+            EventChange::Updated(event) => (event.id.clone(), Some(event), true),
+            EventChange::Deleted(event_id) => (event_id.clone(), None, true),
+        };
+        if event_id.is_empty() {
+            return;
+        }
+
+        let result = async {
+            let removed = if keep_existing {
+                travel_buffers::remove_buffers(&endpoints, &account_id, &access_token, &calendar_id, &event_id).await?
+            } else {
+                Vec::new()
+            };
+            // An event that had buffers gets them again after a move even
+            // when automatic buffers are off
+            let has_place = event.is_some_and(|event| event.location.as_deref().is_some_and(travel_buffers::is_physical_location));
+            if !has_place || (!settings.auto_insert && removed.is_empty()) {
+                return Ok((removed, Vec::new()));
+            }
+            let suggested = travel_buffers::suggest(&endpoints, &account_id, &access_token, &calendar_id, &event_id, &settings).await?;
+            let (clear, blocked): (Vec<_>, Vec<_>) =
+                suggested.suggestions.into_iter().partition(|suggestion| suggestion.conflicts.is_empty());
+            for suggestion in &blocked {
+                println!(
+                    "📅 [TRAVEL-BUFFERS] Skipped a travel buffer for {}: it would overlap {}",
+                    event_id,
+                    suggestion.conflicts.join(", ")
+                );
+            }
+            let created = travel_buffers::insert_buffers(&endpoints, &account_id, &access_token, &calendar_id, &event_id, &clear).await?;
+            Ok::<_, crate::errors::LibreOllamaError>((removed, created))
+        }
+        .await;
+
+        match result {
+            Ok((removed, created)) => {
+                let event_bus = app.state::<EventBus>();
+                let removed: Vec<NewCacheDelta> = removed
+                    .iter()
+                    .map(|id| NewCacheDelta::event(&account_id, id, CHANGE_REMOVED).with_data(serde_json::json!({ "calendar_id": calendar_id })))
+                    .collect();
+                event_bus.cache_changed(&removed);
+                announce(&event_bus, &account_id, &calendar_id, &created);
+            }
+            Err(e) => eprintln!("⚠️ [TRAVEL-BUFFERS] Travel buffers not updated for {}: {}", event_id, e),
+        }
+    }));
+}
+
+#[tauri::command]
+pub async fn get_travel_buffer_settings(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<TravelBufferSettings> {
+    load_settings(&db_manager)
+}
+
+#[tauri::command]
+pub async fn set_travel_buffer_settings(
+    settings: TravelBufferSettings,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<()> {
+    settings.validate()?;
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(travel_buffers::save_settings(&conn, &settings)?)
+}
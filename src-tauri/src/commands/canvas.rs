@@ -1,8 +1,132 @@
 //! Canvas Commands
 //!
-//! This module provides commands for canvas data management.
-//! Currently unused but kept for future development.
+//! AI assistant actions on a canvas selection, the per-canvas history of
+//! what was generated, and export to SVG, PNG or PDF. Canvas documents
+//! themselves are stored by the frontend; `canvas_id` is the document's ID.
 
+use anyhow::Context;
+use std::sync::Arc;
 
-// Note: All command functions have been removed as they are unused.
-// This module is kept for potential future canvas management features.
+use base64::Engine;
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+use crate::database::operations::canvas_operations;
+use crate::database::DatabaseManager;
+use crate::services::canvas_assistant::{
+    self, CanvasDiagram, CanvasGeneration, CanvasPoint, CanvasSummary, DiagramKind, SelectedElement,
+};
+use crate::services::canvas_export::{self, ExportFormat, ExportOptions};
+use crate::services::file_access;
+use crate::services::service_registry::ServiceRegistry;
+use crate::errors::CommandResult;
+
+/// Summarize the selected elements into a note placed beside them
+#[tauri::command]
+pub async fn summarize_canvas_selection(
+    registry: State<'_, ServiceRegistry>,
+    canvas_id: String,
+    elements: Vec<SelectedElement>,
+    instructions: Option<String>,
+    origin: Option<CanvasPoint>,
+) -> CommandResult<CanvasSummary> {
+    Ok(registry
+        .canvas_assistant()
+        .await?
+        .summarize(&canvas_id, &elements, instructions.as_deref(), origin)
+        .await?)
+}
+
+/// Generate outline or mind-map nodes and edges from the selection, or from
+/// `instructions` alone
+#[tauri::command]
+pub async fn generate_canvas_diagram(
+    registry: State<'_, ServiceRegistry>,
+    canvas_id: String,
+    kind: DiagramKind,
+    elements: Vec<SelectedElement>,
+    instructions: Option<String>,
+    max_nodes: Option<usize>,
+    origin: Option<CanvasPoint>,
+) -> CommandResult<CanvasDiagram> {
+    Ok(registry
+        .canvas_assistant()
+        .await?
+        .generate_diagram(&canvas_id, kind, &elements, instructions.as_deref(), max_nodes, origin)
+        .await?)
+}
+
+/// AI generations of a canvas, newest first
+#[tauri::command]
+pub async fn get_canvas_ai_history(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    canvas_id: String,
+    limit: Option<i64>,
+) -> CommandResult<Vec<CanvasGeneration>> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(canvas_assistant::history(&conn, &canvas_id, limit.unwrap_or(50))?)
+}
+
+#[tauri::command]
+pub async fn delete_canvas_ai_generation(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    generation_id: i64,
+) -> CommandResult<bool> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(canvas_operations::delete_generation(&conn, generation_id)?)
+}
+
+/// Forget every generation of a canvas; returns how many were removed
+#[tauri::command]
+pub async fn clear_canvas_ai_history(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    canvas_id: String,
+) -> CommandResult<usize> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(canvas_operations::clear_generations(&conn, &canvas_id)?)
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportedCanvas {
+    pub format: ExportFormat,
+    pub mime_type: String,
+    pub width: u32,
+    pub height: u32,
+    /// Where the file was written, when a path was given
+    pub path: Option<String>,
+    /// Base64 of the file otherwise
+    pub data: Option<String>,
+}
+
+/// Render a canvas document (its JSON with an `elements` list or map) to
+/// SVG, PNG or PDF, written to `path` or returned as base64. `path` must
+/// have been picked in a save dialog or lie in the app's data directory.
+#[tauri::command]
+pub async fn export_canvas(
+    canvas: serde_json::Value,
+    options: ExportOptions,
+    path: Option<String>,
+    app: AppHandle,
+) -> CommandResult<ExportedCanvas> {
+    let target = path.as_deref().map(|path| file_access::permitted_path(&app, path)).transpose()?;
+    tokio::task::spawn_blocking(move || {
+        let export = canvas_export::export(&canvas, &options)?;
+        let data = match &target {
+            Some(target) => {
+                std::fs::write(target, &export.bytes).with_context(|| format!("Failed to write {}", target.display()))?;
+                None
+            }
+            None => Some(base64::engine::general_purpose::STANDARD.encode(&export.bytes)),
+        };
+        Ok(ExportedCanvas {
+            format: export.format,
+            mime_type: export.mime_type,
+            width: export.width,
+            height: export.height,
+            path,
+            data,
+        })
+    })
+    .await
+    .context("Canvas export failed")?
+}
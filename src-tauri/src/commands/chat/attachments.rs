@@ -0,0 +1,100 @@
+//! Chat attachment commands
+//!
+//! Attach a PDF, Word or text file, from disk or from a Gmail message, to a
+//! chat session so later replies can quote it. Files on disk must have been
+//! picked in a file dialog or lie in the app's data directory.
+
+use anyhow::Context;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use tauri::{AppHandle, State};
+use tokio::io::AsyncReadExt;
+
+use crate::database::operations::chat_attachment_operations::{self, ChatAttachment};
+use crate::database::operations::transcript_operations;
+use crate::database::DatabaseManager;
+use crate::services::chat_attachments::{self, MAX_ATTACHMENT_BYTES};
+use crate::services::file_access;
+use crate::services::gmail::api_service::GmailApiService;
+use crate::errors::{CommandResult, LibreOllamaError};
+
+/// Where the file to attach comes from
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChatFileSource {
+    GmailAttachment {
+        account_id: String,
+        message_id: String,
+        attachment_id: String,
+    },
+    LocalFile {
+        path: String,
+    },
+}
+
+#[tauri::command]
+pub async fn attach_file_to_chat(
+    session_id_str: String,
+    source: ChatFileSource,
+    filename: String,
+    mime_type: String,
+    app: AppHandle,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    api_service: State<'_, Arc<GmailApiService>>,
+) -> CommandResult<ChatAttachment> {
+    let session_id = super::parse_session_id(&session_id_str)?;
+
+    let (source_key, bytes) = match &source {
+        ChatFileSource::GmailAttachment { account_id, message_id, attachment_id } => {
+            let bytes = api_service
+                .get_attachment(account_id, message_id, attachment_id)
+                .await?;
+            (transcript_operations::gmail_source_key(account_id, message_id, attachment_id), bytes)
+        }
+        ChatFileSource::LocalFile { path } => {
+            let resolved = file_access::permitted_path(&app, path)?;
+            let file = tokio::fs::File::open(&resolved)
+                .await
+                .with_context(|| format!("Failed to read {}", path))?;
+            // Read one byte past the limit so a file that grew is still caught
+            let mut bytes = Vec::new();
+            file.take(MAX_ATTACHMENT_BYTES as u64 + 1)
+                .read_to_end(&mut bytes)
+                .await
+                .with_context(|| format!("Failed to read {}", path))?;
+            if bytes.len() > MAX_ATTACHMENT_BYTES {
+                return Err(LibreOllamaError::InvalidInput {
+                    message: format!("{} is too large to attach", filename),
+                    field: Some("path".to_string()),
+                }
+                .into());
+            }
+            (transcript_operations::file_source_key(&resolved.to_string_lossy()), bytes)
+        }
+    };
+
+    Ok(chat_attachments::attach(&db_manager, session_id, &source_key, &filename, &mime_type, &bytes)
+        .await?)
+}
+
+#[tauri::command]
+pub async fn list_chat_attachments(
+    session_id_str: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<ChatAttachment>> {
+    let session_id = super::parse_session_id(&session_id_str)?;
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(chat_attachment_operations::list_attachments(&conn, session_id)?)
+}
+
+#[tauri::command]
+pub async fn remove_chat_attachment(
+    attachment_id: i64,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<bool> {
+    let mut conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(chat_attachment_operations::delete_attachment(&mut conn, attachment_id)?)
+}
@@ -0,0 +1,32 @@
+//! Chat memory commands
+//!
+//! View and edit the rolling summary kept for long chat sessions.
+
+use anyhow::Context;
+use std::sync::Arc;
+
+use crate::database::DatabaseManager;
+use crate::services::conversation_memory::{self, SessionMemory};
+use crate::errors::CommandResult;
+
+#[tauri::command]
+pub async fn get_chat_memory(
+    session_id_str: String,
+    db_manager: tauri::State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<SessionMemory> {
+    let session_id = super::parse_session_id(&session_id_str)?;
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(conversation_memory::load_memory(&conn, session_id)?)
+}
+
+/// Replace a session's summary; an empty summary resets the memory
+#[tauri::command]
+pub async fn update_chat_memory(
+    session_id_str: String,
+    summary: String,
+    db_manager: tauri::State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<SessionMemory> {
+    let session_id = super::parse_session_id(&session_id_str)?;
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(conversation_memory::set_summary(&conn, session_id, &summary)?)
+}
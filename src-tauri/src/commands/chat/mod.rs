@@ -2,7 +2,24 @@
 //!
 //! This module contains all chat-related Tauri commands.
 
+use crate::errors::{CommandResult, LibreOllamaError};
+
+pub mod attachments;
+pub mod memory;
 pub mod sessions;
 
 // Re-export all chat commands for easy access
-pub use sessions::*; 
\ No newline at end of file
+pub use attachments::*;
+pub use memory::*;
+pub use sessions::*; 
+
+/// Session IDs reach the frontend as strings
+fn parse_session_id(session_id: &str) -> CommandResult<i32> {
+    session_id.parse().map_err(|_| {
+        LibreOllamaError::InvalidInput {
+            message: "Invalid session ID format".to_string(),
+            field: Some("session_id".to_string()),
+        }
+        .into()
+    })
+}
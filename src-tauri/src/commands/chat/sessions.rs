@@ -6,6 +6,7 @@ use std::sync::Arc;
 // Import database modules
 use crate::database::{ChatSession as DbChatSession, ChatMessage as DbChatMessage};
 use crate::database::operations;
+use crate::errors::{CommandResult, LibreOllamaError};
 
 // Data structures for chat functionality (compatible with frontend)
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -70,15 +71,13 @@ impl From<DbChatMessage> for ChatMessageApi {
 pub async fn create_session(
     title: String,
     db_manager: tauri::State<'_, Arc<crate::database::DatabaseManager>>,
-) -> Result<String, String> {
+) -> CommandResult<String> {
     let db_manager_clone = db_manager.inner().clone();
     let session_id = tokio::task::spawn_blocking(move || {
         let conn = db_manager_clone.get_connection()?;
         operations::chat_operations::create_chat_session(&conn, "user_id_placeholder", &title)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
     
     Ok(session_id.to_string())
 }
@@ -86,15 +85,13 @@ pub async fn create_session(
 #[tauri::command]
 pub async fn get_sessions(
     db_manager: tauri::State<'_, Arc<crate::database::DatabaseManager>>,
-) -> Result<Vec<ChatSessionApi>, String> {
+) -> CommandResult<Vec<ChatSessionApi>> {
     let db_manager_clone = db_manager.inner().clone();
     let db_sessions = tokio::task::spawn_blocking(move || {
         let conn = db_manager_clone.get_connection()?;
         operations::chat_operations::get_chat_sessions_by_user(&conn, "user_id_placeholder")
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
     
     let mut sessions_api = Vec::new();
     for db_session in db_sessions {
@@ -105,9 +102,7 @@ pub async fn get_sessions(
             let conn = db_manager_clone_inner.get_connection()?;
             operations::chat_operations::get_chat_messages_by_session(&conn, session_id)
         })
-        .await
-        .map_err(|e| e.to_string())?
-        .map_err(|e: anyhow::Error| e.to_string())?
+        .await??
         .len();
         session_api.message_count = message_count;
         sessions_api.push(session_api);
@@ -122,8 +117,8 @@ pub async fn send_message(
     content: String,
     role: String,
     db_manager: tauri::State<'_, Arc<crate::database::DatabaseManager>>,
-) -> Result<ChatMessageApi, String> {
-    let session_id: i32 = session_id_str.parse().map_err(|_| "Invalid session ID format".to_string())?;
+) -> CommandResult<ChatMessageApi> {
+    let session_id = super::parse_session_id(&session_id_str)?;
 
     let db_manager_clone = db_manager.inner().clone();
     let content_clone = content.clone();
@@ -132,9 +127,7 @@ pub async fn send_message(
         let conn = db_manager_clone.get_connection()?;
         operations::chat_operations::create_chat_message(&conn, session_id, &role_clone, &content_clone)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
 
     let db_manager_clone_update = db_manager.inner().clone();
     tokio::task::spawn_blocking(move || {
@@ -146,9 +139,7 @@ pub async fn send_message(
             rusqlite::params![now, session_id],
         ).context("Failed to update chat session timestamp")
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
 
     // Get the created message
     let db_manager_clone_get = db_manager.inner().clone();
@@ -156,10 +147,8 @@ pub async fn send_message(
         let conn = db_manager_clone_get.get_connection()?;
         operations::chat_operations::get_chat_message(&conn, message_id)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?
-    .ok_or_else(|| "Failed to retrieve created message".to_string())?;
+    .await??
+    .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("chat message {}", message_id) })?;
 
     Ok(db_message.into())
 }
@@ -168,36 +157,76 @@ pub async fn send_message(
 pub async fn get_session_messages(
     session_id_str: String,
     db_manager: tauri::State<'_, Arc<crate::database::DatabaseManager>>,
-) -> Result<Vec<ChatMessageApi>, String> {
-    let session_id: i32 = session_id_str.parse().map_err(|_| "Invalid session ID format".to_string())?;
+) -> CommandResult<Vec<ChatMessageApi>> {
+    let session_id = super::parse_session_id(&session_id_str)?;
     
     let db_manager_clone = db_manager.inner().clone();
     let db_messages = tokio::task::spawn_blocking(move || {
         let conn = db_manager_clone.get_connection()?;
         operations::chat_operations::get_chat_messages_by_session(&conn, session_id)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
     
     let messages_api: Vec<ChatMessageApi> = db_messages.into_iter().map(|msg| msg.into()).collect();
     Ok(messages_api)
 }
 
+/// Assistant reply generated for a session, with the tokens it used
+#[derive(Serialize, Clone, Debug)]
+pub struct ChatReplyApi {
+    pub message: ChatMessageApi,
+    pub model: String,
+    pub usage: crate::services::llm_provider::TokenUsage,
+    pub latency_ms: u64,
+    pub dropped_messages: usize,
+    pub truncated: bool,
+    pub summarized_messages: i64,
+    pub citations: Vec<crate::services::chat_attachments::Citation>,
+}
+
+#[tauri::command]
+pub async fn generate_chat_reply(
+    session_id_str: String,
+    registry: tauri::State<'_, crate::services::service_registry::ServiceRegistry>,
+    db_manager: tauri::State<'_, Arc<crate::database::DatabaseManager>>,
+) -> CommandResult<ChatReplyApi> {
+    let session_id = super::parse_session_id(&session_id_str)?;
+
+    let reply = registry.chat().await?.reply(session_id).await?;
+
+    let db_manager_clone = db_manager.inner().clone();
+    let message_id = reply.message_id;
+    let db_message = tokio::task::spawn_blocking(move || {
+        let conn = db_manager_clone.get_connection()?;
+        operations::chat_operations::get_chat_message(&conn, message_id)
+    })
+    .await??
+    .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("chat message {}", message_id) })?;
+
+    Ok(ChatReplyApi {
+        message: db_message.into(),
+        model: reply.model,
+        usage: reply.usage,
+        latency_ms: reply.latency_ms,
+        dropped_messages: reply.dropped_messages,
+        truncated: reply.truncated,
+        summarized_messages: reply.summarized_messages,
+        citations: reply.citations,
+    })
+}
+
 // Additional database-specific commands
 
 #[tauri::command]
 pub async fn get_database_stats(
     db_manager: tauri::State<'_, Arc<crate::database::DatabaseManager>>,
-) -> Result<serde_json::Value, String> {
+) -> CommandResult<serde_json::Value> {
     let db_manager_clone = db_manager.inner().clone();
     let sessions = tokio::task::spawn_blocking(move || {
         let conn = db_manager_clone.get_connection()?;
         operations::chat_operations::get_chat_sessions_by_user(&conn, "user_id_placeholder")
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
     
     let mut total_messages = 0;
     for session in &sessions {
@@ -207,9 +236,7 @@ pub async fn get_database_stats(
             let conn = db_manager_clone_inner.get_connection()?;
             operations::chat_operations::get_chat_messages_by_session(&conn, session_id)
         })
-        .await
-        .map_err(|e| e.to_string())?
-        .map_err(|e: anyhow::Error| e.to_string())?
+        .await??
         .len();
         total_messages += message_count;
     }
@@ -228,17 +255,15 @@ pub async fn get_database_stats(
 pub async fn delete_session_v4(
     session_id_str: String,
     db_manager: tauri::State<'_, Arc<crate::database::DatabaseManager>>,
-) -> Result<bool, String> {
-    let session_id: i32 = session_id_str.parse().map_err(|_| "Invalid session ID format".to_string())?;
+) -> CommandResult<bool> {
+    let session_id = super::parse_session_id(&session_id_str)?;
     
     let db_manager_clone = db_manager.inner().clone();
     tokio::task::spawn_blocking(move || {
         let conn = db_manager_clone.get_connection()?;
         operations::chat_operations::delete_chat_session(&conn, session_id)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
     
     Ok(true)
 }
@@ -247,21 +272,19 @@ pub async fn delete_session_v4(
 pub async fn delete_session(
     session_id: String,
     db_manager: tauri::State<'_, Arc<crate::database::DatabaseManager>>,
-) -> Result<bool, String> {
-    let session_id_int: i32 = session_id.parse().map_err(|_| "Invalid session ID format".to_string())?;
+) -> CommandResult<bool> {
+    let session_id_int = super::parse_session_id(&session_id)?;
     
     let db_manager_clone_check = db_manager.inner().clone();
     let session_exists = tokio::task::spawn_blocking(move || {
         let conn = db_manager_clone_check.get_connection()?;
         operations::chat_operations::get_chat_session(&conn, session_id_int)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?
+    .await??
     .is_some();
     
     if !session_exists {
-        return Err("Session not found".to_string());
+        return Err("Session not found".into());
     }
     
     let db_manager_clone_delete = db_manager.inner().clone();
@@ -269,9 +292,7 @@ pub async fn delete_session(
         let conn = db_manager_clone_delete.get_connection()?;
         operations::chat_operations::delete_chat_session(&conn, session_id_int)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
     
     Ok(true)
 }
@@ -281,17 +302,15 @@ pub async fn update_session_title(
     session_id_str: String,
     new_title: String,
     db_manager: tauri::State<'_, Arc<crate::database::DatabaseManager>>,
-) -> Result<bool, String> {
-    let session_id: i32 = session_id_str.parse().map_err(|_| "Invalid session ID format".to_string())?;
+) -> CommandResult<bool> {
+    let session_id = super::parse_session_id(&session_id_str)?;
     
     let db_manager_clone = db_manager.inner().clone();
     tokio::task::spawn_blocking(move || {
         let conn = db_manager_clone.get_connection()?;
         operations::chat_operations::update_chat_session(&conn, session_id, &new_title)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
     
     Ok(true)
 }
\ No newline at end of file
@@ -0,0 +1,77 @@
+//! Contacts Commands
+//!
+//! Commands for syncing Google Contacts and querying the local copy for
+//! compose autocomplete and sender name resolution.
+
+use anyhow::Context;
+use crate::database::{
+    operations::contact_operations::{self, Contact, ContactSuggestion},
+    DatabaseManager,
+};
+use crate::services::google::people_sync::{ContactSyncSummary, PeopleSyncService};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::State;
+use crate::errors::{CommandResult, LibreOllamaError};
+
+/// Sync an account's contacts. Until the contacts.readonly scope is
+/// granted the error mentions it, so the frontend can re-run OAuth with
+/// `extra_scopes`.
+#[tauri::command]
+pub async fn sync_google_contacts(
+    account_id: String,
+    people_sync: State<'_, PeopleSyncService>,
+) -> CommandResult<ContactSyncSummary> {
+    match people_sync.sync_contacts(&account_id).await {
+        Err(e @ LibreOllamaError::PermissionDenied { .. }) => Err(e.into()),
+        result => Ok(result.context("Failed to sync contacts")?),
+    }
+}
+
+#[tauri::command]
+pub async fn get_contacts(
+    account_id: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<Contact>> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(contact_operations::list_contacts(&conn, &account_id)
+        .context("Failed to get contacts")?)
+}
+
+/// Address suggestions for the compose recipient fields
+#[tauri::command]
+pub async fn search_contacts(
+    query: String,
+    account_id: Option<String>,
+    limit: Option<i64>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<ContactSuggestion>> {
+    if query.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(contact_operations::search_contacts(&conn, account_id.as_deref(), &query, limit.unwrap_or(10))
+        .context("Failed to search contacts")?)
+}
+
+/// Map email addresses to contact display names; unknown addresses are omitted
+#[tauri::command]
+pub async fn resolve_contact_names(
+    emails: Vec<String>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<HashMap<String, String>> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+
+    let mut names = HashMap::new();
+    for email in emails {
+        if let Some(name) = contact_operations::resolve_display_name(&conn, &email)
+            .context("Failed to resolve contact name")?
+        {
+            names.insert(email, name);
+        }
+    }
+    Ok(names)
+}
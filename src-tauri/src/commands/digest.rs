@@ -0,0 +1,38 @@
+//! Daily Digest Commands
+//!
+//! Previewing the daily digest and sending it on demand. The schedule and
+//! template are ordinary settings in the "digest" category.
+
+use anyhow::Context;
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+
+use crate::database::DatabaseManager;
+use crate::services::daily_digest::{self, DailyDigest, DigestDelivery};
+use crate::services::time_service::TimeService;
+use crate::errors::CommandResult;
+
+/// Today's digest as it would be sent now
+#[tauri::command]
+pub async fn preview_daily_digest(db_manager: State<'_, Arc<DatabaseManager>>) -> CommandResult<DailyDigest> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(daily_digest::compile(&conn, TimeService::load(&conn)?.now()).context("Failed to compile digest")?)
+}
+
+/// Send today's digest now the ways the schedule names, whether or not the
+/// schedule is enabled. The scheduled digest still goes out as usual.
+#[tauri::command]
+pub async fn send_daily_digest(
+    app_handle: AppHandle,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<DigestDelivery> {
+    let (schedule, digest) = {
+        let conn = db_manager.get_connection().context("Failed to get database connection")?;
+        let schedule = daily_digest::load_schedule(&conn).context("Failed to load digest schedule")?;
+        let digest = daily_digest::compile(&conn, TimeService::load(&conn)?.now()).context("Failed to compile digest")?;
+        (schedule, digest)
+    };
+    Ok(daily_digest::deliver(&app_handle, db_manager.inner(), &schedule, digest)
+        .await
+        .context("Failed to send digest")?)
+}
@@ -0,0 +1,195 @@
+//! Feed Commands
+//!
+//! Subscribing to RSS/Atom feeds, reading and marking their items, and
+//! turning items or the daily digest into notes.
+
+use anyhow::Context;
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::State;
+
+use crate::commands::notes::{create_note, create_sourced_note, NoteResponse};
+use crate::database::operations::feed_operations::{self, FeedItemFilter, FeedItemRecord, FeedRecord};
+use crate::database::DatabaseManager;
+use crate::services::events::EventBus;
+use crate::services::feeds::{self, FeedDigest, FeedRefresh};
+use crate::services::gmail::thread_export::sanitize_html;
+use crate::services::web_clipper;
+use crate::errors::{CommandResult, LibreOllamaError};
+
+/// Owner recorded on notes made from feeds, matching the notes UI
+const DEFAULT_NOTE_USER: &str = "default_user";
+const DEFAULT_DIGEST_HOURS: i64 = 24;
+
+fn escape_html(value: &str) -> String {
+    value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Subscribe to a feed URL, or to the feed a web page links to
+#[tauri::command]
+pub async fn subscribe_feed(url: String, db_manager: State<'_, Arc<DatabaseManager>>) -> CommandResult<FeedRecord> {
+    Ok(feeds::subscribe(&db_manager, &url)
+        .await
+        .with_context(|| format!("Failed to subscribe to {}", url))?)
+}
+
+/// Unsubscribe, deleting the feed's items
+#[tauri::command]
+pub async fn unsubscribe_feed(feed_id: i64, db_manager: State<'_, Arc<DatabaseManager>>) -> CommandResult<bool> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(feed_operations::delete_feed(&conn, feed_id).context("Failed to unsubscribe")?)
+}
+
+/// Subscribed feeds with their unread counts
+#[tauri::command]
+pub async fn get_feeds(db_manager: State<'_, Arc<DatabaseManager>>) -> CommandResult<Vec<FeedRecord>> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(feed_operations::list_feeds(&conn).context("Failed to list feeds")?)
+}
+
+/// Fetch one feed, or all of them, now
+#[tauri::command]
+pub async fn refresh_feeds(
+    feed_id: Option<i64>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    events: State<'_, EventBus>,
+) -> CommandResult<Vec<FeedRefresh>> {
+    let refreshes = feeds::refresh(&db_manager, feed_id)
+        .await
+        .context("Failed to refresh feeds")?;
+    feeds::announce(&events, &refreshes);
+    Ok(refreshes)
+}
+
+/// Feed items, newest first
+#[tauri::command]
+pub async fn get_feed_items(
+    filter: Option<FeedItemFilter>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<FeedItemRecord>> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(feed_operations::list_items(&conn, &filter.unwrap_or_default()).context("Failed to list feed items")?)
+}
+
+/// Mark items read, or unread with `read: false`
+#[tauri::command]
+pub async fn mark_feed_items_read(
+    item_ids: Vec<i64>,
+    read: Option<bool>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<usize> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(feed_operations::set_items_read(&conn, &item_ids, read.unwrap_or(true))
+        .context("Failed to update feed items")?)
+}
+
+/// Mark every item of a feed read, or of all feeds
+#[tauri::command]
+pub async fn mark_all_feed_items_read(
+    feed_id: Option<i64>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<usize> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(feed_operations::mark_all_read(&conn, feed_id).context("Failed to update feed items")?)
+}
+
+/// Starred items are kept after they are read
+#[tauri::command]
+pub async fn star_feed_item(
+    item_id: i64,
+    starred: bool,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<bool> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(feed_operations::set_item_starred(&conn, item_id, starred).context("Failed to update feed item")?)
+}
+
+/// Keep an item as a note: the linked article is clipped when it can be,
+/// otherwise the content from the feed is used
+#[tauri::command]
+pub async fn save_feed_item_as_note(
+    item_id: i64,
+    folder_id: Option<i32>,
+    user_id: Option<String>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<NoteResponse> {
+    let item = {
+        let conn = db_manager.get_connection().context("Failed to get database connection")?;
+        feed_operations::get_item(&conn, item_id)
+            .context("Failed to load feed item")?
+            .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("feed item {}", item_id) })?
+    };
+
+    let clipped = match &item.url {
+        Some(url) => match web_clipper::clip(url).await {
+            Ok(article) => Some(article),
+            Err(e) => {
+                eprintln!("⚠️ [FEEDS] Clipping {} failed, using the feed content: {}", url, e);
+                None
+            }
+        },
+        None => None,
+    };
+    let content = match clipped {
+        Some(article) => article.markdown,
+        None => {
+            // Feed HTML is written by the feed's author; keep only what is safe to show
+            let body = sanitize_html(&item.content.clone().or(item.summary.clone()).unwrap_or_default());
+            match &item.url {
+                Some(url) => format!("<p><a href=\"{}\">{}</a></p>{}", escape_html(url), escape_html(&item.feed_title), body),
+                None => body,
+            }
+        }
+    };
+
+    // The note, its source and the item being read are saved together
+    let db = db_manager.inner().clone();
+    let user_id = user_id.unwrap_or_else(|| DEFAULT_NOTE_USER.to_string());
+    tokio::task::spawn_blocking(move || {
+        let mut conn = db.get_connection().context("Failed to get database connection")?;
+        let tx = conn.transaction().context("Failed to start note transaction")?;
+        let note = create_sourced_note(
+            &tx,
+            &item.title,
+            &content,
+            &user_id,
+            folder_id,
+            item.url.as_deref(),
+            std::slice::from_ref(&item.feed_title),
+        )?;
+        feed_operations::set_items_read(&tx, &[item.id], true).context("Failed to update feed item")?;
+        tx.commit().context("Failed to save feed item as note")?;
+        Ok(NoteResponse::from(note))
+    })
+    .await?
+}
+
+#[derive(Debug, Serialize)]
+pub struct FeedDigestResponse {
+    pub digest: FeedDigest,
+    pub note: Option<NoteResponse>,
+}
+
+/// Unread items from the last day (or `hours`), with an optional model
+/// summary, optionally kept as a note
+#[tauri::command]
+pub async fn get_feed_digest(
+    hours: Option<i64>,
+    summarize: Option<bool>,
+    save_as_note: Option<bool>,
+    user_id: Option<String>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<FeedDigestResponse> {
+    let digest = feeds::digest(&db_manager, hours.unwrap_or(DEFAULT_DIGEST_HOURS), summarize.unwrap_or(false))
+        .await
+        .context("Failed to build feed digest")?;
+
+    let note = if save_as_note.unwrap_or(false) && !digest.items.is_empty() {
+        let title = format!("Feed digest, {}", chrono::Local::now().format("%Y-%m-%d"));
+        let user_id = user_id.unwrap_or_else(|| DEFAULT_NOTE_USER.to_string());
+        Some(create_note(title, digest.markdown.clone(), None, user_id, db_manager).await?)
+    } else {
+        None
+    };
+    Ok(FeedDigestResponse { digest, note })
+}
@@ -7,6 +7,8 @@ use tauri::{command, State};
 use std::sync::Arc;
 use crate::database::models::Folder;
 use crate::database::operations;
+use crate::services::workspaces;
+use crate::errors::{CommandError, CommandResult};
 
 #[derive(Debug, Serialize)]
 pub struct FolderResponse {
@@ -49,15 +51,16 @@ pub struct UpdateFolderRequest {
 #[command]
 pub async fn get_folders(
     db_manager: State<'_, Arc<crate::database::DatabaseManager>>,
-) -> Result<Vec<FolderResponse>, String> {
+) -> CommandResult<Vec<FolderResponse>> {
     let db_manager_clone = db_manager.inner().clone();
     let folders = tokio::task::spawn_blocking(move || {
-        let conn = db_manager_clone.get_connection().map_err(|e| e.to_string())?;
-        operations::folder_operations::get_folders_by_user(&conn, "default_user").map_err(|e| e.to_string())
+        let conn = db_manager_clone.get_connection()?;
+        let mut folders = operations::folder_operations::get_folders_by_user(&conn, "default_user")?;
+        let scope = workspaces::scope(&conn)?;
+        folders.retain(|folder| scope.allows_folder(Some(folder.id)));
+        Ok::<_, CommandError>(folders)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e| e)?;
+    .await??;
 
     let folder_responses: Vec<FolderResponse> = folders.into_iter().map(FolderResponse::from).collect();
     Ok(folder_responses)
@@ -70,21 +73,19 @@ pub async fn create_folder(
     color: Option<String>,
     user_id: String,
     db_manager: State<'_, Arc<crate::database::DatabaseManager>>,
-) -> Result<FolderResponse, String> {
+) -> CommandResult<FolderResponse> {
     let db_manager_clone = db_manager.inner().clone();
     let folder = tokio::task::spawn_blocking(move || {
-        let conn = db_manager_clone.get_connection().map_err(|e| e.to_string())?;
+        let conn = db_manager_clone.get_connection()?;
         operations::folder_operations::create_folder(
             &conn, 
             &name, 
             parent_id, 
             &user_id, 
             color.as_deref()
-        ).map_err(|e| e.to_string())
+        ).map_err(CommandError::from)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e| e)?;
+    .await??;
 
     Ok(FolderResponse::from(folder))
 }
@@ -94,23 +95,21 @@ pub async fn update_folder(
     id: String,
     folder: UpdateFolderRequest,
     db_manager: State<'_, Arc<crate::database::DatabaseManager>>,
-) -> Result<FolderResponse, String> {
+) -> CommandResult<FolderResponse> {
     let folder_id: i32 = id.parse().map_err(|_| "Invalid folder ID")?;
     
     let db_manager_clone = db_manager.inner().clone();
     let updated_folder = tokio::task::spawn_blocking(move || {
-        let conn = db_manager_clone.get_connection().map_err(|e| e.to_string())?;
+        let conn = db_manager_clone.get_connection()?;
         operations::folder_operations::update_folder(
             &conn, 
             folder_id, 
             folder.name.as_deref(), 
             folder.parent_id.flatten(), 
             folder.color.as_deref()
-        ).map_err(|e| e.to_string())
+        ).map_err(CommandError::from)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e| e)?;
+    .await??;
 
     Ok(FolderResponse::from(updated_folder))
 }
@@ -119,17 +118,15 @@ pub async fn update_folder(
 pub async fn delete_folder(
     id: String,
     db_manager: State<'_, Arc<crate::database::DatabaseManager>>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     let folder_id: i32 = id.parse().map_err(|_| "Invalid folder ID")?;
     
     let db_manager_clone = db_manager.inner().clone();
     tokio::task::spawn_blocking(move || {
-        let conn = db_manager_clone.get_connection().map_err(|e| e.to_string())?;
-        operations::folder_operations::delete_folder(&conn, folder_id).map_err(|e| e.to_string())
+        let conn = db_manager_clone.get_connection()?;
+        operations::folder_operations::delete_folder(&conn, folder_id).map_err(CommandError::from)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e| e)?;
+    .await??;
 
     Ok(())
 } 
\ No newline at end of file
@@ -6,25 +6,104 @@
 use tauri::State;
 use std::sync::Arc;
 
+use crate::database::operations::cache_delta_operations::{NewCacheDelta, CHANGE_REMOVED, CHANGE_UPDATED};
+use crate::database::operations::label_cache_operations::{self, LabelOverride};
+use crate::database::{operations::contact_operations, DatabaseManager};
+use crate::services::events::EventBus;
+use crate::services::notification::MailNotice;
+use crate::services::gmail::muted_threads::archive_muted_messages;
+use crate::services::gmail::shipment_service::ShipmentTracker;
+use crate::services::gmail::thread_actions::{self, ThreadAction, ThreadActionResult};
 use crate::services::gmail::api_service::{
-    GmailApiService, GmailLabel, MessageSearchQuery, MessageSearchResult,
+    BodySegment, GmailApiService, GmailLabel, MessageSearchQuery, MessageSearchResult,
     ProcessedGmailMessage, GmailMessage
 };
+use crate::errors::CommandResult;
+
+/// Fill in sender names missing from the From header using local contacts
+fn resolve_sender_names(db_manager: &DatabaseManager, messages: &mut [ProcessedGmailMessage]) {
+    let Ok(conn) = db_manager.get_connection() else {
+        return;
+    };
+    for message in messages.iter_mut() {
+        let from = &mut message.parsed_content.from;
+        if from.name.as_deref().map(str::trim).unwrap_or_default().is_empty() {
+            if let Ok(Some(name)) = contact_operations::resolve_display_name(&conn, &from.email) {
+                from.name = Some(name);
+            }
+        }
+    }
+}
 
 // =============================================================================
 // Command Handlers
 // =============================================================================
 
-/// Get all labels for a Gmail account
+/// Get all labels for a Gmail account, from the label cache while it is
+/// fresh. `force_refresh` refetches the labels and their counts.
 #[tauri::command]
 pub async fn get_gmail_labels(
     account_id: String,
+    force_refresh: Option<bool>,
     api_service: State<'_, Arc<GmailApiService>>,
-) -> Result<Vec<GmailLabel>, String> {
-    api_service
-        .get_labels(&account_id)
-        .await
-        .map_err(|e| e.to_string())
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<Vec<GmailLabel>> {
+    let result = if force_refresh.unwrap_or(false) {
+        api_service.refresh_labels(&account_id).await
+    } else {
+        api_service.get_labels(&account_id).await
+    };
+
+    Ok(result.inspect_err(|e| event_bus.report_error(&account_id, "interactive", e))?)
+}
+
+/// Whether a colour is a `#rrggbb` hex value
+fn is_hex_color(value: &str) -> bool {
+    value.len() == 7 && value.starts_with('#') && value[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Set a label's display name and colours in the app only; Gmail keeps
+/// its own. Leaving every field empty removes the override.
+#[tauri::command]
+pub async fn set_gmail_label_override(
+    account_id: String,
+    label_id: String,
+    display_name: Option<String>,
+    text_color: Option<String>,
+    background_color: Option<String>,
+    api_service: State<'_, Arc<GmailApiService>>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<GmailLabel>> {
+    let non_empty = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let label_override = LabelOverride {
+        label_id,
+        display_name: non_empty(display_name),
+        text_color: non_empty(text_color),
+        background_color: non_empty(background_color),
+    };
+    if [&label_override.text_color, &label_override.background_color]
+        .iter()
+        .any(|color| color.as_deref().is_some_and(|c| !is_hex_color(c)))
+    {
+        return Err("Label colours must be hex values like #16a766".into());
+    }
+
+    let conn = db_manager.get_connection()?;
+    label_cache_operations::set_override(&conn, &account_id, &label_override)?;
+    Ok(api_service.cached_labels(&account_id)?)
+}
+
+/// Go back to the label's Gmail name and colours
+#[tauri::command]
+pub async fn clear_gmail_label_override(
+    account_id: String,
+    label_id: String,
+    api_service: State<'_, Arc<GmailApiService>>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<GmailLabel>> {
+    let conn = db_manager.get_connection()?;
+    label_cache_operations::clear_override(&conn, &account_id, &label_id)?;
+    Ok(api_service.cached_labels(&account_id)?)
 }
 
 /// Search Gmail messages with parsing
@@ -36,7 +115,14 @@ pub async fn search_gmail_messages(
     max_results: Option<u32>,
     page_token: Option<String>,
     api_service: State<'_, Arc<GmailApiService>>,
-) -> Result<MessageSearchResult, String> {
+    event_bus: State<'_, EventBus>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    shipment_tracker: State<'_, ShipmentTracker>,
+) -> CommandResult<MessageSearchResult> {
+    // Only the unfiltered first page of the inbox is used for new-mail detection
+    let is_inbox_listing = label_ids.as_ref().map(|ids| ids.len() == 1 && ids[0] == "INBOX").unwrap_or(false);
+    let is_inbox_head = query.is_none() && page_token.is_none() && is_inbox_listing;
+
     let search_query = MessageSearchQuery {
         query,
         label_ids,
@@ -45,10 +131,26 @@ pub async fn search_gmail_messages(
         include_spam_trash: Some(false),
     };
 
-    api_service
+    let mut result = api_service
         .search_messages(&account_id, &search_query)
         .await
-        .map_err(|e| e.to_string())
+        .inspect_err(|e| event_bus.report_error(&account_id, "interactive", e))?;
+
+    // Muted threads are archived before new-mail detection so they never notify
+    archive_muted_messages(&api_service, &db_manager, &account_id, &mut result.messages, is_inbox_listing).await;
+
+    if is_inbox_head {
+        let notices: Vec<MailNotice> = result.messages.iter().map(MailNotice::from).collect();
+        event_bus.observe_inbox(&account_id, &notices);
+    }
+
+    resolve_sender_names(&db_manager, &mut result.messages);
+
+    if let Err(e) = shipment_tracker.detect_in_messages(&account_id, &result.messages) {
+        eprintln!("⚠️ [GMAIL-API] Failed to detect shipments: {}", e);
+    }
+
+    Ok(result)
 }
 
 /// Get a specific Gmail message by ID
@@ -57,11 +159,10 @@ pub async fn get_gmail_message(
     account_id: String,
     message_id: String,
     api_service: State<'_, Arc<GmailApiService>>,
-) -> Result<GmailMessage, String> {
-    api_service
+) -> CommandResult<GmailMessage> {
+    Ok(api_service
         .get_message(&account_id, &message_id)
-        .await
-        .map_err(|e| e.to_string())
+        .await?)
 }
 
 /// Get a parsed Gmail message by ID
@@ -70,11 +171,51 @@ pub async fn get_parsed_gmail_message(
     account_id: String,
     message_id: String,
     api_service: State<'_, Arc<GmailApiService>>,
-) -> Result<ProcessedGmailMessage, String> {
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    shipment_tracker: State<'_, ShipmentTracker>,
+) -> CommandResult<ProcessedGmailMessage> {
+    let mut message = api_service
+        .get_parsed_message(&account_id, &message_id)
+        .await?;
+
     api_service
+        .resolve_inline_images(&account_id, &mut message)
+        .await?;
+
+    resolve_sender_names(&db_manager, std::slice::from_mut(&mut message));
+
+    if let Err(e) = shipment_tracker.detect_in_messages(&account_id, std::slice::from_ref(&message)) {
+        eprintln!("⚠️ [GMAIL-API] Failed to detect shipments: {}", e);
+    }
+
+    Ok(message)
+}
+
+/// Split a message body into new content, quoted history and signature so
+/// the reader can collapse quotes
+#[tauri::command]
+pub async fn get_gmail_message_segments(
+    account_id: String,
+    message_id: String,
+    api_service: State<'_, Arc<GmailApiService>>,
+) -> CommandResult<Vec<BodySegment>> {
+    let message = api_service
         .get_parsed_message(&account_id, &message_id)
-        .await
-        .map_err(|e| e.to_string())
+        .await?;
+    Ok(api_service.reply_segments(&message))
+}
+
+/// A thread as plain text for LLM summarization, without repeated quotes
+#[tauri::command]
+pub async fn get_thread_summary_input(
+    account_id: String,
+    thread_id: String,
+    api_service: State<'_, Arc<GmailApiService>>,
+) -> CommandResult<String> {
+    let messages = api_service
+        .get_thread(&account_id, &thread_id)
+        .await?;
+    Ok(api_service.thread_summary_input(&messages))
 }
 
 /// Get an entire Gmail thread with parsed messages
@@ -83,11 +224,29 @@ pub async fn get_gmail_thread(
     account_id: String,
     thread_id: String,
     api_service: State<'_, Arc<GmailApiService>>,
-) -> Result<Vec<ProcessedGmailMessage>, String> {
-    api_service
+) -> CommandResult<Vec<ProcessedGmailMessage>> {
+    let mut messages = api_service
         .get_thread(&account_id, &thread_id)
-        .await
-        .map_err(|e| e.to_string())
+        .await?;
+
+    for message in &mut messages {
+        api_service
+            .resolve_inline_images(&account_id, message)
+            .await?;
+    }
+
+    Ok(messages)
+}
+
+/// Journal a label change Gmail already accepted; a failure is only logged,
+/// since the change itself went through
+fn record_message_change(db_manager: &DatabaseManager, account_id: &str, message_ids: &[String], add: &[String], remove: &[String]) {
+    let recorded = db_manager
+        .get_connection()
+        .and_then(|mut conn| thread_actions::record_message_change(&mut conn, account_id, message_ids, add, remove));
+    if let Err(e) = recorded {
+        eprintln!("⚠️ [MAIL] Failed to journal label change for {}: {}", account_id, e);
+    }
 }
 
 /// Modify labels for a batch of messages
@@ -98,11 +257,21 @@ pub async fn modify_gmail_messages(
     add_label_ids: Vec<String>,
     remove_label_ids: Vec<String>,
     api_service: State<'_, Arc<GmailApiService>>,
-) -> Result<(), String> {
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<()> {
+    let change = serde_json::json!({ "added_label_ids": add_label_ids, "removed_label_ids": remove_label_ids });
     api_service
-        .modify_messages(&account_id, message_ids, add_label_ids, remove_label_ids)
-        .await
-        .map_err(|e| e.to_string())
+        .modify_messages(&account_id, message_ids.clone(), add_label_ids.clone(), remove_label_ids.clone())
+        .await?;
+    record_message_change(&db_manager, &account_id, &message_ids, &add_label_ids, &remove_label_ids);
+
+    let deltas: Vec<NewCacheDelta> = message_ids
+        .iter()
+        .map(|id| NewCacheDelta::message(&account_id, id, CHANGE_UPDATED, &[]).with_data(change.clone()))
+        .collect();
+    event_bus.cache_changed(&deltas);
+    Ok(())
 }
 
 /// Move a batch of messages to the trash
@@ -111,11 +280,61 @@ pub async fn trash_gmail_messages(
     account_id: String,
     message_ids: Vec<String>,
     api_service: State<'_, Arc<GmailApiService>>,
-) -> Result<(), String> {
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<()> {
     api_service
-        .trash_messages(&account_id, message_ids)
-        .await
-        .map_err(|e| e.to_string())
+        .trash_messages(&account_id, message_ids.clone())
+        .await?;
+    record_message_change(&db_manager, &account_id, &message_ids, &["TRASH".to_string()], &[]);
+
+    let deltas: Vec<NewCacheDelta> = message_ids
+        .iter()
+        .map(|id| NewCacheDelta::message(&account_id, id, CHANGE_REMOVED, &[]))
+        .collect();
+    event_bus.cache_changed(&deltas);
+    Ok(())
+}
+
+/// Deltas for the messages a thread action or its undo changed
+fn thread_action_deltas(account_id: &str, result: &ThreadActionResult) -> Vec<NewCacheDelta> {
+    result
+        .labels
+        .iter()
+        .map(|(message_id, labels)| NewCacheDelta::message(account_id, message_id, CHANGE_UPDATED, labels))
+        .collect()
+}
+
+/// Archive, label or mark read whole threads, updating the local store and
+/// journaling the change so it can be undone
+#[tauri::command]
+pub async fn apply_gmail_thread_action(
+    account_id: String,
+    thread_ids: Vec<String>,
+    action: ThreadAction,
+    api_service: State<'_, Arc<GmailApiService>>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<ThreadActionResult> {
+    let result = thread_actions::apply(&api_service, &db_manager, &account_id, &thread_ids, &action)
+        .await?;
+    event_bus.cache_changed(&thread_action_deltas(&account_id, &result));
+    Ok(result)
+}
+
+/// Undo the latest thread action on each of the given threads
+#[tauri::command]
+pub async fn undo_gmail_thread_action(
+    account_id: String,
+    thread_ids: Vec<String>,
+    api_service: State<'_, Arc<GmailApiService>>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<ThreadActionResult> {
+    let result = thread_actions::undo(&api_service, &db_manager, &account_id, &thread_ids)
+        .await?;
+    event_bus.cache_changed(&thread_action_deltas(&account_id, &result));
+    Ok(result)
 }
 
 /// Download Gmail attachment data
@@ -125,9 +344,8 @@ pub async fn get_gmail_attachment(
     message_id: String,
     attachment_id: String,
     api_service: State<'_, Arc<GmailApiService>>,
-) -> Result<Vec<u8>, String> {
-    api_service
+) -> CommandResult<Vec<u8>> {
+    Ok(api_service
         .get_attachment(&account_id, &message_id, &attachment_id)
-        .await
-        .map_err(|e| e.to_string())
+        .await?)
 } 
\ No newline at end of file
@@ -0,0 +1,178 @@
+//! Attachment Opening Commands
+//!
+//! Attachments are saved under the attachments directory and opened with
+//! the system's default app. When a local virus scanner is available the
+//! file is scanned first; a flagged file is deleted again and only opened
+//! when the user explicitly overrides the verdict. Previews for the mail
+//! view come from `attachment_preview` without saving anything.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::State;
+
+use crate::config::get_config_manager;
+use crate::database::{
+    operations::attachment_scan_operations::{self, ScanVerdict},
+    DatabaseManager,
+};
+use crate::errors::{CommandResult, LibreOllamaError};
+use crate::services::gmail::api_service::GmailApiService;
+use crate::services::gmail::attachment_preview::{AttachmentPreview, AttachmentPreviewer};
+use crate::services::gmail::attachment_risk::{self, AttachmentRisk, RiskLevel};
+use crate::services::gmail::attachment_scan::{self, Verdict};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenedAttachment {
+    /// False when the file looks dangerous, or the scanner flagged it or
+    /// could not check it, and that was not overridden
+    pub opened: bool,
+    /// Where the file was saved; absent for a blocked file, which is removed
+    pub path: Option<String>,
+    /// Absent when scanning is off or no scanner is installed
+    pub scan: Option<ScanVerdict>,
+    /// Judged from the downloaded content, which Gmail's metadata may lack
+    pub risk: AttachmentRisk,
+}
+
+/// Keep the file name only, without characters Windows rejects
+fn safe_filename(filename: &str) -> String {
+    let name = Path::new(filename).file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_control() || "<>:\"/\\|?*".contains(c) { '_' } else { c })
+        .collect();
+    match name.trim_matches([' ', '.']) {
+        "" => "attachment".to_string(),
+        _ => name,
+    }
+}
+
+/// Each distinct content gets its own directory, so attachments sharing a
+/// name do not overwrite each other
+fn attachment_path(sha256: &str, filename: &str) -> CommandResult<PathBuf> {
+    let config_manager = get_config_manager().map_err(|e| LibreOllamaError::Configuration {
+        message: format!("Failed to get config manager: {}", e),
+        config_key: None,
+    })?;
+    Ok(config_manager
+        .paths()
+        .attachments_dir
+        .join("mail")
+        .join(&sha256[..16])
+        .join(safe_filename(filename)))
+}
+
+/// Download an attachment, scan it and open it with the default app.
+/// Dangerous-looking files, flagged files and files the scanner failed on
+/// are only opened with `allow_flagged`.
+#[tauri::command]
+pub async fn open_attachment(
+    account_id: String,
+    message_id: String,
+    attachment_id: String,
+    filename: String,
+    allow_flagged: Option<bool>,
+    api_service: State<'_, Arc<GmailApiService>>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<OpenedAttachment> {
+    let data = api_service.get_attachment(&account_id, &message_id, &attachment_id).await?;
+    let sha256 = hex::encode(Sha256::digest(&data));
+    let risk = attachment_risk::assess(Some(&filename), "", Some(&data));
+    let allow_flagged = allow_flagged.unwrap_or(false);
+    if risk.level == RiskLevel::Dangerous && !allow_flagged {
+        return Ok(OpenedAttachment { opened: false, path: None, scan: None, risk });
+    }
+    let path = attachment_path(&sha256, &filename)?;
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+    tokio::fs::write(&path, &data).await?;
+
+    let choice = attachment_scan::scanner_choice(&db_manager.get_connection()?)?;
+    let scan = match attachment_scan::scan_file(&choice, &path).await? {
+        Some(result) => Some(attachment_scan_operations::record_verdict(
+            &db_manager.get_connection()?,
+            &account_id,
+            &message_id,
+            &sha256,
+            &filename,
+            &result,
+        )?),
+        None => None,
+    };
+
+    // A scan that failed is no proof the file is safe
+    if scan.as_ref().is_some_and(|scan| matches!(scan.verdict, Verdict::Infected | Verdict::Error)) {
+        if !allow_flagged {
+            let _ = tokio::fs::remove_file(&path).await;
+            return Ok(OpenedAttachment { opened: false, path: None, scan, risk });
+        }
+        attachment_scan_operations::mark_opened_anyway(&db_manager.get_connection()?, &account_id, &message_id, &sha256)?;
+    }
+
+    open::that(&path).map_err(|e| LibreOllamaError::FileSystem {
+        message: format!("Failed to open attachment: {}", e),
+        path: Some(path.display().to_string()),
+    })?;
+    Ok(OpenedAttachment { opened: true, path: Some(path.display().to_string()), scan, risk })
+}
+
+/// Thumbnail or text excerpt of an attachment; `None` for types without
+/// previews
+#[tauri::command]
+pub async fn get_attachment_preview(
+    account_id: String,
+    message_id: String,
+    attachment_id: String,
+    filename: String,
+    content_type: String,
+    api_service: State<'_, Arc<GmailApiService>>,
+) -> CommandResult<Option<AttachmentPreview>> {
+    let previewer = AttachmentPreviewer::from_config()?;
+    // Listed size, so oversized attachments are never downloaded
+    let message = api_service.get_parsed_message(&account_id, &message_id).await?;
+    let size = message
+        .parsed_content
+        .attachments
+        .iter()
+        .find(|attachment| attachment.id == attachment_id)
+        .and_then(|attachment| attachment.size);
+    let key = format!("{}\n{}\n{}", account_id, message_id, attachment_id);
+    let download = api_service.get_attachment(&account_id, &message_id, &attachment_id);
+    Ok(previewer.preview(&key, &content_type, &filename, size, download).await?)
+}
+
+/// Scan verdicts for the attachments of a message
+#[tauri::command]
+pub async fn list_attachment_scan_verdicts(
+    account_id: String,
+    message_id: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<ScanVerdict>> {
+    let conn = db_manager.get_connection()?;
+    Ok(attachment_scan_operations::list_verdicts(&conn, &account_id, &message_id)?)
+}
+
+/// Scanner attachments are checked with under the current setting, if any
+#[tauri::command]
+pub async fn get_attachment_scanner(db_manager: State<'_, Arc<DatabaseManager>>) -> CommandResult<Option<String>> {
+    let choice = attachment_scan::scanner_choice(&db_manager.get_connection()?)?;
+    Ok(attachment_scan::available_scanner(&choice).await.map(str::to_string))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_safe_filename() {
+        assert_eq!(safe_filename("report.pdf"), "report.pdf");
+        assert_eq!(safe_filename("../../etc/passwd"), "passwd");
+        assert_eq!(safe_filename("a<b>:c?.txt"), "a_b__c_.txt");
+        assert_eq!(safe_filename(".."), "attachment");
+        assert_eq!(safe_filename(""), "attachment");
+    }
+}
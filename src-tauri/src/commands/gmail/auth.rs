@@ -3,6 +3,7 @@
 //! This module provides Tauri command handlers for Gmail authentication,
 //! delegating all business logic to the GmailAuthService.
 
+use anyhow::Context;
 use std::sync::Arc;
 use tauri::State;
 use serde::{Deserialize, Serialize};
@@ -12,6 +13,9 @@ use crate::services::gmail::auth_service::{
     GmailAuthService, 
     GmailTokens, UserInfo, StoredGmailAccount
 };
+use crate::database::operations::workspace_operations::WorkspaceKind;
+use crate::services::workspaces;
+use crate::errors::CommandResult;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AuthUrlResponse {
@@ -65,11 +69,14 @@ pub struct OAuthConfig {
 
 // Removed unused start_gmail_oauth function - not registered in Tauri handler
 
-/// Start OAuth flow with automatic callback handling for Desktop applications
+/// Start OAuth flow with automatic callback handling for Desktop applications.
+/// `login_hint` prefills the address, e.g. one from `get_detected_accounts`.
 #[tauri::command]
 pub async fn start_gmail_oauth_with_callback(
+    extra_scopes: Option<Vec<String>>,
+    login_hint: Option<String>,
     auth_service: State<'_, Arc<GmailAuthService>>,
-) -> Result<TokenResponse, String> {
+) -> CommandResult<TokenResponse> {
     use std::sync::{Arc, Mutex};
     use std::thread;
     use std::time::Duration;
@@ -79,10 +86,10 @@ pub async fn start_gmail_oauth_with_callback(
     // For desktop apps, we use a dynamic port on localhost
     // Find an available port
     let listener = TcpListener::bind("127.0.0.1:0")
-        .map_err(|e| format!("Failed to bind to any port: {}", e))?;
+        .context("Failed to bind to any port")?;
     
     let port = listener.local_addr()
-        .map_err(|e| format!("Failed to get local address: {}", e))?
+        .context("Failed to get local address")?
         .port();
     
     // Desktop apps use the loopback redirect
@@ -102,7 +109,7 @@ pub async fn start_gmail_oauth_with_callback(
         for _ in 0..300 { // 30 second timeout (100ms * 300)
             if let Ok((mut stream, _)) = listener.accept() {
                 let mut buffer = [0; 1024];
-                if let Ok(_) = stream.read(&mut buffer) {
+                if stream.read(&mut buffer).is_ok() {
                     let request = String::from_utf8_lossy(&buffer[..]);
                     if let Some(line) = request.lines().next() {
                         // For desktop apps, accept any GET request with code parameter
@@ -141,17 +148,18 @@ pub async fn start_gmail_oauth_with_callback(
     
     // Start OAuth flow with dynamic redirect URI
     println!("[OAuth] Starting authorization with redirect URI: {}", redirect_uri);
+    // Extra scopes (e.g. drive.readonly) are granted incrementally on top of the defaults
+    let extra_scopes = extra_scopes.unwrap_or_default();
+    let extra_scopes: Vec<&str> = extra_scopes.iter().map(|s| s.as_str()).collect();
     let auth_request = auth_service
-        .start_authorization(Some(redirect_uri.clone()))
+        .start_authorization_with_scopes(Some(redirect_uri.clone()), &extra_scopes, login_hint.as_deref())
         .await
-        .map_err(|e| format!("Failed to start OAuth flow: {}", e))?;
+        .context("Failed to start OAuth flow")?;
     
     println!("[OAuth] Opening browser with auth URL: {}", auth_request.auth_url);
     
     // Open browser
-    if let Err(e) = open::that(&auth_request.auth_url) {
-        return Err(format!("Failed to open browser: {}", e));
-    }
+    open::that(&auth_request.auth_url).context("Failed to open browser")?;
     
     // Wait for callback
     println!("[OAuth] Waiting for callback from browser...");
@@ -166,7 +174,7 @@ pub async fn start_gmail_oauth_with_callback(
     let token_response = auth_service
         .complete_authorization(code, state, Some(redirect_uri))
         .await
-        .map_err(|e| format!("Failed to exchange authorization code for tokens: {}", e))?;
+        .context("Failed to exchange authorization code for tokens")?;
     
     println!("[OAuth] Successfully obtained access tokens");
     
@@ -187,11 +195,10 @@ pub async fn start_gmail_oauth_with_callback(
 pub async fn get_gmail_user_info(
     access_token: String,
     auth_service: State<'_, Arc<GmailAuthService>>,
-) -> Result<UserInfo, String> {
-    auth_service
+) -> CommandResult<UserInfo> {
+    Ok(auth_service
         .get_user_info(&access_token)
-        .await
-        .map_err(|e| e.to_string())
+        .await?)
 }
 
 /// Store Gmail tokens securely
@@ -201,11 +208,10 @@ pub async fn store_gmail_tokens_secure(
     tokens: GmailTokens,
     user_info: UserInfo,
     auth_service: State<'_, Arc<GmailAuthService>>,
-) -> Result<(), String> {
-    auth_service
+) -> CommandResult<()> {
+    Ok(auth_service
         .store_account_tokens(account_id, tokens, user_info)
-        .await
-        .map_err(|e| e.to_string())
+        .await?)
 }
 
 /// Get Gmail tokens for an account
@@ -213,23 +219,26 @@ pub async fn store_gmail_tokens_secure(
 pub async fn get_gmail_tokens_secure(
     account_id: String,
     auth_service: State<'_, Arc<GmailAuthService>>,
-) -> Result<Option<GmailTokens>, String> {
-    auth_service
+) -> CommandResult<Option<GmailTokens>> {
+    Ok(auth_service
         .get_account_tokens(&account_id)
-        .await
-        .map_err(|e| e.to_string())
+        .await?)
 }
 
-/// Get all Gmail accounts for a user
+/// Get all Gmail accounts for a user that the active workspace shows
 #[tauri::command]
 pub async fn get_gmail_accounts_secure(
     user_id: String,
     auth_service: State<'_, Arc<GmailAuthService>>,
-) -> Result<Vec<StoredGmailAccount>, String> {
-    auth_service
+    db_manager: State<'_, Arc<crate::database::DatabaseManager>>,
+) -> CommandResult<Vec<StoredGmailAccount>> {
+    let mut accounts = auth_service
         .get_user_accounts(&user_id)
-        .await
-        .map_err(|e| e.to_string())
+        .await?;
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    let scope = workspaces::scope(&conn).context("Failed to load workspace")?;
+    scope.retain(WorkspaceKind::Account, &mut accounts, |account| &account.id);
+    Ok(accounts)
 }
 
 /// Remove a Gmail account from the database
@@ -237,11 +246,10 @@ pub async fn get_gmail_accounts_secure(
 pub async fn remove_gmail_account_secure(
     account_id: String,
     auth_service: State<'_, Arc<GmailAuthService>>,
-) -> Result<(), String> {
-    auth_service
+) -> CommandResult<()> {
+    Ok(auth_service
         .remove_account(&account_id)
-        .await
-        .map_err(|e| e.to_string())
+        .await?)
 }
 
 /// Debug command to check secure table existence and contents
@@ -249,31 +257,28 @@ pub async fn remove_gmail_account_secure(
 pub async fn debug_gmail_secure_table(
     _auth_service: State<'_, Arc<GmailAuthService>>,
     db_manager: State<'_, crate::database::connection::DatabaseManager>,
-) -> Result<String, String> {
-    let conn = db_manager.get_connection().map_err(|e| e.to_string())?;
+) -> CommandResult<String> {
+    let conn = db_manager.get_connection()?;
     
     // Check if table exists
-    let table_exists = conn.prepare("SELECT name FROM sqlite_master WHERE type='table' AND name='gmail_accounts_secure'")
-        .map_err(|e| e.to_string())?
-        .exists([])
-        .map_err(|e| e.to_string())?;
+    let table_exists = conn.prepare("SELECT name FROM sqlite_master WHERE type='table' AND name='gmail_accounts_secure'")?
+        .exists([])?;
     
     if !table_exists {
         return Ok("gmail_accounts_secure table does not exist".to_string());
     }
     
     // Get table info
-    let mut stmt = conn.prepare("PRAGMA table_info(gmail_accounts_secure)").map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare("PRAGMA table_info(gmail_accounts_secure)")?;
     let column_info: Vec<String> = stmt.query_map([], |row| {
         let name: String = row.get(1)?;
         let data_type: String = row.get(2)?;
         Ok(format!("{}: {}", name, data_type))
-    }).map_err(|e| e.to_string())?
-    .collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?;
+    })?
+    .collect::<Result<Vec<_>, _>>()?;
     
     // Count rows
-    let count: i64 = conn.query_row("SELECT COUNT(*) FROM gmail_accounts_secure", [], |row| row.get(0))
-        .map_err(|e| e.to_string())?;
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM gmail_accounts_secure", [], |row| row.get(0))?;
     
     Ok(format!(
         "Table exists: true\nColumns: {}\nRows: {}",
@@ -286,13 +291,13 @@ pub async fn debug_gmail_secure_table(
 #[tauri::command]
 pub async fn debug_gmail_token_expiration(
     db_manager: State<'_, crate::database::connection::DatabaseManager>,
-) -> Result<String, String> {
-    let conn = db_manager.get_connection().map_err(|e| e.to_string())?;
+) -> CommandResult<String> {
+    let conn = db_manager.get_connection()?;
     
     // Get all accounts and their expiration times
     let mut stmt = conn.prepare(
         "SELECT id, email_address, token_expires_at FROM gmail_accounts_secure WHERE is_active = 1"
-    ).map_err(|e| e.to_string())?;
+    )?;
     
     let mut results = Vec::new();
     let rows = stmt.query_map([], |row| {
@@ -300,10 +305,10 @@ pub async fn debug_gmail_token_expiration(
         let email: String = row.get(1)?;
         let expires_at: Option<String> = row.get(2)?;
         Ok((id, email, expires_at))
-    }).map_err(|e| e.to_string())?;
+    })?;
     
     for row in rows {
-        let (id, email, expires_at) = row.map_err(|e| e.to_string())?;
+        let (id, email, expires_at) = row?;
         
         let status = match expires_at {
             Some(ref expires_str) => {
@@ -355,13 +360,13 @@ pub async fn debug_gmail_token_expiration(
 #[tauri::command]
 pub async fn cleanup_corrupted_gmail_tokens(
     db_manager: State<'_, crate::database::connection::DatabaseManager>,
-) -> Result<String, String> {
-    let conn = db_manager.get_connection().map_err(|e| e.to_string())?;
+) -> CommandResult<String> {
+    let conn = db_manager.get_connection()?;
     
     // Find corrupted expiration times
     let mut stmt = conn.prepare(
         "SELECT id, email_address, token_expires_at FROM gmail_accounts_secure WHERE is_active = 1 AND token_expires_at IS NOT NULL"
-    ).map_err(|e| e.to_string())?;
+    )?;
     
     let mut corrupted_accounts = Vec::new();
     let rows = stmt.query_map([], |row| {
@@ -369,10 +374,10 @@ pub async fn cleanup_corrupted_gmail_tokens(
         let email: String = row.get(1)?;
         let expires_at: String = row.get(2)?;
         Ok((id, email, expires_at))
-    }).map_err(|e| e.to_string())?;
+    })?;
     
     for row in rows {
-        let (id, email, expires_at) = row.map_err(|e| e.to_string())?;
+        let (id, email, expires_at) = row?;
         
         // Check if the expiration time is corrupted
         if chrono::DateTime::parse_from_rfc3339(&expires_at).is_err() {
@@ -415,13 +420,13 @@ pub async fn cleanup_corrupted_gmail_tokens(
 #[tauri::command]
 pub async fn debug_list_all_gmail_accounts(
     db_manager: State<'_, crate::database::connection::DatabaseManager>,
-) -> Result<String, String> {
-    let conn = db_manager.get_connection().map_err(|e| e.to_string())?;
+) -> CommandResult<String> {
+    let conn = db_manager.get_connection()?;
     
     // Get all accounts
     let mut stmt = conn.prepare(
         "SELECT id, email_address, user_id, is_active, created_at FROM gmail_accounts_secure ORDER BY created_at DESC"
-    ).map_err(|e| e.to_string())?;
+    )?;
     
     let mut results = Vec::new();
     let rows = stmt.query_map([], |row| {
@@ -431,10 +436,10 @@ pub async fn debug_list_all_gmail_accounts(
         let is_active: bool = row.get(3)?;
         let created_at: String = row.get(4)?;
         Ok((id, email, user_id, is_active, created_at))
-    }).map_err(|e| e.to_string())?;
+    })?;
     
     for row in rows {
-        let (id, email, user_id, is_active, created_at) = row.map_err(|e| e.to_string())?;
+        let (id, email, user_id, is_active, created_at) = row?;
         results.push(format!(
             "Account ID: {}\n  Email: {}\n  User ID: {}\n  Active: {}\n  Created: {}", 
             id, email, user_id, is_active, created_at
@@ -452,18 +457,18 @@ pub async fn debug_list_all_gmail_accounts(
 #[tauri::command]
 pub async fn clear_all_gmail_tokens(
     db_manager: State<'_, crate::database::connection::DatabaseManager>,
-) -> Result<String, String> {
+) -> CommandResult<String> {
     use crate::utils::crypto::{encrypt_data, get_persistent_encryption_key};
     
-    let conn = db_manager.get_connection().map_err(|e| e.to_string())?;
+    let conn = db_manager.get_connection()?;
     
     // Create a properly encrypted dummy token that will be invalid when used
     let encryption_key = get_persistent_encryption_key();
     let dummy_token = encrypt_data("INVALID_REAUTH_REQUIRED", &encryption_key)
-        .map_err(|e| format!("Failed to create dummy token: {}", e))?;
+        .context("Failed to create dummy token")?;
     
     // Update accounts with the dummy encrypted token and mark as requiring re-authentication
-    match conn.execute(
+    let count = conn.execute(
         &format!("UPDATE gmail_accounts_secure SET 
          access_token_encrypted = '{}', 
          refresh_token_encrypted = '{}', 
@@ -472,12 +477,8 @@ pub async fn clear_all_gmail_tokens(
          requires_reauth = 1
          WHERE 1=1", dummy_token, dummy_token),
         [],
-    ) {
-        Ok(count) => {
-            Ok(format!("Invalidated tokens for {} accounts. Please re-authenticate.", count))
-        }
-        Err(e) => {
-            Err(format!("Failed to clear tokens: {}", e))
-        }
-    }
+    )
+    .context("Failed to clear tokens")?;
+
+    Ok(format!("Invalidated tokens for {} accounts. Please re-authenticate.", count))
 } 
\ No newline at end of file
@@ -0,0 +1,66 @@
+//! Compose Autosave Commands
+//!
+//! The composer calls `autosave_draft` periodically with its current state.
+//! Saves are local only, so they work offline, and `recover_unsent_drafts`
+//! on startup returns whatever was being written when the app last closed
+//! without sending.
+
+use anyhow::Context;
+use std::sync::Arc;
+use tauri::State;
+
+use crate::database::{
+    operations::autosave_operations::{self, AutosavedDraft},
+    DatabaseManager,
+};
+use crate::errors::CommandResult;
+
+/// Save the composer's current state as a new version under `autosave_key`
+#[tauri::command]
+pub async fn autosave_draft(
+    account_id: String,
+    autosave_key: String,
+    compose_data: serde_json::Value,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<AutosavedDraft> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(autosave_operations::save_version(&conn, &account_id, &autosave_key, &compose_data)
+        .context("Failed to autosave draft")?)
+}
+
+/// Latest version of every compose window that was never sent or discarded
+#[tauri::command]
+pub async fn recover_unsent_drafts(
+    account_id: Option<String>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<AutosavedDraft>> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(autosave_operations::list_unsent(&conn, account_id.as_deref())
+        .context("Failed to recover drafts")?)
+}
+
+/// Earlier versions of a compose window, newest first
+#[tauri::command]
+pub async fn get_autosave_versions(
+    autosave_key: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<AutosavedDraft>> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(autosave_operations::list_versions(&conn, &autosave_key)
+        .context("Failed to load autosave versions")?)
+}
+
+/// Forget a compose window's autosaves when the user discards it
+#[tauri::command]
+pub async fn discard_autosaved_draft(
+    autosave_key: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<bool> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(autosave_operations::clear(&conn, &autosave_key)
+        .context("Failed to discard autosaved draft")?)
+}
@@ -0,0 +1,72 @@
+//! Gmail Backfill Commands
+//!
+//! Commands for starting, pausing and monitoring the resumable first-time
+//! download of a mailbox, and for paging through what it stored.
+
+use anyhow::Context;
+use std::sync::Arc;
+use tauri::State;
+
+use crate::database::operations::backfill_operations::BackfillProgress;
+use crate::database::operations::message_header_operations::{self, HeaderFilter, HeaderSort, HeaderWindow};
+use crate::database::DatabaseManager;
+use crate::services::gmail::backfill_service::BackfillRunner;
+use crate::errors::CommandResult;
+
+/// Start or resume the backfill for an account. Pass `restart` to discard
+/// the checkpoint and begin again.
+#[tauri::command]
+pub async fn start_gmail_backfill(
+    account_id: String,
+    restart: Option<bool>,
+    backfill_runner: State<'_, BackfillRunner>,
+) -> CommandResult<BackfillProgress> {
+    Ok(backfill_runner
+        .start(&account_id, restart.unwrap_or(false))
+        .context("Failed to start backfill")?)
+}
+
+#[tauri::command]
+pub async fn pause_gmail_backfill(
+    account_id: String,
+    backfill_runner: State<'_, BackfillRunner>,
+) -> CommandResult<()> {
+    Ok(backfill_runner
+        .pause(&account_id)
+        .context("Failed to pause backfill")?)
+}
+
+/// Backfill progress, or `None` if no backfill has been started
+#[tauri::command]
+pub async fn get_gmail_backfill_progress(
+    account_id: String,
+    backfill_runner: State<'_, BackfillRunner>,
+) -> CommandResult<Option<BackfillProgress>> {
+    Ok(backfill_runner
+        .progress(&account_id)
+        .context("Failed to get backfill progress")?)
+}
+
+/// A window of stored message headers for the mail list. Pass the previous
+/// window's `next_cursor` to continue from it.
+#[tauri::command]
+pub async fn get_message_headers(
+    account_id: String,
+    limit: Option<i64>,
+    sort: Option<HeaderSort>,
+    filter: Option<HeaderFilter>,
+    cursor: Option<String>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<HeaderWindow> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(message_header_operations::get_headers(
+        &conn,
+        &account_id,
+        &filter.unwrap_or_default(),
+        sort.unwrap_or_default(),
+        limit.unwrap_or(100),
+        cursor.as_deref(),
+    )
+    .context("Failed to load message headers")?)
+}
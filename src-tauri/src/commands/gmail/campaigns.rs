@@ -0,0 +1,169 @@
+//! Mail Merge Campaign Commands
+//!
+//! Commands for creating mail merge campaigns, previewing the personalized
+//! messages, and starting, pausing or cancelling the background send.
+
+use anyhow::Context;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::State;
+
+use crate::database::{
+    operations::{
+        campaign_operations::{self, CampaignProgress, CampaignRecipient, CampaignRecipientInput, MailCampaign, NewCampaign},
+        contact_operations,
+    },
+    DatabaseManager,
+};
+use crate::services::gmail::campaign_service::{self, CampaignRunner, RenderedMessage};
+use crate::errors::{CommandResult, LibreOllamaError};
+
+/// Create a campaign from a template and a recipient list.
+///
+/// Recipients can be given directly or as local contact IDs; contacts
+/// contribute their name, given/family name and organization as variables.
+#[tauri::command]
+pub async fn create_mail_campaign(
+    campaign: NewCampaign,
+    recipients: Option<Vec<CampaignRecipientInput>>,
+    contact_ids: Option<Vec<i64>>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<MailCampaign> {
+    let mut conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+
+    let mut all_recipients = recipients.unwrap_or_default();
+    for contact_id in contact_ids.unwrap_or_default() {
+        let contact = contact_operations::get_contact(&conn, contact_id)
+            .context("Failed to load contact")?
+            .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("contact {}", contact_id) })?;
+        let Some(email) = contact.emails.first() else {
+            eprintln!("⚠️ Contact {} has no email address, skipping", contact_id);
+            continue;
+        };
+
+        let mut variables = HashMap::new();
+        for (key, value) in [
+            ("first_name", &contact.given_name),
+            ("last_name", &contact.family_name),
+            ("organization", &contact.organization),
+            ("job_title", &contact.job_title),
+        ] {
+            if let Some(value) = value {
+                variables.insert(key.to_string(), value.clone());
+            }
+        }
+
+        all_recipients.push(CampaignRecipientInput {
+            email: email.email.clone(),
+            name: contact.display_name.clone(),
+            variables,
+        });
+    }
+
+    if all_recipients.is_empty() {
+        return Err("A campaign needs at least one recipient".into());
+    }
+
+    let campaign_id = campaign_operations::create_campaign(&mut conn, &campaign, &all_recipients)
+        .context("Failed to create campaign")?;
+    Ok(campaign_operations::get_campaign(&conn, campaign_id)
+        .context("Failed to load campaign")?
+        .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("campaign {}", campaign_id) })?)
+}
+
+#[tauri::command]
+pub async fn list_mail_campaigns(
+    account_id: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<MailCampaign>> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(campaign_operations::list_campaigns(&conn, &account_id)
+        .context("Failed to list campaigns")?)
+}
+
+#[tauri::command]
+pub async fn get_campaign_recipients(
+    campaign_id: i64,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<CampaignRecipient>> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(campaign_operations::list_recipients(&conn, campaign_id)
+        .context("Failed to list campaign recipients")?)
+}
+
+/// Render the personalized message for each recipient (or the first `limit`)
+#[tauri::command]
+pub async fn preview_mail_campaign(
+    campaign_id: i64,
+    limit: Option<usize>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<Result<RenderedMessage, String>>> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    let campaign = campaign_operations::get_campaign(&conn, campaign_id)
+        .context("Failed to load campaign")?
+        .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("campaign {}", campaign_id) })?;
+    let recipients = campaign_operations::list_recipients(&conn, campaign_id)
+        .context("Failed to list campaign recipients")?;
+
+    Ok(recipients
+        .iter()
+        .take(limit.unwrap_or(usize::MAX))
+        .map(|recipient| campaign_service::render_for_recipient(&campaign, recipient))
+        .collect())
+}
+
+/// Start (or resume) sending a campaign in the background
+#[tauri::command]
+pub async fn start_mail_campaign(
+    campaign_id: i64,
+    retry_failed: Option<bool>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    campaign_runner: State<'_, CampaignRunner>,
+) -> CommandResult<()> {
+    if retry_failed.unwrap_or(false) {
+        let conn = db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        campaign_operations::retry_failed_recipients(&conn, campaign_id)
+            .context("Failed to requeue failed recipients")?;
+    }
+
+    Ok(campaign_runner
+        .start(campaign_id)
+        .context("Failed to start campaign")?)
+}
+
+#[tauri::command]
+pub async fn pause_mail_campaign(
+    campaign_id: i64,
+    campaign_runner: State<'_, CampaignRunner>,
+) -> CommandResult<()> {
+    Ok(campaign_runner
+        .stop(campaign_id, "paused")
+        .context("Failed to pause campaign")?)
+}
+
+#[tauri::command]
+pub async fn cancel_mail_campaign(
+    campaign_id: i64,
+    campaign_runner: State<'_, CampaignRunner>,
+) -> CommandResult<()> {
+    Ok(campaign_runner
+        .stop(campaign_id, "cancelled")
+        .context("Failed to cancel campaign")?)
+}
+
+#[tauri::command]
+pub async fn get_campaign_progress(
+    campaign_id: i64,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<CampaignProgress> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(campaign_operations::campaign_progress(&conn, campaign_id)
+        .context("Failed to get campaign progress")?
+        .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("campaign {}", campaign_id) })?)
+}
@@ -3,29 +3,187 @@
 //! This module provides Tauri command handlers for Gmail email composition,
 //! sending, and draft management using the GmailComposeService.
 
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
 use tauri::State;
 use std::sync::Arc;
 
+use crate::config::get_config_manager;
+use crate::database::operations::{autosave_operations, draft_operations::CachedDraft};
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, CommandResult};
+use crate::services::events::{AttachmentUploadProgressEvent, BackendEvent, EventBus};
+use crate::services::gmail::address_validation::{self, RecipientWarning};
+use crate::services::gmail::attachment_reminder::{self, AttachmentReminder};
+use crate::services::gmail::auth_service::{GmailAuthService, DRIVE_FILE_SCOPE};
+use crate::services::gmail::drive_offload;
+use crate::services::gmail::out_of_office::{self, AwayAdvisory};
+use crate::services::gmail::EmailAddress;
+use crate::services::google::GoogleEndpoints;
+use crate::services::google::drive_service::DriveService;
 use crate::services::gmail::compose_service::{
     GmailComposeService, ComposeRequest, 
-    SendResponse, DraftSaveRequest, DraftResponse, MessageTemplate, 
-    ReplyRequest
+    SendResponse, DraftSaveRequest, DraftResponse, DraftSyncSummary, MessageTemplate, 
+    ReplyRequest, ReplyType
 };
 
 // =============================================================================
 // Command Handlers
 // =============================================================================
 
-/// Send an email message
+/// Send an email message.
+///
+/// Attachments over `attachment_max_size_mb` in total are uploaded to Drive
+/// and sent as links, reporting `backend://attachment-upload-progress`,
+/// unless that has been turned off. Uploading needs the drive.file scope;
+/// when it is missing the error names the scope so the frontend can re-run
+/// OAuth with `extra_scopes`.
+///
+/// `autosave_key` identifies the composer's autosaves, which are cleared
+/// once the message is sent.
+///
+/// A message that mentions an attachment but has none is not sent: the
+/// `attachment_missing` error carries the reminder, and sending again with
+/// `attachment_reminder_acknowledged` goes ahead.
 #[tauri::command]
 pub async fn send_gmail_message(
-    compose_request: ComposeRequest,
+    mut compose_request: ComposeRequest,
+    autosave_key: Option<String>,
+    attachment_reminder_acknowledged: Option<bool>,
     compose_service: State<'_, Arc<GmailComposeService>>,
-) -> Result<SendResponse, String> {
-    compose_service
+    drive_service: State<'_, DriveService>,
+    event_bus: State<'_, EventBus>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<SendResponse> {
+    if !attachment_reminder_acknowledged.unwrap_or(false) {
+        if let Some(reminder) = attachment_reminder::check(&db_manager, &compose_request).await {
+            return Err(reminder.into_error());
+        }
+    }
+
+    let limit_mb = get_config_manager()
+        .map(|config| config.gmail().attachment_max_size_mb)
+        .unwrap_or(25);
+    let limit_bytes = limit_mb * 1024 * 1024;
+
+    if drive_offload::total_attachment_size(&compose_request) > limit_bytes {
+        let enabled = db_manager
+            .get_connection()
+            .and_then(|conn| drive_offload::is_enabled(&conn))
+            .context("Failed to read attachment settings")?;
+        if !enabled {
+            return Err(LibreOllamaError::InvalidInput {
+                message: format!("Attachments exceed the {} MB limit", limit_mb),
+                field: Some("attachments".to_string()),
+            }
+            .into());
+        }
+
+        let account_id = compose_request.account_id.clone();
+        let on_progress = |filename: &str, bytes_sent: u64, total_bytes: u64| {
+            event_bus.emit(BackendEvent::AttachmentUploadProgress(AttachmentUploadProgressEvent {
+                account_id: account_id.clone(),
+                filename: filename.to_string(),
+                bytes_sent,
+                total_bytes,
+            }));
+        };
+        drive_offload::offload_large_attachments(&drive_service, &mut compose_request, limit_bytes, &on_progress)
+            .await
+            .map_err(|e| match e {
+                LibreOllamaError::GoogleDriveApi { status_code: Some(403), .. } => LibreOllamaError::PermissionDenied {
+                    message: format!("Sending large attachments via Drive requires additional access: {}", DRIVE_FILE_SCOPE),
+                },
+                e => e,
+            })
+            .context("Failed to upload attachments to Drive")?;
+    }
+
+    let response = compose_service
         .send_message(&compose_request)
-        .await
-        .map_err(|e| e.to_string())
+        .await?;
+
+    if let Some(autosave_key) = autosave_key {
+        let cleared = db_manager
+            .get_connection()
+            .and_then(|conn| autosave_operations::clear(&conn, &autosave_key));
+        if let Err(e) = cleared {
+            eprintln!("⚠️ Failed to clear autosaves for sent message: {}", e);
+        }
+    }
+
+    Ok(response)
+}
+
+/// Check a message's recipients before sending: syntax, whether the domain
+/// accepts mail, disposable services and likely typos. Warnings never
+/// block sending; the composer shows them and lets the user decide.
+#[tauri::command]
+pub async fn validate_gmail_recipients(
+    compose_request: ComposeRequest,
+) -> CommandResult<Vec<RecipientWarning>> {
+    Ok(address_validation::validate_recipients(&compose_request).await)
+}
+
+/// Soft warnings for the composer to show before the final send
+#[derive(Debug, Clone, Serialize)]
+pub struct PreSendCheck {
+    pub recipients: Vec<RecipientWarning>,
+    pub attachment_reminder: Option<AttachmentReminder>,
+    /// Recipients who seem to be away when the message arrives
+    pub away: Vec<AwayAdvisory>,
+}
+
+/// Run every pre-send check: recipient problems, a mentioned but missing
+/// attachment and recipients who are out of office. Nothing here blocks
+/// sending.
+#[tauri::command]
+pub async fn check_gmail_before_send(
+    compose_request: ComposeRequest,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    auth_service: State<'_, Arc<GmailAuthService>>,
+) -> CommandResult<PreSendCheck> {
+    let recipients: Vec<EmailAddress> = compose_request
+        .to
+        .iter()
+        .chain(compose_request.cc.iter().flatten())
+        .chain(compose_request.bcc.iter().flatten())
+        .cloned()
+        .collect();
+    let arrives_at = compose_request.schedule_send.unwrap_or_else(Utc::now);
+    Ok(PreSendCheck {
+        recipients: address_validation::validate_recipients(&compose_request).await,
+        attachment_reminder: attachment_reminder::check(&db_manager, &compose_request).await,
+        away: out_of_office::check_recipients(
+            &db_manager,
+            &auth_service,
+            &GoogleEndpoints::default(),
+            &compose_request.account_id,
+            &recipients,
+            arrives_at,
+        )
+        .await,
+    })
+}
+
+/// Recipients or attendees who seem to be away at `at` (RFC 3339, default
+/// now), from their auto-replies and visible calendars
+#[tauri::command]
+pub async fn check_recipients_away(
+    account_id: String,
+    recipients: Vec<EmailAddress>,
+    at: Option<String>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    auth_service: State<'_, Arc<GmailAuthService>>,
+) -> CommandResult<Vec<AwayAdvisory>> {
+    let at = match at {
+        Some(at) => DateTime::parse_from_rfc3339(&at)
+            .context("Invalid at")?
+            .with_timezone(&Utc),
+        None => Utc::now(),
+    };
+    Ok(out_of_office::check_recipients(&db_manager, &auth_service, &GoogleEndpoints::default(), &account_id, &recipients, at).await)
 }
 
 /// Save message as draft
@@ -33,25 +191,51 @@ pub async fn send_gmail_message(
 pub async fn save_gmail_draft(
     draft_request: DraftSaveRequest,
     compose_service: State<'_, Arc<GmailComposeService>>,
-) -> Result<DraftResponse, String> {
-    compose_service
+) -> CommandResult<DraftResponse> {
+    Ok(compose_service
         .save_draft(&draft_request)
-        .await
-        .map_err(|e| e.to_string())
+        .await?)
+}
+
+/// List drafts for an account from the local cache, refreshed from Gmail when online
+#[tauri::command]
+pub async fn list_gmail_drafts(
+    account_id: String,
+    compose_service: State<'_, Arc<GmailComposeService>>,
+) -> CommandResult<Vec<CachedDraft>> {
+    Ok(compose_service
+        .list_drafts(&account_id)
+        .await?)
+}
+
+/// Get a single draft by Gmail draft ID or local ID
+#[tauri::command]
+pub async fn get_gmail_draft(
+    account_id: String,
+    draft_id: String,
+    compose_service: State<'_, Arc<GmailComposeService>>,
+) -> CommandResult<CachedDraft> {
+    Ok(compose_service
+        .get_draft(&account_id, &draft_id)
+        .await?)
 }
 
-/// Get all drafts for an account
+/// Replace the contents of an existing draft
 #[tauri::command]
-pub async fn get_gmail_drafts(
+pub async fn update_gmail_draft(
     account_id: String,
-    max_results: Option<u32>,
-    page_token: Option<String>,
+    draft_id: String,
+    compose_data: ComposeRequest,
     compose_service: State<'_, Arc<GmailComposeService>>,
-) -> Result<Vec<DraftResponse>, String> {
-    compose_service
-        .get_drafts(&account_id, max_results, page_token.as_deref())
-        .await
-        .map_err(|e| e.to_string())
+) -> CommandResult<DraftResponse> {
+    let draft_request = DraftSaveRequest {
+        account_id,
+        draft_id: Some(draft_id),
+        compose_data,
+    };
+    Ok(compose_service
+        .save_draft(&draft_request)
+        .await?)
 }
 
 /// Delete a draft
@@ -60,11 +244,33 @@ pub async fn delete_gmail_draft(
     account_id: String,
     draft_id: String,
     compose_service: State<'_, Arc<GmailComposeService>>,
-) -> Result<(), String> {
-    compose_service
+) -> CommandResult<()> {
+    Ok(compose_service
         .delete_draft(&account_id, &draft_id)
-        .await
-        .map_err(|e| e.to_string())
+        .await?)
+}
+
+/// Send an existing draft
+#[tauri::command]
+pub async fn send_draft(
+    account_id: String,
+    draft_id: String,
+    compose_service: State<'_, Arc<GmailComposeService>>,
+) -> CommandResult<SendResponse> {
+    Ok(compose_service
+        .send_draft(&account_id, &draft_id)
+        .await?)
+}
+
+/// Push drafts edited offline and refresh the draft cache
+#[tauri::command]
+pub async fn sync_gmail_drafts(
+    account_id: String,
+    compose_service: State<'_, Arc<GmailComposeService>>,
+) -> CommandResult<DraftSyncSummary> {
+    Ok(compose_service
+        .sync_drafts(&account_id)
+        .await?)
 }
 
 /// Create a reply to an existing message
@@ -72,11 +278,36 @@ pub async fn delete_gmail_draft(
 pub async fn create_gmail_reply(
     reply_request: ReplyRequest,
     compose_service: State<'_, Arc<GmailComposeService>>,
-) -> Result<ComposeRequest, String> {
-    compose_service
+) -> CommandResult<ComposeRequest> {
+    Ok(compose_service
         .create_reply(&reply_request)
-        .await
-        .map_err(|e| e.to_string())
+        .await?)
+}
+
+/// Prefill a reply, reply-all or forward to a message, with threading
+/// headers, the quoted original and, for forwards, its attachments
+#[tauri::command]
+pub async fn build_reply(
+    account_id: String,
+    message_id: String,
+    mode: ReplyType,
+    compose_service: State<'_, Arc<GmailComposeService>>,
+) -> CommandResult<ComposeRequest> {
+    Ok(compose_service
+        .build_reply(&account_id, &message_id, &mode)
+        .await?)
+}
+
+/// Prefill a forward of a message, including its attachments
+#[tauri::command]
+pub async fn build_forward(
+    account_id: String,
+    message_id: String,
+    compose_service: State<'_, Arc<GmailComposeService>>,
+) -> CommandResult<ComposeRequest> {
+    Ok(compose_service
+        .build_forward(&account_id, &message_id)
+        .await?)
 }
 
 /// Get message templates
@@ -84,11 +315,10 @@ pub async fn create_gmail_reply(
 pub async fn get_gmail_templates(
     account_id: String,
     compose_service: State<'_, Arc<GmailComposeService>>,
-) -> Result<Vec<MessageTemplate>, String> {
-    compose_service
+) -> CommandResult<Vec<MessageTemplate>> {
+    Ok(compose_service
         .get_templates(&account_id)
-        .await
-        .map_err(|e| e.to_string())
+        .await?)
 }
 
 /// Create a new message template
@@ -97,9 +327,28 @@ pub async fn create_gmail_template(
     account_id: String,
     template: MessageTemplate,
     compose_service: State<'_, Arc<GmailComposeService>>,
-) -> Result<String, String> {
-    compose_service
+) -> CommandResult<String> {
+    Ok(compose_service
         .create_template(&account_id, &template)
-        .await
-        .map_err(|e| e.to_string())
-} 
\ No newline at end of file
+        .await?)
+}
+
+/// Whether oversized attachments are sent as Drive links
+#[tauri::command]
+pub async fn get_drive_attachment_offload(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<bool> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(drive_offload::is_enabled(&conn)?)
+}
+
+#[tauri::command]
+pub async fn set_drive_attachment_offload(
+    enabled: bool,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<()> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(drive_offload::set_enabled(&conn, enabled)?)
+}
@@ -0,0 +1,50 @@
+//! Thread Export Commands
+//!
+//! Save a whole conversation as a PDF or Markdown file to share outside
+//! email; see `services::gmail::thread_export`.
+
+use anyhow::Context;
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+
+use crate::services::events::{BackendEvent, EventBus, ThreadExportProgressEvent};
+use crate::services::file_access;
+use crate::services::gmail::api_service::GmailApiService;
+use crate::services::gmail::thread_export::{self, ExportProgress, ExportStage, ThreadExport, ThreadExportFormat};
+use crate::errors::CommandResult;
+
+/// Export a thread to a file, or into a folder under its subject.
+/// Progress is reported as `backend://thread-export-progress` events.
+#[tauri::command]
+pub async fn export_thread(
+    account_id: String,
+    thread_id: String,
+    format: ThreadExportFormat,
+    path: String,
+    app: AppHandle,
+    api_service: State<'_, Arc<GmailApiService>>,
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<ThreadExport> {
+    let report = |progress: ExportProgress| {
+        event_bus.emit(BackendEvent::ThreadExportProgress(ThreadExportProgressEvent {
+            account_id: account_id.clone(),
+            thread_id: thread_id.clone(),
+            stage: progress.stage,
+            done: progress.done,
+            total: progress.total,
+        }));
+    };
+
+    let path = file_access::permitted_path(&app, &path)?;
+    report(ExportProgress::new(ExportStage::Loading, 0, 1));
+    let messages = api_service.get_thread(&account_id, &thread_id).await?;
+    Ok(thread_export::export_messages(&messages, format, &path, report).await?)
+}
+
+/// Whether a browser that can print PDFs is installed
+#[tauri::command]
+pub async fn get_pdf_export_available() -> CommandResult<bool> {
+    Ok(tokio::task::spawn_blocking(thread_export::renderer_available)
+        .await
+        .context("Renderer check failed")?)
+}
@@ -4,8 +4,20 @@
 
 pub mod auth;
 pub mod api;
+pub mod attachments;
+pub mod autosave;
+pub mod backfill;
 pub mod compose;
+pub mod export;
+pub mod campaigns;
+pub mod mute;
+pub mod receipts;
+pub mod search;
+pub mod send_as;
+pub mod shipments;
 pub mod sync;
+pub mod translation;
+pub mod triage;
 pub mod cache;
 pub mod migration;
 
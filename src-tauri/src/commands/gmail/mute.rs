@@ -0,0 +1,91 @@
+//! Thread Muting Commands
+//!
+//! Muting a thread archives it now and keeps archiving new replies as they
+//! arrive, without raising new-mail notifications for them.
+
+use anyhow::Context;
+use std::sync::Arc;
+use tauri::State;
+
+use crate::database::{
+    operations::mute_operations::{self, MutedThread},
+    DatabaseManager,
+};
+use crate::services::gmail::api_service::GmailApiService;
+use crate::errors::{CommandResult, LibreOllamaError};
+
+/// Mute a thread and archive the messages of it currently in the inbox
+#[tauri::command]
+pub async fn mute_thread(
+    account_id: String,
+    thread_id: String,
+    api_service: State<'_, Arc<GmailApiService>>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<MutedThread> {
+    // The thread is muted locally even if Gmail cannot be reached; the
+    // messages are archived the next time they show up in the inbox
+    let (subject, inbox_ids) = match api_service.get_thread(&account_id, &thread_id).await {
+        Ok(messages) => (
+            messages.first().and_then(|m| m.parsed_content.subject.clone()),
+            messages
+                .iter()
+                .filter(|m| m.labels.iter().any(|l| l == "INBOX"))
+                .map(|m| m.id.clone())
+                .collect::<Vec<_>>(),
+        ),
+        Err(e) => {
+            eprintln!("⚠️ Could not load thread {} while muting: {}", thread_id, e);
+            (None, Vec::new())
+        }
+    };
+
+    let mut archived = 0;
+    if !inbox_ids.is_empty() {
+        match api_service
+            .modify_messages(&account_id, inbox_ids.clone(), Vec::new(), vec!["INBOX".to_string()])
+            .await
+        {
+            Ok(()) => archived = inbox_ids.len() as i64,
+            Err(e) => eprintln!("⚠️ Failed to archive muted thread {}: {}", thread_id, e),
+        }
+    }
+
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    mute_operations::mute_thread(&conn, &account_id, &thread_id, subject.as_deref())
+        .context("Failed to mute thread")?;
+    if archived > 0 {
+        mute_operations::add_archived_count(&conn, &account_id, &thread_id, archived)
+            .context("Failed to mute thread")?;
+    }
+
+    Ok(mute_operations::list_muted_threads(&conn, &account_id)
+        .context("Failed to load muted thread")?
+        .into_iter()
+        .find(|t| t.thread_id == thread_id)
+        .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("muted thread {}", thread_id) })?)
+}
+
+/// Stop muting a thread. Messages already archived stay archived.
+#[tauri::command]
+pub async fn unmute_thread(
+    account_id: String,
+    thread_id: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<bool> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(mute_operations::unmute_thread(&conn, &account_id, &thread_id)
+        .context("Failed to unmute thread")?)
+}
+
+#[tauri::command]
+pub async fn list_muted_threads(
+    account_id: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<MutedThread>> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(mute_operations::list_muted_threads(&conn, &account_id)
+        .context("Failed to list muted threads")?)
+}
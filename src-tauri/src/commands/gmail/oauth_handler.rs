@@ -3,6 +3,7 @@
 //! Provides OAuth callback handling with local HTTP server and browser opening
 //! functionality for secure authentication flow.
 
+use anyhow::Context;
 use tauri::{State, Window, Emitter};
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
@@ -13,6 +14,7 @@ use std::net::{TcpListener, TcpStream};
 use std::io::{Read, Write};
 use std::thread;
 use url::Url;
+use crate::errors::CommandResult;
 
 /// OAuth callback data
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,7 +45,7 @@ impl Default for OAuthListenerState {
 pub async fn listen_oauth_callback(
     window: Window,
     oauth_state: State<'_, Arc<Mutex<OAuthListenerState>>>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     let (tx, rx) = oneshot::channel();
     
     // Find available port
@@ -66,7 +68,7 @@ pub async fn listen_oauth_callback(
 
 /// Open browser with URL
 #[tauri::command]
-pub async fn open_browser(url: String) -> Result<(), String> {
+pub async fn open_browser(url: String) -> CommandResult<()> {
     // Try multiple methods to open browser
     
     // Method 1: Use system's default browser
@@ -118,12 +120,12 @@ pub async fn open_browser(url: String) -> Result<(), String> {
 pub async fn handle_oauth_redirect(
     callback_url: String,
     window: Window,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     let callback = parse_oauth_callback(&callback_url)?;
     
     // Emit event to frontend
     window.emit("oauth_callback", &callback)
-        .map_err(|e| format!("Failed to emit OAuth callback event: {}", e))?;
+        .context("Failed to emit OAuth callback event")?;
     
     Ok(())
 }
@@ -132,7 +134,7 @@ pub async fn handle_oauth_redirect(
 #[tauri::command]
 pub async fn get_oauth_ports(
     oauth_state: State<'_, Arc<Mutex<OAuthListenerState>>>,
-) -> Result<Vec<u16>, String> {
+) -> CommandResult<Vec<u16>> {
     let state = oauth_state.lock().unwrap();
     Ok(state.server_ports.clone())
 }
@@ -242,9 +244,9 @@ fn handle_callback_request(stream: &mut TcpStream) -> Result<Option<OAuthCallbac
 }
 
 /// Parse OAuth callback parameters from URL
-fn parse_oauth_callback(url: &str) -> Result<OAuthCallback, String> {
+fn parse_oauth_callback(url: &str) -> CommandResult<OAuthCallback> {
     let parsed_url = Url::parse(url)
-        .map_err(|e| format!("Invalid callback URL: {}", e))?;
+        .context("Invalid callback URL")?;
     
     let mut code = None;
     let mut state = None;
@@ -0,0 +1,102 @@
+//! Read Receipt and Open Tracking Commands
+//!
+//! Commands for checking the delivery/read status of sent mail, scanning
+//! the inbox for returned receipts, and configuring the optional
+//! self-hosted tracking pixel.
+
+use anyhow::Context;
+use std::sync::Arc;
+use tauri::State;
+
+use crate::database::{
+    operations::receipt_operations::{self, TrackedMessage, TrackingSettings},
+    DatabaseManager,
+};
+use crate::services::gmail::compose_service::{GmailComposeService, ReceiptScanSummary};
+use crate::errors::{CommandResult, LibreOllamaError};
+
+/// Delivery and read status for a sent message, if it is tracked
+#[tauri::command]
+pub async fn get_message_receipt_status(
+    account_id: String,
+    message_id: String,
+    compose_service: State<'_, Arc<GmailComposeService>>,
+) -> CommandResult<Option<TrackedMessage>> {
+    Ok(compose_service
+        .get_receipt_status(&account_id, &message_id)?)
+}
+
+/// Recently sent messages that requested receipts or carry a pixel
+#[tauri::command]
+pub async fn list_tracked_messages(
+    account_id: String,
+    limit: Option<i64>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<TrackedMessage>> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(receipt_operations::list_tracked_messages(&conn, &account_id, limit.unwrap_or(50))
+        .context("Failed to list tracked messages")?)
+}
+
+/// Scan the inbox for read receipts and delivery reports
+#[tauri::command]
+pub async fn sync_message_receipts(
+    account_id: String,
+    compose_service: State<'_, Arc<GmailComposeService>>,
+) -> CommandResult<ReceiptScanSummary> {
+    Ok(compose_service
+        .process_receipts(&account_id)
+        .await?)
+}
+
+#[tauri::command]
+pub async fn get_tracking_pixel_settings(
+    account_id: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<TrackingSettings> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(receipt_operations::get_tracking_settings(&conn, &account_id)
+        .context("Failed to get tracking settings")?)
+}
+
+/// Enable or disable the tracking pixel. The base URL must point at a server
+/// the user runs; it is served `/t/<token>.gif` requests.
+#[tauri::command]
+pub async fn set_tracking_pixel_settings(
+    account_id: String,
+    pixel_enabled: bool,
+    pixel_base_url: Option<String>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<TrackingSettings> {
+    let pixel_base_url = pixel_base_url.map(|url| url.trim().to_string()).filter(|url| !url.is_empty());
+    if pixel_enabled {
+        match pixel_base_url.as_deref() {
+            Some(url) if url.starts_with("https://") || url.starts_with("http://") => {}
+            _ => return Err("A pixel base URL starting with http:// or https:// is required".into()),
+        }
+    }
+
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    receipt_operations::set_pixel_settings(&conn, &account_id, pixel_enabled, pixel_base_url.as_deref())
+        .context("Failed to save tracking settings")?;
+    Ok(receipt_operations::get_tracking_settings(&conn, &account_id)
+        .context("Failed to get tracking settings")?)
+}
+
+/// Record a pixel hit reported by the self-hosted tracking server
+#[tauri::command]
+pub async fn record_tracking_pixel_open(
+    tracking_token: String,
+    opened_at: Option<String>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<TrackedMessage> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    let opened_at = opened_at.unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+    Ok(receipt_operations::record_pixel_open(&conn, &tracking_token, &opened_at)
+        .context("Failed to record pixel open")?
+        .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("sent message with tracking token {}", tracking_token) })?)
+}
@@ -0,0 +1,88 @@
+//! Gmail Search Query Commands
+//!
+//! Build and check Gmail queries from structured filters, and keep saved
+//! searches and smart folders as filters the queries are rebuilt from.
+
+use anyhow::Context;
+use std::sync::Arc;
+use tauri::State;
+
+use crate::database::{
+    operations::saved_search_operations::{self, SavedSearch, KIND_SEARCH, KIND_SMART_FOLDER},
+    DatabaseManager,
+};
+use crate::services::gmail::search_query::{self, BuiltSearchQuery, QueryIssue, SearchFilters};
+use crate::errors::{CommandResult, LibreOllamaError};
+
+/// Build a Gmail query from structured filters
+#[tauri::command]
+pub async fn build_search_query(filters: SearchFilters) -> CommandResult<BuiltSearchQuery> {
+    Ok(search_query::build_query(&filters)?)
+}
+
+/// Check the operators in a hand-written Gmail query
+#[tauri::command]
+pub async fn validate_search_query(query: String) -> CommandResult<Vec<QueryIssue>> {
+    Ok(search_query::validate_query(&query))
+}
+
+/// List an account's saved searches, or only those of one kind
+/// (`search` or `smart_folder`)
+#[tauri::command]
+pub async fn list_saved_gmail_searches(
+    account_id: String,
+    kind: Option<String>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<SavedSearch>> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(saved_search_operations::list_saved_searches(&conn, &account_id, kind.as_deref())
+        .context("Failed to list saved searches")?)
+}
+
+/// Save a search or smart folder, building its query from the filters.
+/// Pass `id` to update an existing one.
+#[tauri::command]
+pub async fn save_gmail_search(
+    account_id: String,
+    id: Option<String>,
+    name: String,
+    kind: Option<String>,
+    filters: SearchFilters,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<SavedSearch> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Saved searches need a name".into());
+    }
+    let kind = kind.as_deref().unwrap_or(KIND_SEARCH);
+    if kind != KIND_SEARCH && kind != KIND_SMART_FOLDER {
+        return Err(LibreOllamaError::InvalidInput {
+            message: format!("Unknown saved search kind: {}", kind),
+            field: Some("kind".to_string()),
+        }
+        .into());
+    }
+
+    let built = search_query::build_query(&filters)?;
+    if built.query.is_empty() {
+        return Err("Saved searches need at least one filter".into());
+    }
+    let filters = serde_json::to_value(&filters)?;
+
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(saved_search_operations::save_search(&conn, id.as_deref(), &account_id, name, kind, &filters, &built.query)
+        .context("Failed to save search")?)
+}
+
+#[tauri::command]
+pub async fn delete_saved_gmail_search(
+    id: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<bool> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(saved_search_operations::delete_saved_search(&conn, &id)
+        .context("Failed to delete saved search")?)
+}
@@ -0,0 +1,30 @@
+//! Send-As Address Commands
+//!
+//! The addresses an account can send from, as set up in Gmail settings.
+//! The compose window offers them, and `ComposeRequest::from_alias` must
+//! name one of them.
+
+use std::sync::Arc;
+use tauri::State;
+
+use crate::database::operations::send_as_operations::SendAsAlias;
+use crate::services::gmail::api_service::GmailApiService;
+use crate::errors::CommandResult;
+
+/// List the account's send-as addresses, fetching them the first time
+#[tauri::command]
+pub async fn list_send_as_aliases(
+    account_id: String,
+    api_service: State<'_, Arc<GmailApiService>>,
+) -> CommandResult<Vec<SendAsAlias>> {
+    Ok(api_service.send_as_aliases(&account_id).await?)
+}
+
+/// Refetch the account's send-as addresses from Gmail settings
+#[tauri::command]
+pub async fn refresh_send_as_aliases(
+    account_id: String,
+    api_service: State<'_, Arc<GmailApiService>>,
+) -> CommandResult<Vec<SendAsAlias>> {
+    Ok(api_service.refresh_send_as(&account_id).await?)
+}
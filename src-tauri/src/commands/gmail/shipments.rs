@@ -0,0 +1,39 @@
+//! Shipment Tracking Commands
+//!
+//! Packages detected in shipping emails, for the "track package" chips and
+//! the deliveries list.
+
+use anyhow::Context;
+use std::sync::Arc;
+use tauri::State;
+
+use crate::database::{
+    operations::shipment_operations::{self, Shipment},
+    DatabaseManager,
+};
+use crate::errors::CommandResult;
+
+/// Shipments that are neither delivered nor dismissed, newest first
+#[tauri::command]
+pub async fn get_active_shipments(
+    account_id: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<Shipment>> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(shipment_operations::list_active(&conn, &account_id)
+        .context("Failed to load shipments")?)
+}
+
+/// Hide a shipment and stop checking its status
+#[tauri::command]
+pub async fn dismiss_shipment(
+    account_id: String,
+    shipment_id: i64,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<bool> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(shipment_operations::dismiss(&conn, &account_id, shipment_id)
+        .context("Failed to dismiss shipment")?)
+}
@@ -1,9 +1,62 @@
 //! Gmail Sync Commands
 //!
-//! This module provides Tauri command handlers for Gmail synchronization,
-//! including full sync, incremental sync, and push notifications.
-//!
-//! Note: These commands are currently unused and kept for future development.
+//! This module provides Tauri command handlers for Gmail synchronization
+//! settings, starting with the per-label sync policies that decide what is
+//! synced, how far back, and whether bodies or only headers are kept.
+
+use tauri::State;
+
+use crate::database::operations::sync_policy_operations::LabelSyncPolicy;
+use crate::services::gmail::sync_service::LabelSyncPlan;
+use crate::services::gmail::GmailSyncService;
+use crate::errors::CommandResult;
+
+/// Get the label sync policies for an account
+#[tauri::command]
+pub async fn get_label_sync_policies(
+    account_id: String,
+    sync_service: State<'_, GmailSyncService>,
+) -> CommandResult<Vec<LabelSyncPolicy>> {
+    Ok(sync_service
+        .get_label_policies(&account_id)
+        .await?)
+}
+
+/// Replace the label sync policies for an account. Include a `*` policy to
+/// control mail under labels that have no policy of their own.
+#[tauri::command]
+pub async fn update_label_sync_policies(
+    account_id: String,
+    policies: Vec<LabelSyncPolicy>,
+    sync_service: State<'_, GmailSyncService>,
+) -> CommandResult<Vec<LabelSyncPolicy>> {
+    if policies.iter().any(|p| p.label_id.trim().is_empty()) {
+        return Err("Label sync policies need a label ID".into());
+    }
+
+    Ok(sync_service
+        .set_label_policies(&account_id, &policies)
+        .await?)
+}
+
+/// Revert an account to the default label sync policies
+#[tauri::command]
+pub async fn reset_label_sync_policies(
+    account_id: String,
+    sync_service: State<'_, GmailSyncService>,
+) -> CommandResult<Vec<LabelSyncPolicy>> {
+    Ok(sync_service
+        .reset_label_policies(&account_id)
+        .await?)
+}
 
-// This file contains placeholder functions for future sync functionality.
-// All functions are currently unused and will be implemented when needed. 
\ No newline at end of file
+/// Preview the per-label fetches the next full sync will make
+#[tauri::command]
+pub async fn preview_label_sync_plan(
+    account_id: String,
+    sync_service: State<'_, GmailSyncService>,
+) -> CommandResult<Vec<LabelSyncPlan>> {
+    Ok(sync_service
+        .plan_label_sync(&account_id)
+        .await?)
+}
@@ -0,0 +1,97 @@
+//! Message Translation Commands
+//!
+//! Translations run on the translation model and are cached per message
+//! and target language.
+
+use anyhow::Context;
+use std::sync::Arc;
+use tauri::State;
+
+use crate::database::{
+    operations::translation_operations::{self, MessageTranslation, NewTranslation},
+    DatabaseManager,
+};
+use crate::services::gmail::api_service::{GmailApiService, SegmentKind};
+use crate::services::gmail::translation_service;
+use crate::services::text_processing;
+use crate::errors::CommandResult;
+
+/// Translate a message's new content (quotes and signature left out) into
+/// `target_lang`. `model` overrides the configured translation model.
+#[tauri::command]
+pub async fn translate_message(
+    account_id: String,
+    message_id: String,
+    target_lang: String,
+    model: Option<String>,
+    api_service: State<'_, Arc<GmailApiService>>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<MessageTranslation> {
+    let target_lang = target_lang.trim().to_string();
+    if target_lang.is_empty() {
+        return Err("Target language cannot be empty".into());
+    }
+
+    {
+        let conn = db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        if let Some(cached) = translation_operations::get_translation(&conn, &account_id, &message_id, &target_lang)?
+        {
+            return Ok(cached);
+        }
+    }
+
+    let message = api_service
+        .get_parsed_message(&account_id, &message_id)
+        .await?;
+    let text = api_service
+        .reply_segments(&message)
+        .into_iter()
+        .filter(|segment| segment.kind == SegmentKind::Content)
+        .map(|segment| segment.text)
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    if text.trim().is_empty() {
+        return Err("Message has no text to translate".into());
+    }
+
+    let source = text_processing::detect_language(&text);
+    let (translated_text, model) = match &source {
+        // Already in the target language, so no model ran
+        Some(source) if translation_service::is_same_language(source, &target_lang) => (text.clone(), String::new()),
+        _ => translation_service::translate_text(&db_manager, model.as_deref(), &text, &target_lang)
+            .await
+            .context("Translation failed")?,
+    };
+
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(translation_operations::save_translation(&conn, &NewTranslation {
+        account_id: &account_id,
+        message_id: &message_id,
+        target_lang: &target_lang,
+        source_lang: source.as_ref().map(|s| s.code.as_str()),
+        source_lang_name: source.as_ref().map(|s| s.name.as_str()),
+        translated_text: &translated_text,
+        model: &model,
+    })?)
+}
+
+#[tauri::command]
+pub async fn get_translation_model(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<String> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(translation_service::translation_model(&conn)?)
+}
+
+#[tauri::command]
+pub async fn set_translation_model(
+    model: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<()> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(translation_service::set_translation_model(&conn, &model)?)
+}
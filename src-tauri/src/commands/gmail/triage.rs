@@ -0,0 +1,130 @@
+//! Mail Triage Commands
+//!
+//! Keyboard-first triage: start a session over a label, act on one message
+//! at a time, undo the latest action, and end the session with its stats.
+//! See `services::gmail::triage`.
+
+use std::sync::Arc;
+use tauri::State;
+
+use crate::commands::tasks::sync_fixed::{create_google_task, delete_google_task, CreateTaskRequest, DeleteTaskRequest};
+use crate::database::DatabaseManager;
+use crate::services::events::EventBus;
+use crate::services::gmail::api_service::GmailApiService;
+use crate::services::gmail::triage::{self, TriageAction, TriageMessage, TriageOutcome, TriageSession, TriageSessionSummary, TriageStats};
+use crate::services::google::tasks_service::GoogleTasksService;
+use crate::services::i18n;
+use crate::errors::{CommandResult, LibreOllamaError};
+
+const DEFAULT_TASK_LIST: &str = "@default";
+const DEFAULT_HISTORY: u32 = 20;
+
+fn task_request(account_id: String, message: &TriageMessage, task_list_id: Option<String>, title: Option<String>, due: Option<String>) -> CreateTaskRequest {
+    let subject = message
+        .subject
+        .clone()
+        .filter(|subject| !subject.trim().is_empty())
+        .unwrap_or_else(|| i18n::t("triage-no-subject"));
+    let sender = message.from.name.clone().unwrap_or_else(|| message.from.email.clone());
+    let mut notes = i18n::t_args("triage-task-notes", &[("sender", sender.into()), ("subject", subject.clone().into())]);
+    if let Some(snippet) = message.snippet.as_deref().filter(|snippet| !snippet.is_empty()) {
+        notes.push_str("\n\n");
+        notes.push_str(snippet);
+    }
+    CreateTaskRequest {
+        account_id,
+        task_list_id: task_list_id.unwrap_or_else(|| DEFAULT_TASK_LIST.to_string()),
+        title: title.filter(|title| !title.trim().is_empty()).unwrap_or(subject),
+        notes: Some(notes),
+        due,
+        priority: None,
+        labels: None,
+        time_block: None,
+    }
+}
+
+/// Start a triage session over the messages under a label (the inbox by
+/// default), oldest first
+#[tauri::command]
+pub async fn start_triage_session(
+    account_id: String,
+    label: Option<String>,
+    limit: Option<u32>,
+    api_service: State<'_, Arc<GmailApiService>>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<TriageSession> {
+    let label = label.unwrap_or_else(|| "INBOX".to_string());
+    Ok(triage::start_session(&api_service, &db_manager, &account_id, &label, limit).await?)
+}
+
+/// Archive, label, make a task from, snooze or skip one message of a session
+#[tauri::command]
+pub async fn process_triage_action(
+    session_id: i64,
+    message_id: String,
+    action: TriageAction,
+    api_service: State<'_, Arc<GmailApiService>>,
+    google_tasks_service: State<'_, GoogleTasksService>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<TriageOutcome> {
+    let TriageAction::Task { task_list_id, title, due, .. } = &action else {
+        return Ok(triage::process_action(&api_service, &db_manager, &event_bus, session_id, &message_id, &action, None).await?);
+    };
+
+    let (account_id, message) = triage::queued_message(&db_manager, session_id, &message_id)?;
+    let request = task_request(account_id.clone(), &message, task_list_id.clone(), title.clone(), due.clone());
+    let task_list_id = request.task_list_id.clone();
+    let task = create_google_task(request, google_tasks_service.clone(), db_manager.clone(), event_bus.clone()).await?;
+
+    match triage::process_action(&api_service, &db_manager, &event_bus, session_id, &message_id, &action, Some((&task_list_id, &task.id))).await {
+        Ok(outcome) => Ok(outcome),
+        Err(e) => {
+            // The message stays where it was, so the task goes too
+            let request = DeleteTaskRequest { account_id, task_list_id, task_id: task.id, base_updated: None };
+            if let Err(delete_error) = delete_google_task(request, google_tasks_service, db_manager, event_bus).await {
+                eprintln!("⚠️ [TRIAGE] Failed to remove task made from {}: {}", message_id, delete_error);
+            }
+            Err(e.into())
+        }
+    }
+}
+
+/// Undo the latest action of a session, removing the task it made if any.
+/// Returns nothing when there is nothing left to undo.
+#[tauri::command]
+pub async fn undo_triage_action(
+    session_id: i64,
+    api_service: State<'_, Arc<GmailApiService>>,
+    google_tasks_service: State<'_, GoogleTasksService>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<Option<TriageOutcome>> {
+    let (tasks_db, tasks_events) = (db_manager.clone(), event_bus.clone());
+    let remove_task = |account_id: String, task_list_id: String, task_id: String| async move {
+        let request = DeleteTaskRequest { account_id, task_list_id, task_id, base_updated: None };
+        delete_google_task(request, google_tasks_service, tasks_db, tasks_events)
+            .await
+            .map_err(|e| LibreOllamaError::GoogleTasksApi { message: e.user_message })
+    };
+    Ok(triage::undo_last(&api_service, &db_manager, &event_bus, session_id, remove_task).await?)
+}
+
+/// End a session and record its stats
+#[tauri::command]
+pub async fn end_triage_session(
+    session_id: i64,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<TriageStats> {
+    Ok(triage::end_session(&db_manager, session_id)?)
+}
+
+/// Finished triage sessions with their stats, newest first
+#[tauri::command]
+pub async fn list_triage_sessions(
+    account_id: String,
+    limit: Option<u32>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<TriageSessionSummary>> {
+    Ok(triage::session_history(&db_manager, &account_id, limit.unwrap_or(DEFAULT_HISTORY))?)
+}
@@ -1,5 +1,13 @@
-use reqwest;
+use anyhow::Context;
 use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::services::gmail::auth_service::DRIVE_READONLY_SCOPE;
+use crate::services::google::drive_service::{DriveAttachment, DriveFilePage, DriveService};
+use crate::utils::http_client::http_client;
+use crate::commands::rate_limiter::RequestPriority;
+use crate::services::request_scheduler;
+use crate::errors::{CommandResult, LibreOllamaError};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct StorageQuota {
@@ -24,35 +32,36 @@ pub struct QuotaInfo {
 }
 
 #[tauri::command]
-pub async fn get_google_drive_quota(access_token: String) -> Result<QuotaInfo, String> {
+pub async fn get_google_drive_quota(access_token: String) -> CommandResult<QuotaInfo> {
     println!("[DEBUG] Fetching Google Drive quota with token");
     println!("[DEBUG] Token length: {}", access_token.len());
-    println!("[DEBUG] Token starts with: {}", if access_token.len() > 10 { &access_token[0..10] } else { &access_token });
     
     if access_token.is_empty() {
         eprintln!("[ERROR] Empty access token provided");
-        return Err("Empty access token".to_string());
+        return Err("Empty access token".into());
     }
     
-    let client = reqwest::Client::new();
+    let client = http_client();
     
-    let response = client
+    let request = client
         .get("https://www.googleapis.com/drive/v3/about")
         .query(&[("fields", "storageQuota(limit,usage,usageInDrive,usageInDriveTrash)")])
         .header("Authorization", format!("Bearer {}", access_token))
-        .header("Content-Type", "application/json")
-        .send()
+        .header("Content-Type", "application/json");
+    let response = request_scheduler::send(None, RequestPriority::High, request)
         .await
         .map_err(|e| {
             eprintln!("[ERROR] Failed to send request to Google Drive API: {}", e);
-            format!("Network error: {}", e)
-        })?;
+            e
+        })
+        .context("Failed to reach Google Drive")?;
 
     if response.status().is_success() {
         let data: DriveAboutResponse = response.json().await.map_err(|e| {
             eprintln!("[ERROR] Failed to parse Google Drive API response: {}", e);
-            format!("Failed to parse response: {}", e)
-        })?;
+            e
+        })
+        .context("Failed to parse Google Drive quota")?;
         
         if let Some(quota) = data.storage_quota {
             println!("[DEBUG] Received quota data: {:?}", quota);
@@ -76,7 +85,7 @@ pub async fn get_google_drive_quota(access_token: String) -> Result<QuotaInfo, S
             Ok(QuotaInfo { used, total })
         } else {
             eprintln!("[ERROR] No storage quota data in response");
-            Err("No storage quota data available".to_string())
+            Err("No storage quota data available".into())
         }
     } else {
         let status = response.status();
@@ -84,11 +93,68 @@ pub async fn get_google_drive_quota(access_token: String) -> Result<QuotaInfo, S
         eprintln!("[ERROR] Google Drive API request failed with status {}: {}", status, error_text);
         
         if status.as_u16() == 401 {
-            Err("Authentication failed - token may be expired".to_string())
+            Err("Authentication failed - token may be expired".into())
         } else if status.as_u16() == 403 {
-            Err("Access forbidden - check API permissions".to_string())
+            Err(LibreOllamaError::PermissionDenied { message: "Access forbidden - check API permissions".to_string() }.into())
         } else {
-            Err(format!("API request failed with status {}: {}", status, error_text))
+            Err(LibreOllamaError::GoogleDriveApi {
+                message: format!("API request failed with status {}: {}", status, error_text),
+                status_code: Some(status.as_u16()),
+            }
+            .into())
         }
     }
-}
\ No newline at end of file
+}
+
+/// List files in a Drive folder (the root folder by default)
+#[tauri::command]
+pub async fn list_drive_files(
+    account_id: String,
+    folder_id: Option<String>,
+    page_size: Option<u32>,
+    page_token: Option<String>,
+    drive_service: State<'_, DriveService>,
+) -> CommandResult<DriveFilePage> {
+    Ok(drive_service
+        .list_files(&account_id, folder_id.as_deref(), page_size, page_token)
+        .await
+        .context("Failed to list Drive files")?)
+}
+
+/// Search Drive files by name and content
+#[tauri::command]
+pub async fn search_drive_files(
+    account_id: String,
+    query: String,
+    page_size: Option<u32>,
+    page_token: Option<String>,
+    drive_service: State<'_, DriveService>,
+) -> CommandResult<DriveFilePage> {
+    Ok(drive_service
+        .search_files(&account_id, &query, page_size, page_token)
+        .await
+        .context("Failed to search Drive files")?)
+}
+
+/// Prepare a Drive file for the composer, as a link or (with `download`) as an attachment.
+///
+/// Downloading needs the drive.readonly scope; when it is missing the error
+/// mentions the scope so the frontend can re-run OAuth with `extra_scopes`.
+#[tauri::command]
+pub async fn attach_drive_file(
+    account_id: String,
+    file_id: String,
+    download: Option<bool>,
+    drive_service: State<'_, DriveService>,
+) -> CommandResult<DriveAttachment> {
+    Ok(drive_service
+        .attachment_from_drive(&account_id, &file_id, download.unwrap_or(false))
+        .await
+        .map_err(|e| match e {
+            LibreOllamaError::GoogleDriveApi { status_code: Some(403), .. } => LibreOllamaError::PermissionDenied {
+                message: format!("Downloading Drive files requires additional access: {}", DRIVE_READONLY_SCOPE),
+            },
+            e => e,
+        })
+        .context("Failed to attach Drive file")?)
+}
@@ -0,0 +1,88 @@
+//! Image Proxy Commands
+//!
+//! The frontend loads remote images in email bodies through these commands
+//! rather than letting the webview fetch them, and keeps per-sender
+//! "always load images" choices.
+
+use anyhow::Context;
+use std::sync::Arc;
+use tauri::State;
+
+use crate::database::{
+    operations::image_preference_operations::{self, ImageLoadPreference},
+    DatabaseManager,
+};
+use crate::services::image_proxy::{ImageProxy, ProxiedImage};
+use crate::errors::CommandResult;
+
+/// Fetch a remote image through the proxy and return it as a data URL
+#[tauri::command]
+pub async fn proxy_remote_image(url: String) -> CommandResult<ProxiedImage> {
+    let proxy = ImageProxy::from_config()?;
+    Ok(proxy
+        .fetch(&url)
+        .await
+        .context("Failed to load image")?)
+}
+
+#[tauri::command]
+pub async fn clear_image_cache() -> CommandResult<usize> {
+    let proxy = ImageProxy::from_config()?;
+    Ok(proxy
+        .clear_cache()
+        .await
+        .context("Failed to clear image cache")?)
+}
+
+/// Whether the sender's remote images should load without asking
+#[tauri::command]
+pub async fn should_load_remote_images(
+    account_id: String,
+    sender: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<bool> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(image_preference_operations::should_load_images(&conn, &account_id, &sender)
+        .context("Failed to read image preference")?)
+}
+
+/// Set the preference for an address, or for a whole domain when `sender`
+/// has no "@"
+#[tauri::command]
+pub async fn set_sender_image_preference(
+    account_id: String,
+    sender: String,
+    always_load: bool,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<ImageLoadPreference>> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    image_preference_operations::set_preference(&conn, &account_id, &sender, always_load)
+        .context("Failed to save image preference")?;
+    Ok(image_preference_operations::list_preferences(&conn, &account_id)
+        .context("Failed to list image preferences")?)
+}
+
+#[tauri::command]
+pub async fn remove_sender_image_preference(
+    account_id: String,
+    sender: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<bool> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(image_preference_operations::remove_preference(&conn, &account_id, &sender)
+        .context("Failed to remove image preference")?)
+}
+
+#[tauri::command]
+pub async fn list_sender_image_preferences(
+    account_id: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<ImageLoadPreference>> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(image_preference_operations::list_preferences(&conn, &account_id)
+        .context("Failed to list image preferences")?)
+}
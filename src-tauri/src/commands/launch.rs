@@ -0,0 +1,26 @@
+//! Launch Request Commands
+//!
+//! What a command line asked the app to do (a `mailto:` link, a
+//! `--capture`), waiting for the frontend to run. See
+//! `services::launch_args`.
+
+use tauri::State;
+
+use crate::services::launch_args::{self, LaunchQueue, LaunchRequest, MailtoFields};
+use crate::errors::{CommandResult, LibreOllamaError};
+
+/// Requests from this launch and any later ones, oldest first; each is
+/// returned once
+#[tauri::command]
+pub async fn take_launch_requests(queue: State<'_, LaunchQueue>) -> CommandResult<Vec<LaunchRequest>> {
+    Ok(queue.take())
+}
+
+/// Recipients, subject and body of a `mailto:` link, for the composer
+#[tauri::command]
+pub async fn parse_mailto_link(uri: String) -> CommandResult<MailtoFields> {
+    Ok(launch_args::parse_mailto(&uri).ok_or_else(|| LibreOllamaError::InvalidInput {
+        message: format!("Not a mailto: link: {}", uri),
+        field: Some("uri".to_string()),
+    })?)
+}
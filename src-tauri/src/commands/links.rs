@@ -1,8 +1,30 @@
-//! Links Management Commands
+//! Links Commands
 //!
-//! This module provides commands for managing links and content indexing.
-//! Currently unused but kept for future development.
+//! Preview cards for URLs in notes, tasks and chats.
 
+use anyhow::Context;
+use std::sync::Arc;
+use tauri::State;
 
-// Note: All command functions have been removed as they are unused.
-// This module is kept for potential future link management and content indexing features.
\ No newline at end of file
+use crate::database::operations::link_preview_operations;
+use crate::database::DatabaseManager;
+use crate::services::link_preview::{self, LinkPreview};
+use crate::errors::CommandResult;
+
+/// Title, description and image of a page for rendering a link card
+#[tauri::command]
+pub async fn unfurl_link(
+    url: String,
+    refresh: Option<bool>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<LinkPreview> {
+    Ok(link_preview::unfurl(&db_manager, &url, refresh.unwrap_or(false))
+        .await
+        .context("Failed to preview link")?)
+}
+
+#[tauri::command]
+pub async fn clear_link_previews(db_manager: State<'_, Arc<DatabaseManager>>) -> CommandResult<usize> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(link_preview_operations::clear_previews(&conn).context("Failed to clear link previews")?)
+}
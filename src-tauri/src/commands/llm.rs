@@ -1,14 +1,27 @@
+use anyhow::Context;
 use async_openai::{
+    error::OpenAIError,
     types::{CreateChatCompletionRequestArgs, ChatCompletionRequestMessage},
     Client as OpenAIClient,
 };
 use serde_json::{Value, json};
-use reqwest;
 use serde::{Serialize, Deserialize};
-use tauri::{AppHandle, Manager};
+use tauri::{AppHandle, Manager, State};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::Arc;
+use crate::database::operations::performance_operations::{self, LlmUsageSummary};
+use crate::database::DatabaseManager;
+use crate::services::events::{BackendEvent, EventBus};
+use crate::services::hardware::{self, HardwareProfile};
+use crate::services::llm_provider::{
+    self, FeatureModelInfo, LlmFeature, LlmProvider, ProviderInfo, ProviderInput, ProviderTestResult,
+};
+use crate::services::model_router::{self, RegisteredModel, ResolvedModel, RoutingRule};
+use crate::utils::http_client::streaming_http_client;
+use crate::utils::tokens;
+use crate::errors::{CommandError, CommandResult, LibreOllamaError};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct LlmProviderConfig {
@@ -22,33 +35,33 @@ struct LlmSettings {
     enabled_models: HashMap<String, Vec<String>>,
 }
 
-fn get_settings_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+fn get_settings_path(app_handle: &AppHandle) -> CommandResult<PathBuf> {
     let path = app_handle.path()
         .app_data_dir()
-        .map_err(|e| e.to_string())?
+        .context("Failed to resolve the app data directory")?
         .join("llm_settings.json");
     Ok(path)
 }
 
-fn read_settings(path: &PathBuf) -> Result<LlmSettings, String> {
+fn read_settings(path: &PathBuf) -> CommandResult<LlmSettings> {
     if !path.exists() {
         return Ok(LlmSettings::default());
     }
-    let data = fs::read_to_string(path).map_err(|e| e.to_string())?;
-    serde_json::from_str(&data).map_err(|e| e.to_string())
+    let data = fs::read_to_string(path)?;
+    serde_json::from_str(&data).map_err(CommandError::from)
 }
 
-fn write_settings(path: &PathBuf, settings: &LlmSettings) -> Result<(), String> {
-    let data = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
-    fs::create_dir_all(path.parent().unwrap()).map_err(|e| e.to_string())?;
-    fs::write(path, data).map_err(|e| e.to_string())
+fn write_settings(path: &PathBuf, settings: &LlmSettings) -> CommandResult<()> {
+    let data = serde_json::to_string_pretty(settings)?;
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(path, data).map_err(CommandError::from)
 }
 
 #[tauri::command]
 pub async fn save_llm_provider_settings(
     app_handle: AppHandle,
     settings: HashMap<String, LlmProviderConfig>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     let path = get_settings_path(&app_handle)?;
     let mut current_settings = read_settings(&path).unwrap_or_default();
     current_settings.providers = settings;
@@ -58,7 +71,7 @@ pub async fn save_llm_provider_settings(
 #[tauri::command]
 pub async fn get_llm_provider_settings(
     app_handle: AppHandle,
-) -> Result<HashMap<String, LlmProviderConfig>, String> {
+) -> CommandResult<HashMap<String, LlmProviderConfig>> {
     let path = get_settings_path(&app_handle)?;
     let settings = read_settings(&path)?;
     Ok(settings.providers)
@@ -69,7 +82,7 @@ pub async fn set_enabled_models(
     app_handle: AppHandle,
     provider: String,
     model_ids: Vec<String>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     let path = get_settings_path(&app_handle)?;
     let mut settings = read_settings(&path)?;
     settings.enabled_models.insert(provider, model_ids);
@@ -80,12 +93,29 @@ pub async fn set_enabled_models(
 pub async fn get_enabled_models(
     app_handle: AppHandle,
     provider: String,
-) -> Result<Vec<String>, String> {
+) -> CommandResult<Vec<String>> {
     let path = get_settings_path(&app_handle)?;
     let settings = read_settings(&path)?;
     Ok(settings.enabled_models.get(&provider).cloned().unwrap_or_default())
 }
 
+/// The error for a provider's failed response: a rejected key is a
+/// permission error, throttling and server errors can be retried, and
+/// anything else is a request the provider won't take
+async fn provider_error(provider: &str, response: reqwest::Response) -> CommandError {
+    let status = response.status().as_u16();
+    let url = response.url().to_string();
+    let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+    let message = format!("{} API error: {}", provider, error_text);
+    match status {
+        401 | 403 => LibreOllamaError::PermissionDenied { message },
+        429 => LibreOllamaError::RateLimit { message, retry_after: None },
+        500..=599 => LibreOllamaError::Network { message, url: Some(url) },
+        _ => LibreOllamaError::InvalidInput { message, field: None },
+    }
+    .into()
+}
+
 // ========== CHAT COMMANDS ==========
 
 #[tauri::command]
@@ -94,7 +124,7 @@ pub async fn llm_chat_openai(
     model: String,
     api_key: String,
     base_url: Option<String>,
-) -> Result<String, String> {
+) -> CommandResult<String> {
     let client = if let Some(url) = base_url {
         OpenAIClient::with_config(
             async_openai::config::OpenAIConfig::new()
@@ -108,24 +138,42 @@ pub async fn llm_chat_openai(
         )
     };
 
-    let request_messages: Vec<ChatCompletionRequestMessage> = messages
+    let request_messages = messages
         .into_iter()
-        .map(|msg| serde_json::from_value(msg).unwrap())
-        .collect();
+        .map(serde_json::from_value)
+        .collect::<Result<Vec<ChatCompletionRequestMessage>, _>>()
+        .map_err(|e| LibreOllamaError::InvalidInput {
+            message: format!("Invalid chat message: {}", e),
+            field: Some("messages".to_string()),
+        })?;
 
     let request = CreateChatCompletionRequestArgs::default()
         .model(model)
         .messages(request_messages)
         .build()
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| LibreOllamaError::InvalidInput {
+            message: format!("Invalid chat request: {}", e),
+            field: None,
+        })?;
 
     let response = client
         .chat()
         .create(request)
         .await
-        .map_err(|e| e.to_string())?;
-
-    Ok(response.choices[0].message.content.clone().unwrap_or_default())
+        .map_err(|e| match e {
+            OpenAIError::Reqwest(e) => LibreOllamaError::from(e),
+            OpenAIError::ApiError(e) => LibreOllamaError::InvalidInput {
+                message: format!("OpenAI API error: {}", e.message),
+                field: None,
+            },
+            e => LibreOllamaError::Internal { message: format!("OpenAI request failed: {}", e) },
+        })?;
+
+    Ok(response
+        .choices
+        .first()
+        .and_then(|choice| choice.message.content.clone())
+        .unwrap_or_default())
 }
 
 #[tauri::command]
@@ -134,8 +182,8 @@ pub async fn llm_chat_anthropic(
     model: String,
     api_key: String,
     base_url: Option<String>,
-) -> Result<String, String> {
-    let client = reqwest::Client::new();
+) -> CommandResult<String> {
+    let client = streaming_http_client();
     let url = base_url.unwrap_or_else(|| "https://api.anthropic.com".to_string());
     let endpoint = format!("{}/v1/messages", url);
 
@@ -152,15 +200,13 @@ pub async fn llm_chat_anthropic(
         .header("anthropic-version", "2023-06-01")
         .json(&request_body)
         .send()
-        .await
-        .map_err(|e| e.to_string())?;
+        .await?;
 
     if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("Anthropic API error: {}", error_text));
+        return Err(provider_error("Anthropic", response).await);
     }
 
-    let response_json: Value = response.json().await.map_err(|e| e.to_string())?;
+    let response_json: Value = response.json().await?;
     
     let content = response_json
         .get("content")
@@ -179,8 +225,8 @@ pub async fn llm_chat_openrouter(
     model: String,
     api_key: String,
     base_url: Option<String>,
-) -> Result<String, String> {
-    let client = reqwest::Client::new();
+) -> CommandResult<String> {
+    let client = streaming_http_client();
     let url = base_url.unwrap_or_else(|| "https://openrouter.ai".to_string());
     let endpoint = format!("{}/api/v1/chat/completions", url);
 
@@ -195,15 +241,13 @@ pub async fn llm_chat_openrouter(
         .header("Content-Type", "application/json")
         .json(&request_body)
         .send()
-        .await
-        .map_err(|e| e.to_string())?;
+        .await?;
 
     if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("OpenRouter API error: {}", error_text));
+        return Err(provider_error("OpenRouter", response).await);
     }
 
-    let response_json: Value = response.json().await.map_err(|e| e.to_string())?;
+    let response_json: Value = response.json().await?;
     
     let content = response_json
         .get("choices")
@@ -223,8 +267,8 @@ pub async fn llm_chat_deepseek(
     model: String,
     api_key: String,
     base_url: Option<String>,
-) -> Result<String, String> {
-    let client = reqwest::Client::new();
+) -> CommandResult<String> {
+    let client = streaming_http_client();
     let url = base_url.unwrap_or_else(|| "https://api.deepseek.com".to_string());
     let endpoint = format!("{}/v1/chat/completions", url);
 
@@ -240,15 +284,13 @@ pub async fn llm_chat_deepseek(
         .header("Content-Type", "application/json")
         .json(&request_body)
         .send()
-        .await
-        .map_err(|e| e.to_string())?;
+        .await?;
 
     if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("DeepSeek API error: {}", error_text));
+        return Err(provider_error("DeepSeek", response).await);
     }
 
-    let response_json: Value = response.json().await.map_err(|e| e.to_string())?;
+    let response_json: Value = response.json().await?;
     
     let content = response_json
         .get("choices")
@@ -268,8 +310,8 @@ pub async fn llm_chat_gemini(
     model: String,
     api_key: String,
     base_url: Option<String>,
-) -> Result<String, String> {
-    let client = reqwest::Client::new();
+) -> CommandResult<String> {
+    let client = streaming_http_client();
     let url = base_url.unwrap_or_else(|| "https://generativelanguage.googleapis.com".to_string());
     let endpoint = format!("{}/v1beta/models/{}:generateContent?key={}", url, model, api_key);
 
@@ -290,15 +332,13 @@ pub async fn llm_chat_gemini(
         .header("Content-Type", "application/json")
         .json(&request_body)
         .send()
-        .await
-        .map_err(|e| e.to_string())?;
+        .await?;
 
     if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("Gemini API error: {}", error_text));
+        return Err(provider_error("Gemini", response).await);
     }
 
-    let response_json: Value = response.json().await.map_err(|e| e.to_string())?;
+    let response_json: Value = response.json().await?;
     
     let content = response_json
         .get("candidates")
@@ -320,8 +360,8 @@ pub async fn llm_chat_mistral(
     model: String,
     api_key: String,
     base_url: Option<String>,
-) -> Result<String, String> {
-    let client = reqwest::Client::new();
+) -> CommandResult<String> {
+    let client = streaming_http_client();
     let url = base_url.unwrap_or_else(|| "https://api.mistral.ai".to_string());
     let endpoint = if url.ends_with("/v1") {
         format!("{}/chat/completions", url)
@@ -342,15 +382,13 @@ pub async fn llm_chat_mistral(
         .header("Content-Type", "application/json")
         .json(&request_body)
         .send()
-        .await
-        .map_err(|e| e.to_string())?;
+        .await?;
 
     if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("Mistral API error: {}", error_text));
+        return Err(provider_error("Mistral", response).await);
     }
 
-    let response_json: Value = response.json().await.map_err(|e| e.to_string())?;
+    let response_json: Value = response.json().await?;
     
     let content = response_json
         .get("choices")
@@ -370,8 +408,8 @@ pub async fn llm_chat_mistral(
 pub async fn llm_list_openai_models(
     api_key: String,
     base_url: Option<String>,
-) -> Result<Vec<Value>, String> {
-    let client = reqwest::Client::new();
+) -> CommandResult<Vec<Value>> {
+    let client = streaming_http_client();
     let url = base_url.unwrap_or_else(|| "https://api.openai.com".to_string());
     let endpoint = format!("{}/v1/models", url);
 
@@ -380,21 +418,18 @@ pub async fn llm_list_openai_models(
         .header("Authorization", format!("Bearer {}", api_key))
         .header("Content-Type", "application/json")
         .send()
-        .await
-        .map_err(|e| e.to_string())?;
+        .await?;
 
     if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("OpenAI API error: {}", error_text));
+        return Err(provider_error("OpenAI", response).await);
     }
 
-    let response_json: Value = response.json().await.map_err(|e| e.to_string())?;
+    let response_json: Value = response.json().await?;
     
     let models = response_json
         .get("data")
-        .and_then(|data| data.as_array())
-        .map(|arr| arr.clone())
-        .unwrap_or_else(Vec::new);
+        .and_then(|data| data.as_array()).cloned()
+        .unwrap_or_default();
 
     Ok(models)
 }
@@ -403,7 +438,7 @@ pub async fn llm_list_openai_models(
 pub async fn llm_list_anthropic_models(
     _api_key: String,
     _base_url: Option<String>,
-) -> Result<Vec<Value>, String> {
+) -> CommandResult<Vec<Value>> {
     // Anthropic doesn't have a public models API endpoint, return hardcoded list
     let models = vec![
         json!({
@@ -430,8 +465,8 @@ pub async fn llm_list_anthropic_models(
 pub async fn llm_list_openrouter_models(
     api_key: String,
     base_url: Option<String>,
-) -> Result<Vec<Value>, String> {
-    let client = reqwest::Client::new();
+) -> CommandResult<Vec<Value>> {
+    let client = streaming_http_client();
     let url = base_url.unwrap_or_else(|| "https://openrouter.ai".to_string());
     let endpoint = format!("{}/api/v1/models", url);
 
@@ -440,21 +475,18 @@ pub async fn llm_list_openrouter_models(
         .header("Authorization", format!("Bearer {}", api_key))
         .header("Content-Type", "application/json")
         .send()
-        .await
-        .map_err(|e| e.to_string())?;
+        .await?;
 
     if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("OpenRouter API error: {}", error_text));
+        return Err(provider_error("OpenRouter", response).await);
     }
 
-    let response_json: Value = response.json().await.map_err(|e| e.to_string())?;
+    let response_json: Value = response.json().await?;
     
     let models = response_json
         .get("data")
-        .and_then(|data| data.as_array())
-        .map(|arr| arr.clone())
-        .unwrap_or_else(Vec::new);
+        .and_then(|data| data.as_array()).cloned()
+        .unwrap_or_default();
 
     Ok(models)
 }
@@ -463,8 +495,8 @@ pub async fn llm_list_openrouter_models(
 pub async fn llm_list_deepseek_models(
     api_key: String,
     base_url: Option<String>,
-) -> Result<Vec<Value>, String> {
-    let client = reqwest::Client::new();
+) -> CommandResult<Vec<Value>> {
+    let client = streaming_http_client();
     let url = base_url.unwrap_or_else(|| "https://api.deepseek.com".to_string());
     let endpoint = format!("{}/v1/models", url);
 
@@ -473,21 +505,18 @@ pub async fn llm_list_deepseek_models(
         .header("Authorization", format!("Bearer {}", api_key))
         .header("Content-Type", "application/json")
         .send()
-        .await
-        .map_err(|e| e.to_string())?;
+        .await?;
 
     if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("DeepSeek API error: {}", error_text));
+        return Err(provider_error("DeepSeek", response).await);
     }
 
-    let response_json: Value = response.json().await.map_err(|e| e.to_string())?;
+    let response_json: Value = response.json().await?;
     
     let models = response_json
         .get("data")
-        .and_then(|data| data.as_array())
-        .map(|arr| arr.clone())
-        .unwrap_or_else(Vec::new);
+        .and_then(|data| data.as_array()).cloned()
+        .unwrap_or_default();
 
     Ok(models)
 }
@@ -496,8 +525,8 @@ pub async fn llm_list_deepseek_models(
 pub async fn llm_list_gemini_models(
     api_key: String,
     base_url: Option<String>,
-) -> Result<Vec<Value>, String> {
-    let client = reqwest::Client::new();
+) -> CommandResult<Vec<Value>> {
+    let client = streaming_http_client();
     let url = base_url.unwrap_or_else(|| "https://generativelanguage.googleapis.com".to_string());
     let endpoint = format!("{}/v1beta/models?key={}", url, api_key);
 
@@ -505,21 +534,18 @@ pub async fn llm_list_gemini_models(
         .get(&endpoint)
         .header("Content-Type", "application/json")
         .send()
-        .await
-        .map_err(|e| e.to_string())?;
+        .await?;
 
     if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("Gemini API error: {}", error_text));
+        return Err(provider_error("Gemini", response).await);
     }
 
-    let response_json: Value = response.json().await.map_err(|e| e.to_string())?;
+    let response_json: Value = response.json().await?;
     
     let models = response_json
         .get("models")
-        .and_then(|models| models.as_array())
-        .map(|arr| arr.clone())
-        .unwrap_or_else(Vec::new);
+        .and_then(|models| models.as_array()).cloned()
+        .unwrap_or_default();
 
     Ok(models)
 }
@@ -528,8 +554,8 @@ pub async fn llm_list_gemini_models(
 pub async fn llm_list_mistral_models(
     api_key: String,
     base_url: Option<String>,
-) -> Result<Vec<Value>, String> {
-    let client = reqwest::Client::new();
+) -> CommandResult<Vec<Value>> {
+    let client = streaming_http_client();
     let url = base_url.unwrap_or_else(|| "https://api.mistral.ai".to_string());
     let endpoint = if url.ends_with("/v1") {
         format!("{}/models", url)
@@ -542,21 +568,190 @@ pub async fn llm_list_mistral_models(
         .header("Authorization", format!("Bearer {}", api_key))
         .header("Content-Type", "application/json")
         .send()
-        .await
-        .map_err(|e| e.to_string())?;
+        .await?;
 
     if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("Mistral API error: {}", error_text));
+        return Err(provider_error("Mistral", response).await);
     }
 
-    let response_json: Value = response.json().await.map_err(|e| e.to_string())?;
+    let response_json: Value = response.json().await?;
     
     let models = response_json
         .get("data")
-        .and_then(|data| data.as_array())
-        .map(|arr| arr.clone())
-        .unwrap_or_else(Vec::new);
+        .and_then(|data| data.as_array()).cloned()
+        .unwrap_or_default();
+
+    Ok(models)
+} 
+// ========== PROVIDER COMMANDS ==========
+
+/// Configured providers; the built-in Ollama server comes first
+#[tauri::command]
+pub async fn list_llm_providers(db_manager: State<'_, Arc<DatabaseManager>>) -> CommandResult<Vec<ProviderInfo>> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(llm_provider::list_providers(&conn)?)
+}
+
+/// Create or update a provider; the API key is stored encrypted
+#[tauri::command]
+pub async fn save_llm_provider(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    provider: ProviderInput,
+) -> CommandResult<ProviderInfo> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(llm_provider::save_provider(&conn, &provider)?)
+}
+
+#[tauri::command]
+pub async fn delete_llm_provider(db_manager: State<'_, Arc<DatabaseManager>>, provider_id: String) -> CommandResult<bool> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(llm_provider::delete_provider(&conn, &provider_id)?)
+}
+
+/// Provider and model used for chat, summarization and embeddings
+#[tauri::command]
+pub async fn get_llm_feature_models(db_manager: State<'_, Arc<DatabaseManager>>) -> CommandResult<Vec<FeatureModelInfo>> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(llm_provider::feature_models(&conn)?)
+}
+
+/// Choose a feature's model; a model that may not fit in memory is still
+/// saved, with a `backend://model-resource-warning` event
+#[tauri::command]
+pub async fn set_llm_feature_model(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    events: State<'_, EventBus>,
+    feature: String,
+    provider_id: String,
+    model: String,
+) -> CommandResult<FeatureModelInfo> {
+    let feature = LlmFeature::parse(&feature)?;
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    let info = llm_provider::set_feature_model(&conn, feature, &provider_id, &model)?;
+    if let Some(warning) = model_router::inspect_model(&conn, feature).ok().and_then(|resolved| resolved.resource_warning()) {
+        events.emit(BackendEvent::ModelResourceWarning(warning));
+    }
+    Ok(info)
+}
+
+/// Check a provider answers, optionally running a short prompt on `model`
+#[tauri::command]
+pub async fn test_llm_provider(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    provider_id: String,
+    model: Option<String>,
+) -> CommandResult<ProviderTestResult> {
+    let provider = {
+        let conn = db_manager.get_connection().context("Failed to get database connection")?;
+        LlmProvider::load(&conn, &provider_id)?
+    };
+    Ok(provider.test(model.as_deref()).await)
+}
+
+// ========== TOKEN USAGE COMMANDS ==========
+
+#[derive(Debug, Serialize)]
+pub struct TokenCount {
+    pub tokens: usize,
+    /// Known context window of `model`, when one was given
+    pub context_window: Option<usize>,
+}
+
+/// Estimated tokens in `text`, for showing prompt size before sending
+#[tauri::command]
+pub async fn count_tokens(text: String, model: Option<String>) -> CommandResult<TokenCount> {
+    Ok(TokenCount {
+        tokens: tokens::count_tokens(&text),
+        context_window: model.as_deref().and_then(tokens::model_context_window),
+    })
+}
 
+/// LLM token usage and latency per feature and model over the last `days` (default 30)
+#[tauri::command]
+pub async fn get_llm_usage(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    days: Option<i64>,
+) -> CommandResult<Vec<LlmUsageSummary>> {
+    let since = chrono::Local::now().naive_local() - chrono::Duration::days(days.unwrap_or(30).max(1));
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(performance_operations::get_llm_usage_summary(&conn, since)?)
+}
+
+// ========== MODEL ROUTING COMMANDS ==========
+
+#[tauri::command]
+pub async fn list_llm_models(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    provider_id: Option<String>,
+) -> CommandResult<Vec<RegisteredModel>> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(model_router::list_models(&conn, provider_id.as_deref())?)
+}
+
+/// Re-read installed models from one provider, or from all of them, and
+/// warn about feature models that may not fit in memory
+#[tauri::command]
+pub async fn refresh_llm_models(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    events: State<'_, EventBus>,
+    provider_id: Option<String>,
+) -> CommandResult<Vec<RegisteredModel>> {
+    let models = model_router::refresh_registry(&db_manager, provider_id.as_deref()).await?;
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    for warning in model_router::resource_warnings(&conn) {
+        events.emit(BackendEvent::ModelResourceWarning(warning));
+    }
     Ok(models)
-} 
\ No newline at end of file
+}
+
+/// The model a feature will use, why, and how it fits this machine. A model
+/// too large to run is returned with alternatives rather than refused.
+#[tauri::command]
+pub async fn resolve_llm_model(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    feature: String,
+) -> CommandResult<ResolvedModel> {
+    let feature = LlmFeature::parse(&feature)?;
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(model_router::inspect_model(&conn, feature)?)
+}
+
+/// Memory and GPUs recorded at startup, or detected again with `refresh`
+#[tauri::command]
+pub async fn get_hardware_profile(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    refresh: Option<bool>,
+) -> CommandResult<HardwareProfile> {
+    if !refresh.unwrap_or(false) {
+        let conn = db_manager.get_connection().context("Failed to get database connection")?;
+        if let Some(profile) = hardware::load_profile(&conn)? {
+            return Ok(profile);
+        }
+    }
+    Ok(model_router::detect_hardware(&db_manager).await?)
+}
+
+#[tauri::command]
+pub async fn get_llm_routing_rules(db_manager: State<'_, Arc<DatabaseManager>>) -> CommandResult<Vec<RoutingRule>> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(model_router::list_rules(&conn)?)
+}
+
+#[tauri::command]
+pub async fn set_llm_routing_rule(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    rule: RoutingRule,
+) -> CommandResult<RoutingRule> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(model_router::set_rule(&conn, &rule)?)
+}
+
+#[tauri::command]
+pub async fn delete_llm_routing_rule(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    feature: String,
+) -> CommandResult<bool> {
+    let feature = LlmFeature::parse(&feature)?;
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(model_router::delete_rule(&conn, feature)?)
+}
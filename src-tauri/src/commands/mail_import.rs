@@ -0,0 +1,118 @@
+//! Mail Import Commands
+//!
+//! Importing Thunderbird and Outlook mail into archive accounts, and
+//! browsing what was imported. Imported messages are searched with the
+//! rest of the mail; see `services::mail_import`.
+
+use anyhow::Context;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::State;
+
+use crate::database::operations::archive_operations::{self, ArchiveAccount, ArchiveFolder, ArchivedMessage};
+use crate::database::DatabaseManager;
+use crate::services::mail_import::{self, ImportSummary};
+use crate::errors::{CommandResult, LibreOllamaError};
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+
+fn archive_name(name: Option<String>, path: &std::path::Path) -> String {
+    name.map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .or_else(|| path.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "Imported mail".to_string())
+}
+
+/// Import a Thunderbird profile, mail folder or mbox file
+#[tauri::command]
+pub async fn import_thunderbird_mail(
+    path: String,
+    name: Option<String>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<ImportSummary> {
+    let path = PathBuf::from(path);
+    let name = archive_name(name, &path);
+    let db_manager = Arc::clone(&db_manager);
+    Ok(tokio::task::spawn_blocking(move || mail_import::import_thunderbird(&db_manager, &path, &name))
+        .await
+        .context("Import task failed")?
+        .context("Failed to import Thunderbird mail")?)
+}
+
+/// Import an Outlook PST or OST file; needs readpst installed
+#[tauri::command]
+pub async fn import_outlook_mail(
+    path: String,
+    name: Option<String>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<ImportSummary> {
+    let path = PathBuf::from(path);
+    let name = archive_name(name, &path);
+    let db_manager = Arc::clone(&db_manager);
+    Ok(tokio::task::spawn_blocking(move || mail_import::import_outlook(&db_manager, &path, &name))
+        .await
+        .context("Import task failed")?
+        .context("Failed to import Outlook mail")?)
+}
+
+/// Whether Outlook files can be imported on this machine
+#[tauri::command]
+pub async fn get_outlook_import_available() -> CommandResult<bool> {
+    Ok(mail_import::readpst_available())
+}
+
+#[tauri::command]
+pub async fn get_archive_accounts(db_manager: State<'_, Arc<DatabaseManager>>) -> CommandResult<Vec<ArchiveAccount>> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(archive_operations::list_archive_accounts(&conn).context("Failed to list archive accounts")?)
+}
+
+/// The archive's original folders, as labels
+#[tauri::command]
+pub async fn get_archive_folders(
+    account_id: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<ArchiveFolder>> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(archive_operations::list_archive_folders(&conn, &account_id).context("Failed to list archive folders")?)
+}
+
+/// Messages in one archive folder, or the whole archive, newest first
+#[tauri::command]
+pub async fn get_archived_messages(
+    account_id: String,
+    label: Option<String>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<ArchivedMessage>> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(archive_operations::list_archived_messages(
+        &conn,
+        &account_id,
+        label.as_deref(),
+        limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, 500),
+        offset.unwrap_or(0).max(0),
+    )
+    .context("Failed to list archived messages")?)
+}
+
+/// A full archived message, in the same form as synced Gmail messages
+#[tauri::command]
+pub async fn get_archived_message(
+    account_id: String,
+    message_id: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<serde_json::Value> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(archive_operations::get_archived_message(&conn, &account_id, &message_id)
+        .context("Failed to load archived message")?
+        .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("archived message {}", message_id) })?)
+}
+
+/// Delete an archive account and everything imported into it
+#[tauri::command]
+pub async fn delete_archive_account(account_id: String, db_manager: State<'_, Arc<DatabaseManager>>) -> CommandResult<bool> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(archive_operations::delete_archive_account(&conn, &account_id).context("Failed to delete archive account")?)
+}
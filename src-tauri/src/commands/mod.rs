@@ -13,6 +13,66 @@ pub mod projects; // Project management
 pub mod system;   // System health and advanced features
 pub mod text_processing;
 pub mod llm;
+pub mod sync;     // Offline change queue and conflict resolution
+pub mod contacts; // Google Contacts sync and lookup
+pub mod image_proxy; // Remote image fetching for email bodies
+pub mod transcription; // Voice note and audio attachment transcription
+pub mod quick_capture; // Global capture window routing
+pub mod search;   // Global search across all domains
+pub mod settings; // Typed settings, import and export
+pub mod onboarding; // First-run setup flow
+pub mod prompt_templates; // Prompt template library and per-feature defaults
+pub mod feeds;    // RSS/Atom subscriptions and their digest
+pub mod digest;   // Daily digest of mail, events, tasks and follow-ups
+pub mod workspaces; // Work/personal workspaces that scope listings and caches
+pub mod mail_import; // Thunderbird and Outlook archives imported into the message store
+pub mod vault;    // Passphrase-protected secure notes
+pub mod app_lock; // Passphrase lock over the whole app
+pub mod launch;   // Requests forwarded from the command line and later launches
+pub mod updates;  // App updates from the stable or beta channel
+pub mod notifications; // VIP senders and their alert rules
+pub mod timeline; // Day-by-day feed of activity across mail, tasks, notes and chat
+
+/// Declares the command registry: one entry per invocable command, grouped
+/// by category and named by its path from the crate root. Expands to the
+/// `COMMANDS` table and the `invoke_handler` in `registry.rs`, so a command
+/// is exposed to the frontend exactly when it has metadata.
+///
+/// ```ignore
+/// command_registry! {
+///     "notes" {
+///         commands::notes::create_note ["local.write"] "Create a note" (title: "String", folder_id: "Option<i32>");
+///     }
+/// }
+/// ```
+macro_rules! command_registry {
+    ($($category:literal {
+        $($($path:ident)::+ [$($permission:literal),*] $description:literal ($($param:ident: $param_type:literal),*);)*
+    })*) => {
+        pub const COMMANDS: &[CommandMetadata] = &[
+            $($(CommandMetadata {
+                name: command_name!($($path)::+),
+                category: $category,
+                description: $description,
+                parameters: &[$(ParameterMetadata { name: stringify!($param), type_name: $param_type }),*],
+                permissions: &[$($permission),*],
+            },)*)*
+        ];
+
+        /// Dispatches every registered command
+        pub fn invoke_handler() -> impl Fn(tauri::ipc::Invoke) -> bool + Send + Sync + 'static {
+            tauri::generate_handler![$($(crate::$($path)::+,)*)*]
+        }
+    };
+}
+
+/// The last segment of a command path, which is the name it is invoked by
+macro_rules! command_name {
+    ($name:ident) => { stringify!($name) };
+    ($module:ident :: $($rest:ident)::+) => { command_name!($($rest)::+) };
+}
+
+pub mod registry; // Command metadata for the command palette
 
 // Legacy flat modules (to be reorganized)
 pub mod ollama;
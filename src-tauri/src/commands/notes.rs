@@ -1,9 +1,15 @@
 //! Notes commands
+use anyhow::Context;
 use serde::{Deserialize, Serialize};
-use tauri::{command, State};
+use tauri::{command, AppHandle, State};
+use std::collections::HashMap;
 use std::sync::Arc;
 use crate::database::models::Note;
 use crate::database::operations;
+use crate::database::operations::note_template_operations::NoteTemplate;
+use crate::services::time_service::TimeService;
+use crate::services::{file_access, note_templates, pdf_extract, web_clipper, workspaces};
+use crate::errors::{CommandError, CommandResult, LibreOllamaError};
 
 #[derive(Debug, Serialize)]
 pub struct NoteResponse {
@@ -28,6 +34,77 @@ impl From<Note> for NoteResponse {
     }
 }
 
+fn note_fields(note: &Note) -> serde_json::Value {
+    serde_json::json!({
+        "title": note.title,
+        "content": note.content,
+        "folder_id": note.folder_id,
+    })
+}
+
+/// Split a note update into the new values and the values they replaced
+fn changed_note_fields(before: &Note, after: &Note) -> (serde_json::Value, serde_json::Value) {
+    let before = note_fields(before);
+    let after = note_fields(after);
+    let mut changed = serde_json::Map::new();
+    let mut replaced = serde_json::Map::new();
+    for key in ["title", "content", "folder_id"] {
+        if before[key] != after[key] {
+            changed.insert(key.to_string(), after[key].clone());
+            replaced.insert(key.to_string(), before[key].clone());
+        }
+    }
+    (serde_json::Value::Object(changed), serde_json::Value::Object(replaced))
+}
+
+/// Record a note mutation in the change journal; failures are logged, not fatal
+fn journal_note_change(
+    conn: &rusqlite::Connection,
+    note_id: &str,
+    operation: &str,
+    payload: serde_json::Value,
+    previous: Option<serde_json::Value>,
+) {
+    if let Err(e) = operations::change_journal_operations::record_change(
+        conn, "note", note_id, operation, &payload, previous.as_ref(), None,
+    ) {
+        eprintln!("⚠️ Failed to journal note {} {}: {}", operation, note_id, e);
+    }
+}
+
+/// Remove the image folder of a note clipped before images were embedded
+fn remove_clip_images(content: &str) {
+    let dirs = match web_clipper::clip_image_dirs(content) {
+        Ok(dirs) => dirs,
+        Err(e) => {
+            eprintln!("⚠️ Failed to find clipped images: {}", e);
+            return;
+        }
+    };
+    for dir in dirs {
+        if let Err(e) = std::fs::remove_dir_all(&dir) {
+            eprintln!("⚠️ Failed to remove clipped images in {}: {}", dir.display(), e);
+        }
+    }
+}
+
+/// Create a note kept from `source_url` and journal it like any new note.
+/// Runs on the caller's connection, so it can share their transaction.
+pub(crate) fn create_sourced_note(
+    conn: &rusqlite::Connection,
+    title: &str,
+    content: &str,
+    user_id: &str,
+    folder_id: Option<i32>,
+    source_url: Option<&str>,
+    tags: &[String],
+) -> CommandResult<Note> {
+    let note = operations::note_operations::create_note(conn, title, content, user_id, folder_id)?;
+    operations::note_operations::set_note_source(conn, note.id, source_url, tags)?;
+    journal_note_change(conn, &note.id.to_string(), "create", note_fields(&note), None);
+    Ok(note)
+}
+
 #[derive(Debug, Deserialize)]
 pub struct UpdateNoteRequest {
     pub title: Option<String>,
@@ -38,15 +115,16 @@ pub struct UpdateNoteRequest {
 #[command]
 pub async fn get_notes(
     db_manager: State<'_, Arc<crate::database::DatabaseManager>>,
-) -> Result<Vec<NoteResponse>, String> {
+) -> CommandResult<Vec<NoteResponse>> {
     let db_manager_clone = db_manager.inner().clone();
     let notes = tokio::task::spawn_blocking(move || {
-        let conn = db_manager_clone.get_connection().map_err(|e| e.to_string())?;
-        operations::note_operations::get_all_notes(&conn).map_err(|e| e.to_string())
+        let conn = db_manager_clone.get_connection()?;
+        let mut notes = operations::note_operations::get_all_notes(&conn)?;
+        let scope = workspaces::scope(&conn)?;
+        notes.retain(|note| scope.allows_folder(note.folder_id));
+        Ok::<_, CommandError>(notes)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e| e)?;
+    .await??;
 
     Ok(notes.into_iter().map(NoteResponse::from).collect())
 }
@@ -58,15 +136,15 @@ pub async fn create_note(
     folder_id: Option<i32>,
     user_id: String,
     db_manager: State<'_, Arc<crate::database::DatabaseManager>>,
-) -> Result<NoteResponse, String> {
+) -> CommandResult<NoteResponse> {
     let db_manager_clone = db_manager.inner().clone();
     let created_note = tokio::task::spawn_blocking(move || {
-        let conn = db_manager_clone.get_connection().map_err(|e| e.to_string())?;
-        operations::note_operations::create_note(&conn, &title, &content, &user_id, folder_id).map_err(|e| e.to_string())
+        let conn = db_manager_clone.get_connection()?;
+        let note = operations::note_operations::create_note(&conn, &title, &content, &user_id, folder_id)?;
+        journal_note_change(&conn, &note.id.to_string(), "create", note_fields(&note), None);
+        Ok::<Note, CommandError>(note)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e| e)?;
+    .await??;
 
     Ok(NoteResponse::from(created_note))
 }
@@ -76,16 +154,20 @@ pub async fn update_note(
     id: String,
     note: UpdateNoteRequest,
     db_manager: State<'_, Arc<crate::database::DatabaseManager>>,
-) -> Result<NoteResponse, String> {
-    let note_id = id.parse().map_err(|_| "Invalid note ID".to_string())?;
+) -> CommandResult<NoteResponse> {
+    let note_id = id.parse().map_err(|_| CommandError::from("Invalid note ID"))?;
     let db_manager_clone = db_manager.inner().clone();
     let updated_note = tokio::task::spawn_blocking(move || {
-        let mut conn = db_manager_clone.get_connection().map_err(|e| e.to_string())?;
-        operations::note_operations::update_note(&mut conn, note_id, note.title.as_deref(), note.content.as_deref(), note.folder_id).map_err(|e| e.to_string())
+        let mut conn = db_manager_clone.get_connection()?;
+        let previous = operations::note_operations::get_note(&conn, note_id)?;
+        let updated = operations::note_operations::update_note(&mut conn, note_id, note.title.as_deref(), note.content.as_deref(), note.folder_id)?;
+        if let Some(previous) = previous {
+            let (changed, replaced) = changed_note_fields(&previous, &updated);
+            journal_note_change(&conn, &id, "update", changed, Some(replaced));
+        }
+        Ok::<Note, CommandError>(updated)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e| e)?;
+    .await??;
 
     Ok(NoteResponse::from(updated_note))
 }
@@ -94,16 +176,231 @@ pub async fn update_note(
 pub async fn delete_note(
     id: String,
     db_manager: State<'_, Arc<crate::database::DatabaseManager>>,
-) -> Result<(), String> {
-    let note_id = id.parse().map_err(|_| "Invalid note ID".to_string())?;
+) -> CommandResult<()> {
+    let note_id = id.parse().map_err(|_| CommandError::from("Invalid note ID"))?;
     let db_manager_clone = db_manager.inner().clone();
     tokio::task::spawn_blocking(move || {
-        let conn = db_manager_clone.get_connection().map_err(|e| e.to_string())?;
-        operations::note_operations::delete_note(&conn, note_id).map_err(|e| e.to_string())
+        let conn = db_manager_clone.get_connection()?;
+        let previous = operations::note_operations::get_note(&conn, note_id)?;
+        let deleted = operations::note_operations::delete_note(&conn, note_id)?;
+        if let Some(previous) = previous {
+            // The owner is kept so undo can restore the note to them
+            let mut fields = note_fields(&previous);
+            fields["user_id"] = serde_json::json!(previous.user_id);
+            journal_note_change(&conn, &id, "delete", serde_json::json!({}), Some(fields));
+            remove_clip_images(&previous.content);
+        }
+        Ok::<usize, CommandError>(deleted)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e| e)?;
+    .await??;
 
     Ok(())
-} 
\ No newline at end of file
+} 
+#[derive(Debug, Serialize)]
+pub struct PdfImportResponse {
+    pub note: NoteResponse,
+    pub page_count: usize,
+    pub ocr_pages: usize,
+    /// Pages whose text could not be read
+    pub missing_pages: Vec<usize>,
+}
+
+/// Create a note from a PDF: headings, paragraphs and lists become HTML, and
+/// the note is titled from the PDF's metadata or file name
+#[command]
+pub async fn import_pdf_note(
+    path: String,
+    folder_id: Option<i32>,
+    user_id: String,
+    app: AppHandle,
+    db_manager: State<'_, Arc<crate::database::DatabaseManager>>,
+) -> CommandResult<PdfImportResponse> {
+    let resolved = file_access::permitted_path(&app, &path)?;
+    let db_manager_clone = db_manager.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        use std::io::Read;
+        let file = std::fs::File::open(&resolved).with_context(|| format!("Failed to read {}", path))?;
+        // Read one byte past the limit so a file that grew is still caught
+        let mut bytes = Vec::new();
+        file.take(pdf_extract::MAX_PDF_BYTES as u64 + 1)
+            .read_to_end(&mut bytes)
+            .with_context(|| format!("Failed to read {}", path))?;
+        if bytes.len() > pdf_extract::MAX_PDF_BYTES {
+            return Err(LibreOllamaError::InvalidInput {
+                message: format!("{} is too large to import", path),
+                field: Some("path".to_string()),
+            }
+            .into());
+        }
+        let document = pdf_extract::extract(&bytes, true)?;
+        if document.text().is_empty() {
+            return Err("No text could be read from the PDF; scanned pages need tesseract and poppler installed".into());
+        }
+
+        let title = document.title.clone().unwrap_or_else(|| {
+            std::path::Path::new(&path)
+                .file_stem()
+                .map_or_else(|| "Imported PDF".to_string(), |stem| stem.to_string_lossy().into_owned())
+        });
+        let conn = db_manager_clone.get_connection()?;
+        let note = operations::note_operations::create_note(&conn, &title, &document.to_html(), &user_id, folder_id)?;
+        journal_note_change(&conn, &note.id.to_string(), "create", note_fields(&note), None);
+
+        Ok(PdfImportResponse {
+            page_count: document.page_count,
+            ocr_pages: document.pages.iter().filter(|page| page.source == pdf_extract::PageTextSource::Ocr).count(),
+            missing_pages: document.missing_pages(),
+            note: NoteResponse::from(note),
+        })
+    })
+    .await?
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClipResponse {
+    pub note: NoteResponse,
+    pub source_url: String,
+    pub tags: Vec<String>,
+    pub images_saved: usize,
+    /// Images left as links to the page
+    pub failed_images: Vec<String>,
+    /// Notes clipped from the same page before
+    pub previous_clips: Vec<String>,
+}
+
+/// Save a web page's article as a Markdown note, with its images stored
+/// locally and the source URL and tags recorded on the note
+#[command]
+pub async fn clip_url(
+    url: String,
+    tags: Option<Vec<String>>,
+    folder_id: Option<i32>,
+    user_id: String,
+    db_manager: State<'_, Arc<crate::database::DatabaseManager>>,
+) -> CommandResult<ClipResponse> {
+    let article = web_clipper::clip(&url).await.with_context(|| format!("Failed to clip {}", url))?;
+    let tags = web_clipper::normalize_tags(&tags.unwrap_or_default());
+
+    let db_manager_clone = db_manager.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let mut conn = db_manager_clone.get_connection()?;
+        let tx = conn.transaction()?;
+        let previous_clips = operations::note_operations::find_notes_by_source(&tx, &article.source_url)?;
+        let note = create_sourced_note(&tx, &article.title, &article.markdown, &user_id, folder_id, Some(&article.source_url), &tags)?;
+        tx.commit()?;
+
+        Ok(ClipResponse {
+            note: NoteResponse::from(note),
+            source_url: article.source_url,
+            tags,
+            images_saved: article.images.len(),
+            failed_images: article.failed_images,
+            previous_clips: previous_clips.iter().map(|id| id.to_string()).collect(),
+        })
+    })
+    .await?
+}
+
+#[command]
+pub async fn list_note_templates(
+    db_manager: State<'_, Arc<crate::database::DatabaseManager>>,
+) -> CommandResult<Vec<NoteTemplate>> {
+    let conn = db_manager.get_connection()?;
+    operations::note_template_operations::list_templates(&conn).map_err(CommandError::from)
+}
+
+/// Create a note template, or replace template `id`. Titles and bodies use
+/// `{{variable}}` placeholders such as `{{date}}` and `{{weekday}}`.
+#[command]
+pub async fn save_note_template(
+    id: Option<i64>,
+    name: String,
+    title: String,
+    content: String,
+    db_manager: State<'_, Arc<crate::database::DatabaseManager>>,
+) -> CommandResult<NoteTemplate> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Template name cannot be empty".into());
+    }
+    let conn = db_manager.get_connection()?;
+    if let Some(existing) = operations::note_template_operations::get_template_by_name(&conn, name)? {
+        if Some(existing.id) != id {
+            return Err(LibreOllamaError::InvalidInput {
+                message: format!("A note template named '{}' already exists", name),
+                field: Some("name".to_string()),
+            }
+            .into());
+        }
+    }
+    match id {
+        Some(id) => operations::note_template_operations::update_template(&conn, id, name, &title, &content)?
+            .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("note template {}", id) }.into()),
+        None => operations::note_template_operations::create_template(&conn, name, &title, &content).map_err(CommandError::from),
+    }
+}
+
+#[command]
+pub async fn delete_note_template(
+    id: i64,
+    db_manager: State<'_, Arc<crate::database::DatabaseManager>>,
+) -> CommandResult<bool> {
+    let conn = db_manager.get_connection()?;
+    operations::note_template_operations::delete_template(&conn, id).map_err(CommandError::from)
+}
+
+/// Create a note from a template; `variables` fill placeholders beyond the
+/// date ones
+#[command]
+pub async fn create_note_from_template(
+    template_id: i64,
+    folder_id: Option<i32>,
+    user_id: String,
+    variables: Option<HashMap<String, String>>,
+    db_manager: State<'_, Arc<crate::database::DatabaseManager>>,
+) -> CommandResult<NoteResponse> {
+    let db_manager_clone = db_manager.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = db_manager_clone.get_connection()?;
+        let note = note_templates::create_from_template(
+            &conn,
+            template_id,
+            &user_id,
+            folder_id,
+            variables.unwrap_or_default(),
+            TimeService::load(&conn)?.now(),
+        )?;
+        journal_note_change(&conn, &note.id.to_string(), "create", note_fields(&note), None);
+        Ok(NoteResponse::from(note))
+    })
+    .await?
+}
+
+#[derive(Debug, Serialize)]
+pub struct DailyNoteResponse {
+    pub note: NoteResponse,
+    /// Local date, "YYYY-MM-DD"
+    pub date: String,
+    /// False when today's note already existed
+    pub created: bool,
+}
+
+/// Open today's note, creating it from the daily note template (see the
+/// `notes.daily_note` setting) the first time each day
+#[command]
+pub async fn get_or_create_daily_note(
+    user_id: String,
+    db_manager: State<'_, Arc<crate::database::DatabaseManager>>,
+) -> CommandResult<DailyNoteResponse> {
+    let db_manager_clone = db_manager.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let mut conn = db_manager_clone.get_connection()?;
+        let now = TimeService::load(&conn)?.now();
+        let daily = note_templates::get_or_create_daily_note(&mut conn, &user_id, now)?;
+        if daily.created {
+            journal_note_change(&conn, &daily.note.id.to_string(), "create", note_fields(&daily.note), None);
+        }
+        Ok(DailyNoteResponse { note: NoteResponse::from(daily.note), date: daily.date, created: daily.created })
+    })
+    .await?
+}
@@ -0,0 +1,147 @@
+//! Notification Commands
+//!
+//! Manage VIP senders and the rules their mail is announced under, globally
+//! or for one workspace, and try a VIP alert; see `services::notification`.
+//! Quiet hours and previews are ordinary settings.
+
+use anyhow::Context;
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+
+use crate::database::operations::vip_operations::{self, VipSender};
+use crate::database::DatabaseManager;
+use crate::services::events::{BackendEvent, EventBus, NewMailEvent};
+use crate::services::file_access;
+use crate::services::i18n;
+use crate::services::notification::{self, MailAlert, MailNotice, NotificationService, VipRules, VIP_RULES_SETTING};
+use crate::services::settings::SettingsService;
+use crate::errors::CommandResult;
+
+const TEST_SENDER: &str = "vip@example.com";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VipRuleSettings {
+    pub rules: VipRules,
+    /// Workspace whose own rules these are; the global rules when absent
+    pub workspace_id: Option<i64>,
+}
+
+/// VIPs of a workspace, or with no workspace those shared by all of them
+#[tauri::command]
+pub async fn list_vip_senders(
+    workspace_id: Option<i64>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<VipSender>> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(vip_operations::list_vips(&conn, workspace_id)?)
+}
+
+/// Add an address, or "@domain" for a whole domain, to the VIPs of a
+/// workspace, or with no workspace to those of every workspace
+#[tauri::command]
+pub async fn add_vip_sender(
+    address: String,
+    name: Option<String>,
+    workspace_id: Option<i64>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<VipSender> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(notification::add_vip(&conn, workspace_id, &address, name.as_deref())?)
+}
+
+#[tauri::command]
+pub async fn remove_vip_sender(
+    id: i64,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<bool> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(vip_operations::remove_vip(&conn, id)?)
+}
+
+/// The VIP rules of a workspace, falling back to the global ones, or with
+/// no workspace given the rules in effect for the active workspace
+#[tauri::command]
+pub async fn get_vip_rules(
+    workspace_id: Option<i64>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<VipRuleSettings> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    let (rules, workspace_id) = match workspace_id {
+        Some(id) => match notification::workspace_rules(&conn, id)? {
+            Some(rules) => (rules, Some(id)),
+            None => (notification::global_rules(&conn)?, None),
+        },
+        None => notification::effective_rules(&conn)?,
+    };
+    Ok(VipRuleSettings { rules, workspace_id })
+}
+
+/// Save the global VIP rules, or a workspace's own rules when a workspace
+/// is given; no rules for a workspace makes it use the global ones again.
+/// A sound file must have been picked in a file dialog or lie in the app's
+/// data directory.
+#[tauri::command]
+pub async fn set_vip_rules(
+    mut rules: Option<VipRules>,
+    workspace_id: Option<i64>,
+    app: AppHandle,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    settings_service: State<'_, SettingsService>,
+) -> CommandResult<()> {
+    if let Some(sound) = rules.as_mut().and_then(|rules| rules.sound.as_mut()) {
+        if notification::is_sound_file(sound) {
+            *sound = file_access::permitted_path(&app, sound)?.to_string_lossy().into_owned();
+        }
+    }
+    match workspace_id {
+        Some(id) => {
+            let conn = db_manager.get_connection()
+                .context("Failed to get database connection")?;
+            notification::save_workspace_rules(&conn, id, rules.as_ref())?;
+        }
+        None => match rules {
+            Some(rules) => {
+                settings_service.set(VIP_RULES_SETTING, &serde_json::to_value(&rules)?)?;
+            }
+            None => {
+                settings_service.reset(VIP_RULES_SETTING)?;
+            }
+        },
+    }
+    Ok(())
+}
+
+/// Announce a made-up message from a VIP under the rules in effect, quiet
+/// hours aside, so the sound and preview can be tried
+#[tauri::command]
+pub async fn test_vip_alert(
+    account_id: String,
+    address: Option<String>,
+    notification_service: State<'_, NotificationService>,
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<MailAlert> {
+    let from_email = match address {
+        Some(address) => notification::normalize_address(&address)?,
+        None => TEST_SENDER.to_string(),
+    };
+    let notice = MailNotice {
+        message_id: format!("vip-test-{}", chrono::Utc::now().timestamp_millis()),
+        from_email,
+        from_name: Some(i18n::t("vip-test-sender")),
+        subject: Some(i18n::t("vip-test-subject")),
+        snippet: Some(i18n::t("vip-test-snippet")),
+    };
+    let alert = notification_service.test_alert(&notice)?;
+    event_bus.emit(BackendEvent::NewMail(NewMailEvent {
+        account_id,
+        message_ids: vec![alert.message_id.clone()],
+        alerts: vec![alert.clone()],
+        test: true,
+    }));
+    Ok(alert)
+}
@@ -1,21 +1,18 @@
 // use anyhow::Result as AnyResult; // Will be used when implementing error handling
 use serde::{Deserialize, Serialize};
-use reqwest;
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::Mutex;
-use std::process::{Child, Command}; // Removed Stdio - unused
 use sysinfo::{System, Pid};
-use tauri::{AppHandle, Emitter};
-use tokio::time::{sleep, Duration};
+use tauri::{AppHandle, Emitter, State};
 use futures_util::StreamExt;
+use crate::database::DatabaseManager;
+use crate::services::llm_provider;
+use crate::services::ollama_supervisor::{OllamaSupervisor, SupervisorStatus};
+use crate::utils::http_client::streaming_http_client;
+use crate::errors::{CommandError, CommandResult, LibreOllamaError};
 // use bytes::Bytes; // Will be used when implementing streaming
 
-// Global sidecar process management
-lazy_static::lazy_static! {
-    static ref OLLAMA_PROCESS: Arc<Mutex<Option<Child>>> = Arc::new(Mutex::new(None));
-    static ref OLLAMA_PID: Arc<Mutex<Option<u32>>> = Arc::new(Mutex::new(None));
-}
+// The sidecar process itself is owned by `OllamaSupervisor`
 
 // Data structures for Ollama integration
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -101,6 +98,27 @@ pub struct ModelMetadata {
     pub quantization_level: String,
 }
 
+/// `path` on the Ollama server set up as the built-in provider
+fn ollama_url(db_manager: &DatabaseManager, path: &str) -> CommandResult<String> {
+    let conn = db_manager.get_connection()?;
+    Ok(format!("{}{}", llm_provider::ollama_base_url(&conn)?, path))
+}
+
+/// The error for a failed Ollama response; an unknown model is a 404
+async fn api_error(context: impl std::fmt::Display, response: reqwest::Response) -> CommandError {
+    let status = response.status();
+    let message = match response.text().await {
+        Ok(error_text) => format!("{}: {} - {}", context, status, error_text),
+        Err(_) => format!("{}: {}", context, status),
+    };
+    match status.as_u16() {
+        404 => LibreOllamaError::NotFound { resource: message },
+        400..=499 => LibreOllamaError::InvalidInput { message, field: None },
+        _ => LibreOllamaError::Internal { message },
+    }
+    .into()
+}
+
 // Helper function to get system process info
 fn get_process_info(pid: u32) -> Option<ProcessInfo> {
     let mut system = System::new_all();
@@ -116,83 +134,42 @@ fn get_process_info(pid: u32) -> Option<ProcessInfo> {
 
 // Sidecar management commands
 #[tauri::command]
-pub async fn ollama_start_sidecar() -> Result<String, String> {
-    let mut process_lock = OLLAMA_PROCESS.lock().await;
-    
-    // Check if already running
-    if let Some(ref mut child) = *process_lock {
-        match child.try_wait() {
-            Ok(Some(_)) => {
-                // Process has exited, clear it
-                *process_lock = None;
-            }
-            Ok(None) => {
-                // Process is still running
-                return Ok("Ollama sidecar is already running".to_string());
-            }
-            Err(e) => {
-                return Err(format!("Failed to check process status: {}", e));
-            }
-        }
-    }
+pub async fn ollama_start_sidecar(supervisor: State<'_, OllamaSupervisor>) -> CommandResult<String> {
+    let status = supervisor.start().await?;
+    Ok(match (status.managed, status.pid) {
+        (true, Some(pid)) => format!("Ollama sidecar running with PID: {}", pid),
+        _ => "Ollama is already running".to_string(),
+    })
+}
 
-    // Start new sidecar process
-    let ollama_command = if cfg!(target_os = "windows") {
-        "ollama.exe"
+#[tauri::command]
+pub async fn ollama_stop_sidecar(supervisor: State<'_, OllamaSupervisor>) -> CommandResult<String> {
+    let had_child = supervisor.managed_pid().await.is_some();
+    supervisor.stop().await?;
+    Ok(if had_child {
+        "Ollama sidecar stopped successfully".to_string()
     } else {
-        "ollama"
-    };
-
-    match Command::new(ollama_command)
-        .args(["serve"])
-        .env("OLLAMA_HOST", "127.0.0.1:11434")
-        .env("OLLAMA_ORIGINS", "*")
-        .spawn()
-    {
-        Ok(child) => {
-            let pid = child.id();
-            *OLLAMA_PID.lock().await = Some(pid);
-            *process_lock = Some(child);
-            
-            // Wait a moment for the server to start
-            sleep(Duration::from_secs(2)).await;
-            
-            Ok(format!("Ollama sidecar started with PID: {}", pid))
-        }
-        Err(e) => Err(format!("Failed to start Ollama sidecar: {}", e)),
-    }
+        "No Ollama sidecar process found".to_string()
+    })
 }
 
+/// Supervisor state: whether Ollama is installed, running, launched by us and restarted
 #[tauri::command]
-pub async fn ollama_stop_sidecar() -> Result<String, String> {
-    let mut process_lock = OLLAMA_PROCESS.lock().await;
-    
-    if let Some(mut child) = process_lock.take() {
-        match child.kill() {
-            Ok(_) => {
-                *OLLAMA_PID.lock().await = None;
-                Ok("Ollama sidecar stopped successfully".to_string())
-            }
-            Err(e) => Err(format!("Failed to stop Ollama sidecar: {}", e)),
-        }
-    } else {
-        Ok("No Ollama sidecar process found".to_string())
-    }
+pub async fn ollama_supervisor_status(supervisor: State<'_, OllamaSupervisor>) -> CommandResult<SupervisorStatus> {
+    Ok(supervisor.status().await)
 }
 
 #[tauri::command]
-pub async fn ollama_get_status() -> Result<OllamaHealthResponse, String> {
-    let client = reqwest::Client::new();
-    let url = "http://localhost:11434/api/tags";
+pub async fn ollama_get_status(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    supervisor: State<'_, OllamaSupervisor>,
+) -> CommandResult<OllamaHealthResponse> {
+    let client = streaming_http_client();
+    let url = ollama_url(&db_manager, "/api/tags")?;
     
-    let pid_lock = OLLAMA_PID.lock().await;
-    let process_info = if let Some(pid) = *pid_lock {
-        get_process_info(pid)
-    } else {
-        None
-    };
+    let process_info = supervisor.managed_pid().await.and_then(get_process_info);
     
-    match client.get(url).send().await {
+    match client.get(&url).send().await {
         Ok(response) => {
             if response.status().is_success() {
                 Ok(OllamaHealthResponse {
@@ -220,95 +197,99 @@ pub async fn ollama_get_status() -> Result<OllamaHealthResponse, String> {
 
 // Enhanced model management commands
 #[tauri::command]
-pub async fn ollama_health_check() -> Result<OllamaHealthResponse, String> {
-    ollama_get_status().await
+pub async fn ollama_health_check(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    supervisor: State<'_, OllamaSupervisor>,
+) -> CommandResult<OllamaHealthResponse> {
+    ollama_get_status(db_manager, supervisor).await
 }
 
 #[tauri::command]
-pub async fn ollama_list_models() -> Result<Vec<ModelInfo>, String> {
-    let client = reqwest::Client::new();
-    let url = "http://localhost:11434/api/tags";
+pub async fn ollama_list_models(db_manager: State<'_, Arc<DatabaseManager>>) -> CommandResult<Vec<ModelInfo>> {
+    installed_models(&db_manager).await
+}
+
+/// Models installed on the configured Ollama server
+pub async fn installed_models(db_manager: &DatabaseManager) -> CommandResult<Vec<ModelInfo>> {
+    let client = streaming_http_client();
+    let url = ollama_url(db_manager, "/api/tags")?;
     
-    match client.get(url).send().await {
+    match client.get(&url).send().await {
         Ok(response) => {
             if response.status().is_success() {
                 match response.json::<OllamaModelsResponse>().await {
                     Ok(models_response) => Ok(models_response.models),
-                    Err(e) => Err(format!("Failed to parse models response: {}", e)),
+                    Err(e) => Err(anyhow::Error::new(e).context("Failed to parse models response").into()),
                 }
             } else {
-                Err(format!("Ollama API error: {}", response.status()))
+                Err(api_error("Ollama API error", response).await)
             }
         }
-        Err(e) => Err(format!("Failed to connect to Ollama: {}", e)),
+        Err(e) => Err(anyhow::Error::new(e).context("Failed to connect to Ollama").into()),
     }
 }
 
 #[tauri::command]
-pub async fn ollama_get_model_info(model_name: String) -> Result<ModelDetails, String> {
-    let client = reqwest::Client::new();
-    let url = "http://localhost:11434/api/show";
+pub async fn ollama_get_model_info(model_name: String, db_manager: State<'_, Arc<DatabaseManager>>) -> CommandResult<ModelDetails> {
+    let client = streaming_http_client();
+    let url = ollama_url(&db_manager, "/api/show")?;
     
     let request_body = serde_json::json!({
         "name": model_name
     });
     
-    match client.post(url).json(&request_body).send().await {
+    match client.post(&url).json(&request_body).send().await {
         Ok(response) => {
             if response.status().is_success() {
                 match response.json::<ModelDetails>().await {
                     Ok(model_details) => Ok(model_details),
-                    Err(e) => Err(format!("Failed to parse model details: {}", e)),
+                    Err(e) => Err(anyhow::Error::new(e).context("Failed to parse model details").into()),
                 }
             } else {
-                let status = response.status();
-                match response.text().await {
-                    Ok(error_text) => Err(format!("Failed to get model info {}: {} - {}", model_name, status, error_text)),
-                    Err(_) => Err(format!("Failed to get model info {}: {}", model_name, status)),
-                }
+                Err(api_error(format!("Failed to get model info {}", model_name), response).await)
             }
         }
-        Err(e) => Err(format!("Failed to connect to Ollama while getting model info for {}: {}", model_name, e)),
+        Err(e) => Err(anyhow::Error::new(e).context(format!("Failed to connect to Ollama while getting model info for {}", model_name)).into()),
     }
 }
 
 #[tauri::command]
-pub async fn ollama_delete_model(model_name: String) -> Result<String, String> {
-    let client = reqwest::Client::new();
-    let url = "http://localhost:11434/api/delete";
+pub async fn ollama_delete_model(model_name: String, db_manager: State<'_, Arc<DatabaseManager>>) -> CommandResult<String> {
+    let client = streaming_http_client();
+    let url = ollama_url(&db_manager, "/api/delete")?;
     
     let request_body = serde_json::json!({
         "name": model_name
     });
     
-    match client.delete(url).json(&request_body).send().await {
+    match client.delete(&url).json(&request_body).send().await {
         Ok(response) => {
             if response.status().is_success() {
                 Ok(format!("Successfully deleted model: {}", model_name))
             } else {
-                let status = response.status();
-                match response.text().await {
-                    Ok(error_text) => Err(format!("Failed to delete model {}: {} - {}", model_name, status, error_text)),
-                    Err(_) => Err(format!("Failed to delete model {}: {}", model_name, status)),
-                }
+                Err(api_error(format!("Failed to delete model {}", model_name), response).await)
             }
         }
-        Err(e) => Err(format!("Failed to connect to Ollama while deleting model {}: {}", model_name, e)),
+        Err(e) => Err(anyhow::Error::new(e).context(format!("Failed to connect to Ollama while deleting model {}", model_name)).into()),
     }
 }
 
 // Enhanced pull with progress tracking
 #[tauri::command]
-pub async fn ollama_pull_model(app_handle: AppHandle, model: String) -> Result<String, String> {
-    let client = reqwest::Client::new();
-    let url = "http://localhost:11434/api/pull";
+pub async fn ollama_pull_model(
+    app_handle: AppHandle,
+    model: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<String> {
+    let client = streaming_http_client();
+    let url = ollama_url(&db_manager, "/api/pull")?;
     
     let request_body = serde_json::json!({
         "name": model,
         "stream": true
     });
     
-    match client.post(url).json(&request_body).send().await {
+    match client.post(&url).json(&request_body).send().await {
         Ok(response) => {
             if response.status().is_success() {
                 let mut stream = response.bytes_stream();
@@ -338,21 +319,17 @@ pub async fn ollama_pull_model(app_handle: AppHandle, model: String) -> Result<S
                             }
                         }
                         Err(e) => {
-                            return Err(format!("Stream error while pulling model {}: {}", model, e));
+                            return Err(anyhow::Error::new(e).context(format!("Stream error while pulling model {}", model)).into());
                         }
                     }
                 }
                 
                 Ok(format!("Model pull completed: {}", model))
             } else {
-                let status = response.status();
-                match response.text().await {
-                    Ok(error_text) => Err(format!("Failed to pull model {}: {} - {}", model, status, error_text)),
-                    Err(_) => Err(format!("Failed to pull model {}: {}", model, status)),
-                }
+                Err(api_error(format!("Failed to pull model {}", model), response).await)
             }
         }
-        Err(e) => Err(format!("Failed to connect to Ollama while pulling model {}: {}", model, e)),
+        Err(e) => Err(anyhow::Error::new(e).context(format!("Failed to connect to Ollama while pulling model {}", model)).into()),
     }
 }
 
@@ -363,9 +340,10 @@ pub async fn ollama_chat_stream(
     messages: Vec<serde_json::Value>,
     model: String,
     stream_id: String,
-) -> Result<String, String> {
-    let client = reqwest::Client::new();
-    let url = "http://localhost:11434/api/chat";
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<String> {
+    let client = streaming_http_client();
+    let url = ollama_url(&db_manager, "/api/chat")?;
     
     let request_body = serde_json::json!({
         "model": model,
@@ -373,7 +351,7 @@ pub async fn ollama_chat_stream(
         "stream": true
     });
     
-    match client.post(url).json(&request_body).send().await {
+    match client.post(&url).json(&request_body).send().await {
         Ok(response) => {
             if response.status().is_success() {
                 let mut stream = response.bytes_stream();
@@ -419,29 +397,29 @@ pub async fn ollama_chat_stream(
                             }
                         }
                         Err(e) => {
-                            return Err(format!("Stream error during chat: {}", e));
+                            return Err(anyhow::Error::new(e).context("Stream error during chat").into());
                         }
                     }
                 }
                 
                 Ok(full_response)
             } else {
-                let status = response.status();
-                match response.text().await {
-                    Ok(error_text) => Err(format!("Ollama API error {}: {}", status, error_text)),
-                    Err(_) => Err(format!("Ollama API error: {}", status)),
-                }
+                Err(api_error("Ollama API error", response).await)
             }
         }
-        Err(e) => Err(format!("Failed to connect to Ollama: {}", e)),
+        Err(e) => Err(anyhow::Error::new(e).context("Failed to connect to Ollama").into()),
     }
 }
 
 // Legacy commands (keeping for backward compatibility)
 #[tauri::command]
-pub async fn ollama_generate(prompt: String, model: String) -> Result<String, String> {
-    let client = reqwest::Client::new();
-    let url = "http://localhost:11434/api/generate";
+pub async fn ollama_generate(
+    prompt: String,
+    model: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<String> {
+    let client = streaming_http_client();
+    let url = ollama_url(&db_manager, "/api/generate")?;
     
     let request_body = OllamaGenerateRequest {
         model,
@@ -451,29 +429,29 @@ pub async fn ollama_generate(prompt: String, model: String) -> Result<String, St
         options: None,
     };
     
-    match client.post(url).json(&request_body).send().await {
+    match client.post(&url).json(&request_body).send().await {
         Ok(response) => {
             if response.status().is_success() {
                 match response.json::<OllamaGenerateResponse>().await {
                     Ok(generate_response) => Ok(generate_response.response),
-                    Err(e) => Err(format!("Failed to parse generate response: {}", e)),
+                    Err(e) => Err(anyhow::Error::new(e).context("Failed to parse generate response").into()),
                 }
             } else {
-                let status = response.status();
-                match response.text().await {
-                    Ok(error_text) => Err(format!("Ollama API error {}: {}", status, error_text)),
-                    Err(_) => Err(format!("Ollama API error: {}", status)),
-                }
+                Err(api_error("Ollama API error", response).await)
             }
         }
-        Err(e) => Err(format!("Failed to connect to Ollama: {}", e)),
+        Err(e) => Err(anyhow::Error::new(e).context("Failed to connect to Ollama").into()),
     }
 }
 
 #[tauri::command]
-pub async fn ollama_chat(messages: Vec<serde_json::Value>, model: String) -> Result<String, String> {
-    let client = reqwest::Client::new();
-    let url = "http://localhost:11434/api/chat";
+pub async fn ollama_chat(
+    messages: Vec<serde_json::Value>,
+    model: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<String> {
+    let client = streaming_http_client();
+    let url = ollama_url(&db_manager, "/api/chat")?;
     
     let request_body = serde_json::json!({
         "model": model,
@@ -481,7 +459,7 @@ pub async fn ollama_chat(messages: Vec<serde_json::Value>, model: String) -> Res
         "stream": false
     });
     
-    match client.post(url).json(&request_body).send().await {
+    match client.post(&url).json(&request_body).send().await {
         Ok(response) => {
             if response.status().is_success() {
                 match response.json::<serde_json::Value>().await {
@@ -491,25 +469,21 @@ pub async fn ollama_chat(messages: Vec<serde_json::Value>, model: String) -> Res
                                 if let Some(content_str) = content.as_str() {
                                     Ok(content_str.to_string())
                                 } else {
-                                    Err("Invalid response format: content is not a string".to_string())
+                                    Err("Invalid response format: content is not a string".into())
                                 }
                             } else {
-                                Err("Invalid response format: missing content field".to_string())
+                                Err("Invalid response format: missing content field".into())
                             }
                         } else {
-                            Err("Invalid response format: missing message field".to_string())
+                            Err("Invalid response format: missing message field".into())
                         }
                     }
-                    Err(e) => Err(format!("Failed to parse chat response: {}", e)),
+                    Err(e) => Err(anyhow::Error::new(e).context("Failed to parse chat response").into()),
                 }
             } else {
-                let status = response.status();
-                match response.text().await {
-                    Ok(error_text) => Err(format!("Ollama API error {}: {}", status, error_text)),
-                    Err(_) => Err(format!("Ollama API error: {}", status)),
-                }
+                Err(api_error("Ollama API error", response).await)
             }
         }
-        Err(e) => Err(format!("Failed to connect to Ollama: {}", e)),
+        Err(e) => Err(anyhow::Error::new(e).context("Failed to connect to Ollama").into()),
     }
 }
\ No newline at end of file
@@ -0,0 +1,92 @@
+//! Onboarding Commands
+//!
+//! First-run flow: report progress with live prerequisite checks, complete
+//! steps, or skip the rest, and offer the mail accounts already set up on
+//! this machine.
+
+use anyhow::Context;
+use std::sync::Arc;
+use tauri::State;
+
+use crate::database::operations::onboarding_operations;
+use crate::database::DatabaseManager;
+use crate::services::account_detection::{self, DetectedAccount};
+use crate::services::onboarding::{self, OnboardingState};
+use crate::errors::CommandResult;
+
+/// Onboarding is tracked for the single local user unless told otherwise
+const DEFAULT_USER: &str = "default_user";
+
+/// Progress, prerequisite checks and a hint for every step
+#[tauri::command]
+pub async fn get_onboarding_state(
+    user_id: Option<String>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<OnboardingState> {
+    let user_id = user_id.unwrap_or_else(|| DEFAULT_USER.to_string());
+    let checks = onboarding::run_checks(&db_manager).await;
+
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    let status = onboarding::load_status(&conn, &user_id)?;
+    Ok(onboarding::build_state(&user_id, &status, checks))
+}
+
+/// Complete a step, saving the persona or models chosen on it. Fails with a
+/// hint when the step's prerequisite (e.g. Ollama running) is not met.
+#[tauri::command]
+pub async fn complete_onboarding_step(
+    step: String,
+    persona: Option<String>,
+    selected_models: Option<Vec<String>>,
+    user_id: Option<String>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<OnboardingState> {
+    let user_id = user_id.unwrap_or_else(|| DEFAULT_USER.to_string());
+    let checks = onboarding::run_checks(&db_manager).await;
+
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    let mut status = onboarding::load_status(&conn, &user_id)?;
+    onboarding::complete_step(&mut status, &step, &checks, persona, selected_models)?;
+    onboarding_operations::update_onboarding_status(&conn, &user_id, &status)
+        .context("Failed to save onboarding progress")?;
+
+    println!("👋 [ONBOARDING] {} completed step {}", user_id, step);
+    Ok(onboarding::build_state(&user_id, &status, checks))
+}
+
+/// Finish onboarding without the remaining steps
+#[tauri::command]
+pub async fn skip_onboarding(
+    user_id: Option<String>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<OnboardingState> {
+    let user_id = user_id.unwrap_or_else(|| DEFAULT_USER.to_string());
+    let checks = onboarding::run_checks(&db_manager).await;
+
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    let mut status = onboarding::load_status(&conn, &user_id)?;
+    onboarding::skip(&mut status);
+    onboarding_operations::update_onboarding_status(&conn, &user_id, &status)
+        .context("Failed to save onboarding progress")?;
+
+    Ok(onboarding::build_state(&user_id, &status, checks))
+}
+
+/// Mail accounts found in Thunderbird, Outlook and Windows, for prefilling
+/// the add-account flow or importing their local mail
+#[tauri::command]
+pub async fn get_detected_accounts(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<DetectedAccount>> {
+    let db_manager = db_manager.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        Ok(account_detection::detect_accounts(&conn))
+    })
+    .await
+    .context("Account detection failed")?
+}
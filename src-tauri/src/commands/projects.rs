@@ -8,6 +8,7 @@ use chrono::{DateTime, Utc, TimeZone};
 // Import database modules
 use crate::database::{Project as DbProject, ProjectGoal as DbProjectGoal, ProjectAsset as DbProjectAsset};
 use crate::database::operations;
+use crate::errors::CommandResult;
 
 // Data structures for project functionality (compatible with frontend)
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -111,15 +112,13 @@ pub async fn create_project(
     color: String,
     user_id: String,
     db_manager: tauri::State<'_, std::sync::Arc<crate::database::DatabaseManager>>,
-) -> Result<String, String> {
+) -> CommandResult<String> {
     let db_manager_clone = db_manager.inner().clone();
     let project_id = tokio::task::spawn_blocking(move || {
-        let conn = db_manager_clone.get_connection().map_err(|e| e.to_string())?;
-        operations::project_operations::create_project(&conn, name, description, color, user_id).map_err(|e| e.to_string())
+        let conn = db_manager_clone.get_connection()?;
+        operations::project_operations::create_project(&conn, name, description, color, user_id)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e| e)?;
+    .await??;
 
     Ok(project_id.to_string())
 }
@@ -128,15 +127,13 @@ pub async fn create_project(
 pub async fn get_projects(
     user_id: String,
     db_manager: tauri::State<'_, std::sync::Arc<crate::database::DatabaseManager>>,
-) -> Result<Vec<ProjectApi>, String> {
+) -> CommandResult<Vec<ProjectApi>> {
     let db_manager_clone = db_manager.inner().clone();
     let projects = tokio::task::spawn_blocking(move || {
         let conn = db_manager_clone.get_connection()?;
         operations::project_operations::get_projects_by_user(&conn, &user_id)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
 
     // Convert to API format
     let projects_api: Vec<ProjectApi> = projects.into_iter().map(ProjectApi::from).collect();
@@ -147,7 +144,7 @@ pub async fn get_projects(
 pub async fn get_project(
     project_id: String,
     db_manager: tauri::State<'_, std::sync::Arc<crate::database::DatabaseManager>>,
-) -> Result<Option<ProjectApi>, String> {
+) -> CommandResult<Option<ProjectApi>> {
     let project_id: i32 = project_id.parse().map_err(|_| "Invalid project ID")?;
     
     let db_manager_clone = db_manager.inner().clone();
@@ -155,9 +152,7 @@ pub async fn get_project(
         let conn = db_manager_clone.get_connection()?;
         operations::project_operations::get_project_by_id(&conn, project_id)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
 
     Ok(project.map(ProjectApi::from))
 }
@@ -172,7 +167,7 @@ pub async fn update_project(
     progress: Option<i32>,
     priority: Option<String>,
     db_manager: tauri::State<'_, std::sync::Arc<crate::database::DatabaseManager>>,
-) -> Result<bool, String> {
+) -> CommandResult<bool> {
     let project_id: i32 = project_id.parse().map_err(|_| "Invalid project ID")?;
     
     let db_manager_clone = db_manager.inner().clone();
@@ -182,9 +177,7 @@ pub async fn update_project(
             &conn, project_id, name, description, color, status, progress, priority
         )
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
 
     Ok(success)
 }
@@ -193,7 +186,7 @@ pub async fn update_project(
 pub async fn delete_project(
     project_id: String,
     db_manager: tauri::State<'_, std::sync::Arc<crate::database::DatabaseManager>>,
-) -> Result<bool, String> {
+) -> CommandResult<bool> {
     let project_id: i32 = project_id.parse().map_err(|_| "Invalid project ID")?;
     
     let db_manager_clone = db_manager.inner().clone();
@@ -201,9 +194,7 @@ pub async fn delete_project(
         let conn = db_manager_clone.get_connection()?;
         operations::project_operations::delete_project(&conn, project_id)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
 
     Ok(success)
 }
@@ -218,7 +209,7 @@ pub async fn create_project_goal(
     title: String,
     priority: String,
     db_manager: tauri::State<'_, std::sync::Arc<crate::database::DatabaseManager>>,
-) -> Result<String, String> {
+) -> CommandResult<String> {
     let project_id: i32 = project_id.parse().map_err(|_| "Invalid project ID")?;
     
     let db_manager_clone = db_manager.inner().clone();
@@ -226,9 +217,7 @@ pub async fn create_project_goal(
         let conn = db_manager_clone.get_connection()?;
         operations::project_operations::create_project_goal(&conn, project_id, title, priority)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
 
     Ok(goal_id.to_string())
 }
@@ -237,7 +226,7 @@ pub async fn create_project_goal(
 pub async fn get_project_goals(
     project_id: String,
     db_manager: tauri::State<'_, std::sync::Arc<crate::database::DatabaseManager>>,
-) -> Result<Vec<ProjectGoalApi>, String> {
+) -> CommandResult<Vec<ProjectGoalApi>> {
     let project_id: i32 = project_id.parse().map_err(|_| "Invalid project ID")?;
     
     let db_manager_clone = db_manager.inner().clone();
@@ -245,9 +234,7 @@ pub async fn get_project_goals(
         let conn = db_manager_clone.get_connection()?;
         operations::project_operations::get_project_goals(&conn, project_id)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
 
     // Convert to API format
     let goals_api: Vec<ProjectGoalApi> = goals.into_iter().map(ProjectGoalApi::from).collect();
@@ -262,7 +249,7 @@ pub async fn update_project_goal(
     completed: Option<bool>,
     priority: Option<String>,
     db_manager: tauri::State<'_, std::sync::Arc<crate::database::DatabaseManager>>,
-) -> Result<bool, String> {
+) -> CommandResult<bool> {
     let goal_id: i32 = goal_id.parse().map_err(|_| "Invalid goal ID")?;
     
     let db_manager_clone = db_manager.inner().clone();
@@ -272,9 +259,7 @@ pub async fn update_project_goal(
             &conn, goal_id, title.as_deref(), description.as_deref(), completed, priority.as_deref()
         )
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
 
     Ok(success)
 }
@@ -283,7 +268,7 @@ pub async fn update_project_goal(
 pub async fn delete_project_goal(
     goal_id: String,
     db_manager: tauri::State<'_, std::sync::Arc<crate::database::DatabaseManager>>,
-) -> Result<bool, String> {
+) -> CommandResult<bool> {
     let goal_id: i32 = goal_id.parse().map_err(|_| "Invalid goal ID")?;
     
     let db_manager_clone = db_manager.inner().clone();
@@ -291,9 +276,7 @@ pub async fn delete_project_goal(
         let conn = db_manager_clone.get_connection()?;
         operations::project_operations::delete_project_goal(&conn, goal_id)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
 
     Ok(success)
 }
@@ -312,7 +295,7 @@ pub async fn create_project_asset(
     size: Option<i64>,
     metadata: Option<String>,
     db_manager: tauri::State<'_, std::sync::Arc<crate::database::DatabaseManager>>,
-) -> Result<String, String> {
+) -> CommandResult<String> {
     let project_id: i32 = project_id.parse().map_err(|_| "Invalid project ID")?;
     
     let db_manager_clone = db_manager.inner().clone();
@@ -322,9 +305,7 @@ pub async fn create_project_asset(
             &conn, project_id, name, asset_type, url, uploaded_by, size, metadata
         )
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
 
     Ok(asset_id.to_string())
 }
@@ -333,7 +314,7 @@ pub async fn create_project_asset(
 pub async fn get_project_assets(
     project_id: String,
     db_manager: tauri::State<'_, std::sync::Arc<crate::database::DatabaseManager>>,
-) -> Result<Vec<ProjectAssetApi>, String> {
+) -> CommandResult<Vec<ProjectAssetApi>> {
     let project_id: i32 = project_id.parse().map_err(|_| "Invalid project ID")?;
     
     let db_manager_clone = db_manager.inner().clone();
@@ -341,9 +322,7 @@ pub async fn get_project_assets(
         let conn = db_manager_clone.get_connection()?;
         operations::project_operations::get_project_assets(&conn, project_id)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
 
     // Convert to API format
     let assets_api: Vec<ProjectAssetApi> = assets.into_iter().map(ProjectAssetApi::from).collect();
@@ -354,7 +333,7 @@ pub async fn get_project_assets(
 pub async fn delete_project_asset(
     asset_id: String,
     db_manager: tauri::State<'_, std::sync::Arc<crate::database::DatabaseManager>>,
-) -> Result<bool, String> {
+) -> CommandResult<bool> {
     let asset_id: i32 = asset_id.parse().map_err(|_| "Invalid asset ID")?;
     
     let db_manager_clone = db_manager.inner().clone();
@@ -362,9 +341,7 @@ pub async fn delete_project_asset(
         let conn = db_manager_clone.get_connection()?;
         operations::project_operations::delete_project_asset(&conn, asset_id)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
 
     Ok(success)
 }
@@ -377,7 +354,7 @@ pub async fn delete_project_asset(
 pub async fn get_project_stats(
     project_id: String,
     db_manager: tauri::State<'_, std::sync::Arc<crate::database::DatabaseManager>>,
-) -> Result<serde_json::Value, String> {
+) -> CommandResult<serde_json::Value> {
     let project_id: i32 = project_id.parse().map_err(|_| "Invalid project ID")?;
     
     let db_manager_clone = db_manager.inner().clone();
@@ -385,9 +362,7 @@ pub async fn get_project_stats(
         let conn = db_manager_clone.get_connection()?;
         operations::project_operations::get_project_stats(&conn, project_id)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
 
     Ok(stats)
 } 
\ No newline at end of file
@@ -0,0 +1,102 @@
+//! Prompt Template Commands
+//!
+//! Manage the prompt template library, its version history and the
+//! default template of each feature, and preview a template against a
+//! sample message before saving it.
+
+use serde_json::{Map, Value};
+use tauri::State;
+
+use crate::database::operations::prompt_template_operations::TemplateVersion;
+use crate::services::prompt_templates::{
+    self, FeatureDefault, PromptPreview, PromptTemplate, SampleMessage, TemplateInput,
+};
+use crate::services::service_registry::ServiceRegistry;
+use crate::errors::CommandResult;
+
+/// Templates, optionally only those of one category
+#[tauri::command]
+pub async fn list_prompt_templates(
+    category: Option<String>,
+    registry: State<'_, ServiceRegistry>,
+) -> CommandResult<Vec<PromptTemplate>> {
+    Ok(registry.prompt_templates().await?.list(category.as_deref())?)
+}
+
+#[tauri::command]
+pub async fn get_prompt_template(id: i64, registry: State<'_, ServiceRegistry>) -> CommandResult<PromptTemplate> {
+    Ok(registry.prompt_templates().await?.get(id)?)
+}
+
+#[tauri::command]
+pub async fn create_prompt_template(
+    template: TemplateInput,
+    registry: State<'_, ServiceRegistry>,
+) -> CommandResult<PromptTemplate> {
+    Ok(registry.prompt_templates().await?.create(&template)?)
+}
+
+/// Save changes; new content or variables become a new version
+#[tauri::command]
+pub async fn update_prompt_template(
+    id: i64,
+    template: TemplateInput,
+    registry: State<'_, ServiceRegistry>,
+) -> CommandResult<PromptTemplate> {
+    Ok(registry.prompt_templates().await?.update(id, &template)?)
+}
+
+#[tauri::command]
+pub async fn delete_prompt_template(id: i64, registry: State<'_, ServiceRegistry>) -> CommandResult<bool> {
+    Ok(registry.prompt_templates().await?.delete(id)?)
+}
+
+/// Earlier versions of a template, newest first
+#[tauri::command]
+pub async fn get_prompt_template_versions(
+    id: i64,
+    registry: State<'_, ServiceRegistry>,
+) -> CommandResult<Vec<TemplateVersion>> {
+    Ok(registry.prompt_templates().await?.versions(id)?)
+}
+
+#[tauri::command]
+pub async fn restore_prompt_template_version(
+    id: i64,
+    version: i64,
+    registry: State<'_, ServiceRegistry>,
+) -> CommandResult<PromptTemplate> {
+    Ok(registry.prompt_templates().await?.restore(id, version)?)
+}
+
+/// Every feature that uses templates and its current default
+#[tauri::command]
+pub async fn get_prompt_template_defaults(
+    registry: State<'_, ServiceRegistry>,
+) -> CommandResult<Vec<FeatureDefault>> {
+    Ok(registry.prompt_templates().await?.defaults()?)
+}
+
+/// Set the default template for a feature, or clear it with no template
+#[tauri::command]
+pub async fn set_prompt_template_default(
+    feature: String,
+    template_id: Option<i64>,
+    registry: State<'_, ServiceRegistry>,
+) -> CommandResult<Vec<FeatureDefault>> {
+    let service = registry.prompt_templates().await?;
+    service.set_default(&feature, template_id)?;
+    Ok(service.defaults()?)
+}
+
+/// Render an unsaved template. Message variables (`subject`, `sender`,
+/// `recipients`, `date`, `body`) come from `sample_message`, or a built-in
+/// sample, unless `values` sets them.
+#[tauri::command]
+pub async fn preview_prompt_template(
+    template: TemplateInput,
+    values: Option<Map<String, Value>>,
+    sample_message: Option<SampleMessage>,
+) -> CommandResult<PromptPreview> {
+    Ok(prompt_templates::preview(&template, &values.unwrap_or_default(), &sample_message.unwrap_or_default())?)
+}
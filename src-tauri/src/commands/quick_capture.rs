@@ -0,0 +1,383 @@
+//! Quick Capture Commands
+//!
+//! One entry point for the global capture window: classify a line of text
+//! and create the task, note, calendar event or email draft it describes.
+//! The clipboard watcher's captures are listed and toggled here too.
+
+use anyhow::Context;
+use chrono::{Duration, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+
+use crate::commands::calendar::{create_calendar_event, GoogleCalendarEvent};
+use crate::commands::notes::{create_note, NoteResponse};
+use crate::commands::tasks::sync_fixed::{create_google_task, CreateTaskRequest, TaskResponse};
+use crate::database::operations::clipboard_capture_operations::{self, ClipboardCaptureRecord, MAX_CLIPBOARD_CAPTURES};
+use crate::database::DatabaseManager;
+use crate::models::task_metadata::TimeBlock;
+use crate::services::availability::{self, AvailabilityConflict, AvailabilityPurpose};
+use crate::services::clipboard_watcher::{self, ClipboardPattern, CLIPBOARD_PATTERNS_PREFERENCE};
+use crate::services::events::EventBus;
+use crate::services::gmail::api_service::EmailAddress;
+use crate::services::gmail::auth_service::GmailAuthService;
+use crate::services::gmail::compose_service::{ComposeRequest, DraftResponse, DraftSaveRequest, GmailComposeService, MessageImportance};
+use crate::services::google::tasks_service::GoogleTasksService;
+use crate::services::quick_capture::{self, CaptureIntent, CaptureKind, LLM_CONFIDENCE_THRESHOLD};
+use crate::services::settings::{self, SettingsService};
+use crate::services::time_service::TimeService;
+use crate::utils::time;
+use crate::errors::{CommandError, CommandResult, LibreOllamaError};
+
+/// Google Tasks alias for the user's default list
+const DEFAULT_TASK_LIST: &str = "@default";
+/// Owner recorded on captured notes, matching the notes UI
+const DEFAULT_NOTE_USER: &str = "default_user";
+const CAPTURED_STATUS: &str = "captured";
+const DISMISSED_STATUS: &str = "dismissed";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct QuickCaptureRequest {
+    pub text: String,
+    /// Google account for tasks, events and drafts; notes need none
+    pub account_id: Option<String>,
+    /// Ask the local model when the heuristics are unsure
+    #[serde(default)]
+    pub use_llm: bool,
+    /// Skip classification and create this kind of item
+    pub kind: Option<CaptureKind>,
+    pub task_list_id: Option<String>,
+    pub calendar_id: Option<String>,
+    /// Clipboard capture this accepts, marked captured once created
+    pub clipboard_capture_id: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", content = "item", rename_all = "snake_case")]
+pub enum CapturedItem {
+    Task(TaskResponse),
+    Note(NoteResponse),
+    Event(Box<GoogleCalendarEvent>),
+    EmailDraft(DraftResponse),
+}
+
+#[derive(Debug, Serialize)]
+pub struct QuickCaptureResult {
+    pub intent: CaptureIntent,
+    pub created: CapturedItem,
+    /// How a captured time block or timed event goes against the
+    /// availability profile
+    pub warnings: Vec<AvailabilityConflict>,
+}
+
+fn clock(db_manager: &DatabaseManager) -> CommandResult<TimeService> {
+    Ok(TimeService::from_db(db_manager).context("Failed to read the time zone")?)
+}
+
+async fn classify_text(text: &str, use_llm: bool, db_manager: &Arc<DatabaseManager>) -> CommandResult<CaptureIntent> {
+    let now = clock(db_manager)?.now().naive_local();
+    let intent = quick_capture::classify(text, now);
+    if !use_llm || intent.confidence >= LLM_CONFIDENCE_THRESHOLD {
+        return Ok(intent);
+    }
+
+    match quick_capture::classify_with_llm(db_manager, text, &intent, now).await {
+        Ok(refined) => Ok(refined),
+        Err(e) => {
+            eprintln!("⚠️ [QUICK-CAPTURE] Model classification failed, keeping heuristics: {}", e);
+            Ok(intent)
+        }
+    }
+}
+
+fn local_rfc3339(clock: &TimeService, at: NaiveDateTime) -> CommandResult<String> {
+    time::local_to_utc(at, &clock.zone())
+        .map(|t| clock.to_local(t).to_rfc3339())
+        .ok_or_else(|| {
+            LibreOllamaError::InvalidInput { message: format!("{} does not exist in {}", at, clock.zone()), field: None }.into()
+        })
+}
+
+fn require_account(account_id: Option<String>, kind: &str) -> CommandResult<String> {
+    account_id
+        .filter(|id| !id.is_empty())
+        .ok_or_else(|| {
+            LibreOllamaError::InvalidInput {
+                message: format!("Capturing {} needs a Google account", kind),
+                field: Some("account_id".to_string()),
+            }
+            .into()
+        })
+}
+
+/// Conflicts of the captured date and time with the availability profile;
+/// nothing when no time was captured
+fn availability_warnings(
+    db_manager: &DatabaseManager,
+    clock: &TimeService,
+    intent: &CaptureIntent,
+    purpose: AvailabilityPurpose,
+) -> Vec<AvailabilityConflict> {
+    let (Some(date), Some(at)) = (intent.date, intent.time) else {
+        return Vec::new();
+    };
+    let start = date.and_time(at);
+    let zone = clock.zone();
+    let (Some(start), Some(end)) = (
+        time::local_to_utc(start, &zone),
+        time::local_to_utc(start + Duration::minutes(intent.duration_minutes), &zone),
+    ) else {
+        return Vec::new();
+    };
+    let profile = db_manager
+        .get_connection()
+        .map_err(CommandError::from)
+        .and_then(|conn| availability::load_profile(&conn).map_err(CommandError::from));
+    match profile {
+        Ok(profile) => profile.conflicts(&zone, start, end, purpose),
+        Err(e) => {
+            eprintln!("⚠️ [QUICK-CAPTURE] Availability check skipped: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Build the event body sent to Google: timed when a time was captured,
+/// otherwise all day
+fn capture_event(intent: &CaptureIntent, clock: &TimeService) -> CommandResult<GoogleCalendarEvent> {
+    let date = intent.date.unwrap_or_else(|| clock.today());
+    let zone = clock.zone().name();
+    let (start, end) = match intent.time {
+        Some(time) => {
+            let start = date.and_time(time);
+            let end = start + Duration::minutes(intent.duration_minutes);
+            (
+                serde_json::json!({ "dateTime": local_rfc3339(clock, start)?, "timeZone": zone }),
+                serde_json::json!({ "dateTime": local_rfc3339(clock, end)?, "timeZone": zone }),
+            )
+        }
+        None => (
+            serde_json::json!({ "date": date.format("%Y-%m-%d").to_string() }),
+            serde_json::json!({ "date": (date + Duration::days(1)).format("%Y-%m-%d").to_string() }),
+        ),
+    };
+    Ok(serde_json::from_value(serde_json::json!({
+        "id": "",
+        "summary": intent.title,
+        "description": intent.body,
+        "start": start,
+        "end": end,
+    }))
+    .context("Failed to build event")?)
+}
+
+/// Classify captured text without creating anything, for previews
+#[tauri::command]
+pub async fn classify_capture(
+    text: String,
+    use_llm: Option<bool>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<CaptureIntent> {
+    if text.trim().is_empty() {
+        return Err("Nothing to capture".into());
+    }
+    classify_text(&text, use_llm.unwrap_or(false), &db_manager).await
+}
+
+/// Classify captured text and create what it describes
+#[tauri::command]
+pub async fn quick_capture(
+    request: QuickCaptureRequest,
+    app: AppHandle,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    tasks_service: State<'_, GoogleTasksService>,
+    auth_service: State<'_, Arc<GmailAuthService>>,
+    compose_service: State<'_, Arc<GmailComposeService>>,
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<QuickCaptureResult> {
+    if request.text.trim().is_empty() {
+        return Err("Nothing to capture".into());
+    }
+
+    let db = db_manager.inner().clone();
+    let clock = clock(&db)?;
+    let mut intent = classify_text(&request.text, request.use_llm, &db_manager).await?;
+    if let Some(kind) = request.kind {
+        intent.kind = kind;
+    }
+    println!(
+        "⚡ [QUICK-CAPTURE] Capturing {:?} '{}' ({:?}, confidence {:.2})",
+        intent.kind, intent.title, intent.classified_by, intent.confidence
+    );
+
+    let mut warnings = Vec::new();
+    let created = match intent.kind {
+        CaptureKind::Note => {
+            let content = intent.body.clone().unwrap_or_else(|| intent.title.clone());
+            let note = create_note(intent.title.clone(), content, None, DEFAULT_NOTE_USER.to_string(), db_manager).await?;
+            CapturedItem::Note(note)
+        }
+        CaptureKind::Task => {
+            let account_id = require_account(request.account_id, "a task")?;
+            let due = intent.date.map(|date| format!("{}T00:00:00.000Z", date.format("%Y-%m-%d")));
+            let time_block = match (intent.date, intent.time) {
+                (Some(date), Some(time)) => {
+                    let start = date.and_time(time);
+                    Some(TimeBlock {
+                        start_time: local_rfc3339(&clock, start)?,
+                        end_time: local_rfc3339(&clock, start + Duration::minutes(intent.duration_minutes))?,
+                        time_zone: Some(clock.zone().name().to_string()),
+                    })
+                }
+                _ => None,
+            };
+            let task = create_google_task(
+                CreateTaskRequest {
+                    account_id,
+                    task_list_id: request.task_list_id.unwrap_or_else(|| DEFAULT_TASK_LIST.to_string()),
+                    title: intent.title.clone(),
+                    notes: intent.body.clone(),
+                    due,
+                    priority: None,
+                    labels: None,
+                    time_block,
+                },
+                tasks_service,
+                db_manager,
+                event_bus,
+            )
+            .await?;
+            warnings = availability_warnings(&db, &clock, &intent, AvailabilityPurpose::Focus);
+            CapturedItem::Task(task)
+        }
+        CaptureKind::Event => {
+            let account_id = require_account(request.account_id, "an event")?;
+            let event = create_calendar_event(
+                account_id,
+                request.calendar_id.unwrap_or_else(|| "primary".to_string()),
+                capture_event(&intent, &clock)?,
+                app,
+                auth_service,
+                db_manager,
+                event_bus,
+            )
+            .await?;
+            warnings = availability_warnings(&db, &clock, &intent, AvailabilityPurpose::Meeting);
+            CapturedItem::Event(Box::new(event))
+        }
+        CaptureKind::EmailDraft => {
+            let account_id = require_account(request.account_id, "an email draft")?;
+            let draft = compose_service
+                .save_draft(&DraftSaveRequest {
+                    account_id: account_id.clone(),
+                    draft_id: None,
+                    compose_data: ComposeRequest {
+                        account_id,
+                        to: intent
+                            .recipients
+                            .iter()
+                            .map(|email| EmailAddress { email: email.clone(), name: None })
+                            .collect(),
+                        cc: None,
+                        bcc: None,
+                        subject: intent.title.clone(),
+                        body_text: intent.body.clone(),
+                        body_html: None,
+                        attachments: None,
+                        reply_to_message_id: None,
+                        in_reply_to: None,
+                        references: None,
+                        thread_id: None,
+                        importance: MessageImportance::Normal,
+                        delivery_receipt: false,
+                        read_receipt: false,
+                        schedule_send: None,
+                        from_alias: None,
+                    },
+                })
+                .await?;
+            CapturedItem::EmailDraft(draft)
+        }
+    };
+
+    if let Some(capture_id) = request.clipboard_capture_id {
+        let conn = db.get_connection().context("Failed to get database connection")?;
+        clipboard_capture_operations::set_status(&conn, capture_id, CAPTURED_STATUS)
+            .context("Failed to update clipboard capture")?;
+    }
+
+    Ok(QuickCaptureResult { intent, created, warnings })
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClipboardWatchStatus {
+    pub enabled: bool,
+    pub patterns: Vec<ClipboardPattern>,
+}
+
+fn clipboard_watch_status(db_manager: &DatabaseManager) -> CommandResult<ClipboardWatchStatus> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(ClipboardWatchStatus {
+        enabled: settings::get_bool(&conn, settings::CLIPBOARD_WATCH_SETTING)?,
+        patterns: clipboard_watcher::load_patterns(&conn)?,
+    })
+}
+
+/// Whether the clipboard watcher is on, and the patterns it looks for
+#[tauri::command]
+pub async fn get_clipboard_watch_status(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<ClipboardWatchStatus> {
+    clipboard_watch_status(&db_manager)
+}
+
+/// Start offering copied links, addresses and pattern matches for capture,
+/// optionally replacing the patterns
+#[tauri::command]
+pub async fn enable_clipboard_watch(
+    patterns: Option<Vec<ClipboardPattern>>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    settings_service: State<'_, SettingsService>,
+) -> CommandResult<ClipboardWatchStatus> {
+    if let Some(patterns) = patterns {
+        let value = serde_json::to_value(&patterns)?;
+        settings_service.set(CLIPBOARD_PATTERNS_PREFERENCE, &value)?;
+    }
+    settings_service.set(settings::CLIPBOARD_WATCH_SETTING, &Value::from(true))?;
+    clipboard_watch_status(&db_manager)
+}
+
+#[tauri::command]
+pub async fn disable_clipboard_watch(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    settings_service: State<'_, SettingsService>,
+) -> CommandResult<ClipboardWatchStatus> {
+    settings_service.set(settings::CLIPBOARD_WATCH_SETTING, &Value::from(false))?;
+    clipboard_watch_status(&db_manager)
+}
+
+/// Captures the watcher has offered, newest first
+#[tauri::command]
+pub async fn get_recent_clipboard_captures(
+    limit: Option<i64>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<ClipboardCaptureRecord>> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(clipboard_capture_operations::list_recent(&conn, limit.unwrap_or(50).clamp(1, MAX_CLIPBOARD_CAPTURES))
+        .context("Failed to list clipboard captures")?)
+}
+
+/// Decline an offered capture; it stays in the list as dismissed
+#[tauri::command]
+pub async fn dismiss_clipboard_capture(id: i64, db_manager: State<'_, Arc<DatabaseManager>>) -> CommandResult<bool> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(clipboard_capture_operations::set_status(&conn, id, DISMISSED_STATUS)
+        .context("Failed to dismiss clipboard capture")?)
+}
+
+#[tauri::command]
+pub async fn clear_clipboard_captures(db_manager: State<'_, Arc<DatabaseManager>>) -> CommandResult<usize> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(clipboard_capture_operations::clear_captures(&conn).context("Failed to clear clipboard captures")?)
+}
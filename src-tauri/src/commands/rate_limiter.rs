@@ -1,7 +1,8 @@
 //! Rate Limiter Commands
 //!
-//! This module provides rate limiting functionality for API requests.
-//! Currently unused but structures kept for future development.
+//! Rate limiting for Google API requests. Every request waits for a slot
+//! from the shared `RequestScheduler` before it is sent; the scheduler's
+//! live queue metrics are exposed here.
 
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
@@ -10,6 +11,12 @@ use std::time::Instant;
 use anyhow::Result;
 use reqwest::Client;
 use std::time::Duration;
+use tauri::State;
+
+use crate::errors::{LibreOllamaError, CommandResult};
+use crate::services::google_backoff;
+use crate::services::request_scheduler::{self, RequestScheduler, SchedulerMetrics};
+use crate::utils::http_client::{http_client, TraceRequest};
 
 // =============================================================================
 // Configuration Structures
@@ -27,6 +34,13 @@ pub struct RateLimitConfig {
     pub queue_timeout_minutes: u32,
     pub enable_batching: bool,
     pub enable_adaptive_rate_limiting: bool,
+    /// Share of the per-minute budget background requests may not use
+    #[serde(default = "default_interactive_reserve")]
+    pub interactive_reserve_fraction: f64,
+}
+
+fn default_interactive_reserve() -> f64 {
+    0.5
 }
 
 impl Default for RateLimitConfig {
@@ -42,6 +56,7 @@ impl Default for RateLimitConfig {
             queue_timeout_minutes: 5,
             enable_batching: true,
             enable_adaptive_rate_limiting: true,
+            interactive_reserve_fraction: default_interactive_reserve(),
         }
     }
 }
@@ -62,6 +77,9 @@ pub struct ApiQuotaStatus {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BatchRequest {
     pub id: String,
+    /// Account the request is made for; throttling backs off per account
+    #[serde(default)]
+    pub account_id: Option<String>,
     pub method: String,
     pub url: String,
     pub headers: HashMap<String, String>,
@@ -110,17 +128,20 @@ pub struct RequestQueue {
     pub request_times: VecDeque<Instant>,
 }
 
+#[derive(Clone)]
 pub struct RateLimiter {
     pub config: RateLimitConfig,
     pub quota_status: ApiQuotaStatus,
     pub request_queue: Arc<Mutex<RequestQueue>>,
     pub adaptive_delay: u64,
     pub client: Client,
+    pub scheduler: Arc<RequestScheduler>,
 }
 
 impl RateLimiter {
     pub fn new(config: RateLimitConfig) -> Self {
         RateLimiter {
+            scheduler: Arc::new(RequestScheduler::new(&config)),
             quota_status: ApiQuotaStatus {
                 daily_quota_used: 0,
                 daily_quota_limit: 1000000000,
@@ -140,17 +161,13 @@ impl RateLimiter {
             })),
             adaptive_delay: 0,
             config,
-            client: Client::new(),
+            client: http_client(),
         }
     }
 
-    pub async fn execute_request(&mut self, request: BatchRequest) -> Result<BatchResponse> {
+    /// Send `request` now; callers wait for a slot from the scheduler first
+    pub async fn execute_request(&self, request: BatchRequest) -> Result<BatchResponse> {
         let start_time = Instant::now();
-        
-        // Apply rate limiting delay if needed
-        if let Some(delay) = self.calculate_rate_limit_delay() {
-            tokio::time::sleep(Duration::from_millis(delay)).await;
-        }
 
         // Build the HTTP request
         let mut req_builder = match request.method.as_str() {
@@ -171,6 +188,7 @@ impl RateLimiter {
         if let Some(body) = &request.body {
             req_builder = req_builder.body(body.clone());
         }
+        req_builder = req_builder.traced();
 
         // Execute the request
         let response = req_builder.send().await;
@@ -197,7 +215,7 @@ impl RateLimiter {
                     
                     // Keep only requests from the last minute for rate limiting
                     let cutoff = Instant::now() - Duration::from_secs(60);
-                    while queue.request_times.front().map_or(false, |&t| t < cutoff) {
+                    while queue.request_times.front().is_some_and(|&t| t < cutoff) {
                         queue.request_times.pop_front();
                     }
                 }
@@ -232,18 +250,77 @@ impl RateLimiter {
         }
     }
 
-    fn calculate_rate_limit_delay(&self) -> Option<u64> {
+    /// Share a scheduler, e.g. one that persists usage
+    pub fn with_scheduler(mut self, scheduler: Arc<RequestScheduler>) -> Self {
+        self.scheduler = scheduler;
+        self
+    }
+
+    /// Requests sent in the last minute
+    pub fn requests_in_last_minute(&self) -> usize {
         let queue = self.request_queue.lock().unwrap();
-        let recent_requests = queue.request_times.len();
-        
-        if recent_requests >= self.config.requests_per_minute as usize {
-            // If we're at the rate limit, wait a bit
-            Some(self.config.initial_backoff_ms)
-        } else {
-            None
+        let cutoff = Instant::now() - Duration::from_secs(60);
+        queue.request_times.iter().filter(|&&t| t >= cutoff).count()
+    }
+}
+
+/// Wait for a slot from the scheduler, then send. The scheduler alone
+/// decides when a request may go, so requests waiting for a slot never
+/// hold up higher-priority ones.
+///
+/// Responses Google wants retried (429, rate limit 403s, backendError)
+/// put the whole account into backoff, so concurrent requests for it wait
+/// too, and are retried up to `max_retries` times when that is safe (see
+/// `google_backoff::retry_is_safe`). After that the result is a
+/// `RateLimit` error carrying the backoff still to wait.
+pub async fn send_scheduled(limiter: &RateLimiter, mut request: BatchRequest) -> Result<BatchResponse> {
+    let priority = request_scheduler::effective_priority(&request.priority);
+    let account_id = request.account_id.clone();
+
+    loop {
+        limiter.scheduler.acquire(account_id.as_deref(), priority.clone()).await;
+        let response = limiter.execute_request(request.clone()).await?;
+
+        let Some(throttle) = google_backoff::throttle_for(response.status_code, &response.headers, &response.body) else {
+            if (200..300).contains(&response.status_code) {
+                limiter.scheduler.succeeded(account_id.as_deref());
+            }
+            return Ok(response);
+        };
+
+        let delay = limiter.scheduler.throttled(account_id.as_deref(), throttle.retry_after);
+        if request.current_retry >= request.max_retries || !google_backoff::retry_is_safe(&request.method, &throttle) {
+            return Err(throttle.into_error(delay).into());
         }
+        request.current_retry += 1;
+        eprintln!(
+            "⏳ [RATE-LIMIT] {} {} for {}, retry {}/{} in {}ms",
+            throttle.status_code,
+            throttle.reason,
+            account_id.as_deref().unwrap_or("app"),
+            request.current_retry,
+            request.max_retries,
+            delay.as_millis()
+        );
+    }
+}
+
+/// The error for a failed scheduled send: rate limiting as reported,
+/// anything else as a network failure
+pub fn request_error(error: anyhow::Error, message: &str, url: &str) -> LibreOllamaError {
+    match error.downcast::<LibreOllamaError>() {
+        Ok(error) => error,
+        Err(error) => LibreOllamaError::Network {
+            message: format!("{}: {}", message, error),
+            url: Some(url.to_string()),
+        },
     }
 }
 
-// Note: All command functions have been removed as they are unused.
-// The structures above are kept for potential future use.
\ No newline at end of file
+/// Requests waiting per priority and usage of the rolling minute and day
+#[tauri::command]
+pub async fn get_request_scheduler_metrics(
+    scheduler: State<'_, Arc<RequestScheduler>>,
+) -> CommandResult<SchedulerMetrics> {
+    Ok(scheduler.metrics())
+}
\ No newline at end of file
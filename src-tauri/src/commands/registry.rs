@@ -0,0 +1,749 @@
+//! Command Registry
+//!
+//! Metadata for every command the frontend can invoke, so it can build a
+//! command palette and validate calls before making them. The invoke
+//! handler is generated from the same entries: adding a command here is
+//! what registers it. Tests fail when a `#[tauri::command]` has no entry
+//! or when an entry's parameters differ from the function's.
+//!
+//! Permissions describe what a command touches:
+//! - `accounts`, `secrets`: linked accounts and stored credentials
+//! - `mail.read`, `mail.write`, `mail.send`: Gmail
+//! - `tasks.read`, `tasks.write`, `calendar.read`, `calendar.write`,
+//!   `contacts.read`, `drive.read`: the other Google APIs
+//! - `local.read`, `local.write`: the local database and files
+//! - `llm`: runs a model; `network`: other outbound requests
+//! - `system`: processes, migrations and other app internals
+
+use serde::Serialize;
+use crate::errors::CommandResult;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ParameterMetadata {
+    pub name: &'static str,
+    pub type_name: &'static str,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CommandMetadata {
+    pub name: &'static str,
+    pub category: &'static str,
+    pub description: &'static str,
+    pub parameters: &'static [ParameterMetadata],
+    pub permissions: &'static [&'static str],
+}
+
+command_registry! {
+    "system" {
+        commands::registry::get_command_registry [] "Describe every command the frontend can invoke, or only those of one category" (category: "Option<String>");
+        greet [] "Return a greeting; used to check the backend is reachable" (name: "&str");
+        commands::system::get_backend_status [] "Whether startup has finished migrating and starting background services" ();
+        commands::system::get_background_tasks ["system"] "Background tasks with their state, last run, next run and last error" ();
+        commands::system::report_user_activity [] "Record key or pointer input so background work waits until the user is idle" ();
+        commands::system::get_resource_usage ["system"] "Memory held by in-memory caches against their budgets" ();
+        get_google_client_id ["accounts"] "The configured Google OAuth client ID" ();
+        commands::system::force_run_migrations ["system"] "Run pending database migrations now" ();
+        commands::system::debug_check_timeblock_data ["system"] "Dump stored time blocks for debugging" ();
+        commands::system::subscribe_events [] "Return the schema of every backend event so the frontend can register listeners" ();
+        commands::system::get_config_status ["system"] "Where the configuration came from, when it was loaded and whether the latest load was rejected" ();
+        commands::system::reload_config ["system"] "Re-read the configuration sources and apply them to running services" ();
+        commands::system::request_secure_wipe ["system"] "Start a secure wipe; returns the confirmation token and what would be destroyed" ();
+        commands::system::secure_wipe ["system", "secrets"] "Shred the database, attachments, caches and logs, then quit" (token: "String", confirmation_phrase: "String");
+        commands::system::list_crash_reports ["system"] "Crash reports saved on this machine, newest first" ();
+        commands::system::get_crash_report ["system"] "A saved crash report with its backtrace" (report_id: "String");
+        commands::system::delete_crash_report ["system"] "Delete a saved crash report" (report_id: "String");
+        commands::system::package_crash_report ["system"] "Zip a sanitized crash report to attach to an issue" (report_id: "String");
+        commands::system::generate_diagnostics_bundle ["system"] "Write a sanitized diagnostics zip for a bug report" ();
+        commands::system::advanced::log_application_event ["local.write"] "Record a frontend log entry in the application log" (level: "String", message: "String", component: "Option<String>", context: "Option<String>");
+    }
+    "accounts" {
+        commands::gmail::auth::start_gmail_oauth_with_callback ["accounts"] "Start OAuth flow with automatic callback handling for Desktop applications" (extra_scopes: "Option<Vec<String>>", login_hint: "Option<String>");
+        commands::gmail::auth::get_gmail_accounts_secure ["accounts"] "Get all Gmail accounts for a user" (user_id: "String");
+        commands::gmail::auth::get_gmail_user_info ["accounts", "network"] "Get user information using access token (for initial auth flow)" (access_token: "String");
+        commands::gmail::auth::store_gmail_tokens_secure ["accounts", "secrets"] "Store Gmail tokens securely" (account_id: "String", tokens: "GmailTokens", user_info: "UserInfo");
+        commands::gmail::auth::get_gmail_tokens_secure ["accounts", "secrets"] "Get Gmail tokens for an account" (account_id: "String");
+        commands::gmail::auth::remove_gmail_account_secure ["accounts"] "Remove a Gmail account from the database" (account_id: "String");
+    }
+    "mail" {
+        commands::gmail::api::search_gmail_messages ["mail.read"] "Search Gmail messages with parsing" (account_id: "String", query: "Option<String>", label_ids: "Option<Vec<String>>", max_results: "Option<u32>", page_token: "Option<String>");
+        commands::gmail::api::get_gmail_labels ["mail.read"] "Get all labels for a Gmail account, from the label cache while it is fresh" (account_id: "String", force_refresh: "Option<bool>");
+        commands::gmail::api::set_gmail_label_override ["local.write"] "Set a label's display name and colours in the app only" (account_id: "String", label_id: "String", display_name: "Option<String>", text_color: "Option<String>", background_color: "Option<String>");
+        commands::gmail::api::clear_gmail_label_override ["local.write"] "Go back to a label's Gmail name and colours" (account_id: "String", label_id: "String");
+        commands::gmail::api::get_gmail_message ["mail.read"] "Get a specific Gmail message by ID" (account_id: "String", message_id: "String");
+        commands::gmail::api::get_parsed_gmail_message ["mail.read"] "Get a parsed Gmail message by ID" (account_id: "String", message_id: "String");
+        commands::gmail::api::get_gmail_thread ["mail.read"] "Get an entire Gmail thread with parsed messages" (account_id: "String", thread_id: "String");
+        commands::gmail::api::modify_gmail_messages ["mail.write"] "Modify labels for a batch of messages" (account_id: "String", message_ids: "Vec<String>", add_label_ids: "Vec<String>", remove_label_ids: "Vec<String>");
+        commands::gmail::api::trash_gmail_messages ["mail.write"] "Move a batch of messages to the trash" (account_id: "String", message_ids: "Vec<String>");
+        commands::gmail::api::apply_gmail_thread_action ["mail.write"] "Archive, label or mark read whole threads, with undo" (account_id: "String", thread_ids: "Vec<String>", action: "ThreadAction");
+        commands::gmail::api::undo_gmail_thread_action ["mail.write"] "Undo the latest thread action on each of the given threads" (account_id: "String", thread_ids: "Vec<String>");
+        commands::gmail::api::get_gmail_attachment ["mail.read"] "Download Gmail attachment data" (account_id: "String", message_id: "String", attachment_id: "String");
+        commands::gmail::attachments::open_attachment ["mail.read", "system"] "Download an attachment, scan it with a local virus scanner and open it; dangerous-looking or flagged files open only with allow_flagged" (account_id: "String", message_id: "String", attachment_id: "String", filename: "String", allow_flagged: "Option<bool>");
+        commands::gmail::attachments::get_attachment_preview ["mail.read"] "Thumbnail of an image or PDF attachment, or an excerpt of a document's text" (account_id: "String", message_id: "String", attachment_id: "String", filename: "String", content_type: "String");
+        commands::gmail::attachments::list_attachment_scan_verdicts ["local.read"] "List virus scan verdicts for the attachments of a message" (account_id: "String", message_id: "String");
+        commands::gmail::attachments::get_attachment_scanner ["local.read"] "Name the virus scanner attachments are checked with, if one is installed" ();
+        commands::gmail::compose::send_gmail_message ["mail.send"] "Send an email message; held back with attachment_missing when it mentions an attachment it lacks, unless acknowledged" (compose_request: "ComposeRequest", autosave_key: "Option<String>", attachment_reminder_acknowledged: "Option<bool>");
+        commands::gmail::compose::validate_gmail_recipients ["network"] "Check recipients for invalid syntax, domains without mail servers, disposable services and likely typos" (compose_request: "ComposeRequest");
+        commands::gmail::compose::check_gmail_before_send ["network", "llm", "mail.read", "calendar.read"] "Soft warnings before sending: recipient problems, a mentioned but missing attachment and recipients who are out of office" (compose_request: "ComposeRequest");
+        commands::gmail::compose::check_recipients_away ["mail.read", "calendar.read"] "Recipients or attendees who seem to be away at a time, from their auto-replies and visible calendars" (account_id: "String", recipients: "Vec<EmailAddress>", at: "Option<String>");
+        commands::gmail::compose::save_gmail_draft ["mail.write"] "Save message as draft" (draft_request: "DraftSaveRequest");
+        commands::gmail::compose::list_gmail_drafts ["mail.read"] "List drafts for an account from the local cache, refreshed from Gmail when online" (account_id: "String");
+        commands::gmail::compose::get_gmail_draft ["mail.read"] "Get a single draft by Gmail draft ID or local ID" (account_id: "String", draft_id: "String");
+        commands::gmail::compose::update_gmail_draft ["mail.write"] "Replace the contents of an existing draft" (account_id: "String", draft_id: "String", compose_data: "ComposeRequest");
+        commands::gmail::compose::delete_gmail_draft ["mail.write"] "Delete a draft" (account_id: "String", draft_id: "String");
+        commands::gmail::compose::send_draft ["mail.send"] "Send an existing draft" (account_id: "String", draft_id: "String");
+        commands::gmail::compose::sync_gmail_drafts ["mail.write"] "Push drafts edited offline and refresh the draft cache" (account_id: "String");
+        commands::gmail::compose::build_reply ["mail.read"] "Prefill a reply, reply-all or forward to a message, with threading headers, the quoted original and, for forwards, its attachments" (account_id: "String", message_id: "String", mode: "ReplyType");
+        commands::gmail::compose::create_gmail_reply ["mail.read"] "Prefill a reply to a message, with any extra recipients added" (reply_request: "ReplyRequest");
+        commands::gmail::compose::get_gmail_templates ["local.read"] "List the account's saved message templates" (account_id: "String");
+        commands::gmail::compose::build_forward ["mail.read"] "Prefill a forward of a message, including its attachments" (account_id: "String", message_id: "String");
+        commands::gmail::campaigns::create_mail_campaign ["local.write"] "Create a campaign from a template and a recipient list" (campaign: "NewCampaign", recipients: "Option<Vec<CampaignRecipientInput>>", contact_ids: "Option<Vec<i64>>");
+        commands::gmail::campaigns::list_mail_campaigns ["local.read"] "List an account's mail campaigns" (account_id: "String");
+        commands::gmail::campaigns::get_campaign_recipients ["local.read"] "Recipients of a campaign with their send status" (campaign_id: "i64");
+        commands::gmail::campaigns::preview_mail_campaign ["local.read"] "Render the personalized message for each recipient (or the first `limit`)" (campaign_id: "i64", limit: "Option<usize>");
+        commands::gmail::campaigns::start_mail_campaign ["mail.send"] "Start (or resume) sending a campaign in the background" (campaign_id: "i64", retry_failed: "Option<bool>");
+        commands::gmail::campaigns::pause_mail_campaign ["local.write"] "Pause a running campaign" (campaign_id: "i64");
+        commands::gmail::campaigns::cancel_mail_campaign ["local.write"] "Cancel a campaign" (campaign_id: "i64");
+        commands::gmail::campaigns::get_campaign_progress ["local.read"] "A campaign's send progress" (campaign_id: "i64");
+        commands::gmail::receipts::get_message_receipt_status ["local.read"] "Delivery and read status for a sent message, if it is tracked" (account_id: "String", message_id: "String");
+        commands::gmail::receipts::list_tracked_messages ["local.read"] "Recently sent messages that requested receipts or carry a pixel" (account_id: "String", limit: "Option<i64>");
+        commands::gmail::receipts::sync_message_receipts ["mail.read"] "Scan the inbox for read receipts and delivery reports" (account_id: "String");
+        commands::gmail::receipts::get_tracking_pixel_settings ["local.read"] "Tracking pixel settings for an account" (account_id: "String");
+        commands::gmail::receipts::set_tracking_pixel_settings ["local.write"] "Enable or disable the tracking pixel" (account_id: "String", pixel_enabled: "bool", pixel_base_url: "Option<String>");
+        commands::gmail::receipts::record_tracking_pixel_open ["mail.write"] "Record a pixel hit reported by the self-hosted tracking server" (tracking_token: "String", opened_at: "Option<String>");
+        commands::gmail::send_as::list_send_as_aliases ["mail.read"] "List the addresses the account can send as, fetching them from Gmail settings the first time" (account_id: "String");
+        commands::gmail::send_as::refresh_send_as_aliases ["mail.read"] "Refetch the account's send-as addresses from Gmail settings" (account_id: "String");
+        commands::gmail::mute::mute_thread ["mail.write"] "Mute a thread and archive the messages of it currently in the inbox" (account_id: "String", thread_id: "String");
+        commands::gmail::mute::unmute_thread ["mail.write"] "Stop muting a thread" (account_id: "String", thread_id: "String");
+        commands::gmail::mute::list_muted_threads ["local.read"] "List muted threads" (account_id: "String");
+        commands::gmail::triage::start_triage_session ["mail.read", "local.write"] "Start a triage session over a label, the inbox by default, oldest message first" (account_id: "String", label: "Option<String>", limit: "Option<u32>");
+        commands::gmail::triage::process_triage_action ["mail.write", "tasks.write"] "Archive, label, make a task from, snooze or skip a message of a triage session" (session_id: "i64", message_id: "String", action: "TriageAction");
+        commands::gmail::triage::undo_triage_action ["mail.write", "tasks.write"] "Undo the latest action of a triage session" (session_id: "i64");
+        commands::gmail::triage::end_triage_session ["local.write"] "End a triage session and record its stats" (session_id: "i64");
+        commands::gmail::triage::list_triage_sessions ["local.read"] "Finished triage sessions with their stats, newest first" (account_id: "String", limit: "Option<u32>");
+        commands::gmail::export::export_thread ["mail.read", "system"] "Export a thread as a PDF or Markdown file, reporting progress" (account_id: "String", thread_id: "String", format: "ThreadExportFormat", path: "String");
+        commands::gmail::export::get_pdf_export_available ["system"] "Whether a browser that can print thread exports to PDF is installed" ();
+        commands::gmail::search::build_search_query [] "Build a Gmail query from structured filters" (filters: "SearchFilters");
+        commands::gmail::search::validate_search_query [] "Check the operators in a hand-written Gmail query" (query: "String");
+        commands::gmail::search::list_saved_gmail_searches ["local.read"] "List an account's saved searches and smart folders" (account_id: "String", kind: "Option<String>");
+        commands::gmail::search::save_gmail_search ["local.write"] "Save a search or smart folder, building its query from the filters" (account_id: "String", id: "Option<String>", name: "String", kind: "Option<String>", filters: "SearchFilters");
+        commands::gmail::search::delete_saved_gmail_search ["local.write"] "Delete a saved search or smart folder" (id: "String");
+        commands::gmail::sync::get_label_sync_policies ["local.read"] "Get the label sync policies for an account" (account_id: "String");
+        commands::gmail::sync::update_label_sync_policies ["local.write"] "Replace the label sync policies for an account" (account_id: "String", policies: "Vec<LabelSyncPolicy>");
+        commands::gmail::sync::reset_label_sync_policies ["local.write"] "Revert an account to the default label sync policies" (account_id: "String");
+        commands::gmail::sync::preview_label_sync_plan ["local.read"] "Preview the per-label fetches the next full sync will make" (account_id: "String");
+        commands::gmail::backfill::start_gmail_backfill ["mail.read", "local.write"] "Start or resume the backfill for an account" (account_id: "String", restart: "Option<bool>");
+        commands::gmail::backfill::pause_gmail_backfill ["local.write"] "Pause the mail backfill" (account_id: "String");
+        commands::gmail::backfill::get_gmail_backfill_progress ["mail.read"] "Backfill progress, or `None` if no backfill has been started" (account_id: "String");
+        commands::gmail::backfill::get_message_headers ["local.read"] "A window of stored message headers, without bodies, with a cursor for the next window and, for the first, the total count" (account_id: "String", limit: "Option<i64>", sort: "Option<HeaderSort>", filter: "Option<HeaderFilter>", cursor: "Option<String>");
+        commands::image_proxy::proxy_remote_image ["network"] "Fetch a remote image through the proxy and return it as a data URL" (url: "String");
+        commands::image_proxy::clear_image_cache ["local.write"] "Empty the remote image cache" ();
+        commands::image_proxy::should_load_remote_images ["local.read"] "Whether the sender's remote images should load without asking" (account_id: "String", sender: "String");
+        commands::image_proxy::set_sender_image_preference ["local.write"] "Set the preference for an address, or for a whole domain when `sender` has no \"@\"" (account_id: "String", sender: "String", always_load: "bool");
+        commands::image_proxy::remove_sender_image_preference ["local.write"] "Forget the remote image preference for a sender" (account_id: "String", sender: "String");
+        commands::image_proxy::list_sender_image_preferences ["local.read"] "List remote image preferences" (account_id: "String");
+        commands::gmail::shipments::get_active_shipments ["local.read"] "Shipments that are neither delivered nor dismissed, newest first" (account_id: "String");
+        commands::gmail::shipments::dismiss_shipment ["local.write"] "Hide a shipment and stop checking its status" (account_id: "String", shipment_id: "i64");
+        commands::gmail::compose::get_drive_attachment_offload ["local.read"] "Whether oversized attachments are sent as Drive links" ();
+        commands::gmail::compose::set_drive_attachment_offload ["local.write"] "Send oversized attachments as Drive links, or not" (enabled: "bool");
+        commands::gmail::autosave::autosave_draft ["local.write"] "Save the composer's current state as a new version under `autosave_key`" (account_id: "String", autosave_key: "String", compose_data: "serde_json::Value");
+        commands::gmail::autosave::recover_unsent_drafts ["local.read"] "Latest version of every compose window that was never sent or discarded" (account_id: "Option<String>");
+        commands::gmail::autosave::get_autosave_versions ["local.read"] "Earlier versions of a compose window, newest first" (autosave_key: "String");
+        commands::gmail::autosave::discard_autosaved_draft ["local.write"] "Forget a compose window's autosaves when the user discards it" (autosave_key: "String");
+        commands::gmail::api::get_gmail_message_segments ["mail.read"] "Split a message body into new content, quoted history and signature so the reader can collapse quotes" (account_id: "String", message_id: "String");
+        commands::gmail::api::get_thread_summary_input ["mail.read"] "A thread as plain text for LLM summarization, without repeated quotes" (account_id: "String", thread_id: "String");
+        commands::gmail::translation::translate_message ["mail.read", "llm"] "Translate a message's new content (quotes and signature left out) into `target_lang`" (account_id: "String", message_id: "String", target_lang: "String", model: "Option<String>");
+        commands::gmail::translation::get_translation_model ["local.read"] "Model used for message translation" ();
+        commands::gmail::translation::set_translation_model ["local.write"] "Set the model used for message translation" (model: "String");
+    }
+    "tasks" {
+        commands::tasks::api::get_task_lists ["tasks.read"] "List the account's task lists from Google Tasks, scoped to the active workspace" (account_id: "String");
+        commands::tasks::api::get_tasks ["tasks.read"] "List the tasks of a Google task list, completed ones included" (account_id: "String", task_list_id: "String", show_completed: "Option<bool>", show_deleted: "Option<bool>", max_results: "Option<u32>");
+        commands::tasks::api::create_task ["tasks.write"] "Create a task in a Google task list" (account_id: "String", task_list_id: "String", task_data: "TaskCreateData");
+        commands::tasks::api::update_task ["tasks.write"] "Update a task's title, notes, status or due date" (account_id: "String", task_list_id: "String", task_id: "String", task_data: "TaskUpdateData");
+        commands::tasks::api::move_task ["tasks.write"] "Move a task under a new parent or after a sibling" (account_id: "String", task_list_id: "String", task_id: "String", parent: "Option<String>", previous: "Option<String>");
+        commands::tasks::api::delete_task ["tasks.write"] "Delete a task from Google Tasks" (account_id: "String", task_list_id: "String", task_id: "String");
+        commands::tasks::api::create_task_list ["tasks.write"] "Create a Google task list" (account_id: "String", title: "String");
+        commands::tasks::api::update_task_list ["tasks.write"] "Rename a Google task list" (account_id: "String", task_list_id: "String", title: "String");
+        commands::tasks::api::delete_task_list ["tasks.write"] "Delete a Google task list and its tasks" (account_id: "String", task_list_id: "String");
+        commands::tasks::metadata::get_task_metadata ["local.read"] "Priority, labels and time block stored for a task" (google_task_id: "String");
+        commands::tasks::metadata::create_task_metadata ["local.write"] "Store priority, labels and time block for a task" (data: "CreateTaskMetadata");
+        commands::tasks::metadata::update_task_metadata ["local.write"] "Update a task's stored metadata" (google_task_id: "String", updates: "UpdateTaskMetadata");
+        commands::tasks::metadata::delete_task_metadata ["local.write"] "Delete a task's stored metadata" (google_task_id: "String");
+        commands::tasks::metadata::get_all_labels ["local.read"] "List every task label" ();
+        commands::tasks::all_task_data::get_all_task_data ["tasks.read"] "Fetch all task lists and tasks for an account with their metadata" (account_id: "String");
+        commands::tasks::sync_fixed::create_google_task ["tasks.write"] "Create a task, queueing it when Google is unreachable" (request: "CreateTaskRequest");
+        commands::tasks::sync_fixed::update_google_task ["tasks.write"] "Update a task, queueing the change when Google is unreachable" (request: "UpdateTaskRequest");
+        commands::tasks::sync_fixed::delete_google_task ["tasks.write"] "Delete a task, queueing the delete when Google is unreachable" (request: "DeleteTaskRequest");
+        commands::tasks::sync_fixed::update_google_task_list ["tasks.write"] "Update a task list (not supported yet)" (_request: "serde_json::Value");
+        commands::tasks::bulk::bulk_update_tasks ["tasks.write", "local.write"] "Move, complete, reopen, relabel, reprioritize or delete many tasks with batched Google calls; reports each task's outcome" (account_id: "String", tasks: "Vec<TaskRef>", operations: "BulkTaskOperations");
+        commands::tasks::boards::create_task_board ["local.write"] "Create a kanban board" (request: "CreateBoardRequest");
+        commands::tasks::boards::get_task_boards ["local.read"] "List kanban boards" (account_id: "Option<String>");
+        commands::tasks::boards::get_task_board ["local.read"] "Get a kanban board with its columns and cards" (board_id: "String");
+        commands::tasks::boards::rename_task_board ["local.write"] "Rename a kanban board" (board_id: "String", name: "String");
+        commands::tasks::boards::delete_task_board ["local.write"] "Delete a kanban board" (board_id: "String");
+        commands::tasks::boards::add_board_column ["local.write"] "Add a column to a kanban board" (board_id: "String", column: "CreateBoardColumn");
+        commands::tasks::boards::update_board_column ["local.write"] "Change a board column's title or colour" (column_id: "String", title: "String", color: "Option<String>");
+        commands::tasks::boards::delete_board_column ["local.write"] "Delete a board column, optionally moving its cards" (column_id: "String", move_cards_to: "Option<String>");
+        commands::tasks::boards::reorder_board_columns ["local.write"] "Reorder the columns of a board" (board_id: "String", column_ids: "Vec<String>");
+        commands::tasks::boards::move_board_card ["local.write"] "Move a task card to another column or position" (request: "MoveCardRequest");
+        commands::tasks::boards::remove_board_card ["local.write"] "Take a task off a board" (board_id: "String", google_task_id: "String");
+        commands::tasks::recurrence::set_task_recurrence ["local.write"] "Set the recurrence rule for a task; returns the normalized rule string" (google_task_id: "String", task_list_id: "String", rule: "String");
+        commands::tasks::recurrence::clear_task_recurrence ["local.write"] "Remove a task's recurrence rule" (google_task_id: "String", task_list_id: "String");
+        commands::tasks::recurrence::get_task_recurrence ["local.read"] "Get a task's recurrence rule" (google_task_id: "String");
+        commands::tasks::recurrence::process_task_recurrences ["tasks.write"] "Spawn next occurrences for completed or overdue recurring tasks" (account_id: "String");
+        commands::tasks::subtasks::sync_task_subtasks ["tasks.write"] "Push local subtasks of a task to Google and pull back the remote hierarchy" (account_id: "String", task_list_id: "String", google_task_id: "String");
+        commands::tasks::subtasks::move_google_task ["tasks.write"] "Move a task under a new parent (or to the top level) after an optional sibling" (account_id: "String", task_list_id: "String", task_id: "String", parent: "Option<String>", previous: "Option<String>");
+        commands::tasks::activity::add_task_comment ["local.write", "tasks.write"] "Comment on a task; with tasks.mirror_activity on, the activity summary in its Google notes is refreshed" (account_id: "String", task_list_id: "String", google_task_id: "String", author: "String", body: "String");
+        commands::tasks::activity::edit_task_comment ["local.write", "tasks.write"] "Change the text of a task comment" (account_id: "String", task_list_id: "String", comment_id: "i64", body: "String");
+        commands::tasks::activity::delete_task_comment ["local.write", "tasks.write"] "Delete a task comment" (account_id: "String", task_list_id: "String", comment_id: "i64");
+        commands::tasks::activity::get_task_activity ["local.read"] "Comments and journaled changes of a task, oldest first" (google_task_id: "String");
+        commands::tasks::importer::import_tasks_from_file ["tasks.write", "local.write", "system"] "Import tasks from a Todoist CSV, JSON or backup zip, or a TickTick backup; projects become lists, with priorities, labels and due dates" (account_id: "String", path: "String", options: "Option<TaskImportOptions>");
+        commands::tasks::export::export_task_list ["tasks.read"] "Export one task list" (account_id: "String", task_list_id: "String", options: "ExportOptions");
+        commands::tasks::export::export_agenda ["tasks.read", "calendar.read"] "Export a day's agenda: the events on the given calendars (primary by default) plus tasks from every list that are due or time-blocked that day" (account_id: "String", date: "String", calendar_ids: "Option<Vec<String>>", options: "ExportOptions");
+    }
+    "calendar" {
+        commands::calendar::get_calendars ["calendar.read"] "Get all calendars for an account" (account_id: "String");
+        commands::calendar::get_calendar_events ["calendar.read"] "Get events for a specific calendar" (account_id: "String", calendar_id: "String", time_min: "Option<String>", time_max: "Option<String>", max_results: "Option<u32>", show_deleted: "Option<bool>", single_events: "Option<bool>");
+        commands::calendar::create_calendar_event ["calendar.write"] "Create a new calendar event" (account_id: "String", calendar_id: "String", event_data: "GoogleCalendarEvent");
+        commands::calendar::update_calendar_event ["calendar.write"] "Update an existing calendar event" (account_id: "String", calendar_id: "String", event_id: "String", event_data: "GoogleCalendarEvent");
+        commands::calendar::delete_calendar_event ["calendar.write"] "Delete a calendar event" (account_id: "String", calendar_id: "String", event_id: "String");
+        commands::calendar::find_meeting_slots ["calendar.read"] "Find candidate meeting times in the requested range, best first" (account_id: "String", request: "MeetingSlotRequest");
+        commands::calendar::get_working_hours ["local.read"] "Working hours in effect, from the active workspace's availability profile if it has one" ();
+        commands::calendar::set_working_hours ["local.write"] "Set the working hours of the global availability profile" (working_hours: "WorkingHours");
+        commands::calendar::get_availability_profile ["local.read"] "Availability profile (working hours, breaks, no-meeting blocks) of a workspace or the one in effect" (workspace_id: "Option<i64>");
+        commands::calendar::set_availability_profile ["local.write"] "Save the global availability profile, or override or clear a workspace's own" (profile: "Option<AvailabilityProfile>", workspace_id: "Option<i64>");
+        commands::calendar::check_availability ["local.read"] "Ways a meeting or time block would go against working hours, breaks or no-meeting blocks" (start: "String", end: "String", purpose: "AvailabilityPurpose");
+        commands::calendar::suggest_travel_buffers ["calendar.read", "network"] "Travel-time blocks before and after an event with a physical location, from fixed buffers or the routing provider" (account_id: "String", event_id: "String", calendar_id: "Option<String>");
+        commands::calendar::create_travel_buffers ["calendar.write"] "Create the chosen travel-time blocks next to their event" (account_id: "String", event_id: "String", calendar_id: "Option<String>", suggestions: "Vec<TravelBufferSuggestion>");
+        commands::calendar::get_travel_buffer_settings ["local.read"] "Travel buffer preferences: fixed minutes, routing provider, home location and automatic insertion" ();
+        commands::calendar::set_travel_buffer_settings ["local.write"] "Set travel buffer preferences" (settings: "TravelBufferSettings");
+        commands::calendar::import_ics_file ["local.read", "local.write"] "Import an ICS file as a local calendar; importing it again replaces its events" (path: "String");
+        commands::calendar::subscribe_webcal_calendar ["network", "local.write"] "Subscribe to a webcal: or https calendar, refreshing it if already subscribed" (url: "String");
+        commands::calendar::refresh_imported_calendar ["network", "local.write"] "Fetch a subscribed calendar again, or re-read an imported file" (calendar_id: "String");
+        commands::calendar::get_imported_calendars ["local.read"] "Calendars imported from ICS files and webcal subscriptions" ();
+        commands::calendar::get_imported_events ["local.read"] "Events of an imported calendar in a time range, with recurring events returned once with their rules" (calendar_id: "String", time_min: "Option<String>", time_max: "Option<String>");
+        commands::calendar::delete_imported_calendar ["local.write"] "Delete an imported calendar and its events" (calendar_id: "String");
+    }
+    "contacts" {
+        commands::contacts::sync_google_contacts ["contacts.read", "local.write"] "Sync Google contacts into the local address book" (account_id: "String");
+        commands::contacts::get_contacts ["local.read"] "List synced contacts" (account_id: "String");
+        commands::contacts::search_contacts ["local.read"] "Address suggestions for the compose recipient fields" (query: "String", account_id: "Option<String>", limit: "Option<i64>");
+        commands::contacts::resolve_contact_names ["local.read"] "Map email addresses to contact display names; unknown addresses are omitted" (emails: "Vec<String>");
+    }
+    "drive" {
+        commands::google_drive::get_google_drive_quota ["drive.read", "secrets"] "Drive storage used and available for an access token" (access_token: "String");
+        commands::google_drive::list_drive_files ["drive.read"] "List files in a Drive folder (the root folder by default)" (account_id: "String", folder_id: "Option<String>", page_size: "Option<u32>", page_token: "Option<String>");
+        commands::google_drive::search_drive_files ["drive.read"] "Search Drive files by name and content" (account_id: "String", query: "String", page_size: "Option<u32>", page_token: "Option<String>");
+        commands::google_drive::attach_drive_file ["drive.read"] "Prepare a Drive file for the composer, as a link or (with `download`) as an attachment" (account_id: "String", file_id: "String", download: "Option<bool>");
+    }
+    "notes" {
+        commands::notes::get_notes ["local.read"] "List notes" ();
+        commands::notes::create_note ["local.write"] "Create a note" (title: "String", content: "String", folder_id: "Option<i32>", user_id: "String");
+        commands::notes::update_note ["local.write"] "Update a note" (id: "String", note: "UpdateNoteRequest");
+        commands::notes::delete_note ["local.write"] "Delete a note" (id: "String");
+        commands::notes::import_pdf_note ["local.read", "local.write"] "Create a note from the text of a PDF" (path: "String", folder_id: "Option<i32>", user_id: "String");
+        commands::notes::clip_url ["network", "local.write"] "Save a web page's article as a Markdown note with local images, its source URL and tags" (url: "String", tags: "Option<Vec<String>>", folder_id: "Option<i32>", user_id: "String");
+        commands::notes::list_note_templates ["local.read"] "List note templates" ();
+        commands::notes::save_note_template ["local.write"] "Create a note template, or replace an existing one; placeholders like {{date}} and {{weekday}} are filled in when used" (id: "Option<i64>", name: "String", title: "String", content: "String");
+        commands::notes::delete_note_template ["local.write"] "Delete a note template" (id: "i64");
+        commands::notes::create_note_from_template ["local.write"] "Create a note from a template, filling in the date and any given variables" (template_id: "i64", folder_id: "Option<i32>", user_id: "String", variables: "Option<HashMap<String, String>>");
+        commands::notes::get_or_create_daily_note ["local.read", "local.write"] "Open today's daily note, creating it from the daily note template, optionally with today's agenda and due tasks" (user_id: "String");
+        commands::folders::get_folders ["local.read"] "List note folders" ();
+        commands::folders::create_folder ["local.write"] "Create a note folder" (name: "String", parent_id: "Option<i32>", color: "Option<String>", user_id: "String");
+        commands::folders::update_folder ["local.write"] "Rename, move or recolour a note folder" (id: "String", folder: "UpdateFolderRequest");
+        commands::folders::delete_folder ["local.write"] "Delete a note folder" (id: "String");
+    }
+    "chat" {
+        commands::chat::create_session ["local.write"] "Start a chat session" (title: "String");
+        commands::chat::get_sessions ["local.read"] "List chat sessions" ();
+        commands::chat::send_message ["local.write"] "Add a message to a chat session" (session_id_str: "String", content: "String", role: "String");
+        commands::chat::generate_chat_reply ["local.write", "llm"] "Answer the latest message of a chat session with the chat model" (session_id_str: "String");
+        commands::chat::get_chat_memory ["local.read"] "Get the rolling summary kept for a chat session" (session_id_str: "String");
+        commands::chat::update_chat_memory ["local.write"] "Edit or reset the rolling summary of a chat session" (session_id_str: "String", summary: "String");
+        commands::chat::attach_file_to_chat ["mail.read", "local.read", "local.write", "llm"] "Extract, chunk and embed a file so chat replies can cite it" (session_id_str: "String", source: "ChatFileSource", filename: "String", mime_type: "String");
+        commands::chat::list_chat_attachments ["local.read"] "List files attached to a chat session" (session_id_str: "String");
+        commands::chat::remove_chat_attachment ["local.write"] "Detach a file from a chat session" (attachment_id: "i64");
+        commands::chat::get_session_messages ["local.read"] "Messages of a chat session" (session_id_str: "String");
+        commands::chat::get_database_stats ["local.read"] "Row counts for the chat tables" ();
+        commands::chat::delete_session ["local.write"] "Delete a chat session" (session_id: "String");
+        commands::chat::delete_session_v4 ["local.write"] "Delete a chat session and its messages" (session_id_str: "String");
+        commands::chat::update_session_title ["local.write"] "Rename a chat session" (session_id_str: "String", new_title: "String");
+    }
+    "llm" {
+        commands::ollama::ollama_health_check ["llm"] "Check whether the local Ollama server answers" ();
+        commands::ollama::ollama_get_status ["llm"] "Status of the local Ollama server" ();
+        commands::ollama::ollama_start_sidecar ["system"] "Start the local Ollama server if nothing is running" ();
+        commands::ollama::ollama_stop_sidecar ["system"] "Stop the Ollama server started by the app" ();
+        commands::ollama::ollama_supervisor_status ["system"] "Whether Ollama is installed, running and being restarted" ();
+        commands::ollama::ollama_list_models ["llm"] "List models installed in Ollama" ();
+        commands::ollama::ollama_get_model_info ["llm"] "Details of an installed Ollama model" (model_name: "String");
+        commands::ollama::ollama_delete_model ["llm", "system"] "Delete an Ollama model" (model_name: "String");
+        commands::ollama::ollama_pull_model ["llm", "system"] "Download an Ollama model, reporting progress" (model: "String");
+        commands::ollama::ollama_chat ["llm"] "Chat with an Ollama model" (messages: "Vec<serde_json::Value>", model: "String");
+        commands::ollama::ollama_chat_stream ["llm"] "Chat with an Ollama model, streaming the reply" (messages: "Vec<serde_json::Value>", model: "String", stream_id: "String");
+        commands::ollama::ollama_generate ["llm"] "Generate a completion with an Ollama model" (prompt: "String", model: "String");
+        commands::llm::llm_chat_openai ["llm", "network", "secrets"] "Chat with a model hosted by OpenAI" (messages: "Vec<Value>", model: "String", api_key: "String", base_url: "Option<String>");
+        commands::llm::llm_list_openai_models ["llm", "network", "secrets"] "List available OpenAI models" (api_key: "String", base_url: "Option<String>");
+        commands::llm::llm_chat_anthropic ["llm", "network", "secrets"] "Chat with a model hosted by Anthropic" (messages: "Vec<Value>", model: "String", api_key: "String", base_url: "Option<String>");
+        commands::llm::llm_list_anthropic_models ["llm", "network", "secrets"] "List available Anthropic models" (_api_key: "String", _base_url: "Option<String>");
+        commands::llm::llm_chat_openrouter ["llm", "network", "secrets"] "Chat with a model hosted by OpenRouter" (messages: "Vec<Value>", model: "String", api_key: "String", base_url: "Option<String>");
+        commands::llm::llm_list_openrouter_models ["llm", "network", "secrets"] "List available OpenRouter models" (api_key: "String", base_url: "Option<String>");
+        commands::llm::llm_chat_deepseek ["llm", "network", "secrets"] "Chat with a model hosted by DeepSeek" (messages: "Vec<Value>", model: "String", api_key: "String", base_url: "Option<String>");
+        commands::llm::llm_list_deepseek_models ["llm", "network", "secrets"] "List available DeepSeek models" (api_key: "String", base_url: "Option<String>");
+        commands::llm::llm_chat_mistral ["llm", "network", "secrets"] "Chat with a model hosted by Mistral" (messages: "Vec<Value>", model: "String", api_key: "String", base_url: "Option<String>");
+        commands::llm::llm_list_mistral_models ["llm", "network", "secrets"] "List available Mistral models" (api_key: "String", base_url: "Option<String>");
+        commands::llm::list_llm_providers ["llm"] "List configured LLM providers" ();
+        commands::llm::save_llm_provider_settings ["llm", "secrets"] "Save the API keys and base URLs of the hosted providers" (settings: "HashMap<String, LlmProviderConfig>");
+        commands::llm::get_llm_provider_settings ["llm", "secrets"] "The saved API keys and base URLs of the hosted providers" ();
+        commands::llm::set_enabled_models ["llm"] "Choose which of a provider's models are offered" (provider: "String", model_ids: "Vec<String>");
+        commands::llm::get_enabled_models ["llm"] "The models offered for a provider" (provider: "String");
+        commands::llm::save_llm_provider ["llm", "secrets"] "Add or update an Ollama or OpenAI-compatible provider" (provider: "ProviderInput");
+        commands::llm::delete_llm_provider ["llm"] "Remove an LLM provider" (provider_id: "String");
+        commands::llm::get_llm_feature_models ["llm"] "Show the model used for chat, summarization and embeddings" ();
+        commands::llm::set_llm_feature_model ["llm"] "Choose the provider and model for a feature" (feature: "String", provider_id: "String", model: "String");
+        commands::llm::test_llm_provider ["llm", "network"] "Check that a provider answers and list its models" (provider_id: "String", model: "Option<String>");
+        commands::llm::count_tokens [] "Estimate the tokens in a text and the model's context window" (text: "String", model: "Option<String>");
+        commands::llm::get_llm_usage ["system"] "Token usage and latency per feature and model" (days: "Option<i64>");
+        commands::llm::list_llm_models ["llm"] "List registered models with size, context length and capabilities" (provider_id: "Option<String>");
+        commands::llm::refresh_llm_models ["llm", "network"] "Re-read installed models from the providers" (provider_id: "Option<String>");
+        commands::llm::resolve_llm_model ["llm"] "Show which model a feature uses, why, and whether it fits in memory" (feature: "String");
+        commands::llm::get_hardware_profile ["system"] "Show detected memory and GPUs" (refresh: "Option<bool>");
+        commands::llm::get_llm_routing_rules ["llm"] "List the rules that pick a model per feature" ();
+        commands::llm::set_llm_routing_rule ["llm"] "Save the rule that picks a feature's model" (rule: "RoutingRule");
+        commands::llm::delete_llm_routing_rule ["llm"] "Remove a feature's routing rule" (feature: "String");
+        commands::llm::llm_chat_gemini ["llm", "network", "secrets"] "Chat with a Gemini model hosted by Google" (messages: "Vec<Value>", model: "String", api_key: "String", base_url: "Option<String>");
+        commands::llm::llm_list_gemini_models ["llm", "network", "secrets"] "List available Gemini models" (api_key: "String", base_url: "Option<String>");
+    }
+    "prompts" {
+        commands::prompt_templates::list_prompt_templates ["local.read"] "Prompt templates, optionally of one category" (category: "Option<String>");
+        commands::prompt_templates::get_prompt_template ["local.read"] "One prompt template with its variables" (id: "i64");
+        commands::prompt_templates::create_prompt_template ["local.write"] "Add a prompt template" (template: "TemplateInput");
+        commands::prompt_templates::update_prompt_template ["local.write"] "Edit a prompt template, keeping the previous version" (id: "i64", template: "TemplateInput");
+        commands::prompt_templates::delete_prompt_template ["local.write"] "Delete a prompt template and its history" (id: "i64");
+        commands::prompt_templates::get_prompt_template_versions ["local.read"] "Earlier versions of a prompt template" (id: "i64");
+        commands::prompt_templates::restore_prompt_template_version ["local.write"] "Make an earlier version of a prompt template current" (id: "i64", version: "i64");
+        commands::prompt_templates::get_prompt_template_defaults ["local.read"] "Default template of each feature that builds prompts" ();
+        commands::prompt_templates::set_prompt_template_default ["local.write"] "Choose or clear the default template for a feature" (feature: "String", template_id: "Option<i64>");
+        commands::prompt_templates::preview_prompt_template [] "Render an unsaved template against a sample message" (template: "TemplateInput", values: "Option<Map<String, Value>>", sample_message: "Option<SampleMessage>");
+    }
+    "transcription" {
+        commands::transcription::transcribe_attachment ["mail.read", "local.read", "local.write"] "Transcribe an audio attachment or local recording" (source: "AudioSource", filename: "String", mime_type: "String", force: "Option<bool>");
+        commands::transcription::search_transcripts ["local.read"] "Full-text search over stored transcripts" (query: "String", account_id: "Option<String>", limit: "Option<usize>");
+        commands::transcription::get_note_transcripts ["local.read"] "Transcripts linked to a note" (note_id: "i64");
+        commands::transcription::get_transcription_settings ["local.read"] "Transcription engine settings" ();
+        commands::transcription::set_transcription_settings ["local.write"] "Update transcription engine settings" (settings: "TranscriptionSettings");
+    }
+    "text" {
+        commands::text_processing::clean_text ["local.read"] "Strip HTML and normalise whitespace" (text: "String");
+        commands::text_processing::analyze_text ["local.read"] "Language, reading time and entities for an email body (HTML or plain text)" (text: "String");
+        commands::text_processing::extract_entities ["local.read"] "Entities the UI can render as smart chips, e.g. \"add to calendar\" for dates or \"track package\" for tracking numbers" (text: "String");
+        commands::text_processing::extract_pdf_text ["local.read"] "Per-page text and structure of a PDF, with OCR for scanned pages" (path: "String", ocr: "Option<bool>");
+    }
+    "onboarding" {
+        commands::onboarding::get_onboarding_state ["local.read", "llm"] "Progress, prerequisite checks and a hint for every step" (user_id: "Option<String>");
+        commands::onboarding::complete_onboarding_step ["local.write", "llm"] "Complete a step, saving the persona or models chosen on it" (step: "String", persona: "Option<String>", selected_models: "Option<Vec<String>>", user_id: "Option<String>");
+        commands::onboarding::skip_onboarding ["local.write", "llm"] "Finish onboarding without the remaining steps" (user_id: "Option<String>");
+        commands::onboarding::get_detected_accounts ["local.read", "system"] "Mail accounts set up in Thunderbird, Outlook or Windows on this machine, with any local mail that can be imported" ();
+    }
+    "settings" {
+        commands::settings::get_settings_schema [] "Every known setting with its type, default and allowed values" ();
+        commands::settings::get_settings ["local.read"] "Current value of every setting, or of one category" (category: "Option<String>");
+        commands::settings::get_setting ["local.read"] "Current value of one setting" (key: "String");
+        commands::settings::set_setting ["local.write"] "Validate and store a setting, notifying running services of the change" (key: "String", value: "serde_json::Value");
+        commands::settings::reset_setting ["local.write"] "Restore a setting's default" (key: "String");
+        commands::settings::export_settings ["local.read"] "All settings as a JSON document the user can save" ();
+        commands::settings::import_settings ["local.write"] "Apply an exported settings document; invalid entries are reported, not applied" (data: "SettingsExport");
+        commands::settings::get_available_locales [] "Languages the backend's messages are translated into" ();
+        commands::settings::set_locale ["local.write"] "Choose a language (\"system\" follows the OS) and return the one now in use" (locale: "String");
+        commands::settings::get_time_zone ["local.read"] "The zone times are shown in, the system's zone and any configured one; the `time.zone` setting chooses it" ();
+    }
+    "search" {
+        commands::search::global_search ["local.read"] "Search every domain (or the requested ones) and return one ranked page" (request: "GlobalSearchRequest");
+    }
+    "capture" {
+        commands::quick_capture::classify_capture ["llm"] "Classify captured text without creating anything, for previews" (text: "String", use_llm: "Option<bool>");
+        commands::quick_capture::quick_capture ["tasks.write", "calendar.write", "mail.write", "local.write", "llm"] "Classify captured text and create what it describes" (request: "QuickCaptureRequest");
+        commands::quick_capture::get_clipboard_watch_status ["local.read"] "Whether the clipboard watcher is on, and the patterns it looks for" ();
+        commands::quick_capture::enable_clipboard_watch ["local.write"] "Offer copied links, addresses and pattern matches for capture" (patterns: "Option<Vec<ClipboardPattern>>");
+        commands::quick_capture::disable_clipboard_watch ["local.write"] "Stop watching the clipboard" ();
+        commands::quick_capture::get_recent_clipboard_captures ["local.read"] "Captures the clipboard watcher has offered, newest first" (limit: "Option<i64>");
+        commands::quick_capture::dismiss_clipboard_capture ["local.write"] "Decline an offered clipboard capture" (id: "i64");
+        commands::quick_capture::clear_clipboard_captures ["local.write"] "Delete every clipboard capture" ();
+    }
+    "links" {
+        commands::links::unfurl_link ["network", "local.write"] "Title, description and image of a page for rendering a link card" (url: "String", refresh: "Option<bool>");
+        commands::links::clear_link_previews ["local.write"] "Empty the link preview cache" ();
+    }
+    "feeds" {
+        commands::feeds::subscribe_feed ["network", "local.write"] "Subscribe to a feed URL, or to the feed a web page links to" (url: "String");
+        commands::feeds::unsubscribe_feed ["local.write"] "Unsubscribe, deleting the feed's items" (feed_id: "i64");
+        commands::feeds::get_feeds ["local.read"] "Subscribed feeds with their unread counts" ();
+        commands::feeds::refresh_feeds ["network", "local.write"] "Fetch one feed, or all of them, now" (feed_id: "Option<i64>");
+        commands::feeds::get_feed_items ["local.read"] "Feed items, newest first" (filter: "Option<FeedItemFilter>");
+        commands::feeds::mark_feed_items_read ["local.write"] "Mark items read, or unread with `read: false`" (item_ids: "Vec<i64>", read: "Option<bool>");
+        commands::feeds::mark_all_feed_items_read ["local.write"] "Mark every item of a feed read, or of all feeds" (feed_id: "Option<i64>");
+        commands::feeds::star_feed_item ["local.write"] "Star an item so it is kept after it is read" (item_id: "i64", starred: "bool");
+        commands::feeds::save_feed_item_as_note ["network", "local.write"] "Keep a feed item as a note, clipping the linked article when possible" (item_id: "i64", folder_id: "Option<i32>", user_id: "Option<String>");
+        commands::feeds::get_feed_digest ["local.read", "local.write", "llm"] "Unread items from the last day, optionally summarized and kept as a note" (hours: "Option<i64>", summarize: "Option<bool>", save_as_note: "Option<bool>", user_id: "Option<String>");
+    }
+    "digest" {
+        commands::digest::preview_daily_digest ["mail.read", "tasks.read", "calendar.read"] "Today's digest of important mail, events, due tasks and follow-ups" ();
+        commands::digest::send_daily_digest ["mail.read", "tasks.read", "calendar.read", "mail.send"] "Send today's digest now as a notification and/or email to yourself" ();
+    }
+    "workspaces" {
+        commands::workspaces::get_workspaces ["local.read"] "Every workspace with its accounts, task lists, calendars and folders" ();
+        commands::workspaces::get_active_workspace ["local.read"] "The active workspace, or none when everything is shown" ();
+        commands::workspaces::create_workspace ["local.write"] "Create a workspace, optionally with its members" (name: "String", color: "Option<String>", members: "Option<Vec<WorkspaceMember>>");
+        commands::workspaces::update_workspace ["local.write"] "Rename or recolour a workspace" (workspace_id: "i64", name: "Option<String>", color: "Option<String>");
+        commands::workspaces::set_workspace_members ["local.write"] "Replace the accounts, task lists, calendars and folders of a workspace" (workspace_id: "i64", members: "Vec<WorkspaceMember>");
+        commands::workspaces::delete_workspace ["local.write"] "Delete a workspace; its members become shared again" (workspace_id: "i64");
+        commands::workspaces::switch_workspace ["local.write"] "Make a workspace active, or show everything" (workspace_id: "Option<i64>");
+    }
+    "notifications" {
+        commands::notifications::list_vip_senders ["local.read"] "VIPs of a workspace, or with no workspace those shared by all of them" (workspace_id: "Option<i64>");
+        commands::notifications::add_vip_sender ["local.write"] "Add an address or @domain to the VIPs of a workspace, or of every workspace" (address: "String", name: "Option<String>", workspace_id: "Option<i64>");
+        commands::notifications::remove_vip_sender ["local.write"] "Remove a VIP sender" (id: "i64");
+        commands::notifications::get_vip_rules ["local.read"] "The VIP alert rules of a workspace, or those in effect" (workspace_id: "Option<i64>");
+        commands::notifications::set_vip_rules ["local.write"] "Save the global VIP alert rules or a workspace's own" (rules: "Option<VipRules>", workspace_id: "Option<i64>");
+        commands::notifications::test_vip_alert [] "Announce a made-up VIP message under the rules in effect to try the sound and preview" (account_id: "String", address: "Option<String>");
+    }
+    "timeline" {
+        commands::timeline::get_activity_timeline ["local.read"] "Changes, sent mail, completed tasks, new notes and chats over a range of days, grouped by day" (range: "TimelineRange", types: "Option<Vec<ActivityType>>");
+    }
+    "mail_import" {
+        commands::mail_import::import_thunderbird_mail ["local.write", "system"] "Import a Thunderbird profile, mail folder or mbox file as an archive account" (path: "String", name: "Option<String>");
+        commands::mail_import::import_outlook_mail ["local.write", "system"] "Import an Outlook PST or OST file as an archive account" (path: "String", name: "Option<String>");
+        commands::mail_import::get_outlook_import_available ["system"] "Whether readpst is installed for Outlook imports" ();
+        commands::mail_import::get_archive_accounts ["local.read"] "Archive accounts holding imported mail" ();
+        commands::mail_import::get_archive_folders ["local.read"] "The original folders of an archive, with message counts" (account_id: "String");
+        commands::mail_import::get_archived_messages ["local.read"] "Messages in an archive folder, newest first" (account_id: "String", label: "Option<String>", limit: "Option<i64>", offset: "Option<i64>");
+        commands::mail_import::get_archived_message ["local.read"] "A full archived message" (account_id: "String", message_id: "String");
+        commands::mail_import::delete_archive_account ["local.write"] "Delete an archive account and its imported mail" (account_id: "String");
+    }
+    "vault" {
+        commands::vault::get_vault_status ["local.read"] "Whether the secure notes vault is set up and unlocked" ();
+        commands::vault::setup_vault ["secrets"] "Choose the passphrase of the secure notes vault" (passphrase: "String");
+        commands::vault::unlock_vault ["secrets"] "Unlock the secure notes vault" (passphrase: "String");
+        commands::vault::lock_vault ["secrets"] "Lock the secure notes vault now" ();
+        commands::vault::change_vault_passphrase ["secrets"] "Change the secure notes vault passphrase" (current_passphrase: "String", new_passphrase: "String");
+        commands::vault::create_secure_note ["secrets"] "Create an encrypted note in the vault" (title: "String", content: "String");
+        commands::vault::get_secure_notes ["secrets"] "Titles of the secure notes" ();
+        commands::vault::get_secure_note ["secrets"] "Open a secure note" (note_id: "i64");
+        commands::vault::update_secure_note ["secrets"] "Edit a secure note" (note_id: "i64", title: "String", content: "String");
+        commands::vault::delete_secure_note ["secrets"] "Delete a secure note" (note_id: "i64");
+    }
+    "app_lock" {
+        commands::app_lock::get_app_lock_status ["local.read"] "Whether the app lock is set, engaged, and its timeout" ();
+        commands::app_lock::set_app_lock_passphrase ["secrets"] "Set or change the app lock passphrase" (current_passphrase: "Option<String>", new_passphrase: "String");
+        commands::app_lock::remove_app_lock_passphrase ["secrets"] "Turn the app lock off" (current_passphrase: "String");
+        commands::app_lock::lock_application ["secrets"] "Lock the app now" ();
+        commands::app_lock::unlock_application ["secrets"] "Unlock the app with its passphrase" (passphrase: "String");
+    }
+    "launch" {
+        commands::launch::take_launch_requests [] "Commands a command line or a second launch asked for, each returned once" ();
+        commands::launch::parse_mailto_link [] "Recipients, subject and body of a mailto: link, for the composer" (uri: "String");
+    }
+    "updates" {
+        commands::updates::check_for_updates ["network"] "Check the chosen release channel for a new version now, ignoring the staged rollout" ();
+        commands::updates::get_update_status [] "The updater's state: the available version, its release notes and download progress" ();
+        commands::updates::download_update ["network"] "Download and verify the available update without installing it" ();
+        commands::updates::install_update_and_restart ["network", "system"] "Install the available update, downloading it first if needed, and relaunch the app" ();
+        commands::updates::get_release_notes ["network"] "Release notes of a version, by default the available update or else the running version" (version: "Option<String>");
+    }
+    "sync" {
+        commands::sync::queue_pending_change ["local.write"] "Queue a local edit made while offline" (change: "NewPendingChange");
+        commands::sync::get_pending_changes ["local.read"] "Edits waiting to be pushed for an account" (account_id: "String");
+        commands::sync::flush_pending_changes ["tasks.write", "calendar.write", "mail.write"] "Push queued edits, auto-merging where safe and recording conflicts otherwise" (account_id: "String");
+        commands::sync::get_sync_conflicts ["local.read"] "Unresolved sync conflicts" (account_id: "Option<String>");
+        commands::sync::resolve_sync_conflict ["local.write"] "Resolve a sync conflict by keeping one side or a merge" (conflict_id: "i64", resolution: "ConflictResolution", merged_payload: "Option<serde_json::Value>");
+        commands::sync::get_failed_changes ["local.read"] "Changes the server refused, with the error it gave" (account_id: "String");
+        commands::sync::retry_failed_change ["local.write"] "Queue a refused change again, e.g. after fixing what the server objected to" (change_id: "i64");
+        commands::sync::discard_failed_change ["local.write"] "Give up on a refused change" (change_id: "i64");
+        commands::sync::get_change_history ["local.read"] "Change journal entries for one item" (entity_type: "String", entity_id: "String");
+        commands::sync::get_recent_changes ["local.read"] "Latest change journal entries" (limit: "Option<i64>");
+        commands::sync::undo_last_change ["local.write", "tasks.write"] "Undo the latest change to an entity" (entity_type: "String", entity_id: "String");
+        commands::sync::compact_change_journal ["local.write"] "Delete old change journal entries" (older_than_days: "Option<i64>");
+        commands::sync::status::get_sync_status ["local.read"] "Sync health for every active account" ();
+        commands::sync::deltas::get_changes_since ["local.read"] "Cache deltas after a sequence number, for stores that missed cache-changed events" (seq: "i64", limit: "Option<i64>");
+        commands::rate_limiter::get_request_scheduler_metrics [] "Google API requests waiting per priority and usage of the rolling minute and day" ();
+    }
+    "projects" {
+        commands::projects::get_projects ["local.read"] "List a user's projects" (user_id: "String");
+        commands::projects::create_project ["local.write"] "Create a project for a user" (name: "String", description: "String", color: "String", user_id: "String");
+        commands::projects::get_project ["local.read"] "A project by ID" (project_id: "String");
+        commands::projects::update_project ["local.write"] "Update a project's details, status or progress" (project_id: "String", name: "Option<String>", description: "Option<String>", color: "Option<String>", status: "Option<String>", progress: "Option<i32>", priority: "Option<String>");
+        commands::projects::delete_project ["local.write"] "Delete a project" (project_id: "String");
+        commands::projects::create_project_goal ["local.write"] "Add a goal to a project" (project_id: "String", title: "String", priority: "String");
+        commands::projects::get_project_goals ["local.read"] "List a project's goals" (project_id: "String");
+        commands::projects::update_project_goal ["local.write"] "Update a project goal" (goal_id: "String", title: "Option<String>", description: "Option<String>", completed: "Option<bool>", priority: "Option<String>");
+        commands::projects::delete_project_goal ["local.write"] "Delete a project goal" (goal_id: "String");
+        commands::projects::create_project_asset ["local.write"] "Attach a file or link to a project" (project_id: "String", name: "String", asset_type: "String", url: "String", uploaded_by: "String", size: "Option<i64>", metadata: "Option<String>");
+        commands::projects::get_project_assets ["local.read"] "List a project's files and links" (project_id: "String");
+        commands::projects::delete_project_asset ["local.write"] "Remove a file or link from a project" (asset_id: "String");
+        commands::projects::get_project_stats ["local.read"] "Goal and asset counts for a project" (project_id: "String");
+        commands::agents::lifecycle::get_agents ["local.read"] "List configured agents" ();
+    }
+    "canvas" {
+        commands::canvas::summarize_canvas_selection ["llm", "local.write"] "Summarize selected canvas elements into a note" (canvas_id: "String", elements: "Vec<SelectedElement>", instructions: "Option<String>", origin: "Option<CanvasPoint>");
+        commands::canvas::generate_canvas_diagram ["llm", "local.write"] "Generate outline or mind-map nodes from a selection" (canvas_id: "String", kind: "DiagramKind", elements: "Vec<SelectedElement>", instructions: "Option<String>", max_nodes: "Option<usize>", origin: "Option<CanvasPoint>");
+        commands::canvas::get_canvas_ai_history ["local.read"] "AI generations of a canvas" (canvas_id: "String", limit: "Option<i64>");
+        commands::canvas::delete_canvas_ai_generation ["local.write"] "Delete one canvas AI generation" (generation_id: "i64");
+        commands::canvas::clear_canvas_ai_history ["local.write"] "Delete the AI history of a canvas" (canvas_id: "String");
+        commands::canvas::export_canvas ["local.write"] "Export a canvas to SVG, PNG or PDF" (canvas: "Value", options: "ExportOptions", path: "Option<String>");
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ParameterInfo {
+    pub name: String,
+    /// Argument name to pass to `invoke` (Tauri expects camelCase)
+    pub invoke_key: String,
+    pub type_name: String,
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandInfo {
+    pub name: String,
+    pub category: String,
+    pub description: String,
+    pub parameters: Vec<ParameterInfo>,
+    pub permissions: Vec<String>,
+}
+
+pub(crate) fn invoke_key(param: &str) -> String {
+    let mut key = String::with_capacity(param.len());
+    let mut upper = false;
+    for c in param.trim_start_matches('_').chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            key.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            key.push(c);
+        }
+    }
+    key
+}
+
+impl From<&CommandMetadata> for CommandInfo {
+    fn from(command: &CommandMetadata) -> Self {
+        Self {
+            name: command.name.to_string(),
+            category: command.category.to_string(),
+            description: command.description.to_string(),
+            parameters: command
+                .parameters
+                .iter()
+                .map(|param| ParameterInfo {
+                    name: param.name.to_string(),
+                    invoke_key: invoke_key(param.name),
+                    type_name: param.type_name.to_string(),
+                    required: !param.type_name.starts_with("Option<"),
+                })
+                .collect(),
+            permissions: command.permissions.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+}
+
+pub fn find_command(name: &str) -> Option<&'static CommandMetadata> {
+    COMMANDS.iter().find(|command| command.name == name)
+}
+
+/// Describe every command the frontend can invoke, optionally one category
+#[tauri::command]
+pub async fn get_command_registry(category: Option<String>) -> CommandResult<Vec<CommandInfo>> {
+    Ok(COMMANDS
+        .iter()
+        .filter(|command| category.as_deref().is_none_or(|c| command.category == c))
+        .map(CommandInfo::from)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{HashMap, HashSet};
+    use std::fs;
+    use std::path::Path;
+
+    /// A command's frontend-supplied parameters: name and type
+    type Signature = Vec<(String, String)>;
+
+    /// Commands written before the registry that are deliberately not
+    /// exposed. Most are never called; the frontend still calls the OAuth
+    /// loopback helpers and the Gmail token debugging tools, but the
+    /// registered OAuth flow replaced the former and the latter read or
+    /// clear stored tokens.
+    /// Register one to expose it; don't add to this list.
+    const UNREGISTERED: &[&str] = &[
+        // commands/agents/lifecycle.rs
+        "create_agent", "get_agent", "update_agent", "delete_agent", "execute_agent", "get_agent_executions",
+        // commands/tasks: superseded by sync_fixed
+        "toggle_task_complete",
+        "create_google_task_simple", "update_google_task_simple", "create_task_id_mapping",
+        "get_task_id_mapping_by_local", "get_task_id_mapping_by_google", "update_task_id_mapping",
+        "delete_task_id_mapping",
+        // commands/gmail: debugging and one-off migrations
+        "listen_oauth_callback", "open_browser", "handle_oauth_redirect", "get_oauth_ports",
+        "create_gmail_template", "debug_gmail_secure_table",
+        "debug_gmail_token_expiration", "cleanup_corrupted_gmail_tokens", "debug_list_all_gmail_accounts",
+        "clear_all_gmail_tokens", "migrate_gmail_accounts_to_default_user",
+        // commands/system
+        "database_health_check", "get_conversation_context", "update_conversation_context",
+        "get_chat_templates", "get_chat_template", "create_chat_template", "update_chat_template",
+        "increment_template_usage", "record_performance_metric", "get_performance_metrics",
+        "cache_request", "get_cached_request", "get_user_preference", "set_user_preference",
+        "get_all_user_preferences", "get_application_logs",
+        "export_chat_session", "export_chat_session_markdown", "get_system_health",
+    ];
+
+    /// A type without whitespace or module paths, e.g. `Option<Vec<Foo>>`
+    fn normalize_type(type_name: &str) -> String {
+        let compact: String = type_name.chars().filter(|c| !c.is_whitespace()).collect();
+        let mut normalized = String::new();
+        let mut rest = compact.as_str();
+        while let Some(index) = rest.find("::") {
+            normalized.push_str(rest[..index].trim_end_matches(|c: char| c.is_alphanumeric() || c == '_'));
+            rest = &rest[index + 2..];
+        }
+        normalized.push_str(rest);
+        normalized
+    }
+
+    /// The parameters in a function's parameter list that the frontend
+    /// passes; Tauri injects state, the app handle and windows itself
+    fn frontend_parameters(parameter_list: &str) -> Signature {
+        let parameter_list: String = parameter_list
+            .lines()
+            .map(|line| line.split("//").next().unwrap())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut parameters = Vec::new();
+        let mut depth = 0;
+        let mut start = 0;
+        for (index, c) in parameter_list.char_indices().chain([(parameter_list.len(), ',')]) {
+            match c {
+                '<' | '(' | '[' => depth += 1,
+                '>' | ')' | ']' => depth -= 1,
+                ',' if depth == 0 => {
+                    let parameter = parameter_list[start..index].trim();
+                    start = index + 1;
+                    let Some((name, type_name)) = parameter.split_once(':') else {
+                        continue;
+                    };
+                    let type_name = normalize_type(type_name);
+                    if ["State<", "AppHandle", "Window", "WebviewWindow"].iter().any(|injected| type_name.starts_with(injected)) {
+                        continue;
+                    }
+                    parameters.push((name.trim().trim_start_matches("mut ").to_string(), type_name));
+                }
+                _ => {}
+            }
+        }
+        parameters
+    }
+
+    /// Every function marked `#[tauri::command]` under `src/`, by name,
+    /// with the parameters of each declaration
+    fn declared_commands() -> HashMap<String, Vec<Signature>> {
+        fn visit(dir: &Path, commands: &mut HashMap<String, Vec<Signature>>) {
+            for entry in fs::read_dir(dir).unwrap().flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    visit(&path, commands);
+                } else if path.extension().is_some_and(|ext| ext == "rs") {
+                    let source = fs::read_to_string(&path).unwrap();
+                    for (attribute, marker) in source.match_indices("#[tauri::command]") {
+                        let line_start = source[..attribute].rfind('\n').map_or(0, |index| index + 1);
+                        let line_end = source[attribute..].find('\n').map_or(source.len(), |index| attribute + index);
+                        if source[line_start..line_end].trim() != marker {
+                            continue;
+                        }
+                        let function = &source[line_end..];
+                        let function = &function[function.find("fn ").expect("command function") + 3..];
+                        let name = function.split(['(', '<']).next().unwrap().trim();
+
+                        let open = function.find('(').unwrap();
+                        let mut depth = 0;
+                        let close = function[open..]
+                            .char_indices()
+                            .find_map(|(index, c)| {
+                                depth += match c {
+                                    '(' => 1,
+                                    ')' => -1,
+                                    _ => 0,
+                                };
+                                (depth == 0).then_some(open + index)
+                            })
+                            .expect("closed parameter list");
+                        let signature = frontend_parameters(&function[open + 1..close]);
+                        commands.entry(name.to_string()).or_default().push(signature);
+                    }
+                }
+            }
+        }
+
+        let mut commands = HashMap::new();
+        visit(&Path::new(env!("CARGO_MANIFEST_DIR")).join("src"), &mut commands);
+        commands
+    }
+
+    #[test]
+    fn test_every_command_is_registered() {
+        let registered: HashSet<String> = COMMANDS.iter().map(|c| c.name.to_string()).collect();
+        assert_eq!(registered.len(), COMMANDS.len(), "duplicate registry entries");
+
+        let declared = declared_commands();
+        let missing: Vec<_> = declared
+            .keys()
+            .filter(|name| !registered.contains(*name) && !UNREGISTERED.contains(&name.as_str()))
+            .collect();
+        assert!(missing.is_empty(), "commands without registry entries: {:?}", missing);
+
+        let stale: Vec<_> = UNREGISTERED
+            .iter()
+            .filter(|name| registered.contains(**name) || !declared.contains_key(**name))
+            .collect();
+        assert!(stale.is_empty(), "UNREGISTERED lists registered or removed commands: {:?}", stale);
+    }
+
+    #[test]
+    fn test_parameters_match_the_command_signatures() {
+        let declared = declared_commands();
+        let mismatched: Vec<_> = COMMANDS
+            .iter()
+            .filter_map(|command| {
+                let registered: Signature = command
+                    .parameters
+                    .iter()
+                    .map(|param| (param.name.to_string(), normalize_type(param.type_name)))
+                    .collect();
+                let signatures = declared.get(command.name)?;
+                (!signatures.contains(&registered)).then(|| format!("{}: registered {:?}, declared {:?}", command.name, registered, signatures))
+            })
+            .collect();
+        assert!(mismatched.is_empty(), "registry parameters differ from the command signatures:\n{}", mismatched.join("\n"));
+    }
+
+    #[test]
+    fn test_invoke_keys() {
+        assert_eq!(invoke_key("account_id"), "accountId");
+        assert_eq!(invoke_key("_request"), "request");
+        let info = CommandInfo::from(find_command("get_calendar_events").unwrap());
+        assert!(info.parameters[0].required);
+        assert!(!info.parameters[2].required);
+    }
+
+    #[tokio::test]
+    async fn test_registry_filters_by_category() {
+        let system = get_command_registry(Some("system".to_string())).await.unwrap();
+        assert!(system.iter().all(|command| command.category == "system"));
+        let registry = system.iter().find(|command| command.name == "get_command_registry").unwrap();
+        assert!(!registry.parameters[0].required);
+        assert!(get_command_registry(Some("no-such-category".to_string())).await.unwrap().is_empty());
+        assert_eq!(get_command_registry(None).await.unwrap().len(), COMMANDS.len());
+    }
+}
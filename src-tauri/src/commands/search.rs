@@ -0,0 +1,27 @@
+//! Global Search Commands
+//!
+//! One search box across mail, notes, tasks, calendar events and chats.
+
+use anyhow::Context;
+use std::sync::Arc;
+use tauri::State;
+
+use crate::database::DatabaseManager;
+use crate::services::global_search::{self, GlobalSearchPage, GlobalSearchRequest};
+use crate::errors::{CommandError, CommandResult};
+
+/// Search every domain (or the requested ones) and return one ranked page
+#[tauri::command]
+pub async fn global_search(
+    request: GlobalSearchRequest,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<GlobalSearchPage> {
+    let db_manager = db_manager.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        global_search::global_search(&conn, &request).map_err(CommandError::from)
+    })
+    .await
+    .context("Task execution failed")?
+}
@@ -0,0 +1,100 @@
+//! Settings Commands
+//!
+//! Typed get/set over user preferences for the settings screen, plus
+//! import and export of all settings as JSON, the backend's language and
+//! the time zone.
+
+use anyhow::Context;
+use serde_json::Value;
+use std::sync::Arc;
+use tauri::State;
+
+use crate::database::DatabaseManager;
+use crate::services::i18n::{self, LocaleInfo, LOCALE_CHOICES, LOCALE_SETTING};
+use crate::services::settings::{self, ImportReport, SettingDescriptor, SettingValue, SettingsExport, SettingsService};
+use crate::services::time_service::{TimeService, TimeZoneInfo};
+use crate::errors::{CommandResult, LibreOllamaError};
+
+/// Every known setting with its type, default and allowed values
+#[tauri::command]
+pub async fn get_settings_schema() -> CommandResult<Vec<SettingDescriptor>> {
+    Ok(settings::SETTINGS.iter().map(SettingDescriptor::from).collect())
+}
+
+/// Current value of every setting, or of one category
+#[tauri::command]
+pub async fn get_settings(
+    category: Option<String>,
+    settings_service: State<'_, SettingsService>,
+) -> CommandResult<Vec<SettingValue>> {
+    Ok(settings_service.get_all(category.as_deref())?)
+}
+
+#[tauri::command]
+pub async fn get_setting(key: String, settings_service: State<'_, SettingsService>) -> CommandResult<SettingValue> {
+    Ok(settings_service.get(&key)?)
+}
+
+/// Validate and store a setting, notifying running services of the change
+#[tauri::command]
+pub async fn set_setting(
+    key: String,
+    value: Value,
+    settings_service: State<'_, SettingsService>,
+) -> CommandResult<SettingValue> {
+    Ok(settings_service.set(&key, &value)?)
+}
+
+/// Restore a setting's default
+#[tauri::command]
+pub async fn reset_setting(key: String, settings_service: State<'_, SettingsService>) -> CommandResult<SettingValue> {
+    Ok(settings_service.reset(&key)?)
+}
+
+/// All settings as a JSON document the user can save
+#[tauri::command]
+pub async fn export_settings(settings_service: State<'_, SettingsService>) -> CommandResult<SettingsExport> {
+    Ok(settings_service.export()?)
+}
+
+/// Apply an exported settings document; invalid entries are reported, not applied
+#[tauri::command]
+pub async fn import_settings(
+    data: SettingsExport,
+    settings_service: State<'_, SettingsService>,
+) -> CommandResult<ImportReport> {
+    Ok(settings_service.import(&data)?)
+}
+
+/// Languages the backend's messages are translated into
+#[tauri::command]
+pub async fn get_available_locales() -> CommandResult<Vec<LocaleInfo>> {
+    Ok(i18n::available_locales())
+}
+
+/// Choose a language ("system" follows the OS) and return the one now in use
+#[tauri::command]
+pub async fn set_locale(locale: String, settings_service: State<'_, SettingsService>) -> CommandResult<String> {
+    if !LOCALE_CHOICES.contains(&locale.as_str()) {
+        return Err(LibreOllamaError::InvalidInput {
+            message: format!("Unsupported locale '{}'", locale),
+            field: Some("locale".to_string()),
+        }
+        .into());
+    }
+    settings_service.set(LOCALE_SETTING, &Value::from(locale.as_str()))?;
+    let active = i18n::resolve(&locale);
+    i18n::set_active(active);
+    Ok(active.to_string())
+}
+
+/// The zone times are shown in, the system's zone and any configured one;
+/// the `time.zone` setting chooses it
+#[tauri::command]
+pub async fn get_time_zone(db_manager: State<'_, Arc<DatabaseManager>>) -> CommandResult<TimeZoneInfo> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(TimeService::load(&conn)
+        .and_then(|clock| clock.info(&conn))
+        .context("Failed to read the time zone")?)
+}
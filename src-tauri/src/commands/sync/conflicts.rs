@@ -0,0 +1,142 @@
+//! Sync Conflict Commands
+//!
+//! Commands for queuing offline edits, flushing them to the server and
+//! resolving the conflicts and rejections met along the way.
+
+use anyhow::Context;
+use crate::{
+    database::{
+        operations::{
+            pending_change_operations::{self, NewPendingChange, PendingChange, SyncConflict},
+            task_queue_operations,
+        },
+        DatabaseManager,
+    },
+    services::{
+        gmail::api_service::GmailApiService,
+        events::EventBus,
+        google::{calendar_service::GoogleCalendarService, tasks_service::GoogleTasksService},
+        sync::conflicts::{self, ConflictResolution, FlushSummary},
+        sync::task_queue,
+    },
+};
+use std::sync::Arc;
+use tauri::State;
+use crate::errors::{CommandResult, LibreOllamaError};
+
+/// Queue a local edit made while offline. Only changes the flush can push
+/// are accepted; anything else would sit in the queue forever.
+#[tauri::command]
+pub async fn queue_pending_change(
+    change: NewPendingChange,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<i64> {
+    if !conflicts::is_replayable(&change.entity_type, &change.operation) {
+        return Err(LibreOllamaError::InvalidInput {
+            message: format!("Cannot queue '{}' changes to {}", change.operation, change.entity_type),
+            field: Some("operation".to_string()),
+        }
+        .into());
+    }
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(pending_change_operations::record_pending_change(&conn, &change)
+        .context("Failed to queue change")?)
+}
+
+#[tauri::command]
+pub async fn get_pending_changes(
+    account_id: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<PendingChange>> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(pending_change_operations::list_pending_changes(&conn, &account_id)
+        .context("Failed to list pending changes")?)
+}
+
+/// Push queued edits, auto-merging where safe and recording conflicts otherwise
+#[tauri::command]
+pub async fn flush_pending_changes(
+    account_id: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    google_tasks_service: State<'_, GoogleTasksService>,
+    calendar_service: State<'_, GoogleCalendarService>,
+    gmail_api: State<'_, Arc<GmailApiService>>,
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<FlushSummary> {
+    let result = conflicts::flush_pending_changes(db_manager.inner().clone(), &google_tasks_service, &calendar_service, &gmail_api, &account_id)
+        .await
+        .context("Failed to flush pending changes");
+
+    task_queue::report_flush(&event_bus, &account_id, &result);
+    Ok(result?)
+}
+
+/// Changes the server refused, with the error it gave
+#[tauri::command]
+pub async fn get_failed_changes(
+    account_id: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<PendingChange>> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(pending_change_operations::list_failed_changes(&conn, &account_id)
+        .context("Failed to list failed changes")?)
+}
+
+/// Queue a refused change again, e.g. after fixing what the server objected to
+#[tauri::command]
+pub async fn retry_failed_change(
+    change_id: i64,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<()> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(pending_change_operations::set_pending_change_status(&conn, change_id, "pending", None)
+        .context("Failed to retry change")?)
+}
+
+/// Give up on a refused change. Dropping the create of a task made offline
+/// also removes the task locally.
+#[tauri::command]
+pub async fn discard_failed_change(
+    change_id: i64,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<()> {
+    let mut conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    let change = pending_change_operations::get_pending_change(&conn, change_id)
+        .context("Failed to load change")?
+        .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("pending change {}", change_id) })?;
+
+    if change.entity_type == "task" && task_queue_operations::is_temp_task_id(&change.entity_id) {
+        task_queue_operations::discard_unsynced_task(&mut conn, &change.entity_id)
+            .context("Failed to discard task")?;
+        return Ok(());
+    }
+    Ok(pending_change_operations::set_pending_change_status(&conn, change_id, "discarded", None)
+        .context("Failed to discard change")?)
+}
+
+#[tauri::command]
+pub async fn get_sync_conflicts(
+    account_id: Option<String>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<SyncConflict>> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(pending_change_operations::list_unresolved_conflicts(&conn, account_id.as_deref())
+        .context("Failed to get sync conflicts")?)
+}
+
+#[tauri::command]
+pub async fn resolve_sync_conflict(
+    conflict_id: i64,
+    resolution: ConflictResolution,
+    merged_payload: Option<serde_json::Value>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<SyncConflict> {
+    Ok(conflicts::resolve_conflict(&db_manager, conflict_id, resolution, merged_payload)
+        .context("Failed to resolve sync conflict")?)
+}
@@ -0,0 +1,28 @@
+//! Cache Delta Commands
+//!
+//! Catch-up for the frontend stores: `backend://cache-changed` events carry
+//! sequence numbers, and a store that missed some asks for everything after
+//! the last one it applied.
+
+use anyhow::Context;
+use crate::database::{
+    operations::cache_delta_operations::{self, ChangesSince},
+    DatabaseManager,
+};
+use std::sync::Arc;
+use tauri::State;
+use crate::errors::CommandResult;
+
+/// Cache deltas after `seq`, oldest first. `reset_required` means they were
+/// already pruned and the stores should reload instead.
+#[tauri::command]
+pub async fn get_changes_since(
+    seq: i64,
+    limit: Option<i64>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<ChangesSince> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(cache_delta_operations::get_changes_since(&conn, seq, limit.unwrap_or(1000).clamp(1, 5000))
+        .context("Failed to load cache changes")?)
+}
@@ -0,0 +1,239 @@
+//! Change Journal Commands
+//!
+//! Commands for browsing the local change journal, undoing the latest change
+//! to an entity and compacting old history. Task changes are undone at
+//! Google Tasks before the journal records the undo.
+
+use anyhow::Context;
+use crate::commands::tasks::sync_fixed::announce_task_change;
+use crate::database::{
+    operations::{change_journal_operations::{self, JournalEntry}, note_operations, task_queue_operations},
+    DatabaseManager,
+};
+use crate::services::events::EventBus;
+use crate::services::google::tasks_service::{CreateTaskInput, GoogleTasksService, UpdateTaskInput};
+use std::sync::Arc;
+use tauri::State;
+use crate::errors::{CommandError, CommandResult};
+
+/// Apply an inverse note event to the live notes table
+fn apply_note_inverse(conn: &rusqlite::Connection, entry: &JournalEntry) -> CommandResult<()> {
+    let note_id: i32 = entry.entity_id.parse().map_err(|_| CommandError::from("Invalid note ID"))?;
+    let title = entry.payload.get("title").and_then(|v| v.as_str());
+    let content = entry.payload.get("content").and_then(|v| v.as_str());
+    let folder_id = entry
+        .payload
+        .get("folder_id")
+        .map(|v| v.as_i64().map(|f| f as i32));
+
+    match entry.operation.as_str() {
+        "delete" => {
+            note_operations::delete_note(conn, note_id).context("Failed to delete note")?;
+        }
+        "create" => {
+            let user_id = entry
+                .payload
+                .get("user_id")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| CommandError::from("The deleted note's owner was not recorded, so it cannot be restored"))?;
+            // Deleted notes cannot get their old ID back; the restored copy is journaled under its new ID
+            let note = note_operations::create_note(
+                conn,
+                title.unwrap_or_default(),
+                content.unwrap_or_default(),
+                user_id,
+                folder_id.flatten(),
+            )
+            .context("Failed to restore note")?;
+            change_journal_operations::record_change(
+                conn, "note", &note.id.to_string(), "create", &entry.payload, None, None,
+            )
+            .context("Failed to journal restored note")?;
+        }
+        _ => {
+            note_operations::update_note_in(conn, note_id, title, content, folder_id)
+                .context("Failed to update note")?;
+        }
+    }
+    Ok(())
+}
+
+/// Apply the inverse of a task change at Google. Returns the new ID when
+/// a deleted task is recreated.
+async fn apply_task_inverse(
+    google_tasks_service: &GoogleTasksService,
+    account_id: &str,
+    change: &JournalEntry,
+    operation: &str,
+    payload: &serde_json::Value,
+) -> CommandResult<Option<String>> {
+    let text = |value: &serde_json::Value, key: &str| value.get(key).and_then(|v| v.as_str()).map(|s| s.to_string());
+    // The list the task is in now: where the change put it, or where it was
+    let list = text(&change.payload, "task_list_id")
+        .or_else(|| change.previous_payload.as_ref().and_then(|previous| text(previous, "task_list_id")))
+        .ok_or_else(|| CommandError::from("The task's list was not recorded, so the change cannot be undone"))?;
+    let task_id = &change.entity_id;
+
+    match operation {
+        "delete" => {
+            google_tasks_service.delete_task(account_id, &list, task_id).await?;
+            Ok(None)
+        }
+        "create" => {
+            let title = text(payload, "title")
+                .ok_or_else(|| CommandError::from("The deleted task was not recorded, so it cannot be restored"))?;
+            let task = google_tasks_service
+                .create_task(
+                    account_id,
+                    &list,
+                    CreateTaskInput {
+                        title,
+                        notes: text(payload, "notes"),
+                        due: text(payload, "due"),
+                        status: text(payload, "status"),
+                    },
+                )
+                .await?;
+            Ok(Some(task.id))
+        }
+        _ => {
+            let mut list = list;
+            if let Some(previous_list) = text(payload, "task_list_id").filter(|previous| *previous != list) {
+                google_tasks_service.move_task_to_list(account_id, &list, task_id, &previous_list).await?;
+                list = previous_list;
+            }
+            let input = UpdateTaskInput {
+                title: text(payload, "title"),
+                notes: text(payload, "notes"),
+                due: text(payload, "due"),
+                status: text(payload, "status"),
+            };
+            if input.title.is_some() || input.notes.is_some() || input.due.is_some() || input.status.is_some() {
+                google_tasks_service.update_task(account_id, &list, task_id, input).await?;
+            }
+            Ok(None)
+        }
+    }
+}
+
+/// Undo the latest change to a task: Google first, then the journal
+async fn undo_task_change(
+    task_id: &str,
+    db_manager: &DatabaseManager,
+    google_tasks_service: &GoogleTasksService,
+    event_bus: &EventBus,
+) -> CommandResult<Option<JournalEntry>> {
+    let change = {
+        let conn = db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        change_journal_operations::last_undoable_change(&conn, "task", task_id)
+            .context("Failed to load last change")?
+    };
+    let Some(change) = change else {
+        return Ok(None);
+    };
+    if task_queue_operations::is_temp_task_id(task_id) {
+        return Err("This task has not reached Google yet; undo it once it has synced".into());
+    }
+    let account_id = change
+        .account_id
+        .clone()
+        .ok_or_else(|| CommandError::from("The change has no account"))?;
+
+    let (operation, payload) = change_journal_operations::inverse_of(&change);
+    let restored_id = apply_task_inverse(google_tasks_service, &account_id, &change, operation, &payload).await?;
+
+    let inverse = {
+        let mut conn = db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        let tx = conn.transaction().context("Failed to start transaction")?;
+        let inverse = change_journal_operations::undo_last_change(&tx, "task", task_id)
+            .context("Failed to undo change")?;
+        // A recreated task has a new ID; its history continues under it
+        if let Some(restored_id) = &restored_id {
+            change_journal_operations::record_change(&tx, "task", restored_id, "create", &payload, None, Some(&account_id))
+                .context("Failed to journal restored task")?;
+        }
+        tx.commit().context("Failed to commit undo")?;
+        inverse
+    };
+
+    let announced = restored_id.as_deref().unwrap_or(task_id);
+    announce_task_change(event_bus, &account_id, announced, operation, payload);
+    Ok(inverse)
+}
+
+#[tauri::command]
+pub async fn get_change_history(
+    entity_type: String,
+    entity_id: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<JournalEntry>> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(change_journal_operations::get_entity_history(&conn, &entity_type, &entity_id)
+        .context("Failed to get change history")?)
+}
+
+#[tauri::command]
+pub async fn get_recent_changes(
+    limit: Option<i64>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<JournalEntry>> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(change_journal_operations::get_recent_changes(&conn, limit.unwrap_or(50))
+        .context("Failed to get recent changes")?)
+}
+
+/// Undo the latest change to an entity.
+///
+/// Notes and tasks are restored directly; for other entity types the
+/// inverse event is returned for the caller to apply through the owning
+/// service.
+#[tauri::command]
+pub async fn undo_last_change(
+    entity_type: String,
+    entity_id: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    google_tasks_service: State<'_, GoogleTasksService>,
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<Option<JournalEntry>> {
+    if entity_type == "task" {
+        return undo_task_change(&entity_id, &db_manager, &google_tasks_service, &event_bus).await;
+    }
+
+    let db_manager_clone = db_manager.inner().clone();
+
+    tokio::task::spawn_blocking(move || {
+        let mut conn = db_manager_clone.get_connection()
+            .context("Failed to get database connection")?;
+        // The undone flag and the restored data land together or not at all
+        let tx = conn.transaction().context("Failed to start transaction")?;
+
+        let inverse = change_journal_operations::undo_last_change(&tx, &entity_type, &entity_id)
+            .context("Failed to undo change")?;
+
+        if let Some(entry) = &inverse {
+            if entity_type == "note" {
+                apply_note_inverse(&tx, entry)?;
+            }
+        }
+        tx.commit().context("Failed to commit undo")?;
+
+        Ok::<Option<JournalEntry>, CommandError>(inverse)
+    })
+    .await
+    .context("Task join error")?
+}
+
+#[tauri::command]
+pub async fn compact_change_journal(
+    older_than_days: Option<i64>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<usize> {
+    let mut conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(change_journal_operations::compact_journal(&mut conn, older_than_days.unwrap_or(30))
+        .context("Failed to compact change journal")?)
+}
@@ -0,0 +1,14 @@
+//! Sync Commands Module
+//!
+//! This module contains cross-domain sync commands.
+
+pub mod conflicts;
+pub mod deltas;
+pub mod journal;
+pub mod status;
+
+// Re-export all sync commands for easy access
+pub use conflicts::*;
+pub use deltas::*;
+pub use journal::*;
+pub use status::*;
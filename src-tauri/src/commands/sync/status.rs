@@ -0,0 +1,175 @@
+//! Sync Status Dashboard
+//!
+//! Gathers per-account sync health for the settings panel: when each kind of
+//! sync last succeeded, what is still waiting to upload, background jobs in
+//! progress, recent categorized errors, and when the next runs are due.
+
+use anyhow::Context;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::State;
+
+use crate::config::get_config_manager;
+use crate::database::{
+    operations::{
+        backfill_operations, campaign_operations, draft_operations, pending_change_operations,
+        sync_run_operations::{self, SyncRun},
+    },
+    DatabaseManager,
+};
+use crate::errors::{CommandError, CommandResult};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingUploads {
+    pub changes: i64,
+    pub drafts: i64,
+    pub conflicts: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncJobProgress {
+    /// "backfill" or "campaign"
+    pub kind: String,
+    pub id: String,
+    pub status: String,
+    pub percent_complete: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncErrorSummary {
+    pub domain: String,
+    pub category: String,
+    pub message: String,
+    pub occurred_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountSyncStatus {
+    pub account_id: String,
+    pub email: String,
+    pub last_full_sync: Option<String>,
+    pub last_incremental_sync: Option<String>,
+    pub pending_uploads: PendingUploads,
+    pub jobs: Vec<SyncJobProgress>,
+    pub recent_errors: Vec<SyncErrorSummary>,
+    pub next_full_sync: String,
+    pub next_incremental_sync: String,
+}
+
+/// sync_runs timestamps are SQLite CURRENT_TIMESTAMP values in UTC
+fn parse_run_time(run: &SyncRun) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(&run.finished_at, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .map(|naive| naive.and_utc())
+}
+
+/// Next run is one interval after the last success, or now if it is overdue
+fn next_due(last: Option<&SyncRun>, interval: chrono::Duration) -> String {
+    let now = Utc::now();
+    last.and_then(parse_run_time)
+        .map(|at| (at + interval).max(now))
+        .unwrap_or(now)
+        .to_rfc3339()
+}
+
+fn account_status(
+    conn: &rusqlite::Connection,
+    account_id: &str,
+    email: &str,
+    full_interval: chrono::Duration,
+    incremental_interval: chrono::Duration,
+) -> anyhow::Result<AccountSyncStatus> {
+    let last_full = sync_run_operations::last_successful_run(conn, account_id, "full")?;
+    let last_incremental = sync_run_operations::last_successful_run(conn, account_id, "incremental")?;
+
+    let pending_uploads = PendingUploads {
+        changes: pending_change_operations::count_pending_changes(conn, account_id)?,
+        drafts: draft_operations::list_unsynced_drafts(conn, account_id)?.len() as i64,
+        conflicts: pending_change_operations::list_unresolved_conflicts(conn, Some(account_id))?.len() as i64,
+    };
+
+    let mut jobs = Vec::new();
+    if let Some(job) = backfill_operations::get_job(conn, account_id)? {
+        if job.status != "completed" {
+            jobs.push(SyncJobProgress {
+                kind: "backfill".to_string(),
+                id: account_id.to_string(),
+                status: job.status.clone(),
+                percent_complete: job.percent_complete(),
+            });
+        }
+    }
+    for campaign in campaign_operations::list_campaigns(conn, account_id)? {
+        if !matches!(campaign.status.as_str(), "running" | "paused") {
+            continue;
+        }
+        if let Some(progress) = campaign_operations::campaign_progress(conn, campaign.id)? {
+            jobs.push(SyncJobProgress {
+                kind: "campaign".to_string(),
+                id: campaign.id.to_string(),
+                status: progress.status,
+                percent_complete: progress.percent_complete,
+            });
+        }
+    }
+
+    let recent_errors = sync_run_operations::recent_failures(conn, account_id, 10)?
+        .into_iter()
+        .map(|run| SyncErrorSummary {
+            domain: run.domain,
+            category: run.error_category.unwrap_or_else(|| "general".to_string()),
+            message: run.error.unwrap_or_default(),
+            occurred_at: run.finished_at,
+        })
+        .collect();
+
+    Ok(AccountSyncStatus {
+        account_id: account_id.to_string(),
+        email: email.to_string(),
+        next_full_sync: next_due(last_full.as_ref(), full_interval),
+        next_incremental_sync: next_due(last_incremental.as_ref(), incremental_interval),
+        last_full_sync: last_full.map(|run| run.finished_at),
+        last_incremental_sync: last_incremental.map(|run| run.finished_at),
+        pending_uploads,
+        jobs,
+        recent_errors,
+    })
+}
+
+/// Sync health for every active account
+#[tauri::command]
+pub async fn get_sync_status(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<AccountSyncStatus>> {
+    let (full_interval, incremental_interval) = match get_config_manager() {
+        Ok(config) => (
+            chrono::Duration::hours(config.sync().full_sync_interval_hours as i64),
+            chrono::Duration::minutes(config.sync().incremental_sync_interval_minutes as i64),
+        ),
+        Err(_) => (chrono::Duration::hours(24), chrono::Duration::minutes(15)),
+    };
+
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+
+    let accounts: Vec<(String, String)> = {
+        let mut stmt = conn
+            .prepare("SELECT id, email_address FROM gmail_accounts_secure WHERE is_active = 1 ORDER BY created_at")
+            .context("Failed to prepare account query")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .context("Failed to query accounts")?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .context("Failed to read accounts")?
+    };
+
+    accounts
+        .iter()
+        .map(|(account_id, email)| {
+            account_status(&conn, account_id, email, full_interval, incremental_interval)
+                .with_context(|| format!("Failed to get sync status for {}", email))
+                .map_err(CommandError::from)
+        })
+        .collect()
+}
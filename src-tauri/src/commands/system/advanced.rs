@@ -3,17 +3,19 @@
 //! This module contains Tauri commands for context management,
 //! chat templates, performance metrics, and other advanced features.
 
+use anyhow::Context;
 use serde::{Deserialize, Serialize};
 use crate::database::models::{ConversationContext, ChatTemplate, UserPreference, ApplicationLog, PerformanceMetric, MetricType, RequestCache, PreferenceType, LogLevel, ChatSession, ChatMessage};
 use tauri::State;
+use crate::errors::CommandResult;
 
 // ===== Context Management Commands =====
 
 #[tauri::command]
-pub async fn get_conversation_context(session_id: String) -> Result<Option<ConversationContext>, String> {
-    crate::database::get_conversation_context(&session_id)
+pub async fn get_conversation_context(session_id: String) -> CommandResult<Option<ConversationContext>> {
+    Ok(crate::database::get_conversation_context(&session_id)
         .await
-        .map_err(|e| format!("Failed to get conversation context: {}", e))
+        .context("Failed to get conversation context")?)
 }
 
 #[tauri::command]
@@ -23,11 +25,11 @@ pub async fn update_conversation_context(
     context_summary: Option<String>,
     _token_count: i32, // This parameter seems unused based on ConversationContext::new and update_context
     db_manager: State<'_, crate::database::DatabaseManager>,
-) -> Result<ConversationContext, String> {
+) -> CommandResult<ConversationContext> {
     // Get existing context or create new one
     let mut context = crate::database::get_conversation_context(&session_id)
         .await
-        .map_err(|e| format!("Failed to get context: {}", e))?
+        .context("Failed to get context")?
         .unwrap_or_else(|| ConversationContext::new(session_id.clone(), context_window_size.unwrap_or_default()));
 
     // Update context
@@ -62,7 +64,7 @@ pub async fn update_conversation_context(
         }
     });
 
-    update_task.await.map_err(|e| e.to_string())?.map_err(|e: anyhow::Error| e.to_string())?;
+    update_task.await??;
 
     Ok(context)
 }
@@ -70,17 +72,17 @@ pub async fn update_conversation_context(
 // ===== Chat Template Commands =====
 
 #[tauri::command]
-pub async fn get_chat_templates(active_only: Option<bool>) -> Result<Vec<ChatTemplate>, String> {
-    crate::database::get_chat_templates(active_only.unwrap_or(true))
+pub async fn get_chat_templates(active_only: Option<bool>) -> CommandResult<Vec<ChatTemplate>> {
+    Ok(crate::database::get_chat_templates(active_only.unwrap_or(true))
         .await
-        .map_err(|e| format!("Failed to get chat templates: {}", e))
+        .context("Failed to get chat templates")?)
 }
 
 #[tauri::command]
-pub async fn get_chat_template(template_id: String) -> Result<Option<ChatTemplate>, String> {
-    crate::database::get_chat_template_by_id(&template_id.parse().unwrap_or_default()) // Assuming template_id is i32
+pub async fn get_chat_template(template_id: String) -> CommandResult<Option<ChatTemplate>> {
+    Ok(crate::database::get_chat_template_by_id(&template_id.parse().unwrap_or_default()) // Assuming template_id is i32
         .await
-        .map_err(|e| format!("Failed to get chat template: {}", e))
+        .context("Failed to get chat template")?)
 }
 
 #[tauri::command]
@@ -92,7 +94,7 @@ pub async fn create_chat_template(
     _model_config: Option<String>,    // These fields are not in ChatTemplate struct
     _is_default: Option<bool>,        // These fields are not in ChatTemplate struct
     db_manager: State<'_, crate::database::DatabaseManager>,
-) -> Result<ChatTemplate, String> {
+) -> CommandResult<ChatTemplate> {
     let template = ChatTemplate::new(name, description.unwrap_or_default(), system_message.unwrap_or_default());
 
     let template_clone = template.clone();
@@ -102,7 +104,7 @@ pub async fn create_chat_template(
         crate::database::operations::template_operations::create_chat_template(&conn, &template_clone.template_name, &template_clone.template_content)
     });
 
-    create_task.await.map_err(|e| e.to_string())?.map_err(|e: anyhow::Error| e.to_string())?;
+    create_task.await??;
 
     Ok(template)
 }
@@ -118,7 +120,7 @@ pub async fn update_chat_template(
     _is_default: Option<bool>,        // Not a field
     _is_active: Option<bool>,         // Not a field
     db_manager: State<'_, crate::database::DatabaseManager>,
-) -> Result<ChatTemplate, String> {
+) -> CommandResult<ChatTemplate> {
     let template_id_int = template_id.parse().unwrap_or_default();
     let db_manager_clone = db_manager.inner().clone();
 
@@ -127,9 +129,7 @@ pub async fn update_chat_template(
         let conn = db_manager_clone.get_connection()?;
         crate::database::operations::template_operations::get_chat_template(&conn, template_id_int)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?
+    .await??
     .ok_or("Template not found")?;
 
     // Update fields if provided
@@ -143,7 +143,7 @@ pub async fn update_chat_template(
         crate::database::operations::template_operations::update_chat_template(&conn, template_clone.id, &template_clone.template_name, &template_clone.template_content)
     });
 
-    update_task.await.map_err(|e| e.to_string())?.map_err(|e: anyhow::Error| e.to_string())?;
+    update_task.await??;
 
     Ok(template)
 }
@@ -152,7 +152,7 @@ pub async fn update_chat_template(
 pub async fn increment_template_usage(
     template_id: String,
     db_manager: State<'_, crate::database::DatabaseManager>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     let template_id_int = template_id.parse().unwrap_or_default();
     let db_manager_clone = db_manager.inner().clone();
     
@@ -160,9 +160,7 @@ pub async fn increment_template_usage(
         let conn = db_manager_clone.get_connection()?;
         crate::database::operations::template_operations::get_chat_template(&conn, template_id_int)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?
+    .await??
     .ok_or("Template not found")?;
 
     // template.increment_usage(); // increment_usage method doesn't exist
@@ -173,9 +171,7 @@ pub async fn increment_template_usage(
         let conn = db_manager_clone_update.get_connection()?;
         crate::database::operations::template_operations::update_chat_template(&conn, template_clone.id, &template_clone.template_name, &template_clone.template_content)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
 
     Ok(())
 }
@@ -190,7 +186,7 @@ pub async fn record_performance_metric(
     _operation: Option<String>, // Not a field in PerformanceMetric
     tags: Option<String>, // Renamed to metadata in PerformanceMetric
     db_manager: State<'_, crate::database::DatabaseManager>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     let metric = PerformanceMetric::new(
         MetricType::from(metric_type),
         value,
@@ -203,9 +199,7 @@ pub async fn record_performance_metric(
         let conn = db_manager_clone.get_connection()?;
         crate::database::operations::performance_operations::create_performance_metric(&conn, metric.metric_type, metric.value, metric.metadata)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
 
     Ok(())
 }
@@ -220,14 +214,14 @@ pub async fn get_performance_metrics(
     end_time: Option<String>,   
     limit: Option<i32>,
     db_manager: State<'_, crate::database::DatabaseManager>,
-) -> Result<Vec<PerformanceMetric>, String> {
+) -> CommandResult<Vec<PerformanceMetric>> {
     let metric_type = metric_type.map(MetricType::from);
     let start_time = start_time.and_then(|s| s.parse::<chrono::NaiveDateTime>().ok());
     let end_time = end_time.and_then(|s| s.parse::<chrono::NaiveDateTime>().ok());
     let limit = limit.map(|l| l as usize);
     
     let db_manager_clone = db_manager.inner().clone();
-    tokio::task::spawn_blocking(move || {
+    Ok(tokio::task::spawn_blocking(move || {
         let conn = db_manager_clone.get_connection()?;
         crate::database::operations::performance_operations::get_performance_metrics_by_type(
             &conn,
@@ -237,9 +231,7 @@ pub async fn get_performance_metrics(
             limit,
         )
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())
+    .await??)
 }
 
 // ===== Cache Management Commands =====
@@ -250,7 +242,7 @@ pub async fn cache_request(
     value: String, // JSON string
     ttl_seconds: Option<i64>,
     db_manager: State<'_, crate::database::DatabaseManager>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     let expires_at = chrono::Local::now().naive_local() + chrono::Duration::seconds(ttl_seconds.unwrap_or(3600));
     let cache = RequestCache {
         id: 0,
@@ -263,16 +255,15 @@ pub async fn cache_request(
     let db_manager_clone = db_manager.inner().clone();
     tokio::task::spawn_blocking(move || {
         let conn = db_manager_clone.get_connection()?;
+        let key = crate::services::workspaces::scope(&conn)?.cache_key(&cache.request_hash);
         crate::database::operations::cache_operations::create_cache_entry(
             &conn,
-            &cache.request_hash,
+            &key,
             &cache.response_body,
             cache.expires_at.unwrap_or_else(|| chrono::Local::now().naive_local()),
         )
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
 
     Ok(())
 }
@@ -281,15 +272,14 @@ pub async fn cache_request(
 pub async fn get_cached_request(
     key: String,
     db_manager: State<'_, crate::database::DatabaseManager>,
-) -> Result<Option<RequestCache>, String> {
+) -> CommandResult<Option<RequestCache>> {
     let db_manager_clone = db_manager.inner().clone();
-    tokio::task::spawn_blocking(move || {
+    Ok(tokio::task::spawn_blocking(move || {
         let conn = db_manager_clone.get_connection()?;
+        let key = crate::services::workspaces::scope(&conn)?.cache_key(&key);
         crate::database::operations::cache_operations::get_valid_cache_entry(&conn, &key)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())
+    .await??)
 }
 
 // ===== User Preference Commands =====
@@ -298,15 +288,13 @@ pub async fn get_cached_request(
 pub async fn get_user_preference(
     key: String,
     db_manager: State<'_, crate::database::DatabaseManager>,
-) -> Result<Option<UserPreference>, String> {
+) -> CommandResult<Option<UserPreference>> {
     let db_manager_clone = db_manager.inner().clone();
-    tokio::task::spawn_blocking(move || {
+    Ok(tokio::task::spawn_blocking(move || {
         let conn = db_manager_clone.get_connection()?;
         crate::database::operations::preference_operations::get_user_preference_by_key(&conn, &key)
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())
+    .await??)
 }
 
 #[tauri::command]
@@ -316,7 +304,7 @@ pub async fn set_user_preference(
     preference_type: Option<String>, // "string", "number", "boolean", "json"
     _is_system_preference: Option<bool>, // Not a field in UserPreference
     db_manager: State<'_, crate::database::DatabaseManager>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     let pref_type = preference_type.map(|pt| PreferenceType::from_string(&pt)).unwrap_or(PreferenceType::String);
     
     let db_manager_clone = db_manager.inner().clone();
@@ -329,9 +317,7 @@ pub async fn set_user_preference(
             pref_type.as_str(),
         )
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
 
     Ok(())
 }
@@ -340,11 +326,11 @@ pub async fn set_user_preference(
 pub async fn get_all_user_preferences(
     system_only: Option<bool>,
     db_manager: State<'_, crate::database::DatabaseManager>,
-) -> Result<Vec<UserPreference>, String> {
+) -> CommandResult<Vec<UserPreference>> {
     let system_only = system_only.unwrap_or(false);
     let db_manager_clone = db_manager.inner().clone();
 
-    tokio::task::spawn_blocking(move || {
+    Ok(tokio::task::spawn_blocking(move || {
         let conn = db_manager_clone.get_connection()?;
         if system_only {
             crate::database::operations::preference_operations::get_user_preferences_by_type_pattern(&conn, "system%")
@@ -352,9 +338,7 @@ pub async fn get_all_user_preferences(
             crate::database::operations::preference_operations::get_all_user_preferences(&conn)
         }
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())
+    .await??)
 }
 
 // ===== Application Logging Commands =====
@@ -366,7 +350,7 @@ pub async fn log_application_event(
     component: Option<String>, // Not a field in ApplicationLog, combined into message
     context: Option<String>, // Renamed to details in ApplicationLog::new
     db_manager: State<'_, crate::database::DatabaseManager>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     let log_level = LogLevel::from_string(&level);
     let module_name = component.unwrap_or_else(|| "frontend".to_string());
     // 'context' can be mapped to 'function_name' or part of the message
@@ -383,9 +367,7 @@ pub async fn log_application_event(
             &function_name,
         )
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e: anyhow::Error| e.to_string())?;
+    .await??;
 
     Ok(())
 }
@@ -397,9 +379,9 @@ pub async fn get_application_logs(
     start_time: Option<String>, // ISO 8601
     end_time: Option<String>,   // ISO 8601
     limit: Option<i32>,
-) -> Result<Vec<ApplicationLog>, String> {
+) -> CommandResult<Vec<ApplicationLog>> {
     let log_level = level.map(|l| LogLevel::from_string(&l));
-    crate::database::get_application_logs(
+    Ok(crate::database::get_application_logs(
         log_level,
         component,
         start_time.and_then(|s| s.parse::<chrono::NaiveDateTime>().ok()), // Parse to NaiveDateTime
@@ -407,7 +389,7 @@ pub async fn get_application_logs(
         limit.map(|l| l as usize),
     )
     .await
-    .map_err(|e| format!("Failed to get application logs: {}", e))
+    .context("Failed to get application logs")?)
 }
 
 // ===== Chat Export/Import Commands =====
@@ -425,7 +407,7 @@ pub struct ChatExport {
 pub async fn export_chat_session(
     session_id: String,
     db_manager: State<'_, crate::database::DatabaseManager>,
-) -> Result<ChatExport, String> {
+) -> CommandResult<ChatExport> {
     let session_id_int = session_id.parse().unwrap_or_default();
     let db_manager_clone = db_manager.inner().clone();
 
@@ -441,9 +423,7 @@ pub async fn export_chat_session(
 
         Ok((session, messages, context))
     })
-    .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e| e.to_string())?;
+    .await??;
 
     Ok(ChatExport {
         session: export_data.0,
@@ -458,7 +438,7 @@ pub async fn export_chat_session(
 pub async fn export_chat_session_markdown(
     session_id: String,
     db_manager: State<'_, crate::database::DatabaseManager>,
-) -> Result<String, String> {
+) -> CommandResult<String> {
     let export = export_chat_session(session_id, db_manager).await?;
     
     let mut markdown = format!("# {}\n\n", export.session.session_name); // Use session_name
@@ -496,7 +476,7 @@ pub async fn export_chat_session_markdown(
 // ===== System Health Commands =====
 
 #[tauri::command]
-pub async fn get_system_health() -> Result<serde_json::Value, String> {
+pub async fn get_system_health() -> CommandResult<serde_json::Value> {
     // Database stats would need to be implemented in operations
     // For now, return placeholder data
     let db_stats = serde_json::json!({
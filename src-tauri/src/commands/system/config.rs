@@ -0,0 +1,21 @@
+//! Configuration Commands
+//!
+//! Inspect and reload the app configuration (`.env`, `config.json` and the
+//! environment) without restarting.
+
+use crate::config::{get_config_manager, ConfigReload, ConfigStatus};
+use crate::errors::CommandResult;
+
+/// Where the configuration came from, when it was loaded and whether the
+/// latest load was rejected
+#[tauri::command]
+pub async fn get_config_status() -> CommandResult<ConfigStatus> {
+    Ok(get_config_manager()?.status())
+}
+
+/// Re-read the configuration sources and apply them to running services.
+/// An invalid configuration is rejected and the current one kept.
+#[tauri::command]
+pub async fn reload_config() -> CommandResult<ConfigReload> {
+    Ok(get_config_manager()?.reload()?)
+}
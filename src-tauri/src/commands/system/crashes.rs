@@ -0,0 +1,43 @@
+//! Crash Report Commands
+//!
+//! Listing the crash reports saved by the panic hook and packaging one,
+//! sanitized, for a bug report. Nothing is sent anywhere; see
+//! `services::crash_reports`.
+
+use anyhow::Context;
+
+use crate::config::get_config_manager;
+use crate::services::crash_reports::{self, CrashReport, CrashReportSummary};
+use crate::errors::CommandResult;
+
+/// Saved crash reports, newest first
+#[tauri::command]
+pub async fn list_crash_reports() -> CommandResult<Vec<CrashReportSummary>> {
+    let config_manager = get_config_manager()?;
+    Ok(crash_reports::list_reports(&crash_reports::crashes_dir(&config_manager.paths().logs_dir))
+        .context("Failed to list crash reports")?)
+}
+
+#[tauri::command]
+pub async fn get_crash_report(report_id: String) -> CommandResult<CrashReport> {
+    let config_manager = get_config_manager()?;
+    Ok(crash_reports::get_report(&crash_reports::crashes_dir(&config_manager.paths().logs_dir), &report_id)
+        .context("Failed to read crash report")?)
+}
+
+#[tauri::command]
+pub async fn delete_crash_report(report_id: String) -> CommandResult<bool> {
+    let config_manager = get_config_manager()?;
+    Ok(crash_reports::delete_report(&crash_reports::crashes_dir(&config_manager.paths().logs_dir), &report_id)
+        .context("Failed to delete crash report")?)
+}
+
+/// Zip a sanitized copy of the report to attach to an issue; returns the
+/// zip's path
+#[tauri::command]
+pub async fn package_crash_report(report_id: String) -> CommandResult<String> {
+    let config_manager = get_config_manager()?;
+    Ok(crash_reports::package_report(&crash_reports::crashes_dir(&config_manager.paths().logs_dir), &report_id)
+        .map(|path| path.display().to_string())
+        .context("Failed to package crash report")?)
+}
@@ -1,18 +1,20 @@
 //! Debug database commands
+use anyhow::Context;
 use crate::database::DatabaseManager;
 use tauri::State;
 use std::sync::Arc;
 use serde_json::Value;
+use crate::errors::CommandResult;
 
 #[tauri::command]
 pub async fn debug_check_timeblock_data(
     db_manager: State<'_, Arc<DatabaseManager>>,
-) -> Result<Value, String> {
+) -> CommandResult<Value> {
     println!("🔍 DEBUG: Checking timeBlock data in database...");
     
     let conn = db_manager
         .get_connection()
-        .map_err(|e| format!("Failed to get database connection: {}", e))?;
+        .context("Failed to get database connection")?;
     
     // Check if time_block column exists
     let table_check: String = conn
@@ -39,13 +41,13 @@ pub async fn debug_check_timeblock_data(
     // Get a sample of tasks with timeBlock
     let mut stmt = conn
         .prepare("SELECT google_task_id, time_block FROM task_metadata WHERE time_block IS NOT NULL LIMIT 5")
-        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+        .context("Failed to prepare statement")?;
     
     let tasks_with_timeblock: Vec<(String, String)> = stmt
         .query_map([], |row| {
             Ok((row.get(0)?, row.get(1)?))
         })
-        .map_err(|e| format!("Failed to query: {}", e))?
+        .context("Failed to query")?
         .filter_map(Result::ok)
         .collect();
     
@@ -57,7 +59,7 @@ pub async fn debug_check_timeblock_data(
     // Get all metadata for debugging
     let mut all_metadata_stmt = conn
         .prepare("SELECT google_task_id, task_list_id, priority, time_block FROM task_metadata LIMIT 10")
-        .map_err(|e| format!("Failed to prepare metadata query: {}", e))?;
+        .context("Failed to prepare metadata query")?;
     
     let all_metadata: Vec<serde_json::Value> = all_metadata_stmt
         .query_map([], |row| {
@@ -73,7 +75,7 @@ pub async fn debug_check_timeblock_data(
                 "time_block": time_block
             }))
         })
-        .map_err(|e| format!("Failed to query metadata: {}", e))?
+        .context("Failed to query metadata")?
         .filter_map(Result::ok)
         .collect();
     
@@ -0,0 +1,75 @@
+//! Diagnostics Bundle Command
+//!
+//! Gathers config, schema, sync and health details into a sanitized zip the
+//! user can attach to a bug report. See `services::diagnostics`.
+
+use anyhow::Context;
+use std::sync::Arc;
+use tauri::State;
+
+use crate::commands::ollama::ollama_get_status;
+use crate::commands::sync::get_sync_status;
+use crate::config::get_config_manager;
+use crate::database::{operations::sync_run_operations, DatabaseManager};
+use crate::services::crash_reports;
+use crate::services::diagnostics::{self, DiagnosticsBundle, Manifest};
+use crate::services::ollama_supervisor::OllamaSupervisor;
+use crate::errors::CommandResult;
+
+/// Sync runs included alongside the per-account status
+const SYNC_RUN_LIMIT: i64 = 100;
+
+/// Write a diagnostics zip under the logs directory; returns its path
+#[tauri::command]
+pub async fn generate_diagnostics_bundle(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    supervisor: State<'_, OllamaSupervisor>,
+) -> CommandResult<String> {
+    let config_manager = get_config_manager()?;
+    let paths = config_manager.paths();
+    let status = get_sync_status(db_manager.clone()).await;
+    let ollama = ollama_get_status(db_manager.clone(), supervisor).await;
+
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+
+    let schema = diagnostics::schema_info(&conn)
+        .context("Failed to read migration history")?;
+    let recent_runs = sync_run_operations::recent_runs(&conn, SYNC_RUN_LIMIT)
+        .context("Failed to read sync runs")?;
+    let logs = diagnostics::recent_logs(&conn, &paths.logs_dir)
+        .context("Failed to read logs")?;
+    let quick_check = conn
+        .query_row("PRAGMA quick_check", [], |row| row.get::<_, String>(0))
+        .unwrap_or_else(|e| e.to_string());
+    let crash_count = crash_reports::list_reports(&crash_reports::crashes_dir(&paths.logs_dir))
+        .map(|reports| reports.len())
+        .unwrap_or(0);
+
+    let bundle = DiagnosticsBundle {
+        manifest: Manifest::now(),
+        config: (*config_manager.config()).clone(),
+        schema,
+        sync: serde_json::json!({
+            "accounts": status.unwrap_or_else(|e| { eprintln!("⚠️ [DIAGNOSTICS] {}", e); Vec::new() }),
+            "recent_runs": recent_runs,
+        }),
+        health: serde_json::json!({
+            "database": {
+                "path": db_manager.get_db_path(),
+                "reachable": db_manager.test_connection().unwrap_or(false),
+                "quick_check": quick_check,
+            },
+            "config": config_manager.status(),
+            "ollama": ollama
+                .map(|health| serde_json::json!(health))
+                .unwrap_or_else(|e| serde_json::json!({ "status": "error", "message": e })),
+            "crash_reports": crash_count,
+        }),
+        logs,
+    };
+
+    Ok(diagnostics::write_bundle(&diagnostics::diagnostics_dir(&paths.logs_dir), &bundle)
+        .map(|path| path.display().to_string())
+        .context("Failed to write diagnostics bundle")?)
+}
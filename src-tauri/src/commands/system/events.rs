@@ -0,0 +1,12 @@
+//! Backend Event Commands
+//!
+//! Lets the frontend discover which real-time events the backend emits.
+
+use crate::services::events::{self, EventDescriptor};
+use crate::errors::CommandResult;
+
+/// Return the schema of every backend event so the frontend can register listeners
+#[tauri::command]
+pub async fn subscribe_events() -> CommandResult<Vec<EventDescriptor>> {
+    Ok(events::event_schema())
+}
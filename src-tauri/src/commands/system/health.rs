@@ -2,9 +2,44 @@
 //!
 //! Commands for checking system health and status
 
+use crate::errors::CommandResult;
+
 /// Database health check command
 #[tauri::command]
-pub async fn database_health_check() -> Result<bool, String> {
+pub async fn database_health_check() -> CommandResult<bool> {
     // For now, return a simple success since we're using async operations
     Ok(true)
-} 
\ No newline at end of file
+}
+
+/// Whether startup has finished migrating the database and starting
+/// background services, for a frontend that missed `backend://ready`
+#[tauri::command]
+pub async fn get_backend_status(
+    registry: tauri::State<'_, crate::services::service_registry::ServiceRegistry>,
+) -> CommandResult<crate::services::service_registry::BackendStatus> {
+    Ok(registry.status())
+}
+
+/// Background tasks with their last run, next run and last error
+#[tauri::command]
+pub async fn get_background_tasks(
+    supervisor: tauri::State<'_, crate::services::task_supervisor::TaskSupervisor>,
+) -> CommandResult<Vec<crate::services::task_supervisor::BackgroundTaskStatus>> {
+    Ok(supervisor.statuses())
+}
+
+/// Key or pointer input in a window, which holds off idle-time work. The
+/// frontend throttles these reports.
+#[tauri::command]
+pub async fn report_user_activity(
+    activity: tauri::State<'_, crate::services::activity::ActivityMonitor>,
+) -> CommandResult<()> {
+    activity.record_activity();
+    Ok(())
+}
+
+/// Memory held by in-memory caches against their budgets
+#[tauri::command]
+pub async fn get_resource_usage() -> CommandResult<crate::services::resource_budget::ResourceUsage> {
+    Ok(crate::services::resource_budget::usage())
+}
@@ -1,18 +1,20 @@
 //! Database migration commands
+use anyhow::Context;
 use crate::database::DatabaseManager;
 use tauri::State;
 use std::sync::Arc;
+use crate::errors::CommandResult;
 
 #[tauri::command]
 pub async fn force_run_migrations(
     db_manager: State<'_, Arc<DatabaseManager>>,
-) -> Result<String, String> {
+) -> CommandResult<String> {
     println!("Force running database migrations...");
     
     db_manager
         .run_migrations()
         .await
-        .map_err(|e| format!("Failed to run migrations: {}", e))?;
+        .context("Failed to run migrations")?;
     
     println!("Database migrations completed successfully");
     Ok("Database migrations completed successfully".to_string())
@@ -6,9 +6,19 @@ pub mod advanced;
 pub mod health;
 pub mod migrations;
 pub mod debug_db;
+pub mod events;
+pub mod config;
+pub mod wipe;
+pub mod crashes;
+pub mod diagnostics;
 
 // Re-export all system commands for easy access
 pub use advanced::*;
 pub use health::*;
 pub use migrations::*;
-pub use debug_db::*; 
\ No newline at end of file
+pub use debug_db::*;
+pub use events::*;
+pub use config::*;
+pub use wipe::*;
+pub use crashes::*;
+pub use diagnostics::*;
\ No newline at end of file
@@ -0,0 +1,81 @@
+//! Secure Wipe Commands
+//!
+//! Destroying all local data before a machine is handed on. The app quits
+//! once the wipe is done; see `services::secure_wipe`.
+
+use anyhow::Context;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, State};
+
+use crate::config::{get_config_manager, ConfigManager};
+use crate::database::DatabaseManager;
+use crate::services::resource_budget;
+use crate::services::secure_wipe::{self, SecureWipe, WipeChallenge, WipeReport};
+use crate::services::task_supervisor::TaskSupervisor;
+use crate::errors::CommandResult;
+
+/// Lets the frontend show the report before the app quits
+const EXIT_DELAY: Duration = Duration::from_secs(3);
+
+/// How long running background work gets to stop before the wipe starts
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn targets(db_manager: &DatabaseManager, config_manager: &ConfigManager) -> Vec<PathBuf> {
+    let paths = config_manager.paths();
+    let mut others = vec![paths.attachments_dir, paths.cache_dir, paths.logs_dir, paths.temp_dir];
+    others.extend(config_manager.files());
+    secure_wipe::wipe_targets(db_manager.get_db_path(), &others)
+}
+
+/// Start a wipe: returns the token and phrase `secure_wipe` needs, and what
+/// would be destroyed
+#[tauri::command]
+pub async fn request_secure_wipe(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    wipe: State<'_, SecureWipe>,
+) -> CommandResult<WipeChallenge> {
+    let config_manager = get_config_manager()?;
+    Ok(wipe.request(&targets(&db_manager, &config_manager)))
+}
+
+/// Stop background work, then shred the database, attachments, caches,
+/// logs and configuration, and quit
+#[tauri::command]
+pub async fn secure_wipe(
+    token: String,
+    confirmation_phrase: String,
+    app_handle: AppHandle,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    supervisor: State<'_, TaskSupervisor>,
+    wipe: State<'_, SecureWipe>,
+) -> CommandResult<WipeReport> {
+    let config_manager = get_config_manager()?;
+    wipe.confirm(&token, &confirmation_phrase)?;
+
+    println!("🧨 [WIPE] Wiping all local data");
+    // Nothing may write to the files while they are overwritten
+    let running = supervisor.drain(DRAIN_TIMEOUT).await;
+    if running > 0 {
+        eprintln!("⚠️ [WIPE] {} background job(s) still running; wiping anyway", running);
+    }
+    db_manager.close();
+    resource_budget::clear();
+    let targets = targets(&db_manager, &config_manager);
+    let report = tokio::task::spawn_blocking(move || secure_wipe::shred(&targets))
+        .await
+        .context("Wipe task failed")?;
+    println!(
+        "🧨 [WIPE] Shredded {} files ({} bytes), {} failures",
+        report.files_shredded,
+        report.bytes_overwritten,
+        report.failures.len()
+    );
+
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(EXIT_DELAY).await;
+        app_handle.exit(0);
+    });
+    Ok(report)
+}
@@ -0,0 +1,96 @@
+//! Task Activity Commands
+//!
+//! Comments on tasks and the activity log shown in the task detail pane.
+
+use crate::{
+    database::{
+        operations::task_comment_operations::{self, TaskComment},
+        DatabaseManager,
+    },
+    services::google::{
+        task_activity::{self, TaskActivity},
+        tasks_service::GoogleTasksService,
+    },
+};
+use std::sync::Arc;
+use tauri::State;
+use crate::errors::{CommandResult, LibreOllamaError};
+
+/// Comment on a task
+#[tauri::command]
+pub async fn add_task_comment(
+    account_id: String,
+    task_list_id: String,
+    google_task_id: String,
+    author: String,
+    body: String,
+    google_tasks_service: State<'_, GoogleTasksService>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<TaskComment> {
+    let body = task_activity::comment_body(&body)?;
+    let comment = {
+        let conn = db_manager.get_connection()?;
+        task_comment_operations::add_comment(&conn, &google_task_id, Some(&account_id), author.trim(), body)?
+    };
+    task_activity::mirror_or_queue(&google_tasks_service, &db_manager, &account_id, &task_list_id, &google_task_id).await;
+    Ok(comment)
+}
+
+/// Change the text of a comment left from `account_id`
+#[tauri::command]
+pub async fn edit_task_comment(
+    account_id: String,
+    task_list_id: String,
+    comment_id: i64,
+    body: String,
+    google_tasks_service: State<'_, GoogleTasksService>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<TaskComment> {
+    let body = task_activity::comment_body(&body)?;
+    let comment = {
+        let conn = db_manager.get_connection()?;
+        let not_found = || LibreOllamaError::NotFound { resource: format!("task comment {}", comment_id) };
+        let owned = task_comment_operations::get_comment(&conn, comment_id)?
+            .is_some_and(|comment| comment.account_id.as_deref() == Some(account_id.as_str()));
+        if !owned {
+            return Err(not_found().into());
+        }
+        task_comment_operations::edit_comment(&conn, comment_id, body)?.ok_or_else(not_found)?
+    };
+    task_activity::mirror_or_queue(&google_tasks_service, &db_manager, &account_id, &task_list_id, &comment.google_task_id).await;
+    Ok(comment)
+}
+
+/// Delete a comment left from `account_id`
+#[tauri::command]
+pub async fn delete_task_comment(
+    account_id: String,
+    task_list_id: String,
+    comment_id: i64,
+    google_tasks_service: State<'_, GoogleTasksService>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<bool> {
+    let comment = {
+        let conn = db_manager.get_connection()?;
+        let Some(comment) = task_comment_operations::get_comment(&conn, comment_id)? else {
+            return Ok(false);
+        };
+        if comment.account_id.as_deref() != Some(account_id.as_str()) {
+            return Ok(false);
+        }
+        task_comment_operations::delete_comment(&conn, comment_id)?;
+        comment
+    };
+    task_activity::mirror_or_queue(&google_tasks_service, &db_manager, &account_id, &task_list_id, &comment.google_task_id).await;
+    Ok(true)
+}
+
+/// Comments and changes of a task, oldest first
+#[tauri::command]
+pub async fn get_task_activity(
+    google_task_id: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<TaskActivity>> {
+    let conn = db_manager.get_connection()?;
+    Ok(task_activity::task_activity(&conn, &google_task_id)?)
+}
@@ -1,14 +1,17 @@
-use crate::models::task_metadata::*;
+use anyhow::Context;
 use crate::{
     database::DatabaseManager,
-    models::task_metadata::{TaskMetadata, TaskMetadataWithRelations, TimeBlock},
-    services::google::tasks_service::GoogleTasksService,
+    models::task_metadata::TimeBlock,
+    services::{events::EventBus, google::tasks_service::GoogleTasksService},
 };
 use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 use tauri::State;
-use super::metadata::get_task_metadata;
 use super::metadata_simple::SimpleLabel;
+use crate::database::operations::search_operations::{self, NewSearchItem};
+use crate::database::operations::workspace_operations::WorkspaceKind;
+use crate::services::workspaces;
+use crate::errors::CommandResult;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UnifiedTaskData {
@@ -19,12 +22,15 @@ pub struct UnifiedTaskData {
     pub notes: Option<String>,
     pub due: Option<String>,
     pub status: String,
+    /// When the task was completed, RFC 3339
+    pub completed: Option<String>,
     pub updated: String,
     pub position: String,
     pub priority: String,
     pub labels: Vec<SimpleLabel>,
     pub time_block: Option<TimeBlock>,
     pub column_id: String,
+    pub parent: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,11 +44,12 @@ pub async fn get_all_task_data(
     account_id: String,
     db_manager: State<'_, Arc<DatabaseManager>>,
     google_tasks_service: State<'_, GoogleTasksService>,
-) -> Result<AllTaskData, String> {
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<AllTaskData> {
     let task_lists = google_tasks_service
         .get_task_lists(&account_id)
         .await
-        .map_err(|e| format!("Failed to get task lists: {}", e))?;
+        .context("Failed to get task lists")?;
 
     let mut all_tasks = std::collections::HashMap::new();
     let mut column_task_ids: std::collections::HashMap<String, Vec<String>> = std::collections::HashMap::new();
@@ -51,7 +58,23 @@ pub async fn get_all_task_data(
         let tasks = google_tasks_service
             .get_tasks(&account_id, &list.id)
             .await
-            .map_err(|e| format!("Failed to get tasks: {}", e))?;
+            .context("Failed to get tasks")?;
+
+        // Mirror the remote parent/child hierarchy into local subtasks
+        let db_manager_clone = db_manager.inner().clone();
+        let list_id = list.id.clone();
+        let hierarchy = tasks.clone();
+        let reconciled = tokio::task::spawn_blocking(move || {
+            let mut conn = db_manager_clone.get_connection()
+                .context("Failed to get database connection")?;
+            crate::services::google::subtask_sync::reconcile_remote_hierarchy(&mut conn, &list_id, &hierarchy)
+                .context("Failed to reconcile subtasks")
+        })
+        .await
+        .context("Task execution failed")?;
+        if let Err(e) = reconciled {
+            eprintln!("⚠️ Subtask hierarchy sync skipped for list {}: {}", list.id, e);
+        }
 
         let mut list_task_ids = Vec::new();
         
@@ -81,12 +104,14 @@ pub async fn get_all_task_data(
                 notes: task.notes,
                 due: task.due,
                 status: task.status,
+                completed: task.completed,
                 updated: task.updated.unwrap_or_default(),
                 position: task.position.unwrap_or_default(),
                 priority,
                 labels,
                 time_block,
                 column_id: list.id.clone(),
+                parent: task.parent.clone(),
             };
             
             list_task_ids.push(task.id.clone());
@@ -96,7 +121,7 @@ pub async fn get_all_task_data(
         column_task_ids.insert(list.id.clone(), list_task_ids);
     }
 
-    let columns = task_lists
+    let mut columns: Vec<serde_json::Value> = task_lists
         .into_iter()
         .map(|list| {
             let task_ids = column_task_ids.get(&list.id).cloned().unwrap_or_default();
@@ -109,6 +134,63 @@ pub async fn get_all_task_data(
         })
         .collect();
 
+    // Drop board cards for tasks that were deleted remotely; placements for
+    // surviving tasks are keyed by Google task ID and are left untouched.
+    let known_task_ids: Vec<String> = all_tasks.keys().cloned().collect();
+    let db_manager_clone = db_manager.inner().clone();
+    let prune_account_id = account_id.clone();
+    let pruned = tokio::task::spawn_blocking(move || -> CommandResult<usize> {
+        let conn = db_manager_clone.get_connection()
+            .context("Failed to get database connection")?;
+        Ok(crate::database::operations::board_operations::prune_missing_cards(&conn, &prune_account_id, &known_task_ids)
+            .context("Failed to prune board cards")?)
+    })
+    .await
+    .context("Task execution failed")?;
+    if let Err(e) = pruned {
+        eprintln!("⚠️ Board card cleanup skipped: {}", e);
+    }
+
+    // Keep the fetched tasks searchable offline
+    let indexed: Vec<UnifiedTaskData> = all_tasks.values().cloned().collect();
+    let db_manager_clone = db_manager.inner().clone();
+    let index_account_id = account_id.clone();
+    let index_result = tokio::task::spawn_blocking(move || -> CommandResult<()> {
+        let times: Vec<(Option<String>, Option<String>)> = indexed
+            .iter()
+            .map(|task| {
+                (
+                    task.due.as_deref().and_then(search_operations::utc_item_time),
+                    task.completed.as_deref().and_then(search_operations::utc_item_time),
+                )
+            })
+            .collect();
+        let items: Vec<NewSearchItem> = indexed
+            .iter()
+            .zip(&times)
+            .map(|(task, (due, completed))| NewSearchItem {
+                item_id: &task.id,
+                container_id: &task.google_task_list_id,
+                title: &task.title,
+                body: task.notes.as_deref().unwrap_or_default(),
+                item_time: due.as_deref(),
+                status: Some(&task.status),
+                completed_at: completed.as_deref(),
+                all_day: false,
+            })
+            .collect();
+        let mut conn = db_manager_clone.get_connection()
+            .context("Failed to get database connection")?;
+        search_operations::replace_task_items(&mut conn, &index_account_id, &items)
+            .context("Failed to index tasks")?;
+        Ok(())
+    })
+    .await
+    .context("Task execution failed")?;
+    if let Err(e) = index_result {
+        eprintln!("⚠️ Task search index update skipped: {}", e);
+    }
+
     // Debug log for TZTEST tasks
     for (id, task) in &all_tasks {
         if task.title.contains("TZTEST") {
@@ -124,6 +206,17 @@ pub async fn get_all_task_data(
         }
     }
     
+    event_bus.sync_finished(&account_id, "tasks", "full", all_tasks.len() as u32, None);
+
+    // Lists of other workspaces are still synced and indexed, just not shown
+    let scope = {
+        let conn = db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        workspaces::scope(&conn).context("Failed to load workspace")?
+    };
+    columns.retain(|column| column["id"].as_str().is_none_or(|id| scope.allows(WorkspaceKind::TaskList, id)));
+    all_tasks.retain(|_, task| scope.allows(WorkspaceKind::TaskList, &task.google_task_list_id));
+
     Ok(AllTaskData {
         tasks: all_tasks,
         columns,
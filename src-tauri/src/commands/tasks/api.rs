@@ -2,9 +2,16 @@
 //!
 //! This module provides Tauri command handlers for Google Tasks API operations.
 
+use anyhow::Context;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tauri::State;
+use crate::utils::http_client::http_client;
+use crate::commands::rate_limiter::RequestPriority;
+use crate::services::request_scheduler;
+use crate::database::operations::workspace_operations::WorkspaceKind;
+use crate::services::workspaces;
+use crate::errors::{CommandResult, LibreOllamaError};
 
 // Define the task structures that match the frontend types
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -81,29 +88,30 @@ pub struct TasksResponse {
 pub async fn get_task_lists(
     account_id: String,
     auth_service: State<'_, Arc<crate::services::gmail::auth_service::GmailAuthService>>,
-) -> Result<Vec<GoogleTaskList>, String> {
+    db_manager: State<'_, Arc<crate::database::DatabaseManager>>,
+) -> CommandResult<Vec<GoogleTaskList>> {
     println!("📋 [TASKS-API] Getting task lists for account: {}", account_id);
     
     // Get access token
     let tokens = auth_service.get_account_tokens(&account_id).await
-        .map_err(|e| format!("Failed to get tokens: {}", e))?
+        .context("Failed to get tokens")?
         .ok_or("No tokens found for account")?;
 
     // Make API call to Google Tasks
-    let client = reqwest::Client::new();
-    let response = client
+    let client = http_client();
+    let request = client
         .get("https://www.googleapis.com/tasks/v1/users/@me/lists")
-        .bearer_auth(&tokens.access_token)
-        .send()
+        .bearer_auth(&tokens.access_token);
+    let response = request_scheduler::send(Some(&account_id), RequestPriority::High, request)
         .await
-        .map_err(|e| format!("API request failed: {}", e))?;
+        .context("API request failed")?;
 
     if !response.status().is_success() {
-        return Err(format!("Tasks API failed: {}", response.status()));
+        return Err(LibreOllamaError::GoogleTasksApi { message: format!("Tasks API failed: {}", response.status()) }.into());
     }
 
     let task_lists_data: serde_json::Value = response.json().await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+        .context("Failed to parse response")?;
 
     let mut task_lists = Vec::new();
     if let Some(items) = task_lists_data["items"].as_array() {
@@ -120,6 +128,9 @@ pub async fn get_task_lists(
     }
     
     println!("✅ [TASKS-API] Retrieved {} task lists", task_lists.len());
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    let scope = workspaces::scope(&conn).context("Failed to load workspace")?;
+    scope.retain(WorkspaceKind::TaskList, &mut task_lists, |list| &list.id);
     Ok(task_lists)
 }
 
@@ -132,7 +143,7 @@ pub async fn get_tasks(
     show_deleted: Option<bool>,
     max_results: Option<u32>,
     auth_service: State<'_, Arc<crate::services::gmail::auth_service::GmailAuthService>>,
-) -> Result<TasksResponse, String> {
+) -> CommandResult<TasksResponse> {
     // Always fetch ALL tasks including completed ones for client-side filtering
     // This follows the "Always Fetch All + Client-Side Filtering" pattern
     let show_completed = show_completed.unwrap_or(true);
@@ -143,11 +154,11 @@ pub async fn get_tasks(
 
     // Get access token
     let tokens = auth_service.get_account_tokens(&account_id).await
-        .map_err(|e| format!("Failed to get tokens: {}", e))?
+        .context("Failed to get tokens")?
         .ok_or("No tokens found for account")?;
 
     // Make API call to Google Tasks
-    let client = reqwest::Client::new();
+    let client = http_client();
     let mut url = format!("https://www.googleapis.com/tasks/v1/lists/{}/tasks", task_list_id);
     
     // Add date range parameters to fetch all tasks including future ones
@@ -168,19 +179,19 @@ pub async fn get_tasks(
     
     println!("🔗 [TASKS-API] API URL: {}", url);
 
-    let response = client
+    let request = client
         .get(&url)
-        .bearer_auth(&tokens.access_token)
-        .send()
+        .bearer_auth(&tokens.access_token);
+    let response = request_scheduler::send(Some(&account_id), RequestPriority::High, request)
         .await
-        .map_err(|e| format!("API request failed: {}", e))?;
+        .context("API request failed")?;
 
     if !response.status().is_success() {
-        return Err(format!("Tasks API failed: {}", response.status()));
+        return Err(LibreOllamaError::GoogleTasksApi { message: format!("Tasks API failed: {}", response.status()) }.into());
     }
 
     let tasks_data: serde_json::Value = response.json().await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+        .context("Failed to parse response")?;
 
     let mut tasks = Vec::new();
     if let Some(items) = tasks_data["items"].as_array() {
@@ -245,13 +256,13 @@ pub async fn create_task(
     task_list_id: String,
     task_data: TaskCreateData,
     auth_service: State<'_, Arc<crate::services::gmail::auth_service::GmailAuthService>>,
-) -> Result<GoogleTask, String> {
+) -> CommandResult<GoogleTask> {
     println!("📋 [TASKS-API] Creating task '{}' in list: {} (account: {})", 
              task_data.title, task_list_id, account_id);
 
     // Get access token
     let tokens = auth_service.get_account_tokens(&account_id).await
-        .map_err(|e| format!("Failed to get tokens: {}", e))?
+        .context("Failed to get tokens")?
         .ok_or("No tokens found for account")?;
 
     // Build the request body
@@ -268,7 +279,7 @@ pub async fn create_task(
     }
 
     // Make API call to Google Tasks
-    let client = reqwest::Client::new();
+    let client = http_client();
     let mut request = client
         .post(&format!("https://www.googleapis.com/tasks/v1/lists/{}/tasks", task_list_id))
         .bearer_auth(&tokens.access_token)
@@ -282,17 +293,17 @@ pub async fn create_task(
         request = request.query(&[("previous", previous)]);
     }
     
-    let response = request.send().await
-        .map_err(|e| format!("API request failed: {}", e))?;
+    let response = request_scheduler::send(Some(&account_id), RequestPriority::High, request).await
+        .context("API request failed")?;
 
     let status = response.status();
     if !status.is_success() {
         let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("Tasks API failed: {} - {}", status, error_text));
+        return Err(LibreOllamaError::GoogleTasksApi { message: format!("Tasks API failed: {} - {}", status, error_text) }.into());
     }
 
     let task_data: serde_json::Value = response.json().await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+        .context("Failed to parse response")?;
 
     let new_task = GoogleTask {
         id: task_data["id"].as_str().unwrap_or("").to_string(),
@@ -324,13 +335,13 @@ pub async fn update_task(
     task_id: String,
     task_data: TaskUpdateData,
     auth_service: State<'_, Arc<crate::services::gmail::auth_service::GmailAuthService>>,
-) -> Result<GoogleTask, String> {
+) -> CommandResult<GoogleTask> {
     println!("📋 [TASKS-API] Updating task {} in list: {} (account: {})", 
              task_id, task_list_id, account_id);
 
     // Get access token
     let tokens = auth_service.get_account_tokens(&account_id).await
-        .map_err(|e| format!("Failed to get tokens: {}", e))?
+        .context("Failed to get tokens")?
         .ok_or("No tokens found for account")?;
 
     // Build the request body with only the fields that are being updated
@@ -353,23 +364,23 @@ pub async fn update_task(
     }
 
     // Make API call to Google Tasks
-    let client = reqwest::Client::new();
-    let response = client
+    let client = http_client();
+    let request = client
         .patch(&format!("https://www.googleapis.com/tasks/v1/lists/{}/tasks/{}", task_list_id, task_id))
         .bearer_auth(&tokens.access_token)
-        .json(&body)
-        .send()
+        .json(&body);
+    let response = request_scheduler::send(Some(&account_id), RequestPriority::High, request)
         .await
-        .map_err(|e| format!("API request failed: {}", e))?;
+        .context("API request failed")?;
 
     let status = response.status();
     if !status.is_success() {
         let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("Tasks API failed: {} - {}", status, error_text));
+        return Err(LibreOllamaError::GoogleTasksApi { message: format!("Tasks API failed: {} - {}", status, error_text) }.into());
     }
 
     let task_data: serde_json::Value = response.json().await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+        .context("Failed to parse response")?;
 
     let updated_task = GoogleTask {
         id: task_data["id"].as_str().unwrap_or("").to_string(),
@@ -408,17 +419,17 @@ pub async fn move_task(
     parent: Option<String>,
     previous: Option<String>,
     auth_service: State<'_, Arc<crate::services::gmail::auth_service::GmailAuthService>>,
-) -> Result<GoogleTask, String> {
+) -> CommandResult<GoogleTask> {
     println!("📋 [TASKS-API] Moving task {} to list: {} (account: {})", 
              task_id, task_list_id, account_id);
 
     // Get access token
     let tokens = auth_service.get_account_tokens(&account_id).await
-        .map_err(|e| format!("Failed to get tokens: {}", e))?
+        .context("Failed to get tokens")?
         .ok_or("No tokens found for account")?;
 
     // Make API call to Google Tasks Move endpoint
-    let client = reqwest::Client::new();
+    let client = http_client();
     let mut url = format!("https://www.googleapis.com/tasks/v1/lists/{}/tasks/{}/move", 
                          task_list_id, task_id);
     
@@ -436,21 +447,21 @@ pub async fn move_task(
         url.push_str(&query_params.join("&"));
     }
     
-    let response = client
+    let request = client
         .post(&url)
-        .bearer_auth(&tokens.access_token)
-        .send()
+        .bearer_auth(&tokens.access_token);
+    let response = request_scheduler::send(Some(&account_id), RequestPriority::High, request)
         .await
-        .map_err(|e| format!("API request failed: {}", e))?;
+        .context("API request failed")?;
 
     let status = response.status();
     if !status.is_success() {
         let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("Tasks API failed: {} - {}", status, error_text));
+        return Err(LibreOllamaError::GoogleTasksApi { message: format!("Tasks API failed: {} - {}", status, error_text) }.into());
     }
 
     let task_data: serde_json::Value = response.json().await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+        .context("Failed to parse response")?;
 
     let moved_task = GoogleTask {
         id: task_data["id"].as_str().unwrap_or("").to_string(),
@@ -481,28 +492,28 @@ pub async fn delete_task(
     task_list_id: String,
     task_id: String,
     auth_service: State<'_, Arc<crate::services::gmail::auth_service::GmailAuthService>>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     println!("📋 [TASKS-API] Deleting task {} from list: {} (account: {})", 
              task_id, task_list_id, account_id);
 
     // Get access token
     let tokens = auth_service.get_account_tokens(&account_id).await
-        .map_err(|e| format!("Failed to get tokens: {}", e))?
+        .context("Failed to get tokens")?
         .ok_or("No tokens found for account")?;
 
     // Make API call to Google Tasks
-    let client = reqwest::Client::new();
-    let response = client
+    let client = http_client();
+    let request = client
         .delete(&format!("https://www.googleapis.com/tasks/v1/lists/{}/tasks/{}", task_list_id, task_id))
-        .bearer_auth(&tokens.access_token)
-        .send()
+        .bearer_auth(&tokens.access_token);
+    let response = request_scheduler::send(Some(&account_id), RequestPriority::High, request)
         .await
-        .map_err(|e| format!("API request failed: {}", e))?;
+        .context("API request failed")?;
 
     let status = response.status();
     if !status.is_success() {
         let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("Tasks API failed: {} - {}", status, error_text));
+        return Err(LibreOllamaError::GoogleTasksApi { message: format!("Tasks API failed: {} - {}", status, error_text) }.into());
     }
 
     println!("✅ [TASKS-API] Task deleted successfully: {}", task_id);
@@ -515,12 +526,12 @@ pub async fn create_task_list(
     account_id: String,
     title: String,
     auth_service: State<'_, Arc<crate::services::gmail::auth_service::GmailAuthService>>,
-) -> Result<GoogleTaskList, String> {
+) -> CommandResult<GoogleTaskList> {
     println!("📋 [TASKS-API] Creating task list '{}' (account: {})", title, account_id);
 
     // Get access token
     let tokens = auth_service.get_account_tokens(&account_id).await
-        .map_err(|e| format!("Failed to get tokens: {}", e))?
+        .context("Failed to get tokens")?
         .ok_or("No tokens found for account")?;
 
     // Prepare the request body
@@ -529,23 +540,23 @@ pub async fn create_task_list(
     });
 
     // Make API call to Google Tasks
-    let client = reqwest::Client::new();
-    let response = client
+    let client = http_client();
+    let request = client
         .post("https://www.googleapis.com/tasks/v1/users/@me/lists")
         .bearer_auth(&tokens.access_token)
-        .json(&body)
-        .send()
+        .json(&body);
+    let response = request_scheduler::send(Some(&account_id), RequestPriority::High, request)
         .await
-        .map_err(|e| format!("API request failed: {}", e))?;
+        .context("API request failed")?;
 
     if !response.status().is_success() {
         let status = response.status();
         let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("Tasks API failed: {} - {}", status, error_text));
+        return Err(LibreOllamaError::GoogleTasksApi { message: format!("Tasks API failed: {} - {}", status, error_text) }.into());
     }
 
     let list_data: serde_json::Value = response.json().await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+        .context("Failed to parse response")?;
 
     let new_list = GoogleTaskList {
         id: list_data["id"].as_str().unwrap_or("").to_string(),
@@ -567,13 +578,13 @@ pub async fn update_task_list(
     task_list_id: String,
     title: String,
     auth_service: State<'_, Arc<crate::services::gmail::auth_service::GmailAuthService>>,
-) -> Result<GoogleTaskList, String> {
+) -> CommandResult<GoogleTaskList> {
     println!("📋 [TASKS-API] Updating task list {} to '{}' (account: {})", 
              task_list_id, title, account_id);
 
     // Get access token
     let tokens = auth_service.get_account_tokens(&account_id).await
-        .map_err(|e| format!("Failed to get tokens: {}", e))?
+        .context("Failed to get tokens")?
         .ok_or("No tokens found for account")?;
 
     // Prepare the request body
@@ -582,23 +593,23 @@ pub async fn update_task_list(
     });
 
     // Make API call to Google Tasks
-    let client = reqwest::Client::new();
-    let response = client
+    let client = http_client();
+    let request = client
         .put(&format!("https://www.googleapis.com/tasks/v1/users/@me/lists/{}", task_list_id))
         .bearer_auth(&tokens.access_token)
-        .json(&body)
-        .send()
+        .json(&body);
+    let response = request_scheduler::send(Some(&account_id), RequestPriority::High, request)
         .await
-        .map_err(|e| format!("API request failed: {}", e))?;
+        .context("API request failed")?;
 
     if !response.status().is_success() {
         let status = response.status();
         let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("Tasks API failed: {} - {}", status, error_text));
+        return Err(LibreOllamaError::GoogleTasksApi { message: format!("Tasks API failed: {} - {}", status, error_text) }.into());
     }
 
     let list_data: serde_json::Value = response.json().await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+        .context("Failed to parse response")?;
 
     let updated_list = GoogleTaskList {
         id: list_data["id"].as_str().unwrap_or("").to_string(),
@@ -619,27 +630,27 @@ pub async fn delete_task_list(
     account_id: String,
     task_list_id: String,
     auth_service: State<'_, Arc<crate::services::gmail::auth_service::GmailAuthService>>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     println!("📋 [TASKS-API] Deleting task list {} (account: {})", task_list_id, account_id);
 
     // Get access token
     let tokens = auth_service.get_account_tokens(&account_id).await
-        .map_err(|e| format!("Failed to get tokens: {}", e))?
+        .context("Failed to get tokens")?
         .ok_or("No tokens found for account")?;
 
     // Make API call to Google Tasks
-    let client = reqwest::Client::new();
-    let response = client
+    let client = http_client();
+    let request = client
         .delete(&format!("https://www.googleapis.com/tasks/v1/users/@me/lists/{}", task_list_id))
-        .bearer_auth(&tokens.access_token)
-        .send()
+        .bearer_auth(&tokens.access_token);
+    let response = request_scheduler::send(Some(&account_id), RequestPriority::High, request)
         .await
-        .map_err(|e| format!("API request failed: {}", e))?;
+        .context("API request failed")?;
 
     if !response.status().is_success() {
         let status = response.status();
         let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("Tasks API failed: {} - {}", status, error_text));
+        return Err(LibreOllamaError::GoogleTasksApi { message: format!("Tasks API failed: {} - {}", status, error_text) }.into());
     }
 
     println!("✅ [TASKS-API] Task list deleted successfully: {}", task_list_id);
@@ -652,7 +663,7 @@ pub async fn toggle_task_complete(
     account_id: String,
     task_list_id: String,
     task_id: String,
-) -> Result<GoogleTask, String> {
+) -> CommandResult<GoogleTask> {
     println!("📋 [TASKS-API] Toggling completion status for task {} in list: {} (account: {})", 
              task_id, task_list_id, account_id);
 
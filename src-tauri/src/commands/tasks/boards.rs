@@ -0,0 +1,227 @@
+//! Kanban Board Commands
+//!
+//! Commands for managing task boards with custom columns. Card placement is
+//! stored locally by Google task ID, independent of Google task lists.
+
+use anyhow::Context;
+use crate::{
+    database::{operations::board_operations, DatabaseManager},
+    models::task_board::{BoardCard, BoardColumn, CreateBoardColumn, TaskBoard, TaskBoardWithColumns},
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::State;
+use crate::errors::CommandResult;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateBoardRequest {
+    pub name: String,
+    pub account_id: Option<String>,
+    pub columns: Option<Vec<CreateBoardColumn>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveCardRequest {
+    pub board_id: String,
+    pub google_task_id: String,
+    pub column_id: String,
+    pub position: Option<usize>,
+}
+
+/// Default columns used when a board is created without explicit columns
+fn default_columns() -> Vec<CreateBoardColumn> {
+    ["To do", "In progress", "Done"]
+        .iter()
+        .map(|title| CreateBoardColumn { title: title.to_string(), color: None })
+        .collect()
+}
+
+#[tauri::command]
+pub async fn create_task_board(
+    request: CreateBoardRequest,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<TaskBoard> {
+    let db_manager = db_manager.inner().clone();
+    Ok(tokio::task::spawn_blocking(move || {
+        let mut conn = db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        let columns = request.columns.unwrap_or_else(default_columns);
+        board_operations::create_board(&mut conn, &request.name, request.account_id.as_deref(), &columns)
+            .context("Failed to create board")
+    })
+    .await
+    .context("Task execution failed")??)
+}
+
+#[tauri::command]
+pub async fn get_task_boards(
+    account_id: Option<String>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<TaskBoard>> {
+    let db_manager = db_manager.inner().clone();
+    Ok(tokio::task::spawn_blocking(move || {
+        let conn = db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        board_operations::list_boards(&conn, account_id.as_deref())
+            .context("Failed to list boards")
+    })
+    .await
+    .context("Task execution failed")??)
+}
+
+#[tauri::command]
+pub async fn get_task_board(
+    board_id: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Option<TaskBoardWithColumns>> {
+    let db_manager = db_manager.inner().clone();
+    Ok(tokio::task::spawn_blocking(move || {
+        let conn = db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        board_operations::get_board_with_columns(&conn, &board_id)
+            .context("Failed to load board")
+    })
+    .await
+    .context("Task execution failed")??)
+}
+
+#[tauri::command]
+pub async fn rename_task_board(
+    board_id: String,
+    name: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<bool> {
+    let db_manager = db_manager.inner().clone();
+    Ok(tokio::task::spawn_blocking(move || {
+        let conn = db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        board_operations::rename_board(&conn, &board_id, &name)
+            .context("Failed to rename board")
+    })
+    .await
+    .context("Task execution failed")??)
+}
+
+#[tauri::command]
+pub async fn delete_task_board(
+    board_id: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<bool> {
+    let db_manager = db_manager.inner().clone();
+    Ok(tokio::task::spawn_blocking(move || {
+        let conn = db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        board_operations::delete_board(&conn, &board_id)
+            .context("Failed to delete board")
+    })
+    .await
+    .context("Task execution failed")??)
+}
+
+#[tauri::command]
+pub async fn add_board_column(
+    board_id: String,
+    column: CreateBoardColumn,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<BoardColumn> {
+    let db_manager = db_manager.inner().clone();
+    Ok(tokio::task::spawn_blocking(move || {
+        let conn = db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        board_operations::add_column(&conn, &board_id, &column)
+            .context("Failed to add column")
+    })
+    .await
+    .context("Task execution failed")??)
+}
+
+#[tauri::command]
+pub async fn update_board_column(
+    column_id: String,
+    title: String,
+    color: Option<String>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<bool> {
+    let db_manager = db_manager.inner().clone();
+    Ok(tokio::task::spawn_blocking(move || {
+        let conn = db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        board_operations::update_column(&conn, &column_id, &title, color.as_deref())
+            .context("Failed to update column")
+    })
+    .await
+    .context("Task execution failed")??)
+}
+
+#[tauri::command]
+pub async fn delete_board_column(
+    column_id: String,
+    move_cards_to: Option<String>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<bool> {
+    let db_manager = db_manager.inner().clone();
+    Ok(tokio::task::spawn_blocking(move || {
+        let mut conn = db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        board_operations::delete_column(&mut conn, &column_id, move_cards_to.as_deref())
+            .context("Failed to delete column")
+    })
+    .await
+    .context("Task execution failed")??)
+}
+
+#[tauri::command]
+pub async fn reorder_board_columns(
+    board_id: String,
+    column_ids: Vec<String>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<()> {
+    let db_manager = db_manager.inner().clone();
+    Ok(tokio::task::spawn_blocking(move || {
+        let mut conn = db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        board_operations::reorder_columns(&mut conn, &board_id, &column_ids)
+            .context("Failed to reorder columns")
+    })
+    .await
+    .context("Task execution failed")??)
+}
+
+#[tauri::command]
+pub async fn move_board_card(
+    request: MoveCardRequest,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<BoardCard> {
+    let db_manager = db_manager.inner().clone();
+    Ok(tokio::task::spawn_blocking(move || {
+        let mut conn = db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        board_operations::move_card(
+            &mut conn,
+            &request.board_id,
+            &request.google_task_id,
+            &request.column_id,
+            request.position,
+        )
+        .context("Failed to move card")
+    })
+    .await
+    .context("Task execution failed")??)
+}
+
+#[tauri::command]
+pub async fn remove_board_card(
+    board_id: String,
+    google_task_id: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<bool> {
+    let db_manager = db_manager.inner().clone();
+    Ok(tokio::task::spawn_blocking(move || {
+        let mut conn = db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        board_operations::remove_card(&mut conn, &board_id, &google_task_id)
+            .context("Failed to remove card")
+    })
+    .await
+    .context("Task execution failed")??)
+}
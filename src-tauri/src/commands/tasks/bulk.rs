@@ -0,0 +1,73 @@
+//! Bulk Task Commands
+//!
+//! Change, move or delete many tasks in one call.
+
+use crate::{
+    database::DatabaseManager,
+    services::events::EventBus,
+    services::google::{
+        task_bulk::{self, BulkTaskOperations, BulkUpdateSummary, TaskRef},
+        tasks_service::GoogleTasksService,
+    },
+};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tauri::State;
+use super::sync_fixed::journal_task_change;
+use crate::errors::CommandResult;
+
+/// Apply one set of operations to many tasks, reporting each task's outcome
+#[tauri::command]
+pub async fn bulk_update_tasks(
+    account_id: String,
+    tasks: Vec<TaskRef>,
+    operations: BulkTaskOperations,
+    google_tasks_service: State<'_, GoogleTasksService>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<BulkUpdateSummary> {
+    // Deleted tasks are read first so undo can recreate them
+    let mut deleted = HashMap::new();
+    if operations.delete {
+        let lists: HashSet<&str> = tasks.iter().map(|task| task.task_list_id.as_str()).collect();
+        for list in lists {
+            match google_tasks_service.get_tasks(&account_id, list).await {
+                Ok(listed) => deleted.extend(listed.into_iter().map(|task| (task.id.clone(), (list.to_string(), task)))),
+                Err(e) => eprintln!("⚠️ Failed to read task list {} before deleting from it: {}", list, e),
+            }
+        }
+    }
+    let lists: HashMap<&str, &str> = tasks.iter().map(|task| (task.task_id.as_str(), task.task_list_id.as_str())).collect();
+
+    let summary = task_bulk::bulk_update(&google_tasks_service, &db_manager, &account_id, &tasks, &operations).await?;
+
+    for result in summary.results.iter().filter(|result| result.success || result.pending_sync) {
+        if operations.delete {
+            let previous = deleted.get(&result.task_id).map(|(list, task)| {
+                serde_json::json!({
+                    "title": task.title,
+                    "notes": task.notes,
+                    "due": task.due,
+                    "status": task.status,
+                    "task_list_id": list,
+                })
+            });
+            journal_task_change(&db_manager, &event_bus, &result.task_id, "delete", serde_json::json!({}), previous, &account_id);
+            continue;
+        }
+        let mut fields = operations.status_fields().unwrap_or_default();
+        let mut previous = None;
+        if result.moved {
+            fields.insert("task_list_id".to_string(), serde_json::json!(result.task_list_id));
+            previous = lists
+                .get(result.task_id.as_str())
+                .map(|list| serde_json::json!({ "task_list_id": list }));
+        }
+        if !fields.is_empty() {
+            journal_task_change(&db_manager, &event_bus, &result.task_id, "update", serde_json::Value::Object(fields), previous, &account_id);
+        }
+    }
+    eprintln!("✅ Bulk task update: {} succeeded, {} failed", summary.succeeded, summary.failed);
+
+    Ok(summary)
+}
@@ -0,0 +1,174 @@
+//! Task Export Commands
+//!
+//! Export a task list, or one day's agenda of tasks and calendar events, as
+//! Markdown, CSV or printable HTML for sharing.
+
+use anyhow::Context;
+use chrono::NaiveDate;
+use std::sync::Arc;
+use tauri::State;
+
+use crate::commands::calendar::{get_calendar_events, EventDateTime, GoogleCalendarEvent};
+use crate::commands::tasks::metadata_simple::get_simple_metadata;
+use crate::database::DatabaseManager;
+use crate::models::task_metadata::TimeBlock;
+use crate::services::gmail::auth_service::GmailAuthService;
+use crate::services::google::task_export::{self, AgendaEvent, ExportOptions, ExportTask, ExportedDocument};
+use crate::services::google::tasks_service::{GoogleTask, GoogleTasksService};
+use crate::services::time_service::{self, TimeService};
+use crate::utils::time;
+use crate::errors::{CommandResult, LibreOllamaError};
+
+fn clock(db_manager: &DatabaseManager) -> CommandResult<TimeService> {
+    Ok(TimeService::from_db(db_manager).context("Failed to read the time zone")?)
+}
+
+/// Time blocks are shown in the user's zone
+async fn to_export_task(task: GoogleTask, db_manager: &Arc<DatabaseManager>, clock: &TimeService) -> CommandResult<ExportTask> {
+    let (priority, labels, time_block) = get_simple_metadata(task.id.clone(), db_manager.clone()).await?;
+    let local = |value: &str| clock.format(value, "%Y-%m-%dT%H:%M:%S%:z").unwrap_or_else(|| value.to_string());
+    let time_block = time_block.map(|block| TimeBlock {
+        start_time: local(&block.start_time),
+        end_time: local(&block.end_time),
+        time_zone: Some(clock.zone().name().to_string()),
+    });
+    Ok(ExportTask {
+        title: task.title,
+        notes: task.notes,
+        due: task.due,
+        completed: task.status == "completed",
+        priority,
+        labels: labels.into_iter().map(|label| label.name).collect(),
+        time_block,
+    })
+}
+
+/// Whether a task belongs on the agenda for `date`: due that day, or
+/// time-blocked to start that day
+fn is_on_date(task: &ExportTask, date: NaiveDate, clock: &TimeService) -> bool {
+    let due_today = task.due.as_deref().and_then(time_service::due_date) == Some(date);
+    let blocked_today = task
+        .time_block
+        .as_ref()
+        .and_then(|block| clock.to_utc(&block.start_time))
+        .map(|start| clock.to_local(start).date_naive())
+        == Some(date);
+    due_today || blocked_today
+}
+
+fn to_agenda_event(event: GoogleCalendarEvent, clock: &TimeService) -> AgendaEvent {
+    let local_time = |time: Option<&EventDateTime>| {
+        time.and_then(|t| t.date_time.as_deref()).and_then(|t| clock.format(t, "%H:%M"))
+    };
+    AgendaEvent {
+        title: event.summary.unwrap_or_else(|| "(No title)".to_string()),
+        start: local_time(event.start.as_ref()),
+        end: local_time(event.end.as_ref()),
+        location: event.location,
+    }
+}
+
+/// Export one task list
+#[tauri::command]
+pub async fn export_task_list(
+    account_id: String,
+    task_list_id: String,
+    options: ExportOptions,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    tasks_service: State<'_, GoogleTasksService>,
+) -> CommandResult<ExportedDocument> {
+    println!("📤 [TASK-EXPORT] Exporting list {} as {:?}", task_list_id, options.format);
+
+    let title = tasks_service
+        .get_task_lists(&account_id)
+        .await
+        .context("Failed to get task lists")?
+        .into_iter()
+        .find(|list| list.id == task_list_id)
+        .map(|list| list.title)
+        .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("task list {}", task_list_id) })?;
+
+    let google_tasks = tasks_service
+        .get_tasks(&account_id, &task_list_id)
+        .await
+        .context("Failed to get tasks")?;
+    let clock = clock(&db_manager)?;
+    let mut tasks = Vec::with_capacity(google_tasks.len());
+    for task in google_tasks {
+        tasks.push(to_export_task(task, &db_manager, &clock).await?);
+    }
+
+    let content = task_export::render_task_list(&title, &tasks, &options);
+    Ok(ExportedDocument::new(&title, options.format, content))
+}
+
+/// Export a day's agenda: the events on the given calendars (primary by
+/// default) plus tasks from every list that are due or time-blocked that day
+#[tauri::command]
+pub async fn export_agenda(
+    account_id: String,
+    date: String,
+    calendar_ids: Option<Vec<String>>,
+    options: ExportOptions,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    tasks_service: State<'_, GoogleTasksService>,
+    auth_service: State<'_, Arc<GmailAuthService>>,
+) -> CommandResult<ExportedDocument> {
+    let day = NaiveDate::parse_from_str(&date, "%Y-%m-%d").context("Invalid date")?;
+    let (Some(day_start), Some(next_day)) = (day.and_hms_opt(0, 0, 0), day.succ_opt().and_then(|d| d.and_hms_opt(0, 0, 0))) else {
+        return Err(LibreOllamaError::InvalidInput { message: format!("Invalid date: {}", date), field: Some("date".to_string()) }.into());
+    };
+    let clock = clock(&db_manager)?;
+    let (Some(time_min), Some(time_max)) =
+        (time::local_to_utc(day_start, &clock.zone()), time::local_to_utc(next_day, &clock.zone()))
+    else {
+        return Err(LibreOllamaError::InvalidInput { message: format!("Invalid local date: {}", date), field: Some("date".to_string()) }.into());
+    };
+
+    println!("📤 [TASK-EXPORT] Exporting agenda for {} as {:?}", date, options.format);
+
+    let mut events = Vec::new();
+    for calendar_id in calendar_ids.unwrap_or_else(|| vec!["primary".to_string()]) {
+        let response = get_calendar_events(
+            account_id.clone(),
+            calendar_id,
+            Some(time_min.to_rfc3339()),
+            Some(time_max.to_rfc3339()),
+            Some(250),
+            Some(false),
+            Some(true),
+            auth_service.clone(),
+            db_manager.clone(),
+        )
+        .await?;
+        events.extend(
+            response
+                .items
+                .into_iter()
+                .filter(|event| event.status.as_deref() != Some("cancelled"))
+                .map(|event| to_agenda_event(event, &clock)),
+        );
+    }
+
+    let lists = tasks_service
+        .get_task_lists(&account_id)
+        .await
+        .context("Failed to get task lists")?;
+    let mut tasks = Vec::new();
+    for list in lists {
+        let google_tasks = tasks_service
+            .get_tasks(&account_id, &list.id)
+            .await
+            .context("Failed to get tasks")?;
+        for task in google_tasks {
+            let task = to_export_task(task, &db_manager, &clock).await?;
+            if is_on_date(&task, day, &clock) {
+                tasks.push(task);
+            }
+        }
+    }
+
+    let date_label = day.format("%A, %B %-d, %Y").to_string();
+    let content = task_export::render_agenda(&date_label, &events, &tasks, &options);
+    Ok(ExportedDocument::new(&format!("agenda-{}", date), options.format, content))
+}
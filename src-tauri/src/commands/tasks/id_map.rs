@@ -1,3 +1,4 @@
+use anyhow::Context;
 use crate::{
     database::DatabaseManager,
     models::task_id_map::{TaskIdMap, CreateTaskIdMap, UpdateTaskIdMap},
@@ -5,7 +6,8 @@ use crate::{
 use std::sync::Arc;
 use tauri::State;
 use uuid::Uuid;
-use rusqlite::{params, Result as SqlResult};
+use rusqlite::params;
+use crate::errors::CommandResult;
 
 /// Generate a new stable local ID for a task
 pub fn generate_local_id() -> String {
@@ -16,9 +18,9 @@ pub fn generate_local_id() -> String {
 pub async fn create_task_id_mapping(
     mapping: CreateTaskIdMap,
     db_manager: State<'_, Arc<DatabaseManager>>,
-) -> Result<TaskIdMap, String> {
+) -> CommandResult<TaskIdMap> {
     let conn = db_manager.get_connection()
-        .map_err(|e| format!("Failed to get database connection: {}", e))?;
+        .context("Failed to get database connection")?;
     
     // Insert the mapping
     conn.execute(
@@ -26,7 +28,7 @@ pub async fn create_task_id_mapping(
          VALUES (?1, ?2, ?3, datetime('now'), datetime('now'))",
         params![&mapping.local_id, &mapping.google_task_id, &mapping.task_list_id],
     )
-    .map_err(|e| format!("Failed to create task ID mapping: {}", e))?;
+    .context("Failed to create task ID mapping")?;
     
     // Get the inserted row
     let result = conn.query_row(
@@ -43,7 +45,7 @@ pub async fn create_task_id_mapping(
             })
         },
     )
-    .map_err(|e| format!("Failed to retrieve created mapping: {}", e))?;
+    .context("Failed to retrieve created mapping")?;
     
     Ok(result)
 }
@@ -52,9 +54,9 @@ pub async fn create_task_id_mapping(
 pub async fn get_task_id_mapping_by_local(
     local_id: String,
     db_manager: State<'_, Arc<DatabaseManager>>,
-) -> Result<Option<TaskIdMap>, String> {
+) -> CommandResult<Option<TaskIdMap>> {
     let conn = db_manager.get_connection()
-        .map_err(|e| format!("Failed to get database connection: {}", e))?;
+        .context("Failed to get database connection")?;
     
     let result = conn.query_row(
         "SELECT * FROM task_id_map WHERE local_id = ?1",
@@ -74,7 +76,7 @@ pub async fn get_task_id_mapping_by_local(
     match result {
         Ok(mapping) => Ok(Some(mapping)),
         Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-        Err(e) => Err(format!("Failed to get task ID mapping: {}", e)),
+        Err(e) => Err(anyhow::Error::new(e).context("Failed to get task ID mapping").into()),
     }
 }
 
@@ -82,9 +84,9 @@ pub async fn get_task_id_mapping_by_local(
 pub async fn get_task_id_mapping_by_google(
     google_task_id: String,
     db_manager: State<'_, Arc<DatabaseManager>>,
-) -> Result<Option<TaskIdMap>, String> {
+) -> CommandResult<Option<TaskIdMap>> {
     let conn = db_manager.get_connection()
-        .map_err(|e| format!("Failed to get database connection: {}", e))?;
+        .context("Failed to get database connection")?;
     
     let result = conn.query_row(
         "SELECT * FROM task_id_map WHERE google_task_id = ?1",
@@ -104,7 +106,7 @@ pub async fn get_task_id_mapping_by_google(
     match result {
         Ok(mapping) => Ok(Some(mapping)),
         Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-        Err(e) => Err(format!("Failed to get task ID mapping: {}", e)),
+        Err(e) => Err(anyhow::Error::new(e).context("Failed to get task ID mapping").into()),
     }
 }
 
@@ -113,9 +115,9 @@ pub async fn update_task_id_mapping(
     local_id: String,
     updates: UpdateTaskIdMap,
     db_manager: State<'_, Arc<DatabaseManager>>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     let conn = db_manager.get_connection()
-        .map_err(|e| format!("Failed to get database connection: {}", e))?;
+        .context("Failed to get database connection")?;
     
     // Build dynamic update query based on provided fields
     let mut query_parts = Vec::new();
@@ -147,13 +149,13 @@ pub async fn update_task_id_mapping(
     match bind_values.len() {
         1 => {
             conn.execute(&query, params![bind_values[0], &local_id])
-                .map_err(|e| format!("Failed to update task ID mapping: {}", e))?;
+                .context("Failed to update task ID mapping")?;
         },
         2 => {
             conn.execute(&query, params![bind_values[0], bind_values[1], &local_id])
-                .map_err(|e| format!("Failed to update task ID mapping: {}", e))?;
+                .context("Failed to update task ID mapping")?;
         },
-        _ => return Err("Invalid number of update fields".to_string()),
+        _ => return Err("Invalid number of update fields".into()),
     }
     
     Ok(())
@@ -163,15 +165,15 @@ pub async fn update_task_id_mapping(
 pub async fn delete_task_id_mapping(
     local_id: String,
     db_manager: State<'_, Arc<DatabaseManager>>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     let conn = db_manager.get_connection()
-        .map_err(|e| format!("Failed to get database connection: {}", e))?;
+        .context("Failed to get database connection")?;
     
     conn.execute(
         "DELETE FROM task_id_map WHERE local_id = ?1",
         params![&local_id],
     )
-    .map_err(|e| format!("Failed to delete task ID mapping: {}", e))?;
+    .context("Failed to delete task ID mapping")?;
     
     Ok(())
 }
\ No newline at end of file
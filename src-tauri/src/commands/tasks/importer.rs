@@ -0,0 +1,60 @@
+//! Task Import Commands
+//!
+//! Import tasks from Todoist and TickTick export files; see
+//! `services::google::task_import`.
+
+use anyhow::Context;
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+
+use crate::database::DatabaseManager;
+use crate::services::events::EventBus;
+use crate::services::file_access;
+use crate::services::google::task_import::{self, TaskImportOptions, TaskImportSummary};
+use crate::services::google::tasks_service::GoogleTasksService;
+use super::sync_fixed::journal_task_change;
+use crate::errors::CommandResult;
+
+/// Import a Todoist CSV, JSON or backup zip, or a TickTick backup CSV.
+/// An import cut short by going offline still returns what it created.
+#[tauri::command]
+pub async fn import_tasks_from_file(
+    account_id: String,
+    path: String,
+    options: Option<TaskImportOptions>,
+    app: AppHandle,
+    google_tasks_service: State<'_, GoogleTasksService>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<TaskImportSummary> {
+    let options = options.unwrap_or_default();
+    let path = file_access::permitted_path(&app, &path)?;
+    let parse_options = options.clone();
+    let (format, parsed) = tokio::task::spawn_blocking(move || task_import::parse_file(&path, &parse_options))
+        .await
+        .context("Import task failed")??;
+
+    let summary = task_import::import_tasks(&google_tasks_service, &db_manager, &account_id, format, parsed, &options)
+        .await
+        .context("Failed to import tasks")?;
+
+    for task in &summary.imported {
+        let payload = serde_json::json!({
+            "title": task.title,
+            "task_list_id": task.task_list_id,
+            "source": "import",
+        });
+        journal_task_change(&db_manager, &event_bus, &task.task_id, "create", payload, None, &account_id);
+    }
+    eprintln!(
+        "✅ Imported {} tasks ({} skipped, {} lists created)",
+        summary.imported.len(),
+        summary.skipped.len(),
+        summary.lists_created.len()
+    );
+    if let Some(reason) = &summary.interrupted {
+        eprintln!("📴 Task import stopped early: {}", reason);
+    }
+
+    Ok(summary)
+}
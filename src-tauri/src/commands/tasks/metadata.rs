@@ -1,25 +1,95 @@
+use anyhow::Context;
 use crate::models::task_metadata::*;
+use crate::database::operations::change_journal_operations;
 use crate::database::DatabaseManager;
-use rusqlite::{params, OptionalExtension, ToSql};
+use crate::services::availability::{self, AvailabilityPurpose};
+use crate::services::events::EventBus;
+use crate::services::time_service::TimeService;
+use rusqlite::{params, Connection, OptionalExtension, ToSql};
+use serde_json::{json, Value};
 use tauri::State;
 use std::sync::Arc;
+use crate::errors::{CommandResult, LibreOllamaError};
+
+/// Warn when a task's new time block goes against the availability profile
+fn warn_on_availability(db_manager: &DatabaseManager, event_bus: &EventBus, google_task_id: &str, block: &TimeBlock) {
+    let conflicts = db_manager
+        .get_connection()
+        .map_err(LibreOllamaError::from)
+        .and_then(|conn| availability::time_block_conflicts(&conn, block));
+    match conflicts {
+        Ok(conflicts) => event_bus.availability_conflicts("task", google_task_id, None, AvailabilityPurpose::Focus, conflicts),
+        Err(e) => eprintln!("⚠️ Availability check skipped for task {}: {}", google_task_id, e),
+    }
+}
+
+/// change_journal entity type for task metadata
+const METADATA_ENTITY: &str = "task_metadata";
+
+/// A task's metadata with its labels and subtasks, as journaled
+fn metadata_snapshot(conn: &Connection, google_task_id: &str) -> rusqlite::Result<Option<Value>> {
+    let Some((id, task_list_id, priority, time_block)) = conn
+        .query_row(
+            "SELECT id, task_list_id, priority, time_block FROM task_metadata WHERE google_task_id = ?1",
+            params![google_task_id],
+            |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, Option<String>>(3)?)),
+        )
+        .optional()?
+    else {
+        return Ok(None);
+    };
+
+    let labels = conn
+        .prepare(
+            "SELECT l.name FROM task_labels tl JOIN labels l ON l.id = tl.label_id
+             WHERE tl.task_metadata_id = ?1 ORDER BY l.name",
+        )?
+        .query_map(params![id], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let subtasks = conn
+        .prepare("SELECT title, completed, position FROM subtasks WHERE task_metadata_id = ?1 ORDER BY position, id")?
+        .query_map(params![id], |row| {
+            Ok(json!({ "title": row.get::<_, String>(0)?, "completed": row.get::<_, bool>(1)?, "position": row.get::<_, i32>(2)? }))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    Ok(Some(json!({
+        "task_list_id": task_list_id,
+        "priority": priority,
+        "time_block": time_block.and_then(|block| serde_json::from_str::<Value>(&block).ok()),
+        "labels": labels,
+        "subtasks": subtasks,
+    })))
+}
+
+/// Journal the change from `previous` to the task's current metadata
+fn journal_metadata(conn: &Connection, google_task_id: &str, previous: Option<Value>) -> anyhow::Result<()> {
+    let current = metadata_snapshot(conn, google_task_id)?;
+    let (operation, payload) = match (&previous, current) {
+        (_, None) => ("delete", json!({})),
+        (None, Some(current)) => ("create", current),
+        (Some(_), Some(current)) => ("update", current),
+    };
+    change_journal_operations::record_change(conn, METADATA_ENTITY, google_task_id, operation, &payload, previous.as_ref(), None)?;
+    Ok(())
+}
 
 #[tauri::command]
 pub async fn get_task_metadata(
     google_task_id: String,
     db_manager: State<'_, Arc<DatabaseManager>>,
-) -> Result<Option<TaskMetadataWithRelations>, String> {
+) -> CommandResult<Option<TaskMetadataWithRelations>> {
     let db_manager_clone = Arc::clone(&db_manager);
     
-    tokio::task::spawn_blocking(move || -> Result<Option<TaskMetadataWithRelations>, String> {
+    tokio::task::spawn_blocking(move || -> CommandResult<Option<TaskMetadataWithRelations>> {
         let conn = db_manager_clone.get_connection()
-            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+            .context("Failed to get database connection")?;
     
         // Get base metadata
         let metadata_query = "SELECT id, google_task_id, task_list_id, priority, time_block, created_at, updated_at FROM task_metadata WHERE google_task_id = ?1";
         let metadata: Option<TaskMetadata> = conn.query_row(
             metadata_query,
-            &[&google_task_id],
+            [&google_task_id],
             |row| {
                 let time_block_json: Option<String> = row.get(4)?;
                 let time_block = time_block_json.and_then(|json| {
@@ -37,7 +107,7 @@ pub async fn get_task_metadata(
                 })
             }
         ).optional()
-        .map_err(|e| format!("Failed to fetch task metadata: {}", e))?;
+        .context("Failed to fetch task metadata")?;
     
         match metadata {
             Some(metadata) => {
@@ -46,10 +116,10 @@ pub async fn get_task_metadata(
                                    JOIN task_labels tl ON l.id = tl.label_id 
                                    WHERE tl.task_metadata_id = ?1";
                 let mut labels_stmt = conn.prepare(labels_query)
-                    .map_err(|e| format!("Failed to prepare labels query: {}", e))?;
+                    .context("Failed to prepare labels query")?;
                 
                 let labels: Vec<Label> = labels_stmt.query_map(
-                    &[&metadata.id],
+                    [&metadata.id],
                     |row| {
                         Ok(Label {
                             id: row.get(0)?,
@@ -58,18 +128,18 @@ pub async fn get_task_metadata(
                             created_at: row.get(3)?,
                         })
                     }
-                ).map_err(|e| format!("Failed to query labels: {}", e))?
+                ).context("Failed to query labels")?
                 .collect::<Result<Vec<_>, rusqlite::Error>>()
-                .map_err(|e| format!("Failed to collect labels: {}", e))?;
+                .context("Failed to collect labels")?;
                 
                 // Get subtasks
                 let subtasks_query = "SELECT id, task_metadata_id, title, completed, position, created_at 
                                      FROM subtasks WHERE task_metadata_id = ?1 ORDER BY position";
                 let mut subtasks_stmt = conn.prepare(subtasks_query)
-                    .map_err(|e| format!("Failed to prepare subtasks query: {}", e))?;
+                    .context("Failed to prepare subtasks query")?;
                 
                 let subtasks: Vec<Subtask> = subtasks_stmt.query_map(
-                    &[&metadata.id],
+                    [&metadata.id],
                     |row| {
                         Ok(Subtask {
                             id: row.get(0)?,
@@ -80,9 +150,9 @@ pub async fn get_task_metadata(
                             created_at: row.get(5)?,
                         })
                     }
-                ).map_err(|e| format!("Failed to query subtasks: {}", e))?
+                ).context("Failed to query subtasks")?
                 .collect::<Result<Vec<_>, rusqlite::Error>>()
-                .map_err(|e| format!("Failed to collect subtasks: {}", e))?;
+                .context("Failed to collect subtasks")?;
                 
                 Ok(Some(TaskMetadataWithRelations {
                     metadata,
@@ -94,28 +164,32 @@ pub async fn get_task_metadata(
         }
     })
     .await
-    .map_err(|e| format!("Task execution failed: {}", e))?
+    .context("Task execution failed")?
 }
 
 #[tauri::command]
 pub async fn create_task_metadata(
     data: CreateTaskMetadata,
     db_manager: State<'_, Arc<DatabaseManager>>,
-) -> Result<TaskMetadataWithRelations, String> {
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<TaskMetadataWithRelations> {
     let db_manager_clone = Arc::clone(&db_manager);
     let google_task_id = data.google_task_id.clone();
+    let time_block = data.time_block.clone();
     
-    let _metadata_id = tokio::task::spawn_blocking(move || -> Result<i64, String> {
+    let _metadata_id = tokio::task::spawn_blocking(move || -> CommandResult<i64> {
         let mut conn = db_manager_clone.get_connection()
-            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+            .context("Failed to get database connection")?;
         
-        let tx = conn.transaction().map_err(|e| format!("Failed to start transaction: {}", e))?;
+        let clock = TimeService::load(&conn).context("Failed to read the time zone")?;
+        let tx = conn.transaction().context("Failed to start transaction")?;
+        let previous = metadata_snapshot(&tx, &data.google_task_id).context("Failed to read task metadata")?;
         
         // Insert task metadata (or update if it exists)
         // Use "none" as default to match frontend expectations (not "normal")
         let priority = data.priority.unwrap_or_else(|| "none".to_string());
         let time_block_json = data.time_block.as_ref().map(|tb| {
-            serde_json::to_string(tb).unwrap_or_else(|_| "null".to_string())
+            serde_json::to_string(&clock.time_block(tb)).unwrap_or_else(|_| "null".to_string())
         });
         
         // Try to insert, and if it fails due to unique constraint, update instead
@@ -129,9 +203,9 @@ pub async fn create_task_metadata(
                 tx.execute(
                     "UPDATE task_metadata SET task_list_id = ?2, priority = ?3, time_block = ?4, updated_at = CURRENT_TIMESTAMP WHERE google_task_id = ?1",
                     params![&data.google_task_id, &data.task_list_id, &priority, &time_block_json],
-                ).map_err(|e| format!("Failed to update existing task metadata: {}", e))?;
+                ).context("Failed to update existing task metadata")?;
             },
-            Err(e) => return Err(format!("Failed to insert task metadata: {}", e)),
+            Err(e) => return Err(anyhow::Error::new(e).context("Failed to insert task metadata").into()),
         }
         let metadata_id = tx.last_insert_rowid();
         
@@ -140,13 +214,13 @@ pub async fn create_task_metadata(
             for label_name in label_names {
                 // Get or create label
                 tx.execute("INSERT OR IGNORE INTO labels (name) VALUES (?1)", params![&label_name])
-                    .map_err(|e| format!("Failed to insert label: {}", e))?;
+                    .context("Failed to insert label")?;
                 let label_id = tx.last_insert_rowid();
                 
                 let label_id = if label_id == 0 {
                     // Label already exists, get its ID
                     tx.query_row("SELECT id FROM labels WHERE name = ?1", params![&label_name], |row| row.get(0))
-                        .map_err(|e| format!("Failed to fetch label ID: {}", e))?
+                        .context("Failed to fetch label ID")?
                 } else {
                     label_id
                 };
@@ -155,7 +229,7 @@ pub async fn create_task_metadata(
                 tx.execute(
                     "INSERT INTO task_labels (task_metadata_id, label_id) VALUES (?1, ?2)",
                     params![metadata_id, label_id],
-                ).map_err(|e| format!("Failed to associate label: {}", e))?;
+                ).context("Failed to associate label")?;
             }
         }
         
@@ -165,18 +239,22 @@ pub async fn create_task_metadata(
                 tx.execute(
                     "INSERT INTO subtasks (task_metadata_id, title, completed, position) VALUES (?1, ?2, ?3, ?4)",
                     params![metadata_id, &subtask.title, subtask.completed, subtask.position],
-                ).map_err(|e| format!("Failed to insert subtask: {}", e))?;
+                ).context("Failed to insert subtask")?;
             }
         }
         
-        tx.commit().map_err(|e| format!("Failed to commit transaction: {}", e))?;
+        journal_metadata(&tx, &data.google_task_id, previous).context("Failed to journal task metadata")?;
+        tx.commit().context("Failed to commit transaction")?;
         
         Ok(metadata_id)
     })
     .await
-    .map_err(|e| format!("Task execution failed: {}", e))??;
+    .context("Task execution failed")??;
 
-    get_task_metadata(google_task_id, db_manager).await?.ok_or_else(|| "Failed to fetch created metadata".to_string())
+    if let Some(block) = &time_block {
+        warn_on_availability(&db_manager, &event_bus, &google_task_id, block);
+    }
+    get_task_metadata(google_task_id, db_manager).await?.ok_or_else(|| "Failed to fetch created metadata".into())
 }
 
 #[tauri::command]
@@ -184,15 +262,17 @@ pub async fn update_task_metadata(
     google_task_id: String,
     updates: UpdateTaskMetadata,
     db_manager: State<'_, Arc<DatabaseManager>>,
-) -> Result<TaskMetadataWithRelations, String> {
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<TaskMetadataWithRelations> {
     let db_manager_clone = Arc::clone(&db_manager);
     let google_task_id_clone = google_task_id.clone();
+    let time_block = updates.time_block.clone();
     
-    tokio::task::spawn_blocking(move || -> Result<(), String> {
+    tokio::task::spawn_blocking(move || -> CommandResult<()> {
         let mut conn = db_manager_clone.get_connection()
-            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+            .context("Failed to get database connection")?;
         
-        let tx = conn.transaction().map_err(|e| format!("Failed to start transaction: {}", e))?;
+        let tx = conn.transaction().context("Failed to start transaction")?;
         
         // Get metadata ID
         let metadata_id: i64 = tx.query_row(
@@ -200,42 +280,44 @@ pub async fn update_task_metadata(
             params![&google_task_id_clone],
             |row| row.get(0),
         ).optional()
-        .map_err(|e| format!("Failed to fetch metadata ID: {}", e))?
-        .ok_or_else(|| "Task metadata not found".to_string())?;
+        .context("Failed to fetch metadata ID")?
+        .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("task metadata for {}", google_task_id_clone) })?;
+        let previous = metadata_snapshot(&tx, &google_task_id_clone).context("Failed to read task metadata")?;
         
         // Update priority if provided
         if let Some(priority) = updates.priority {
             tx.execute(
                 "UPDATE task_metadata SET priority = ?1 WHERE id = ?2",
                 params![&priority, metadata_id],
-            ).map_err(|e| format!("Failed to update priority: {}", e))?;
+            ).context("Failed to update priority")?;
         }
         
         // Update time_block if provided
         if let Some(time_block) = updates.time_block {
-            let time_block_json = serde_json::to_string(&time_block)
+            let clock = TimeService::load(&tx).context("Failed to read the time zone")?;
+            let time_block_json = serde_json::to_string(&clock.time_block(&time_block))
                 .unwrap_or_else(|_| "null".to_string());
             tx.execute(
                 "UPDATE task_metadata SET time_block = ?1 WHERE id = ?2",
                 params![&time_block_json, metadata_id],
-            ).map_err(|e| format!("Failed to update time_block: {}", e))?;
+            ).context("Failed to update time_block")?;
         }
         
         // Update labels if provided
         if let Some(label_names) = updates.labels {
             // Remove existing labels
             tx.execute("DELETE FROM task_labels WHERE task_metadata_id = ?1", params![metadata_id])
-                .map_err(|e| format!("Failed to delete existing labels: {}", e))?;
+                .context("Failed to delete existing labels")?;
             
             // Add new labels
             for label_name in label_names {
                 tx.execute("INSERT OR IGNORE INTO labels (name) VALUES (?1)", params![&label_name])
-                    .map_err(|e| format!("Failed to insert label: {}", e))?;
+                    .context("Failed to insert label")?;
                 let label_id = tx.last_insert_rowid();
                 
                 let label_id = if label_id == 0 {
                     tx.query_row("SELECT id FROM labels WHERE name = ?1", params![&label_name], |row| row.get(0))
-                        .map_err(|e| format!("Failed to fetch label ID: {}", e))?
+                        .context("Failed to fetch label ID")?
                 } else {
                     label_id
                 };
@@ -243,7 +325,7 @@ pub async fn update_task_metadata(
                 tx.execute(
                     "INSERT INTO task_labels (task_metadata_id, label_id) VALUES (?1, ?2)",
                     params![metadata_id, label_id],
-                ).map_err(|e| format!("Failed to associate label: {}", e))?;
+                ).context("Failed to associate label")?;
             }
         }
         
@@ -264,11 +346,11 @@ pub async fn update_task_metadata(
                     params_vec.push(id);
                 }
                 
-                tx.execute(&query, &*params_vec).map_err(|e| format!("Failed to delete removed subtasks: {}", e))?;
+                tx.execute(&query, &*params_vec).context("Failed to delete removed subtasks")?;
             } else {
                 // No existing IDs, delete all subtasks
                 tx.execute("DELETE FROM subtasks WHERE task_metadata_id = ?1", params![metadata_id])
-                    .map_err(|e| format!("Failed to delete all subtasks: {}", e))?;
+                    .context("Failed to delete all subtasks")?;
             }
             
             // Insert or update subtasks
@@ -278,57 +360,67 @@ pub async fn update_task_metadata(
                     tx.execute(
                         "UPDATE subtasks SET title = ?1, completed = ?2, position = ?3 WHERE id = ?4 AND task_metadata_id = ?5",
                         params![&subtask.title, subtask.completed, subtask.position, id, metadata_id],
-                    ).map_err(|e| format!("Failed to update subtask: {}", e))?;
+                    ).context("Failed to update subtask")?;
                 } else {
                     // Insert new subtask
                     tx.execute(
                         "INSERT INTO subtasks (task_metadata_id, title, completed, position) VALUES (?1, ?2, ?3, ?4)",
                         params![metadata_id, &subtask.title, subtask.completed, subtask.position],
-                    ).map_err(|e| format!("Failed to insert subtask: {}", e))?;
+                    ).context("Failed to insert subtask")?;
                 }
             }
         }
         
-        tx.commit().map_err(|e| format!("Failed to commit transaction: {}", e))?;
+        journal_metadata(&tx, &google_task_id_clone, previous).context("Failed to journal task metadata")?;
+        tx.commit().context("Failed to commit transaction")?;
         
         Ok(())
     })
     .await
-    .map_err(|e| format!("Task execution failed: {}", e))??;
+    .context("Task execution failed")??;
 
-    get_task_metadata(google_task_id, db_manager).await?.ok_or_else(|| "Failed to fetch updated metadata".to_string())
+    if let Some(block) = &time_block {
+        warn_on_availability(&db_manager, &event_bus, &google_task_id, block);
+    }
+    get_task_metadata(google_task_id, db_manager).await?.ok_or_else(|| "Failed to fetch updated metadata".into())
 }
 
 #[tauri::command]
 pub async fn delete_task_metadata(
     google_task_id: String,
     db_manager: State<'_, Arc<DatabaseManager>>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     let db_manager_clone = Arc::clone(&db_manager);
     
-    tokio::task::spawn_blocking(move || -> Result<(), String> {
-        let conn = db_manager_clone.get_connection()
-            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+    tokio::task::spawn_blocking(move || -> CommandResult<()> {
+        let mut conn = db_manager_clone.get_connection()
+            .context("Failed to get database connection")?;
+        let tx = conn.transaction().context("Failed to start transaction")?;
         
-        conn.execute("DELETE FROM task_metadata WHERE google_task_id = ?1", params![&google_task_id])
-            .map_err(|e| format!("Failed to delete task metadata: {}", e))?;
+        let Some(previous) = metadata_snapshot(&tx, &google_task_id).context("Failed to read task metadata")? else {
+            return Ok(());
+        };
+        tx.execute("DELETE FROM task_metadata WHERE google_task_id = ?1", params![&google_task_id])
+            .context("Failed to delete task metadata")?;
+        journal_metadata(&tx, &google_task_id, Some(previous)).context("Failed to journal task metadata")?;
+        tx.commit().context("Failed to commit transaction")?;
         
         Ok(())
     })
     .await
-    .map_err(|e| format!("Task execution failed: {}", e))?
+    .context("Task execution failed")?
 }
 
 #[tauri::command]
-pub async fn get_all_labels(db_manager: State<'_, Arc<DatabaseManager>>) -> Result<Vec<Label>, String> {
+pub async fn get_all_labels(db_manager: State<'_, Arc<DatabaseManager>>) -> CommandResult<Vec<Label>> {
     let db_manager_clone = Arc::clone(&db_manager);
     
-    tokio::task::spawn_blocking(move || -> Result<Vec<Label>, String> {
+    tokio::task::spawn_blocking(move || -> CommandResult<Vec<Label>> {
         let conn = db_manager_clone.get_connection()
-            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+            .context("Failed to get database connection")?;
         
         let mut stmt = conn.prepare("SELECT * FROM labels ORDER BY name")
-            .map_err(|e| format!("Failed to prepare labels query: {}", e))?;
+            .context("Failed to prepare labels query")?;
         
         let labels: Vec<Label> = stmt.query_map([], |row| {
             Ok(Label {
@@ -338,12 +430,36 @@ pub async fn get_all_labels(db_manager: State<'_, Arc<DatabaseManager>>) -> Resu
                 created_at: row.get(3)?,
             })
         })
-        .map_err(|e| format!("Failed to query labels: {}", e))?
+        .context("Failed to query labels")?
         .collect::<Result<Vec<_>, rusqlite::Error>>()
-        .map_err(|e| format!("Failed to collect labels: {}", e))?;
+        .context("Failed to collect labels")?;
         
         Ok(labels)
     })
     .await
-    .map_err(|e| format!("Task execution failed: {}", e))?
+    .context("Task execution failed")?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    #[test]
+    fn test_metadata_changes_are_journaled() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        conn.execute("INSERT INTO task_metadata (google_task_id, task_list_id, priority) VALUES ('t1', 'l1', 'high')", []).unwrap();
+        journal_metadata(&conn, "t1", None).unwrap();
+
+        let previous = metadata_snapshot(&conn, "t1").unwrap();
+        conn.execute("DELETE FROM task_metadata WHERE google_task_id = 't1'", []).unwrap();
+        journal_metadata(&conn, "t1", previous).unwrap();
+
+        let history = change_journal_operations::get_entity_history(&conn, METADATA_ENTITY, "t1").unwrap();
+        assert_eq!(history.iter().map(|entry| entry.operation.as_str()).collect::<Vec<_>>(), ["create", "delete"]);
+        assert_eq!(history[1].previous_payload.as_ref().unwrap()["priority"], "high");
+        let (operation, restored) = change_journal_operations::inverse_of(&history[1]);
+        assert_eq!((operation, restored["task_list_id"].as_str()), ("create", Some("l1")));
+    }
 }
@@ -1,9 +1,12 @@
+use anyhow::Context;
 use crate::database::DatabaseManager;
 use crate::models::task_metadata::{TimeBlock};
+use crate::services::availability::{self, AvailabilityConflict};
+use crate::services::time_service::TimeService;
 use rusqlite::params;
-use tauri::State;
 use std::sync::Arc;
 use serde::{Deserialize, Serialize};
+use crate::errors::CommandResult;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SimpleLabel {
@@ -11,7 +14,8 @@ pub struct SimpleLabel {
     pub color: String,
 }
 
-/// Create or update task metadata with simplified label storage
+/// Create or update task metadata with simplified label storage. Returns
+/// the ways the time block, if any, goes against the availability profile.
 pub async fn create_or_update_metadata(
     google_task_id: String,
     task_list_id: String,
@@ -19,26 +23,42 @@ pub async fn create_or_update_metadata(
     labels: Option<Vec<SimpleLabel>>,
     time_block: Option<TimeBlock>,
     db_manager: Arc<DatabaseManager>,
-) -> Result<(), String> {
-    tokio::task::spawn_blocking(move || -> Result<(), String> {
+) -> CommandResult<Vec<AvailabilityConflict>> {
+    tokio::task::spawn_blocking(move || -> CommandResult<Vec<AvailabilityConflict>> {
         let conn = db_manager.get_connection()
-            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+            .context("Failed to get database connection")?;
         
         // Use "none" as default priority to match frontend expectations
         let priority_value = priority.unwrap_or_else(|| "none".to_string());
         
         // Convert labels to JSON
-        let labels_json = if let Some(label_list) = labels {
-            Some(serde_json::to_string(&label_list).unwrap_or_else(|_| "[]".to_string()))
-        } else {
-            None
-        };
+        let labels_json = labels.map(|label_list| serde_json::to_string(&label_list).unwrap_or_else(|_| "[]".to_string()));
         
-        // Convert time_block to JSON
+        // Convert time_block to JSON, stored in UTC
+        let clock = TimeService::load(&conn).context("Failed to read the time zone")?;
+        let time_block = time_block.map(|tb| clock.time_block(&tb));
         let time_block_json = time_block.as_ref().map(|tb| {
             serde_json::to_string(tb).unwrap_or_else(|_| "null".to_string())
         });
         
+        // Capture the values being replaced for the change journal
+        let previous: Option<serde_json::Value> = conn
+            .query_row(
+                "SELECT priority, labels_json, time_block FROM task_metadata WHERE google_task_id = ?1",
+                params![&google_task_id],
+                |row| {
+                    let priority: String = row.get(0)?;
+                    let labels: Option<String> = row.get(1)?;
+                    let time_block: Option<String> = row.get(2)?;
+                    Ok(serde_json::json!({
+                        "priority": priority,
+                        "labels": labels.and_then(|l| serde_json::from_str::<serde_json::Value>(&l).ok()),
+                        "time_block": time_block.and_then(|t| serde_json::from_str::<serde_json::Value>(&t).ok()),
+                    }))
+                },
+            )
+            .ok();
+
         // Try to insert, and if it fails due to unique constraint, update instead
         match conn.execute(
             "INSERT INTO task_metadata (google_task_id, task_list_id, priority, labels_json, time_block) 
@@ -56,27 +76,46 @@ pub async fn create_or_update_metadata(
                      SET task_list_id = ?2, priority = ?3, labels_json = ?4, time_block = ?5, updated_at = CURRENT_TIMESTAMP 
                      WHERE google_task_id = ?1",
                     params![&google_task_id, &task_list_id, &priority_value, &labels_json, &time_block_json],
-                ).map_err(|e| format!("Failed to update existing task metadata: {}", e))?;
+                ).context("Failed to update existing task metadata")?;
                 eprintln!("✅ Updated metadata for task {}: priority={}, labels={:?}", 
                     google_task_id, priority_value, labels_json);
             },
-            Err(e) => return Err(format!("Failed to insert task metadata: {}", e)),
+            Err(e) => return Err(anyhow::Error::new(e).context("Failed to insert task metadata").into()),
         }
-        
-        Ok(())
+
+        let payload = serde_json::json!({
+            "priority": priority_value,
+            "labels": labels_json.as_ref().and_then(|l| serde_json::from_str::<serde_json::Value>(l).ok()),
+            "time_block": time_block.as_ref().and_then(|t| serde_json::to_value(t).ok()),
+        });
+        let operation = if previous.is_some() { "update" } else { "create" };
+        if let Err(e) = crate::database::operations::change_journal_operations::record_change(
+            &conn, "task_metadata", &google_task_id, operation, &payload, previous.as_ref(), None,
+        ) {
+            eprintln!("⚠️ Failed to journal metadata change for task {}: {}", google_task_id, e);
+        }
+
+        let conflicts = match &time_block {
+            Some(tb) => availability::time_block_conflicts(&conn, tb).unwrap_or_else(|e| {
+                eprintln!("⚠️ Availability check skipped for task {}: {}", google_task_id, e);
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+        Ok(conflicts)
     })
     .await
-    .map_err(|e| format!("Task execution failed: {}", e))?
+    .context("Task execution failed")?
 }
 
 /// Get task metadata with simplified label retrieval
 pub async fn get_simple_metadata(
     google_task_id: String,
     db_manager: Arc<DatabaseManager>,
-) -> Result<(String, Vec<SimpleLabel>, Option<TimeBlock>), String> {
-    tokio::task::spawn_blocking(move || -> Result<(String, Vec<SimpleLabel>, Option<TimeBlock>), String> {
+) -> CommandResult<(String, Vec<SimpleLabel>, Option<TimeBlock>)> {
+    tokio::task::spawn_blocking(move || -> CommandResult<(String, Vec<SimpleLabel>, Option<TimeBlock>)> {
         let conn = db_manager.get_connection()
-            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+            .context("Failed to get database connection")?;
         
         let (priority, labels_json, time_block_json): (String, Option<String>, Option<String>) = conn
             .query_row(
@@ -88,10 +127,7 @@ pub async fn get_simple_metadata(
         
         // Parse labels from JSON (keep color info)
         let labels = if let Some(json) = labels_json {
-            match serde_json::from_str::<Vec<SimpleLabel>>(&json) {
-                Ok(label_list) => label_list,
-                Err(_) => Vec::new(),
-            }
+            serde_json::from_str::<Vec<SimpleLabel>>(&json).unwrap_or_default()
         } else {
             Vec::new()
         };
@@ -104,5 +140,5 @@ pub async fn get_simple_metadata(
         Ok((priority, labels, time_block))
     })
     .await
-    .map_err(|e| format!("Task execution failed: {}", e))?
+    .context("Task execution failed")?
 }
\ No newline at end of file
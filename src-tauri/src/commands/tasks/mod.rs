@@ -1,10 +1,18 @@
 pub mod all_task_data;
+pub mod api;
 pub mod metadata;
 pub mod metadata_simple;
 // pub mod sync;  // Disabled - using sync_fixed instead
 pub mod sync_fixed;
 pub mod sync_simple;
 pub mod id_map;
+pub mod boards;
+pub mod recurrence;
+pub mod subtasks;
+pub mod export;
+pub mod activity;
+pub mod bulk;
+pub mod importer;
 
 // Re-export the fixed sync functions
 pub use sync_fixed::{create_google_task, update_google_task, delete_google_task, update_google_task_list};
\ No newline at end of file
@@ -0,0 +1,68 @@
+//! Task Recurrence Commands
+//!
+//! Commands for attaching RRULE-like recurrence rules to Google Tasks and for
+//! running the local recurrence scheduler.
+
+use anyhow::Context;
+use crate::{
+    database::DatabaseManager,
+    services::google::{
+        task_recurrence::{self, RecurrenceOccurrence, RecurrenceRule},
+        tasks_service::GoogleTasksService,
+    },
+};
+use std::sync::Arc;
+use tauri::State;
+use crate::errors::{CommandResult, LibreOllamaError};
+
+/// Set the recurrence rule for a task; returns the normalized rule string
+#[tauri::command]
+pub async fn set_task_recurrence(
+    google_task_id: String,
+    task_list_id: String,
+    rule: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<String> {
+    let parsed = RecurrenceRule::parse(&rule)
+        .map_err(|e| LibreOllamaError::InvalidInput {
+            message: format!("Invalid recurrence rule: {}", e),
+            field: Some("rule".to_string()),
+        })?;
+
+    task_recurrence::set_task_recurrence_rule(&db_manager, &google_task_id, &task_list_id, Some(&parsed))
+        .context("Failed to set task recurrence")?;
+
+    Ok(parsed.to_rule_string())
+}
+
+#[tauri::command]
+pub async fn clear_task_recurrence(
+    google_task_id: String,
+    task_list_id: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<()> {
+    Ok(task_recurrence::set_task_recurrence_rule(&db_manager, &google_task_id, &task_list_id, None)
+        .context("Failed to clear task recurrence")?)
+}
+
+#[tauri::command]
+pub async fn get_task_recurrence(
+    google_task_id: String,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Option<String>> {
+    Ok(task_recurrence::get_task_recurrence_rule(&db_manager, &google_task_id)
+        .map(|rule| rule.map(|r| r.to_rule_string()))
+        .context("Failed to get task recurrence")?)
+}
+
+/// Spawn next occurrences for completed or overdue recurring tasks
+#[tauri::command]
+pub async fn process_task_recurrences(
+    account_id: String,
+    google_tasks_service: State<'_, GoogleTasksService>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<RecurrenceOccurrence>> {
+    Ok(task_recurrence::process_recurring_tasks(&google_tasks_service, db_manager.inner().clone(), &account_id)
+        .await
+        .context("Failed to process recurring tasks")?)
+}
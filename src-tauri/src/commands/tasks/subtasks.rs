@@ -0,0 +1,52 @@
+//! Subtask Sync Commands
+//!
+//! Commands for syncing local subtasks with Google Tasks child tasks.
+
+use anyhow::Context;
+use crate::{
+    database::DatabaseManager,
+    services::google::{
+        subtask_sync::{self, SubtaskSyncSummary},
+        tasks_service::GoogleTasksService,
+    },
+};
+use std::sync::Arc;
+use tauri::State;
+use crate::errors::CommandResult;
+
+/// Push local subtasks of a task to Google and pull back the remote hierarchy
+#[tauri::command]
+pub async fn sync_task_subtasks(
+    account_id: String,
+    task_list_id: String,
+    google_task_id: String,
+    google_tasks_service: State<'_, GoogleTasksService>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<SubtaskSyncSummary> {
+    Ok(subtask_sync::sync_task_subtasks(
+        &google_tasks_service,
+        db_manager.inner().clone(),
+        &account_id,
+        &task_list_id,
+        &google_task_id,
+    )
+    .await
+    .context("Failed to sync subtasks")?)
+}
+
+/// Move a task under a new parent (or to the top level) after an optional sibling
+#[tauri::command]
+pub async fn move_google_task(
+    account_id: String,
+    task_list_id: String,
+    task_id: String,
+    parent: Option<String>,
+    previous: Option<String>,
+    google_tasks_service: State<'_, GoogleTasksService>,
+) -> CommandResult<()> {
+    Ok(google_tasks_service
+        .move_task(&account_id, &task_list_id, &task_id, parent.as_deref(), previous.as_deref())
+        .await
+        .map(|_| ())
+        .context("Failed to move Google Task")?)
+}
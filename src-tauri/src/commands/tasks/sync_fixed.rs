@@ -1,12 +1,18 @@
+use anyhow::Context;
 use crate::{
-    database::DatabaseManager,
-    services::google::tasks_service::{GoogleTasksService, CreateTaskInput, UpdateTaskInput},
+    database::{operations::{cache_delta_operations::{self, NewCacheDelta}, change_journal_operations, task_queue_operations}, DatabaseManager},
+    services::availability::AvailabilityPurpose,
+    services::events::EventBus,
+    services::google::task_activity,
+    services::google::tasks_service::{GoogleTasksService, GoogleTask, CreateTaskInput, UpdateTaskInput},
+    services::sync::task_queue,
     models::task_metadata::{TimeBlock},
 };
 use std::sync::Arc;
 use tauri::State;
 use serde::{Deserialize, Serialize};
 use super::metadata_simple::SimpleLabel;
+use crate::errors::CommandResult;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateTaskRequest {
@@ -24,7 +30,7 @@ pub struct CreateTaskRequest {
 pub struct UpdateTaskRequest {
     pub account_id: String,
     pub task_list_id: String,
-    pub task_id: String,  // Google Task ID
+    pub task_id: String,  // Google Task ID, or the temporary ID of a task created offline
     pub title: Option<String>,
     pub notes: Option<String>,
     pub due: Option<String>,
@@ -32,6 +38,10 @@ pub struct UpdateTaskRequest {
     pub priority: Option<String>,
     pub labels: Option<Vec<SimpleLabel>>,
     pub time_block: Option<TimeBlock>,
+    /// The task's `updated` as last seen, so an edit queued offline can be
+    /// checked against changes made on the server meanwhile
+    #[serde(default)]
+    pub base_updated: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +49,8 @@ pub struct DeleteTaskRequest {
     pub account_id: String,
     pub task_list_id: String,
     pub task_id: String,
+    #[serde(default)]
+    pub base_updated: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,6 +64,106 @@ pub struct TaskResponse {
     pub updated: Option<String>,
     pub priority: String,
     pub labels: Vec<SimpleLabel>,
+    /// The change is queued locally and has not reached Google yet
+    #[serde(default)]
+    pub pending_sync: bool,
+}
+
+/// Fields an update request actually sets, for the change journal
+fn changed_task_fields(request: &UpdateTaskRequest) -> serde_json::Map<String, serde_json::Value> {
+    let mut fields = serde_json::Map::new();
+    if let Some(title) = &request.title {
+        fields.insert("title".to_string(), serde_json::json!(title));
+    }
+    if let Some(notes) = &request.notes {
+        fields.insert("notes".to_string(), serde_json::json!(notes));
+    }
+    if let Some(due) = &request.due {
+        fields.insert("due".to_string(), serde_json::json!(due));
+    }
+    if let Some(status) = &request.status {
+        fields.insert("status".to_string(), serde_json::json!(status));
+    }
+    fields
+}
+
+/// A task's fields as the change journal records them
+fn task_fields(task: &GoogleTask, task_list_id: &str) -> serde_json::Map<String, serde_json::Value> {
+    let mut fields = serde_json::Map::new();
+    fields.insert("title".to_string(), serde_json::json!(task.title));
+    fields.insert("notes".to_string(), serde_json::json!(task.notes));
+    fields.insert("due".to_string(), serde_json::json!(task.due));
+    fields.insert("status".to_string(), serde_json::json!(task.status));
+    fields.insert("task_list_id".to_string(), serde_json::json!(task_list_id));
+    fields
+}
+
+/// The values an update replaced, plus the list the task was in
+fn replaced_fields(
+    before: &serde_json::Map<String, serde_json::Value>,
+    changed: &serde_json::Map<String, serde_json::Value>,
+) -> serde_json::Value {
+    let replaced = before
+        .iter()
+        .filter(|(key, _)| changed.contains_key(*key) || key.as_str() == "task_list_id")
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    serde_json::Value::Object(replaced)
+}
+
+/// Record a task mutation in the change journal and announce it to the
+/// task store; failures are logged, not fatal.
+///
+/// `previous` is what undo restores. Without it, the state rebuilt from
+/// the journal is used, which only knows tasks changed in this app.
+pub(crate) fn journal_task_change(
+    db_manager: &DatabaseManager,
+    event_bus: &EventBus,
+    task_id: &str,
+    operation: &str,
+    payload: serde_json::Value,
+    previous: Option<serde_json::Value>,
+    account_id: &str,
+) {
+    let result = db_manager.get_connection().and_then(|conn| {
+        let previous = match previous {
+            Some(previous) => Some(previous),
+            None if operation == "create" => None,
+            None => {
+                let journaled = change_journal_operations::replay_entity(&conn, "task", task_id)?;
+                let journaled = journaled.as_ref().and_then(|state| state.as_object());
+                match (journaled, payload.as_object()) {
+                    (Some(state), Some(changed)) if operation == "update" => Some(replaced_fields(state, changed)),
+                    (Some(state), _) => Some(serde_json::Value::Object(state.clone())),
+                    (None, _) => None,
+                }
+            }
+        };
+        change_journal_operations::record_change(
+            &conn, "task", task_id, operation, &payload, previous.as_ref(), Some(account_id),
+        )
+    });
+    if let Err(e) = result {
+        eprintln!("⚠️ Failed to journal task {} {}: {}", operation, task_id, e);
+    }
+
+    announce_task_change(event_bus, account_id, task_id, operation, payload);
+}
+
+/// Tell the task store a task was added, changed or removed
+pub(crate) fn announce_task_change(
+    event_bus: &EventBus,
+    account_id: &str,
+    task_id: &str,
+    operation: &str,
+    payload: serde_json::Value,
+) {
+    let change = match operation {
+        "create" => cache_delta_operations::CHANGE_ADDED,
+        "delete" => cache_delta_operations::CHANGE_REMOVED,
+        _ => cache_delta_operations::CHANGE_UPDATED,
+    };
+    event_bus.cache_changed(&[NewCacheDelta::task(account_id, task_id, change).with_data(payload)]);
 }
 
 #[tauri::command]
@@ -59,9 +171,10 @@ pub async fn create_google_task(
     request: CreateTaskRequest,
     google_tasks_service: State<'_, GoogleTasksService>,
     db_manager: State<'_, Arc<DatabaseManager>>,
-) -> Result<TaskResponse, String> {
-    // Create task in Google Tasks
-    let google_task = google_tasks_service
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<TaskResponse> {
+    // Create task in Google Tasks, or queue it when Google is unreachable
+    let created = google_tasks_service
         .create_task(
             &request.account_id,
             &request.task_list_id,
@@ -72,17 +185,40 @@ pub async fn create_google_task(
                 status: Some("needsAction".to_string()),
             },
         )
-        .await
-        .map_err(|e| format!("Failed to create Google Task: {}", e))?;
+        .await;
+    let mut google_task = match created {
+        Ok(task) => task,
+        Err(e) if task_queue::is_offline(&e) => return create_task_offline(request, &db_manager, &event_bus).await,
+        Err(e) => return Err(anyhow::Error::new(e).context("Failed to create Google Task").into()),
+    };
 
     eprintln!("📝 Created Google task with ID: {}", google_task.id);
 
+    journal_task_change(
+        &db_manager,
+        &event_bus,
+        &google_task.id,
+        "create",
+        serde_json::json!({
+            "title": google_task.title,
+            "notes": google_task.notes,
+            "due": google_task.due,
+            "status": google_task.status,
+            "task_list_id": request.task_list_id,
+        }),
+        None,
+        &request.account_id,
+    );
+    if let Some(notes) = task_activity::mirror_or_queue(&google_tasks_service, &db_manager, &request.account_id, &request.task_list_id, &google_task.id).await {
+        google_task.notes = Some(notes);
+    }
+
     // Store metadata in local DB
     if request.priority.is_some() || request.labels.is_some() || request.time_block.is_some() {
         eprintln!("💾 Storing metadata for task {}: priority={:?}, labels={:?}", 
             google_task.id, request.priority, request.labels);
             
-        super::metadata_simple::create_or_update_metadata(
+        let conflicts = super::metadata_simple::create_or_update_metadata(
             google_task.id.clone(),
             request.task_list_id.clone(),
             request.priority.clone(),
@@ -91,6 +227,7 @@ pub async fn create_google_task(
             db_manager.inner().clone(),
         )
         .await?;
+        event_bus.availability_conflicts("task", &google_task.id, Some(&google_task.title), AvailabilityPurpose::Focus, conflicts);
     }
 
     Ok(TaskResponse {
@@ -103,6 +240,63 @@ pub async fn create_google_task(
         updated: google_task.updated,
         priority: request.priority.unwrap_or_else(|| "none".to_string()),
         labels: request.labels.unwrap_or_default(),
+        pending_sync: false,
+    })
+}
+
+/// Queue a create under a temporary ID and answer as if it had succeeded
+async fn create_task_offline(
+    request: CreateTaskRequest,
+    db_manager: &Arc<DatabaseManager>,
+    event_bus: &EventBus,
+) -> CommandResult<TaskResponse> {
+    let mut fields = serde_json::Map::new();
+    fields.insert("title".to_string(), serde_json::json!(request.title));
+    if let Some(notes) = &request.notes {
+        fields.insert("notes".to_string(), serde_json::json!(notes));
+    }
+    if let Some(due) = &request.due {
+        fields.insert("due".to_string(), serde_json::json!(due));
+    }
+    fields.insert("status".to_string(), serde_json::json!("needsAction"));
+
+    let temp_id = {
+        let conn = db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        task_queue::queue_task_create(&conn, &request.account_id, &request.task_list_id, fields.clone())
+            .context("Failed to queue task")?
+    };
+    eprintln!("📴 Google Tasks unreachable, queued task {}", temp_id);
+
+    let mut payload = fields;
+    payload.insert("task_list_id".to_string(), serde_json::json!(request.task_list_id));
+    journal_task_change(db_manager, event_bus, &temp_id, "create", serde_json::Value::Object(payload), None, &request.account_id);
+    task_activity::queue_mirror(db_manager, &request.account_id, &request.task_list_id, &temp_id);
+
+    if request.priority.is_some() || request.labels.is_some() || request.time_block.is_some() {
+        let conflicts = super::metadata_simple::create_or_update_metadata(
+            temp_id.clone(),
+            request.task_list_id.clone(),
+            request.priority.clone(),
+            request.labels.clone(),
+            request.time_block.clone(),
+            db_manager.clone(),
+        )
+        .await?;
+        event_bus.availability_conflicts("task", &temp_id, Some(&request.title), AvailabilityPurpose::Focus, conflicts);
+    }
+
+    Ok(TaskResponse {
+        id: temp_id,
+        title: request.title,
+        notes: request.notes,
+        due: request.due,
+        status: "needsAction".to_string(),
+        position: None,
+        updated: None,
+        priority: request.priority.unwrap_or_else(|| "none".to_string()),
+        labels: request.labels.unwrap_or_default(),
+        pending_sync: true,
     })
 }
 
@@ -111,12 +305,31 @@ pub async fn update_google_task(
     request: UpdateTaskRequest,
     google_tasks_service: State<'_, GoogleTasksService>,
     db_manager: State<'_, Arc<DatabaseManager>>,
-) -> Result<TaskResponse, String> {
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<TaskResponse> {
     eprintln!("📝 Updating task {}: priority={:?}, labels={:?}", 
         request.task_id, request.priority, request.labels);
 
+    // A task created offline only exists in the queue until it is pushed
+    if task_queue_operations::is_temp_task_id(&request.task_id) {
+        return update_task_offline(request, &db_manager, &event_bus).await;
+    }
+
+    // What the update replaces, so it can be undone
+    let before = match google_tasks_service
+        .get_single_task(&request.account_id, &request.task_list_id, &request.task_id)
+        .await
+    {
+        Ok(task) => Some(task_fields(&task, &request.task_list_id)),
+        Err(e) if task_queue::is_offline(&e) => return update_task_offline(request, &db_manager, &event_bus).await,
+        Err(e) => {
+            eprintln!("⚠️ Failed to read task {} before updating it: {}", request.task_id, e);
+            None
+        }
+    };
+
     // Update task in Google Tasks (only fields Google supports)
-    let google_task = google_tasks_service
+    let updated = google_tasks_service
         .update_task(
             &request.account_id,
             &request.task_list_id,
@@ -128,14 +341,18 @@ pub async fn update_google_task(
                 status: request.status.clone(),
             },
         )
-        .await
-        .map_err(|e| format!("Failed to update Google Task: {}", e))?;
+        .await;
+    let mut google_task = match updated {
+        Ok(task) => task,
+        Err(e) if task_queue::is_offline(&e) => return update_task_offline(request, &db_manager, &event_bus).await,
+        Err(e) => return Err(anyhow::Error::new(e).context("Failed to update Google Task").into()),
+    };
 
     // Always update/create metadata in local DB to ensure it persists
     eprintln!("💾 Updating metadata for task {}: priority={:?}, labels={:?}", 
         request.task_id, request.priority, request.labels);
         
-    super::metadata_simple::create_or_update_metadata(
+    let conflicts = super::metadata_simple::create_or_update_metadata(
         request.task_id.clone(),
         request.task_list_id.clone(),
         request.priority.clone(),
@@ -144,6 +361,44 @@ pub async fn update_google_task(
         db_manager.inner().clone(),
     )
     .await?;
+    event_bus.availability_conflicts("task", &request.task_id, Some(&google_task.title), AvailabilityPurpose::Focus, conflicts);
+
+    let changed = changed_task_fields(&request);
+    let previous = before.map(|before| replaced_fields(&before, &changed));
+    journal_task_change(
+        &db_manager,
+        &event_bus,
+        &google_task.id,
+        "update",
+        serde_json::Value::Object(changed),
+        previous,
+        &request.account_id,
+    );
+    if let Some(notes) = task_activity::mirror_or_queue(&google_tasks_service, &db_manager, &request.account_id, &request.task_list_id, &google_task.id).await {
+        google_task.notes = Some(notes);
+    }
+
+    // Completing a recurring task schedules its next occurrence locally
+    if google_task.status == "completed" {
+        match crate::services::google::task_recurrence::get_task_recurrence_rule(&db_manager, &google_task.id) {
+            Ok(Some(rule)) => {
+                match crate::services::google::task_recurrence::spawn_next_occurrence(
+                    &google_tasks_service,
+                    db_manager.inner().clone(),
+                    &request.account_id,
+                    &request.task_list_id,
+                    &google_task,
+                    &rule,
+                ).await {
+                    Ok(Some(occurrence)) => eprintln!("🔁 Created next occurrence {} due {}", occurrence.new_task_id, occurrence.due),
+                    Ok(None) => eprintln!("🔁 Recurrence for task {} has ended", google_task.id),
+                    Err(e) => eprintln!("⚠️ Failed to create next occurrence for {}: {}", google_task.id, e),
+                }
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("⚠️ Failed to read recurrence rule for {}: {}", google_task.id, e),
+        }
+    }
 
     // Get metadata from DB to return
     let (priority, labels, _time_block) = super::metadata_simple::get_simple_metadata(
@@ -166,35 +421,132 @@ pub async fn update_google_task(
         updated: google_task.updated,
         priority,
         labels,
+        pending_sync: false,
     })
 }
 
-#[tauri::command]
-pub async fn delete_google_task(
-    request: DeleteTaskRequest,
-    google_tasks_service: State<'_, GoogleTasksService>,
-    _db_manager: State<'_, Arc<DatabaseManager>>,
-) -> Result<(), String> {
-    google_tasks_service
-        .delete_task(
+/// Queue an update and answer with the fields known locally. Fields the
+/// request does not set are only known for tasks created offline; for other
+/// tasks the frontend keeps its own copy until the next sync.
+async fn update_task_offline(
+    request: UpdateTaskRequest,
+    db_manager: &Arc<DatabaseManager>,
+    event_bus: &EventBus,
+) -> CommandResult<TaskResponse> {
+    let fields = changed_task_fields(&request);
+    let queued = {
+        let conn = db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        task_queue::queue_task_update(
+            &conn,
             &request.account_id,
             &request.task_list_id,
             &request.task_id,
+            fields.clone(),
+            request.base_updated.as_deref(),
         )
-        .await
-        .map_err(|e| format!("Failed to delete Google Task: {}", e))?;
+        .context("Failed to queue task update")?
+    };
+    eprintln!("📴 Queued update of task {}", request.task_id);
+
+    let conflicts = super::metadata_simple::create_or_update_metadata(
+        request.task_id.clone(),
+        request.task_list_id.clone(),
+        request.priority.clone(),
+        request.labels.clone(),
+        request.time_block.clone(),
+        db_manager.clone(),
+    )
+    .await?;
+    event_bus.availability_conflicts("task", &request.task_id, request.title.as_deref(), AvailabilityPurpose::Focus, conflicts);
+
+    journal_task_change(db_manager, event_bus, &request.task_id, "update", serde_json::Value::Object(fields), None, &request.account_id);
+    task_activity::queue_mirror(db_manager, &request.account_id, &request.task_list_id, &request.task_id);
+
+    let (priority, labels, _time_block) = super::metadata_simple::get_simple_metadata(
+        request.task_id.clone(),
+        db_manager.clone(),
+    )
+    .await
+    .unwrap_or_else(|_| ("none".to_string(), Vec::new(), None));
+
+    let text = |key: &str| queued.get(key).and_then(|v| v.as_str()).map(|s| s.to_string());
+    Ok(TaskResponse {
+        id: request.task_id,
+        title: text("title").unwrap_or_default(),
+        notes: text("notes"),
+        due: text("due"),
+        status: text("status").unwrap_or_else(|| "needsAction".to_string()),
+        position: None,
+        updated: None,
+        priority,
+        labels,
+        pending_sync: true,
+    })
+}
+
+#[tauri::command]
+pub async fn delete_google_task(
+    request: DeleteTaskRequest,
+    google_tasks_service: State<'_, GoogleTasksService>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<()> {
+    let mut previous = None;
+    let deleted = if task_queue_operations::is_temp_task_id(&request.task_id) {
+        Err(None)
+    } else {
+        // Kept so undo can recreate the task
+        previous = google_tasks_service
+            .get_single_task(&request.account_id, &request.task_list_id, &request.task_id)
+            .await
+            .ok()
+            .map(|task| serde_json::Value::Object(task_fields(&task, &request.task_list_id)));
+        google_tasks_service
+            .delete_task(
+                &request.account_id,
+                &request.task_list_id,
+                &request.task_id,
+            )
+            .await
+            .map_err(Some)
+    };
+
+    match deleted {
+        Ok(()) => {}
+        // Never reached Google, or Google is unreachable: settle it in the queue
+        Err(None) => queue_task_delete(&request, &db_manager)?,
+        Err(Some(e)) if task_queue::is_offline(&e) => queue_task_delete(&request, &db_manager)?,
+        Err(Some(e)) => return Err(anyhow::Error::new(e).context("Failed to delete Google Task").into()),
+    }
+
+    journal_task_change(&db_manager, &event_bus, &request.task_id, "delete", serde_json::json!({}), previous, &request.account_id);
 
     // Note: We could also delete metadata here, but it will be orphaned and harmless
 
     Ok(())
 }
 
+fn queue_task_delete(request: &DeleteTaskRequest, db_manager: &DatabaseManager) -> CommandResult<()> {
+    let mut conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    task_queue::queue_task_delete(
+        &mut conn,
+        &request.account_id,
+        &request.task_list_id,
+        &request.task_id,
+        request.base_updated.as_deref(),
+    )
+    .context("Failed to queue task delete")?;
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn update_google_task_list(
     _request: serde_json::Value,
     _google_tasks_service: State<'_, GoogleTasksService>,
     _db_manager: State<'_, Arc<DatabaseManager>>,
-) -> Result<serde_json::Value, String> {
+) -> CommandResult<serde_json::Value> {
     // Placeholder for task list updates
     Ok(serde_json::json!({}))
 }
\ No newline at end of file
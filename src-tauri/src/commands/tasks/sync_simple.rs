@@ -1,3 +1,4 @@
+use anyhow::Context;
 use crate::{
     database::DatabaseManager,
     services::google::tasks_service::{GoogleTasksService, CreateTaskInput, UpdateTaskInput},
@@ -7,6 +8,7 @@ use std::sync::Arc;
 use tauri::State;
 use serde::{Deserialize, Serialize};
 use super::metadata_simple::SimpleLabel;
+use crate::errors::CommandResult;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateTaskRequest {
@@ -52,7 +54,7 @@ pub async fn create_google_task_simple(
     request: CreateTaskRequest,
     google_tasks_service: State<'_, GoogleTasksService>,
     db_manager: State<'_, Arc<DatabaseManager>>,
-) -> Result<TaskResponse, String> {
+) -> CommandResult<TaskResponse> {
     // Create task in Google Tasks
     let google_task = google_tasks_service
         .create_task(
@@ -66,7 +68,7 @@ pub async fn create_google_task_simple(
             },
         )
         .await
-        .map_err(|e| format!("Failed to create Google Task: {}", e))?;
+        .context("Failed to create Google Task")?;
 
     // Store metadata in local DB
     if request.priority.is_some() || request.labels.is_some() || request.time_block.is_some() {
@@ -99,7 +101,7 @@ pub async fn update_google_task_simple(
     request: UpdateTaskRequest,
     google_tasks_service: State<'_, GoogleTasksService>,
     db_manager: State<'_, Arc<DatabaseManager>>,
-) -> Result<TaskResponse, String> {
+) -> CommandResult<TaskResponse> {
     // Fetch current task state from Google
     let current_task = google_tasks_service
         .get_single_task(
@@ -108,7 +110,7 @@ pub async fn update_google_task_simple(
             &request.task_id,
         )
         .await
-        .map_err(|e| format!("Failed to fetch current task state: {}", e))?;
+        .context("Failed to fetch current task state")?;
     
     // Merge updates with current state
     let merged_input = UpdateTaskInput {
@@ -127,7 +129,7 @@ pub async fn update_google_task_simple(
             merged_input,
         )
         .await
-        .map_err(|e| format!("Failed to update Google Task: {}", e))?;
+        .context("Failed to update Google Task")?;
 
     // Update metadata in local DB if needed
     if request.priority.is_some() || request.labels.is_some() || request.time_block.is_some() {
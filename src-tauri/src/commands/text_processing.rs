@@ -1,6 +1,36 @@
+use anyhow::Context;
 use strip_markdown::strip_markdown;
 
+use crate::services::pdf_extract::{self, PdfDocument};
+use crate::services::text_processing::{self, ExtractedEntity, TextAnalysis};
+use crate::errors::{CommandError, CommandResult};
+
 #[tauri::command]
 pub fn clean_text(text: String) -> String {
     strip_markdown(&text)
-} 
\ No newline at end of file
+}
+
+/// Language, reading time and entities for an email body (HTML or plain text)
+#[tauri::command]
+pub fn analyze_text(text: String) -> TextAnalysis {
+    text_processing::analyze(&text, chrono::Local::now().date_naive())
+}
+
+/// Entities the UI can render as smart chips, e.g. "add to calendar" for
+/// dates or "track package" for tracking numbers
+#[tauri::command]
+pub fn extract_entities(text: String) -> Vec<ExtractedEntity> {
+    let plain = text_processing::to_plain_text(&text);
+    text_processing::extract_entities(&plain, chrono::Local::now().date_naive())
+}
+
+/// Per-page text and structure of a PDF on disk; pages without a text layer
+/// are read by OCR when `ocr` is set (default) and tesseract is installed
+#[tauri::command]
+pub async fn extract_pdf_text(path: String, ocr: Option<bool>) -> CommandResult<PdfDocument> {
+    tokio::task::spawn_blocking(move || {
+        let bytes = std::fs::read(&path).with_context(|| format!("Failed to read {}", path))?;
+        pdf_extract::extract(&bytes, ocr.unwrap_or(true)).map_err(CommandError::from)
+    })
+    .await?
+}
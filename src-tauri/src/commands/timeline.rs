@@ -0,0 +1,25 @@
+//! Activity Timeline Commands
+//!
+//! What the user did over a range of days across mail, tasks, notes and
+//! chat, grouped by day; see `services::timeline`.
+
+use anyhow::Context;
+use std::sync::Arc;
+use tauri::State;
+
+use crate::database::DatabaseManager;
+use crate::services::timeline::{self, ActivityTimeline, ActivityType, TimelineRange};
+use crate::errors::CommandResult;
+
+/// Activity in a range of local days, oldest first, limited to some types
+/// of activity or with none given all of them
+#[tauri::command]
+pub async fn get_activity_timeline(
+    range: TimelineRange,
+    types: Option<Vec<ActivityType>>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<ActivityTimeline> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(timeline::timeline(&conn, range, &types.unwrap_or_default())?)
+}
@@ -0,0 +1,193 @@
+//! Transcription Commands
+//!
+//! Transcribe voice notes and audio attachments and search the transcripts.
+//! A transcript is stored once per attachment, or per version of a file on
+//! disk; asking again returns the stored one unless `force` is set. Files
+//! on disk must have been picked in a file dialog or lie in the app's data
+//! directory.
+
+use anyhow::Context;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use tauri::{AppHandle, State};
+use tokio::io::AsyncReadExt;
+
+use crate::database::{
+    operations::transcript_operations::{self, AttachmentTranscript, NewTranscript, TranscriptSearchResult},
+    DatabaseManager,
+};
+use crate::services::file_access;
+use crate::services::gmail::api_service::GmailApiService;
+use crate::services::transcription_service::{self, TranscriptionSettings, MAX_AUDIO_BYTES};
+use crate::errors::{CommandError, CommandResult, LibreOllamaError};
+
+/// Where the audio to transcribe comes from
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AudioSource {
+    GmailAttachment {
+        account_id: String,
+        message_id: String,
+        attachment_id: String,
+    },
+    /// A recording saved on disk, optionally attached to a note
+    LocalFile {
+        path: String,
+        note_id: Option<i64>,
+    },
+}
+
+fn too_large_to_transcribe(filename: &str) -> CommandError {
+    LibreOllamaError::InvalidInput {
+        message: format!("{} is too large to transcribe", filename),
+        field: Some("source".to_string()),
+    }
+    .into()
+}
+
+#[tauri::command]
+pub async fn transcribe_attachment(
+    source: AudioSource,
+    filename: String,
+    mime_type: String,
+    force: Option<bool>,
+    app: AppHandle,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    api_service: State<'_, Arc<GmailApiService>>,
+) -> CommandResult<AttachmentTranscript> {
+    if !transcription_service::is_audio(&mime_type, &filename) {
+        return Err(LibreOllamaError::InvalidInput {
+            message: format!("{} is not an audio file", filename),
+            field: Some("filename".to_string()),
+        }
+        .into());
+    }
+
+    let (source_key, local_path) = match &source {
+        AudioSource::GmailAttachment { account_id, message_id, attachment_id } => {
+            (transcript_operations::gmail_source_key(account_id, message_id, attachment_id), None)
+        }
+        AudioSource::LocalFile { path, .. } => {
+            let resolved = file_access::permitted_path(&app, path)?;
+            let metadata = tokio::fs::metadata(&resolved)
+                .await
+                .with_context(|| format!("Failed to read {}", path))?;
+            if metadata.len() > MAX_AUDIO_BYTES as u64 {
+                return Err(too_large_to_transcribe(&filename));
+            }
+            let key = transcript_operations::file_version_key(&resolved.to_string_lossy(), metadata.len(), metadata.modified().ok());
+            (key, Some(resolved))
+        }
+    };
+
+    let settings = {
+        let conn = db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        if !force.unwrap_or(false) {
+            if let Some(existing) = transcript_operations::get_transcript(&conn, &source_key)?
+            {
+                return Ok(existing);
+            }
+        }
+        transcription_service::load_settings(&conn)?
+    };
+
+    let audio = match &source {
+        AudioSource::GmailAttachment { account_id, message_id, attachment_id } => api_service
+            .get_attachment(account_id, message_id, attachment_id)
+            .await
+            ?,
+        AudioSource::LocalFile { path, .. } => {
+            let resolved = local_path.as_ref().context("Local file was not resolved")?;
+            let file = tokio::fs::File::open(resolved)
+                .await
+                .with_context(|| format!("Failed to read {}", path))?;
+            // Read one byte past the limit so a file that grew is still caught
+            let mut audio = Vec::new();
+            file.take(MAX_AUDIO_BYTES as u64 + 1)
+                .read_to_end(&mut audio)
+                .await
+                .with_context(|| format!("Failed to read {}", path))?;
+            if audio.len() > MAX_AUDIO_BYTES {
+                return Err(too_large_to_transcribe(&filename));
+            }
+            audio
+        }
+    };
+
+    let text = transcription_service::transcribe(&settings, &filename, &mime_type, audio)
+        .await?;
+
+    let engine = settings.engine_name();
+    let new_transcript = match &source {
+        AudioSource::GmailAttachment { account_id, message_id, attachment_id } => NewTranscript {
+            source_key: &source_key,
+            account_id: Some(account_id),
+            message_id: Some(message_id),
+            attachment_id: Some(attachment_id),
+            note_id: None,
+            filename: &filename,
+            mime_type: &mime_type,
+            transcript: &text,
+            engine: &engine,
+        },
+        AudioSource::LocalFile { note_id, .. } => NewTranscript {
+            source_key: &source_key,
+            account_id: None,
+            message_id: None,
+            attachment_id: None,
+            note_id: *note_id,
+            filename: &filename,
+            mime_type: &mime_type,
+            transcript: &text,
+            engine: &engine,
+        },
+    };
+
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(transcript_operations::save_transcript(&conn, &new_transcript)?)
+}
+
+/// Full-text search over stored transcripts
+#[tauri::command]
+pub async fn search_transcripts(
+    query: String,
+    account_id: Option<String>,
+    limit: Option<usize>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<TranscriptSearchResult>> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(transcript_operations::search_transcripts(&conn, &query, account_id.as_deref(), limit.unwrap_or(20).min(100))?)
+}
+
+#[tauri::command]
+pub async fn get_note_transcripts(
+    note_id: i64,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Vec<AttachmentTranscript>> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(transcript_operations::list_for_note(&conn, note_id)?)
+}
+
+#[tauri::command]
+pub async fn get_transcription_settings(
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<TranscriptionSettings> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(transcription_service::load_settings(&conn)?)
+}
+
+#[tauri::command]
+pub async fn set_transcription_settings(
+    settings: TranscriptionSettings,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<()> {
+    let conn = db_manager.get_connection()
+        .context("Failed to get database connection")?;
+    Ok(transcription_service::save_settings(&conn, &settings)?)
+}
@@ -0,0 +1,46 @@
+//! Update Commands
+//!
+//! Check for, download and install app updates from the channel chosen in
+//! settings. See `services::updater`.
+
+use tauri::State;
+
+use crate::services::events::UpdateStatusChangedEvent;
+use crate::services::updater::UpdateService;
+use crate::errors::CommandResult;
+
+/// Check the chosen channel now, ignoring the staged rollout
+#[tauri::command]
+pub async fn check_for_updates(updater: State<'_, UpdateService>) -> CommandResult<UpdateStatusChangedEvent> {
+    updater.check(true).await?;
+    Ok(updater.status())
+}
+
+#[tauri::command]
+pub async fn get_update_status(updater: State<'_, UpdateService>) -> CommandResult<UpdateStatusChangedEvent> {
+    Ok(updater.status())
+}
+
+/// Download the available update without installing it
+#[tauri::command]
+pub async fn download_update(updater: State<'_, UpdateService>) -> CommandResult<UpdateStatusChangedEvent> {
+    updater.download().await?;
+    Ok(updater.status())
+}
+
+/// Install the available update, downloading it first if needed, and
+/// relaunch the app
+#[tauri::command]
+pub async fn install_update_and_restart(updater: State<'_, UpdateService>) -> CommandResult<()> {
+    Ok(updater.install_and_restart().await?)
+}
+
+/// Release notes of `version`, by default the available update or else the
+/// running version
+#[tauri::command]
+pub async fn get_release_notes(
+    version: Option<String>,
+    updater: State<'_, UpdateService>,
+) -> CommandResult<String> {
+    Ok(updater.release_notes(version).await?)
+}
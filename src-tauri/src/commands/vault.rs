@@ -0,0 +1,89 @@
+//! Secure Notes Commands
+//!
+//! The passphrase-protected vault for 2FA backup codes and other sensitive
+//! snippets. Every note command needs the vault unlocked; see
+//! `services::vault`.
+
+use anyhow::Context;
+use tauri::State;
+
+use crate::services::vault::{self, SecureNote, SecureNoteSummary, VaultService, VaultStatus};
+use crate::errors::CommandResult;
+
+#[tauri::command]
+pub async fn get_vault_status(vault_service: State<'_, VaultService>) -> CommandResult<VaultStatus> {
+    Ok(vault_service.status().context("Failed to read vault status")?)
+}
+
+/// Choose the passphrase of a new vault; it is left unlocked
+#[tauri::command]
+pub async fn setup_vault(passphrase: String, vault_service: State<'_, VaultService>) -> CommandResult<VaultStatus> {
+    Ok(vault_service.setup(&passphrase).context("Failed to set up the vault")?)
+}
+
+#[tauri::command]
+pub async fn unlock_vault(passphrase: String, vault_service: State<'_, VaultService>) -> CommandResult<VaultStatus> {
+    Ok(vault_service.unlock(&passphrase).context("Failed to unlock the vault")?)
+}
+
+/// Lock the vault now; false if it was already locked
+#[tauri::command]
+pub async fn lock_vault(vault_service: State<'_, VaultService>) -> CommandResult<bool> {
+    Ok(vault_service.lock("manual"))
+}
+
+#[tauri::command]
+pub async fn change_vault_passphrase(
+    current_passphrase: String,
+    new_passphrase: String,
+    vault_service: State<'_, VaultService>,
+) -> CommandResult<()> {
+    Ok(vault_service
+        .change_passphrase(&current_passphrase, &new_passphrase)
+        .context("Failed to change the vault passphrase")?)
+}
+
+#[tauri::command]
+pub async fn create_secure_note(
+    title: String,
+    content: String,
+    vault_service: State<'_, VaultService>,
+) -> CommandResult<SecureNote> {
+    Ok(vault_service
+        .with_key(|conn, key| vault::create_note(conn, key, title.trim(), &content))
+        .context("Failed to create secure note")?)
+}
+
+/// Titles of every secure note, most recently edited first
+#[tauri::command]
+pub async fn get_secure_notes(vault_service: State<'_, VaultService>) -> CommandResult<Vec<SecureNoteSummary>> {
+    Ok(vault_service
+        .with_key(vault::list_notes)
+        .context("Failed to list secure notes")?)
+}
+
+#[tauri::command]
+pub async fn get_secure_note(note_id: i64, vault_service: State<'_, VaultService>) -> CommandResult<SecureNote> {
+    Ok(vault_service
+        .with_key(|conn, key| vault::get_note(conn, key, note_id))
+        .context("Failed to open secure note")?)
+}
+
+#[tauri::command]
+pub async fn update_secure_note(
+    note_id: i64,
+    title: String,
+    content: String,
+    vault_service: State<'_, VaultService>,
+) -> CommandResult<SecureNote> {
+    Ok(vault_service
+        .with_key(|conn, key| vault::update_note(conn, key, note_id, title.trim(), &content))
+        .context("Failed to update secure note")?)
+}
+
+#[tauri::command]
+pub async fn delete_secure_note(note_id: i64, vault_service: State<'_, VaultService>) -> CommandResult<bool> {
+    Ok(vault_service
+        .with_key(|conn, _| Ok(crate::database::operations::vault_operations::delete_secure_note(conn, note_id)?))
+        .context("Failed to delete secure note")?)
+}
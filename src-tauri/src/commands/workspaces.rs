@@ -0,0 +1,144 @@
+//! Workspace Commands
+//!
+//! Creating workspaces, choosing what belongs to each, and switching the
+//! active one. Listings, search, cache keys and the daily digest follow the
+//! active workspace; see `services::workspaces`.
+
+use anyhow::Context;
+use std::sync::Arc;
+use tauri::State;
+
+use crate::database::operations::workspace_operations::{self, Workspace, WorkspaceMember};
+use crate::database::DatabaseManager;
+use crate::services::events::{BackendEvent, EventBus, WorkspaceChangedEvent};
+use crate::services::workspaces;
+use crate::errors::{CommandResult, LibreOllamaError};
+
+fn workspace_name(name: &str) -> CommandResult<&str> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Workspace name must not be empty".into());
+    }
+    Ok(name)
+}
+
+fn announce(event_bus: &EventBus, workspace: Option<&Workspace>) {
+    event_bus.emit(BackendEvent::WorkspaceChanged(WorkspaceChangedEvent {
+        workspace_id: workspace.map(|workspace| workspace.id),
+        name: workspace.map(|workspace| workspace.name.clone()),
+    }));
+}
+
+/// Every workspace with its members
+#[tauri::command]
+pub async fn get_workspaces(db_manager: State<'_, Arc<DatabaseManager>>) -> CommandResult<Vec<Workspace>> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(workspace_operations::list_workspaces(&conn).context("Failed to list workspaces")?)
+}
+
+/// The active workspace, or None when everything is shown
+#[tauri::command]
+pub async fn get_active_workspace(db_manager: State<'_, Arc<DatabaseManager>>) -> CommandResult<Option<Workspace>> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    Ok(workspaces::active_workspace(&conn).context("Failed to load workspace")?)
+}
+
+#[tauri::command]
+pub async fn create_workspace(
+    name: String,
+    color: Option<String>,
+    members: Option<Vec<WorkspaceMember>>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Workspace> {
+    let name = workspace_name(&name)?;
+    let mut conn = db_manager.get_connection().context("Failed to get database connection")?;
+    let workspace = workspace_operations::create_workspace(&conn, name, color.as_deref())
+        .context("Failed to create workspace")?;
+    let Some(members) = members else {
+        return Ok(workspace);
+    };
+    workspace_operations::set_members(&mut conn, workspace.id, &members)
+        .context("Failed to add workspace members")?;
+    Ok(workspace_operations::get_workspace(&conn, workspace.id)
+        .context("Failed to load workspace")?
+        .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("workspace {}", workspace.id) })?)
+}
+
+/// Rename or recolour a workspace; an empty color clears it
+#[tauri::command]
+pub async fn update_workspace(
+    workspace_id: i64,
+    name: Option<String>,
+    color: Option<String>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+) -> CommandResult<Workspace> {
+    let name = name.as_deref().map(workspace_name).transpose()?;
+    let color = color.as_deref().map(|color| Some(color.trim()).filter(|color| !color.is_empty()));
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    workspace_operations::update_workspace(&conn, workspace_id, name, color)
+        .context("Failed to update workspace")?;
+    Ok(workspace_operations::get_workspace(&conn, workspace_id)
+        .context("Failed to load workspace")?
+        .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("workspace {}", workspace_id) })?)
+}
+
+/// Replace the accounts, task lists, calendars and folders of a workspace
+#[tauri::command]
+pub async fn set_workspace_members(
+    workspace_id: i64,
+    members: Vec<WorkspaceMember>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<Workspace> {
+    let mut conn = db_manager.get_connection().context("Failed to get database connection")?;
+    workspace_operations::set_members(&mut conn, workspace_id, &members)
+        .context("Failed to set workspace members")?;
+    let workspace = workspace_operations::get_workspace(&conn, workspace_id)
+        .context("Failed to load workspace")?
+        .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("workspace {}", workspace_id) })?;
+
+    // Any membership change can hide or reveal items in the active workspace
+    let active = workspaces::active_workspace(&conn).context("Failed to load workspace")?;
+    if active.is_some() {
+        announce(&event_bus, active.as_ref());
+    }
+    Ok(workspace)
+}
+
+/// Delete a workspace; its members become shared again. Deleting the
+/// active workspace switches to showing everything.
+#[tauri::command]
+pub async fn delete_workspace(
+    workspace_id: i64,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<bool> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    let was_active = workspaces::active_workspace(&conn)
+        .context("Failed to load workspace")?
+        .is_some_and(|active| active.id == workspace_id);
+    let deleted = workspace_operations::delete_workspace(&conn, workspace_id)
+        .context("Failed to delete workspace")?;
+    if was_active {
+        workspaces::switch(&conn, None).context("Failed to switch workspace")?;
+    }
+    if deleted {
+        // Its members are no longer hidden from the active workspace either
+        let active = workspaces::active_workspace(&conn).context("Failed to load workspace")?;
+        announce(&event_bus, active.as_ref());
+    }
+    Ok(deleted)
+}
+
+/// Make a workspace active, or show everything with no `workspace_id`
+#[tauri::command]
+pub async fn switch_workspace(
+    workspace_id: Option<i64>,
+    db_manager: State<'_, Arc<DatabaseManager>>,
+    event_bus: State<'_, EventBus>,
+) -> CommandResult<Option<Workspace>> {
+    let conn = db_manager.get_connection().context("Failed to get database connection")?;
+    let workspace = workspaces::switch(&conn, workspace_id).context("Failed to switch workspace")?;
+    announce(&event_bus, workspace.as_ref());
+    Ok(workspace)
+}
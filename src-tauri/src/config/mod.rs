@@ -2,26 +2,40 @@
 //!
 //! Centralizes all configuration handling including OAuth settings,
 //! database configuration, and environment variable management.
+//!
+//! Configuration is layered: built-in defaults, then the optional
+//! `config.json` in the data directory, then environment variables (with
+//! `.env` loaded into the environment). `ConfigManager` holds the current
+//! snapshot and can reload it while the app runs; subscribers are told
+//! about every successful reload.
 
 use crate::errors::{LibreOllamaError, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, SystemTime};
+use tokio::sync::watch;
 
 /// Main application configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[derive(Default)]
+#[serde(default)]
 pub struct AppConfig {
     pub oauth: OAuthConfig,
     pub database: DatabaseConfig,
     pub gmail: GmailConfig,
     pub sync: SyncConfig,
+    pub network: NetworkConfig,
     pub security: SecurityConfig,
+    pub memory: MemoryConfig,
     pub paths: PathConfig,
 }
 
 /// OAuth configuration settings
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct OAuthConfig {
     pub client_id: String,
     pub client_secret: String,
@@ -32,7 +46,8 @@ pub struct OAuthConfig {
 }
 
 /// Database configuration settings
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct DatabaseConfig {
     pub db_path: PathBuf,
     pub encryption_key: String,
@@ -43,7 +58,8 @@ pub struct DatabaseConfig {
 }
 
 /// Gmail-specific configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct GmailConfig {
     pub max_messages_per_sync: u32,
     pub sync_interval_minutes: u64,
@@ -54,7 +70,8 @@ pub struct GmailConfig {
 }
 
 /// Sync configuration settings
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SyncConfig {
     pub max_retry_attempts: u32,
     pub retry_delay_ms: u64,
@@ -64,8 +81,21 @@ pub struct SyncConfig {
     pub incremental_sync_interval_minutes: u64,
 }
 
+/// HTTP client configuration shared by all outgoing requests
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    pub connect_timeout_ms: u64,
+    pub request_timeout_ms: u64,
+    pub pool_idle_timeout_secs: u64,
+    pub pool_max_idle_per_host: usize,
+    pub user_agent: Option<String>,
+    pub enable_request_tracing: bool,
+}
+
 /// Security configuration settings
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SecurityConfig {
     pub token_encryption_algorithm: String,
     pub key_derivation_rounds: u32,
@@ -74,8 +104,18 @@ pub struct SecurityConfig {
     pub log_sensitive_data: bool,
 }
 
+/// Limits on memory held by in-memory caches
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[derive(Default)]
+pub struct MemoryConfig {
+    /// Total for all in-memory caches; 0 sizes it from system memory
+    pub cache_budget_mb: u64,
+}
+
 /// Path configuration for various data directories
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PathConfig {
     pub data_dir: PathBuf,
     pub cache_dir: PathBuf,
@@ -148,6 +188,19 @@ impl Default for SyncConfig {
     }
 }
 
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_ms: 10_000,
+            request_timeout_ms: 60_000,
+            pool_idle_timeout_secs: 90,
+            pool_max_idle_per_host: 8,
+            user_agent: None,
+            enable_request_tracing: false,
+        }
+    }
+}
+
 impl Default for SecurityConfig {
     fn default() -> Self {
         Self {
@@ -188,50 +241,80 @@ impl EnvConfig {
     /// Load configuration from environment variables
     pub fn load() -> Result<AppConfig> {
         let mut config = AppConfig::default();
+        Self::apply(&mut config);
+        Self::validate_config(&config)?;
+        Ok(config)
+    }
+
+    /// Override `config` with whatever the environment sets
+    pub fn apply(config: &mut AppConfig) {
+        Self::apply_with(config, |key| env::var(key).ok());
+    }
 
+    /// Override `config` with the variables `lookup` finds
+    pub fn apply_with(config: &mut AppConfig, lookup: impl Fn(&str) -> Option<String>) {
         // OAuth configuration from environment
-        if let Ok(client_id) = env::var("GMAIL_CLIENT_ID") {
+        if let Some(client_id) = lookup("GMAIL_CLIENT_ID") {
             config.oauth.client_id = client_id;
         }
-        if let Ok(client_secret) = env::var("GMAIL_CLIENT_SECRET") {
+        if let Some(client_secret) = lookup("GMAIL_CLIENT_SECRET") {
             config.oauth.client_secret = client_secret;
         }
-        if let Ok(redirect_uri) = env::var("OAUTH_REDIRECT_URI") {
+        if let Some(redirect_uri) = lookup("OAUTH_REDIRECT_URI") {
             config.oauth.redirect_uri = redirect_uri;
         }
-        if let Ok(port) = env::var("OAUTH_CALLBACK_PORT") {
+        if let Some(port) = lookup("OAUTH_CALLBACK_PORT") {
             config.oauth.callback_port = port.parse().unwrap_or(1423);
         }
 
         // Database configuration from environment
-        if let Ok(db_path) = env::var("DATABASE_PATH") {
+        if let Some(db_path) = lookup("DATABASE_PATH") {
             config.database.db_path = PathBuf::from(db_path);
         }
-        if let Ok(encryption_key) = env::var("DATABASE_ENCRYPTION_KEY") {
+        if let Some(encryption_key) = lookup("DATABASE_ENCRYPTION_KEY") {
             config.database.encryption_key = encryption_key;
         }
 
         // Gmail configuration from environment
-        if let Ok(max_messages) = env::var("GMAIL_MAX_MESSAGES_PER_SYNC") {
+        if let Some(max_messages) = lookup("GMAIL_MAX_MESSAGES_PER_SYNC") {
             config.gmail.max_messages_per_sync = max_messages.parse().unwrap_or(100);
         }
-        if let Ok(sync_interval) = env::var("GMAIL_SYNC_INTERVAL_MINUTES") {
+        if let Some(sync_interval) = lookup("GMAIL_SYNC_INTERVAL_MINUTES") {
             config.gmail.sync_interval_minutes = sync_interval.parse().unwrap_or(5);
         }
-        if let Ok(rate_limit) = env::var("GMAIL_RATE_LIMIT_RPM") {
+        if let Some(rate_limit) = lookup("GMAIL_RATE_LIMIT_RPM") {
             config.gmail.rate_limit_requests_per_minute = rate_limit.parse().unwrap_or(200);
         }
 
+        // Network configuration from environment
+        if let Some(timeout) = lookup("HTTP_CONNECT_TIMEOUT_MS") {
+            config.network.connect_timeout_ms = timeout.parse().unwrap_or(10_000);
+        }
+        if let Some(timeout) = lookup("HTTP_REQUEST_TIMEOUT_MS") {
+            config.network.request_timeout_ms = timeout.parse().unwrap_or(60_000);
+        }
+        if let Some(user_agent) = lookup("HTTP_USER_AGENT") {
+            config.network.user_agent = Some(user_agent);
+        }
+        if let Some(tracing) = lookup("HTTP_REQUEST_TRACING") {
+            config.network.enable_request_tracing = tracing.parse().unwrap_or(false);
+        }
+
         // Security configuration from environment
-        if let Ok(secure_storage) = env::var("ENABLE_SECURE_STORAGE") {
+        if let Some(secure_storage) = lookup("ENABLE_SECURE_STORAGE") {
             config.security.enable_secure_storage = secure_storage.parse().unwrap_or(true);
         }
-        if let Ok(log_sensitive) = env::var("LOG_SENSITIVE_DATA") {
+        if let Some(log_sensitive) = lookup("LOG_SENSITIVE_DATA") {
             config.security.log_sensitive_data = log_sensitive.parse().unwrap_or(false);
         }
 
+        // Memory configuration from environment
+        if let Some(budget) = lookup("CACHE_BUDGET_MB") {
+            config.memory.cache_budget_mb = budget.parse().unwrap_or(0);
+        }
+
         // Path configuration from environment
-        if let Ok(data_dir) = env::var("DATA_DIRECTORY") {
+        if let Some(data_dir) = lookup("DATA_DIRECTORY") {
             let data_path = PathBuf::from(data_dir);
             config.paths = PathConfig {
                 data_dir: data_path.clone(),
@@ -241,9 +324,6 @@ impl EnvConfig {
                 attachments_dir: data_path.join("attachments"),
             };
         }
-
-        Self::validate_config(&config)?;
-        Ok(config)
     }
 
     /// Get a specific environment variable with fallback
@@ -278,7 +358,7 @@ impl EnvConfig {
     }
 
     /// Validate configuration values
-    fn validate_config(config: &AppConfig) -> Result<()> {
+    pub fn validate_config(config: &AppConfig) -> Result<()> {
         // Check if we're in development/test mode
         let is_dev_mode = cfg!(debug_assertions) || env::var("NODE_ENV").unwrap_or_default() == "development";
         
@@ -333,10 +413,61 @@ impl EnvConfig {
     }
 }
 
+/// Where configuration is read from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigSources {
+    /// `.env` file found at startup, if any
+    pub env_file: Option<PathBuf>,
+    /// Optional JSON overrides; any subset of `AppConfig` may be given
+    pub config_file: PathBuf,
+}
+
+impl Default for ConfigSources {
+    fn default() -> Self {
+        Self {
+            env_file: None,
+            config_file: get_default_data_dir().join("config.json"),
+        }
+    }
+}
+
+/// Outcome of the latest load, for the settings screen
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigStatus {
+    pub sources: ConfigSources,
+    pub loaded_at: String,
+    /// Bumped on every reload that changed something
+    pub generation: u64,
+    /// Why the latest load or reload was rejected
+    pub last_error: Option<String>,
+}
+
+/// Result of a reload: which top-level sections changed
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigReload {
+    pub changed_sections: Vec<String>,
+    pub status: ConfigStatus,
+}
+
 /// Configuration manager for runtime config access
-#[derive(Default)]
+///
+/// Readers get a snapshot (`config()` or one of the section accessors);
+/// `reload` swaps in a new snapshot only when it loads and validates, so a
+/// broken edit keeps the last good configuration instead of defaults.
 pub struct ConfigManager {
-    config: AppConfig,
+    current: watch::Sender<Arc<AppConfig>>,
+    sources: ConfigSources,
+    status: RwLock<ConfigStatus>,
+    /// `.env` values exported into the environment at startup
+    startup_env_file: HashMap<String, String>,
+    /// Values last read from the `.env` file
+    env_file_values: Mutex<HashMap<String, String>>,
+}
+
+impl Default for ConfigManager {
+    fn default() -> Self {
+        Self::with_config(AppConfig::default())
+    }
 }
 
 #[allow(dead_code)]
@@ -344,57 +475,240 @@ impl ConfigManager {
     /// Create new configuration manager with environment-loaded config
     pub fn new() -> Result<Self> {
         let config = EnvConfig::load()?;
-        Ok(Self { config })
+        Ok(Self::with_config(config))
     }
 
     /// Create configuration manager with custom config
     pub fn with_config(config: AppConfig) -> Self {
-        Self { config }
+        Self::build(config, ConfigSources::default(), None)
+    }
+
+    /// Load from `sources` for the running app. A configuration file that
+    /// cannot be read falls back to the last one that loaded, and a
+    /// configuration that fails validation is still used; either way the
+    /// problem is kept in `status()`.
+    pub fn load(sources: ConfigSources) -> Self {
+        let env_file_values = sources.env_file.as_deref().map(read_env_file).unwrap_or_default();
+        // Like dotenv, the file never overrides the environment
+        let lookup = |key: &str| env::var(key).ok().or_else(|| env_file_values.get(key).cloned());
+        let (config, error) = match Self::read(&sources, lookup) {
+            Ok(config) => {
+                Self::keep_last_good(&sources);
+                let error = EnvConfig::validate_config(&config).err().map(|e| e.to_string());
+                (config, error)
+            }
+            Err(e) => match Self::read_last_good(&sources, lookup) {
+                Some(config) => (config, Some(format!("{}; using the last configuration that loaded", e))),
+                None => (AppConfig::default(), Some(format!("{}; using the defaults", e))),
+            },
+        };
+        if let Some(error) = &error {
+            eprintln!("⚠️  [CONFIG] {}", error);
+        }
+
+        let mut manager = Self::build(config, sources, error);
+        manager.startup_env_file = env_file_values.clone();
+        *manager.env_file_values.get_mut().unwrap_or_else(|e| e.into_inner()) = env_file_values;
+        manager
+    }
+
+    fn build(config: AppConfig, sources: ConfigSources, last_error: Option<String>) -> Self {
+        let (current, _) = watch::channel(Arc::new(config));
+        Self {
+            current,
+            status: RwLock::new(ConfigStatus {
+                sources: sources.clone(),
+                loaded_at: chrono::Utc::now().to_rfc3339(),
+                generation: 0,
+                last_error,
+            }),
+            sources,
+            startup_env_file: HashMap::new(),
+            env_file_values: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Defaults, then the config file, then the variables `lookup` finds
+    fn read(sources: &ConfigSources, lookup: impl Fn(&str) -> Option<String>) -> Result<AppConfig> {
+        let mut config = match std::fs::read_to_string(&sources.config_file) {
+            Ok(text) => parse_config(&text, &sources.config_file)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => AppConfig::default(),
+            Err(e) => {
+                return Err(LibreOllamaError::FileSystem {
+                    message: format!("Failed to read config file: {}", e),
+                    path: Some(sources.config_file.display().to_string()),
+                })
+            }
+        };
+        EnvConfig::apply_with(&mut config, lookup);
+        Ok(config)
+    }
+
+    /// Copy a config file that loaded, so a later broken edit has something
+    /// to fall back to at startup. Only the file is kept, never the
+    /// environment's secrets.
+    fn keep_last_good(sources: &ConfigSources) {
+        if sources.config_file.exists() {
+            if let Err(e) = std::fs::copy(&sources.config_file, last_good_file(sources)) {
+                eprintln!("⚠️  [CONFIG] Failed to keep a copy of the configuration: {}", e);
+            }
+        }
+    }
+
+    fn read_last_good(sources: &ConfigSources, lookup: impl Fn(&str) -> Option<String>) -> Option<AppConfig> {
+        let path = last_good_file(sources);
+        let mut config = parse_config(&std::fs::read_to_string(&path).ok()?, &path).ok()?;
+        EnvConfig::apply_with(&mut config, lookup);
+        Some(config)
+    }
+
+    /// A variable as the `.env` file has it now. Values the file exported at
+    /// startup follow later edits to the file; anything set outside the
+    /// file wins. The process environment itself is never changed.
+    fn lookup(&self, key: &str) -> Option<String> {
+        let file = self.env_file_values.lock().unwrap_or_else(|e| e.into_inner());
+        match env::var(key) {
+            Ok(value) if self.startup_env_file.get(key) != Some(&value) => Some(value),
+            _ => file.get(key).cloned(),
+        }
+    }
+
+    /// Re-read the `.env` and config files. On failure the current
+    /// configuration stays in place and the error is returned.
+    pub fn reload(&self) -> Result<ConfigReload> {
+        if let Some(env_file) = &self.sources.env_file {
+            *self.env_file_values.lock().unwrap_or_else(|e| e.into_inner()) = read_env_file(env_file);
+        }
+
+        let result = Self::read(&self.sources, |key| self.lookup(key)).and_then(|config| {
+            EnvConfig::validate_config(&config)?;
+            Ok(config)
+        });
+        let config = match result {
+            Ok(config) => config,
+            Err(e) => {
+                self.status.write().unwrap_or_else(|e| e.into_inner()).last_error = Some(e.to_string());
+                return Err(e);
+            }
+        };
+        Self::keep_last_good(&self.sources);
+
+        let previous = self.config();
+        let changed_sections = changed_sections(&previous, &config);
+        let status = {
+            let mut status = self.status.write().unwrap_or_else(|e| e.into_inner());
+            status.loaded_at = chrono::Utc::now().to_rfc3339();
+            status.last_error = None;
+            if !changed_sections.is_empty() {
+                status.generation += 1;
+            }
+            status.clone()
+        };
+        if !changed_sections.is_empty() {
+            println!("🔧 [CONFIG] Reloaded, changed: {}", changed_sections.join(", "));
+            self.current.send_replace(Arc::new(config));
+        }
+
+        Ok(ConfigReload { changed_sections, status })
+    }
+
+    /// Watch the sources and reload when one of them is modified
+    pub fn start_watching(self: &Arc<Self>) {
+        let manager = Arc::clone(self);
+        tauri::async_runtime::spawn(async move {
+            let mut last_seen = manager.source_mtimes();
+            loop {
+                tokio::time::sleep(WATCH_INTERVAL).await;
+                let mtimes = manager.source_mtimes();
+                if mtimes == last_seen {
+                    continue;
+                }
+                last_seen = mtimes;
+                if let Err(e) = manager.reload() {
+                    eprintln!("⚠️  [CONFIG] Keeping previous configuration: {}", e);
+                }
+            }
+        });
+    }
+
+    fn source_mtimes(&self) -> Vec<Option<SystemTime>> {
+        let mtime = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        vec![
+            self.sources.env_file.as_deref().and_then(mtime),
+            mtime(&self.sources.config_file),
+        ]
+    }
+
+    /// Receive each new configuration after a successful reload
+    pub fn subscribe(&self) -> watch::Receiver<Arc<AppConfig>> {
+        self.current.subscribe()
+    }
+
+    pub fn status(&self) -> ConfigStatus {
+        self.status.read().unwrap_or_else(|e| e.into_inner()).clone()
     }
 
-    /// Get immutable reference to configuration
-    pub fn config(&self) -> &AppConfig {
-        &self.config
+    /// Snapshot of the current configuration
+    pub fn config(&self) -> Arc<AppConfig> {
+        self.current.borrow().clone()
     }
 
     /// Get OAuth configuration
-    pub fn oauth(&self) -> &OAuthConfig {
-        &self.config.oauth
+    pub fn oauth(&self) -> OAuthConfig {
+        self.config().oauth.clone()
     }
 
     /// Get database configuration
-    pub fn database(&self) -> &DatabaseConfig {
-        &self.config.database
+    pub fn database(&self) -> DatabaseConfig {
+        self.config().database.clone()
     }
 
     /// Get Gmail configuration
-    pub fn gmail(&self) -> &GmailConfig {
-        &self.config.gmail
+    pub fn gmail(&self) -> GmailConfig {
+        self.config().gmail.clone()
     }
 
     /// Get sync configuration
-    pub fn sync(&self) -> &SyncConfig {
-        &self.config.sync
+    pub fn sync(&self) -> SyncConfig {
+        self.config().sync.clone()
+    }
+
+    /// Get network configuration
+    pub fn network(&self) -> NetworkConfig {
+        self.config().network.clone()
     }
 
     /// Get security configuration
-    pub fn security(&self) -> &SecurityConfig {
-        &self.config.security
+    pub fn security(&self) -> SecurityConfig {
+        self.config().security.clone()
+    }
+
+    /// Get memory configuration
+    pub fn memory(&self) -> MemoryConfig {
+        self.config().memory.clone()
     }
 
     /// Get path configuration
-    pub fn paths(&self) -> &PathConfig {
-        &self.config.paths
+    pub fn paths(&self) -> PathConfig {
+        self.config().paths.clone()
+    }
+
+    /// Files the configuration was read from, with the last good copy
+    pub fn files(&self) -> Vec<PathBuf> {
+        let mut files = vec![self.sources.config_file.clone(), last_good_file(&self.sources)];
+        files.extend(self.sources.env_file.clone());
+        files
     }
 
     /// Ensure all configured directories exist
     pub fn ensure_directories(&self) -> Result<()> {
+        let paths = self.paths();
         let dirs_to_create = [
-            &self.config.paths.data_dir,
-            &self.config.paths.cache_dir,
-            &self.config.paths.logs_dir,
-            &self.config.paths.temp_dir,
-            &self.config.paths.attachments_dir,
+            &paths.data_dir,
+            &paths.cache_dir,
+            &paths.logs_dir,
+            &paths.temp_dir,
+            &paths.attachments_dir,
         ];
 
         for dir in &dirs_to_create {
@@ -411,32 +725,79 @@ impl ConfigManager {
 
     /// Update configuration at runtime (for testing)
     #[cfg(test)]
-    pub fn update_config(&mut self, config: AppConfig) {
-        self.config = config;
+    pub fn update_config(&self, config: AppConfig) {
+        self.current.send_replace(Arc::new(config));
     }
 }
 
-/// Global configuration instance (lazy initialization)
-use std::sync::OnceLock;
-static CONFIG_MANAGER: OnceLock<ConfigManager> = OnceLock::new();
+/// How often the watcher checks the sources for modifications
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+fn parse_config(text: &str, path: &Path) -> Result<AppConfig> {
+    serde_json::from_str(text).map_err(|e| LibreOllamaError::Configuration {
+        message: format!("Invalid config file {}: {}", path.display(), e),
+        config_key: None,
+    })
+}
+
+/// Copy of the config file as it last loaded
+fn last_good_file(sources: &ConfigSources) -> PathBuf {
+    sources.config_file.with_extension("last-good.json")
+}
+
+// dotenv has no other way to read a file without exporting it
+#[allow(deprecated)]
+fn read_env_file(path: &Path) -> HashMap<String, String> {
+    match dotenv::from_path_iter(path) {
+        Ok(entries) => entries.filter_map(|entry| entry.ok()).collect(),
+        Err(e) => {
+            eprintln!("⚠️  [CONFIG] Failed to read {}: {}", path.display(), e);
+            HashMap::new()
+        }
+    }
+}
+
+fn changed_sections(old: &AppConfig, new: &AppConfig) -> Vec<String> {
+    let sections = [
+        ("oauth", old.oauth != new.oauth),
+        ("database", old.database != new.database),
+        ("gmail", old.gmail != new.gmail),
+        ("sync", old.sync != new.sync),
+        ("network", old.network != new.network),
+        ("security", old.security != new.security),
+        ("paths", old.paths != new.paths),
+    ];
+    sections
+        .iter()
+        .filter(|(_, changed)| *changed)
+        .map(|(name, _)| name.to_string())
+        .collect()
+}
+
+/// The manager the app runs with. This is the only instance: services and
+/// commands all read it through `get_config_manager`.
+static CONFIG_MANAGER: OnceLock<Arc<ConfigManager>> = OnceLock::new();
+
+/// Load the app's configuration once at startup
+pub fn init_config_manager(sources: ConfigSources) -> Arc<ConfigManager> {
+    CONFIG_MANAGER
+        .get_or_init(|| Arc::new(ConfigManager::load(sources)))
+        .clone()
+}
 
 /// Get global configuration manager instance
-pub fn get_config_manager() -> Result<&'static ConfigManager> {
-    Ok(CONFIG_MANAGER.get_or_init(|| {
-        ConfigManager::new().unwrap_or_else(|e| {
-            eprintln!("Failed to initialize global config: {}", e);
-            ConfigManager::default()
-        })
-    }))
+pub fn get_config_manager() -> Result<Arc<ConfigManager>> {
+    Ok(init_config_manager(ConfigSources::default()))
 }
 
 /// Initialize configuration manager with custom config (for testing)
 #[cfg(test)]
-pub fn init_config_manager_with_config(config: AppConfig) -> &'static ConfigManager {
-    CONFIG_MANAGER.get_or_init(|| ConfigManager::with_config(config))
+pub fn init_config_manager_with_config(config: AppConfig) -> Arc<ConfigManager> {
+    CONFIG_MANAGER
+        .get_or_init(|| Arc::new(ConfigManager::with_config(config)))
+        .clone()
 }
 
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -525,6 +886,60 @@ mod tests {
         env::remove_var("DATABASE_ENCRYPTION_KEY");
     }
 
+    #[test]
+    fn test_reload_from_config_file() {
+        let dir = std::env::temp_dir().join(format!("libreollama_config_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let sources = ConfigSources { env_file: None, config_file: dir.join("config.json") };
+
+        // Missing file: defaults
+        let manager = ConfigManager::load(sources.clone());
+        assert_eq!(manager.gmail().sync_interval_minutes, 5);
+        let mut updates = manager.subscribe();
+
+        // A partial file only overrides what it names
+        std::fs::write(&sources.config_file, r#"{"gmail": {"sync_interval_minutes": 9}}"#).unwrap();
+        let reload = manager.reload().unwrap();
+        assert_eq!(reload.changed_sections, vec!["gmail".to_string()]);
+        assert_eq!(reload.status.generation, 1);
+        assert_eq!(manager.gmail().sync_interval_minutes, 9);
+        assert_eq!(manager.gmail().attachment_max_size_mb, 25);
+        assert!(updates.has_changed().unwrap());
+        assert_eq!(updates.borrow_and_update().gmail.sync_interval_minutes, 9);
+
+        // Invalid values are rejected and the last good config stays
+        std::fs::write(&sources.config_file, r#"{"sync": {"max_retry_attempts": 0}}"#).unwrap();
+        assert!(manager.reload().is_err());
+        assert_eq!(manager.gmail().sync_interval_minutes, 9);
+        assert!(manager.status().last_error.is_some());
+        assert!(!updates.has_changed().unwrap());
+
+        // A file that no longer parses falls back to the last one that did
+        std::fs::write(&sources.config_file, r#"{"gmail": {"sync_interval_minutes": "#).unwrap();
+        let restarted = ConfigManager::load(sources.clone());
+        assert_eq!(restarted.gmail().sync_interval_minutes, 9);
+        assert!(restarted.status().last_error.unwrap().contains("last configuration"));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_env_file_edits_do_not_touch_the_environment() {
+        let dir = std::env::temp_dir().join(format!("libreollama_config_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let env_file = dir.join(".env");
+        std::fs::write(&env_file, "HTTP_USER_AGENT=first\n").unwrap();
+        let sources = ConfigSources { env_file: Some(env_file.clone()), config_file: dir.join("config.json") };
+
+        let manager = ConfigManager::load(sources);
+        std::fs::write(&env_file, "HTTP_USER_AGENT=second\n").unwrap();
+        manager.reload().unwrap();
+        assert_eq!(manager.network().user_agent.as_deref(), Some("second"));
+        assert!(env::var("HTTP_USER_AGENT").is_err());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
     #[test]
     fn test_directory_creation() {
         let temp_dir = std::env::temp_dir().join("libreollama_test");
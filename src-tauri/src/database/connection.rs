@@ -5,24 +5,39 @@
 //! Note: Encryption will be added in a future phase.
 
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use anyhow::{Context, Result};
 use dirs::data_dir;
 
 use rusqlite::{Connection, OpenFlags};
 
+use crate::errors::LibreOllamaError;
+
 /// Database manager that handles SQLCipher connections
 #[derive(Debug, Clone)]
 pub struct DatabaseManager {
     db_path: PathBuf,
     _encryption_key: String,
     connection: Arc<Mutex<Option<Connection>>>,
+    /// Set by `close`; no connection is opened after it
+    closed: Arc<AtomicBool>,
 }
 
 impl DatabaseManager {
     /// Create a new database manager instance
     pub async fn new() -> Result<Self> {
-        let db_path = get_database_path()?;
+        Self::open()
+    }
+
+    /// Open the database without running migrations, so startup can
+    /// migrate later off the window-creation path
+    pub fn open() -> Result<Self> {
+        Self::open_at(get_database_path()?)
+    }
+
+    /// Open the database at `db_path` instead of the app data directory
+    pub fn open_at(db_path: PathBuf) -> Result<Self> {
         let encryption_key = get_or_create_encryption_key()?;
         
         // Ensure the database directory exists
@@ -35,16 +50,17 @@ impl DatabaseManager {
             db_path,
             _encryption_key: encryption_key,
             connection: Arc::new(Mutex::new(None)),
+            closed: Arc::new(AtomicBool::new(false)),
         };
 
         // Initialize the connection
-        manager.init_connection().await?;
+        manager.init_connection()?;
         
         Ok(manager)
     }
 
     /// Initialize the database connection
-    async fn init_connection(&self) -> Result<()> {
+    fn init_connection(&self) -> Result<()> {
         let conn = Connection::open_with_flags(
             &self.db_path,
             OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_CREATE,
@@ -90,10 +106,13 @@ impl DatabaseManager {
 
     /// Get a database connection (creates new connection for thread safety)
     pub fn get_connection(&self) -> Result<Connection> {
+        if self.closed.load(Ordering::SeqCst) {
+            return Err(LibreOllamaError::DatabaseConnection { message: "The database has been closed".to_string() }.into());
+        }
         let conn = Connection::open_with_flags(
             &self.db_path,
             OpenFlags::SQLITE_OPEN_READ_WRITE,
-        ).context("Failed to open database connection")?;
+        ).map_err(|e| LibreOllamaError::DatabaseConnection { message: e.to_string() })?;
 
         // Configure settings
         conn.execute("PRAGMA foreign_keys = ON", [])?;
@@ -134,6 +153,39 @@ impl DatabaseManager {
     pub fn get_db_path(&self) -> &PathBuf {
         &self.db_path
     }
+
+    /// Refresh query planner statistics and fold the WAL back into the
+    /// database file
+    pub fn optimize(&self) -> Result<()> {
+        let conn = self.get_connection()?;
+        conn.execute_batch("PRAGMA optimize; PRAGMA wal_checkpoint(TRUNCATE);")
+            .context("Failed to optimize the database")
+    }
+
+    /// Rebuild the database file when at least `min_free_share` of its
+    /// pages are free, returning whether it did. Deleted mail and caches
+    /// leave free pages behind that SQLite only reuses, never returns.
+    pub fn vacuum_if_fragmented(&self, min_free_share: f64) -> Result<bool> {
+        let conn = self.get_connection()?;
+        let page_count: i64 = conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+        let free_pages: i64 = conn.query_row("PRAGMA freelist_count", [], |row| row.get(0))?;
+        if page_count == 0 || (free_pages as f64) < page_count as f64 * min_free_share {
+            return Ok(false);
+        }
+        conn.execute_batch("VACUUM;").context("Failed to vacuum the database")?;
+        Ok(true)
+    }
+
+    /// Close the connection held since startup, checkpointing the WAL.
+    /// Connections already handed out stay usable until dropped, but no
+    /// new ones are opened.
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        let mut connection_guard = self.connection.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(conn) = connection_guard.take() {
+            let _ = conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE)");
+        }
+    }
 }
 
 /// Get the database file path in the app data directory
@@ -166,7 +218,7 @@ pub fn get_connection() -> Result<Connection> {
     let conn = Connection::open_with_flags(
         &db_path,
         OpenFlags::SQLITE_OPEN_READ_WRITE,
-    ).context("Failed to open database connection")?;
+    ).map_err(|e| LibreOllamaError::DatabaseConnection { message: e.to_string() })?;
     
     conn.execute("PRAGMA foreign_keys = ON", [])?;
     
@@ -346,7 +346,7 @@ mod database_health_tests {
         println!("🔧 Testing database health check command...");
         
         // This tests the actual command that will be called from the frontend
-        let health_result = crate::database_health_check().await;
+        let health_result = crate::commands::system::database_health_check().await;
         assert!(health_result.is_ok(), "Health check should succeed: {:?}", health_result.err());
         assert_eq!(health_result.unwrap(), true, "Health check should return true");
         
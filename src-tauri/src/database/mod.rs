@@ -9,6 +9,61 @@ pub mod models;
 pub mod schema;
 pub mod schema_v13;
 pub mod schema_v14;
+pub mod schema_v15;
+pub mod schema_v16;
+pub mod schema_v17;
+pub mod schema_v18;
+pub mod schema_v19;
+pub mod schema_v20;
+pub mod schema_v21;
+pub mod schema_v22;
+pub mod schema_v23;
+pub mod schema_v24;
+pub mod schema_v25;
+pub mod schema_v26;
+pub mod schema_v27;
+pub mod schema_v28;
+pub mod schema_v29;
+pub mod schema_v30;
+pub mod schema_v31;
+pub mod schema_v32;
+pub mod schema_v33;
+pub mod schema_v34;
+pub mod schema_v35;
+pub mod schema_v36;
+pub mod schema_v37;
+pub mod schema_v38;
+pub mod schema_v39;
+pub mod schema_v40;
+pub mod schema_v41;
+pub mod schema_v42;
+pub mod schema_v43;
+pub mod schema_v44;
+pub mod schema_v45;
+pub mod schema_v46;
+pub mod schema_v47;
+pub mod schema_v48;
+pub mod schema_v49;
+pub mod schema_v50;
+pub mod schema_v51;
+pub mod schema_v52;
+pub mod schema_v53;
+pub mod schema_v54;
+pub mod schema_v55;
+pub mod schema_v56;
+pub mod schema_v57;
+pub mod schema_v58;
+pub mod schema_v59;
+pub mod schema_v60;
+pub mod schema_v61;
+pub mod schema_v62;
+pub mod schema_v63;
+pub mod schema_v64;
+pub mod schema_v65;
+pub mod schema_v66;
+pub mod schema_v67;
+pub mod schema_v68;
+pub mod schema_v69;
 pub mod operations;
 pub mod connection;
 #[cfg(test)]
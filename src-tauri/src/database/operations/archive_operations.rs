@@ -0,0 +1,206 @@
+//! Archive accounts for imported mail
+//!
+//! An archive account holds mail imported from a Thunderbird profile or an
+//! Outlook data file. Its messages live in `gmail_message_store` like synced
+//! mail, keyed by the archive's ID, with the original folders as labels.
+
+use std::collections::BTreeSet;
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use super::backfill_operations::{self, StoredMessage};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveAccount {
+    pub id: String,
+    pub name: String,
+    /// "thunderbird" or "outlook"
+    pub source: String,
+    pub source_path: String,
+    pub message_count: i64,
+    pub created_at: String,
+    /// When the last import finished
+    pub imported_at: Option<String>,
+}
+
+fn account_from_row(row: &Row) -> rusqlite::Result<ArchiveAccount> {
+    Ok(ArchiveAccount {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        source: row.get(2)?,
+        source_path: row.get(3)?,
+        message_count: row.get(4)?,
+        created_at: row.get(5)?,
+        imported_at: row.get(6)?,
+    })
+}
+
+const ACCOUNT_COLUMNS: &str = "id, name, source, source_path, message_count, created_at, imported_at";
+
+/// An imported folder and how many messages carry it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveFolder {
+    pub label: String,
+    pub message_count: i64,
+}
+
+/// A message as listed in an archive folder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedMessage {
+    pub message_id: String,
+    pub thread_id: String,
+    pub subject: String,
+    pub from: String,
+    pub snippet: String,
+    pub label_ids: Vec<String>,
+    /// Epoch milliseconds
+    pub internal_date: Option<i64>,
+}
+
+fn message_from_row(row: &Row) -> rusqlite::Result<ArchivedMessage> {
+    let label_ids: String = row.get(5)?;
+    Ok(ArchivedMessage {
+        message_id: row.get(0)?,
+        thread_id: row.get(1)?,
+        subject: row.get(2)?,
+        from: row.get(3)?,
+        snippet: row.get(4)?,
+        label_ids: serde_json::from_str(&label_ids).unwrap_or_default(),
+        internal_date: row.get(6)?,
+    })
+}
+
+pub fn create_archive_account(conn: &Connection, id: &str, name: &str, source: &str, source_path: &str) -> Result<ArchiveAccount> {
+    conn.execute(
+        "INSERT INTO archive_accounts (id, name, source, source_path) VALUES (?1, ?2, ?3, ?4)",
+        params![id, name, source, source_path],
+    ).with_context(|| format!("Failed to create archive account '{}'", name))?;
+
+    get_archive_account(conn, id)?.context("Created archive account not found")
+}
+
+pub fn get_archive_account(conn: &Connection, id: &str) -> Result<Option<ArchiveAccount>> {
+    conn.query_row(
+        &format!("SELECT {} FROM archive_accounts WHERE id = ?1", ACCOUNT_COLUMNS),
+        params![id],
+        account_from_row,
+    )
+    .optional()
+    .context("Failed to load archive account")
+}
+
+pub fn list_archive_accounts(conn: &Connection) -> Result<Vec<ArchiveAccount>> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM archive_accounts ORDER BY name", ACCOUNT_COLUMNS))?;
+    let accounts = stmt
+        .query_map([], account_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list archive accounts")?;
+    Ok(accounts)
+}
+
+/// Recount the archive's messages and stamp the import time
+pub fn finish_import(conn: &Connection, id: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE archive_accounts
+         SET message_count = (SELECT COUNT(*) FROM gmail_message_store WHERE account_id = ?1),
+             imported_at = datetime('now')
+         WHERE id = ?1",
+        params![id],
+    ).context("Failed to update archive account")?;
+    Ok(())
+}
+
+/// Delete an archive account and its messages
+pub fn delete_archive_account(conn: &Connection, id: &str) -> Result<bool> {
+    let tx = conn.unchecked_transaction().context("Failed to start archive transaction")?;
+    tx.execute("DELETE FROM gmail_message_store WHERE account_id = ?1", params![id])
+        .context("Failed to delete archived messages")?;
+    let deleted = tx
+        .execute("DELETE FROM archive_accounts WHERE id = ?1", params![id])
+        .context("Failed to delete archive account")?;
+    tx.commit().context("Failed to delete archive account")?;
+    Ok(deleted > 0)
+}
+
+/// Store an imported message. A message already in the archive, such as
+/// one filed in two folders, keeps its labels and gains the new ones.
+/// Returns false if it was already stored.
+pub fn store_archived_message(conn: &Connection, message: &StoredMessage) -> Result<bool> {
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT label_ids FROM gmail_message_store WHERE account_id = ?1 AND message_id = ?2",
+            params![message.account_id, message.message_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("Failed to look up archived message")?;
+
+    let Some(existing) = existing else {
+        backfill_operations::store_message(conn, message)?;
+        return Ok(true);
+    };
+
+    let mut labels: BTreeSet<String> = serde_json::from_str::<Vec<String>>(&existing).unwrap_or_default().into_iter().collect();
+    labels.extend(message.label_ids.iter().cloned());
+    conn.execute(
+        "UPDATE gmail_message_store SET label_ids = ?3 WHERE account_id = ?1 AND message_id = ?2",
+        params![message.account_id, message.message_id, serde_json::to_string(&labels)?],
+    ).context("Failed to merge archived message labels")?;
+    Ok(false)
+}
+
+/// The archive's folders with their message counts
+pub fn list_archive_folders(conn: &Connection, id: &str) -> Result<Vec<ArchiveFolder>> {
+    let mut stmt = conn.prepare(
+        "SELECT l.value, COUNT(*) FROM gmail_message_store s, json_each(s.label_ids) l
+         WHERE s.account_id = ?1
+         GROUP BY l.value ORDER BY l.value",
+    )?;
+    let folders = stmt
+        .query_map(params![id], |row| Ok(ArchiveFolder { label: row.get(0)?, message_count: row.get(1)? }))?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list archive folders")?;
+    Ok(folders)
+}
+
+/// Messages in an archive folder, or the whole archive, newest first
+pub fn list_archived_messages(
+    conn: &Connection,
+    id: &str,
+    label: Option<&str>,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<ArchivedMessage>> {
+    let mut stmt = conn.prepare(
+        "SELECT s.message_id, s.thread_id,
+                coalesce(json_extract(s.message_data, '$.parsed_content.subject'), '(no subject)'),
+                coalesce(nullif(json_extract(s.message_data, '$.parsed_content.from.name'), ''),
+                         json_extract(s.message_data, '$.parsed_content.from.email'), ''),
+                coalesce(json_extract(s.message_data, '$.snippet'), ''),
+                s.label_ids, s.internal_date
+         FROM gmail_message_store s
+         WHERE s.account_id = ?1
+           AND (?2 IS NULL OR EXISTS (SELECT 1 FROM json_each(s.label_ids) WHERE value = ?2))
+         ORDER BY s.internal_date DESC LIMIT ?3 OFFSET ?4",
+    )?;
+    let messages = stmt
+        .query_map(params![id, label, limit, offset], message_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list archived messages")?;
+    Ok(messages)
+}
+
+/// The stored message, as kept in the message store
+pub fn get_archived_message(conn: &Connection, id: &str, message_id: &str) -> Result<Option<serde_json::Value>> {
+    let data: Option<String> = conn
+        .query_row(
+            "SELECT message_data FROM gmail_message_store WHERE account_id = ?1 AND message_id = ?2",
+            params![id, message_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("Failed to load archived message")?;
+    data.map(|data| serde_json::from_str(&data).context("Stored message is not valid JSON")).transpose()
+}
@@ -0,0 +1,128 @@
+//! Attachment scan verdict operations
+//!
+//! Each scan of an attachment before opening it leaves a verdict here, so
+//! the message view can mark flagged attachments and a user choosing to
+//! open one anyway is on record.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::services::gmail::attachment_scan::{ScanResult, Verdict};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanVerdict {
+    pub account_id: String,
+    pub message_id: String,
+    pub sha256: String,
+    pub filename: String,
+    pub verdict: Verdict,
+    pub engine: String,
+    pub detail: Option<String>,
+    pub scanned_at: String,
+    pub opened_anyway_at: Option<String>,
+}
+
+const VERDICT_COLUMNS: &str =
+    "account_id, message_id, sha256, filename, verdict, engine, detail, scanned_at, opened_anyway_at";
+
+fn verdict_from_row(row: &Row) -> rusqlite::Result<ScanVerdict> {
+    let verdict: String = row.get(4)?;
+    Ok(ScanVerdict {
+        account_id: row.get(0)?,
+        message_id: row.get(1)?,
+        sha256: row.get(2)?,
+        filename: row.get(3)?,
+        verdict: Verdict::parse(&verdict).unwrap_or(Verdict::Error),
+        engine: row.get(5)?,
+        detail: row.get(6)?,
+        scanned_at: row.get(7)?,
+        opened_anyway_at: row.get(8)?,
+    })
+}
+
+/// Store the latest scan of an attachment, replacing an earlier verdict
+pub fn record_verdict(
+    conn: &Connection,
+    account_id: &str,
+    message_id: &str,
+    sha256: &str,
+    filename: &str,
+    result: &ScanResult,
+) -> Result<ScanVerdict> {
+    conn.execute(
+        "INSERT INTO attachment_scan_verdicts (account_id, message_id, sha256, filename, verdict, engine, detail)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT(account_id, message_id, sha256) DO UPDATE SET
+            filename = excluded.filename, verdict = excluded.verdict, engine = excluded.engine,
+            detail = excluded.detail, scanned_at = CURRENT_TIMESTAMP",
+        params![account_id, message_id, sha256, filename, result.verdict.as_str(), result.engine, result.detail],
+    ).context("Failed to record attachment scan verdict")?;
+    get_verdict(conn, account_id, message_id, sha256)?.context("Attachment scan verdict was not stored")
+}
+
+pub fn get_verdict(conn: &Connection, account_id: &str, message_id: &str, sha256: &str) -> Result<Option<ScanVerdict>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM attachment_scan_verdicts WHERE account_id = ?1 AND message_id = ?2 AND sha256 = ?3",
+            VERDICT_COLUMNS
+        ),
+        params![account_id, message_id, sha256],
+        verdict_from_row,
+    )
+    .optional()
+    .context("Failed to load attachment scan verdict")
+}
+
+/// Verdicts for the attachments of one message, by filename
+pub fn list_verdicts(conn: &Connection, account_id: &str, message_id: &str) -> Result<Vec<ScanVerdict>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM attachment_scan_verdicts WHERE account_id = ?1 AND message_id = ?2 ORDER BY filename",
+        VERDICT_COLUMNS
+    ))?;
+    let verdicts = stmt
+        .query_map(params![account_id, message_id], verdict_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list attachment scan verdicts")?;
+    Ok(verdicts)
+}
+
+/// Note that a flagged attachment was opened on the user's say-so
+pub fn mark_opened_anyway(conn: &Connection, account_id: &str, message_id: &str, sha256: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE attachment_scan_verdicts SET opened_anyway_at = CURRENT_TIMESTAMP
+         WHERE account_id = ?1 AND message_id = ?2 AND sha256 = ?3",
+        params![account_id, message_id, sha256],
+    ).context("Failed to record opening a flagged attachment")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    fn result(verdict: Verdict, detail: Option<&str>) -> ScanResult {
+        ScanResult { verdict, engine: "clamscan".to_string(), detail: detail.map(str::to_string) }
+    }
+
+    #[test]
+    fn test_latest_verdict_replaces_earlier_one() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        record_verdict(&conn, "acc", "m-1", "aa", "invoice.pdf", &result(Verdict::Clean, None)).unwrap();
+        record_verdict(&conn, "acc", "m-1", "bb", "setup.exe", &result(Verdict::Error, Some("no database"))).unwrap();
+        let rescanned =
+            record_verdict(&conn, "acc", "m-1", "bb", "setup.exe", &result(Verdict::Infected, Some("Win.Test.EICAR"))).unwrap();
+        assert_eq!(rescanned.verdict, Verdict::Infected);
+        assert_eq!(rescanned.detail.as_deref(), Some("Win.Test.EICAR"));
+        assert!(rescanned.opened_anyway_at.is_none());
+
+        mark_opened_anyway(&conn, "acc", "m-1", "bb").unwrap();
+        let verdicts = list_verdicts(&conn, "acc", "m-1").unwrap();
+        assert_eq!(verdicts.iter().map(|v| v.filename.as_str()).collect::<Vec<_>>(), ["invoice.pdf", "setup.exe"]);
+        assert!(verdicts[1].opened_anyway_at.is_some());
+        assert!(list_verdicts(&conn, "acc", "m-2").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,108 @@
+//! Recent mail from recipients
+//!
+//! Out-of-office checks look at what a recipient last sent the account:
+//! an auto-reply means they may be away, anything after it means they are
+//! back. Only the fields those checks read are pulled from the store.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SenderMessage {
+    pub message_id: String,
+    /// Lowercased
+    pub from_email: String,
+    pub from_name: Option<String>,
+    pub subject: String,
+    /// Plain-text body, or the snippet for header-only copies
+    pub text: String,
+    /// Lowercased header names
+    pub headers: HashMap<String, String>,
+    /// Epoch milliseconds
+    pub internal_date: i64,
+}
+
+fn message_from_row(row: &Row) -> rusqlite::Result<SenderMessage> {
+    let headers: Option<String> = row.get(5)?;
+    Ok(SenderMessage {
+        message_id: row.get(0)?,
+        from_email: row.get(1)?,
+        from_name: row.get(2)?,
+        subject: row.get(3)?,
+        text: row.get(4)?,
+        headers: headers.and_then(|headers| serde_json::from_str(&headers).ok()).unwrap_or_default(),
+        internal_date: row.get(6)?,
+    })
+}
+
+/// Stored messages from any of `senders` received at or after `since`
+/// (epoch milliseconds), newest first
+pub fn recent_from(conn: &Connection, account_id: &str, senders: &[String], since: i64) -> Result<Vec<SenderMessage>> {
+    let senders: Vec<String> = senders.iter().map(|email| email.trim().to_lowercase()).collect();
+    let mut stmt = conn.prepare(
+        "SELECT s.message_id,
+                lower(json_extract(s.message_data, '$.parsed_content.from.email')),
+                json_extract(s.message_data, '$.parsed_content.from.name'),
+                coalesce(json_extract(s.message_data, '$.parsed_content.subject'), ''),
+                coalesce(json_extract(s.message_data, '$.parsed_content.body_text'), json_extract(s.message_data, '$.snippet'), ''),
+                json_extract(s.message_data, '$.parsed_content.headers'),
+                s.internal_date
+         FROM gmail_message_store s
+         WHERE s.account_id = ?1 AND s.internal_date >= ?2
+           AND lower(json_extract(s.message_data, '$.parsed_content.from.email')) IN (SELECT value FROM json_each(?3))
+         ORDER BY s.internal_date DESC",
+    )?;
+    let messages = stmt
+        .query_map(params![account_id, since, serde_json::to_string(&senders)?], message_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list recent mail from recipients")?;
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::operations::backfill_operations::{store_message, StoredMessage};
+    use crate::database::schema::run_migrations;
+    use serde_json::json;
+
+    fn store(conn: &Connection, id: &str, date: i64, from: &str, parsed_content: serde_json::Value) {
+        let mut parsed_content = parsed_content;
+        parsed_content["from"] = json!({ "email": from, "name": "Sam Lee" });
+        store_message(conn, &StoredMessage {
+            account_id: "acc".to_string(),
+            message_id: id.to_string(),
+            thread_id: format!("t-{}", id),
+            label_ids: vec!["INBOX".to_string()],
+            internal_date: Some(date),
+            content: "full".to_string(),
+            message_data: json!({ "snippet": format!("snippet {}", id), "parsed_content": parsed_content }),
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_recent_from_matches_senders_case_insensitively() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        store(&conn, "old", 1_000, "sam@example.com", json!({ "subject": "Old" }));
+        store(&conn, "ooo", 5_000, "Sam@Example.com", json!({
+            "subject": "Automatic reply: Budget",
+            "body_text": "I am out of the office until Monday.",
+            "headers": { "auto-submitted": "auto-replied" },
+        }));
+        store(&conn, "hdr", 6_000, "sam@example.com", json!({ "subject": "Headers only" }));
+        store(&conn, "other", 7_000, "kim@example.com", json!({ "subject": "Unrelated" }));
+
+        let messages = recent_from(&conn, "acc", &["SAM@example.com".to_string()], 2_000).unwrap();
+        assert_eq!(messages.iter().map(|m| m.message_id.as_str()).collect::<Vec<_>>(), ["hdr", "ooo"]);
+        assert_eq!(messages[0].text, "snippet hdr");
+        assert!(messages[0].headers.is_empty());
+        assert_eq!(messages[1].from_email, "sam@example.com");
+        assert_eq!(messages[1].headers.get("auto-submitted").map(String::as_str), Some("auto-replied"));
+        assert_eq!(messages[1].from_name.as_deref(), Some("Sam Lee"));
+    }
+}
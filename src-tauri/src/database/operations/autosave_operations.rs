@@ -0,0 +1,150 @@
+//! Compose autosave operations
+//!
+//! The composer saves its state every few seconds under a key it generates
+//! when the window opens. Each change is kept as a new version, up to
+//! `MAX_VERSIONS` per key, and the versions are removed once the message is
+//! sent or discarded. Anything still here after a restart was never sent.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+/// Versions kept per compose window
+pub const MAX_VERSIONS: i64 = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutosavedDraft {
+    pub autosave_key: String,
+    pub account_id: String,
+    pub version: i64,
+    pub compose_data: serde_json::Value,
+    pub saved_at: String,
+}
+
+const AUTOSAVE_COLUMNS: &str = "autosave_key, account_id, version, compose_data, saved_at";
+
+fn autosave_from_row(row: &Row) -> rusqlite::Result<AutosavedDraft> {
+    let compose_data: String = row.get(3)?;
+    Ok(AutosavedDraft {
+        autosave_key: row.get(0)?,
+        account_id: row.get(1)?,
+        version: row.get(2)?,
+        compose_data: serde_json::from_str(&compose_data).unwrap_or(serde_json::Value::Null),
+        saved_at: row.get(4)?,
+    })
+}
+
+pub fn latest_version(conn: &Connection, autosave_key: &str) -> Result<Option<AutosavedDraft>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM draft_autosaves WHERE autosave_key = ?1 ORDER BY version DESC LIMIT 1",
+            AUTOSAVE_COLUMNS
+        ),
+        params![autosave_key],
+        autosave_from_row,
+    )
+    .optional()
+    .context("Failed to load latest autosave")
+}
+
+/// Save a new version unless the content is unchanged since the last one,
+/// in which case the last version is returned as is
+pub fn save_version(
+    conn: &Connection,
+    account_id: &str,
+    autosave_key: &str,
+    compose_data: &serde_json::Value,
+) -> Result<AutosavedDraft> {
+    let latest = latest_version(conn, autosave_key)?;
+    if let Some(latest) = latest.as_ref().filter(|l| &l.compose_data == compose_data) {
+        return Ok(latest.clone());
+    }
+
+    let version = latest.map(|l| l.version + 1).unwrap_or(1);
+    conn.execute(
+        "INSERT INTO draft_autosaves (account_id, autosave_key, version, compose_data) VALUES (?1, ?2, ?3, ?4)",
+        params![account_id, autosave_key, version, compose_data.to_string()],
+    ).context("Failed to save autosave version")?;
+    conn.execute(
+        "DELETE FROM draft_autosaves WHERE autosave_key = ?1 AND version <= ?2",
+        params![autosave_key, version - MAX_VERSIONS],
+    ).context("Failed to prune old autosave versions")?;
+
+    latest_version(conn, autosave_key)?.context("Autosave version missing after insert")
+}
+
+/// Every version of one compose window, newest first
+pub fn list_versions(conn: &Connection, autosave_key: &str) -> Result<Vec<AutosavedDraft>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM draft_autosaves WHERE autosave_key = ?1 ORDER BY version DESC",
+        AUTOSAVE_COLUMNS
+    ))?;
+    let versions = stmt
+        .query_map(params![autosave_key], autosave_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list autosave versions")?;
+    Ok(versions)
+}
+
+/// The latest version of each compose window that was never sent, newest
+/// first, optionally limited to one account
+pub fn list_unsent(conn: &Connection, account_id: Option<&str>) -> Result<Vec<AutosavedDraft>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM draft_autosaves a
+         WHERE version = (SELECT MAX(version) FROM draft_autosaves WHERE autosave_key = a.autosave_key)
+           AND (?1 IS NULL OR account_id = ?1)
+         ORDER BY saved_at DESC, id DESC",
+        AUTOSAVE_COLUMNS
+    ))?;
+    let drafts = stmt
+        .query_map(params![account_id], autosave_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list unsent autosaves")?;
+    Ok(drafts)
+}
+
+/// Drop every version of a compose window once it was sent or discarded.
+/// Returns false if nothing was saved under the key.
+pub fn clear(conn: &Connection, autosave_key: &str) -> Result<bool> {
+    let removed = conn.execute(
+        "DELETE FROM draft_autosaves WHERE autosave_key = ?1",
+        params![autosave_key],
+    ).context("Failed to clear autosaves")?;
+    Ok(removed > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+    use serde_json::json;
+
+    #[test]
+    fn test_autosave_versions_and_recovery() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let first = save_version(&conn, "acc", "compose-1", &json!({ "subject": "Hi" })).unwrap();
+        assert_eq!(first.version, 1);
+
+        // Unchanged content does not create a version
+        let same = save_version(&conn, "acc", "compose-1", &json!({ "subject": "Hi" })).unwrap();
+        assert_eq!(same.version, 1);
+
+        for i in 0..MAX_VERSIONS + 5 {
+            save_version(&conn, "acc", "compose-1", &json!({ "subject": format!("Hi {}", i) })).unwrap();
+        }
+        let versions = list_versions(&conn, "compose-1").unwrap();
+        assert_eq!(versions.len() as i64, MAX_VERSIONS);
+        assert_eq!(versions[0].version, MAX_VERSIONS + 6);
+
+        save_version(&conn, "acc", "compose-2", &json!({ "subject": "Other" })).unwrap();
+        assert_eq!(list_unsent(&conn, Some("acc")).unwrap().len(), 2);
+        assert!(list_unsent(&conn, Some("other")).unwrap().is_empty());
+
+        assert!(clear(&conn, "compose-1").unwrap());
+        let unsent = list_unsent(&conn, None).unwrap();
+        assert_eq!(unsent.len(), 1);
+        assert_eq!(unsent[0].autosave_key, "compose-2");
+    }
+}
@@ -0,0 +1,361 @@
+//! Gmail backfill job and local message store operations
+//!
+//! A backfill walks an account's mail in date windows from the oldest
+//! date its sync policies need up to the time the job started. After every
+//! page the window and Gmail page token are checkpointed, so an interrupted
+//! job picks up where it left off. Messages that could not be downloaded
+//! are kept in `gmail_backfill_failures` and retried before the job
+//! completes.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackfillJob {
+    pub account_id: String,
+    pub status: String,
+    pub range_start: i64,
+    pub range_end: i64,
+    pub window_start: i64,
+    pub page_token: Option<String>,
+    pub messages_stored: i64,
+    pub messages_skipped: i64,
+    pub error: Option<String>,
+    pub started_at: String,
+    pub updated_at: String,
+    pub completed_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackfillProgress {
+    pub account_id: String,
+    pub status: String,
+    pub percent_complete: f64,
+    pub messages_stored: i64,
+    pub messages_skipped: i64,
+    /// Messages that could not be downloaded, even after retrying
+    pub messages_failed: i64,
+    /// Oldest date not yet fully backfilled, RFC 3339
+    pub current_window_start: Option<String>,
+    pub error: Option<String>,
+}
+
+/// A message as kept in the local message store
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredMessage {
+    pub account_id: String,
+    pub message_id: String,
+    pub thread_id: String,
+    pub label_ids: Vec<String>,
+    /// Epoch milliseconds
+    pub internal_date: Option<i64>,
+    /// "full" or "headers"
+    pub content: String,
+    pub message_data: serde_json::Value,
+}
+
+impl BackfillJob {
+    /// Share of the date range already walked
+    pub fn percent_complete(&self) -> f64 {
+        if self.status == "completed" {
+            return 100.0;
+        }
+        let total = (self.range_end - self.range_start).max(1) as f64;
+        let done = (self.window_start - self.range_start).clamp(0, self.range_end - self.range_start) as f64;
+        (done * 100.0 / total * 10.0).round() / 10.0
+    }
+
+    /// Progress with `messages_failed` from `failure_count`
+    pub fn progress(&self, messages_failed: i64) -> BackfillProgress {
+        BackfillProgress {
+            account_id: self.account_id.clone(),
+            status: self.status.clone(),
+            percent_complete: self.percent_complete(),
+            messages_stored: self.messages_stored,
+            messages_skipped: self.messages_skipped,
+            messages_failed,
+            current_window_start: chrono::DateTime::from_timestamp(self.window_start, 0).map(|d| d.to_rfc3339()),
+            error: self.error.clone(),
+        }
+    }
+}
+
+const JOB_COLUMNS: &str = "account_id, status, range_start, range_end, window_start, page_token,
+    messages_stored, messages_skipped, error, started_at, updated_at, completed_at";
+
+fn job_from_row(row: &Row) -> rusqlite::Result<BackfillJob> {
+    Ok(BackfillJob {
+        account_id: row.get(0)?,
+        status: row.get(1)?,
+        range_start: row.get(2)?,
+        range_end: row.get(3)?,
+        window_start: row.get(4)?,
+        page_token: row.get(5)?,
+        messages_stored: row.get(6)?,
+        messages_skipped: row.get(7)?,
+        error: row.get(8)?,
+        started_at: row.get(9)?,
+        updated_at: row.get(10)?,
+        completed_at: row.get(11)?,
+    })
+}
+
+/// Create a fresh job for an account, replacing any finished one and the
+/// failures it recorded
+pub fn create_job(conn: &Connection, account_id: &str, range_start: i64, range_end: i64) -> Result<BackfillJob> {
+    conn.execute("DELETE FROM gmail_backfill_failures WHERE account_id = ?1", params![account_id])
+        .context("Failed to clear backfill failures")?;
+    conn.execute(
+        "INSERT OR REPLACE INTO gmail_backfill_jobs (account_id, status, range_start, range_end, window_start)
+         VALUES (?1, 'running', ?2, ?3, ?2)",
+        params![account_id, range_start, range_end],
+    ).context("Failed to create backfill job")?;
+    get_job(conn, account_id)?.context("Backfill job missing after insert")
+}
+
+pub fn get_job(conn: &Connection, account_id: &str) -> Result<Option<BackfillJob>> {
+    conn.query_row(
+        &format!("SELECT {} FROM gmail_backfill_jobs WHERE account_id = ?1", JOB_COLUMNS),
+        params![account_id],
+        job_from_row,
+    )
+    .optional()
+    .context("Failed to get backfill job")
+}
+
+pub fn list_jobs_with_status(conn: &Connection, status: &str) -> Result<Vec<BackfillJob>> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM gmail_backfill_jobs WHERE status = ?1", JOB_COLUMNS))?;
+    let jobs = stmt
+        .query_map(params![status], job_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list backfill jobs")?;
+    Ok(jobs)
+}
+
+/// Persist progress after a page has been stored
+pub fn save_checkpoint(
+    conn: &Connection,
+    account_id: &str,
+    window_start: i64,
+    page_token: Option<&str>,
+    stored: i64,
+    skipped: i64,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE gmail_backfill_jobs
+         SET window_start = ?1, page_token = ?2,
+             messages_stored = messages_stored + ?3,
+             messages_skipped = messages_skipped + ?4,
+             updated_at = CURRENT_TIMESTAMP
+         WHERE account_id = ?5",
+        params![window_start, page_token, stored, skipped, account_id],
+    ).context("Failed to save backfill checkpoint")?;
+    Ok(())
+}
+
+/// Remember a message that could not be downloaded, counting the attempt
+pub fn record_failure(conn: &Connection, account_id: &str, message_id: &str, error: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO gmail_backfill_failures (account_id, message_id, error) VALUES (?1, ?2, ?3)
+         ON CONFLICT(account_id, message_id)
+         DO UPDATE SET error = ?3, attempts = attempts + 1, failed_at = CURRENT_TIMESTAMP",
+        params![account_id, message_id, error],
+    ).context("Failed to record backfill failure")?;
+    Ok(())
+}
+
+/// Failed messages tried fewer than `max_attempts` times
+pub fn retryable_failures(conn: &Connection, account_id: &str, max_attempts: i64) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT message_id FROM gmail_backfill_failures WHERE account_id = ?1 AND attempts < ?2 ORDER BY failed_at",
+    )?;
+    let ids = stmt
+        .query_map(params![account_id, max_attempts], |row| row.get(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()
+        .context("Failed to list backfill failures")?;
+    Ok(ids)
+}
+
+pub fn clear_failure(conn: &Connection, account_id: &str, message_id: &str) -> Result<()> {
+    conn.execute(
+        "DELETE FROM gmail_backfill_failures WHERE account_id = ?1 AND message_id = ?2",
+        params![account_id, message_id],
+    ).context("Failed to clear backfill failure")?;
+    Ok(())
+}
+
+pub fn failure_count(conn: &Connection, account_id: &str) -> Result<i64> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM gmail_backfill_failures WHERE account_id = ?1",
+        params![account_id],
+        |row| row.get(0),
+    )
+    .context("Failed to count backfill failures")
+}
+
+pub fn set_job_status(conn: &Connection, account_id: &str, status: &str, error: Option<&str>) -> Result<()> {
+    conn.execute(
+        "UPDATE gmail_backfill_jobs
+         SET status = ?1, error = ?2, updated_at = CURRENT_TIMESTAMP,
+             completed_at = CASE WHEN ?1 = 'completed' THEN CURRENT_TIMESTAMP ELSE completed_at END
+         WHERE account_id = ?3",
+        params![status, error, account_id],
+    ).context("Failed to update backfill status")?;
+    Ok(())
+}
+
+/// Store (or refresh) a synced message in the local message store. The
+/// statement is cached on the connection, so storing many messages on one
+/// connection parses it once; see `cache_operations::store_messages`.
+pub fn store_message(conn: &Connection, message: &StoredMessage) -> Result<()> {
+    let mut stmt = conn.prepare_cached(
+        "INSERT OR REPLACE INTO gmail_message_store
+         (account_id, message_id, thread_id, label_ids, internal_date, content, message_data, stored_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, CURRENT_TIMESTAMP)",
+    )?;
+    stmt.execute(params![
+        message.account_id,
+        message.message_id,
+        message.thread_id,
+        serde_json::to_string(&message.label_ids)?,
+        message.internal_date,
+        message.content,
+        message.message_data.to_string(),
+    ])
+    .context("Failed to store message")?;
+    Ok(())
+}
+
+/// Remove messages deleted in Gmail from the local message store
+pub fn delete_messages(conn: &Connection, account_id: &str, message_ids: &[String]) -> Result<usize> {
+    let mut stmt = conn.prepare_cached("DELETE FROM gmail_message_store WHERE account_id = ?1 AND message_id = ?2")?;
+    let mut deleted = 0;
+    for message_id in message_ids {
+        deleted += stmt.execute(params![account_id, message_id]).context("Failed to delete stored message")?;
+    }
+    Ok(deleted)
+}
+
+pub fn is_message_stored(conn: &Connection, account_id: &str, message_id: &str) -> Result<bool> {
+    let exists: i64 = conn
+        .prepare_cached("SELECT COUNT(*) FROM gmail_message_store WHERE account_id = ?1 AND message_id = ?2")?
+        .query_row(params![account_id, message_id], |row| row.get(0))?;
+    Ok(exists > 0)
+}
+
+/// Message IDs and labels of a thread's stored messages, oldest first
+pub fn get_thread_labels(conn: &Connection, account_id: &str, thread_id: &str) -> Result<Vec<(String, Vec<String>)>> {
+    let mut stmt = conn.prepare(
+        "SELECT message_id, label_ids FROM gmail_message_store
+         WHERE account_id = ?1 AND thread_id = ?2 ORDER BY internal_date ASC",
+    )?;
+    let rows = stmt
+        .query_map(params![account_id, thread_id], |row| {
+            let labels: String = row.get(1)?;
+            Ok((row.get(0)?, serde_json::from_str(&labels).unwrap_or_default()))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to load thread labels")?;
+    Ok(rows)
+}
+
+/// Replace a stored message's labels, keeping its message data in step.
+/// Returns false if the message is not stored.
+/// A stored message's labels; None when the message is not in the store
+pub fn get_message_labels(conn: &Connection, account_id: &str, message_id: &str) -> Result<Option<Vec<String>>> {
+    let labels: Option<String> = conn
+        .query_row(
+            "SELECT label_ids FROM gmail_message_store WHERE account_id = ?1 AND message_id = ?2",
+            params![account_id, message_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("Failed to load message labels")?;
+    Ok(labels.map(|labels| serde_json::from_str(&labels).unwrap_or_default()))
+}
+
+pub fn set_message_labels(conn: &Connection, account_id: &str, message_id: &str, label_ids: &[String]) -> Result<bool> {
+    let labels = serde_json::to_string(label_ids)?;
+    let updated = conn.execute(
+        "UPDATE gmail_message_store
+         SET label_ids = ?3, message_data = json_set(message_data, '$.labels', json(?3))
+         WHERE account_id = ?1 AND message_id = ?2",
+        params![account_id, message_id, labels],
+    ).context("Failed to update message labels")?;
+    Ok(updated > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    #[test]
+    fn test_backfill_checkpoint_and_progress() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let job = create_job(&conn, "acc", 1_000, 2_000).unwrap();
+        assert_eq!(job.percent_complete(), 0.0);
+
+        save_checkpoint(&conn, "acc", 1_250, Some("page-2"), 40, 2).unwrap();
+        save_checkpoint(&conn, "acc", 1_500, None, 10, 0).unwrap();
+        let job = get_job(&conn, "acc").unwrap().unwrap();
+        assert_eq!((job.messages_stored, job.messages_skipped), (50, 2));
+        assert_eq!(job.page_token, None);
+        assert_eq!(job.percent_complete(), 50.0);
+
+        set_job_status(&conn, "acc", "paused", None).unwrap();
+        assert!(list_jobs_with_status(&conn, "running").unwrap().is_empty());
+        set_job_status(&conn, "acc", "completed", None).unwrap();
+        assert_eq!(get_job(&conn, "acc").unwrap().unwrap().percent_complete(), 100.0);
+    }
+
+    #[test]
+    fn test_failures_are_retried_a_few_times() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        create_job(&conn, "acc", 1_000, 2_000).unwrap();
+
+        record_failure(&conn, "acc", "m1", "timeout").unwrap();
+        record_failure(&conn, "acc", "m2", "timeout").unwrap();
+        record_failure(&conn, "acc", "m2", "timeout").unwrap();
+        assert_eq!(retryable_failures(&conn, "acc", 2).unwrap(), vec!["m1".to_string()]);
+        assert_eq!(failure_count(&conn, "acc").unwrap(), 2);
+
+        clear_failure(&conn, "acc", "m1").unwrap();
+        assert_eq!(failure_count(&conn, "acc").unwrap(), 1);
+        create_job(&conn, "acc", 1_000, 2_000).unwrap();
+        assert_eq!(failure_count(&conn, "acc").unwrap(), 0);
+    }
+    #[test]
+    fn test_thread_labels_follow_updates() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let message = |id: &str, date: i64| StoredMessage {
+            account_id: "acc".to_string(),
+            message_id: id.to_string(),
+            thread_id: "t1".to_string(),
+            label_ids: vec!["INBOX".to_string(), "UNREAD".to_string()],
+            internal_date: Some(date),
+            content: "full".to_string(),
+            message_data: serde_json::json!({ "id": id, "labels": ["INBOX", "UNREAD"] }),
+        };
+        store_message(&conn, &message("m2", 2)).unwrap();
+        store_message(&conn, &message("m1", 1)).unwrap();
+
+        assert!(set_message_labels(&conn, "acc", "m1", &["INBOX".to_string()]).unwrap());
+        assert!(!set_message_labels(&conn, "acc", "missing", &[]).unwrap());
+
+        let labels = get_thread_labels(&conn, "acc", "t1").unwrap();
+        assert_eq!(labels[0], ("m1".to_string(), vec!["INBOX".to_string()]));
+        assert_eq!(labels[1].1, vec!["INBOX".to_string(), "UNREAD".to_string()]);
+
+        let data: String = conn
+            .query_row("SELECT message_data FROM gmail_message_store WHERE message_id = 'm1'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&data).unwrap()["labels"], serde_json::json!(["INBOX"]));
+    }
+}
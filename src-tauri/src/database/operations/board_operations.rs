@@ -0,0 +1,464 @@
+//! Kanban board database operations
+//!
+//! This module provides CRUD and ordering operations for task boards.
+//! Boards, columns and card placement are stored locally and keyed by
+//! Google task ID, so a board layout is independent of Google task lists
+//! and survives a full resync.
+
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use uuid::Uuid;
+
+use crate::models::task_board::{
+    BoardCard, BoardColumn, BoardColumnWithCards, CreateBoardColumn, TaskBoard, TaskBoardWithColumns,
+};
+
+fn board_from_row(row: &Row) -> rusqlite::Result<TaskBoard> {
+    Ok(TaskBoard {
+        id: row.get(0)?,
+        account_id: row.get(1)?,
+        name: row.get(2)?,
+        created_at: row.get(3)?,
+        updated_at: row.get(4)?,
+    })
+}
+
+fn column_from_row(row: &Row) -> rusqlite::Result<BoardColumn> {
+    Ok(BoardColumn {
+        id: row.get(0)?,
+        board_id: row.get(1)?,
+        title: row.get(2)?,
+        color: row.get(3)?,
+        position: row.get(4)?,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+    })
+}
+
+// ===== Board Operations =====
+
+/// Create a board with an optional initial set of columns
+pub fn create_board(
+    conn: &mut Connection,
+    name: &str,
+    account_id: Option<&str>,
+    columns: &[CreateBoardColumn],
+) -> Result<TaskBoard> {
+    let board_id = Uuid::new_v4().to_string();
+    let tx = conn.transaction().context("Failed to start board transaction")?;
+
+    tx.execute(
+        "INSERT INTO task_boards (id, account_id, name) VALUES (?1, ?2, ?3)",
+        params![board_id, account_id, name],
+    ).context("Failed to create task board")?;
+
+    for (position, column) in columns.iter().enumerate() {
+        tx.execute(
+            "INSERT INTO board_columns (id, board_id, title, color, position) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![Uuid::new_v4().to_string(), board_id, column.title, column.color, position as i32],
+        ).context("Failed to create board column")?;
+    }
+
+    tx.commit().context("Failed to commit board creation")?;
+
+    get_board(conn, &board_id)?.ok_or_else(|| anyhow::anyhow!("Board {} not found after creation", board_id))
+}
+
+/// Get a board by ID
+pub fn get_board(conn: &Connection, board_id: &str) -> Result<Option<TaskBoard>> {
+    conn.query_row(
+        "SELECT id, account_id, name, created_at, updated_at FROM task_boards WHERE id = ?1",
+        params![board_id],
+        board_from_row,
+    )
+    .optional()
+    .context("Failed to get task board")
+}
+
+/// List boards, optionally restricted to one account
+pub fn list_boards(conn: &Connection, account_id: Option<&str>) -> Result<Vec<TaskBoard>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, account_id, name, created_at, updated_at FROM task_boards
+         WHERE ?1 IS NULL OR account_id = ?1
+         ORDER BY created_at ASC",
+    )?;
+
+    let boards = stmt
+        .query_map(params![account_id], board_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list task boards")?;
+
+    Ok(boards)
+}
+
+/// Rename a board
+pub fn rename_board(conn: &Connection, board_id: &str, name: &str) -> Result<bool> {
+    let rows = conn.execute(
+        "UPDATE task_boards SET name = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
+        params![name, board_id],
+    ).context("Failed to rename task board")?;
+    Ok(rows > 0)
+}
+
+/// Delete a board along with its columns and card placements
+pub fn delete_board(conn: &Connection, board_id: &str) -> Result<bool> {
+    let rows = conn
+        .execute("DELETE FROM task_boards WHERE id = ?1", params![board_id])
+        .context("Failed to delete task board")?;
+    Ok(rows > 0)
+}
+
+/// Load a board with its ordered columns and the ordered task IDs in each column
+pub fn get_board_with_columns(conn: &Connection, board_id: &str) -> Result<Option<TaskBoardWithColumns>> {
+    let board = match get_board(conn, board_id)? {
+        Some(board) => board,
+        None => return Ok(None),
+    };
+
+    let mut cards_stmt = conn.prepare(
+        "SELECT google_task_id FROM board_cards WHERE column_id = ?1 ORDER BY position ASC",
+    )?;
+
+    let columns = get_board_columns(conn, board_id)?
+        .into_iter()
+        .map(|column| {
+            let task_ids = cards_stmt
+                .query_map(params![column.id], |row| row.get(0))?
+                .collect::<rusqlite::Result<Vec<String>>>()?;
+            Ok(BoardColumnWithCards { column, task_ids })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Some(TaskBoardWithColumns { board, columns }))
+}
+
+// ===== Column Operations =====
+
+/// Get the ordered columns of a board
+pub fn get_board_columns(conn: &Connection, board_id: &str) -> Result<Vec<BoardColumn>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, board_id, title, color, position, created_at, updated_at
+         FROM board_columns WHERE board_id = ?1 ORDER BY position ASC",
+    )?;
+
+    let columns = stmt
+        .query_map(params![board_id], column_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to get board columns")?;
+
+    Ok(columns)
+}
+
+/// Append a column to the end of a board
+pub fn add_column(conn: &Connection, board_id: &str, column: &CreateBoardColumn) -> Result<BoardColumn> {
+    let column_id = Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO board_columns (id, board_id, title, color, position)
+         VALUES (?1, ?2, ?3, ?4, (SELECT COALESCE(MAX(position) + 1, 0) FROM board_columns WHERE board_id = ?2))",
+        params![column_id, board_id, column.title, column.color],
+    ).context("Failed to add board column")?;
+
+    conn.query_row(
+        "SELECT id, board_id, title, color, position, created_at, updated_at FROM board_columns WHERE id = ?1",
+        params![column_id],
+        column_from_row,
+    )
+    .context("Failed to load created board column")
+}
+
+/// Update a column's title and color
+pub fn update_column(conn: &Connection, column_id: &str, title: &str, color: Option<&str>) -> Result<bool> {
+    let rows = conn.execute(
+        "UPDATE board_columns SET title = ?1, color = ?2, updated_at = CURRENT_TIMESTAMP WHERE id = ?3",
+        params![title, color, column_id],
+    ).context("Failed to update board column")?;
+    Ok(rows > 0)
+}
+
+/// The board a column belongs to
+fn column_board(conn: &Connection, column_id: &str) -> Result<Option<String>> {
+    Ok(conn
+        .query_row("SELECT board_id FROM board_columns WHERE id = ?1", params![column_id], |row| row.get(0))
+        .optional()?)
+}
+
+/// Delete a column, moving its cards to the end of another column of the
+/// same board when one is given
+pub fn delete_column(conn: &mut Connection, column_id: &str, move_cards_to: Option<&str>) -> Result<bool> {
+    let tx = conn.transaction().context("Failed to start column transaction")?;
+
+    if let Some(target_column_id) = move_cards_to {
+        let target_board = column_board(&tx, target_column_id)?;
+        if target_column_id == column_id || target_board.is_none() || target_board != column_board(&tx, column_id)? {
+            return Err(anyhow::anyhow!(
+                "Cards of column {} can only move to another column of the same board",
+                column_id
+            ));
+        }
+        let mut target = column_task_ids(&tx, target_column_id)?;
+        target.extend(column_task_ids(&tx, column_id)?);
+        write_column_order(&tx, target_column_id, &target)?;
+    }
+
+    let rows = tx
+        .execute("DELETE FROM board_columns WHERE id = ?1", params![column_id])
+        .context("Failed to delete board column")?;
+
+    tx.commit().context("Failed to commit column deletion")?;
+    Ok(rows > 0)
+}
+
+/// Reorder a board's columns to match the given ID order
+pub fn reorder_columns(conn: &mut Connection, board_id: &str, ordered_column_ids: &[String]) -> Result<()> {
+    let tx = conn.transaction().context("Failed to start reorder transaction")?;
+
+    let existing: HashSet<String> = get_board_columns(&tx, board_id)?.into_iter().map(|c| c.id).collect();
+    let requested: HashSet<String> = ordered_column_ids.iter().cloned().collect();
+    if requested.len() != ordered_column_ids.len() || requested != existing {
+        return Err(anyhow::anyhow!(
+            "Column order must contain exactly the columns of board {}",
+            board_id
+        ));
+    }
+
+    for (position, column_id) in ordered_column_ids.iter().enumerate() {
+        tx.execute(
+            "UPDATE board_columns SET position = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
+            params![position as i32, column_id],
+        ).context("Failed to update column position")?;
+    }
+
+    tx.commit().context("Failed to commit column reorder")?;
+    Ok(())
+}
+
+// ===== Card Operations =====
+
+fn column_task_ids(conn: &Connection, column_id: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT google_task_id FROM board_cards WHERE column_id = ?1 ORDER BY position ASC",
+    )?;
+    let ids = stmt
+        .query_map(params![column_id], |row| row.get(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+    Ok(ids)
+}
+
+fn write_column_order(conn: &Connection, column_id: &str, task_ids: &[String]) -> Result<()> {
+    for (position, task_id) in task_ids.iter().enumerate() {
+        conn.execute(
+            "UPDATE board_cards SET column_id = ?1, position = ?2, updated_at = CURRENT_TIMESTAMP
+             WHERE google_task_id = ?3
+               AND board_id = (SELECT board_id FROM board_columns WHERE id = ?1)",
+            params![column_id, position as i32, task_id],
+        ).context("Failed to update card position")?;
+    }
+    Ok(())
+}
+
+/// Place a task on a board, or move it if it is already there.
+///
+/// `position` is the zero-based index inside the target column; `None` or an
+/// out-of-range index appends the card. Positions in both the source and the
+/// target column are renumbered so they stay contiguous.
+pub fn move_card(
+    conn: &mut Connection,
+    board_id: &str,
+    google_task_id: &str,
+    column_id: &str,
+    position: Option<usize>,
+) -> Result<BoardCard> {
+    let tx = conn.transaction().context("Failed to start card transaction")?;
+
+    if column_board(&tx, column_id)?.as_deref() != Some(board_id) {
+        return Err(anyhow::anyhow!("Column {} does not belong to board {}", column_id, board_id));
+    }
+
+    let previous_column: Option<String> = tx
+        .query_row(
+            "SELECT column_id FROM board_cards WHERE board_id = ?1 AND google_task_id = ?2",
+            params![board_id, google_task_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    match &previous_column {
+        Some(_) => {
+            tx.execute(
+                "UPDATE board_cards SET column_id = ?1 WHERE board_id = ?2 AND google_task_id = ?3",
+                params![column_id, board_id, google_task_id],
+            )?;
+        }
+        None => {
+            tx.execute(
+                "INSERT INTO board_cards (board_id, column_id, google_task_id, position) VALUES (?1, ?2, ?3, -1)",
+                params![board_id, column_id, google_task_id],
+            ).context("Failed to add card to board")?;
+        }
+    }
+
+    let mut target: Vec<String> = column_task_ids(&tx, column_id)?
+        .into_iter()
+        .filter(|id| id != google_task_id)
+        .collect();
+    let index = position.unwrap_or(target.len()).min(target.len());
+    target.insert(index, google_task_id.to_string());
+    write_column_order(&tx, column_id, &target)?;
+
+    if let Some(source_column) = previous_column.filter(|c| c != column_id) {
+        let source = column_task_ids(&tx, &source_column)?;
+        write_column_order(&tx, &source_column, &source)?;
+    }
+
+    tx.commit().context("Failed to commit card move")?;
+
+    Ok(BoardCard {
+        board_id: board_id.to_string(),
+        column_id: column_id.to_string(),
+        google_task_id: google_task_id.to_string(),
+        position: index as i32,
+    })
+}
+
+/// Remove a task from a board
+pub fn remove_card(conn: &mut Connection, board_id: &str, google_task_id: &str) -> Result<bool> {
+    let tx = conn.transaction().context("Failed to start card transaction")?;
+
+    let column_id: Option<String> = tx
+        .query_row(
+            "SELECT column_id FROM board_cards WHERE board_id = ?1 AND google_task_id = ?2",
+            params![board_id, google_task_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    let Some(column_id) = column_id else {
+        return Ok(false);
+    };
+
+    tx.execute(
+        "DELETE FROM board_cards WHERE board_id = ?1 AND google_task_id = ?2",
+        params![board_id, google_task_id],
+    ).context("Failed to remove card from board")?;
+
+    let remaining = column_task_ids(&tx, &column_id)?;
+    write_column_order(&tx, &column_id, &remaining)?;
+
+    tx.commit().context("Failed to commit card removal")?;
+    Ok(true)
+}
+
+/// Drop card placements for tasks that no longer exist remotely.
+///
+/// Called after a sync with the full set of task IDs known for an account so
+/// deleted tasks do not linger as ghost cards on that account's boards.
+pub fn prune_missing_cards(conn: &Connection, account_id: &str, known_task_ids: &[String]) -> Result<usize> {
+    let mut stmt = conn.prepare(
+        "SELECT DISTINCT bc.google_task_id FROM board_cards bc
+         JOIN task_boards b ON b.id = bc.board_id
+         WHERE b.account_id = ?1",
+    )?;
+    let placed: Vec<String> = stmt
+        .query_map(params![account_id], |row| row.get(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut removed = 0;
+    for task_id in placed.iter().filter(|id| !known_task_ids.contains(id)) {
+        removed += conn.execute(
+            "DELETE FROM board_cards WHERE google_task_id = ?1
+               AND board_id IN (SELECT id FROM task_boards WHERE account_id = ?2)",
+            params![task_id, account_id],
+        ).context("Failed to prune board card")?;
+    }
+
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        conn
+    }
+
+    fn columns(titles: &[&str]) -> Vec<CreateBoardColumn> {
+        titles
+            .iter()
+            .map(|t| CreateBoardColumn { title: t.to_string(), color: None })
+            .collect()
+    }
+
+    #[test]
+    fn test_create_board_with_columns() {
+        let mut conn = setup_test_db();
+        let board = create_board(&mut conn, "Sprint", None, &columns(&["Todo", "Doing", "Done"])).unwrap();
+
+        let loaded = get_board_with_columns(&conn, &board.id).unwrap().unwrap();
+        let titles: Vec<_> = loaded.columns.iter().map(|c| c.column.title.as_str()).collect();
+        assert_eq!(titles, vec!["Todo", "Doing", "Done"]);
+    }
+
+    #[test]
+    fn test_move_card_between_columns_keeps_positions_contiguous() {
+        let mut conn = setup_test_db();
+        let board = create_board(&mut conn, "Sprint", None, &columns(&["Todo", "Done"])).unwrap();
+        let cols = get_board_columns(&conn, &board.id).unwrap();
+
+        for task in ["a", "b", "c"] {
+            move_card(&mut conn, &board.id, task, &cols[0].id, None).unwrap();
+        }
+        move_card(&mut conn, &board.id, "b", &cols[1].id, Some(0)).unwrap();
+        move_card(&mut conn, &board.id, "c", &cols[0].id, Some(0)).unwrap();
+
+        let loaded = get_board_with_columns(&conn, &board.id).unwrap().unwrap();
+        assert_eq!(loaded.columns[0].task_ids, vec!["c", "a"]);
+        assert_eq!(loaded.columns[1].task_ids, vec!["b"]);
+    }
+
+    #[test]
+    fn test_reorder_columns_takes_only_a_permutation() {
+        let mut conn = setup_test_db();
+        let board = create_board(&mut conn, "Sprint", None, &columns(&["Todo", "Done"])).unwrap();
+        let cols = get_board_columns(&conn, &board.id).unwrap();
+
+        let reversed = vec![cols[1].id.clone(), cols[0].id.clone()];
+        reorder_columns(&mut conn, &board.id, &reversed).unwrap();
+        let reordered = get_board_columns(&conn, &board.id).unwrap();
+        assert_eq!(reordered[0].title, "Done");
+
+        let bogus = vec![cols[0].id.clone(), "missing".to_string()];
+        assert!(reorder_columns(&mut conn, &board.id, &bogus).is_err());
+
+        let duplicated = vec![cols[0].id.clone(), cols[0].id.clone()];
+        assert!(reorder_columns(&mut conn, &board.id, &duplicated).is_err());
+        let unchanged = get_board_columns(&conn, &board.id).unwrap();
+        assert_eq!((unchanged[0].title.as_str(), unchanged[1].title.as_str()), ("Done", "Todo"));
+    }
+
+    #[test]
+    fn test_delete_column_moves_cards_within_its_board_only() {
+        let mut conn = setup_test_db();
+        let board = create_board(&mut conn, "Sprint", None, &columns(&["Todo", "Done"])).unwrap();
+        let other = create_board(&mut conn, "Backlog", None, &columns(&["Ideas"])).unwrap();
+        let cols = get_board_columns(&conn, &board.id).unwrap();
+        let foreign = get_board_columns(&conn, &other.id).unwrap();
+        move_card(&mut conn, &board.id, "a", &cols[0].id, None).unwrap();
+        move_card(&mut conn, &board.id, "b", &cols[1].id, None).unwrap();
+
+        assert!(delete_column(&mut conn, &cols[0].id, Some(&foreign[0].id)).is_err());
+        assert!(delete_column(&mut conn, &cols[0].id, Some(&cols[0].id)).is_err());
+        let loaded = get_board_with_columns(&conn, &board.id).unwrap().unwrap();
+        assert_eq!(loaded.columns[0].task_ids, vec!["a"]);
+
+        assert!(delete_column(&mut conn, &cols[0].id, Some(&cols[1].id)).unwrap());
+        let loaded = get_board_with_columns(&conn, &board.id).unwrap().unwrap();
+        assert_eq!(loaded.columns.len(), 1);
+        assert_eq!(loaded.columns[0].task_ids, vec!["b", "a"]);
+    }
+}
@@ -0,0 +1,226 @@
+//! Cache delta log operations
+//!
+//! Changes to locally cached messages, tasks and calendar events are logged
+//! with increasing sequence numbers as they are pushed to the frontend, so
+//! a window that missed some (closed, reloading, asleep) can catch up from
+//! the last sequence number it saw instead of refetching every store. Only
+//! the newest `MAX_RETAINED` deltas are kept; a client further behind than
+//! that is told to reload.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Deltas kept for catch-up
+pub const MAX_RETAINED: i64 = 10_000;
+
+pub const ENTITY_MESSAGE: &str = "message";
+pub const ENTITY_TASK: &str = "task";
+pub const ENTITY_EVENT: &str = "event";
+
+pub const CHANGE_ADDED: &str = "added";
+pub const CHANGE_UPDATED: &str = "updated";
+pub const CHANGE_REMOVED: &str = "removed";
+
+/// A change to log, before it has a sequence number
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewCacheDelta {
+    pub account_id: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub change: String,
+    /// For messages, the labels it is now under (or was, when removed)
+    pub label_ids: Vec<String>,
+    /// Changed fields, when they are cheap to include
+    pub data: Option<Value>,
+}
+
+impl NewCacheDelta {
+    fn new(entity_type: &str, account_id: &str, entity_id: &str, change: &str) -> Self {
+        Self {
+            account_id: account_id.to_string(),
+            entity_type: entity_type.to_string(),
+            entity_id: entity_id.to_string(),
+            change: change.to_string(),
+            label_ids: Vec::new(),
+            data: None,
+        }
+    }
+
+    pub fn message(account_id: &str, message_id: &str, change: &str, label_ids: &[String]) -> Self {
+        Self { label_ids: label_ids.to_vec(), ..Self::new(ENTITY_MESSAGE, account_id, message_id, change) }
+    }
+
+    pub fn task(account_id: &str, task_id: &str, change: &str) -> Self {
+        Self::new(ENTITY_TASK, account_id, task_id, change)
+    }
+
+    pub fn event(account_id: &str, event_id: &str, change: &str) -> Self {
+        Self::new(ENTITY_EVENT, account_id, event_id, change)
+    }
+
+    pub fn with_data(self, data: Value) -> Self {
+        Self { data: Some(data), ..self }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheDelta {
+    pub seq: i64,
+    pub account_id: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub change: String,
+    pub label_ids: Vec<String>,
+    pub data: Option<Value>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangesSince {
+    pub deltas: Vec<CacheDelta>,
+    /// Highest sequence number handed out so far
+    pub latest_seq: i64,
+    /// More deltas follow the last one returned
+    pub has_more: bool,
+    /// Deltas after the requested sequence number were already pruned;
+    /// the stores must be reloaded
+    pub reset_required: bool,
+}
+
+const COLUMNS: &str = "seq, account_id, entity_type, entity_id, change, label_ids, data, created_at";
+
+fn delta_from_row(row: &Row) -> rusqlite::Result<CacheDelta> {
+    let label_ids: String = row.get(5)?;
+    let data: Option<String> = row.get(6)?;
+    Ok(CacheDelta {
+        seq: row.get(0)?,
+        account_id: row.get(1)?,
+        entity_type: row.get(2)?,
+        entity_id: row.get(3)?,
+        change: row.get(4)?,
+        label_ids: serde_json::from_str(&label_ids).unwrap_or_default(),
+        data: data.and_then(|d| serde_json::from_str(&d).ok()),
+        created_at: row.get(7)?,
+    })
+}
+
+/// Log deltas in order and return them with their sequence numbers
+pub fn record_deltas(conn: &Connection, deltas: &[NewCacheDelta]) -> Result<Vec<CacheDelta>> {
+    if deltas.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let created_at = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let mut recorded = Vec::with_capacity(deltas.len());
+    // One commit for the whole batch rather than one per delta
+    let tx = conn.unchecked_transaction().context("Failed to start cache delta transaction")?;
+    let mut stmt = tx.prepare_cached(
+        "INSERT INTO cache_deltas (account_id, entity_type, entity_id, change, label_ids, data, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+    )?;
+    for delta in deltas {
+        stmt.execute(params![
+            delta.account_id,
+            delta.entity_type,
+            delta.entity_id,
+            delta.change,
+            serde_json::to_string(&delta.label_ids)?,
+            delta.data.as_ref().map(|d| d.to_string()),
+            created_at,
+        ])
+        .context("Failed to record cache delta")?;
+        recorded.push(CacheDelta {
+            seq: tx.last_insert_rowid(),
+            account_id: delta.account_id.clone(),
+            entity_type: delta.entity_type.clone(),
+            entity_id: delta.entity_id.clone(),
+            change: delta.change.clone(),
+            label_ids: delta.label_ids.clone(),
+            data: delta.data.clone(),
+            created_at: created_at.clone(),
+        });
+    }
+    drop(stmt);
+    prune_deltas(&tx, MAX_RETAINED)?;
+    tx.commit().context("Failed to commit cache deltas")?;
+    Ok(recorded)
+}
+
+/// Highest sequence number handed out, 0 before the first delta. Kept by
+/// SQLite for AUTOINCREMENT tables, so it survives pruning.
+pub fn latest_seq(conn: &Connection) -> Result<i64> {
+    conn.query_row(
+        "SELECT COALESCE((SELECT seq FROM sqlite_sequence WHERE name = 'cache_deltas'), 0)",
+        [],
+        |row| row.get(0),
+    )
+    .context("Failed to read latest cache delta")
+}
+
+/// Deltas after `since`, oldest first, at most `limit` of them
+pub fn get_changes_since(conn: &Connection, since: i64, limit: i64) -> Result<ChangesSince> {
+    let latest = latest_seq(conn)?;
+    let oldest: Option<i64> = conn.query_row("SELECT MIN(seq) FROM cache_deltas", [], |row| row.get(0))?;
+
+    let pruned = match oldest {
+        Some(oldest) => since + 1 < oldest,
+        None => true,
+    };
+    if since < latest && pruned {
+        return Ok(ChangesSince { deltas: Vec::new(), latest_seq: latest, has_more: false, reset_required: true });
+    }
+
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM cache_deltas WHERE seq > ?1 ORDER BY seq LIMIT ?2", COLUMNS))?;
+    let mut deltas = stmt
+        .query_map(params![since, limit + 1], delta_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to load cache deltas")?;
+    let has_more = deltas.len() as i64 > limit;
+    deltas.truncate(limit.max(0) as usize);
+
+    Ok(ChangesSince { deltas, latest_seq: latest, has_more, reset_required: false })
+}
+
+/// Drop all but the newest `keep` deltas
+pub fn prune_deltas(conn: &Connection, keep: i64) -> Result<usize> {
+    conn.execute(
+        "DELETE FROM cache_deltas WHERE seq <= (SELECT MAX(seq) FROM cache_deltas) - ?1",
+        params![keep],
+    )
+    .context("Failed to prune cache deltas")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+    use serde_json::json;
+
+    #[test]
+    fn test_catch_up_and_reset() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        assert_eq!(latest_seq(&conn).unwrap(), 0);
+
+        let recorded = record_deltas(&conn, &[
+            NewCacheDelta::message("acc", "m1", CHANGE_ADDED, &["INBOX".to_string()]),
+            NewCacheDelta::task("acc", "t1", CHANGE_UPDATED).with_data(json!({ "status": "completed" })),
+            NewCacheDelta::event("acc", "e1", CHANGE_REMOVED),
+        ]).unwrap();
+        assert_eq!(recorded.iter().map(|d| d.seq).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(recorded[0].label_ids, vec!["INBOX".to_string()]);
+
+        let changes = get_changes_since(&conn, 1, 1).unwrap();
+        assert_eq!(changes.deltas[0].entity_id, "t1");
+        assert_eq!(changes.deltas[0].data, Some(json!({ "status": "completed" })));
+        assert!(changes.has_more && !changes.reset_required);
+        assert!(get_changes_since(&conn, 3, 10).unwrap().deltas.is_empty());
+
+        prune_deltas(&conn, 1).unwrap();
+        assert!(get_changes_since(&conn, 1, 10).unwrap().reset_required);
+        assert_eq!(get_changes_since(&conn, 2, 10).unwrap().deltas.len(), 1);
+        assert_eq!(latest_seq(&conn).unwrap(), 3);
+    }
+}
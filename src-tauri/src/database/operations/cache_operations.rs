@@ -1,10 +1,16 @@
 //! Request cache-related database operations
 //!
-//! This module provides CRUD operations for request caches.
+//! This module provides CRUD operations for request caches, and batch
+//! writers for the local message store. A batch runs in one transaction
+//! with a cached prepared statement, so writing a sync page costs one
+//! commit instead of one per message.
 
-use anyhow::Result;
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
 use rusqlite::{Connection, params, OptionalExtension};
 use crate::database::models::RequestCache;
+use crate::database::operations::backfill_operations::{self, StoredMessage};
 use chrono::{Local, NaiveDateTime};
 
 // ===== Request Cache Operations =====
@@ -230,4 +236,66 @@ pub fn clear_all_cache(conn: &Connection) -> Result<usize> {
     )?;
 
     Ok(rows_affected)
-} 
\ No newline at end of file
+} 
+
+// ===== Message Store Batch Operations =====
+
+/// Store a page of synced messages in one transaction. Returns how many
+/// were written.
+pub fn store_messages(conn: &mut Connection, messages: &[StoredMessage]) -> Result<usize> {
+    if messages.is_empty() {
+        return Ok(0);
+    }
+    let tx = conn.transaction().context("Failed to start message store transaction")?;
+    for message in messages {
+        backfill_operations::store_message(&tx, message)?;
+    }
+    tx.commit().context("Failed to commit stored messages")?;
+    Ok(messages.len())
+}
+
+/// The subset of `message_ids` already in an account's message store
+pub fn stored_message_ids(conn: &Connection, account_id: &str, message_ids: &[String]) -> Result<HashSet<String>> {
+    if message_ids.is_empty() {
+        return Ok(HashSet::new());
+    }
+    let mut stmt = conn.prepare_cached(
+        "SELECT message_id FROM gmail_message_store
+         WHERE account_id = ?1 AND message_id IN (SELECT value FROM json_each(?2))",
+    )?;
+    let stored = stmt
+        .query_map(params![account_id, serde_json::to_string(message_ids)?], |row| row.get(0))?
+        .collect::<rusqlite::Result<HashSet<String>>>()
+        .context("Failed to look up stored messages")?;
+    Ok(stored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    #[test]
+    fn test_batches_are_written_together() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let message = |id: &str| StoredMessage {
+            account_id: "acc".to_string(),
+            message_id: id.to_string(),
+            thread_id: "t1".to_string(),
+            label_ids: vec!["INBOX".to_string()],
+            internal_date: Some(1),
+            content: "full".to_string(),
+            message_data: serde_json::json!({ "id": id }),
+        };
+        assert_eq!(store_messages(&mut conn, &[message("m1"), message("m2"), message("m1")]).unwrap(), 3);
+        assert_eq!(store_messages(&mut conn, &[]).unwrap(), 0);
+
+        let ids = ["m1", "m2", "m3"].map(String::from);
+        let stored = stored_message_ids(&conn, "acc", &ids).unwrap();
+        assert_eq!(stored, HashSet::from(["m1".to_string(), "m2".to_string()]));
+        assert!(stored_message_ids(&conn, "other", &ids).unwrap().is_empty());
+    }
+}
+
@@ -0,0 +1,291 @@
+//! Mail merge campaign database operations
+//!
+//! A campaign pairs a subject/body template with a recipient list. Each
+//! recipient tracks its own send status so a campaign can be paused,
+//! resumed after a restart, or cancelled part way through.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MailCampaign {
+    pub id: i64,
+    pub account_id: String,
+    pub name: String,
+    pub subject_template: String,
+    pub body_template: String,
+    pub is_html: bool,
+    pub send_interval_ms: i64,
+    pub status: String,
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub completed_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampaignRecipient {
+    pub id: i64,
+    pub campaign_id: i64,
+    pub email: String,
+    pub name: Option<String>,
+    pub variables: HashMap<String, String>,
+    pub status: String,
+    pub message_id: Option<String>,
+    pub error: Option<String>,
+    pub sent_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampaignRecipientInput {
+    pub email: String,
+    pub name: Option<String>,
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewCampaign {
+    pub account_id: String,
+    pub name: String,
+    pub subject_template: String,
+    pub body_template: String,
+    #[serde(default)]
+    pub is_html: bool,
+    pub send_interval_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CampaignProgress {
+    pub campaign_id: i64,
+    pub status: String,
+    pub total: i64,
+    pub sent: i64,
+    pub failed: i64,
+    pub pending: i64,
+    pub percent_complete: f64,
+}
+
+const CAMPAIGN_COLUMNS: &str = "id, account_id, name, subject_template, body_template, is_html,
+    send_interval_ms, status, created_at, started_at, completed_at";
+
+const RECIPIENT_COLUMNS: &str = "id, campaign_id, email, name, variables, status, message_id, error, sent_at";
+
+fn campaign_from_row(row: &Row) -> rusqlite::Result<MailCampaign> {
+    Ok(MailCampaign {
+        id: row.get(0)?,
+        account_id: row.get(1)?,
+        name: row.get(2)?,
+        subject_template: row.get(3)?,
+        body_template: row.get(4)?,
+        is_html: row.get(5)?,
+        send_interval_ms: row.get(6)?,
+        status: row.get(7)?,
+        created_at: row.get(8)?,
+        started_at: row.get(9)?,
+        completed_at: row.get(10)?,
+    })
+}
+
+fn recipient_from_row(row: &Row) -> rusqlite::Result<CampaignRecipient> {
+    let variables: String = row.get(4)?;
+    Ok(CampaignRecipient {
+        id: row.get(0)?,
+        campaign_id: row.get(1)?,
+        email: row.get(2)?,
+        name: row.get(3)?,
+        variables: serde_json::from_str(&variables).unwrap_or_default(),
+        status: row.get(5)?,
+        message_id: row.get(6)?,
+        error: row.get(7)?,
+        sent_at: row.get(8)?,
+    })
+}
+
+/// Create a campaign with its recipients; duplicate addresses are ignored
+pub fn create_campaign(conn: &mut Connection, campaign: &NewCampaign, recipients: &[CampaignRecipientInput]) -> Result<i64> {
+    let tx = conn.transaction().context("Failed to start campaign transaction")?;
+
+    tx.execute(
+        "INSERT INTO mail_campaigns (account_id, name, subject_template, body_template, is_html, send_interval_ms)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            campaign.account_id,
+            campaign.name,
+            campaign.subject_template,
+            campaign.body_template,
+            campaign.is_html,
+            campaign.send_interval_ms.unwrap_or(5000).max(0),
+        ],
+    ).context("Failed to create campaign")?;
+    let campaign_id = tx.last_insert_rowid();
+
+    for recipient in recipients {
+        tx.execute(
+            "INSERT OR IGNORE INTO campaign_recipients (campaign_id, email, name, variables) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                campaign_id,
+                recipient.email.trim(),
+                recipient.name,
+                serde_json::to_string(&recipient.variables)?,
+            ],
+        ).context("Failed to add campaign recipient")?;
+    }
+
+    tx.commit().context("Failed to commit campaign")?;
+    Ok(campaign_id)
+}
+
+pub fn get_campaign(conn: &Connection, campaign_id: i64) -> Result<Option<MailCampaign>> {
+    conn.query_row(
+        &format!("SELECT {} FROM mail_campaigns WHERE id = ?1", CAMPAIGN_COLUMNS),
+        params![campaign_id],
+        campaign_from_row,
+    )
+    .optional()
+    .context("Failed to get campaign")
+}
+
+pub fn list_campaigns(conn: &Connection, account_id: &str) -> Result<Vec<MailCampaign>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM mail_campaigns WHERE account_id = ?1 ORDER BY created_at DESC, id DESC",
+        CAMPAIGN_COLUMNS
+    ))?;
+    let campaigns = stmt
+        .query_map(params![account_id], campaign_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list campaigns")?;
+    Ok(campaigns)
+}
+
+pub fn list_recipients(conn: &Connection, campaign_id: i64) -> Result<Vec<CampaignRecipient>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM campaign_recipients WHERE campaign_id = ?1 ORDER BY id ASC",
+        RECIPIENT_COLUMNS
+    ))?;
+    let recipients = stmt
+        .query_map(params![campaign_id], recipient_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list campaign recipients")?;
+    Ok(recipients)
+}
+
+/// Update a campaign's status, stamping start and completion times
+pub fn set_campaign_status(conn: &Connection, campaign_id: i64, status: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE mail_campaigns
+         SET status = ?1,
+             started_at = CASE WHEN ?1 = 'running' AND started_at IS NULL THEN CURRENT_TIMESTAMP ELSE started_at END,
+             completed_at = CASE WHEN ?1 IN ('completed', 'cancelled') THEN CURRENT_TIMESTAMP ELSE completed_at END
+         WHERE id = ?2",
+        params![status, campaign_id],
+    ).context("Failed to update campaign status")?;
+    Ok(())
+}
+
+/// Next recipient still waiting to be sent
+pub fn next_pending_recipient(conn: &Connection, campaign_id: i64) -> Result<Option<CampaignRecipient>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM campaign_recipients WHERE campaign_id = ?1 AND status = 'pending' ORDER BY id ASC LIMIT 1",
+            RECIPIENT_COLUMNS
+        ),
+        params![campaign_id],
+        recipient_from_row,
+    )
+    .optional()
+    .context("Failed to get next campaign recipient")
+}
+
+pub fn mark_recipient_sent(conn: &Connection, recipient_id: i64, message_id: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE campaign_recipients SET status = 'sent', message_id = ?1, error = NULL, sent_at = CURRENT_TIMESTAMP
+         WHERE id = ?2",
+        params![message_id, recipient_id],
+    ).context("Failed to mark recipient sent")?;
+    Ok(())
+}
+
+pub fn mark_recipient_failed(conn: &Connection, recipient_id: i64, error: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE campaign_recipients SET status = 'failed', error = ?1 WHERE id = ?2",
+        params![error, recipient_id],
+    ).context("Failed to mark recipient failed")?;
+    Ok(())
+}
+
+/// Queue failed recipients to be attempted again on the next run
+pub fn retry_failed_recipients(conn: &Connection, campaign_id: i64) -> Result<usize> {
+    conn.execute(
+        "UPDATE campaign_recipients SET status = 'pending', error = NULL WHERE campaign_id = ?1 AND status = 'failed'",
+        params![campaign_id],
+    ).context("Failed to requeue failed recipients")
+}
+
+pub fn campaign_progress(conn: &Connection, campaign_id: i64) -> Result<Option<CampaignProgress>> {
+    let Some(campaign) = get_campaign(conn, campaign_id)? else {
+        return Ok(None);
+    };
+
+    let (total, sent, failed, pending): (i64, i64, i64, i64) = conn.query_row(
+        "SELECT COUNT(*),
+                COALESCE(SUM(status = 'sent'), 0),
+                COALESCE(SUM(status = 'failed'), 0),
+                COALESCE(SUM(status = 'pending'), 0)
+         FROM campaign_recipients WHERE campaign_id = ?1",
+        params![campaign_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+    )?;
+
+    let percent_complete = if total == 0 { 100.0 } else { (sent + failed) as f64 * 100.0 / total as f64 };
+
+    Ok(Some(CampaignProgress {
+        campaign_id,
+        status: campaign.status,
+        total,
+        sent,
+        failed,
+        pending,
+        percent_complete,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    #[test]
+    fn test_campaign_progress_tracking() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let recipient = |email: &str| CampaignRecipientInput { email: email.to_string(), name: None, variables: HashMap::new() };
+        let campaign_id = create_campaign(
+            &mut conn,
+            &NewCampaign {
+                account_id: "acc".to_string(),
+                name: "Launch".to_string(),
+                subject_template: "Hi {{first_name}}".to_string(),
+                body_template: "Hello".to_string(),
+                is_html: false,
+                send_interval_ms: None,
+            },
+            &[recipient("a@example.com"), recipient("b@example.com"), recipient("a@example.com")],
+        )
+        .unwrap();
+
+        let first = next_pending_recipient(&conn, campaign_id).unwrap().unwrap();
+        mark_recipient_sent(&conn, first.id, "m-1").unwrap();
+        let second = next_pending_recipient(&conn, campaign_id).unwrap().unwrap();
+        mark_recipient_failed(&conn, second.id, "bounced").unwrap();
+
+        let progress = campaign_progress(&conn, campaign_id).unwrap().unwrap();
+        assert_eq!((progress.total, progress.sent, progress.failed, progress.pending), (2, 1, 1, 0));
+        assert_eq!(progress.percent_complete, 100.0);
+
+        assert_eq!(retry_failed_recipients(&conn, campaign_id).unwrap(), 1);
+        assert_eq!(next_pending_recipient(&conn, campaign_id).unwrap().unwrap().id, second.id);
+    }
+}
@@ -0,0 +1,92 @@
+//! AI generation history of canvas documents
+//!
+//! Every summary, outline or mind map generated for a canvas is kept with
+//! the selection it was made from, so the canvas can list and re-insert
+//! earlier results.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanvasGenerationRecord {
+    pub id: i64,
+    pub canvas_id: String,
+    pub action: String,
+    pub instructions: Option<String>,
+    /// JSON array of the selected elements
+    pub selection: String,
+    /// JSON of the generated summary or nodes and edges
+    pub result: String,
+    pub model: String,
+    pub created_at: String,
+}
+
+fn generation_from_row(row: &Row) -> rusqlite::Result<CanvasGenerationRecord> {
+    Ok(CanvasGenerationRecord {
+        id: row.get(0)?,
+        canvas_id: row.get(1)?,
+        action: row.get(2)?,
+        instructions: row.get(3)?,
+        selection: row.get(4)?,
+        result: row.get(5)?,
+        model: row.get(6)?,
+        created_at: row.get(7)?,
+    })
+}
+
+const GENERATION_COLUMNS: &str = "id, canvas_id, action, instructions, selection, result, model, created_at";
+
+pub fn save_generation(
+    conn: &Connection,
+    canvas_id: &str,
+    action: &str,
+    instructions: Option<&str>,
+    selection: &str,
+    result: &str,
+    model: &str,
+) -> Result<CanvasGenerationRecord> {
+    conn.execute(
+        "INSERT INTO canvas_ai_generations (canvas_id, action, instructions, selection, result, model)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![canvas_id, action, instructions, selection, result, model],
+    ).context("Failed to save canvas generation")?;
+
+    get_generation(conn, conn.last_insert_rowid())?.context("Saved canvas generation not found")
+}
+
+pub fn get_generation(conn: &Connection, id: i64) -> Result<Option<CanvasGenerationRecord>> {
+    conn.query_row(
+        &format!("SELECT {} FROM canvas_ai_generations WHERE id = ?1", GENERATION_COLUMNS),
+        params![id],
+        generation_from_row,
+    )
+    .optional()
+    .context("Failed to load canvas generation")
+}
+
+/// Generations of a canvas, newest first
+pub fn list_generations(conn: &Connection, canvas_id: &str, limit: i64) -> Result<Vec<CanvasGenerationRecord>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM canvas_ai_generations WHERE canvas_id = ?1 ORDER BY created_at DESC, id DESC LIMIT ?2",
+        GENERATION_COLUMNS
+    ))?;
+    let generations = stmt
+        .query_map(params![canvas_id, limit], generation_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list canvas generations")?;
+    Ok(generations)
+}
+
+pub fn delete_generation(conn: &Connection, id: i64) -> Result<bool> {
+    let deleted = conn
+        .execute("DELETE FROM canvas_ai_generations WHERE id = ?1", params![id])
+        .context("Failed to delete canvas generation")?;
+    Ok(deleted > 0)
+}
+
+/// Forget the history of a canvas, e.g. when the document is deleted
+pub fn clear_generations(conn: &Connection, canvas_id: &str) -> Result<usize> {
+    conn.execute("DELETE FROM canvas_ai_generations WHERE canvas_id = ?1", params![canvas_id])
+        .context("Failed to clear canvas generations")
+}
@@ -0,0 +1,392 @@
+//! Change journal database operations
+//!
+//! Every local mutation (task edit, label change, note update, ...) is
+//! appended to `change_journal` as an event carrying the entity type/id, the
+//! new field values and the values they replaced. The journal backs sync
+//! retries, undo and the activity timeline.
+//!
+//! Payloads are JSON objects of changed fields. Replaying the events of an
+//! entity folds them into its current state: `create` sets the state,
+//! `update` merges fields and `delete` clears it.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// A single journaled mutation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: i64,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub operation: String,
+    pub payload: Value,
+    pub previous_payload: Option<Value>,
+    pub account_id: Option<String>,
+    pub undone: bool,
+    pub synced_at: Option<String>,
+    pub created_at: String,
+}
+
+fn entry_from_row(row: &Row) -> rusqlite::Result<JournalEntry> {
+    let payload: String = row.get(4)?;
+    let previous: Option<String> = row.get(5)?;
+    Ok(JournalEntry {
+        id: row.get(0)?,
+        entity_type: row.get(1)?,
+        entity_id: row.get(2)?,
+        operation: row.get(3)?,
+        payload: serde_json::from_str(&payload).unwrap_or(Value::Null),
+        previous_payload: previous.and_then(|p| serde_json::from_str(&p).ok()),
+        account_id: row.get(6)?,
+        undone: row.get(7)?,
+        synced_at: row.get(8)?,
+        created_at: row.get(9)?,
+    })
+}
+
+const ENTRY_COLUMNS: &str =
+    "id, entity_type, entity_id, operation, payload, previous_payload, account_id, undone, synced_at, created_at";
+
+/// Append a mutation to the journal and return its ID
+pub fn record_change(
+    conn: &Connection,
+    entity_type: &str,
+    entity_id: &str,
+    operation: &str,
+    payload: &Value,
+    previous_payload: Option<&Value>,
+    account_id: Option<&str>,
+) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO change_journal (entity_type, entity_id, operation, payload, previous_payload, account_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            entity_type,
+            entity_id,
+            operation,
+            payload.to_string(),
+            previous_payload.map(|p| p.to_string()),
+            account_id,
+        ],
+    ).context("Failed to record change journal entry")?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+/// Get the events of an entity, oldest first
+pub fn get_entity_history(conn: &Connection, entity_type: &str, entity_id: &str) -> Result<Vec<JournalEntry>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM change_journal WHERE entity_type = ?1 AND entity_id = ?2 ORDER BY id ASC",
+        ENTRY_COLUMNS
+    ))?;
+
+    let entries = stmt
+        .query_map(params![entity_type, entity_id], entry_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to get entity history")?;
+
+    Ok(entries)
+}
+
+/// Get the most recent events across all entities, newest first
+pub fn get_recent_changes(conn: &Connection, limit: i64) -> Result<Vec<JournalEntry>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM change_journal ORDER BY id DESC LIMIT ?1",
+        ENTRY_COLUMNS
+    ))?;
+
+    let entries = stmt
+        .query_map(params![limit], entry_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to get recent changes")?;
+
+    Ok(entries)
+}
+
+/// Events recorded in [`start`, `end`) (UTC, "YYYY-MM-DD HH:MM:SS"),
+/// oldest first
+pub fn get_changes_between(conn: &Connection, start: &str, end: &str) -> Result<Vec<JournalEntry>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM change_journal WHERE created_at >= ?1 AND created_at < ?2 ORDER BY id ASC",
+        ENTRY_COLUMNS
+    ))?;
+
+    let entries = stmt
+        .query_map(params![start, end], entry_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to get changes in range")?;
+
+    Ok(entries)
+}
+
+/// Events that have not been acknowledged by a sync yet, oldest first
+pub fn list_unsynced_changes(conn: &Connection, entity_type: Option<&str>) -> Result<Vec<JournalEntry>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM change_journal
+         WHERE synced_at IS NULL AND (?1 IS NULL OR entity_type = ?1)
+         ORDER BY id ASC",
+        ENTRY_COLUMNS
+    ))?;
+
+    let entries = stmt
+        .query_map(params![entity_type], entry_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list unsynced changes")?;
+
+    Ok(entries)
+}
+
+/// Mark events as synced
+pub fn mark_changes_synced(conn: &Connection, entry_ids: &[i64]) -> Result<usize> {
+    let mut updated = 0;
+    for entry_id in entry_ids {
+        updated += conn.execute(
+            "UPDATE change_journal SET synced_at = CURRENT_TIMESTAMP WHERE id = ?1 AND synced_at IS NULL",
+            params![entry_id],
+        ).context("Failed to mark change synced")?;
+    }
+    Ok(updated)
+}
+
+/// Fold a sequence of events into the resulting entity state
+pub fn fold_events(entries: &[JournalEntry]) -> Option<Value> {
+    let mut state: Option<Map<String, Value>> = None;
+
+    for entry in entries {
+        match entry.operation.as_str() {
+            "create" => state = entry.payload.as_object().cloned(),
+            "update" => {
+                let current = state.get_or_insert_with(Map::new);
+                if let Some(fields) = entry.payload.as_object() {
+                    for (key, value) in fields {
+                        current.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+            "delete" => state = None,
+            _ => {}
+        }
+    }
+
+    state.map(Value::Object)
+}
+
+/// Rebuild the journaled state of an entity by replaying its events
+pub fn replay_entity(conn: &Connection, entity_type: &str, entity_id: &str) -> Result<Option<Value>> {
+    let entries = get_entity_history(conn, entity_type, entity_id)?;
+    Ok(fold_events(&entries))
+}
+
+/// The change [`undo_last_change`] would undo next, without undoing it.
+/// Callers that must apply the undo elsewhere first (e.g. at Gmail) look
+/// here, and only journal the undo once that succeeded.
+pub fn last_undoable_change(conn: &Connection, entity_type: &str, entity_id: &str) -> Result<Option<JournalEntry>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM change_journal
+             WHERE entity_type = ?1 AND entity_id = ?2 AND undone = 0
+             ORDER BY id DESC LIMIT 1",
+            ENTRY_COLUMNS
+        ),
+        params![entity_type, entity_id],
+        entry_from_row,
+    )
+    .optional()
+    .context("Failed to load last change")
+}
+
+/// The operation and payload that reverse a change
+pub fn inverse_of(entry: &JournalEntry) -> (&'static str, Value) {
+    match entry.operation.as_str() {
+        // Undoing a create deletes the entity again
+        "create" => ("delete", Value::Object(Map::new())),
+        // Undoing a delete recreates it from the captured previous state
+        "delete" => ("create", entry.previous_payload.clone().unwrap_or(Value::Object(Map::new()))),
+        _ => ("update", entry.previous_payload.clone().unwrap_or(Value::Object(Map::new()))),
+    }
+}
+
+/// Undo the most recent, not yet undone change of an entity.
+///
+/// Records the inverse event (so undo itself is journaled and can be synced)
+/// and returns it; the caller applies it to the live data.
+pub fn undo_last_change(conn: &Connection, entity_type: &str, entity_id: &str) -> Result<Option<JournalEntry>> {
+    let Some(last) = last_undoable_change(conn, entity_type, entity_id)? else {
+        return Ok(None);
+    };
+
+    let (operation, payload) = inverse_of(&last);
+
+    conn.execute("UPDATE change_journal SET undone = 1 WHERE id = ?1", params![last.id])
+        .context("Failed to flag change as undone")?;
+
+    let inverse_id = record_change(
+        conn,
+        entity_type,
+        entity_id,
+        operation,
+        &payload,
+        Some(&last.payload),
+        last.account_id.as_deref(),
+    )?;
+
+    // Inverse events are flagged too so repeated undo walks further back
+    // through history instead of undoing the undo
+    conn.execute("UPDATE change_journal SET undone = 1 WHERE id = ?1", params![inverse_id])
+        .context("Failed to flag undo entry")?;
+
+    conn.query_row(
+        &format!("SELECT {} FROM change_journal WHERE id = ?1", ENTRY_COLUMNS),
+        params![inverse_id],
+        entry_from_row,
+    )
+    .optional()
+    .context("Failed to load undo entry")
+}
+
+/// Compact synced history older than the given number of days.
+///
+/// Each entity's old synced events are collapsed into a single snapshot
+/// `create` event (or removed entirely if the entity ended up deleted), so
+/// replay still produces the same state with far fewer rows. Unsynced events
+/// are never touched.
+pub fn compact_journal(conn: &mut Connection, older_than_days: i64) -> Result<usize> {
+    let cutoff = format!("-{} days", older_than_days);
+    let tx = conn.transaction().context("Failed to start compaction transaction")?;
+
+    let entities: Vec<(String, String)> = {
+        let mut stmt = tx.prepare(
+            "SELECT entity_type, entity_id FROM change_journal
+             WHERE synced_at IS NOT NULL AND created_at < datetime('now', ?1)
+             GROUP BY entity_type, entity_id
+             HAVING COUNT(*) > 1 OR SUM(operation = 'delete') > 0",
+        )?;
+        let rows = stmt
+            .query_map(params![cutoff], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        rows
+    };
+
+    let mut removed = 0;
+
+    for (entity_type, entity_id) in entities {
+        let old_entries: Vec<JournalEntry> = {
+            let mut stmt = tx.prepare(&format!(
+                "SELECT {} FROM change_journal
+                 WHERE entity_type = ?1 AND entity_id = ?2
+                   AND synced_at IS NOT NULL AND created_at < datetime('now', ?3)
+                 ORDER BY id ASC",
+                ENTRY_COLUMNS
+            ))?;
+            let rows = stmt
+                .query_map(params![entity_type, entity_id, cutoff], entry_from_row)?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            rows
+        };
+
+        // Only a contiguous prefix can be compacted without changing replay
+        let first_unsynced: Option<i64> = tx
+            .query_row(
+                "SELECT MIN(id) FROM change_journal WHERE entity_type = ?1 AND entity_id = ?2 AND synced_at IS NULL",
+                params![entity_type, entity_id],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+        let prefix: Vec<JournalEntry> = old_entries
+            .into_iter()
+            .take_while(|e| first_unsynced.map(|u| e.id < u).unwrap_or(true))
+            .collect();
+
+        if prefix.is_empty() {
+            continue;
+        }
+
+        let snapshot = fold_events(&prefix);
+        let (first, rest) = prefix.split_first().unwrap();
+
+        for entry in rest {
+            removed += tx.execute("DELETE FROM change_journal WHERE id = ?1", params![entry.id])?;
+        }
+
+        // Rewrite the first event in place so the snapshot keeps its position
+        // ahead of any newer events during replay
+        match snapshot {
+            Some(state) => {
+                tx.execute(
+                    "UPDATE change_journal SET operation = 'create', payload = ?1, previous_payload = NULL WHERE id = ?2",
+                    params![state.to_string(), first.id],
+                )?;
+            }
+            None => {
+                removed += tx.execute("DELETE FROM change_journal WHERE id = ?1", params![first.id])?;
+            }
+        }
+    }
+
+    tx.commit().context("Failed to commit journal compaction")?;
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+    use serde_json::json;
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_replay_folds_events() {
+        let conn = setup_test_db();
+        record_change(&conn, "note", "1", "create", &json!({ "title": "A", "content": "x" }), None, None).unwrap();
+        record_change(&conn, "note", "1", "update", &json!({ "title": "B" }), Some(&json!({ "title": "A" })), None).unwrap();
+
+        let state = replay_entity(&conn, "note", "1").unwrap().unwrap();
+        assert_eq!(state, json!({ "title": "B", "content": "x" }));
+
+        record_change(&conn, "note", "1", "delete", &json!({}), Some(&state), None).unwrap();
+        assert!(replay_entity(&conn, "note", "1").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_undo_records_inverse_event() {
+        let conn = setup_test_db();
+        record_change(&conn, "note", "1", "create", &json!({ "title": "A" }), None, None).unwrap();
+        record_change(&conn, "note", "1", "update", &json!({ "title": "B" }), Some(&json!({ "title": "A" })), None).unwrap();
+
+        let inverse = undo_last_change(&conn, "note", "1").unwrap().unwrap();
+        assert_eq!(inverse.operation, "update");
+        assert_eq!(inverse.payload, json!({ "title": "A" }));
+        assert_eq!(replay_entity(&conn, "note", "1").unwrap().unwrap(), json!({ "title": "A" }));
+
+        // The next undo walks further back instead of undoing the undo
+        let inverse = undo_last_change(&conn, "note", "1").unwrap().unwrap();
+        assert_eq!(inverse.operation, "delete");
+    }
+
+    #[test]
+    fn test_compaction_preserves_replay() {
+        let mut conn = setup_test_db();
+        record_change(&conn, "task", "t", "create", &json!({ "title": "A" }), None, None).unwrap();
+        record_change(&conn, "task", "t", "update", &json!({ "title": "B" }), None, None).unwrap();
+        record_change(&conn, "task", "t", "update", &json!({ "notes": "n" }), None, None).unwrap();
+        conn.execute(
+            "UPDATE change_journal SET synced_at = datetime('now', '-40 days'), created_at = datetime('now', '-40 days')",
+            [],
+        ).unwrap();
+        record_change(&conn, "task", "t", "update", &json!({ "title": "C" }), None, None).unwrap();
+
+        let before = replay_entity(&conn, "task", "t").unwrap();
+        let removed = compact_journal(&mut conn, 30).unwrap();
+        assert_eq!(removed, 2);
+        assert_eq!(replay_entity(&conn, "task", "t").unwrap(), before);
+        assert_eq!(get_entity_history(&conn, "task", "t").unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,209 @@
+//! Files attached to chat sessions
+//!
+//! The extracted text of each file is split into chunks with their
+//! character offsets, so answers can cite the exact passage they used.
+//! Chunks are stored without embeddings, which are filled in later while
+//! the user is idle.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatAttachment {
+    pub id: i64,
+    pub session_id: i32,
+    pub source_key: String,
+    pub filename: String,
+    pub mime_type: String,
+    pub char_count: i64,
+    pub chunk_count: i64,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct AttachmentChunk {
+    pub attachment_id: i64,
+    pub chunk_index: i64,
+    pub start_offset: i64,
+    pub end_offset: i64,
+    pub content: String,
+    pub embedding: Option<Vec<f32>>,
+}
+
+/// A chunk to store; offsets are character positions in the extracted text
+#[derive(Debug, Clone)]
+pub struct NewChunk<'a> {
+    pub start_offset: usize,
+    pub end_offset: usize,
+    pub content: &'a str,
+}
+
+const ATTACHMENT_COLUMNS: &str = "a.id, a.session_id, a.source_key, a.filename, a.mime_type, a.char_count,
+    (SELECT COUNT(*) FROM chat_attachment_chunks c WHERE c.attachment_id = a.id), a.created_at";
+
+fn attachment_from_row(row: &Row) -> rusqlite::Result<ChatAttachment> {
+    Ok(ChatAttachment {
+        id: row.get(0)?,
+        session_id: row.get(1)?,
+        source_key: row.get(2)?,
+        filename: row.get(3)?,
+        mime_type: row.get(4)?,
+        char_count: row.get(5)?,
+        chunk_count: row.get(6)?,
+        created_at: row.get(7)?,
+    })
+}
+
+fn embedding_to_blob(embedding: &[f32]) -> Vec<u8> {
+    embedding.iter().flat_map(|value| value.to_le_bytes()).collect()
+}
+
+fn embedding_from_blob(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect()
+}
+
+/// Store a file and its chunks, replacing an earlier copy of the same source
+/// in the session
+pub fn save_attachment(
+    conn: &mut Connection,
+    session_id: i32,
+    source_key: &str,
+    filename: &str,
+    mime_type: &str,
+    char_count: usize,
+    chunks: &[NewChunk],
+) -> Result<ChatAttachment> {
+    let tx = conn.transaction()?;
+    tx.execute(
+        "DELETE FROM chat_attachments WHERE session_id = ?1 AND source_key = ?2",
+        params![session_id, source_key],
+    ).context("Failed to replace chat attachment")?;
+    tx.execute(
+        "INSERT INTO chat_attachments (session_id, source_key, filename, mime_type, char_count)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![session_id, source_key, filename, mime_type, char_count as i64],
+    ).context("Failed to save chat attachment")?;
+    let attachment_id = tx.last_insert_rowid();
+
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO chat_attachment_chunks (attachment_id, chunk_index, start_offset, end_offset, content)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        for (index, chunk) in chunks.iter().enumerate() {
+            stmt.execute(params![
+                attachment_id,
+                index as i64,
+                chunk.start_offset as i64,
+                chunk.end_offset as i64,
+                chunk.content,
+            ]).context("Failed to save chat attachment chunk")?;
+        }
+    }
+
+    let attachment = get_attachment(&tx, attachment_id)?.context("Chat attachment missing after insert")?;
+    tx.commit()?;
+    Ok(attachment)
+}
+
+pub fn get_attachment(conn: &Connection, id: i64) -> Result<Option<ChatAttachment>> {
+    conn.query_row(
+        &format!("SELECT {} FROM chat_attachments a WHERE a.id = ?1", ATTACHMENT_COLUMNS),
+        params![id],
+        attachment_from_row,
+    )
+    .optional()
+    .context("Failed to load chat attachment")
+}
+
+pub fn list_attachments(conn: &Connection, session_id: i32) -> Result<Vec<ChatAttachment>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM chat_attachments a WHERE a.session_id = ?1 ORDER BY a.created_at, a.id",
+        ATTACHMENT_COLUMNS
+    ))?;
+    let attachments = stmt
+        .query_map(params![session_id], attachment_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list chat attachments")?;
+    Ok(attachments)
+}
+
+/// Every chunk of every file attached to a session
+pub fn list_session_chunks(conn: &Connection, session_id: i32) -> Result<Vec<AttachmentChunk>> {
+    let mut stmt = conn.prepare(
+        "SELECT c.attachment_id, c.chunk_index, c.start_offset, c.end_offset, c.content, c.embedding
+         FROM chat_attachment_chunks c JOIN chat_attachments a ON a.id = c.attachment_id
+         WHERE a.session_id = ?1 ORDER BY c.attachment_id, c.chunk_index",
+    )?;
+    let chunks = stmt
+        .query_map(params![session_id], |row| {
+            let embedding: Option<Vec<u8>> = row.get(5)?;
+            Ok(AttachmentChunk {
+                attachment_id: row.get(0)?,
+                chunk_index: row.get(1)?,
+                start_offset: row.get(2)?,
+                end_offset: row.get(3)?,
+                content: row.get(4)?,
+                embedding: embedding.as_deref().map(embedding_from_blob),
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to load chat attachment chunks")?;
+    Ok(chunks)
+}
+
+/// The oldest attachment with chunks still to be embedded
+pub fn next_unembedded_attachment(conn: &Connection) -> Result<Option<i64>> {
+    conn.query_row(
+        "SELECT attachment_id FROM chat_attachment_chunks WHERE embedding IS NULL ORDER BY attachment_id LIMIT 1",
+        [],
+        |row| row.get(0),
+    )
+    .optional()
+    .context("Failed to find chat attachment chunks to embed")
+}
+
+/// Chunk index and text of an attachment's chunks without an embedding
+pub fn unembedded_chunks(conn: &Connection, attachment_id: i64) -> Result<Vec<(i64, String)>> {
+    let mut stmt = conn.prepare(
+        "SELECT chunk_index, content FROM chat_attachment_chunks
+         WHERE attachment_id = ?1 AND embedding IS NULL ORDER BY chunk_index",
+    )?;
+    let chunks = stmt
+        .query_map(params![attachment_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to load chat attachment chunks to embed")?;
+    Ok(chunks)
+}
+
+/// Store the embeddings of an attachment's chunks together, so a file is
+/// never left half embedded by different models
+pub fn set_chunk_embeddings(conn: &mut Connection, attachment_id: i64, embeddings: &[(i64, Vec<f32>)]) -> Result<()> {
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare(
+            "UPDATE chat_attachment_chunks SET embedding = ?3 WHERE attachment_id = ?1 AND chunk_index = ?2",
+        )?;
+        for (chunk_index, embedding) in embeddings {
+            stmt.execute(params![attachment_id, chunk_index, embedding_to_blob(embedding)])
+                .context("Failed to save chat attachment chunk embedding")?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Remove a file and its chunks together
+pub fn delete_attachment(conn: &mut Connection, id: i64) -> Result<bool> {
+    let tx = conn.transaction()?;
+    tx.execute("DELETE FROM chat_attachment_chunks WHERE attachment_id = ?1", params![id])
+        .context("Failed to delete chat attachment chunks")?;
+    let deleted = tx
+        .execute("DELETE FROM chat_attachments WHERE id = ?1", params![id])
+        .context("Failed to delete chat attachment")?;
+    tx.commit()?;
+    Ok(deleted > 0)
+}
@@ -0,0 +1,99 @@
+//! Captures offered by the clipboard watcher
+//!
+//! Each URL, email address or pattern match copied while the watcher is on
+//! is kept here so the capture list survives the notification being missed.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+/// Captures kept; older ones are pruned when new ones are recorded
+pub const MAX_CLIPBOARD_CAPTURES: i64 = 200;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipboardCaptureRecord {
+    pub id: i64,
+    pub kind: String,
+    pub pattern_name: Option<String>,
+    pub content: String,
+    /// offered, captured or dismissed
+    pub status: String,
+    pub created_at: String,
+}
+
+fn capture_from_row(row: &Row) -> rusqlite::Result<ClipboardCaptureRecord> {
+    Ok(ClipboardCaptureRecord {
+        id: row.get(0)?,
+        kind: row.get(1)?,
+        pattern_name: row.get(2)?,
+        content: row.get(3)?,
+        status: row.get(4)?,
+        created_at: row.get(5)?,
+    })
+}
+
+const CAPTURE_COLUMNS: &str = "id, kind, pattern_name, content, status, created_at";
+
+pub fn record_capture(
+    conn: &Connection,
+    kind: &str,
+    pattern_name: Option<&str>,
+    content: &str,
+) -> Result<ClipboardCaptureRecord> {
+    conn.execute(
+        "INSERT INTO clipboard_captures (kind, pattern_name, content) VALUES (?1, ?2, ?3)",
+        params![kind, pattern_name, content],
+    ).context("Failed to record clipboard capture")?;
+    let id = conn.last_insert_rowid();
+
+    conn.execute(
+        "DELETE FROM clipboard_captures WHERE id NOT IN (
+            SELECT id FROM clipboard_captures ORDER BY id DESC LIMIT ?1
+        )",
+        params![MAX_CLIPBOARD_CAPTURES],
+    ).context("Failed to prune clipboard captures")?;
+
+    get_capture(conn, id)?.context("Recorded clipboard capture not found")
+}
+
+pub fn get_capture(conn: &Connection, id: i64) -> Result<Option<ClipboardCaptureRecord>> {
+    conn.query_row(
+        &format!("SELECT {} FROM clipboard_captures WHERE id = ?1", CAPTURE_COLUMNS),
+        params![id],
+        capture_from_row,
+    )
+    .optional()
+    .context("Failed to load clipboard capture")
+}
+
+/// Most recent captures first
+pub fn list_recent(conn: &Connection, limit: i64) -> Result<Vec<ClipboardCaptureRecord>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM clipboard_captures ORDER BY id DESC LIMIT ?1",
+        CAPTURE_COLUMNS
+    ))?;
+    let captures = stmt
+        .query_map(params![limit], capture_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list clipboard captures")?;
+    Ok(captures)
+}
+
+pub fn set_status(conn: &Connection, id: i64, status: &str) -> Result<bool> {
+    let updated = conn
+        .execute("UPDATE clipboard_captures SET status = ?2 WHERE id = ?1", params![id, status])
+        .context("Failed to update clipboard capture")?;
+    Ok(updated > 0)
+}
+
+pub fn delete_capture(conn: &Connection, id: i64) -> Result<bool> {
+    let deleted = conn
+        .execute("DELETE FROM clipboard_captures WHERE id = ?1", params![id])
+        .context("Failed to delete clipboard capture")?;
+    Ok(deleted > 0)
+}
+
+pub fn clear_captures(conn: &Connection) -> Result<usize> {
+    conn.execute("DELETE FROM clipboard_captures", [])
+        .context("Failed to clear clipboard captures")
+}
@@ -0,0 +1,300 @@
+//! Contact database operations
+//!
+//! Local copy of each account's Google Contacts, maintained by the People
+//! API sync and used for compose autocomplete and sender name resolution.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContactEmail {
+    pub email: String,
+    pub label: Option<String>,
+    pub is_primary: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Contact {
+    pub id: i64,
+    pub account_id: String,
+    pub resource_name: String,
+    pub etag: Option<String>,
+    pub display_name: Option<String>,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+    pub photo_url: Option<String>,
+    pub organization: Option<String>,
+    pub job_title: Option<String>,
+    pub emails: Vec<ContactEmail>,
+    pub updated_at: String,
+}
+
+/// Contact fields as received from the People API
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContactInput {
+    pub resource_name: String,
+    pub etag: Option<String>,
+    pub display_name: Option<String>,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+    pub photo_url: Option<String>,
+    pub organization: Option<String>,
+    pub job_title: Option<String>,
+    pub emails: Vec<ContactEmail>,
+}
+
+/// A single address suggestion for compose autocomplete
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContactSuggestion {
+    pub contact_id: i64,
+    pub display_name: Option<String>,
+    pub email: String,
+    pub photo_url: Option<String>,
+}
+
+const CONTACT_COLUMNS: &str = "id, account_id, resource_name, etag, display_name, given_name, family_name,
+    photo_url, organization, job_title, updated_at";
+
+fn contact_from_row(row: &Row) -> rusqlite::Result<Contact> {
+    Ok(Contact {
+        id: row.get(0)?,
+        account_id: row.get(1)?,
+        resource_name: row.get(2)?,
+        etag: row.get(3)?,
+        display_name: row.get(4)?,
+        given_name: row.get(5)?,
+        family_name: row.get(6)?,
+        photo_url: row.get(7)?,
+        organization: row.get(8)?,
+        job_title: row.get(9)?,
+        emails: Vec::new(),
+        updated_at: row.get(10)?,
+    })
+}
+
+fn load_emails(conn: &Connection, contact_id: i64) -> Result<Vec<ContactEmail>> {
+    let mut stmt = conn.prepare(
+        "SELECT email, label, is_primary FROM contact_emails WHERE contact_id = ?1 ORDER BY is_primary DESC, id ASC",
+    )?;
+    let emails = stmt
+        .query_map(params![contact_id], |row| {
+            Ok(ContactEmail {
+                email: row.get(0)?,
+                label: row.get(1)?,
+                is_primary: row.get(2)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to load contact emails")?;
+    Ok(emails)
+}
+
+/// Insert or update a contact and replace its email addresses
+pub fn upsert_contact(conn: &Connection, account_id: &str, contact: &ContactInput) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO contacts
+            (account_id, resource_name, etag, display_name, given_name, family_name, photo_url, organization, job_title)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+         ON CONFLICT(account_id, resource_name) DO UPDATE SET
+            etag = excluded.etag,
+            display_name = excluded.display_name,
+            given_name = excluded.given_name,
+            family_name = excluded.family_name,
+            photo_url = excluded.photo_url,
+            organization = excluded.organization,
+            job_title = excluded.job_title,
+            updated_at = CURRENT_TIMESTAMP",
+        params![
+            account_id,
+            contact.resource_name,
+            contact.etag,
+            contact.display_name,
+            contact.given_name,
+            contact.family_name,
+            contact.photo_url,
+            contact.organization,
+            contact.job_title,
+        ],
+    ).context("Failed to upsert contact")?;
+
+    let contact_id: i64 = conn.query_row(
+        "SELECT id FROM contacts WHERE account_id = ?1 AND resource_name = ?2",
+        params![account_id, contact.resource_name],
+        |row| row.get(0),
+    )?;
+
+    conn.execute("DELETE FROM contact_emails WHERE contact_id = ?1", params![contact_id])
+        .context("Failed to clear contact emails")?;
+    for email in &contact.emails {
+        conn.execute(
+            "INSERT INTO contact_emails (contact_id, email, label, is_primary) VALUES (?1, ?2, ?3, ?4)",
+            params![contact_id, email.email.trim(), email.label, email.is_primary],
+        ).context("Failed to insert contact email")?;
+    }
+
+    Ok(contact_id)
+}
+
+/// Delete a contact removed on the server
+pub fn delete_contact(conn: &Connection, account_id: &str, resource_name: &str) -> Result<usize> {
+    conn.execute(
+        "DELETE FROM contact_emails WHERE contact_id IN
+            (SELECT id FROM contacts WHERE account_id = ?1 AND resource_name = ?2)",
+        params![account_id, resource_name],
+    )?;
+    conn.execute(
+        "DELETE FROM contacts WHERE account_id = ?1 AND resource_name = ?2",
+        params![account_id, resource_name],
+    ).context("Failed to delete contact")
+}
+
+/// Remove every contact of an account (before a full resync)
+pub fn clear_contacts(conn: &Connection, account_id: &str) -> Result<usize> {
+    conn.execute(
+        "DELETE FROM contact_emails WHERE contact_id IN (SELECT id FROM contacts WHERE account_id = ?1)",
+        params![account_id],
+    )?;
+    conn.execute("DELETE FROM contacts WHERE account_id = ?1", params![account_id])
+        .context("Failed to clear contacts")
+}
+
+pub fn get_contact(conn: &Connection, contact_id: i64) -> Result<Option<Contact>> {
+    let contact = conn
+        .query_row(
+            &format!("SELECT {} FROM contacts WHERE id = ?1", CONTACT_COLUMNS),
+            params![contact_id],
+            contact_from_row,
+        )
+        .optional()
+        .context("Failed to get contact")?;
+
+    match contact {
+        Some(mut contact) => {
+            contact.emails = load_emails(conn, contact.id)?;
+            Ok(Some(contact))
+        }
+        None => Ok(None),
+    }
+}
+
+/// List an account's contacts ordered by name
+pub fn list_contacts(conn: &Connection, account_id: &str) -> Result<Vec<Contact>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM contacts WHERE account_id = ?1 ORDER BY display_name COLLATE NOCASE ASC",
+        CONTACT_COLUMNS
+    ))?;
+    let mut contacts = stmt
+        .query_map(params![account_id], contact_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list contacts")?;
+
+    for contact in &mut contacts {
+        contact.emails = load_emails(conn, contact.id)?;
+    }
+    Ok(contacts)
+}
+
+/// Autocomplete suggestions matching the start of a name or email address
+pub fn search_contacts(conn: &Connection, account_id: Option<&str>, prefix: &str, limit: i64) -> Result<Vec<ContactSuggestion>> {
+    let prefix = prefix.trim().replace('%', "\\%").replace('_', "\\_");
+    let pattern = format!("{}%", prefix);
+    let word_pattern = format!("% {}%", prefix);
+
+    let mut stmt = conn.prepare(
+        "SELECT c.id, c.display_name, e.email, c.photo_url
+         FROM contact_emails e JOIN contacts c ON c.id = e.contact_id
+         WHERE (?1 IS NULL OR c.account_id = ?1)
+           AND (e.email LIKE ?2 ESCAPE '\\'
+                OR c.display_name LIKE ?2 ESCAPE '\\'
+                OR c.display_name LIKE ?3 ESCAPE '\\')
+         ORDER BY e.is_primary DESC, c.display_name COLLATE NOCASE ASC
+         LIMIT ?4",
+    )?;
+    let suggestions = stmt
+        .query_map(params![account_id, pattern, word_pattern, limit], |row| {
+            Ok(ContactSuggestion {
+                contact_id: row.get(0)?,
+                display_name: row.get(1)?,
+                email: row.get(2)?,
+                photo_url: row.get(3)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to search contacts")?;
+    Ok(suggestions)
+}
+
+/// Display name stored for an email address, if any contact has it
+pub fn resolve_display_name(conn: &Connection, email: &str) -> Result<Option<String>> {
+    conn.query_row(
+        "SELECT c.display_name FROM contact_emails e JOIN contacts c ON c.id = e.contact_id
+         WHERE e.email = ?1 AND c.display_name IS NOT NULL
+         ORDER BY e.is_primary DESC LIMIT 1",
+        params![email.trim()],
+        |row| row.get(0),
+    )
+    .optional()
+    .context("Failed to resolve contact name")
+}
+
+pub fn get_sync_token(conn: &Connection, account_id: &str) -> Result<Option<String>> {
+    Ok(conn
+        .query_row(
+            "SELECT sync_token FROM contact_sync_state WHERE account_id = ?1",
+            params![account_id],
+            |row| row.get::<_, Option<String>>(0),
+        )
+        .optional()
+        .context("Failed to get contact sync token")?
+        .flatten())
+}
+
+pub fn set_sync_token(conn: &Connection, account_id: &str, sync_token: Option<&str>) -> Result<()> {
+    conn.execute(
+        "INSERT INTO contact_sync_state (account_id, sync_token, last_synced_at)
+         VALUES (?1, ?2, CURRENT_TIMESTAMP)
+         ON CONFLICT(account_id) DO UPDATE SET sync_token = excluded.sync_token, last_synced_at = CURRENT_TIMESTAMP",
+        params![account_id, sync_token],
+    ).context("Failed to store contact sync token")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    fn contact(resource: &str, name: &str, email: &str) -> ContactInput {
+        ContactInput {
+            resource_name: resource.to_string(),
+            display_name: Some(name.to_string()),
+            emails: vec![ContactEmail { email: email.to_string(), label: None, is_primary: true }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_upsert_search_and_resolve() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        upsert_contact(&conn, "acc", &contact("people/1", "Ada Lovelace", "ada@example.com")).unwrap();
+        upsert_contact(&conn, "acc", &contact("people/2", "Alan Turing", "alan@example.com")).unwrap();
+        // Updating replaces the email list instead of appending
+        upsert_contact(&conn, "acc", &contact("people/1", "Ada King", "ada@example.org")).unwrap();
+
+        let by_word = search_contacts(&conn, Some("acc"), "kin", 10).unwrap();
+        assert_eq!(by_word.len(), 1);
+        assert_eq!(by_word[0].email, "ada@example.org");
+
+        let by_email = search_contacts(&conn, None, "al", 10).unwrap();
+        assert_eq!(by_email.len(), 1);
+
+        assert_eq!(resolve_display_name(&conn, "ADA@example.org").unwrap().as_deref(), Some("Ada King"));
+        assert!(resolve_display_name(&conn, "ada@example.com").unwrap().is_none());
+
+        delete_contact(&conn, "acc", "people/2").unwrap();
+        assert_eq!(list_contacts(&conn, "acc").unwrap().len(), 1);
+    }
+}
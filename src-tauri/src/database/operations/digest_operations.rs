@@ -0,0 +1,137 @@
+//! Daily digest queries
+//!
+//! The digest is built from the local copies the app already keeps: mail
+//! from the Gmail message store, tasks and events from `search_items`. It
+//! reads whatever was last synced, so it can be compiled offline.
+
+use anyhow::{Context, Result};
+use rusqlite::{params_from_iter, Connection, Row};
+use serde::{Deserialize, Serialize};
+
+use super::workspace_operations::HiddenIds;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestMessage {
+    pub account_id: String,
+    pub message_id: String,
+    pub thread_id: String,
+    pub subject: String,
+    pub from: String,
+    pub snippet: String,
+    /// RFC 3339
+    pub received_at: Option<String>,
+}
+
+fn message_from_row(row: &Row) -> rusqlite::Result<DigestMessage> {
+    let internal_date: Option<i64> = row.get(6)?;
+    Ok(DigestMessage {
+        account_id: row.get(0)?,
+        message_id: row.get(1)?,
+        thread_id: row.get(2)?,
+        subject: row.get(3)?,
+        from: row.get(4)?,
+        snippet: row.get(5)?,
+        received_at: internal_date
+            .and_then(chrono::DateTime::from_timestamp_millis)
+            .map(|time| time.to_rfc3339()),
+    })
+}
+
+/// A task or event from the local search copy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestItem {
+    pub account_id: String,
+    pub item_id: String,
+    /// Task list or calendar
+    pub container_id: String,
+    pub title: String,
+    /// Due date or start time, UTC RFC 3339
+    pub time: Option<String>,
+    /// An event taking the whole day; `time` is midnight UTC of its date
+    #[serde(default)]
+    pub all_day: bool,
+}
+
+fn item_from_row(row: &Row) -> rusqlite::Result<DigestItem> {
+    Ok(DigestItem {
+        account_id: row.get(0)?,
+        item_id: row.get(1)?,
+        container_id: row.get(2)?,
+        title: row.get(3)?,
+        time: row.get(4)?,
+        all_day: row.get(5)?,
+    })
+}
+
+/// Stored messages carrying every one of `labels`, newest first, outside
+/// the `hidden` accounts. Imported archives are left out; their flags
+/// belong to the old mail client, not to today's work.
+pub fn messages_with_labels(conn: &Connection, labels: &[&str], hidden: &HiddenIds, limit: i64) -> Result<Vec<DigestMessage>> {
+    let mut sql = String::from(
+        "SELECT s.account_id, s.message_id, s.thread_id,
+                coalesce(json_extract(s.message_data, '$.parsed_content.subject'), '(no subject)'),
+                coalesce(nullif(json_extract(s.message_data, '$.parsed_content.from.name'), ''),
+                         json_extract(s.message_data, '$.parsed_content.from.email'), ''),
+                coalesce(json_extract(s.message_data, '$.snippet'), ''),
+                s.internal_date
+         FROM gmail_message_store s WHERE s.account_id NOT IN (SELECT value FROM json_each(?1))
+           AND s.account_id NOT IN (SELECT id FROM archive_accounts)",
+    );
+    let mut values: Vec<rusqlite::types::Value> = vec![hidden.accounts.clone().into()];
+    for label in labels {
+        values.push(label.to_string().into());
+        sql.push_str(&format!(
+            " AND EXISTS (SELECT 1 FROM json_each(s.label_ids) WHERE value = ?{})",
+            values.len()
+        ));
+    }
+    values.push(limit.into());
+    sql.push_str(&format!(" ORDER BY s.internal_date DESC LIMIT ?{}", values.len()));
+
+    let mut stmt = conn.prepare(&sql)?;
+    let messages = stmt
+        .query_map(params_from_iter(values), message_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list messages for the digest")?;
+    Ok(messages)
+}
+
+/// Events of the local day `date` ("YYYY-MM-DD") that were not cancelled:
+/// timed events starting in [`start`, `end`) (UTC RFC 3339), and all-day
+/// events of that date, which are stored at midnight UTC of it. Events of
+/// `hidden` accounts and calendars are left out.
+pub fn events_on(conn: &Connection, date: &str, start: &str, end: &str, hidden: &HiddenIds) -> Result<Vec<DigestItem>> {
+    let mut stmt = conn.prepare(
+        "SELECT account_id, item_id, container_id, title, item_time, all_day FROM search_items
+         WHERE domain = 'event' AND coalesce(status, '') != 'cancelled'
+           AND ((all_day = 1 AND substr(item_time, 1, 10) = ?1)
+             OR (all_day = 0 AND item_time >= ?2 AND item_time < ?3))
+           AND account_id NOT IN (SELECT value FROM json_each(?4))
+           AND container_id NOT IN (SELECT value FROM json_each(?5))
+         ORDER BY item_time, title",
+    )?;
+    let events = stmt
+        .query_map([date, start, end, &hidden.accounts, &hidden.containers], item_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list events for the digest")?;
+    Ok(events)
+}
+
+/// Open tasks due on or before `date` ("YYYY-MM-DD"), oldest first.
+/// Google keeps due dates as midnight UTC of the chosen day, so only the
+/// date part is compared. Tasks of `hidden` accounts and lists are left out.
+pub fn open_tasks_due_by(conn: &Connection, date: &str, hidden: &HiddenIds) -> Result<Vec<DigestItem>> {
+    let mut stmt = conn.prepare(
+        "SELECT account_id, item_id, container_id, title, item_time, all_day FROM search_items
+         WHERE domain = 'task' AND item_time IS NOT NULL AND substr(item_time, 1, 10) <= ?1
+           AND coalesce(status, '') != 'completed'
+           AND account_id NOT IN (SELECT value FROM json_each(?2))
+           AND container_id NOT IN (SELECT value FROM json_each(?3))
+         ORDER BY item_time, title",
+    )?;
+    let tasks = stmt
+        .query_map([date, &hidden.accounts, &hidden.containers], item_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list tasks for the digest")?;
+    Ok(tasks)
+}
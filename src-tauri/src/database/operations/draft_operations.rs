@@ -0,0 +1,227 @@
+//! Gmail draft cache operations
+//!
+//! Drafts are cached locally so they can be listed and edited offline.
+//! Offline edits are flagged with a pending sync status and pushed by the
+//! compose service once Gmail is reachable again.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedDraft {
+    pub local_id: String,
+    pub account_id: String,
+    pub draft_id: Option<String>,
+    pub message_id: Option<String>,
+    pub thread_id: Option<String>,
+    pub compose_data: serde_json::Value,
+    pub sync_status: String,
+    pub last_error: Option<String>,
+    pub updated_at: String,
+    pub synced_at: Option<String>,
+}
+
+const DRAFT_COLUMNS: &str = "local_id, account_id, draft_id, message_id, thread_id, compose_data,
+    sync_status, last_error, updated_at, synced_at";
+
+fn draft_from_row(row: &Row) -> rusqlite::Result<CachedDraft> {
+    let compose_data: String = row.get(5)?;
+    Ok(CachedDraft {
+        local_id: row.get(0)?,
+        account_id: row.get(1)?,
+        draft_id: row.get(2)?,
+        message_id: row.get(3)?,
+        thread_id: row.get(4)?,
+        compose_data: serde_json::from_str(&compose_data).unwrap_or(serde_json::Value::Null),
+        sync_status: row.get(6)?,
+        last_error: row.get(7)?,
+        updated_at: row.get(8)?,
+        synced_at: row.get(9)?,
+    })
+}
+
+/// Find a draft by its Gmail draft ID or its local ID
+pub fn find_draft(conn: &Connection, account_id: &str, id: &str) -> Result<Option<CachedDraft>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM gmail_drafts WHERE account_id = ?1 AND (draft_id = ?2 OR local_id = ?2)",
+            DRAFT_COLUMNS
+        ),
+        params![account_id, id],
+        draft_from_row,
+    )
+    .optional()
+    .context("Failed to get cached draft")
+}
+
+/// List an account's drafts, newest first, excluding ones queued for deletion
+pub fn list_drafts(conn: &Connection, account_id: &str) -> Result<Vec<CachedDraft>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM gmail_drafts
+         WHERE account_id = ?1 AND sync_status != 'pending_delete'
+         ORDER BY updated_at DESC",
+        DRAFT_COLUMNS
+    ))?;
+    let drafts = stmt
+        .query_map(params![account_id], draft_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list cached drafts")?;
+    Ok(drafts)
+}
+
+/// List drafts with local changes that have not reached Gmail yet
+pub fn list_unsynced_drafts(conn: &Connection, account_id: &str) -> Result<Vec<CachedDraft>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM gmail_drafts
+         WHERE account_id = ?1 AND sync_status != 'synced'
+         ORDER BY updated_at ASC",
+        DRAFT_COLUMNS
+    ))?;
+    let drafts = stmt
+        .query_map(params![account_id], draft_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list unsynced drafts")?;
+    Ok(drafts)
+}
+
+/// Save a local edit; drafts without a Gmail ID stay pending creation
+pub fn save_local_draft(
+    conn: &Connection,
+    local_id: &str,
+    account_id: &str,
+    draft_id: Option<&str>,
+    thread_id: Option<&str>,
+    compose_data: &serde_json::Value,
+) -> Result<()> {
+    let status = if draft_id.is_some() { "pending_update" } else { "pending_create" };
+    conn.execute(
+        "INSERT INTO gmail_drafts (local_id, account_id, draft_id, thread_id, compose_data, sync_status)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(local_id) DO UPDATE SET
+            thread_id = excluded.thread_id,
+            compose_data = excluded.compose_data,
+            sync_status = CASE WHEN gmail_drafts.draft_id IS NULL THEN 'pending_create' ELSE 'pending_update' END,
+            updated_at = CURRENT_TIMESTAMP",
+        params![local_id, account_id, draft_id, thread_id, compose_data.to_string(), status],
+    ).context("Failed to save local draft")?;
+    Ok(())
+}
+
+/// Cache a draft exactly as it exists in Gmail
+pub fn cache_remote_draft(
+    conn: &Connection,
+    local_id: &str,
+    account_id: &str,
+    draft_id: &str,
+    message_id: &str,
+    thread_id: Option<&str>,
+    compose_data: &serde_json::Value,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO gmail_drafts
+            (local_id, account_id, draft_id, message_id, thread_id, compose_data, sync_status, synced_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'synced', CURRENT_TIMESTAMP)
+         ON CONFLICT(local_id) DO UPDATE SET
+            draft_id = excluded.draft_id,
+            message_id = excluded.message_id,
+            thread_id = excluded.thread_id,
+            compose_data = excluded.compose_data,
+            sync_status = 'synced',
+            last_error = NULL,
+            updated_at = CURRENT_TIMESTAMP,
+            synced_at = CURRENT_TIMESTAMP",
+        params![local_id, account_id, draft_id, message_id, thread_id, compose_data.to_string()],
+    ).context("Failed to cache remote draft")?;
+    Ok(())
+}
+
+/// Record that a draft's local state now matches Gmail
+pub fn mark_draft_synced(conn: &Connection, local_id: &str, draft_id: &str, message_id: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE gmail_drafts
+         SET draft_id = ?1, message_id = ?2, sync_status = 'synced', last_error = NULL, synced_at = CURRENT_TIMESTAMP
+         WHERE local_id = ?3",
+        params![draft_id, message_id, local_id],
+    ).context("Failed to mark draft synced")?;
+    Ok(())
+}
+
+pub fn set_draft_error(conn: &Connection, local_id: &str, error: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE gmail_drafts SET last_error = ?1 WHERE local_id = ?2",
+        params![error, local_id],
+    ).context("Failed to record draft error")?;
+    Ok(())
+}
+
+/// Queue a draft for deletion, or drop it outright if Gmail never saw it
+pub fn mark_draft_deleted(conn: &Connection, local_id: &str) -> Result<()> {
+    conn.execute(
+        "DELETE FROM gmail_drafts WHERE local_id = ?1 AND draft_id IS NULL",
+        params![local_id],
+    )?;
+    conn.execute(
+        "UPDATE gmail_drafts SET sync_status = 'pending_delete', updated_at = CURRENT_TIMESTAMP WHERE local_id = ?1",
+        params![local_id],
+    ).context("Failed to mark draft deleted")?;
+    Ok(())
+}
+
+pub fn remove_draft(conn: &Connection, local_id: &str) -> Result<usize> {
+    conn.execute("DELETE FROM gmail_drafts WHERE local_id = ?1", params![local_id])
+        .context("Failed to remove cached draft")
+}
+
+/// Drop synced drafts that no longer exist in Gmail
+pub fn prune_remote_drafts(conn: &Connection, account_id: &str, remote_ids: &[String]) -> Result<usize> {
+    let cached: Vec<(String, String)> = {
+        let mut stmt = conn.prepare(
+            "SELECT local_id, draft_id FROM gmail_drafts
+             WHERE account_id = ?1 AND sync_status = 'synced' AND draft_id IS NOT NULL",
+        )?;
+        let rows = stmt
+            .query_map(params![account_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        rows
+    };
+
+    let mut removed = 0;
+    for (local_id, draft_id) in cached {
+        if !remote_ids.contains(&draft_id) {
+            removed += remove_draft(conn, &local_id)?;
+        }
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    #[test]
+    fn test_offline_draft_lifecycle() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        let data = serde_json::json!({ "subject": "Hello" });
+
+        save_local_draft(&conn, "local-1", "acc", None, None, &data).unwrap();
+        assert_eq!(find_draft(&conn, "acc", "local-1").unwrap().unwrap().sync_status, "pending_create");
+
+        mark_draft_synced(&conn, "local-1", "r-1", "m-1").unwrap();
+        save_local_draft(&conn, "local-1", "acc", None, None, &data).unwrap();
+        let draft = find_draft(&conn, "acc", "r-1").unwrap().unwrap();
+        assert_eq!(draft.sync_status, "pending_update");
+        assert_eq!(list_unsynced_drafts(&conn, "acc").unwrap().len(), 1);
+
+        // A deleted draft Gmail knows about waits for the push; a never-synced one disappears
+        mark_draft_deleted(&conn, "local-1").unwrap();
+        assert!(list_drafts(&conn, "acc").unwrap().is_empty());
+        assert!(find_draft(&conn, "acc", "local-1").unwrap().is_some());
+
+        save_local_draft(&conn, "local-2", "acc", None, None, &data).unwrap();
+        mark_draft_deleted(&conn, "local-2").unwrap();
+        assert!(find_draft(&conn, "acc", "local-2").unwrap().is_none());
+    }
+}
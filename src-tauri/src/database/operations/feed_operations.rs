@@ -0,0 +1,272 @@
+//! RSS/Atom feed subscriptions and items
+//!
+//! Items are keyed by (feed, guid), so re-polling a feed only inserts the
+//! entries not seen before and never resets their read state.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedRecord {
+    pub id: i64,
+    pub url: String,
+    pub title: String,
+    pub site_url: Option<String>,
+    pub description: Option<String>,
+    pub last_fetched_at: Option<String>,
+    pub last_error: Option<String>,
+    pub created_at: String,
+    pub unread_count: i64,
+}
+
+fn feed_from_row(row: &Row) -> rusqlite::Result<FeedRecord> {
+    Ok(FeedRecord {
+        id: row.get(0)?,
+        url: row.get(1)?,
+        title: row.get(2)?,
+        site_url: row.get(3)?,
+        description: row.get(4)?,
+        last_fetched_at: row.get(5)?,
+        last_error: row.get(6)?,
+        created_at: row.get(7)?,
+        unread_count: row.get(8)?,
+    })
+}
+
+const FEED_COLUMNS: &str = "f.id, f.url, f.title, f.site_url, f.description, f.last_fetched_at, f.last_error, f.created_at, \
+     (SELECT COUNT(*) FROM feed_items i WHERE i.feed_id = f.id AND i.is_read = 0)";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NewFeedItem {
+    pub guid: String,
+    pub title: String,
+    pub url: Option<String>,
+    pub author: Option<String>,
+    pub summary: Option<String>,
+    pub content: Option<String>,
+    /// UTC, "YYYY-MM-DD HH:MM:SS" like SQLite's datetime()
+    pub published_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedItemRecord {
+    pub id: i64,
+    pub feed_id: i64,
+    pub feed_title: String,
+    pub guid: String,
+    pub title: String,
+    pub url: Option<String>,
+    pub author: Option<String>,
+    pub summary: Option<String>,
+    pub content: Option<String>,
+    pub published_at: Option<String>,
+    pub is_read: bool,
+    pub is_starred: bool,
+    pub created_at: String,
+}
+
+fn item_from_row(row: &Row) -> rusqlite::Result<FeedItemRecord> {
+    Ok(FeedItemRecord {
+        id: row.get(0)?,
+        feed_id: row.get(1)?,
+        feed_title: row.get(2)?,
+        guid: row.get(3)?,
+        title: row.get(4)?,
+        url: row.get(5)?,
+        author: row.get(6)?,
+        summary: row.get(7)?,
+        content: row.get(8)?,
+        published_at: row.get(9)?,
+        is_read: row.get(10)?,
+        is_starred: row.get(11)?,
+        created_at: row.get(12)?,
+    })
+}
+
+const ITEM_COLUMNS: &str = "i.id, i.feed_id, f.title, i.guid, i.title, i.url, i.author, i.summary, i.content, \
+     i.published_at, i.is_read, i.is_starred, i.created_at";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeedItemFilter {
+    pub feed_id: Option<i64>,
+    #[serde(default)]
+    pub unread_only: bool,
+    #[serde(default)]
+    pub starred_only: bool,
+    /// Only items published (or first seen) at or after this UTC
+    /// "YYYY-MM-DD HH:MM:SS" time
+    pub since: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+pub fn add_feed(
+    conn: &Connection,
+    url: &str,
+    title: &str,
+    site_url: Option<&str>,
+    description: Option<&str>,
+) -> Result<FeedRecord> {
+    conn.execute(
+        "INSERT INTO feeds (url, title, site_url, description) VALUES (?1, ?2, ?3, ?4)",
+        params![url, title, site_url, description],
+    ).with_context(|| format!("Failed to subscribe to {}", url))?;
+
+    get_feed(conn, conn.last_insert_rowid())?.context("Added feed not found")
+}
+
+pub fn get_feed(conn: &Connection, id: i64) -> Result<Option<FeedRecord>> {
+    conn.query_row(
+        &format!("SELECT {} FROM feeds f WHERE f.id = ?1", FEED_COLUMNS),
+        params![id],
+        feed_from_row,
+    )
+    .optional()
+    .context("Failed to load feed")
+}
+
+pub fn find_feed_by_url(conn: &Connection, url: &str) -> Result<Option<FeedRecord>> {
+    conn.query_row(
+        &format!("SELECT {} FROM feeds f WHERE f.url = ?1", FEED_COLUMNS),
+        params![url],
+        feed_from_row,
+    )
+    .optional()
+    .context("Failed to load feed")
+}
+
+pub fn list_feeds(conn: &Connection) -> Result<Vec<FeedRecord>> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM feeds f ORDER BY f.title COLLATE NOCASE", FEED_COLUMNS))?;
+    let feeds = stmt
+        .query_map([], feed_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list feeds")?;
+    Ok(feeds)
+}
+
+/// Unsubscribe; the feed's items go with it
+pub fn delete_feed(conn: &Connection, id: i64) -> Result<bool> {
+    let deleted = conn
+        .execute("DELETE FROM feeds WHERE id = ?1", params![id])
+        .context("Failed to delete feed")?;
+    Ok(deleted > 0)
+}
+
+/// Note a successful fetch, taking the feed's current title if it has one
+pub fn record_fetch(conn: &Connection, id: i64, title: Option<&str>) -> Result<()> {
+    conn.execute(
+        "UPDATE feeds SET title = COALESCE(?2, title), last_fetched_at = datetime('now'), last_error = NULL
+         WHERE id = ?1",
+        params![id, title],
+    ).context("Failed to record feed fetch")?;
+    Ok(())
+}
+
+pub fn record_fetch_error(conn: &Connection, id: i64, error: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE feeds SET last_fetched_at = datetime('now'), last_error = ?2 WHERE id = ?1",
+        params![id, error],
+    ).context("Failed to record feed error")?;
+    Ok(())
+}
+
+/// Insert the items not already stored, returning how many were new
+pub fn insert_items(conn: &Connection, feed_id: i64, items: &[NewFeedItem]) -> Result<usize> {
+    let mut stmt = conn.prepare(
+        "INSERT OR IGNORE INTO feed_items (feed_id, guid, title, url, author, summary, content, published_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+    )?;
+    let mut inserted = 0;
+    for item in items {
+        inserted += stmt
+            .execute(params![
+                feed_id,
+                item.guid,
+                item.title,
+                item.url,
+                item.author,
+                item.summary,
+                item.content,
+                item.published_at,
+            ])
+            .context("Failed to store feed item")?;
+    }
+    Ok(inserted)
+}
+
+/// Items matching the filter, newest first
+pub fn list_items(conn: &Connection, filter: &FeedItemFilter) -> Result<Vec<FeedItemRecord>> {
+    let mut sql = format!("SELECT {} FROM feed_items i JOIN feeds f ON f.id = i.feed_id WHERE 1 = 1", ITEM_COLUMNS);
+    let mut values: Vec<rusqlite::types::Value> = Vec::new();
+    if let Some(feed_id) = filter.feed_id {
+        values.push(feed_id.into());
+        sql.push_str(&format!(" AND i.feed_id = ?{}", values.len()));
+    }
+    if filter.unread_only {
+        sql.push_str(" AND i.is_read = 0");
+    }
+    if filter.starred_only {
+        sql.push_str(" AND i.is_starred = 1");
+    }
+    if let Some(since) = &filter.since {
+        values.push(since.clone().into());
+        sql.push_str(&format!(" AND COALESCE(i.published_at, i.created_at) >= ?{}", values.len()));
+    }
+    values.push(filter.limit.unwrap_or(50).into());
+    sql.push_str(&format!(" ORDER BY COALESCE(i.published_at, i.created_at) DESC, i.id DESC LIMIT ?{}", values.len()));
+    values.push(filter.offset.unwrap_or(0).into());
+    sql.push_str(&format!(" OFFSET ?{}", values.len()));
+
+    let mut stmt = conn.prepare(&sql)?;
+    let items = stmt
+        .query_map(params_from_iter(values), item_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list feed items")?;
+    Ok(items)
+}
+
+pub fn get_item(conn: &Connection, id: i64) -> Result<Option<FeedItemRecord>> {
+    conn.query_row(
+        &format!("SELECT {} FROM feed_items i JOIN feeds f ON f.id = i.feed_id WHERE i.id = ?1", ITEM_COLUMNS),
+        params![id],
+        item_from_row,
+    )
+    .optional()
+    .context("Failed to load feed item")
+}
+
+pub fn set_items_read(conn: &Connection, ids: &[i64], read: bool) -> Result<usize> {
+    let mut stmt = conn.prepare("UPDATE feed_items SET is_read = ?2 WHERE id = ?1")?;
+    let mut updated = 0;
+    for id in ids {
+        updated += stmt.execute(params![id, read]).context("Failed to update feed item")?;
+    }
+    Ok(updated)
+}
+
+/// Mark every item read, or only those of one feed
+pub fn mark_all_read(conn: &Connection, feed_id: Option<i64>) -> Result<usize> {
+    conn.execute(
+        "UPDATE feed_items SET is_read = 1 WHERE is_read = 0 AND (?1 IS NULL OR feed_id = ?1)",
+        params![feed_id],
+    )
+    .context("Failed to mark feed items read")
+}
+
+pub fn set_item_starred(conn: &Connection, id: i64, starred: bool) -> Result<bool> {
+    let updated = conn
+        .execute("UPDATE feed_items SET is_starred = ?2 WHERE id = ?1", params![id, starred])
+        .context("Failed to update feed item")?;
+    Ok(updated > 0)
+}
+
+/// Forget read, unstarred items first seen more than `days` ago
+pub fn prune_items(conn: &Connection, days: i64) -> Result<usize> {
+    conn.execute(
+        "DELETE FROM feed_items
+         WHERE is_read = 1 AND is_starred = 0 AND created_at < datetime('now', ?1)",
+        params![format!("-{} days", days)],
+    )
+    .context("Failed to prune feed items")
+}
@@ -0,0 +1,93 @@
+//! Per-sender image loading preferences
+//!
+//! Remote images are blocked by default. A preference can name a single
+//! address or a whole domain; an address entry wins over its domain.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageLoadPreference {
+    pub account_id: String,
+    pub sender: String,
+    pub always_load: bool,
+    pub updated_at: String,
+}
+
+fn preference_from_row(row: &Row) -> rusqlite::Result<ImageLoadPreference> {
+    Ok(ImageLoadPreference {
+        account_id: row.get(0)?,
+        sender: row.get(1)?,
+        always_load: row.get(2)?,
+        updated_at: row.get(3)?,
+    })
+}
+
+/// Lowercase a sender and strip any display name, so "Ann <Ann@Example.com>"
+/// and "ann@example.com" share one preference
+pub fn normalize_sender(sender: &str) -> String {
+    let sender = sender.trim();
+    let address = match (sender.rfind('<'), sender.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &sender[start + 1..end],
+        _ => sender,
+    };
+    address.trim().trim_start_matches('@').to_ascii_lowercase()
+}
+
+pub fn set_preference(conn: &Connection, account_id: &str, sender: &str, always_load: bool) -> Result<()> {
+    let sender = normalize_sender(sender);
+    if sender.is_empty() {
+        anyhow::bail!("Sender cannot be empty");
+    }
+    conn.execute(
+        "INSERT INTO image_load_preferences (account_id, sender, always_load) VALUES (?1, ?2, ?3)
+         ON CONFLICT(account_id, sender) DO UPDATE SET
+            always_load = excluded.always_load, updated_at = CURRENT_TIMESTAMP",
+        params![account_id, sender, always_load],
+    ).context("Failed to save image load preference")?;
+    Ok(())
+}
+
+/// Returns false if there was no preference for the sender
+pub fn remove_preference(conn: &Connection, account_id: &str, sender: &str) -> Result<bool> {
+    let removed = conn.execute(
+        "DELETE FROM image_load_preferences WHERE account_id = ?1 AND sender = ?2",
+        params![account_id, normalize_sender(sender)],
+    ).context("Failed to remove image load preference")?;
+    Ok(removed > 0)
+}
+
+pub fn list_preferences(conn: &Connection, account_id: &str) -> Result<Vec<ImageLoadPreference>> {
+    let mut stmt = conn.prepare(
+        "SELECT account_id, sender, always_load, updated_at
+         FROM image_load_preferences WHERE account_id = ?1 ORDER BY sender",
+    )?;
+    let preferences = stmt
+        .query_map(params![account_id], preference_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list image load preferences")?;
+    Ok(preferences)
+}
+
+/// Whether remote images from `sender` should load without asking
+pub fn should_load_images(conn: &Connection, account_id: &str, sender: &str) -> Result<bool> {
+    let address = normalize_sender(sender);
+    let domain = address.rsplit_once('@').map(|(_, domain)| domain.to_string());
+
+    for key in std::iter::once(address).chain(domain) {
+        let always_load: Option<bool> = conn
+            .query_row(
+                "SELECT always_load FROM image_load_preferences WHERE account_id = ?1 AND sender = ?2",
+                params![account_id, key],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Failed to read image load preference")?;
+        if let Some(always_load) = always_load {
+            return Ok(always_load);
+        }
+    }
+
+    Ok(false)
+}
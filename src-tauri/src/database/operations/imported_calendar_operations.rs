@@ -0,0 +1,212 @@
+//! Calendars imported from ICS files and webcal subscriptions
+//!
+//! An import replaces the calendar's events wholesale, so refreshing a
+//! subscription drops events the publisher removed. Events are read back in
+//! Google's event shape so the calendar view can merge them with synced ones.
+
+use anyhow::{Context, Result};
+use chrono_tz::Tz;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use super::search_operations::{self, NewSearchItem};
+use crate::utils::time;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedCalendar {
+    pub id: String,
+    pub name: String,
+    /// "file" or "webcal"
+    pub source: String,
+    /// The file path, or the https URL a webcal link points at
+    pub source_url: String,
+    pub event_count: i64,
+    pub created_at: String,
+    pub refreshed_at: String,
+}
+
+fn calendar_from_row(row: &Row) -> rusqlite::Result<ImportedCalendar> {
+    Ok(ImportedCalendar {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        source: row.get(2)?,
+        source_url: row.get(3)?,
+        event_count: row.get(4)?,
+        created_at: row.get(5)?,
+        refreshed_at: row.get(6)?,
+    })
+}
+
+const CALENDAR_COLUMNS: &str = "id, name, source, source_url, event_count, created_at, refreshed_at";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImportedEvent {
+    pub uid: String,
+    pub summary: Option<String>,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub status: Option<String>,
+    pub start_date_time: Option<String>,
+    /// Set instead of `start_date_time` for all-day events
+    pub start_date: Option<String>,
+    pub end_date_time: Option<String>,
+    pub end_date: Option<String>,
+    pub time_zone: Option<String>,
+    /// RRULE and EXDATE lines as written in the file
+    pub recurrence: Vec<String>,
+}
+
+impl ImportedEvent {
+    /// The start as UTC RFC 3339, for ordering and range queries. Times
+    /// without a zone the system knows are read in `floating`, the user's
+    /// zone; all-day events sort at midnight UTC of their date.
+    pub fn sort_time(&self, floating: &Tz) -> Option<String> {
+        match (&self.start_date_time, &self.start_date) {
+            (Some(start), _) => time::zoned_to_utc(start, self.time_zone.as_deref(), floating).map(time::utc_rfc3339),
+            (None, Some(date)) => search_operations::utc_item_time(date),
+            (None, None) => None,
+        }
+    }
+}
+
+fn event_from_row(row: &Row) -> rusqlite::Result<ImportedEvent> {
+    let recurrence: Option<String> = row.get(10)?;
+    Ok(ImportedEvent {
+        uid: row.get(0)?,
+        summary: row.get(1)?,
+        description: row.get(2)?,
+        location: row.get(3)?,
+        status: row.get(4)?,
+        start_date_time: row.get(5)?,
+        start_date: row.get(6)?,
+        end_date_time: row.get(7)?,
+        end_date: row.get(8)?,
+        time_zone: row.get(9)?,
+        recurrence: recurrence.and_then(|text| serde_json::from_str(&text).ok()).unwrap_or_default(),
+    })
+}
+
+const EVENT_COLUMNS: &str = "uid, summary, description, location, status, start_date_time, start_date, \
+     end_date_time, end_date, time_zone, recurrence";
+
+/// Create or refresh a calendar with exactly these events. Events without
+/// a usable start are skipped; returns how many were stored.
+pub fn replace_calendar(
+    conn: &Connection,
+    id: &str,
+    name: &str,
+    source: &str,
+    source_url: &str,
+    events: &[ImportedEvent],
+    floating: &Tz,
+) -> Result<usize> {
+    let tx = conn.unchecked_transaction().context("Failed to start import transaction")?;
+    tx.execute(
+        "INSERT INTO imported_calendars (id, name, source, source_url) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(id) DO UPDATE SET name = excluded.name, source_url = excluded.source_url,
+             refreshed_at = datetime('now')",
+        params![id, name, source, source_url],
+    ).with_context(|| format!("Failed to save imported calendar '{}'", name))?;
+    tx.execute("DELETE FROM imported_events WHERE calendar_id = ?1", params![id])
+        .context("Failed to clear imported events")?;
+
+    let mut stored = Vec::new();
+    for event in events {
+        let Some(sort_time) = event.sort_time(floating) else { continue };
+        let recurrence = (!event.recurrence.is_empty()).then(|| serde_json::to_string(&event.recurrence)).transpose()?;
+        tx.execute(
+            "INSERT OR REPLACE INTO imported_events (calendar_id, uid, summary, description, location, status,
+                 start_date_time, start_date, end_date_time, end_date, time_zone, recurrence, sort_time)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            params![
+                id, event.uid, event.summary, event.description, event.location, event.status,
+                event.start_date_time, event.start_date, event.end_date_time, event.end_date,
+                event.time_zone, recurrence, sort_time,
+            ],
+        ).context("Failed to store imported event")?;
+        stored.push((event, sort_time));
+    }
+
+    let items: Vec<NewSearchItem> = stored
+        .iter()
+        .map(|(event, sort_time)| NewSearchItem {
+            item_id: &event.uid,
+            container_id: id,
+            title: event.summary.as_deref().unwrap_or(""),
+            body: event.description.as_deref().unwrap_or(""),
+            item_time: Some(sort_time),
+            status: event.status.as_deref(),
+            completed_at: None,
+            all_day: event.start_date_time.is_none() && event.start_date.is_some(),
+        })
+        .collect();
+    // Each calendar is its own search account, since UIDs are only unique
+    // within one calendar
+    search_operations::replace_calendar_items(&tx, id, id, &items)?;
+
+    tx.execute(
+        "UPDATE imported_calendars SET event_count = (SELECT COUNT(*) FROM imported_events WHERE calendar_id = ?1)
+         WHERE id = ?1",
+        params![id],
+    ).context("Failed to count imported events")?;
+    tx.commit().context("Failed to save imported calendar")?;
+    Ok(items.len())
+}
+
+pub fn get_calendar(conn: &Connection, id: &str) -> Result<Option<ImportedCalendar>> {
+    conn.query_row(
+        &format!("SELECT {} FROM imported_calendars WHERE id = ?1", CALENDAR_COLUMNS),
+        params![id],
+        calendar_from_row,
+    )
+    .optional()
+    .context("Failed to load imported calendar")
+}
+
+pub fn list_calendars(conn: &Connection) -> Result<Vec<ImportedCalendar>> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM imported_calendars ORDER BY name", CALENDAR_COLUMNS))?;
+    let calendars = stmt
+        .query_map([], calendar_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list imported calendars")?;
+    Ok(calendars)
+}
+
+/// Events starting in `[time_min, time_max)`, plus recurring events that
+/// began before `time_max`, since their later occurrences may fall inside.
+/// Bounds are RFC 3339 times or dates.
+pub fn list_events(
+    conn: &Connection,
+    calendar_id: &str,
+    time_min: Option<&str>,
+    time_max: Option<&str>,
+) -> Result<Vec<ImportedEvent>> {
+    let time_min = time_min.and_then(search_operations::utc_item_time);
+    let time_max = time_max.and_then(search_operations::utc_item_time);
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM imported_events
+         WHERE calendar_id = ?1
+           AND (?3 IS NULL OR sort_time < ?3)
+           AND (?2 IS NULL OR sort_time >= ?2 OR recurrence IS NOT NULL)
+         ORDER BY sort_time",
+        EVENT_COLUMNS
+    ))?;
+    let events = stmt
+        .query_map(params![calendar_id, time_min, time_max], event_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list imported events")?;
+    Ok(events)
+}
+
+/// Delete an imported calendar, its events and their search entries
+pub fn delete_calendar(conn: &Connection, id: &str) -> Result<bool> {
+    let tx = conn.unchecked_transaction().context("Failed to start delete transaction")?;
+    search_operations::replace_calendar_items(&tx, id, id, &[])?;
+    tx.execute("DELETE FROM imported_events WHERE calendar_id = ?1", params![id])
+        .context("Failed to delete imported events")?;
+    let deleted = tx
+        .execute("DELETE FROM imported_calendars WHERE id = ?1", params![id])
+        .context("Failed to delete imported calendar")?;
+    tx.commit().context("Failed to delete imported calendar")?;
+    Ok(deleted > 0)
+}
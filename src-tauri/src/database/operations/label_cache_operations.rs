@@ -0,0 +1,284 @@
+//! Gmail label cache and local label override operations
+//!
+//! Label metadata is cached per account so the label list does not need a
+//! round trip every time it is shown. Counts are refreshed separately, in
+//! one batch, and the oldest count refresh decides whether the cache is
+//! still fresh. Overrides hold display names and colours the user set in
+//! the app; they are applied on read and never sent to Gmail.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CachedLabel {
+    pub label_id: String,
+    pub name: String,
+    pub label_type: Option<String>,
+    pub message_list_visibility: Option<String>,
+    pub label_list_visibility: Option<String>,
+    pub text_color: Option<String>,
+    pub background_color: Option<String>,
+    pub messages_total: Option<i64>,
+    pub messages_unread: Option<i64>,
+    pub threads_total: Option<i64>,
+    pub threads_unread: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LabelCounts {
+    pub label_id: String,
+    pub messages_total: Option<i64>,
+    pub messages_unread: Option<i64>,
+    pub threads_total: Option<i64>,
+    pub threads_unread: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LabelOverride {
+    pub label_id: String,
+    pub display_name: Option<String>,
+    pub text_color: Option<String>,
+    pub background_color: Option<String>,
+}
+
+fn label_from_row(row: &Row) -> rusqlite::Result<CachedLabel> {
+    Ok(CachedLabel {
+        label_id: row.get(0)?,
+        name: row.get(1)?,
+        label_type: row.get(2)?,
+        message_list_visibility: row.get(3)?,
+        label_list_visibility: row.get(4)?,
+        text_color: row.get(5)?,
+        background_color: row.get(6)?,
+        messages_total: row.get(7)?,
+        messages_unread: row.get(8)?,
+        threads_total: row.get(9)?,
+        threads_unread: row.get(10)?,
+    })
+}
+
+/// Cached labels for an account, ordered by name
+pub fn get_labels(conn: &Connection, account_id: &str) -> Result<Vec<CachedLabel>> {
+    let mut stmt = conn.prepare(
+        "SELECT label_id, name, label_type, message_list_visibility, label_list_visibility,
+                text_color, background_color, messages_total, messages_unread, threads_total, threads_unread
+         FROM gmail_label_cache WHERE account_id = ?1 ORDER BY name COLLATE NOCASE",
+    )?;
+    let labels = stmt
+        .query_map(params![account_id], label_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to load cached labels")?;
+    Ok(labels)
+}
+
+/// Unix time of the oldest count refresh; None when nothing is cached.
+/// Stale or never-counted labels report 0.
+pub fn oldest_refresh(conn: &Connection, account_id: &str) -> Result<Option<i64>> {
+    let oldest = conn
+        .query_row(
+            "SELECT MIN(counts_refreshed_at) FROM gmail_label_cache WHERE account_id = ?1",
+            params![account_id],
+            |row| row.get::<_, Option<i64>>(0),
+        )
+        .optional()
+        .context("Failed to read label cache age")?;
+    Ok(oldest.flatten())
+}
+
+/// Replace an account's label metadata with a fresh list.
+///
+/// Labels missing from the list are dropped along with their overrides.
+/// Labels already cached keep their counts until the next count refresh.
+pub fn replace_labels(conn: &mut Connection, account_id: &str, labels: &[CachedLabel]) -> Result<()> {
+    let tx = conn.transaction().context("Failed to start label cache transaction")?;
+
+    let cached_ids: Vec<String> = {
+        let mut stmt = tx.prepare("SELECT label_id FROM gmail_label_cache WHERE account_id = ?1")?;
+        let rows = stmt.query_map(params![account_id], |row| row.get(0))?;
+        rows.collect::<rusqlite::Result<_>>().context("Failed to read cached label IDs")?
+    };
+    for label_id in cached_ids.iter().filter(|id| !labels.iter().any(|l| &l.label_id == *id)) {
+        tx.execute(
+            "DELETE FROM gmail_label_cache WHERE account_id = ?1 AND label_id = ?2",
+            params![account_id, label_id],
+        ).context("Failed to drop deleted label")?;
+        tx.execute(
+            "DELETE FROM gmail_label_overrides WHERE account_id = ?1 AND label_id = ?2",
+            params![account_id, label_id],
+        ).context("Failed to drop override for deleted label")?;
+    }
+
+    for label in labels {
+        tx.execute(
+            "INSERT INTO gmail_label_cache (
+                account_id, label_id, name, label_type, message_list_visibility, label_list_visibility,
+                text_color, background_color
+             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT(account_id, label_id) DO UPDATE SET
+                name = excluded.name,
+                label_type = excluded.label_type,
+                message_list_visibility = excluded.message_list_visibility,
+                label_list_visibility = excluded.label_list_visibility,
+                text_color = excluded.text_color,
+                background_color = excluded.background_color",
+            params![
+                account_id,
+                label.label_id,
+                label.name,
+                label.label_type,
+                label.message_list_visibility,
+                label.label_list_visibility,
+                label.text_color,
+                label.background_color,
+            ],
+        ).context("Failed to cache label")?;
+    }
+
+    tx.commit().context("Failed to commit label cache")?;
+    Ok(())
+}
+
+/// Store a batch of refreshed counts, stamping them with `refreshed_at`
+pub fn update_counts(conn: &mut Connection, account_id: &str, counts: &[LabelCounts], refreshed_at: i64) -> Result<()> {
+    let tx = conn.transaction().context("Failed to start label count transaction")?;
+    for count in counts {
+        tx.execute(
+            "UPDATE gmail_label_cache SET
+                messages_total = ?3, messages_unread = ?4, threads_total = ?5, threads_unread = ?6,
+                counts_refreshed_at = ?7
+             WHERE account_id = ?1 AND label_id = ?2",
+            params![
+                account_id,
+                count.label_id,
+                count.messages_total,
+                count.messages_unread,
+                count.threads_total,
+                count.threads_unread,
+                refreshed_at,
+            ],
+        ).context("Failed to update label counts")?;
+    }
+    tx.commit().context("Failed to commit label counts")?;
+    Ok(())
+}
+
+/// Mark an account's counts as out of date so the next read refreshes them
+pub fn mark_stale(conn: &Connection, account_id: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE gmail_label_cache SET counts_refreshed_at = 0 WHERE account_id = ?1",
+        params![account_id],
+    ).context("Failed to mark label cache stale")?;
+    Ok(())
+}
+
+pub fn get_overrides(conn: &Connection, account_id: &str) -> Result<Vec<LabelOverride>> {
+    let mut stmt = conn.prepare(
+        "SELECT label_id, display_name, text_color, background_color
+         FROM gmail_label_overrides WHERE account_id = ?1",
+    )?;
+    let overrides = stmt
+        .query_map(params![account_id], |row| {
+            Ok(LabelOverride {
+                label_id: row.get(0)?,
+                display_name: row.get(1)?,
+                text_color: row.get(2)?,
+                background_color: row.get(3)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to load label overrides")?;
+    Ok(overrides)
+}
+
+/// Save a label's local display name and colours. An override with nothing
+/// set is removed instead.
+pub fn set_override(conn: &Connection, account_id: &str, label_override: &LabelOverride) -> Result<()> {
+    let is_empty = label_override.display_name.is_none()
+        && label_override.text_color.is_none()
+        && label_override.background_color.is_none();
+    if is_empty {
+        return clear_override(conn, account_id, &label_override.label_id);
+    }
+
+    conn.execute(
+        "INSERT OR REPLACE INTO gmail_label_overrides (account_id, label_id, display_name, text_color, background_color, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, datetime('now'))",
+        params![
+            account_id,
+            label_override.label_id,
+            label_override.display_name,
+            label_override.text_color,
+            label_override.background_color,
+        ],
+    ).context("Failed to save label override")?;
+    Ok(())
+}
+
+pub fn clear_override(conn: &Connection, account_id: &str, label_id: &str) -> Result<()> {
+    conn.execute(
+        "DELETE FROM gmail_label_overrides WHERE account_id = ?1 AND label_id = ?2",
+        params![account_id, label_id],
+    ).context("Failed to clear label override")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    fn label(label_id: &str, name: &str) -> CachedLabel {
+        CachedLabel { label_id: label_id.to_string(), name: name.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_label_cache_keeps_counts_and_drops_deleted_labels() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        assert_eq!(oldest_refresh(&conn, "acc").unwrap(), None);
+
+        replace_labels(&mut conn, "acc", &[label("INBOX", "INBOX"), label("Label_1", "Work")]).unwrap();
+        assert_eq!(oldest_refresh(&conn, "acc").unwrap(), Some(0));
+
+        let counts = |label_id: &str, unread: i64| LabelCounts {
+            label_id: label_id.to_string(),
+            messages_unread: Some(unread),
+            ..Default::default()
+        };
+        update_counts(&mut conn, "acc", &[counts("INBOX", 3), counts("Label_1", 1)], 1_000).unwrap();
+        assert_eq!(oldest_refresh(&conn, "acc").unwrap(), Some(1_000));
+
+        let work = LabelOverride { label_id: "Label_1".to_string(), display_name: Some("Job".to_string()), ..Default::default() };
+        set_override(&conn, "acc", &work).unwrap();
+        assert_eq!(get_overrides(&conn, "acc").unwrap(), vec![work]);
+
+        // A rename keeps the counts; the deleted label takes its override with it
+        replace_labels(&mut conn, "acc", &[label("INBOX", "Inbox")]).unwrap();
+        let labels = get_labels(&conn, "acc").unwrap();
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[0].name, "Inbox");
+        assert_eq!(labels[0].messages_unread, Some(3));
+        assert!(get_overrides(&conn, "acc").unwrap().is_empty());
+
+        mark_stale(&conn, "acc").unwrap();
+        assert_eq!(oldest_refresh(&conn, "acc").unwrap(), Some(0));
+    }
+
+    #[test]
+    fn test_empty_override_is_cleared() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let colored = LabelOverride {
+            label_id: "Label_2".to_string(),
+            background_color: Some("#16a766".to_string()),
+            ..Default::default()
+        };
+        set_override(&conn, "acc", &colored).unwrap();
+        assert_eq!(get_overrides(&conn, "acc").unwrap().len(), 1);
+
+        set_override(&conn, "acc", &LabelOverride { label_id: "Label_2".to_string(), ..Default::default() }).unwrap();
+        assert!(get_overrides(&conn, "acc").unwrap().is_empty());
+    }
+}
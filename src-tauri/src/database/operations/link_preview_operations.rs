@@ -0,0 +1,89 @@
+//! Link preview cache
+//!
+//! Unfurled page metadata keyed by the URL that was asked for. Rows expire
+//! rather than being deleted, so a stale preview can still be replaced in
+//! place on the next fetch.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkPreviewRecord {
+    pub url: String,
+    pub final_url: Option<String>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub site_name: Option<String>,
+    pub favicon_url: Option<String>,
+    pub content_type: Option<String>,
+    /// Why the last fetch failed; the other fields are empty then
+    pub error: Option<String>,
+    pub fetched_at: String,
+    pub expires_at: i64,
+}
+
+fn preview_from_row(row: &Row) -> rusqlite::Result<LinkPreviewRecord> {
+    Ok(LinkPreviewRecord {
+        url: row.get(0)?,
+        final_url: row.get(1)?,
+        title: row.get(2)?,
+        description: row.get(3)?,
+        image_url: row.get(4)?,
+        site_name: row.get(5)?,
+        favicon_url: row.get(6)?,
+        content_type: row.get(7)?,
+        error: row.get(8)?,
+        fetched_at: row.get(9)?,
+        expires_at: row.get(10)?,
+    })
+}
+
+const PREVIEW_COLUMNS: &str =
+    "url, final_url, title, description, image_url, site_name, favicon_url, content_type, error, fetched_at, expires_at";
+
+/// Cached preview of `url` that has not expired by `now` (unix seconds)
+pub fn get_fresh_preview(conn: &Connection, url: &str, now: i64) -> Result<Option<LinkPreviewRecord>> {
+    conn.query_row(
+        &format!("SELECT {} FROM link_previews WHERE url = ?1 AND expires_at > ?2", PREVIEW_COLUMNS),
+        params![url, now],
+        preview_from_row,
+    )
+    .optional()
+    .context("Failed to load link preview")
+}
+
+/// Insert or replace the preview of `record.url`
+pub fn save_preview(conn: &Connection, record: &LinkPreviewRecord) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO link_previews
+         (url, final_url, title, description, image_url, site_name, favicon_url, content_type, error, fetched_at, expires_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![
+            record.url,
+            record.final_url,
+            record.title,
+            record.description,
+            record.image_url,
+            record.site_name,
+            record.favicon_url,
+            record.content_type,
+            record.error,
+            record.fetched_at,
+            record.expires_at,
+        ],
+    ).context("Failed to save link preview")?;
+    Ok(())
+}
+
+/// Drop previews that expired before `now`, returning how many went
+pub fn delete_expired(conn: &Connection, now: i64) -> Result<usize> {
+    conn.execute("DELETE FROM link_previews WHERE expires_at <= ?1", params![now])
+        .context("Failed to delete expired link previews")
+}
+
+pub fn clear_previews(conn: &Connection) -> Result<usize> {
+    conn.execute("DELETE FROM link_previews", [])
+        .context("Failed to clear link previews")
+}
@@ -0,0 +1,156 @@
+//! LLM providers and the model chosen for each feature
+//!
+//! API keys are stored encrypted; callers pass and receive the ciphertext
+//! and decrypt only when a request is about to be made.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmProviderRecord {
+    pub id: String,
+    pub name: String,
+    pub kind: String,
+    pub base_url: String,
+    pub api_key_encrypted: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+fn provider_from_row(row: &Row) -> rusqlite::Result<LlmProviderRecord> {
+    Ok(LlmProviderRecord {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        kind: row.get(2)?,
+        base_url: row.get(3)?,
+        api_key_encrypted: row.get(4)?,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+    })
+}
+
+pub fn list_providers(conn: &Connection) -> Result<Vec<LlmProviderRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, kind, base_url, api_key_encrypted, created_at, updated_at
+         FROM llm_providers ORDER BY id = 'ollama' DESC, name",
+    )?;
+    let providers = stmt
+        .query_map([], provider_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list LLM providers")?;
+    Ok(providers)
+}
+
+pub fn get_provider(conn: &Connection, id: &str) -> Result<Option<LlmProviderRecord>> {
+    conn.query_row(
+        "SELECT id, name, kind, base_url, api_key_encrypted, created_at, updated_at
+         FROM llm_providers WHERE id = ?1",
+        params![id],
+        provider_from_row,
+    )
+    .optional()
+    .context("Failed to load LLM provider")
+}
+
+/// Insert or update a provider. `api_key_encrypted` of `None` keeps the stored key.
+pub fn upsert_provider(
+    conn: &Connection,
+    id: &str,
+    name: &str,
+    kind: &str,
+    base_url: &str,
+    api_key_encrypted: Option<&str>,
+) -> Result<LlmProviderRecord> {
+    conn.execute(
+        "INSERT INTO llm_providers (id, name, kind, base_url, api_key_encrypted)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(id) DO UPDATE SET
+            name = excluded.name,
+            kind = excluded.kind,
+            base_url = excluded.base_url,
+            api_key_encrypted = COALESCE(excluded.api_key_encrypted, llm_providers.api_key_encrypted),
+            updated_at = datetime('now')",
+        params![id, name, kind, base_url, api_key_encrypted],
+    ).context("Failed to save LLM provider")?;
+
+    get_provider(conn, id)?.context("LLM provider missing after save")
+}
+
+pub fn clear_api_key(conn: &Connection, id: &str) -> Result<bool> {
+    let updated = conn.execute(
+        "UPDATE llm_providers SET api_key_encrypted = NULL, updated_at = datetime('now') WHERE id = ?1",
+        params![id],
+    ).context("Failed to clear LLM provider key")?;
+    Ok(updated > 0)
+}
+
+/// Remove a provider and any feature that was using it
+pub fn delete_provider(conn: &Connection, id: &str) -> Result<bool> {
+    conn.execute("DELETE FROM llm_feature_models WHERE provider_id = ?1", params![id])
+        .context("Failed to clear feature models for provider")?;
+    conn.execute("DELETE FROM llm_routing_rules WHERE provider_id = ?1", params![id])
+        .context("Failed to clear routing rules for provider")?;
+    conn.execute("DELETE FROM llm_models WHERE provider_id = ?1", params![id])
+        .context("Failed to clear registered models for provider")?;
+    let deleted = conn
+        .execute("DELETE FROM llm_providers WHERE id = ?1", params![id])
+        .context("Failed to delete LLM provider")?;
+    Ok(deleted > 0)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureModel {
+    pub feature: String,
+    pub provider_id: String,
+    pub model: String,
+    pub updated_at: String,
+}
+
+fn feature_model_from_row(row: &Row) -> rusqlite::Result<FeatureModel> {
+    Ok(FeatureModel {
+        feature: row.get(0)?,
+        provider_id: row.get(1)?,
+        model: row.get(2)?,
+        updated_at: row.get(3)?,
+    })
+}
+
+pub fn list_feature_models(conn: &Connection) -> Result<Vec<FeatureModel>> {
+    let mut stmt = conn.prepare("SELECT feature, provider_id, model, updated_at FROM llm_feature_models ORDER BY feature")?;
+    let models = stmt
+        .query_map([], feature_model_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list feature models")?;
+    Ok(models)
+}
+
+pub fn get_feature_model(conn: &Connection, feature: &str) -> Result<Option<FeatureModel>> {
+    conn.query_row(
+        "SELECT feature, provider_id, model, updated_at FROM llm_feature_models WHERE feature = ?1",
+        params![feature],
+        feature_model_from_row,
+    )
+    .optional()
+    .context("Failed to load feature model")
+}
+
+pub fn set_feature_model(conn: &Connection, feature: &str, provider_id: &str, model: &str) -> Result<FeatureModel> {
+    conn.execute(
+        "INSERT INTO llm_feature_models (feature, provider_id, model) VALUES (?1, ?2, ?3)
+         ON CONFLICT(feature) DO UPDATE SET
+            provider_id = excluded.provider_id,
+            model = excluded.model,
+            updated_at = datetime('now')",
+        params![feature, provider_id, model],
+    ).context("Failed to save feature model")?;
+
+    get_feature_model(conn, feature)?.context("Feature model missing after save")
+}
+
+pub fn clear_feature_model(conn: &Connection, feature: &str) -> Result<bool> {
+    let deleted = conn
+        .execute("DELETE FROM llm_feature_models WHERE feature = ?1", params![feature])
+        .context("Failed to clear feature model")?;
+    Ok(deleted > 0)
+}
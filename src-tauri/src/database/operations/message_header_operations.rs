@@ -0,0 +1,315 @@
+//! Windowed message header queries for virtualized mail lists
+//!
+//! The list only needs a few header fields per row, never the bodies, and
+//! only for the rows on screen. They are read from `gmail_message_headers`,
+//! which triggers keep in step with the message store (migration v69).
+//! Windows are paged by keyset: each one ends in a cursor holding the last
+//! row's sort key, and the next starts right after it, so new mail above
+//! does not shift it. Every sort is backed by an index with the message ID
+//! as tie-break, so a window costs the same at row 90,000 as at row 0.
+
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use rusqlite::{params_from_iter, types::Value as SqlValue, Connection, Row};
+use serde::{Deserialize, Serialize};
+
+/// Most rows returned in one window
+pub const MAX_WINDOW: i64 = 500;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HeaderSort {
+    #[default]
+    DateDesc,
+    DateAsc,
+    Sender,
+    Subject,
+}
+
+impl HeaderSort {
+    /// Sort key; each leads one of the indexes from v69
+    fn key(&self) -> &'static str {
+        match self {
+            HeaderSort::DateDesc | HeaderSort::DateAsc => "h.internal_date",
+            HeaderSort::Sender => "h.sender_key",
+            HeaderSort::Subject => "h.subject_key",
+        }
+    }
+
+    fn descending(&self) -> bool {
+        matches!(self, HeaderSort::DateDesc)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeaderFilter {
+    pub label_id: Option<String>,
+    pub thread_id: Option<String>,
+    pub unread_only: bool,
+    pub starred_only: bool,
+    pub has_attachments: Option<bool>,
+}
+
+/// A row of the mail list, without bodies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageHeader {
+    pub message_id: String,
+    pub thread_id: String,
+    pub subject: Option<String>,
+    pub from_name: Option<String>,
+    pub from_email: String,
+    pub snippet: Option<String>,
+    /// Epoch milliseconds
+    pub internal_date: Option<i64>,
+    pub label_ids: Vec<String>,
+    pub is_unread: bool,
+    pub is_starred: bool,
+    pub has_attachments: bool,
+    /// "full" or "headers"
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeaderWindow {
+    pub headers: Vec<MessageHeader>,
+    /// Rows matching the filter; only counted for the first window, the
+    /// list keeps it while paging on
+    pub total: Option<i64>,
+    /// Position of the first header in the full list
+    pub offset: i64,
+    /// Pass back to get the rows after this window; None at the end
+    pub next_cursor: Option<String>,
+}
+
+/// Where a window ends: the last row's sort key and message ID, and the
+/// position of the row after it
+#[derive(Debug, Serialize, Deserialize)]
+struct Cursor {
+    sort: HeaderSort,
+    key: serde_json::Value,
+    id: String,
+    position: i64,
+}
+
+fn encode_cursor(cursor: &Cursor) -> Result<String> {
+    Ok(URL_SAFE_NO_PAD.encode(serde_json::to_vec(cursor)?))
+}
+
+fn decode_cursor(cursor: &str) -> Result<Cursor> {
+    let bytes = URL_SAFE_NO_PAD.decode(cursor).context("Invalid list cursor")?;
+    serde_json::from_slice(&bytes).context("Invalid list cursor")
+}
+
+fn sql_value(value: &serde_json::Value) -> SqlValue {
+    match value {
+        serde_json::Value::Number(n) => n.as_i64().map(SqlValue::Integer).unwrap_or(SqlValue::Null),
+        serde_json::Value::String(s) => SqlValue::Text(s.clone()),
+        _ => SqlValue::Null,
+    }
+}
+
+fn json_value(value: SqlValue) -> serde_json::Value {
+    match value {
+        SqlValue::Integer(n) => n.into(),
+        SqlValue::Text(s) => s.into(),
+        _ => serde_json::Value::Null,
+    }
+}
+
+const HEADER_COLUMNS: &str = "h.message_id, h.thread_id, h.subject, h.from_name, h.from_email, h.snippet,
+    NULLIF(h.internal_date, 0), h.label_ids, h.has_attachments, h.content";
+
+fn header_from_row(row: &Row) -> rusqlite::Result<(MessageHeader, SqlValue)> {
+    let labels: String = row.get(7)?;
+    let label_ids: Vec<String> = serde_json::from_str(&labels).unwrap_or_default();
+    let has_label = |label: &str| label_ids.iter().any(|l| l == label);
+    let header = MessageHeader {
+        message_id: row.get(0)?,
+        thread_id: row.get(1)?,
+        subject: row.get(2)?,
+        from_name: row.get(3)?,
+        from_email: row.get(4)?,
+        snippet: row.get(5)?,
+        internal_date: row.get(6)?,
+        is_unread: has_label("UNREAD"),
+        is_starred: has_label("STARRED"),
+        label_ids: label_ids.clone(),
+        has_attachments: row.get(8)?,
+        content: row.get(9)?,
+    };
+    Ok((header, row.get(10)?))
+}
+
+/// WHERE clause and values for the account and filter. Labels are matched
+/// on the stored JSON text, which avoids parsing every row's label array.
+fn filter_clause(account_id: &str, filter: &HeaderFilter) -> (String, Vec<SqlValue>) {
+    let mut sql = String::from("h.account_id = ?1");
+    let mut values: Vec<SqlValue> = vec![account_id.to_string().into()];
+
+    let require_label = |sql: &mut String, values: &mut Vec<SqlValue>, label: &str| {
+        values.push(label.to_string().into());
+        sql.push_str(&format!(" AND instr(h.label_ids, json_quote(?{})) > 0", values.len()));
+    };
+    if let Some(label_id) = &filter.label_id {
+        require_label(&mut sql, &mut values, label_id);
+    }
+    if filter.unread_only {
+        require_label(&mut sql, &mut values, "UNREAD");
+    }
+    if filter.starred_only {
+        require_label(&mut sql, &mut values, "STARRED");
+    }
+    if let Some(thread_id) = &filter.thread_id {
+        values.push(thread_id.clone().into());
+        sql.push_str(&format!(" AND h.thread_id = ?{}", values.len()));
+    }
+    match filter.has_attachments {
+        Some(true) => sql.push_str(" AND h.has_attachments = 1"),
+        Some(false) => sql.push_str(" AND h.has_attachments = 0"),
+        None => {}
+    }
+    (sql, values)
+}
+
+fn count(conn: &Connection, where_sql: &str, values: &[SqlValue]) -> Result<i64> {
+    conn.query_row(
+        &format!("SELECT COUNT(*) FROM gmail_message_headers h WHERE {}", where_sql),
+        params_from_iter(values),
+        |row| row.get(0),
+    )
+    .context("Failed to count message headers")
+}
+
+/// One window of an account's message headers: the first, or the one
+/// right after the window `cursor` came from. `limit` is capped at
+/// [`MAX_WINDOW`].
+pub fn get_headers(
+    conn: &Connection,
+    account_id: &str,
+    filter: &HeaderFilter,
+    sort: HeaderSort,
+    limit: i64,
+    cursor: Option<&str>,
+) -> Result<HeaderWindow> {
+    let limit = limit.clamp(1, MAX_WINDOW);
+    let (mut where_sql, mut values) = filter_clause(account_id, filter);
+
+    let key = sort.key();
+    let (comparison, direction) = if sort.descending() { ("<", "DESC") } else { (">", "ASC") };
+
+    let (offset, total) = match cursor {
+        Some(cursor) => {
+            let cursor = decode_cursor(cursor)?;
+            if cursor.sort != sort {
+                bail!("The list cursor belongs to a different sort order");
+            }
+            values.push(sql_value(&cursor.key));
+            let key_param = values.len();
+            values.push(cursor.id.into());
+            let id_param = values.len();
+            where_sql.push_str(&format!(
+                " AND ({key} {comparison} ?{key_param} OR ({key} = ?{key_param} AND h.message_id {comparison} ?{id_param}))"
+            ));
+            (cursor.position, None)
+        }
+        None => (0, Some(count(conn, &where_sql, &values)?)),
+    };
+
+    // One row past the window tells whether another follows
+    values.push((limit + 1).into());
+    let sql = format!(
+        "SELECT {HEADER_COLUMNS}, {key} FROM gmail_message_headers h WHERE {where_sql}
+         ORDER BY {key} {direction}, h.message_id {direction} LIMIT ?{}",
+        values.len()
+    );
+
+    let mut stmt = conn.prepare(&sql)?;
+    let mut rows = stmt
+        .query_map(params_from_iter(values), header_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to load message headers")?;
+
+    let more = rows.len() as i64 > limit;
+    rows.truncate(limit as usize);
+    let next_cursor = match rows.last() {
+        Some((header, key)) if more => Some(encode_cursor(&Cursor {
+            sort,
+            key: json_value(key.clone()),
+            id: header.message_id.clone(),
+            position: offset + rows.len() as i64,
+        })?),
+        _ => None,
+    };
+
+    Ok(HeaderWindow {
+        headers: rows.into_iter().map(|(header, _)| header).collect(),
+        total,
+        offset,
+        next_cursor,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::operations::backfill_operations::{store_message, StoredMessage};
+    use crate::database::schema::run_migrations;
+    use serde_json::json;
+
+    fn store(conn: &Connection, id: &str, date: i64, from: &str, labels: &[&str]) {
+        store_message(conn, &StoredMessage {
+            account_id: "acc".to_string(),
+            message_id: id.to_string(),
+            thread_id: format!("t-{}", id),
+            label_ids: labels.iter().map(|l| l.to_string()).collect(),
+            internal_date: Some(date),
+            content: "headers".to_string(),
+            message_data: json!({
+                "snippet": format!("snippet {}", id),
+                "parsed_content": { "subject": format!("Subject {}", id), "from": { "email": from, "name": null }, "attachments": [] },
+            }),
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_windows_by_offset_and_cursor() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        for (i, id) in ["a", "b", "c", "d", "e"].iter().enumerate() {
+            store(&conn, id, 1_000 + i as i64, &format!("{}@example.com", id), &["INBOX", "UNREAD"]);
+        }
+        store(&conn, "x", 5_000, "x@example.com", &["SENT"]);
+
+        let inbox = HeaderFilter { label_id: Some("INBOX".to_string()), ..Default::default() };
+        let first = get_headers(&conn, "acc", &inbox, HeaderSort::DateDesc, 2, None).unwrap();
+        assert_eq!(first.total, Some(5));
+        assert_eq!(first.headers.iter().map(|h| h.message_id.as_str()).collect::<Vec<_>>(), vec!["e", "d"]);
+        assert!(first.headers[0].is_unread);
+
+        // New mail above the window does not shift the next page
+        store(&conn, "f", 9_000, "f@example.com", &["INBOX"]);
+        let second = get_headers(&conn, "acc", &inbox, HeaderSort::DateDesc, 2, first.next_cursor.as_deref()).unwrap();
+        assert_eq!(second.headers.iter().map(|h| h.message_id.as_str()).collect::<Vec<_>>(), vec!["c", "b"]);
+        assert_eq!((second.total, second.offset), (None, 2));
+
+        let last = get_headers(&conn, "acc", &inbox, HeaderSort::DateDesc, 2, second.next_cursor.as_deref()).unwrap();
+        assert_eq!(last.headers.len(), 1);
+        assert_eq!(last.offset, 4);
+        assert!(last.next_cursor.is_none());
+
+        // Label changes and deletes in the store reach the header table
+        conn.execute("UPDATE gmail_message_store SET label_ids = '[\"SENT\"]' WHERE message_id = 'a'", []).unwrap();
+        conn.execute("DELETE FROM gmail_message_store WHERE message_id = 'b'", []).unwrap();
+        let refreshed = get_headers(&conn, "acc", &inbox, HeaderSort::DateDesc, 10, None).unwrap();
+        assert_eq!(refreshed.headers.iter().map(|h| h.message_id.as_str()).collect::<Vec<_>>(), vec!["f", "e", "d", "c"]);
+
+        let by_sender = get_headers(&conn, "acc", &HeaderFilter::default(), HeaderSort::Sender, 3, None).unwrap();
+        assert_eq!(by_sender.headers.iter().map(|h| h.message_id.as_str()).collect::<Vec<_>>(), vec!["a", "c", "d"]);
+        let rest = get_headers(&conn, "acc", &HeaderFilter::default(), HeaderSort::Sender, 3, by_sender.next_cursor.as_deref()).unwrap();
+        assert_eq!(rest.headers.iter().map(|h| h.message_id.as_str()).collect::<Vec<_>>(), vec!["e", "f", "x"]);
+        assert!(rest.next_cursor.is_none());
+        assert!(get_headers(&conn, "acc", &inbox, HeaderSort::Sender, 2, first.next_cursor.as_deref()).is_err());
+    }
+}
@@ -8,20 +8,65 @@
 
 // Core operations modules
 pub mod agent_operations;
+pub mod archive_operations;
+pub mod attachment_scan_operations;
+pub mod auto_reply_operations;
+pub mod autosave_operations;
+pub mod backfill_operations;
+pub mod board_operations;
+pub mod cache_delta_operations;
 pub mod cache_operations;
+pub mod campaign_operations;
+pub mod canvas_operations;
+pub mod change_journal_operations;
+pub mod chat_attachment_operations;
 pub mod chat_operations;
+pub mod clipboard_capture_operations;
+pub mod contact_operations;
 pub mod conversation_operations;
+pub mod digest_operations;
+pub mod draft_operations;
+pub mod feed_operations;
 pub mod folder_operations;
+pub mod image_preference_operations;
+pub mod imported_calendar_operations;
+pub mod label_cache_operations;
 pub mod link_operations;
+pub mod link_preview_operations;
+pub mod llm_provider_operations;
 pub mod log_operations;
 pub mod mcp_operations;
+pub mod message_header_operations;
+pub mod model_registry_operations;
+pub mod mute_operations;
 pub mod n8n_operations;
 pub mod note_operations;
+pub mod note_template_operations;
 pub mod onboarding_operations;
+pub mod pending_change_operations;
 pub mod performance_operations;
 pub mod preference_operations;
 pub mod project_operations;
+pub mod prompt_template_operations;
+pub mod rate_limit_operations;
+pub mod receipt_operations;
+pub mod saved_search_operations;
+pub mod search_operations;
+pub mod send_as_operations;
+pub mod shipment_operations;
+pub mod sync_policy_operations;
+pub mod sync_run_operations;
+pub mod task_comment_operations;
+pub mod task_import_operations;
+pub mod task_queue_operations;
 pub mod template_operations;
+pub mod timeline_operations;
+pub mod transcript_operations;
+pub mod translation_operations;
+pub mod triage_operations;
+pub mod vault_operations;
+pub mod vip_operations;
+pub mod workspace_operations;
 
 // Re-export all operations for convenience
 // Note: These are comprehensive database operations - some are used by current commands,
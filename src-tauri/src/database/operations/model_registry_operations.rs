@@ -0,0 +1,161 @@
+//! Model registry and routing rule operations
+//!
+//! `llm_models` lists the models each provider offers with what is known
+//! about them; `llm_routing_rules` says how to pick one for a feature when
+//! none was chosen explicitly.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelRecord {
+    pub provider_id: String,
+    pub name: String,
+    pub family: Option<String>,
+    pub parameter_size: Option<String>,
+    pub quantization: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub context_length: Option<i64>,
+    /// JSON array of capability names
+    pub capabilities: String,
+    pub refreshed_at: String,
+}
+
+fn model_from_row(row: &Row) -> rusqlite::Result<ModelRecord> {
+    Ok(ModelRecord {
+        provider_id: row.get(0)?,
+        name: row.get(1)?,
+        family: row.get(2)?,
+        parameter_size: row.get(3)?,
+        quantization: row.get(4)?,
+        size_bytes: row.get(5)?,
+        context_length: row.get(6)?,
+        capabilities: row.get(7)?,
+        refreshed_at: row.get(8)?,
+    })
+}
+
+const MODEL_COLUMNS: &str =
+    "provider_id, name, family, parameter_size, quantization, size_bytes, context_length, capabilities, refreshed_at";
+
+pub fn list_models(conn: &Connection, provider_id: Option<&str>) -> Result<Vec<ModelRecord>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM llm_models WHERE ?1 IS NULL OR provider_id = ?1 ORDER BY provider_id, name",
+        MODEL_COLUMNS
+    ))?;
+    let models = stmt
+        .query_map(params![provider_id], model_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list registered models")?;
+    Ok(models)
+}
+
+pub fn get_model(conn: &Connection, provider_id: &str, name: &str) -> Result<Option<ModelRecord>> {
+    conn.query_row(
+        &format!("SELECT {} FROM llm_models WHERE provider_id = ?1 AND name = ?2", MODEL_COLUMNS),
+        params![provider_id, name],
+        model_from_row,
+    )
+    .optional()
+    .context("Failed to load registered model")
+}
+
+/// Replace the registered models of a provider with what it offers now
+pub fn replace_models(conn: &mut Connection, provider_id: &str, models: &[ModelRecord]) -> Result<()> {
+    let tx = conn.transaction()?;
+    tx.execute("DELETE FROM llm_models WHERE provider_id = ?1", params![provider_id])
+        .context("Failed to clear registered models")?;
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO llm_models (provider_id, name, family, parameter_size, quantization, size_bytes, context_length, capabilities)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?;
+        for model in models {
+            stmt.execute(params![
+                provider_id,
+                model.name,
+                model.family,
+                model.parameter_size,
+                model.quantization,
+                model.size_bytes,
+                model.context_length,
+                model.capabilities,
+            ]).context("Failed to register model")?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingRuleRecord {
+    pub feature: String,
+    pub provider_id: String,
+    pub capability: String,
+    /// "smallest" or "largest"
+    pub prefer: String,
+    pub max_size_bytes: Option<i64>,
+    pub min_context_length: Option<i64>,
+    pub updated_at: String,
+}
+
+fn rule_from_row(row: &Row) -> rusqlite::Result<RoutingRuleRecord> {
+    Ok(RoutingRuleRecord {
+        feature: row.get(0)?,
+        provider_id: row.get(1)?,
+        capability: row.get(2)?,
+        prefer: row.get(3)?,
+        max_size_bytes: row.get(4)?,
+        min_context_length: row.get(5)?,
+        updated_at: row.get(6)?,
+    })
+}
+
+const RULE_COLUMNS: &str = "feature, provider_id, capability, prefer, max_size_bytes, min_context_length, updated_at";
+
+pub fn list_rules(conn: &Connection) -> Result<Vec<RoutingRuleRecord>> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM llm_routing_rules ORDER BY feature", RULE_COLUMNS))?;
+    let rules = stmt
+        .query_map([], rule_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list routing rules")?;
+    Ok(rules)
+}
+
+pub fn get_rule(conn: &Connection, feature: &str) -> Result<Option<RoutingRuleRecord>> {
+    conn.query_row(
+        &format!("SELECT {} FROM llm_routing_rules WHERE feature = ?1", RULE_COLUMNS),
+        params![feature],
+        rule_from_row,
+    )
+    .optional()
+    .context("Failed to load routing rule")
+}
+
+pub fn set_rule(
+    conn: &Connection,
+    feature: &str,
+    provider_id: &str,
+    capability: &str,
+    prefer: &str,
+    max_size_bytes: Option<i64>,
+    min_context_length: Option<i64>,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO llm_routing_rules (feature, provider_id, capability, prefer, max_size_bytes, min_context_length)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(feature) DO UPDATE SET provider_id = excluded.provider_id, capability = excluded.capability,
+            prefer = excluded.prefer, max_size_bytes = excluded.max_size_bytes,
+            min_context_length = excluded.min_context_length, updated_at = datetime('now')",
+        params![feature, provider_id, capability, prefer, max_size_bytes, min_context_length],
+    ).context("Failed to save routing rule")?;
+    Ok(())
+}
+
+pub fn delete_rule(conn: &Connection, feature: &str) -> Result<bool> {
+    let deleted = conn
+        .execute("DELETE FROM llm_routing_rules WHERE feature = ?1", params![feature])
+        .context("Failed to delete routing rule")?;
+    Ok(deleted > 0)
+}
@@ -0,0 +1,76 @@
+//! Muted thread database operations
+//!
+//! New messages on a muted thread are archived as they arrive and never
+//! raise a new-mail notification.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MutedThread {
+    pub account_id: String,
+    pub thread_id: String,
+    pub subject: Option<String>,
+    pub archived_count: i64,
+    pub muted_at: String,
+}
+
+fn muted_thread_from_row(row: &Row) -> rusqlite::Result<MutedThread> {
+    Ok(MutedThread {
+        account_id: row.get(0)?,
+        thread_id: row.get(1)?,
+        subject: row.get(2)?,
+        archived_count: row.get(3)?,
+        muted_at: row.get(4)?,
+    })
+}
+
+/// Mute a thread; muting an already muted thread keeps its original timestamp
+pub fn mute_thread(conn: &Connection, account_id: &str, thread_id: &str, subject: Option<&str>) -> Result<()> {
+    conn.execute(
+        "INSERT INTO muted_threads (account_id, thread_id, subject) VALUES (?1, ?2, ?3)
+         ON CONFLICT(account_id, thread_id) DO UPDATE SET subject = COALESCE(excluded.subject, subject)",
+        params![account_id, thread_id, subject],
+    ).context("Failed to mute thread")?;
+    Ok(())
+}
+
+/// Returns false if the thread was not muted
+pub fn unmute_thread(conn: &Connection, account_id: &str, thread_id: &str) -> Result<bool> {
+    let removed = conn.execute(
+        "DELETE FROM muted_threads WHERE account_id = ?1 AND thread_id = ?2",
+        params![account_id, thread_id],
+    ).context("Failed to unmute thread")?;
+    Ok(removed > 0)
+}
+
+pub fn list_muted_threads(conn: &Connection, account_id: &str) -> Result<Vec<MutedThread>> {
+    let mut stmt = conn.prepare(
+        "SELECT account_id, thread_id, subject, archived_count, muted_at
+         FROM muted_threads WHERE account_id = ?1 ORDER BY muted_at DESC",
+    )?;
+    let threads = stmt
+        .query_map(params![account_id], muted_thread_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list muted threads")?;
+    Ok(threads)
+}
+
+pub fn muted_thread_ids(conn: &Connection, account_id: &str) -> Result<HashSet<String>> {
+    let mut stmt = conn.prepare("SELECT thread_id FROM muted_threads WHERE account_id = ?1")?;
+    let ids = stmt
+        .query_map(params![account_id], |row| row.get(0))?
+        .collect::<rusqlite::Result<HashSet<String>>>()
+        .context("Failed to load muted thread ids")?;
+    Ok(ids)
+}
+
+pub fn add_archived_count(conn: &Connection, account_id: &str, thread_id: &str, count: i64) -> Result<()> {
+    conn.execute(
+        "UPDATE muted_threads SET archived_count = archived_count + ?1 WHERE account_id = ?2 AND thread_id = ?3",
+        params![count, account_id, thread_id],
+    ).context("Failed to update muted thread archive count")?;
+    Ok(())
+}
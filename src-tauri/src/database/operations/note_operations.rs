@@ -40,29 +40,34 @@ pub fn create_note(conn: &Connection, title: &str, content: &str, user_id: &str,
 
 pub fn update_note(conn: &mut Connection, id: i32, title: Option<&str>, content: Option<&str>, folder_id: Option<Option<i32>>) -> Result<Note> {
     let tx = conn.transaction()?;
+    let note = update_note_in(&tx, id, title, content, folder_id)?;
+    tx.commit()?;
+    Ok(note)
+}
+
+/// [`update_note`] inside a transaction the caller already holds
+pub fn update_note_in(conn: &Connection, id: i32, title: Option<&str>, content: Option<&str>, folder_id: Option<Option<i32>>) -> Result<Note> {
     let mut updated = false;
 
     if let Some(t) = title {
-        tx.execute("UPDATE notes SET title = ?1 WHERE id = ?2", params![t, id])?;
+        conn.execute("UPDATE notes SET title = ?1 WHERE id = ?2", params![t, id])?;
         updated = true;
     }
 
     if let Some(c) = content {
-        tx.execute("UPDATE notes SET content = ?1 WHERE id = ?2", params![c, id])?;
+        conn.execute("UPDATE notes SET content = ?1 WHERE id = ?2", params![c, id])?;
         updated = true;
     }
     
     if let Some(fid) = folder_id {
-        tx.execute("UPDATE notes SET folder_id = ?1 WHERE id = ?2", params![fid, id])?;
+        conn.execute("UPDATE notes SET folder_id = ?1 WHERE id = ?2", params![fid, id])?;
         updated = true;
     }
 
     if updated {
         let now = Local::now().naive_local();
-        tx.execute("UPDATE notes SET updated_at = ?1 WHERE id = ?2", params![now, id])?;
+        conn.execute("UPDATE notes SET updated_at = ?1 WHERE id = ?2", params![now, id])?;
     }
-    
-    tx.commit()?;
 
     get_note(conn, id).and_then(|n| n.ok_or_else(|| rusqlite::Error::QueryReturnedNoRows))
 }
@@ -90,4 +95,20 @@ pub fn get_all_notes(conn: &Connection) -> Result<Vec<Note>> {
         notes.push(note?);
     }
     Ok(notes)
-} 
\ No newline at end of file
+} 
+/// Record the page a note was clipped from and its tags
+pub fn set_note_source(conn: &Connection, id: i32, source_url: Option<&str>, tags: &[String]) -> Result<()> {
+    let tags = serde_json::to_string(tags).unwrap_or_else(|_| "[]".to_string());
+    conn.execute(
+        "UPDATE notes SET source_url = ?1, tags = ?2 WHERE id = ?3",
+        params![source_url, tags, id],
+    )?;
+    Ok(())
+}
+
+/// Notes already clipped from a page, newest first
+pub fn find_notes_by_source(conn: &Connection, source_url: &str) -> Result<Vec<i32>> {
+    let mut stmt = conn.prepare("SELECT id FROM notes WHERE source_url = ?1 ORDER BY id DESC")?;
+    let ids = stmt.query_map(params![source_url], |row| row.get(0))?;
+    ids.collect()
+}
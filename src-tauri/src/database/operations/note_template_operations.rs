@@ -0,0 +1,136 @@
+//! Note templates and the daily note index
+//!
+//! Templates hold a title and a body with `{{variable}}` placeholders;
+//! `daily_notes` remembers which note was created for each local date.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NoteTemplate {
+    pub id: i64,
+    pub name: String,
+    pub title: String,
+    pub content: String,
+    pub updated_at: String,
+}
+
+fn template_from_row(row: &Row) -> rusqlite::Result<NoteTemplate> {
+    Ok(NoteTemplate {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        title: row.get(2)?,
+        content: row.get(3)?,
+        updated_at: row.get(4)?,
+    })
+}
+
+const TEMPLATE_COLUMNS: &str = "id, name, title, content, updated_at";
+
+pub fn list_templates(conn: &Connection) -> Result<Vec<NoteTemplate>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM note_templates ORDER BY name COLLATE NOCASE",
+        TEMPLATE_COLUMNS
+    ))?;
+    let templates = stmt
+        .query_map([], template_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list note templates")?;
+    Ok(templates)
+}
+
+pub fn get_template(conn: &Connection, id: i64) -> Result<Option<NoteTemplate>> {
+    conn.query_row(
+        &format!("SELECT {} FROM note_templates WHERE id = ?1", TEMPLATE_COLUMNS),
+        params![id],
+        template_from_row,
+    )
+    .optional()
+    .context("Failed to load note template")
+}
+
+pub fn get_template_by_name(conn: &Connection, name: &str) -> Result<Option<NoteTemplate>> {
+    conn.query_row(
+        &format!("SELECT {} FROM note_templates WHERE name = ?1 COLLATE NOCASE", TEMPLATE_COLUMNS),
+        params![name],
+        template_from_row,
+    )
+    .optional()
+    .context("Failed to load note template")
+}
+
+pub fn create_template(conn: &Connection, name: &str, title: &str, content: &str) -> Result<NoteTemplate> {
+    conn.execute(
+        "INSERT INTO note_templates (name, title, content) VALUES (?1, ?2, ?3)",
+        params![name, title, content],
+    )
+    .context("Failed to create note template")?;
+    get_template(conn, conn.last_insert_rowid())?.context("Note template vanished after insert")
+}
+
+/// Replace a template's fields; `None` when it does not exist
+pub fn update_template(conn: &Connection, id: i64, name: &str, title: &str, content: &str) -> Result<Option<NoteTemplate>> {
+    let updated = conn
+        .execute(
+            "UPDATE note_templates SET name = ?1, title = ?2, content = ?3, updated_at = datetime('now') WHERE id = ?4",
+            params![name, title, content, id],
+        )
+        .context("Failed to update note template")?;
+    if updated == 0 {
+        return Ok(None);
+    }
+    get_template(conn, id)
+}
+
+pub fn delete_template(conn: &Connection, id: i64) -> Result<bool> {
+    let deleted = conn
+        .execute("DELETE FROM note_templates WHERE id = ?1", params![id])
+        .context("Failed to delete note template")?;
+    Ok(deleted > 0)
+}
+
+/// Note created for a local date ("YYYY-MM-DD"), if any
+pub fn daily_note_id(conn: &Connection, date: &str) -> Result<Option<i32>> {
+    conn.query_row("SELECT note_id FROM daily_notes WHERE date = ?1", params![date], |row| row.get(0))
+        .optional()
+        .context("Failed to look up the daily note")
+}
+
+pub fn set_daily_note(conn: &Connection, date: &str, note_id: i32) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO daily_notes (date, note_id) VALUES (?1, ?2)",
+        params![date, note_id],
+    )
+    .context("Failed to record the daily note")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    #[test]
+    fn test_template_crud_and_daily_index() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let meeting = create_template(&conn, "Meeting", "{{title}}", "Attendees:\n").unwrap();
+        create_template(&conn, "daily", "{{date}}", "# {{weekday}}").unwrap();
+        assert!(create_template(&conn, "Meeting", "x", "y").is_err());
+        assert_eq!(list_templates(&conn).unwrap().iter().map(|t| t.name.as_str()).collect::<Vec<_>>(), ["daily", "Meeting"]);
+        assert_eq!(get_template_by_name(&conn, "DAILY").unwrap().unwrap().content, "# {{weekday}}");
+
+        let updated = update_template(&conn, meeting.id, "Meeting notes", "{{title}}", "Attendees:\nActions:\n").unwrap().unwrap();
+        assert_eq!(updated.name, "Meeting notes");
+        assert!(update_template(&conn, 999, "a", "b", "c").unwrap().is_none());
+        assert!(delete_template(&conn, meeting.id).unwrap());
+        assert!(!delete_template(&conn, meeting.id).unwrap());
+
+        assert_eq!(daily_note_id(&conn, "2026-10-16").unwrap(), None);
+        set_daily_note(&conn, "2026-10-16", 7).unwrap();
+        set_daily_note(&conn, "2026-10-16", 9).unwrap();
+        assert_eq!(daily_note_id(&conn, "2026-10-16").unwrap(), Some(9));
+    }
+}
@@ -3,7 +3,7 @@
 //! This module provides CRUD operations for user onboarding status.
 
 use anyhow::Result;
-use rusqlite::{Connection, OptionalExtension};
+use rusqlite::{params, Connection, OptionalExtension};
 
 /// Onboarding status structure
 #[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
@@ -61,21 +61,22 @@ pub fn update_onboarding_status(
           ollama_setup_status, selected_models, sample_data_created, tour_progress,
           started_at, completed_at)
          VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
-        [
-            &format!("onboarding_{}", user_id),
+        params![
+            format!("onboarding_{}", user_id),
             user_id,
-            &status.is_completed.to_string(),
-            &status.current_step,
-            &status.completed_steps,
-            status.selected_persona.as_deref().unwrap_or(""),
-            &status.ollama_setup_status,
-            &status.selected_models,
-            &status.sample_data_created.to_string(),
-            &status.tour_progress,
-            &status.started_at,
-            status.completed_at.as_deref().unwrap_or(""),
+            status.is_completed,
+            status.current_step,
+            status.completed_steps,
+            status.selected_persona,
+            status.ollama_setup_status,
+            status.selected_models,
+            status.sample_data_created,
+            status.tour_progress,
+            status.started_at,
+            status.completed_at,
         ],
     )?;
 
     Ok(())
-} 
\ No newline at end of file
+}
+
@@ -0,0 +1,328 @@
+//! Pending change and sync conflict database operations
+//!
+//! This module persists local edits that still need to reach the server
+//! (`pending_changes`) together with the server state they were based on,
+//! and the conflicts detected when the server changed in the meantime
+//! (`sync_conflicts`).
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+/// A local mutation waiting to be pushed to the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingChange {
+    pub id: i64,
+    pub account_id: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub container_id: Option<String>,
+    pub operation: String,
+    pub payload: serde_json::Value,
+    pub base_payload: Option<serde_json::Value>,
+    pub base_etag: Option<String>,
+    pub base_updated: Option<String>,
+    pub status: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub created_at: String,
+}
+
+/// Input for recording a new pending change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewPendingChange {
+    pub account_id: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub container_id: Option<String>,
+    pub operation: String,
+    pub payload: serde_json::Value,
+    pub base_payload: Option<serde_json::Value>,
+    pub base_etag: Option<String>,
+    pub base_updated: Option<String>,
+}
+
+/// A detected conflict awaiting manual resolution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncConflict {
+    pub id: i64,
+    pub pending_change_id: i64,
+    pub account_id: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub conflicting_fields: Vec<String>,
+    pub local_payload: serde_json::Value,
+    pub remote_payload: serde_json::Value,
+    pub base_payload: Option<serde_json::Value>,
+    pub resolution: Option<String>,
+    pub detected_at: String,
+    pub resolved_at: Option<String>,
+}
+
+fn parse_json(text: Option<String>) -> Option<serde_json::Value> {
+    text.and_then(|t| serde_json::from_str(&t).ok())
+}
+
+fn pending_change_from_row(row: &Row) -> rusqlite::Result<PendingChange> {
+    Ok(PendingChange {
+        id: row.get(0)?,
+        account_id: row.get(1)?,
+        entity_type: row.get(2)?,
+        entity_id: row.get(3)?,
+        container_id: row.get(4)?,
+        operation: row.get(5)?,
+        payload: parse_json(row.get(6)?).unwrap_or(serde_json::Value::Null),
+        base_payload: parse_json(row.get(7)?),
+        base_etag: row.get(8)?,
+        base_updated: row.get(9)?,
+        status: row.get(10)?,
+        attempts: row.get(11)?,
+        last_error: row.get(12)?,
+        created_at: row.get(13)?,
+    })
+}
+
+fn conflict_from_row(row: &Row) -> rusqlite::Result<SyncConflict> {
+    let fields: String = row.get(5)?;
+    Ok(SyncConflict {
+        id: row.get(0)?,
+        pending_change_id: row.get(1)?,
+        account_id: row.get(2)?,
+        entity_type: row.get(3)?,
+        entity_id: row.get(4)?,
+        conflicting_fields: serde_json::from_str(&fields).unwrap_or_default(),
+        local_payload: parse_json(row.get(6)?).unwrap_or(serde_json::Value::Null),
+        remote_payload: parse_json(row.get(7)?).unwrap_or(serde_json::Value::Null),
+        base_payload: parse_json(row.get(8)?),
+        resolution: row.get(9)?,
+        detected_at: row.get(10)?,
+        resolved_at: row.get(11)?,
+    })
+}
+
+const PENDING_CHANGE_COLUMNS: &str = "id, account_id, entity_type, entity_id, container_id, operation, payload,
+    base_payload, base_etag, base_updated, status, attempts, last_error, created_at";
+
+const CONFLICT_COLUMNS: &str = "id, pending_change_id, account_id, entity_type, entity_id, conflicting_fields,
+    local_payload, remote_payload, base_payload, resolution, detected_at, resolved_at";
+
+// ===== Pending Change Operations =====
+
+/// Record a pending change and return its ID
+pub fn record_pending_change(conn: &Connection, change: &NewPendingChange) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO pending_changes
+            (account_id, entity_type, entity_id, container_id, operation, payload, base_payload, base_etag, base_updated)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            change.account_id,
+            change.entity_type,
+            change.entity_id,
+            change.container_id,
+            change.operation,
+            change.payload.to_string(),
+            change.base_payload.as_ref().map(|v| v.to_string()),
+            change.base_etag,
+            change.base_updated,
+        ],
+    ).context("Failed to record pending change")?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+/// Get a pending change by ID
+pub fn get_pending_change(conn: &Connection, change_id: i64) -> Result<Option<PendingChange>> {
+    conn.query_row(
+        &format!("SELECT {} FROM pending_changes WHERE id = ?1", PENDING_CHANGE_COLUMNS),
+        params![change_id],
+        pending_change_from_row,
+    )
+    .optional()
+    .context("Failed to get pending change")
+}
+
+/// List changes still waiting to be pushed for an account, oldest first
+pub fn list_pending_changes(conn: &Connection, account_id: &str) -> Result<Vec<PendingChange>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM pending_changes WHERE account_id = ?1 AND status = 'pending' ORDER BY id ASC",
+        PENDING_CHANGE_COLUMNS
+    ))?;
+
+    let changes = stmt
+        .query_map(params![account_id], pending_change_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list pending changes")?;
+
+    Ok(changes)
+}
+
+/// Changes that were rejected by the server and need the user's attention
+pub fn list_failed_changes(conn: &Connection, account_id: &str) -> Result<Vec<PendingChange>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM pending_changes WHERE account_id = ?1 AND status = 'failed' ORDER BY id ASC",
+        PENDING_CHANGE_COLUMNS
+    ))?;
+
+    let changes = stmt
+        .query_map(params![account_id], pending_change_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list failed changes")?;
+
+    Ok(changes)
+}
+
+/// The queued, not yet pushed create for an entity, if any
+pub fn find_unpushed_create(conn: &Connection, entity_type: &str, entity_id: &str) -> Result<Option<PendingChange>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM pending_changes
+             WHERE entity_type = ?1 AND entity_id = ?2 AND operation = 'create' AND status IN ('pending', 'failed')
+             ORDER BY id DESC LIMIT 1",
+            PENDING_CHANGE_COLUMNS
+        ),
+        params![entity_type, entity_id],
+        pending_change_from_row,
+    )
+    .optional()
+    .context("Failed to find queued create")
+}
+
+/// Replace the payload of a change that has not been pushed yet
+pub fn update_pending_payload(conn: &Connection, change_id: i64, payload: &serde_json::Value) -> Result<()> {
+    conn.execute(
+        "UPDATE pending_changes SET payload = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
+        params![payload.to_string(), change_id],
+    ).context("Failed to update pending change payload")?;
+    Ok(())
+}
+
+/// Accounts with changes waiting to be pushed
+pub fn accounts_with_pending_changes(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT DISTINCT account_id FROM pending_changes WHERE status = 'pending'")?;
+    let accounts = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()
+        .context("Failed to list accounts with pending changes")?;
+    Ok(accounts)
+}
+
+/// Count pending changes for an account
+pub fn count_pending_changes(conn: &Connection, account_id: &str) -> Result<i64> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM pending_changes WHERE account_id = ?1 AND status = 'pending'",
+        params![account_id],
+        |row| row.get(0),
+    )
+    .context("Failed to count pending changes")
+}
+
+/// Update the status of a pending change (pending, applied, conflict, discarded, failed)
+pub fn set_pending_change_status(conn: &Connection, change_id: i64, status: &str, error: Option<&str>) -> Result<()> {
+    conn.execute(
+        "UPDATE pending_changes
+         SET status = ?1, last_error = ?2, attempts = attempts + CASE WHEN ?2 IS NULL THEN 0 ELSE 1 END,
+             updated_at = CURRENT_TIMESTAMP
+         WHERE id = ?3",
+        params![status, error, change_id],
+    ).context("Failed to update pending change status")?;
+    Ok(())
+}
+
+/// Rebase a pending change onto a newer server state and queue it again.
+///
+/// Used after manual conflict resolution so the next flush applies the
+/// chosen payload without re-detecting the same conflict.
+pub fn rebase_pending_change(
+    conn: &Connection,
+    change_id: i64,
+    payload: &serde_json::Value,
+    base_payload: &serde_json::Value,
+    base_etag: Option<&str>,
+    base_updated: Option<&str>,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE pending_changes
+         SET payload = ?1, base_payload = ?2, base_etag = ?3, base_updated = ?4,
+             status = 'pending', last_error = NULL, updated_at = CURRENT_TIMESTAMP
+         WHERE id = ?5",
+        params![payload.to_string(), base_payload.to_string(), base_etag, base_updated, change_id],
+    ).context("Failed to rebase pending change")?;
+    Ok(())
+}
+
+/// Remove applied and discarded changes older than the given number of days
+pub fn purge_settled_changes(conn: &Connection, older_than_days: i64) -> Result<usize> {
+    conn.execute(
+        "DELETE FROM pending_changes
+         WHERE status IN ('applied', 'discarded')
+           AND updated_at < datetime('now', ?1)",
+        params![format!("-{} days", older_than_days)],
+    ).context("Failed to purge settled changes")
+}
+
+// ===== Conflict Operations =====
+
+/// Record a conflict for a pending change and flag the change as conflicted
+pub fn record_conflict(
+    conn: &Connection,
+    change: &PendingChange,
+    conflicting_fields: &[String],
+    remote_payload: &serde_json::Value,
+) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO sync_conflicts
+            (pending_change_id, account_id, entity_type, entity_id, conflicting_fields, local_payload, remote_payload, base_payload)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            change.id,
+            change.account_id,
+            change.entity_type,
+            change.entity_id,
+            serde_json::to_string(conflicting_fields)?,
+            change.payload.to_string(),
+            remote_payload.to_string(),
+            change.base_payload.as_ref().map(|v| v.to_string()),
+        ],
+    ).context("Failed to record sync conflict")?;
+
+    let conflict_id = conn.last_insert_rowid();
+    set_pending_change_status(conn, change.id, "conflict", None)?;
+    Ok(conflict_id)
+}
+
+/// Get a conflict by ID
+pub fn get_conflict(conn: &Connection, conflict_id: i64) -> Result<Option<SyncConflict>> {
+    conn.query_row(
+        &format!("SELECT {} FROM sync_conflicts WHERE id = ?1", CONFLICT_COLUMNS),
+        params![conflict_id],
+        conflict_from_row,
+    )
+    .optional()
+    .context("Failed to get sync conflict")
+}
+
+/// List unresolved conflicts, optionally for a single account
+pub fn list_unresolved_conflicts(conn: &Connection, account_id: Option<&str>) -> Result<Vec<SyncConflict>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM sync_conflicts
+         WHERE resolved_at IS NULL AND (?1 IS NULL OR account_id = ?1)
+         ORDER BY detected_at ASC",
+        CONFLICT_COLUMNS
+    ))?;
+
+    let conflicts = stmt
+        .query_map(params![account_id], conflict_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list sync conflicts")?;
+
+    Ok(conflicts)
+}
+
+/// Mark a conflict resolved with the given strategy
+pub fn mark_conflict_resolved(conn: &Connection, conflict_id: i64, resolution: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE sync_conflicts SET resolution = ?1, resolved_at = CURRENT_TIMESTAMP WHERE id = ?2",
+        params![resolution, conflict_id],
+    ).context("Failed to resolve sync conflict")?;
+    Ok(())
+}
@@ -281,6 +281,49 @@ pub fn get_performance_metrics_count(conn: &Connection) -> Result<i32> {
     Ok(count)
 }
 
+/// Token usage of LLM requests grouped by feature and model
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LlmUsageSummary {
+    pub feature: String,
+    pub provider_id: String,
+    pub model: String,
+    pub requests: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub avg_latency_ms: f64,
+}
+
+/// LLM token usage recorded since `since`, busiest first
+pub fn get_llm_usage_summary(conn: &Connection, since: NaiveDateTime) -> Result<Vec<LlmUsageSummary>> {
+    let since_str = since.format("%Y-%m-%d %H:%M:%S").to_string();
+    let mut stmt = conn.prepare(
+        "SELECT json_extract(metadata, '$.feature'), json_extract(metadata, '$.provider_id'),
+                json_extract(metadata, '$.model'), COUNT(*),
+                COALESCE(SUM(json_extract(metadata, '$.prompt_tokens')), 0),
+                COALESCE(SUM(json_extract(metadata, '$.completion_tokens')), 0),
+                COALESCE(AVG(json_extract(metadata, '$.latency_ms')), 0)
+         FROM performance_metrics
+         WHERE metric_type = 'token_count' AND json_extract(metadata, '$.source') = 'llm' AND timestamp >= ?1
+         GROUP BY 1, 2, 3
+         ORDER BY 4 DESC",
+    )?;
+    let summary = stmt
+        .query_map(params![since_str], |row| {
+            Ok(LlmUsageSummary {
+                feature: row.get(0)?,
+                provider_id: row.get(1)?,
+                model: row.get(2)?,
+                requests: row.get(3)?,
+                prompt_tokens: row.get(4)?,
+                completion_tokens: row.get(5)?,
+                avg_latency_ms: row.get(6)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to summarize LLM usage")?;
+    Ok(summary)
+}
+
 /// Get performance metrics (legacy function for command compatibility)
 pub fn get_performance_metrics(
     _metric_type: Option<MetricType>,
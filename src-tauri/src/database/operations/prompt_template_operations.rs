@@ -0,0 +1,188 @@
+//! Prompt templates with categories, variables and version history
+//!
+//! Built on the `chat_templates` table. Every edit bumps `version` and
+//! keeps the previous content in `chat_template_versions`.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplateRecord {
+    pub id: i64,
+    pub name: String,
+    pub category: String,
+    pub description: Option<String>,
+    pub content: String,
+    /// JSON array of variable definitions
+    pub variables: String,
+    pub version: i64,
+    pub updated_at: Option<String>,
+}
+
+fn template_from_row(row: &Row) -> rusqlite::Result<PromptTemplateRecord> {
+    Ok(PromptTemplateRecord {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        category: row.get(2)?,
+        description: row.get(3)?,
+        content: row.get(4)?,
+        variables: row.get(5)?,
+        version: row.get(6)?,
+        updated_at: row.get(7)?,
+    })
+}
+
+const TEMPLATE_COLUMNS: &str =
+    "id, template_name, category, description, template_content, variables, version, updated_at";
+
+pub fn list_templates(conn: &Connection, category: Option<&str>) -> Result<Vec<PromptTemplateRecord>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM chat_templates WHERE ?1 IS NULL OR category = ?1 ORDER BY category, template_name",
+        TEMPLATE_COLUMNS
+    ))?;
+    let templates = stmt
+        .query_map(params![category], template_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list prompt templates")?;
+    Ok(templates)
+}
+
+pub fn get_template(conn: &Connection, id: i64) -> Result<Option<PromptTemplateRecord>> {
+    conn.query_row(
+        &format!("SELECT {} FROM chat_templates WHERE id = ?1", TEMPLATE_COLUMNS),
+        params![id],
+        template_from_row,
+    )
+    .optional()
+    .context("Failed to load prompt template")
+}
+
+pub fn get_template_by_name(conn: &Connection, name: &str) -> Result<Option<PromptTemplateRecord>> {
+    conn.query_row(
+        &format!("SELECT {} FROM chat_templates WHERE template_name = ?1", TEMPLATE_COLUMNS),
+        params![name],
+        template_from_row,
+    )
+    .optional()
+    .context("Failed to load prompt template")
+}
+
+/// Template content and metadata to store
+#[derive(Debug, Clone)]
+pub struct TemplateFields<'a> {
+    pub name: &'a str,
+    pub category: &'a str,
+    pub description: Option<&'a str>,
+    pub content: &'a str,
+    pub variables: &'a str,
+}
+
+pub fn create_template(conn: &Connection, fields: &TemplateFields) -> Result<PromptTemplateRecord> {
+    conn.execute(
+        "INSERT INTO chat_templates (template_name, category, description, template_content, variables, version, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, 1, datetime('now'))",
+        params![fields.name, fields.category, fields.description, fields.content, fields.variables],
+    ).context("Failed to create prompt template")?;
+
+    get_template(conn, conn.last_insert_rowid())?.context("Prompt template missing after insert")
+}
+
+/// Save new content, archiving the current version first. Metadata-only
+/// edits (name, category, description) do not create a version.
+pub fn update_template(conn: &mut Connection, id: i64, fields: &TemplateFields) -> Result<Option<PromptTemplateRecord>> {
+    let tx = conn.transaction()?;
+    let Some(current) = get_template(&tx, id)? else {
+        return Ok(None);
+    };
+
+    let content_changed = current.content != fields.content || current.variables != fields.variables;
+    let version = if content_changed {
+        tx.execute(
+            "INSERT OR REPLACE INTO chat_template_versions (template_id, version, template_content, variables)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![id, current.version, current.content, current.variables],
+        ).context("Failed to archive prompt template version")?;
+        current.version + 1
+    } else {
+        current.version
+    };
+
+    tx.execute(
+        "UPDATE chat_templates SET template_name = ?1, category = ?2, description = ?3, template_content = ?4,
+            variables = ?5, version = ?6, updated_at = datetime('now')
+         WHERE id = ?7",
+        params![fields.name, fields.category, fields.description, fields.content, fields.variables, version, id],
+    ).context("Failed to update prompt template")?;
+
+    let updated = get_template(&tx, id)?;
+    tx.commit()?;
+    Ok(updated)
+}
+
+pub fn delete_template(conn: &Connection, id: i64) -> Result<bool> {
+    conn.execute("DELETE FROM prompt_template_defaults WHERE template_id = ?1", params![id])
+        .context("Failed to clear prompt template defaults")?;
+    conn.execute("DELETE FROM chat_template_versions WHERE template_id = ?1", params![id])
+        .context("Failed to delete prompt template versions")?;
+    let deleted = conn
+        .execute("DELETE FROM chat_templates WHERE id = ?1", params![id])
+        .context("Failed to delete prompt template")?;
+    Ok(deleted > 0)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateVersion {
+    pub template_id: i64,
+    pub version: i64,
+    pub content: String,
+    pub variables: String,
+    pub created_at: String,
+}
+
+/// Archived versions, newest first
+pub fn list_versions(conn: &Connection, template_id: i64) -> Result<Vec<TemplateVersion>> {
+    let mut stmt = conn.prepare(
+        "SELECT template_id, version, template_content, variables, created_at
+         FROM chat_template_versions WHERE template_id = ?1 ORDER BY version DESC",
+    )?;
+    let versions = stmt
+        .query_map(params![template_id], |row| {
+            Ok(TemplateVersion {
+                template_id: row.get(0)?,
+                version: row.get(1)?,
+                content: row.get(2)?,
+                variables: row.get(3)?,
+                created_at: row.get(4)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list prompt template versions")?;
+    Ok(versions)
+}
+
+pub fn get_default_template_id(conn: &Connection, feature: &str) -> Result<Option<i64>> {
+    conn.query_row(
+        "SELECT template_id FROM prompt_template_defaults WHERE feature = ?1",
+        params![feature],
+        |row| row.get(0),
+    )
+    .optional()
+    .context("Failed to load default prompt template")
+}
+
+pub fn set_default_template(conn: &Connection, feature: &str, template_id: i64) -> Result<()> {
+    conn.execute(
+        "INSERT INTO prompt_template_defaults (feature, template_id) VALUES (?1, ?2)
+         ON CONFLICT(feature) DO UPDATE SET template_id = excluded.template_id, updated_at = datetime('now')",
+        params![feature, template_id],
+    ).context("Failed to set default prompt template")?;
+    Ok(())
+}
+
+pub fn clear_default_template(conn: &Connection, feature: &str) -> Result<bool> {
+    let deleted = conn
+        .execute("DELETE FROM prompt_template_defaults WHERE feature = ?1", params![feature])
+        .context("Failed to clear default prompt template")?;
+    Ok(deleted > 0)
+}
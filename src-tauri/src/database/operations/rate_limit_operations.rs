@@ -0,0 +1,90 @@
+//! Google API request usage operations
+//!
+//! The request scheduler keeps its rolling window in memory and mirrors
+//! its grants here, a few seconds at a time, as per-second counts, so the
+//! window and the day's total survive a restart.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+
+/// Requests sent in one second
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecondUsage {
+    /// Epoch seconds
+    pub second: i64,
+    pub interactive: u32,
+    pub background: u32,
+}
+
+/// Add `usage` to the stored counts, in one transaction
+pub fn record_usage(conn: &Connection, usage: &[SecondUsage]) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO api_request_usage (second, interactive, background) VALUES (?1, ?2, ?3)
+             ON CONFLICT(second) DO UPDATE SET
+                interactive = interactive + excluded.interactive,
+                background = background + excluded.background",
+        )?;
+        for second in usage {
+            stmt.execute(params![second.second, second.interactive, second.background])
+                .context("Failed to record API request usage")?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Usage from `since` (epoch seconds) on, oldest first
+pub fn get_usage_since(conn: &Connection, since: i64) -> Result<Vec<SecondUsage>> {
+    let mut stmt = conn.prepare(
+        "SELECT second, interactive, background FROM api_request_usage WHERE second >= ?1 ORDER BY second",
+    )?;
+    let usage = stmt
+        .query_map(params![since], |row| {
+            Ok(SecondUsage { second: row.get(0)?, interactive: row.get(1)?, background: row.get(2)? })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to load API request usage")?;
+    Ok(usage)
+}
+
+/// Total requests from `since` (epoch seconds) on
+pub fn count_since(conn: &Connection, since: i64) -> Result<u64> {
+    conn.query_row(
+        "SELECT COALESCE(SUM(interactive + background), 0) FROM api_request_usage WHERE second >= ?1",
+        params![since],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|total| total as u64)
+    .context("Failed to count API request usage")
+}
+
+pub fn prune_before(conn: &Connection, before: i64) -> Result<usize> {
+    conn.execute("DELETE FROM api_request_usage WHERE second < ?1", params![before])
+        .context("Failed to prune API request usage")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    #[test]
+    fn test_usage_is_counted_per_second() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let usage = |second, interactive, background| SecondUsage { second, interactive, background };
+        record_usage(&conn, &[usage(100, 1, 1), usage(160, 0, 1)]).unwrap();
+        record_usage(&conn, &[usage(100, 1, 0)]).unwrap();
+
+        assert_eq!(
+            get_usage_since(&conn, 100).unwrap()[0],
+            SecondUsage { second: 100, interactive: 2, background: 1 }
+        );
+        assert_eq!(count_since(&conn, 0).unwrap(), 4);
+        assert_eq!(prune_before(&conn, 150).unwrap(), 1);
+        assert_eq!(count_since(&conn, 0).unwrap(), 1);
+    }
+}
@@ -0,0 +1,223 @@
+//! Sent mail receipt and open tracking database operations
+//!
+//! Sent messages that request a read/delivery receipt, or that carry a
+//! self-hosted tracking pixel, get a row here keyed by their RFC 822
+//! Message-ID. Returned MDN/DSN reports and pixel hits update that row.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackedMessage {
+    pub id: i64,
+    pub account_id: String,
+    pub message_id: String,
+    pub thread_id: Option<String>,
+    pub rfc822_message_id: String,
+    pub subject: String,
+    pub recipients: Vec<String>,
+    pub read_receipt_requested: bool,
+    pub delivery_receipt_requested: bool,
+    pub tracking_token: Option<String>,
+    pub delivery_status: Option<String>,
+    pub delivered_at: Option<String>,
+    pub read_at: Option<String>,
+    pub read_by: Option<String>,
+    pub open_count: i64,
+    pub first_opened_at: Option<String>,
+    pub last_opened_at: Option<String>,
+    pub sent_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewTrackedMessage {
+    pub account_id: String,
+    pub message_id: String,
+    pub thread_id: Option<String>,
+    pub rfc822_message_id: String,
+    pub subject: String,
+    pub recipients: Vec<String>,
+    pub read_receipt_requested: bool,
+    pub delivery_receipt_requested: bool,
+    pub tracking_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrackingSettings {
+    pub account_id: String,
+    pub pixel_enabled: bool,
+    pub pixel_base_url: Option<String>,
+    pub last_receipt_scan_at: Option<String>,
+}
+
+const TRACKING_COLUMNS: &str = "id, account_id, message_id, thread_id, rfc822_message_id, subject, recipients,
+    read_receipt_requested, delivery_receipt_requested, tracking_token, delivery_status, delivered_at,
+    read_at, read_by, open_count, first_opened_at, last_opened_at, sent_at";
+
+fn tracked_message_from_row(row: &Row) -> rusqlite::Result<TrackedMessage> {
+    let recipients: String = row.get(6)?;
+    Ok(TrackedMessage {
+        id: row.get(0)?,
+        account_id: row.get(1)?,
+        message_id: row.get(2)?,
+        thread_id: row.get(3)?,
+        rfc822_message_id: row.get(4)?,
+        subject: row.get(5)?,
+        recipients: serde_json::from_str(&recipients).unwrap_or_default(),
+        read_receipt_requested: row.get(7)?,
+        delivery_receipt_requested: row.get(8)?,
+        tracking_token: row.get(9)?,
+        delivery_status: row.get(10)?,
+        delivered_at: row.get(11)?,
+        read_at: row.get(12)?,
+        read_by: row.get(13)?,
+        open_count: row.get(14)?,
+        first_opened_at: row.get(15)?,
+        last_opened_at: row.get(16)?,
+        sent_at: row.get(17)?,
+    })
+}
+
+/// Strip the angle brackets and whitespace around a Message-ID so stored and
+/// reported ids compare equal
+pub fn normalize_message_id(message_id: &str) -> String {
+    message_id.trim().trim_start_matches('<').trim_end_matches('>').trim().to_lowercase()
+}
+
+pub fn record_sent_message(conn: &Connection, message: &NewTrackedMessage) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO sent_message_tracking (
+            account_id, message_id, thread_id, rfc822_message_id, subject, recipients,
+            read_receipt_requested, delivery_receipt_requested, tracking_token
+         ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+         ON CONFLICT(rfc822_message_id) DO UPDATE SET
+            message_id = excluded.message_id,
+            thread_id = excluded.thread_id",
+        params![
+            message.account_id,
+            message.message_id,
+            message.thread_id,
+            normalize_message_id(&message.rfc822_message_id),
+            message.subject,
+            serde_json::to_string(&message.recipients)?,
+            message.read_receipt_requested,
+            message.delivery_receipt_requested,
+            message.tracking_token,
+        ],
+    ).context("Failed to record sent message tracking")?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn get_tracked_message(conn: &Connection, account_id: &str, message_id: &str) -> Result<Option<TrackedMessage>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM sent_message_tracking WHERE account_id = ?1 AND message_id = ?2",
+            TRACKING_COLUMNS
+        ),
+        params![account_id, message_id],
+        tracked_message_from_row,
+    )
+    .optional()
+    .context("Failed to get tracked message")
+}
+
+pub fn list_tracked_messages(conn: &Connection, account_id: &str, limit: i64) -> Result<Vec<TrackedMessage>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM sent_message_tracking WHERE account_id = ?1 ORDER BY sent_at DESC, id DESC LIMIT ?2",
+        TRACKING_COLUMNS
+    ))?;
+    let messages = stmt
+        .query_map(params![account_id, limit], tracked_message_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list tracked messages")?;
+    Ok(messages)
+}
+
+/// Apply a delivery status notification; returns false if the message is not tracked
+pub fn apply_delivery_report(conn: &Connection, rfc822_message_id: &str, status: &str, reported_at: &str) -> Result<bool> {
+    let updated = conn.execute(
+        "UPDATE sent_message_tracking
+         SET delivery_status = ?1,
+             delivered_at = CASE WHEN ?1 = 'delivered' THEN COALESCE(delivered_at, ?2) ELSE delivered_at END
+         WHERE rfc822_message_id = ?3",
+        params![status, reported_at, normalize_message_id(rfc822_message_id)],
+    ).context("Failed to apply delivery report")?;
+    Ok(updated > 0)
+}
+
+/// Apply a read receipt (MDN); the first receipt wins
+pub fn apply_read_receipt(conn: &Connection, rfc822_message_id: &str, read_by: Option<&str>, reported_at: &str) -> Result<bool> {
+    let updated = conn.execute(
+        "UPDATE sent_message_tracking
+         SET read_at = COALESCE(read_at, ?1),
+             read_by = COALESCE(read_by, ?2)
+         WHERE rfc822_message_id = ?3",
+        params![reported_at, read_by, normalize_message_id(rfc822_message_id)],
+    ).context("Failed to apply read receipt")?;
+    Ok(updated > 0)
+}
+
+/// Count a tracking pixel hit and return the updated message
+pub fn record_pixel_open(conn: &Connection, tracking_token: &str, opened_at: &str) -> Result<Option<TrackedMessage>> {
+    let updated = conn.execute(
+        "UPDATE sent_message_tracking
+         SET open_count = open_count + 1,
+             first_opened_at = COALESCE(first_opened_at, ?1),
+             last_opened_at = ?1
+         WHERE tracking_token = ?2",
+        params![opened_at, tracking_token],
+    ).context("Failed to record tracking pixel open")?;
+    if updated == 0 {
+        return Ok(None);
+    }
+
+    conn.query_row(
+        &format!("SELECT {} FROM sent_message_tracking WHERE tracking_token = ?1", TRACKING_COLUMNS),
+        params![tracking_token],
+        tracked_message_from_row,
+    )
+    .optional()
+    .context("Failed to get tracked message")
+}
+
+pub fn get_tracking_settings(conn: &Connection, account_id: &str) -> Result<TrackingSettings> {
+    let settings = conn.query_row(
+        "SELECT account_id, pixel_enabled, pixel_base_url, last_receipt_scan_at FROM tracking_settings WHERE account_id = ?1",
+        params![account_id],
+        |row| {
+            Ok(TrackingSettings {
+                account_id: row.get(0)?,
+                pixel_enabled: row.get(1)?,
+                pixel_base_url: row.get(2)?,
+                last_receipt_scan_at: row.get(3)?,
+            })
+        },
+    )
+    .optional()
+    .context("Failed to get tracking settings")?;
+
+    Ok(settings.unwrap_or_else(|| TrackingSettings { account_id: account_id.to_string(), ..Default::default() }))
+}
+
+pub fn set_pixel_settings(conn: &Connection, account_id: &str, pixel_enabled: bool, pixel_base_url: Option<&str>) -> Result<()> {
+    conn.execute(
+        "INSERT INTO tracking_settings (account_id, pixel_enabled, pixel_base_url, updated_at)
+         VALUES (?1, ?2, ?3, CURRENT_TIMESTAMP)
+         ON CONFLICT(account_id) DO UPDATE SET
+            pixel_enabled = excluded.pixel_enabled,
+            pixel_base_url = excluded.pixel_base_url,
+            updated_at = CURRENT_TIMESTAMP",
+        params![account_id, pixel_enabled, pixel_base_url],
+    ).context("Failed to save tracking settings")?;
+    Ok(())
+}
+
+pub fn set_last_receipt_scan(conn: &Connection, account_id: &str, scanned_at: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO tracking_settings (account_id, last_receipt_scan_at) VALUES (?1, ?2)
+         ON CONFLICT(account_id) DO UPDATE SET last_receipt_scan_at = excluded.last_receipt_scan_at",
+        params![account_id, scanned_at],
+    ).context("Failed to record receipt scan time")?;
+    Ok(())
+}
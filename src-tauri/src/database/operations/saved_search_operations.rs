@@ -0,0 +1,139 @@
+//! Saved Gmail search and smart folder operations
+//!
+//! Both are a name plus the structured filters a Gmail query is built from.
+//! Saved searches are offered in the search bar; smart folders are listed
+//! with the account's labels. The built query is stored next to the filters
+//! so listing them needs no rebuilding.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub const KIND_SEARCH: &str = "search";
+pub const KIND_SMART_FOLDER: &str = "smart_folder";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub id: String,
+    pub account_id: String,
+    pub name: String,
+    /// "search" or "smart_folder"
+    pub kind: String,
+    pub filters: serde_json::Value,
+    pub query: String,
+    pub position: i64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+const COLUMNS: &str = "id, account_id, name, kind, filters, query, position, created_at, updated_at";
+
+fn saved_search_from_row(row: &Row) -> rusqlite::Result<SavedSearch> {
+    let filters: String = row.get(4)?;
+    Ok(SavedSearch {
+        id: row.get(0)?,
+        account_id: row.get(1)?,
+        name: row.get(2)?,
+        kind: row.get(3)?,
+        filters: serde_json::from_str(&filters).unwrap_or(serde_json::Value::Null),
+        query: row.get(5)?,
+        position: row.get(6)?,
+        created_at: row.get(7)?,
+        updated_at: row.get(8)?,
+    })
+}
+
+pub fn get_saved_search(conn: &Connection, id: &str) -> Result<Option<SavedSearch>> {
+    conn.query_row(
+        &format!("SELECT {} FROM gmail_saved_searches WHERE id = ?1", COLUMNS),
+        params![id],
+        saved_search_from_row,
+    )
+    .optional()
+    .context("Failed to load saved search")
+}
+
+/// An account's saved searches, optionally of one kind, in display order
+pub fn list_saved_searches(conn: &Connection, account_id: &str, kind: Option<&str>) -> Result<Vec<SavedSearch>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM gmail_saved_searches
+         WHERE account_id = ?1 AND (?2 IS NULL OR kind = ?2)
+         ORDER BY position, name COLLATE NOCASE",
+        COLUMNS
+    ))?;
+    let searches = stmt
+        .query_map(params![account_id, kind], saved_search_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list saved searches")?;
+    Ok(searches)
+}
+
+/// Create a saved search, or update it when `id` names an existing one.
+/// New entries go to the end of their kind's list.
+pub fn save_search(
+    conn: &Connection,
+    id: Option<&str>,
+    account_id: &str,
+    name: &str,
+    kind: &str,
+    filters: &serde_json::Value,
+    query: &str,
+) -> Result<SavedSearch> {
+    let id = match id {
+        Some(id) => id.to_string(),
+        None => Uuid::new_v4().to_string(),
+    };
+
+    conn.execute(
+        "INSERT INTO gmail_saved_searches (id, account_id, name, kind, filters, query, position)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6,
+                 (SELECT COALESCE(MAX(position) + 1, 0) FROM gmail_saved_searches WHERE account_id = ?2 AND kind = ?4))
+         ON CONFLICT(id) DO UPDATE SET
+            name = excluded.name,
+            kind = excluded.kind,
+            filters = excluded.filters,
+            query = excluded.query,
+            updated_at = datetime('now')",
+        params![id, account_id, name, kind, filters.to_string(), query],
+    ).context("Failed to save search")?;
+
+    get_saved_search(conn, &id)?.context("Saved search disappeared after saving")
+}
+
+/// Returns false if there was no such saved search
+pub fn delete_saved_search(conn: &Connection, id: &str) -> Result<bool> {
+    let removed = conn
+        .execute("DELETE FROM gmail_saved_searches WHERE id = ?1", params![id])
+        .context("Failed to delete saved search")?;
+    Ok(removed > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+    use serde_json::json;
+
+    #[test]
+    fn test_save_list_and_delete() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let invoices = save_search(&conn, None, "acc", "Invoices", KIND_SEARCH, &json!({ "subject": "invoice" }), "subject:invoice").unwrap();
+        let boss = save_search(&conn, None, "acc", "Boss", KIND_SMART_FOLDER, &json!({ "from": "boss@example.com" }), "from:boss@example.com").unwrap();
+        save_search(&conn, None, "acc", "Receipts", KIND_SEARCH, &json!({}), "receipt").unwrap();
+        assert_eq!(invoices.position, 0);
+        assert_eq!(boss.position, 0);
+
+        let renamed = save_search(&conn, Some(&invoices.id), "acc", "Bills", KIND_SEARCH, &invoices.filters, &invoices.query).unwrap();
+        assert_eq!((renamed.name.as_str(), renamed.position), ("Bills", 0));
+
+        let searches = list_saved_searches(&conn, "acc", Some(KIND_SEARCH)).unwrap();
+        assert_eq!(searches.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(), vec!["Bills", "Receipts"]);
+        assert_eq!(list_saved_searches(&conn, "acc", None).unwrap().len(), 3);
+
+        assert!(delete_saved_search(&conn, &boss.id).unwrap());
+        assert!(!delete_saved_search(&conn, &boss.id).unwrap());
+    }
+}
@@ -0,0 +1,362 @@
+//! Global search operations
+//!
+//! Full-text queries over each searchable domain: stored mail, notes, chat
+//! history, and the local copies of Google tasks and calendar events kept
+//! in `search_items`. Every query returns `SearchHit`s ranked by bm25 with
+//! title matches weighted up, so results from different domains can be
+//! merged by the search service.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::database::operations::workspace_operations::HiddenIds;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchDomain {
+    Mail,
+    Note,
+    Task,
+    Event,
+    Chat,
+}
+
+impl SearchDomain {
+    pub const ALL: [SearchDomain; 5] = [
+        SearchDomain::Mail,
+        SearchDomain::Note,
+        SearchDomain::Task,
+        SearchDomain::Event,
+        SearchDomain::Chat,
+    ];
+
+    /// The `search_items.domain` value for domains cached there
+    fn item_domain(&self) -> Option<&'static str> {
+        match self {
+            SearchDomain::Task => Some("task"),
+            SearchDomain::Event => Some("event"),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub domain: SearchDomain,
+    pub id: String,
+    pub account_id: Option<String>,
+    /// Thread, task list, calendar or chat session the hit belongs to
+    pub container_id: Option<String>,
+    pub title: String,
+    /// Excerpt with matched words in [brackets]
+    pub snippet: String,
+    pub timestamp: Option<String>,
+    /// bm25 score; lower is a better match
+    pub rank: f64,
+}
+
+/// A task or event to keep in the local search copy
+#[derive(Debug, Clone)]
+pub struct NewSearchItem<'a> {
+    pub item_id: &'a str,
+    pub container_id: &'a str,
+    pub title: &'a str,
+    pub body: &'a str,
+    /// Due date or start time, as UTC RFC 3339 so it sorts and compares
+    pub item_time: Option<&'a str>,
+    /// Google's status of the task or event
+    pub status: Option<&'a str>,
+    /// When a task was completed, UTC RFC 3339
+    pub completed_at: Option<&'a str>,
+    /// An event that takes whole days; `item_time` is midnight UTC of its date
+    pub all_day: bool,
+}
+
+/// Turn free text into an FTS5 query that matches every word, so user
+/// input with quotes or operators cannot break the MATCH expression
+pub fn fts_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Like `fts_query`, but the last word also matches as a prefix, for
+/// search-as-you-type
+pub fn fts_prefix_query(query: &str) -> String {
+    let query = fts_query(query);
+    if query.is_empty() {
+        query
+    } else {
+        format!("{}*", query)
+    }
+}
+
+/// Normalise an RFC 3339 time or a bare date to UTC RFC 3339, the form
+/// `item_time` is stored and compared in
+pub fn utc_item_time(value: &str) -> Option<String> {
+    let time = match chrono::DateTime::parse_from_rfc3339(value) {
+        Ok(time) => time.with_timezone(&chrono::Utc),
+        Err(_) => chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0)?.and_utc(),
+    };
+    Some(time.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+}
+
+fn hit_from_row(domain: SearchDomain) -> impl Fn(&Row) -> rusqlite::Result<SearchHit> {
+    move |row| {
+        Ok(SearchHit {
+            domain,
+            id: row.get(0)?,
+            account_id: row.get(1)?,
+            container_id: row.get(2)?,
+            title: row.get(3)?,
+            snippet: row.get(4)?,
+            timestamp: row.get(5)?,
+            rank: row.get(6)?,
+        })
+    }
+}
+
+fn run_search(conn: &Connection, sql: &str, args: impl rusqlite::Params, domain: SearchDomain) -> Result<Vec<SearchHit>> {
+    let mut stmt = conn.prepare(sql)?;
+    let hits = stmt
+        .query_map(args, hit_from_row(domain))?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .with_context(|| format!("Failed to search {:?}", domain))?;
+    Ok(hits)
+}
+
+/// Search one domain with an FTS5 query built by `fts_query`, leaving out
+/// the `hidden` accounts and containers
+pub fn search_domain(
+    conn: &Connection,
+    domain: SearchDomain,
+    fts_query: &str,
+    account_id: Option<&str>,
+    hidden: &HiddenIds,
+    limit: usize,
+) -> Result<Vec<SearchHit>> {
+    if fts_query.is_empty() {
+        return Ok(Vec::new());
+    }
+    let limit = limit as i64;
+
+    match domain {
+        SearchDomain::Mail => {
+            let mut hits = run_search(
+                conn,
+                "SELECT s.message_id, s.account_id, s.thread_id,
+                        coalesce(json_extract(s.message_data, '$.parsed_content.subject'), '(no subject)'),
+                        snippet(gmail_message_fts, -1, '[', ']', '…', 12),
+                        CAST(s.internal_date AS TEXT),
+                        bm25(gmail_message_fts, 10.0, 5.0, 1.0)
+                 FROM gmail_message_fts
+                 JOIN gmail_message_store s ON s.rowid = gmail_message_fts.rowid
+                 WHERE gmail_message_fts MATCH ?1 AND (?2 IS NULL OR s.account_id = ?2)
+                   AND s.account_id NOT IN (SELECT value FROM json_each(?3))
+                 ORDER BY bm25(gmail_message_fts, 10.0, 5.0, 1.0) LIMIT ?4",
+                params![fts_query, account_id, hidden.accounts, limit],
+                domain,
+            )?;
+            // internal_date is epoch milliseconds
+            for hit in &mut hits {
+                hit.timestamp = hit
+                    .timestamp
+                    .as_deref()
+                    .and_then(|ms| ms.parse::<i64>().ok())
+                    .and_then(chrono::DateTime::from_timestamp_millis)
+                    .map(|t| t.to_rfc3339());
+            }
+            Ok(hits)
+        }
+        SearchDomain::Note => run_search(
+            conn,
+            "SELECT CAST(n.id AS TEXT), NULL, CAST(n.folder_id AS TEXT), n.title,
+                    snippet(notes_fts, -1, '[', ']', '…', 12),
+                    CAST(n.updated_at AS TEXT),
+                    bm25(notes_fts, 10.0, 1.0)
+             FROM notes_fts
+             JOIN notes n ON n.id = notes_fts.rowid
+             WHERE notes_fts MATCH ?1
+               AND (n.folder_id IS NULL OR CAST(n.folder_id AS TEXT) NOT IN (SELECT value FROM json_each(?2)))
+             ORDER BY bm25(notes_fts, 10.0, 1.0) LIMIT ?3",
+            params![fts_query, hidden.containers, limit],
+            domain,
+        ),
+        SearchDomain::Chat => run_search(
+            conn,
+            "SELECT CAST(m.id AS TEXT), NULL, CAST(m.session_id AS TEXT), coalesce(cs.session_name, 'Chat'),
+                    snippet(chat_messages_fts, -1, '[', ']', '…', 12),
+                    CAST(m.created_at AS TEXT),
+                    bm25(chat_messages_fts)
+             FROM chat_messages_fts
+             JOIN chat_messages m ON m.id = chat_messages_fts.rowid
+             LEFT JOIN chat_sessions cs ON cs.id = m.session_id
+             WHERE chat_messages_fts MATCH ?1
+             ORDER BY bm25(chat_messages_fts) LIMIT ?2",
+            params![fts_query, limit],
+            domain,
+        ),
+        SearchDomain::Task | SearchDomain::Event => run_search(
+            conn,
+            "SELECT i.item_id, i.account_id, i.container_id, i.title,
+                    snippet(search_items_fts, -1, '[', ']', '…', 12),
+                    i.item_time,
+                    bm25(search_items_fts, 10.0, 1.0)
+             FROM search_items_fts
+             JOIN search_items i ON i.id = search_items_fts.rowid
+             WHERE search_items_fts MATCH ?1 AND i.domain = ?2 AND (?3 IS NULL OR i.account_id = ?3)
+               AND coalesce(i.account_id, '') NOT IN (SELECT value FROM json_each(?4))
+               AND coalesce(i.container_id, '') NOT IN (SELECT value FROM json_each(?5))
+             ORDER BY bm25(search_items_fts, 10.0, 1.0) LIMIT ?6",
+            params![fts_query, domain.item_domain(), account_id, hidden.accounts, hidden.containers, limit],
+            domain,
+        ),
+    }
+}
+
+fn upsert_item(conn: &Connection, domain: &str, account_id: &str, item: &NewSearchItem) -> Result<()> {
+    conn.execute(
+        "INSERT INTO search_items (domain, account_id, item_id, container_id, title, body, item_time, status, completed_at, all_day)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+         ON CONFLICT(domain, account_id, item_id) DO UPDATE SET
+            container_id = excluded.container_id, title = excluded.title, body = excluded.body,
+            item_time = excluded.item_time, status = excluded.status, completed_at = excluded.completed_at,
+            all_day = excluded.all_day, indexed_at = CURRENT_TIMESTAMP",
+        params![
+            domain, account_id, item.item_id, item.container_id, item.title, item.body, item.item_time, item.status,
+            item.completed_at, item.all_day,
+        ],
+    ).context("Failed to index search item")?;
+    Ok(())
+}
+
+/// Replace the search copy of an account's tasks with a full fetch
+pub fn replace_task_items(conn: &mut Connection, account_id: &str, items: &[NewSearchItem]) -> Result<()> {
+    let tx = conn.transaction().context("Failed to start search index transaction")?;
+    tx.execute(
+        "DELETE FROM search_items WHERE domain = 'task' AND account_id = ?1",
+        params![account_id],
+    ).context("Failed to clear indexed tasks")?;
+    for item in items {
+        upsert_item(&tx, "task", account_id, item)?;
+    }
+    tx.commit().context("Failed to index tasks")?;
+    Ok(())
+}
+
+/// Replace the search copy of one calendar's events between `window_start`
+/// and `window_end` (UTC RFC 3339) with the events fetched for that window
+pub fn replace_event_items(
+    conn: &mut Connection,
+    account_id: &str,
+    calendar_id: &str,
+    window_start: &str,
+    window_end: &str,
+    items: &[NewSearchItem],
+) -> Result<()> {
+    let tx = conn.transaction().context("Failed to start search index transaction")?;
+    tx.execute(
+        "DELETE FROM search_items
+         WHERE domain = 'event' AND account_id = ?1 AND container_id = ?2 AND item_time >= ?3 AND item_time < ?4",
+        params![account_id, calendar_id, window_start, window_end],
+    ).context("Failed to clear indexed events")?;
+    for item in items {
+        upsert_item(&tx, "event", account_id, item)?;
+    }
+    tx.commit().context("Failed to index events")?;
+    Ok(())
+}
+
+/// Replace the search copy of every event of a calendar kept wholly on
+/// this machine; call inside the transaction that stores the events
+pub fn replace_calendar_items(conn: &Connection, account_id: &str, calendar_id: &str, items: &[NewSearchItem]) -> Result<()> {
+    conn.execute(
+        "DELETE FROM search_items WHERE domain = 'event' AND account_id = ?1 AND container_id = ?2",
+        params![account_id, calendar_id],
+    ).context("Failed to clear indexed events")?;
+    for item in items {
+        upsert_item(conn, "event", account_id, item)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    #[test]
+    fn test_every_domain_is_searchable() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let message = serde_json::json!({
+            "parsed_content": {
+                "subject": "Quarterly budget",
+                "from": { "email": "cfo@example.com", "name": "Dana" },
+                "body_text": "Numbers attached",
+            },
+            "snippet": "Numbers attached",
+        });
+        for _ in 0..2 {
+            // Stored twice: the replace must not leave a stale index entry
+            conn.execute(
+                "INSERT OR REPLACE INTO gmail_message_store (account_id, message_id, thread_id, internal_date, message_data)
+                 VALUES ('acc', 'm1', 't1', 1717401600000, ?1)",
+                params![message.to_string()],
+            ).unwrap();
+        }
+        conn.execute(
+            "INSERT INTO notes (title, content, user_id, created_at, updated_at)
+             VALUES ('Budget ideas', 'cut travel', 'default_user', '2024-06-01 10:00:00', '2024-06-01 10:00:00')",
+            [],
+        ).unwrap();
+        conn.execute(
+            "INSERT INTO chat_sessions (user_id, session_name, created_at, updated_at)
+             VALUES ('default_user', 'Planning', '2024-06-01 10:00:00', '2024-06-01 10:00:00')",
+            [],
+        ).unwrap();
+        conn.execute(
+            "INSERT INTO chat_messages (session_id, role, content, created_at)
+             VALUES (1, 'user', 'help me draft the budget', '2024-06-01 10:00:00')",
+            [],
+        ).unwrap();
+        replace_task_items(&mut conn, "acc", &[NewSearchItem {
+            item_id: "task-1",
+            container_id: "list-1",
+            title: "Send budget",
+            body: "",
+            item_time: Some("2024-06-03T00:00:00Z"),
+            status: Some("needsAction"),
+            completed_at: None,
+            all_day: false,
+        }]).unwrap();
+        replace_event_items(&mut conn, "acc", "primary", "2024-06-01T00:00:00Z", "2024-07-01T00:00:00Z", &[NewSearchItem {
+            item_id: "event-1",
+            container_id: "primary",
+            title: "Budget review",
+            body: "Room 4",
+            item_time: Some("2024-06-04T09:00:00Z"),
+            status: Some("confirmed"),
+            completed_at: None,
+            all_day: false,
+        }]).unwrap();
+
+        let query = fts_prefix_query("budg");
+        for domain in SearchDomain::ALL {
+            let hits = search_domain(&conn, domain, &query, None, &HiddenIds::default(), 10).unwrap();
+            assert_eq!(hits.len(), 1, "{:?}", domain);
+            assert!(hits[0].snippet.contains("[Budget]") || hits[0].snippet.contains("[budget]"), "{:?}", domain);
+        }
+        let mail = search_domain(&conn, SearchDomain::Mail, &fts_query("dana"), Some("acc"), &HiddenIds::default(), 10).unwrap();
+        assert_eq!(mail[0].title, "Quarterly budget");
+        assert_eq!(mail[0].timestamp.as_deref(), Some("2024-06-03T08:00:00+00:00"));
+
+        // A refetch of the window drops events that are gone
+        replace_event_items(&mut conn, "acc", "primary", "2024-06-01T00:00:00Z", "2024-07-01T00:00:00Z", &[]).unwrap();
+        assert!(search_domain(&conn, SearchDomain::Event, &query, None, &HiddenIds::default(), 10).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,113 @@
+//! Gmail send-as address operations
+//!
+//! The addresses an account may send as (its own plus any aliases set up
+//! in Gmail settings), stored so a compose request's `from_alias` can be
+//! checked without a round trip.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SendAsAlias {
+    pub email: String,
+    pub display_name: Option<String>,
+    pub reply_to: Option<String>,
+    /// HTML signature Gmail adds for this address
+    pub signature: Option<String>,
+    /// The account's own address
+    pub is_primary: bool,
+    /// The address Gmail selects in its own compose window
+    pub is_default: bool,
+    pub treat_as_alias: bool,
+    /// "accepted" or "pending" for custom addresses; absent for the primary
+    pub verification_status: Option<String>,
+}
+
+impl SendAsAlias {
+    /// Gmail only sends from addresses that are verified
+    pub fn is_usable(&self) -> bool {
+        self.is_primary || self.verification_status.as_deref().is_none_or(|status| status == "accepted")
+    }
+}
+
+fn alias_from_row(row: &Row) -> rusqlite::Result<SendAsAlias> {
+    Ok(SendAsAlias {
+        email: row.get(0)?,
+        display_name: row.get(1)?,
+        reply_to: row.get(2)?,
+        signature: row.get(3)?,
+        is_primary: row.get(4)?,
+        is_default: row.get(5)?,
+        treat_as_alias: row.get(6)?,
+        verification_status: row.get(7)?,
+    })
+}
+
+/// Replace the stored send-as addresses of an account with a fresh list
+pub fn replace_aliases(conn: &mut Connection, account_id: &str, aliases: &[SendAsAlias]) -> Result<()> {
+    let tx = conn.transaction().context("Failed to start send-as transaction")?;
+    tx.execute("DELETE FROM gmail_send_as WHERE account_id = ?1", params![account_id])
+        .context("Failed to clear send-as addresses")?;
+    for alias in aliases {
+        tx.execute(
+            "INSERT OR REPLACE INTO gmail_send_as (
+                account_id, email, display_name, reply_to, signature,
+                is_primary, is_default, treat_as_alias, verification_status
+             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                account_id,
+                alias.email,
+                alias.display_name,
+                alias.reply_to,
+                alias.signature,
+                alias.is_primary,
+                alias.is_default,
+                alias.treat_as_alias,
+                alias.verification_status,
+            ],
+        ).context("Failed to store send-as address")?;
+    }
+    tx.commit().context("Failed to commit send-as addresses")?;
+    Ok(())
+}
+
+/// Primary address first, then the rest by address
+pub fn list_aliases(conn: &Connection, account_id: &str) -> Result<Vec<SendAsAlias>> {
+    let mut stmt = conn.prepare(
+        "SELECT email, display_name, reply_to, signature, is_primary, is_default, treat_as_alias, verification_status
+         FROM gmail_send_as WHERE account_id = ?1 ORDER BY is_primary DESC, email COLLATE NOCASE",
+    )?;
+    let aliases = stmt
+        .query_map(params![account_id], alias_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list send-as addresses")?;
+    Ok(aliases)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    fn alias(email: &str, verification_status: Option<&str>) -> SendAsAlias {
+        SendAsAlias { email: email.to_string(), verification_status: verification_status.map(str::to_string), ..Default::default() }
+    }
+
+    #[test]
+    fn test_refresh_replaces_the_stored_list() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let primary = SendAsAlias { is_primary: true, is_default: true, ..alias("ada@example.com", None) };
+        replace_aliases(&mut conn, "acc", &[alias("old@example.com", Some("accepted")), primary.clone()]).unwrap();
+        replace_aliases(&mut conn, "acc", &[alias("billing@example.com", Some("pending")), primary.clone()]).unwrap();
+        replace_aliases(&mut conn, "other", &[alias("grace@example.com", None)]).unwrap();
+
+        let aliases = list_aliases(&conn, "acc").unwrap();
+        assert_eq!(aliases.iter().map(|a| a.email.as_str()).collect::<Vec<_>>(), ["ada@example.com", "billing@example.com"]);
+        assert_eq!(aliases[0], primary);
+        assert!(aliases[0].is_usable());
+        assert!(!aliases[1].is_usable());
+    }
+}
@@ -0,0 +1,168 @@
+//! Shipment tracking database operations
+//!
+//! Shipments are created from tracking numbers found in email and polled
+//! until they are delivered, dismissed or go stale.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Shipment {
+    pub id: i64,
+    pub account_id: String,
+    pub carrier: String,
+    pub tracking_number: String,
+    pub tracking_url: String,
+    pub message_id: Option<String>,
+    pub subject: Option<String>,
+    pub status: String,
+    pub status_detail: Option<String>,
+    pub check_failures: i64,
+    pub last_checked_at: Option<i64>,
+    pub next_check_at: Option<i64>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// A tracking number found in a message
+#[derive(Debug, Clone)]
+pub struct NewShipment<'a> {
+    pub account_id: &'a str,
+    pub carrier: &'a str,
+    pub tracking_number: &'a str,
+    pub tracking_url: &'a str,
+    pub message_id: Option<&'a str>,
+    pub subject: Option<&'a str>,
+}
+
+const SHIPMENT_COLUMNS: &str = "id, account_id, carrier, tracking_number, tracking_url, message_id, subject,
+    status, status_detail, check_failures, last_checked_at, next_check_at, created_at, updated_at";
+
+fn shipment_from_row(row: &Row) -> rusqlite::Result<Shipment> {
+    Ok(Shipment {
+        id: row.get(0)?,
+        account_id: row.get(1)?,
+        carrier: row.get(2)?,
+        tracking_number: row.get(3)?,
+        tracking_url: row.get(4)?,
+        message_id: row.get(5)?,
+        subject: row.get(6)?,
+        status: row.get(7)?,
+        status_detail: row.get(8)?,
+        check_failures: row.get(9)?,
+        last_checked_at: row.get(10)?,
+        next_check_at: row.get(11)?,
+        created_at: row.get(12)?,
+        updated_at: row.get(13)?,
+    })
+}
+
+/// Record a detected shipment. Returns false if it was already known.
+pub fn insert_detected(conn: &Connection, shipment: &NewShipment, next_check_at: i64) -> Result<bool> {
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO shipments
+            (account_id, carrier, tracking_number, tracking_url, message_id, subject, next_check_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            shipment.account_id,
+            shipment.carrier,
+            shipment.tracking_number,
+            shipment.tracking_url,
+            shipment.message_id,
+            shipment.subject,
+            next_check_at,
+        ],
+    ).context("Failed to save detected shipment")?;
+    Ok(inserted > 0)
+}
+
+pub fn get_shipment(conn: &Connection, id: i64) -> Result<Option<Shipment>> {
+    conn.query_row(
+        &format!("SELECT {} FROM shipments WHERE id = ?1", SHIPMENT_COLUMNS),
+        params![id],
+        shipment_from_row,
+    )
+    .optional()
+    .context("Failed to load shipment")
+}
+
+pub fn find_shipment(conn: &Connection, account_id: &str, carrier: &str, tracking_number: &str) -> Result<Option<Shipment>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM shipments WHERE account_id = ?1 AND carrier = ?2 AND tracking_number = ?3",
+            SHIPMENT_COLUMNS
+        ),
+        params![account_id, carrier, tracking_number],
+        shipment_from_row,
+    )
+    .optional()
+    .context("Failed to load shipment")
+}
+
+/// Undelivered, undismissed shipments, newest first
+pub fn list_active(conn: &Connection, account_id: &str) -> Result<Vec<Shipment>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM shipments
+         WHERE account_id = ?1 AND dismissed = 0 AND status != 'delivered'
+         ORDER BY created_at DESC",
+        SHIPMENT_COLUMNS
+    ))?;
+    let shipments = stmt
+        .query_map(params![account_id], shipment_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list active shipments")?;
+    Ok(shipments)
+}
+
+/// Shipments whose next poll is due, oldest due first
+pub fn due_for_check(conn: &Connection, now: i64, limit: usize) -> Result<Vec<Shipment>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM shipments
+         WHERE dismissed = 0 AND next_check_at IS NOT NULL AND next_check_at <= ?1
+         ORDER BY next_check_at LIMIT ?2",
+        SHIPMENT_COLUMNS
+    ))?;
+    let shipments = stmt
+        .query_map(params![now, limit as i64], shipment_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to load due shipments")?;
+    Ok(shipments)
+}
+
+/// Store the result of a successful check. `next_check_at` of `None` stops polling.
+pub fn update_status(
+    conn: &Connection,
+    id: i64,
+    status: &str,
+    status_detail: Option<&str>,
+    checked_at: i64,
+    next_check_at: Option<i64>,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE shipments SET status = ?1, status_detail = COALESCE(?2, status_detail),
+            check_failures = 0, last_checked_at = ?3, next_check_at = ?4, updated_at = CURRENT_TIMESTAMP
+         WHERE id = ?5",
+        params![status, status_detail, checked_at, next_check_at, id],
+    ).context("Failed to update shipment status")?;
+    Ok(())
+}
+
+pub fn record_check_failure(conn: &Connection, id: i64, checked_at: i64, next_check_at: Option<i64>) -> Result<()> {
+    conn.execute(
+        "UPDATE shipments SET check_failures = check_failures + 1, last_checked_at = ?1, next_check_at = ?2
+         WHERE id = ?3",
+        params![checked_at, next_check_at, id],
+    ).context("Failed to record shipment check failure")?;
+    Ok(())
+}
+
+/// Hide a shipment and stop polling it. Returns false if it does not exist.
+pub fn dismiss(conn: &Connection, account_id: &str, id: i64) -> Result<bool> {
+    let updated = conn.execute(
+        "UPDATE shipments SET dismissed = 1, next_check_at = NULL, updated_at = CURRENT_TIMESTAMP
+         WHERE id = ?1 AND account_id = ?2",
+        params![id, account_id],
+    ).context("Failed to dismiss shipment")?;
+    Ok(updated > 0)
+}
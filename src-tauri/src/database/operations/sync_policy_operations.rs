@@ -0,0 +1,187 @@
+//! Per-label Gmail sync policy operations
+//!
+//! A policy says whether mail under a label is synced, how far back, and
+//! whether bodies are downloaded or only headers. Accounts without stored
+//! policies use [`default_policies`].
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncContent {
+    Headers,
+    Full,
+}
+
+impl SyncContent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SyncContent::Headers => "headers",
+            SyncContent::Full => "full",
+        }
+    }
+
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "headers" => SyncContent::Headers,
+            _ => SyncContent::Full,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabelSyncPolicy {
+    pub label_id: String,
+    pub enabled: bool,
+    pub max_age_days: Option<i64>,
+    pub content: SyncContent,
+}
+
+/// What to do with a single message after resolving its labels' policies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageSyncDecision {
+    Skip,
+    HeadersOnly,
+    Full,
+}
+
+/// Label used for mail that matches no other policy
+pub const DEFAULT_LABEL: &str = "*";
+
+/// Policies used until an account configures its own
+pub fn default_policies() -> Vec<LabelSyncPolicy> {
+    let policy = |label_id: &str, enabled: bool, max_age_days: Option<i64>, content: SyncContent| LabelSyncPolicy {
+        label_id: label_id.to_string(),
+        enabled,
+        max_age_days,
+        content,
+    };
+    vec![
+        policy("INBOX", true, Some(365), SyncContent::Full),
+        policy("SENT", true, Some(365), SyncContent::Full),
+        policy("CATEGORY_PROMOTIONS", true, Some(30), SyncContent::Headers),
+        policy("CATEGORY_SOCIAL", true, Some(30), SyncContent::Headers),
+        policy("CATEGORY_UPDATES", true, Some(90), SyncContent::Headers),
+        policy("SPAM", false, None, SyncContent::Headers),
+        policy("TRASH", false, None, SyncContent::Headers),
+        policy(DEFAULT_LABEL, true, Some(90), SyncContent::Headers),
+    ]
+}
+
+fn policy_from_row(row: &Row) -> rusqlite::Result<LabelSyncPolicy> {
+    let content: String = row.get(3)?;
+    Ok(LabelSyncPolicy {
+        label_id: row.get(0)?,
+        enabled: row.get(1)?,
+        max_age_days: row.get(2)?,
+        content: SyncContent::parse(&content),
+    })
+}
+
+/// Stored policies for an account, falling back to the defaults
+pub fn get_policies(conn: &Connection, account_id: &str) -> Result<Vec<LabelSyncPolicy>> {
+    let mut stmt = conn.prepare(
+        "SELECT label_id, enabled, max_age_days, content FROM label_sync_policies
+         WHERE account_id = ?1 ORDER BY label_id",
+    )?;
+    let policies = stmt
+        .query_map(params![account_id], policy_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to load label sync policies")?;
+
+    if policies.is_empty() {
+        return Ok(default_policies());
+    }
+    Ok(policies)
+}
+
+/// Replace an account's policies with the given set
+pub fn set_policies(conn: &mut Connection, account_id: &str, policies: &[LabelSyncPolicy]) -> Result<()> {
+    let tx = conn.transaction().context("Failed to start policy transaction")?;
+    tx.execute("DELETE FROM label_sync_policies WHERE account_id = ?1", params![account_id])
+        .context("Failed to clear label sync policies")?;
+    for policy in policies {
+        tx.execute(
+            "INSERT OR REPLACE INTO label_sync_policies (account_id, label_id, enabled, max_age_days, content)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                account_id,
+                policy.label_id,
+                policy.enabled,
+                policy.max_age_days.map(|days| days.max(1)),
+                policy.content.as_str(),
+            ],
+        ).context("Failed to save label sync policy")?;
+    }
+    tx.commit().context("Failed to commit label sync policies")?;
+    Ok(())
+}
+
+/// Drop stored policies so the account goes back to the defaults
+pub fn reset_policies(conn: &Connection, account_id: &str) -> Result<()> {
+    conn.execute("DELETE FROM label_sync_policies WHERE account_id = ?1", params![account_id])
+        .context("Failed to reset label sync policies")?;
+    Ok(())
+}
+
+/// Resolve the policies for a message's labels.
+///
+/// A message is synced if any enabled label policy still covers its age; the
+/// most generous content level among those wins. A disabled label (e.g. SPAM)
+/// excludes the message outright. Labels without a policy fall back to `*`.
+pub fn decide(policies: &[LabelSyncPolicy], label_ids: &[String], age_days: i64) -> MessageSyncDecision {
+    let find = |label: &str| policies.iter().find(|p| p.label_id == label);
+
+    let mut matched: Vec<&LabelSyncPolicy> = label_ids.iter().filter_map(|label| find(label)).collect();
+    if matched.iter().any(|p| !p.enabled) {
+        return MessageSyncDecision::Skip;
+    }
+    if matched.is_empty() {
+        matched.extend(find(DEFAULT_LABEL));
+    }
+    if matched.is_empty() {
+        return MessageSyncDecision::Full;
+    }
+
+    matched
+        .iter()
+        .filter(|p| p.enabled && p.max_age_days.is_none_or(|max| age_days <= max))
+        .map(|p| p.content)
+        .max()
+        .map(|content| match content {
+            SyncContent::Full => MessageSyncDecision::Full,
+            SyncContent::Headers => MessageSyncDecision::HeadersOnly,
+        })
+        .unwrap_or(MessageSyncDecision::Skip)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    #[test]
+    fn test_policy_resolution() {
+        let policies = default_policies();
+        let labels = |ids: &[&str]| ids.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(decide(&policies, &labels(&["INBOX"]), 10), MessageSyncDecision::Full);
+        assert_eq!(decide(&policies, &labels(&["INBOX"]), 400), MessageSyncDecision::Skip);
+        assert_eq!(decide(&policies, &labels(&["CATEGORY_PROMOTIONS"]), 10), MessageSyncDecision::HeadersOnly);
+        // INBOX's full-body policy beats the promotions policy while both apply
+        assert_eq!(decide(&policies, &labels(&["INBOX", "CATEGORY_PROMOTIONS"]), 10), MessageSyncDecision::Full);
+        assert_eq!(decide(&policies, &labels(&["INBOX", "SPAM"]), 1), MessageSyncDecision::Skip);
+        assert_eq!(decide(&policies, &labels(&["Label_42"]), 30), MessageSyncDecision::HeadersOnly);
+
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        let custom = vec![LabelSyncPolicy { label_id: "INBOX".to_string(), enabled: true, max_age_days: None, content: SyncContent::Full }];
+        set_policies(&mut conn, "acc", &custom).unwrap();
+        assert_eq!(get_policies(&conn, "acc").unwrap().len(), 1);
+        reset_policies(&conn, "acc").unwrap();
+        assert_eq!(get_policies(&conn, "acc").unwrap().len(), default_policies().len());
+    }
+}
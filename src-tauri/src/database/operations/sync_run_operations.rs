@@ -0,0 +1,114 @@
+//! Sync run history operations
+//!
+//! Every sync run, successful or not, is recorded so the settings panel can
+//! show when each account last synced and what has been going wrong.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncRun {
+    pub id: i64,
+    pub account_id: String,
+    pub domain: String,
+    pub sync_type: String,
+    pub success: bool,
+    pub items_changed: i64,
+    pub error: Option<String>,
+    pub error_category: Option<String>,
+    pub finished_at: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewSyncRun<'a> {
+    pub account_id: &'a str,
+    pub domain: &'a str,
+    pub sync_type: &'a str,
+    pub items_changed: i64,
+    pub error: Option<&'a str>,
+    pub error_category: Option<&'a str>,
+}
+
+/// Runs older than this are pruned as new ones are recorded
+const RETENTION_DAYS: i64 = 30;
+
+const RUN_COLUMNS: &str = "id, account_id, domain, sync_type, success, items_changed, error, error_category, finished_at";
+
+fn run_from_row(row: &Row) -> rusqlite::Result<SyncRun> {
+    Ok(SyncRun {
+        id: row.get(0)?,
+        account_id: row.get(1)?,
+        domain: row.get(2)?,
+        sync_type: row.get(3)?,
+        success: row.get(4)?,
+        items_changed: row.get(5)?,
+        error: row.get(6)?,
+        error_category: row.get(7)?,
+        finished_at: row.get(8)?,
+    })
+}
+
+pub fn record_sync_run(conn: &Connection, run: &NewSyncRun) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO sync_runs (account_id, domain, sync_type, success, items_changed, error, error_category)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            run.account_id,
+            run.domain,
+            run.sync_type,
+            run.error.is_none(),
+            run.items_changed,
+            run.error,
+            run.error_category,
+        ],
+    ).context("Failed to record sync run")?;
+    let id = conn.last_insert_rowid();
+
+    conn.execute(
+        "DELETE FROM sync_runs WHERE finished_at < datetime('now', ?1)",
+        params![format!("-{} days", RETENTION_DAYS)],
+    ).context("Failed to prune sync runs")?;
+
+    Ok(id)
+}
+
+/// Most recent successful run of a type across all domains of an account
+pub fn last_successful_run(conn: &Connection, account_id: &str, sync_type: &str) -> Result<Option<SyncRun>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM sync_runs WHERE account_id = ?1 AND sync_type = ?2 AND success = 1
+             ORDER BY finished_at DESC, id DESC LIMIT 1",
+            RUN_COLUMNS
+        ),
+        params![account_id, sync_type],
+        run_from_row,
+    )
+    .optional()
+    .context("Failed to get last successful sync run")
+}
+
+pub fn recent_failures(conn: &Connection, account_id: &str, limit: i64) -> Result<Vec<SyncRun>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM sync_runs WHERE account_id = ?1 AND success = 0 ORDER BY finished_at DESC, id DESC LIMIT ?2",
+        RUN_COLUMNS
+    ))?;
+    let runs = stmt
+        .query_map(params![account_id, limit], run_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list recent sync failures")?;
+    Ok(runs)
+}
+
+/// Latest runs across every account, for diagnostics
+pub fn recent_runs(conn: &Connection, limit: i64) -> Result<Vec<SyncRun>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM sync_runs ORDER BY finished_at DESC, id DESC LIMIT ?1",
+        RUN_COLUMNS
+    ))?;
+    let runs = stmt
+        .query_map(params![limit], run_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list recent sync runs")?;
+    Ok(runs)
+}
@@ -0,0 +1,184 @@
+//! Task comment operations
+//!
+//! Comments on a task, kept locally next to the change journal that
+//! records the task's edits. Google Tasks only ever sees a summary; tasks
+//! whose summary could not be written are queued in
+//! `task_activity_mirrors` until it can.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskComment {
+    pub id: i64,
+    pub google_task_id: String,
+    pub account_id: Option<String>,
+    pub author: String,
+    pub body: String,
+    /// UTC, "YYYY-MM-DD HH:MM:SS" like the change journal
+    pub created_at: String,
+    pub edited_at: Option<String>,
+}
+
+fn comment_from_row(row: &Row) -> rusqlite::Result<TaskComment> {
+    Ok(TaskComment {
+        id: row.get(0)?,
+        google_task_id: row.get(1)?,
+        account_id: row.get(2)?,
+        author: row.get(3)?,
+        body: row.get(4)?,
+        created_at: row.get(5)?,
+        edited_at: row.get(6)?,
+    })
+}
+
+const COMMENT_COLUMNS: &str = "id, google_task_id, account_id, author, body, created_at, edited_at";
+
+pub fn get_comment(conn: &Connection, id: i64) -> Result<Option<TaskComment>> {
+    conn.query_row(
+        &format!("SELECT {} FROM task_comments WHERE id = ?1", COMMENT_COLUMNS),
+        params![id],
+        comment_from_row,
+    )
+    .optional()
+    .context("Failed to load task comment")
+}
+
+pub fn add_comment(conn: &Connection, google_task_id: &str, account_id: Option<&str>, author: &str, body: &str) -> Result<TaskComment> {
+    conn.execute(
+        "INSERT INTO task_comments (google_task_id, account_id, author, body) VALUES (?1, ?2, ?3, ?4)",
+        params![google_task_id, account_id, author, body],
+    )
+    .context("Failed to add task comment")?;
+    get_comment(conn, conn.last_insert_rowid())?.context("Task comment vanished after insert")
+}
+
+/// Replace a comment's text; `None` when it does not exist
+pub fn edit_comment(conn: &Connection, id: i64, body: &str) -> Result<Option<TaskComment>> {
+    let updated = conn
+        .execute(
+            "UPDATE task_comments SET body = ?1, edited_at = CURRENT_TIMESTAMP WHERE id = ?2",
+            params![body, id],
+        )
+        .context("Failed to edit task comment")?;
+    if updated == 0 {
+        return Ok(None);
+    }
+    get_comment(conn, id)
+}
+
+pub fn delete_comment(conn: &Connection, id: i64) -> Result<bool> {
+    let deleted = conn
+        .execute("DELETE FROM task_comments WHERE id = ?1", params![id])
+        .context("Failed to delete task comment")?;
+    Ok(deleted > 0)
+}
+
+/// Comments on any of `task_ids` (a task's Google ID and any temporary ID
+/// it had), oldest first
+pub fn list_comments(conn: &Connection, task_ids: &[String]) -> Result<Vec<TaskComment>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM task_comments WHERE google_task_id IN (SELECT value FROM json_each(?1))
+         ORDER BY created_at, id",
+        COMMENT_COLUMNS
+    ))?;
+    let comments = stmt
+        .query_map(params![serde_json::to_string(task_ids)?], comment_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list task comments")?;
+    Ok(comments)
+}
+
+/// A task whose mirrored activity is waiting to be written to Google
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedMirror {
+    pub google_task_id: String,
+    pub account_id: String,
+    pub task_list_id: String,
+}
+
+pub fn queue_mirror(conn: &Connection, google_task_id: &str, account_id: &str, task_list_id: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO task_activity_mirrors (google_task_id, account_id, task_list_id) VALUES (?1, ?2, ?3)
+         ON CONFLICT(google_task_id) DO UPDATE SET account_id = ?2, task_list_id = ?3",
+        params![google_task_id, account_id, task_list_id],
+    )
+    .context("Failed to queue task activity mirror")?;
+    Ok(())
+}
+
+/// Queued mirrors, oldest first
+pub fn queued_mirrors(conn: &Connection) -> Result<Vec<QueuedMirror>> {
+    let mut stmt = conn.prepare(
+        "SELECT google_task_id, account_id, task_list_id FROM task_activity_mirrors ORDER BY queued_at, google_task_id",
+    )?;
+    let mirrors = stmt
+        .query_map([], |row| {
+            Ok(QueuedMirror {
+                google_task_id: row.get(0)?,
+                account_id: row.get(1)?,
+                task_list_id: row.get(2)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list queued task activity mirrors")?;
+    Ok(mirrors)
+}
+
+pub fn clear_mirror(conn: &Connection, google_task_id: &str) -> Result<()> {
+    conn.execute("DELETE FROM task_activity_mirrors WHERE google_task_id = ?1", params![google_task_id])
+        .context("Failed to clear queued task activity mirror")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::operations::task_queue_operations;
+    use crate::database::schema::run_migrations;
+
+    #[test]
+    fn test_comment_lifecycle() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let first = add_comment(&conn, "temp-1", Some("acc"), "ada@example.com", "Waiting on legal").unwrap();
+        let second = add_comment(&conn, "task-1", Some("acc"), "ada@example.com", "Approved").unwrap();
+        add_comment(&conn, "task-2", None, "ada@example.com", "Other task").unwrap();
+        assert!(first.edited_at.is_none());
+
+        let ids = ["task-1".to_string(), "temp-1".to_string()];
+        let comments = list_comments(&conn, &ids).unwrap();
+        assert_eq!(comments.iter().map(|c| c.id).collect::<Vec<_>>(), [first.id, second.id]);
+
+        let edited = edit_comment(&conn, second.id, "Approved by legal").unwrap().unwrap();
+        assert_eq!(edited.body, "Approved by legal");
+        assert!(edited.edited_at.is_some());
+        assert!(edit_comment(&conn, 999, "x").unwrap().is_none());
+
+        assert!(delete_comment(&conn, first.id).unwrap());
+        assert_eq!(list_comments(&conn, &ids).unwrap(), [edited]);
+    }
+
+    #[test]
+    fn test_queued_mirror_follows_the_task_id() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let temp_id = task_queue_operations::new_temp_task_id();
+        task_queue_operations::create_temp_mapping(&conn, &temp_id, "list").unwrap();
+        queue_mirror(&conn, &temp_id, "acc", "list").unwrap();
+        queue_mirror(&conn, &temp_id, "acc", "list").unwrap();
+        task_queue_operations::reconcile_task_id(&mut conn, &temp_id, "google-1").unwrap();
+
+        let queued = queued_mirrors(&conn).unwrap();
+        assert_eq!(queued, [QueuedMirror {
+            google_task_id: "google-1".to_string(),
+            account_id: "acc".to_string(),
+            task_list_id: "list".to_string(),
+        }]);
+        clear_mirror(&conn, "google-1").unwrap();
+        assert!(queued_mirrors(&conn).unwrap().is_empty());
+    }
+}
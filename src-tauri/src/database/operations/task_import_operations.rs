@@ -0,0 +1,48 @@
+//! Task import records
+//!
+//! Which Google task each row of an imported Todoist or TickTick file
+//! became, keyed by `services::google::task_import::import_key`, so a
+//! rerun skips the rows already imported.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+
+/// Google task and list of each row imported for an account, by import key
+pub fn imported_tasks(conn: &Connection, account_id: &str) -> Result<HashMap<String, (String, String)>> {
+    let mut stmt = conn.prepare("SELECT import_key, task_id, task_list_id FROM task_imports WHERE account_id = ?1")?;
+    let rows = stmt
+        .query_map(params![account_id], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))?
+        .collect::<rusqlite::Result<HashMap<_, _>>>()
+        .context("Failed to load task imports")?;
+    Ok(rows)
+}
+
+pub fn record_import(conn: &Connection, account_id: &str, import_key: &str, task_id: &str, task_list_id: &str) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO task_imports (account_id, import_key, task_id, task_list_id, imported_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![account_id, import_key, task_id, task_list_id, chrono::Utc::now().to_rfc3339()],
+    )
+    .context("Failed to record task import")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    #[test]
+    fn test_imports_are_kept_per_account() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        record_import(&conn, "a", "todoist_json:1", "g1", "l1").unwrap();
+        record_import(&conn, "b", "todoist_json:1", "g2", "l2").unwrap();
+
+        let imported = imported_tasks(&conn, "a").unwrap();
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported["todoist_json:1"], ("g1".to_string(), "l1".to_string()));
+    }
+}
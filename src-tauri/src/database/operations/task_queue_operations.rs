@@ -0,0 +1,159 @@
+//! Optimistic task ID operations
+//!
+//! A task created while offline gets a temporary ID straight away so the UI
+//! can show, edit and file it like any other task. The temporary ID is
+//! recorded in `task_id_map` (as both the local and the Google ID) and used
+//! as the key for metadata and board cards. Once the queued create reaches
+//! Google, every reference is rewritten to the real ID.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+
+/// Prefix of temporary task IDs
+pub const TEMP_TASK_ID_PREFIX: &str = "local-task-";
+
+pub fn new_temp_task_id() -> String {
+    format!("{}{}", TEMP_TASK_ID_PREFIX, uuid::Uuid::new_v4())
+}
+
+pub fn is_temp_task_id(task_id: &str) -> bool {
+    task_id.starts_with(TEMP_TASK_ID_PREFIX)
+}
+
+/// Record a temporary ID in the ID map until the real one is known
+pub fn create_temp_mapping(conn: &Connection, temp_id: &str, task_list_id: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO task_id_map (local_id, google_task_id, task_list_id) VALUES (?1, ?1, ?2)",
+        params![temp_id, task_list_id],
+    ).context("Failed to record temporary task ID")?;
+    Ok(())
+}
+
+/// Point every reference to a temporary ID at the Google ID it was given
+pub fn reconcile_task_id(conn: &mut Connection, temp_id: &str, google_task_id: &str) -> Result<()> {
+    let tx = conn.transaction().context("Failed to start task ID transaction")?;
+    tx.execute(
+        "UPDATE task_id_map SET google_task_id = ?1, updated_at = CURRENT_TIMESTAMP WHERE local_id = ?2",
+        params![google_task_id, temp_id],
+    ).context("Failed to update task ID map")?;
+    tx.execute(
+        "UPDATE task_metadata SET google_task_id = ?1, updated_at = CURRENT_TIMESTAMP WHERE google_task_id = ?2",
+        params![google_task_id, temp_id],
+    ).context("Failed to update task metadata ID")?;
+    tx.execute(
+        "UPDATE board_cards SET google_task_id = ?1 WHERE google_task_id = ?2",
+        params![google_task_id, temp_id],
+    ).context("Failed to update board card task ID")?;
+    tx.execute(
+        "UPDATE task_comments SET google_task_id = ?1 WHERE google_task_id = ?2",
+        params![google_task_id, temp_id],
+    ).context("Failed to update task comment task ID")?;
+    tx.execute(
+        "UPDATE task_activity_mirrors SET google_task_id = ?1 WHERE google_task_id = ?2",
+        params![google_task_id, temp_id],
+    ).context("Failed to update queued activity mirror task ID")?;
+    tx.execute(
+        "UPDATE pending_changes SET entity_id = ?1, updated_at = CURRENT_TIMESTAMP
+         WHERE entity_type = 'task' AND entity_id = ?2 AND status IN ('pending', 'failed', 'conflict')",
+        params![google_task_id, temp_id],
+    ).context("Failed to update queued task changes")?;
+    tx.commit().context("Failed to reconcile task ID")?;
+    Ok(())
+}
+
+/// Temporary IDs a task had before it reached Google; its journal entries
+/// from that time are still keyed by them
+pub fn former_task_ids(conn: &Connection, google_task_id: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT local_id FROM task_id_map WHERE google_task_id = ?1 AND local_id != google_task_id",
+    )?;
+    let ids = stmt
+        .query_map(params![google_task_id], |row| row.get(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()
+        .context("Failed to list former task IDs")?;
+    Ok(ids)
+}
+
+/// Drop a task that never reached Google: its queued changes are discarded
+/// and its local records removed. Returns false if nothing was queued for it.
+pub fn discard_unsynced_task(conn: &mut Connection, temp_id: &str) -> Result<bool> {
+    let tx = conn.transaction().context("Failed to start task ID transaction")?;
+    let discarded = tx.execute(
+        "UPDATE pending_changes SET status = 'discarded', updated_at = CURRENT_TIMESTAMP
+         WHERE entity_type = 'task' AND entity_id = ?1 AND status IN ('pending', 'failed')",
+        params![temp_id],
+    ).context("Failed to discard queued task changes")?;
+    tx.execute("DELETE FROM task_id_map WHERE local_id = ?1", params![temp_id])
+        .context("Failed to remove temporary task ID")?;
+    tx.execute("DELETE FROM task_metadata WHERE google_task_id = ?1", params![temp_id])
+        .context("Failed to remove task metadata")?;
+    tx.execute("DELETE FROM board_cards WHERE google_task_id = ?1", params![temp_id])
+        .context("Failed to remove board cards")?;
+    tx.execute("DELETE FROM task_comments WHERE google_task_id = ?1", params![temp_id])
+        .context("Failed to remove task comments")?;
+    tx.execute("DELETE FROM task_activity_mirrors WHERE google_task_id = ?1", params![temp_id])
+        .context("Failed to remove queued activity mirror")?;
+    tx.commit().context("Failed to discard unsynced task")?;
+    Ok(discarded > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::operations::pending_change_operations::{self, NewPendingChange};
+    use crate::database::schema::run_migrations;
+    use serde_json::json;
+
+    fn queue(conn: &Connection, entity_id: &str, operation: &str) -> i64 {
+        pending_change_operations::record_pending_change(conn, &NewPendingChange {
+            account_id: "acc".to_string(),
+            entity_type: "task".to_string(),
+            entity_id: entity_id.to_string(),
+            container_id: Some("list".to_string()),
+            operation: operation.to_string(),
+            payload: json!({ "title": "Offline task" }),
+            base_payload: None,
+            base_etag: None,
+            base_updated: None,
+        }).unwrap()
+    }
+
+    #[test]
+    fn test_temporary_ids_are_reconciled() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let temp_id = new_temp_task_id();
+        assert!(is_temp_task_id(&temp_id));
+        assert!(!is_temp_task_id("MTIzNDU2Nzg5"));
+
+        create_temp_mapping(&conn, &temp_id, "list").unwrap();
+        let create_id = queue(&conn, &temp_id, "create");
+        assert!(pending_change_operations::find_unpushed_create(&conn, "task", &temp_id).unwrap().is_some());
+
+        reconcile_task_id(&mut conn, &temp_id, "google-1").unwrap();
+        let google_id: String = conn
+            .query_row("SELECT google_task_id FROM task_id_map WHERE local_id = ?1", params![temp_id], |r| r.get(0))
+            .unwrap();
+        assert_eq!(google_id, "google-1");
+        assert_eq!(former_task_ids(&conn, "google-1").unwrap(), [temp_id]);
+        let change = pending_change_operations::get_pending_change(&conn, create_id).unwrap().unwrap();
+        assert_eq!(change.entity_id, "google-1");
+    }
+
+    #[test]
+    fn test_discarding_an_unsynced_task() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let temp_id = new_temp_task_id();
+        create_temp_mapping(&conn, &temp_id, "list").unwrap();
+        let create_id = queue(&conn, &temp_id, "create");
+
+        assert!(discard_unsynced_task(&mut conn, &temp_id).unwrap());
+        let change = pending_change_operations::get_pending_change(&conn, create_id).unwrap().unwrap();
+        assert_eq!(change.status, "discarded");
+        assert!(pending_change_operations::list_pending_changes(&conn, "acc").unwrap().is_empty());
+        assert!(!discard_unsynced_task(&mut conn, &temp_id).unwrap());
+    }
+}
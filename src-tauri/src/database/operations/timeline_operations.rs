@@ -0,0 +1,228 @@
+//! Activity timeline queries
+//!
+//! What the user did in a time range, read from the local copies: sent
+//! mail from the Gmail message store, notes and chat sessions by creation
+//! time. Journaled changes come from `change_journal_operations`; the
+//! titles they lack are looked up here.
+//!
+//! Timestamps are stored differently per table: message dates as epoch
+//! milliseconds, notes and chat sessions in local time, task completions
+//! as UTC RFC 3339, so each query takes bounds in the table's own form.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SentMessage {
+    pub account_id: String,
+    pub message_id: String,
+    pub thread_id: String,
+    pub subject: Option<String>,
+    /// Recipient addresses, comma separated
+    pub to: String,
+    /// Epoch milliseconds
+    pub sent_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatedNote {
+    pub id: i32,
+    pub title: String,
+    pub folder_id: Option<i32>,
+    /// Local time, "YYYY-MM-DD HH:MM:SS[.fff]"
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartedChat {
+    pub id: i32,
+    pub session_name: String,
+    pub message_count: i64,
+    /// Local time, "YYYY-MM-DD HH:MM:SS[.fff]"
+    pub created_at: String,
+}
+
+/// A synced task Google reports as completed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompletedTask {
+    pub account_id: String,
+    pub task_id: String,
+    pub task_list_id: String,
+    pub title: String,
+    /// UTC RFC 3339
+    pub completed_at: String,
+}
+
+/// Stored messages labelled SENT with a date in [`start`, `end`) (epoch
+/// milliseconds), oldest first
+pub fn sent_between(conn: &Connection, start: i64, end: i64) -> Result<Vec<SentMessage>> {
+    let mut stmt = conn.prepare(
+        "SELECT s.account_id, s.message_id, s.thread_id,
+                nullif(json_extract(s.message_data, '$.parsed_content.subject'), ''),
+                coalesce((SELECT group_concat(json_extract(t.value, '$.email'), ', ')
+                          FROM json_each(s.message_data, '$.parsed_content.to') t), ''),
+                s.internal_date
+         FROM gmail_message_store s
+         WHERE s.internal_date >= ?1 AND s.internal_date < ?2
+           AND EXISTS (SELECT 1 FROM json_each(s.label_ids) WHERE value = 'SENT')
+         ORDER BY s.internal_date",
+    )?;
+    let messages = stmt
+        .query_map(params![start, end], |row| {
+            Ok(SentMessage {
+                account_id: row.get(0)?,
+                message_id: row.get(1)?,
+                thread_id: row.get(2)?,
+                subject: row.get(3)?,
+                to: row.get(4)?,
+                sent_at: row.get(5)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list sent messages")?;
+    Ok(messages)
+}
+
+/// Notes created in [`start`, `end`) (local, "YYYY-MM-DD HH:MM:SS"),
+/// oldest first
+pub fn notes_created_between(conn: &Connection, start: &str, end: &str) -> Result<Vec<CreatedNote>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, title, folder_id, created_at FROM notes
+         WHERE created_at >= ?1 AND created_at < ?2 ORDER BY created_at",
+    )?;
+    let notes = stmt
+        .query_map(params![start, end], |row| {
+            Ok(CreatedNote {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                folder_id: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list created notes")?;
+    Ok(notes)
+}
+
+/// Chat sessions started in [`start`, `end`) (local, "YYYY-MM-DD
+/// HH:MM:SS"), oldest first
+pub fn chats_started_between(conn: &Connection, start: &str, end: &str) -> Result<Vec<StartedChat>> {
+    let mut stmt = conn.prepare(
+        "SELECT s.id, s.session_name, (SELECT COUNT(*) FROM chat_messages m WHERE m.session_id = s.id), s.created_at
+         FROM chat_sessions s
+         WHERE s.created_at >= ?1 AND s.created_at < ?2 ORDER BY s.created_at",
+    )?;
+    let chats = stmt
+        .query_map(params![start, end], |row| {
+            Ok(StartedChat {
+                id: row.get(0)?,
+                session_name: row.get(1)?,
+                message_count: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list chat sessions")?;
+    Ok(chats)
+}
+
+/// Synced tasks completed in [`start`, `end`) (UTC RFC 3339), from the
+/// local search copy, oldest first
+pub fn tasks_completed_between(conn: &Connection, start: &str, end: &str) -> Result<Vec<CompletedTask>> {
+    let mut stmt = conn.prepare(
+        "SELECT account_id, item_id, container_id, title, completed_at FROM search_items
+         WHERE domain = 'task' AND status = 'completed' AND completed_at >= ?1 AND completed_at < ?2
+         ORDER BY completed_at",
+    )?;
+    let tasks = stmt
+        .query_map(params![start, end], |row| {
+            Ok(CompletedTask {
+                account_id: row.get(0)?,
+                task_id: row.get(1)?,
+                task_list_id: row.get(2)?,
+                title: row.get(3)?,
+                completed_at: row.get(4)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list completed tasks")?;
+    Ok(tasks)
+}
+
+/// Title of a synced task, from the local search copy
+pub fn task_title(conn: &Connection, task_id: &str) -> Result<Option<String>> {
+    conn.query_row(
+        "SELECT title FROM search_items WHERE domain = 'task' AND item_id = ?1 LIMIT 1",
+        params![task_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .context("Failed to look up task title")
+}
+
+pub fn note_title(conn: &Connection, note_id: i64) -> Result<Option<String>> {
+    conn.query_row("SELECT title FROM notes WHERE id = ?1", params![note_id], |row| row.get(0))
+        .optional()
+        .context("Failed to look up note title")
+}
+
+/// Subject of the first stored message of a thread that has one
+pub fn thread_subject(conn: &Connection, account_id: Option<&str>, thread_id: &str) -> Result<Option<String>> {
+    conn.query_row(
+        "SELECT json_extract(message_data, '$.parsed_content.subject') FROM gmail_message_store
+         WHERE thread_id = ?1 AND (?2 IS NULL OR account_id = ?2)
+           AND coalesce(json_extract(message_data, '$.parsed_content.subject'), '') != ''
+         ORDER BY internal_date LIMIT 1",
+        params![thread_id, account_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .context("Failed to look up thread subject")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    #[test]
+    fn test_range_queries() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        for (id, labels, date) in [("m1", r#"["SENT"]"#, 1_000), ("m2", r#"["INBOX"]"#, 1_500), ("m3", r#"["SENT"]"#, 9_000)] {
+            conn.execute(
+                "INSERT INTO gmail_message_store (account_id, message_id, thread_id, label_ids, internal_date, message_data)
+                 VALUES ('acc', ?1, 't1', ?2, ?3, ?4)",
+                params![
+                    id,
+                    labels,
+                    date,
+                    serde_json::json!({ "parsed_content": { "subject": "Budget", "to": [{ "email": "a@x.org" }, { "email": "b@x.org" }] } })
+                        .to_string()
+                ],
+            )
+            .unwrap();
+        }
+        let sent = sent_between(&conn, 0, 5_000).unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!((sent[0].message_id.as_str(), sent[0].to.as_str()), ("m1", "a@x.org, b@x.org"));
+        assert_eq!(thread_subject(&conn, None, "t1").unwrap().as_deref(), Some("Budget"));
+
+        conn.execute(
+            "INSERT INTO notes (title, content, user_id, created_at, updated_at)
+             VALUES ('Plan', '', 'default_user', '2026-10-13 09:15:00.5', '2026-10-13 09:15:00.5')",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO chat_sessions (user_id, session_name, created_at, updated_at)
+             VALUES ('default_user', 'Ideas', '2026-10-14 00:00:00', '2026-10-14 00:00:00')",
+            [],
+        )
+        .unwrap();
+        assert_eq!(notes_created_between(&conn, "2026-10-13 00:00:00", "2026-10-14 00:00:00").unwrap().len(), 1);
+        assert!(chats_started_between(&conn, "2026-10-13 00:00:00", "2026-10-14 00:00:00").unwrap().is_empty());
+        assert_eq!(chats_started_between(&conn, "2026-10-14 00:00:00", "2026-10-15 00:00:00").unwrap().len(), 1);
+    }
+}
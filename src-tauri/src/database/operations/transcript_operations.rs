@@ -0,0 +1,209 @@
+//! Audio transcript operations
+//!
+//! Transcripts of voice notes and audio attachments are stored once per
+//! source and indexed in `attachment_transcripts_fts` so they can be found
+//! by what was said.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use super::search_operations::fts_query;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachmentTranscript {
+    pub id: i64,
+    pub source_key: String,
+    pub account_id: Option<String>,
+    pub message_id: Option<String>,
+    pub attachment_id: Option<String>,
+    pub note_id: Option<i64>,
+    pub filename: String,
+    pub mime_type: String,
+    pub transcript: String,
+    pub engine: String,
+    pub created_at: String,
+}
+
+/// A search hit with a highlighted excerpt of the transcript
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptSearchResult {
+    pub transcript: AttachmentTranscript,
+    pub snippet: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewTranscript<'a> {
+    pub source_key: &'a str,
+    pub account_id: Option<&'a str>,
+    pub message_id: Option<&'a str>,
+    pub attachment_id: Option<&'a str>,
+    pub note_id: Option<i64>,
+    pub filename: &'a str,
+    pub mime_type: &'a str,
+    pub transcript: &'a str,
+    pub engine: &'a str,
+}
+
+const TRANSCRIPT_COLUMNS: &str = "t.id, t.source_key, t.account_id, t.message_id, t.attachment_id, t.note_id,
+    t.filename, t.mime_type, t.transcript, t.engine, t.created_at";
+
+fn transcript_from_row(row: &Row) -> rusqlite::Result<AttachmentTranscript> {
+    Ok(AttachmentTranscript {
+        id: row.get(0)?,
+        source_key: row.get(1)?,
+        account_id: row.get(2)?,
+        message_id: row.get(3)?,
+        attachment_id: row.get(4)?,
+        note_id: row.get(5)?,
+        filename: row.get(6)?,
+        mime_type: row.get(7)?,
+        transcript: row.get(8)?,
+        engine: row.get(9)?,
+        created_at: row.get(10)?,
+    })
+}
+
+/// Key for a Gmail attachment
+pub fn gmail_source_key(account_id: &str, message_id: &str, attachment_id: &str) -> String {
+    format!("gmail:{}:{}:{}", account_id, message_id, attachment_id)
+}
+
+/// Key for a local file
+pub fn file_source_key(path: &str) -> String {
+    format!("file:{}", path)
+}
+
+/// Key for one version of a local file, by size and modification time, so
+/// a file changed since it was transcribed is transcribed again
+pub fn file_version_key(path: &str, size: u64, modified: Option<std::time::SystemTime>) -> String {
+    let modified_ms = modified
+        .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(0, |since| since.as_millis());
+    format!("file:{}:{}:{}", path, size, modified_ms)
+}
+
+pub fn get_transcript(conn: &Connection, source_key: &str) -> Result<Option<AttachmentTranscript>> {
+    conn.query_row(
+        &format!("SELECT {} FROM attachment_transcripts t WHERE t.source_key = ?1", TRANSCRIPT_COLUMNS),
+        params![source_key],
+        transcript_from_row,
+    )
+    .optional()
+    .context("Failed to load transcript")
+}
+
+/// Store a transcript, replacing an earlier one for the same source
+pub fn save_transcript(conn: &Connection, transcript: &NewTranscript) -> Result<AttachmentTranscript> {
+    conn.execute(
+        "INSERT INTO attachment_transcripts
+            (source_key, account_id, message_id, attachment_id, note_id, filename, mime_type, transcript, engine)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+         ON CONFLICT(source_key) DO UPDATE SET
+            filename = excluded.filename, mime_type = excluded.mime_type,
+            transcript = excluded.transcript, engine = excluded.engine, created_at = CURRENT_TIMESTAMP",
+        params![
+            transcript.source_key,
+            transcript.account_id,
+            transcript.message_id,
+            transcript.attachment_id,
+            transcript.note_id,
+            transcript.filename,
+            transcript.mime_type,
+            transcript.transcript,
+            transcript.engine,
+        ],
+    ).context("Failed to save transcript")?;
+
+    get_transcript(conn, transcript.source_key)?.context("Transcript missing after save")
+}
+
+/// Transcripts linked to a note
+pub fn list_for_note(conn: &Connection, note_id: i64) -> Result<Vec<AttachmentTranscript>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM attachment_transcripts t WHERE t.note_id = ?1 ORDER BY t.created_at",
+        TRANSCRIPT_COLUMNS
+    ))?;
+    let transcripts = stmt
+        .query_map(params![note_id], transcript_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list note transcripts")?;
+    Ok(transcripts)
+}
+
+/// Full-text search over transcripts and their filenames, best match first
+pub fn search_transcripts(
+    conn: &Connection,
+    query: &str,
+    account_id: Option<&str>,
+    limit: usize,
+) -> Result<Vec<TranscriptSearchResult>> {
+    let fts_query = fts_query(query);
+    if fts_query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}, snippet(attachment_transcripts_fts, 0, '[', ']', '…', 12)
+         FROM attachment_transcripts_fts
+         JOIN attachment_transcripts t ON t.id = attachment_transcripts_fts.rowid
+         WHERE attachment_transcripts_fts MATCH ?1 AND (?2 IS NULL OR t.account_id = ?2)
+         ORDER BY bm25(attachment_transcripts_fts) LIMIT ?3",
+        TRANSCRIPT_COLUMNS
+    ))?;
+    let results = stmt
+        .query_map(params![fts_query, account_id, limit as i64], |row| {
+            Ok(TranscriptSearchResult {
+                transcript: transcript_from_row(row)?,
+                snippet: row.get(11)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to search transcripts")?;
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    #[test]
+    fn test_file_keys_change_with_the_file() {
+        let saved = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_760_000_000);
+        let key = file_version_key("/notes/memo.m4a", 2048, Some(saved));
+        assert_eq!(key, "file:/notes/memo.m4a:2048:1760000000000");
+        assert_ne!(key, file_version_key("/notes/memo.m4a", 2048, Some(saved + std::time::Duration::from_secs(5))));
+        assert_ne!(key, file_version_key("/notes/memo.m4a", 4096, Some(saved)));
+    }
+
+    #[test]
+    fn test_transcripts_are_searchable() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let key = gmail_source_key("acc", "msg-1", "att-1");
+        let new_transcript = NewTranscript {
+            source_key: &key,
+            account_id: Some("acc"),
+            message_id: Some("msg-1"),
+            attachment_id: Some("att-1"),
+            note_id: None,
+            filename: "voicemail.m4a",
+            mime_type: "audio/mp4",
+            transcript: "Hi, the quarterly budget review moved to Thursday",
+            engine: "whisper.cpp",
+        };
+        save_transcript(&conn, &new_transcript).unwrap();
+
+        let hits = search_transcripts(&conn, "budget thursday", Some("acc"), 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert!(hits[0].snippet.contains("[budget]"));
+        assert!(search_transcripts(&conn, "budget", Some("other"), 10).unwrap().is_empty());
+
+        // Re-transcribing replaces the indexed text
+        save_transcript(&conn, &NewTranscript { transcript: "Call me back about \"invoices\"", ..new_transcript }).unwrap();
+        assert!(search_transcripts(&conn, "budget", None, 10).unwrap().is_empty());
+        assert_eq!(search_transcripts(&conn, "\"invoices", None, 10).unwrap().len(), 1);
+    }
+}
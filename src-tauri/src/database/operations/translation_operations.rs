@@ -0,0 +1,84 @@
+//! Cached message translations
+//!
+//! Translating with a local model is slow, so each message is translated
+//! once per target language and served from here afterwards.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageTranslation {
+    pub account_id: String,
+    pub message_id: String,
+    pub target_lang: String,
+    /// ISO 639-3 code of the detected source language
+    pub source_lang: Option<String>,
+    pub source_lang_name: Option<String>,
+    pub translated_text: String,
+    /// Model that produced the translation, kept for diagnostics only
+    #[serde(default, skip_serializing)]
+    pub model: String,
+    pub created_at: String,
+}
+
+fn translation_from_row(row: &Row) -> rusqlite::Result<MessageTranslation> {
+    Ok(MessageTranslation {
+        account_id: row.get(0)?,
+        message_id: row.get(1)?,
+        target_lang: row.get(2)?,
+        source_lang: row.get(3)?,
+        source_lang_name: row.get(4)?,
+        translated_text: row.get(5)?,
+        model: row.get(6)?,
+        created_at: row.get(7)?,
+    })
+}
+
+pub fn get_translation(
+    conn: &Connection,
+    account_id: &str,
+    message_id: &str,
+    target_lang: &str,
+) -> Result<Option<MessageTranslation>> {
+    conn.query_row(
+        "SELECT account_id, message_id, target_lang, source_lang, source_lang_name, translated_text, model, created_at
+         FROM message_translations WHERE account_id = ?1 AND message_id = ?2 AND target_lang = ?3",
+        params![account_id, message_id, target_lang],
+        translation_from_row,
+    )
+    .optional()
+    .context("Failed to load cached translation")
+}
+
+/// A translation to cache
+#[derive(Debug, Clone)]
+pub struct NewTranslation<'a> {
+    pub account_id: &'a str,
+    pub message_id: &'a str,
+    pub target_lang: &'a str,
+    pub source_lang: Option<&'a str>,
+    pub source_lang_name: Option<&'a str>,
+    pub translated_text: &'a str,
+    pub model: &'a str,
+}
+
+pub fn save_translation(conn: &Connection, translation: &NewTranslation) -> Result<MessageTranslation> {
+    conn.execute(
+        "INSERT OR REPLACE INTO message_translations
+            (account_id, message_id, target_lang, source_lang, source_lang_name, translated_text, model)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            translation.account_id,
+            translation.message_id,
+            translation.target_lang,
+            translation.source_lang,
+            translation.source_lang_name,
+            translation.translated_text,
+            translation.model,
+        ],
+    ).context("Failed to cache translation")?;
+
+    get_translation(conn, translation.account_id, translation.message_id, translation.target_lang)?
+        .context("Translation missing after insert")
+}
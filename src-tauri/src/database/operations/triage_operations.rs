@@ -0,0 +1,384 @@
+//! Mail triage database operations
+//!
+//! A triage session walks a queue of messages one at a time. Each action
+//! taken is kept with the labels before and after it so the latest one can
+//! be undone, and the session's stats are stored when it ends. Snoozed
+//! messages wait here until they are due back in the inbox.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TriageSessionRow {
+    pub id: i64,
+    pub account_id: String,
+    pub label_id: String,
+    /// The queue as returned when the session started
+    pub queue_json: String,
+    pub started_at: String,
+    pub ended_at: Option<String>,
+    pub stats_json: Option<String>,
+}
+
+fn session_from_row(row: &Row) -> rusqlite::Result<TriageSessionRow> {
+    Ok(TriageSessionRow {
+        id: row.get(0)?,
+        account_id: row.get(1)?,
+        label_id: row.get(2)?,
+        queue_json: row.get(3)?,
+        started_at: row.get(4)?,
+        ended_at: row.get(5)?,
+        stats_json: row.get(6)?,
+    })
+}
+
+const SESSION_COLUMNS: &str = "id, account_id, label_id, queue_json, started_at, ended_at, stats_json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TriageActionRow {
+    pub id: i64,
+    pub session_id: i64,
+    pub message_id: String,
+    pub kind: String,
+    pub action_json: String,
+    pub labels_before: Vec<String>,
+    pub labels_after: Vec<String>,
+    pub task_id: Option<String>,
+    pub task_list_id: Option<String>,
+    pub snooze_id: Option<i64>,
+    pub created_at: String,
+    pub undone_at: Option<String>,
+}
+
+fn labels_column(row: &Row, index: usize) -> rusqlite::Result<Vec<String>> {
+    let json: String = row.get(index)?;
+    serde_json::from_str(&json)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(e)))
+}
+
+fn action_from_row(row: &Row) -> rusqlite::Result<TriageActionRow> {
+    Ok(TriageActionRow {
+        id: row.get(0)?,
+        session_id: row.get(1)?,
+        message_id: row.get(2)?,
+        kind: row.get(3)?,
+        action_json: row.get(4)?,
+        labels_before: labels_column(row, 5)?,
+        labels_after: labels_column(row, 6)?,
+        task_id: row.get(7)?,
+        task_list_id: row.get(8)?,
+        snooze_id: row.get(9)?,
+        created_at: row.get(10)?,
+        undone_at: row.get(11)?,
+    })
+}
+
+const ACTION_COLUMNS: &str = "id, session_id, message_id, kind, action_json, labels_before, labels_after, \
+                              task_id, task_list_id, snooze_id, created_at, undone_at";
+
+/// An action as it is about to be recorded
+#[derive(Debug, Clone)]
+pub struct NewTriageAction<'a> {
+    pub session_id: i64,
+    pub message_id: &'a str,
+    pub kind: &'a str,
+    pub action_json: &'a str,
+    pub labels_before: &'a [String],
+    pub labels_after: &'a [String],
+    pub task_id: Option<&'a str>,
+    pub task_list_id: Option<&'a str>,
+    pub snooze_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnoozedMessage {
+    pub id: i64,
+    pub account_id: String,
+    pub message_id: String,
+    pub thread_id: String,
+    /// Unix seconds
+    pub wake_at: i64,
+    /// Failed attempts to put it back
+    pub attempts: u32,
+}
+
+fn snooze_from_row(row: &Row) -> rusqlite::Result<SnoozedMessage> {
+    Ok(SnoozedMessage {
+        id: row.get(0)?,
+        account_id: row.get(1)?,
+        message_id: row.get(2)?,
+        thread_id: row.get(3)?,
+        wake_at: row.get(4)?,
+        attempts: row.get(5)?,
+    })
+}
+
+pub fn create_session(conn: &Connection, account_id: &str, label_id: &str, queue_json: &str) -> Result<TriageSessionRow> {
+    conn.execute(
+        "INSERT INTO triage_sessions (account_id, label_id, queue_json) VALUES (?1, ?2, ?3)",
+        params![account_id, label_id, queue_json],
+    )
+    .context("Failed to start triage session")?;
+    get_session(conn, conn.last_insert_rowid())?.context("Triage session vanished after insert")
+}
+
+pub fn get_session(conn: &Connection, id: i64) -> Result<Option<TriageSessionRow>> {
+    conn.query_row(
+        &format!("SELECT {} FROM triage_sessions WHERE id = ?1", SESSION_COLUMNS),
+        params![id],
+        session_from_row,
+    )
+    .optional()
+    .context("Failed to load triage session")
+}
+
+/// Finished sessions of an account, newest first
+pub fn list_ended_sessions(conn: &Connection, account_id: &str, limit: u32) -> Result<Vec<TriageSessionRow>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM triage_sessions WHERE account_id = ?1 AND ended_at IS NOT NULL
+         ORDER BY ended_at DESC, id DESC LIMIT ?2",
+        SESSION_COLUMNS
+    ))?;
+    let sessions = stmt
+        .query_map(params![account_id, limit], session_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list triage sessions")?;
+    Ok(sessions)
+}
+
+/// Returns false if the session was already ended
+pub fn end_session(conn: &Connection, id: i64, stats_json: &str) -> Result<bool> {
+    let updated = conn.execute(
+        "UPDATE triage_sessions SET ended_at = CURRENT_TIMESTAMP, stats_json = ?1 WHERE id = ?2 AND ended_at IS NULL",
+        params![stats_json, id],
+    )
+    .context("Failed to end triage session")?;
+    Ok(updated > 0)
+}
+
+pub fn record_action(conn: &Connection, action: &NewTriageAction) -> Result<TriageActionRow> {
+    conn.execute(
+        "INSERT INTO triage_actions (session_id, message_id, kind, action_json, labels_before, labels_after,
+                                     task_id, task_list_id, snooze_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            action.session_id,
+            action.message_id,
+            action.kind,
+            action.action_json,
+            serde_json::to_string(action.labels_before)?,
+            serde_json::to_string(action.labels_after)?,
+            action.task_id,
+            action.task_list_id,
+            action.snooze_id,
+        ],
+    )
+    .context("Failed to record triage action")?;
+    let id = conn.last_insert_rowid();
+    conn.query_row(
+        &format!("SELECT {} FROM triage_actions WHERE id = ?1", ACTION_COLUMNS),
+        params![id],
+        action_from_row,
+    )
+    .context("Triage action vanished after insert")
+}
+
+/// The session's latest action that has not been undone
+pub fn last_action(conn: &Connection, session_id: i64) -> Result<Option<TriageActionRow>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM triage_actions WHERE session_id = ?1 AND undone_at IS NULL ORDER BY id DESC LIMIT 1",
+            ACTION_COLUMNS
+        ),
+        params![session_id],
+        action_from_row,
+    )
+    .optional()
+    .context("Failed to load last triage action")
+}
+
+pub fn mark_undone(conn: &Connection, action_id: i64) -> Result<()> {
+    conn.execute(
+        "UPDATE triage_actions SET undone_at = CURRENT_TIMESTAMP WHERE id = ?1",
+        params![action_id],
+    )
+    .context("Failed to mark triage action undone")?;
+    Ok(())
+}
+
+/// Actions of a session in the order they were taken, undone ones included
+pub fn list_actions(conn: &Connection, session_id: i64) -> Result<Vec<TriageActionRow>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM triage_actions WHERE session_id = ?1 ORDER BY id",
+        ACTION_COLUMNS
+    ))?;
+    let actions = stmt
+        .query_map(params![session_id], action_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list triage actions")?;
+    Ok(actions)
+}
+
+pub fn snooze_message(conn: &Connection, account_id: &str, message_id: &str, thread_id: &str, wake_at: i64) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO snoozed_messages (account_id, message_id, thread_id, wake_at) VALUES (?1, ?2, ?3, ?4)",
+        params![account_id, message_id, thread_id, wake_at],
+    )
+    .context("Failed to snooze message")?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Returns false if the snooze had already woken or been cancelled
+pub fn cancel_snooze(conn: &Connection, id: i64) -> Result<bool> {
+    let updated = conn.execute(
+        "UPDATE snoozed_messages SET cancelled_at = CURRENT_TIMESTAMP
+         WHERE id = ?1 AND woken_at IS NULL AND cancelled_at IS NULL",
+        params![id],
+    )
+    .context("Failed to cancel snooze")?;
+    Ok(updated > 0)
+}
+
+/// Snoozes due back by `now` and not waiting out a retry, earliest first
+pub fn due_snoozes(conn: &Connection, now: i64, limit: u32) -> Result<Vec<SnoozedMessage>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, account_id, message_id, thread_id, wake_at, attempts FROM snoozed_messages
+         WHERE wake_at <= ?1 AND woken_at IS NULL AND cancelled_at IS NULL
+           AND (next_attempt_at IS NULL OR next_attempt_at <= ?1)
+         ORDER BY wake_at, id LIMIT ?2",
+    )?;
+    let snoozes = stmt
+        .query_map(params![now, limit], snooze_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to load due snoozes")?;
+    Ok(snoozes)
+}
+
+/// When the next pending snooze is due, counting retry waits
+pub fn next_wake_at(conn: &Connection) -> Result<Option<i64>> {
+    conn.query_row(
+        "SELECT MIN(MAX(wake_at, IFNULL(next_attempt_at, 0))) FROM snoozed_messages
+         WHERE woken_at IS NULL AND cancelled_at IS NULL",
+        [],
+        |row| row.get(0),
+    )
+    .context("Failed to load next snooze")
+}
+
+/// Count a failed wake and hold the snooze back until `retry_at`
+pub fn record_wake_failure(conn: &Connection, id: i64, retry_at: i64) -> Result<()> {
+    conn.execute(
+        "UPDATE snoozed_messages SET attempts = attempts + 1, next_attempt_at = ?2 WHERE id = ?1",
+        params![id, retry_at],
+    )
+    .context("Failed to record snooze wake failure")?;
+    Ok(())
+}
+
+/// Cancel pending snoozes of accounts that are no longer signed in
+pub fn cancel_snoozes_without_account(conn: &Connection) -> Result<usize> {
+    conn.execute(
+        "UPDATE snoozed_messages SET cancelled_at = CURRENT_TIMESTAMP
+         WHERE woken_at IS NULL AND cancelled_at IS NULL
+           AND account_id NOT IN (SELECT id FROM gmail_accounts_secure WHERE is_active = 1)",
+        [],
+    )
+    .context("Failed to cancel orphaned snoozes")
+}
+
+pub fn mark_woken(conn: &Connection, id: i64) -> Result<()> {
+    conn.execute(
+        "UPDATE snoozed_messages SET woken_at = CURRENT_TIMESTAMP WHERE id = ?1",
+        params![id],
+    )
+    .context("Failed to mark snooze woken")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    fn labels(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_session_actions_and_snoozes() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let session = create_session(&conn, "acc", "INBOX", "[]").unwrap();
+        let before = labels(&["INBOX", "UNREAD"]);
+        let after = labels(&["UNREAD"]);
+        let snooze_id = snooze_message(&conn, "acc", "m2", "t2", 100).unwrap();
+        let archived = record_action(&conn, &NewTriageAction {
+            session_id: session.id,
+            message_id: "m1",
+            kind: "archive",
+            action_json: r#"{"type":"archive"}"#,
+            labels_before: &before,
+            labels_after: &after,
+            task_id: None,
+            task_list_id: None,
+            snooze_id: None,
+        }).unwrap();
+        let snoozed = record_action(&conn, &NewTriageAction {
+            session_id: session.id,
+            message_id: "m2",
+            kind: "snooze",
+            action_json: r#"{"type":"snooze"}"#,
+            labels_before: &before,
+            labels_after: &after,
+            task_id: None,
+            task_list_id: None,
+            snooze_id: Some(snooze_id),
+        }).unwrap();
+        assert_eq!(archived.labels_before, before);
+
+        assert_eq!(last_action(&conn, session.id).unwrap().unwrap().id, snoozed.id);
+        mark_undone(&conn, snoozed.id).unwrap();
+        assert!(cancel_snooze(&conn, snooze_id).unwrap());
+        assert!(!cancel_snooze(&conn, snooze_id).unwrap());
+        assert_eq!(last_action(&conn, session.id).unwrap().unwrap().id, archived.id);
+        assert_eq!(list_actions(&conn, session.id).unwrap().len(), 2);
+
+        let woken = snooze_message(&conn, "acc", "m3", "t3", 50).unwrap();
+        snooze_message(&conn, "acc", "m4", "t4", 500).unwrap();
+        assert_eq!(due_snoozes(&conn, 100, 10).unwrap().iter().map(|s| s.id).collect::<Vec<_>>(), [woken]);
+        mark_woken(&conn, woken).unwrap();
+        assert!(due_snoozes(&conn, 100, 10).unwrap().is_empty());
+        assert_eq!(next_wake_at(&conn).unwrap(), Some(500));
+
+        assert!(end_session(&conn, session.id, "{}").unwrap());
+        assert!(!end_session(&conn, session.id, "{}").unwrap());
+        assert_eq!(list_ended_sessions(&conn, "acc", 5).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_failed_wakes_wait_and_orphans_are_cancelled() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO gmail_accounts_secure (id, email_address, access_token_encrypted, scopes, created_at, updated_at, user_id)
+             VALUES ('acc', 'a@x.org', '', '', '', '', 'default_user')",
+            [],
+        )
+        .unwrap();
+
+        let failing = snooze_message(&conn, "acc", "m1", "t1", 10).unwrap();
+        let later = snooze_message(&conn, "acc", "m2", "t2", 20).unwrap();
+        record_wake_failure(&conn, failing, 200).unwrap();
+        let due = due_snoozes(&conn, 100, 1).unwrap();
+        assert_eq!(due.iter().map(|s| s.id).collect::<Vec<_>>(), [later]);
+        assert_eq!(due_snoozes(&conn, 200, 5).unwrap()[0].attempts, 1);
+        mark_woken(&conn, later).unwrap();
+        assert_eq!(next_wake_at(&conn).unwrap(), Some(200));
+
+        snooze_message(&conn, "gone", "m3", "t3", 10).unwrap();
+        assert_eq!(cancel_snoozes_without_account(&conn).unwrap(), 1);
+        assert_eq!(due_snoozes(&conn, 200, 5).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,129 @@
+//! Secure notes vault storage
+//!
+//! Rows hold ciphertext only; encryption and the passphrase live in
+//! `services::vault`.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultRecord {
+    /// Base64 Argon2 salt
+    pub salt: String,
+    /// JSON Argon2 cost parameters
+    pub kdf_params: String,
+    /// Notes key encrypted with the passphrase key
+    pub wrapped_key: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+fn vault_from_row(row: &Row) -> rusqlite::Result<VaultRecord> {
+    Ok(VaultRecord {
+        salt: row.get(0)?,
+        kdf_params: row.get(1)?,
+        wrapped_key: row.get(2)?,
+        created_at: row.get(3)?,
+        updated_at: row.get(4)?,
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecureNoteRecord {
+    pub id: i64,
+    pub title_encrypted: String,
+    pub content_encrypted: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+fn note_from_row(row: &Row) -> rusqlite::Result<SecureNoteRecord> {
+    Ok(SecureNoteRecord {
+        id: row.get(0)?,
+        title_encrypted: row.get(1)?,
+        content_encrypted: row.get(2)?,
+        created_at: row.get(3)?,
+        updated_at: row.get(4)?,
+    })
+}
+
+const NOTE_COLUMNS: &str = "id, title_encrypted, content_encrypted, created_at, updated_at";
+
+pub fn get_vault(conn: &Connection) -> Result<Option<VaultRecord>> {
+    conn.query_row(
+        "SELECT salt, kdf_params, wrapped_key, created_at, updated_at FROM secure_vault WHERE id = 1",
+        [],
+        vault_from_row,
+    )
+    .optional()
+    .context("Failed to load vault")
+}
+
+/// Create the vault; false if it already exists, so two setups cannot
+/// both win
+pub fn create_vault(conn: &Connection, salt: &str, kdf_params: &str, wrapped_key: &str) -> Result<bool> {
+    let inserted = conn.execute(
+        "INSERT INTO secure_vault (id, salt, kdf_params, wrapped_key) VALUES (1, ?1, ?2, ?3)
+         ON CONFLICT(id) DO NOTHING",
+        params![salt, kdf_params, wrapped_key],
+    ).context("Failed to create vault")?;
+    Ok(inserted == 1)
+}
+
+/// Replace the key wrapping after a passphrase change
+pub fn save_vault(conn: &Connection, salt: &str, kdf_params: &str, wrapped_key: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE secure_vault SET salt = ?1, kdf_params = ?2, wrapped_key = ?3, updated_at = datetime('now') WHERE id = 1",
+        params![salt, kdf_params, wrapped_key],
+    ).context("Failed to save vault")?;
+    Ok(())
+}
+
+pub fn insert_secure_note(conn: &Connection, title_encrypted: &str, content_encrypted: &str) -> Result<SecureNoteRecord> {
+    conn.execute(
+        "INSERT INTO secure_notes (title_encrypted, content_encrypted) VALUES (?1, ?2)",
+        params![title_encrypted, content_encrypted],
+    ).context("Failed to create secure note")?;
+
+    get_secure_note(conn, conn.last_insert_rowid())?.context("Created secure note not found")
+}
+
+pub fn get_secure_note(conn: &Connection, id: i64) -> Result<Option<SecureNoteRecord>> {
+    conn.query_row(
+        &format!("SELECT {} FROM secure_notes WHERE id = ?1", NOTE_COLUMNS),
+        params![id],
+        note_from_row,
+    )
+    .optional()
+    .context("Failed to load secure note")
+}
+
+/// Every secure note, most recently edited first
+pub fn list_secure_notes(conn: &Connection) -> Result<Vec<SecureNoteRecord>> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM secure_notes ORDER BY updated_at DESC, id DESC", NOTE_COLUMNS))?;
+    let notes = stmt
+        .query_map([], note_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list secure notes")?;
+    Ok(notes)
+}
+
+pub fn update_secure_note(conn: &Connection, id: i64, title_encrypted: &str, content_encrypted: &str) -> Result<bool> {
+    let updated = conn
+        .execute(
+            "UPDATE secure_notes
+             SET title_encrypted = ?2, content_encrypted = ?3, updated_at = datetime('now')
+             WHERE id = ?1",
+            params![id, title_encrypted, content_encrypted],
+        )
+        .context("Failed to update secure note")?;
+    Ok(updated > 0)
+}
+
+pub fn delete_secure_note(conn: &Connection, id: i64) -> Result<bool> {
+    let deleted = conn
+        .execute("DELETE FROM secure_notes WHERE id = ?1", params![id])
+        .context("Failed to delete secure note")?;
+    Ok(deleted > 0)
+}
@@ -0,0 +1,151 @@
+//! VIP sender database operations
+//!
+//! Mail from a VIP is announced under the VIP alert rules instead of the
+//! usual ones. A VIP belongs to one workspace, or to none and so to all.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VipSender {
+    pub id: i64,
+    pub workspace_id: Option<i64>,
+    /// Lowercase address, or "@domain" for everyone at a domain
+    pub address: String,
+    pub name: Option<String>,
+    pub added_at: String,
+}
+
+fn vip_from_row(row: &Row) -> rusqlite::Result<VipSender> {
+    Ok(VipSender {
+        id: row.get(0)?,
+        workspace_id: row.get(1)?,
+        address: row.get(2)?,
+        name: row.get(3)?,
+        added_at: row.get(4)?,
+    })
+}
+
+const VIP_COLUMNS: &str = "id, workspace_id, address, name, added_at";
+
+pub fn get_vip(conn: &Connection, id: i64) -> Result<Option<VipSender>> {
+    conn.query_row(
+        &format!("SELECT {} FROM vip_senders WHERE id = ?1", VIP_COLUMNS),
+        params![id],
+        vip_from_row,
+    )
+    .optional()
+    .context("Failed to load VIP sender")
+}
+
+/// Add a VIP; adding one already on the list updates its name
+pub fn add_vip(conn: &Connection, workspace_id: Option<i64>, address: &str, name: Option<&str>) -> Result<VipSender> {
+    conn.execute(
+        "INSERT INTO vip_senders (workspace_id, address, name) VALUES (?1, ?2, ?3)
+         ON CONFLICT(IFNULL(workspace_id, 0), address) DO UPDATE SET name = COALESCE(excluded.name, name)",
+        params![workspace_id, address, name],
+    )
+    .context("Failed to add VIP sender")?;
+    conn.query_row(
+        &format!("SELECT {} FROM vip_senders WHERE IFNULL(workspace_id, 0) = IFNULL(?1, 0) AND address = ?2", VIP_COLUMNS),
+        params![workspace_id, address],
+        vip_from_row,
+    )
+    .context("VIP sender vanished after insert")
+}
+
+/// Returns false if there was no such VIP
+pub fn remove_vip(conn: &Connection, id: i64) -> Result<bool> {
+    let removed = conn
+        .execute("DELETE FROM vip_senders WHERE id = ?1", params![id])
+        .context("Failed to remove VIP sender")?;
+    Ok(removed > 0)
+}
+
+/// The VIPs of one workspace, or with `None` those of every workspace
+pub fn list_vips(conn: &Connection, workspace_id: Option<i64>) -> Result<Vec<VipSender>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM vip_senders WHERE workspace_id IS ?1 ORDER BY address",
+        VIP_COLUMNS
+    ))?;
+    let vips = stmt
+        .query_map(params![workspace_id], vip_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list VIP senders")?;
+    Ok(vips)
+}
+
+/// Addresses that count as VIPs while `workspace_id` is active: its own and
+/// those of every workspace
+pub fn vip_addresses(conn: &Connection, workspace_id: Option<i64>) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT DISTINCT address FROM vip_senders WHERE workspace_id IS NULL OR workspace_id = ?1",
+    )?;
+    let addresses = stmt
+        .query_map(params![workspace_id], |row| row.get(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()
+        .context("Failed to load VIP addresses")?;
+    Ok(addresses)
+}
+
+/// JSON VIP alert rules a workspace overrides the global ones with
+pub fn get_workspace_rules(conn: &Connection, workspace_id: i64) -> Result<Option<String>> {
+    conn.query_row(
+        "SELECT rules_json FROM workspace_vip_rules WHERE workspace_id = ?1",
+        params![workspace_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .context("Failed to load workspace VIP rules")
+}
+
+/// Store a workspace's VIP alert rules, or drop them with `None` so the
+/// global rules apply again
+pub fn set_workspace_rules(conn: &Connection, workspace_id: i64, rules_json: Option<&str>) -> Result<()> {
+    match rules_json {
+        Some(json) => conn.execute(
+            "INSERT INTO workspace_vip_rules (workspace_id, rules_json) VALUES (?1, ?2)
+             ON CONFLICT(workspace_id) DO UPDATE SET rules_json = excluded.rules_json, updated_at = datetime('now')",
+            params![workspace_id, json],
+        ),
+        None => conn.execute("DELETE FROM workspace_vip_rules WHERE workspace_id = ?1", params![workspace_id]),
+    }
+    .context("Failed to save workspace VIP rules")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::operations::workspace_operations::create_workspace;
+    use crate::database::schema::run_migrations;
+
+    #[test]
+    fn test_vips_per_workspace() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        let work = create_workspace(&conn, "Work", None).unwrap();
+        let home = create_workspace(&conn, "Home", None).unwrap();
+
+        let boss = add_vip(&conn, Some(work.id), "boss@example.com", None).unwrap();
+        add_vip(&conn, None, "@family.org", Some("Family")).unwrap();
+        add_vip(&conn, Some(home.id), "friend@example.com", None).unwrap();
+        let renamed = add_vip(&conn, Some(work.id), "boss@example.com", Some("Boss")).unwrap();
+        assert_eq!((renamed.id, renamed.name.as_deref()), (boss.id, Some("Boss")));
+
+        let mut addresses = vip_addresses(&conn, Some(work.id)).unwrap();
+        addresses.sort();
+        assert_eq!(addresses, ["@family.org", "boss@example.com"]);
+        assert_eq!(vip_addresses(&conn, None).unwrap(), ["@family.org"]);
+        assert_eq!(list_vips(&conn, Some(home.id)).unwrap().len(), 1);
+
+        assert!(remove_vip(&conn, boss.id).unwrap());
+        assert!(!remove_vip(&conn, boss.id).unwrap());
+
+        set_workspace_rules(&conn, work.id, Some("{}")).unwrap();
+        assert_eq!(get_workspace_rules(&conn, work.id).unwrap().as_deref(), Some("{}"));
+        set_workspace_rules(&conn, work.id, None).unwrap();
+        assert!(get_workspace_rules(&conn, work.id).unwrap().is_none());
+    }
+}
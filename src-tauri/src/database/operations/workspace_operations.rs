@@ -0,0 +1,227 @@
+//! Workspaces
+//!
+//! A workspace groups Gmail accounts, Google task lists and calendars, and
+//! note folders. Membership is stored as (kind, member ID) pairs; an item
+//! may belong to several workspaces, and items in none are shared by all.
+
+use anyhow::{bail, Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkspaceKind {
+    Account,
+    TaskList,
+    Calendar,
+    Folder,
+}
+
+impl WorkspaceKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WorkspaceKind::Account => "account",
+            WorkspaceKind::TaskList => "task_list",
+            WorkspaceKind::Calendar => "calendar",
+            WorkspaceKind::Folder => "folder",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "account" => Some(WorkspaceKind::Account),
+            "task_list" => Some(WorkspaceKind::TaskList),
+            "calendar" => Some(WorkspaceKind::Calendar),
+            "folder" => Some(WorkspaceKind::Folder),
+            _ => None,
+        }
+    }
+}
+
+/// What a listing leaves out: accounts and containers (task lists,
+/// calendars or folders), each a JSON array of IDs read with `json_each`
+/// so the filter runs before any `LIMIT`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HiddenIds {
+    pub accounts: String,
+    pub containers: String,
+}
+
+impl Default for HiddenIds {
+    fn default() -> Self {
+        Self { accounts: "[]".to_string(), containers: "[]".to_string() }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct WorkspaceMember {
+    pub kind: WorkspaceKind,
+    pub member_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    pub id: i64,
+    pub name: String,
+    pub color: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub members: Vec<WorkspaceMember>,
+}
+
+fn workspace_from_row(row: &Row) -> rusqlite::Result<Workspace> {
+    Ok(Workspace {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        color: row.get(2)?,
+        created_at: row.get(3)?,
+        updated_at: row.get(4)?,
+        members: Vec::new(),
+    })
+}
+
+const WORKSPACE_COLUMNS: &str = "id, name, color, created_at, updated_at";
+
+fn load_members(conn: &Connection, workspace_id: i64) -> Result<Vec<WorkspaceMember>> {
+    let mut stmt = conn.prepare(
+        "SELECT kind, member_id FROM workspace_members WHERE workspace_id = ?1 ORDER BY kind, member_id",
+    )?;
+    let rows = stmt
+        .query_map(params![workspace_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to load workspace members")?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|(kind, member_id)| Some(WorkspaceMember { kind: WorkspaceKind::parse(&kind)?, member_id }))
+        .collect())
+}
+
+pub fn create_workspace(conn: &Connection, name: &str, color: Option<&str>) -> Result<Workspace> {
+    conn.execute(
+        "INSERT INTO workspaces (name, color) VALUES (?1, ?2)",
+        params![name, color],
+    ).with_context(|| format!("Failed to create workspace '{}'", name))?;
+
+    get_workspace(conn, conn.last_insert_rowid())?.context("Created workspace not found")
+}
+
+pub fn get_workspace(conn: &Connection, id: i64) -> Result<Option<Workspace>> {
+    let workspace = conn
+        .query_row(
+            &format!("SELECT {} FROM workspaces WHERE id = ?1", WORKSPACE_COLUMNS),
+            params![id],
+            workspace_from_row,
+        )
+        .optional()
+        .context("Failed to load workspace")?;
+    match workspace {
+        Some(mut workspace) => {
+            workspace.members = load_members(conn, id)?;
+            Ok(Some(workspace))
+        }
+        None => Ok(None),
+    }
+}
+
+pub fn list_workspaces(conn: &Connection) -> Result<Vec<Workspace>> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM workspaces ORDER BY name", WORKSPACE_COLUMNS))?;
+    let mut workspaces = stmt
+        .query_map([], workspace_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to list workspaces")?;
+    for workspace in &mut workspaces {
+        workspace.members = load_members(conn, workspace.id)?;
+    }
+    Ok(workspaces)
+}
+
+/// Rename or recolour a workspace; `None` keeps the current value
+pub fn update_workspace(conn: &Connection, id: i64, name: Option<&str>, color: Option<Option<&str>>) -> Result<bool> {
+    let updated = conn
+        .execute(
+            "UPDATE workspaces
+             SET name = COALESCE(?2, name),
+                 color = CASE WHEN ?3 THEN ?4 ELSE color END,
+                 updated_at = datetime('now')
+             WHERE id = ?1",
+            params![id, name, color.is_some(), color.flatten()],
+        )
+        .context("Failed to update workspace")?;
+    Ok(updated > 0)
+}
+
+pub fn delete_workspace(conn: &Connection, id: i64) -> Result<bool> {
+    let deleted = conn
+        .execute("DELETE FROM workspaces WHERE id = ?1", params![id])
+        .context("Failed to delete workspace")?;
+    Ok(deleted > 0)
+}
+
+/// Replace the members of a workspace
+pub fn set_members(conn: &mut Connection, id: i64, members: &[WorkspaceMember]) -> Result<()> {
+    let tx = conn.transaction().context("Failed to start workspace transaction")?;
+    let exists: bool = tx
+        .query_row("SELECT COUNT(*) FROM workspaces WHERE id = ?1", params![id], |row| row.get::<_, i64>(0))
+        .map(|count| count > 0)?;
+    if !exists {
+        bail!("Workspace {} not found", id);
+    }
+    tx.execute("DELETE FROM workspace_members WHERE workspace_id = ?1", params![id])
+        .context("Failed to clear workspace members")?;
+    for member in members {
+        tx.execute(
+            "INSERT OR IGNORE INTO workspace_members (workspace_id, kind, member_id) VALUES (?1, ?2, ?3)",
+            params![id, member.kind.as_str(), member.member_id],
+        ).context("Failed to add workspace member")?;
+    }
+    tx.execute("UPDATE workspaces SET updated_at = datetime('now') WHERE id = ?1", params![id])?;
+    tx.commit().context("Failed to save workspace members")?;
+    Ok(())
+}
+
+/// Items that belong to some other workspace but not to `workspace_id`,
+/// which are hidden while it is active
+pub fn hidden_members(conn: &Connection, workspace_id: i64) -> Result<Vec<WorkspaceMember>> {
+    let mut stmt = conn.prepare(
+        "SELECT DISTINCT m.kind, m.member_id FROM workspace_members m
+         WHERE m.workspace_id != ?1
+           AND NOT EXISTS (
+               SELECT 1 FROM workspace_members own
+               WHERE own.workspace_id = ?1 AND own.kind = m.kind AND own.member_id = m.member_id
+           )",
+    )?;
+    let rows = stmt
+        .query_map(params![workspace_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to load hidden workspace members")?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|(kind, member_id)| Some(WorkspaceMember { kind: WorkspaceKind::parse(&kind)?, member_id }))
+        .collect())
+}
+
+/// JSON availability profile a workspace overrides the global one with
+pub fn get_availability(conn: &Connection, workspace_id: i64) -> Result<Option<String>> {
+    conn.query_row(
+        "SELECT profile_json FROM workspace_availability WHERE workspace_id = ?1",
+        params![workspace_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .context("Failed to load workspace availability")
+}
+
+/// Store a workspace's availability profile, or drop it with `None` so the
+/// global profile applies again
+pub fn set_availability(conn: &Connection, workspace_id: i64, profile_json: Option<&str>) -> Result<()> {
+    match profile_json {
+        Some(json) => conn.execute(
+            "INSERT INTO workspace_availability (workspace_id, profile_json) VALUES (?1, ?2)
+             ON CONFLICT(workspace_id) DO UPDATE SET profile_json = excluded.profile_json, updated_at = datetime('now')",
+            params![workspace_id, json],
+        ),
+        None => conn.execute("DELETE FROM workspace_availability WHERE workspace_id = ?1", params![workspace_id]),
+    }
+    .context("Failed to save workspace availability")?;
+    Ok(())
+}
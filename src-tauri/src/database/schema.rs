@@ -104,6 +104,391 @@ pub fn run_migrations(conn: &Connection) -> Result<()> {
         println!("Migration v14 completed successfully");
     }
 
+    if current_version < 15 {
+        println!("Running migration v15 to add kanban boards for tasks...");
+        crate::database::schema_v15::run_migration_v15(conn)?;
+        record_migration(conn, 15)?;
+        println!("Migration v15 completed successfully");
+    }
+
+    if current_version < 16 {
+        println!("Running migration v16 to add task recurrence rules...");
+        crate::database::schema_v16::run_migration_v16(conn)?;
+        record_migration(conn, 16)?;
+        println!("Migration v16 completed successfully");
+    }
+
+    if current_version < 17 {
+        println!("Running migration v17 to link subtasks with Google child tasks...");
+        crate::database::schema_v17::run_migration_v17(conn)?;
+        record_migration(conn, 17)?;
+        println!("Migration v17 completed successfully");
+    }
+
+    if current_version < 18 {
+        println!("Running migration v18 to add pending changes and sync conflicts...");
+        crate::database::schema_v18::run_migration_v18(conn)?;
+        record_migration(conn, 18)?;
+        println!("Migration v18 completed successfully");
+    }
+
+    if current_version < 19 {
+        println!("Running migration v19 to add the change journal...");
+        crate::database::schema_v19::run_migration_v19(conn)?;
+        record_migration(conn, 19)?;
+        println!("Migration v19 completed successfully");
+    }
+
+    if current_version < 20 {
+        println!("Running migration v20 to add local Google Contacts tables...");
+        crate::database::schema_v20::run_migration_v20(conn)?;
+        record_migration(conn, 20)?;
+        println!("Migration v20 completed successfully");
+    }
+
+    if current_version < 21 {
+        println!("Running migration v21 to add the local Gmail draft cache...");
+        crate::database::schema_v21::run_migration_v21(conn)?;
+        record_migration(conn, 21)?;
+        println!("Migration v21 completed successfully");
+    }
+
+    if current_version < 22 {
+        println!("Running migration v22 to add mail merge campaigns...");
+        crate::database::schema_v22::run_migration_v22(conn)?;
+        record_migration(conn, 22)?;
+        println!("Migration v22 completed successfully");
+    }
+
+    if current_version < 23 {
+        println!("Running migration v23 to add sent mail receipt tracking...");
+        crate::database::schema_v23::run_migration_v23(conn)?;
+        record_migration(conn, 23)?;
+        println!("Migration v23 completed successfully");
+    }
+
+    if current_version < 24 {
+        println!("Running migration v24 to add muted threads...");
+        crate::database::schema_v24::run_migration_v24(conn)?;
+        record_migration(conn, 24)?;
+        println!("Migration v24 completed successfully");
+    }
+
+    if current_version < 25 {
+        println!("Running migration v25 to add per-label sync policies...");
+        crate::database::schema_v25::run_migration_v25(conn)?;
+        record_migration(conn, 25)?;
+        println!("Migration v25 completed successfully");
+    }
+
+    if current_version < 26 {
+        println!("Running migration v26 to add Gmail backfill jobs...");
+        crate::database::schema_v26::run_migration_v26(conn)?;
+        record_migration(conn, 26)?;
+        println!("Migration v26 completed successfully");
+    }
+
+    if current_version < 27 {
+        println!("Running migration v27 to add sync run history...");
+        crate::database::schema_v27::run_migration_v27(conn)?;
+        record_migration(conn, 27)?;
+        println!("Migration v27 completed successfully");
+    }
+
+    if current_version < 28 {
+        println!("Running migration v28 to add per-sender image loading preferences...");
+        crate::database::schema_v28::run_migration_v28(conn)?;
+        record_migration(conn, 28)?;
+        println!("Migration v28 completed successfully");
+    }
+
+    if current_version < 29 {
+        println!("Running migration v29 to add shipments detected in email...");
+        crate::database::schema_v29::run_migration_v29(conn)?;
+        record_migration(conn, 29)?;
+        println!("Migration v29 completed successfully");
+    }
+
+    if current_version < 30 {
+        println!("Running migration v30 to add versioned compose autosaves...");
+        crate::database::schema_v30::run_migration_v30(conn)?;
+        record_migration(conn, 30)?;
+        println!("Migration v30 completed successfully");
+    }
+
+    if current_version < 31 {
+        println!("Running migration v31 to add cached message translations...");
+        crate::database::schema_v31::run_migration_v31(conn)?;
+        record_migration(conn, 31)?;
+        println!("Migration v31 completed successfully");
+    }
+
+    if current_version < 32 {
+        println!("Running migration v32 to add audio attachment transcripts...");
+        crate::database::schema_v32::run_migration_v32(conn)?;
+        record_migration(conn, 32)?;
+        println!("Migration v32 completed successfully");
+    }
+
+    if current_version < 33 {
+        println!("Running migration v33 to add full-text indexes for global search...");
+        crate::database::schema_v33::run_migration_v33(conn)?;
+        record_migration(conn, 33)?;
+        println!("Migration v33 completed successfully");
+    }
+
+    if current_version < 34 {
+        println!("Running migration v34 to add first-run onboarding progress...");
+        crate::database::schema_v34::run_migration_v34(conn)?;
+        record_migration(conn, 34)?;
+        println!("Migration v34 completed successfully");
+    }
+
+    if current_version < 35 {
+        println!("Running migration v35 to add LLM providers...");
+        crate::database::schema_v35::run_migration_v35(conn)?;
+        record_migration(conn, 35)?;
+        println!("Migration v35 completed successfully");
+    }
+
+    if current_version < 36 {
+        println!("Running migration v36 to version prompt templates...");
+        crate::database::schema_v36::run_migration_v36(conn)?;
+        record_migration(conn, 36)?;
+        println!("Migration v36 completed successfully");
+    }
+
+    if current_version < 37 {
+        println!("Running migration v37 to add chat attachments...");
+        crate::database::schema_v37::run_migration_v37(conn)?;
+        record_migration(conn, 37)?;
+        println!("Migration v37 completed successfully");
+    }
+
+    if current_version < 38 {
+        println!("Running migration v38 to add the model registry and routing rules...");
+        crate::database::schema_v38::run_migration_v38(conn)?;
+        record_migration(conn, 38)?;
+        println!("Migration v38 completed successfully");
+    }
+
+    if current_version < 39 {
+        println!("Running migration v39 to add canvas AI generation history...");
+        crate::database::schema_v39::run_migration_v39(conn)?;
+        record_migration(conn, 39)?;
+        println!("Migration v39 completed successfully");
+    }
+
+    if current_version < 40 {
+        println!("Running migration v40 to add clipboard captures...");
+        crate::database::schema_v40::run_migration_v40(conn)?;
+        record_migration(conn, 40)?;
+        println!("Migration v40 completed successfully");
+    }
+
+    if current_version < 41 {
+        println!("Running migration v41 to add the link preview cache...");
+        crate::database::schema_v41::run_migration_v41(conn)?;
+        record_migration(conn, 41)?;
+        println!("Migration v41 completed successfully");
+    }
+
+    if current_version < 42 {
+        println!("Running migration v42 to record where clipped notes came from...");
+        crate::database::schema_v42::run_migration_v42(conn)?;
+        record_migration(conn, 42)?;
+        println!("Migration v42 completed successfully");
+    }
+
+    if current_version < 43 {
+        println!("Running migration v43 to add feed subscriptions...");
+        crate::database::schema_v43::run_migration_v43(conn)?;
+        record_migration(conn, 43)?;
+        println!("Migration v43 completed successfully");
+    }
+
+    if current_version < 44 {
+        println!("Running migration v44 to keep task and event status in the local search copy...");
+        crate::database::schema_v44::run_migration_v44(conn)?;
+        record_migration(conn, 44)?;
+        println!("Migration v44 completed successfully");
+    }
+
+    if current_version < 45 {
+        println!("Running migration v45 to add workspaces grouping accounts, task lists, calendars and folders...");
+        crate::database::schema_v45::run_migration_v45(conn)?;
+        record_migration(conn, 45)?;
+        println!("Migration v45 completed successfully");
+    }
+
+    if current_version < 46 {
+        println!("Running migration v46 to add archive accounts for imported mail...");
+        crate::database::schema_v46::run_migration_v46(conn)?;
+        record_migration(conn, 46)?;
+        println!("Migration v46 completed successfully");
+    }
+
+    if current_version < 47 {
+        println!("Running migration v47 to add the secure notes vault...");
+        crate::database::schema_v47::run_migration_v47(conn)?;
+        record_migration(conn, 47)?;
+        println!("Migration v47 completed successfully");
+    }
+
+    if current_version < 48 {
+        println!("Running migration v48 to add calendars imported from ICS files and webcal subscriptions...");
+        crate::database::schema_v48::run_migration_v48(conn)?;
+        record_migration(conn, 48)?;
+        println!("Migration v48 completed successfully");
+    }
+
+    if current_version < 49 {
+        println!("Running migration v49 to store task time blocks and imported event times in UTC...");
+        crate::database::schema_v49::run_migration_v49(conn)?;
+        record_migration(conn, 49)?;
+        println!("Migration v49 completed successfully");
+    }
+
+    if current_version < 50 {
+        println!("Running migration v50 to cache Gmail label metadata and keep local label overrides...");
+        crate::database::schema_v50::run_migration_v50(conn)?;
+        record_migration(conn, 50)?;
+        println!("Migration v50 completed successfully");
+    }
+
+    if current_version < 51 {
+        println!("Running migration v51 to add saved Gmail searches and smart folders...");
+        crate::database::schema_v51::run_migration_v51(conn)?;
+        record_migration(conn, 51)?;
+        println!("Migration v51 completed successfully");
+    }
+
+    if current_version < 52 {
+        println!("Running migration v52 to index the message store for windowed header queries...");
+        crate::database::schema_v52::run_migration_v52(conn)?;
+        record_migration(conn, 52)?;
+        println!("Migration v52 completed successfully");
+    }
+
+    if current_version < 53 {
+        println!("Running migration v53 to add the cache delta log...");
+        crate::database::schema_v53::run_migration_v53(conn)?;
+        record_migration(conn, 53)?;
+        println!("Migration v53 completed successfully");
+    }
+
+    if current_version < 54 {
+        println!("Running migration v54 to persist Google API request usage for the scheduler...");
+        crate::database::schema_v54::run_migration_v54(conn)?;
+        record_migration(conn, 54)?;
+        println!("Migration v54 completed successfully");
+    }
+
+    if current_version < 55 {
+        println!("Running migration v55 to record attachment scan verdicts...");
+        crate::database::schema_v55::run_migration_v55(conn)?;
+        record_migration(conn, 55)?;
+        println!("Migration v55 completed successfully");
+    }
+
+    if current_version < 56 {
+        println!("Running migration v56 to store Gmail send-as addresses...");
+        crate::database::schema_v56::run_migration_v56(conn)?;
+        record_migration(conn, 56)?;
+        println!("Migration v56 completed successfully");
+    }
+
+    if current_version < 57 {
+        println!("Running migration v57 to add note templates and daily notes...");
+        crate::database::schema_v57::run_migration_v57(conn)?;
+        record_migration(conn, 57)?;
+        println!("Migration v57 completed successfully");
+    }
+
+    if current_version < 58 {
+        println!("Running migration v58 to add local task comments...");
+        crate::database::schema_v58::run_migration_v58(conn)?;
+        record_migration(conn, 58)?;
+        println!("Migration v58 completed successfully");
+    }
+
+    if current_version < 59 {
+        println!("Running migration v59 to add per-workspace availability profiles...");
+        crate::database::schema_v59::run_migration_v59(conn)?;
+        record_migration(conn, 59)?;
+        println!("Migration v59 completed successfully");
+    }
+
+    if current_version < 60 {
+        println!("Running migration v60 to add mail triage sessions and snoozed messages...");
+        crate::database::schema_v60::run_migration_v60(conn)?;
+        record_migration(conn, 60)?;
+        println!("Migration v60 completed successfully");
+    }
+
+    if current_version < 61 {
+        println!("Running migration v61 to add VIP senders and per-workspace VIP alert rules...");
+        crate::database::schema_v61::run_migration_v61(conn)?;
+        record_migration(conn, 61)?;
+        println!("Migration v61 completed successfully");
+    }
+
+    if current_version < 62 {
+        println!("Running migration v62 to add retry backoff to snoozed messages...");
+        crate::database::schema_v62::run_migration_v62(conn)?;
+        record_migration(conn, 62)?;
+        println!("Migration v62 completed successfully");
+    }
+
+    if current_version < 63 {
+        println!("Running migration v63 to add Gmail sync state and sync config tables...");
+        crate::database::schema_v63::run_migration_v63(conn)?;
+        record_migration(conn, 63)?;
+        println!("Migration v63 completed successfully");
+    }
+
+    if current_version < 64 {
+        println!("Running migration v64 to add task import records...");
+        crate::database::schema_v64::run_migration_v64(conn)?;
+        record_migration(conn, 64)?;
+        println!("Migration v64 completed successfully");
+    }
+
+    if current_version < 65 {
+        println!("Running migration v65 to keep when synced tasks were completed...");
+        crate::database::schema_v65::run_migration_v65(conn)?;
+        record_migration(conn, 65)?;
+        println!("Migration v65 completed successfully");
+    }
+
+    if current_version < 66 {
+        println!("Running migration v66 to queue task activity mirrors...");
+        crate::database::schema_v66::run_migration_v66(conn)?;
+        record_migration(conn, 66)?;
+        println!("Migration v66 completed successfully");
+    }
+
+    if current_version < 67 {
+        println!("Running migration v67 to keep failed backfill messages for retry...");
+        crate::database::schema_v67::run_migration_v67(conn)?;
+        record_migration(conn, 67)?;
+        println!("Migration v67 completed successfully");
+    }
+
+    if current_version < 68 {
+        println!("Running migration v68 to mark all-day events in the search index...");
+        crate::database::schema_v68::run_migration_v68(conn)?;
+        record_migration(conn, 68)?;
+        println!("Migration v68 completed successfully");
+    }
+
+    if current_version < 69 {
+        println!("Running migration v69 to keep mail list headers in their own table...");
+        crate::database::schema_v69::run_migration_v69(conn)?;
+        record_migration(conn, 69)?;
+        println!("Migration v69 completed successfully");
+    }
+
     Ok(())
 }
 
@@ -58,7 +58,7 @@ pub fn run_migration_v14(conn: &rusqlite::Connection) -> anyhow::Result<()> {
                 .map(|label| {
                     let parts: Vec<&str> = label.split(':').collect();
                     serde_json::json!({
-                        "name": parts.get(0).unwrap_or(&""),
+                        "name": parts.first().unwrap_or(&""),
                         "color": parts.get(1).unwrap_or(&"blue")
                     })
                 })
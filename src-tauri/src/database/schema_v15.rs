@@ -0,0 +1,63 @@
+/// Run migration v15 - Add kanban boards with custom columns for tasks
+pub fn run_migration_v15(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // Boards are independent of Google task lists so a board layout survives sync
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS task_boards (
+            id TEXT PRIMARY KEY,
+            account_id TEXT,
+            name TEXT NOT NULL,
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    ).context("Failed to create task_boards table")?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS board_columns (
+            id TEXT PRIMARY KEY,
+            board_id TEXT NOT NULL,
+            title TEXT NOT NULL,
+            color TEXT,
+            position INTEGER NOT NULL DEFAULT 0,
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (board_id) REFERENCES task_boards(id) ON DELETE CASCADE
+        )",
+        [],
+    ).context("Failed to create board_columns table")?;
+
+    // A task appears at most once per board, keyed by its Google task ID
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS board_cards (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            board_id TEXT NOT NULL,
+            column_id TEXT NOT NULL,
+            google_task_id TEXT NOT NULL,
+            position INTEGER NOT NULL DEFAULT 0,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (board_id) REFERENCES task_boards(id) ON DELETE CASCADE,
+            FOREIGN KEY (column_id) REFERENCES board_columns(id) ON DELETE CASCADE,
+            UNIQUE(board_id, google_task_id)
+        )",
+        [],
+    ).context("Failed to create board_cards table")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_board_columns_board ON board_columns(board_id, position)",
+        [],
+    ).context("Failed to create idx_board_columns_board")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_board_cards_column ON board_cards(column_id, position)",
+        [],
+    ).context("Failed to create idx_board_cards_column")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_board_cards_task ON board_cards(google_task_id)",
+        [],
+    ).context("Failed to create idx_board_cards_task")?;
+
+    Ok(())
+}
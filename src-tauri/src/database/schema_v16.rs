@@ -0,0 +1,32 @@
+/// Run migration v16 - Add local recurrence rules to task metadata
+pub fn run_migration_v16(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // Google Tasks has no recurrence support, so the rule lives locally
+    let has_recurrence_rule: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('task_metadata') WHERE name='recurrence_rule'",
+            [],
+            |row| {
+                let count: i32 = row.get(0)?;
+                Ok(count > 0)
+            },
+        )
+        .unwrap_or(false);
+
+    if !has_recurrence_rule {
+        conn.execute(
+            "ALTER TABLE task_metadata ADD COLUMN recurrence_rule TEXT",
+            [],
+        ).context("Failed to add recurrence_rule column to task_metadata")?;
+        println!("Added recurrence_rule column to task_metadata table");
+    }
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_task_metadata_recurrence ON task_metadata(recurrence_rule)
+         WHERE recurrence_rule IS NOT NULL",
+        [],
+    ).context("Failed to create idx_task_metadata_recurrence")?;
+
+    Ok(())
+}
@@ -0,0 +1,31 @@
+/// Run migration v17 - Link local subtasks to Google child tasks
+pub fn run_migration_v17(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    let has_google_task_id: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('subtasks') WHERE name='google_task_id'",
+            [],
+            |row| {
+                let count: i32 = row.get(0)?;
+                Ok(count > 0)
+            },
+        )
+        .unwrap_or(false);
+
+    if !has_google_task_id {
+        conn.execute(
+            "ALTER TABLE subtasks ADD COLUMN google_task_id TEXT",
+            [],
+        ).context("Failed to add google_task_id column to subtasks")?;
+        println!("Added google_task_id column to subtasks table");
+    }
+
+    conn.execute(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_subtasks_google_task_id ON subtasks(google_task_id)
+         WHERE google_task_id IS NOT NULL",
+        [],
+    ).context("Failed to create idx_subtasks_google_task_id")?;
+
+    Ok(())
+}
@@ -0,0 +1,62 @@
+/// Run migration v18 - Add pending changes journal and sync conflicts
+pub fn run_migration_v18(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // Local edits waiting to be pushed, with the server state they were based on
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS pending_changes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            account_id TEXT NOT NULL,
+            entity_type TEXT NOT NULL,
+            entity_id TEXT NOT NULL,
+            container_id TEXT,
+            operation TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            base_payload TEXT,
+            base_etag TEXT,
+            base_updated TEXT,
+            status TEXT NOT NULL DEFAULT 'pending',
+            attempts INTEGER NOT NULL DEFAULT 0,
+            last_error TEXT,
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    ).context("Failed to create pending_changes table")?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sync_conflicts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            pending_change_id INTEGER NOT NULL,
+            account_id TEXT NOT NULL,
+            entity_type TEXT NOT NULL,
+            entity_id TEXT NOT NULL,
+            conflicting_fields TEXT NOT NULL,
+            local_payload TEXT NOT NULL,
+            remote_payload TEXT NOT NULL,
+            base_payload TEXT,
+            resolution TEXT,
+            detected_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            resolved_at DATETIME,
+            FOREIGN KEY (pending_change_id) REFERENCES pending_changes(id) ON DELETE CASCADE
+        )",
+        [],
+    ).context("Failed to create sync_conflicts table")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_pending_changes_status ON pending_changes(account_id, status, created_at)",
+        [],
+    ).context("Failed to create idx_pending_changes_status")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_pending_changes_entity ON pending_changes(entity_type, entity_id)",
+        [],
+    ).context("Failed to create idx_pending_changes_entity")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_sync_conflicts_unresolved ON sync_conflicts(account_id, resolved_at)",
+        [],
+    ).context("Failed to create idx_sync_conflicts_unresolved")?;
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+/// Run migration v19 - Add the change journal for local mutations
+pub fn run_migration_v19(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // Append-only log of local mutations; payload holds the new field values
+    // and previous_payload the values they replaced (used for undo)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS change_journal (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            entity_type TEXT NOT NULL,
+            entity_id TEXT NOT NULL,
+            operation TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            previous_payload TEXT,
+            account_id TEXT,
+            undone INTEGER NOT NULL DEFAULT 0,
+            synced_at DATETIME,
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    ).context("Failed to create change_journal table")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_change_journal_entity ON change_journal(entity_type, entity_id, id)",
+        [],
+    ).context("Failed to create idx_change_journal_entity")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_change_journal_unsynced ON change_journal(synced_at)
+         WHERE synced_at IS NULL",
+        [],
+    ).context("Failed to create idx_change_journal_unsynced")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_change_journal_created ON change_journal(created_at)",
+        [],
+    ).context("Failed to create idx_change_journal_created")?;
+
+    Ok(())
+}
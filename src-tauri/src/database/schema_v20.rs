@@ -0,0 +1,59 @@
+/// Run migration v20 - Add local Google Contacts tables
+pub fn run_migration_v20(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // One row per People API person, keyed by its resource name (people/c123)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS contacts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            account_id TEXT NOT NULL,
+            resource_name TEXT NOT NULL,
+            etag TEXT,
+            display_name TEXT,
+            given_name TEXT,
+            family_name TEXT,
+            photo_url TEXT,
+            organization TEXT,
+            job_title TEXT,
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE(account_id, resource_name)
+        )",
+        [],
+    ).context("Failed to create contacts table")?;
+
+    // Email addresses are stored separately so lookups by address are indexed
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS contact_emails (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            contact_id INTEGER NOT NULL,
+            email TEXT NOT NULL COLLATE NOCASE,
+            label TEXT,
+            is_primary BOOLEAN NOT NULL DEFAULT 0,
+            FOREIGN KEY (contact_id) REFERENCES contacts(id) ON DELETE CASCADE
+        )",
+        [],
+    ).context("Failed to create contact_emails table")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_contact_emails_email ON contact_emails(email)",
+        [],
+    ).context("Failed to create idx_contact_emails_email")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_contacts_display_name ON contacts(account_id, display_name)",
+        [],
+    ).context("Failed to create idx_contacts_display_name")?;
+
+    // Incremental People API sync token per account
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS contact_sync_state (
+            account_id TEXT PRIMARY KEY,
+            sync_token TEXT,
+            last_synced_at DATETIME
+        )",
+        [],
+    ).context("Failed to create contact_sync_state table")?;
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+/// Run migration v21 - Add the local Gmail draft cache
+pub fn run_migration_v21(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // Drafts are keyed by a local ID so drafts written offline exist before
+    // Gmail assigns them a draft_id. sync_status tracks what still has to be
+    // pushed: synced, pending_create, pending_update or pending_delete.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS gmail_drafts (
+            local_id TEXT PRIMARY KEY,
+            account_id TEXT NOT NULL,
+            draft_id TEXT,
+            message_id TEXT,
+            thread_id TEXT,
+            compose_data TEXT NOT NULL,
+            sync_status TEXT NOT NULL DEFAULT 'pending_create',
+            last_error TEXT,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            synced_at DATETIME
+        )",
+        [],
+    ).context("Failed to create gmail_drafts table")?;
+
+    conn.execute(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_gmail_drafts_remote
+         ON gmail_drafts(account_id, draft_id) WHERE draft_id IS NOT NULL",
+        [],
+    ).context("Failed to create idx_gmail_drafts_remote")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_gmail_drafts_status ON gmail_drafts(account_id, sync_status)",
+        [],
+    ).context("Failed to create idx_gmail_drafts_status")?;
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+/// Run migration v22 - Add mail merge campaigns
+pub fn run_migration_v22(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // status: draft, running, paused, cancelled, completed
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS mail_campaigns (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            account_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            subject_template TEXT NOT NULL,
+            body_template TEXT NOT NULL,
+            is_html BOOLEAN NOT NULL DEFAULT 0,
+            send_interval_ms INTEGER NOT NULL DEFAULT 5000,
+            status TEXT NOT NULL DEFAULT 'draft',
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            started_at DATETIME,
+            completed_at DATETIME
+        )",
+        [],
+    ).context("Failed to create mail_campaigns table")?;
+
+    // status: pending, sent, failed
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS campaign_recipients (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            campaign_id INTEGER NOT NULL,
+            email TEXT NOT NULL,
+            name TEXT,
+            variables TEXT NOT NULL DEFAULT '{}',
+            status TEXT NOT NULL DEFAULT 'pending',
+            message_id TEXT,
+            error TEXT,
+            sent_at DATETIME,
+            FOREIGN KEY (campaign_id) REFERENCES mail_campaigns(id) ON DELETE CASCADE,
+            UNIQUE(campaign_id, email)
+        )",
+        [],
+    ).context("Failed to create campaign_recipients table")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_campaign_recipients_status ON campaign_recipients(campaign_id, status)",
+        [],
+    ).context("Failed to create idx_campaign_recipients_status")?;
+
+    Ok(())
+}
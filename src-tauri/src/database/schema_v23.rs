@@ -0,0 +1,49 @@
+/// Run migration v23 - Add read receipt and open tracking for sent mail
+pub fn run_migration_v23(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // One row per sent message that asked for a receipt or carries a pixel.
+    // rfc822_message_id is the Message-ID header that MDN/DSN reports quote back.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sent_message_tracking (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            account_id TEXT NOT NULL,
+            message_id TEXT NOT NULL,
+            thread_id TEXT,
+            rfc822_message_id TEXT NOT NULL UNIQUE,
+            subject TEXT NOT NULL DEFAULT '',
+            recipients TEXT NOT NULL DEFAULT '[]',
+            read_receipt_requested BOOLEAN NOT NULL DEFAULT 0,
+            delivery_receipt_requested BOOLEAN NOT NULL DEFAULT 0,
+            tracking_token TEXT UNIQUE,
+            delivery_status TEXT,
+            delivered_at DATETIME,
+            read_at DATETIME,
+            read_by TEXT,
+            open_count INTEGER NOT NULL DEFAULT 0,
+            first_opened_at DATETIME,
+            last_opened_at DATETIME,
+            sent_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    ).context("Failed to create sent_message_tracking table")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_sent_message_tracking_message ON sent_message_tracking(account_id, message_id)",
+        [],
+    ).context("Failed to create idx_sent_message_tracking_message")?;
+
+    // Pixel mode is opt-in per account and points at a server the user runs
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS tracking_settings (
+            account_id TEXT PRIMARY KEY,
+            pixel_enabled BOOLEAN NOT NULL DEFAULT 0,
+            pixel_base_url TEXT,
+            last_receipt_scan_at DATETIME,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    ).context("Failed to create tracking_settings table")?;
+
+    Ok(())
+}
@@ -0,0 +1,18 @@
+/// Run migration v24 - Add muted Gmail threads
+pub fn run_migration_v24(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS muted_threads (
+            account_id TEXT NOT NULL,
+            thread_id TEXT NOT NULL,
+            subject TEXT,
+            archived_count INTEGER NOT NULL DEFAULT 0,
+            muted_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (account_id, thread_id)
+        )",
+        [],
+    ).context("Failed to create muted_threads table")?;
+
+    Ok(())
+}
@@ -0,0 +1,21 @@
+/// Run migration v25 - Add per-label Gmail sync policies
+pub fn run_migration_v25(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // content: full (headers and bodies) or headers (metadata only).
+    // max_age_days NULL means no age limit.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS label_sync_policies (
+            account_id TEXT NOT NULL,
+            label_id TEXT NOT NULL,
+            enabled BOOLEAN NOT NULL DEFAULT 1,
+            max_age_days INTEGER,
+            content TEXT NOT NULL DEFAULT 'full',
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (account_id, label_id)
+        )",
+        [],
+    ).context("Failed to create label_sync_policies table")?;
+
+    Ok(())
+}
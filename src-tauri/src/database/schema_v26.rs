@@ -0,0 +1,53 @@
+/// Run migration v26 - Add resumable Gmail backfill jobs and the local message store
+pub fn run_migration_v26(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // One job per account. The checkpoint is the current date window plus the
+    // Gmail page token within it; range_* and window_start are epoch seconds.
+    // status: running, paused, completed, failed
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS gmail_backfill_jobs (
+            account_id TEXT PRIMARY KEY,
+            status TEXT NOT NULL DEFAULT 'running',
+            range_start INTEGER NOT NULL,
+            range_end INTEGER NOT NULL,
+            window_start INTEGER NOT NULL,
+            page_token TEXT,
+            messages_stored INTEGER NOT NULL DEFAULT 0,
+            messages_skipped INTEGER NOT NULL DEFAULT 0,
+            error TEXT,
+            started_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            completed_at DATETIME
+        )",
+        [],
+    ).context("Failed to create gmail_backfill_jobs table")?;
+
+    // content: full or headers, per the label sync policy at fetch time
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS gmail_message_store (
+            account_id TEXT NOT NULL,
+            message_id TEXT NOT NULL,
+            thread_id TEXT NOT NULL,
+            label_ids TEXT NOT NULL DEFAULT '[]',
+            internal_date INTEGER,
+            content TEXT NOT NULL DEFAULT 'full',
+            message_data TEXT NOT NULL,
+            stored_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (account_id, message_id)
+        )",
+        [],
+    ).context("Failed to create gmail_message_store table")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_gmail_message_store_thread ON gmail_message_store(account_id, thread_id)",
+        [],
+    ).context("Failed to create idx_gmail_message_store_thread")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_gmail_message_store_date ON gmail_message_store(account_id, internal_date)",
+        [],
+    ).context("Failed to create idx_gmail_message_store_date")?;
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+/// Run migration v27 - Add sync run history for the sync status dashboard
+pub fn run_migration_v27(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // sync_type: full or incremental. error_category comes from
+    // LibreOllamaError::category() where the error is typed.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sync_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            account_id TEXT NOT NULL,
+            domain TEXT NOT NULL,
+            sync_type TEXT NOT NULL DEFAULT 'incremental',
+            success BOOLEAN NOT NULL,
+            items_changed INTEGER NOT NULL DEFAULT 0,
+            error TEXT,
+            error_category TEXT,
+            finished_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    ).context("Failed to create sync_runs table")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_sync_runs_account ON sync_runs(account_id, finished_at)",
+        [],
+    ).context("Failed to create idx_sync_runs_account")?;
+
+    Ok(())
+}
@@ -0,0 +1,19 @@
+/// Run migration v28 - Add per-sender "always load images" preferences
+pub fn run_migration_v28(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // sender is a lowercased email address, or a bare domain to cover
+    // everyone sending from it
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS image_load_preferences (
+            account_id TEXT NOT NULL,
+            sender TEXT NOT NULL,
+            always_load BOOLEAN NOT NULL DEFAULT 1,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (account_id, sender)
+        )",
+        [],
+    ).context("Failed to create image_load_preferences table")?;
+
+    Ok(())
+}
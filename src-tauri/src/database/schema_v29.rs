@@ -0,0 +1,35 @@
+/// Run migration v29 - Add shipments detected in email for package tracking
+pub fn run_migration_v29(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // status: unknown, label_created, in_transit, out_for_delivery,
+    // delivered or exception. next_check_at is NULL once polling stops.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS shipments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            account_id TEXT NOT NULL,
+            carrier TEXT NOT NULL,
+            tracking_number TEXT NOT NULL,
+            tracking_url TEXT NOT NULL,
+            message_id TEXT,
+            subject TEXT,
+            status TEXT NOT NULL DEFAULT 'unknown',
+            status_detail TEXT,
+            check_failures INTEGER NOT NULL DEFAULT 0,
+            last_checked_at INTEGER,
+            next_check_at INTEGER,
+            dismissed BOOLEAN NOT NULL DEFAULT 0,
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE(account_id, carrier, tracking_number)
+        )",
+        [],
+    ).context("Failed to create shipments table")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_shipments_next_check ON shipments(next_check_at)",
+        [],
+    ).context("Failed to create idx_shipments_next_check")?;
+
+    Ok(())
+}
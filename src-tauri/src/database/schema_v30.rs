@@ -0,0 +1,27 @@
+/// Run migration v30 - Add versioned compose autosaves for crash recovery
+pub fn run_migration_v30(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // One row per saved version of a compose window, identified by the
+    // client-generated autosave_key. Rows are deleted once the message is
+    // sent or discarded, so whatever remains was never sent.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS draft_autosaves (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            account_id TEXT NOT NULL,
+            autosave_key TEXT NOT NULL,
+            version INTEGER NOT NULL,
+            compose_data TEXT NOT NULL,
+            saved_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE(autosave_key, version)
+        )",
+        [],
+    ).context("Failed to create draft_autosaves table")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_draft_autosaves_account ON draft_autosaves(account_id, autosave_key)",
+        [],
+    ).context("Failed to create idx_draft_autosaves_account")?;
+
+    Ok(())
+}
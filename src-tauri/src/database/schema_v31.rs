@@ -0,0 +1,21 @@
+/// Run migration v31 - Add cached message translations
+pub fn run_migration_v31(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS message_translations (
+            account_id TEXT NOT NULL,
+            message_id TEXT NOT NULL,
+            target_lang TEXT NOT NULL,
+            source_lang TEXT,
+            source_lang_name TEXT,
+            translated_text TEXT NOT NULL,
+            model TEXT NOT NULL,
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (account_id, message_id, target_lang)
+        )",
+        [],
+    ).context("Failed to create message_translations table")?;
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+/// Run migration v32 - Add audio attachment transcripts with a full-text index
+pub fn run_migration_v32(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // source_key identifies what was transcribed: "gmail:<account>:<message>:<attachment>"
+    // for mail attachments or "file:<path>" for local audio such as note recordings
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS attachment_transcripts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            source_key TEXT NOT NULL UNIQUE,
+            account_id TEXT,
+            message_id TEXT,
+            attachment_id TEXT,
+            note_id INTEGER,
+            filename TEXT NOT NULL,
+            mime_type TEXT NOT NULL,
+            transcript TEXT NOT NULL,
+            engine TEXT NOT NULL,
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    ).context("Failed to create attachment_transcripts table")?;
+
+    conn.execute(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS attachment_transcripts_fts USING fts5(
+            transcript, filename,
+            content='attachment_transcripts', content_rowid='id'
+        )",
+        [],
+    ).context("Failed to create attachment_transcripts_fts index")?;
+
+    // Keep the external-content index in step with the table
+    conn.execute_batch(
+        "CREATE TRIGGER IF NOT EXISTS attachment_transcripts_ai AFTER INSERT ON attachment_transcripts BEGIN
+            INSERT INTO attachment_transcripts_fts(rowid, transcript, filename)
+            VALUES (new.id, new.transcript, new.filename);
+         END;
+         CREATE TRIGGER IF NOT EXISTS attachment_transcripts_ad AFTER DELETE ON attachment_transcripts BEGIN
+            INSERT INTO attachment_transcripts_fts(attachment_transcripts_fts, rowid, transcript, filename)
+            VALUES ('delete', old.id, old.transcript, old.filename);
+         END;
+         CREATE TRIGGER IF NOT EXISTS attachment_transcripts_au AFTER UPDATE ON attachment_transcripts BEGIN
+            INSERT INTO attachment_transcripts_fts(attachment_transcripts_fts, rowid, transcript, filename)
+            VALUES ('delete', old.id, old.transcript, old.filename);
+            INSERT INTO attachment_transcripts_fts(rowid, transcript, filename)
+            VALUES (new.id, new.transcript, new.filename);
+         END;",
+    ).context("Failed to create attachment_transcripts_fts triggers")?;
+
+    Ok(())
+}
@@ -0,0 +1,145 @@
+/// Run migration v33 - Add full-text indexes for global search
+pub fn run_migration_v33(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // Stored mail: the message is JSON, so the indexed text is pulled out by
+    // the triggers. Rows are keyed by the store's rowid. INSERT OR REPLACE
+    // does not fire delete triggers, so the old entry is dropped beforehand.
+    conn.execute(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS gmail_message_fts USING fts5(subject, sender, body)",
+        [],
+    ).context("Failed to create gmail_message_fts index")?;
+
+    conn.execute_batch(
+        "CREATE TRIGGER IF NOT EXISTS gmail_message_store_bi BEFORE INSERT ON gmail_message_store BEGIN
+            DELETE FROM gmail_message_fts WHERE rowid = (
+                SELECT rowid FROM gmail_message_store WHERE account_id = new.account_id AND message_id = new.message_id
+            );
+         END;
+         CREATE TRIGGER IF NOT EXISTS gmail_message_store_ai AFTER INSERT ON gmail_message_store BEGIN
+            INSERT INTO gmail_message_fts(rowid, subject, sender, body) VALUES (
+                new.rowid,
+                json_extract(new.message_data, '$.parsed_content.subject'),
+                trim(coalesce(json_extract(new.message_data, '$.parsed_content.from.name'), '') || ' ' ||
+                     coalesce(json_extract(new.message_data, '$.parsed_content.from.email'), '')),
+                coalesce(json_extract(new.message_data, '$.parsed_content.body_text'), json_extract(new.message_data, '$.snippet'))
+            );
+         END;
+         CREATE TRIGGER IF NOT EXISTS gmail_message_store_ad AFTER DELETE ON gmail_message_store BEGIN
+            DELETE FROM gmail_message_fts WHERE rowid = old.rowid;
+         END;
+         CREATE TRIGGER IF NOT EXISTS gmail_message_store_au AFTER UPDATE ON gmail_message_store BEGIN
+            DELETE FROM gmail_message_fts WHERE rowid = old.rowid;
+            INSERT INTO gmail_message_fts(rowid, subject, sender, body) VALUES (
+                new.rowid,
+                json_extract(new.message_data, '$.parsed_content.subject'),
+                trim(coalesce(json_extract(new.message_data, '$.parsed_content.from.name'), '') || ' ' ||
+                     coalesce(json_extract(new.message_data, '$.parsed_content.from.email'), '')),
+                coalesce(json_extract(new.message_data, '$.parsed_content.body_text'), json_extract(new.message_data, '$.snippet'))
+            );
+         END;",
+    ).context("Failed to create gmail_message_fts triggers")?;
+
+    conn.execute(
+        "INSERT INTO gmail_message_fts(rowid, subject, sender, body)
+         SELECT rowid,
+                json_extract(message_data, '$.parsed_content.subject'),
+                trim(coalesce(json_extract(message_data, '$.parsed_content.from.name'), '') || ' ' ||
+                     coalesce(json_extract(message_data, '$.parsed_content.from.email'), '')),
+                coalesce(json_extract(message_data, '$.parsed_content.body_text'), json_extract(message_data, '$.snippet'))
+         FROM gmail_message_store
+         WHERE rowid NOT IN (SELECT rowid FROM gmail_message_fts)",
+        [],
+    ).context("Failed to index stored messages")?;
+
+    // Notes and chat messages: external-content indexes over the tables
+    conn.execute(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS notes_fts USING fts5(
+            title, content,
+            content='notes', content_rowid='id'
+        )",
+        [],
+    ).context("Failed to create notes_fts index")?;
+
+    conn.execute_batch(
+        "CREATE TRIGGER IF NOT EXISTS notes_fts_ai AFTER INSERT ON notes BEGIN
+            INSERT INTO notes_fts(rowid, title, content) VALUES (new.id, new.title, new.content);
+         END;
+         CREATE TRIGGER IF NOT EXISTS notes_fts_ad AFTER DELETE ON notes BEGIN
+            INSERT INTO notes_fts(notes_fts, rowid, title, content) VALUES ('delete', old.id, old.title, old.content);
+         END;
+         CREATE TRIGGER IF NOT EXISTS notes_fts_au AFTER UPDATE ON notes BEGIN
+            INSERT INTO notes_fts(notes_fts, rowid, title, content) VALUES ('delete', old.id, old.title, old.content);
+            INSERT INTO notes_fts(rowid, title, content) VALUES (new.id, new.title, new.content);
+         END;
+         INSERT INTO notes_fts(notes_fts) VALUES ('rebuild');",
+    ).context("Failed to create notes_fts triggers")?;
+
+    conn.execute(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS chat_messages_fts USING fts5(
+            content,
+            content='chat_messages', content_rowid='id'
+        )",
+        [],
+    ).context("Failed to create chat_messages_fts index")?;
+
+    conn.execute_batch(
+        "CREATE TRIGGER IF NOT EXISTS chat_messages_fts_ai AFTER INSERT ON chat_messages BEGIN
+            INSERT INTO chat_messages_fts(rowid, content) VALUES (new.id, new.content);
+         END;
+         CREATE TRIGGER IF NOT EXISTS chat_messages_fts_ad AFTER DELETE ON chat_messages BEGIN
+            INSERT INTO chat_messages_fts(chat_messages_fts, rowid, content) VALUES ('delete', old.id, old.content);
+         END;
+         CREATE TRIGGER IF NOT EXISTS chat_messages_fts_au AFTER UPDATE ON chat_messages BEGIN
+            INSERT INTO chat_messages_fts(chat_messages_fts, rowid, content) VALUES ('delete', old.id, old.content);
+            INSERT INTO chat_messages_fts(rowid, content) VALUES (new.id, new.content);
+         END;
+         INSERT INTO chat_messages_fts(chat_messages_fts) VALUES ('rebuild');",
+    ).context("Failed to create chat_messages_fts triggers")?;
+
+    // Tasks and calendar events live in Google; a copy of what was last
+    // fetched is kept here so they can be searched offline
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS search_items (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            domain TEXT NOT NULL,
+            account_id TEXT NOT NULL,
+            item_id TEXT NOT NULL,
+            container_id TEXT NOT NULL,
+            title TEXT NOT NULL,
+            body TEXT NOT NULL DEFAULT '',
+            item_time TEXT,
+            indexed_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE(domain, account_id, item_id)
+        )",
+        [],
+    ).context("Failed to create search_items table")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_search_items_container ON search_items(domain, account_id, container_id)",
+        [],
+    ).context("Failed to create idx_search_items_container")?;
+
+    conn.execute(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS search_items_fts USING fts5(
+            title, body,
+            content='search_items', content_rowid='id'
+        )",
+        [],
+    ).context("Failed to create search_items_fts index")?;
+
+    conn.execute_batch(
+        "CREATE TRIGGER IF NOT EXISTS search_items_ai AFTER INSERT ON search_items BEGIN
+            INSERT INTO search_items_fts(rowid, title, body) VALUES (new.id, new.title, new.body);
+         END;
+         CREATE TRIGGER IF NOT EXISTS search_items_ad AFTER DELETE ON search_items BEGIN
+            INSERT INTO search_items_fts(search_items_fts, rowid, title, body) VALUES ('delete', old.id, old.title, old.body);
+         END;
+         CREATE TRIGGER IF NOT EXISTS search_items_au AFTER UPDATE ON search_items BEGIN
+            INSERT INTO search_items_fts(search_items_fts, rowid, title, body) VALUES ('delete', old.id, old.title, old.body);
+            INSERT INTO search_items_fts(rowid, title, body) VALUES (new.id, new.title, new.body);
+         END;",
+    ).context("Failed to create search_items_fts triggers")?;
+
+    Ok(())
+}
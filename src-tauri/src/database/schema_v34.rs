@@ -0,0 +1,25 @@
+/// Run migration v34 - Add first-run onboarding progress
+pub fn run_migration_v34(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // completed_steps and selected_models are JSON arrays, tour_progress a JSON object
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS user_onboarding (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL UNIQUE,
+            is_completed BOOLEAN NOT NULL DEFAULT 0,
+            current_step TEXT NOT NULL,
+            completed_steps TEXT NOT NULL DEFAULT '[]',
+            selected_persona TEXT,
+            ollama_setup_status TEXT NOT NULL DEFAULT 'unknown',
+            selected_models TEXT NOT NULL DEFAULT '[]',
+            sample_data_created BOOLEAN NOT NULL DEFAULT 0,
+            tour_progress TEXT NOT NULL DEFAULT '{}',
+            started_at TEXT NOT NULL,
+            completed_at TEXT
+        )",
+        [],
+    ).context("Failed to create user_onboarding table")?;
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+/// Run migration v35 - Add LLM providers and per-feature model selection
+pub fn run_migration_v35(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // kind is 'ollama' or 'openai_compatible'; api_key_encrypted is AES-GCM, base64
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS llm_providers (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            base_url TEXT NOT NULL,
+            api_key_encrypted TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )",
+        [],
+    ).context("Failed to create llm_providers table")?;
+
+    conn.execute(
+        "INSERT OR IGNORE INTO llm_providers (id, name, kind, base_url)
+         VALUES ('ollama', 'Ollama', 'ollama', 'http://localhost:11434')",
+        [],
+    ).context("Failed to add the built-in Ollama provider")?;
+
+    // feature is 'chat', 'summarization' or 'embeddings'
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS llm_feature_models (
+            feature TEXT PRIMARY KEY,
+            provider_id TEXT NOT NULL REFERENCES llm_providers(id) ON DELETE CASCADE,
+            model TEXT NOT NULL,
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )",
+        [],
+    ).context("Failed to create llm_feature_models table")?;
+
+    Ok(())
+}
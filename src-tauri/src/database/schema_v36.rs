@@ -0,0 +1,53 @@
+/// Run migration v36 - Turn chat_templates into a versioned prompt template library
+pub fn run_migration_v36(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // variables is a JSON array of {name, kind, required, default, description}
+    let columns = [
+        ("category", "TEXT NOT NULL DEFAULT 'general'"),
+        ("description", "TEXT"),
+        ("variables", "TEXT NOT NULL DEFAULT '[]'"),
+        ("version", "INTEGER NOT NULL DEFAULT 1"),
+        ("updated_at", "TEXT"),
+    ];
+    for (column, definition) in columns {
+        let exists: bool = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('chat_templates') WHERE name = ?1",
+                [column],
+                |row| Ok(row.get::<_, i32>(0)? > 0),
+            )
+            .unwrap_or(false);
+        if !exists {
+            conn.execute(&format!("ALTER TABLE chat_templates ADD COLUMN {} {}", column, definition), [])
+                .with_context(|| format!("Failed to add {} column to chat_templates", column))?;
+        }
+    }
+    conn.execute("UPDATE chat_templates SET updated_at = datetime('now') WHERE updated_at IS NULL", [])
+        .context("Failed to backfill chat_templates.updated_at")?;
+
+    // Earlier versions of a template, kept when it is edited
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS chat_template_versions (
+            template_id INTEGER NOT NULL REFERENCES chat_templates(id) ON DELETE CASCADE,
+            version INTEGER NOT NULL,
+            template_content TEXT NOT NULL,
+            variables TEXT NOT NULL DEFAULT '[]',
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            PRIMARY KEY (template_id, version)
+        )",
+        [],
+    ).context("Failed to create chat_template_versions table")?;
+
+    // Template a feature (mail summary, reply drafting...) uses unless told otherwise
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS prompt_template_defaults (
+            feature TEXT PRIMARY KEY,
+            template_id INTEGER NOT NULL REFERENCES chat_templates(id) ON DELETE CASCADE,
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )",
+        [],
+    ).context("Failed to create prompt_template_defaults table")?;
+
+    Ok(())
+}
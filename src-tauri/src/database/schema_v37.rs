@@ -0,0 +1,44 @@
+/// Run migration v37 - Add files attached to chat sessions, chunked with embeddings
+pub fn run_migration_v37(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // source_key is "gmail:<account>:<message>:<attachment>" or "file:<path>",
+    // as for attachment_transcripts
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS chat_attachments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id INTEGER NOT NULL,
+            source_key TEXT NOT NULL,
+            filename TEXT NOT NULL,
+            mime_type TEXT NOT NULL,
+            char_count INTEGER NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            UNIQUE(session_id, source_key),
+            FOREIGN KEY (session_id) REFERENCES chat_sessions (id) ON DELETE CASCADE
+        )",
+        [],
+    ).context("Failed to create chat_attachments table")?;
+
+    // Offsets are character positions in the extracted text; embedding is a
+    // little-endian f32 array, NULL when no embedding model was available
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS chat_attachment_chunks (
+            attachment_id INTEGER NOT NULL,
+            chunk_index INTEGER NOT NULL,
+            start_offset INTEGER NOT NULL,
+            end_offset INTEGER NOT NULL,
+            content TEXT NOT NULL,
+            embedding BLOB,
+            PRIMARY KEY (attachment_id, chunk_index),
+            FOREIGN KEY (attachment_id) REFERENCES chat_attachments (id) ON DELETE CASCADE
+        )",
+        [],
+    ).context("Failed to create chat_attachment_chunks table")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_chat_attachments_session ON chat_attachments(session_id)",
+        [],
+    ).context("Failed to create chat_attachments index")?;
+
+    Ok(())
+}
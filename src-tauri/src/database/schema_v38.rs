@@ -0,0 +1,48 @@
+/// Run migration v38 - Add the model registry and per-feature routing rules
+pub fn run_migration_v38(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // Filled from each provider's model list; capabilities is a JSON array of
+    // "completion", "embedding", "vision", "tools"
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS llm_models (
+            provider_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            family TEXT,
+            parameter_size TEXT,
+            quantization TEXT,
+            size_bytes INTEGER,
+            context_length INTEGER,
+            capabilities TEXT NOT NULL DEFAULT '[]',
+            refreshed_at TEXT NOT NULL DEFAULT (datetime('now')),
+            PRIMARY KEY (provider_id, name)
+        )",
+        [],
+    ).context("Failed to create llm_models table")?;
+
+    // Used when a feature has no model chosen: the smallest or largest
+    // registered model of the provider with the required capability
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS llm_routing_rules (
+            feature TEXT PRIMARY KEY,
+            provider_id TEXT NOT NULL,
+            capability TEXT NOT NULL,
+            prefer TEXT NOT NULL,
+            max_size_bytes INTEGER,
+            min_context_length INTEGER,
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )",
+        [],
+    ).context("Failed to create llm_routing_rules table")?;
+
+    conn.execute_batch(
+        "INSERT OR IGNORE INTO llm_routing_rules (feature, provider_id, capability, prefer) VALUES
+            ('chat', 'ollama', 'completion', 'largest'),
+            ('summarization', 'ollama', 'completion', 'largest'),
+            ('classification', 'ollama', 'completion', 'smallest'),
+            ('translation', 'ollama', 'completion', 'largest'),
+            ('embeddings', 'ollama', 'embedding', 'smallest');",
+    ).context("Failed to add default routing rules")?;
+
+    Ok(())
+}
@@ -0,0 +1,29 @@
+/// Run migration v39 - Add AI generation history for canvas documents
+pub fn run_migration_v39(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // Canvas documents are stored by the frontend, so canvas_id is its
+    // document ID. selection and result are JSON: the selected elements sent
+    // to the model and the summary or nodes and edges returned.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS canvas_ai_generations (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            canvas_id TEXT NOT NULL,
+            action TEXT NOT NULL,
+            instructions TEXT,
+            selection TEXT NOT NULL DEFAULT '[]',
+            result TEXT NOT NULL,
+            model TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )",
+        [],
+    ).context("Failed to create canvas_ai_generations table")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_canvas_ai_generations_canvas
+         ON canvas_ai_generations (canvas_id, created_at)",
+        [],
+    ).context("Failed to create canvas_ai_generations index")?;
+
+    Ok(())
+}
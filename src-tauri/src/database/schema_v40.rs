@@ -0,0 +1,26 @@
+/// Run migration v40 - Add captures found by the clipboard watcher
+pub fn run_migration_v40(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // kind is url, email or pattern; pattern_name names the user pattern
+    // that matched. status moves from offered to captured or dismissed.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS clipboard_captures (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            kind TEXT NOT NULL,
+            pattern_name TEXT,
+            content TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'offered',
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )",
+        [],
+    ).context("Failed to create clipboard_captures table")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_clipboard_captures_created
+         ON clipboard_captures (created_at)",
+        [],
+    ).context("Failed to create clipboard_captures index")?;
+
+    Ok(())
+}
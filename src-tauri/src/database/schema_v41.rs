@@ -0,0 +1,31 @@
+/// Run migration v41 - Add the link preview cache
+pub fn run_migration_v41(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // One row per requested URL. A failed fetch is cached too, with error
+    // set, so a dead link is not retried on every render. expires_at is a
+    // unix timestamp.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS link_previews (
+            url TEXT PRIMARY KEY,
+            final_url TEXT,
+            title TEXT,
+            description TEXT,
+            image_url TEXT,
+            site_name TEXT,
+            favicon_url TEXT,
+            content_type TEXT,
+            error TEXT,
+            fetched_at TEXT NOT NULL DEFAULT (datetime('now')),
+            expires_at INTEGER NOT NULL
+        )",
+        [],
+    ).context("Failed to create link_previews table")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_link_previews_expires ON link_previews (expires_at)",
+        [],
+    ).context("Failed to create link_previews index")?;
+
+    Ok(())
+}
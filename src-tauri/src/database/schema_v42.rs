@@ -0,0 +1,30 @@
+/// Run migration v42 - Record where clipped notes came from
+pub fn run_migration_v42(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // tags is a JSON array of strings
+    let columns = [
+        ("source_url", "TEXT"),
+        ("tags", "TEXT NOT NULL DEFAULT '[]'"),
+    ];
+    for (column, definition) in columns {
+        let exists: bool = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('notes') WHERE name = ?1",
+                [column],
+                |row| Ok(row.get::<_, i32>(0)? > 0),
+            )
+            .unwrap_or(false);
+        if !exists {
+            conn.execute(&format!("ALTER TABLE notes ADD COLUMN {} {}", column, definition), [])
+                .with_context(|| format!("Failed to add {} column to notes", column))?;
+        }
+    }
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_notes_source_url ON notes (source_url)",
+        [],
+    ).context("Failed to create notes source_url index")?;
+
+    Ok(())
+}
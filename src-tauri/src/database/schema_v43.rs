@@ -0,0 +1,48 @@
+/// Run migration v43 - Add RSS/Atom feed subscriptions and their items
+pub fn run_migration_v43(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // last_error is cleared by the next successful fetch
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS feeds (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            url TEXT NOT NULL UNIQUE,
+            title TEXT NOT NULL,
+            site_url TEXT,
+            description TEXT,
+            last_fetched_at TEXT,
+            last_error TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )",
+        [],
+    ).context("Failed to create feeds table")?;
+
+    // guid is the entry ID from the feed, or its link when it has none.
+    // published_at is UTC in SQLite's datetime format so it sorts and
+    // compares with created_at.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS feed_items (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            feed_id INTEGER NOT NULL REFERENCES feeds(id) ON DELETE CASCADE,
+            guid TEXT NOT NULL,
+            title TEXT NOT NULL,
+            url TEXT,
+            author TEXT,
+            summary TEXT,
+            content TEXT,
+            published_at TEXT,
+            is_read INTEGER NOT NULL DEFAULT 0,
+            is_starred INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            UNIQUE (feed_id, guid)
+        )",
+        [],
+    ).context("Failed to create feed_items table")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_feed_items_unread ON feed_items (is_read, published_at)",
+        [],
+    ).context("Failed to create feed_items index")?;
+
+    Ok(())
+}
@@ -0,0 +1,24 @@
+/// Run migration v44 - Keep task and event status in the local search copy
+pub fn run_migration_v44(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // Google's status: needsAction/completed for tasks, confirmed/tentative/cancelled for events
+    let exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('search_items') WHERE name = 'status'",
+            [],
+            |row| Ok(row.get::<_, i32>(0)? > 0),
+        )
+        .unwrap_or(false);
+    if !exists {
+        conn.execute("ALTER TABLE search_items ADD COLUMN status TEXT", [])
+            .context("Failed to add status column to search_items")?;
+    }
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_search_items_time ON search_items(domain, item_time)",
+        [],
+    ).context("Failed to create idx_search_items_time")?;
+
+    Ok(())
+}
@@ -0,0 +1,34 @@
+/// Run migration v45 - Add workspaces grouping accounts, task lists, calendars and folders
+pub fn run_migration_v45(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS workspaces (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            color TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )",
+        [],
+    ).context("Failed to create workspaces table")?;
+
+    // kind is account, task_list, calendar or folder; member_id is the
+    // Gmail account ID, Google list or calendar ID, or local folder ID
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS workspace_members (
+            workspace_id INTEGER NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+            kind TEXT NOT NULL,
+            member_id TEXT NOT NULL,
+            PRIMARY KEY (workspace_id, kind, member_id)
+        )",
+        [],
+    ).context("Failed to create workspace_members table")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_workspace_members_member ON workspace_members (kind, member_id)",
+        [],
+    ).context("Failed to create workspace_members index")?;
+
+    Ok(())
+}
@@ -0,0 +1,22 @@
+/// Run migration v46 - Add archive accounts for mail imported from Thunderbird and Outlook
+pub fn run_migration_v46(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // The messages themselves go into gmail_message_store under the
+    // archive's ID, so they are searched alongside synced mail. source is
+    // thunderbird or outlook.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS archive_accounts (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            source TEXT NOT NULL,
+            source_path TEXT NOT NULL,
+            message_count INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            imported_at TEXT
+        )",
+        [],
+    ).context("Failed to create archive_accounts table")?;
+
+    Ok(())
+}
@@ -0,0 +1,33 @@
+/// Run migration v47 - Add the secure notes vault
+pub fn run_migration_v47(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // One row. The notes key is random and stored encrypted with a key
+    // derived from the passphrase (Argon2id, with salt and cost parameters
+    // here), so a new passphrase only re-wraps it.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS secure_vault (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            salt TEXT NOT NULL,
+            kdf_params TEXT NOT NULL,
+            wrapped_key TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )",
+        [],
+    ).context("Failed to create secure_vault table")?;
+
+    // Title and content are both encrypted; nothing here is searchable
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS secure_notes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            title_encrypted TEXT NOT NULL,
+            content_encrypted TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )",
+        [],
+    ).context("Failed to create secure_notes table")?;
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+/// Run migration v48 - Add calendars imported from ICS files and webcal subscriptions
+pub fn run_migration_v48(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // source is file or webcal; a webcal calendar is refreshed from
+    // source_url, replacing its events
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS imported_calendars (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            source TEXT NOT NULL,
+            source_url TEXT NOT NULL,
+            event_count INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            refreshed_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )",
+        [],
+    ).context("Failed to create imported_calendars table")?;
+
+    // Times follow Google's event shape: start_date for all-day events,
+    // otherwise start_date_time with time_zone when the file named one.
+    // recurrence holds the RRULE/EXDATE lines as written.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS imported_events (
+            calendar_id TEXT NOT NULL REFERENCES imported_calendars(id) ON DELETE CASCADE,
+            uid TEXT NOT NULL,
+            summary TEXT,
+            description TEXT,
+            location TEXT,
+            status TEXT,
+            start_date_time TEXT,
+            start_date TEXT,
+            end_date_time TEXT,
+            end_date TEXT,
+            time_zone TEXT,
+            recurrence TEXT,
+            sort_time TEXT NOT NULL,
+            PRIMARY KEY (calendar_id, uid)
+        )",
+        [],
+    ).context("Failed to create imported_events table")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_imported_events_time ON imported_events(calendar_id, sort_time)",
+        [],
+    ).context("Failed to create idx_imported_events_time")?;
+
+    Ok(())
+}
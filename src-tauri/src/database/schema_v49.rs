@@ -0,0 +1,52 @@
+/// Run migration v49 - Store task time blocks and imported event times in UTC
+pub fn run_migration_v49(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+    use rusqlite::params;
+
+    use crate::database::operations::imported_calendar_operations::ImportedEvent;
+    use crate::models::task_metadata::TimeBlock;
+    use crate::utils::time;
+
+    // Existing values were written in whatever zone the machine was in;
+    // the current one is the best guess
+    let zone = time::system_zone();
+
+    // Time blocks held naive local strings; they become UTC with the zone
+    // they were planned in
+    let blocks: Vec<(i64, String)> = {
+        let mut stmt = conn.prepare("SELECT id, time_block FROM task_metadata WHERE time_block IS NOT NULL")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<rusqlite::Result<_>>().context("Failed to read task time blocks")?
+    };
+    for (id, json) in blocks {
+        let Ok(block) = serde_json::from_str::<TimeBlock>(&json) else { continue };
+        conn.execute(
+            "UPDATE task_metadata SET time_block = ?1 WHERE id = ?2",
+            params![serde_json::to_string(&block.in_utc(&zone))?, id],
+        ).context("Failed to convert task time block")?;
+    }
+
+    // Imported events in a named or floating zone were sorted as if in UTC
+    let events: Vec<(String, String, String, Option<String>)> = {
+        let mut stmt = conn.prepare(
+            "SELECT calendar_id, uid, start_date_time, time_zone FROM imported_events WHERE start_date_time IS NOT NULL",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?;
+        rows.collect::<rusqlite::Result<_>>().context("Failed to read imported events")?
+    };
+    for (calendar_id, uid, start, time_zone) in events {
+        let event = ImportedEvent { uid, start_date_time: Some(start), time_zone, ..Default::default() };
+        let Some(sort_time) = event.sort_time(&zone) else { continue };
+        conn.execute(
+            "UPDATE imported_events SET sort_time = ?1 WHERE calendar_id = ?2 AND uid = ?3",
+            params![sort_time, calendar_id, event.uid],
+        ).context("Failed to update imported event time")?;
+        conn.execute(
+            "UPDATE search_items SET item_time = ?1
+             WHERE domain = 'event' AND account_id = ?2 AND container_id = ?2 AND item_id = ?3",
+            params![sort_time, calendar_id, event.uid],
+        ).context("Failed to update indexed event time")?;
+    }
+
+    Ok(())
+}
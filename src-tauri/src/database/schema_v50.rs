@@ -0,0 +1,43 @@
+/// Run migration v50 - Cache Gmail label metadata and keep local label overrides
+pub fn run_migration_v50(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // Label metadata from the list call; counts come from the per-label
+    // details and counts_refreshed_at is 0 until they have been fetched or
+    // once a change has made them stale
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS gmail_label_cache (
+            account_id TEXT NOT NULL,
+            label_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            label_type TEXT,
+            message_list_visibility TEXT,
+            label_list_visibility TEXT,
+            text_color TEXT,
+            background_color TEXT,
+            messages_total INTEGER,
+            messages_unread INTEGER,
+            threads_total INTEGER,
+            threads_unread INTEGER,
+            counts_refreshed_at INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (account_id, label_id)
+        )",
+        [],
+    ).context("Failed to create gmail_label_cache table")?;
+
+    // Display names and colours set in the app only; never sent to Gmail
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS gmail_label_overrides (
+            account_id TEXT NOT NULL,
+            label_id TEXT NOT NULL,
+            display_name TEXT,
+            text_color TEXT,
+            background_color TEXT,
+            updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+            PRIMARY KEY (account_id, label_id)
+        )",
+        [],
+    ).context("Failed to create gmail_label_overrides table")?;
+
+    Ok(())
+}
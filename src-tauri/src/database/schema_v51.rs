@@ -0,0 +1,28 @@
+/// Run migration v51 - Add saved Gmail searches and smart folders
+pub fn run_migration_v51(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // kind is search or smart_folder. filters holds the structured filters
+    // the query was built from, so both can be edited and rebuilt later.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS gmail_saved_searches (
+            id TEXT PRIMARY KEY,
+            account_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            kind TEXT NOT NULL DEFAULT 'search',
+            filters TEXT NOT NULL,
+            query TEXT NOT NULL,
+            position INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )",
+        [],
+    ).context("Failed to create gmail_saved_searches table")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_gmail_saved_searches_account ON gmail_saved_searches(account_id, kind, position)",
+        [],
+    ).context("Failed to create idx_gmail_saved_searches_account")?;
+
+    Ok(())
+}
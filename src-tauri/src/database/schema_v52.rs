@@ -0,0 +1,18 @@
+/// Run migration v52 - Index the message store for windowed header queries
+pub fn run_migration_v52(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // Each index matches a sort key in message_header_operations exactly,
+    // with message_id as the tie-break the cursors page on
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_gmail_message_store_date_key
+            ON gmail_message_store(account_id, COALESCE(internal_date, 0), message_id);
+         CREATE INDEX IF NOT EXISTS idx_gmail_message_store_sender_key
+            ON gmail_message_store(account_id, lower(COALESCE(NULLIF(json_extract(message_data, '$.parsed_content.from.name'), ''),
+                                                             json_extract(message_data, '$.parsed_content.from.email'), '')), message_id);
+         CREATE INDEX IF NOT EXISTS idx_gmail_message_store_subject_key
+            ON gmail_message_store(account_id, lower(COALESCE(json_extract(message_data, '$.parsed_content.subject'), '')), message_id);",
+    ).context("Failed to create message header indexes")?;
+
+    Ok(())
+}
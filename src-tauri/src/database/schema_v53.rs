@@ -0,0 +1,23 @@
+/// Run migration v53 - Add the cache delta log
+pub fn run_migration_v53(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // seq is AUTOINCREMENT so sequence numbers are never reused after old
+    // deltas are pruned. entity_type is message, task or event; change is
+    // added, updated or removed.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS cache_deltas (
+            seq INTEGER PRIMARY KEY AUTOINCREMENT,
+            account_id TEXT NOT NULL,
+            entity_type TEXT NOT NULL,
+            entity_id TEXT NOT NULL,
+            change TEXT NOT NULL,
+            label_ids TEXT NOT NULL DEFAULT '[]',
+            data TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )",
+        [],
+    ).context("Failed to create cache_deltas table")?;
+
+    Ok(())
+}
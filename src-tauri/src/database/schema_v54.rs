@@ -0,0 +1,17 @@
+/// Run migration v54 - Persist Google API request usage for the scheduler
+pub fn run_migration_v54(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // One row per second with requests sent, split by traffic class, so a
+    // restart can rebuild the rolling minute and the day's total
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS api_request_usage (
+            second INTEGER PRIMARY KEY,
+            interactive INTEGER NOT NULL DEFAULT 0,
+            background INTEGER NOT NULL DEFAULT 0
+        )",
+        [],
+    ).context("Failed to create api_request_usage table")?;
+
+    Ok(())
+}
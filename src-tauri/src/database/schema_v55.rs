@@ -0,0 +1,24 @@
+/// Run migration v55 - Record attachment scan verdicts
+pub fn run_migration_v55(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // Gmail attachment IDs change between fetches, so a verdict is kept
+    // per message and content hash, with the filename for display
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS attachment_scan_verdicts (
+            account_id TEXT NOT NULL,
+            message_id TEXT NOT NULL,
+            sha256 TEXT NOT NULL,
+            filename TEXT NOT NULL,
+            verdict TEXT NOT NULL CHECK (verdict IN ('clean', 'infected', 'error')),
+            engine TEXT NOT NULL,
+            detail TEXT,
+            scanned_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            opened_anyway_at DATETIME,
+            PRIMARY KEY (account_id, message_id, sha256)
+        )",
+        [],
+    ).context("Failed to create attachment_scan_verdicts table")?;
+
+    Ok(())
+}
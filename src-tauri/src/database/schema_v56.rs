@@ -0,0 +1,24 @@
+/// Run migration v56 - Store Gmail send-as addresses
+pub fn run_migration_v56(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // Replaced as a whole each time the list is fetched from Gmail settings
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS gmail_send_as (
+            account_id TEXT NOT NULL,
+            email TEXT NOT NULL,
+            display_name TEXT,
+            reply_to TEXT,
+            signature TEXT,
+            is_primary INTEGER NOT NULL DEFAULT 0,
+            is_default INTEGER NOT NULL DEFAULT 0,
+            treat_as_alias INTEGER NOT NULL DEFAULT 0,
+            verification_status TEXT,
+            refreshed_at TEXT NOT NULL DEFAULT (datetime('now')),
+            PRIMARY KEY (account_id, email)
+        )",
+        [],
+    ).context("Failed to create gmail_send_as table")?;
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+/// Run migration v57 - Note templates and daily notes
+pub fn run_migration_v57(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS note_templates (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            title TEXT NOT NULL,
+            content TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )",
+        [],
+    ).context("Failed to create note_templates table")?;
+
+    // One note per local date; a deleted note is recreated on next open
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS daily_notes (
+            date TEXT PRIMARY KEY,
+            note_id INTEGER NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )",
+        [],
+    ).context("Failed to create daily_notes table")?;
+
+    Ok(())
+}
@@ -0,0 +1,26 @@
+/// Run migration v58 - Add local task comments
+pub fn run_migration_v58(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // Comments stay local; Google Tasks has nowhere to keep them besides the
+    // notes field, which only gets a summary
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS task_comments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            google_task_id TEXT NOT NULL,
+            account_id TEXT,
+            author TEXT NOT NULL,
+            body TEXT NOT NULL,
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            edited_at DATETIME
+        )",
+        [],
+    ).context("Failed to create task_comments table")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_task_comments_task ON task_comments(google_task_id, created_at)",
+        [],
+    ).context("Failed to create idx_task_comments_task")?;
+
+    Ok(())
+}
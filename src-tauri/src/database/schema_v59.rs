@@ -0,0 +1,17 @@
+/// Run migration v59 - Add per-workspace availability profiles
+pub fn run_migration_v59(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // The global profile is the calendar.availability setting; a workspace
+    // with a row here uses its own profile while it is active
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS workspace_availability (
+            workspace_id INTEGER PRIMARY KEY REFERENCES workspaces(id) ON DELETE CASCADE,
+            profile_json TEXT NOT NULL,
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )",
+        [],
+    ).context("Failed to create workspace_availability table")?;
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+/// Run migration v60 - Add mail triage sessions and snoozed messages
+pub fn run_migration_v60(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // One row per triage pass over a label; stats are filled in when it ends
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS triage_sessions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            account_id TEXT NOT NULL,
+            label_id TEXT NOT NULL,
+            queue_json TEXT NOT NULL,
+            started_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            ended_at TEXT,
+            stats_json TEXT
+        )",
+        [],
+    ).context("Failed to create triage_sessions table")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_triage_sessions_account ON triage_sessions(account_id, started_at)",
+        [],
+    ).context("Failed to create triage_sessions index")?;
+
+    // Every action taken in a session, with what undo needs to reverse it
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS triage_actions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id INTEGER NOT NULL REFERENCES triage_sessions(id) ON DELETE CASCADE,
+            message_id TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            action_json TEXT NOT NULL,
+            labels_before TEXT NOT NULL,
+            labels_after TEXT NOT NULL,
+            task_id TEXT,
+            task_list_id TEXT,
+            snooze_id INTEGER,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            undone_at TEXT
+        )",
+        [],
+    ).context("Failed to create triage_actions table")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_triage_actions_session ON triage_actions(session_id, id)",
+        [],
+    ).context("Failed to create triage_actions index")?;
+
+    // Snoozed messages leave the inbox until wake_at (unix seconds)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS snoozed_messages (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            account_id TEXT NOT NULL,
+            message_id TEXT NOT NULL,
+            thread_id TEXT NOT NULL,
+            wake_at INTEGER NOT NULL,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            woken_at TEXT,
+            cancelled_at TEXT
+        )",
+        [],
+    ).context("Failed to create snoozed_messages table")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_snoozed_messages_wake ON snoozed_messages(wake_at) WHERE woken_at IS NULL AND cancelled_at IS NULL",
+        [],
+    ).context("Failed to create snoozed_messages index")?;
+
+    Ok(())
+}
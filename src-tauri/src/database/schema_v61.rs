@@ -0,0 +1,35 @@
+/// Run migration v61 - Add VIP senders and per-workspace VIP alert rules
+pub fn run_migration_v61(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // A sender with no workspace is a VIP in every workspace; addresses
+    // are lowercase, and "@example.com" covers a whole domain
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS vip_senders (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            workspace_id INTEGER REFERENCES workspaces(id) ON DELETE CASCADE,
+            address TEXT NOT NULL,
+            name TEXT,
+            added_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    ).context("Failed to create vip_senders table")?;
+
+    conn.execute(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_vip_senders_address ON vip_senders(IFNULL(workspace_id, 0), address)",
+        [],
+    ).context("Failed to create vip_senders index")?;
+
+    // The global rules are the notifications.vip setting; a workspace with
+    // a row here uses its own rules while it is active
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS workspace_vip_rules (
+            workspace_id INTEGER PRIMARY KEY REFERENCES workspaces(id) ON DELETE CASCADE,
+            rules_json TEXT NOT NULL,
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )",
+        [],
+    ).context("Failed to create workspace_vip_rules table")?;
+
+    Ok(())
+}
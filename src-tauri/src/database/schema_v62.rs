@@ -0,0 +1,16 @@
+/// Run migration v62 - Add retry backoff to snoozed messages
+pub fn run_migration_v62(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // A wake that Gmail rejects is retried later instead of on every tick
+    conn.execute(
+        "ALTER TABLE snoozed_messages ADD COLUMN attempts INTEGER NOT NULL DEFAULT 0",
+        [],
+    ).context("Failed to add snoozed_messages.attempts")?;
+    conn.execute(
+        "ALTER TABLE snoozed_messages ADD COLUMN next_attempt_at INTEGER",
+        [],
+    ).context("Failed to add snoozed_messages.next_attempt_at")?;
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+/// Run migration v63 - Add Gmail sync state and sync config tables
+pub fn run_migration_v63(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // One row per account: the history ID incremental syncs start from and
+    // the outcome of the last run
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sync_states (
+            account_id TEXT PRIMARY KEY,
+            history_id TEXT,
+            last_sync_timestamp TEXT,
+            messages_synced INTEGER NOT NULL DEFAULT 0,
+            messages_failed INTEGER NOT NULL DEFAULT 0,
+            sync_status TEXT NOT NULL DEFAULT 'Idle',
+            errors TEXT NOT NULL DEFAULT '[]',
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        )",
+        [],
+    ).context("Failed to create sync_states table")?;
+
+    // Label lists are JSON arrays, or empty for no restriction
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sync_configs (
+            account_id TEXT PRIMARY KEY,
+            max_messages_per_batch INTEGER NOT NULL,
+            sync_interval_minutes INTEGER NOT NULL,
+            labels_to_sync TEXT,
+            exclude_labels TEXT,
+            full_sync_on_startup BOOLEAN NOT NULL DEFAULT 0,
+            enable_incremental_sync BOOLEAN NOT NULL DEFAULT 1,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        )",
+        [],
+    ).context("Failed to create sync_configs table")?;
+
+    Ok(())
+}
@@ -0,0 +1,20 @@
+/// Run migration v64 - Add task import records
+pub fn run_migration_v64(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // The Google task each imported row became, so importing the same file
+    // again skips the rows that already made it
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS task_imports (
+            account_id TEXT NOT NULL,
+            import_key TEXT NOT NULL,
+            task_id TEXT NOT NULL,
+            task_list_id TEXT NOT NULL,
+            imported_at TEXT NOT NULL,
+            PRIMARY KEY (account_id, import_key)
+        )",
+        [],
+    ).context("Failed to create task_imports table")?;
+
+    Ok(())
+}
@@ -0,0 +1,25 @@
+/// Run migration v65 - Keep when synced tasks were completed
+pub fn run_migration_v65(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // Google's `completed` time of a task, UTC RFC 3339, so completions
+    // made on other devices show in the activity timeline
+    let exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('search_items') WHERE name = 'completed_at'",
+            [],
+            |row| Ok(row.get::<_, i32>(0)? > 0),
+        )
+        .unwrap_or(false);
+    if !exists {
+        conn.execute("ALTER TABLE search_items ADD COLUMN completed_at TEXT", [])
+            .context("Failed to add completed_at column to search_items")?;
+    }
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_search_items_completed ON search_items(domain, completed_at)",
+        [],
+    ).context("Failed to create idx_search_items_completed")?;
+
+    Ok(())
+}
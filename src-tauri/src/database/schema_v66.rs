@@ -0,0 +1,18 @@
+/// Run migration v66 - Queue task activity mirrors that could not reach Google
+pub fn run_migration_v66(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // One row per task whose mirrored activity block is out of date
+    // because Google was unreachable or the task had no Google ID yet
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS task_activity_mirrors (
+            google_task_id TEXT PRIMARY KEY,
+            account_id TEXT NOT NULL,
+            task_list_id TEXT NOT NULL,
+            queued_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    ).context("Failed to create task_activity_mirrors table")?;
+
+    Ok(())
+}
@@ -0,0 +1,20 @@
+/// Run migration v67 - Keep backfill messages that failed to download for retry
+pub fn run_migration_v67(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // A message the backfill listed but could not fetch; retried before the
+    // job completes, up to a few attempts
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS gmail_backfill_failures (
+            account_id TEXT NOT NULL,
+            message_id TEXT NOT NULL,
+            error TEXT,
+            attempts INTEGER NOT NULL DEFAULT 1,
+            failed_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (account_id, message_id)
+        )",
+        [],
+    ).context("Failed to create gmail_backfill_failures table")?;
+
+    Ok(())
+}
@@ -0,0 +1,20 @@
+/// Run migration v68 - Mark all-day events in the search index
+pub fn run_migration_v68(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // All-day events sort at midnight UTC of their date, as does a timed
+    // event starting then, so the kind is stored rather than guessed
+    conn.execute(
+        "ALTER TABLE search_items ADD COLUMN all_day INTEGER NOT NULL DEFAULT 0",
+        [],
+    ).context("Failed to add all_day to search_items")?;
+
+    // Events indexed before this were guessed from the time; the next
+    // calendar fetch indexes them again with the real kind
+    conn.execute(
+        "UPDATE search_items SET all_day = 1 WHERE domain = 'event' AND substr(item_time, 12) = '00:00:00Z'",
+        [],
+    ).context("Failed to mark indexed all-day events")?;
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+/// Run migration v69 - Keep mail list headers in their own narrow table
+pub fn run_migration_v69(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // The mail list sorts, filters and counts on these fields; pulled out
+    // of the message JSON once on write, a window never reads a message body
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS gmail_message_headers (
+            account_id TEXT NOT NULL,
+            message_id TEXT NOT NULL,
+            thread_id TEXT NOT NULL,
+            internal_date INTEGER NOT NULL DEFAULT 0,
+            label_ids TEXT NOT NULL DEFAULT '[]',
+            subject TEXT,
+            from_name TEXT,
+            from_email TEXT NOT NULL DEFAULT '',
+            snippet TEXT,
+            has_attachments INTEGER NOT NULL DEFAULT 0,
+            content TEXT NOT NULL DEFAULT 'full',
+            sender_key TEXT NOT NULL DEFAULT '',
+            subject_key TEXT NOT NULL DEFAULT '',
+            PRIMARY KEY (account_id, message_id)
+        )",
+        [],
+    ).context("Failed to create gmail_message_headers table")?;
+
+    // The sort keys of message_header_operations, with message_id as the
+    // tie-break the cursors page on
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_gmail_message_headers_date
+            ON gmail_message_headers(account_id, internal_date, message_id);
+         CREATE INDEX IF NOT EXISTS idx_gmail_message_headers_sender
+            ON gmail_message_headers(account_id, sender_key, message_id);
+         CREATE INDEX IF NOT EXISTS idx_gmail_message_headers_subject
+            ON gmail_message_headers(account_id, subject_key, message_id);
+         CREATE INDEX IF NOT EXISTS idx_gmail_message_headers_thread
+            ON gmail_message_headers(account_id, thread_id);",
+    ).context("Failed to create message header indexes")?;
+
+    // Replaced by the header table's indexes
+    conn.execute_batch(
+        "DROP INDEX IF EXISTS idx_gmail_message_store_date_key;
+         DROP INDEX IF EXISTS idx_gmail_message_store_sender_key;
+         DROP INDEX IF EXISTS idx_gmail_message_store_subject_key;",
+    ).context("Failed to drop message store header indexes")?;
+
+    // INSERT OR REPLACE into the store does not fire its delete trigger,
+    // so the insert trigger replaces the header row itself
+    let header_row = |row: &str| {
+        format!(
+            "SELECT {row}.account_id, {row}.message_id, {row}.thread_id, COALESCE({row}.internal_date, 0), {row}.label_ids,
+                    json_extract({row}.message_data, '$.parsed_content.subject'),
+                    NULLIF(json_extract({row}.message_data, '$.parsed_content.from.name'), ''),
+                    COALESCE(json_extract({row}.message_data, '$.parsed_content.from.email'), ''),
+                    json_extract({row}.message_data, '$.snippet'),
+                    COALESCE(json_array_length({row}.message_data, '$.parsed_content.attachments'), 0) > 0,
+                    {row}.content,
+                    lower(COALESCE(NULLIF(json_extract({row}.message_data, '$.parsed_content.from.name'), ''),
+                                   json_extract({row}.message_data, '$.parsed_content.from.email'), '')),
+                    lower(COALESCE(json_extract({row}.message_data, '$.parsed_content.subject'), ''))"
+        )
+    };
+    let columns = "account_id, message_id, thread_id, internal_date, label_ids, subject, from_name, from_email,
+                   snippet, has_attachments, content, sender_key, subject_key";
+    conn.execute_batch(&format!(
+        "CREATE TRIGGER IF NOT EXISTS gmail_message_headers_ai AFTER INSERT ON gmail_message_store BEGIN
+            INSERT OR REPLACE INTO gmail_message_headers ({columns}) {new};
+         END;
+         CREATE TRIGGER IF NOT EXISTS gmail_message_headers_au AFTER UPDATE ON gmail_message_store BEGIN
+            DELETE FROM gmail_message_headers WHERE account_id = old.account_id AND message_id = old.message_id;
+            INSERT OR REPLACE INTO gmail_message_headers ({columns}) {new};
+         END;
+         CREATE TRIGGER IF NOT EXISTS gmail_message_headers_ad AFTER DELETE ON gmail_message_store BEGIN
+            DELETE FROM gmail_message_headers WHERE account_id = old.account_id AND message_id = old.message_id;
+         END;",
+        new = header_row("new"),
+    )).context("Failed to create gmail_message_headers triggers")?;
+
+    conn.execute(
+        &format!("INSERT OR REPLACE INTO gmail_message_headers ({columns}) {} FROM gmail_message_store s", header_row("s")),
+        [],
+    ).context("Failed to fill gmail_message_headers")?;
+
+    Ok(())
+}
@@ -0,0 +1,217 @@
+//! Errors returned to the frontend by Tauri commands
+//!
+//! Commands return a `CommandError` rather than a bare string so the UI can
+//! tell a rate limit from a lost session from a bad input: `code` and
+//! `category` come from the underlying `LibreOllamaError`, `retryable` and
+//! `retry_after_ms` say whether and when trying again makes sense, and
+//! `user_message` is safe to show as is. A fixed message (`&str`) becomes
+//! `command_failed`; there is no conversion from `String`, so an error
+//! built with `format!` has to say what kind it is by going through a
+//! `LibreOllamaError` variant.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::LibreOllamaError;
+
+/// Code for errors that were only ever a message
+pub const COMMAND_FAILED: &str = "command_failed";
+
+pub type CommandResult<T> = std::result::Result<T, CommandError>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandError {
+    /// Error kind, e.g. `rate_limit` or `not_found`
+    pub code: String,
+    /// Domain, e.g. `gmail`, `database`, `auth`
+    pub category: String,
+    /// Localized message to show
+    pub user_message: String,
+    /// Whether the same call may succeed if repeated
+    pub retryable: bool,
+    /// How long to wait before retrying, when known
+    pub retry_after_ms: Option<u64>,
+    /// Technical description for logs and bug reports
+    pub details: Option<String>,
+}
+
+impl CommandError {
+    /// An error that only has a message
+    pub fn message(message: impl Into<String>) -> Self {
+        Self {
+            code: COMMAND_FAILED.to_string(),
+            category: "general".to_string(),
+            user_message: message.into(),
+            retryable: false,
+            retry_after_ms: None,
+            details: None,
+        }
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.details.as_deref().unwrap_or(&self.user_message))
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl From<LibreOllamaError> for CommandError {
+    fn from(error: LibreOllamaError) -> Self {
+        Self {
+            code: error.code().to_string(),
+            category: error.category().to_string(),
+            user_message: error.user_message(),
+            retryable: error.is_retryable(),
+            retry_after_ms: error.retry_delay_ms(),
+            details: Some(error.to_string()),
+        }
+    }
+}
+
+/// Typed errors keep their code and category wherever they sit in the
+/// chain; the context added on the way up goes into `details`. Without a
+/// typed cause the outermost context becomes the message.
+impl From<anyhow::Error> for CommandError {
+    fn from(error: anyhow::Error) -> Self {
+        let details = format!("{:#}", error);
+        if let Some(command_error) = error.chain().find_map(|cause| cause.downcast_ref::<CommandError>()) {
+            return Self { details: Some(details), ..command_error.clone() };
+        }
+        match error.chain().find_map(typed_cause) {
+            Some((cause, true)) => Self { details: Some(details), ..Self::from(cause) },
+            Some((cause, false)) => Self {
+                user_message: error.to_string(),
+                details: Some(details),
+                ..Self::from(cause)
+            },
+            None => Self {
+                code: "internal".to_string(),
+                category: "general".to_string(),
+                user_message: error.to_string(),
+                retryable: false,
+                retry_after_ms: None,
+                details: Some(details),
+            },
+        }
+    }
+}
+
+/// The `LibreOllamaError` for a cause in an error chain, and whether it was
+/// one already (with its own user message) rather than a library error
+fn typed_cause(cause: &(dyn std::error::Error + 'static)) -> Option<(LibreOllamaError, bool)> {
+    if let Some(error) = cause.downcast_ref::<LibreOllamaError>() {
+        return Some((error.clone(), true));
+    }
+    let error = if let Some(error) = cause.downcast_ref::<rusqlite::Error>() {
+        LibreOllamaError::DatabaseQuery { message: error.to_string(), query_type: "sqlite_operation".to_string() }
+    } else if let Some(error) = cause.downcast_ref::<std::io::Error>() {
+        LibreOllamaError::FileSystem { message: error.to_string(), path: None }
+    } else if let Some(error) = cause.downcast_ref::<serde_json::Error>() {
+        LibreOllamaError::Serialization { message: error.to_string(), data_type: "json".to_string() }
+    } else if let Some(error) = cause.downcast_ref::<reqwest::Error>() {
+        if error.is_timeout() {
+            LibreOllamaError::Timeout { operation: "http_request".to_string(), duration_ms: None }
+        } else {
+            LibreOllamaError::Network { message: error.to_string(), url: error.url().map(|u| u.to_string()) }
+        }
+    } else {
+        return None;
+    };
+    Some((error, false))
+}
+
+impl From<rusqlite::Error> for CommandError {
+    fn from(error: rusqlite::Error) -> Self {
+        LibreOllamaError::from(error).into()
+    }
+}
+
+impl From<serde_json::Error> for CommandError {
+    fn from(error: serde_json::Error) -> Self {
+        LibreOllamaError::from(error).into()
+    }
+}
+
+impl From<std::io::Error> for CommandError {
+    fn from(error: std::io::Error) -> Self {
+        LibreOllamaError::from(error).into()
+    }
+}
+
+impl From<reqwest::Error> for CommandError {
+    fn from(error: reqwest::Error) -> Self {
+        LibreOllamaError::from(error).into()
+    }
+}
+
+/// A `spawn_blocking` task panicked or was cancelled
+impl From<tokio::task::JoinError> for CommandError {
+    fn from(error: tokio::task::JoinError) -> Self {
+        LibreOllamaError::Internal { message: format!("Background task failed: {}", error) }.into()
+    }
+}
+
+/// Fixed messages only: formatted ones go through a `LibreOllamaError`
+impl From<&str> for CommandError {
+    fn from(message: &str) -> Self {
+        Self::message(message)
+    }
+}
+
+/// For helpers that still report errors as strings
+impl From<CommandError> for String {
+    fn from(error: CommandError) -> Self {
+        error.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limit_envelope() {
+        let error = CommandError::from(LibreOllamaError::RateLimit {
+            message: "Google API returned 429 (rateLimitExceeded)".to_string(),
+            retry_after: Some(30_000),
+        });
+        assert_eq!(error.code, "rate_limit");
+        assert_eq!(error.category, "sync");
+        assert!(error.retryable);
+        assert_eq!(error.retry_after_ms, Some(30_000));
+
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["retry_after_ms"], 30_000);
+    }
+
+    #[test]
+    fn test_plain_messages_and_anyhow() {
+        let error = CommandError::from("Invalid note ID");
+        assert_eq!((error.code.as_str(), error.user_message.as_str()), (COMMAND_FAILED, "Invalid note ID"));
+        assert!(!error.retryable);
+
+        let not_found = anyhow::Error::new(LibreOllamaError::NotFound { resource: "thread t1".to_string() });
+        assert_eq!(CommandError::from(not_found).code, "not_found");
+    }
+
+    #[test]
+    fn test_context_keeps_typed_cause() {
+        use anyhow::Context;
+
+        let rate_limited: anyhow::Result<()> = Err(LibreOllamaError::RateLimit { message: "429".to_string(), retry_after: Some(5_000) }.into());
+        let error = CommandError::from(rate_limited.context("Failed to fetch labels").unwrap_err());
+        assert_eq!((error.code.as_str(), error.retry_after_ms), ("rate_limit", Some(5_000)));
+        assert!(error.details.unwrap().starts_with("Failed to fetch labels: "));
+
+        let io: std::io::Result<()> = Err(std::io::Error::new(std::io::ErrorKind::NotFound, "missing"));
+        let error = CommandError::from(io.context("Failed to read notes.pdf").unwrap_err());
+        assert_eq!((error.code.as_str(), error.category.as_str()), ("file_system", "system"));
+        assert_eq!(error.user_message, "Failed to read notes.pdf");
+
+        let plain = CommandError::from(anyhow::anyhow!("boom").context("Failed to save"));
+        assert_eq!((plain.code.as_str(), plain.user_message.as_str()), ("internal", "Failed to save"));
+    }
+}
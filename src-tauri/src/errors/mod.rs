@@ -10,8 +10,14 @@
 use std::fmt;
 use thiserror::Error;
 use serde::{Deserialize, Serialize};
+
+use crate::services::i18n;
 // use std::collections::HashMap;  // Not currently used
 
+mod command;
+
+pub use command::{CommandError, CommandResult};
+
 /// Main error type that encompasses all possible errors in the application
 #[derive(Error, Debug, Clone, Serialize, Deserialize)]
 pub enum LibreOllamaError {
@@ -25,6 +31,15 @@ pub enum LibreOllamaError {
     #[error("Google Tasks API error: {message}")]
     GoogleTasksApi { message: String },
 
+    #[error("Google Drive API error: {message}")]
+    GoogleDriveApi { message: String, status_code: Option<u16> },
+
+    #[error("Google People API error: {message}")]
+    GooglePeopleApi { message: String, status_code: Option<u16> },
+
+    #[error("Google Calendar API error: {message}")]
+    GoogleCalendarApi { message: String, status_code: Option<u16> },
+
     #[error("Gmail token error: {message}")]
     GmailToken { message: String, token_type: String },
 
@@ -380,25 +395,53 @@ impl LibreOllamaError {
     /// Get user-friendly error message
     pub fn user_message(&self) -> String {
         match self {
-            LibreOllamaError::GmailAuth { .. } => {
-                "Gmail authentication failed. Please sign in again.".to_string()
-            }
-            LibreOllamaError::GmailApi { .. } => {
-                "Gmail service is temporarily unavailable. Please try again later.".to_string()
-            }
-            LibreOllamaError::DatabaseConnection { .. } => {
-                "Database connection failed. Please restart the application.".to_string()
-            }
-            LibreOllamaError::Network { .. } => {
-                "Network connection failed. Please check your internet connection.".to_string()
-            }
-            LibreOllamaError::RateLimit { .. } => {
-                "Rate limit exceeded. Please wait a moment before trying again.".to_string()
-            }
-            LibreOllamaError::InvalidInput { .. } => {
-                "Invalid input provided. Please check your data and try again.".to_string()
-            }
-            _ => "An unexpected error occurred. Please try again.".to_string(),
+            LibreOllamaError::GmailAuth { .. } => i18n::t("error-gmail-auth"),
+            LibreOllamaError::GmailApi { .. } => i18n::t("error-gmail-api"),
+            LibreOllamaError::DatabaseConnection { .. } => i18n::t("error-database-connection"),
+            LibreOllamaError::Network { .. } => i18n::t("error-network"),
+            LibreOllamaError::RateLimit { .. } => i18n::t("error-rate-limit"),
+            LibreOllamaError::InvalidInput { .. } => i18n::t("error-invalid-input"),
+            _ => i18n::t("error-unexpected"),
+        }
+    }
+
+    /// Stable identifier for the error kind, e.g. `rate_limit`
+    pub fn code(&self) -> &'static str {
+        match self {
+            LibreOllamaError::GmailAuth { .. } => "gmail_auth",
+            LibreOllamaError::GmailApi { .. } => "gmail_api",
+            LibreOllamaError::GoogleTasksApi { .. } => "google_tasks_api",
+            LibreOllamaError::GoogleDriveApi { .. } => "google_drive_api",
+            LibreOllamaError::GooglePeopleApi { .. } => "google_people_api",
+            LibreOllamaError::GoogleCalendarApi { .. } => "google_calendar_api",
+            LibreOllamaError::GmailToken { .. } => "gmail_token",
+            LibreOllamaError::GmailSync { .. } => "gmail_sync",
+            LibreOllamaError::GmailCompose { .. } => "gmail_compose",
+            LibreOllamaError::GmailAttachment { .. } => "gmail_attachment",
+            LibreOllamaError::DatabaseConnection { .. } => "database_connection",
+            LibreOllamaError::DatabaseQuery { .. } => "database_query",
+            LibreOllamaError::DatabaseMigration { .. } => "database_migration",
+            LibreOllamaError::DatabaseTransaction { .. } => "database_transaction",
+            LibreOllamaError::DatabaseConstraint { .. } => "database_constraint",
+            LibreOllamaError::SyncOperation { .. } => "sync_operation",
+            LibreOllamaError::RateLimit { .. } => "rate_limit",
+            LibreOllamaError::Cache { .. } => "cache",
+            LibreOllamaError::OAuth { .. } => "oauth",
+            LibreOllamaError::TokenStorage { .. } => "token_storage",
+            LibreOllamaError::Crypto { .. } => "crypto",
+            LibreOllamaError::Keyring { .. } => "keyring",
+            LibreOllamaError::Configuration { .. } => "configuration",
+            LibreOllamaError::Environment { .. } => "environment",
+            LibreOllamaError::FileSystem { .. } => "file_system",
+            LibreOllamaError::Network { .. } => "network",
+            LibreOllamaError::InvalidInput { .. } => "invalid_input",
+            LibreOllamaError::Serialization { .. } => "serialization",
+            LibreOllamaError::EmailParsing { .. } => "email_parsing",
+            LibreOllamaError::Internal { .. } => "internal",
+            LibreOllamaError::NotSupported { .. } => "not_supported",
+            LibreOllamaError::NotFound { .. } => "not_found",
+            LibreOllamaError::PermissionDenied { .. } => "permission_denied",
+            LibreOllamaError::Timeout { .. } => "timeout",
         }
     }
 
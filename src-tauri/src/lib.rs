@@ -1,37 +1,39 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 
 // Import command modules
-mod commands;
-mod database;
-mod models;
+// (commands, database, models, errors and services are public so the CLI
+// companion in src/bin can reuse them)
+pub mod commands;
+pub mod database;
+pub mod models;
 mod db;
 
 // Import foundation modules
-mod errors;
+pub mod errors;
 mod utils;
 mod config;
 
 // Import services module
-mod services;
+pub mod services;
 
 // Import setup module
 mod setup;
 
-// Re-export commands for easy access
-use commands::tasks::all_task_data::*;
-use commands::tasks::metadata::*;
-// Use the fixed sync functions instead of the broken ones
-use commands::tasks::{create_google_task, update_google_task, delete_google_task, update_google_task_list};
+// Mock Google APIs and fixtures for tests
+#[cfg(test)]
+mod testing;
 
 // Database imports
 use std::sync::Arc;
 
 // Import required services and configuration
 use crate::config::ConfigManager;
-use crate::services::gmail::{auth_service::GmailAuthService, api_service::GmailApiService, GmailCacheService, GmailSyncService};
-use crate::services::google::tasks_service::GoogleTasksService;
+use crate::services::gmail::{auth_service::GmailAuthService, api_service::GmailApiService, backfill_service::BackfillRunner, campaign_service::CampaignRunner, compose_service::GmailComposeService, shipment_service::ShipmentTracker, GmailCacheService, GmailSyncService};
+use crate::services::google::{calendar_service::GoogleCalendarService, drive_service::DriveService, people_sync::PeopleSyncService, tasks_service::GoogleTasksService};
 use crate::commands::rate_limiter::RateLimiter;
+use crate::services::request_scheduler::RequestScheduler;
 use tauri::Manager;
+use crate::errors::CommandResult;
 
 #[tauri::command]
 fn greet(name: &str) -> String {
@@ -39,59 +41,128 @@ fn greet(name: &str) -> String {
 }
 
 #[tauri::command]
-fn get_google_client_id() -> Result<String, String> {
-    match ConfigManager::new() {
-        Ok(config) => {
-            let client_id = config.oauth().client_id.clone();
-            if client_id.is_empty() {
-                // In development mode, try to get from environment variable
-                if let Ok(env_client_id) = std::env::var("GMAIL_CLIENT_ID") {
-                    if !env_client_id.is_empty() {
-                        return Ok(env_client_id);
-                    }
+fn get_google_client_id() -> CommandResult<String> {
+    let client_id = config::get_config_manager()?.oauth().client_id;
+    if client_id.is_empty() {
+        Err("Google Client ID not configured. Please set GMAIL_CLIENT_ID environment variable.".into())
+    } else {
+        Ok(client_id)
+    }
+}
+
+/// Push reloaded configuration into services that copied it at startup
+fn propagate_config_changes(app: tauri::AppHandle, config_manager: &ConfigManager) {
+    let mut updates = config_manager.subscribe();
+    tauri::async_runtime::spawn(async move {
+        let mut previous = updates.borrow_and_update().clone();
+        while updates.changed().await.is_ok() {
+            let config = updates.borrow_and_update().clone();
+            if config.network != previous.network {
+                utils::http_client::HttpClientFactory::reconfigure(&config.network);
+            }
+            if config.memory != previous.memory {
+                services::resource_budget::apply(&config.memory);
+            }
+            if config.oauth != previous.oauth {
+                if let Some(auth_service) = app.try_state::<Arc<GmailAuthService>>() {
+                    auth_service.apply_oauth_config(&config.oauth);
                 }
-                Err("Google Client ID not configured. Please set GMAIL_CLIENT_ID environment variable.".to_string())
-            } else {
-                Ok(client_id)
             }
+            previous = config;
         }
-        Err(e) => Err(format!("Failed to load configuration: {}", e))
-    }
+    });
 }
 
-/// Initialize database and return success status
-async fn init_database_system(app: tauri::AppHandle) -> Result<(), String> {
-    println!("🔧 [BACKEND-DEBUG] Starting database initialization...");
-
-    match database::init_database().await {
-        Ok(db_manager) => {
-            println!("✅ [BACKEND-SUCCESS] Database initialized successfully");
+/// Migrate the database and start the background services, off the
+/// window-creation path, then announce that the backend is ready
+async fn finish_startup(app: tauri::AppHandle, db_manager: Arc<database::DatabaseManager>) {
+    println!("🔧 [BACKEND-DEBUG] Running database migrations...");
+    let migration_db = db_manager.clone();
+    let migrated = tauri::async_runtime::spawn_blocking(move || -> anyhow::Result<i32> {
+        let conn = migration_db.get_connection()?;
+        database::schema::run_migrations(&conn)?;
+        Ok(conn.query_row("SELECT COALESCE(MAX(version), 0) FROM schema_version", [], |row| row.get(0))?)
+    })
+    .await
+    .map_err(anyhow::Error::from)
+    .and_then(|result| result);
 
-            // Test database connectivity
-            match db_manager.test_connection() {
-                Ok(true) => {
-                    println!("✅ [BACKEND-SUCCESS] Database connection test passed");
-                    app.manage(Arc::new(db_manager));
-                    Ok(())
-                }
-                Ok(false) => {
-                    let error_msg = "Database connection test failed - query returned unexpected result";
-                    eprintln!("❌ [BACKEND-ERROR] {}", error_msg);
-                    Err(error_msg.to_string())
-                }
-                Err(e) => {
-                    let error_msg = format!("Database connection test failed: {}", e);
-                    eprintln!("❌ [BACKEND-ERROR] {}", error_msg);
-                    Err(error_msg)
-                }
-            }
+    let schema_version = match migrated {
+        Ok(version) => {
+            println!("✅ [BACKEND-SUCCESS] Database migrated to schema v{}", version);
+            version
         }
         Err(e) => {
-            let error_msg = format!("Database initialization failed: {}", e);
-            eprintln!("❌ [BACKEND-ERROR] {}", error_msg);
-            Err(error_msg)
+            // Nothing may touch a half-migrated database: commands get the
+            // error instead of running, and no background loop starts
+            let error_msg = format!("Database migration failed: {:#}", e);
+            eprintln!("❌ [BACKEND-CRITICAL] {}", error_msg);
+            let failed = app.state::<services::service_registry::ServiceRegistry>().mark_ready(0, Some(error_msg));
+            app.state::<services::events::EventBus>().emit(services::events::BackendEvent::BackendReady(failed));
+            return;
         }
+    };
+
+    // Messages, notifications and digests use the chosen language
+    match db_manager.get_connection().and_then(|conn| services::i18n::apply_setting(&conn)) {
+        Ok(locale) => println!("🌐 [I18N] Locale set to {}", locale),
+        Err(e) => eprintln!("⚠️  [BACKEND-WARNING] Failed to apply the locale setting: {}", e),
     }
+    services::i18n::follow_setting(app.clone(), db_manager.clone());
+    // mailto: and webcal: links are only claimed at runtime once the user opts in
+    services::launch_args::follow_link_handlers(app.clone(), db_manager.clone());
+
+    // Initial mailbox backfills, mail syncs and mail merge campaigns pick up where they stopped
+    if let Err(e) = app.state::<BackfillRunner>().resume_interrupted() {
+        eprintln!("⚠️  [BACKEND-WARNING] Failed to resume Gmail backfill: {}", e);
+    }
+    if let Err(e) = app.state::<GmailSyncService>().resume_interrupted() {
+        eprintln!("⚠️  [BACKEND-WARNING] Failed to resume Gmail sync: {}", e);
+    }
+    if let Err(e) = app.state::<CampaignRunner>().resume_interrupted() {
+        eprintln!("⚠️  [BACKEND-WARNING] Failed to resume mail campaigns: {}", e);
+    }
+
+    // Periodic jobs run under the supervisor, which reports them to `get_background_tasks`
+    let supervisor = app.state::<services::task_supervisor::TaskSupervisor>();
+    // Shipments found in mail are polled on their carrier's tracking page
+    supervisor.register(app.state::<ShipmentTracker>().task());
+    // Edits queued while offline are pushed once Google is reachable again
+    supervisor.register(services::sync::task_queue::PendingChangeReplayer::new(db_manager.clone(), app.clone()).task());
+    // Recurring tasks completed elsewhere or past due get their next occurrence
+    supervisor.register(services::google::task_recurrence::RecurrenceScheduler::new(db_manager.clone(), app.clone()).task());
+    // Subscribed feeds are polled for new items
+    supervisor.register(services::feeds::FeedPoller::new(db_manager.clone(), app.clone()).task());
+    // Daily digest at the scheduled time
+    supervisor.register(services::daily_digest::DigestScheduler::new(db_manager.clone(), app.clone()).task());
+    // Snoozed mail returns to the inbox when it is due
+    supervisor.register(services::gmail::triage::SnoozeWaker::new(db_manager.clone(), app.clone()).task());
+    // Query planner statistics, the WAL and free pages are tidied while the user is away
+    supervisor.register(services::task_supervisor::database_maintenance(db_manager.clone()));
+    // Chat attachments are embedded while the user is away
+    supervisor.register(services::chat_attachments::EmbeddingBackfill::new(db_manager.clone()).task());
+    // Google API usage is saved every few seconds so it survives a restart
+    supervisor.register(app.state::<Arc<RequestScheduler>>().usage_task());
+    // New versions are checked for and downloaded in the background
+    supervisor.register(app.state::<services::updater::UpdateService>().task());
+    // Mail changed since the last sync is fetched for every account
+    supervisor.register(services::gmail::sync_service::background_sync_task(app.clone()));
+    // Copied links and addresses are offered for capture while the watcher is switched on
+    supervisor.register(services::clipboard_watcher::ClipboardWatcher::new(db_manager.clone(), app.clone()).task());
+    // The vault and the app lock relock after inactivity
+    supervisor.register(app.state::<services::vault::VaultService>().task());
+    supervisor.register(app.state::<services::app_lock::AppLock>().task());
+    if let Err(e) = supervisor.start(app.try_state::<services::settings::SettingsService>().map(|service| service.subscribe())) {
+        eprintln!("⚠️  [BACKEND-WARNING] Failed to start background tasks: {}", e);
+    }
+
+    // Ollama is launched if nothing answers locally, and restarted if it dies
+    app.state::<services::ollama_supervisor::OllamaSupervisor>().start_monitoring();
+    services::model_router::spawn_startup_refresh(app.clone(), db_manager);
+
+    let ready = app.state::<services::service_registry::ServiceRegistry>().mark_ready(schema_version, None);
+    println!("✅ [BACKEND-SUCCESS] Backend ready in {} ms", ready.startup_ms);
+    app.state::<services::events::EventBus>().emit(services::events::BackendEvent::BackendReady(ready));
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -99,10 +170,25 @@ pub fn run() {
     println!("🚀 [BACKEND-DEBUG] Starting LibreOllama Tauri application...");
 
     // Load environment variables from .env file
-    match dotenv::dotenv() {
-        Ok(path) => println!("✅ [BACKEND-SUCCESS] Loaded .env file from: {:?}", path),
-        Err(e) => println!("⚠️  [BACKEND-WARNING] No .env file found or failed to load: {}", e),
-    }
+    let env_file = match dotenv::dotenv() {
+        Ok(path) => {
+            println!("✅ [BACKEND-SUCCESS] Loaded .env file from: {:?}", path);
+            Some(path)
+        }
+        Err(e) => {
+            println!("⚠️  [BACKEND-WARNING] No .env file found or failed to load: {}", e);
+            None
+        }
+    };
+
+    // Loaded before anything reads it; reloadable while the app runs
+    let config_manager = config::init_config_manager(config::ConfigSources {
+        env_file,
+        ..Default::default()
+    });
+
+    // Panics are saved as local crash reports
+    services::crash_reports::install(services::crash_reports::crashes_dir(&config_manager.paths().logs_dir));
 
     // CRITICAL FIX: Enable WebView2 hardware acceleration for canvas rendering
     std::env::set_var("WEBVIEW2_ADDITIONAL_BROWSER_ARGUMENTS", 
@@ -110,24 +196,71 @@ pub fn run() {
 
     println!("🎨 [BACKEND-DEBUG] WebView2 hardware acceleration enabled for canvas rendering");
 
-    tauri::Builder::default()
-        .setup(|app| {
-            let rt = tokio::runtime::Runtime::new().expect("Failed to create async runtime");
-            rt.block_on(async {
-                if let Err(e) = init_database_system(app.handle().clone()).await {
-                    eprintln!("❌ [BACKEND-CRITICAL] Failed to initialize database: {}", e);
-                }
-            });
+    let builder = tauri::Builder::default();
+
+    // A second launch focuses this window and hands over its arguments
+    #[cfg(desktop)]
+    let builder = builder.plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+        services::launch_args::focus_main_window(app);
+        services::launch_args::handle_args(app, &argv);
+    }));
+
+    // mailto: and webcal: links; registered after single-instance so links
+    // opened on Linux and Windows reach the running copy as arguments
+    #[cfg(desktop)]
+    let builder = builder.plugin(tauri_plugin_deep_link::init());
+
+    // Updates are checked against the release channel from settings
+    #[cfg(desktop)]
+    let builder = builder.plugin(tauri_plugin_updater::Builder::new().build());
+
+    // Files picked in dialogs join its scope; see `services::file_access`
+    let builder = builder.plugin(tauri_plugin_fs::init());
+
+    builder
+        .setup(move |app| {
+            let started_at = std::time::Instant::now();
+            services::resource_budget::apply(&config_manager.memory());
+            config_manager.start_watching();
+            propagate_config_changes(app.handle().clone(), &config_manager);
 
-            let db_manager: tauri::State<Arc<database::DatabaseManager>> = app.state();
-            let db_manager_arc = db_manager.inner().clone();
+            // Only opened here; migrations run in `finish_startup` so they
+            // do not hold up the window
+            let db_manager_arc = Arc::new(database::DatabaseManager::open().map_err(|e| {
+                eprintln!("❌ [BACKEND-CRITICAL] Failed to open database: {}", e);
+                e
+            })?);
+            app.manage(db_manager_arc.clone());
+
+            app.manage(services::events::EventBus::new(app.handle().clone()));
+
+            // Background jobs check in with this so quitting waits for their checkpoints
+            let shutdown = services::shutdown::ShutdownCoordinator::new();
+            app.manage(shutdown.clone());
+            // Heavy background work waits for the user to go idle
+            let activity = services::activity::ActivityMonitor::default();
+            app.manage(activity.clone());
+            app.manage(services::task_supervisor::TaskSupervisor::new(shutdown.clone(), activity.clone()));
+
+            // mailto: links and --capture from this launch wait for the frontend
+            app.manage(services::launch_args::LaunchQueue::new());
+            services::launch_args::handle_args(app.handle(), &std::env::args().collect::<Vec<_>>());
+
+            // macOS opens links and files as events, not arguments
+            #[cfg(target_os = "macos")]
+            {
+                use tauri_plugin_deep_link::DeepLinkExt;
+                let handle = app.handle().clone();
+                app.deep_link().on_open_url(move |event| {
+                    let urls: Vec<String> = event.urls().iter().map(|url| url.to_string()).collect();
+                    services::launch_args::focus_main_window(&handle);
+                    services::launch_args::handle_urls(&handle, &urls);
+                });
+            }
 
             let gmail_cache_service = GmailCacheService::new(db_manager_arc.clone());
             app.manage(gmail_cache_service);
 
-            let gmail_sync_service = GmailSyncService::new(db_manager_arc.clone());
-            app.manage(gmail_sync_service);
-            
             let encryption_key = [0u8; 32]; // Placeholder key
             let auth_service = GmailAuthService::new(db_manager_arc.clone(), encryption_key).expect("Failed to create auth service");
             app.manage(Arc::new(auth_service));
@@ -135,13 +268,56 @@ pub fn run() {
             let auth_service_state: tauri::State<Arc<GmailAuthService>> = app.state();
             let google_tasks_service = GoogleTasksService::new(auth_service_state.inner().clone(), db_manager_arc.clone());
             app.manage(google_tasks_service);
+            app.manage(GoogleCalendarService::new(auth_service_state.inner().clone(), db_manager_arc.clone()));
+
+            let drive_service = DriveService::new(auth_service_state.inner().clone(), db_manager_arc.clone());
+            app.manage(drive_service);
+
+            let people_sync_service = PeopleSyncService::new(auth_service_state.inner().clone(), db_manager_arc.clone());
+            app.manage(people_sync_service);
             
             // Initialize rate limiter for Gmail API
-            let rate_limiter = Arc::new(tokio::sync::Mutex::new(RateLimiter::new(crate::commands::rate_limiter::RateLimitConfig::default())));
+            // One scheduler shares the request budget between interactive and background work
+            let rate_limit_config = crate::commands::rate_limiter::RateLimitConfig::default();
+            let request_scheduler = Arc::new(RequestScheduler::with_usage_store(&rate_limit_config, db_manager_arc.clone()));
+            app.manage(request_scheduler.clone());
+            crate::services::request_scheduler::install(request_scheduler.clone());
+            let rate_limiter = Arc::new(RateLimiter::new(rate_limit_config).with_scheduler(request_scheduler));
             
             // Initialize Gmail API service
-            let gmail_api_service = GmailApiService::new(auth_service_state.inner().clone(), db_manager_arc.clone(), rate_limiter);
-            app.manage(Arc::new(gmail_api_service));
+            let gmail_api_service = Arc::new(GmailApiService::new(auth_service_state.inner().clone(), db_manager_arc.clone(), rate_limiter.clone()));
+            app.manage(gmail_api_service.clone());
+
+            // Full and incremental Gmail sync into the local message store
+            app.manage(GmailSyncService::new(db_manager_arc.clone(), gmail_api_service.clone(), app.handle().clone()));
+
+            // Initial mailbox backfill runs in the background below the interactive rate budget
+            app.manage(BackfillRunner::new(db_manager_arc.clone(), gmail_api_service, shutdown.clone(), activity, app.handle().clone()));
+
+            // Initialize Gmail compose service (sending and drafts)
+            let gmail_compose_service = Arc::new(GmailComposeService::new(auth_service_state.inner().clone(), db_manager_arc.clone(), rate_limiter));
+            app.manage(gmail_compose_service.clone());
+
+            // Mail merge campaigns send through the compose service
+            app.manage(CampaignRunner::new(db_manager_arc.clone(), gmail_compose_service, shutdown.clone()));
+
+            // Setting changes reach the background loops without a restart
+            app.manage(services::settings::SettingsService::new(db_manager_arc.clone(), app.handle().clone()));
+            app.manage(ShipmentTracker::new(db_manager_arc.clone(), app.handle().clone()));
+            // New mail is announced under the quiet hours and VIP rules
+            app.manage(services::notification::NotificationService::new(db_manager_arc.clone()));
+
+            // Secure notes vault and app lock; the lock is engaged from the
+            // start when a passphrase is set
+            app.manage(services::vault::VaultService::new(db_manager_arc.clone(), app.handle().clone()));
+            app.manage(services::app_lock::AppLock::new(db_manager_arc.clone(), app.handle().clone()));
+            app.manage(services::secure_wipe::SecureWipe::new());
+            app.manage(services::ollama_supervisor::OllamaSupervisor::new(db_manager_arc.clone()));
+            app.manage(services::updater::UpdateService::new(db_manager_arc.clone(), app.handle().clone()));
+
+            // Prompt templates, chat and the canvas assistant are built on first use
+            app.manage(services::service_registry::ServiceRegistry::new(db_manager_arc.clone(), started_at));
+            tauri::async_runtime::spawn(finish_startup(app.handle().clone(), db_manager_arc));
             
             // Configure webview to disable context menus
             if let Err(e) = setup::configure_webview(app) {
@@ -150,96 +326,19 @@ pub fn run() {
             
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
-            greet,
-            get_google_client_id,
-            // Gmail authentication commands
-            commands::gmail::auth::start_gmail_oauth_with_callback,
-            commands::gmail::auth::get_gmail_accounts_secure,
-            commands::gmail::auth::get_gmail_user_info,
-            commands::gmail::auth::store_gmail_tokens_secure,
-            commands::gmail::auth::get_gmail_tokens_secure,
-            commands::gmail::auth::remove_gmail_account_secure,
-            // Gmail API commands
-            commands::gmail::api::search_gmail_messages,
-            commands::gmail::api::get_gmail_labels,
-            commands::gmail::api::get_gmail_message,
-            commands::gmail::api::get_parsed_gmail_message,
-            commands::gmail::api::get_gmail_thread,
-            commands::gmail::api::modify_gmail_messages,
-            commands::gmail::api::trash_gmail_messages,
-            commands::gmail::api::get_gmail_attachment,
-            // Project commands
-            commands::projects::get_projects,
-            // Agent commands
-            commands::agents::lifecycle::get_agents,
-            // Task commands
-            get_task_metadata,
-            create_task_metadata,
-            update_task_metadata,
-            delete_task_metadata,
-            get_all_labels,
-            get_all_task_data,
-            create_google_task,
-            update_google_task,
-            delete_google_task,
-            update_google_task_list,
-            // Calendar commands
-            commands::calendar::get_calendars,
-            commands::calendar::get_calendar_events,
-            commands::calendar::create_calendar_event,
-            commands::calendar::update_calendar_event,
-            commands::calendar::delete_calendar_event,
-            // Chat commands
-            commands::chat::create_session,
-            commands::chat::get_sessions,
-            commands::chat::send_message,
-            commands::chat::get_session_messages,
-            commands::chat::get_database_stats,
-            commands::chat::delete_session,
-            commands::chat::delete_session_v4,
-            commands::chat::update_session_title,
-            // Text processing commands
-            commands::text_processing::clean_text,
-            // Ollama commands
-            commands::ollama::ollama_health_check,
-            commands::ollama::ollama_get_status,
-            commands::ollama::ollama_start_sidecar,
-            commands::ollama::ollama_stop_sidecar,
-            commands::ollama::ollama_list_models,
-            commands::ollama::ollama_get_model_info,
-            commands::ollama::ollama_delete_model,
-            commands::ollama::ollama_pull_model,
-            commands::ollama::ollama_chat,
-            commands::ollama::ollama_chat_stream,
-            commands::ollama::ollama_generate,
-            // LLM commands
-            commands::llm::llm_chat_openai,
-            commands::llm::llm_list_openai_models,
-            commands::llm::llm_chat_anthropic,
-            commands::llm::llm_list_anthropic_models,
-            commands::llm::llm_chat_openrouter,
-            commands::llm::llm_list_openrouter_models,
-            commands::llm::llm_chat_deepseek,
-            commands::llm::llm_list_deepseek_models,
-            commands::llm::llm_chat_mistral,
-            commands::llm::llm_list_mistral_models,
-            commands::llm::llm_chat_gemini,
-            commands::llm::llm_list_gemini_models,
-            // Notes commands
-            commands::notes::get_notes,
-            commands::notes::create_note,
-            commands::notes::update_note,
-            commands::notes::delete_note,
-            // Folders commands
-            commands::folders::get_folders,
-            commands::folders::create_folder,
-            commands::folders::update_folder,
-            commands::folders::delete_folder,
-            // System commands
-            commands::system::force_run_migrations,
-            commands::system::debug_check_timeblock_data,
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::Focused(focused) = event {
+                window.state::<services::activity::ActivityMonitor>().set_focused(*focused);
+            }
+        })
+        .invoke_handler(services::app_lock::guard(services::service_registry::gate(commands::registry::invoke_handler())))
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Finish running work, flush queues and close the database, and
+            // don't leave a server we launched running after the app quits
+            if let tauri::RunEvent::Exit = event {
+                tauri::async_runtime::block_on(services::shutdown::shutdown(app));
+            }
+        });
 }
\ No newline at end of file
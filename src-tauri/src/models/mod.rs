@@ -1,2 +1,3 @@
 pub mod task_metadata;
 pub mod task_id_map;
+pub mod task_board;
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskBoard {
+    pub id: String,
+    pub account_id: Option<String>,
+    pub name: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardColumn {
+    pub id: String,
+    pub board_id: String,
+    pub title: String,
+    pub color: Option<String>,
+    pub position: i32,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardCard {
+    pub board_id: String,
+    pub column_id: String,
+    pub google_task_id: String,
+    pub position: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardColumnWithCards {
+    #[serde(flatten)]
+    pub column: BoardColumn,
+    pub task_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskBoardWithColumns {
+    #[serde(flatten)]
+    pub board: TaskBoard,
+    pub columns: Vec<BoardColumnWithCards>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateBoardColumn {
+    pub title: String,
+    pub color: Option<String>,
+}
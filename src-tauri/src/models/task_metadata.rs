@@ -1,10 +1,31 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 
+use crate::utils::time;
+
+/// Stored as UTC RFC 3339 with the zone it was planned in
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TimeBlock {
     pub start_time: String,
     pub end_time: String,
+    /// IANA zone; local times sent without an offset are read in it
+    #[serde(default)]
+    pub time_zone: Option<String>,
+}
+
+impl TimeBlock {
+    /// The block with both ends in UTC, reading times without an offset in
+    /// the block's own zone or else `zone`. Unparseable times are kept.
+    pub fn in_utc(&self, zone: &Tz) -> TimeBlock {
+        let zone = self.time_zone.as_deref().and_then(time::parse_zone).unwrap_or(*zone);
+        let utc = |value: &str| time::to_utc(value, &zone).map(time::utc_rfc3339).unwrap_or_else(|| value.to_string());
+        TimeBlock {
+            start_time: utc(&self.start_time),
+            end_time: utc(&self.end_time),
+            time_zone: Some(zone.name().to_string()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,359 @@
+//! Mail account detection
+//!
+//! During onboarding the app looks for mail accounts already set up on this
+//! machine, so adding one is a click instead of typing an address:
+//!
+//! - Thunderbird profiles listed in `profiles.ini`, read from `prefs.js`
+//! - On Windows, Outlook profiles in the registry, and the Microsoft
+//!   accounts Windows Mail signs in with
+//!
+//! Nothing is read beyond addresses, names and server settings; passwords
+//! and tokens stay where they are. Google-hosted accounts are connected
+//! through OAuth with the address prefilled. For the rest, mail the other
+//! client keeps locally (a Thunderbird folder, an Outlook data file) can be
+//! imported as an archive without signing in anywhere.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+const GOOGLE_DOMAINS: &[&str] = &["gmail.com", "googlemail.com"];
+/// Thunderbird server types that hold mail; local folders, feeds and news are skipped
+const THUNDERBIRD_MAIL_SERVERS: &[&str] = &["imap", "pop3"];
+
+/// Local mail that can be imported as an archive account
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetectedImport {
+    /// "thunderbird" or "outlook", matching the import commands
+    pub kind: String,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetectedAccount {
+    pub email: String,
+    pub display_name: Option<String>,
+    /// "thunderbird", "outlook" or "windows"
+    pub source: String,
+    /// "imap", "pop3" or "exchange", when the source says
+    pub protocol: Option<String>,
+    pub incoming_server: Option<String>,
+    /// Can be connected through Google sign-in
+    pub is_google: bool,
+    /// Already connected to this app
+    pub already_connected: bool,
+    pub import: Option<DetectedImport>,
+}
+
+impl DetectedAccount {
+    fn new(source: &str, email: &str) -> Self {
+        Self {
+            email: email.trim().to_string(),
+            display_name: None,
+            source: source.to_string(),
+            protocol: None,
+            incoming_server: None,
+            is_google: false,
+            already_connected: false,
+            import: None,
+        }
+    }
+}
+
+fn is_email(text: &str) -> bool {
+    text.split_once('@').is_some_and(|(user, domain)| !user.is_empty() && domain.contains('.'))
+}
+
+fn is_google(account: &DetectedAccount) -> bool {
+    let domain = account.email.rsplit('@').next().unwrap_or_default().to_ascii_lowercase();
+    GOOGLE_DOMAINS.contains(&domain.as_str())
+        || account.incoming_server.as_deref().is_some_and(|server| {
+            let server = server.to_ascii_lowercase();
+            GOOGLE_DOMAINS.iter().any(|domain| server.ends_with(&format!(".{}", domain)))
+        })
+}
+
+/// Profile directories listed in a Thunderbird `profiles.ini`
+pub fn parse_profiles_ini(text: &str, base: &Path) -> Vec<PathBuf> {
+    let mut profiles = Vec::new();
+    let mut section: HashMap<String, String> = HashMap::new();
+    let mut in_profile = false;
+    let mut finish = |section: &mut HashMap<String, String>, in_profile: bool| {
+        if let Some(path) = section.get("Path").filter(|_| in_profile) {
+            let relative = section.get("IsRelative").is_none_or(|value| value == "1");
+            profiles.push(if relative { base.join(path) } else { PathBuf::from(path) });
+        }
+        section.clear();
+    };
+    for line in text.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            finish(&mut section, in_profile);
+            in_profile = name.starts_with("Profile");
+        } else if let Some((key, value)) = line.split_once('=') {
+            section.insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+    finish(&mut section, in_profile);
+    profiles
+}
+
+/// The `user_pref("key", value);` lines of a Thunderbird `prefs.js`
+pub fn parse_prefs_js(text: &str) -> HashMap<String, String> {
+    let mut prefs = HashMap::new();
+    for line in text.lines().map(str::trim) {
+        let Some(args) = line.strip_prefix("user_pref(").and_then(|rest| rest.strip_suffix(");")) else {
+            continue;
+        };
+        let Some((key, value)) = args.split_once(',') else { continue };
+        let unquote = |text: &str| {
+            let text = text.trim();
+            match text.strip_prefix('"').and_then(|rest| rest.strip_suffix('"')) {
+                Some(inner) => inner.replace("\\\"", "\"").replace("\\\\", "\\"),
+                None => text.to_string(),
+            }
+        };
+        prefs.insert(unquote(key), unquote(value));
+    }
+    prefs
+}
+
+/// Mail accounts configured in one Thunderbird profile
+pub fn thunderbird_accounts(profile: &Path, prefs: &HashMap<String, String>) -> Vec<DetectedAccount> {
+    let pref = |key: String| prefs.get(&key).map(String::as_str).filter(|value| !value.is_empty());
+    let account_ids = pref("mail.accountmanager.accounts".to_string()).unwrap_or_default();
+
+    let mut accounts = Vec::new();
+    for account_id in account_ids.split(',').map(str::trim).filter(|id| !id.is_empty()) {
+        let Some(server) = pref(format!("mail.account.{}.server", account_id)) else { continue };
+        let protocol = pref(format!("mail.server.{}.type", server)).unwrap_or_default();
+        if !THUNDERBIRD_MAIL_SERVERS.contains(&protocol) {
+            continue;
+        }
+        let identity = pref(format!("mail.account.{}.identities", account_id))
+            .and_then(|identities| identities.split(',').next())
+            .map(str::trim);
+        let user_name = pref(format!("mail.server.{}.userName", server));
+        let email = identity
+            .and_then(|identity| pref(format!("mail.identity.{}.useremail", identity)))
+            .or(user_name.filter(|name| is_email(name)));
+        let Some(email) = email.filter(|email| is_email(email)) else { continue };
+
+        let mut account = DetectedAccount::new("thunderbird", email);
+        account.display_name = identity.and_then(|identity| pref(format!("mail.identity.{}.fullName", identity))).map(str::to_string);
+        account.protocol = Some(protocol.to_string());
+        account.incoming_server = pref(format!("mail.server.{}.hostname", server)).map(str::to_string);
+
+        // Mail kept offline lives in the server's directory
+        let directory = pref(format!("mail.server.{}.directory-rel", server))
+            .and_then(|relative| relative.strip_prefix("[ProfD]"))
+            .map(|relative| profile.join(relative))
+            .or_else(|| pref(format!("mail.server.{}.directory", server)).map(PathBuf::from));
+        account.import = directory.filter(|dir| dir.is_dir()).map(|dir| DetectedImport {
+            kind: "thunderbird".to_string(),
+            path: dir.display().to_string(),
+        });
+        accounts.push(account);
+    }
+    accounts
+}
+
+/// Where Thunderbird keeps `profiles.ini` on this platform
+fn thunderbird_roots() -> Vec<PathBuf> {
+    let home = dirs::home_dir().unwrap_or_default();
+    if cfg!(windows) {
+        dirs::config_dir().map(|dir| dir.join("Thunderbird")).into_iter().collect()
+    } else if cfg!(target_os = "macos") {
+        vec![home.join("Library/Thunderbird")]
+    } else {
+        vec![
+            home.join(".thunderbird"),
+            home.join("snap/thunderbird/common/.thunderbird"),
+            home.join(".var/app/org.mozilla.Thunderbird/.thunderbird"),
+        ]
+    }
+}
+
+fn detect_thunderbird() -> Vec<DetectedAccount> {
+    let mut accounts = Vec::new();
+    for root in thunderbird_roots() {
+        let Ok(ini) = std::fs::read_to_string(root.join("profiles.ini")) else { continue };
+        for profile in parse_profiles_ini(&ini, &root) {
+            let Ok(prefs) = std::fs::read_to_string(profile.join("prefs.js")) else { continue };
+            accounts.extend(thunderbird_accounts(&profile, &parse_prefs_js(&prefs)));
+        }
+    }
+    accounts
+}
+
+#[cfg(windows)]
+mod windows {
+    //! Outlook profiles and Windows sign-ins from the registry
+
+    use std::path::PathBuf;
+
+    use winreg::enums::{RegType, HKEY_CURRENT_USER};
+    use winreg::RegKey;
+
+    use super::{is_email, DetectedAccount, DetectedImport};
+
+    const OFFICE_VERSIONS: &[&str] = &["16.0", "15.0"];
+    /// Subkey of an Outlook profile holding one key per mail account
+    const OUTLOOK_ACCOUNTS_KEY: &str = "9375CFF0413111d3B88A00104B2A6676";
+    const WINDOWS_ACCOUNTS_KEY: &str = r"Software\Microsoft\IdentityCRL\UserExtendedProperties";
+
+    /// Outlook stores most account values as UTF-16 in binary values
+    fn text(key: &RegKey, name: &str) -> Option<String> {
+        let value = key.get_raw_value(name).ok()?;
+        let text = match value.vtype {
+            RegType::REG_SZ | RegType::REG_BINARY => {
+                let units: Vec<u16> = value.bytes.chunks_exact(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect();
+                String::from_utf16_lossy(&units)
+            }
+            _ => return None,
+        };
+        Some(text.trim_end_matches('\0').trim().to_string()).filter(|text| !text.is_empty())
+    }
+
+    /// An Outlook data file named after the account, which is how Outlook
+    /// names them by default
+    fn outlook_data_file(email: &str) -> Option<PathBuf> {
+        let documents = dirs::document_dir().map(|dir| dir.join("Outlook Files"));
+        let local = dirs::data_local_dir().map(|dir| dir.join("Microsoft").join("Outlook"));
+        [documents, local]
+            .into_iter()
+            .flatten()
+            .flat_map(|dir| ["pst", "ost"].map(|ext| dir.join(format!("{}.{}", email, ext))))
+            .find(|path| path.is_file())
+    }
+
+    pub fn detect() -> Vec<DetectedAccount> {
+        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+        let mut accounts = Vec::new();
+
+        for version in OFFICE_VERSIONS {
+            let path = format!(r"Software\Microsoft\Office\{}\Outlook\Profiles", version);
+            let Ok(profiles) = hkcu.open_subkey(&path) else { continue };
+            for profile in profiles.enum_keys().flatten() {
+                let Ok(list) = profiles.open_subkey(format!(r"{}\{}", profile, OUTLOOK_ACCOUNTS_KEY)) else { continue };
+                for entry in list.enum_keys().flatten() {
+                    let Ok(key) = list.open_subkey(&entry) else { continue };
+                    let Some(email) = text(&key, "Email").or_else(|| text(&key, "Account Name")).filter(|email| is_email(email)) else {
+                        continue;
+                    };
+                    let mut account = DetectedAccount::new("outlook", &email);
+                    account.display_name = text(&key, "Display Name");
+                    (account.protocol, account.incoming_server) = match (text(&key, "IMAP Server"), text(&key, "POP3 Server")) {
+                        (Some(server), _) => (Some("imap".to_string()), Some(server)),
+                        (None, Some(server)) => (Some("pop3".to_string()), Some(server)),
+                        (None, None) => (Some("exchange".to_string()), None),
+                    };
+                    account.import = outlook_data_file(&email).map(|path| DetectedImport {
+                        kind: "outlook".to_string(),
+                        path: path.display().to_string(),
+                    });
+                    accounts.push(account);
+                }
+            }
+        }
+
+        // Microsoft accounts signed in to Windows, which Windows Mail uses
+        if let Ok(signed_in) = hkcu.open_subkey(WINDOWS_ACCOUNTS_KEY) {
+            accounts.extend(signed_in.enum_keys().flatten().filter(|email| is_email(email)).map(|email| DetectedAccount::new("windows", &email)));
+        }
+        accounts
+    }
+}
+
+/// Addresses of the Google accounts already connected
+fn connected_addresses(conn: &Connection) -> HashSet<String> {
+    conn.prepare("SELECT email_address FROM gmail_accounts_secure WHERE is_active = 1")
+        .and_then(|mut stmt| stmt.query_map([], |row| row.get::<_, String>(0))?.collect::<rusqlite::Result<HashSet<_>>>())
+        .map(|emails| emails.into_iter().map(|email| email.to_lowercase()).collect())
+        .unwrap_or_default()
+}
+
+/// One entry per address: the first source to report it wins, later ones
+/// fill in what it lacked
+pub fn merge(found: Vec<DetectedAccount>, connected: &HashSet<String>) -> Vec<DetectedAccount> {
+    let mut merged: BTreeMap<String, DetectedAccount> = BTreeMap::new();
+    for account in found {
+        let key = account.email.to_lowercase();
+        match merged.get_mut(&key) {
+            Some(existing) => {
+                existing.display_name = existing.display_name.take().or(account.display_name);
+                existing.protocol = existing.protocol.take().or(account.protocol);
+                existing.incoming_server = existing.incoming_server.take().or(account.incoming_server);
+                existing.import = existing.import.take().or(account.import);
+            }
+            None => {
+                merged.insert(key, account);
+            }
+        }
+    }
+    merged
+        .into_iter()
+        .map(|(key, mut account)| {
+            account.is_google = is_google(&account);
+            account.already_connected = connected.contains(&key);
+            account
+        })
+        .collect()
+}
+
+/// Mail accounts found on this machine, by address
+pub fn detect_accounts(conn: &Connection) -> Vec<DetectedAccount> {
+    #[allow(unused_mut)]
+    let mut found = detect_thunderbird();
+    #[cfg(windows)]
+    found.extend(windows::detect());
+    merge(found, &connected_addresses(conn))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thunderbird_accounts() {
+        let base = Path::new("/home/ada/.thunderbird");
+        let ini = "[General]\nStartWithLastProfile=1\n\n[Profile0]\nName=default\nIsRelative=1\nPath=abcd.default\n\n[Profile1]\nIsRelative=0\nPath=/mnt/old/profile\n";
+        assert_eq!(parse_profiles_ini(ini, base), vec![base.join("abcd.default"), PathBuf::from("/mnt/old/profile")]);
+
+        let prefs = parse_prefs_js(r#"
+            user_pref("mail.accountmanager.accounts", "account1,account2,account3");
+            user_pref("mail.account.account1.identities", "id1");
+            user_pref("mail.account.account1.server", "server1");
+            user_pref("mail.server.server1.type", "imap");
+            user_pref("mail.server.server1.hostname", "imap.gmail.com");
+            user_pref("mail.server.server1.directory-rel", "[ProfD]ImapMail/imap.gmail.com");
+            user_pref("mail.identity.id1.useremail", "ada@gmail.com");
+            user_pref("mail.identity.id1.fullName", "Ada \"Countess\" Lovelace");
+            user_pref("mail.account.account2.server", "server2");
+            user_pref("mail.server.server2.type", "none");
+            user_pref("mail.account.account3.server", "server3");
+            user_pref("mail.server.server3.type", "pop3");
+            user_pref("mail.server.server3.hostname", "pop.example.org");
+            user_pref("mail.server.server3.userName", "ada@example.org");
+            user_pref("mail.server.server3.check_new_mail", true);
+        "#);
+        let accounts = thunderbird_accounts(&base.join("abcd.default"), &prefs);
+        assert_eq!(accounts.len(), 2);
+        assert_eq!(accounts[0].email, "ada@gmail.com");
+        assert_eq!(accounts[0].display_name.as_deref(), Some("Ada \"Countess\" Lovelace"));
+        assert_eq!(accounts[1].email, "ada@example.org");
+        assert_eq!(accounts[1].protocol.as_deref(), Some("pop3"));
+
+        let connected: HashSet<String> = ["ada@gmail.com".to_string()].into();
+        let mut outlook = DetectedAccount::new("outlook", "Ada@Example.org");
+        outlook.display_name = Some("Ada".to_string());
+        let merged = merge(accounts.into_iter().chain([outlook]).collect(), &connected);
+        assert_eq!(merged.len(), 2);
+        let (example, gmail) = (&merged[0], &merged[1]);
+        assert!(gmail.is_google && gmail.already_connected);
+        assert!(!example.is_google && !example.already_connected);
+        assert_eq!(example.source, "thunderbird");
+        assert_eq!(example.display_name.as_deref(), Some("Ada"));
+    }
+}
@@ -0,0 +1,121 @@
+//! User activity tracking
+//!
+//! Heavy background work (backfill pages, database maintenance, embedding
+//! chat attachments) waits for the user to be idle so it does not compete
+//! with the UI. The user counts as idle while no window has focus, or once
+//! `IDLE_AFTER` has passed without input. Both signals come from the app
+//! itself: window focus events, and key and pointer input the frontend
+//! reports through `report_user_activity`. Commands alone are not activity,
+//! since the frontend also invokes them on timers.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::sync::watch;
+
+/// Time without input after which a focused window counts as idle
+pub const IDLE_AFTER: Duration = Duration::from_secs(2 * 60);
+
+/// Whether the user is active, managed as Tauri state
+#[derive(Clone)]
+pub struct ActivityMonitor {
+    focused: Arc<watch::Sender<bool>>,
+    last_activity: Arc<Mutex<Instant>>,
+    idle_after: Duration,
+}
+
+impl Default for ActivityMonitor {
+    fn default() -> Self {
+        Self::new(IDLE_AFTER)
+    }
+}
+
+impl ActivityMonitor {
+    /// Starts focused and active, as the window opens in front
+    pub fn new(idle_after: Duration) -> Self {
+        Self {
+            focused: Arc::new(watch::channel(true).0),
+            last_activity: Arc::new(Mutex::new(Instant::now())),
+            idle_after,
+        }
+    }
+
+    pub fn record_activity(&self) {
+        *self.last_activity.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+    }
+
+    pub fn set_focused(&self, focused: bool) {
+        self.focused.send_if_modified(|current| std::mem::replace(current, focused) != focused);
+        if focused {
+            self.record_activity();
+        }
+    }
+
+    /// How long until the user counts as idle; zero once they are
+    fn until_idle(&self) -> Duration {
+        if !*self.focused.borrow() {
+            return Duration::ZERO;
+        }
+        let since = self.last_activity.lock().unwrap_or_else(|e| e.into_inner()).elapsed();
+        self.idle_after.saturating_sub(since)
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.until_idle().is_zero()
+    }
+
+    /// Resolves once the user is idle
+    pub async fn idle(&self) {
+        let mut focus = self.focused.subscribe();
+        loop {
+            let remaining = self.until_idle();
+            if remaining.is_zero() {
+                return;
+            }
+            // Losing focus ends the wait early; activity pushes it back,
+            // which the next pass sees
+            tokio::select! {
+                _ = tokio::time::sleep(remaining) => {}
+                _ = focus.changed() => {}
+            }
+        }
+    }
+
+    /// Wait for the user to be idle, but no longer than `max_defer`, so
+    /// heavy work is put off rather than starved. Returns whether the user
+    /// went idle.
+    pub async fn idle_or_after(&self, max_defer: Duration) -> bool {
+        tokio::time::timeout(max_defer, self.idle()).await.is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_idle_after_blur_or_inactivity() {
+        let activity = ActivityMonitor::new(Duration::from_millis(200));
+        assert!(!activity.is_idle());
+        assert!(!activity.idle_or_after(Duration::from_millis(10)).await);
+
+        // Inactivity
+        assert!(activity.idle_or_after(Duration::from_secs(1)).await);
+        activity.record_activity();
+        assert!(!activity.is_idle());
+
+        // Blur ends a wait straight away
+        let waiting = tokio::spawn({
+            let activity = activity.clone();
+            async move { activity.idle_or_after(Duration::from_secs(1)).await }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        activity.set_focused(false);
+        let started = Instant::now();
+        assert!(waiting.await.unwrap());
+        assert!(started.elapsed() < Duration::from_millis(100));
+
+        activity.set_focused(true);
+        assert!(!activity.is_idle());
+    }
+}
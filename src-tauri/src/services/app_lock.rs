@@ -0,0 +1,393 @@
+//! Application lock
+//!
+//! Once a lock passphrase is set, the app starts locked and locks again
+//! on request or after `SecurityConfig::session_timeout_minutes` without a
+//! command being invoked (0 turns the timer off). While locked, every
+//! command except the lock screen's own is refused before it runs, so no
+//! data leaves the backend. Locking also locks the secure notes vault and
+//! empties the in-memory caches of mail content.
+//!
+//! While locked, backend events other than the lock screen's own are
+//! dropped instead of reaching the WebView. A lock state that cannot be
+//! read counts as locked.
+//!
+//! Wrong passphrases are throttled: after a few free tries each further
+//! attempt waits twice as long as the last, and the wait is stored so
+//! restarting the app does not reset it.
+//!
+//! Only an Argon2 hash of the passphrase is kept, in `user_preferences`.
+//! Tauri's biometric plugin covers iOS and Android only, so on the desktop
+//! the passphrase is the only way to unlock; `biometric_available` reports
+//! this to the lock screen.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use rand::{rngs::OsRng, RngCore};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tauri::ipc::Invoke;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::config::get_config_manager;
+use crate::database::operations::preference_operations;
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::events::{AppLockChangedEvent, BackendEvent, EventBus};
+use crate::services::resource_budget;
+use crate::services::task_supervisor::TaskSpec;
+use crate::services::vault::{VaultService, MIN_PASSPHRASE_CHARS};
+
+/// user_preferences key holding the Argon2 hash of the lock passphrase
+pub const APP_LOCK_HASH_PREFERENCE: &str = "app_lock.passphrase_hash";
+/// What a refused command returns while the app is locked
+pub const LOCKED_MESSAGE: &str = "The application is locked";
+/// Commands the lock screen needs
+const ALLOWED_WHILE_LOCKED: &[&str] = &["get_app_lock_status", "unlock_application", "lock_application", "get_backend_status"];
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// Wrong passphrases allowed before attempts are spaced out
+const FREE_ATTEMPTS: u32 = 3;
+const FIRST_RETRY_WAIT_SECS: i64 = 30;
+const MAX_RETRY_WAIT_SECS: i64 = 60 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppLockStatus {
+    /// Whether a lock passphrase is set
+    pub enabled: bool,
+    pub locked: bool,
+    /// Minutes of inactivity before locking; 0 never locks
+    pub timeout_minutes: u64,
+    pub biometric_available: bool,
+}
+
+pub fn hash_passphrase(passphrase: &str) -> Result<String> {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let salt = SaltString::encode_b64(&salt).map_err(|e| LibreOllamaError::Crypto { message: format!("Invalid salt: {}", e) })?;
+    Argon2::default()
+        .hash_password(passphrase.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| LibreOllamaError::Crypto { message: format!("Failed to hash passphrase: {}", e) })
+}
+
+pub fn verify_passphrase(passphrase: &str, hash: &str) -> bool {
+    PasswordHash::new(hash).is_ok_and(|hash| Argon2::default().verify_password(passphrase.as_bytes(), &hash).is_ok())
+}
+
+fn wrong_passphrase() -> LibreOllamaError {
+    LibreOllamaError::PermissionDenied { message: "Wrong passphrase".to_string() }
+}
+
+/// Failed unlock attempts, persisted under `<prefix>.failed_attempts` and
+/// `<prefix>.retry_at` in `user_preferences`
+pub struct UnlockThrottle {
+    attempts_key: String,
+    retry_at_key: String,
+}
+
+impl UnlockThrottle {
+    pub fn new(prefix: &str) -> Self {
+        Self {
+            attempts_key: format!("{}.failed_attempts", prefix),
+            retry_at_key: format!("{}.retry_at", prefix),
+        }
+    }
+
+    /// Refuse an attempt made before the wait after the last failure is over
+    pub fn check(&self, conn: &Connection, now: i64) -> Result<()> {
+        let retry_at = preference_operations::get_preference_value(conn, &self.retry_at_key)?
+            .and_then(|value| value.parse::<i64>().ok())
+            .unwrap_or(0);
+        if now < retry_at {
+            return Err(LibreOllamaError::RateLimit {
+                message: format!("Too many wrong passphrases; try again in {} seconds", retry_at - now),
+                retry_after: Some((retry_at - now) as u64),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn record_failure(&self, conn: &Connection, now: i64) -> Result<()> {
+        let attempts = preference_operations::get_preference_value(conn, &self.attempts_key)?
+            .and_then(|value| value.parse::<u32>().ok())
+            .unwrap_or(0)
+            .saturating_add(1);
+        preference_operations::set_preference_value(conn, &self.attempts_key, &attempts.to_string(), "security")?;
+        if let Some(wait) = retry_wait_secs(attempts) {
+            preference_operations::set_preference_value(conn, &self.retry_at_key, &(now + wait).to_string(), "security")?;
+        }
+        Ok(())
+    }
+
+    pub fn reset(&self, conn: &Connection) -> Result<()> {
+        preference_operations::delete_user_preference_by_key(conn, &self.attempts_key)?;
+        preference_operations::delete_user_preference_by_key(conn, &self.retry_at_key)?;
+        Ok(())
+    }
+}
+
+/// How long to wait after `failures` wrong passphrases in a row
+fn retry_wait_secs(failures: u32) -> Option<i64> {
+    let extra = failures.checked_sub(FREE_ATTEMPTS + 1)?;
+    Some(FIRST_RETRY_WAIT_SECS.saturating_mul(1i64 << extra.min(20)).min(MAX_RETRY_WAIT_SECS))
+}
+
+/// Whether a command is refused, given the lock state; an unknown state
+/// refuses like a locked one
+fn refused(locked: Option<bool>, command: &str) -> bool {
+    locked != Some(false) && !ALLOWED_WHILE_LOCKED.contains(&command)
+}
+
+struct LockState {
+    locked: bool,
+    last_activity: Instant,
+}
+
+/// Lock state of the application, managed as Tauri state
+#[derive(Clone)]
+pub struct AppLock {
+    db_manager: Arc<DatabaseManager>,
+    app: AppHandle,
+    state: Arc<Mutex<LockState>>,
+    /// Held through an unlock attempt so attempts cannot race the throttle
+    unlocking: Arc<Mutex<()>>,
+    throttle: Arc<UnlockThrottle>,
+}
+
+impl AppLock {
+    /// Starts locked when a passphrase is set
+    pub fn new(db_manager: Arc<DatabaseManager>, app: AppHandle) -> Self {
+        let lock = Self {
+            db_manager,
+            app,
+            state: Arc::new(Mutex::new(LockState { locked: true, last_activity: Instant::now() })),
+            unlocking: Arc::new(Mutex::new(())),
+            throttle: Arc::new(UnlockThrottle::new("app_lock")),
+        };
+        // Unlocking with no passphrase set clears the lock once the
+        // database can be read again
+        let enabled = lock.stored_hash().map(|hash| hash.is_some()).unwrap_or_else(|e| {
+            eprintln!("⚠️ [APP-LOCK] Failed to read the lock passphrase, starting locked: {}", e);
+            true
+        });
+        lock.state().locked = enabled;
+        lock
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, LockState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn stored_hash(&self) -> Result<Option<String>> {
+        let conn = self.db_manager.get_connection()?;
+        Ok(preference_operations::get_preference_value(&conn, APP_LOCK_HASH_PREFERENCE)?)
+    }
+
+    fn timeout_minutes(&self) -> u64 {
+        get_config_manager()
+            .map(|config| config.security().session_timeout_minutes)
+            .unwrap_or(0)
+    }
+
+    fn announce(&self, locked: bool, reason: Option<&str>) {
+        if let Some(event_bus) = self.app.try_state::<EventBus>() {
+            event_bus.emit(BackendEvent::AppLockChanged(AppLockChangedEvent {
+                locked,
+                reason: reason.map(str::to_string),
+            }));
+        }
+    }
+
+    /// Lock after the configured inactivity
+    pub fn task(&self) -> TaskSpec {
+        let lock = self.clone();
+        TaskSpec::new("app_auto_lock", move || {
+            let lock = lock.clone();
+            async move {
+                let timeout = lock.timeout_minutes();
+                let idle = {
+                    let state = lock.state();
+                    (!state.locked).then(|| state.last_activity.elapsed())
+                };
+                if timeout > 0 && idle.is_some_and(|idle| idle >= Duration::from_secs(timeout * 60)) {
+                    lock.lock("inactivity")?;
+                }
+                Ok(())
+            }
+        })
+        .every(|| CHECK_INTERVAL)
+        .first_run_after(CHECK_INTERVAL)
+    }
+
+    /// Whether `command` must be refused; any other command counts as activity
+    pub fn is_blocked(&self, command: &str) -> bool {
+        let mut state = self.state();
+        if refused(Some(state.locked), command) {
+            return true;
+        }
+        if !state.locked {
+            state.last_activity = Instant::now();
+        }
+        false
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.state().locked
+    }
+
+    pub fn status(&self) -> Result<AppLockStatus> {
+        Ok(AppLockStatus {
+            enabled: self.stored_hash()?.is_some(),
+            locked: self.state().locked,
+            timeout_minutes: self.timeout_minutes(),
+            biometric_available: false,
+        })
+    }
+
+    /// Set or change the lock passphrase; changing needs the current one
+    pub fn set_passphrase(&self, current: Option<&str>, new: &str) -> Result<AppLockStatus> {
+        if let Some(hash) = self.stored_hash()? {
+            if !current.is_some_and(|current| verify_passphrase(current, &hash)) {
+                return Err(wrong_passphrase());
+            }
+        }
+        if new.chars().count() < MIN_PASSPHRASE_CHARS {
+            return Err(LibreOllamaError::InvalidInput {
+                message: format!("The lock passphrase needs at least {} characters", MIN_PASSPHRASE_CHARS),
+                field: Some("new_passphrase".to_string()),
+            });
+        }
+        let conn = self.db_manager.get_connection()?;
+        preference_operations::set_preference_value(&conn, APP_LOCK_HASH_PREFERENCE, &hash_passphrase(new)?, "security")?;
+        self.status()
+    }
+
+    /// Turn the lock off
+    pub fn remove_passphrase(&self, current: &str) -> Result<AppLockStatus> {
+        let Some(hash) = self.stored_hash()? else {
+            return self.status();
+        };
+        if !verify_passphrase(current, &hash) {
+            return Err(wrong_passphrase());
+        }
+        let conn = self.db_manager.get_connection()?;
+        preference_operations::delete_user_preference_by_key(&conn, APP_LOCK_HASH_PREFERENCE)?;
+        self.state().locked = false;
+        self.status()
+    }
+
+    pub fn lock(&self, reason: &str) -> Result<AppLockStatus> {
+        if self.stored_hash()?.is_none() {
+            return Err(LibreOllamaError::InvalidInput {
+                message: "Set a lock passphrase first".to_string(),
+                field: None,
+            });
+        }
+        let newly_locked = !std::mem::replace(&mut self.state().locked, true);
+        if newly_locked {
+            println!("🔒 [APP-LOCK] Locked ({})", reason);
+            if let Some(vault) = self.app.try_state::<VaultService>() {
+                vault.lock("app_lock");
+            }
+            // Mail content stays out of memory while nobody can see it
+            resource_budget::clear();
+            self.announce(true, Some(reason));
+        }
+        self.status()
+    }
+
+    pub fn unlock(&self, passphrase: &str) -> Result<AppLockStatus> {
+        let _attempt = self.unlocking.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let Some(hash) = self.stored_hash()? else {
+            self.state().locked = false;
+            return self.status();
+        };
+        let conn = self.db_manager.get_connection()?;
+        let now = chrono::Utc::now().timestamp();
+        self.throttle.check(&conn, now)?;
+        if !verify_passphrase(passphrase, &hash) {
+            self.throttle.record_failure(&conn, now)?;
+            return Err(wrong_passphrase());
+        }
+        self.throttle.reset(&conn)?;
+        {
+            let mut state = self.state();
+            state.locked = false;
+            state.last_activity = Instant::now();
+        }
+        println!("🔓 [APP-LOCK] Unlocked");
+        self.announce(false, None);
+        self.status()
+    }
+}
+
+/// Wrap the command handler so commands are refused while the app is locked
+pub fn guard<R: Runtime>(
+    handler: impl Fn(Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        let command = invoke.message.command();
+        let blocked = match invoke.message.webview().try_state::<AppLock>() {
+            Some(lock) => lock.is_blocked(command),
+            None => refused(None, command),
+        };
+        if blocked {
+            invoke.resolver.reject(LOCKED_MESSAGE);
+            return true;
+        }
+        handler(invoke)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_passphrase_hash() {
+        let hash = hash_passphrase("open sesame").unwrap();
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(!hash.contains("open sesame"));
+        assert!(verify_passphrase("open sesame", &hash));
+        assert!(!verify_passphrase("open sesame!", &hash));
+        assert!(!verify_passphrase("open sesame", "not a hash"));
+        assert_ne!(hash, hash_passphrase("open sesame").unwrap());
+    }
+
+    #[test]
+    fn test_guard_refuses_unless_unlocked() {
+        assert!(refused(Some(true), "get_notes"));
+        assert!(!refused(Some(true), "unlock_application"));
+        assert!(!refused(Some(false), "get_notes"));
+        // No lock state to consult: fail closed
+        assert!(refused(None, "get_notes"));
+        assert!(!refused(None, "get_app_lock_status"));
+    }
+
+    #[test]
+    fn test_unlock_throttle_persists() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::database::schema::run_migrations(&conn).unwrap();
+        let throttle = UnlockThrottle::new("app_lock");
+
+        for _ in 0..FREE_ATTEMPTS {
+            throttle.check(&conn, 1000).unwrap();
+            throttle.record_failure(&conn, 1000).unwrap();
+        }
+        throttle.check(&conn, 1000).unwrap();
+        throttle.record_failure(&conn, 1000).unwrap();
+        assert!(matches!(throttle.check(&conn, 1010), Err(LibreOllamaError::RateLimit { retry_after: Some(20), .. })));
+        throttle.check(&conn, 1030).unwrap();
+
+        // A fresh throttle reads the same state back
+        UnlockThrottle::new("app_lock").record_failure(&conn, 1030).unwrap();
+        assert!(throttle.check(&conn, 1089).is_err());
+        throttle.reset(&conn).unwrap();
+        throttle.check(&conn, 1030).unwrap();
+
+        assert_eq!(retry_wait_secs(FREE_ATTEMPTS - 1), None);
+        assert_eq!(retry_wait_secs(FREE_ATTEMPTS + 40), Some(MAX_RETRY_WAIT_SECS));
+    }
+}
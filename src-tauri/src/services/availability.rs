@@ -0,0 +1,479 @@
+//! Availability profile
+//!
+//! When the user works and when they would rather not be booked: working
+//! days and hours, breaks such as lunch, and no-meeting blocks kept free
+//! for focused work. Meeting suggestions avoid breaks and no-meeting
+//! blocks; created or moved events, time-blocked tasks and captures are
+//! checked against the profile, and the daily note and digest show the
+//! day's hours.
+//!
+//! The profile is the `calendar.availability` setting. A workspace can
+//! carry its own profile, which applies while that workspace is active.
+//! Before the profile existed only working hours were stored, under
+//! `calendar.working_hours`; those still count until a profile is saved.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc, Weekday};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::database::operations::{preference_operations, workspace_operations};
+use crate::errors::{LibreOllamaError, Result};
+use crate::models::task_metadata::TimeBlock;
+use crate::services::time_service::TimeService;
+use crate::services::{i18n, settings, workspaces};
+use crate::utils::time;
+
+/// JSON encoded global `AvailabilityProfile`
+pub const AVAILABILITY_SETTING: &str = "calendar.availability";
+/// Working hours as stored before the availability profile
+pub const WORKING_HOURS_PREFERENCE: &str = "calendar.working_hours";
+
+/// Longest span checked day by day for conflicts
+const MAX_CHECKED_DAYS: i64 = 31;
+
+fn parse_clock(value: &str, field: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(value, "%H:%M").map_err(|_| LibreOllamaError::InvalidInput {
+        message: format!("{} must be HH:MM, got {}", field, value),
+        field: Some(field.to_string()),
+    })
+}
+
+fn check_days(days: &[u32], field: &str) -> Result<()> {
+    if days.iter().any(|day| !(1..=7).contains(day)) {
+        return Err(LibreOllamaError::InvalidInput {
+            message: "Days must be numbered 1 (Monday) to 7 (Sunday)".to_string(),
+            field: Some(field.to_string()),
+        });
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkingHours {
+    /// Local start of the working day, "HH:MM"
+    pub start: String,
+    /// Local end of the working day, "HH:MM"
+    pub end: String,
+    /// Working days, 1 = Monday through 7 = Sunday
+    pub days: Vec<u32>,
+}
+
+impl Default for WorkingHours {
+    fn default() -> Self {
+        Self {
+            start: "09:00".to_string(),
+            end: "17:00".to_string(),
+            days: vec![1, 2, 3, 4, 5],
+        }
+    }
+}
+
+impl WorkingHours {
+    /// Parsed start and end of the working day
+    pub fn bounds(&self) -> Result<(NaiveTime, NaiveTime)> {
+        let start = parse_clock(&self.start, "start")?;
+        let end = parse_clock(&self.end, "end")?;
+        if end <= start {
+            return Err(LibreOllamaError::InvalidInput {
+                message: "Working hours must end after they start".to_string(),
+                field: Some("end".to_string()),
+            });
+        }
+        Ok((start, end))
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        self.bounds()?;
+        if self.days.is_empty() {
+            return Err(LibreOllamaError::InvalidInput {
+                message: "At least one working day is needed".to_string(),
+                field: Some("days".to_string()),
+            });
+        }
+        check_days(&self.days, "days")
+    }
+
+    pub fn is_working_day(&self, weekday: Weekday) -> bool {
+        self.days.contains(&weekday.number_from_monday())
+    }
+}
+
+/// A daily stretch of time, such as lunch or a focus block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AvailabilityBlock {
+    pub label: Option<String>,
+    /// Local start, "HH:MM"
+    pub start: String,
+    /// Local end, "HH:MM"
+    pub end: String,
+    /// Days it applies to, 1 = Monday through 7 = Sunday; every working
+    /// day when empty
+    #[serde(default)]
+    pub days: Vec<u32>,
+}
+
+impl AvailabilityBlock {
+    pub fn bounds(&self) -> Result<(NaiveTime, NaiveTime)> {
+        let start = parse_clock(&self.start, "start")?;
+        let end = parse_clock(&self.end, "end")?;
+        if end <= start {
+            return Err(LibreOllamaError::InvalidInput {
+                message: format!("The block {}–{} must end after it starts", self.start, self.end),
+                field: Some("end".to_string()),
+            });
+        }
+        Ok((start, end))
+    }
+
+    fn applies_on(&self, weekday: Weekday, hours: &WorkingHours) -> bool {
+        if self.days.is_empty() {
+            hours.is_working_day(weekday)
+        } else {
+            self.days.contains(&weekday.number_from_monday())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AvailabilityProfile {
+    pub working_hours: WorkingHours,
+    /// Times the user is away, such as lunch; nothing is planned in them
+    pub breaks: Vec<AvailabilityBlock>,
+    /// Times kept free of meetings; tasks may still be time-blocked there
+    pub no_meeting_blocks: Vec<AvailabilityBlock>,
+}
+
+/// What a stretch of time is for, which decides the blocks that apply
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AvailabilityPurpose {
+    Meeting,
+    /// Working on a time-blocked task
+    Focus,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AvailabilityConflict {
+    NonWorkingDay { date: NaiveDate },
+    /// Part of the time falls before or after the day's working hours
+    OutsideWorkingHours { date: NaiveDate, start: String, end: String },
+    Break { date: NaiveDate, label: Option<String>, start: String, end: String },
+    NoMeetingBlock { date: NaiveDate, label: Option<String>, start: String, end: String },
+}
+
+/// Minutes since midnight
+fn minutes(time: NaiveTime) -> i64 {
+    i64::from(time.hour()) * 60 + i64::from(time.minute())
+}
+
+impl AvailabilityProfile {
+    pub fn validate(&self) -> Result<()> {
+        self.working_hours.validate()?;
+        for block in self.breaks.iter().chain(&self.no_meeting_blocks) {
+            block.bounds()?;
+            check_days(&block.days, "days")?;
+        }
+        Ok(())
+    }
+
+    fn blocks_on<'a>(
+        &'a self,
+        blocks: &'a [AvailabilityBlock],
+        weekday: Weekday,
+    ) -> impl Iterator<Item = (&'a AvailabilityBlock, NaiveTime, NaiveTime)> + 'a {
+        blocks
+            .iter()
+            .filter(move |block| block.applies_on(weekday, &self.working_hours))
+            .filter_map(|block| block.bounds().ok().map(|(start, end)| (block, start, end)))
+    }
+
+    /// Ways the local time from `start` to `end` in zone `tz` goes against
+    /// the profile, day by day
+    pub fn conflicts<Tz: TimeZone>(
+        &self,
+        tz: &Tz,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        purpose: AvailabilityPurpose,
+    ) -> Vec<AvailabilityConflict> {
+        let Ok((work_start, work_end)) = self.working_hours.bounds() else {
+            return Vec::new();
+        };
+        let local_start = start.with_timezone(tz).naive_local();
+        let local_end = end.with_timezone(tz).naive_local().max(local_start);
+        let last_day = (local_end - Duration::nanoseconds(1)).date().max(local_start.date());
+
+        let mut conflicts = Vec::new();
+        let mut day = local_start.date();
+        while day <= last_day && (day - local_start.date()).num_days() < MAX_CHECKED_DAYS {
+            let midnight = day.and_time(NaiveTime::MIN);
+            let clip = |time: NaiveDateTime| (time - midnight).num_minutes().clamp(0, 24 * 60);
+            let (from, to) = (clip(local_start), clip(local_end));
+            let weekday = day.weekday();
+            let overlaps = |start: NaiveTime, end: NaiveTime| minutes(start) < to && from < minutes(end);
+
+            if !self.working_hours.is_working_day(weekday) {
+                conflicts.push(AvailabilityConflict::NonWorkingDay { date: day });
+            } else {
+                if from < minutes(work_start) || to > minutes(work_end) {
+                    conflicts.push(AvailabilityConflict::OutsideWorkingHours {
+                        date: day,
+                        start: self.working_hours.start.clone(),
+                        end: self.working_hours.end.clone(),
+                    });
+                }
+                for (block, block_start, block_end) in self.blocks_on(&self.breaks, weekday) {
+                    if overlaps(block_start, block_end) {
+                        conflicts.push(AvailabilityConflict::Break {
+                            date: day,
+                            label: block.label.clone(),
+                            start: block.start.clone(),
+                            end: block.end.clone(),
+                        });
+                    }
+                }
+            }
+            if purpose == AvailabilityPurpose::Meeting {
+                for (block, block_start, block_end) in self.blocks_on(&self.no_meeting_blocks, weekday) {
+                    if overlaps(block_start, block_end) {
+                        conflicts.push(AvailabilityConflict::NoMeetingBlock {
+                            date: day,
+                            label: block.label.clone(),
+                            start: block.start.clone(),
+                            end: block.end.clone(),
+                        });
+                    }
+                }
+            }
+            day = match day.succ_opt() {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        conflicts
+    }
+
+    /// Whether a meeting could be put at this time without breaking into
+    /// a break or a no-meeting block
+    pub fn allows_meeting<Tz: TimeZone>(&self, tz: &Tz, start: DateTime<Utc>, end: DateTime<Utc>) -> bool {
+        !self.conflicts(tz, start, end, AvailabilityPurpose::Meeting).iter().any(|conflict| {
+            matches!(conflict, AvailabilityConflict::Break { .. } | AvailabilityConflict::NoMeetingBlock { .. })
+        })
+    }
+
+    /// One line describing the hours of a day, in the active locale
+    pub fn day_summary(&self, weekday: Weekday) -> String {
+        if !self.working_hours.is_working_day(weekday) {
+            return i18n::t("availability-day-off");
+        }
+        let range = |label: String, block: &AvailabilityBlock| {
+            i18n::t_args(
+                "availability-block",
+                &[("label", label.into()), ("start", block.start.clone().into()), ("end", block.end.clone().into())],
+            )
+        };
+        let mut parts = vec![i18n::t_args(
+            "availability-working",
+            &[("start", self.working_hours.start.clone().into()), ("end", self.working_hours.end.clone().into())],
+        )];
+        let mut blocks: Vec<(NaiveTime, String)> = self
+            .blocks_on(&self.breaks, weekday)
+            .map(|(block, start, _)| (start, range(block.label.clone().unwrap_or_else(|| i18n::t("availability-break")), block)))
+            .chain(self.blocks_on(&self.no_meeting_blocks, weekday).map(|(block, start, _)| {
+                (start, range(block.label.clone().unwrap_or_else(|| i18n::t("availability-no-meetings")), block))
+            }))
+            .collect();
+        blocks.sort();
+        parts.extend(blocks.into_iter().map(|(_, part)| part));
+        parts.join(" · ")
+    }
+}
+
+/// The global profile; saved working hours from before the profile fill
+/// in while it is untouched
+pub fn global_profile(conn: &Connection) -> anyhow::Result<AvailabilityProfile> {
+    let setting = settings::get_setting(conn, AVAILABILITY_SETTING)?;
+    if setting.is_default {
+        let legacy: Option<WorkingHours> = preference_operations::get_preference_value(conn, WORKING_HOURS_PREFERENCE)?
+            .and_then(|value| serde_json::from_str(&value).ok());
+        if let Some(working_hours) = legacy {
+            return Ok(AvailabilityProfile { working_hours, ..Default::default() });
+        }
+    }
+    Ok(serde_json::from_value(setting.value).unwrap_or_default())
+}
+
+/// The profile a workspace overrides the global one with, if any
+pub fn workspace_profile(conn: &Connection, workspace_id: i64) -> anyhow::Result<Option<AvailabilityProfile>> {
+    Ok(workspace_operations::get_availability(conn, workspace_id)?.and_then(|json| serde_json::from_str(&json).ok()))
+}
+
+pub fn save_workspace_profile(conn: &Connection, workspace_id: i64, profile: Option<&AvailabilityProfile>) -> Result<()> {
+    if workspace_operations::get_workspace(conn, workspace_id)?.is_none() {
+        return Err(LibreOllamaError::NotFound { resource: format!("Workspace {}", workspace_id) });
+    }
+    let json = match profile {
+        Some(profile) => {
+            profile.validate()?;
+            Some(serde_json::to_string(profile)?)
+        }
+        None => None,
+    };
+    Ok(workspace_operations::set_availability(conn, workspace_id, json.as_deref())?)
+}
+
+/// The profile in effect, with the workspace it comes from when the active
+/// workspace overrides the global one
+pub fn effective_profile(conn: &Connection) -> Result<(AvailabilityProfile, Option<i64>)> {
+    if let Some(workspace) = workspaces::active_workspace(conn)? {
+        if let Some(profile) = workspace_profile(conn, workspace.id)? {
+            return Ok((profile, Some(workspace.id)));
+        }
+    }
+    Ok((global_profile(conn)?, None))
+}
+
+pub fn load_profile(conn: &Connection) -> Result<AvailabilityProfile> {
+    Ok(effective_profile(conn)?.0)
+}
+
+/// Ways the time from `start` to `end` goes against the profile in effect,
+/// read in the user's time zone
+pub fn check(
+    conn: &Connection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    purpose: AvailabilityPurpose,
+) -> Result<Vec<AvailabilityConflict>> {
+    let zone = TimeService::load(conn)?.zone();
+    Ok(load_profile(conn)?.conflicts(&zone, start, end, purpose))
+}
+
+/// The hours of the user's day at `at`, read in their time zone rather
+/// than the system's
+pub fn day_summary_at<Tz: TimeZone>(conn: &Connection, at: &DateTime<Tz>) -> Result<String> {
+    let zone = TimeService::load(conn)?.zone();
+    Ok(load_profile(conn)?.day_summary(at.with_timezone(&zone).weekday()))
+}
+
+/// `check` for a task's time block; a block whose times cannot be read
+/// has no conflicts
+pub fn time_block_conflicts(conn: &Connection, block: &TimeBlock) -> Result<Vec<AvailabilityConflict>> {
+    let zone = TimeService::load(conn)?.zone();
+    let at = |value: &str| time::zoned_to_utc(value, block.time_zone.as_deref(), &zone);
+    let (Some(start), Some(end)) = (at(&block.start_time), at(&block.end_time)) else {
+        return Ok(Vec::new());
+    };
+    Ok(load_profile(conn)?.conflicts(&zone, start, end, AvailabilityPurpose::Focus))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::operations::workspace_operations::create_workspace;
+    use crate::database::schema::run_migrations;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        value.parse().unwrap()
+    }
+
+    fn block(label: &str, start: &str, end: &str, days: &[u32]) -> AvailabilityBlock {
+        AvailabilityBlock {
+            label: Some(label.to_string()),
+            start: start.to_string(),
+            end: end.to_string(),
+            days: days.to_vec(),
+        }
+    }
+
+    fn profile() -> AvailabilityProfile {
+        AvailabilityProfile {
+            working_hours: WorkingHours::default(),
+            breaks: vec![block("Lunch", "12:00", "13:00", &[])],
+            no_meeting_blocks: vec![block("Focus", "14:00", "16:00", &[3])],
+        }
+    }
+
+    #[test]
+    fn test_conflicts_follow_the_profile() {
+        let profile = profile();
+        // Wednesday 2024-06-05
+        assert!(profile.conflicts(&Utc, at("2024-06-05T10:00:00Z"), at("2024-06-05T11:00:00Z"), AvailabilityPurpose::Meeting).is_empty());
+        // Ending as lunch starts is fine
+        assert!(profile.allows_meeting(&Utc, at("2024-06-05T11:00:00Z"), at("2024-06-05T12:00:00Z")));
+        assert!(!profile.allows_meeting(&Utc, at("2024-06-05T11:30:00Z"), at("2024-06-05T12:30:00Z")));
+
+        let focus = (at("2024-06-05T15:00:00Z"), at("2024-06-05T17:30:00Z"));
+        assert_eq!(profile.conflicts(&Utc, focus.0, focus.1, AvailabilityPurpose::Meeting), vec![
+            AvailabilityConflict::OutsideWorkingHours {
+                date: NaiveDate::from_ymd_opt(2024, 6, 5).unwrap(),
+                start: "09:00".to_string(),
+                end: "17:00".to_string(),
+            },
+            AvailabilityConflict::NoMeetingBlock {
+                date: NaiveDate::from_ymd_opt(2024, 6, 5).unwrap(),
+                label: Some("Focus".to_string()),
+                start: "14:00".to_string(),
+                end: "16:00".to_string(),
+            },
+        ]);
+        // Tasks may use no-meeting blocks
+        assert_eq!(profile.conflicts(&Utc, focus.0, focus.1, AvailabilityPurpose::Focus).len(), 1);
+
+        // Saturday
+        assert!(matches!(
+            profile.conflicts(&Utc, at("2024-06-08T10:00:00Z"), at("2024-06-08T11:00:00Z"), AvailabilityPurpose::Focus)[..],
+            [AvailabilityConflict::NonWorkingDay { .. }]
+        ));
+        // Local times are what count
+        let berlin: chrono_tz::Tz = "Europe/Berlin".parse().unwrap();
+        assert!(!profile.allows_meeting(&berlin, at("2024-06-05T10:15:00Z"), at("2024-06-05T10:45:00Z")));
+    }
+
+    #[test]
+    fn test_profile_validation_and_summary() {
+        assert!(profile().validate().is_ok());
+        let mut broken = profile();
+        broken.breaks.push(block("Nap", "15:00", "14:00", &[]));
+        assert!(broken.validate().is_err());
+        let mut no_days = profile();
+        no_days.working_hours.days.clear();
+        assert!(no_days.validate().is_err());
+
+        assert_eq!(profile().day_summary(Weekday::Wed), "Working 09:00–17:00 · Lunch 12:00–13:00 · Focus 14:00–16:00");
+        assert_eq!(profile().day_summary(Weekday::Thu), "Working 09:00–17:00 · Lunch 12:00–13:00");
+        assert_eq!(profile().day_summary(Weekday::Sun), "Not a working day");
+    }
+
+    #[test]
+    fn test_workspace_profiles_override_the_global_one() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        // Hours saved before the profile existed still apply
+        let legacy = WorkingHours { start: "08:00".to_string(), end: "16:00".to_string(), days: vec![1, 2, 3, 4] };
+        preference_operations::set_preference_value(&conn, WORKING_HOURS_PREFERENCE, &serde_json::to_string(&legacy).unwrap(), "calendar")
+            .unwrap();
+        assert_eq!(load_profile(&conn).unwrap().working_hours, legacy);
+
+        settings::set_setting(&conn, AVAILABILITY_SETTING, &serde_json::to_value(profile()).unwrap()).unwrap();
+        assert_eq!(load_profile(&conn).unwrap(), profile());
+
+        let side = create_workspace(&conn, "Side project", None).unwrap();
+        let evenings = AvailabilityProfile {
+            working_hours: WorkingHours { start: "18:00".to_string(), end: "21:00".to_string(), days: vec![1, 3] },
+            ..Default::default()
+        };
+        save_workspace_profile(&conn, side.id, Some(&evenings)).unwrap();
+        // Only while the workspace is active
+        assert_eq!(effective_profile(&conn).unwrap(), (profile(), None));
+        workspaces::switch(&conn, Some(side.id)).unwrap();
+        assert_eq!(effective_profile(&conn).unwrap(), (evenings, Some(side.id)));
+
+        save_workspace_profile(&conn, side.id, None).unwrap();
+        assert_eq!(effective_profile(&conn).unwrap(), (profile(), None));
+        assert!(save_workspace_profile(&conn, 999, Some(&profile())).is_err());
+    }
+}
@@ -0,0 +1,577 @@
+//! AI assistant for the canvas
+//!
+//! The canvas sends the elements the user selected; their text is turned
+//! into a prompt for a summary, an outline or a mind map. Outlines and mind
+//! maps are asked for as a JSON tree, laid out here next to the selection and
+//! returned as nodes and edges the canvas inserts as sticky notes and
+//! connectors. Each generation is kept in the canvas's AI history.
+//!
+//! Node and edge fields are camelCase like the canvas's own elements.
+
+use std::sync::Arc;
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::database::operations::canvas_operations::{self, CanvasGenerationRecord};
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::llm_provider::{self, ChatMessage, ChatOptions, LlmFeature};
+use crate::services::prompt_templates;
+use crate::utils::tokens;
+
+/// Selection text sent to the model
+const MAX_SELECTION_TOKENS: usize = 3000;
+pub const DEFAULT_MAX_NODES: usize = 24;
+const MAX_NODES_LIMIT: usize = 80;
+const MAX_DEPTH: usize = 3;
+/// Prompt template features for summaries and diagrams
+const SUMMARY_PROMPT_FEATURE: &str = "canvas.summarize";
+const DIAGRAM_PROMPT_FEATURE: &str = "canvas.diagram";
+
+/// Gap between the selection and the inserted nodes
+const MARGIN: f64 = 80.0;
+const NODE_WIDTH: f64 = 200.0;
+const NODE_HEIGHT: f64 = 80.0;
+const OUTLINE_ROW_HEIGHT: f64 = 56.0;
+const OUTLINE_INDENT: f64 = 48.0;
+const MIND_MAP_COLUMN_WIDTH: f64 = 280.0;
+const MIND_MAP_ROW_HEIGHT: f64 = 110.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CanvasPoint {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// A selected canvas element; fields the assistant does not use are ignored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectedElement {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub element_type: String,
+    #[serde(default)]
+    pub x: f64,
+    #[serde(default)]
+    pub y: f64,
+    pub width: Option<f64>,
+    pub height: Option<f64>,
+    /// Circles are positioned by their centre
+    pub radius: Option<f64>,
+    pub text: Option<String>,
+    pub title: Option<String>,
+    pub start_element_id: Option<String>,
+    pub end_element_id: Option<String>,
+}
+
+impl SelectedElement {
+    /// Text or title on a single line
+    fn label(&self) -> Option<String> {
+        let label = self.text.as_deref().or(self.title.as_deref())?;
+        let label = label.split_whitespace().collect::<Vec<_>>().join(" ");
+        (!label.is_empty()).then_some(label)
+    }
+
+    /// Left, top, right, bottom
+    fn bounds(&self) -> (f64, f64, f64, f64) {
+        match self.radius {
+            Some(radius) => (self.x - radius, self.y - radius, self.x + radius, self.y + radius),
+            None => (self.x, self.y, self.x + self.width.unwrap_or(0.0), self.y + self.height.unwrap_or(0.0)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagramKind {
+    /// Indented list reading top to bottom
+    Outline,
+    /// Tree growing to the right of a central topic
+    MindMap,
+}
+
+impl DiagramKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiagramKind::Outline => "outline",
+            DiagramKind::MindMap => "mind_map",
+        }
+    }
+}
+
+/// A node to insert, positioned in canvas coordinates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeneratedNode {
+    pub id: String,
+    pub text: String,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    /// 0 for the root topic
+    pub depth: usize,
+    pub parent_id: Option<String>,
+}
+
+/// A connector between two generated nodes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeneratedEdge {
+    pub id: String,
+    pub start_element_id: String,
+    pub end_element_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CanvasSummary {
+    pub generation_id: i64,
+    pub summary: String,
+    /// The summary as a note placed next to the selection
+    pub node: GeneratedNode,
+    pub model: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CanvasDiagram {
+    pub generation_id: i64,
+    pub kind: DiagramKind,
+    pub nodes: Vec<GeneratedNode>,
+    pub edges: Vec<GeneratedEdge>,
+    pub model: String,
+}
+
+/// One entry of a canvas's AI history
+#[derive(Debug, Clone, Serialize)]
+pub struct CanvasGeneration {
+    pub id: i64,
+    pub canvas_id: String,
+    pub action: String,
+    pub instructions: Option<String>,
+    pub element_count: usize,
+    /// The `CanvasSummary` or `CanvasDiagram` that was returned
+    pub result: Value,
+    pub model: String,
+    pub created_at: String,
+}
+
+impl From<CanvasGenerationRecord> for CanvasGeneration {
+    fn from(record: CanvasGenerationRecord) -> Self {
+        Self {
+            id: record.id,
+            element_count: serde_json::from_str::<Vec<Value>>(&record.selection).map_or(0, |elements| elements.len()),
+            canvas_id: record.canvas_id,
+            action: record.action,
+            instructions: record.instructions,
+            result: serde_json::from_str(&record.result).unwrap_or(Value::Null),
+            model: record.model,
+            created_at: record.created_at,
+        }
+    }
+}
+
+/// Topic tree as returned by the model
+#[derive(Debug, Clone, Deserialize)]
+struct TopicTree {
+    #[serde(alias = "title", alias = "text")]
+    label: String,
+    #[serde(default)]
+    children: Vec<TopicTree>,
+}
+
+/// Text of the selection in reading order, with connectors as arrows
+pub fn selection_text(elements: &[SelectedElement]) -> String {
+    let mut ordered: Vec<&SelectedElement> = elements.iter().collect();
+    ordered.sort_by(|a, b| (a.y, a.x).partial_cmp(&(b.y, b.x)).unwrap_or(std::cmp::Ordering::Equal));
+    let label_of = |id: &Option<String>| {
+        id.as_deref()
+            .and_then(|id| elements.iter().find(|element| element.id == id))
+            .and_then(SelectedElement::label)
+    };
+
+    let (connectors, shapes): (Vec<&SelectedElement>, Vec<&SelectedElement>) =
+        ordered.into_iter().partition(|element| element.element_type == "connector");
+    let mut lines: Vec<String> = shapes.iter().filter_map(|element| element.label()).map(|label| format!("- {}", label)).collect();
+    lines.extend(connectors.iter().filter_map(|connector| {
+        let start = label_of(&connector.start_element_id)?;
+        let end = label_of(&connector.end_element_id)?;
+        Some(format!("{} -> {}", start, end))
+    }));
+    lines.join("\n")
+}
+
+/// Top-left of the space right of the selection, or `origin` when nothing
+/// is selected
+fn insert_origin(elements: &[SelectedElement], origin: Option<CanvasPoint>) -> CanvasPoint {
+    let bounds = elements
+        .iter()
+        .filter(|element| element.element_type != "connector")
+        .map(SelectedElement::bounds)
+        .reduce(|a, b| (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3)));
+    match bounds {
+        Some((_, top, right, _)) => CanvasPoint { x: right + MARGIN, y: top },
+        None => origin.unwrap_or_default(),
+    }
+}
+
+fn check_request(text: &str, instructions: Option<&str>) -> Result<()> {
+    if text.trim().is_empty() && instructions.is_none_or(|instructions| instructions.trim().is_empty()) {
+        return Err(LibreOllamaError::InvalidInput {
+            message: "Select elements with text or describe what to generate".to_string(),
+            field: Some("elements".to_string()),
+        });
+    }
+    Ok(())
+}
+
+fn user_prompt(text: &str, instructions: Option<&str>) -> String {
+    let mut prompt = String::new();
+    if !text.trim().is_empty() {
+        prompt.push_str("Canvas selection:\n");
+        prompt.push_str(tokens::truncate_to_tokens(text, MAX_SELECTION_TOKENS));
+        prompt.push_str("\n\n");
+    }
+    if let Some(instructions) = instructions.map(str::trim).filter(|instructions| !instructions.is_empty()) {
+        prompt.push_str("Instructions: ");
+        prompt.push_str(instructions);
+    }
+    prompt.trim_end().to_string()
+}
+
+/// System prompt for a topic tree of `kind`
+fn diagram_prompt(conn: &Connection, kind: DiagramKind, max_nodes: usize) -> Result<String> {
+    let shape = match kind {
+        DiagramKind::Outline => "an outline: the root is the title and its children are sections with their points",
+        DiagramKind::MindMap => "a mind map: the root is the central topic and its children are the main branches",
+    };
+    let mut values = serde_json::Map::new();
+    values.insert("shape".to_string(), shape.into());
+    values.insert("max_nodes".to_string(), max_nodes.into());
+    values.insert("max_depth".to_string(), MAX_DEPTH.into());
+    prompt_templates::render_feature_prompt(conn, DIAGRAM_PROMPT_FEATURE, &values)
+}
+
+/// Parse the model's topic tree; a reply wrapped in a code fence is accepted
+fn parse_tree(reply: &str) -> Result<TopicTree> {
+    let json = reply.trim().trim_start_matches("```json").trim_start_matches("```").trim_end_matches("```").trim();
+    let tree: TopicTree = serde_json::from_str(json).map_err(|e| LibreOllamaError::Serialization {
+        message: format!("Model did not return a topic tree: {}", e),
+        data_type: "canvas diagram".to_string(),
+    })?;
+    if tree.label.trim().is_empty() {
+        return Err(LibreOllamaError::Serialization {
+            message: "Model returned a topic tree without a root label".to_string(),
+            data_type: "canvas diagram".to_string(),
+        });
+    }
+    Ok(tree)
+}
+
+/// Flattened tree: label, depth, parent index, in pre-order. Levels deeper
+/// than `MAX_DEPTH` and nodes past `max_nodes` (breadth first) are dropped.
+fn flatten(tree: &TopicTree, max_nodes: usize) -> Vec<(String, usize, Option<usize>)> {
+    // Breadth-first pass decides which nodes are kept, so a deep first
+    // branch cannot starve the others
+    let mut kept = std::collections::HashSet::new();
+    let mut queue = std::collections::VecDeque::from([(tree, 0usize, Vec::<usize>::new())]);
+    while let Some((node, depth, path)) = queue.pop_front() {
+        if kept.len() >= max_nodes {
+            break;
+        }
+        kept.insert(path.clone());
+        if depth < MAX_DEPTH {
+            for (index, child) in node.children.iter().enumerate() {
+                let mut child_path = path.clone();
+                child_path.push(index);
+                queue.push_back((child, depth + 1, child_path));
+            }
+        }
+    }
+
+    fn visit(
+        node: &TopicTree,
+        path: &mut Vec<usize>,
+        parent: Option<usize>,
+        kept: &std::collections::HashSet<Vec<usize>>,
+        out: &mut Vec<(String, usize, Option<usize>)>,
+    ) {
+        if !kept.contains(path) {
+            return;
+        }
+        let index = out.len();
+        out.push((node.label.trim().to_string(), path.len(), parent));
+        for (child_index, child) in node.children.iter().enumerate() {
+            path.push(child_index);
+            visit(child, path, Some(index), kept, out);
+            path.pop();
+        }
+    }
+
+    let mut out = Vec::new();
+    visit(tree, &mut Vec::new(), None, &kept, &mut out);
+    out
+}
+
+/// Position the flattened tree with its top-left corner at `origin`
+pub fn layout(
+    kind: DiagramKind,
+    flat: &[(String, usize, Option<usize>)],
+    origin: CanvasPoint,
+    id_prefix: &str,
+) -> (Vec<GeneratedNode>, Vec<GeneratedEdge>) {
+    let id = |index: usize| format!("{}-{}", id_prefix, index);
+    let positions: Vec<CanvasPoint> = match kind {
+        DiagramKind::Outline => flat
+            .iter()
+            .enumerate()
+            .map(|(row, (_, depth, _))| CanvasPoint {
+                x: origin.x + *depth as f64 * OUTLINE_INDENT,
+                y: origin.y + row as f64 * OUTLINE_ROW_HEIGHT,
+            })
+            .collect(),
+        DiagramKind::MindMap => {
+            // Leaves take one row each; a parent is centred on its children
+            let mut rows = vec![0.0; flat.len()];
+            let mut next_row = 0.0;
+            for (index, row) in rows.iter_mut().enumerate() {
+                if !flat.iter().any(|(_, _, parent)| *parent == Some(index)) {
+                    *row = next_row;
+                    next_row += 1.0;
+                }
+            }
+            // Pre-order reversed visits children before their parent
+            for index in (0..flat.len()).rev() {
+                let children: Vec<f64> =
+                    (index + 1..flat.len()).filter(|child| flat[*child].2 == Some(index)).map(|child| rows[child]).collect();
+                if let (Some(first), Some(last)) = (children.first(), children.last()) {
+                    rows[index] = (first + last) / 2.0;
+                }
+            }
+            flat.iter()
+                .zip(rows)
+                .map(|((_, depth, _), row)| CanvasPoint {
+                    x: origin.x + *depth as f64 * MIND_MAP_COLUMN_WIDTH,
+                    y: origin.y + row * MIND_MAP_ROW_HEIGHT,
+                })
+                .collect()
+        }
+    };
+    let height = match kind {
+        DiagramKind::Outline => OUTLINE_ROW_HEIGHT - 12.0,
+        DiagramKind::MindMap => NODE_HEIGHT,
+    };
+
+    let nodes = flat
+        .iter()
+        .zip(positions)
+        .enumerate()
+        .map(|(index, ((label, depth, parent), position))| GeneratedNode {
+            id: id(index),
+            text: label.clone(),
+            x: position.x,
+            y: position.y,
+            width: NODE_WIDTH,
+            height,
+            depth: *depth,
+            parent_id: parent.map(id),
+        })
+        .collect();
+    let edges = flat
+        .iter()
+        .enumerate()
+        .filter_map(|(index, (_, _, parent))| {
+            parent.map(|parent| GeneratedEdge {
+                id: format!("{}-edge-{}", id_prefix, index),
+                start_element_id: id(parent),
+                end_element_id: id(index),
+            })
+        })
+        .collect();
+    (nodes, edges)
+}
+
+fn new_id_prefix() -> String {
+    format!("ai-{}", &uuid::Uuid::new_v4().simple().to_string()[..8])
+}
+
+pub struct CanvasAssistant {
+    db_manager: Arc<DatabaseManager>,
+}
+
+impl CanvasAssistant {
+    pub fn new(db_manager: Arc<DatabaseManager>) -> Self {
+        Self { db_manager }
+    }
+
+    fn record(
+        &self,
+        canvas_id: &str,
+        action: &str,
+        instructions: Option<&str>,
+        elements: &[SelectedElement],
+        result: &impl Serialize,
+        model: &str,
+    ) -> Result<i64> {
+        let conn = self.db_manager.get_connection()?;
+        let record = canvas_operations::save_generation(
+            &conn,
+            canvas_id,
+            action,
+            instructions,
+            &serde_json::to_string(elements)?,
+            &serde_json::to_string(result)?,
+            model,
+        )?;
+        Ok(record.id)
+    }
+
+    /// Summarize the selected elements into a note placed beside them
+    pub async fn summarize(
+        &self,
+        canvas_id: &str,
+        elements: &[SelectedElement],
+        instructions: Option<&str>,
+        origin: Option<CanvasPoint>,
+    ) -> Result<CanvasSummary> {
+        let text = selection_text(elements);
+        check_request(&text, instructions)?;
+
+        let system = prompt_templates::render_feature_prompt(
+            &self.db_manager.get_connection()?,
+            SUMMARY_PROMPT_FEATURE,
+            &serde_json::Map::new(),
+        )?;
+        let messages = [ChatMessage::system(system), ChatMessage::user(user_prompt(&text, instructions))];
+        let completion = llm_provider::complete(&self.db_manager, LlmFeature::Summarization, &messages).await?;
+        let summary = completion.content.trim().to_string();
+
+        let position = insert_origin(elements, origin);
+        let lines = summary.lines().count().max(1) as f64;
+        let mut result = CanvasSummary {
+            generation_id: 0,
+            node: GeneratedNode {
+                id: new_id_prefix(),
+                text: summary.clone(),
+                x: position.x,
+                y: position.y,
+                width: NODE_WIDTH * 1.5,
+                height: (lines * 24.0 + 32.0).max(NODE_HEIGHT),
+                depth: 0,
+                parent_id: None,
+            },
+            summary,
+            model: completion.model,
+        };
+        result.generation_id = self.record(canvas_id, "summarize", instructions, elements, &result, &result.model)?;
+        Ok(result)
+    }
+
+    /// Generate an outline or mind map from the selected elements
+    pub async fn generate_diagram(
+        &self,
+        canvas_id: &str,
+        kind: DiagramKind,
+        elements: &[SelectedElement],
+        instructions: Option<&str>,
+        max_nodes: Option<usize>,
+        origin: Option<CanvasPoint>,
+    ) -> Result<CanvasDiagram> {
+        let text = selection_text(elements);
+        check_request(&text, instructions)?;
+        let max_nodes = max_nodes.unwrap_or(DEFAULT_MAX_NODES).clamp(1, MAX_NODES_LIMIT);
+
+        let messages = [
+            ChatMessage::system(diagram_prompt(&self.db_manager.get_connection()?, kind, max_nodes)?),
+            ChatMessage::user(user_prompt(&text, instructions)),
+        ];
+        let options = ChatOptions { temperature: Some(0.3), json: true, ..Default::default() };
+        let completion = llm_provider::complete_with(&self.db_manager, LlmFeature::Chat, &messages, &options).await?;
+        let tree = parse_tree(&completion.content)?;
+
+        let (nodes, edges) = layout(kind, &flatten(&tree, max_nodes), insert_origin(elements, origin), &new_id_prefix());
+        let mut result = CanvasDiagram { generation_id: 0, kind, nodes, edges, model: completion.model };
+        result.generation_id = self.record(canvas_id, kind.as_str(), instructions, elements, &result, &result.model)?;
+        Ok(result)
+    }
+}
+
+/// AI history of a canvas, newest first
+pub fn history(conn: &Connection, canvas_id: &str, limit: i64) -> Result<Vec<CanvasGeneration>> {
+    Ok(canvas_operations::list_generations(conn, canvas_id, limit.clamp(1, 500))?
+        .into_iter()
+        .map(CanvasGeneration::from)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(id: &str, element_type: &str, x: f64, y: f64, text: Option<&str>) -> SelectedElement {
+        SelectedElement {
+            id: id.to_string(),
+            element_type: element_type.to_string(),
+            x,
+            y,
+            width: Some(100.0),
+            height: Some(50.0),
+            radius: None,
+            text: text.map(str::to_string),
+            title: None,
+            start_element_id: None,
+            end_element_id: None,
+        }
+    }
+
+    #[test]
+    fn test_selection_text_and_origin() {
+        let mut connector = element("c", "connector", 0.0, 0.0, None);
+        connector.start_element_id = Some("a".to_string());
+        connector.end_element_id = Some("b".to_string());
+        let elements = vec![
+            element("b", "sticky-note", 300.0, 200.0, Some("Ship beta")),
+            element("a", "sticky-note", 0.0, 100.0, Some("Fix login\nbug")),
+            element("d", "rectangle", 50.0, 400.0, None),
+            connector,
+        ];
+        assert_eq!(selection_text(&elements), "- Fix login bug\n- Ship beta\nFix login bug -> Ship beta");
+        assert_eq!(insert_origin(&elements, None), CanvasPoint { x: 400.0 + MARGIN, y: 100.0 });
+        assert_eq!(insert_origin(&[], Some(CanvasPoint { x: 5.0, y: 6.0 })), CanvasPoint { x: 5.0, y: 6.0 });
+        assert!(check_request("", Some("  ")).is_err());
+    }
+
+    #[test]
+    fn test_diagram_layout() {
+        let tree = parse_tree(
+            "```json\n{\"label\": \"Launch\", \"children\": [\
+             {\"label\": \"Build\", \"children\": [{\"label\": \"API\"}, {\"label\": \"UI\"}]},\
+             {\"title\": \"Market\"}]}\n```",
+        )
+        .unwrap();
+        let flat = flatten(&tree, 10);
+        let labels: Vec<&str> = flat.iter().map(|(label, _, _)| label.as_str()).collect();
+        assert_eq!(labels, ["Launch", "Build", "API", "UI", "Market"]);
+
+        // Breadth first: both branches survive a small limit
+        let small = flatten(&tree, 3);
+        let labels: Vec<&str> = small.iter().map(|(label, _, _)| label.as_str()).collect();
+        assert_eq!(labels, ["Launch", "Build", "Market"]);
+
+        let origin = CanvasPoint { x: 10.0, y: 20.0 };
+        let (nodes, edges) = layout(DiagramKind::MindMap, &flat, origin, "ai-test");
+        assert_eq!(edges.len(), 4);
+        assert_eq!(nodes[2].parent_id.as_deref(), Some("ai-test-1"));
+        assert_eq!((nodes[2].x, nodes[2].y), (10.0 + 2.0 * MIND_MAP_COLUMN_WIDTH, 20.0));
+        // Build is centred on API and UI; the root on Build and Market
+        assert_eq!(nodes[1].y, 20.0 + 0.5 * MIND_MAP_ROW_HEIGHT);
+        assert_eq!(nodes[0].y, 20.0 + 1.25 * MIND_MAP_ROW_HEIGHT);
+
+        let (outline, _) = layout(DiagramKind::Outline, &flat, origin, "ai-test");
+        assert_eq!((outline[4].x, outline[4].y), (10.0 + OUTLINE_INDENT, 20.0 + 4.0 * OUTLINE_ROW_HEIGHT));
+        assert!(parse_tree("{\"label\": \" \"}").is_err());
+    }
+}
@@ -0,0 +1,850 @@
+//! Canvas export to SVG, PNG and PDF
+//!
+//! The canvas document is drawn to SVG here rather than captured from the
+//! WebView, so exports look the same on every platform. PNG is rasterized
+//! from that SVG with resvg; PDF is a single page holding the PNG raster at
+//! the requested scale. Images are only embedded when their URL is a data
+//! URL, since the renderer does not fetch remote files.
+
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::sync::Arc;
+
+use resvg::{tiny_skia, usvg};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::errors::{LibreOllamaError, Result};
+
+/// Space around the content when no region is given
+const CONTENT_PADDING: f64 = 32.0;
+const MAX_SCALE: f64 = 8.0;
+const MIN_SCALE: f64 = 0.1;
+/// Largest raster side, in pixels
+const MAX_RASTER_SIDE: u32 = 16384;
+/// Largest raster, in pixels (64 MP, 256 MB as RGBA)
+const MAX_RASTER_PIXELS: u64 = 64_000_000;
+/// CSS pixels per PDF point
+const PX_TO_PT: f64 = 0.75;
+const DEFAULT_BACKGROUND: &str = "#ffffff";
+const DEFAULT_FONT: &str = "Inter, Arial, sans-serif";
+const STICKY_NOTE_COLOR: &str = "#fff59d";
+
+lazy_static::lazy_static! {
+    static ref FONTS: Arc<usvg::fontdb::Database> = {
+        let mut fonts = usvg::fontdb::Database::new();
+        fonts.load_system_fonts();
+        Arc::new(fonts)
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Svg,
+    Png,
+    Pdf,
+}
+
+impl ExportFormat {
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Svg => "image/svg+xml",
+            ExportFormat::Png => "image/png",
+            ExportFormat::Pdf => "application/pdf",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Svg => "svg",
+            ExportFormat::Png => "png",
+            ExportFormat::Pdf => "pdf",
+        }
+    }
+}
+
+/// Area of the canvas to export, in canvas coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ExportRegion {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    /// Whole content when omitted
+    pub region: Option<ExportRegion>,
+    /// Output pixels per canvas unit (default 1)
+    pub scale: Option<f64>,
+    /// CSS colour, or "transparent"; PDF pages are always opaque
+    pub background: Option<String>,
+    /// Export only these elements
+    pub element_ids: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CanvasExport {
+    pub format: ExportFormat,
+    pub mime_type: String,
+    pub width: u32,
+    pub height: u32,
+    #[serde(skip)]
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+struct Point {
+    x: f64,
+    y: f64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct DrawStyle {
+    color: Option<String>,
+    width: Option<f64>,
+    opacity: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct TableCell {
+    content: Option<String>,
+    text: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct EnhancedTable {
+    #[serde(default)]
+    cells: Vec<Vec<TableCell>>,
+}
+
+/// The fields of the canvas's element types that affect drawing
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct CanvasShape {
+    id: String,
+    #[serde(rename = "type")]
+    element_type: String,
+    x: f64,
+    y: f64,
+    width: Option<f64>,
+    height: Option<f64>,
+    radius: Option<f64>,
+    rotation: Option<f64>,
+    is_hidden: bool,
+    z_index: Option<f64>,
+    fill: Option<String>,
+    stroke: Option<String>,
+    stroke_width: Option<f64>,
+    corner_radius: Option<f64>,
+    opacity: Option<f64>,
+    text: Option<String>,
+    title: Option<String>,
+    font_size: Option<f64>,
+    font_family: Option<String>,
+    text_color: Option<String>,
+    text_align: Option<String>,
+    background_color: Option<String>,
+    border_color: Option<String>,
+    border_width: Option<f64>,
+    points: Option<Vec<f64>>,
+    sub_type: Option<String>,
+    start_point: Option<Point>,
+    end_point: Option<Point>,
+    intermediate_points: Option<Vec<Point>>,
+    image_url: Option<String>,
+    rows: Option<usize>,
+    cols: Option<usize>,
+    table_data: Option<Vec<Vec<TableCell>>>,
+    enhanced_table_data: Option<EnhancedTable>,
+    style: Option<DrawStyle>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bounds {
+    min_x: f64,
+    min_y: f64,
+    max_x: f64,
+    max_y: f64,
+}
+
+impl Bounds {
+    fn of_points(points: impl IntoIterator<Item = (f64, f64)>) -> Option<Bounds> {
+        points.into_iter().fold(None, |bounds: Option<Bounds>, (x, y)| {
+            Some(match bounds {
+                Some(b) => Bounds { min_x: b.min_x.min(x), min_y: b.min_y.min(y), max_x: b.max_x.max(x), max_y: b.max_y.max(y) },
+                None => Bounds { min_x: x, min_y: y, max_x: x, max_y: y },
+            })
+        })
+    }
+
+    fn union(self, other: Bounds) -> Bounds {
+        Bounds {
+            min_x: self.min_x.min(other.min_x),
+            min_y: self.min_y.min(other.min_y),
+            max_x: self.max_x.max(other.max_x),
+            max_y: self.max_y.max(other.max_y),
+        }
+    }
+
+    fn grow(self, by: f64) -> Bounds {
+        Bounds { min_x: self.min_x - by, min_y: self.min_y - by, max_x: self.max_x + by, max_y: self.max_y + by }
+    }
+}
+
+impl CanvasShape {
+    /// Absolute coordinates of a point list stored relative to the element
+    fn absolute_points(&self) -> Vec<(f64, f64)> {
+        self.points
+            .as_deref()
+            .unwrap_or_default()
+            .chunks_exact(2)
+            .map(|pair| (self.x + pair[0], self.y + pair[1]))
+            .collect()
+    }
+
+    fn connector_points(&self) -> Vec<(f64, f64)> {
+        let mut points = Vec::new();
+        points.extend(self.start_point.map(|p| (p.x, p.y)));
+        points.extend(self.intermediate_points.iter().flatten().map(|p| (p.x, p.y)));
+        points.extend(self.end_point.map(|p| (p.x, p.y)));
+        points
+    }
+
+    fn line_width(&self) -> f64 {
+        self.style.as_ref().and_then(|style| style.width).or(self.stroke_width).unwrap_or(2.0)
+    }
+
+    fn bounds(&self) -> Option<Bounds> {
+        match self.element_type.as_str() {
+            "connector" => Bounds::of_points(self.connector_points()).map(|b| b.grow(self.line_width() * 2.0)),
+            "pen" | "marker" | "highlighter" => Bounds::of_points(self.absolute_points()).map(|b| b.grow(self.line_width())),
+            "circle" => {
+                let radius = self.radius.unwrap_or(0.0);
+                Some(Bounds { min_x: self.x - radius, min_y: self.y - radius, max_x: self.x + radius, max_y: self.y + radius })
+            }
+            _ => {
+                let width = self.width.unwrap_or(0.0);
+                let height = self.height.unwrap_or_else(|| self.font_size.unwrap_or(16.0) * 1.25);
+                Some(Bounds { min_x: self.x, min_y: self.y, max_x: self.x + width, max_y: self.y + height })
+            }
+        }
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.text.as_deref().or(self.title.as_deref()).filter(|text| !text.trim().is_empty())
+    }
+}
+
+/// Elements of a canvas document: `{"elements": [...]}`, `{"elements": {id: element}}`
+/// or a bare array. Elements that cannot be read are skipped.
+fn parse_elements(canvas: &Value) -> Vec<CanvasShape> {
+    let elements = match canvas.get("elements").unwrap_or(canvas) {
+        Value::Array(elements) => elements.iter().collect::<Vec<_>>(),
+        Value::Object(elements) => elements.values().collect(),
+        _ => Vec::new(),
+    };
+    let mut shapes: Vec<CanvasShape> = elements
+        .into_iter()
+        .filter_map(|element| match serde_json::from_value::<CanvasShape>(element.clone()) {
+            Ok(shape) => Some(shape),
+            Err(e) => {
+                eprintln!("⚠️ [CANVAS] Skipping element in export: {}", e);
+                None
+            }
+        })
+        .filter(|shape| !shape.is_hidden)
+        .collect();
+    // Sections sit behind everything else, then stacking order
+    shapes.sort_by(|a, b| {
+        let key = |shape: &CanvasShape| (shape.element_type != "section", shape.z_index.unwrap_or(0.0));
+        key(a).partial_cmp(&key(b)).unwrap_or(std::cmp::Ordering::Equal)
+    });
+    shapes
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Break text into lines that fit `width`, estimating glyph widths
+fn wrap_text(text: &str, font_size: f64, width: Option<f64>) -> Vec<String> {
+    let max_chars = width.map(|width| ((width / (font_size * 0.55)).floor() as usize).max(1));
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let Some(max_chars) = max_chars else {
+            lines.push(paragraph.to_string());
+            continue;
+        };
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > max_chars {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+struct TextBox<'a> {
+    text: &'a str,
+    x: f64,
+    y: f64,
+    width: Option<f64>,
+    /// Centre the lines vertically in this height
+    height: Option<f64>,
+    padding: f64,
+    font_size: f64,
+    font_family: &'a str,
+    color: &'a str,
+    align: &'a str,
+}
+
+fn write_text(svg: &mut String, text: &TextBox) {
+    let inner_width = text.width.map(|width| (width - 2.0 * text.padding).max(1.0));
+    let lines = wrap_text(text.text, text.font_size, inner_width);
+    let line_height = text.font_size * 1.25;
+    let block = lines.len() as f64 * line_height;
+    let top = match text.height {
+        Some(height) => text.y + ((height - block) / 2.0).max(text.padding),
+        None => text.y + text.padding,
+    };
+    let (anchor, x) = match (text.align, inner_width) {
+        ("center", Some(width)) => ("middle", text.x + text.padding + width / 2.0),
+        ("right", Some(width)) => ("end", text.x + text.padding + width),
+        _ => ("start", text.x + text.padding),
+    };
+    let _ = write!(
+        svg,
+        r#"<text font-family="{}" font-size="{}" fill="{}" text-anchor="{}">"#,
+        escape(text.font_family),
+        text.font_size,
+        escape(text.color),
+        anchor
+    );
+    for (index, line) in lines.iter().enumerate() {
+        let baseline = top + index as f64 * line_height + text.font_size * 0.9;
+        let _ = write!(svg, r#"<tspan x="{}" y="{}">{}</tspan>"#, x, baseline, escape(line));
+    }
+    svg.push_str("</text>");
+}
+
+fn polyline(points: &[(f64, f64)]) -> String {
+    points.iter().map(|(x, y)| format!("{},{}", x, y)).collect::<Vec<_>>().join(" ")
+}
+
+fn write_shape(svg: &mut String, shape: &CanvasShape) {
+    let font_size = shape.font_size.unwrap_or(16.0);
+    let font_family = shape.font_family.as_deref().unwrap_or(DEFAULT_FONT);
+    let stroke = shape.stroke.as_deref().unwrap_or("none");
+    let stroke_width = shape.stroke_width.unwrap_or(1.0);
+    let align = shape.text_align.as_deref().unwrap_or("left");
+    let width = shape.width.unwrap_or(0.0);
+    let height = shape.height.unwrap_or(0.0);
+    let text_box = |text, color, padding, align, vertical_centre: bool| TextBox {
+        text,
+        x: shape.x,
+        y: shape.y,
+        width: Some(width),
+        height: vertical_centre.then_some(height),
+        padding,
+        font_size,
+        font_family,
+        color,
+        align,
+    };
+
+    if let Some(rotation) = shape.rotation.filter(|rotation| *rotation != 0.0) {
+        let _ = write!(svg, r#"<g transform="rotate({} {} {})">"#, rotation, shape.x, shape.y);
+    }
+    if let Some(opacity) = shape.opacity.filter(|opacity| *opacity < 1.0) {
+        let _ = write!(svg, r#"<g opacity="{}">"#, opacity);
+    }
+
+    match shape.element_type.as_str() {
+        "rectangle" => {
+            let _ = write!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}" fill="{}" stroke="{}" stroke-width="{}"/>"#,
+                shape.x,
+                shape.y,
+                width,
+                height,
+                shape.corner_radius.unwrap_or(0.0),
+                escape(shape.fill.as_deref().unwrap_or("none")),
+                escape(stroke),
+                stroke_width
+            );
+            if let Some(text) = shape.label() {
+                let color = shape.text_color.as_deref().unwrap_or("#1f2937");
+                write_text(svg, &text_box(text, color, 8.0, shape.text_align.as_deref().unwrap_or("center"), true));
+            }
+        }
+        "circle" => {
+            let radius = shape.radius.unwrap_or(0.0);
+            let _ = write!(
+                svg,
+                r#"<circle cx="{}" cy="{}" r="{}" fill="{}" stroke="{}" stroke-width="{}"/>"#,
+                shape.x,
+                shape.y,
+                radius,
+                escape(shape.fill.as_deref().unwrap_or("none")),
+                escape(stroke),
+                stroke_width
+            );
+            if let Some(text) = shape.label() {
+                write_text(
+                    svg,
+                    &TextBox {
+                        x: shape.x - radius,
+                        y: shape.y - radius,
+                        width: Some(radius * 2.0),
+                        height: Some(radius * 2.0),
+                        ..text_box(text, shape.text_color.as_deref().unwrap_or("#1f2937"), 8.0, "center", true)
+                    },
+                );
+            }
+        }
+        "triangle" => {
+            let points = if shape.points.as_ref().is_some_and(|points| points.len() >= 6) {
+                shape.absolute_points()
+            } else {
+                vec![(shape.x + width / 2.0, shape.y), (shape.x + width, shape.y + height), (shape.x, shape.y + height)]
+            };
+            let _ = write!(
+                svg,
+                r#"<polygon points="{}" fill="{}" stroke="{}" stroke-width="{}"/>"#,
+                polyline(&points),
+                escape(shape.fill.as_deref().unwrap_or("none")),
+                escape(stroke),
+                stroke_width
+            );
+        }
+        "text" | "rich-text" => {
+            if let Some(text) = shape.label() {
+                let color = shape.fill.as_deref().or(shape.text_color.as_deref()).unwrap_or("#000000");
+                write_text(svg, &TextBox { width: shape.width, ..text_box(text, color, 0.0, align, false) });
+            }
+        }
+        "sticky-note" => {
+            let _ = write!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" rx="4" fill="{}"/>"#,
+                shape.x,
+                shape.y,
+                width,
+                height,
+                escape(shape.background_color.as_deref().unwrap_or(STICKY_NOTE_COLOR))
+            );
+            if let Some(text) = shape.label() {
+                write_text(svg, &text_box(text, shape.text_color.as_deref().unwrap_or("#1f2937"), 12.0, align, false));
+            }
+        }
+        "section" | "group" => {
+            let fill = shape.background_color.as_deref().unwrap_or(if shape.element_type == "section" { "#f8fafc" } else { "none" });
+            let border = shape.border_color.as_deref().unwrap_or(if shape.element_type == "section" { "#cbd5e1" } else { "none" });
+            let _ = write!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}" fill="{}" stroke="{}" stroke-width="{}"/>"#,
+                shape.x,
+                shape.y,
+                width,
+                height,
+                shape.corner_radius.unwrap_or(8.0),
+                escape(fill),
+                escape(border),
+                shape.border_width.unwrap_or(1.0)
+            );
+            if let Some(title) = shape.title.as_deref().filter(|title| !title.trim().is_empty()) {
+                write_text(svg, &TextBox { font_size: 14.0, ..text_box(title, "#475569", 10.0, "left", false) });
+            }
+        }
+        "table" => write_table(svg, shape, font_family),
+        "image" => {
+            // Only inline images; a file or web URL would be opened by
+            // whatever views the SVG later
+            if let Some(url) = shape.image_url.as_deref().filter(|url| url.starts_with("data:image/")) {
+                let _ = write!(
+                    svg,
+                    r#"<image x="{}" y="{}" width="{}" height="{}" preserveAspectRatio="none" href="{}"/>"#,
+                    shape.x,
+                    shape.y,
+                    width,
+                    height,
+                    escape(url)
+                );
+            }
+        }
+        "connector" => {
+            let points = shape.connector_points();
+            let color = shape.stroke.as_deref().unwrap_or("#374151");
+            let line_width = shape.line_width();
+            let _ = write!(
+                svg,
+                r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round"/>"#,
+                polyline(&points),
+                escape(color),
+                line_width
+            );
+            if shape.sub_type.as_deref() == Some("arrow") && points.len() >= 2 {
+                let (tip_x, tip_y) = points[points.len() - 1];
+                let (from_x, from_y) = points[points.len() - 2];
+                let angle = (tip_y - from_y).atan2(tip_x - from_x);
+                let size = 6.0 + line_width * 2.0;
+                let wing = |offset: f64| (tip_x - size * (angle + offset).cos(), tip_y - size * (angle + offset).sin());
+                let _ = write!(svg, r#"<polygon points="{}" fill="{}"/>"#, polyline(&[(tip_x, tip_y), wing(0.45), wing(-0.45)]), escape(color));
+            }
+        }
+        "pen" | "marker" | "highlighter" => {
+            let style = shape.style.clone().unwrap_or_default();
+            let color = style.color.as_deref().or(shape.stroke.as_deref()).unwrap_or("#000000");
+            let opacity = style.opacity.unwrap_or(if shape.element_type == "highlighter" { 0.4 } else { 1.0 });
+            let _ = write!(
+                svg,
+                r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="{}" stroke-opacity="{}" stroke-linecap="round" stroke-linejoin="round"/>"#,
+                polyline(&shape.absolute_points()),
+                escape(color),
+                shape.line_width(),
+                opacity
+            );
+        }
+        _ => {}
+    }
+
+    if shape.opacity.is_some_and(|opacity| opacity < 1.0) {
+        svg.push_str("</g>");
+    }
+    if shape.rotation.is_some_and(|rotation| rotation != 0.0) {
+        svg.push_str("</g>");
+    }
+}
+
+fn write_table(svg: &mut String, shape: &CanvasShape, font_family: &str) {
+    let cells = shape
+        .enhanced_table_data
+        .as_ref()
+        .map(|table| table.cells.clone())
+        .filter(|cells| !cells.is_empty())
+        .or_else(|| shape.table_data.clone())
+        .unwrap_or_default();
+    let rows = shape.rows.unwrap_or(cells.len()).max(1);
+    let cols = shape.cols.unwrap_or_else(|| cells.first().map_or(1, Vec::len)).max(1);
+    let cell_width = shape.width.unwrap_or(0.0) / cols as f64;
+    let cell_height = shape.height.unwrap_or(0.0) / rows as f64;
+    let border = shape.border_color.as_deref().unwrap_or("#cbd5e1");
+
+    for row in 0..rows {
+        for col in 0..cols {
+            let x = shape.x + col as f64 * cell_width;
+            let y = shape.y + row as f64 * cell_height;
+            let _ = write!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" stroke="{}" stroke-width="{}"/>"#,
+                x,
+                y,
+                cell_width,
+                cell_height,
+                if row == 0 { "#f1f5f9" } else { "#ffffff" },
+                escape(border),
+                shape.border_width.unwrap_or(1.0)
+            );
+            let text = cells
+                .get(row)
+                .and_then(|cells| cells.get(col))
+                .and_then(|cell| cell.content.as_deref().or(cell.text.as_deref()))
+                .filter(|text| !text.trim().is_empty());
+            if let Some(text) = text {
+                write_text(
+                    svg,
+                    &TextBox {
+                        text,
+                        x,
+                        y,
+                        width: Some(cell_width),
+                        height: Some(cell_height),
+                        padding: 6.0,
+                        font_size: shape.font_size.unwrap_or(14.0),
+                        font_family,
+                        color: "#1f2937",
+                        align: "left",
+                    },
+                );
+            }
+        }
+    }
+}
+
+fn background_color(options: &ExportOptions) -> Option<String> {
+    let background = options.background.as_deref().unwrap_or(DEFAULT_BACKGROUND).trim();
+    let transparent = background.is_empty() || background.eq_ignore_ascii_case("transparent") || background == "none";
+    match (transparent, options.format) {
+        (true, ExportFormat::Pdf) => Some(DEFAULT_BACKGROUND.to_string()),
+        (true, _) => None,
+        (false, _) => Some(background.to_string()),
+    }
+}
+
+fn scale(options: &ExportOptions) -> f64 {
+    options.scale.filter(|scale| scale.is_finite()).unwrap_or(1.0).clamp(MIN_SCALE, MAX_SCALE)
+}
+
+/// SVG of the canvas and the exported region
+pub fn render_svg(canvas: &Value, options: &ExportOptions) -> Result<(String, ExportRegion)> {
+    let mut shapes = parse_elements(canvas);
+    if let Some(ids) = &options.element_ids {
+        shapes.retain(|shape| ids.contains(&shape.id));
+    }
+
+    let region = match options.region {
+        Some(region) if region.width > 0.0 && region.height > 0.0 => region,
+        Some(_) => {
+            return Err(LibreOllamaError::InvalidInput {
+                message: "The export region must have a positive width and height".to_string(),
+                field: Some("region".to_string()),
+            })
+        }
+        None => {
+            let bounds = shapes.iter().filter_map(CanvasShape::bounds).reduce(Bounds::union).ok_or_else(|| {
+                LibreOllamaError::InvalidInput {
+                    message: "The canvas has nothing to export".to_string(),
+                    field: Some("canvas".to_string()),
+                }
+            })?;
+            let bounds = bounds.grow(CONTENT_PADDING);
+            ExportRegion {
+                x: bounds.min_x,
+                y: bounds.min_y,
+                width: (bounds.max_x - bounds.min_x).max(1.0),
+                height: (bounds.max_y - bounds.min_y).max(1.0),
+            }
+        }
+    };
+
+    let scale = scale(options);
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="{}" height="{}" viewBox="{} {} {} {}">"#,
+        (region.width * scale).round(),
+        (region.height * scale).round(),
+        region.x,
+        region.y,
+        region.width,
+        region.height
+    );
+    if let Some(background) = background_color(options) {
+        let _ = write!(
+            svg,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+            region.x,
+            region.y,
+            region.width,
+            region.height,
+            escape(&background)
+        );
+    }
+    for shape in &shapes {
+        write_shape(&mut svg, shape);
+    }
+    svg.push_str("</svg>");
+    Ok((svg, region))
+}
+
+fn rasterize(svg: &str) -> Result<tiny_skia::Pixmap> {
+    let options = usvg::Options { fontdb: FONTS.clone(), ..Default::default() };
+    let tree = usvg::Tree::from_str(svg, &options).map_err(|e| LibreOllamaError::Serialization {
+        message: format!("Failed to read the canvas drawing: {}", e),
+        data_type: "svg".to_string(),
+    })?;
+    let size = tree.size().to_int_size();
+    if size.width() > MAX_RASTER_SIDE || size.height() > MAX_RASTER_SIDE {
+        return Err(LibreOllamaError::InvalidInput {
+            message: format!(
+                "The export would be {}x{} pixels; use a smaller scale or region (at most {} pixels per side)",
+                size.width(),
+                size.height(),
+                MAX_RASTER_SIDE
+            ),
+            field: Some("scale".to_string()),
+        });
+    }
+    if size.width() as u64 * size.height() as u64 > MAX_RASTER_PIXELS {
+        return Err(LibreOllamaError::InvalidInput {
+            message: format!(
+                "The export would be {}x{} pixels; use a smaller scale or region (at most {} megapixels)",
+                size.width(),
+                size.height(),
+                MAX_RASTER_PIXELS / 1_000_000
+            ),
+            field: Some("scale".to_string()),
+        });
+    }
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height()).ok_or_else(|| LibreOllamaError::InvalidInput {
+        message: "The export region is empty".to_string(),
+        field: Some("region".to_string()),
+    })?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    Ok(pixmap)
+}
+
+/// One-page PDF showing an opaque RGB raster over `width_pt` × `height_pt`
+pub fn raster_pdf(rgb: &[u8], pixel_width: u32, pixel_height: u32, width_pt: f64, height_pt: f64) -> Result<Vec<u8>> {
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(rgb)?;
+    let image = encoder.finish()?;
+    let content = format!("q {:.2} 0 0 {:.2} 0 0 cm /Im0 Do Q", width_pt, height_pt);
+
+    let mut pdf: Vec<u8> = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+    let mut offsets = Vec::new();
+    let mut object = |pdf: &mut Vec<u8>, header: String, stream: Option<&[u8]>| {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n{}\n", offsets.len(), header).as_bytes());
+        if let Some(stream) = stream {
+            pdf.extend_from_slice(b"stream\n");
+            pdf.extend_from_slice(stream);
+            pdf.extend_from_slice(b"\nendstream\n");
+        }
+        pdf.extend_from_slice(b"endobj\n");
+    };
+    object(&mut pdf, "<< /Type /Catalog /Pages 2 0 R >>".to_string(), None);
+    object(&mut pdf, "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(), None);
+    object(
+        &mut pdf,
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] /Resources << /XObject << /Im0 4 0 R >> >> /Contents 5 0 R >>",
+            width_pt, height_pt
+        ),
+        None,
+    );
+    object(
+        &mut pdf,
+        format!(
+            "<< /Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /FlateDecode /Length {} >>",
+            pixel_width,
+            pixel_height,
+            image.len()
+        ),
+        Some(&image),
+    );
+    object(&mut pdf, format!("<< /Length {} >>", content.len()), Some(content.as_bytes()));
+
+    let xref = pdf.len();
+    let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", offsets.len() + 1);
+    for offset in &offsets {
+        let _ = writeln!(table, "{:010} 00000 n ", offset);
+    }
+    let _ = write!(table, "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", offsets.len() + 1, xref);
+    pdf.extend_from_slice(table.as_bytes());
+    Ok(pdf)
+}
+
+/// Render a canvas document in the requested format
+pub fn export(canvas: &Value, options: &ExportOptions) -> Result<CanvasExport> {
+    let (svg, region) = render_svg(canvas, options)?;
+    let scale = scale(options);
+    let (bytes, width, height) = match options.format {
+        ExportFormat::Svg => {
+            let (width, height) = ((region.width * scale).round() as u32, (region.height * scale).round() as u32);
+            (svg.into_bytes(), width, height)
+        }
+        ExportFormat::Png => {
+            let pixmap = rasterize(&svg)?;
+            let png = pixmap.encode_png().map_err(|e| LibreOllamaError::Serialization {
+                message: format!("Failed to encode PNG: {}", e),
+                data_type: "png".to_string(),
+            })?;
+            (png, pixmap.width(), pixmap.height())
+        }
+        ExportFormat::Pdf => {
+            let pixmap = rasterize(&svg)?;
+            // The background is opaque, so premultiplied pixels equal plain RGB
+            let rgb: Vec<u8> = pixmap.data().chunks_exact(4).flat_map(|pixel| [pixel[0], pixel[1], pixel[2]]).collect();
+            let pdf = raster_pdf(&rgb, pixmap.width(), pixmap.height(), region.width * PX_TO_PT, region.height * PX_TO_PT)?;
+            (pdf, pixmap.width(), pixmap.height())
+        }
+    };
+    Ok(CanvasExport { format: options.format, mime_type: options.format.mime_type().to_string(), width, height, bytes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn options(format: ExportFormat) -> ExportOptions {
+        ExportOptions { format, region: None, scale: None, background: None, element_ids: None }
+    }
+
+    fn canvas() -> Value {
+        json!({
+            "elements": {
+                "a": {"id": "a", "type": "sticky-note", "x": 0, "y": 0, "width": 200, "height": 100, "text": "Ship <beta>"},
+                "b": {"id": "b", "type": "circle", "x": 400, "y": 50, "radius": 50, "fill": "#93c5fd"},
+                "c": {"id": "c", "type": "connector", "subType": "arrow", "startPoint": {"x": 200, "y": 50}, "endPoint": {"x": 350, "y": 50}},
+                "d": {"id": "d", "type": "pen", "x": 0, "y": 0, "points": [0, 300, 50, 320], "isHidden": true},
+                "e": {"id": "e", "type": "unknown-widget", "x": "not a number"}
+            }
+        })
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let (svg, region) = render_svg(&canvas(), &options(ExportFormat::Svg)).unwrap();
+        // Content runs from (0, 0) to (450, 100); the hidden pen is left out
+        assert_eq!(region, ExportRegion { x: -32.0, y: -32.0, width: 514.0, height: 164.0 });
+        assert!(svg.contains("Ship &lt;beta&gt;"));
+        assert!(svg.contains(r##"<circle cx="400" cy="50" r="50" fill="#93c5fd""##));
+        assert_eq!(svg.matches("<polygon").count(), 1);
+
+        let only_note = ExportOptions { element_ids: Some(vec!["a".to_string()]), scale: Some(2.0), ..options(ExportFormat::Svg) };
+        let (svg, region) = render_svg(&canvas(), &only_note).unwrap();
+        assert_eq!(region.width, 264.0);
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="528""#));
+        assert!(!svg.contains("<circle"));
+
+        assert!(render_svg(&json!({"elements": []}), &options(ExportFormat::Svg)).is_err());
+    }
+
+    #[test]
+    fn test_raster_exports() {
+        let region = ExportRegion { x: 0.0, y: 0.0, width: 40.0, height: 20.0 };
+        let png = export(&canvas(), &ExportOptions { region: Some(region), scale: Some(0.5), ..options(ExportFormat::Png) }).unwrap();
+        assert_eq!((png.width, png.height), (20, 10));
+        assert!(png.bytes.starts_with(b"\x89PNG"));
+
+        let pdf = export(&canvas(), &ExportOptions { region: Some(region), ..options(ExportFormat::Pdf) }).unwrap();
+        let text = String::from_utf8_lossy(&pdf.bytes);
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.contains("/MediaBox [0 0 30.00 15.00]"));
+        assert!(text.trim_end().ends_with("%%EOF"));
+
+        let huge = ExportRegion { x: 0.0, y: 0.0, width: 10000.0, height: 10000.0 };
+        assert!(export(&canvas(), &ExportOptions { region: Some(huge), ..options(ExportFormat::Png) }).is_err());
+    }
+
+    #[test]
+    fn test_only_inline_images() {
+        let images = json!({"elements": [
+            {"id": "a", "type": "image", "x": 0, "y": 0, "width": 10, "height": 10, "imageUrl": "data:image/png;base64,AAAA"},
+            {"id": "b", "type": "image", "x": 20, "y": 0, "width": 10, "height": 10, "imageUrl": "file:///etc/passwd"}
+        ]});
+        let (svg, _) = render_svg(&images, &options(ExportFormat::Svg)).unwrap();
+        assert_eq!(svg.matches("<image").count(), 1);
+        assert!(!svg.contains("file://"));
+    }
+
+    #[test]
+    fn test_wrap_text() {
+        assert_eq!(wrap_text("one two three four", 10.0, Some(60.0)), ["one two", "three four"]);
+        assert_eq!(wrap_text("a\nb", 10.0, None), ["a", "b"]);
+    }
+}
@@ -0,0 +1,528 @@
+//! Files attached to chat sessions
+//!
+//! Text is extracted from a PDF, Word document or plain-text file, split
+//! into chunks of a few hundred tokens at paragraph or sentence breaks.
+//! The chunks are embedded with the embeddings model later, while the user
+//! is idle (see `EmbeddingBackfill`). When a session is
+//! answered, the chunks closest to the latest question are added to the
+//! prompt as numbered excerpts, and the reply returns them as citations
+//! with their character offsets in the extracted text. Without an
+//! embeddings model, and until a file is embedded, chunks are matched by
+//! the words they share with the question instead, as are chunks embedded
+//! by a model whose vectors differ in size from the question's.
+
+use std::collections::HashSet;
+use std::io::{Cursor, Read};
+use std::sync::Arc;
+use std::time::Duration;
+
+use regex::Regex;
+use rusqlite::Connection;
+use serde::Serialize;
+
+use crate::database::operations::chat_attachment_operations::{self, AttachmentChunk, ChatAttachment, NewChunk};
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::llm_provider::{self, ChatMessage};
+use crate::services::pdf_extract;
+use crate::services::prompt_templates;
+use crate::services::task_supervisor::TaskSpec;
+use crate::utils::tokens;
+
+/// Larger files are refused rather than read into memory
+pub const MAX_ATTACHMENT_BYTES: usize = 25 * 1024 * 1024;
+/// Largest body a Word document may unpack to; zip compression makes a
+/// small file able to claim far more
+const MAX_DOCX_XML_BYTES: u64 = 64 * 1024 * 1024;
+/// Target size of one chunk
+const CHUNK_TOKENS: usize = 400;
+/// Chunks embedded per request
+const EMBED_BATCH: usize = 32;
+/// How often files still to be embedded are looked for
+const EMBED_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// Files embedded per run, so one run does not hold the model for long
+const EMBED_FILES_PER_RUN: usize = 4;
+/// Most excerpts added to one prompt
+const MAX_EXCERPTS: usize = 6;
+/// Prompt template feature introducing the excerpts
+const EXCERPT_PROMPT_FEATURE: &str = "chat.excerpts";
+
+lazy_static::lazy_static! {
+    static ref DOCX_PARAGRAPH_END: Regex = Regex::new(r"</w:p>|<w:br\s*/>|<w:tab\s*/>").unwrap();
+    static ref DOCX_TEXT: Regex = Regex::new(r"(?s)<w:t(?:\s[^>]*)?>(.*?)</w:t>|\n|\t").unwrap();
+    static ref WORD: Regex = Regex::new(r"\w{3,}").unwrap();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentKind {
+    Pdf,
+    Docx,
+    Text,
+}
+
+/// What kind of document a file is, if text can be read from it
+pub fn document_kind(mime_type: &str, filename: &str) -> Option<DocumentKind> {
+    let extension = filename.rsplit_once('.').map(|(_, ext)| ext.to_lowercase()).unwrap_or_default();
+    match (mime_type, extension.as_str()) {
+        ("application/pdf", _) | (_, "pdf") => Some(DocumentKind::Pdf),
+        ("application/vnd.openxmlformats-officedocument.wordprocessingml.document", _) | (_, "docx") => {
+            Some(DocumentKind::Docx)
+        }
+        (_, "txt" | "md" | "markdown" | "csv" | "json" | "log" | "xml" | "html" | "htm") => Some(DocumentKind::Text),
+        (mime, _) if mime.starts_with("text/") => Some(DocumentKind::Text),
+        _ => None,
+    }
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Paragraph text of a .docx, read from `word/document.xml`
+fn docx_text(bytes: &[u8]) -> Result<String> {
+    docx_text_within(bytes, MAX_DOCX_XML_BYTES)
+}
+
+/// `docx_text` refusing bodies over `max_bytes` unpacked
+fn docx_text_within(bytes: &[u8], max_bytes: u64) -> Result<String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| LibreOllamaError::InvalidInput {
+        message: format!("Not a valid Word document: {}", e),
+        field: Some("file".to_string()),
+    })?;
+    let body = archive.by_name("word/document.xml").map_err(|e| LibreOllamaError::InvalidInput {
+        message: format!("Word document has no body: {}", e),
+        field: Some("file".to_string()),
+    })?;
+    let too_large = || LibreOllamaError::InvalidInput {
+        message: "Word document is too large to read".to_string(),
+        field: Some("file".to_string()),
+    };
+    if body.size() > max_bytes {
+        return Err(too_large());
+    }
+    // The declared size can lie, so the read is capped as well
+    let mut xml = String::new();
+    body.take(max_bytes + 1).read_to_string(&mut xml)?;
+    if xml.len() as u64 > max_bytes {
+        return Err(too_large());
+    }
+
+    // Mark paragraph ends and tabs, then keep only the text runs
+    let marked = DOCX_PARAGRAPH_END.replace_all(&xml, |caps: &regex::Captures| {
+        if caps[0].starts_with("<w:tab") { "\t" } else { "\n" }
+    });
+    let text: String = DOCX_TEXT
+        .captures_iter(&marked)
+        .map(|caps| caps.get(1).map_or_else(|| caps[0].to_string(), |run| unescape_xml(run.as_str())))
+        .collect();
+    Ok(text)
+}
+
+/// Text of a file; scanned PDF pages are read by OCR when it is installed.
+/// Blocking.
+pub fn extract_text(kind: DocumentKind, bytes: &[u8]) -> Result<String> {
+    let text = match kind {
+        DocumentKind::Pdf => {
+            let document = pdf_extract::extract(bytes, true)?;
+            let text = document.text();
+            if text.is_empty() && !document.ocr_available {
+                return Err(LibreOllamaError::InvalidInput {
+                    message: "The PDF has no text layer; install tesseract and poppler to read scanned pages".to_string(),
+                    field: Some("file".to_string()),
+                });
+            }
+            text
+        }
+        DocumentKind::Docx => docx_text(bytes)?,
+        DocumentKind::Text => String::from_utf8_lossy(bytes).into_owned(),
+    };
+    Ok(text.replace("\r\n", "\n"))
+}
+
+/// A piece of extracted text; offsets are character positions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextChunk {
+    pub start: usize,
+    pub end: usize,
+    pub content: String,
+}
+
+/// Split `text` into chunks of at most `max_tokens`, preferring to end a
+/// chunk at a paragraph or sentence break in its second half
+pub fn chunk_text(text: &str, max_tokens: usize) -> Vec<TextChunk> {
+    let mut chunks = Vec::new();
+    let mut byte_pos = 0;
+    let mut char_pos = 0;
+
+    while byte_pos < text.len() {
+        let rest = &text[byte_pos..];
+        let mut piece = tokens::truncate_to_tokens(rest, max_tokens);
+        if piece.is_empty() {
+            // A single unbroken run longer than the budget
+            piece = rest.char_indices().nth(max_tokens * 4).map_or(rest, |(end, _)| &rest[..end]);
+        }
+        if piece.len() < rest.len() {
+            let half = piece.len() / 2;
+            let cut = piece
+                .rfind("\n\n")
+                .filter(|&at| at > half)
+                .or_else(|| piece.rfind(". ").filter(|&at| at > half))
+                .or_else(|| piece.rfind('\n').filter(|&at| at > half));
+            if let Some(at) = cut {
+                piece = &piece[..at + 1];
+            }
+        }
+
+        let content = piece.trim();
+        if !content.is_empty() {
+            let lead = piece.len() - piece.trim_start().len();
+            let start = char_pos + piece[..lead].chars().count();
+            chunks.push(TextChunk { start, end: start + content.chars().count(), content: content.to_string() });
+        }
+        byte_pos += piece.len();
+        char_pos += piece.chars().count();
+    }
+    chunks
+}
+
+/// `vectors` if there is one per input, all of the same, non-zero size as
+/// those seen before
+fn check_embeddings(vectors: Vec<Vec<f32>>, inputs: usize, dimensions: &mut Option<usize>) -> Result<Vec<Vec<f32>>> {
+    if vectors.len() != inputs {
+        return Err(LibreOllamaError::Internal {
+            message: format!("Expected {} embeddings, got {}", inputs, vectors.len()),
+        });
+    }
+    for vector in &vectors {
+        let expected = *dimensions.get_or_insert(vector.len());
+        if vector.is_empty() || vector.len() != expected {
+            return Err(LibreOllamaError::Internal {
+                message: format!("Embedding has {} dimensions, expected {}", vector.len(), expected),
+            });
+        }
+    }
+    Ok(vectors)
+}
+
+/// Extract and chunk a file, and attach it to a chat session
+pub async fn attach(
+    db_manager: &DatabaseManager,
+    session_id: i32,
+    source_key: &str,
+    filename: &str,
+    mime_type: &str,
+    bytes: &[u8],
+) -> Result<ChatAttachment> {
+    let kind = document_kind(mime_type, filename).ok_or_else(|| LibreOllamaError::InvalidInput {
+        message: format!("Text cannot be read from {}; attach a PDF, Word or text file", filename),
+        field: Some("filename".to_string()),
+    })?;
+    if bytes.len() > MAX_ATTACHMENT_BYTES {
+        return Err(LibreOllamaError::InvalidInput {
+            message: format!("{} is too large to attach", filename),
+            field: Some("file".to_string()),
+        });
+    }
+
+    let owned = bytes.to_vec();
+    let text = tokio::task::spawn_blocking(move || extract_text(kind, &owned))
+        .await
+        .map_err(|e| LibreOllamaError::Internal { message: format!("Text extraction failed: {}", e) })??;
+    let chunks = chunk_text(&text, CHUNK_TOKENS);
+    if chunks.is_empty() {
+        return Err(LibreOllamaError::InvalidInput {
+            message: format!("{} contains no readable text", filename),
+            field: Some("file".to_string()),
+        });
+    }
+
+    let new_chunks: Vec<NewChunk> = chunks
+        .iter()
+        .map(|chunk| NewChunk { start_offset: chunk.start, end_offset: chunk.end, content: &chunk.content })
+        .collect();
+
+    let mut conn = db_manager.get_connection()?;
+    let attachment = chat_attachment_operations::save_attachment(
+        &mut conn,
+        session_id,
+        source_key,
+        filename,
+        mime_type,
+        text.chars().count(),
+        &new_chunks,
+    )?;
+    Ok(attachment)
+}
+
+/// Embeds attached files in the background, while the user is idle
+#[derive(Clone)]
+pub struct EmbeddingBackfill {
+    db_manager: Arc<DatabaseManager>,
+}
+
+impl EmbeddingBackfill {
+    pub fn new(db_manager: Arc<DatabaseManager>) -> Self {
+        Self { db_manager }
+    }
+
+    pub fn task(&self) -> TaskSpec {
+        let backfill = self.clone();
+        TaskSpec::new("chat_embeddings", move || {
+            let backfill = backfill.clone();
+            async move { Ok(backfill.embed_pending().await?) }
+        })
+        .every(|| EMBED_INTERVAL)
+        .first_run_after(EMBED_INTERVAL)
+        .jitter(0.1)
+        .when_idle(EMBED_INTERVAL * 6)
+    }
+
+    /// Embed the chunks of up to `EMBED_FILES_PER_RUN` files. A file is
+    /// saved only once all its chunks are embedded, so a failure leaves it
+    /// to keyword matching and the next run.
+    pub async fn embed_pending(&self) -> Result<()> {
+        for _ in 0..EMBED_FILES_PER_RUN {
+            let pending = {
+                let conn = self.db_manager.get_connection()?;
+                match chat_attachment_operations::next_unembedded_attachment(&conn)? {
+                    Some(attachment_id) => (attachment_id, chat_attachment_operations::unembedded_chunks(&conn, attachment_id)?),
+                    None => return Ok(()),
+                }
+            };
+            let (attachment_id, chunks) = pending;
+
+            let mut embeddings = Vec::with_capacity(chunks.len());
+            let mut dimensions = None;
+            for batch in chunks.chunks(EMBED_BATCH) {
+                let inputs: Vec<String> = batch.iter().map(|(_, content)| content.clone()).collect();
+                let vectors = check_embeddings(llm_provider::embed(&self.db_manager, &inputs).await?, inputs.len(), &mut dimensions)?;
+                embeddings.extend(batch.iter().map(|(chunk_index, _)| *chunk_index).zip(vectors));
+            }
+
+            let mut conn = self.db_manager.get_connection()?;
+            chat_attachment_operations::set_chunk_embeddings(&mut conn, attachment_id, &embeddings)?;
+        }
+        Ok(())
+    }
+}
+
+/// An excerpt of an attached file used to answer a question
+#[derive(Debug, Clone, Serialize)]
+pub struct Citation {
+    pub attachment_id: i64,
+    pub filename: String,
+    pub chunk_index: i64,
+    pub start_offset: i64,
+    pub end_offset: i64,
+    pub excerpt: String,
+    pub score: f32,
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = a.iter().map(|x| x * x).sum::<f32>().sqrt() * b.iter().map(|y| y * y).sum::<f32>().sqrt();
+    if norm == 0.0 { 0.0 } else { dot / norm }
+}
+
+fn words(text: &str) -> HashSet<String> {
+    WORD.find_iter(text).map(|word| word.as_str().to_lowercase()).collect()
+}
+
+/// Share of the question's words found in the chunk
+fn keyword_score(question: &HashSet<String>, chunk: &str) -> f32 {
+    if question.is_empty() {
+        return 0.0;
+    }
+    let chunk_words = words(chunk);
+    question.intersection(&chunk_words).count() as f32 / question.len() as f32
+}
+
+/// Pick the best-scoring chunks that fit in `max_tokens`
+pub fn select_excerpts(mut scored: Vec<(f32, AttachmentChunk)>, max_tokens: usize) -> Vec<(f32, AttachmentChunk)> {
+    scored.retain(|(score, _)| *score > 0.0);
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut used = 0;
+    let mut selected = Vec::new();
+    for (score, chunk) in scored {
+        let cost = tokens::count_tokens(&chunk.content);
+        if used + cost > max_tokens {
+            continue;
+        }
+        used += cost;
+        selected.push((score, chunk));
+        if selected.len() == MAX_EXCERPTS {
+            break;
+        }
+    }
+    selected
+}
+
+/// Excerpts of the session's files most relevant to `question`
+pub async fn relevant_excerpts(
+    db_manager: &DatabaseManager,
+    session_id: i32,
+    question: &str,
+    max_tokens: usize,
+) -> Result<Vec<Citation>> {
+    let (attachments, chunks) = {
+        let conn = db_manager.get_connection()?;
+        (
+            chat_attachment_operations::list_attachments(&conn, session_id)?,
+            chat_attachment_operations::list_session_chunks(&conn, session_id)?,
+        )
+    };
+    if chunks.is_empty() || question.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let query_embedding = if chunks.iter().any(|chunk| chunk.embedding.is_some()) {
+        match llm_provider::embed(db_manager, &[question.to_string()]).await {
+            Ok(mut vectors) => vectors.pop(),
+            Err(e) => {
+                eprintln!("⚠️ [CHAT] Matching attachments by keyword: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let question_words = words(question);
+    let scored = chunks
+        .into_iter()
+        .map(|chunk| {
+            let score = match (&query_embedding, &chunk.embedding) {
+                (Some(query), Some(embedding)) if query.len() == embedding.len() => cosine_similarity(query, embedding),
+                // Not embedded, or by a model other than the current one
+                _ => keyword_score(&question_words, &chunk.content),
+            };
+            (score, chunk)
+        })
+        .collect();
+
+    Ok(select_excerpts(scored, max_tokens)
+        .into_iter()
+        .map(|(score, chunk)| Citation {
+            filename: attachments
+                .iter()
+                .find(|attachment| attachment.id == chunk.attachment_id)
+                .map(|attachment| attachment.filename.clone())
+                .unwrap_or_default(),
+            attachment_id: chunk.attachment_id,
+            chunk_index: chunk.chunk_index,
+            start_offset: chunk.start_offset,
+            end_offset: chunk.end_offset,
+            excerpt: chunk.content,
+            score,
+        })
+        .collect())
+}
+
+/// System message listing the excerpts; the model cites them as [1], [2]...
+pub fn excerpt_prompt(conn: &Connection, citations: &[Citation]) -> Result<Option<ChatMessage>> {
+    if citations.is_empty() {
+        return Ok(None);
+    }
+    let excerpts: Vec<String> = citations
+        .iter()
+        .enumerate()
+        .map(|(index, citation)| {
+            format!(
+                "[{}] {} (characters {}-{}):\n{}",
+                index + 1,
+                citation.filename,
+                citation.start_offset,
+                citation.end_offset,
+                citation.excerpt
+            )
+        })
+        .collect();
+    let mut values = serde_json::Map::new();
+    values.insert("excerpts".to_string(), excerpts.join("\n\n").into());
+    let prompt = prompt_templates::render_feature_prompt(conn, EXCERPT_PROMPT_FEATURE, &values)?;
+    Ok(Some(ChatMessage::system(prompt)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_offsets() {
+        let text = "First paragraph about the budget.\n\nSecond paragraph about the launch date. It moved to May.";
+        let chunks = chunk_text(text, 12);
+        assert!(chunks.len() >= 2);
+        let chars: Vec<char> = text.chars().collect();
+        for chunk in &chunks {
+            let cited: String = chars[chunk.start..chunk.end].iter().collect();
+            assert_eq!(cited, chunk.content);
+        }
+        assert_eq!(chunks[0].content, "First paragraph about the budget.");
+
+        // Offsets count characters, not bytes
+        let accented = chunk_text("Café crème. Déjà vu.", 100);
+        assert_eq!((accented[0].start, accented[0].end), (0, 20));
+    }
+
+    #[test]
+    fn test_document_kind_and_selection() {
+        assert_eq!(document_kind("application/pdf", "report"), Some(DocumentKind::Pdf));
+        assert_eq!(document_kind("application/octet-stream", "Notes.DOCX"), Some(DocumentKind::Docx));
+        assert_eq!(document_kind("text/plain", "readme"), Some(DocumentKind::Text));
+        assert_eq!(document_kind("image/png", "photo.png"), None);
+
+        let chunk = |index: i64, content: &str| AttachmentChunk {
+            attachment_id: 1,
+            chunk_index: index,
+            start_offset: 0,
+            end_offset: content.chars().count() as i64,
+            content: content.to_string(),
+            embedding: None,
+        };
+        let question = words("When is the launch?");
+        let scored = vec![
+            (keyword_score(&question, "Budget is fixed."), chunk(0, "Budget is fixed.")),
+            (keyword_score(&question, "The launch moved to May."), chunk(1, "The launch moved to May.")),
+        ];
+        let selected = select_excerpts(scored, 100);
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].1.chunk_index, 1);
+
+        assert!((cosine_similarity(&[1.0, 0.0], &[1.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]), 0.0);
+    }
+
+    #[test]
+    fn test_embeddings_must_agree_in_size() {
+        let mut dimensions = None;
+        assert!(check_embeddings(vec![vec![0.1, 0.2], vec![0.3, 0.4]], 2, &mut dimensions).is_ok());
+        assert_eq!(dimensions, Some(2));
+        // A later batch from a model with other dimensions is refused
+        assert!(check_embeddings(vec![vec![0.1, 0.2, 0.3]], 1, &mut dimensions).is_err());
+        assert!(check_embeddings(vec![vec![0.1, 0.2]], 2, &mut dimensions).is_err());
+        assert!(check_embeddings(vec![Vec::new()], 1, &mut None).is_err());
+    }
+
+    #[test]
+    fn test_oversized_docx_bodies_are_refused() {
+        let mut bytes = Vec::new();
+        {
+            let mut writer = zip::ZipWriter::new(Cursor::new(&mut bytes));
+            let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+            writer.start_file("word/document.xml", options).unwrap();
+            let body = "<w:p><w:r><w:t>Quarterly numbers</w:t></w:r></w:p>".repeat(100);
+            std::io::Write::write_all(&mut writer, body.as_bytes()).unwrap();
+            writer.finish().unwrap();
+        }
+        assert!(docx_text_within(&bytes, 1024 * 1024).unwrap().starts_with("Quarterly numbers\n"));
+        assert!(docx_text_within(&bytes, 1024).is_err());
+    }
+}
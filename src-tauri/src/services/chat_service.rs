@@ -0,0 +1,155 @@
+//! Chat replies from the configured chat model
+//!
+//! Builds the prompt for a chat session from its stored messages, with the
+//! default `chat.system` template as system prompt when one is set. Long
+//! sessions are folded into a rolling summary (see `conversation_memory`)
+//! that is sent ahead of the recent turns; the provider layer then fits the
+//! prompt to the model's context window. Excerpts of files attached to the
+//! session that match the latest question are added as a numbered system
+//! message. The reply is stored in the session and returned with its token
+//! usage and the excerpts it could cite.
+
+use std::sync::Arc;
+
+use rusqlite::Connection;
+use serde::Serialize;
+use serde_json::Map;
+
+use crate::database::operations::chat_operations;
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::chat_attachments::{self, Citation};
+use crate::services::conversation_memory::{self, SessionMemory};
+use crate::services::llm_provider::{self, ChatMessage, LlmFeature, TokenUsage};
+use crate::services::prompt_templates;
+use crate::utils::tokens;
+
+/// Prompt feature whose default template is the chat system prompt
+const SYSTEM_PROMPT_FEATURE: &str = "chat.system";
+/// Share of the context window (1/n) given to attachment excerpts
+const EXCERPT_WINDOW_SHARE: usize = 4;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatReply {
+    pub message_id: i32,
+    pub content: String,
+    pub model: String,
+    pub usage: TokenUsage,
+    pub latency_ms: u64,
+    pub context_window: usize,
+    /// Oldest session messages left out of the prompt
+    pub dropped_messages: usize,
+    pub truncated: bool,
+    /// Messages covered by the session summary instead of being sent
+    pub summarized_messages: i64,
+    /// Attachment excerpts given to the model; `[n]` in the reply refers to
+    /// the n-th entry
+    pub citations: Vec<Citation>,
+}
+
+fn system_prompt(conn: &Connection) -> Option<ChatMessage> {
+    match prompt_templates::render_for_feature(conn, SYSTEM_PROMPT_FEATURE, &Map::new()) {
+        Ok(system) => system.map(ChatMessage::system),
+        Err(e) => {
+            eprintln!("⚠️ [CHAT] Ignoring chat system prompt: {}", e);
+            None
+        }
+    }
+}
+
+/// Prompt messages for a session: system prompt, session summary, attachment
+/// excerpts, then the turns the summary does not cover, oldest first
+pub fn session_prompt(
+    conn: &Connection,
+    session_id: i32,
+    memory: &SessionMemory,
+    citations: &[Citation],
+) -> Result<Vec<ChatMessage>> {
+    let history = chat_operations::get_chat_messages_by_session(conn, session_id)?;
+    if history.is_empty() {
+        return Err(LibreOllamaError::InvalidInput {
+            message: "The chat session has no messages to reply to".to_string(),
+            field: Some("session_id".to_string()),
+        });
+    }
+
+    let mut messages = Vec::with_capacity(history.len() + 3);
+    messages.extend(system_prompt(conn));
+    messages.extend(memory.prompt_message());
+    messages.extend(chat_attachments::excerpt_prompt(conn, citations)?);
+    messages.extend(
+        memory
+            .pending(&history)
+            .iter()
+            .map(|message| ChatMessage { role: message.role.clone(), content: message.content.clone() }),
+    );
+    Ok(messages)
+}
+
+pub struct ChatService {
+    db_manager: Arc<DatabaseManager>,
+}
+
+impl ChatService {
+    pub fn new(db_manager: Arc<DatabaseManager>) -> Self {
+        Self { db_manager }
+    }
+
+    /// Answer the latest message of a session and store the answer
+    pub async fn reply(&self, session_id: i32) -> Result<ChatReply> {
+        let (context_window, system_tokens, question) = {
+            let conn = self.db_manager.get_connection()?;
+            if chat_operations::get_chat_session(&conn, session_id)?.is_none() {
+                return Err(LibreOllamaError::NotFound { resource: format!("chat session {}", session_id) });
+            }
+            let (provider, model) = llm_provider::resolve(&conn, LlmFeature::Chat)?;
+            let system_tokens = system_prompt(&conn).map_or(0, |message| tokens::message_tokens(&message.content));
+            let question = chat_operations::get_chat_messages_by_session(&conn, session_id)?
+                .into_iter()
+                .rev()
+                .find(|message| message.role == "user")
+                .map(|message| message.content)
+                .unwrap_or_default();
+            (provider.context_window(&model), system_tokens, question)
+        };
+
+        let excerpt_budget = context_window / EXCERPT_WINDOW_SHARE;
+        let citations =
+            match chat_attachments::relevant_excerpts(&self.db_manager, session_id, &question, excerpt_budget).await {
+                Ok(citations) => citations,
+                Err(e) => {
+                    eprintln!("⚠️ [CHAT] Could not search attachments of session {}: {}", session_id, e);
+                    Vec::new()
+                }
+            };
+        let excerpts = chat_attachments::excerpt_prompt(&self.db_manager.get_connection()?, &citations)?;
+        let fixed_tokens = system_tokens + excerpts.map_or(0, |message| tokens::message_tokens(&message.content));
+
+        // Without a summary the oldest turns are simply dropped to fit
+        let memory = match conversation_memory::refresh(&self.db_manager, session_id, fixed_tokens, context_window).await {
+            Ok(memory) => memory,
+            Err(e) => {
+                eprintln!("⚠️ [CHAT] Could not update memory of session {}: {}", session_id, e);
+                conversation_memory::load_memory(&self.db_manager.get_connection()?, session_id)?
+            }
+        };
+        let messages = session_prompt(&self.db_manager.get_connection()?, session_id, &memory, &citations)?;
+
+        let completion = llm_provider::complete(&self.db_manager, LlmFeature::Chat, &messages).await?;
+
+        let conn = self.db_manager.get_connection()?;
+        let message_id = chat_operations::create_chat_message(&conn, session_id, "assistant", &completion.content)?;
+        Ok(ChatReply {
+            message_id,
+            content: completion.content,
+            model: completion.model,
+            usage: completion.usage,
+            latency_ms: completion.latency_ms,
+            context_window: completion.context_window,
+            dropped_messages: completion.dropped_messages,
+            truncated: completion.truncated,
+            summarized_messages: memory.summarized_messages,
+            citations,
+        })
+    }
+}
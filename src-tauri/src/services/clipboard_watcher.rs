@@ -0,0 +1,259 @@
+//! Clipboard watcher capture mode
+//!
+//! When `capture.clipboard_watch` is on, the clipboard is polled on a
+//! blocking thread of its own, run by the task supervisor, and copied text containing a URL, an email address or
+//! a match for one of the user's patterns is recorded and offered to the
+//! frontend as a `backend://clipboard-capture` event. Nothing is created
+//! from an offer; accepting it goes through quick capture as usual. The
+//! watcher is off by default and reads nothing while it is off.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use regex::Regex;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::database::operations::clipboard_capture_operations;
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::events::{BackendEvent, ClipboardCaptureEvent, EventBus};
+use crate::services::settings;
+use crate::services::shutdown::ShutdownCoordinator;
+use crate::services::task_supervisor::TaskSpec;
+
+/// user_preferences key holding the user's patterns as JSON
+pub const CLIPBOARD_PATTERNS_PREFERENCE: &str = "capture.clipboard_patterns";
+
+/// Clipboard poll interval while watching
+const WATCH_TICK: Duration = Duration::from_millis(1000);
+/// Settings check interval while the watcher is off
+const IDLE_TICK: Duration = Duration::from_secs(2);
+/// Longer clipboard contents are documents, not something to capture
+const MAX_CLIPBOARD_CHARS: usize = 4000;
+
+lazy_static::lazy_static! {
+    static ref URL: Regex = Regex::new(r#"(?i)\bhttps?://[^\s<>"']+[^\s<>"'.,;:!?)\]]"#).unwrap();
+    static ref EMAIL_ADDRESS: Regex = Regex::new(r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b").unwrap();
+}
+
+/// A user pattern; matching text is offered as a capture under its name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClipboardPattern {
+    pub name: String,
+    pub pattern: String,
+}
+
+impl ClipboardPattern {
+    fn compile(&self) -> Result<Regex> {
+        if self.name.trim().is_empty() {
+            return Err(LibreOllamaError::InvalidInput {
+                message: "Clipboard patterns need a name".to_string(),
+                field: Some("name".to_string()),
+            });
+        }
+        Regex::new(&self.pattern).map_err(|e| LibreOllamaError::InvalidInput {
+            message: format!("Invalid pattern '{}': {}", self.name, e),
+            field: Some("pattern".to_string()),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardMatchKind {
+    Url,
+    Email,
+    Pattern,
+}
+
+impl ClipboardMatchKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClipboardMatchKind::Url => "url",
+            ClipboardMatchKind::Email => "email",
+            ClipboardMatchKind::Pattern => "pattern",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClipboardMatch {
+    pub kind: ClipboardMatchKind,
+    pub pattern_name: Option<String>,
+    /// The matched text, not the whole clipboard
+    pub content: String,
+}
+
+/// Check patterns before saving them, so the watcher never meets a bad one
+pub fn validate_patterns(patterns: &[ClipboardPattern]) -> Result<()> {
+    patterns.iter().try_for_each(|pattern| pattern.compile().map(|_| ()))
+}
+
+pub fn load_patterns(conn: &Connection) -> Result<Vec<ClipboardPattern>> {
+    let value = settings::get_setting(conn, CLIPBOARD_PATTERNS_PREFERENCE)?.value;
+    Ok(serde_json::from_value(value)?)
+}
+
+/// What copied text offers for capture: the user's patterns win over URLs,
+/// and URLs over email addresses
+pub fn detect(text: &str, patterns: &[(String, Regex)]) -> Option<ClipboardMatch> {
+    let text = text.trim();
+    if text.is_empty() || text.chars().count() > MAX_CLIPBOARD_CHARS {
+        return None;
+    }
+
+    let found = |kind: ClipboardMatchKind, pattern_name: Option<&str>, content: &str| ClipboardMatch {
+        kind,
+        pattern_name: pattern_name.map(str::to_string),
+        content: content.to_string(),
+    };
+    for (name, regex) in patterns {
+        if let Some(m) = regex.find(text).filter(|m| !m.as_str().trim().is_empty()) {
+            return Some(found(ClipboardMatchKind::Pattern, Some(name), m.as_str()));
+        }
+    }
+    if let Some(m) = URL.find(text) {
+        return Some(found(ClipboardMatchKind::Url, None, m.as_str()));
+    }
+    EMAIL_ADDRESS
+        .find(text)
+        .map(|m| found(ClipboardMatchKind::Email, None, m.as_str()))
+}
+
+/// Polls the clipboard while capture mode is on
+#[derive(Clone)]
+pub struct ClipboardWatcher {
+    db_manager: Arc<DatabaseManager>,
+    app: AppHandle,
+}
+
+impl ClipboardWatcher {
+    pub fn new(db_manager: Arc<DatabaseManager>, app: AppHandle) -> Self {
+        Self { db_manager, app }
+    }
+
+    /// Each run watches until capture mode is switched off or the app
+    /// quits. The clipboard handle is not shareable on every platform, so
+    /// a run owns one on a blocking thread rather than the async runtime.
+    pub fn task(&self) -> TaskSpec {
+        let watcher = self.clone();
+        TaskSpec::new("clipboard_watcher", move || {
+            let watcher = watcher.clone();
+            async move { Ok(tokio::task::spawn_blocking(move || watcher.watch()).await??) }
+        })
+        .every(|| IDLE_TICK)
+        .first_run_after(Duration::ZERO)
+    }
+
+    /// Checked every tick, so switching capture mode applies within a second or two
+    pub fn enabled(&self) -> bool {
+        self.db_manager
+            .get_connection()
+            .and_then(|conn| settings::get_bool(&conn, settings::CLIPBOARD_WATCH_SETTING))
+            .unwrap_or(false)
+    }
+
+    fn stopping(&self) -> bool {
+        self.app.try_state::<ShutdownCoordinator>().is_some_and(|shutdown| shutdown.is_stopping())
+    }
+
+    fn watch(&self) -> Result<()> {
+        if !self.enabled() {
+            return Ok(());
+        }
+        let mut clipboard = arboard::Clipboard::new()
+            .map_err(|e| LibreOllamaError::Internal { message: format!("Clipboard unavailable: {}", e) })?;
+        // What was on the clipboard when watching started is not offered
+        let mut last_seen: Option<String> = None;
+        let mut patterns: Vec<(String, Regex)> = Vec::new();
+
+        while self.enabled() && !self.stopping() {
+            // Non-text contents (images, files) read as an error and are skipped
+            let text = clipboard.get_text().ok();
+            if let Some(text) = text {
+                if last_seen.as_ref().is_some_and(|seen| seen != &text) {
+                    patterns = self.compiled_patterns().unwrap_or(patterns);
+                    if let Some(found) = detect(&text, &patterns) {
+                        if let Err(e) = self.offer(&found) {
+                            eprintln!("⚠️ [CLIPBOARD] Failed to record capture: {}", e);
+                        }
+                    }
+                }
+                last_seen = Some(text);
+            }
+            std::thread::sleep(WATCH_TICK);
+        }
+        Ok(())
+    }
+
+    fn compiled_patterns(&self) -> Result<Vec<(String, Regex)>> {
+        let conn = self.db_manager.get_connection()?;
+        load_patterns(&conn)?
+            .into_iter()
+            .map(|pattern| Ok((pattern.name.clone(), pattern.compile()?)))
+            .collect()
+    }
+
+    fn offer(&self, found: &ClipboardMatch) -> Result<()> {
+        let conn = self.db_manager.get_connection()?;
+        let record = clipboard_capture_operations::record_capture(
+            &conn,
+            found.kind.as_str(),
+            found.pattern_name.as_deref(),
+            &found.content,
+        )?;
+        if let Some(event_bus) = self.app.try_state::<EventBus>() {
+            event_bus.emit(BackendEvent::ClipboardCapture(ClipboardCaptureEvent {
+                id: record.id,
+                kind: record.kind,
+                pattern_name: record.pattern_name,
+                content: record.content,
+                created_at: record.created_at,
+            }));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compiled(patterns: &[ClipboardPattern]) -> Vec<(String, Regex)> {
+        patterns.iter().map(|p| (p.name.clone(), p.compile().unwrap())).collect()
+    }
+
+    #[test]
+    fn test_detect() {
+        let url = detect("see https://example.com/docs?page=2.", &[]).unwrap();
+        assert_eq!(url.kind, ClipboardMatchKind::Url);
+        assert_eq!(url.content, "https://example.com/docs?page=2");
+
+        let email = detect("  Reach me at jane.doe@example.org ", &[]).unwrap();
+        assert_eq!(email.kind, ClipboardMatchKind::Email);
+        assert_eq!(email.content, "jane.doe@example.org");
+
+        assert!(detect("just some words", &[]).is_none());
+        assert!(detect("   ", &[]).is_none());
+        assert!(detect(&"https://example.com ".repeat(500), &[]).is_none());
+    }
+
+    #[test]
+    fn test_patterns_win_and_validate() {
+        let patterns = compiled(&[ClipboardPattern {
+            name: "Ticket".to_string(),
+            pattern: r"\b[A-Z]{2,5}-\d+\b".to_string(),
+        }]);
+        let ticket = detect("Fixed in LO-1234, see https://example.com", &patterns).unwrap();
+        assert_eq!(ticket.kind, ClipboardMatchKind::Pattern);
+        assert_eq!(ticket.pattern_name.as_deref(), Some("Ticket"));
+        assert_eq!(ticket.content, "LO-1234");
+
+        let bad = ClipboardPattern { name: "Broken".to_string(), pattern: "(".to_string() };
+        assert!(validate_patterns(&[bad]).is_err());
+        let unnamed = ClipboardPattern { name: " ".to_string(), pattern: "x".to_string() };
+        assert!(validate_patterns(&[unnamed]).is_err());
+    }
+}
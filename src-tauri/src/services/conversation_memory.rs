@@ -0,0 +1,249 @@
+//! Rolling memory for chat sessions
+//!
+//! When a session no longer fits the chat model's context window, its older
+//! turns are folded into a running summary by the summarization model. The
+//! summary lives in `conversation_contexts.context_summary` (one row per
+//! session, named after the session id) and `context_data` records the last
+//! message it covers. Prompts then carry the summary followed by only the
+//! turns after that message.
+
+use rusqlite::Connection;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::database::models::ChatMessage as DbChatMessage;
+use crate::database::operations::{chat_operations, conversation_operations};
+use crate::database::DatabaseManager;
+use crate::errors::Result;
+use crate::services::llm_provider::{self, ChatMessage, LlmFeature};
+use crate::services::prompt_templates;
+use crate::utils::tokens;
+
+/// Prompt template feature for the summary instructions
+const SUMMARY_PROMPT_FEATURE: &str = "chat.memory";
+
+/// Memory of one chat session
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SessionMemory {
+    pub session_id: i32,
+    pub summary: Option<String>,
+    /// Last message folded into the summary; later messages are sent as-is
+    pub summarized_through: Option<i32>,
+    pub summarized_messages: i64,
+    pub context_window: i32,
+}
+
+impl SessionMemory {
+    /// System message carrying the summary into a prompt
+    pub fn prompt_message(&self) -> Option<ChatMessage> {
+        self.summary
+            .as_deref()
+            .filter(|summary| !summary.trim().is_empty())
+            .map(|summary| ChatMessage::system(format!("Summary of the earlier conversation:\n{}", summary)))
+    }
+
+    /// Messages not yet covered by the summary
+    pub fn pending<'a>(&self, history: &'a [DbChatMessage]) -> &'a [DbChatMessage] {
+        match self.summarized_through {
+            Some(last) => {
+                let start = history.iter().position(|message| message.id > last).unwrap_or(history.len());
+                &history[start..]
+            }
+            None => history,
+        }
+    }
+}
+
+pub fn load_memory(conn: &Connection, session_id: i32) -> Result<SessionMemory> {
+    let Some(context) = conversation_operations::get_conversation_context_by_name(conn, &session_id.to_string())? else {
+        return Ok(SessionMemory { session_id, ..Default::default() });
+    };
+    Ok(SessionMemory {
+        session_id,
+        summary: context.context_summary,
+        summarized_through: context.context_data["summarized_through"].as_i64().map(|id| id as i32),
+        summarized_messages: context.context_data["summarized_messages"].as_i64().unwrap_or(0),
+        context_window: context.context_window_size,
+    })
+}
+
+pub fn save_memory(conn: &Connection, memory: &SessionMemory) -> Result<()> {
+    let name = memory.session_id.to_string();
+    let data = json!({
+        "session_id": memory.session_id,
+        "summarized_through": memory.summarized_through,
+        "summarized_messages": memory.summarized_messages,
+    })
+    .to_string();
+
+    match conversation_operations::get_conversation_context_by_name(conn, &name)? {
+        Some(context) => conversation_operations::update_conversation_context(
+            conn,
+            context.id,
+            &data,
+            memory.context_window,
+            memory.summary.as_deref(),
+        )?,
+        None => {
+            conversation_operations::create_conversation_context(
+                conn,
+                &name,
+                &data,
+                memory.context_window,
+                memory.summary.as_deref(),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Replace the summary by hand. Clearing it also forgets which messages it
+/// covered, so the full history is sent again (trimmed to the window).
+pub fn set_summary(conn: &Connection, session_id: i32, summary: &str) -> Result<SessionMemory> {
+    let mut memory = load_memory(conn, session_id)?;
+    if summary.trim().is_empty() {
+        memory.summary = None;
+        memory.summarized_through = None;
+        memory.summarized_messages = 0;
+    } else {
+        memory.summary = Some(summary.trim().to_string());
+    }
+    save_memory(conn, &memory)?;
+    Ok(memory)
+}
+
+/// How many of the oldest `turns` to fold into the summary so the rest fit
+/// `budget` alongside `fixed_tokens` of system prompt and summary. Once the
+/// window overflows, only about half the remaining room is kept for recent
+/// turns so summarizing is not needed again on the very next message.
+pub fn turns_to_summarize(turns: &[DbChatMessage], fixed_tokens: usize, budget: usize) -> usize {
+    let costs: Vec<usize> = turns.iter().map(|turn| tokens::message_tokens(&turn.content)).collect();
+    if fixed_tokens + costs.iter().sum::<usize>() <= budget {
+        return 0;
+    }
+
+    let recent_budget = budget.saturating_sub(fixed_tokens) / 2;
+    let mut used = 0;
+    let mut kept = 0;
+    for cost in costs.iter().rev() {
+        if kept > 0 && used + cost > recent_budget {
+            break;
+        }
+        used += cost;
+        kept += 1;
+    }
+    turns.len() - kept
+}
+
+fn transcript(turns: &[DbChatMessage]) -> String {
+    turns
+        .iter()
+        .map(|turn| format!("{}: {}", turn.role, turn.content.trim()))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Fold older turns into the session summary when the session overflows the
+/// prompt budget of `context_window`; `fixed_tokens` is what the system
+/// prompt already uses
+pub async fn refresh(
+    db_manager: &DatabaseManager,
+    session_id: i32,
+    fixed_tokens: usize,
+    context_window: usize,
+) -> Result<SessionMemory> {
+    let budget = llm_provider::prompt_budget(context_window);
+    let (mut memory, history) = {
+        let conn = db_manager.get_connection()?;
+        (load_memory(&conn, session_id)?, chat_operations::get_chat_messages_by_session(&conn, session_id)?)
+    };
+
+    let summary_tokens = memory.prompt_message().map_or(0, |message| tokens::message_tokens(&message.content));
+    let pending = memory.pending(&history);
+    let count = turns_to_summarize(pending, fixed_tokens + summary_tokens, budget);
+    if count == 0 {
+        return Ok(memory);
+    }
+
+    let folded = &pending[..count];
+    let request = format!(
+        "Existing summary:\n{}\n\nNew turns:\n{}",
+        memory.summary.as_deref().unwrap_or("(none)"),
+        transcript(folded)
+    );
+    let instructions =
+        prompt_templates::render_feature_prompt(&db_manager.get_connection()?, SUMMARY_PROMPT_FEATURE, &serde_json::Map::new())?;
+    let completion = llm_provider::complete(
+        db_manager,
+        LlmFeature::Summarization,
+        &[ChatMessage::system(instructions), ChatMessage::user(request)],
+    )
+    .await?;
+
+    memory.summary = Some(completion.content);
+    memory.summarized_through = folded.last().map(|turn| turn.id);
+    memory.summarized_messages += count as i64;
+    memory.context_window = context_window as i32;
+
+    let conn = db_manager.get_connection()?;
+    save_memory(&conn, &memory)?;
+    Ok(memory)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    fn turn(id: i32, content: &str) -> DbChatMessage {
+        DbChatMessage {
+            id,
+            session_id: 1,
+            role: if id % 2 == 1 { "user" } else { "assistant" }.to_string(),
+            content: content.to_string(),
+            token_count: 0,
+            created_at: chrono::Local::now().naive_local(),
+        }
+    }
+
+    #[test]
+    fn test_turns_to_summarize() {
+        let turns: Vec<DbChatMessage> = (1..=10).map(|id| turn(id, &"word ".repeat(20))).collect();
+        // 10 turns of 25 tokens each
+        assert_eq!(turns_to_summarize(&turns, 0, 1000), 0);
+
+        let folded = turns_to_summarize(&turns, 40, 200);
+        assert_eq!(folded, 7);
+        // The newest turn is always kept, even when it alone is too big
+        assert_eq!(turns_to_summarize(&turns, 0, 10), 9);
+    }
+
+    #[test]
+    fn test_memory_round_trip() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let empty = load_memory(&conn, 7).unwrap();
+        assert!(empty.summary.is_none() && empty.prompt_message().is_none());
+
+        let memory = SessionMemory {
+            session_id: 7,
+            summary: Some("The user is planning a trip to Lisbon.".to_string()),
+            summarized_through: Some(4),
+            summarized_messages: 4,
+            context_window: 8192,
+        };
+        save_memory(&conn, &memory).unwrap();
+        let loaded = load_memory(&conn, 7).unwrap();
+        assert_eq!(loaded.summarized_through, Some(4));
+        assert!(loaded.prompt_message().unwrap().content.contains("Lisbon"));
+
+        let history: Vec<DbChatMessage> = (1..=6).map(|id| turn(id, "hi")).collect();
+        assert_eq!(loaded.pending(&history).iter().map(|t| t.id).collect::<Vec<_>>(), vec![5, 6]);
+
+        let edited = set_summary(&conn, 7, "Trip to Porto instead.").unwrap();
+        assert_eq!(edited.summarized_through, Some(4));
+        let cleared = set_summary(&conn, 7, "  ").unwrap();
+        assert!(cleared.summary.is_none() && cleared.pending(&history).len() == 6);
+    }
+}
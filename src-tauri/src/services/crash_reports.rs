@@ -0,0 +1,253 @@
+//! Local crash reports
+//!
+//! A panic hook writes the panic message, location, thread and backtrace,
+//! with the app version and platform, to `paths.logs_dir/crashes` as one
+//! JSON file per crash. Native crashes that never reach Rust's panic
+//! machinery are not captured.
+//!
+//! Reports never leave the machine on their own. Sharing one means
+//! packaging it into a zip with emails, tokens and the home directory
+//! redacted, which the user can then attach to a GitHub issue.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{LibreOllamaError, Result};
+
+const CRASHES_DIR: &str = "crashes";
+const REPORT_PREFIX: &str = "crash-";
+/// Zips are written here, inside the crashes directory
+const SHARED_DIR: &str = "shared";
+const SUMMARY_CHARS: usize = 200;
+
+lazy_static::lazy_static! {
+    static ref EMAIL: regex::Regex = regex::Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}").unwrap();
+    static ref BEARER: regex::Regex = regex::Regex::new(r"(?i)\b(bearer|basic)\s+[A-Za-z0-9._~+/=-]+").unwrap();
+    static ref GOOGLE_TOKEN: regex::Regex = regex::Regex::new(r"\b(ya29\.|1//)[A-Za-z0-9._-]+").unwrap();
+    static ref SECRET_PARAM: regex::Regex = regex::Regex::new(
+        r#"(?i)\b(access_token|refresh_token|id_token|client_secret|api_key|apikey|password|passphrase|token)(["']?\s*[:=]\s*["']?)[^\s"'&,}]+"#
+    ).unwrap();
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub id: String,
+    /// RFC 3339
+    pub occurred_at: String,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub thread: String,
+    pub message: String,
+    /// file:line:column of the panic
+    pub location: Option<String>,
+    pub backtrace: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReportSummary {
+    pub id: String,
+    pub occurred_at: String,
+    pub app_version: String,
+    /// First line of the panic message
+    pub message: String,
+    pub location: Option<String>,
+}
+
+/// Redact email addresses, credentials and the home directory
+pub fn sanitize(text: &str) -> String {
+    let mut text = text.to_string();
+    if let Some(home) = dirs::home_dir().map(|home| home.display().to_string()).filter(|home| home.len() > 1) {
+        text = text.replace(&home, "~");
+    }
+    let text = EMAIL.replace_all(&text, "<email>");
+    let text = BEARER.replace_all(&text, "$1 <redacted>");
+    let text = GOOGLE_TOKEN.replace_all(&text, "<token>");
+    SECRET_PARAM.replace_all(&text, "$1$2<redacted>").into_owned()
+}
+
+/// Where reports are kept, under the configured logs directory
+pub fn crashes_dir(logs_dir: &Path) -> PathBuf {
+    logs_dir.join(CRASHES_DIR)
+}
+
+fn panic_message(info: &std::panic::PanicHookInfo) -> String {
+    let payload = info.payload();
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Box<dyn Any>".to_string())
+}
+
+fn write_report(dir: &Path, report: &CrashReport) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{}.json", report.id));
+    std::fs::write(&path, serde_json::to_vec_pretty(report)?)?;
+    Ok(path)
+}
+
+/// Record every panic under `dir`, then run the previous hook
+pub fn install(dir: PathBuf) {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let now = Utc::now();
+        let report = CrashReport {
+            id: format!(
+                "{}{}-{}",
+                REPORT_PREFIX,
+                now.format("%Y%m%d-%H%M%S"),
+                &uuid::Uuid::new_v4().simple().to_string()[..8]
+            ),
+            occurred_at: now.to_rfc3339(),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            thread: std::thread::current().name().unwrap_or("unnamed").to_string(),
+            message: panic_message(info),
+            location: info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+            backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+        };
+        match write_report(&dir, &report) {
+            Ok(path) => eprintln!("💥 [CRASH] Crash report saved to {}", path.display()),
+            Err(e) => eprintln!("⚠️ [CRASH] Failed to save crash report: {}", e),
+        }
+        previous(info);
+    }));
+}
+
+/// Report IDs become file names, so only the form `install` writes is accepted
+fn report_path(dir: &Path, id: &str) -> Result<PathBuf> {
+    let valid = id.starts_with(REPORT_PREFIX)
+        && id[REPORT_PREFIX.len()..].chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    if !valid {
+        return Err(LibreOllamaError::InvalidInput {
+            message: format!("Invalid crash report ID '{}'", id),
+            field: Some("report_id".to_string()),
+        });
+    }
+    Ok(dir.join(format!("{}.json", id)))
+}
+
+pub fn get_report(dir: &Path, id: &str) -> Result<CrashReport> {
+    let path = report_path(dir, id)?;
+    let bytes = std::fs::read(&path).map_err(|_| LibreOllamaError::NotFound {
+        resource: format!("Crash report {}", id),
+    })?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+/// Saved reports, newest first; unreadable files are skipped
+pub fn list_reports(dir: &Path) -> Result<Vec<CrashReportSummary>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut reports: Vec<CrashReportSummary> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(REPORT_PREFIX))
+        .filter_map(|entry| serde_json::from_slice::<CrashReport>(&std::fs::read(entry.path()).ok()?).ok())
+        .map(|report| CrashReportSummary {
+            message: report.message.lines().next().unwrap_or_default().chars().take(SUMMARY_CHARS).collect(),
+            id: report.id,
+            occurred_at: report.occurred_at,
+            app_version: report.app_version,
+            location: report.location,
+        })
+        .collect();
+    reports.sort_by(|a, b| b.occurred_at.cmp(&a.occurred_at));
+    Ok(reports)
+}
+
+pub fn delete_report(dir: &Path, id: &str) -> Result<bool> {
+    let path = report_path(dir, id)?;
+    match std::fs::remove_file(path) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Write a zip of the sanitized report for sharing, returning its path
+pub fn package_report(dir: &Path, id: &str) -> Result<PathBuf> {
+    let report = get_report(dir, id)?;
+    let sanitized = CrashReport {
+        message: sanitize(&report.message),
+        location: report.location.as_deref().map(sanitize),
+        backtrace: sanitize(&report.backtrace),
+        thread: sanitize(&report.thread),
+        ..report
+    };
+
+    let shared_dir = dir.join(SHARED_DIR);
+    std::fs::create_dir_all(&shared_dir)?;
+    let path = shared_dir.join(format!("libreollama-{}.zip", id));
+    let zip_error = |e: zip::result::ZipError| LibreOllamaError::Internal { message: format!("Failed to write zip: {}", e) };
+
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(&path)?);
+    let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    zip.start_file(format!("{}.json", id), options).map_err(zip_error)?;
+    zip.write_all(&serde_json::to_vec_pretty(&sanitized)?)?;
+    zip.start_file("backtrace.txt", options).map_err(zip_error)?;
+    zip.write_all(sanitized.backtrace.as_bytes())?;
+    zip.finish().map_err(zip_error)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_redacts_secrets() {
+        let text = "request for ada@example.com failed: Authorization: Bearer abc.def \
+                    refresh_token=1//0gabc&x=1 token ya29.a0AfH6 {\"client_secret\": \"shh\"}";
+        let clean = sanitize(text);
+        assert!(!clean.contains("ada@example.com"));
+        assert!(!clean.contains("abc.def"));
+        assert!(!clean.contains("1//0gabc"));
+        assert!(!clean.contains("ya29"));
+        assert!(!clean.contains("shh"));
+        assert!(clean.contains("Bearer <redacted>"));
+    }
+
+    #[test]
+    fn test_reports_list_and_package() {
+        let dir = std::env::temp_dir().join(format!("libreollama-crashes-{}", uuid::Uuid::new_v4()));
+        for (id, at) in [("crash-20261001-090000-aaaa0000", "2026-10-01T09:00:00+00:00"), ("crash-20261002-090000-bbbb0000", "2026-10-02T09:00:00+00:00")] {
+            write_report(&dir, &CrashReport {
+                id: id.to_string(),
+                occurred_at: at.to_string(),
+                app_version: "0.1.0".to_string(),
+                os: "linux".to_string(),
+                arch: "x86_64".to_string(),
+                thread: "main".to_string(),
+                message: "sync failed for ada@example.com\nmore".to_string(),
+                location: Some("src/lib.rs:1:1".to_string()),
+                backtrace: "0: main".to_string(),
+            }).unwrap();
+        }
+
+        let reports = list_reports(&dir).unwrap();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].id, "crash-20261002-090000-bbbb0000");
+        assert_eq!(reports[0].message, "sync failed for ada@example.com");
+
+        let zip_path = package_report(&dir, &reports[0].id).unwrap();
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(&zip_path).unwrap()).unwrap();
+        let mut packaged = String::new();
+        std::io::Read::read_to_string(&mut archive.by_name("crash-20261002-090000-bbbb0000.json").unwrap(), &mut packaged).unwrap();
+        assert!(packaged.contains("<email>"));
+        // The shared zip is not mistaken for a report
+        assert_eq!(list_reports(&dir).unwrap().len(), 2);
+
+        assert!(get_report(&dir, "../../etc/passwd").is_err());
+        assert!(delete_report(&dir, &reports[1].id).unwrap());
+        assert!(!delete_report(&dir, &reports[1].id).unwrap());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,582 @@
+//! Daily digest
+//!
+//! Once a day, at the time in the digest schedule, the unread important
+//! mail, today's events, open tasks due today or earlier and starred
+//! messages (Gmail's flag for following up) are gathered from the local
+//! copies, limited to what the active workspace shows, and rendered
+//! through the digest template. The result goes out as a
+//! `backend://daily-digest` event for the desktop notification, as an email
+//! to the chosen account's own address, or both. Each day gets one attempt;
+//! a digest that could not be emailed is not retried.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use chrono_tz::Tz;
+use regex::Regex;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::database::operations::digest_operations::{self, DigestItem, DigestMessage};
+use crate::database::operations::preference_operations;
+use crate::database::operations::workspace_operations::WorkspaceKind;
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::events::{BackendEvent, DailyDigestEvent, EventBus};
+use crate::services::availability;
+use crate::services::gmail::api_service::EmailAddress;
+use crate::services::gmail::campaign_service::render_template;
+use crate::services::gmail::compose_service::{ComposeRequest, GmailComposeService, MessageImportance};
+use crate::services::i18n;
+use crate::services::settings;
+use crate::services::task_supervisor::TaskSpec;
+use crate::services::time_service::TimeService;
+use crate::services::workspaces;
+
+/// user_preferences key holding the JSON encoded `DigestSchedule`
+pub const DIGEST_SCHEDULE_PREFERENCE: &str = "digest.schedule";
+pub const DIGEST_TEMPLATE_PREFERENCE: &str = "digest.template";
+/// Local date of the last scheduled digest, so each day gets one
+const LAST_DELIVERED_PREFERENCE: &str = "digest.last_delivered_on";
+
+/// Entries listed per section
+const MAX_SECTION_ITEMS: i64 = 15;
+/// Upper bound on a scheduler wait, so sleep and clock changes are caught up
+const MAX_WAIT: Duration = Duration::from_secs(15 * 60);
+/// Lets the first sync after launch land before a catch-up digest
+const STARTUP_DELAY: Duration = Duration::from_secs(60);
+
+lazy_static::lazy_static! {
+    /// `{{name}}` without a fallback
+    static ref BARE_PLACEHOLDER: Regex = Regex::new(r"\{\{\s*([A-Za-z0-9_.]+)\s*\}\}").unwrap();
+}
+
+pub const DEFAULT_DIGEST_TEMPLATE: &str = "{{title}}\n\n\
+Important unread mail ({{mail_count}})\n{{mail | Nothing waiting.}}\n\n\
+Today's calendar ({{event_count}})\n{{events | No events today.}}\n\n\
+Tasks due ({{task_count}})\n{{tasks | Nothing due.}}\n\n\
+Follow-ups ({{follow_up_count}})\n{{follow_ups | No starred messages.}}";
+
+/// Placeholders a digest template can use
+const TEMPLATE_VARIABLES: &[&str] = &[
+    "title", "date", "summary", "mail", "mail_count", "events", "event_count", "tasks", "task_count", "follow_ups",
+    "follow_up_count", "availability",
+];
+
+/// Fail on placeholders the digest does not fill and on malformed ones
+pub fn check_template(template: &str) -> Result<()> {
+    let variables = TEMPLATE_VARIABLES.iter().map(|name| (name.to_string(), String::new())).collect();
+    render(template, &variables).map(|_| ())
+}
+
+/// `DEFAULT_DIGEST_TEMPLATE` in the active locale, used while the template
+/// setting is untouched
+pub fn default_template() -> String {
+    let section = |heading: &str, count: &str, lines: &str, empty: &str| {
+        format!("{} ({{{{{}}}}})\n{{{{{} | {}}}}}", i18n::t(heading), count, lines, i18n::t(empty))
+    };
+    [
+        "{{title}}".to_string(),
+        section("digest-mail-heading", "mail_count", "mail", "digest-mail-empty"),
+        section("digest-events-heading", "event_count", "events", "digest-events-empty"),
+        section("digest-tasks-heading", "task_count", "tasks", "digest-tasks-empty"),
+        section("digest-follow-ups-heading", "follow_up_count", "follow_ups", "digest-follow-ups-empty"),
+    ]
+    .join("\n\n")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DigestSchedule {
+    pub enabled: bool,
+    /// Local delivery time, "HH:MM"
+    pub time: String,
+    /// Announce the digest as a desktop notification
+    pub notify: bool,
+    /// Account whose own address the digest is mailed to
+    pub email_account_id: Option<String>,
+}
+
+impl Default for DigestSchedule {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            time: "07:30".to_string(),
+            notify: true,
+            email_account_id: None,
+        }
+    }
+}
+
+impl DigestSchedule {
+    pub fn delivery_time(&self) -> Result<NaiveTime> {
+        NaiveTime::parse_from_str(self.time.trim(), "%H:%M").map_err(|_| LibreOllamaError::InvalidInput {
+            message: format!("Invalid digest time '{}', expected HH:MM", self.time),
+            field: Some("time".to_string()),
+        })
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        self.delivery_time()?;
+        let email = self.email_account_id.as_deref().map(str::trim);
+        if email == Some("") {
+            return Err(LibreOllamaError::InvalidInput {
+                message: "Choose the account to email the digest to".to_string(),
+                field: Some("email_account_id".to_string()),
+            });
+        }
+        if !self.notify && email.is_none() {
+            return Err(LibreOllamaError::InvalidInput {
+                message: "The digest needs a notification, an email or both".to_string(),
+                field: Some("notify".to_string()),
+            });
+        }
+        Ok(())
+    }
+}
+
+pub fn load_schedule(conn: &Connection) -> anyhow::Result<DigestSchedule> {
+    Ok(preference_operations::get_preference_value(conn, DIGEST_SCHEDULE_PREFERENCE)?
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyDigest {
+    /// Local date covered, "YYYY-MM-DD"
+    pub date: String,
+    pub title: String,
+    pub summary: String,
+    pub important_mail: Vec<DigestMessage>,
+    pub events: Vec<DigestItem>,
+    pub due_tasks: Vec<DigestItem>,
+    pub follow_ups: Vec<DigestMessage>,
+    /// The rendered template
+    pub body: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestDelivery {
+    pub digest: DailyDigest,
+    pub notified: bool,
+    pub emailed_to: Option<String>,
+    pub message_id: Option<String>,
+}
+
+fn message_lines(messages: &[DigestMessage]) -> String {
+    messages
+        .iter()
+        .map(|message| {
+            if message.from.is_empty() {
+                format!("- {}", message.subject)
+            } else {
+                format!("- {} — {}", message.subject, message.from)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Events as a Markdown list, with start times in `zone`
+pub fn event_lines(events: &[DigestItem], zone: Tz) -> String {
+    events
+        .iter()
+        .map(|event| {
+            let when = match event.time.as_deref() {
+                Some(_) if event.all_day => Some(i18n::t("digest-all-day")),
+                Some(time) => DateTime::parse_from_rfc3339(time)
+                    .ok()
+                    .map(|start| start.with_timezone(&zone).format("%H:%M").to_string()),
+                None => None,
+            };
+            match when {
+                Some(when) => format!("- {} {}", when, event.title),
+                None => format!("- {}", event.title),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Tasks as a Markdown list, noting how long overdue ones have been due
+pub fn task_lines(tasks: &[DigestItem], today: NaiveDate) -> String {
+    tasks
+        .iter()
+        .map(|task| {
+            let due = task
+                .time
+                .as_deref()
+                .and_then(|time| NaiveDate::parse_from_str(time.get(..10)?, "%Y-%m-%d").ok());
+            match due {
+                Some(due) if due < today => {
+                    let since = chrono::Utc
+                        .from_utc_datetime(&due.and_time(NaiveTime::MIN))
+                        .format_localized(&i18n::t("digest-short-date-format"), i18n::chrono_locale())
+                        .to_string();
+                    format!("- {} {}", task.title, i18n::t_args("digest-overdue", &[("date", since.into())]))
+                }
+                _ => format!("- {}", task.title),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn plural(id: &str, count: usize) -> String {
+    i18n::t_args(id, &[("count", count.into())])
+}
+
+/// Render the digest template. Sections that are empty and have no
+/// fallback in the template read "(none)", in the active locale.
+pub fn render(template: &str, variables: &HashMap<String, String>) -> Result<String> {
+    let template = BARE_PLACEHOLDER.replace_all(template, |caps: &regex::Captures| {
+        if variables.contains_key(&caps[1]) {
+            format!("{{{{{} | {}}}}}", &caps[1], i18n::t("digest-none"))
+        } else {
+            caps[0].to_string()
+        }
+    });
+    render_template(&template, variables, false).map_err(|message| LibreOllamaError::InvalidInput {
+        message,
+        field: Some(DIGEST_TEMPLATE_PREFERENCE.to_string()),
+    })
+}
+
+/// Events of the local day of `now`, all-day ones first, and open tasks
+/// due by then, limited to what the active workspace shows
+pub fn agenda(conn: &Connection, now: DateTime<Tz>) -> Result<(Vec<DigestItem>, Vec<DigestItem>)> {
+    let today = now.date_naive();
+    let date = today.format("%Y-%m-%d").to_string();
+    let day_start = today
+        .and_time(NaiveTime::MIN)
+        .and_local_timezone(now.timezone())
+        .earliest()
+        .unwrap_or(now)
+        .with_timezone(&chrono::Utc);
+    let day_end = day_start + chrono::Duration::days(1);
+    let utc = |time: DateTime<chrono::Utc>| time.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+
+    let scope = workspaces::scope(conn)?;
+    let mut events =
+        digest_operations::events_on(conn, &date, &utc(day_start), &utc(day_end), &scope.hidden(Some(WorkspaceKind::Calendar)))?;
+    // All-day events first, then by start time
+    events.sort_by_key(|event| !event.all_day);
+    let mut due_tasks = digest_operations::open_tasks_due_by(conn, &date, &scope.hidden(Some(WorkspaceKind::TaskList)))?;
+    due_tasks.truncate(MAX_SECTION_ITEMS as usize);
+    Ok((events, due_tasks))
+}
+
+/// Gather the digest for the local day of `now` from the local copies
+pub fn compile(conn: &Connection, now: DateTime<Tz>) -> Result<DailyDigest> {
+    let today = now.date_naive();
+    let date = today.format("%Y-%m-%d").to_string();
+
+    let hidden = workspaces::scope(conn)?.hidden(None);
+    let important_mail = digest_operations::messages_with_labels(conn, &["INBOX", "UNREAD", "IMPORTANT"], &hidden, MAX_SECTION_ITEMS)?;
+    let follow_ups = digest_operations::messages_with_labels(conn, &["STARRED"], &hidden, MAX_SECTION_ITEMS)?;
+    let (events, due_tasks) = agenda(conn, now)?;
+
+    let day = now.format_localized(&i18n::t("digest-date-format"), i18n::chrono_locale()).to_string();
+    let title = i18n::t_args("digest-title", &[("date", day.into())]);
+    let summary = [
+        plural("digest-important-mail", important_mail.len()),
+        plural("digest-events", events.len()),
+        plural("digest-tasks-due", due_tasks.len()),
+        plural("digest-follow-ups", follow_ups.len()),
+    ]
+    .join(", ");
+
+    let variables = HashMap::from([
+        ("title".to_string(), title.clone()),
+        ("date".to_string(), date.clone()),
+        ("summary".to_string(), summary.clone()),
+        ("mail".to_string(), message_lines(&important_mail)),
+        ("mail_count".to_string(), important_mail.len().to_string()),
+        ("events".to_string(), event_lines(&events, now.timezone())),
+        ("event_count".to_string(), events.len().to_string()),
+        ("tasks".to_string(), task_lines(&due_tasks, today)),
+        ("task_count".to_string(), due_tasks.len().to_string()),
+        ("follow_ups".to_string(), message_lines(&follow_ups)),
+        ("follow_up_count".to_string(), follow_ups.len().to_string()),
+        ("availability".to_string(), availability::day_summary_at(conn, &now)?),
+    ]);
+    let setting = settings::get_setting(conn, DIGEST_TEMPLATE_PREFERENCE)?;
+    let template = match setting.value.as_str() {
+        Some(template) if !setting.is_default => template.to_string(),
+        _ => default_template(),
+    };
+    let body = render(&template, &variables)?;
+
+    Ok(DailyDigest { date, title, summary, important_mail, events, due_tasks, follow_ups, body })
+}
+
+fn account_address(conn: &Connection, account_id: &str) -> Result<String> {
+    conn.query_row(
+        "SELECT email_address FROM gmail_accounts_secure WHERE id = ?1",
+        [account_id],
+        |row| row.get(0),
+    )
+    .map_err(|_| LibreOllamaError::NotFound {
+        resource: format!("Gmail account {}", account_id),
+    })
+}
+
+/// Send the digest the ways the schedule asks for
+pub async fn deliver(
+    app: &AppHandle,
+    db_manager: &Arc<DatabaseManager>,
+    schedule: &DigestSchedule,
+    digest: DailyDigest,
+) -> Result<DigestDelivery> {
+    let mut delivery = DigestDelivery { digest, notified: false, emailed_to: None, message_id: None };
+
+    if schedule.notify {
+        if let Some(event_bus) = app.try_state::<EventBus>() {
+            event_bus.emit(BackendEvent::DailyDigest(DailyDigestEvent {
+                date: delivery.digest.date.clone(),
+                title: delivery.digest.title.clone(),
+                summary: delivery.digest.summary.clone(),
+                body: delivery.digest.body.clone(),
+            }));
+            delivery.notified = true;
+        }
+    }
+
+    if let Some(account_id) = schedule.email_account_id.as_deref() {
+        let address = {
+            let conn = db_manager.get_connection()?;
+            account_address(&conn, account_id)?
+        };
+        let compose_service = app.try_state::<Arc<GmailComposeService>>().ok_or_else(|| LibreOllamaError::Internal {
+            message: "Compose service is not available".to_string(),
+        })?;
+        let request = ComposeRequest {
+            account_id: account_id.to_string(),
+            to: vec![EmailAddress { email: address.clone(), name: None }],
+            cc: None,
+            bcc: None,
+            subject: delivery.digest.title.clone(),
+            body_text: Some(delivery.digest.body.clone()),
+            body_html: None,
+            attachments: None,
+            reply_to_message_id: None,
+            in_reply_to: None,
+            references: None,
+            thread_id: None,
+            importance: MessageImportance::Normal,
+            delivery_receipt: false,
+            read_receipt: false,
+            schedule_send: None,
+            from_alias: None,
+        };
+        let response = compose_service.send_message(&request).await?;
+        delivery.emailed_to = Some(address);
+        delivery.message_id = Some(response.message_id);
+    }
+
+    Ok(delivery)
+}
+
+/// Whether the scheduled digest for `now`'s day is still to be sent
+fn is_due(now: NaiveDateTime, at: NaiveTime, last_delivered: Option<&str>) -> bool {
+    now.time() >= at && last_delivered != Some(now.date().format("%Y-%m-%d").to_string().as_str())
+}
+
+/// Time from `now` until the next delivery at `at`
+fn until_next(now: NaiveDateTime, at: NaiveTime, delivered_today: bool) -> Duration {
+    let mut next = now.date().and_time(at);
+    if delivered_today || next <= now {
+        next += chrono::Duration::days(1);
+    }
+    (next - now).to_std().unwrap_or(MAX_WAIT)
+}
+
+/// Delivers the digest at the scheduled time each day
+#[derive(Clone)]
+pub struct DigestScheduler {
+    db_manager: Arc<DatabaseManager>,
+    app: AppHandle,
+}
+
+impl DigestScheduler {
+    pub fn new(db_manager: Arc<DatabaseManager>, app: AppHandle) -> Self {
+        Self { db_manager, app }
+    }
+
+    /// A changed schedule is picked up straight away
+    pub fn task(&self) -> TaskSpec {
+        let scheduler = self.clone();
+        let waiting = self.clone();
+        TaskSpec::new("daily_digest", move || {
+            let scheduler = scheduler.clone();
+            async move { Ok(scheduler.run_if_due().await?) }
+        })
+        .every(move || waiting.until_due().unwrap_or(MAX_WAIT))
+        .first_run_after(STARTUP_DELAY)
+        .wake_on(&[DIGEST_SCHEDULE_PREFERENCE])
+    }
+
+    fn load_schedule(&self) -> Result<(DigestSchedule, Option<String>)> {
+        let conn = self.db_manager.get_connection()?;
+        Ok((
+            load_schedule(&conn)?,
+            preference_operations::get_preference_value(&conn, LAST_DELIVERED_PREFERENCE)?,
+        ))
+    }
+
+    /// How long until today's digest is due, capped at `MAX_WAIT`
+    fn until_due(&self) -> Result<Duration> {
+        let now = TimeService::from_db(&self.db_manager)?.now();
+        let today = now.format("%Y-%m-%d").to_string();
+        let (schedule, last_delivered) = self.load_schedule()?;
+        if !schedule.enabled {
+            return Ok(MAX_WAIT);
+        }
+        let at = schedule.delivery_time()?;
+        if is_due(now.naive_local(), at, last_delivered.as_deref()) {
+            return Ok(Duration::ZERO);
+        }
+        Ok(until_next(now.naive_local(), at, last_delivered.as_deref() == Some(today.as_str())).min(MAX_WAIT))
+    }
+
+    /// Deliver today's digest if its time has come
+    async fn run_if_due(&self) -> Result<()> {
+        let now = TimeService::from_db(&self.db_manager)?.now();
+        let today = now.format("%Y-%m-%d").to_string();
+        let (schedule, last_delivered) = self.load_schedule()?;
+        if !schedule.enabled || !is_due(now.naive_local(), schedule.delivery_time()?, last_delivered.as_deref()) {
+            return Ok(());
+        }
+
+        // Recorded first so a failed delivery is not repeated all day
+        let digest = {
+            let conn = self.db_manager.get_connection()?;
+            preference_operations::set_preference_value(&conn, LAST_DELIVERED_PREFERENCE, &today, "digest")?;
+            compile(&conn, now)?
+        };
+        let delivery = deliver(&self.app, &self.db_manager, &schedule, digest).await?;
+        println!(
+            "📰 [DIGEST] Delivered the daily digest ({}){}",
+            delivery.digest.summary,
+            delivery.emailed_to.as_deref().map(|to| format!(", emailed to {}", to)).unwrap_or_default()
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::operations::search_operations::{replace_event_items, replace_task_items, NewSearchItem};
+    use crate::database::schema::run_migrations;
+
+    fn at(date: &str, time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_schedule_timing() {
+        let time = NaiveTime::from_hms_opt(7, 30, 0).unwrap();
+        assert!(!is_due(at("2026-10-16", "07:00"), time, None));
+        assert!(is_due(at("2026-10-16", "07:30"), time, Some("2026-10-15")));
+        assert!(!is_due(at("2026-10-16", "21:00"), time, Some("2026-10-16")));
+
+        assert_eq!(until_next(at("2026-10-16", "07:00"), time, false), Duration::from_secs(30 * 60));
+        assert_eq!(until_next(at("2026-10-16", "08:30"), time, true), Duration::from_secs(23 * 3600));
+    }
+
+    #[test]
+    fn test_schedule_validation() {
+        assert!(DigestSchedule::default().validate().is_ok());
+        let schedule = DigestSchedule { time: "7.30".to_string(), ..DigestSchedule::default() };
+        assert!(schedule.validate().is_err());
+        let schedule = DigestSchedule { notify: false, ..DigestSchedule::default() };
+        assert!(schedule.validate().is_err());
+    }
+
+    #[test]
+    fn test_compile_from_local_copies() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        conn.execute(
+            "INSERT INTO archive_accounts (id, name, source, source_path) VALUES ('archive:old', 'Old mail', 'thunderbird', '/tmp')",
+            [],
+        ).unwrap();
+        let messages = [
+            ("acc", "m1", r#"["INBOX","UNREAD","IMPORTANT"]"#, "Contract renewal"),
+            ("acc", "m2", r#"["INBOX","IMPORTANT"]"#, "Already read"),
+            ("acc", "m3", r#"["INBOX","STARRED"]"#, "Reply to Sam"),
+            // Imported archives stay out of the digest
+            ("archive:old", "a1", r#"["INBOX","UNREAD","IMPORTANT","STARRED"]"#, "Flagged in 2014"),
+        ];
+        for (account, id, labels, subject) in messages {
+            let data = serde_json::json!({
+                "parsed_content": { "subject": subject, "from": { "email": "ops@example.com", "name": "Ops" } },
+                "snippet": "",
+            });
+            conn.execute(
+                "INSERT INTO gmail_message_store (account_id, message_id, thread_id, label_ids, internal_date, message_data)
+                 VALUES (?1, ?2, ?2, ?3, 1760000000000, ?4)",
+                rusqlite::params![account, id, labels, data.to_string()],
+            ).unwrap();
+        }
+
+        let zone = chrono_tz::Europe::Berlin;
+        let now = zone.with_ymd_and_hms(2026, 10, 16, 7, 30, 0).unwrap();
+        let noon = zone.with_ymd_and_hms(2026, 10, 16, 12, 0, 0).unwrap().with_timezone(&chrono::Utc);
+        let noon = noon.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        let item = |id: &'static str, title: &'static str, time: &'static str, status: &'static str| NewSearchItem {
+            item_id: id,
+            container_id: "c",
+            title,
+            body: "",
+            item_time: Some(time),
+            status: Some(status),
+            completed_at: None,
+            all_day: false,
+        };
+        replace_task_items(&mut conn, "acc", &[
+            item("t1", "File expenses", "2026-10-14T00:00:00Z", "needsAction"),
+            item("t2", "Done already", "2026-10-16T00:00:00Z", "completed"),
+            item("t3", "Next week", "2026-10-23T00:00:00Z", "needsAction"),
+        ]).unwrap();
+        replace_event_items(&mut conn, "acc", "primary", "2026-10-01T00:00:00Z", "2026-11-01T00:00:00Z", &[
+            NewSearchItem { item_time: Some(&noon), ..item("e1", "Lunch with Kim", "", "confirmed") },
+            NewSearchItem { all_day: true, ..item("e2", "Company holiday", "2026-10-16T00:00:00Z", "confirmed") },
+            NewSearchItem { all_day: true, ..item("e3", "Tomorrow's offsite", "2026-10-17T00:00:00Z", "confirmed") },
+            // Starts at midnight UTC but is not all-day
+            item("e4", "Early call", "2026-10-16T00:00:00Z", "confirmed"),
+        ]).unwrap();
+
+        let digest = compile(&conn, now).unwrap();
+        assert_eq!(digest.summary, "1 important email, 3 events, 1 task due, 1 follow-up");
+        assert!(digest.body.starts_with("Daily digest for Friday, October 16"));
+        assert!(digest.body.contains("- Contract renewal — Ops"));
+        assert!(digest.body.contains("- All day Company holiday\n- 02:00 Early call\n- 12:00 Lunch with Kim"));
+        assert!(digest.body.contains("- File expenses (overdue since Oct 14)"));
+        assert!(digest.body.contains("- Reply to Sam — Ops"));
+    }
+
+    #[test]
+    fn test_check_template() {
+        assert!(check_template(DEFAULT_DIGEST_TEMPLATE).is_ok());
+        assert!(check_template(&default_template()).is_ok());
+        assert!(check_template("{{title}}\n{{weather}}").is_err());
+    }
+
+    #[test]
+    fn test_render_fills_empty_sections() {
+        let variables = HashMap::from([("mail".to_string(), String::new())]);
+        assert_eq!(render("Mail: {{mail | Nothing}}", &variables).unwrap(), "Mail: Nothing");
+        assert_eq!(render("Mail: {{mail}}", &variables).unwrap(), "Mail: (none)");
+        assert!(render("{{unknown}}", &variables).is_err());
+    }
+
+    #[test]
+    fn test_default_template_matches_english() {
+        assert_eq!(default_template(), DEFAULT_DIGEST_TEMPLATE);
+    }
+}
@@ -0,0 +1,211 @@
+//! Diagnostics bundle
+//!
+//! Collects what a maintainer needs to look into a bug report into one zip:
+//! app and platform details, the configuration with its secrets replaced,
+//! the schema version and migration history, sync status and recent runs,
+//! health checks, and the tail of the application logs.
+//!
+//! Every text file in the bundle goes through `crash_reports::sanitize`, so
+//! emails, tokens and the home directory are redacted before anything is
+//! written. The bundle stays on disk until the user chooses to share it.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppConfig;
+use crate::database::operations::log_operations;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::crash_reports::sanitize;
+
+const DIAGNOSTICS_DIR: &str = "diagnostics";
+const REDACTED: &str = "<redacted>";
+/// Database log entries included in logs.txt
+pub const LOG_ENTRY_LIMIT: i32 = 500;
+/// Only the end of each log file is kept
+const LOG_FILE_TAIL_BYTES: u64 = 64 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub generated_at: String,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+}
+
+impl Manifest {
+    pub fn now() -> Self {
+        Self {
+            generated_at: Utc::now().to_rfc3339(),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppliedMigration {
+    pub version: i32,
+    pub applied_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaInfo {
+    pub current_version: i32,
+    pub migrations: Vec<AppliedMigration>,
+}
+
+/// Everything that goes into the zip, one file per field
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsBundle {
+    pub manifest: Manifest,
+    pub config: AppConfig,
+    pub schema: SchemaInfo,
+    pub sync: serde_json::Value,
+    pub health: serde_json::Value,
+    pub logs: String,
+}
+
+/// The configuration with secret values replaced
+pub fn redacted_config(config: &AppConfig) -> AppConfig {
+    let mut config = config.clone();
+    for secret in [&mut config.oauth.client_secret, &mut config.database.encryption_key] {
+        if !secret.is_empty() {
+            *secret = REDACTED.to_string();
+        }
+    }
+    config
+}
+
+/// Applied migrations, oldest first
+pub fn schema_info(conn: &Connection) -> Result<SchemaInfo> {
+    let mut stmt = conn.prepare("SELECT version, applied_at FROM schema_version ORDER BY version")?;
+    let migrations = stmt
+        .query_map([], |row| {
+            Ok(AppliedMigration {
+                version: row.get(0)?,
+                applied_at: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(SchemaInfo {
+        current_version: migrations.last().map(|migration| migration.version).unwrap_or(0),
+        migrations,
+    })
+}
+
+fn tail(path: &Path) -> std::io::Result<String> {
+    use std::io::{Read, Seek, SeekFrom};
+    let mut file = std::fs::File::open(path)?;
+    let length = file.metadata()?.len();
+    file.seek(SeekFrom::Start(length.saturating_sub(LOG_FILE_TAIL_BYTES)))?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Recent database log entries followed by the tail of each `.log` file
+/// in `logs_dir`
+pub fn recent_logs(conn: &Connection, logs_dir: &Path) -> Result<String> {
+    let mut logs = String::from("== application_logs ==\n");
+    let mut entries = log_operations::get_recent_application_logs(conn, LOG_ENTRY_LIMIT)?;
+    entries.reverse();
+    for entry in entries {
+        logs.push_str(&format!(
+            "{} {:<5} {}::{} {}\n",
+            entry.timestamp, entry.log_level.as_str(), entry.module_name, entry.function_name, entry.message
+        ));
+    }
+
+    let mut files: Vec<PathBuf> = std::fs::read_dir(logs_dir)
+        .map(|entries| entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect())
+        .unwrap_or_default();
+    files.retain(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "log"));
+    files.sort();
+    for path in files {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        match tail(&path) {
+            Ok(text) => logs.push_str(&format!("\n== {} ==\n{}", name, text)),
+            Err(e) => logs.push_str(&format!("\n== {} ==\n(unreadable: {})\n", name, e)),
+        }
+    }
+    Ok(logs)
+}
+
+/// Where bundles are written, under the configured logs directory
+pub fn diagnostics_dir(logs_dir: &Path) -> PathBuf {
+    logs_dir.join(DIAGNOSTICS_DIR)
+}
+
+/// Write the bundle as a zip of sanitized files, returning its path
+pub fn write_bundle(dir: &Path, bundle: &DiagnosticsBundle) -> Result<PathBuf> {
+    let files: [(&str, String); 6] = [
+        ("manifest.json", serde_json::to_string_pretty(&bundle.manifest)?),
+        ("config.json", serde_json::to_string_pretty(&redacted_config(&bundle.config))?),
+        ("schema.json", serde_json::to_string_pretty(&bundle.schema)?),
+        ("sync.json", serde_json::to_string_pretty(&bundle.sync)?),
+        ("health.json", serde_json::to_string_pretty(&bundle.health)?),
+        ("logs.txt", bundle.logs.clone()),
+    ];
+
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!("libreollama-diagnostics-{}.zip", Utc::now().format("%Y%m%d-%H%M%S")));
+    let zip_error = |e: zip::result::ZipError| LibreOllamaError::Internal { message: format!("Failed to write zip: {}", e) };
+
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(&path)?);
+    let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for (name, contents) in files {
+        zip.start_file(name, options).map_err(zip_error)?;
+        zip.write_all(sanitize(&contents).as_bytes())?;
+    }
+    zip.finish().map_err(zip_error)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_is_redacted() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE schema_version (version INTEGER PRIMARY KEY, applied_at TEXT);
+             INSERT INTO schema_version VALUES (1, '2026-01-01 00:00:00'), (2, '2026-02-01 00:00:00');",
+        )
+        .unwrap();
+        let schema = schema_info(&conn).unwrap();
+        assert_eq!(schema.current_version, 2);
+        assert_eq!(schema.migrations.len(), 2);
+
+        let mut config = AppConfig::default();
+        config.oauth.client_secret = "very-secret".to_string();
+        let bundle = DiagnosticsBundle {
+            manifest: Manifest::now(),
+            config,
+            schema,
+            sync: serde_json::json!([{ "email": "ada@example.com", "recent_errors": [] }]),
+            health: serde_json::json!({ "database": { "reachable": true } }),
+            logs: "refresh failed: refresh_token=1//0gabc".to_string(),
+        };
+
+        let dir = std::env::temp_dir().join(format!("libreollama-diagnostics-{}", uuid::Uuid::new_v4()));
+        let path = write_bundle(&dir, &bundle).unwrap();
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+        assert_eq!(archive.len(), 6);
+        let mut read = |name: &str| {
+            let mut text = String::new();
+            std::io::Read::read_to_string(&mut archive.by_name(name).unwrap(), &mut text).unwrap();
+            text
+        };
+        assert!(read("config.json").contains(REDACTED));
+        assert!(!read("config.json").contains("very-secret"));
+        assert!(read("sync.json").contains("<email>"));
+        assert!(!read("logs.txt").contains("1//0gabc"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,669 @@
+//! Backend → frontend event bus
+//!
+//! State changes that happen in the backend (a sync finishing, a token
+//! expiring, new mail arriving) are pushed to every window as typed Tauri
+//! events instead of waiting for the frontend to poll. All event names share
+//! the `backend://` prefix, and `event_schema` describes each payload so the
+//! frontend can subscribe without hard-coding shapes.
+
+use crate::database::operations::cache_delta_operations::{self, CacheDelta, NewCacheDelta};
+use crate::database::operations::sync_run_operations::{self, NewSyncRun};
+use crate::database::DatabaseManager;
+use crate::errors::LibreOllamaError;
+use crate::services::app_lock::AppLock;
+use crate::services::availability::{AvailabilityConflict, AvailabilityPurpose};
+use crate::services::gmail::thread_export::ExportStage;
+use crate::services::hardware::FitLevel;
+use crate::services::notification::{MailAlert, MailNotice, NotificationService};
+use crate::services::settings;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// A sync run (tasks, mail, pending changes...) completed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncFinishedEvent {
+    pub account_id: String,
+    pub domain: String,
+    pub success: bool,
+    pub items_changed: u32,
+    pub error: Option<String>,
+}
+
+/// An account's OAuth token expired or was revoked and needs re-authentication
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenExpiredEvent {
+    pub account_id: String,
+    pub reason: String,
+}
+
+/// Messages appeared in an account's inbox since it was last seen
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewMailEvent {
+    pub account_id: String,
+    pub message_ids: Vec<String>,
+    /// How to announce each message; messages held back by quiet hours
+    /// are left out of the event
+    pub alerts: Vec<MailAlert>,
+    /// Sent by `test_vip_alert` rather than by arriving mail
+    pub test: bool,
+}
+
+/// Offline edits ran into server-side changes and need manual resolution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConflictDetectedEvent {
+    pub account_id: String,
+    pub conflict_count: u32,
+}
+
+/// Queued offline edits were refused by the server and need attention
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangesRejectedEvent {
+    pub account_id: String,
+    pub rejected_count: u32,
+}
+
+/// A tracked shipment moved to a new status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShipmentUpdatedEvent {
+    pub account_id: String,
+    pub shipment_id: i64,
+    pub carrier: String,
+    pub tracking_number: String,
+    pub previous_status: String,
+    pub status: String,
+}
+
+/// Progress of uploading an oversized attachment to Drive before sending
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachmentUploadProgressEvent {
+    pub account_id: String,
+    pub filename: String,
+    pub bytes_sent: u64,
+    pub total_bytes: u64,
+}
+
+/// Progress of exporting a thread to PDF or Markdown
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadExportProgressEvent {
+    pub account_id: String,
+    pub thread_id: String,
+    pub stage: ExportStage,
+    pub done: usize,
+    pub total: usize,
+}
+
+/// A setting was changed from the settings UI or by an import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingChangedEvent {
+    pub key: String,
+    pub category: String,
+    pub value: serde_json::Value,
+}
+
+/// A feature's model is tight on memory or too large for this machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelResourceWarningEvent {
+    pub feature: String,
+    pub provider_id: String,
+    pub model: String,
+    pub level: FitLevel,
+    pub required_bytes: u64,
+    pub available_bytes: u64,
+    pub message: String,
+    /// Registered models that fit instead
+    pub alternatives: Vec<String>,
+}
+
+/// Copied text was recorded as a capture the user may accept
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipboardCaptureEvent {
+    pub id: i64,
+    pub kind: String,
+    pub pattern_name: Option<String>,
+    pub content: String,
+    pub created_at: String,
+}
+
+/// A feed poll found items not seen before
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedItemsAddedEvent {
+    pub feed_id: i64,
+    pub feed_title: String,
+    pub new_count: usize,
+}
+
+/// The daily digest is ready to be shown as a desktop notification
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyDigestEvent {
+    /// Local date the digest covers, "YYYY-MM-DD"
+    pub date: String,
+    pub title: String,
+    /// One line with the count of each section
+    pub summary: String,
+    /// The full rendered digest
+    pub body: String,
+}
+
+/// Another workspace became active; views and caches should reload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceChangedEvent {
+    /// None when no workspace is active and everything is shown
+    pub workspace_id: Option<i64>,
+    pub name: Option<String>,
+}
+
+/// The secure notes vault locked; open secure notes should be closed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultLockedEvent {
+    /// "manual", "inactivity" or "app_lock"
+    pub reason: String,
+}
+
+/// The application locked or unlocked; while locked only the lock screen
+/// can be shown
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppLockChangedEvent {
+    pub locked: bool,
+    /// "manual" or "inactivity" when locking
+    pub reason: Option<String>,
+}
+
+/// Migrations finished and background services started; commands that
+/// waited on startup go ahead from here
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendReadyEvent {
+    /// From the start of setup until ready
+    pub startup_ms: u64,
+    pub schema_version: i32,
+    /// Why migrations failed, if they did
+    pub error: Option<String>,
+}
+
+/// A command line, from this launch or a later one, asked for something;
+/// `take_launch_requests` returns what
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LaunchRequestedEvent {
+    /// Requests waiting to be taken
+    pub pending: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateState {
+    Idle,
+    Checking,
+    UpToDate,
+    Available,
+    Downloading,
+    /// Downloaded and verified; installs on restart
+    Ready,
+    Error,
+}
+
+/// The updater moved on: a check finished, or a download started or
+/// finished
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateStatusChangedEvent {
+    pub state: UpdateState,
+    pub current_version: String,
+    pub channel: String,
+    pub available_version: Option<String>,
+    pub release_notes: Option<String>,
+    pub release_date: Option<String>,
+    pub downloaded_bytes: u64,
+    pub total_bytes: Option<u64>,
+    pub last_checked_at: Option<String>,
+    pub error: Option<String>,
+}
+
+/// A saved event or time-blocked task goes against the availability
+/// profile: outside working hours, in a break or in a no-meeting block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvailabilityConflictEvent {
+    /// `event` or `task`
+    pub item_type: String,
+    pub item_id: String,
+    pub title: Option<String>,
+    pub purpose: AvailabilityPurpose,
+    pub conflicts: Vec<AvailabilityConflict>,
+}
+
+/// Cached messages, tasks or calendar events changed. Stores apply the
+/// deltas in `seq` order; after a gap they call `get_changes_since`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheChangedEvent {
+    pub deltas: Vec<CacheDelta>,
+}
+
+/// Every event the backend can push to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "payload", rename_all = "snake_case")]
+pub enum BackendEvent {
+    SyncFinished(SyncFinishedEvent),
+    TokenExpired(TokenExpiredEvent),
+    NewMail(NewMailEvent),
+    ConflictDetected(ConflictDetectedEvent),
+    ChangesRejected(ChangesRejectedEvent),
+    ShipmentUpdated(ShipmentUpdatedEvent),
+    AttachmentUploadProgress(AttachmentUploadProgressEvent),
+    ThreadExportProgress(ThreadExportProgressEvent),
+    SettingChanged(SettingChangedEvent),
+    ModelResourceWarning(ModelResourceWarningEvent),
+    ClipboardCapture(ClipboardCaptureEvent),
+    FeedItemsAdded(FeedItemsAddedEvent),
+    DailyDigest(DailyDigestEvent),
+    WorkspaceChanged(WorkspaceChangedEvent),
+    VaultLocked(VaultLockedEvent),
+    AppLockChanged(AppLockChangedEvent),
+    BackendReady(BackendReadyEvent),
+    LaunchRequested(LaunchRequestedEvent),
+    UpdateStatusChanged(UpdateStatusChangedEvent),
+    CacheChanged(CacheChangedEvent),
+    AvailabilityConflict(AvailabilityConflictEvent),
+}
+
+impl BackendEvent {
+    /// Tauri event name the frontend listens on
+    pub fn name(&self) -> &'static str {
+        match self {
+            BackendEvent::SyncFinished(_) => "backend://sync-finished",
+            BackendEvent::TokenExpired(_) => "backend://token-expired",
+            BackendEvent::NewMail(_) => "backend://new-mail",
+            BackendEvent::ConflictDetected(_) => "backend://conflict-detected",
+            BackendEvent::ChangesRejected(_) => "backend://changes-rejected",
+            BackendEvent::ShipmentUpdated(_) => "backend://shipment-updated",
+            BackendEvent::AttachmentUploadProgress(_) => "backend://attachment-upload-progress",
+            BackendEvent::ThreadExportProgress(_) => "backend://thread-export-progress",
+            BackendEvent::SettingChanged(_) => "backend://setting-changed",
+            BackendEvent::ModelResourceWarning(_) => "backend://model-resource-warning",
+            BackendEvent::ClipboardCapture(_) => "backend://clipboard-capture",
+            BackendEvent::FeedItemsAdded(_) => "backend://feed-items-added",
+            BackendEvent::DailyDigest(_) => "backend://daily-digest",
+            BackendEvent::WorkspaceChanged(_) => "backend://workspace-changed",
+            BackendEvent::VaultLocked(_) => "backend://vault-locked",
+            BackendEvent::AppLockChanged(_) => "backend://app-lock-changed",
+            BackendEvent::BackendReady(_) => "backend://ready",
+            BackendEvent::LaunchRequested(_) => "backend://launch-requested",
+            BackendEvent::UpdateStatusChanged(_) => "backend://update-status",
+            BackendEvent::CacheChanged(_) => "backend://cache-changed",
+            BackendEvent::AvailabilityConflict(_) => "backend://availability-conflict",
+        }
+    }
+}
+
+/// Description of one event for the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventDescriptor {
+    pub name: String,
+    pub description: String,
+    pub fields: Vec<String>,
+}
+
+/// Names, descriptions and payload fields of every backend event
+pub fn event_schema() -> Vec<EventDescriptor> {
+    let describe = |name: &str, description: &str, fields: &[&str]| EventDescriptor {
+        name: name.to_string(),
+        description: description.to_string(),
+        fields: fields.iter().map(|f| f.to_string()).collect(),
+    };
+
+    vec![
+        describe(
+            "backend://sync-finished",
+            "A sync run completed",
+            &["account_id", "domain", "success", "items_changed", "error"],
+        ),
+        describe(
+            "backend://token-expired",
+            "An account needs to re-authenticate",
+            &["account_id", "reason"],
+        ),
+        describe(
+            "backend://new-mail",
+            "New messages arrived in the inbox; each alert says whether it is from a VIP, its sound and whether to preview it",
+            &["account_id", "message_ids", "alerts", "test"],
+        ),
+        describe(
+            "backend://conflict-detected",
+            "Offline edits conflict with server changes",
+            &["account_id", "conflict_count"],
+        ),
+        describe(
+            "backend://changes-rejected",
+            "Offline edits were refused by the server",
+            &["account_id", "rejected_count"],
+        ),
+        describe(
+            "backend://shipment-updated",
+            "A tracked shipment changed status",
+            &["account_id", "shipment_id", "carrier", "tracking_number", "previous_status", "status"],
+        ),
+        describe(
+            "backend://attachment-upload-progress",
+            "An oversized attachment is being uploaded to Drive",
+            &["account_id", "filename", "bytes_sent", "total_bytes"],
+        ),
+        describe(
+            "backend://thread-export-progress",
+            "A thread is being exported to PDF or Markdown",
+            &["account_id", "thread_id", "stage", "done", "total"],
+        ),
+        describe(
+            "backend://setting-changed",
+            "A setting changed",
+            &["key", "category", "value"],
+        ),
+        describe(
+            "backend://model-resource-warning",
+            "A feature's model may not fit in memory",
+            &["feature", "provider_id", "model", "level", "required_bytes", "available_bytes", "message", "alternatives"],
+        ),
+        describe(
+            "backend://clipboard-capture",
+            "Copied text can be captured",
+            &["id", "kind", "pattern_name", "content", "created_at"],
+        ),
+        describe(
+            "backend://feed-items-added",
+            "A subscribed feed has new items",
+            &["feed_id", "feed_title", "new_count"],
+        ),
+        describe(
+            "backend://daily-digest",
+            "The daily digest of mail, events, tasks and follow-ups is ready",
+            &["date", "title", "summary", "body"],
+        ),
+        describe(
+            "backend://workspace-changed",
+            "Another workspace became active, so listings and cached data should be reloaded",
+            &["workspace_id", "name"],
+        ),
+        describe(
+            "backend://vault-locked",
+            "The secure notes vault locked, by request or after inactivity",
+            &["reason"],
+        ),
+        describe(
+            "backend://app-lock-changed",
+            "The application locked or unlocked; data commands are refused while locked",
+            &["locked", "reason"],
+        ),
+        describe(
+            "backend://ready",
+            "Startup finished: the database is migrated and background services are running",
+            &["startup_ms", "schema_version", "error"],
+        ),
+        describe(
+            "backend://launch-requested",
+            "A mailto: link or capture arrived on the command line; take_launch_requests returns it",
+            &["pending"],
+        ),
+        describe(
+            "backend://update-status",
+            "An update check finished or an update download started or finished",
+            &["state", "current_version", "channel", "available_version", "release_notes", "release_date", "downloaded_bytes", "total_bytes", "last_checked_at", "error"],
+        ),
+        describe(
+            "backend://cache-changed",
+            "Cached messages, tasks or events were added, updated or removed; each delta has a sequence number for get_changes_since",
+            &["deltas"],
+        ),
+        describe(
+            "backend://availability-conflict",
+            "A saved event or time-blocked task falls outside working hours or in a break or no-meeting block",
+            &["item_type", "item_id", "title", "purpose", "conflicts"],
+        ),
+    ]
+}
+
+/// The category of the typed error behind a sync failure, or `sync` when
+/// there is none
+pub fn sync_error_category(error: &anyhow::Error) -> &'static str {
+    error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<LibreOllamaError>())
+        .map(|e| e.category())
+        .unwrap_or("sync")
+}
+
+/// Central emitter for backend events, managed as Tauri state
+pub struct EventBus {
+    app: AppHandle,
+    /// Inbox message IDs already reported per account, for new-mail detection
+    seen_inbox: Mutex<HashMap<String, HashSet<String>>>,
+}
+
+impl EventBus {
+    pub fn new(app: AppHandle) -> Self {
+        Self {
+            app,
+            seen_inbox: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Broadcast an event to all windows
+    pub fn emit(&self, event: BackendEvent) {
+        // Only the lock screen's own events get through while the app is
+        // locked, and new mail, whose alerts then carry no preview
+        let lock_screen_event = matches!(
+            event,
+            BackendEvent::AppLockChanged(_)
+                | BackendEvent::BackendReady(_)
+                | BackendEvent::VaultLocked(_)
+                | BackendEvent::NewMail(NewMailEvent { test: false, .. })
+        );
+        if !lock_screen_event && self.app.try_state::<AppLock>().is_some_and(|lock| lock.is_locked()) {
+            return;
+        }
+        let name = event.name();
+        let result = match &event {
+            BackendEvent::SyncFinished(payload) => self.app.emit(name, payload),
+            BackendEvent::TokenExpired(payload) => self.app.emit(name, payload),
+            BackendEvent::NewMail(payload) => self.app.emit(name, payload),
+            BackendEvent::ConflictDetected(payload) => self.app.emit(name, payload),
+            BackendEvent::ChangesRejected(payload) => self.app.emit(name, payload),
+            BackendEvent::ShipmentUpdated(payload) => self.app.emit(name, payload),
+            BackendEvent::AttachmentUploadProgress(payload) => self.app.emit(name, payload),
+            BackendEvent::ThreadExportProgress(payload) => self.app.emit(name, payload),
+            BackendEvent::SettingChanged(payload) => self.app.emit(name, payload),
+            BackendEvent::ModelResourceWarning(payload) => self.app.emit(name, payload),
+            BackendEvent::ClipboardCapture(payload) => self.app.emit(name, payload),
+            BackendEvent::FeedItemsAdded(payload) => self.app.emit(name, payload),
+            BackendEvent::DailyDigest(payload) => self.app.emit(name, payload),
+            BackendEvent::WorkspaceChanged(payload) => self.app.emit(name, payload),
+            BackendEvent::VaultLocked(payload) => self.app.emit(name, payload),
+            BackendEvent::AppLockChanged(payload) => self.app.emit(name, payload),
+            BackendEvent::BackendReady(payload) => self.app.emit(name, payload),
+            BackendEvent::LaunchRequested(payload) => self.app.emit(name, payload),
+            BackendEvent::UpdateStatusChanged(payload) => self.app.emit(name, payload),
+            BackendEvent::CacheChanged(payload) => self.app.emit(name, payload),
+            BackendEvent::AvailabilityConflict(payload) => self.app.emit(name, payload),
+        };
+
+        if let Err(e) = result {
+            eprintln!("⚠️ Failed to emit {}: {}", name, e);
+        }
+    }
+
+    /// Record a finished sync run in the sync history and broadcast it.
+    /// `sync_type` is `full`, `incremental`, or `interactive` for requests
+    /// the user made directly. A failure is filed under its error category.
+    pub fn sync_finished(&self, account_id: &str, domain: &str, sync_type: &str, items_changed: u32, error: Option<&anyhow::Error>) {
+        let message = error.map(|e| format!("{:#}", e));
+        self.record_run(&NewSyncRun {
+            account_id,
+            domain,
+            sync_type,
+            items_changed: items_changed as i64,
+            error: message.as_deref(),
+            error_category: error.map(sync_error_category),
+        });
+
+        self.emit(BackendEvent::SyncFinished(SyncFinishedEvent {
+            account_id: account_id.to_string(),
+            domain: domain.to_string(),
+            success: error.is_none(),
+            items_changed,
+            error: message,
+        }));
+    }
+
+    /// Emit `token-expired` when an error means the account must sign in again
+    /// Failures are also kept in the sync history under the error's category.
+    pub fn report_error(&self, account_id: &str, sync_type: &str, error: &LibreOllamaError) {
+        let message = error.to_string();
+        self.record_run(&NewSyncRun {
+            account_id,
+            domain: "gmail",
+            sync_type,
+            items_changed: 0,
+            error: Some(&message),
+            error_category: Some(error.category()),
+        });
+
+        if let LibreOllamaError::GmailAuth { message, .. } = error {
+            self.emit(BackendEvent::TokenExpired(TokenExpiredEvent {
+                account_id: account_id.to_string(),
+                reason: message.clone(),
+            }));
+        }
+    }
+
+    /// Log cache deltas and broadcast them with their sequence numbers.
+    /// Failures are logged; stores catch up on their next reload.
+    pub fn cache_changed(&self, deltas: &[NewCacheDelta]) {
+        if deltas.is_empty() {
+            return;
+        }
+        let Some(db_manager) = self.app.try_state::<Arc<DatabaseManager>>() else {
+            return;
+        };
+        let recorded = db_manager
+            .get_connection()
+            .and_then(|conn| cache_delta_operations::record_deltas(&conn, deltas));
+        match recorded {
+            Ok(deltas) => self.emit(BackendEvent::CacheChanged(CacheChangedEvent { deltas })),
+            Err(e) => eprintln!("⚠️ Failed to record cache deltas: {}", e),
+        }
+    }
+
+    /// Warn that a saved event or time-blocked task goes against the
+    /// availability profile; nothing is sent when it fits
+    pub fn availability_conflicts(
+        &self,
+        item_type: &str,
+        item_id: &str,
+        title: Option<&str>,
+        purpose: AvailabilityPurpose,
+        conflicts: Vec<AvailabilityConflict>,
+    ) {
+        if conflicts.is_empty() {
+            return;
+        }
+        self.emit(BackendEvent::AvailabilityConflict(AvailabilityConflictEvent {
+            item_type: item_type.to_string(),
+            item_id: item_id.to_string(),
+            title: title.map(|t| t.to_string()),
+            purpose,
+            conflicts,
+        }));
+    }
+
+    fn record_run(&self, run: &NewSyncRun) {
+        let Some(db_manager) = self.app.try_state::<Arc<DatabaseManager>>() else {
+            return;
+        };
+        let result = db_manager
+            .get_connection()
+            .and_then(|conn| sync_run_operations::record_sync_run(&conn, run));
+        if let Err(e) = result {
+            eprintln!("⚠️ Failed to record sync run: {}", e);
+        }
+    }
+
+    fn new_mail_notifications_enabled(&self) -> bool {
+        let Some(db_manager) = self.app.try_state::<Arc<DatabaseManager>>() else {
+            return true;
+        };
+        db_manager
+            .get_connection()
+            .and_then(|conn| settings::get_bool(&conn, settings::NEW_MAIL_NOTIFICATIONS_SETTING))
+            .unwrap_or(true)
+    }
+
+    /// Track the first inbox page and emit `new-mail` for unseen messages.
+    ///
+    /// The first page seen for an account only establishes the baseline.
+    /// The notification service decides which messages are announced now.
+    pub fn observe_inbox(&self, account_id: &str, messages: &[MailNotice]) {
+        let new_messages: Vec<MailNotice> = {
+            let mut seen = match self.seen_inbox.lock() {
+                Ok(seen) => seen,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            let first_page = !seen.contains_key(account_id);
+            let known = seen.entry(account_id.to_string()).or_default();
+            let new_messages: Vec<MailNotice> = messages
+                .iter()
+                .filter(|message| known.insert(message.message_id.clone()))
+                .cloned()
+                .collect();
+            if first_page {
+                Vec::new()
+            } else {
+                new_messages
+            }
+        };
+        self.announce_mail(account_id, new_messages);
+    }
+
+    /// Emit `new-mail` for inbox messages a sync just fetched, unless the
+    /// inbox listing announced them already
+    pub fn mail_arrived(&self, account_id: &str, messages: &[MailNotice]) {
+        let new_messages: Vec<MailNotice> = {
+            let mut seen = match self.seen_inbox.lock() {
+                Ok(seen) => seen,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            // Without a baseline yet, the first listing will take these in
+            match seen.get_mut(account_id) {
+                Some(known) => messages.iter().filter(|message| known.insert(message.message_id.clone())).cloned().collect(),
+                None => messages.to_vec(),
+            }
+        };
+        self.announce_mail(account_id, new_messages);
+    }
+
+    fn announce_mail(&self, account_id: &str, notices: Vec<MailNotice>) {
+        if notices.is_empty() || !self.new_mail_notifications_enabled() {
+            return;
+        }
+        // If the rules cannot be read every message is announced plainly
+        let alerts = match self.app.try_state::<NotificationService>().map(|service| service.alerts(&notices)) {
+            Some(Ok(alerts)) => alerts,
+            Some(Err(e)) => {
+                eprintln!("⚠️ Failed to apply notification rules: {}", e);
+                notices.iter().map(MailAlert::plain).collect()
+            }
+            None => notices.iter().map(MailAlert::plain).collect(),
+        };
+        if alerts.is_empty() {
+            return;
+        }
+        let locked = self.app.try_state::<AppLock>().is_some_and(|lock| lock.is_locked());
+        let alerts: Vec<MailAlert> = if locked {
+            alerts.into_iter().map(MailAlert::without_preview).collect()
+        } else {
+            alerts
+        };
+        let message_ids = alerts.iter().map(|alert| alert.message_id.clone()).collect();
+        self.emit(BackendEvent::NewMail(NewMailEvent {
+            account_id: account_id.to_string(),
+            message_ids,
+            alerts,
+            test: false,
+        }));
+    }
+}
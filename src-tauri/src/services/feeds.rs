@@ -0,0 +1,493 @@
+//! RSS, Atom and JSON feed subscriptions
+//!
+//! Feeds are fetched with `fetch_public` like any other URL from the user,
+//! parsed with `feed-rs`, and their entries stored in `feed_items`. A page
+//! URL works too when it advertises its feed with a `<link rel="alternate">`
+//! tag. The poller refreshes every feed on the interval from settings and
+//! announces new items; the digest gathers the day's unread items, with an
+//! optional summary from the local model written from the `feeds.digest`
+//! prompt template.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Manager};
+use url::Url;
+
+use crate::database::operations::feed_operations::{self, FeedItemFilter, FeedItemRecord, FeedRecord, NewFeedItem};
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::events::{BackendEvent, EventBus, FeedItemsAddedEvent};
+use crate::services::i18n;
+use crate::services::link_preview::{self, decode_entities};
+use crate::services::llm_provider::{self, ChatMessage, LlmFeature};
+use crate::services::prompt_templates;
+use crate::services::settings;
+use crate::services::task_supervisor::TaskSpec;
+use crate::services::text_processing::to_plain_text;
+use crate::utils::http_client::fetch_public;
+
+const MAX_FEED_BYTES: usize = 5 * 1024 * 1024;
+const MAX_SUMMARY_CHARS: usize = 500;
+const MAX_TITLE_CHARS: usize = 200;
+/// Read, unstarred items are forgotten after this many days
+const KEEP_READ_DAYS: i64 = 90;
+/// Items gathered into one digest
+const MAX_DIGEST_ITEMS: i64 = 40;
+const FEED_ACCEPT: &str =
+    "application/rss+xml, application/atom+xml, application/feed+json, application/xml;q=0.9, text/xml;q=0.9, text/html;q=0.5";
+const FEED_LINK_TYPES: &[&str] =
+    &["application/rss+xml", "application/atom+xml", "application/feed+json", "application/json"];
+const SQLITE_DATETIME: &str = "%Y-%m-%d %H:%M:%S";
+/// Prompt template feature the digest summary is written with
+const DIGEST_PROMPT_FEATURE: &str = "feeds.digest";
+
+/// A feed document reduced to what is stored
+#[derive(Debug, Clone, Default)]
+pub struct ParsedFeed {
+    pub title: Option<String>,
+    pub site_url: Option<String>,
+    pub description: Option<String>,
+    pub items: Vec<NewFeedItem>,
+}
+
+/// Outcome of refreshing one feed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedRefresh {
+    pub feed_id: i64,
+    pub feed_title: String,
+    pub new_items: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedDigest {
+    /// Items published since this UTC time are included
+    pub since: String,
+    pub items: Vec<FeedItemRecord>,
+    pub summary: Option<String>,
+    pub model: Option<String>,
+    /// The digest as a Markdown document, ready to keep as a note
+    pub markdown: String,
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(max_chars - 1).collect();
+    format!("{}…", cut.trim_end())
+}
+
+/// Plain single-line text from feed markup
+fn plain(text: &str, max_chars: usize) -> Option<String> {
+    let text = decode_entities(&to_plain_text(text)).split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then(|| truncate_chars(&text, max_chars))
+}
+
+pub fn parse_feed(bytes: &[u8]) -> Result<ParsedFeed> {
+    let feed = feed_rs::parser::parse(bytes).map_err(|e| LibreOllamaError::InvalidInput {
+        message: format!("Not an RSS, Atom or JSON feed: {}", e),
+        field: Some("url".to_string()),
+    })?;
+
+    let items = feed
+        .entries
+        .into_iter()
+        .filter_map(|entry| {
+            let url = entry
+                .links
+                .iter()
+                .find(|link| link.rel.as_deref().is_none_or(|rel| rel == "alternate"))
+                .or(entry.links.first())
+                .map(|link| link.href.clone());
+            let guid = Some(entry.id.trim().to_string()).filter(|id| !id.is_empty()).or(url.clone())?;
+            let content = entry.content.and_then(|content| content.body);
+            let summary = entry
+                .summary
+                .map(|summary| summary.content)
+                .or(content.clone())
+                .and_then(|summary| plain(&summary, MAX_SUMMARY_CHARS));
+            let title = entry
+                .title
+                .and_then(|title| plain(&title.content, MAX_TITLE_CHARS))
+                .or_else(|| summary.as_deref().map(|summary| truncate_chars(summary, 80)))
+                .unwrap_or_else(|| "Untitled".to_string());
+            Some(NewFeedItem {
+                guid,
+                title,
+                url,
+                author: entry.authors.first().map(|person| person.name.trim().to_string()).filter(|name| !name.is_empty()),
+                summary,
+                content,
+                published_at: entry.published.or(entry.updated).map(|date| date.format(SQLITE_DATETIME).to_string()),
+            })
+        })
+        .collect();
+
+    Ok(ParsedFeed {
+        title: feed.title.and_then(|title| plain(&title.content, MAX_TITLE_CHARS)),
+        site_url: feed
+            .links
+            .iter()
+            .find(|link| link.rel.as_deref() != Some("self"))
+            .map(|link| link.href.clone()),
+        description: feed.description.and_then(|description| plain(&description.content, MAX_SUMMARY_CHARS)),
+        items,
+    })
+}
+
+/// The feed a web page advertises in its head, if any
+pub fn discover_feed_url(html: &str, base: &Url) -> Option<Url> {
+    link_preview::head_links(html).into_iter().find_map(|attrs| {
+        let rel = link_preview::attribute(&attrs, "rel")?.to_ascii_lowercase();
+        let kind = link_preview::attribute(&attrs, "type")?.trim().to_ascii_lowercase();
+        let href = link_preview::attribute(&attrs, "href")?;
+        if !rel.split_whitespace().any(|token| token == "alternate") || !FEED_LINK_TYPES.contains(&kind.as_str()) {
+            return None;
+        }
+        base.join(&decode_entities(href.trim())).ok()
+    })
+}
+
+fn is_feed_type(content_type: &str) -> bool {
+    content_type.is_empty()
+        || ["xml", "rss", "atom", "json"].iter().any(|kind| content_type.contains(kind))
+        || content_type == "text/html"
+        || content_type == "text/plain"
+}
+
+fn parse_url(url: &str) -> Result<Url> {
+    Url::parse(url.trim()).map_err(|e| LibreOllamaError::InvalidInput {
+        message: format!("Invalid feed URL: {}", e),
+        field: Some("url".to_string()),
+    })
+}
+
+/// Fetch and parse a feed, following a page's feed link when given a page
+async fn fetch_feed(url: Url) -> Result<(Url, ParsedFeed)> {
+    let response = fetch_public(url, FEED_ACCEPT, MAX_FEED_BYTES, is_feed_type).await?;
+    match parse_feed(&response.body) {
+        Ok(parsed) => Ok((response.url, parsed)),
+        Err(e) if response.content_type == "text/html" => {
+            let html = String::from_utf8_lossy(&response.body);
+            let Some(feed_url) = discover_feed_url(&html, &response.url) else {
+                return Err(LibreOllamaError::InvalidInput {
+                    message: format!("{} is a web page without a feed link", response.url),
+                    field: Some("url".to_string()),
+                });
+            };
+            println!("📰 [FEEDS] {} advertises {}", response.url, feed_url);
+            let response = fetch_public(feed_url, FEED_ACCEPT, MAX_FEED_BYTES, is_feed_type).await?;
+            parse_feed(&response.body).map(|parsed| (response.url, parsed)).map_err(|_| e)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Subscribe to a feed, or to the feed a page links to, storing its current items
+pub async fn subscribe(db_manager: &Arc<DatabaseManager>, url: &str) -> Result<FeedRecord> {
+    let (feed_url, parsed) = fetch_feed(parse_url(url)?).await?;
+
+    let conn = db_manager.get_connection()?;
+    if let Some(existing) = feed_operations::find_feed_by_url(&conn, feed_url.as_str())? {
+        return Err(LibreOllamaError::InvalidInput {
+            message: format!("Already subscribed to {}", existing.title),
+            field: Some("url".to_string()),
+        });
+    }
+
+    let title = parsed
+        .title
+        .clone()
+        .unwrap_or_else(|| feed_url.host_str().unwrap_or("Feed").to_string());
+    let feed = feed_operations::add_feed(
+        &conn,
+        feed_url.as_str(),
+        &title,
+        parsed.site_url.as_deref(),
+        parsed.description.as_deref(),
+    )?;
+    feed_operations::insert_items(&conn, feed.id, &parsed.items)?;
+    feed_operations::record_fetch(&conn, feed.id, None)?;
+    println!("📰 [FEEDS] Subscribed to {} with {} items", title, parsed.items.len());
+
+    feed_operations::get_feed(&conn, feed.id)?.ok_or_else(|| LibreOllamaError::NotFound {
+        resource: format!("feed {}", feed.id),
+    })
+}
+
+async fn refresh_one(db_manager: &Arc<DatabaseManager>, feed: &FeedRecord) -> FeedRefresh {
+    let result = match parse_url(&feed.url) {
+        Ok(url) => fetch_feed(url).await,
+        Err(e) => Err(e),
+    };
+    let stored = db_manager.get_connection().map_err(LibreOllamaError::from).and_then(|conn| match &result {
+        Ok((_, parsed)) => {
+            let new_items = feed_operations::insert_items(&conn, feed.id, &parsed.items)?;
+            feed_operations::record_fetch(&conn, feed.id, parsed.title.as_deref())?;
+            Ok(new_items)
+        }
+        Err(e) => {
+            feed_operations::record_fetch_error(&conn, feed.id, &e.to_string())?;
+            Ok(0)
+        }
+    });
+
+    let error = match (&result, &stored) {
+        (Err(e), _) | (Ok(_), Err(e)) => Some(e.to_string()),
+        _ => None,
+    };
+    FeedRefresh {
+        feed_id: feed.id,
+        feed_title: result
+            .ok()
+            .and_then(|(_, parsed)| parsed.title)
+            .unwrap_or_else(|| feed.title.clone()),
+        new_items: stored.unwrap_or(0),
+        error,
+    }
+}
+
+/// Refresh one feed or all of them; a feed that fails does not stop the rest
+pub async fn refresh(db_manager: &Arc<DatabaseManager>, feed_id: Option<i64>) -> Result<Vec<FeedRefresh>> {
+    let feeds = {
+        let conn = db_manager.get_connection()?;
+        match feed_id {
+            Some(id) => vec![feed_operations::get_feed(&conn, id)?.ok_or_else(|| LibreOllamaError::NotFound {
+                resource: format!("feed {}", id),
+            })?],
+            None => feed_operations::list_feeds(&conn)?,
+        }
+    };
+
+    let mut refreshes = Vec::with_capacity(feeds.len());
+    for feed in &feeds {
+        let refresh = refresh_one(db_manager, feed).await;
+        if let Some(error) = &refresh.error {
+            eprintln!("⚠️ [FEEDS] Failed to refresh {}: {}", feed.url, error);
+        }
+        refreshes.push(refresh);
+    }
+    Ok(refreshes)
+}
+
+/// Tell the frontend which feeds have new items
+pub fn announce(event_bus: &EventBus, refreshes: &[FeedRefresh]) {
+    for refresh in refreshes.iter().filter(|refresh| refresh.new_items > 0) {
+        event_bus.emit(BackendEvent::FeedItemsAdded(FeedItemsAddedEvent {
+            feed_id: refresh.feed_id,
+            feed_title: refresh.feed_title.clone(),
+            new_count: refresh.new_items,
+        }));
+    }
+}
+
+fn digest_markdown(items: &[FeedItemRecord], summary: Option<&str>) -> String {
+    let mut markdown = format!("# Feed digest, {}\n\n", chrono::Local::now().format("%A %-d %B %Y"));
+    if items.is_empty() {
+        markdown.push_str("Nothing new in your feeds.\n");
+        return markdown;
+    }
+    if let Some(summary) = summary {
+        markdown.push_str(summary.trim());
+        markdown.push_str("\n\n");
+    }
+
+    let mut by_feed: BTreeMap<&str, Vec<&FeedItemRecord>> = BTreeMap::new();
+    for item in items {
+        by_feed.entry(item.feed_title.as_str()).or_default().push(item);
+    }
+    for (feed, items) in by_feed {
+        markdown.push_str(&format!("## {}\n\n", feed));
+        for item in items {
+            match &item.url {
+                Some(url) => markdown.push_str(&format!("- [{}]({})", item.title.replace(['[', ']'], ""), url)),
+                None => markdown.push_str(&format!("- {}", item.title)),
+            }
+            if let Some(summary) = &item.summary {
+                markdown.push_str(&format!(": {}", truncate_chars(summary, 160)));
+            }
+            markdown.push('\n');
+        }
+        markdown.push('\n');
+    }
+    markdown
+}
+
+/// Unread items from the last `hours`, optionally summarized by the model
+pub async fn digest(db_manager: &Arc<DatabaseManager>, hours: i64, summarize: bool) -> Result<FeedDigest> {
+    let since = (chrono::Utc::now() - chrono::Duration::hours(hours.max(1))).format(SQLITE_DATETIME).to_string();
+    let items = {
+        let conn = db_manager.get_connection()?;
+        feed_operations::list_items(
+            &conn,
+            &FeedItemFilter {
+                unread_only: true,
+                since: Some(since.clone()),
+                limit: Some(MAX_DIGEST_ITEMS),
+                ..FeedItemFilter::default()
+            },
+        )?
+    };
+
+    let (summary, model) = if summarize && !items.is_empty() {
+        let listing = items
+            .iter()
+            .map(|item| match &item.summary {
+                Some(summary) => format!("- [{}] {}: {}", item.feed_title, item.title, truncate_chars(summary, 300)),
+                None => format!("- [{}] {}", item.feed_title, item.title),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let prompt = {
+            let conn = db_manager.get_connection()?;
+            let mut values = Map::new();
+            values.insert("language".to_string(), Value::from(i18n::language_name()));
+            values.insert("items".to_string(), Value::from(listing));
+            prompt_templates::render_feature_prompt(&conn, DIGEST_PROMPT_FEATURE, &values)?
+        };
+        let messages = [ChatMessage::user(prompt)];
+        let completion = llm_provider::complete(db_manager, LlmFeature::Summarization, &messages).await?;
+        (Some(completion.content.trim().to_string()), Some(completion.model))
+    } else {
+        (None, None)
+    };
+
+    Ok(FeedDigest {
+        markdown: digest_markdown(&items, summary.as_deref()),
+        since,
+        items,
+        summary,
+        model,
+    })
+}
+
+/// Polls every subscribed feed on the interval from settings
+#[derive(Clone)]
+pub struct FeedPoller {
+    db_manager: Arc<DatabaseManager>,
+    app: AppHandle,
+}
+
+impl FeedPoller {
+    pub fn new(db_manager: Arc<DatabaseManager>, app: AppHandle) -> Self {
+        Self { db_manager, app }
+    }
+
+    /// A changed interval restarts the wait straight away
+    pub fn task(&self) -> TaskSpec {
+        let poller = self.clone();
+        let interval = self.clone();
+        TaskSpec::new("feeds", move || {
+            let poller = poller.clone();
+            async move { Ok(poller.poll_once().await?) }
+        })
+        .every(move || interval.poll_interval())
+        .first_run_after(self.poll_interval())
+        .wake_on(&[settings::FEED_POLL_INTERVAL_SETTING])
+        .jitter(0.1)
+    }
+
+    fn poll_interval(&self) -> Duration {
+        let minutes = self
+            .db_manager
+            .get_connection()
+            .and_then(|conn| settings::get_integer(&conn, settings::FEED_POLL_INTERVAL_SETTING))
+            .unwrap_or(60);
+        Duration::from_secs(minutes.max(1) as u64 * 60)
+    }
+
+    async fn poll_once(&self) -> Result<()> {
+        let refreshes = refresh(&self.db_manager, None).await?;
+        if let Some(event_bus) = self.app.try_state::<EventBus>() {
+            announce(&event_bus, &refreshes);
+        }
+        let conn = self.db_manager.get_connection()?;
+        feed_operations::prune_items(&conn, KEEP_READ_DAYS)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RSS: &str = r#"<?xml version="1.0"?>
+        <rss version="2.0"><channel>
+          <title>Example &amp; Co</title>
+          <link>https://example.com/</link>
+          <description>News from Example</description>
+          <item>
+            <title>First post</title>
+            <link>https://example.com/first</link>
+            <guid>https://example.com/?p=1</guid>
+            <description><![CDATA[<p>Hello <b>world</b></p>]]></description>
+            <pubDate>Mon, 12 Oct 2026 08:30:00 GMT</pubDate>
+          </item>
+          <item>
+            <link>https://example.com/second</link>
+            <description>No title here</description>
+          </item>
+        </channel></rss>"#;
+
+    const ATOM: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+        <feed xmlns="http://www.w3.org/2005/Atom">
+          <title>Atom Blog</title>
+          <link rel="self" href="https://blog.example.org/feed.xml"/>
+          <link href="https://blog.example.org/"/>
+          <id>urn:uuid:60a76c80</id>
+          <updated>2026-10-14T18:30:02Z</updated>
+          <entry>
+            <title>Atom entry</title>
+            <link href="https://blog.example.org/entry"/>
+            <id>urn:uuid:1225c695</id>
+            <updated>2026-10-14T18:30:02Z</updated>
+            <author><name>Ada</name></author>
+            <summary>Some text.</summary>
+          </entry>
+        </feed>"#;
+
+    #[test]
+    fn test_parse_rss() {
+        let feed = parse_feed(RSS.as_bytes()).unwrap();
+        assert_eq!(feed.title.as_deref(), Some("Example & Co"));
+        assert_eq!(feed.description.as_deref(), Some("News from Example"));
+        assert_eq!(feed.items.len(), 2);
+
+        let first = &feed.items[0];
+        assert_eq!(first.guid, "https://example.com/?p=1");
+        assert_eq!(first.url.as_deref(), Some("https://example.com/first"));
+        assert_eq!(first.summary.as_deref(), Some("Hello world"));
+        assert_eq!(first.published_at.as_deref(), Some("2026-10-12 08:30:00"));
+
+        assert_eq!(feed.items[1].title, "No title here");
+    }
+
+    #[test]
+    fn test_parse_atom() {
+        let feed = parse_feed(ATOM.as_bytes()).unwrap();
+        assert_eq!(feed.title.as_deref(), Some("Atom Blog"));
+        assert_eq!(feed.site_url.as_deref(), Some("https://blog.example.org/"));
+        let entry = &feed.items[0];
+        assert_eq!(entry.guid, "urn:uuid:1225c695");
+        assert_eq!(entry.author.as_deref(), Some("Ada"));
+        assert_eq!(entry.published_at.as_deref(), Some("2026-10-14 18:30:02"));
+
+        assert!(parse_feed(b"<html><body>not a feed</body></html>").is_err());
+    }
+
+    #[test]
+    fn test_discover_feed_url() {
+        let base = Url::parse("https://example.com/blog/").unwrap();
+        let html = r#"<html><head>
+            <link rel="stylesheet" href="/style.css">
+            <link rel="alternate" type="application/rss+xml" title="RSS" href="feed.xml">
+            </head><body></body></html>"#;
+        assert_eq!(discover_feed_url(html, &base).unwrap().as_str(), "https://example.com/blog/feed.xml");
+        assert!(discover_feed_url("<html><head></head></html>", &base).is_none());
+    }
+}
@@ -0,0 +1,73 @@
+//! Which local files commands may read or write
+//!
+//! Paths arrive from the WebView, so commands that open or write a file
+//! the frontend names only accept two kinds of path: ones the user picked
+//! in a file dialog, which the dialog adds to the file system scope, and
+//! ones inside the app's own data directory. Anything else is refused.
+
+use std::path::{Component, Path, PathBuf};
+
+use tauri::{AppHandle, Manager};
+use tauri_plugin_fs::FsExt;
+
+use crate::errors::{LibreOllamaError, Result};
+
+/// `path`, resolved, if the user picked it or it lies in the app's data
+/// directory. A file that does not exist yet is judged by its folder.
+pub fn permitted_path(app: &AppHandle, path: &str) -> Result<PathBuf> {
+    let resolved = resolve(Path::new(path))?;
+    let picked = app.try_fs_scope().is_some_and(|scope| scope.is_allowed(&resolved));
+    if picked || app_dirs(app).iter().any(|dir| resolved.starts_with(dir)) {
+        Ok(resolved)
+    } else {
+        Err(denied(path))
+    }
+}
+
+/// The app's data directories, resolved
+fn app_dirs(app: &AppHandle) -> Vec<PathBuf> {
+    let mut dirs = vec![crate::config::get_default_data_dir()];
+    dirs.extend(app.path().app_data_dir().ok());
+    dirs.into_iter().filter_map(|dir| resolve(&dir).ok()).collect()
+}
+
+/// An absolute path with symlinks and `..` resolved, as far as it exists
+fn resolve(path: &Path) -> Result<PathBuf> {
+    if !path.is_absolute() {
+        return Err(denied(&path.to_string_lossy()));
+    }
+    if let Ok(path) = path.canonicalize() {
+        return Ok(path);
+    }
+    // Not created yet: resolve the folder and keep the plain file name
+    let name = match path.components().next_back() {
+        Some(Component::Normal(name)) => name,
+        _ => return Err(denied(&path.to_string_lossy())),
+    };
+    let parent = path.parent().ok_or_else(|| denied(&path.to_string_lossy()))?;
+    Ok(resolve(parent)?.join(name))
+}
+
+fn denied(path: &str) -> LibreOllamaError {
+    LibreOllamaError::PermissionDenied {
+        message: format!("{} was not chosen in a file dialog", path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_resolve_through_missing_files() {
+        let dir = std::env::temp_dir().join(format!("libreollama_file_access_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("inner")).unwrap();
+        let dir = dir.canonicalize().unwrap();
+
+        assert_eq!(resolve(&dir.join("inner/../out.png")).unwrap(), dir.join("out.png"));
+        assert_eq!(resolve(&dir.join("missing/out.png")).unwrap(), dir.join("missing/out.png"));
+        assert!(resolve(Path::new("relative/out.png")).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
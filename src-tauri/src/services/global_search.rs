@@ -0,0 +1,162 @@
+//! Global search
+//!
+//! Fans one query out to every searchable domain (mail, notes, tasks,
+//! calendar events, chat history), merges the hits into a single ranking
+//! and pages through it. Each domain is asked for enough hits to fill the
+//! requested page, so deep pages cost more; `MAX_RESULT_WINDOW` caps that.
+//! Hits from accounts, lists, calendars and folders the active workspace
+//! hides are left out.
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::database::operations::search_operations::{self, SearchDomain, SearchHit};
+use crate::services::workspaces;
+
+pub const DEFAULT_PAGE_SIZE: usize = 20;
+pub const MAX_PAGE_SIZE: usize = 100;
+/// Deepest result (offset + limit) a search can page to
+pub const MAX_RESULT_WINDOW: usize = 500;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GlobalSearchRequest {
+    pub query: String,
+    /// Domains to search; all of them when unset
+    pub domains: Option<Vec<SearchDomain>>,
+    /// Limit mail, tasks and events to one account
+    pub account_id: Option<String>,
+    #[serde(default)]
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GlobalSearchPage {
+    pub hits: Vec<SearchHit>,
+    pub offset: usize,
+    pub limit: usize,
+    pub has_more: bool,
+}
+
+/// Best match first; equal scores fall back to the newer item
+fn compare_hits(a: &SearchHit, b: &SearchHit) -> std::cmp::Ordering {
+    a.rank
+        .total_cmp(&b.rank)
+        .then_with(|| b.timestamp.cmp(&a.timestamp))
+}
+
+pub fn global_search(conn: &Connection, request: &GlobalSearchRequest) -> anyhow::Result<GlobalSearchPage> {
+    let limit = request.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    if request.offset + limit > MAX_RESULT_WINDOW {
+        anyhow::bail!("Search results can only be paged to the first {}", MAX_RESULT_WINDOW);
+    }
+
+    let fts_query = search_operations::fts_prefix_query(&request.query);
+    if fts_query.is_empty() {
+        return Ok(GlobalSearchPage { hits: Vec::new(), offset: request.offset, limit, has_more: false });
+    }
+
+    let scope = workspaces::scope(conn)?;
+    let domains = request.domains.as_deref().unwrap_or(&SearchDomain::ALL);
+    // One extra hit per domain tells whether another page exists
+    let window = request.offset + limit + 1;
+    let mut hits = Vec::new();
+    for domain in domains {
+        let hidden = scope.hidden_in(*domain);
+        hits.extend(search_operations::search_domain(conn, *domain, &fts_query, request.account_id.as_deref(), &hidden, window)?);
+    }
+    hits.sort_by(compare_hits);
+
+    let has_more = hits.len() > request.offset + limit;
+    let hits = hits.into_iter().skip(request.offset).take(limit).collect();
+    Ok(GlobalSearchPage { hits, offset: request.offset, limit, has_more })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::operations::search_operations::{replace_task_items, NewSearchItem};
+    use crate::database::operations::workspace_operations::{self, WorkspaceKind, WorkspaceMember};
+    use crate::database::schema::run_migrations;
+
+    #[test]
+    fn test_merged_results_page() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let ids: Vec<String> = (0..5).map(|i| format!("task-{}", i)).collect();
+        let items: Vec<NewSearchItem> = ids
+            .iter()
+            .map(|id| NewSearchItem { item_id: id, container_id: "list", title: "Plan offsite", body: "", item_time: None, status: None, completed_at: None, all_day: false })
+            .collect();
+        replace_task_items(&mut conn, "acc", &items).unwrap();
+        conn.execute(
+            "INSERT INTO notes (title, content, user_id, created_at, updated_at)
+             VALUES ('Offsite', 'venues', 'default_user', '2024-06-01 10:00:00', '2024-06-01 10:00:00')",
+            [],
+        ).unwrap();
+
+        let mut request = GlobalSearchRequest {
+            query: "offs".to_string(),
+            domains: None,
+            account_id: None,
+            offset: 0,
+            limit: Some(4),
+        };
+        let first = global_search(&conn, &request).unwrap();
+        assert_eq!(first.hits.len(), 4);
+        assert!(first.has_more);
+
+        request.offset = 4;
+        let second = global_search(&conn, &request).unwrap();
+        assert_eq!(second.hits.len(), 2);
+        assert!(!second.has_more);
+
+        request.domains = Some(vec![SearchDomain::Note]);
+        request.offset = 0;
+        let notes = global_search(&conn, &request).unwrap();
+        assert_eq!(notes.hits.len(), 1);
+        assert_eq!(notes.hits[0].domain, SearchDomain::Note);
+
+        request.offset = MAX_RESULT_WINDOW;
+        assert!(global_search(&conn, &request).is_err());
+    }
+
+    #[test]
+    fn test_hidden_hits_do_not_shorten_pages() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        for (account, list) in [("work", "work-list"), ("home", "home-list")] {
+            let ids: Vec<String> = (0..5).map(|i| format!("{}-{}", account, i)).collect();
+            let items: Vec<NewSearchItem> = ids
+                .iter()
+                .map(|id| NewSearchItem { item_id: id, container_id: list, title: "Plan offsite", body: "", item_time: None, status: None, completed_at: None, all_day: false })
+                .collect();
+            replace_task_items(&mut conn, account, &items).unwrap();
+        }
+        let home = workspace_operations::create_workspace(&conn, "Home", None).unwrap();
+        let work = workspace_operations::create_workspace(&conn, "Work", None).unwrap();
+        workspace_operations::set_members(&mut conn, work.id, &[WorkspaceMember {
+            kind: WorkspaceKind::Account,
+            member_id: "work".to_string(),
+        }])
+        .unwrap();
+        workspaces::switch(&conn, Some(home.id)).unwrap();
+
+        let request = GlobalSearchRequest {
+            query: "offs".to_string(),
+            domains: Some(vec![SearchDomain::Task]),
+            account_id: None,
+            offset: 0,
+            limit: Some(4),
+        };
+        let page = global_search(&conn, &request).unwrap();
+        assert_eq!(page.hits.len(), 4);
+        assert!(page.has_more);
+        assert!(page.hits.iter().all(|hit| hit.account_id.as_deref() == Some("home")));
+
+        let last = global_search(&conn, &GlobalSearchRequest { offset: 4, ..request }).unwrap();
+        assert_eq!(last.hits.len(), 1);
+        assert!(!last.has_more);
+    }
+}
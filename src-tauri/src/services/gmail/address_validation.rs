@@ -0,0 +1,339 @@
+//! Recipient checks before sending
+//!
+//! Every To/Cc/Bcc address is checked for RFC 5322 syntax, for a domain
+//! that can receive mail (MX records, or an address record as the implicit
+//! MX), for throwaway mail services, and for likely misspellings of common
+//! providers. Nothing here blocks a send: the compose UI shows the warnings
+//! and lets the user decide. Each distinct domain is looked up once, all
+//! of them at the same time through one shared resolver, and the answers
+//! are cached briefly so re-checking while the user edits does not hit the
+//! resolver each time.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use hickory_resolver::error::ResolveErrorKind;
+use hickory_resolver::proto::op::ResponseCode;
+use hickory_resolver::TokioAsyncResolver;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::services::gmail::api_service::EmailAddress;
+use crate::services::gmail::compose_service::ComposeRequest;
+use crate::services::i18n;
+
+/// How long a domain's mail-server answer is reused
+const MX_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+/// Per-query resolver timeout; a slow resolver skips the check
+const DNS_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Throwaway mail services; subdomains match too
+const DISPOSABLE_DOMAINS: &[&str] = &[
+    "10minutemail.com", "20minutemail.com", "discard.email", "dispostable.com", "emailondeck.com",
+    "fakeinbox.com", "getairmail.com", "getnada.com", "guerrillamail.com", "guerrillamail.net",
+    "guerrillamailblock.com", "maildrop.cc", "mailinator.com", "mailnesia.com", "mintemail.com",
+    "mohmal.com", "mytemp.email", "sharklasers.com", "spamgourmet.com", "temp-mail.org",
+    "tempmail.com", "tempmail.net", "tempr.email", "throwawaymail.com", "trashmail.com",
+    "trashmail.de", "yopmail.com", "yopmail.fr",
+];
+
+/// Providers common enough that a near miss is almost certainly a typo
+const COMMON_DOMAINS: &[&str] = &[
+    "aol.com", "comcast.net", "fastmail.com", "gmail.com", "gmx.com", "gmx.de", "googlemail.com",
+    "hotmail.co.uk", "hotmail.com", "icloud.com", "live.com", "mail.com", "me.com", "msn.com",
+    "outlook.com", "proton.me", "protonmail.com", "web.de", "yahoo.co.uk", "yahoo.com", "zoho.com",
+];
+
+lazy_static::lazy_static! {
+    static ref LOCAL_PART: Regex = Regex::new(
+        r#"^(?:[A-Za-z0-9!#$%&'*+/=?^_`{|}~-]+(?:\.[A-Za-z0-9!#$%&'*+/=?^_`{|}~-]+)*|"(?:[^"\\\r\n]|\\.)*")$"#
+    ).unwrap();
+    static ref DOMAIN_LABEL: Regex = Regex::new(r"^[A-Za-z0-9](?:[A-Za-z0-9-]{0,61}[A-Za-z0-9])?$").unwrap();
+    static ref MX_CACHE: Mutex<HashMap<String, (Instant, MailServerStatus)>> = Mutex::new(HashMap::new());
+}
+
+/// Built on first use from the system DNS configuration; None when that
+/// cannot be read
+static RESOLVER: OnceLock<Option<TokioAsyncResolver>> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecipientField {
+    To,
+    Cc,
+    Bcc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecipientWarningKind {
+    InvalidSyntax,
+    NoMailServer,
+    DisposableDomain,
+    PossibleTypo,
+}
+
+/// One problem with one recipient
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecipientWarning {
+    pub field: RecipientField,
+    pub email: String,
+    pub kind: RecipientWarningKind,
+    pub message: String,
+    /// Corrected address for `PossibleTypo`
+    pub suggestion: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MailServerStatus {
+    Accepts,
+    NoMailServer,
+    /// The lookup failed or timed out; nothing is reported
+    Unknown,
+}
+
+/// Check every recipient of a message; an empty list means nothing to flag
+pub async fn validate_recipients(compose: &ComposeRequest) -> Vec<RecipientWarning> {
+    let recipients: Vec<(RecipientField, &EmailAddress)> = compose
+        .to
+        .iter()
+        .map(|addr| (RecipientField::To, addr))
+        .chain(compose.cc.iter().flatten().map(|addr| (RecipientField::Cc, addr)))
+        .chain(compose.bcc.iter().flatten().map(|addr| (RecipientField::Bcc, addr)))
+        .collect();
+
+    // Address literals have no DNS to check
+    let domains: BTreeSet<String> = recipients
+        .iter()
+        .filter_map(|(_, addr)| check_syntax(addr.email.trim()))
+        .map(str::to_ascii_lowercase)
+        .filter(|domain| !domain.starts_with('['))
+        .collect();
+    let statuses: HashMap<String, MailServerStatus> =
+        futures::future::join_all(domains.into_iter().map(|domain| async move {
+            let status = mail_server_status(&domain).await;
+            (domain, status)
+        }))
+        .await
+        .into_iter()
+        .collect();
+
+    let mut warnings = Vec::new();
+    for (field, addr) in recipients {
+        let email = addr.email.trim();
+        let warning = |kind, message: String, suggestion| RecipientWarning {
+            field,
+            email: email.to_string(),
+            kind,
+            message,
+            suggestion,
+        };
+
+        let Some(domain) = check_syntax(email) else {
+            warnings.push(warning(
+                RecipientWarningKind::InvalidSyntax,
+                i18n::t_args("recipient-invalid", &[("email", email.into())]),
+                None,
+            ));
+            continue;
+        };
+        let domain = domain.to_ascii_lowercase();
+
+        if let Some(suggested) = suggest_domain(&domain) {
+            let suggestion = format!("{}@{}", &email[..email.rfind('@').unwrap_or(0)], suggested);
+            warnings.push(warning(
+                RecipientWarningKind::PossibleTypo,
+                i18n::t_args("recipient-typo", &[("email", email.into()), ("suggestion", suggestion.clone().into())]),
+                Some(suggestion),
+            ));
+        }
+
+        if is_disposable(&domain) {
+            warnings.push(warning(
+                RecipientWarningKind::DisposableDomain,
+                i18n::t_args("recipient-disposable", &[("domain", domain.clone().into())]),
+                None,
+            ));
+        }
+
+        if statuses.get(&domain) == Some(&MailServerStatus::NoMailServer) {
+            warnings.push(warning(
+                RecipientWarningKind::NoMailServer,
+                i18n::t_args("recipient-no-mail-server", &[("domain", domain.clone().into())]),
+                None,
+            ));
+        }
+    }
+
+    warnings
+}
+
+/// The domain of a syntactically valid addr-spec, or None
+pub fn check_syntax(email: &str) -> Option<&str> {
+    if email.len() > 254 {
+        return None;
+    }
+    let (local, domain) = email.rsplit_once('@')?;
+    if local.is_empty() || local.len() > 64 || !LOCAL_PART.is_match(local) {
+        return None;
+    }
+
+    if let Some(literal) = domain.strip_prefix('[').and_then(|d| d.strip_suffix(']')) {
+        let ip = literal.strip_prefix("IPv6:").unwrap_or(literal);
+        return ip.parse::<std::net::IpAddr>().ok().map(|_| domain);
+    }
+
+    let labels: Vec<&str> = domain.split('.').collect();
+    let tld = labels.last()?;
+    let valid = domain.len() <= 253
+        && labels.len() >= 2
+        && labels.iter().all(|label| DOMAIN_LABEL.is_match(label))
+        && !tld.chars().all(|c| c.is_ascii_digit());
+    valid.then_some(domain)
+}
+
+pub fn is_disposable(domain: &str) -> bool {
+    DISPOSABLE_DOMAINS
+        .iter()
+        .any(|d| domain == *d || domain.strip_suffix(d).is_some_and(|rest| rest.ends_with('.')))
+}
+
+/// A common provider `domain` is probably a misspelling of
+pub fn suggest_domain(domain: &str) -> Option<&'static str> {
+    if COMMON_DOMAINS.contains(&domain) {
+        return None;
+    }
+    // Short domains are too easy to confuse with real ones at distance 2
+    let allowed = if domain.len() <= 7 { 1 } else { 2 };
+    COMMON_DOMAINS
+        .iter()
+        .map(|candidate| (edit_distance(domain, candidate), *candidate))
+        .filter(|(distance, _)| *distance > 0 && *distance <= allowed)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Optimal string alignment distance: edits plus adjacent transpositions
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Whether a domain can receive mail, from the cache when it is fresh
+pub async fn mail_server_status(domain: &str) -> MailServerStatus {
+    if let Some((checked_at, status)) = MX_CACHE.lock().unwrap_or_else(|p| p.into_inner()).get(domain) {
+        if checked_at.elapsed() < MX_CACHE_TTL {
+            return *status;
+        }
+    }
+
+    let status = lookup_mail_server(domain).await;
+    // Failed lookups are retried next time rather than cached
+    if status != MailServerStatus::Unknown {
+        MX_CACHE
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .insert(domain.to_string(), (Instant::now(), status));
+    }
+    status
+}
+
+fn resolver() -> Option<&'static TokioAsyncResolver> {
+    RESOLVER
+        .get_or_init(|| match hickory_resolver::system_conf::read_system_conf() {
+            Ok((config, mut options)) => {
+                options.timeout = DNS_TIMEOUT;
+                options.attempts = 1;
+                Some(TokioAsyncResolver::tokio(config, options))
+            }
+            Err(e) => {
+                eprintln!("⚠️ [MAIL] Cannot read the DNS configuration: {}", e);
+                None
+            }
+        })
+        .as_ref()
+}
+
+async fn lookup_mail_server(domain: &str) -> MailServerStatus {
+    let Some(resolver) = resolver() else {
+        return MailServerStatus::Unknown;
+    };
+    // Fully qualified so the search domains are not appended
+    let name = format!("{}.", domain.trim_end_matches('.'));
+
+    match resolver.mx_lookup(name.as_str()).await {
+        Ok(lookup) => {
+            // A lone "." exchange is a null MX (RFC 7505): no mail accepted
+            let null_mx = lookup.iter().all(|mx| mx.exchange().is_root());
+            if null_mx {
+                MailServerStatus::NoMailServer
+            } else {
+                MailServerStatus::Accepts
+            }
+        }
+        Err(e) => match e.kind() {
+            ResolveErrorKind::NoRecordsFound { response_code: ResponseCode::NXDomain, .. } => {
+                MailServerStatus::NoMailServer
+            }
+            // No MX: an address record makes the host its own mail server
+            ResolveErrorKind::NoRecordsFound { .. } => match resolver.lookup_ip(name.as_str()).await {
+                Ok(_) => MailServerStatus::Accepts,
+                Err(e) if matches!(e.kind(), ResolveErrorKind::NoRecordsFound { .. }) => MailServerStatus::NoMailServer,
+                Err(_) => MailServerStatus::Unknown,
+            },
+            _ => MailServerStatus::Unknown,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_address_syntax() {
+        assert_eq!(check_syntax("jane.doe+news@example.co.uk"), Some("example.co.uk"));
+        assert_eq!(check_syntax("\"jane doe\"@example.com"), Some("example.com"));
+        assert_eq!(check_syntax("jane@[192.0.2.1]"), Some("[192.0.2.1]"));
+        assert_eq!(check_syntax("jane..doe@example.com"), None);
+        assert_eq!(check_syntax("jane@example"), None);
+        assert_eq!(check_syntax("jane@-example.com"), None);
+        assert_eq!(check_syntax("@example.com"), None);
+        assert_eq!(check_syntax("jane example.com"), None);
+    }
+
+    #[test]
+    fn suggests_common_providers_for_near_misses() {
+        assert_eq!(suggest_domain("gamil.com"), Some("gmail.com"));
+        assert_eq!(suggest_domain("gmail.con"), Some("gmail.com"));
+        assert_eq!(suggest_domain("hotmial.com"), Some("hotmail.com"));
+        assert_eq!(suggest_domain("gmail.com"), None);
+        assert_eq!(suggest_domain("example.org"), None);
+    }
+
+    #[test]
+    fn flags_disposable_domains_and_their_subdomains() {
+        assert!(is_disposable("mailinator.com"));
+        assert!(is_disposable("eu.mailinator.com"));
+        assert!(!is_disposable("notmailinator.com"));
+    }
+}
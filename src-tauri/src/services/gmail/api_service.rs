@@ -6,18 +6,35 @@
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use base64::{Engine as _, engine::general_purpose};
 use std::sync::Arc;
 use uuid::Uuid;
 use chrono;
 
+use crate::database::operations::label_cache_operations::{self, CachedLabel, LabelCounts, LabelOverride};
+use crate::database::operations::send_as_operations::{self, SendAsAlias};
 use crate::database::DatabaseManager;
 use crate::errors::{LibreOllamaError, Result};
 use crate::services::gmail::auth_service::GmailAuthService;
-use crate::commands::rate_limiter::{RateLimiter, BatchRequest, RequestPriority};
+use crate::services::gmail::attachment_risk::{self, AttachmentRisk};
+use crate::services::gmail::{inline_images, mime};
+use crate::services::google::batch::{build_batch_body, parse_batch_response, BatchPart};
+use crate::services::google::GoogleEndpoints;
+use crate::services::resource_budget;
+use crate::commands::rate_limiter::{self, RateLimiter, BatchRequest, RequestPriority};
+use crate::utils::http_client::http_client;
+use regex::Regex;
 
 /// Gmail API endpoints
-const GMAIL_API_BASE: &str = "https://www.googleapis.com/gmail/v1";
+/// Most requests Gmail accepts in one batch call
+const GMAIL_BATCH_LIMIT: usize = 50;
+/// Most message IDs batchModify takes in one call
+pub const BATCH_MODIFY_LIMIT: usize = 1000;
+/// How long cached labels are served before they are refetched
+const LABEL_CACHE_TTL_SECONDS: i64 = 10 * 60;
+/// Attachments Gmail left out of the payload are downloaded for sniffing
+/// up to this size, and only this many per message
+const SNIFF_MAX_BYTES: usize = 512 * 1024;
+const SNIFF_MAX_ATTACHMENTS: usize = 5;
 
 /// Gmail API message structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -82,6 +99,21 @@ pub struct GmailLabel {
     pub threads_total: Option<i64>,
     #[serde(rename = "threadsUnread")]
     pub threads_unread: Option<i64>,
+    pub color: Option<GmailLabelColor>,
+    /// Name set in the app, shown instead of `name`; never sent to Gmail
+    #[serde(rename = "displayName", default)]
+    pub display_name: Option<String>,
+    /// Colours set in the app, shown instead of `color`
+    #[serde(rename = "displayColor", default)]
+    pub display_color: Option<GmailLabelColor>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GmailLabelColor {
+    #[serde(rename = "textColor")]
+    pub text_color: Option<String>,
+    #[serde(rename = "backgroundColor")]
+    pub background_color: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -108,6 +140,44 @@ pub struct MessageRef {
     pub thread_id: String,
 }
 
+/// One page of `users.history.list`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryListResponse {
+    pub history: Option<Vec<HistoryRecord>>,
+    #[serde(rename = "nextPageToken")]
+    pub next_page_token: Option<String>,
+    #[serde(rename = "historyId")]
+    pub history_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryRecord {
+    pub id: String,
+    #[serde(rename = "messagesAdded", default)]
+    pub messages_added: Vec<HistoryMessageChange>,
+    #[serde(rename = "messagesDeleted", default)]
+    pub messages_deleted: Vec<HistoryMessageChange>,
+    #[serde(rename = "labelsAdded", default)]
+    pub labels_added: Vec<HistoryMessageChange>,
+    #[serde(rename = "labelsRemoved", default)]
+    pub labels_removed: Vec<HistoryMessageChange>,
+}
+
+/// A message touched by a history record, with its labels after the change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryMessageChange {
+    pub message: HistoryMessage,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryMessage {
+    pub id: String,
+    #[serde(rename = "threadId")]
+    pub thread_id: String,
+    #[serde(rename = "labelIds")]
+    pub label_ids: Option<Vec<String>>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LabelListResponse {
     pub labels: Vec<GmailLabel>,
@@ -149,6 +219,9 @@ pub struct EmailAttachment {
     pub content_id: Option<String>,
     pub is_inline: bool,
     pub data: Option<Vec<u8>>,
+    /// Warnings from the name, declared type and sniffed content
+    #[serde(default)]
+    pub risk: Option<AttachmentRisk>,
 }
 
 /// Processed Gmail message with parsed content
@@ -186,7 +259,8 @@ pub struct GmailApiService {
     client: Client,
     auth_service: Arc<GmailAuthService>,
     db_manager: std::sync::Arc<DatabaseManager>,
-    rate_limiter: Arc<tokio::sync::Mutex<RateLimiter>>,
+    rate_limiter: Arc<RateLimiter>,
+    endpoints: GoogleEndpoints,
 }
 
 impl GmailApiService {
@@ -194,16 +268,23 @@ impl GmailApiService {
     pub fn new(
         auth_service: Arc<GmailAuthService>,
         db_manager: std::sync::Arc<DatabaseManager>,
-        rate_limiter: Arc<tokio::sync::Mutex<RateLimiter>>,
+        rate_limiter: Arc<RateLimiter>,
     ) -> Self {
         Self {
-            client: Client::new(),
+            client: http_client(),
             auth_service,
             db_manager,
             rate_limiter,
+            endpoints: GoogleEndpoints::default(),
         }
     }
 
+    /// Send requests to `endpoints` instead of Google's production hosts
+    pub fn with_endpoints(mut self, endpoints: GoogleEndpoints) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
     /// Make authenticated API request to Gmail
     async fn make_api_request<T>(&self, account_id: &str, endpoint: &str) -> Result<T>
     where
@@ -214,11 +295,12 @@ impl GmailApiService {
             .validate_and_refresh_tokens(&self.db_manager, account_id)
             .await?;
 
-        let url = format!("{}/{}", GMAIL_API_BASE, endpoint.trim_start_matches('/'));
+        let url = GoogleEndpoints::url(&self.endpoints.gmail_api, endpoint);
         
         // Create rate-limited request
         let batch_request = BatchRequest {
             id: format!("gmail_api_{}", Uuid::new_v4()),
+            account_id: Some(account_id.to_string()),
             method: "GET".to_string(),
             url: url.clone(),
             headers: {
@@ -235,14 +317,8 @@ impl GmailApiService {
         };
 
         // Execute request through rate limiter
-        let response = {
-            let mut rate_limiter = self.rate_limiter.lock().await;
-            rate_limiter.execute_request(batch_request).await
-                .map_err(|e| LibreOllamaError::Network {
-                    message: format!("Rate limited Gmail API request failed: {}", e),
-                    url: Some(url.clone()),
-                })?
-        };
+        let response = rate_limiter::send_scheduled(&self.rate_limiter, batch_request).await
+            .map_err(|e| rate_limiter::request_error(e, "Rate limited Gmail API request failed", &url))?;
 
         // Parse response
         if response.status_code != 200 {
@@ -274,10 +350,11 @@ impl GmailApiService {
             .validate_and_refresh_tokens(&self.db_manager, account_id)
             .await?;
 
-        let url = format!("{}/{}", GMAIL_API_BASE, endpoint.trim_start_matches('/'));
+        let url = GoogleEndpoints::url(&self.endpoints.gmail_api, endpoint);
 
         let batch_request = BatchRequest {
             id: format!("gmail_api_post_{}", Uuid::new_v4()),
+            account_id: Some(account_id.to_string()),
             method: "POST".to_string(),
             url: url.clone(),
             headers: {
@@ -293,14 +370,8 @@ impl GmailApiService {
             current_retry: 0,
         };
 
-        let response = {
-            let mut rate_limiter = self.rate_limiter.lock().await;
-            rate_limiter.execute_request(batch_request).await
-                .map_err(|e| LibreOllamaError::Network {
-                    message: format!("Rate limited Gmail POST request failed: {}", e),
-                    url: Some(url.clone()),
-                })?
-        };
+        let response = rate_limiter::send_scheduled(&self.rate_limiter, batch_request).await
+            .map_err(|e| rate_limiter::request_error(e, "Rate limited Gmail POST request failed", &url))?;
 
         if response.status_code < 200 || response.status_code >= 300 {
              return Err(LibreOllamaError::GmailApi {
@@ -322,8 +393,118 @@ impl GmailApiService {
             })
     }
 
-    /// Get all labels for an account
+    /// Send several GET requests in one Gmail batch call.
+    ///
+    /// Results line up with `endpoints`; an item that failed inside the
+    /// batch is None. Requests are split into batches of
+    /// [`GMAIL_BATCH_LIMIT`].
+    async fn make_batch_get_request(&self, account_id: &str, endpoints: &[String]) -> Result<Vec<Option<serde_json::Value>>> {
+        let mut results = vec![None; endpoints.len()];
+
+        for (chunk_index, chunk) in endpoints.chunks(GMAIL_BATCH_LIMIT).enumerate() {
+            let tokens = self.auth_service
+                .validate_and_refresh_tokens(&self.db_manager, account_id)
+                .await?;
+
+            let boundary = format!("batch_{}", Uuid::new_v4().simple());
+            let batch_request = BatchRequest {
+                id: format!("gmail_batch_{}", Uuid::new_v4()),
+                account_id: Some(account_id.to_string()),
+                method: "POST".to_string(),
+                url: self.endpoints.gmail_batch.clone(),
+                headers: {
+                    let mut headers = std::collections::HashMap::new();
+                    headers.insert("Authorization".to_string(), format!("Bearer {}", tokens.access_token));
+                    headers.insert("Content-Type".to_string(), format!("multipart/mixed; boundary={}", boundary));
+                    headers
+                },
+                body: Some(build_batch_body(&boundary, &chunk
+                    .iter()
+                    .map(|endpoint| BatchPart::get(format!("/gmail/v1/{}", endpoint.trim_start_matches('/'))))
+                    .collect::<Vec<_>>())),
+                priority: RequestPriority::Medium,
+                created_at: chrono::Utc::now().to_rfc3339(),
+                max_retries: 3,
+                current_retry: 0,
+            };
+
+            let response = rate_limiter::send_scheduled(&self.rate_limiter, batch_request).await
+                .map_err(|e| rate_limiter::request_error(e, "Rate limited Gmail batch request failed", &self.endpoints.gmail_batch))?;
+
+            if let Some(error) = response.error {
+                return Err(LibreOllamaError::Network {
+                    message: format!("Gmail batch request failed: {}", error),
+                    url: Some(self.endpoints.gmail_batch.clone()),
+                });
+            }
+            if response.status_code != 200 {
+                return Err(LibreOllamaError::GmailApi {
+                    message: format!("Gmail batch error: {} - {}", response.status_code, response.body),
+                    status_code: Some(response.status_code),
+                });
+            }
+
+            let content_type = response.headers.get("content-type").map(String::as_str).unwrap_or("");
+            for item in parse_batch_response(content_type, &response.body) {
+                if item.index >= chunk.len() {
+                    continue;
+                }
+                if item.status_code != 200 {
+                    eprintln!("⚠️ [GMAIL-API] Batch item {} failed: {}", chunk[item.index], item.status_code);
+                    continue;
+                }
+                results[chunk_index * GMAIL_BATCH_LIMIT + item.index] = serde_json::from_str(&item.body).ok();
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Get all labels for an account.
+    ///
+    /// Served from the local label cache while its counts are younger than
+    /// [`LABEL_CACHE_TTL_SECONDS`]; otherwise the labels are refetched. If
+    /// Gmail cannot be reached, stale cached labels are returned instead.
     pub async fn get_labels(&self, account_id: &str) -> Result<Vec<GmailLabel>> {
+        let (fresh, cached) = {
+            let conn = self.db_manager.get_connection()?;
+            let oldest = label_cache_operations::oldest_refresh(&conn, account_id)?;
+            let fresh = oldest.is_some_and(|at| chrono::Utc::now().timestamp() - at < LABEL_CACHE_TTL_SECONDS);
+            (fresh, self.cached_labels_from(&conn, account_id)?)
+        };
+        if fresh {
+            return Ok(cached);
+        }
+
+        match self.refresh_labels(account_id).await {
+            Err(LibreOllamaError::Network { message, .. }) if !cached.is_empty() => {
+                eprintln!("📴 [GMAIL-API] Serving cached labels for {}, Gmail unreachable: {}", account_id, message);
+                Ok(cached)
+            }
+            result => result,
+        }
+    }
+
+    /// Cached labels with local overrides applied, without any request
+    pub fn cached_labels(&self, account_id: &str) -> Result<Vec<GmailLabel>> {
+        let conn = self.db_manager.get_connection()?;
+        self.cached_labels_from(&conn, account_id)
+    }
+
+    fn cached_labels_from(&self, conn: &rusqlite::Connection, account_id: &str) -> Result<Vec<GmailLabel>> {
+        let labels = label_cache_operations::get_labels(conn, account_id)?;
+        let overrides = label_cache_operations::get_overrides(conn, account_id)?;
+        Ok(labels
+            .into_iter()
+            .map(|cached| {
+                let label_override = overrides.iter().find(|o| o.label_id == cached.label_id);
+                label_from_cache(cached, label_override)
+            })
+            .collect())
+    }
+
+    /// Refetch the label list and all counts, replacing the cache
+    pub async fn refresh_labels(&self, account_id: &str) -> Result<Vec<GmailLabel>> {
         println!("🏷️  [GMAIL-API] Getting labels for account: {}", account_id);
         
         // Get valid tokens, though they are not used in the URL but required for auth
@@ -333,40 +514,145 @@ impl GmailApiService {
                 message: format!("No tokens found for account_id: {}", account_id),
             })?;
 
-        let endpoint = "users/me/labels";
-
         let list_response = self
-            .make_api_request::<serde_json::Value>(account_id, &endpoint).await?;
+            .make_api_request::<serde_json::Value>(account_id, "users/me/labels").await?;
+
+        // The list call carries names, types and colours but not counts
+        let labels: Vec<CachedLabel> = list_response
+            .get("labels")
+            .and_then(|v| v.as_array())
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|item| serde_json::from_value::<GmailLabel>(item.clone()).ok())
+                    .map(cache_entry)
+                    .collect()
+            })
+            .unwrap_or_default();
 
-        let mut labels = vec![];
-        if let Some(items) = list_response.get("labels").and_then(|v| v.as_array()) {
-            for item in items {
-                if let Ok(label) = serde_json::from_value::<GmailLabel>(item.clone()) {
-                    labels.push(label);
-                }
-            }
-        } else {
-            return Ok(labels); // Return empty vec if no labels found
+        {
+            let mut conn = self.db_manager.get_connection()?;
+            label_cache_operations::replace_labels(&mut conn, account_id, &labels)?;
         }
+        self.refresh_label_counts(account_id).await?;
 
-        // Fetch details for each label to get message counts (sequentially for now)
-        let mut detailed_labels = Vec::new();
-        for mut label in labels {
-            let endpoint = format!("users/me/labels/{}", label.id);
-            if let Ok(detail_response) = self.make_api_request::<serde_json::Value>(account_id, &endpoint).await {
-                label.messages_total = detail_response.get("messagesTotal").and_then(|v| v.as_i64());
-                label.messages_unread = detail_response.get("messagesUnread").and_then(|v| v.as_i64());
-                label.threads_total = detail_response.get("threadsTotal").and_then(|v| v.as_i64());
-                label.threads_unread = detail_response.get("threadsUnread").and_then(|v| v.as_i64());
-            }
-            detailed_labels.push(label);
+        let labels = self.cached_labels(account_id)?;
+        println!("✅ [GMAIL-API] Successfully cached {} labels with details", labels.len());
+        Ok(labels)
+    }
+
+    /// Fetch the account's send-as addresses from Gmail settings and store them
+    pub async fn refresh_send_as(&self, account_id: &str) -> Result<Vec<SendAsAlias>> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct GmailSendAs {
+            send_as_email: String,
+            display_name: Option<String>,
+            reply_to_address: Option<String>,
+            signature: Option<String>,
+            #[serde(default)]
+            is_primary: bool,
+            #[serde(default)]
+            is_default: bool,
+            #[serde(default)]
+            treat_as_alias: bool,
+            verification_status: Option<String>,
         }
 
-        println!("✅ [GMAIL-API] Successfully parsed {} labels with details", detailed_labels.len());
-        Ok(detailed_labels)
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct SendAsList {
+            #[serde(default)]
+            send_as: Vec<GmailSendAs>,
+        }
+
+        let non_empty = |value: Option<String>| value.filter(|v| !v.trim().is_empty());
+        let list: SendAsList = self.make_api_request(account_id, "users/me/settings/sendAs").await?;
+        let aliases: Vec<SendAsAlias> = list
+            .send_as
+            .into_iter()
+            .map(|entry| SendAsAlias {
+                email: entry.send_as_email,
+                display_name: non_empty(entry.display_name),
+                reply_to: non_empty(entry.reply_to_address),
+                signature: non_empty(entry.signature),
+                is_primary: entry.is_primary,
+                is_default: entry.is_default,
+                treat_as_alias: entry.treat_as_alias,
+                verification_status: entry.verification_status,
+            })
+            .collect();
+
+        let mut conn = self.db_manager.get_connection()?;
+        send_as_operations::replace_aliases(&mut conn, account_id, &aliases)?;
+        send_as_operations::list_aliases(&conn, account_id).map_err(Into::into)
+    }
+
+    /// Stored send-as addresses, fetched from Gmail when none are stored yet
+    pub async fn send_as_aliases(&self, account_id: &str) -> Result<Vec<SendAsAlias>> {
+        let stored = {
+            let conn = self.db_manager.get_connection()?;
+            send_as_operations::list_aliases(&conn, account_id)?
+        };
+        if !stored.is_empty() {
+            return Ok(stored);
+        }
+        self.refresh_send_as(account_id).await
+    }
+
+    /// Refetch counts for every cached label in one batch call; returns how
+    /// many labels were updated. Sync calls this after storing new mail.
+    pub async fn refresh_label_counts(&self, account_id: &str) -> Result<usize> {
+        let label_ids: Vec<String> = {
+            let conn = self.db_manager.get_connection()?;
+            label_cache_operations::get_labels(&conn, account_id)?
+                .into_iter()
+                .map(|label| label.label_id)
+                .collect()
+        };
+        if label_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let endpoints: Vec<String> = label_ids
+            .iter()
+            .map(|id| format!("users/me/labels/{}", urlencoding::encode(id)))
+            .collect();
+        let details = self.make_batch_get_request(account_id, &endpoints).await?;
+
+        let counts: Vec<LabelCounts> = label_ids
+            .into_iter()
+            .zip(details)
+            .filter_map(|(label_id, detail)| {
+                let detail = detail?;
+                Some(LabelCounts {
+                    label_id,
+                    messages_total: detail.get("messagesTotal").and_then(|v| v.as_i64()),
+                    messages_unread: detail.get("messagesUnread").and_then(|v| v.as_i64()),
+                    threads_total: detail.get("threadsTotal").and_then(|v| v.as_i64()),
+                    threads_unread: detail.get("threadsUnread").and_then(|v| v.as_i64()),
+                })
+            })
+            .collect();
+
+        let mut conn = self.db_manager.get_connection()?;
+        label_cache_operations::update_counts(&mut conn, account_id, &counts, chrono::Utc::now().timestamp())?;
+        Ok(counts.len())
+    }
+
+    /// Counts are out of date once messages change labels
+    fn mark_label_counts_stale(&self, account_id: &str) {
+        let result = self
+            .db_manager
+            .get_connection()
+            .and_then(|conn| label_cache_operations::mark_stale(&conn, account_id));
+        if let Err(e) = result {
+            eprintln!("⚠️ [GMAIL-API] Failed to mark label counts stale for {}: {}", account_id, e);
+        }
     }
     
-    /// Modify labels for a batch of messages
+    /// Modify labels for a batch of messages, in calls of up to
+    /// `BATCH_MODIFY_LIMIT` IDs. A failed call leaves the earlier ones applied.
     pub async fn modify_messages(
         &self,
         account_id: &str,
@@ -374,20 +660,26 @@ impl GmailApiService {
         add_label_ids: Vec<String>,
         remove_label_ids: Vec<String>,
     ) -> Result<()> {
-        let body = serde_json::json!({
-            "ids": message_ids,
-            "addLabelIds": add_label_ids,
-            "removeLabelIds": remove_label_ids,
-        });
-
-        self.make_api_post_request::<serde_json::Value>(
-            account_id,
-            "users/me/messages/batchModify",
-            body,
-        )
-        .await?;
+        let result = async {
+            for chunk in message_ids.chunks(BATCH_MODIFY_LIMIT) {
+                let body = serde_json::json!({
+                    "ids": chunk,
+                    "addLabelIds": add_label_ids,
+                    "removeLabelIds": remove_label_ids,
+                });
+                self.make_api_post_request::<serde_json::Value>(
+                    account_id,
+                    "users/me/messages/batchModify",
+                    body,
+                )
+                .await?;
+            }
+            Ok(())
+        }
+        .await;
 
-        Ok(())
+        self.mark_label_counts_stale(account_id);
+        result
     }
 
     /// Move a batch of messages to the trash
@@ -401,6 +693,7 @@ impl GmailApiService {
         )
         .await?;
 
+        self.mark_label_counts_stale(account_id);
         Ok(())
     }
 
@@ -442,25 +735,67 @@ impl GmailApiService {
         self.make_api_request(account_id, &endpoint).await
     }
 
+    /// The mailbox's current history ID, where an incremental sync
+    /// started now would pick up
+    pub async fn get_history_id(&self, account_id: &str) -> Result<String> {
+        let profile: serde_json::Value = self.make_api_request(account_id, "users/me/profile").await?;
+        profile["historyId"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| LibreOllamaError::GmailApi {
+                message: "Gmail profile has no history ID".to_string(),
+                status_code: None,
+            })
+    }
+
+    /// One page of mailbox changes after `start_history_id`. Gmail answers
+    /// 404 once the ID is too old, and a full sync is needed.
+    pub async fn list_history(
+        &self,
+        account_id: &str,
+        start_history_id: &str,
+        page_token: Option<&str>,
+    ) -> Result<HistoryListResponse> {
+        let mut endpoint = format!(
+            "users/me/history?startHistoryId={}&historyTypes=messageAdded&historyTypes=messageDeleted&historyTypes=labelAdded&historyTypes=labelRemoved",
+            urlencoding::encode(start_history_id)
+        );
+        if let Some(page_token) = page_token {
+            endpoint.push_str(&format!("&pageToken={}", urlencoding::encode(page_token)));
+        }
+        self.make_api_request(account_id, &endpoint).await
+    }
+
     /// Get a specific message by ID
     pub async fn get_message(&self, account_id: &str, message_id: &str) -> Result<GmailMessage> {
         let endpoint = format!("users/me/messages/{}?format=full", message_id);
         self.make_api_request(account_id, &endpoint).await
     }
 
-    /// Get a message with parsed content
+    /// Get a message with parsed content. Bodies never change, so a
+    /// message parsed before is served from memory with only its labels
+    /// fetched again.
     pub async fn get_parsed_message(
         &self,
         account_id: &str,
         message_id: &str,
     ) -> Result<ProcessedGmailMessage> {
+        let key = (account_id.to_string(), message_id.to_string());
+        if let Some(cached) = resource_budget::PARSED_MESSAGES.get(&key) {
+            let endpoint = format!("users/me/messages/{}?format=minimal", message_id);
+            let minimal: serde_json::Value = self.make_api_request(account_id, &endpoint).await?;
+            let labels = serde_json::from_value(minimal["labelIds"].clone()).unwrap_or_default();
+            return Ok(ProcessedGmailMessage { labels, ..cached });
+        }
+
         let gmail_message = self.get_message(account_id, message_id).await?;
-        let parsed_content = self.parse_gmail_message(&gmail_message)?;
+        let mut parsed_content = self.parse_gmail_message(&gmail_message)?;
+        self.sniff_attachments(account_id, &gmail_message.id, &mut parsed_content).await;
 
         // Generate snippet from parsed content if Gmail API snippet is empty or contains error text
         let snippet = self.generate_snippet(&gmail_message.snippet, &parsed_content);
 
-        Ok(ProcessedGmailMessage {
+        let message = ProcessedGmailMessage {
             id: gmail_message.id,
             thread_id: gmail_message.thread_id,
             parsed_content,
@@ -468,7 +803,9 @@ impl GmailApiService {
             snippet: Some(snippet),
             internal_date: gmail_message.internal_date,
             size_estimate: gmail_message.size_estimate,
-        })
+        };
+        resource_budget::PARSED_MESSAGES.insert(key, message.clone());
+        Ok(message)
     }
 
     /// Get an entire thread with parsed messages
@@ -479,7 +816,8 @@ impl GmailApiService {
         let mut processed_messages = Vec::new();
         for gmail_message in gmail_thread.messages {
             match self.parse_gmail_message(&gmail_message) {
-                Ok(parsed_content) => {
+                Ok(mut parsed_content) => {
+                    self.sniff_attachments(account_id, &gmail_message.id, &mut parsed_content).await;
                     // Generate snippet from parsed content if Gmail API snippet is empty or contains error text
                     let snippet = self.generate_snippet(&gmail_message.snippet, &parsed_content);
                     
@@ -503,6 +841,25 @@ impl GmailApiService {
         Ok(processed_messages)
     }
 
+    /// Message IDs and labels of a thread, without fetching any content
+    pub async fn get_thread_labels(&self, account_id: &str, thread_id: &str) -> Result<Vec<(String, Vec<String>)>> {
+        let endpoint = format!("users/me/threads/{}?format=minimal", thread_id);
+        let thread: serde_json::Value = self.make_api_request(account_id, &endpoint).await?;
+
+        let messages = thread.get("messages").and_then(|m| m.as_array()).cloned().unwrap_or_default();
+        Ok(messages
+            .iter()
+            .filter_map(|message| {
+                let id = message.get("id")?.as_str()?.to_string();
+                let labels = message
+                    .get("labelIds")
+                    .and_then(|labels| serde_json::from_value(labels.clone()).ok())
+                    .unwrap_or_default();
+                Some((id, labels))
+            })
+            .collect())
+    }
+
     /// Search messages with parsing
     pub async fn search_messages(
         &self,
@@ -552,21 +909,68 @@ impl GmailApiService {
         let response: AttachmentResponse = self.make_api_request(account_id, &endpoint).await?;
 
         // Decode base64 data using robust method
-        self.decode_base64_with_padding(&response.data)
+        mime::decode_base64_with_padding(&response.data)
+    }
+
+    /// Re-judge small attachments whose content Gmail did not include, now
+    /// with their first bytes. A failed download keeps the name-only risk.
+    async fn sniff_attachments(&self, account_id: &str, message_id: &str, parsed: &mut ParsedEmail) {
+        let unsniffed = parsed.attachments.iter_mut().filter(|attachment| {
+            attachment.data.is_none()
+                && !attachment.id.starts_with("att_")
+                && attachment.size.is_some_and(|size| size <= SNIFF_MAX_BYTES)
+        });
+        for attachment in unsniffed.take(SNIFF_MAX_ATTACHMENTS) {
+            match self.get_attachment(account_id, message_id, &attachment.id).await {
+                Ok(data) => {
+                    attachment.risk = Some(attachment_risk::assess(
+                        attachment.filename.as_deref(),
+                        &attachment.content_type,
+                        Some(&data),
+                    ));
+                }
+                Err(e) => eprintln!("⚠️ Failed to fetch attachment {} of {} for sniffing: {}", attachment.id, message_id, e),
+            }
+        }
+    }
+
+    /// Point the HTML body's `cid:` images at their inline attachments,
+    /// downloading the ones Gmail did not include in the message payload
+    pub async fn resolve_inline_images(&self, account_id: &str, message: &mut ProcessedGmailMessage) -> Result<()> {
+        let Some(html) = message.parsed_content.body_html.clone() else {
+            return Ok(());
+        };
+
+        let referenced = inline_images::referenced_content_ids(&html);
+        if referenced.is_empty() {
+            return Ok(());
+        }
+
+        for attachment in &mut message.parsed_content.attachments {
+            let wanted = attachment.content_id.as_ref().is_some_and(|id| referenced.contains(id));
+            if !wanted || attachment.data.is_some() || attachment.id.starts_with("att_") {
+                continue;
+            }
+            match self.get_attachment(account_id, &message.id, &attachment.id).await {
+                Ok(data) => attachment.data = Some(data),
+                Err(e) => eprintln!("⚠️ Failed to fetch inline image {} of {}: {}", attachment.id, message.id, e),
+            }
+        }
+
+        message.parsed_content.body_html =
+            Some(inline_images::resolve_cid_references(&html, &message.parsed_content.attachments));
+        Ok(())
     }
 
     /// Parse Gmail message content
     fn parse_gmail_message(&self, gmail_message: &GmailMessage) -> Result<ParsedEmail> {
         // If we have raw content, use that for better parsing
         if let Some(raw) = &gmail_message.raw {
-            let decoded = self.decode_base64_with_padding(raw)?;
-            
-            let raw_str = String::from_utf8(decoded)
-                .map_err(|e| LibreOllamaError::Serialization {
-                    message: format!("Failed to convert raw message to UTF-8: {}", e),
-                    data_type: "utf8".to_string(),
-                })?;
+            let decoded = mime::decode_base64_with_padding(raw)?;
             
+            // 8-bit bodies in other charsets must not fail the whole message
+            let raw_str = String::from_utf8_lossy(&decoded);
+
             return self.parse_raw_email(&raw_str, &gmail_message.id, &gmail_message.thread_id);
         }
 
@@ -580,12 +984,8 @@ impl GmailApiService {
         // In a full implementation, you'd use a crate like `mailparse`
         let headers = self.extract_headers_from_raw(raw_content)?;
         
-        let subject = headers.get("subject").cloned();
-        let from = self.parse_address(headers.get("from").unwrap_or(&String::new()))?;
-        let to = self.parse_address_list(headers.get("to").unwrap_or(&String::new()));
-        let cc = self.parse_address_list(headers.get("cc").unwrap_or(&String::new()));
-        let bcc = self.parse_address_list(headers.get("bcc").unwrap_or(&String::new()));
-        let reply_to = self.parse_optional_address(headers.get("reply-to").unwrap_or(&String::new()))?;
+        let subject = headers.get("subject").map(|subject| mime::decode_encoded_words(subject));
+        let (from, to, cc, bcc, reply_to) = parse_address_headers(&headers);
         let date = headers.get("date").cloned();
 
         // Extract body content (simplified)
@@ -618,12 +1018,8 @@ impl GmailApiService {
     fn parse_gmail_payload(&self, payload: &GmailPayload, message_id: &str, thread_id: &str) -> Result<ParsedEmail> {
         let headers = self.extract_gmail_headers(&payload.headers);
         
-        let subject = headers.get("subject").cloned();
-        let from = self.parse_address(headers.get("from").unwrap_or(&String::new()))?;
-        let to = self.parse_address_list(headers.get("to").unwrap_or(&String::new()));
-        let cc = self.parse_address_list(headers.get("cc").unwrap_or(&String::new()));
-        let bcc = self.parse_address_list(headers.get("bcc").unwrap_or(&String::new()));
-        let reply_to = self.parse_optional_address(headers.get("reply-to").unwrap_or(&String::new()))?;
+        let subject = headers.get("subject").map(|subject| mime::decode_encoded_words(subject));
+        let (from, to, cc, bcc, reply_to) = parse_address_headers(&headers);
         let date = headers.get("date").cloned();
 
         // Extract body content and attachments
@@ -725,38 +1121,67 @@ impl GmailApiService {
     ) -> Result<()> {
         // Check if this part has content
         if let Some(body) = &part.body {
-            if let Some(data) = &body.data {
+            let decoded = match &body.data {
                 // Decode base64 content with robust padding handling
-                let decoded = self.decode_base64_with_padding(data)?;
-
-                let content = String::from_utf8_lossy(&decoded);
-
-                match part.mime_type.as_str() {
-                    "text/plain" => {
-                        if body_text.is_none() {
-                            *body_text = Some(content.to_string());
-                        }
+                Some(data) => Some(mime::decode_base64_with_padding(data)?),
+                None => None,
+            };
+
+            let header = |name: &str| {
+                part.headers
+                    .iter()
+                    .find(|h| h.name.eq_ignore_ascii_case(name))
+                    .map(|h| h.value.as_str())
+            };
+            let charset = header("Content-Type").and_then(mime::charset_param);
+            let content = decoded.as_ref().map(|d| mime::decode_text(d, charset.as_deref()));
+            // A text file attached to the message is not its body
+            let is_attachment = part.filename.as_deref().is_some_and(|f| !f.is_empty())
+                || header("Content-Disposition").is_some_and(|d| d.trim_start().to_ascii_lowercase().starts_with("attachment"));
+
+            match (part.mime_type.as_str(), content) {
+                ("text/plain", Some(content)) if !is_attachment => {
+                    if body_text.is_none() {
+                        *body_text = Some(content);
+                    }
+                }
+                ("text/html", Some(content)) if !is_attachment => {
+                    if body_html.is_none() {
+                        *body_html = Some(content);
                     }
-                    "text/html" => {
-                        if body_html.is_none() {
-                            *body_html = Some(content.to_string());
-                        }
+                }
+                // Gmail leaves a multipart body unsplit when its boundary
+                // is broken; showing it as text beats showing nothing
+                (mime_type, Some(content)) if mime_type.starts_with("multipart/") && part.parts.is_none() => {
+                    if body_text.is_none() {
+                        *body_text = Some(content);
                     }
-                    _ => {
-                        // Handle as attachment
-                        if part.filename.is_some() || body.attachment_id.is_some() {
-                            attachments.push(EmailAttachment {
-                                id: body.attachment_id.clone().unwrap_or_else(|| 
-                                    format!("att_{}", attachments.len())
-                                ),
-                                filename: part.filename.clone(),
-                                content_type: part.mime_type.clone(),
-                                size: body.size.map(|s| s as usize),
-                                content_id: None,
-                                is_inline: false,
-                                data: Some(decoded),
-                            });
-                        }
+                }
+                _ => {
+                    // Handle as attachment; large parts only carry an attachment ID
+                    let content_id = header("Content-ID").map(inline_images::normalize_content_id);
+                    let is_inline = header("Content-Disposition")
+                        .map(|d| d.trim_start().to_ascii_lowercase().starts_with("inline"))
+                        .unwrap_or(content_id.is_some());
+
+                    let has_name = part.filename.as_deref().is_some_and(|f| !f.is_empty());
+                    if (has_name || body.attachment_id.is_some() || content_id.is_some())
+                        && (decoded.is_some() || body.attachment_id.is_some())
+                    {
+                        let filename = part.filename.as_deref().map(mime::decode_encoded_words);
+                        let risk = attachment_risk::assess(filename.as_deref(), &part.mime_type, decoded.as_deref());
+                        attachments.push(EmailAttachment {
+                            id: body.attachment_id.clone().unwrap_or_else(||
+                                format!("att_{}", attachments.len())
+                            ),
+                            filename,
+                            content_type: part.mime_type.clone(),
+                            size: body.size.map(|s| s as usize),
+                            content_id,
+                            is_inline,
+                            data: decoded,
+                            risk: Some(risk),
+                        });
                     }
                 }
             }
@@ -772,103 +1197,6 @@ impl GmailApiService {
         Ok(())
     }
 
-    /// Parse email address
-    fn parse_address(&self, addr_str: &str) -> Result<EmailAddress> {
-        let trimmed = addr_str.trim();
-        
-        if let Some(start) = trimmed.rfind('<') {
-            if let Some(end) = trimmed.rfind('>') {
-                if start < end {
-                    let email = trimmed[(start + 1)..end].trim().to_string();
-                    let name = if start > 0 {
-                        let name_part = trimmed[..start].trim();
-                        let clean_name = name_part.trim_matches('"').trim();
-                        if clean_name.is_empty() {
-                            None
-                        } else {
-                            Some(clean_name.to_string())
-                        }
-                    } else {
-                        None
-                    };
-                    return Ok(EmailAddress { email, name });
-                }
-            }
-        }
-        
-        Ok(EmailAddress {
-            email: trimmed.to_string(),
-            name: None,
-        })
-    }
-
-    /// Parse optional email address
-    fn parse_optional_address(&self, addr_str: &str) -> Result<Option<EmailAddress>> {
-        if addr_str.trim().is_empty() {
-            Ok(None)
-        } else {
-            Ok(Some(self.parse_address(addr_str)?))
-        }
-    }
-
-    /// Parse list of email addresses
-    fn parse_address_list(&self, addr_list: &str) -> Vec<EmailAddress> {
-        if addr_list.trim().is_empty() {
-            return Vec::new();
-        }
-
-        // Split by comma, but be careful about commas inside quoted names
-        let addresses = self.split_address_list(addr_list);
-        addresses
-            .into_iter()
-            .filter_map(|addr| self.parse_address(&addr).ok())
-            .collect()
-    }
-
-    /// Split address list respecting quoted names
-    fn split_address_list(&self, addr_list: &str) -> Vec<String> {
-        let mut addresses = Vec::new();
-        let mut current = String::new();
-        let mut in_quotes = false;
-        let mut in_brackets = false;
-
-        for ch in addr_list.chars() {
-            match ch {
-                '"' => {
-                    in_quotes = !in_quotes;
-                    current.push(ch);
-                }
-                '<' => {
-                    in_brackets = true;
-                    current.push(ch);
-                }
-                '>' => {
-                    in_brackets = false;
-                    current.push(ch);
-                }
-                ',' => {
-                    if !in_quotes && !in_brackets {
-                        if !current.trim().is_empty() {
-                            addresses.push(current.trim().to_string());
-                        }
-                        current.clear();
-                    } else {
-                        current.push(ch);
-                    }
-                }
-                _ => {
-                    current.push(ch);
-                }
-            }
-        }
-
-        if !current.trim().is_empty() {
-            addresses.push(current.trim().to_string());
-        }
-
-        addresses
-    }
-
     /// Convert HTML to plain text
     pub fn html_to_text(&self, html: &str) -> String {
         // Basic HTML to text conversion
@@ -889,6 +1217,42 @@ impl GmailApiService {
             .join("\n")
     }
 
+    /// Split a message body into new content, quoted history and signature
+    pub fn reply_segments(&self, message: &ProcessedGmailMessage) -> Vec<BodySegment> {
+        let content = &message.parsed_content;
+        let body = content
+            .body_text
+            .clone()
+            .or_else(|| content.body_html.as_deref().map(|html| self.html_to_text(html)))
+            .unwrap_or_default();
+        split_reply_chain(&body)
+    }
+
+    /// Plain-text rendering of a thread for LLM summarization, with quoted
+    /// history and signatures left out so each message appears once
+    pub fn thread_summary_input(&self, messages: &[ProcessedGmailMessage]) -> String {
+        messages
+            .iter()
+            .filter_map(|message| {
+                let new_content = self
+                    .reply_segments(message)
+                    .into_iter()
+                    .filter(|segment| segment.kind == SegmentKind::Content)
+                    .map(|segment| segment.text)
+                    .collect::<Vec<_>>()
+                    .join("\n\n");
+                if new_content.trim().is_empty() {
+                    return None;
+                }
+                let from = &message.parsed_content.from;
+                let sender = from.name.as_deref().filter(|n| !n.trim().is_empty()).unwrap_or(&from.email);
+                let date = message.parsed_content.date.as_deref().unwrap_or_default();
+                Some(format!("From: {} ({})\n{}", sender, date, new_content))
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n---\n\n")
+    }
+
     /// Generate a snippet from parsed content as fallback
     fn generate_snippet(&self, api_snippet: &Option<String>, parsed_content: &ParsedEmail) -> String {
         // Check if Gmail API snippet is usable
@@ -924,8 +1288,8 @@ impl GmailApiService {
             .collect::<Vec<_>>()
             .join(" ");
 
-        let snippet = if cleaned_text.len() > 120 {
-            format!("{}...", &cleaned_text[..120])
+        let snippet = if cleaned_text.chars().count() > 120 {
+            format!("{}...", cleaned_text.chars().take(120).collect::<String>())
         } else {
             cleaned_text
         };
@@ -937,71 +1301,6 @@ impl GmailApiService {
         }
     }
 
-    /// Decode Base64 data with robust padding handling
-    fn decode_base64_with_padding(&self, data: &str) -> Result<Vec<u8>> {
-        // Clean the input data
-        let cleaned_data = data.trim().replace('\n', "").replace('\r', "").replace(' ', "");
-        
-        // Try URL_SAFE_NO_PAD first (Gmail API standard)
-        if let Ok(decoded) = general_purpose::URL_SAFE_NO_PAD.decode(&cleaned_data) {
-            return Ok(decoded);
-        }
-
-        // Try with standard Base64 encoding
-        if let Ok(decoded) = general_purpose::STANDARD.decode(&cleaned_data) {
-            return Ok(decoded);
-        }
-
-        // Try adding padding
-        let mut padded_data = cleaned_data.clone();
-        while padded_data.len() % 4 != 0 {
-            padded_data.push('=');
-        }
-        
-        if let Ok(decoded) = general_purpose::URL_SAFE_NO_PAD.decode(&padded_data) {
-            return Ok(decoded);
-        }
-
-        // Try standard with padding
-        if let Ok(decoded) = general_purpose::STANDARD.decode(&padded_data) {
-            return Ok(decoded);
-        }
-
-        // Try URL_SAFE with padding
-        if let Ok(decoded) = general_purpose::URL_SAFE.decode(&padded_data) {
-            return Ok(decoded);
-        }
-
-        // Try replacing URL-safe characters and decode as standard
-        let standard_data = cleaned_data.replace('-', "+").replace('_', "/");
-        if let Ok(decoded) = general_purpose::STANDARD.decode(&standard_data) {
-            return Ok(decoded);
-        }
-
-        // Try standard with proper padding
-        let mut standard_padded = standard_data;
-        while standard_padded.len() % 4 != 0 {
-            standard_padded.push('=');
-        }
-        if let Ok(decoded) = general_purpose::STANDARD.decode(&standard_padded) {
-            return Ok(decoded);
-        }
-
-        // If it's empty or very short, return empty
-        if cleaned_data.is_empty() || cleaned_data.len() < 4 {
-            return Ok(Vec::new());
-        }
-
-        // All attempts failed, return a more detailed error
-        Err(LibreOllamaError::Serialization {
-            message: format!("Failed to decode base64 data after all attempts. Data length: {}, sample: '{}'", 
-                cleaned_data.len(), 
-                if cleaned_data.len() > 20 { &cleaned_data[..20] } else { &cleaned_data }
-            ),
-            data_type: "base64".to_string(),
-        })
-    }
-
     /// Make API request with detailed logging for debugging
     async fn make_api_request_with_logging(&self, account_id: &str, endpoint: &str) -> Result<String> {
         println!("🔍 [GMAIL-API] Making request to endpoint: {}", endpoint);
@@ -1011,12 +1310,13 @@ impl GmailApiService {
             .validate_and_refresh_tokens(&self.db_manager, account_id)
             .await?;
 
-        let url = format!("{}/{}", GMAIL_API_BASE, endpoint.trim_start_matches('/'));
+        let url = GoogleEndpoints::url(&self.endpoints.gmail_api, endpoint);
         println!("🔍 [GMAIL-API] Full URL: {}", url);
         
         // Create rate-limited request
         let batch_request = BatchRequest {
             id: format!("gmail_api_{}", Uuid::new_v4()),
+            account_id: Some(account_id.to_string()),
             method: "GET".to_string(),
             url: url.clone(),
             headers: {
@@ -1033,14 +1333,8 @@ impl GmailApiService {
         };
 
         // Execute request through rate limiter
-        let response = {
-            let mut rate_limiter = self.rate_limiter.lock().await;
-            rate_limiter.execute_request(batch_request).await
-                .map_err(|e| LibreOllamaError::Network {
-                    message: format!("Rate limited Gmail API request failed: {}", e),
-                    url: Some(url.clone()),
-                })?
-        };
+        let response = rate_limiter::send_scheduled(&self.rate_limiter, batch_request).await
+            .map_err(|e| rate_limiter::request_error(e, "Rate limited Gmail API request failed", &url))?;
 
         println!("🔍 [GMAIL-API] Response status: {}", response.status_code);
         println!("🔍 [GMAIL-API] Response body preview: {}", response.body.chars().take(300).collect::<String>());
@@ -1055,4 +1349,250 @@ impl GmailApiService {
 
         Ok(response.body)
     }
-} 
\ No newline at end of file
+}
+
+// =============================================================================
+// Label cache and batch requests
+// =============================================================================
+
+fn cache_entry(label: GmailLabel) -> CachedLabel {
+    let (text_color, background_color) = label
+        .color
+        .map(|color| (color.text_color, color.background_color))
+        .unwrap_or_default();
+    CachedLabel {
+        label_id: label.id,
+        name: label.name,
+        label_type: label.label_type,
+        message_list_visibility: label.message_list_visibility,
+        label_list_visibility: label.label_list_visibility,
+        text_color,
+        background_color,
+        messages_total: label.messages_total,
+        messages_unread: label.messages_unread,
+        threads_total: label.threads_total,
+        threads_unread: label.threads_unread,
+    }
+}
+
+fn label_from_cache(cached: CachedLabel, label_override: Option<&LabelOverride>) -> GmailLabel {
+    let color = |text_color: Option<String>, background_color: Option<String>| {
+        (text_color.is_some() || background_color.is_some()).then_some(GmailLabelColor { text_color, background_color })
+    };
+    GmailLabel {
+        id: cached.label_id,
+        name: cached.name,
+        message_list_visibility: cached.message_list_visibility,
+        label_list_visibility: cached.label_list_visibility,
+        label_type: cached.label_type,
+        messages_total: cached.messages_total,
+        messages_unread: cached.messages_unread,
+        threads_total: cached.threads_total,
+        threads_unread: cached.threads_unread,
+        color: color(cached.text_color, cached.background_color),
+        display_name: label_override.and_then(|o| o.display_name.clone()),
+        display_color: label_override.and_then(|o| color(o.text_color.clone(), o.background_color.clone())),
+    }
+}
+
+/// From, To, Cc, Bcc and Reply-To of a header map keyed by lowercase name
+fn parse_address_headers(
+    headers: &HashMap<String, String>,
+) -> (EmailAddress, Vec<EmailAddress>, Vec<EmailAddress>, Vec<EmailAddress>, Option<EmailAddress>) {
+    let header = |name: &str| headers.get(name).map(String::as_str).unwrap_or_default();
+    (
+        mime::parse_address(header("from")),
+        mime::parse_address_list(header("to")),
+        mime::parse_address_list(header("cc")),
+        mime::parse_address_list(header("bcc")),
+        mime::parse_address_list(header("reply-to")).into_iter().next(),
+    )
+}
+
+// =============================================================================
+// Reply chain parsing
+// =============================================================================
+
+lazy_static::lazy_static! {
+    /// "On <date>, <name> wrote:" and its equivalents in other locales
+    static ref ATTRIBUTION_LINE: Regex = Regex::new(
+        r"(?i)^(on|am|le|el|il|em|op|den|dnia|в)\s.+\b(wrote|schrieb|a écrit|escribió|ha scritto|escreveu|schreef|skrev|napisał|написал|написала)\b.*:\s*$"
+    ).unwrap();
+    /// Outlook style "-----Original Message-----" separators
+    static ref ORIGINAL_MESSAGE_LINE: Regex = Regex::new(
+        r"(?i)^-{2,}\s*(original message|ursprüngliche nachricht|mensaje original|message d'origine|messaggio originale|mensagem original|oorspronkelijk bericht)\s*-{2,}\s*$"
+    ).unwrap();
+    /// First line of a quoted header block ("From: ...")
+    static ref HEADER_FROM_LINE: Regex = Regex::new(r"(?i)^(from|von|de|da|van|från)\s?:\s*\S").unwrap();
+    /// A following header line that confirms the block ("Sent: ...")
+    static ref HEADER_DATE_LINE: Regex =
+        Regex::new(r"(?i)^(sent|date|gesendet|datum|envoyé|enviado|inviato|verzonden|skickat)\s?:").unwrap();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SegmentKind {
+    /// Text written in this message
+    Content,
+    /// Earlier messages quoted in the reply
+    Quote,
+    /// Text after a "-- " signature delimiter
+    Signature,
+}
+
+/// A run of lines in a message body, so the UI can collapse quotes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BodySegment {
+    pub kind: SegmentKind,
+    pub text: String,
+    /// The "On ... wrote:" line or separator that introduced a quote
+    pub attribution: Option<String>,
+}
+
+/// Where quoted history starts at `index`, and how many lines the
+/// attribution takes up (attribution lines are often wrapped in two)
+fn quote_start(lines: &[&str], index: usize) -> Option<(String, usize)> {
+    let line = lines[index].trim();
+    if ATTRIBUTION_LINE.is_match(line) || ORIGINAL_MESSAGE_LINE.is_match(line) {
+        return Some((line.to_string(), 1));
+    }
+    if let Some(next) = lines.get(index + 1) {
+        let joined = format!("{} {}", line, next.trim());
+        if !line.is_empty() && ATTRIBUTION_LINE.is_match(&joined) {
+            return Some((joined, 2));
+        }
+    }
+    if HEADER_FROM_LINE.is_match(line)
+        && lines
+            .iter()
+            .skip(index + 1)
+            .take(4)
+            .any(|l| HEADER_DATE_LINE.is_match(l.trim_start_matches('>').trim()))
+    {
+        return Some((line.to_string(), 0));
+    }
+    None
+}
+
+/// Remove one level of "> " quoting
+fn unquote(line: &str) -> &str {
+    let trimmed = line.trim_start();
+    match trimmed.strip_prefix('>') {
+        Some(rest) => rest.strip_prefix(' ').unwrap_or(rest),
+        None => line,
+    }
+}
+
+/// Append a line to the last segment if it is of the same kind, otherwise
+/// start a new one. A segment with an attribution always starts fresh.
+fn push_line(segments: &mut Vec<BodySegment>, kind: SegmentKind, line: &str, attribution: Option<String>) {
+    match segments.last_mut() {
+        Some(last) if last.kind == kind && attribution.is_none() => {
+            last.text.push('\n');
+            last.text.push_str(line);
+        }
+        _ => segments.push(BodySegment { kind, text: line.to_string(), attribution }),
+    }
+}
+
+/// Split a plain-text body into new content, quoted history and signature.
+///
+/// Handles ">" prefixed quoting, "On ... wrote:" attributions in several
+/// languages (including ones wrapped over two lines), and Outlook's
+/// "Original Message" separators and From:/Sent: header blocks, after
+/// which the rest of the body is treated as quoted.
+pub fn split_reply_chain(body: &str) -> Vec<BodySegment> {
+    let lines: Vec<&str> = body.lines().collect();
+    let mut segments: Vec<BodySegment> = Vec::new();
+    let mut in_signature = false;
+
+    let mut index = 0;
+    while index < lines.len() {
+        let line = lines[index];
+
+        if let Some((attribution, consumed)) = quote_start(&lines, index) {
+            let rest: Vec<&str> = lines[index + consumed..].iter().map(|l| unquote(l)).collect();
+            push_line(&mut segments, SegmentKind::Quote, &rest.join("\n"), Some(attribution));
+            break;
+        }
+
+        if line.trim_start().starts_with('>') {
+            push_line(&mut segments, SegmentKind::Quote, unquote(line), None);
+        } else if line == "-- " || line.trim() == "--" {
+            in_signature = true;
+            segments.push(BodySegment { kind: SegmentKind::Signature, text: String::new(), attribution: None });
+        } else if in_signature {
+            push_line(&mut segments, SegmentKind::Signature, line, None);
+        } else {
+            push_line(&mut segments, SegmentKind::Content, line, None);
+        }
+        index += 1;
+    }
+
+    segments
+        .into_iter()
+        .filter_map(|mut segment| {
+            segment.text = segment.text.trim().to_string();
+            (!segment.text.is_empty()).then_some(segment)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod reply_chain_tests {
+    use super::*;
+
+    #[test]
+    fn splits_reply_from_wrapped_attribution() {
+        let body = "Sounds good, see you then.\n\nOn Mon, Jan 6, 2025 at 10:00 AM Jane Doe <\njane@example.com> wrote:\n> Lunch on Friday?\n> Jane";
+        let segments = split_reply_chain(body);
+
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].kind, SegmentKind::Content);
+        assert_eq!(segments[0].text, "Sounds good, see you then.");
+        assert_eq!(segments[1].kind, SegmentKind::Quote);
+        assert_eq!(segments[1].text, "Lunch on Friday?\nJane");
+        assert!(segments[1].attribution.as_deref().unwrap().ends_with("wrote:"));
+    }
+
+    #[test]
+    fn handles_other_locales_outlook_and_signatures() {
+        let german = split_reply_chain("Danke!\nAm 06.01.2025 um 10:00 schrieb Max Mustermann:\nHallo");
+        assert_eq!(german.iter().map(|s| s.kind).collect::<Vec<_>>(), vec![SegmentKind::Content, SegmentKind::Quote]);
+
+        let outlook = split_reply_chain("Approved.\n-- \nBob\nFrom: Alice\nSent: Monday\nSubject: Budget\nPlease approve");
+        assert_eq!(
+            outlook.iter().map(|s| s.kind).collect::<Vec<_>>(),
+            vec![SegmentKind::Content, SegmentKind::Signature, SegmentKind::Quote]
+        );
+        assert_eq!(outlook[1].text, "Bob");
+    }
+}
+
+#[cfg(test)]
+mod label_cache_tests {
+    use super::*;
+
+    #[test]
+    fn applies_local_overrides_without_touching_gmail_values() {
+        let cached = CachedLabel {
+            label_id: "Label_1".to_string(),
+            name: "Work".to_string(),
+            background_color: Some("#000000".to_string()),
+            ..Default::default()
+        };
+        let label_override = LabelOverride {
+            label_id: "Label_1".to_string(),
+            display_name: Some("Job".to_string()),
+            background_color: Some("#16a766".to_string()),
+            ..Default::default()
+        };
+        let label = label_from_cache(cached.clone(), Some(&label_override));
+
+        assert_eq!(label.name, "Work");
+        assert_eq!(label.display_name.as_deref(), Some("Job"));
+        assert_eq!(label.color.unwrap().background_color.as_deref(), Some("#000000"));
+        assert_eq!(label.display_color.unwrap().background_color.as_deref(), Some("#16a766"));
+        assert!(label_from_cache(cached, None).display_color.is_none());
+    }
+}
@@ -0,0 +1,372 @@
+//! Attachment previews
+//!
+//! The mail view shows a preview of an attachment without the user saving
+//! or opening it. Images are scaled down to a PNG thumbnail with resvg, the
+//! first page of a PDF is rendered with `pdftoppm` when poppler is
+//! installed, and Word, plain-text and image-less PDF attachments get an
+//! excerpt of their text instead. Images are measured before they are
+//! decoded and refused past `image::Limits`, so a small file cannot expand
+//! into gigabytes of pixels. Previews are cached as JSON under
+//! `cache_dir/previews`, so each attachment is downloaded once.
+
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use base64::{engine::general_purpose, Engine as _};
+use resvg::{tiny_skia, usvg};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config::get_config_manager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::chat_attachments::{self, DocumentKind};
+use crate::services::gmail::attachment_risk;
+use crate::services::pdf_extract;
+
+/// Longer side of a thumbnail, in pixels
+pub const THUMBNAIL_SIDE: u32 = 320;
+/// Larger attachments are not downloaded for a preview
+pub const MAX_PREVIEW_SOURCE_BYTES: usize = 25 * 1024 * 1024;
+/// Length of a text excerpt, in characters
+const EXCERPT_CHARS: usize = 1200;
+/// Widest or tallest image decoded for a thumbnail, in pixels
+const MAX_IMAGE_SIDE: u32 = 10_000;
+/// Most memory a decoded image may take
+const MAX_DECODED_BYTES: u64 = 256 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreviewKind {
+    Image,
+    Text,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachmentPreview {
+    pub kind: PreviewKind,
+    /// PNG data URL for image previews
+    pub thumbnail: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// Opening text for text previews
+    pub excerpt: Option<String>,
+    /// Pages in a PDF
+    pub page_count: Option<usize>,
+    #[serde(default)]
+    pub from_cache: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    Image,
+    Pdf,
+    Document(DocumentKind),
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedPreview {
+    key: String,
+    preview: AttachmentPreview,
+}
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp"];
+
+fn source(content_type: &str, filename: &str) -> Option<Source> {
+    let content_type = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    let extension = filename.rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase()).unwrap_or_default();
+    if matches!(content_type.as_str(), "image/png" | "image/jpeg" | "image/jpg" | "image/gif" | "image/webp")
+        || IMAGE_EXTENSIONS.contains(&extension.as_str())
+    {
+        return Some(Source::Image);
+    }
+    match chat_attachments::document_kind(&content_type, filename)? {
+        DocumentKind::Pdf => Some(Source::Pdf),
+        kind => Some(Source::Document(kind)),
+    }
+}
+
+/// Whether an attachment of this type gets a preview
+pub fn can_preview(content_type: &str, filename: &str) -> bool {
+    source(content_type, filename).is_some()
+}
+
+/// Raster type resvg can decode, from the content rather than the headers
+fn image_type(bytes: &[u8]) -> Option<&'static str> {
+    match attachment_risk::sniff(bytes) {
+        Some(image @ ("image/png" | "image/jpeg" | "image/gif")) => Some(image),
+        _ if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP") => Some("image/webp"),
+        _ => None,
+    }
+}
+
+fn png_data_url(png: &[u8]) -> String {
+    format!("data:image/png;base64,{}", general_purpose::STANDARD.encode(png))
+}
+
+fn decode_limits() -> image::Limits {
+    let mut limits = image::Limits::default();
+    limits.max_image_width = Some(MAX_IMAGE_SIDE);
+    limits.max_image_height = Some(MAX_IMAGE_SIDE);
+    limits.max_alloc = Some(MAX_DECODED_BYTES);
+    limits
+}
+
+/// Size of an image from its header, without decoding the pixels
+fn dimensions(bytes: &[u8], image_type: &str) -> Option<(u32, u32)> {
+    if image_type == "image/gif" {
+        // Logical screen size, little-endian after the signature
+        let size = bytes.get(6..10)?;
+        return Some((u16::from_le_bytes([size[0], size[1]]).into(), u16::from_le_bytes([size[2], size[3]]).into()));
+    }
+    let mut reader = image::ImageReader::new(Cursor::new(bytes)).with_guessed_format().ok()?;
+    reader.limits(decode_limits());
+    reader.into_dimensions().ok()
+}
+
+/// Scale an image down to fit `max_side`, as PNG with its size
+fn thumbnail(bytes: &[u8], max_side: u32) -> Result<(Vec<u8>, u32, u32)> {
+    let invalid = |message: String| LibreOllamaError::InvalidInput { message, field: Some("attachment".to_string()) };
+    let image_type = image_type(bytes).ok_or_else(|| invalid("Not a PNG, JPEG, GIF or WebP image".to_string()))?;
+    let (width, height) = dimensions(bytes, image_type).ok_or_else(|| invalid("Image could not be read".to_string()))?;
+    let mut limits = decode_limits();
+    limits
+        .check_dimensions(width, height)
+        .and_then(|_| limits.reserve(u64::from(width) * u64::from(height) * 4))
+        .map_err(|_| invalid(format!("Image is too large to preview ({}×{})", width, height)))?;
+    // An SVG holding just the image lets resvg decode and scale it
+    let svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink"><image xlink:href="data:{};base64,{}"/></svg>"#,
+        image_type,
+        general_purpose::STANDARD.encode(bytes)
+    );
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default())
+        .map_err(|e| invalid(format!("Image could not be read: {}", e)))?;
+    let bounds = tree.root().bounding_box();
+    if bounds.width() < 1.0 || bounds.height() < 1.0 {
+        return Err(invalid("Image could not be decoded".to_string()));
+    }
+
+    let scale = (max_side as f32 / bounds.width().max(bounds.height())).min(1.0);
+    let width = (bounds.width() * scale).round().max(1.0) as u32;
+    let height = (bounds.height() * scale).round().max(1.0) as u32;
+    let mut pixmap = tiny_skia::Pixmap::new(width, height).ok_or_else(|| invalid("Image is too large".to_string()))?;
+    resvg::render(&tree, tiny_skia::Transform::from_scale(scale, scale), &mut pixmap.as_mut());
+    let png = pixmap.encode_png().map_err(|e| LibreOllamaError::Internal { message: format!("Failed to encode thumbnail: {}", e) })?;
+    Ok((png, width, height))
+}
+
+/// The start of `text` with runs of blank lines collapsed
+fn excerpt(text: &str) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.lines().map(str::trim_end) {
+        if !(line.trim().is_empty() && lines.last().is_none_or(|last| last.trim().is_empty())) {
+            lines.push(line);
+        }
+    }
+    let joined = lines.join("\n");
+    match joined.char_indices().nth(EXCERPT_CHARS) {
+        Some((end, _)) => format!("{}…", joined[..end].trim_end()),
+        None => joined.trim_end().to_string(),
+    }
+}
+
+fn image_preview(png: Vec<u8>, width: u32, height: u32, page_count: Option<usize>) -> AttachmentPreview {
+    AttachmentPreview {
+        kind: PreviewKind::Image,
+        thumbnail: Some(png_data_url(&png)),
+        width: Some(width),
+        height: Some(height),
+        excerpt: None,
+        page_count,
+        from_cache: false,
+    }
+}
+
+fn text_preview(text: &str, page_count: Option<usize>) -> AttachmentPreview {
+    AttachmentPreview {
+        kind: PreviewKind::Text,
+        thumbnail: None,
+        width: None,
+        height: None,
+        excerpt: Some(excerpt(text)),
+        page_count,
+        from_cache: false,
+    }
+}
+
+/// Build the preview of an attachment's content. Blocking.
+fn render(source: Source, bytes: &[u8]) -> Result<AttachmentPreview> {
+    match source {
+        Source::Image => {
+            let (png, width, height) = thumbnail(bytes, THUMBNAIL_SIDE)?;
+            Ok(image_preview(png, width, height, None))
+        }
+        Source::Pdf => {
+            let page_count = pdf_extract::page_count(bytes);
+            if pdf_extract::render_available() {
+                match pdf_extract::render_page(bytes, 1, THUMBNAIL_SIDE).and_then(|page| thumbnail(&page, THUMBNAIL_SIDE)) {
+                    Ok((png, width, height)) => return Ok(image_preview(png, width, height, page_count)),
+                    Err(e) => eprintln!("⚠️ [PREVIEW] Could not render the first PDF page: {}", e),
+                }
+            }
+            let document = pdf_extract::extract(bytes, false)?;
+            let first_page = document.pages.iter().map(|page| page.text.as_str()).find(|text| !text.trim().is_empty());
+            Ok(text_preview(first_page.unwrap_or_default(), Some(document.page_count)))
+        }
+        Source::Document(kind) => Ok(text_preview(&chat_attachments::extract_text(kind, bytes)?, None)),
+    }
+}
+
+pub struct AttachmentPreviewer {
+    cache_dir: PathBuf,
+}
+
+impl AttachmentPreviewer {
+    pub fn new(cache_dir: PathBuf) -> Self {
+        Self { cache_dir }
+    }
+
+    /// Previewer caching under `<cache_dir>/previews` from the app config
+    pub fn from_config() -> Result<Self> {
+        let config_manager = get_config_manager().map_err(|e| LibreOllamaError::Configuration {
+            message: format!("Failed to get config manager: {}", e),
+            config_key: None,
+        })?;
+        Ok(Self::new(config_manager.paths().cache_dir.join("previews")))
+    }
+
+    /// Preview of the attachment identified by `key`, from the cache or by
+    /// awaiting `download`. `None` for types without previews and for
+    /// attachments over `MAX_PREVIEW_SOURCE_BYTES`, which are judged by the
+    /// `size` the message lists and never downloaded.
+    pub async fn preview(
+        &self,
+        key: &str,
+        content_type: &str,
+        filename: &str,
+        size: Option<usize>,
+        download: impl std::future::Future<Output = Result<Vec<u8>>>,
+    ) -> Result<Option<AttachmentPreview>> {
+        let Some(source) = source(content_type, filename) else {
+            return Ok(None);
+        };
+        if size.is_some_and(|size| size > MAX_PREVIEW_SOURCE_BYTES) {
+            return Ok(None);
+        }
+        if let Some(preview) = self.read_cache(key).await {
+            return Ok(Some(preview));
+        }
+
+        let bytes = download.await?;
+        // The listed size can be missing or understated
+        if bytes.len() > MAX_PREVIEW_SOURCE_BYTES {
+            return Ok(None);
+        }
+        let preview = tokio::task::spawn_blocking(move || render(source, &bytes))
+            .await
+            .map_err(|e| LibreOllamaError::Internal { message: format!("Preview generation failed: {}", e) })??;
+        if let Err(e) = self.write_cache(key, &preview).await {
+            eprintln!("⚠️ [PREVIEW] Failed to cache preview: {}", e);
+        }
+        Ok(Some(preview))
+    }
+
+    fn cache_path(&self, key: &str) -> PathBuf {
+        self.cache_dir.join(format!("{}.json", hex::encode(Sha256::digest(key.as_bytes()))))
+    }
+
+    async fn read_cache(&self, key: &str) -> Option<AttachmentPreview> {
+        let cached: CachedPreview = serde_json::from_slice(&tokio::fs::read(self.cache_path(key)).await.ok()?).ok()?;
+        // Guards against a hash collision serving the wrong preview
+        (cached.key == key).then_some(AttachmentPreview { from_cache: true, ..cached.preview })
+    }
+
+    async fn write_cache(&self, key: &str, preview: &AttachmentPreview) -> Result<()> {
+        tokio::fs::create_dir_all(&self.cache_dir).await?;
+        let cached = CachedPreview { key: key.to_string(), preview: preview.clone() };
+        tokio::fs::write(self.cache_path(key), serde_json::to_vec(&cached)?).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut pixmap = tiny_skia::Pixmap::new(width, height).unwrap();
+        pixmap.fill(tiny_skia::Color::from_rgba8(200, 30, 30, 255));
+        pixmap.encode_png().unwrap()
+    }
+
+    #[test]
+    fn test_thumbnails_keep_aspect_ratio() {
+        let (thumbnail_png, width, height) = thumbnail(&png(800, 400), THUMBNAIL_SIDE).unwrap();
+        assert_eq!((width, height), (320, 160));
+        assert_eq!(image_type(&thumbnail_png), Some("image/png"));
+
+        // Small images are not scaled up
+        let (_, width, height) = thumbnail(&png(40, 60), THUMBNAIL_SIDE).unwrap();
+        assert_eq!((width, height), (40, 60));
+
+        assert!(thumbnail(b"not an image", THUMBNAIL_SIDE).is_err());
+    }
+
+    #[test]
+    fn test_oversized_images_are_refused_before_decoding() {
+        // A GIF header claiming 60000×60000 pixels with no image data
+        let mut gif = b"GIF89a".to_vec();
+        gif.extend_from_slice(&60_000u16.to_le_bytes());
+        gif.extend_from_slice(&60_000u16.to_le_bytes());
+        gif.extend_from_slice(&[0, 0, 0]);
+        assert_eq!(dimensions(&gif, "image/gif"), Some((60_000, 60_000)));
+        assert!(thumbnail(&gif, THUMBNAIL_SIDE).is_err());
+    }
+
+    #[test]
+    fn test_preview_sources() {
+        assert_eq!(source("image/jpeg", "photo.jpg"), Some(Source::Image));
+        assert_eq!(source("application/octet-stream", "scan.PNG"), Some(Source::Image));
+        assert_eq!(source("application/pdf", "q1.pdf"), Some(Source::Pdf));
+        assert_eq!(source("application/octet-stream", "letter.docx"), Some(Source::Document(DocumentKind::Docx)));
+        assert_eq!(source("text/plain; charset=utf-8", "notes"), Some(Source::Document(DocumentKind::Text)));
+        assert!(!can_preview("application/zip", "archive.zip"));
+    }
+
+    #[test]
+    fn test_excerpt_collapses_blank_lines_and_truncates() {
+        assert_eq!(excerpt("\n\nDear Ada,\n\n\n\nThe numbers.\n"), "Dear Ada,\n\nThe numbers.");
+        let long = "word ".repeat(1000);
+        let cut = excerpt(&long);
+        assert!(cut.ends_with('…'));
+        assert!(cut.chars().count() <= EXCERPT_CHARS + 1);
+    }
+
+    #[tokio::test]
+    async fn test_previews_are_cached() {
+        let dir = std::env::temp_dir().join(format!("libreollama-previews-{}", uuid::Uuid::new_v4()));
+        let previewer = AttachmentPreviewer::new(dir.clone());
+        let text = b"Agenda\n\n1. Budget\n2. Hiring".to_vec();
+
+        let first = previewer.preview("acc\nm-1\na-1", "text/plain", "agenda.txt", Some(26), async { Ok(text) }).await.unwrap().unwrap();
+        assert_eq!((first.kind, first.from_cache), (PreviewKind::Text, false));
+        assert_eq!(first.excerpt.as_deref(), Some("Agenda\n\n1. Budget\n2. Hiring"));
+
+        let download = async { Err(LibreOllamaError::Internal { message: "cached previews are not downloaded".to_string() }) };
+        let second = previewer.preview("acc\nm-1\na-1", "text/plain", "agenda.txt", Some(26), download).await.unwrap().unwrap();
+        assert!(second.from_cache);
+        assert_eq!(second.excerpt, first.excerpt);
+
+        let unsupported = previewer.preview("acc\nm-1\na-2", "application/zip", "x.zip", None, async { Ok(Vec::new()) }).await.unwrap();
+        assert!(unsupported.is_none());
+
+        // Listed as too large, so never downloaded
+        let download = async { Err(LibreOllamaError::Internal { message: "oversized attachments are not downloaded".to_string() }) };
+        let oversized = previewer
+            .preview("acc\nm-1\na-3", "text/plain", "dump.txt", Some(MAX_PREVIEW_SOURCE_BYTES + 1), download)
+            .await
+            .unwrap();
+        assert!(oversized.is_none());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,211 @@
+//! "Did you forget the attachment?" check before sending
+//!
+//! A message with no attachments whose new text talks about one ("see
+//! attached", "anbei", "pièce jointe", a CV...) gets a soft warning. Only
+//! the text written in this message counts; quoted history is dropped first
+//! so replies to a message that had an attachment are not flagged. When the
+//! model check is turned on, the classification model gets the last word on
+//! keyword hits, which cuts out false alarms like "the attached file I sent
+//! last week". Model failures fall back to the keyword result. Sending runs
+//! the check too and holds the message back with an `attachment_missing`
+//! error until the user confirms.
+
+use regex::Regex;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::database::DatabaseManager;
+use crate::errors::{CommandError, LibreOllamaError, Result};
+use crate::services::gmail::api_service::{split_reply_chain, SegmentKind};
+use crate::services::gmail::compose_service::ComposeRequest;
+use crate::services::i18n;
+use crate::services::llm_provider::{self, ChatMessage, ChatOptions, LlmFeature};
+use crate::services::prompt_templates;
+use crate::services::settings;
+
+/// Setting for confirming keyword hits with the model
+pub const ATTACHMENT_REMINDER_LLM_PREFERENCE: &str = "gmail.attachment_reminder_llm";
+/// `CommandError` code of a send held back by the reminder
+pub const ATTACHMENT_MISSING: &str = "attachment_missing";
+/// Prompt template feature for the model check
+const CHECK_PROMPT_FEATURE: &str = "mail.attachment_check";
+
+lazy_static::lazy_static! {
+    /// Attachment mentions in English, German, French, Spanish, Italian,
+    /// Portuguese and Dutch
+    static ref ATTACHMENT_MENTION: Regex = Regex::new(
+        r"(?i)\b(attached|attachments?|attaching|enclosed|anbei|angeh[äa]ngt|anhang|beigef[üu]gt|lebenslauf|ci-joint|pi[èe]ces? jointes?|adjunt[oa]s?|curr[íi]culum|allegat[oi]|in allegato|anexad[oa]s?|em anexo|bijlage|bijgevoegd|résumé|résume|resumé)\b"
+    ).unwrap();
+    /// "CV" only in capitals; lower case is too common inside other text
+    static ref CV_MENTION: Regex = Regex::new(r"\bCVs?\b").unwrap();
+    static ref QUOTED_HTML: Regex =
+        Regex::new(r#"(?is)<blockquote\b.*?</blockquote>|<div class="gmail_quote".*$"#).unwrap();
+    static ref HTML_TAG: Regex = Regex::new(r"(?s)<[^>]*>").unwrap();
+}
+
+/// Soft warning shown before the final send
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachmentReminder {
+    /// The words that suggested an attachment
+    pub phrase: String,
+    pub message: String,
+    /// Whether the model agreed an attachment is expected
+    pub confirmed_by_model: bool,
+}
+
+pub fn llm_check_enabled(conn: &Connection) -> anyhow::Result<bool> {
+    settings::get_bool(conn, ATTACHMENT_REMINDER_LLM_PREFERENCE)
+}
+
+impl AttachmentReminder {
+    /// The error `send_gmail_message` returns until the user confirms
+    /// sending without an attachment
+    pub fn into_error(self) -> CommandError {
+        CommandError {
+            code: ATTACHMENT_MISSING.to_string(),
+            category: "gmail".to_string(),
+            user_message: self.message,
+            retryable: false,
+            retry_after_ms: None,
+            details: Some(format!("Message mentions \"{}\" but has no attachment", self.phrase)),
+        }
+    }
+}
+
+/// The text written in this message, without quotes and signature
+pub fn new_content(compose: &ComposeRequest) -> String {
+    let body = match (&compose.body_text, &compose.body_html) {
+        (Some(text), _) => text.clone(),
+        (None, Some(html)) => {
+            let html = QUOTED_HTML.replace_all(html, "");
+            HTML_TAG.replace_all(&html.replace("<br>", "\n"), " ").replace("&nbsp;", " ")
+        }
+        (None, None) => String::new(),
+    };
+
+    split_reply_chain(&body)
+        .into_iter()
+        .filter(|segment| segment.kind == SegmentKind::Content)
+        .map(|segment| segment.text)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The first attachment mention in the subject or new text
+pub fn find_mention(subject: &str, text: &str) -> Option<String> {
+    [subject, text].iter().find_map(|haystack| {
+        ATTACHMENT_MENTION
+            .find(haystack)
+            .or_else(|| CV_MENTION.find(haystack))
+            .map(|m| m.as_str().to_string())
+    })
+}
+
+/// Check a message about to be sent; None when nothing looks missing
+pub async fn check(db_manager: &DatabaseManager, compose: &ComposeRequest) -> Option<AttachmentReminder> {
+    let has_attachments = compose.attachments.iter().flatten().any(|a| !a.is_inline);
+    if has_attachments {
+        return None;
+    }
+
+    let text = new_content(compose);
+    let phrase = find_mention(&compose.subject, &text)?;
+
+    let use_llm = db_manager
+        .get_connection()
+        .and_then(|conn| llm_check_enabled(&conn))
+        .unwrap_or(false);
+    let mut confirmed_by_model = false;
+    if use_llm {
+        match expects_attachment(db_manager, &compose.subject, &text).await {
+            Ok(false) => return None,
+            Ok(true) => confirmed_by_model = true,
+            Err(e) => eprintln!("⚠️ [MAIL] Attachment check fell back to keywords: {}", e),
+        }
+    }
+
+    Some(AttachmentReminder {
+        message: i18n::t_args("attachment-reminder", &[("phrase", phrase.clone().into())]),
+        phrase,
+        confirmed_by_model,
+    })
+}
+
+#[derive(Deserialize)]
+struct LlmVerdict {
+    expects_attachment: bool,
+}
+
+/// Ask the classification model whether the sender meant to attach something
+async fn expects_attachment(db_manager: &DatabaseManager, subject: &str, text: &str) -> Result<bool> {
+    let options = ChatOptions { temperature: Some(0.0), json: true, ..Default::default() };
+    let prompt = prompt_templates::render_feature_prompt(
+        &db_manager.get_connection()?,
+        CHECK_PROMPT_FEATURE,
+        &serde_json::Map::new(),
+    )?;
+    let completion = llm_provider::complete_with(
+        db_manager,
+        LlmFeature::Classification,
+        &[ChatMessage::system(prompt), ChatMessage::user(format!("Subject: {}\n\n{}", subject, text))],
+        &options,
+    )
+    .await?;
+
+    let verdict: LlmVerdict = serde_json::from_str(completion.content.trim()).map_err(|e| LibreOllamaError::Serialization {
+        message: format!("Model did not return an attachment verdict: {}", e),
+        data_type: "attachment reminder verdict".to_string(),
+    })?;
+    Ok(verdict.expects_attachment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::gmail::compose_service::MessageImportance;
+
+    fn compose(body_text: &str) -> ComposeRequest {
+        ComposeRequest {
+            account_id: "acc".to_string(),
+            to: Vec::new(),
+            cc: None,
+            bcc: None,
+            subject: "Hello".to_string(),
+            body_text: Some(body_text.to_string()),
+            body_html: None,
+            attachments: None,
+            reply_to_message_id: None,
+            in_reply_to: None,
+            references: None,
+            thread_id: None,
+            importance: MessageImportance::Normal,
+            delivery_receipt: false,
+            read_receipt: false,
+            schedule_send: None,
+            from_alias: None,
+        }
+    }
+
+    #[test]
+    fn finds_mentions_in_several_languages() {
+        assert_eq!(find_mention("", "Please see the attached report."), Some("attached".to_string()));
+        assert_eq!(find_mention("", "Anbei die Unterlagen."), Some("Anbei".to_string()));
+        assert_eq!(find_mention("", "Voir la pièce jointe."), Some("pièce jointe".to_string()));
+        assert_eq!(find_mention("My CV", ""), Some("CV".to_string()));
+        assert_eq!(find_mention("", "Mi currículum vitae"), Some("currículum".to_string()));
+        assert_eq!(find_mention("Lunch", "We can resume after lunch."), None);
+    }
+
+    #[test]
+    fn ignores_mentions_in_quoted_history() {
+        let reply = compose("Thanks, got it.\n\nOn Mon, Sep 1, 2026, Alice wrote:\n> See the attached file.");
+        assert_eq!(find_mention(&reply.subject, &new_content(&reply)), None);
+
+        let html = ComposeRequest {
+            body_text: None,
+            body_html: Some("<p>Thanks!</p><blockquote>attached below</blockquote>".to_string()),
+            ..compose("")
+        };
+        assert_eq!(find_mention(&html.subject, &new_content(&html)), None);
+    }
+}
@@ -0,0 +1,373 @@
+//! Attachment type sniffing and risk warnings
+//!
+//! The MIME type and extension of an attachment are whatever the sender
+//! wrote. When the content is at hand its first bytes are checked against
+//! known file signatures, so an executable named `invoice.pdf` is caught.
+//! Gmail leaves attachment content out of the message, so small ones are
+//! fetched for this; larger ones are judged on name and declared type only. The result travels with the
+//! attachment metadata as `risk`.
+
+use serde::{Deserialize, Serialize};
+
+/// Extensions Windows, macOS or Linux will run or install when opened
+const EXECUTABLE_EXTENSIONS: &[&str] = &[
+    "exe", "com", "scr", "pif", "cpl", "dll", "msi", "msp", "msix", "appx", "bat", "cmd", "ps1", "psm1", "vbs",
+    "vbe", "js", "jse", "wsf", "wsh", "hta", "lnk", "reg", "jar", "sh", "command", "app", "pkg", "apk", "deb", "rpm",
+];
+/// Disk images mount with one click and are a common way past mail filters
+const DISK_IMAGE_EXTENSIONS: &[&str] = &["iso", "img", "vhd", "vhdx", "dmg"];
+/// Office formats that can carry macros
+const MACRO_EXTENSIONS: &[&str] = &["docm", "dotm", "xlsm", "xltm", "xlam", "pptm", "potm", "ppsm"];
+
+/// Types told apart by their first bytes. Office Open XML, OpenDocument,
+/// JAR and APK files are all ZIP archives; legacy Office files and MSI
+/// installers share the OLE compound format.
+const SIGNATURES: &[(&[u8], &str)] = &[
+    (b"%PDF-", "application/pdf"),
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"PK\x05\x06", "application/zip"),
+    (b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1", "application/x-ole-storage"),
+    (b"Rar!\x1a\x07", "application/vnd.rar"),
+    (b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+    (b"\x1f\x8b", "application/gzip"),
+    (b"{\\rtf", "application/rtf"),
+    (b"MZ", "application/x-msdownload"),
+    (b"\x7fELF", "application/x-executable"),
+    (b"\xcf\xfa\xed\xfe", "application/x-mach-binary"),
+    (b"\xce\xfa\xed\xfe", "application/x-mach-binary"),
+    (b"\xca\xfe\xba\xbe", "application/x-mach-binary"),
+    (b"#!", "text/x-shellscript"),
+    (b"L\x00\x00\x00\x01\x14\x02\x00", "application/x-ms-shortcut"),
+];
+
+/// Detected types that run when opened, whatever the file is called
+const EXECUTABLE_TYPES: &[&str] = &[
+    "application/x-msdownload",
+    "application/x-executable",
+    "application/x-mach-binary",
+    "application/x-ms-shortcut",
+    "text/x-shellscript",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskLevel {
+    None,
+    /// Worth a second look, such as a macro-enabled document
+    Caution,
+    /// Runs code when opened, or pretends to be something it is not
+    Dangerous,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RiskReason {
+    /// The extension is one that runs or installs software
+    ExecutableExtension { extension: String },
+    DiskImage { extension: String },
+    MacroEnabled { extension: String },
+    /// A harmless-looking extension in front of the real one, as in `scan.pdf.exe`
+    DoubleExtension { shown: String, actual: String },
+    /// The content is executable regardless of the name
+    ExecutableContent { detected_type: String },
+    /// The content is not what the extension or MIME type claims
+    TypeMismatch { declared: String, detected_type: String },
+}
+
+/// Broad kinds of file a mismatch can stay within. A PNG sent as `.jpg`
+/// or an RTF saved as `.docx` is sloppy rather than hostile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Family {
+    Image,
+    Office,
+}
+
+/// Family of a declared `.extension` or MIME type
+fn declared_family(declared: &str) -> Option<Family> {
+    let declared = declared.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    if let Some(extension) = declared.strip_prefix('.') {
+        return match extension {
+            "png" | "jpg" | "jpeg" | "gif" => Some(Family::Image),
+            "doc" | "docx" | "xls" | "xlsx" | "ppt" | "pptx" | "odt" | "ods" | "odp" | "rtf" => Some(Family::Office),
+            _ => None,
+        };
+    }
+    if declared.starts_with("image/") {
+        Some(Family::Image)
+    } else if declared == "application/msword"
+        || declared == "text/rtf"
+        || declared == "application/rtf"
+        || declared.starts_with("application/vnd.ms-")
+        || declared.starts_with("application/vnd.openxmlformats")
+        || declared.starts_with("application/vnd.oasis.opendocument")
+    {
+        Some(Family::Office)
+    } else {
+        None
+    }
+}
+
+/// Family of a sniffed type. ZIP and OLE content only count as office
+/// documents when the declared side already claimed one.
+fn detected_family(detected: &str, declared: Option<Family>) -> Option<Family> {
+    match detected {
+        "image/png" | "image/jpeg" | "image/gif" => Some(Family::Image),
+        "application/rtf" => Some(Family::Office),
+        "application/zip" | "application/x-ole-storage" if declared == Some(Family::Office) => Some(Family::Office),
+        _ => None,
+    }
+}
+
+impl RiskReason {
+    fn level(&self) -> RiskLevel {
+        match self {
+            RiskReason::MacroEnabled { .. } | RiskReason::DiskImage { .. } => RiskLevel::Caution,
+            RiskReason::TypeMismatch { declared, detected_type } => {
+                let declared = declared_family(declared);
+                if declared.is_some() && detected_family(detected_type, declared) == declared {
+                    RiskLevel::Caution
+                } else {
+                    RiskLevel::Dangerous
+                }
+            }
+            _ => RiskLevel::Dangerous,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttachmentRisk {
+    pub level: RiskLevel,
+    /// Type found from the content; absent when it was not available or
+    /// matched no known signature
+    pub detected_type: Option<String>,
+    pub reasons: Vec<RiskReason>,
+}
+
+/// "MZ" alone also starts plenty of text; a Windows executable's DOS header
+/// points a short way on to a "PE" header, checked when that much of the
+/// file is present
+fn is_portable_executable(data: &[u8]) -> bool {
+    const MAX_HEADER_OFFSET: usize = 0x10000;
+    let Some(offset) = data.get(0x3c..0x40).map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+    else {
+        return false;
+    };
+    offset < MAX_HEADER_OFFSET && data.get(offset..offset + 4).is_none_or(|header| header == b"PE\0\0")
+}
+
+/// Content type from the first bytes, if they match a known signature
+pub fn sniff(data: &[u8]) -> Option<&'static str> {
+    SIGNATURES
+        .iter()
+        .find(|(magic, mime_type)| {
+            data.starts_with(magic) && (*mime_type != "application/x-msdownload" || is_portable_executable(data))
+        })
+        .map(|(_, mime_type)| *mime_type)
+}
+
+fn extensions(filename: &str) -> Vec<String> {
+    let name = filename.rsplit(['/', '\\']).next().unwrap_or_default().trim_end_matches([' ', '.']);
+    name.split('.').skip(1).map(str::to_ascii_lowercase).collect()
+}
+
+/// Detected types an extension is honestly allowed to have
+fn expected_types(extension: &str) -> Option<&'static [&'static str]> {
+    Some(match extension {
+        "pdf" => &["application/pdf"],
+        "png" => &["image/png"],
+        "jpg" | "jpeg" => &["image/jpeg"],
+        "gif" => &["image/gif"],
+        "zip" | "docx" | "xlsx" | "pptx" | "odt" | "ods" | "odp" | "epub" | "jar" | "apk" | "msix" | "appx" => {
+            &["application/zip"]
+        }
+        "docm" | "dotm" | "xlsm" | "xltm" | "xlam" | "pptm" | "potm" | "ppsm" => &["application/zip"],
+        // Word happily saves RTF under .doc
+        "doc" => &["application/x-ole-storage", "application/rtf"],
+        "xls" | "ppt" | "msg" | "msi" | "msp" => &["application/x-ole-storage"],
+        "rar" => &["application/vnd.rar"],
+        "7z" => &["application/x-7z-compressed"],
+        "gz" | "tgz" => &["application/gzip"],
+        "rtf" => &["application/rtf"],
+        "txt" | "csv" => &[],
+        "exe" | "dll" | "scr" | "com" | "cpl" | "sys" => &["application/x-msdownload"],
+        _ => return None,
+    })
+}
+
+/// Declared MIME types whose content should match a detected type
+fn declared_type_matches(declared: &str, detected: &str) -> bool {
+    let declared = declared.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    if declared.is_empty() || declared == "application/octet-stream" || declared == detected {
+        return true;
+    }
+    match detected {
+        "image/jpeg" => declared == "image/jpg" || declared == "image/pjpeg",
+        "application/zip" => {
+            declared.starts_with("application/vnd.openxmlformats")
+                || declared.starts_with("application/vnd.oasis.opendocument")
+                || declared.starts_with("application/vnd.ms-")
+                || declared.contains("zip")
+                || declared == "application/epub+zip"
+                || declared == "application/java-archive"
+                || declared == "application/vnd.android.package-archive"
+        }
+        "application/x-ole-storage" => {
+            declared == "application/msword" || declared.starts_with("application/vnd.ms-") || declared == "application/x-msi"
+        }
+        "application/vnd.rar" => declared.contains("rar"),
+        "application/gzip" => declared.contains("gzip"),
+        "application/rtf" => declared == "text/rtf" || declared == "application/msword",
+        _ => false,
+    }
+}
+
+/// Judge an attachment from its name, declared type and, when given, its
+/// first bytes
+pub fn assess(filename: Option<&str>, declared_type: &str, data: Option<&[u8]>) -> AttachmentRisk {
+    let extensions = filename.map(extensions).unwrap_or_default();
+    let extension = extensions.last().map(String::as_str).unwrap_or_default();
+    let detected_type = data.and_then(sniff);
+    let mut reasons = Vec::new();
+
+    if EXECUTABLE_EXTENSIONS.contains(&extension) {
+        reasons.push(RiskReason::ExecutableExtension { extension: extension.to_string() });
+        if let [.., shown, _] = extensions.as_slice() {
+            if expected_types(shown).is_some() {
+                reasons.push(RiskReason::DoubleExtension { shown: shown.clone(), actual: extension.to_string() });
+            }
+        }
+    } else if DISK_IMAGE_EXTENSIONS.contains(&extension) {
+        reasons.push(RiskReason::DiskImage { extension: extension.to_string() });
+    } else if MACRO_EXTENSIONS.contains(&extension) {
+        reasons.push(RiskReason::MacroEnabled { extension: extension.to_string() });
+    }
+
+    if let Some(detected) = detected_type {
+        let executable = EXECUTABLE_TYPES.contains(&detected);
+        let named_executable = EXECUTABLE_EXTENSIONS.contains(&extension)
+            || expected_types(extension).is_some_and(|types| types.contains(&detected));
+        if executable && !named_executable {
+            reasons.push(RiskReason::ExecutableContent { detected_type: detected.to_string() });
+        }
+        let extension_mismatch = expected_types(extension).is_some_and(|types| !types.contains(&detected));
+        if extension_mismatch {
+            reasons.push(RiskReason::TypeMismatch { declared: format!(".{}", extension), detected_type: detected.to_string() });
+        } else if !declared_type_matches(declared_type, detected) {
+            reasons.push(RiskReason::TypeMismatch { declared: declared_type.to_string(), detected_type: detected.to_string() });
+        }
+    }
+
+    AttachmentRisk {
+        level: reasons.iter().map(RiskReason::level).max().unwrap_or(RiskLevel::None),
+        detected_type: detected_type.map(str::to_string),
+        reasons,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// DOS header pointing at a PE header right after it
+    fn executable() -> Vec<u8> {
+        let mut data = vec![0u8; 0x48];
+        data[..2].copy_from_slice(b"MZ");
+        data[0x3c] = 0x40;
+        data[0x40..0x44].copy_from_slice(b"PE\0\0");
+        data
+    }
+
+    #[test]
+    fn test_sniffs_common_signatures() {
+        assert_eq!(sniff(b"%PDF-1.7\n"), Some("application/pdf"));
+        assert_eq!(sniff(b"PK\x03\x04\x14\x00"), Some("application/zip"));
+        assert_eq!(sniff(&executable()), Some("application/x-msdownload"));
+        assert_eq!(sniff(b"MZ Holdings quarterly figures, all regions, prepared for the board meeting"), None);
+        assert_eq!(sniff(b"\xff\xd8\xff\xe0"), Some("image/jpeg"));
+        assert_eq!(sniff(b"Hello"), None);
+        assert_eq!(sniff(b""), None);
+    }
+
+    #[test]
+    fn test_honest_attachments_carry_no_risk() {
+        let pdf = assess(Some("Q1 report.pdf"), "application/pdf", Some(b"%PDF-1.4"));
+        assert_eq!((pdf.level, pdf.detected_type.as_deref()), (RiskLevel::None, Some("application/pdf")));
+
+        let docx = assess(
+            Some("letter.docx"),
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            Some(b"PK\x03\x04"),
+        );
+        assert_eq!(docx.level, RiskLevel::None);
+
+        // Unknown content and octet-stream say nothing either way
+        assert_eq!(assess(Some("notes.txt"), "text/plain", Some(b"hello")).level, RiskLevel::None);
+        assert_eq!(assess(Some("photo.jpg"), "application/octet-stream", Some(b"\xff\xd8\xff")).level, RiskLevel::None);
+        assert_eq!(assess(None, "image/png", None).level, RiskLevel::None);
+    }
+
+    #[test]
+    fn test_executable_disguised_as_pdf() {
+        let risk = assess(Some("invoice.pdf"), "application/pdf", Some(&executable()));
+        assert_eq!(risk.level, RiskLevel::Dangerous);
+        assert!(risk.reasons.contains(&RiskReason::ExecutableContent { detected_type: "application/x-msdownload".to_string() }));
+        assert!(risk.reasons.contains(&RiskReason::TypeMismatch {
+            declared: ".pdf".to_string(),
+            detected_type: "application/x-msdownload".to_string(),
+        }));
+    }
+
+    #[test]
+    fn test_dangerous_names_without_content() {
+        let risk = assess(Some("scan.PDF.exe"), "application/pdf", None);
+        assert_eq!(risk.level, RiskLevel::Dangerous);
+        assert_eq!(
+            risk.reasons,
+            vec![
+                RiskReason::ExecutableExtension { extension: "exe".to_string() },
+                RiskReason::DoubleExtension { shown: "pdf".to_string(), actual: "exe".to_string() },
+            ]
+        );
+
+        assert_eq!(assess(Some("budget.xlsm"), "application/vnd.ms-excel.sheet.macroEnabled.12", None).level, RiskLevel::Caution);
+        assert_eq!(assess(Some("photos.iso"), "application/octet-stream", None).level, RiskLevel::Caution);
+        // A plain installer is dangerous but not disguised
+        let setup = assess(Some("setup.exe"), "application/x-msdownload", Some(&executable()));
+        assert_eq!(setup.reasons, vec![RiskReason::ExecutableExtension { extension: "exe".to_string() }]);
+    }
+
+    #[test]
+    fn test_declared_type_mismatch() {
+        let risk = assess(Some("image"), "image/png", Some(b"PK\x03\x04"));
+        assert_eq!(risk.level, RiskLevel::Dangerous);
+        assert_eq!(
+            risk.reasons,
+            vec![RiskReason::TypeMismatch { declared: "image/png".to_string(), detected_type: "application/zip".to_string() }]
+        );
+    }
+
+    #[test]
+    fn test_mismatch_within_a_family_is_only_caution() {
+        let png_as_jpg = assess(Some("photo.jpg"), "image/jpeg", Some(b"\x89PNG\r\n\x1a\n"));
+        assert_eq!(png_as_jpg.level, RiskLevel::Caution);
+
+        let rtf_as_docx = assess(
+            Some("letter.docx"),
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            Some(b"{\\rtf1"),
+        );
+        assert_eq!(rtf_as_docx.level, RiskLevel::Caution);
+
+        let ole_as_xlsx = assess(Some("budget.xlsx"), "application/vnd.ms-excel", Some(b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1"));
+        assert_eq!(ole_as_xlsx.level, RiskLevel::Caution);
+
+        // Crossing families stays dangerous
+        assert_eq!(assess(Some("photo.jpg"), "image/jpeg", Some(b"%PDF-1.4")).level, RiskLevel::Dangerous);
+        assert_eq!(assess(Some("letter.doc"), "application/msword", Some(b"\x89PNG\r\n\x1a\n")).level, RiskLevel::Dangerous);
+    }
+}
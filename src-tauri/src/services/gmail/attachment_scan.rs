@@ -0,0 +1,288 @@
+//! Attachment virus scanning
+//!
+//! Before an attachment is opened it can be checked with a locally
+//! installed scanner: ClamAV (`clamdscan`, falling back to `clamscan` when
+//! the daemon is not running) or the Windows Defender command line tool.
+//! Nothing is uploaded anywhere. The scanner is picked by
+//! `mail.attachment_scan`; "auto" uses whichever is installed, and with no
+//! scanner present attachments open unscanned as before. A file the
+//! scanner could not check is held back like an infected one.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+use tokio::sync::OnceCell;
+
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::settings;
+
+/// Which scanner checks attachments before they are opened
+pub const ATTACHMENT_SCAN_SETTING: &str = "mail.attachment_scan";
+pub const SCANNER_CHOICES: &[&str] = &["auto", "clamav", "defender", "off"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Clean,
+    Infected,
+    /// The scanner ran but could not give an answer
+    Error,
+}
+
+impl Verdict {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Verdict::Clean => "clean",
+            Verdict::Infected => "infected",
+            Verdict::Error => "error",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "clean" => Some(Verdict::Clean),
+            "infected" => Some(Verdict::Infected),
+            "error" => Some(Verdict::Error),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanResult {
+    pub verdict: Verdict,
+    pub engine: String,
+    /// Threat name for infected files, the scanner's message for errors
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Engine {
+    Clamd,
+    ClamScan,
+    Defender,
+}
+
+impl Engine {
+    fn name(&self) -> &'static str {
+        match self {
+            Engine::Clamd => "clamdscan",
+            Engine::ClamScan => "clamscan",
+            Engine::Defender => "defender",
+        }
+    }
+
+    /// The scanner's executable, if installed. Looked up once per run, off
+    /// the async runtime, since the ClamAV check starts a process.
+    async fn program(self) -> Option<PathBuf> {
+        PROGRAMS[self as usize]
+            .get_or_init(|| async move { tokio::task::spawn_blocking(move || self.find()).await.ok().flatten() })
+            .await
+            .clone()
+    }
+
+    fn find(&self) -> Option<PathBuf> {
+        match self {
+            Engine::Clamd | Engine::ClamScan => {
+                let program = self.name();
+                std::process::Command::new(program).arg("--version").output().is_ok().then(|| PathBuf::from(program))
+            }
+            Engine::Defender => defender_path(),
+        }
+    }
+
+    fn command(&self, program: &Path, file: &Path) -> Command {
+        let mut command = Command::new(program);
+        command.kill_on_drop(true);
+        match self {
+            // --fdpass lets the daemon read files its own user cannot
+            Engine::Clamd => command.args(["--no-summary", "--fdpass"]).arg(file),
+            Engine::ClamScan => command.arg("--no-summary").arg(file),
+            // -DisableRemediation leaves the file alone; we only want the verdict
+            Engine::Defender => command.args(["-Scan", "-ScanType", "3", "-DisableRemediation", "-File"]).arg(file),
+        };
+        command
+    }
+
+    fn verdict(&self, code: Option<i32>, stdout: &str, stderr: &str) -> ScanResult {
+        let (verdict, detail) = match self {
+            Engine::Clamd | Engine::ClamScan => clamav_verdict(code, stdout),
+            Engine::Defender => defender_verdict(code, stdout),
+        };
+        let detail = match verdict {
+            Verdict::Error => detail.or_else(|| Some(first_line(stderr).unwrap_or("scanner failed").to_string())),
+            _ => detail,
+        };
+        ScanResult { verdict, engine: self.name().to_string(), detail }
+    }
+}
+
+/// Where each `Engine` was found, by discriminant
+static PROGRAMS: [OnceCell<Option<PathBuf>>; 3] = [OnceCell::const_new(), OnceCell::const_new(), OnceCell::const_new()];
+
+/// Longest a scan may take; clamscan alone spends a while loading signatures
+const SCAN_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[cfg(windows)]
+fn defender_path() -> Option<PathBuf> {
+    let program_files = std::env::var_os("ProgramFiles")?;
+    let path = PathBuf::from(program_files).join("Windows Defender").join("MpCmdRun.exe");
+    path.is_file().then_some(path)
+}
+
+#[cfg(not(windows))]
+fn defender_path() -> Option<PathBuf> {
+    None
+}
+
+fn first_line(text: &str) -> Option<&str> {
+    text.lines().map(str::trim).find(|line| !line.is_empty())
+}
+
+/// clamscan and clamdscan exit 0 when clean, 1 when a virus was found and
+/// 2 on errors, printing `<path>: <signature> FOUND` for each hit
+fn clamav_verdict(code: Option<i32>, stdout: &str) -> (Verdict, Option<String>) {
+    match code {
+        Some(0) => (Verdict::Clean, None),
+        Some(1) => {
+            let threat = stdout
+                .lines()
+                .filter_map(|line| line.trim().strip_suffix(" FOUND"))
+                .filter_map(|line| line.rsplit_once(": ").map(|(_, signature)| signature.trim().to_string()))
+                .next();
+            (Verdict::Infected, threat)
+        }
+        _ => (Verdict::Error, first_line(stdout).map(str::to_string)),
+    }
+}
+
+/// MpCmdRun exits 0 when no threat was found and 2 when one was, listing
+/// `Threat : <name>` lines
+fn defender_verdict(code: Option<i32>, stdout: &str) -> (Verdict, Option<String>) {
+    match code {
+        Some(0) => (Verdict::Clean, None),
+        Some(2) => {
+            let threat = stdout
+                .lines()
+                .filter_map(|line| line.trim().split_once(':'))
+                .find(|(label, _)| label.trim().eq_ignore_ascii_case("threat"))
+                .map(|(_, name)| name.trim().to_string());
+            (Verdict::Infected, threat)
+        }
+        _ => (Verdict::Error, first_line(stdout).map(str::to_string)),
+    }
+}
+
+/// The `mail.attachment_scan` choice, "auto" unless changed
+pub fn scanner_choice(conn: &Connection) -> anyhow::Result<String> {
+    let setting = settings::get_setting(conn, ATTACHMENT_SCAN_SETTING)?;
+    Ok(setting.value.as_str().unwrap_or("auto").to_string())
+}
+
+/// Scanners to try for a `mail.attachment_scan` choice, in order
+fn engines(choice: &str) -> &'static [Engine] {
+    match choice {
+        "clamav" => &[Engine::Clamd, Engine::ClamScan],
+        "defender" => &[Engine::Defender],
+        "off" => &[],
+        _ => &[Engine::Defender, Engine::Clamd, Engine::ClamScan],
+    }
+}
+
+/// Name of the scanner `choice` would use, if one is installed
+pub async fn available_scanner(choice: &str) -> Option<&'static str> {
+    for engine in engines(choice) {
+        if engine.program().await.is_some() {
+            return Some(engine.name());
+        }
+    }
+    None
+}
+
+/// Scan `file` with the chosen scanner. `None` when scanning is off or no
+/// scanner is installed. A scanner that fails or takes longer than
+/// `SCAN_TIMEOUT` hands over to the next one, so a stopped ClamAV daemon
+/// falls back to `clamscan`; when none can answer the last error is returned.
+pub async fn scan_file(choice: &str, file: &Path) -> Result<Option<ScanResult>> {
+    let mut last = None;
+    for engine in engines(choice) {
+        let Some(program) = engine.program().await else {
+            continue;
+        };
+        let run = tokio::time::timeout(SCAN_TIMEOUT, engine.command(&program, file).output()).await;
+        let result = match run {
+            Ok(output) => {
+                let output = output.map_err(|e| LibreOllamaError::Internal {
+                    message: format!("Failed to run {}: {}", engine.name(), e),
+                })?;
+                engine.verdict(
+                    output.status.code(),
+                    &String::from_utf8_lossy(&output.stdout),
+                    &String::from_utf8_lossy(&output.stderr),
+                )
+            }
+            // The process is killed as the timed-out future is dropped
+            Err(_) => ScanResult {
+                verdict: Verdict::Error,
+                engine: engine.name().to_string(),
+                detail: Some(format!("timed out after {} seconds", SCAN_TIMEOUT.as_secs())),
+            },
+        };
+        if result.verdict != Verdict::Error {
+            return Ok(Some(result));
+        }
+        eprintln!("⚠️ [ATTACHMENT-SCAN] {} could not scan {}: {:?}", engine.name(), file.display(), result.detail);
+        last = Some(result);
+    }
+    Ok(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clamav_exit_codes() {
+        assert_eq!(clamav_verdict(Some(0), "/tmp/a.pdf: OK\n"), (Verdict::Clean, None));
+
+        let found = "/tmp/invoice.exe: Win.Test.EICAR_HDB-1 FOUND\n";
+        assert_eq!(clamav_verdict(Some(1), found), (Verdict::Infected, Some("Win.Test.EICAR_HDB-1".to_string())));
+
+        let error = "ERROR: Could not connect to clamd on LocalSocket /run/clamav/clamd.ctl\n";
+        let (verdict, detail) = clamav_verdict(Some(2), error);
+        assert_eq!(verdict, Verdict::Error);
+        assert!(detail.unwrap().contains("clamd"));
+        assert_eq!(clamav_verdict(None, "").0, Verdict::Error);
+    }
+
+    #[test]
+    fn test_defender_exit_codes() {
+        let clean = "Scan starting...\nScan finished.\nScanning C:\\a.pdf found no threats.\n";
+        assert_eq!(defender_verdict(Some(0), clean), (Verdict::Clean, None));
+
+        let found = "Scan starting...\nScan finished.\nScanning C:\\a.com found 1 threats.\n\n\
+                     <===========================LIST OF DETECTED THREATS==========================>\n\
+                     ----------------------------- Threat information ------------------------------\n\
+                     Threat                  : Virus:DOS/EICAR_Test_File\n\
+                     Resources               : 1 total\n";
+        assert_eq!(defender_verdict(Some(2), found), (Verdict::Infected, Some("Virus:DOS/EICAR_Test_File".to_string())));
+        assert_eq!(defender_verdict(Some(-2147024894), "").0, Verdict::Error);
+    }
+
+    #[test]
+    fn test_error_detail_falls_back_to_stderr() {
+        let result = Engine::ClamScan.verdict(Some(2), "", "\nLibClamAV Error: cl_load(): No such file or directory\n");
+        assert_eq!(result.verdict, Verdict::Error);
+        assert_eq!(result.detail.as_deref(), Some("LibClamAV Error: cl_load(): No such file or directory"));
+        assert_eq!(result.engine, "clamscan");
+    }
+
+    #[tokio::test]
+    async fn test_scanning_off_leaves_files_unscanned() {
+        assert!(available_scanner("off").await.is_none());
+        assert!(scan_file("off", Path::new("/nonexistent")).await.unwrap().is_none());
+    }
+}
@@ -11,7 +11,6 @@ use oauth2::{
     RedirectUrl, RefreshToken, RevocationUrl, Scope, TokenUrl,
     TokenResponse, // Import the trait to use token methods
 };
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -22,6 +21,9 @@ use crate::config::get_config_manager;
 use crate::database::connection::DatabaseManager;
 use crate::utils::crypto::{encrypt_data, decrypt_data};
 use crate::errors::{LibreOllamaError, Result};
+use crate::services::google::GoogleEndpoints;
+use crate::services::resource_budget;
+use crate::utils::http_client::http_client;
 
 /// Gmail OAuth2 scopes
 pub const GMAIL_SCOPES: &[&str] = &[
@@ -35,6 +37,19 @@ pub const GMAIL_SCOPES: &[&str] = &[
     "https://www.googleapis.com/auth/tasks",
 ];
 
+/// Broader Drive scope requested on demand to download files as attachments
+pub const DRIVE_READONLY_SCOPE: &str = "https://www.googleapis.com/auth/drive.readonly";
+
+/// Drive scope requested on demand to upload large attachments; it only
+/// covers files the app creates
+pub const DRIVE_FILE_SCOPE: &str = "https://www.googleapis.com/auth/drive.file";
+
+/// Contacts scope requested on demand for People API sync
+pub const CONTACTS_READONLY_SCOPE: &str = "https://www.googleapis.com/auth/contacts.readonly";
+
+/// Scopes an incremental authorization may add to `GMAIL_SCOPES`
+pub const ON_DEMAND_SCOPES: &[&str] = &[DRIVE_READONLY_SCOPE, DRIVE_FILE_SCOPE, CONTACTS_READONLY_SCOPE];
+
 /// Gmail OAuth2 endpoints
 pub const GMAIL_AUTH_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
 pub const GMAIL_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
@@ -117,11 +132,13 @@ struct PendingAuthorization {
 /// consolidating OAuth2 flow, secure token storage, and account management.
 #[derive(Debug, Clone)]
 pub struct GmailAuthService {
-    config: AuthConfig,
+    /// OAuth client settings; replaced when the configuration is reloaded
+    config: Arc<std::sync::RwLock<AuthConfig>>,
     pending_authorizations: Arc<RwLock<HashMap<String, PendingAuthorization>>>,
     callback_sender: Arc<RwLock<Option<oneshot::Sender<CallbackResult>>>>,
     db_manager: Arc<DatabaseManager>,
     encryption_key: [u8; 32],
+    token_url: String,
 }
 
 impl GmailAuthService {
@@ -133,10 +150,20 @@ impl GmailAuthService {
                 config_key: None,
             })?;
 
+        Self::with_oauth_config(db_manager, encryption_key, &config_manager.oauth())
+    }
+
+    /// Create the service with explicit OAuth client settings rather than
+    /// the loaded configuration
+    pub fn with_oauth_config(
+        db_manager: Arc<DatabaseManager>,
+        encryption_key: [u8; 32],
+        oauth: &crate::config::OAuthConfig,
+    ) -> Result<Self> {
         let auth_config = AuthConfig {
-            redirect_uri: config_manager.oauth().redirect_uri.clone(),
-            client_id: config_manager.oauth().client_id.clone(),
-            client_secret: config_manager.oauth().client_secret.clone(),
+            redirect_uri: oauth.redirect_uri.clone(),
+            client_id: oauth.client_id.clone(),
+            client_secret: oauth.client_secret.clone(),
         };
 
         // Validate configuration
@@ -148,25 +175,51 @@ impl GmailAuthService {
         }
 
         Ok(Self {
-            config: auth_config,
+            config: Arc::new(std::sync::RwLock::new(auth_config)),
             pending_authorizations: Arc::new(RwLock::new(HashMap::new())),
             callback_sender: Arc::new(RwLock::new(None)),
             db_manager,
             encryption_key,
+            token_url: GMAIL_TOKEN_URL.to_string(),
         })
     }
 
+    /// Exchange and refresh tokens at `endpoints` instead of Google
+    pub fn with_endpoints(mut self, endpoints: &GoogleEndpoints) -> Self {
+        self.token_url = endpoints.oauth_token.clone();
+        self
+    }
+
+    fn auth_config(&self) -> AuthConfig {
+        self.config.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Use new OAuth client settings from a reloaded configuration.
+    /// Authorizations already in progress finish with the old client.
+    pub fn apply_oauth_config(&self, oauth: &crate::config::OAuthConfig) {
+        if oauth.client_id.is_empty() || oauth.client_secret.is_empty() {
+            eprintln!("⚠️ [AUTH] Ignoring OAuth config without client credentials");
+            return;
+        }
+        *self.config.write().unwrap_or_else(|e| e.into_inner()) = AuthConfig {
+            redirect_uri: oauth.redirect_uri.clone(),
+            client_id: oauth.client_id.clone(),
+            client_secret: oauth.client_secret.clone(),
+        };
+    }
+
     /// Create OAuth2 client with proper configuration
     fn create_oauth_client(&self, redirect_uri: &str) -> Result<BasicClient> {
+        let config = self.auth_config();
         Ok(BasicClient::new(
-            ClientId::new(self.config.client_id.clone()),
-            Some(ClientSecret::new(self.config.client_secret.clone())),
+            ClientId::new(config.client_id),
+            Some(ClientSecret::new(config.client_secret)),
             AuthUrl::new(GMAIL_AUTH_URL.to_string())
                 .map_err(|e| LibreOllamaError::OAuth {
                     message: format!("Invalid auth URL: {}", e),
                     step: "client_creation".to_string(),
                 })?,
-            Some(TokenUrl::new(GMAIL_TOKEN_URL.to_string())
+            Some(TokenUrl::new(self.token_url.clone())
                 .map_err(|e| LibreOllamaError::OAuth {
                     message: format!("Invalid token URL: {}", e),
                     step: "client_creation".to_string(),
@@ -186,20 +239,43 @@ impl GmailAuthService {
 
     /// Start OAuth2 authorization flow with PKCE
     pub async fn start_authorization(&self, redirect_uri: Option<String>) -> Result<AuthorizationRequest> {
-        let redirect_uri = redirect_uri.unwrap_or_else(|| self.config.redirect_uri.clone());
+        self.start_authorization_with_scopes(redirect_uri, &[], None).await
+    }
+
+    /// Start an incremental authorization that adds `extra_scopes` (from
+    /// `ON_DEMAND_SCOPES`) on top of the scopes the account has already
+    /// granted. `login_hint` preselects an address in Google's account
+    /// chooser.
+    pub async fn start_authorization_with_scopes(
+        &self,
+        redirect_uri: Option<String>,
+        extra_scopes: &[&str],
+        login_hint: Option<&str>,
+    ) -> Result<AuthorizationRequest> {
+        if let Some(scope) = extra_scopes.iter().find(|scope| !ON_DEMAND_SCOPES.contains(scope)) {
+            return Err(LibreOllamaError::InvalidInput {
+                message: format!("Scope {} cannot be requested", scope),
+                field: Some("extra_scopes".to_string()),
+            });
+        }
+        let redirect_uri = redirect_uri.unwrap_or_else(|| self.auth_config().redirect_uri);
         let client = self.create_oauth_client(&redirect_uri)?;
 
         // Generate PKCE challenge for security
         let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
 
         // Generate CSRF token for state verification
-        let (auth_url, csrf_token) = client
+        let mut request = client
             .authorize_url(CsrfToken::new_random)
-            .add_scopes(GMAIL_SCOPES.iter().map(|&s| Scope::new(s.to_string())))
+            .add_scopes(GMAIL_SCOPES.iter().chain(extra_scopes.iter()).map(|&s| Scope::new(s.to_string())))
             .set_pkce_challenge(pkce_challenge)
             .add_extra_param("access_type", "offline")
             .add_extra_param("prompt", "consent")
-            .url();
+            .add_extra_param("include_granted_scopes", "true");
+        if let Some(login_hint) = login_hint {
+            request = request.add_extra_param("login_hint", login_hint.to_string());
+        }
+        let (auth_url, csrf_token) = request.url();
 
         // Store pending authorization securely
         let state = csrf_token.secret().clone();
@@ -233,7 +309,7 @@ impl GmailAuthService {
         state: String,
         redirect_uri: Option<String>,
     ) -> Result<GmailTokenResponse> {
-        let redirect_uri = redirect_uri.unwrap_or_else(|| self.config.redirect_uri.clone());
+        let redirect_uri = redirect_uri.unwrap_or_else(|| self.auth_config().redirect_uri);
 
         // Retrieve and validate pending authorization
         let pending = {
@@ -283,7 +359,7 @@ impl GmailAuthService {
         refresh_token: String,
         redirect_uri: Option<String>,
     ) -> Result<GmailTokenResponse> {
-        let redirect_uri = redirect_uri.unwrap_or_else(|| self.config.redirect_uri.clone());
+        let redirect_uri = redirect_uri.unwrap_or_else(|| self.auth_config().redirect_uri);
         let client = self.create_oauth_client(&redirect_uri)?;
         
         let token_result = client
@@ -309,7 +385,7 @@ impl GmailAuthService {
 
     /// Get user information using access token
     pub async fn get_user_info(&self, access_token: &str) -> Result<UserInfo> {
-        let client = Client::new();
+        let client = http_client();
         let response = client
             .get(GMAIL_USERINFO_URL)
             .bearer_auth(access_token)
@@ -501,7 +577,7 @@ impl GmailAuthService {
 
     /// Revoke a token (access or refresh)
     pub async fn revoke_token(&self, token: String) -> Result<()> {
-        let client = Client::new();
+        let client = http_client();
         let response = client
             .post(GMAIL_REVOKE_URL)
             .form(&[("token", token.as_str())])
@@ -592,6 +668,7 @@ impl GmailAuthService {
             message: format!("Failed to delete account: {}", e),
             query_type: "delete".to_string(),
         })?;
+        resource_budget::clear_account(account_id);
 
         Ok(())
     }
@@ -805,5 +882,6 @@ mod tests {
         assert!(GMAIL_SCOPES.contains(&"https://www.googleapis.com/auth/gmail.compose"));
         assert!(GMAIL_SCOPES.contains(&"https://www.googleapis.com/auth/userinfo.email"));
         assert!(GMAIL_SCOPES.contains(&"https://www.googleapis.com/auth/userinfo.profile"));
+        assert!(ON_DEMAND_SCOPES.contains(&CONTACTS_READONLY_SCOPE));
     }
 } 
\ No newline at end of file
@@ -0,0 +1,381 @@
+//! Resumable initial backfill of a Gmail mailbox
+//!
+//! Walks the account's mail oldest to newest in fixed date windows
+//! (`after:`/`before:` queries), paging within each window, and stores each
+//! message in the local message store as its label sync policy allows.
+//! Gmail lists newest first inside a window, so ordering is oldest-first at
+//! window granularity. Each page's messages are written in one transaction
+//! and the window and page token are checkpointed after it, so jobs still
+//! running at exit resume on the next launch. Messages that fail to
+//! download are recorded and retried before the job completes. At most one
+//! run per account is active: starting a job that is already running only
+//! makes sure it keeps going.
+//!
+//! Its requests are scheduled as background traffic, so they only use the
+//! part of the shared request budget not reserved for interactive use, and
+//! pages are fetched while the user is idle where possible.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Context;
+use tauri::{AppHandle, Manager};
+
+use crate::commands::rate_limiter::RequestPriority;
+use crate::database::operations::backfill_operations::{self, BackfillJob, BackfillProgress};
+use crate::database::operations::cache_operations;
+use crate::database::operations::sync_policy_operations::{self, LabelSyncPolicy};
+use crate::database::operations::sync_run_operations::{self, NewSyncRun};
+use crate::database::DatabaseManager;
+use crate::errors::LibreOllamaError;
+use crate::services::activity::ActivityMonitor;
+use crate::services::events::{sync_error_category, EventBus};
+use crate::services::gmail::api_service::{GmailApiService, MessageSearchQuery};
+use crate::services::gmail::muted_threads::archive_muted_messages;
+use crate::services::gmail::sync_service;
+use crate::services::request_scheduler;
+use crate::services::shutdown::ShutdownCoordinator;
+
+/// Width of one backfill date window
+const WINDOW_SECONDS: i64 = 30 * 24 * 60 * 60;
+/// How far back to go when a policy has no age limit
+const DEFAULT_BACKFILL_YEARS: i64 = 10;
+const PAGE_SIZE: u32 = 50;
+/// Longest a page waits for the user to go idle, so a backfill keeps
+/// moving while they work
+const MAX_IDLE_DEFER: Duration = Duration::from_secs(30);
+/// Downloads of a message tried before it is left out for good
+const MAX_FETCH_ATTEMPTS: i64 = 3;
+
+#[derive(Clone)]
+pub struct BackfillRunner {
+    db_manager: Arc<DatabaseManager>,
+    api_service: Arc<GmailApiService>,
+    running: Arc<Mutex<HashSet<String>>>,
+    shutdown: ShutdownCoordinator,
+    activity: ActivityMonitor,
+    app: AppHandle,
+}
+
+impl BackfillRunner {
+    pub fn new(
+        db_manager: Arc<DatabaseManager>,
+        api_service: Arc<GmailApiService>,
+        shutdown: ShutdownCoordinator,
+        activity: ActivityMonitor,
+        app: AppHandle,
+    ) -> Self {
+        Self {
+            db_manager,
+            api_service,
+            running: Arc::new(Mutex::new(HashSet::new())),
+            shutdown,
+            activity,
+            app,
+        }
+    }
+
+    /// Start a backfill, or resume a paused/failed one from its checkpoint.
+    ///
+    /// `restart` discards an existing checkpoint and begins again from the
+    /// oldest date the account's sync policies need; it is refused while a
+    /// run is active.
+    pub fn start(&self, account_id: &str, restart: bool) -> anyhow::Result<BackfillProgress> {
+        let conn = self.db_manager.get_connection()?;
+        if !self.claim(account_id) {
+            if restart {
+                anyhow::bail!("The backfill for {} is still running; pause it before restarting", account_id);
+            }
+            // The active run re-reads the status before every page, so an
+            // unfinished pause is undone rather than a second run started
+            if let Some(job) = backfill_operations::get_job(&conn, account_id)?.filter(|job| job.status != "completed") {
+                backfill_operations::set_job_status(&conn, account_id, "running", None)?;
+                return Ok(BackfillProgress {
+                    status: "running".to_string(),
+                    ..job.progress(backfill_operations::failure_count(&conn, account_id)?)
+                });
+            }
+            return self.progress(account_id)?.context("Backfill job missing");
+        }
+
+        let job = (|| match backfill_operations::get_job(&conn, account_id)? {
+            Some(job) if !restart && job.status != "completed" => {
+                backfill_operations::set_job_status(&conn, account_id, "running", None)?;
+                Ok(job)
+            }
+            _ => {
+                let policies = sync_policy_operations::get_policies(&conn, account_id)?;
+                let now = chrono::Utc::now().timestamp();
+                backfill_operations::create_job(&conn, account_id, backfill_range_start(&policies, now), now)
+            }
+        })();
+        let job = match job {
+            Ok(job) => job,
+            Err(e) => {
+                self.release(account_id);
+                return Err(e);
+            }
+        };
+
+        self.spawn_claimed(account_id);
+        let failed = backfill_operations::failure_count(&conn, account_id)?;
+        Ok(BackfillProgress { status: "running".to_string(), ..job.progress(failed) })
+    }
+
+    /// Pause after the page currently being fetched
+    pub fn pause(&self, account_id: &str) -> anyhow::Result<()> {
+        let conn = self.db_manager.get_connection()?;
+        match backfill_operations::get_job(&conn, account_id)? {
+            Some(job) if job.status == "running" => {
+                backfill_operations::set_job_status(&conn, account_id, "paused", None)
+            }
+            Some(_) => Ok(()),
+            None => Err(LibreOllamaError::NotFound { resource: format!("backfill for {}", account_id) }.into()),
+        }
+    }
+
+    pub fn progress(&self, account_id: &str) -> anyhow::Result<Option<BackfillProgress>> {
+        let conn = self.db_manager.get_connection()?;
+        let Some(job) = backfill_operations::get_job(&conn, account_id)? else {
+            return Ok(None);
+        };
+        Ok(Some(job.progress(backfill_operations::failure_count(&conn, account_id)?)))
+    }
+
+    /// Restart jobs that were still running when the app last exited
+    pub fn resume_interrupted(&self) -> anyhow::Result<usize> {
+        let jobs = {
+            let conn = self.db_manager.get_connection()?;
+            backfill_operations::list_jobs_with_status(&conn, "running")?
+        };
+        for job in &jobs {
+            if self.claim(&job.account_id) {
+                self.spawn_claimed(&job.account_id);
+            }
+        }
+        Ok(jobs.len())
+    }
+
+    /// Take the account's run slot; false when a run is already active
+    fn claim(&self, account_id: &str) -> bool {
+        self.running.lock().unwrap_or_else(|e| e.into_inner()).insert(account_id.to_string())
+    }
+
+    fn release(&self, account_id: &str) {
+        self.running.lock().unwrap_or_else(|e| e.into_inner()).remove(account_id);
+    }
+
+    /// Run the job of an account whose slot was claimed
+    fn spawn_claimed(&self, account_id: &str) {
+        let runner = self.clone();
+        let account_id = account_id.to_string();
+        tauri::async_runtime::spawn(async move {
+            let result = request_scheduler::with_priority(RequestPriority::Low, runner.run(&account_id)).await;
+            if let Err(e) = &result {
+                eprintln!("❌ Backfill for {} failed: {}", account_id, e);
+                if let Ok(conn) = runner.db_manager.get_connection() {
+                    let error = e.to_string();
+                    let category = sync_error_category(e);
+                    let _ = backfill_operations::set_job_status(&conn, &account_id, "failed", Some(&error));
+                    let _ = sync_run_operations::record_sync_run(&conn, &NewSyncRun {
+                        account_id: &account_id,
+                        domain: "gmail_backfill",
+                        sync_type: "full",
+                        items_changed: 0,
+                        error: Some(&error),
+                        error_category: Some(category),
+                    });
+                }
+            }
+            runner.release(&account_id);
+
+            // A start that came in while this run was stopping for a pause
+            // set the job running again; pick it back up
+            if result.is_ok() && !runner.shutdown.is_stopping() {
+                let resumed = runner.load_job(&account_id).is_ok_and(|job| job.status == "running");
+                if resumed && runner.claim(&account_id) {
+                    runner.spawn_claimed(&account_id);
+                }
+            }
+        });
+    }
+
+    fn load_job(&self, account_id: &str) -> anyhow::Result<BackfillJob> {
+        let conn = self.db_manager.get_connection()?;
+        backfill_operations::get_job(&conn, account_id)?
+            .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("backfill for {}", account_id) }.into())
+    }
+
+    /// Download and store messages in one transaction. Messages that fail
+    /// to download are recorded for retry; returns how many were stored and
+    /// how many the sync policies left out.
+    async fn store_page(&self, account_id: &str, policies: &[LabelSyncPolicy], message_ids: &[String]) -> anyhow::Result<(i64, i64)> {
+        let mut messages = Vec::new();
+        let mut failures = Vec::new();
+        for message_id in message_ids {
+            match self.api_service.get_parsed_message(account_id, message_id).await {
+                Ok(message) => messages.push(message),
+                Err(e) => {
+                    eprintln!("⚠️ Backfill could not download message {}, will retry: {}", message_id, e);
+                    failures.push((message_id, e.to_string()));
+                }
+            }
+        }
+        archive_muted_messages(&self.api_service, &self.db_manager, account_id, &mut messages, false).await;
+
+        let mut skipped = 0;
+        let mut fetched = Vec::new();
+        let mut batch = Vec::new();
+        let mut deltas = Vec::new();
+        for message in messages {
+            fetched.push(message.id.clone());
+            match sync_service::stored_message(account_id, policies, message)? {
+                Some((message, delta)) => {
+                    batch.push(message);
+                    deltas.push(delta);
+                }
+                None => skipped += 1,
+            }
+        }
+
+        let stored = {
+            let mut conn = self.db_manager.get_connection()?;
+            let stored = cache_operations::store_messages(&mut conn, &batch)? as i64;
+            for (message_id, error) in &failures {
+                backfill_operations::record_failure(&conn, account_id, message_id, error)?;
+            }
+            for message_id in &fetched {
+                backfill_operations::clear_failure(&conn, account_id, message_id)?;
+            }
+            stored
+        };
+        if let Some(event_bus) = self.app.try_state::<EventBus>() {
+            event_bus.cache_changed(&deltas);
+        }
+        Ok((stored, skipped))
+    }
+
+    async fn run(&self, account_id: &str) -> anyhow::Result<()> {
+        println!("📥 Backfill started for {}", account_id);
+
+        loop {
+            tokio::select! {
+                _ = self.activity.idle_or_after(MAX_IDLE_DEFER) => {}
+                _ = self.shutdown.stopping() => {}
+            }
+
+            // Each page is stored and checkpointed before shutdown closes the
+            // database; the job stays running and resumes on next launch
+            let Some(_work) = self.shutdown.begin_work() else {
+                println!("🛑 Backfill for {} stopped for shutdown", account_id);
+                return Ok(());
+            };
+
+            // Re-read the job every page so pause takes effect
+            let job = self.load_job(account_id)?;
+            if job.status != "running" {
+                println!("⏸️ Backfill for {} is {}, stopping", account_id, job.status);
+                return Ok(());
+            }
+            if job.window_start >= job.range_end {
+                // Give messages that failed to download another try first
+                let retry = {
+                    let conn = self.db_manager.get_connection()?;
+                    backfill_operations::retryable_failures(&conn, account_id, MAX_FETCH_ATTEMPTS)?
+                };
+                if !retry.is_empty() {
+                    let policies = {
+                        let conn = self.db_manager.get_connection()?;
+                        sync_policy_operations::get_policies(&conn, account_id)?
+                    };
+                    let batch: Vec<String> = retry.into_iter().take(PAGE_SIZE as usize).collect();
+                    let (stored, skipped) = self.store_page(account_id, &policies, &batch).await?;
+                    let conn = self.db_manager.get_connection()?;
+                    backfill_operations::save_checkpoint(&conn, account_id, job.window_start, None, stored, skipped)?;
+                    continue;
+                }
+
+                let conn = self.db_manager.get_connection()?;
+                backfill_operations::set_job_status(&conn, account_id, "completed", None)?;
+                sync_run_operations::record_sync_run(&conn, &NewSyncRun {
+                    account_id,
+                    domain: "gmail_backfill",
+                    sync_type: "full",
+                    items_changed: job.messages_stored,
+                    error: None,
+                    error_category: None,
+                })?;
+                println!("✅ Backfill for {} completed ({} messages)", account_id, job.messages_stored);
+                return Ok(());
+            }
+
+            let policies = {
+                let conn = self.db_manager.get_connection()?;
+                sync_policy_operations::get_policies(&conn, account_id)?
+            };
+
+            let window_end = (job.window_start + WINDOW_SECONDS).min(job.range_end);
+            let query = MessageSearchQuery {
+                query: Some(format!("after:{} before:{}", job.window_start, window_end)),
+                label_ids: None,
+                max_results: Some(PAGE_SIZE),
+                page_token: job.page_token.clone(),
+                include_spam_trash: Some(false),
+            };
+
+            let page = match self.api_service.get_messages(account_id, &query).await {
+                Ok(page) => page,
+                Err(LibreOllamaError::Network { message, .. }) => {
+                    // Offline: keep the checkpoint and pause rather than fail
+                    let conn = self.db_manager.get_connection()?;
+                    backfill_operations::set_job_status(&conn, account_id, "paused", Some(&message))?;
+                    eprintln!("📴 Backfill for {} paused, Gmail unreachable: {}", account_id, message);
+                    return Ok(());
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            let message_refs = page.messages.unwrap_or_default();
+            let already_stored = {
+                let conn = self.db_manager.get_connection()?;
+                let message_ids: Vec<String> = message_refs.iter().map(|message_ref| message_ref.id.clone()).collect();
+                cache_operations::stored_message_ids(&conn, account_id, &message_ids)?
+            };
+
+            let message_ids: Vec<String> = message_refs
+                .iter()
+                .filter(|message_ref| !already_stored.contains(&message_ref.id))
+                .map(|message_ref| message_ref.id.clone())
+                .collect();
+            let (stored, skipped) = self.store_page(account_id, &policies, &message_ids).await?;
+
+            // Move to the next window once this one has no more pages
+            {
+                let conn = self.db_manager.get_connection()?;
+                match &page.next_page_token {
+                    Some(token) => backfill_operations::save_checkpoint(&conn, account_id, job.window_start, Some(token), stored, skipped)?,
+                    None => backfill_operations::save_checkpoint(&conn, account_id, window_end, None, stored, skipped)?,
+                }
+            }
+
+            // Label counts are refreshed in one batch call per finished window
+            if page.next_page_token.is_none() {
+                if let Err(e) = self.api_service.refresh_label_counts(account_id).await {
+                    eprintln!("⚠️ Backfill could not refresh label counts for {}: {}", account_id, e);
+                }
+            }
+        }
+    }
+}
+
+/// Oldest date (epoch seconds) any enabled label policy wants synced
+fn backfill_range_start(policies: &[LabelSyncPolicy], now: i64) -> i64 {
+    let enabled: Vec<&LabelSyncPolicy> = policies.iter().filter(|p| p.enabled).collect();
+    let max_days = if enabled.iter().any(|p| p.max_age_days.is_none()) {
+        DEFAULT_BACKFILL_YEARS * 365
+    } else {
+        enabled.iter().filter_map(|p| p.max_age_days).max().unwrap_or(0)
+    };
+    now - max_days * 24 * 60 * 60
+}
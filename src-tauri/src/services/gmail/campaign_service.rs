@@ -0,0 +1,287 @@
+//! Mail merge campaign runner
+//!
+//! Renders a campaign's subject/body templates for each recipient and sends
+//! the messages one at a time through the compose service (and so through
+//! the shared rate limiter, at background priority), waiting
+//! `send_interval_ms` between sends.
+//! Progress lives in the database, so a campaign can be paused, cancelled,
+//! or resumed after an app restart.
+
+use crate::commands::rate_limiter::RequestPriority;
+use crate::database::operations::campaign_operations::{self, CampaignRecipient, MailCampaign};
+use crate::database::DatabaseManager;
+use crate::errors::LibreOllamaError;
+use crate::services::gmail::api_service::EmailAddress;
+use crate::services::gmail::compose_service::{ComposeRequest, GmailComposeService, MessageImportance};
+use crate::services::request_scheduler;
+use crate::services::shutdown::ShutdownCoordinator;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+lazy_static::lazy_static! {
+    /// `{{ name }}` or `{{ name | fallback }}`
+    static ref PLACEHOLDER: Regex =
+        Regex::new(r"\{\{\s*([A-Za-z0-9_.]+)\s*(?:\|\s*([^}]*?)\s*)?\}\}").unwrap();
+}
+
+/// A rendered message for one recipient
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderedMessage {
+    pub email: String,
+    pub subject: String,
+    pub body: String,
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Substitute placeholders, failing on variables with neither a value nor a fallback
+pub fn render_template(template: &str, variables: &HashMap<String, String>, escape: bool) -> Result<String, String> {
+    let mut missing = Vec::new();
+
+    let rendered = PLACEHOLDER.replace_all(template, |caps: &regex::Captures| {
+        let name = &caps[1];
+        match variables.get(name).filter(|v| !v.is_empty()) {
+            Some(value) if escape => escape_html(value),
+            Some(value) => value.clone(),
+            None => match caps.get(2) {
+                Some(fallback) => fallback.as_str().to_string(),
+                None => {
+                    missing.push(name.to_string());
+                    String::new()
+                }
+            },
+        }
+    });
+
+    if missing.is_empty() {
+        Ok(rendered.into_owned())
+    } else {
+        Err(format!("Missing template variables: {}", missing.join(", ")))
+    }
+}
+
+/// Built-in variables for a recipient, overridden by its own variables
+pub fn recipient_variables(recipient: &CampaignRecipient) -> HashMap<String, String> {
+    let mut variables = HashMap::new();
+    variables.insert("email".to_string(), recipient.email.clone());
+
+    if let Some(name) = &recipient.name {
+        variables.insert("name".to_string(), name.clone());
+        let mut parts = name.split_whitespace();
+        if let Some(first) = parts.next() {
+            variables.insert("first_name".to_string(), first.to_string());
+        }
+        let last: Vec<&str> = parts.collect();
+        if !last.is_empty() {
+            variables.insert("last_name".to_string(), last.join(" "));
+        }
+    }
+
+    variables.extend(recipient.variables.clone());
+    variables
+}
+
+pub fn render_for_recipient(campaign: &MailCampaign, recipient: &CampaignRecipient) -> Result<RenderedMessage, String> {
+    let variables = recipient_variables(recipient);
+    Ok(RenderedMessage {
+        email: recipient.email.clone(),
+        subject: render_template(&campaign.subject_template, &variables, false)?,
+        body: render_template(&campaign.body_template, &variables, campaign.is_html)?,
+    })
+}
+
+/// Runs campaigns in the background, at most one task per campaign
+#[derive(Clone)]
+pub struct CampaignRunner {
+    db_manager: Arc<DatabaseManager>,
+    compose_service: Arc<GmailComposeService>,
+    running: Arc<Mutex<HashSet<i64>>>,
+    shutdown: ShutdownCoordinator,
+}
+
+impl CampaignRunner {
+    pub fn new(db_manager: Arc<DatabaseManager>, compose_service: Arc<GmailComposeService>, shutdown: ShutdownCoordinator) -> Self {
+        Self {
+            db_manager,
+            compose_service,
+            running: Arc::new(Mutex::new(HashSet::new())),
+            shutdown,
+        }
+    }
+
+    fn load_campaign(&self, campaign_id: i64) -> anyhow::Result<MailCampaign> {
+        let conn = self.db_manager.get_connection()?;
+        campaign_operations::get_campaign(&conn, campaign_id)?
+            .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("campaign {}", campaign_id) }.into())
+    }
+
+    /// Start or resume sending a campaign in the background
+    pub fn start(&self, campaign_id: i64) -> anyhow::Result<()> {
+        let campaign = self.load_campaign(campaign_id)?;
+        if matches!(campaign.status.as_str(), "completed" | "cancelled") {
+            return Err(LibreOllamaError::InvalidInput {
+                message: format!("Campaign is already {}", campaign.status),
+                field: Some("campaign_id".to_string()),
+            }.into());
+        }
+
+        {
+            let conn = self.db_manager.get_connection()?;
+            campaign_operations::set_campaign_status(&conn, campaign_id, "running")?;
+        }
+
+        if !self.running.lock().unwrap_or_else(|e| e.into_inner()).insert(campaign_id) {
+            // Already sending; the status change above is enough to keep it going
+            return Ok(());
+        }
+
+        let runner = self.clone();
+        tauri::async_runtime::spawn(async move {
+            // Campaign sends are background traffic and yield to interactive requests
+            let result = request_scheduler::with_priority(RequestPriority::Low, runner.run(campaign_id)).await;
+            if let Err(e) = result {
+                eprintln!("❌ Campaign {} stopped: {}", campaign_id, e);
+            }
+            runner.running.lock().unwrap_or_else(|e| e.into_inner()).remove(&campaign_id);
+        });
+
+        Ok(())
+    }
+
+    /// Stop after the message currently being sent; `cancelled` is final, `paused` can be resumed
+    pub fn stop(&self, campaign_id: i64, status: &str) -> anyhow::Result<()> {
+        let campaign = self.load_campaign(campaign_id)?;
+        if campaign.status == "completed" {
+            return Ok(());
+        }
+        let conn = self.db_manager.get_connection()?;
+        campaign_operations::set_campaign_status(&conn, campaign_id, status)
+    }
+
+    /// Restart campaigns that were still running when the app last exited
+    pub fn resume_interrupted(&self) -> anyhow::Result<usize> {
+        let running: Vec<i64> = {
+            let conn = self.db_manager.get_connection()?;
+            let mut stmt = conn.prepare("SELECT id FROM mail_campaigns WHERE status = 'running'")?;
+            let ids = stmt.query_map([], |row| row.get(0))?.collect::<rusqlite::Result<Vec<_>>>()?;
+            ids
+        };
+
+        for campaign_id in &running {
+            self.start(*campaign_id)?;
+        }
+        Ok(running.len())
+    }
+
+    async fn run(&self, campaign_id: i64) -> anyhow::Result<()> {
+        loop {
+            // A message is never sent without being marked sent before
+            // shutdown closes the database; the campaign resumes on next launch
+            let Some(work) = self.shutdown.begin_work() else {
+                println!("🛑 Campaign {} stopped for shutdown", campaign_id);
+                return Ok(());
+            };
+
+            // Re-read the campaign every iteration so pause/cancel take effect
+            let campaign = self.load_campaign(campaign_id)?;
+            if campaign.status != "running" {
+                println!("⏸️ Campaign {} is {}, stopping", campaign_id, campaign.status);
+                return Ok(());
+            }
+
+            let next = {
+                let conn = self.db_manager.get_connection()?;
+                campaign_operations::next_pending_recipient(&conn, campaign_id)?
+            };
+            let Some(recipient) = next else {
+                let conn = self.db_manager.get_connection()?;
+                campaign_operations::set_campaign_status(&conn, campaign_id, "completed")?;
+                println!("✅ Campaign {} completed", campaign_id);
+                return Ok(());
+            };
+
+            let rendered = match render_for_recipient(&campaign, &recipient) {
+                Ok(rendered) => rendered,
+                Err(e) => {
+                    let conn = self.db_manager.get_connection()?;
+                    campaign_operations::mark_recipient_failed(&conn, recipient.id, &e)?;
+                    continue;
+                }
+            };
+
+            let request = ComposeRequest {
+                account_id: campaign.account_id.clone(),
+                to: vec![EmailAddress { email: recipient.email.clone(), name: recipient.name.clone() }],
+                cc: None,
+                bcc: None,
+                subject: rendered.subject,
+                body_text: if campaign.is_html { None } else { Some(rendered.body.clone()) },
+                body_html: if campaign.is_html { Some(rendered.body) } else { None },
+                attachments: None,
+                reply_to_message_id: None,
+                in_reply_to: None,
+                references: None,
+                thread_id: None,
+                importance: MessageImportance::Normal,
+                delivery_receipt: false,
+                read_receipt: false,
+                schedule_send: None,
+                from_alias: None,
+            };
+
+            match self.compose_service.send_message(&request).await {
+                Ok(response) => {
+                    let conn = self.db_manager.get_connection()?;
+                    campaign_operations::mark_recipient_sent(&conn, recipient.id, &response.message_id)?;
+                }
+                Err(e) if matches!(e.downcast_ref::<LibreOllamaError>(), Some(LibreOllamaError::Network { .. })) => {
+                    // Offline: leave the recipient pending and pause instead of failing everyone
+                    let conn = self.db_manager.get_connection()?;
+                    campaign_operations::set_campaign_status(&conn, campaign_id, "paused")?;
+                    eprintln!("📴 Campaign {} paused, Gmail unreachable: {}", campaign_id, e);
+                    return Ok(());
+                }
+                Err(e) => {
+                    let conn = self.db_manager.get_connection()?;
+                    campaign_operations::mark_recipient_failed(&conn, recipient.id, &e.to_string())?;
+                }
+            }
+
+            drop(work);
+            tokio::time::sleep(Duration::from_millis(campaign.send_interval_ms.max(0) as u64)).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_template_with_fallbacks_and_escaping() {
+        let mut vars = HashMap::new();
+        vars.insert("first_name".to_string(), "Ada".to_string());
+        vars.insert("company".to_string(), "<Analytical>".to_string());
+
+        assert_eq!(
+            render_template("Hi {{ first_name }} from {{company}}", &vars, false).unwrap(),
+            "Hi Ada from <Analytical>"
+        );
+        assert_eq!(
+            render_template("<p>{{company}}</p>", &vars, true).unwrap(),
+            "<p>&lt;Analytical&gt;</p>"
+        );
+        assert_eq!(render_template("Dear {{title | friend}}", &vars, false).unwrap(), "Dear friend");
+        assert!(render_template("{{missing}}", &vars, false).unwrap_err().contains("missing"));
+    }
+}
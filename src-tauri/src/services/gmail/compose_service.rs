@@ -14,8 +14,17 @@ use uuid::Uuid;
 use crate::database::DatabaseManager;
 use crate::errors::LibreOllamaError;
 use crate::services::gmail::auth_service::GmailAuthService;
-use crate::services::gmail::api_service::EmailAddress;
-use crate::commands::rate_limiter::{RateLimiter, BatchRequest, RequestPriority};
+use crate::services::gmail::api_service::{EmailAddress, GmailApiService};
+use crate::database::operations::draft_operations::{self, CachedDraft};
+use crate::database::operations::receipt_operations::{self, NewTrackedMessage, TrackedMessage};
+use crate::database::operations::send_as_operations::SendAsAlias;
+use crate::services::gmail::receipt_service::{self, ReceiptReport};
+use crate::services::gmail::inline_images;
+use crate::services::gmail::reply_builder;
+use crate::services::time_service::TimeService;
+use crate::services::i18n;
+use crate::commands::rate_limiter::{self, RateLimiter, BatchRequest, BatchResponse, RequestPriority};
+use crate::utils::http_client::http_client;
 
 /// Gmail compose API endpoints
 #[allow(unused)]
@@ -35,11 +44,21 @@ pub struct ComposeRequest {
     pub body_html: Option<String>,
     pub attachments: Option<Vec<ComposeAttachment>>,
     pub reply_to_message_id: Option<String>,
+    /// Message-ID of the message being replied to, without angle brackets
+    #[serde(default)]
+    pub in_reply_to: Option<String>,
+    /// Message-IDs of the conversation so far, oldest first
+    #[serde(default)]
+    pub references: Option<Vec<String>>,
     pub thread_id: Option<String>,
     pub importance: MessageImportance,
     pub delivery_receipt: bool,
     pub read_receipt: bool,
     pub schedule_send: Option<DateTime<Utc>>,
+    /// Send-as address to send from instead of the account's own; must be
+    /// one Gmail settings list for the account
+    #[serde(default)]
+    pub from_alias: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -102,12 +121,23 @@ pub struct DraftSaveRequest {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DraftResponse {
+    /// Gmail draft ID, or the local ID while the draft is still queued offline
     pub draft_id: String,
     pub message_id: String,
+    pub local_id: String,
+    pub synced: bool,
     pub saved_at: DateTime<Utc>,
     pub auto_save: bool,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DraftSyncSummary {
+    pub pushed: u32,
+    pub pulled: u32,
+    pub removed: u32,
+    pub failed: u32,
+}
+
 /// Email templates
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MessageTemplate {
@@ -152,7 +182,9 @@ pub struct GmailComposeService {
     client: Client,
     auth_service: Arc<GmailAuthService>,
     db_manager: std::sync::Arc<DatabaseManager>,
-    rate_limiter: Arc<tokio::sync::Mutex<RateLimiter>>,
+    rate_limiter: Arc<RateLimiter>,
+    /// Fetches the send-as list a `from_alias` is checked against
+    api_service: GmailApiService,
 }
 
 impl GmailComposeService {
@@ -160,10 +192,11 @@ impl GmailComposeService {
     pub fn new(
         auth_service: Arc<GmailAuthService>,
         db_manager: std::sync::Arc<DatabaseManager>,
-        rate_limiter: Arc<tokio::sync::Mutex<RateLimiter>>,
+        rate_limiter: Arc<RateLimiter>,
     ) -> Self {
         Self {
-            client: Client::new(),
+            client: http_client(),
+            api_service: GmailApiService::new(auth_service.clone(), db_manager.clone(), rate_limiter.clone()),
             auth_service,
             db_manager,
             rate_limiter,
@@ -185,7 +218,8 @@ impl GmailComposeService {
         }
 
         // Format the email message
-        let message = self.format_email_message(compose_request)?;
+        let tracking = self.prepare_tracking(compose_request, true).await?;
+        let message = self.format_email_message(compose_request, &tracking)?;
 
         // Prepare request body
         let request_body = serde_json::json!({
@@ -196,6 +230,7 @@ impl GmailComposeService {
         // Create rate-limited request
         let batch_request = BatchRequest {
             id: format!("send_{}", Uuid::new_v4()),
+            account_id: Some(compose_request.account_id.clone()),
             method: "POST".to_string(),
             url: GMAIL_SEND_ENDPOINT.to_string(),
             headers: {
@@ -207,19 +242,14 @@ impl GmailComposeService {
             body: Some(request_body.to_string()),
             priority: RequestPriority::High,
             created_at: chrono::Utc::now().to_rfc3339(),
-            max_retries: 3,
+            // Never resent, so a mail cannot go out twice
+            max_retries: 0,
             current_retry: 0,
         };
 
         // Execute rate-limited request
-        let response = {
-            let mut rate_limiter = self.rate_limiter.lock().await;
-            rate_limiter.execute_request(batch_request).await
-                .map_err(|e| LibreOllamaError::Network {
-                    message: format!("Rate-limited request failed: {}", e),
-                    url: Some(GMAIL_SEND_ENDPOINT.to_string()),
-                })?
-        };
+        let response = rate_limiter::send_scheduled(&self.rate_limiter, batch_request).await
+            .map_err(|e| rate_limiter::request_error(e, "Rate-limited request failed", GMAIL_SEND_ENDPOINT))?;
 
         if response.status_code != 200 {
             return Err(LibreOllamaError::GmailApi {
@@ -235,7 +265,7 @@ impl GmailComposeService {
             })?;
 
         // Store sent message locally
-        self.store_sent_message(compose_request, &gmail_response).await?;
+        self.store_sent_message(compose_request, &tracking, &gmail_response).await?;
 
         Ok(SendResponse {
             message_id: gmail_response["id"].as_str().unwrap_or("").to_string(),
@@ -250,65 +280,173 @@ impl GmailComposeService {
         })
     }
 
-    /// Save message as draft
-    pub async fn save_draft(&self, draft_request: &DraftSaveRequest) -> Result<DraftResponse> {
-        // Get valid tokens
+    /// Execute an authenticated Gmail request through the rate limiter
+    async fn execute_gmail_request(
+        &self,
+        account_id: &str,
+        method: &str,
+        url: &str,
+        body: Option<serde_json::Value>,
+        priority: RequestPriority,
+    ) -> Result<BatchResponse> {
+        self.execute_gmail_request_with_retries(account_id, method, url, body, priority, 3).await
+    }
+
+    async fn execute_gmail_request_with_retries(
+        &self,
+        account_id: &str,
+        method: &str,
+        url: &str,
+        body: Option<serde_json::Value>,
+        priority: RequestPriority,
+        max_retries: u32,
+    ) -> Result<BatchResponse> {
         let tokens = self.auth_service
-            .validate_and_refresh_tokens(&self.db_manager, &draft_request.account_id)
+            .validate_and_refresh_tokens(&self.db_manager, account_id)
             .await?;
 
-        // Format the draft message
-        let message = self.format_email_message(&draft_request.compose_data)?;
-
-        let (url, request_body) = if let Some(draft_id) = &draft_request.draft_id {
-            // Update existing draft
-            let url = format!("{}/{}", GMAIL_DRAFTS_ENDPOINT, draft_id);
-            let body = serde_json::json!({
-                "id": draft_id,
-                "message": {
-                    "raw": general_purpose::URL_SAFE_NO_PAD.encode(&message),
-                }
-            });
-            (url, body)
-        } else {
-            // Create new draft
-            let body = serde_json::json!({
-                "message": {
-                    "raw": general_purpose::URL_SAFE_NO_PAD.encode(&message),
-                }
-            });
-            (GMAIL_DRAFTS_ENDPOINT.to_string(), body)
-        };
-
-        // Create rate-limited request
         let batch_request = BatchRequest {
-            id: format!("draft_{}", Uuid::new_v4()),
-            method: "POST".to_string(),
-            url: url.clone(),
+            id: format!("{}_{}", method.to_lowercase(), Uuid::new_v4()),
+            account_id: Some(account_id.to_string()),
+            method: method.to_string(),
+            url: url.to_string(),
             headers: {
                 let mut headers = std::collections::HashMap::new();
                 headers.insert("Authorization".to_string(), format!("Bearer {}", tokens.access_token));
-                headers.insert("Content-Type".to_string(), "application/json".to_string());
+                if body.is_some() {
+                    headers.insert("Content-Type".to_string(), "application/json".to_string());
+                }
                 headers
             },
-            body: Some(request_body.to_string()),
-            priority: RequestPriority::Medium,
+            body: body.map(|b| b.to_string()),
+            priority,
             created_at: chrono::Utc::now().to_rfc3339(),
-            max_retries: 3,
+            max_retries,
             current_retry: 0,
         };
 
-        // Execute rate-limited request
-        let response = {
-            let mut rate_limiter = self.rate_limiter.lock().await;
-            rate_limiter.execute_request(batch_request).await
-                .map_err(|e| LibreOllamaError::Network {
-                    message: format!("Rate-limited request failed: {}", e),
-                    url: Some(url.clone()),
-                })?
+        rate_limiter::send_scheduled(&self.rate_limiter, batch_request)
+            .await
+            .map_err(|e| rate_limiter::request_error(e, "Rate-limited request failed", url).into())
+    }
+
+    /// Whether an error means Gmail could not be reached (the change stays queued)
+    fn is_offline_error(error: &anyhow::Error) -> bool {
+        matches!(error.downcast_ref::<LibreOllamaError>(), Some(LibreOllamaError::Network { .. }))
+    }
+
+    /// Save message as draft.
+    ///
+    /// The draft is written to the local cache first and then pushed to
+    /// Gmail; when offline it stays queued and is pushed by `sync_drafts`.
+    pub async fn save_draft(&self, draft_request: &DraftSaveRequest) -> Result<DraftResponse> {
+        let compose_data = serde_json::to_value(&draft_request.compose_data)?;
+
+        let draft = {
+            let conn = self.db_manager.get_connection()?;
+            let existing = match &draft_request.draft_id {
+                Some(id) => draft_operations::find_draft(&conn, &draft_request.account_id, id)?,
+                None => None,
+            };
+            let local_id = existing
+                .as_ref()
+                .map(|d| d.local_id.clone())
+                .unwrap_or_else(|| Uuid::new_v4().to_string());
+            // An unknown draft_id is a Gmail draft that has not been cached yet
+            let remote_id = existing
+                .as_ref()
+                .and_then(|d| d.draft_id.clone())
+                .or_else(|| if existing.is_none() { draft_request.draft_id.clone() } else { None });
+
+            draft_operations::save_local_draft(
+                &conn,
+                &local_id,
+                &draft_request.account_id,
+                remote_id.as_deref(),
+                draft_request.compose_data.thread_id.as_deref(),
+                &compose_data,
+            )?;
+            draft_operations::find_draft(&conn, &draft_request.account_id, &local_id)?
+                .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("draft {}", local_id) })?
         };
 
+        match self.push_draft(&draft).await {
+            Ok((draft_id, message_id)) => Ok(DraftResponse {
+                draft_id,
+                message_id,
+                local_id: draft.local_id,
+                synced: true,
+                saved_at: Utc::now(),
+                auto_save: false,
+            }),
+            Err(e) if Self::is_offline_error(&e) => {
+                println!("📴 Gmail unreachable, draft {} queued for sync", draft.local_id);
+                Ok(DraftResponse {
+                    draft_id: draft.draft_id.clone().unwrap_or_else(|| draft.local_id.clone()),
+                    message_id: draft.message_id.clone().unwrap_or_default(),
+                    local_id: draft.local_id,
+                    synced: false,
+                    saved_at: Utc::now(),
+                    auto_save: false,
+                })
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Push one cached draft's pending change to Gmail, returning its draft and message IDs
+    async fn push_draft(&self, draft: &CachedDraft) -> Result<(String, String)> {
+        if draft.sync_status == "pending_delete" {
+            if let Some(draft_id) = &draft.draft_id {
+                let url = format!("{}/{}", GMAIL_DRAFTS_ENDPOINT, draft_id);
+                let response = self
+                    .execute_gmail_request(&draft.account_id, "DELETE", &url, None, RequestPriority::Medium)
+                    .await?;
+                // 404 means it is already gone remotely
+                if ![200, 204, 404].contains(&response.status_code) {
+                    return Err(LibreOllamaError::GmailApi {
+                        message: format!("Delete draft failed: {} - {}", response.status_code, response.body),
+                        status_code: Some(response.status_code),
+                    }.into());
+                }
+            }
+            let conn = self.db_manager.get_connection()?;
+            draft_operations::remove_draft(&conn, &draft.local_id)?;
+            return Ok((draft.draft_id.clone().unwrap_or_default(), draft.message_id.clone().unwrap_or_default()));
+        }
+
+        let compose: ComposeRequest = serde_json::from_value(draft.compose_data.clone())?;
+        // Drafts never carry a pixel: the token would be stale after every edit
+        let tracking = self.prepare_tracking(&compose, false).await?;
+        let message = self.format_email_message(&compose, &tracking)?;
+        let mut message_body = serde_json::json!({
+            "raw": general_purpose::URL_SAFE_NO_PAD.encode(&message),
+        });
+        if let Some(thread_id) = &draft.thread_id {
+            message_body["threadId"] = serde_json::json!(thread_id);
+        }
+
+        let (method, url, request_body) = match &draft.draft_id {
+            // Gmail replaces draft contents with PUT drafts/{id}
+            Some(draft_id) => (
+                "PUT",
+                format!("{}/{}", GMAIL_DRAFTS_ENDPOINT, draft_id),
+                serde_json::json!({ "id": draft_id, "message": message_body }),
+            ),
+            None => (
+                "POST",
+                GMAIL_DRAFTS_ENDPOINT.to_string(),
+                serde_json::json!({ "message": message_body }),
+            ),
+        };
+
+        let response = self
+            .execute_gmail_request(&draft.account_id, method, &url, Some(request_body), RequestPriority::Medium)
+            .await?;
+
         if response.status_code != 200 {
+            let conn = self.db_manager.get_connection()?;
+            draft_operations::set_draft_error(&conn, &draft.local_id, &response.body)?;
             return Err(LibreOllamaError::GmailApi {
                 message: format!("Save draft failed: {} - {}", response.status_code, response.body),
                 status_code: Some(response.status_code),
@@ -321,152 +459,279 @@ impl GmailComposeService {
                 data_type: "Gmail draft response".to_string(),
             })?;
 
-        // Store draft locally
-        self.store_draft_locally(draft_request, &gmail_response).await?;
+        let draft_id = gmail_response["id"].as_str().unwrap_or("").to_string();
+        let message_id = gmail_response["message"]["id"].as_str().unwrap_or("").to_string();
 
-        Ok(DraftResponse {
-            draft_id: gmail_response["id"].as_str().unwrap_or("").to_string(),
-            message_id: gmail_response["message"]["id"].as_str().unwrap_or("").to_string(),
-            saved_at: Utc::now(),
-            auto_save: false,
-        })
+        let conn = self.db_manager.get_connection()?;
+        draft_operations::mark_draft_synced(&conn, &draft.local_id, &draft_id, &message_id)?;
+
+        Ok((draft_id, message_id))
     }
 
-    /// Get all drafts for an account
-    pub async fn get_drafts(
-        &self,
-        account_id: &str,
-        max_results: Option<u32>,
-        page_token: Option<&str>,
-    ) -> Result<Vec<DraftResponse>> {
-        // Get valid tokens
-        let tokens = self.auth_service
-            .validate_and_refresh_tokens(&self.db_manager, account_id)
+    /// Download a draft from Gmail and refresh its cached copy
+    pub async fn fetch_draft(&self, account_id: &str, draft_id: &str) -> Result<CachedDraft> {
+        let url = format!("{}/{}?format=raw", GMAIL_DRAFTS_ENDPOINT, draft_id);
+        let response = self
+            .execute_gmail_request(account_id, "GET", &url, None, RequestPriority::Medium)
             .await?;
 
-        let mut url = GMAIL_DRAFTS_ENDPOINT.to_string();
-        let mut params = Vec::new();
-
-        if let Some(max) = max_results {
-            params.push(format!("maxResults={}", max));
+        if response.status_code != 200 {
+            return Err(LibreOllamaError::GmailApi {
+                message: format!("Get draft failed: {} - {}", response.status_code, response.body),
+                status_code: Some(response.status_code),
+            }.into());
         }
 
-        if let Some(token) = page_token {
-            params.push(format!("pageToken={}", urlencoding::encode(token)));
-        }
+        let gmail_response: serde_json::Value = serde_json::from_str(&response.body)
+            .map_err(|e| LibreOllamaError::Serialization {
+                message: format!("Failed to parse draft response: {}", e),
+                data_type: "Gmail draft response".to_string(),
+            })?;
 
-        if !params.is_empty() {
-            url.push_str(&format!("?{}", params.join("&")));
-        }
+        let message_id = gmail_response["message"]["id"].as_str().unwrap_or("").to_string();
+        let thread_id = gmail_response["message"]["threadId"].as_str().map(|s| s.to_string());
+        let raw = gmail_response["message"]["raw"].as_str().unwrap_or("");
+        let raw = general_purpose::URL_SAFE_NO_PAD
+            .decode(raw.trim_end_matches('='))
+            .map_err(|e| LibreOllamaError::EmailParsing {
+                message: format!("Failed to decode draft message: {}", e),
+            })?;
+        let compose = compose_from_raw(account_id, &raw, thread_id.clone())?;
+
+        let conn = self.db_manager.get_connection()?;
+        let local_id = draft_operations::find_draft(&conn, account_id, draft_id)?
+            .map(|d| d.local_id)
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        draft_operations::cache_remote_draft(
+            &conn,
+            &local_id,
+            account_id,
+            draft_id,
+            &message_id,
+            thread_id.as_deref(),
+            &serde_json::to_value(&compose)?,
+        )?;
+
+        draft_operations::find_draft(&conn, account_id, &local_id)?
+            .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("draft {}", draft_id) }.into())
+    }
 
-        // Create rate-limited request
-        let batch_request = BatchRequest {
-            id: format!("get_drafts_{}", Uuid::new_v4()),
-            method: "GET".to_string(),
-            url: url.clone(),
-            headers: {
-                let mut headers = std::collections::HashMap::new();
-                headers.insert("Authorization".to_string(), format!("Bearer {}", tokens.access_token));
-                headers
-            },
-            body: None,
-            priority: RequestPriority::Medium,
-            created_at: chrono::Utc::now().to_rfc3339(),
-            max_retries: 3,
-            current_retry: 0,
-        };
+    /// Push queued local draft changes, then refresh the cache from Gmail
+    pub async fn sync_drafts(&self, account_id: &str) -> Result<DraftSyncSummary> {
+        let mut summary = DraftSyncSummary::default();
 
-        // Execute rate-limited request
-        let response = {
-            let mut rate_limiter = self.rate_limiter.lock().await;
-            rate_limiter.execute_request(batch_request).await
-                .map_err(|e| LibreOllamaError::Network {
-                    message: format!("Rate-limited request failed: {}", e),
-                    url: Some(url.clone()),
-                })?
+        let unsynced = {
+            let conn = self.db_manager.get_connection()?;
+            draft_operations::list_unsynced_drafts(&conn, account_id)?
         };
-
-        if response.status_code != 200 {
-            return Err(LibreOllamaError::GmailApi {
-                message: format!("Get drafts failed: {} - {}", response.status_code, response.body),
-                status_code: Some(response.status_code),
-            }.into());
+        for draft in unsynced {
+            match self.push_draft(&draft).await {
+                Ok(_) => summary.pushed += 1,
+                Err(e) if Self::is_offline_error(&e) => return Err(e),
+                Err(e) => {
+                    eprintln!("⚠️ Failed to push draft {}: {}", draft.local_id, e);
+                    summary.failed += 1;
+                }
+            }
         }
 
         #[derive(serde::Deserialize)]
-        #[allow(dead_code)]
+        #[serde(rename_all = "camelCase")]
         struct DraftListResponse {
             drafts: Option<Vec<serde_json::Value>>,
+            next_page_token: Option<String>,
         }
 
-        let draft_list: DraftListResponse = serde_json::from_str(&response.body)
-            .map_err(|e| LibreOllamaError::Serialization {
-                message: format!("Failed to parse drafts response: {}", e),
-                data_type: "Gmail drafts response".to_string(),
-            })?;
+        let mut remote: Vec<(String, String)> = Vec::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut url = format!("{}?maxResults=100", GMAIL_DRAFTS_ENDPOINT);
+            if let Some(token) = &page_token {
+                url.push_str(&format!("&pageToken={}", urlencoding::encode(token)));
+            }
 
-        let mut drafts = Vec::new();
-        if let Some(draft_items) = draft_list.drafts {
-            for draft in draft_items {
-                drafts.push(DraftResponse {
-                    draft_id: draft["id"].as_str().unwrap_or("").to_string(),
-                    message_id: draft["message"]["id"].as_str().unwrap_or("").to_string(),
-                    saved_at: Utc::now(), // Would parse from API in real implementation
-                    auto_save: false,
-                });
+            let response = self
+                .execute_gmail_request(account_id, "GET", &url, None, RequestPriority::Low)
+                .await?;
+            if response.status_code != 200 {
+                return Err(LibreOllamaError::GmailApi {
+                    message: format!("Get drafts failed: {} - {}", response.status_code, response.body),
+                    status_code: Some(response.status_code),
+                }.into());
+            }
+
+            let page: DraftListResponse = serde_json::from_str(&response.body)
+                .map_err(|e| LibreOllamaError::Serialization {
+                    message: format!("Failed to parse drafts response: {}", e),
+                    data_type: "Gmail drafts response".to_string(),
+                })?;
+
+            for draft in page.drafts.unwrap_or_default() {
+                remote.push((
+                    draft["id"].as_str().unwrap_or("").to_string(),
+                    draft["message"]["id"].as_str().unwrap_or("").to_string(),
+                ));
+            }
+
+            match page.next_page_token {
+                Some(token) => page_token = Some(token),
+                None => break,
+            }
+        }
+
+        // Only download drafts whose message changed since they were cached
+        for (draft_id, message_id) in &remote {
+            let cached = {
+                let conn = self.db_manager.get_connection()?;
+                draft_operations::find_draft(&conn, account_id, draft_id)?
+            };
+            let stale = match &cached {
+                Some(d) => d.sync_status == "synced" && d.message_id.as_deref() != Some(message_id.as_str()),
+                None => true,
+            };
+            if stale {
+                self.fetch_draft(account_id, draft_id).await?;
+                summary.pulled += 1;
             }
         }
 
-        Ok(drafts)
+        let conn = self.db_manager.get_connection()?;
+        let remote_ids: Vec<String> = remote.into_iter().map(|(id, _)| id).collect();
+        summary.removed = draft_operations::prune_remote_drafts(&conn, account_id, &remote_ids)? as u32;
+
+        Ok(summary)
     }
 
-    /// Delete a draft
-    pub async fn delete_draft(&self, account_id: &str, draft_id: &str) -> Result<()> {
-        // Get valid tokens
-        let tokens = self.auth_service
-            .validate_and_refresh_tokens(&self.db_manager, account_id)
-            .await?;
+    /// List cached drafts, refreshing from Gmail first when it is reachable
+    pub async fn list_drafts(&self, account_id: &str) -> Result<Vec<CachedDraft>> {
+        if let Err(e) = self.sync_drafts(account_id).await {
+            if !Self::is_offline_error(&e) {
+                return Err(e);
+            }
+            println!("📴 Gmail unreachable, listing cached drafts for {}", account_id);
+        }
 
-        let url = format!("{}/{}", GMAIL_DRAFTS_ENDPOINT, draft_id);
+        let conn = self.db_manager.get_connection()?;
+        draft_operations::list_drafts(&conn, account_id)
+    }
 
-        // Create rate-limited request
-        let batch_request = BatchRequest {
-            id: format!("delete_draft_{}", Uuid::new_v4()),
-            method: "DELETE".to_string(),
-            url: url.clone(),
-            headers: {
-                let mut headers = std::collections::HashMap::new();
-                headers.insert("Authorization".to_string(), format!("Bearer {}", tokens.access_token));
-                headers
-            },
-            body: None,
-            priority: RequestPriority::Medium,
-            created_at: chrono::Utc::now().to_rfc3339(),
-            max_retries: 3,
-            current_retry: 0,
+    /// Get a draft from the cache, downloading it if it is not cached yet
+    pub async fn get_draft(&self, account_id: &str, draft_id: &str) -> Result<CachedDraft> {
+        let cached = {
+            let conn = self.db_manager.get_connection()?;
+            draft_operations::find_draft(&conn, account_id, draft_id)?
         };
 
-        // Execute rate-limited request
-        let response = {
-            let mut rate_limiter = self.rate_limiter.lock().await;
-            rate_limiter.execute_request(batch_request).await
-                .map_err(|e| LibreOllamaError::Network {
-                    message: format!("Rate-limited request failed: {}", e),
-                    url: Some(url.clone()),
-                })?
+        match cached {
+            Some(draft) if draft.sync_status != "pending_delete" => Ok(draft),
+            Some(_) => Err(LibreOllamaError::NotFound { resource: format!("draft {}", draft_id) }.into()),
+            None => self.fetch_draft(account_id, draft_id).await,
+        }
+    }
+
+    /// Delete a draft; offline deletions are pushed on the next sync
+    pub async fn delete_draft(&self, account_id: &str, draft_id: &str) -> Result<()> {
+        let draft = {
+            let conn = self.db_manager.get_connection()?;
+            let draft = draft_operations::find_draft(&conn, account_id, draft_id)?;
+            match draft {
+                Some(draft) => {
+                    draft_operations::mark_draft_deleted(&conn, &draft.local_id)?;
+                    draft_operations::find_draft(&conn, account_id, &draft.local_id)?
+                }
+                // Not cached: delete it in Gmail directly
+                None => {
+                    draft_operations::save_local_draft(&conn, draft_id, account_id, Some(draft_id), None, &serde_json::json!({}))?;
+                    draft_operations::mark_draft_deleted(&conn, draft_id)?;
+                    draft_operations::find_draft(&conn, account_id, draft_id)?
+                }
+            }
+        };
+
+        // Drafts that never reached Gmail are already gone
+        let Some(draft) = draft else {
+            return Ok(());
+        };
+
+        match self.push_draft(&draft).await {
+            Err(e) if Self::is_offline_error(&e) => {
+                println!("📴 Gmail unreachable, draft deletion {} queued for sync", draft.local_id);
+                Ok(())
+            }
+            result => result.map(|_| ()),
+        }
+    }
+
+    /// Send a draft, pushing any unsynced local edits first
+    pub async fn send_draft(&self, account_id: &str, draft_id: &str) -> Result<SendResponse> {
+        let draft = self.get_draft(account_id, draft_id).await?;
+
+        let remote_id = if draft.sync_status == "synced" {
+            draft.draft_id.clone().unwrap_or_default()
+        } else {
+            self.push_draft(&draft).await?.0
         };
 
-        if response.status_code != 200 && response.status_code != 204 {
+        let response = self
+            .execute_gmail_request_with_retries(
+                account_id,
+                "POST",
+                &format!("{}/send", GMAIL_DRAFTS_ENDPOINT),
+                Some(serde_json::json!({ "id": remote_id })),
+                RequestPriority::High,
+                0,
+            )
+            .await?;
+
+        if response.status_code != 200 {
             return Err(LibreOllamaError::GmailApi {
-                message: format!("Delete draft failed: {} - {}", response.status_code, response.body),
+                message: format!("Send draft failed: {} - {}", response.status_code, response.body),
                 status_code: Some(response.status_code),
             }.into());
         }
 
-        // Remove from local storage
-        self.remove_draft_locally(account_id, draft_id).await?;
+        let gmail_response: serde_json::Value = serde_json::from_str(&response.body)
+            .map_err(|e| LibreOllamaError::Serialization {
+                message: format!("Failed to parse send response: {}", e),
+                data_type: "Gmail send response".to_string(),
+            })?;
 
-        Ok(())
+        // Gmail deletes a draft once it is sent
+        let conn = self.db_manager.get_connection()?;
+        draft_operations::remove_draft(&conn, &draft.local_id)?;
+
+        let compose: Option<ComposeRequest> = serde_json::from_value(draft.compose_data).ok();
+
+        if let Some(compose) = compose.as_ref().filter(|c| c.read_receipt || c.delivery_receipt) {
+            let message_id = gmail_response["id"].as_str().unwrap_or("");
+            match self.fetch_rfc822_message_id(account_id, message_id).await {
+                Ok(Some(rfc822_message_id)) => {
+                    let tracking = OutgoingTracking {
+                        rfc822_message_id,
+                        sender: None,
+                        sender_name: None,
+                        reply_to: None,
+                        tracking_token: None,
+                        pixel_url: None,
+                    };
+                    self.store_sent_message(compose, &tracking, &gmail_response).await?;
+                }
+                Ok(None) => {}
+                Err(e) => eprintln!("⚠️ Could not track receipts for sent draft {}: {}", message_id, e),
+            }
+        }
+
+        Ok(SendResponse {
+            message_id: gmail_response["id"].as_str().unwrap_or("").to_string(),
+            thread_id: gmail_response["threadId"].as_str().unwrap_or("").to_string(),
+            label_ids: gmail_response["labelIds"].as_array()
+                .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+                .unwrap_or_default(),
+            sent_at: Utc::now(),
+            size_estimate: compose.as_ref().map(|c| self.estimate_message_size(c)).unwrap_or(0),
+            status: SendStatus::Sent,
+            delivery_info: None,
+        })
     }
 
     /// Schedule a message for later sending
@@ -490,63 +755,105 @@ impl GmailComposeService {
         })
     }
 
-    /// Create a reply to an existing message
-    pub async fn create_reply(&self, reply_request: &ReplyRequest) -> Result<ComposeRequest> {
-        // Get the original message (implementation would fetch from API)
-        let original_message = self.get_cached_message(
-            &reply_request.account_id,
-            &reply_request.original_message_id,
-        ).await?;
-
-        // Build reply based on type
-        let mut compose = ComposeRequest {
-            account_id: reply_request.account_id.clone(),
-            to: match reply_request.reply_type {
-                ReplyType::Reply => vec![original_message.from.clone()],
-                ReplyType::ReplyAll => {
-                    let mut recipients = vec![original_message.from.clone()];
-                    recipients.extend(original_message.to.clone());
-                    recipients.extend(original_message.cc.clone());
-                    recipients
-                }
-                ReplyType::Forward => reply_request.additional_recipients.clone().unwrap_or_default(),
-            },
-            cc: None,
-            bcc: None,
-            subject: self.format_reply_subject(&original_message.subject, &reply_request.reply_type),
-            body_text: reply_request.body_text.clone(),
-            body_html: reply_request.body_html.clone(),
-            attachments: None,
-            reply_to_message_id: Some(reply_request.original_message_id.clone()),
-            thread_id: Some(original_message.thread_id.clone()),
-            importance: MessageImportance::Normal,
-            delivery_receipt: false,
-            read_receipt: false,
-            schedule_send: None,
+    /// Fetch the original message a reply or forward is built from
+    async fn fetch_original(&self, account_id: &str, message_id: &str) -> Result<reply_builder::OriginalMessage> {
+        let url = format!("https://www.googleapis.com/gmail/v1/users/me/messages/{}?format=raw", message_id);
+        let response = self
+            .execute_gmail_request(account_id, "GET", &url, None, RequestPriority::High)
+            .await?;
+
+        if response.status_code != 200 {
+            return Err(LibreOllamaError::GmailApi {
+                message: format!("Get message failed: {} - {}", response.status_code, response.body),
+                status_code: Some(response.status_code),
+            }.into());
+        }
+
+        let message: serde_json::Value = serde_json::from_str(&response.body)
+            .map_err(|e| LibreOllamaError::Serialization {
+                message: format!("Failed to parse message response: {}", e),
+                data_type: "Gmail message response".to_string(),
+            })?;
+        let raw = message["raw"].as_str().unwrap_or("");
+        let raw = general_purpose::URL_SAFE_NO_PAD
+            .decode(raw.trim_end_matches('='))
+            .map_err(|e| LibreOllamaError::EmailParsing {
+                message: format!("Failed to decode message: {}", e),
+            })?;
+
+        Ok(reply_builder::parse_original(
+            message_id,
+            message["threadId"].as_str().map(|s| s.to_string()),
+            &raw,
+        )?)
+    }
+
+    /// The original's date in the user's time zone, for attribution lines
+    fn attribution_date(&self, original: &reply_builder::OriginalMessage) -> Option<String> {
+        let date = original.date?;
+        let format = i18n::t("reply-date-format");
+        let local = match TimeService::from_db(&self.db_manager) {
+            Ok(time) => time.to_local(date).format_localized(&format, i18n::chrono_locale()).to_string(),
+            Err(_) => format!("{} UTC", date.format_localized(&format, i18n::chrono_locale())),
         };
+        Some(local)
+    }
+
+    /// Prefill a reply or reply-all to a message
+    pub async fn build_reply(&self, account_id: &str, message_id: &str, mode: &ReplyType) -> Result<ComposeRequest> {
+        if matches!(mode, ReplyType::Forward) {
+            return self.build_forward(account_id, message_id).await;
+        }
+
+        let original = self.fetch_original(account_id, message_id).await?;
+        let own_address = self.sender_address(account_id)?;
+        Ok(reply_builder::build_reply(
+            account_id,
+            own_address.as_deref(),
+            &original,
+            mode,
+            self.attribution_date(&original),
+        ))
+    }
+
+    /// Prefill a forward of a message, including its attachments
+    pub async fn build_forward(&self, account_id: &str, message_id: &str) -> Result<ComposeRequest> {
+        let original = self.fetch_original(account_id, message_id).await?;
+        Ok(reply_builder::build_forward(account_id, &original, self.attribution_date(&original)))
+    }
+
+    /// Create a reply to an existing message with the user's text above the quote
+    pub async fn create_reply(&self, reply_request: &ReplyRequest) -> Result<ComposeRequest> {
+        let mut compose = self
+            .build_reply(&reply_request.account_id, &reply_request.original_message_id, &reply_request.reply_type)
+            .await?;
 
         // Add additional recipients if specified
         if let Some(additional) = &reply_request.additional_recipients {
-            compose.to.extend(additional.clone());
-        }
-
-        // Include original message if requested
-        if reply_request.include_original {
-            let original_text = format!(
-                "\n\n--- Original Message ---\nFrom: {}\nDate: {}\nSubject: {}\n\n{}",
-                self.format_address(&original_message.from),
-                original_message.date,
-                original_message.subject,
-                original_message.body_text.unwrap_or_default()
-            );
-
-            if let Some(ref mut body) = compose.body_text {
-                body.push_str(&original_text);
-            } else {
-                compose.body_text = Some(original_text);
+            for addr in additional {
+                if !compose.to.iter().any(|a| a.email.eq_ignore_ascii_case(&addr.email)) {
+                    compose.to.push(addr.clone());
+                }
             }
         }
 
+        let (quoted_text, quoted_html) = if reply_request.include_original {
+            (compose.body_text.take(), compose.body_html.take())
+        } else {
+            (None, None)
+        };
+        compose.body_text = match (&reply_request.body_text, quoted_text) {
+            (Some(text), Some(quote)) => Some(format!("{}{}", text, quote)),
+            (text, quote) => text.clone().or(quote),
+        };
+        compose.body_html = match (&reply_request.body_html, quoted_html) {
+            (Some(html), Some(quote)) => Some(format!("{}{}", html, quote)),
+            (html, quote) => html.clone().or(quote),
+        };
+        if !reply_request.include_original && !matches!(reply_request.reply_type, ReplyType::Forward) {
+            compose.attachments = None;
+        }
+
         Ok(compose)
     }
 
@@ -565,10 +872,18 @@ impl GmailComposeService {
     }
 
     /// Format email message for sending
-    fn format_email_message(&self, compose: &ComposeRequest) -> Result<Vec<u8>> {
+    fn format_email_message(&self, compose: &ComposeRequest, tracking: &OutgoingTracking) -> Result<Vec<u8>> {
         let mut message = String::new();
 
         // Headers
+        if let Some(sender) = &tracking.sender {
+            let from = EmailAddress { email: sender.clone(), name: tracking.sender_name.clone() };
+            message.push_str(&format!("From: {}\r\n", self.format_address(&from)));
+        }
+        if let Some(reply_to) = &tracking.reply_to {
+            message.push_str(&format!("Reply-To: {}\r\n", reply_to));
+        }
+        message.push_str(&format!("Message-ID: <{}>\r\n", tracking.rfc822_message_id));
         message.push_str(&format!("To: {}\r\n", self.format_address_list(&compose.to)));
         
         if let Some(cc) = &compose.cc {
@@ -584,6 +899,14 @@ impl GmailComposeService {
         }
 
         message.push_str(&format!("Subject: {}\r\n", compose.subject));
+
+        if let Some(in_reply_to) = &compose.in_reply_to {
+            message.push_str(&format!("In-Reply-To: <{}>\r\n", in_reply_to));
+        }
+        if let Some(references) = compose.references.as_ref().filter(|r| !r.is_empty()) {
+            let ids: Vec<String> = references.iter().map(|id| format!("<{}>", id)).collect();
+            message.push_str(&format!("References: {}\r\n", ids.join(" ")));
+        }
         
         // Additional headers
         match compose.importance {
@@ -592,59 +915,64 @@ impl GmailComposeService {
             MessageImportance::Normal => {}
         }
 
-        if compose.delivery_receipt {
-            message.push_str("Return-Receipt-To: sender@example.com\r\n");
-        }
+        // Receipts go back to the sending account; without a known address
+        // the request cannot be honoured, so the header is left out
+        if let Some(sender) = &tracking.sender {
+            if compose.delivery_receipt {
+                message.push_str(&format!("Return-Receipt-To: {}\r\n", sender));
+            }
 
-        if compose.read_receipt {
-            message.push_str("Disposition-Notification-To: sender@example.com\r\n");
+            if compose.read_receipt {
+                message.push_str(&format!("Disposition-Notification-To: {}\r\n", sender));
+            }
         }
 
-        // MIME headers
-        let boundary = format!("boundary_{}", Uuid::new_v4());
-        message.push_str("MIME-Version: 1.0\r\n");
+        let attachments = compose.attachments.as_deref().unwrap_or_default();
 
-        if compose.attachments.is_some() || (compose.body_text.is_some() && compose.body_html.is_some()) {
-            message.push_str(&format!("Content-Type: multipart/mixed; boundary=\"{}\"\r\n\r\n", boundary));
-            
-            // Body parts
-            if let Some(text) = &compose.body_text {
-                message.push_str(&format!("--{}\r\n", boundary));
-                message.push_str("Content-Type: text/plain; charset=utf-8\r\n\r\n");
-                message.push_str(text);
-                message.push_str("\r\n");
-            }
+        // Embedded images ride in a multipart/related container next to the HTML
+        let domain = tracking.rfc822_message_id.split_once('@').map(|(_, d)| d).unwrap_or("libreollama.local");
+        let inline = compose
+            .body_html
+            .as_ref()
+            .map(|html| inline_images::prepare_inline_images(html, attachments, domain));
+        let related = inline.as_ref().map(|i| i.related.as_slice()).unwrap_or_default();
+        let attached: Vec<&ComposeAttachment> = attachments
+            .iter()
+            .filter(|a| !inline_images::is_related_part(a, related))
+            .collect();
 
-            if let Some(html) = &compose.body_html {
-                message.push_str(&format!("--{}\r\n", boundary));
-                message.push_str("Content-Type: text/html; charset=utf-8\r\n\r\n");
-                message.push_str(html);
-                message.push_str("\r\n");
-            }
+        let body_html = inline.as_ref().map(|i| match &tracking.pixel_url {
+            Some(pixel_url) => receipt_service::inject_tracking_pixel(&i.html, pixel_url),
+            None => i.html.clone(),
+        });
 
-            // Attachments
-            if let Some(attachments) = &compose.attachments {
-                for attachment in attachments {
-                    message.push_str(&format!("--{}\r\n", boundary));
-                    message.push_str(&format!("Content-Type: {}\r\n", attachment.content_type));
-                    message.push_str("Content-Transfer-Encoding: base64\r\n");
-                    message.push_str(&format!("Content-Disposition: attachment; filename=\"{}\"\r\n\r\n", attachment.filename));
-                    message.push_str(&attachment.data);
-                    message.push_str("\r\n");
-                }
-            }
+        // Innermost part first: text/html alternatives, then related images,
+        // then regular attachments
+        let mut body = match (&compose.body_text, &body_html) {
+            (Some(text), Some(html)) => multipart("alternative", &[
+                text_part("text/plain", text),
+                text_part("text/html", html),
+            ]),
+            (None, Some(html)) => text_part("text/html", html),
+            (Some(text), None) => text_part("text/plain", text),
+            (None, None) => text_part("text/plain", ""),
+        };
 
-            message.push_str(&format!("--{}--\r\n", boundary));
-        } else {
-            // Simple text message
-            message.push_str("Content-Type: text/plain; charset=utf-8\r\n\r\n");
-            if let Some(text) = &compose.body_text {
-                message.push_str(text);
-            } else if let Some(html) = &compose.body_html {
-                message.push_str(html);
-            }
+        if !related.is_empty() {
+            let mut parts = vec![body];
+            parts.extend(related.iter().map(|image| attachment_part(image, true)));
+            body = multipart("related", &parts);
         }
 
+        if !attached.is_empty() {
+            let mut parts = vec![body];
+            parts.extend(attached.iter().map(|attachment| attachment_part(attachment, false)));
+            body = multipart("mixed", &parts);
+        }
+
+        message.push_str("MIME-Version: 1.0\r\n");
+        message.push_str(&body);
+
         Ok(message.into_bytes())
     }
 
@@ -666,26 +994,6 @@ impl GmailComposeService {
         }
     }
 
-    /// Format reply subject
-    fn format_reply_subject(&self, original_subject: &str, reply_type: &ReplyType) -> String {
-        match reply_type {
-            ReplyType::Reply | ReplyType::ReplyAll => {
-                if original_subject.starts_with("Re: ") {
-                    original_subject.to_string()
-                } else {
-                    format!("Re: {}", original_subject)
-                }
-            }
-            ReplyType::Forward => {
-                if original_subject.starts_with("Fwd: ") {
-                    original_subject.to_string()
-                } else {
-                    format!("Fwd: {}", original_subject)
-                }
-            }
-        }
-    }
-
     /// Estimate message size
     fn estimate_message_size(&self, compose: &ComposeRequest) -> u64 {
         let mut size = 0u64;
@@ -704,33 +1012,214 @@ impl GmailComposeService {
     /// Store sent message locally
     async fn store_sent_message(
         &self,
-        _compose: &ComposeRequest,
+        compose: &ComposeRequest,
+        tracking: &OutgoingTracking,
         gmail_response: &serde_json::Value,
     ) -> Result<()> {
-        // Implementation would store in database
-        // For now, just log success
-        println!("Stored sent message: {}", gmail_response["id"].as_str().unwrap_or("unknown"));
+        let message_id = gmail_response["id"].as_str().unwrap_or("unknown");
+
+        // Only messages with something to report back on are tracked
+        if compose.read_receipt || compose.delivery_receipt || tracking.tracking_token.is_some() {
+            let recipients = compose.to.iter()
+                .chain(compose.cc.iter().flatten())
+                .chain(compose.bcc.iter().flatten())
+                .map(|addr| addr.email.clone())
+                .collect();
+
+            let conn = self.db_manager.get_connection()?;
+            receipt_operations::record_sent_message(&conn, &NewTrackedMessage {
+                account_id: compose.account_id.clone(),
+                message_id: message_id.to_string(),
+                thread_id: gmail_response["threadId"].as_str().map(|s| s.to_string()),
+                rfc822_message_id: tracking.rfc822_message_id.clone(),
+                subject: compose.subject.clone(),
+                recipients,
+                read_receipt_requested: compose.read_receipt,
+                delivery_receipt_requested: compose.delivery_receipt,
+                tracking_token: tracking.tracking_token.clone(),
+            })?;
+        }
+
+        println!("Stored sent message: {}", message_id);
         Ok(())
     }
 
-    /// Store draft locally
-    async fn store_draft_locally(
-        &self,
-        _draft_request: &DraftSaveRequest,
-        gmail_response: &serde_json::Value,
-    ) -> Result<()> {
-        // Implementation would store in database
-        // For now, just log success
-        println!("Stored draft: {}", gmail_response["id"].as_str().unwrap_or("unknown"));
-        Ok(())
+    /// The account's own address, if it is known
+    fn sender_address(&self, account_id: &str) -> Result<Option<String>> {
+        let conn = self.db_manager.get_connection()?;
+        Ok(conn
+            .query_row(
+                "SELECT email_address FROM gmail_accounts_secure WHERE id = ?1",
+                [account_id],
+                |row| row.get(0),
+            )
+            .ok())
     }
 
-    /// Remove draft from local storage
-    async fn remove_draft_locally(&self, account_id: &str, draft_id: &str) -> Result<()> {
-        // Implementation would remove from database
-        // For now, just log success
-        println!("Removed draft: {} for account: {}", draft_id, account_id);
-        Ok(())
+    /// Work out the sender address, Message-ID and optional pixel for an outgoing message
+    async fn prepare_tracking(&self, compose: &ComposeRequest, allow_pixel: bool) -> Result<OutgoingTracking> {
+        let own_address = self.sender_address(&compose.account_id)?;
+
+        let (sender, sender_name, reply_to) = match compose.from_alias.as_deref().map(str::trim).filter(|a| !a.is_empty()) {
+            Some(requested) => {
+                // Fetched from Gmail settings when none are stored yet
+                let aliases = self.api_service.send_as_aliases(&compose.account_id).await?;
+                let alias = resolve_from_alias(own_address.as_deref(), &aliases, requested)?;
+                (Some(alias.email), alias.display_name, alias.reply_to)
+            }
+            None => (own_address, None, None),
+        };
+
+        let domain = sender
+            .as_deref()
+            .and_then(|email| email.split_once('@'))
+            .map(|(_, domain)| domain.to_string())
+            .unwrap_or_else(|| "libreollama.local".to_string());
+
+        let conn = self.db_manager.get_connection()?;
+        let settings = receipt_operations::get_tracking_settings(&conn, &compose.account_id)?;
+        let pixel_base_url = settings
+            .pixel_base_url
+            .filter(|url| allow_pixel && settings.pixel_enabled && !url.trim().is_empty() && compose.body_html.is_some());
+        let tracking_token = pixel_base_url.as_ref().map(|_| Uuid::new_v4().simple().to_string());
+        let pixel_url = pixel_base_url
+            .zip(tracking_token.as_ref())
+            .map(|(base_url, token)| receipt_service::tracking_pixel_url(&base_url, token));
+
+        Ok(OutgoingTracking {
+            rfc822_message_id: format!("{}@{}", Uuid::new_v4(), domain),
+            sender,
+            sender_name,
+            reply_to,
+            tracking_token,
+            pixel_url,
+        })
+    }
+
+    /// Look up the RFC 822 Message-ID Gmail holds for a sent message
+    async fn fetch_rfc822_message_id(&self, account_id: &str, message_id: &str) -> Result<Option<String>> {
+        let url = format!(
+            "https://www.googleapis.com/gmail/v1/users/me/messages/{}?format=metadata&metadataHeaders=Message-ID",
+            message_id
+        );
+        let response = self
+            .execute_gmail_request(account_id, "GET", &url, None, RequestPriority::Low)
+            .await?;
+
+        if response.status_code != 200 {
+            return Err(LibreOllamaError::GmailApi {
+                message: format!("Get message failed: {} - {}", response.status_code, response.body),
+                status_code: Some(response.status_code),
+            }.into());
+        }
+
+        let message: serde_json::Value = serde_json::from_str(&response.body)?;
+        Ok(message["payload"]["headers"]
+            .as_array()
+            .and_then(|headers| {
+                headers.iter().find(|h| {
+                    h["name"].as_str().is_some_and(|name| name.eq_ignore_ascii_case("Message-ID"))
+                })
+            })
+            .and_then(|h| h["value"].as_str())
+            .map(receipt_operations::normalize_message_id))
+    }
+
+    /// Scan the inbox for MDN/DSN reports and apply them to tracked sent mail
+    pub async fn process_receipts(&self, account_id: &str) -> Result<ReceiptScanSummary> {
+        let mut query = receipt_service::RECEIPT_SEARCH_QUERY.to_string();
+        {
+            let conn = self.db_manager.get_connection()?;
+            let settings = receipt_operations::get_tracking_settings(&conn, account_id)?;
+            if let Some(last_scan) = settings.last_receipt_scan_at.and_then(|t| DateTime::parse_from_rfc3339(&t).ok()) {
+                // Gmail's after: takes epoch seconds; overlap a day to allow for late arrivals
+                query.push_str(&format!(" after:{}", (last_scan - chrono::Duration::days(1)).timestamp()));
+            }
+        }
+
+        let list_url = format!(
+            "https://www.googleapis.com/gmail/v1/users/me/messages?maxResults=100&q={}",
+            urlencoding::encode(&query)
+        );
+        let response = self
+            .execute_gmail_request(account_id, "GET", &list_url, None, RequestPriority::Low)
+            .await?;
+        if response.status_code != 200 {
+            return Err(LibreOllamaError::GmailApi {
+                message: format!("Receipt search failed: {} - {}", response.status_code, response.body),
+                status_code: Some(response.status_code),
+            }.into());
+        }
+
+        let listing: serde_json::Value = serde_json::from_str(&response.body)?;
+        let message_ids: Vec<String> = listing["messages"]
+            .as_array()
+            .map(|messages| messages.iter().filter_map(|m| m["id"].as_str().map(|s| s.to_string())).collect())
+            .unwrap_or_default();
+
+        let mut summary = ReceiptScanSummary { scanned: message_ids.len(), ..Default::default() };
+
+        for message_id in message_ids {
+            let url = format!("https://www.googleapis.com/gmail/v1/users/me/messages/{}?format=raw", message_id);
+            let response = self
+                .execute_gmail_request(account_id, "GET", &url, None, RequestPriority::Low)
+                .await?;
+            if response.status_code != 200 {
+                eprintln!("⚠️ Skipping receipt candidate {}: {}", message_id, response.status_code);
+                continue;
+            }
+
+            let message: serde_json::Value = serde_json::from_str(&response.body)?;
+            let Some(raw) = message["raw"]
+                .as_str()
+                .and_then(|raw| general_purpose::URL_SAFE.decode(raw).or_else(|_| general_purpose::URL_SAFE_NO_PAD.decode(raw)).ok())
+            else {
+                continue;
+            };
+            let Some(report) = receipt_service::parse_receipt_report(&raw) else {
+                continue;
+            };
+
+            // internalDate is epoch milliseconds; it is when the report reached the inbox
+            let reported_at = message["internalDate"]
+                .as_str()
+                .and_then(|ms| ms.parse::<i64>().ok())
+                .and_then(DateTime::<Utc>::from_timestamp_millis)
+                .unwrap_or_else(Utc::now)
+                .to_rfc3339();
+
+            let conn = self.db_manager.get_connection()?;
+            let applied = match &report {
+                ReceiptReport::Read { original_message_id, reader } => {
+                    let applied = receipt_operations::apply_read_receipt(&conn, original_message_id, reader.as_deref(), &reported_at)?;
+                    if applied {
+                        summary.read_receipts += 1;
+                    }
+                    applied
+                }
+                ReceiptReport::Delivery { original_message_id, status, .. } => {
+                    let applied = receipt_operations::apply_delivery_report(&conn, original_message_id, status, &reported_at)?;
+                    if applied {
+                        summary.delivery_reports += 1;
+                    }
+                    applied
+                }
+            };
+            if !applied {
+                summary.unmatched += 1;
+            }
+        }
+
+        let conn = self.db_manager.get_connection()?;
+        receipt_operations::set_last_receipt_scan(&conn, account_id, &Utc::now().to_rfc3339())?;
+
+        Ok(summary)
+    }
+
+    /// Delivery and read status recorded for a sent message
+    pub fn get_receipt_status(&self, account_id: &str, message_id: &str) -> Result<Option<TrackedMessage>> {
+        let conn = self.db_manager.get_connection()?;
+        receipt_operations::get_tracked_message(&conn, account_id, message_id)
     }
 
     /// Store scheduled message
@@ -744,37 +1233,211 @@ impl GmailComposeService {
         println!("Scheduled message for: {}", schedule_time);
         Ok(())
     }
+}
 
-    /// Get cached message for replies
-    async fn get_cached_message(&self, _account_id: &str, _message_id: &str) -> Result<CachedMessage> {
-        // Implementation would fetch from database or API
-        // For now, return mock data
-        Ok(CachedMessage {
-            from: EmailAddress {
-                email: "sender@example.com".to_string(),
-                name: Some("Example Sender".to_string()),
-            },
-            to: vec![EmailAddress {
-                email: "recipient@example.com".to_string(),
-                name: Some("Example Recipient".to_string()),
-            }],
-            cc: Vec::new(),
-            subject: "Example Subject".to_string(),
-            body_text: Some("Example message body".to_string()),
-            thread_id: "thread_123".to_string(),
-            date: Utc::now().to_rfc3339(),
-        })
+/// The send-as address a `from_alias` names: the account's own address or
+/// one of the verified addresses Gmail settings list for it
+pub fn resolve_from_alias(
+    own_address: Option<&str>,
+    aliases: &[SendAsAlias],
+    requested: &str,
+) -> std::result::Result<SendAsAlias, LibreOllamaError> {
+    let not_allowed = |message: String| LibreOllamaError::InvalidInput { message, field: Some("from_alias".to_string()) };
+    match aliases.iter().find(|alias| alias.email.eq_ignore_ascii_case(requested)) {
+        Some(alias) if alias.is_usable() => Ok(alias.clone()),
+        Some(_) => Err(not_allowed(format!("{} has not been verified in Gmail settings yet", requested))),
+        None if own_address.is_some_and(|own| own.eq_ignore_ascii_case(requested)) => {
+            Ok(SendAsAlias { email: requested.to_string(), is_primary: true, ..Default::default() })
+        }
+        None => Err(not_allowed(format!("{} is not a send-as address of this account", requested))),
     }
 }
 
-/// Cached message data for replies
+/// Rebuild a compose request from a raw RFC 822 draft message.
+///
+/// Only inline images with a Content-ID are restored so `cid:` references
+/// in the body keep working; other attachments stay in Gmail until the
+/// draft is edited locally.
+fn compose_from_raw(account_id: &str, raw: &[u8], thread_id: Option<String>) -> Result<ComposeRequest> {
+    use mailparse::{MailAddr, MailHeaderMap, ParsedMail};
+
+    let parsed = mailparse::parse_mail(raw).map_err(|e| LibreOllamaError::EmailParsing {
+        message: format!("Failed to parse draft message: {}", e),
+    })?;
+
+    let addresses = |header: &str| -> Vec<EmailAddress> {
+        parsed
+            .headers
+            .get_first_value(header)
+            .and_then(|value| mailparse::addrparse(&value).ok())
+            .map(|list| {
+                list.iter()
+                    .flat_map(|addr| match addr {
+                        MailAddr::Single(single) => vec![single.clone()],
+                        MailAddr::Group(group) => group.addrs.clone(),
+                    })
+                    .map(|single| EmailAddress { email: single.addr, name: single.display_name })
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    fn collect_bodies(
+        part: &ParsedMail,
+        text: &mut Option<String>,
+        html: &mut Option<String>,
+        inline: &mut Vec<ComposeAttachment>,
+    ) {
+        if part.subparts.is_empty() {
+            let content_id = part.headers.get_first_value("Content-ID");
+            match part.ctype.mimetype.as_str() {
+                "text/plain" if text.is_none() => *text = part.get_body().ok(),
+                "text/html" if html.is_none() => *html = part.get_body().ok(),
+                _ if content_id.is_some() => {
+                    if let Ok(data) = part.get_body_raw() {
+                        let disposition = part.get_content_disposition();
+                        inline.push(ComposeAttachment {
+                            filename: disposition
+                                .params
+                                .get("filename")
+                                .or_else(|| part.ctype.params.get("name"))
+                                .cloned()
+                                .unwrap_or_else(|| "image".to_string()),
+                            content_type: part.ctype.mimetype.clone(),
+                            content_id: content_id.as_deref().map(inline_images::normalize_content_id),
+                            size: data.len() as u64,
+                            data: general_purpose::STANDARD.encode(&data),
+                            is_inline: true,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        } else {
+            for subpart in &part.subparts {
+                collect_bodies(subpart, text, html, inline);
+            }
+        }
+    }
+
+    let (mut body_text, mut body_html, mut inline) = (None, None, Vec::new());
+    collect_bodies(&parsed, &mut body_text, &mut body_html, &mut inline);
+
+    let importance = match parsed.headers.get_first_value("Importance").as_deref() {
+        Some("high") => MessageImportance::High,
+        Some("low") => MessageImportance::Low,
+        _ => MessageImportance::Normal,
+    };
+
+    let optional = |list: Vec<EmailAddress>| if list.is_empty() { None } else { Some(list) };
+
+    Ok(ComposeRequest {
+        account_id: account_id.to_string(),
+        to: addresses("To"),
+        cc: optional(addresses("Cc")),
+        bcc: optional(addresses("Bcc")),
+        subject: parsed.headers.get_first_value("Subject").unwrap_or_default(),
+        body_text,
+        body_html,
+        attachments: if inline.is_empty() { None } else { Some(inline) },
+        reply_to_message_id: None,
+        in_reply_to: parsed
+            .headers
+            .get_first_value("In-Reply-To")
+            .and_then(|value| reply_builder::message_ids(&value).into_iter().next()),
+        references: parsed
+            .headers
+            .get_first_value("References")
+            .map(|value| reply_builder::message_ids(&value))
+            .filter(|ids| !ids.is_empty()),
+        thread_id,
+        importance,
+        delivery_receipt: parsed.headers.get_first_value("Return-Receipt-To").is_some(),
+        read_receipt: parsed.headers.get_first_value("Disposition-Notification-To").is_some(),
+        schedule_send: None,
+        from_alias: addresses("From").into_iter().next().map(|from| from.email),
+    })
+}
+
+/// A MIME entity (headers, blank line, content) for a UTF-8 text body
+fn text_part(content_type: &str, content: &str) -> String {
+    format!("Content-Type: {}; charset=utf-8\r\n\r\n{}\r\n", content_type, content)
+}
+
+/// A base64 attachment entity; inline parts carry their Content-ID
+fn attachment_part(attachment: &ComposeAttachment, inline: bool) -> String {
+    let mut part = format!("Content-Type: {}; name=\"{}\"\r\n", attachment.content_type, attachment.filename);
+    part.push_str("Content-Transfer-Encoding: base64\r\n");
+    if inline {
+        if let Some(content_id) = &attachment.content_id {
+            part.push_str(&format!("Content-ID: <{}>\r\n", content_id));
+        }
+        part.push_str(&format!("Content-Disposition: inline; filename=\"{}\"\r\n\r\n", attachment.filename));
+    } else {
+        part.push_str(&format!("Content-Disposition: attachment; filename=\"{}\"\r\n\r\n", attachment.filename));
+    }
+    part.push_str(&attachment.data);
+    part.push_str("\r\n");
+    part
+}
+
+/// Wrap entities in a multipart container of the given subtype
+fn multipart(subtype: &str, parts: &[String]) -> String {
+    let boundary = format!("{}_{}", subtype, Uuid::new_v4().simple());
+    let mut entity = format!("Content-Type: multipart/{}; boundary=\"{}\"\r\n\r\n", subtype, boundary);
+    for part in parts {
+        entity.push_str(&format!("--{}\r\n", boundary));
+        entity.push_str(part);
+    }
+    entity.push_str(&format!("--{}--\r\n", boundary));
+    entity
+}
+
+/// Per-message tracking details decided before formatting
 #[derive(Debug, Clone)]
-struct CachedMessage {
-    from: EmailAddress,
-    to: Vec<EmailAddress>,
-    cc: Vec<EmailAddress>,
-    subject: String,
-    body_text: Option<String>,
-    thread_id: String,
-    date: String,
-} 
\ No newline at end of file
+struct OutgoingTracking {
+    /// Message-ID header value, without angle brackets
+    rfc822_message_id: String,
+    sender: Option<String>,
+    /// Display name of the send-as address, when one was chosen
+    sender_name: Option<String>,
+    reply_to: Option<String>,
+    tracking_token: Option<String>,
+    pixel_url: Option<String>,
+}
+
+/// Result of scanning the inbox for receipts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReceiptScanSummary {
+    pub scanned: usize,
+    pub read_receipts: usize,
+    pub delivery_reports: usize,
+    pub unmatched: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alias(email: &str, verification_status: Option<&str>) -> SendAsAlias {
+        SendAsAlias { email: email.to_string(), verification_status: verification_status.map(str::to_string), ..Default::default() }
+    }
+
+    #[test]
+    fn test_from_alias_must_be_a_verified_send_as_address() {
+        let aliases = vec![
+            SendAsAlias { display_name: Some("Ada at Work".to_string()), ..alias("ada@work.example", Some("accepted")) },
+            alias("billing@example.com", Some("pending")),
+        ];
+        let own = Some("ada@example.com");
+
+        let chosen = resolve_from_alias(own, &aliases, "ADA@work.example").unwrap();
+        assert_eq!((chosen.email.as_str(), chosen.display_name.as_deref()), ("ada@work.example", Some("Ada at Work")));
+        assert!(resolve_from_alias(own, &aliases, "ada@example.com").unwrap().is_primary);
+        assert!(resolve_from_alias(own, &aliases, "billing@example.com").is_err());
+        assert!(matches!(
+            resolve_from_alias(own, &aliases, "ceo@example.com"),
+            Err(LibreOllamaError::InvalidInput { field: Some(field), .. }) if field == "from_alias"
+        ));
+    }
+}
@@ -0,0 +1,205 @@
+//! Sending oversized attachments through Google Drive
+//!
+//! When a message's attachments add up to more than Gmail accepts
+//! (`attachment_max_size_mb`), the largest ones are uploaded to Drive,
+//! shared with the recipients, and replaced by links in the body, the way
+//! Gmail's own composer does it. The behaviour can be turned off, in which
+//! case the oversized message is refused before sending.
+
+use base64::{engine::general_purpose, Engine as _};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::database::operations::preference_operations;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::gmail::compose_service::{ComposeAttachment, ComposeRequest};
+use crate::services::google::drive_service::DriveService;
+
+/// user_preferences key for the on/off setting
+pub const DRIVE_OFFLOAD_PREFERENCE: &str = "gmail.drive_offload_large_attachments";
+
+/// An attachment that was replaced by a Drive link
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OffloadedAttachment {
+    pub filename: String,
+    pub size: u64,
+    pub file_id: String,
+    pub url: String,
+}
+
+/// Whether oversized attachments go through Drive (on unless turned off)
+pub fn is_enabled(conn: &Connection) -> anyhow::Result<bool> {
+    Ok(preference_operations::get_preference_value(conn, DRIVE_OFFLOAD_PREFERENCE)?
+        .map(|value| value != "false")
+        .unwrap_or(true))
+}
+
+pub fn set_enabled(conn: &Connection, enabled: bool) -> anyhow::Result<()> {
+    preference_operations::set_preference_value(conn, DRIVE_OFFLOAD_PREFERENCE, &enabled.to_string(), "gmail")
+}
+
+pub fn total_attachment_size(compose: &ComposeRequest) -> u64 {
+    compose.attachments.iter().flatten().map(|a| a.size).sum()
+}
+
+/// Indices of the attachments to move to Drive so the rest fit in
+/// `limit_bytes`, largest first. Inline images stay in the message since
+/// the body refers to them.
+pub fn select_for_offload(attachments: &[ComposeAttachment], limit_bytes: u64) -> Vec<usize> {
+    let mut remaining: u64 = attachments.iter().map(|a| a.size).sum();
+    let mut candidates: Vec<usize> = (0..attachments.len()).filter(|&i| !attachments[i].is_inline).collect();
+    candidates.sort_by_key(|&i| std::cmp::Reverse(attachments[i].size));
+
+    let mut selected = Vec::new();
+    for index in candidates {
+        if remaining <= limit_bytes {
+            break;
+        }
+        remaining -= attachments[index].size;
+        selected.push(index);
+    }
+    selected
+}
+
+/// Append a block of Drive links to the text and HTML bodies
+pub fn insert_drive_links(compose: &mut ComposeRequest, links: &[OffloadedAttachment]) {
+    if links.is_empty() {
+        return;
+    }
+
+    let text_block: String = links
+        .iter()
+        .map(|l| format!("\n{} ({}): {}", l.filename, format_size(l.size), l.url))
+        .collect();
+    let text = compose.body_text.get_or_insert_with(String::new);
+    text.push_str("\n\nShared via Google Drive:");
+    text.push_str(&text_block);
+
+    if let Some(html) = compose.body_html.as_mut() {
+        let items: String = links
+            .iter()
+            .map(|l| {
+                format!(
+                    "<li><a href=\"{}\">{}</a> ({})</li>",
+                    l.url,
+                    escape_html(&l.filename),
+                    format_size(l.size)
+                )
+            })
+            .collect();
+        html.push_str(&format!("<p>Shared via Google Drive:</p><ul>{}</ul>", items));
+    }
+}
+
+/// Upload attachments until the rest fit in `limit_bytes`, share them with
+/// every recipient, and swap them for links in the body.
+/// `on_progress(filename, bytes_sent, total_bytes)` reports each upload.
+pub async fn offload_large_attachments(
+    drive_service: &DriveService,
+    compose: &mut ComposeRequest,
+    limit_bytes: u64,
+    on_progress: &(dyn Fn(&str, u64, u64) + Send + Sync),
+) -> Result<Vec<OffloadedAttachment>> {
+    let attachments = compose.attachments.clone().unwrap_or_default();
+    let selected = select_for_offload(&attachments, limit_bytes);
+    if selected.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let recipients: Vec<String> = compose
+        .to
+        .iter()
+        .chain(compose.cc.iter().flatten())
+        .chain(compose.bcc.iter().flatten())
+        .map(|address| address.email.clone())
+        .collect();
+
+    let mut offloaded = Vec::new();
+    for &index in &selected {
+        let attachment = &attachments[index];
+        let bytes = general_purpose::STANDARD
+            .decode(&attachment.data)
+            .map_err(|e| LibreOllamaError::InvalidInput {
+                message: format!("Attachment {} is not valid base64: {}", attachment.filename, e),
+                field: Some("attachments".to_string()),
+            })?;
+
+        println!("☁️ [DRIVE-OFFLOAD] Uploading {} ({} bytes)", attachment.filename, bytes.len());
+        let filename = attachment.filename.clone();
+        let file = drive_service
+            .upload_file(
+                &compose.account_id,
+                &attachment.filename,
+                &attachment.content_type,
+                &bytes,
+                &|sent, total| on_progress(&filename, sent, total),
+            )
+            .await?;
+        drive_service.share_with(&compose.account_id, &file.id, &recipients).await?;
+
+        offloaded.push(OffloadedAttachment {
+            filename: attachment.filename.clone(),
+            size: attachment.size,
+            url: file
+                .web_view_link
+                .clone()
+                .unwrap_or_else(|| format!("https://drive.google.com/open?id={}", file.id)),
+            file_id: file.id,
+        });
+    }
+
+    compose.attachments = Some(
+        attachments
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| !selected.contains(i))
+            .map(|(_, attachment)| attachment)
+            .collect(),
+    );
+    insert_drive_links(compose, &offloaded);
+
+    Ok(offloaded)
+}
+
+fn format_size(bytes: u64) -> String {
+    const MB: f64 = 1024.0 * 1024.0;
+    if bytes as f64 >= MB {
+        format!("{:.1} MB", bytes as f64 / MB)
+    } else {
+        format!("{} KB", bytes.div_ceil(1024))
+    }
+}
+
+fn escape_html(value: &str) -> String {
+    value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attachment(name: &str, size: u64, is_inline: bool) -> ComposeAttachment {
+        ComposeAttachment {
+            filename: name.to_string(),
+            content_type: "application/octet-stream".to_string(),
+            content_id: None,
+            data: String::new(),
+            size,
+            is_inline,
+        }
+    }
+
+    #[test]
+    fn offloads_largest_attachments_until_the_rest_fit() {
+        let attachments = vec![
+            attachment("small.txt", 2, false),
+            attachment("inline.png", 30, true),
+            attachment("big.zip", 50, false),
+            attachment("medium.pdf", 20, false),
+        ];
+
+        assert_eq!(select_for_offload(&attachments, 60), vec![2]);
+        assert_eq!(select_for_offload(&attachments, 40), vec![2, 3]);
+        assert_eq!(select_for_offload(&attachments, 120), Vec::<usize>::new());
+    }
+}
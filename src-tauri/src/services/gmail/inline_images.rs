@@ -0,0 +1,207 @@
+//! Inline images referenced by Content-ID
+//!
+//! HTML bodies point at embedded images with `<img src="cid:...">`. When
+//! composing, images the editor pasted as `data:` URIs are turned into
+//! inline parts with generated Content-IDs, and the parts the body refers
+//! to go into a multipart/related container next to the HTML. When
+//! displaying received mail, `cid:` references are swapped for `data:` URIs
+//! built from the matching inline attachments so the webview can show them.
+
+use base64::{engine::general_purpose, Engine as _};
+use regex::{Captures, Regex};
+use uuid::Uuid;
+
+use crate::services::gmail::api_service::EmailAttachment;
+use crate::services::gmail::compose_service::ComposeAttachment;
+
+lazy_static::lazy_static! {
+    static ref IMG_SRC: Regex =
+        Regex::new(r#"(?i)(<img\b[^>]*?\bsrc\s*=\s*)(["'])(.*?)(["'])"#).unwrap();
+    static ref DATA_URI: Regex =
+        Regex::new(r"(?is)^data:(image/[a-z0-9.+-]+);base64,(.+)$").unwrap();
+}
+
+/// HTML body and inline parts for a message being composed
+#[derive(Debug, Clone)]
+pub struct PreparedInlineImages {
+    pub html: String,
+    /// Parts for the multipart/related container, in reference order
+    pub related: Vec<ComposeAttachment>,
+}
+
+/// Content-ID without angle brackets or surrounding whitespace
+pub fn normalize_content_id(content_id: &str) -> String {
+    content_id.trim().trim_start_matches('<').trim_end_matches('>').trim().to_string()
+}
+
+/// Whether an attachment goes into the related container rather than the
+/// attachment list: it has to be inline, carry a Content-ID, and be
+/// referenced from the HTML body
+pub fn is_related_part(attachment: &ComposeAttachment, related: &[ComposeAttachment]) -> bool {
+    attachment.is_inline
+        && attachment.content_id.as_deref().map(normalize_content_id).is_some_and(|id| {
+            related.iter().any(|part| part.content_id.as_deref() == Some(id.as_str()))
+        })
+}
+
+/// Rewrite the HTML body's image sources for sending.
+///
+/// `data:` images become inline parts with fresh Content-IDs, and `cid:`
+/// references are normalized to match the attachments' Content-IDs.
+/// References to Content-IDs no attachment carries are left alone.
+pub fn prepare_inline_images(html: &str, attachments: &[ComposeAttachment], domain: &str) -> PreparedInlineImages {
+    let mut related: Vec<ComposeAttachment> = Vec::new();
+
+    let rewritten = IMG_SRC.replace_all(html, |caps: &Captures| {
+        let src = caps[3].trim();
+
+        if let Some(data) = DATA_URI.captures(src) {
+            let content_type = data[1].to_ascii_lowercase();
+            let encoded: String = data[2].chars().filter(|c| !c.is_whitespace()).collect();
+            let Ok(bytes) = general_purpose::STANDARD.decode(&encoded) else {
+                return caps[0].to_string();
+            };
+            let content_id = format!("{}@{}", Uuid::new_v4().simple(), domain);
+            let extension = content_type.rsplit('/').next().unwrap_or("img").trim_end_matches("+xml");
+            related.push(ComposeAttachment {
+                filename: format!("image{}.{}", related.len() + 1, extension),
+                content_type,
+                content_id: Some(content_id.clone()),
+                data: encoded,
+                size: bytes.len() as u64,
+                is_inline: true,
+            });
+            return format!("{}{}cid:{}{}", &caps[1], &caps[2], content_id, &caps[4]);
+        }
+
+        let Some(reference) = strip_cid_scheme(src) else {
+            return caps[0].to_string();
+        };
+        let reference = normalize_content_id(&percent_decode(reference));
+        let attachment = attachments.iter().find(|a| {
+            a.is_inline && a.content_id.as_deref().map(normalize_content_id).as_deref() == Some(reference.as_str())
+        });
+        let Some(attachment) = attachment else {
+            return caps[0].to_string();
+        };
+
+        if !related.iter().any(|part| part.content_id.as_deref() == Some(reference.as_str())) {
+            let mut part = attachment.clone();
+            part.content_id = Some(reference.clone());
+            related.push(part);
+        }
+        format!("{}{}cid:{}{}", &caps[1], &caps[2], reference, &caps[4])
+    });
+
+    PreparedInlineImages { html: rewritten.into_owned(), related }
+}
+
+/// Inline attachments a received HTML body refers to by Content-ID
+pub fn referenced_content_ids(html: &str) -> Vec<String> {
+    let mut ids: Vec<String> = IMG_SRC
+        .captures_iter(html)
+        .filter_map(|caps| strip_cid_scheme(caps[3].trim()).map(|id| normalize_content_id(&percent_decode(id))))
+        .collect();
+    ids.dedup();
+    ids
+}
+
+/// Replace `cid:` image sources in a received body with `data:` URIs from
+/// the matching attachments. Attachments without downloaded data are
+/// skipped and keep their `cid:` reference.
+pub fn resolve_cid_references(html: &str, attachments: &[EmailAttachment]) -> String {
+    IMG_SRC
+        .replace_all(html, |caps: &Captures| {
+            let resolved = strip_cid_scheme(caps[3].trim())
+                .map(|id| normalize_content_id(&percent_decode(id)))
+                .and_then(|id| {
+                    attachments.iter().find(|a| {
+                        a.content_id.as_deref().map(normalize_content_id).as_deref() == Some(id.as_str())
+                    })
+                })
+                .and_then(|attachment| {
+                    attachment.data.as_ref().map(|data| {
+                        format!("data:{};base64,{}", attachment.content_type, general_purpose::STANDARD.encode(data))
+                    })
+                });
+
+            match resolved {
+                Some(uri) => format!("{}{}{}{}", &caps[1], &caps[2], uri, &caps[4]),
+                None => caps[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+fn strip_cid_scheme(src: &str) -> Option<&str> {
+    src.get(..4).filter(|scheme| scheme.eq_ignore_ascii_case("cid:")).map(|_| &src[4..])
+}
+
+/// RFC 2392 allows Content-IDs to be URL-encoded in `cid:` URLs
+fn percent_decode(value: &str) -> String {
+    urlencoding::decode(value).map(|v| v.into_owned()).unwrap_or_else(|_| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inline(content_id: &str) -> ComposeAttachment {
+        ComposeAttachment {
+            filename: "logo.png".to_string(),
+            content_type: "image/png".to_string(),
+            content_id: Some(content_id.to_string()),
+            data: general_purpose::STANDARD.encode(b"png"),
+            size: 3,
+            is_inline: true,
+        }
+    }
+
+    #[test]
+    fn moves_data_uri_images_into_related_parts() {
+        let html = r#"<p>Hi</p><img alt="x" src="data:image/png;base64,cG5n">"#;
+        let prepared = prepare_inline_images(html, &[], "example.com");
+
+        assert_eq!(prepared.related.len(), 1);
+        let content_id = prepared.related[0].content_id.clone().unwrap();
+        assert!(content_id.ends_with("@example.com"));
+        assert_eq!(prepared.related[0].size, 3);
+        assert_eq!(prepared.html, format!(r#"<p>Hi</p><img alt="x" src="cid:{}">"#, content_id));
+    }
+
+    #[test]
+    fn normalizes_cid_references_and_ignores_unknown_ones() {
+        let attachments = vec![inline("<logo@example.com>")];
+        let html = r#"<img src='CID:logo%40example.com'><img src="cid:missing@example.com">"#;
+        let prepared = prepare_inline_images(html, &attachments, "example.com");
+
+        assert_eq!(
+            prepared.html,
+            r#"<img src='cid:logo@example.com'><img src="cid:missing@example.com">"#
+        );
+        assert_eq!(prepared.related.len(), 1);
+        assert!(is_related_part(&attachments[0], &prepared.related));
+        assert!(!is_related_part(&inline("other@example.com"), &prepared.related));
+    }
+
+    #[test]
+    fn resolves_received_cid_references_to_data_uris() {
+        let attachments = vec![EmailAttachment {
+            id: "att_0".to_string(),
+            filename: Some("logo.png".to_string()),
+            content_type: "image/png".to_string(),
+            size: Some(3),
+            content_id: Some("<logo@example.com>".to_string()),
+            is_inline: true,
+            data: Some(b"png".to_vec()),
+            risk: None,
+        }];
+        let html = r#"<img src="cid:logo@example.com"><img src="cid:gone@example.com">"#;
+
+        assert_eq!(referenced_content_ids(html), vec!["logo@example.com", "gone@example.com"]);
+        assert_eq!(
+            resolve_cid_references(html, &attachments),
+            r#"<img src="data:image/png;base64,cG5n"><img src="cid:gone@example.com">"#
+        );
+    }
+}
@@ -0,0 +1,346 @@
+//! Decoding of message headers and part bodies
+//!
+//! Gmail returns header values as they were sent, so subjects and display
+//! names can still hold RFC 2047 encoded-words, and part bodies are bytes
+//! in whatever charset the part declares. Address headers come in every
+//! shape mailers produce: quoted names with escapes and commas, trailing
+//! comments, and groups such as `undisclosed-recipients:;`.
+
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::Engine as _;
+use encoding_rs::{Encoding, UTF_8};
+use regex::Regex;
+
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::gmail::api_service::EmailAddress;
+
+/// Standard alphabet that accepts missing padding and non-zero trailing
+/// bits, which some mailers leave behind
+const LENIENT_BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new()
+        .with_decode_padding_mode(DecodePaddingMode::Indifferent)
+        .with_decode_allow_trailing_bits(true),
+);
+
+lazy_static::lazy_static! {
+    static ref ENCODED_WORD: Regex = Regex::new(r"=\?([^?\s]+)\?([bBqQ])\?([^?\s]*)\?=").unwrap();
+}
+
+/// Decode base64 in either alphabet, with or without padding, ignoring
+/// line breaks. Chunks that were padded separately and then concatenated
+/// are decoded one after another.
+pub fn decode_base64_with_padding(data: &str) -> Result<Vec<u8>> {
+    let cleaned: String = data
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace())
+        .map(|c| match c {
+            '-' => '+',
+            '_' => '/',
+            c => c,
+        })
+        .collect();
+
+    let mut decoded = Vec::new();
+    for chunk in cleaned.split('=').filter(|chunk| !chunk.is_empty()) {
+        match LENIENT_BASE64.decode(chunk) {
+            Ok(bytes) => decoded.extend(bytes),
+            // Too short to hold a single byte
+            Err(_) if cleaned.len() < 4 => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(LibreOllamaError::Serialization {
+                    message: format!(
+                        "Failed to decode base64 data ({}). Data length: {}, sample: '{}'",
+                        e,
+                        cleaned.len(),
+                        cleaned.chars().take(20).collect::<String>()
+                    ),
+                    data_type: "base64".to_string(),
+                })
+            }
+        }
+    }
+    Ok(decoded)
+}
+
+/// Text in `charset`, falling back to UTF-8 for unknown labels
+pub fn decode_text(bytes: &[u8], charset: Option<&str>) -> String {
+    let encoding = charset
+        .and_then(|label| Encoding::for_label(label.trim().as_bytes()))
+        .unwrap_or(UTF_8);
+    encoding.decode_without_bom_handling(bytes).0.into_owned()
+}
+
+/// The charset parameter of a Content-Type value
+pub fn charset_param(content_type: &str) -> Option<String> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+/// Decode RFC 2047 encoded-words in a header value. Whitespace between
+/// adjacent encoded-words is dropped, and their bytes are joined before
+/// decoding so a character split across two words survives. Malformed
+/// words are left as they are.
+pub fn decode_encoded_words(value: &str) -> String {
+    let mut decoded = String::new();
+    // Charset and bytes of the encoded-words seen since the last plain text
+    let mut pending: Option<(String, Vec<u8>)> = None;
+    let mut last_end = 0;
+
+    for captures in ENCODED_WORD.captures_iter(value) {
+        let word = captures.get(0).unwrap();
+        // RFC 2231 allows a language after the charset: utf-8*en
+        let charset = captures[1].split('*').next().unwrap_or_default().to_ascii_lowercase();
+        let text = &captures[3];
+        let bytes = if captures[2].eq_ignore_ascii_case("b") {
+            decode_base64_with_padding(text).ok()
+        } else {
+            Some(decode_q(text))
+        };
+        let Some(bytes) = bytes else {
+            continue;
+        };
+
+        let between = &value[last_end..word.start()];
+        if !between.trim().is_empty() || pending.is_none() {
+            flush(&mut decoded, &mut pending);
+            decoded.push_str(between);
+        }
+        match &mut pending {
+            Some((pending_charset, pending_bytes)) if *pending_charset == charset => pending_bytes.extend(bytes),
+            _ => {
+                flush(&mut decoded, &mut pending);
+                pending = Some((charset, bytes));
+            }
+        }
+        last_end = word.end();
+    }
+
+    flush(&mut decoded, &mut pending);
+    decoded.push_str(&value[last_end..]);
+    decoded
+}
+
+fn flush(decoded: &mut String, pending: &mut Option<(String, Vec<u8>)>) {
+    if let Some((charset, bytes)) = pending.take() {
+        decoded.push_str(&decode_text(&bytes, Some(&charset)));
+    }
+}
+
+/// The "Q" encoding: quoted-printable with `_` for space
+fn decode_q(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'_' => decoded.push(b' '),
+            b'=' if i + 2 < bytes.len() => {
+                let hex = |byte: u8| (byte as char).to_digit(16);
+                match (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                    (Some(high), Some(low)) => {
+                        decoded.push((high * 16 + low) as u8);
+                        i += 2;
+                    }
+                    _ => decoded.push(b'='),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    decoded
+}
+
+/// Parse one address: `Name <user@host>`, `"Last, First" <user@host>`,
+/// `user@host (Name)` or a bare `user@host`
+pub fn parse_address(value: &str) -> EmailAddress {
+    let value = value.trim();
+
+    if let (Some(start), Some(end)) = (value.rfind('<'), value.rfind('>')) {
+        if start < end {
+            return EmailAddress {
+                email: value[start + 1..end].trim().to_string(),
+                name: display_name(&value[..start]),
+            };
+        }
+    }
+
+    // A comment after the address is the old way of giving a name
+    if let (Some(open), true) = (value.find('('), value.ends_with(')')) {
+        let email = value[..open].trim();
+        if !email.is_empty() {
+            return EmailAddress {
+                email: email.to_string(),
+                name: display_name(&value[open + 1..value.len() - 1]),
+            };
+        }
+    }
+
+    EmailAddress { email: value.to_string(), name: None }
+}
+
+/// Parse an address list, leaving out group names and empty groups
+pub fn parse_address_list(value: &str) -> Vec<EmailAddress> {
+    split_address_list(value)
+        .iter()
+        .map(|address| parse_address(address))
+        .filter(|address| !address.email.is_empty())
+        .collect()
+}
+
+/// Split an address list on commas outside quotes, angle brackets and
+/// comments. A group's `name:` is dropped and its closing `;` ends an
+/// address like a comma does.
+fn split_address_list(value: &str) -> Vec<String> {
+    let mut addresses = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut escaped = false;
+    let mut in_brackets = false;
+    let mut comment_depth = 0usize;
+
+    for ch in value.chars() {
+        if escaped {
+            escaped = false;
+            current.push(ch);
+            continue;
+        }
+        match ch {
+            '\\' if in_quotes || comment_depth > 0 => escaped = true,
+            '"' if comment_depth == 0 => in_quotes = !in_quotes,
+            '(' if !in_quotes => comment_depth += 1,
+            ')' if !in_quotes => comment_depth = comment_depth.saturating_sub(1),
+            '<' if !in_quotes && comment_depth == 0 => in_brackets = true,
+            '>' if !in_quotes && comment_depth == 0 => in_brackets = false,
+            ':' if !in_quotes && !in_brackets && comment_depth == 0 => {
+                current.clear();
+                continue;
+            }
+            ',' | ';' if !in_quotes && !in_brackets && comment_depth == 0 => {
+                if !current.trim().is_empty() {
+                    addresses.push(current.trim().to_string());
+                }
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(ch);
+    }
+
+    if !current.trim().is_empty() {
+        addresses.push(current.trim().to_string());
+    }
+    addresses
+}
+
+/// A display name without quotes or escapes, with encoded-words decoded
+fn display_name(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    let unquoted = match raw.strip_prefix('"').and_then(|rest| rest.strip_suffix('"')) {
+        Some(quoted) => unescape(quoted),
+        None => raw.trim_matches('"').to_string(),
+    };
+    let name = decode_encoded_words(&unquoted).trim().to_string();
+    (!name.is_empty()).then_some(name)
+}
+
+fn unescape(quoted: &str) -> String {
+    let mut unescaped = String::with_capacity(quoted.len());
+    let mut chars = quoted.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => unescaped.extend(chars.next()),
+            ch => unescaped.push(ch),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::engine::general_purpose;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_encoded_words() {
+        assert_eq!(decode_encoded_words("=?UTF-8?B?w6l0w6k=?= report"), "été report");
+        assert_eq!(decode_encoded_words("=?ISO-8859-1?Q?caf=E9_cr=E8me?="), "café crème");
+        // "é" split across two words, with folding whitespace between them
+        assert_eq!(decode_encoded_words("=?utf-8?q?caf=C3?=\r\n =?utf-8?q?=A9?="), "café");
+        assert_eq!(decode_encoded_words("Re: =?utf-8?x?bad?= ok"), "Re: =?utf-8?x?bad?= ok");
+    }
+
+    #[test]
+    fn test_address_shapes() {
+        let quoted = parse_address(r#""Lovelace, Ada \"A.\"" <ada@example.com>"#);
+        assert_eq!((quoted.email.as_str(), quoted.name.as_deref()), ("ada@example.com", Some(r#"Lovelace, Ada "A.""#)));
+
+        let comment = parse_address("grace@example.com (Grace Hopper)");
+        assert_eq!((comment.email.as_str(), comment.name.as_deref()), ("grace@example.com", Some("Grace Hopper")));
+
+        let list = parse_address_list(r#"undisclosed-recipients:;, "a, b" <ab@example.com>, team: x@example.com, y@example.com;"#);
+        let emails: Vec<_> = list.iter().map(|address| address.email.as_str()).collect();
+        assert_eq!(emails, ["ab@example.com", "x@example.com", "y@example.com"]);
+    }
+
+    #[test]
+    fn test_charset_fallback() {
+        assert_eq!(charset_param(r#"text/plain; format=flowed; charset="ISO-8859-1""#).as_deref(), Some("ISO-8859-1"));
+        assert_eq!(decode_text(b"caf\xe9", Some("latin1")), "café");
+        assert_eq!(decode_text("café".as_bytes(), Some("x-unknown")), "café");
+    }
+
+    proptest! {
+        #[test]
+        fn prop_base64_variants_round_trip(bytes in prop::collection::vec(any::<u8>(), 0..512), wrap in 1usize..80) {
+            for encoded in [
+                general_purpose::URL_SAFE.encode(&bytes),
+                general_purpose::URL_SAFE_NO_PAD.encode(&bytes),
+                general_purpose::STANDARD.encode(&bytes),
+                general_purpose::STANDARD_NO_PAD.encode(&bytes),
+            ] {
+                prop_assert_eq!(&decode_base64_with_padding(&encoded).unwrap(), &bytes);
+
+                let wrapped: Vec<String> = encoded.as_bytes().chunks(wrap).map(|line| String::from_utf8_lossy(line).into_owned()).collect();
+                prop_assert_eq!(&decode_base64_with_padding(&wrapped.join("\r\n")).unwrap(), &bytes);
+            }
+        }
+
+        #[test]
+        fn prop_decoders_never_panic(value in any::<String>()) {
+            let _ = decode_base64_with_padding(&value);
+            let _ = decode_encoded_words(&value);
+            let _ = parse_address(&value);
+            let _ = parse_address_list(&value);
+        }
+
+        #[test]
+        fn prop_named_address_round_trips(name in "[^\"\\\\\r\n]{0,40}", email in "[a-z0-9._%+-]{1,20}@[a-z0-9-]{1,20}\\.[a-z]{2,6}") {
+            let address = parse_address(&format!("\"{}\" <{}>", name, email));
+            prop_assert_eq!(address.email, email);
+            prop_assert_eq!(address.name, Some(name.trim().to_string()).filter(|name| !name.is_empty()));
+        }
+
+        #[test]
+        fn prop_address_list_keeps_every_address(names in prop::collection::vec("[a-zA-Z ,;:<>()]{0,20}", 1..8)) {
+            let list: Vec<String> = names
+                .iter()
+                .enumerate()
+                .map(|(i, name)| format!("\"{}\" <user{}@example.com>", name, i))
+                .collect();
+            let parsed = parse_address_list(&list.join(", "));
+            prop_assert_eq!(parsed.len(), names.len());
+            for (i, address) in parsed.iter().enumerate() {
+                prop_assert_eq!(&address.email, &format!("user{}@example.com", i));
+            }
+        }
+    }
+}
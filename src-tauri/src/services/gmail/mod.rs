@@ -5,11 +5,31 @@
 
 // Service modules
 pub mod auth_service;
+pub mod address_validation;
 pub mod api_service;
 pub mod compose_service;
+pub mod campaign_service;
+pub mod drive_offload;
+pub mod inline_images;
+pub mod mime;
+pub mod muted_threads;
+pub mod out_of_office;
+pub mod receipt_service;
+pub mod reply_builder;
+pub mod search_query;
+pub mod shipment_service;
 pub mod attachment_service;
+pub mod attachment_preview;
+pub mod attachment_reminder;
+pub mod attachment_risk;
+pub mod attachment_scan;
+pub mod backfill_service;
 pub mod cache_service;
 pub mod sync_service;
+pub mod thread_actions;
+pub mod thread_export;
+pub mod translation_service;
+pub mod triage;
 
 // Test modules
 #[cfg(test)]
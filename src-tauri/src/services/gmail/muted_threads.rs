@@ -0,0 +1,70 @@
+//! Muted threads
+//!
+//! Muting a thread archives new replies as they arrive. Listings and sync
+//! both pass fetched messages through `archive_muted_messages` before they
+//! are shown, stored or announced, so replies on a muted thread never reach
+//! the inbox or raise a new-mail notification.
+
+use crate::database::{operations::mute_operations, DatabaseManager};
+use crate::services::gmail::api_service::{GmailApiService, ProcessedGmailMessage};
+
+/// Archive inbox messages that arrived on muted threads.
+///
+/// Archived messages lose their INBOX label in the result, and are dropped
+/// entirely when the listing was of the inbox itself.
+pub async fn archive_muted_messages(
+    api_service: &GmailApiService,
+    db_manager: &DatabaseManager,
+    account_id: &str,
+    messages: &mut Vec<ProcessedGmailMessage>,
+    inbox_listing: bool,
+) {
+    let muted = match db_manager.get_connection().map_err(|e| e.to_string())
+        .and_then(|conn| mute_operations::muted_thread_ids(&conn, account_id).map_err(|e| e.to_string()))
+    {
+        Ok(muted) => muted,
+        Err(e) => {
+            eprintln!("⚠️ [MUTE] Failed to load muted threads: {}", e);
+            return;
+        }
+    };
+    if muted.is_empty() {
+        return;
+    }
+
+    let to_archive: Vec<&ProcessedGmailMessage> = messages
+        .iter()
+        .filter(|m| muted.contains(&m.thread_id) && m.labels.iter().any(|l| l == "INBOX"))
+        .collect();
+    if to_archive.is_empty() {
+        return;
+    }
+    let ids: Vec<String> = to_archive.iter().map(|m| m.id.clone()).collect();
+    let mut per_thread: std::collections::HashMap<String, i64> = std::collections::HashMap::new();
+    for message in &to_archive {
+        *per_thread.entry(message.thread_id.clone()).or_default() += 1;
+    }
+
+    if let Err(e) = api_service
+        .modify_messages(account_id, ids.clone(), Vec::new(), vec!["INBOX".to_string()])
+        .await
+    {
+        eprintln!("⚠️ [MUTE] Failed to archive muted messages: {}", e);
+        return;
+    }
+    println!("🔇 [MUTE] Archived {} messages on muted threads", ids.len());
+
+    if let Ok(conn) = db_manager.get_connection() {
+        for (thread_id, count) in per_thread {
+            let _ = mute_operations::add_archived_count(&conn, account_id, &thread_id, count);
+        }
+    }
+
+    if inbox_listing {
+        messages.retain(|m| !ids.contains(&m.id));
+    } else {
+        for message in messages.iter_mut().filter(|m| ids.contains(&m.id)) {
+            message.labels.retain(|l| l != "INBOX");
+        }
+    }
+}
@@ -0,0 +1,659 @@
+//! Out-of-office advisories
+//!
+//! Before a message is sent or a meeting proposed, recipients are checked
+//! for signs of being away. The first source is their recent auto-replies
+//! in the message cache: a message flagged by `Auto-Submitted`,
+//! `X-Autoreply` and the like, or with an "Automatic reply" subject, that
+//! talks about being away. Its text is read for a return date ("back on
+//! Monday", "until October 20th"), and anything they sent after it means
+//! they are back. Where the account can read a recipient's calendar, an
+//! out-of-office event, or an all-day event showing them busy, over the
+//! time in question counts as well; meetings never do, however many fill
+//! the day. Advisories are warnings only.
+
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use chrono::{DateTime, Datelike, Duration, Month, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::commands::calendar::{EventDateTime, GoogleCalendarEvent};
+use crate::commands::rate_limiter::RequestPriority;
+use crate::database::operations::auto_reply_operations::{self, SenderMessage};
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::gmail::api_service::EmailAddress;
+use crate::services::gmail::auth_service::GmailAuthService;
+use crate::services::google::meeting_slots::{BusyInterval, CalendarAvailability};
+use crate::services::google::GoogleEndpoints;
+use crate::services::i18n;
+use crate::services::request_scheduler;
+use crate::services::time_service::TimeService;
+use crate::utils::http_client::http_client;
+use crate::utils::time;
+
+/// Auto-replies older than this are not looked at
+const AUTO_REPLY_LOOKBACK_DAYS: i64 = 30;
+/// An auto-reply without a return date only counts for this long
+const UNDATED_AUTO_REPLY_DAYS: i64 = 5;
+/// How far past the time in question calendars are queried
+const CALENDAR_LOOKAHEAD_DAYS: i64 = 14;
+/// At most this many recipients' calendars are read per check
+const MAX_CALENDARS: usize = 49;
+/// Return dates up to this many days away are shown as a weekday
+const WEEKDAY_NAME_DAYS: i64 = 6;
+/// How many words after a cue like "until" the date may appear
+const RETURN_DATE_WINDOW: usize = 6;
+
+/// Subject prefixes of Outlook, Exchange and similar auto-replies
+const AUTO_REPLY_SUBJECTS: &[&str] = &[
+    "automatic reply",
+    "auto reply",
+    "auto-reply",
+    "autoreply",
+    "out of office",
+    "out of the office",
+    "abwesenheitsnotiz",
+    "réponse automatique",
+    "respuesta automática",
+];
+/// Wording that tells an absence notice from other automatic replies,
+/// such as ticket receipts
+const AWAY_PHRASES: &[&str] = &[
+    "out of office",
+    "out of the office",
+    "ooo",
+    "on vacation",
+    "on holiday",
+    "on leave",
+    "annual leave",
+    "parental leave",
+    "away from",
+    "limited access",
+    "traveling",
+    "travelling",
+    "abwesend",
+    "urlaub",
+    "absent",
+    "vacaciones",
+];
+/// Words a return date follows; dates are only read in English
+const RETURN_CUES: &[&str] = &["until", "till", "til", "through", "thru", "back", "returning", "return"];
+/// Cues meaning the date is the last day away, not the first day back
+const INCLUSIVE_CUES: &[&str] = &["through", "thru", "including"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AwaySource {
+    AutoReply,
+    Calendar,
+}
+
+/// A recipient who seems to be away at the time in question
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AwayAdvisory {
+    pub email: String,
+    pub source: AwaySource,
+    /// Local date they are expected back, when known
+    pub back_on: Option<NaiveDate>,
+    /// The auto-reply the advisory is based on
+    pub message_id: Option<String>,
+    pub message: String,
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '-'))
+        .map(|word| word.trim_matches('-').to_lowercase())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+fn contains_phrase(words: &[String], phrases: &[&str]) -> bool {
+    let text = format!(" {} ", words.join(" "));
+    phrases.iter().any(|phrase| text.contains(&format!(" {} ", phrase)))
+}
+
+fn has_auto_reply_subject(subject: &str) -> bool {
+    let subject = subject.trim().to_lowercase();
+    AUTO_REPLY_SUBJECTS.iter().any(|prefix| subject.starts_with(prefix))
+}
+
+/// Whether a message was sent automatically in answer to one of ours
+fn is_auto_reply(message: &SenderMessage) -> bool {
+    let header = |name: &str| message.headers.get(name).map(|value| value.trim().to_lowercase());
+    header("auto-submitted").is_some_and(|value| value.starts_with("auto-replied"))
+        || header("precedence").is_some_and(|value| value == "auto_reply")
+        || message.headers.contains_key("x-autoreply")
+        || message.headers.contains_key("x-autorespond")
+        || has_auto_reply_subject(&message.subject)
+}
+
+fn is_out_of_office(message: &SenderMessage) -> bool {
+    is_auto_reply(message)
+        && (has_auto_reply_subject(&message.subject)
+            || contains_phrase(&words(&format!("{} {}", message.subject, message.text)), AWAY_PHRASES))
+}
+
+fn day_number(word: &str) -> Option<u32> {
+    let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let suffix = &word[digits.len()..];
+    if !matches!(suffix, "" | "st" | "nd" | "rd" | "th") {
+        return None;
+    }
+    digits.parse().ok().filter(|day| (1..=31).contains(day))
+}
+
+fn next_weekday(after: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let ahead = (weekday.num_days_from_monday() + 7 - after.weekday().num_days_from_monday()) % 7;
+    after + Duration::days(if ahead == 0 { 7 } else { ahead as i64 })
+}
+
+/// `month` `day` in the year given by `year`, or the next one on or after
+/// `after`
+fn month_day(month: Month, day: u32, year: Option<&String>, after: NaiveDate) -> Option<NaiveDate> {
+    let month = month.number_from_month();
+    if let Some(year) = year.filter(|year| year.len() == 4).and_then(|year| year.parse().ok()) {
+        return NaiveDate::from_ymd_opt(year, month, day);
+    }
+    let date = NaiveDate::from_ymd_opt(after.year(), month, day)?;
+    if date >= after {
+        Some(date)
+    } else {
+        NaiveDate::from_ymd_opt(after.year() + 1, month, day)
+    }
+}
+
+/// A date starting at `words[i]`: "tomorrow", a weekday, "2026-10-20",
+/// "20th of October" or "October 20, 2026"
+fn date_at(words: &[String], i: usize, received: NaiveDate) -> Option<NaiveDate> {
+    let word = words[i].as_str();
+    if word == "tomorrow" {
+        return received.succ_opt();
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(word, "%Y-%m-%d") {
+        return Some(date);
+    }
+    if let Ok(weekday) = Weekday::from_str(word) {
+        // "Friday, October 23rd" names the date outright
+        let spelled_out = (i + 1 < words.len()).then(|| date_at(words, i + 1, received)).flatten();
+        return spelled_out.or_else(|| Some(next_weekday(received, weekday)));
+    }
+    if let Some(day) = day_number(word) {
+        let next = if words.get(i + 1).is_some_and(|word| word == "of") { i + 2 } else { i + 1 };
+        if let Some(month) = words.get(next).and_then(|word| Month::from_str(word).ok()) {
+            return month_day(month, day, words.get(next + 1), received);
+        }
+    }
+    if let Ok(month) = Month::from_str(word) {
+        if let Some(day) = words.get(i + 1).and_then(|word| day_number(word)) {
+            return month_day(month, day, words.get(i + 2), received);
+        }
+    }
+    None
+}
+
+/// The day someone is back, read from auto-reply text like "back on
+/// Monday" or "out through October 20th" (back the day after). Weekdays
+/// and dates without a year are the next ones after `received`.
+fn return_date(text: &str, received: NaiveDate) -> Option<NaiveDate> {
+    let words = words(text);
+    words
+        .iter()
+        .enumerate()
+        .filter(|(_, word)| RETURN_CUES.contains(&word.as_str()))
+        .find_map(|(cue, _)| {
+            let end = (cue + 1 + RETURN_DATE_WINDOW).min(words.len());
+            (cue + 1..end).find_map(|i| {
+                let date = date_at(&words, i, received)?;
+                let inclusive = words[cue..i].iter().any(|word| INCLUSIVE_CUES.contains(&word.as_str()));
+                Some(if inclusive { date + Duration::days(1) } else { date })
+            })
+        })
+}
+
+/// Whether one sender's latest mail says they are away on `date`, with
+/// the auto-reply and return date it is based on. `messages` are theirs,
+/// newest first.
+fn away_by_auto_reply<'a>(
+    time: &TimeService,
+    messages: &[&'a SenderMessage],
+    date: NaiveDate,
+) -> Option<(&'a SenderMessage, Option<NaiveDate>)> {
+    for message in messages {
+        if !is_auto_reply(message) {
+            // They have written since
+            return None;
+        }
+        if !is_out_of_office(message) {
+            continue;
+        }
+        let received = time.to_local(DateTime::from_timestamp_millis(message.internal_date)?).date_naive();
+        let back_on = return_date(&format!("{}\n{}", message.subject, message.text), received);
+        let away = match back_on {
+            Some(back_on) => back_on > date,
+            None => (date - received).num_days() < UNDATED_AUTO_REPLY_DAYS,
+        };
+        return away.then_some((*message, back_on));
+    }
+    None
+}
+
+/// When a calendar event has its owner away: out-of-office events, and
+/// all-day events that show them busy. All-day dates are read in the
+/// user's time zone.
+fn away_interval(time: &TimeService, event: &GoogleCalendarEvent) -> Option<BusyInterval> {
+    if event.status.as_deref() == Some("cancelled") {
+        return None;
+    }
+    let (start, end) = (event.start.as_ref()?, event.end.as_ref()?);
+    let out_of_office = event.event_type.as_deref() == Some("outOfOffice");
+    let busy_all_day = start.date.is_some() && event.transparency.as_deref() != Some("transparent");
+    if !out_of_office && !busy_all_day {
+        return None;
+    }
+
+    let zone = time.zone();
+    let bound = |at: &EventDateTime| match (&at.date_time, &at.date) {
+        (Some(value), _) => time::zoned_to_utc(value, at.time_zone.as_deref(), &zone),
+        (None, Some(date)) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| time::local_to_utc(date.and_time(NaiveTime::MIN), &zone)),
+        (None, None) => None,
+    };
+    let (start, end) = (bound(start)?, bound(end)?);
+    (end > start).then_some(BusyInterval { start, end })
+}
+
+/// End of the absence covering `at`. Back-to-back absences, such as a
+/// run of all-day events, are joined first.
+fn away_by_calendar(busy: &[BusyInterval], at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let mut busy = busy.to_vec();
+    busy.sort_by_key(|interval| interval.start);
+    let mut blocks: Vec<BusyInterval> = Vec::new();
+    for interval in busy {
+        match blocks.last_mut() {
+            Some(last) if interval.start <= last.end => last.end = last.end.max(interval.end),
+            _ => blocks.push(interval),
+        }
+    }
+    blocks
+        .into_iter()
+        .find(|block| block.start <= at && at < block.end)
+        .map(|block| block.end)
+}
+
+#[derive(Debug, Deserialize)]
+struct EventList {
+    #[serde(default)]
+    items: Vec<GoogleCalendarEvent>,
+}
+
+/// Events on `calendar_id` between `start` and `end`, recurring ones
+/// expanded; None when the account can't read that calendar
+async fn calendar_events(
+    endpoints: &GoogleEndpoints,
+    account_id: &str,
+    access_token: &str,
+    calendar_id: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Option<Vec<GoogleCalendarEvent>>> {
+    let url = GoogleEndpoints::url(&endpoints.calendar_api, &format!("calendars/{}/events", urlencoding::encode(calendar_id)));
+    let request = http_client().get(&url).bearer_auth(access_token).query(&[
+        ("timeMin", start.to_rfc3339()),
+        ("timeMax", end.to_rfc3339()),
+        ("singleEvents", "true".to_string()),
+        ("maxResults", "250".to_string()),
+    ]);
+    let response = request_scheduler::send(Some(account_id), RequestPriority::Medium, request)
+        .await
+        .map_err(|e| LibreOllamaError::Network {
+            message: format!("Calendar request failed: {}", e),
+            url: Some(url.clone()),
+        })?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::FORBIDDEN || status == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(LibreOllamaError::GoogleCalendarApi {
+            message: format!("Calendar request failed: {}", error_text),
+            status_code: Some(status.as_u16()),
+        });
+    }
+    let list: EventList = response.json().await.map_err(|e| LibreOllamaError::Serialization {
+        message: format!("Failed to parse calendar response: {}", e),
+        data_type: "Calendar events".to_string(),
+    })?;
+    Ok(Some(list.items))
+}
+
+/// The absences on each calendar in `emails` the account can read,
+/// fetched together
+async fn away_calendars(
+    endpoints: &GoogleEndpoints,
+    account_id: &str,
+    access_token: &str,
+    time: &TimeService,
+    emails: &[String],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Vec<CalendarAvailability> {
+    let lookups = emails.iter().map(|email| async move {
+        match calendar_events(endpoints, account_id, access_token, email, start, end).await {
+            Ok(Some(events)) => Some(CalendarAvailability {
+                email: email.clone(),
+                visible: true,
+                busy: events.iter().filter_map(|event| away_interval(time, event)).collect(),
+            }),
+            Ok(None) => None,
+            Err(e) => {
+                eprintln!("⚠️ [OOO] Skipping the calendar of {}: {}", email, e);
+                None
+            }
+        }
+    });
+    futures::future::join_all(lookups).await.into_iter().flatten().collect()
+}
+
+/// "Sam" for "Sam Lee" or "Lee, Sam"; the address when there is no name
+fn short_name(email: &str, name: Option<&str>) -> String {
+    let name = name.map(|name| name.trim().trim_matches('"')).unwrap_or_default();
+    let given = name.split_once(',').map_or(name, |(_, given)| given);
+    given.split_whitespace().next().unwrap_or(email).to_string()
+}
+
+/// A weekday when it is close to `today`, otherwise a short date
+fn describe_date(date: NaiveDate, today: NaiveDate) -> String {
+    let format = match (date - today).num_days() {
+        0..=WEEKDAY_NAME_DAYS => "%A".to_string(),
+        _ => i18n::t("away-date-format"),
+    };
+    Utc.from_utc_datetime(&date.and_time(NaiveTime::MIN))
+        .format_localized(&format, i18n::chrono_locale())
+        .to_string()
+}
+
+/// Recipients whose cached auto-replies say they are away at `at`
+pub fn check_auto_replies(
+    conn: &Connection,
+    time: &TimeService,
+    account_id: &str,
+    recipients: &[EmailAddress],
+    at: DateTime<Utc>,
+) -> anyhow::Result<Vec<AwayAdvisory>> {
+    let emails: Vec<String> = recipients.iter().map(|recipient| recipient.email.trim().to_lowercase()).collect();
+    let since = (Utc::now() - Duration::days(AUTO_REPLY_LOOKBACK_DAYS)).timestamp_millis();
+    let messages = auto_reply_operations::recent_from(conn, account_id, &emails, since)?;
+    let date = time.to_local(at).date_naive();
+    let today = time.today();
+
+    Ok(recipients
+        .iter()
+        .zip(emails)
+        .filter_map(|(recipient, email)| {
+            let theirs: Vec<&SenderMessage> = messages.iter().filter(|message| message.from_email == email).collect();
+            let (reply, back_on) = away_by_auto_reply(time, &theirs, date)?;
+            let name = short_name(&email, recipient.name.as_deref().or(reply.from_name.as_deref()));
+            let message = match back_on {
+                Some(back_on) => i18n::t_args(
+                    "away-auto-reply-until",
+                    &[("name", name.into()), ("date", describe_date(back_on, today).into())],
+                ),
+                None => {
+                    let received = time.to_local(DateTime::from_timestamp_millis(reply.internal_date)?).date_naive();
+                    i18n::t_args("away-auto-reply", &[("name", name.into()), ("date", describe_date(received, today).into())])
+                }
+            };
+            Some(AwayAdvisory {
+                email,
+                source: AwaySource::AutoReply,
+                back_on,
+                message_id: Some(reply.message_id.clone()),
+                message,
+            })
+        })
+        .collect())
+}
+
+/// Recipients whose calendars have them away at `at`; `busy` holds each
+/// calendar's absences
+pub fn check_calendars(
+    time: &TimeService,
+    recipients: &[EmailAddress],
+    calendars: &[CalendarAvailability],
+    at: DateTime<Utc>,
+) -> Vec<AwayAdvisory> {
+    let today = time.today();
+    calendars
+        .iter()
+        .filter(|calendar| calendar.visible)
+        .filter_map(|calendar| {
+            let until = away_by_calendar(&calendar.busy, at)?;
+            let recipient = recipients.iter().find(|recipient| recipient.email.eq_ignore_ascii_case(&calendar.email));
+            let back_on = time.to_local(until).date_naive();
+            let name = short_name(&calendar.email, recipient.and_then(|recipient| recipient.name.as_deref()));
+            Some(AwayAdvisory {
+                email: calendar.email.to_lowercase(),
+                source: AwaySource::Calendar,
+                back_on: Some(back_on),
+                message_id: None,
+                message: i18n::t_args(
+                    "away-calendar-until",
+                    &[("name", name.into()), ("date", describe_date(back_on, today).into())],
+                ),
+            })
+        })
+        .collect()
+}
+
+/// Every advisory for `recipients` at `at`. Auto-replies are checked
+/// first and calendars only for everyone else. A source that fails is
+/// logged and left out, so the checks never hold up a send.
+pub async fn check_recipients(
+    db_manager: &Arc<DatabaseManager>,
+    auth_service: &GmailAuthService,
+    endpoints: &GoogleEndpoints,
+    account_id: &str,
+    recipients: &[EmailAddress],
+    at: DateTime<Utc>,
+) -> Vec<AwayAdvisory> {
+    let mut seen = HashSet::new();
+    let recipients: Vec<EmailAddress> = recipients
+        .iter()
+        .filter(|recipient| !recipient.email.trim().is_empty() && seen.insert(recipient.email.trim().to_lowercase()))
+        .cloned()
+        .collect();
+    if recipients.is_empty() {
+        return Vec::new();
+    }
+    let time = match TimeService::from_db(db_manager) {
+        Ok(time) => time,
+        Err(e) => {
+            eprintln!("⚠️ [OOO] Using UTC for out-of-office checks: {}", e);
+            TimeService::new(chrono_tz::UTC)
+        }
+    };
+
+    let mut advisories = match db_manager.get_connection() {
+        Ok(conn) => check_auto_replies(&conn, &time, account_id, &recipients, at).unwrap_or_else(|e| {
+            eprintln!("⚠️ [OOO] Failed to check auto-replies: {}", e);
+            Vec::new()
+        }),
+        Err(e) => {
+            eprintln!("⚠️ [OOO] Failed to check auto-replies: {}", e);
+            Vec::new()
+        }
+    };
+
+    let remaining: Vec<String> = recipients
+        .iter()
+        .map(|recipient| recipient.email.trim().to_lowercase())
+        .filter(|email| !advisories.iter().any(|advisory| &advisory.email == email))
+        .take(MAX_CALENDARS)
+        .collect();
+    if remaining.is_empty() {
+        return advisories;
+    }
+    match auth_service.validate_and_refresh_tokens(db_manager, account_id).await {
+        Ok(tokens) => {
+            let range_start = at - Duration::days(1);
+            let range_end = at + Duration::days(CALENDAR_LOOKAHEAD_DAYS);
+            let calendars =
+                away_calendars(endpoints, account_id, &tokens.access_token, &time, &remaining, range_start, range_end).await;
+            advisories.extend(check_calendars(&time, &recipients, &calendars, at));
+        }
+        Err(e) => eprintln!("⚠️ [OOO] Skipping calendar checks: {}", e),
+    }
+    advisories
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn time(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value).unwrap().with_timezone(&Utc)
+    }
+
+    fn message(id: &str, received: &str, subject: &str, text: &str, auto_submitted: bool) -> SenderMessage {
+        let mut headers = HashMap::new();
+        if auto_submitted {
+            headers.insert("auto-submitted".to_string(), "auto-replied".to_string());
+        }
+        SenderMessage {
+            message_id: id.to_string(),
+            from_email: "sam@example.com".to_string(),
+            from_name: Some("Sam Lee".to_string()),
+            subject: subject.to_string(),
+            text: text.to_string(),
+            headers,
+            internal_date: time(received).timestamp_millis(),
+        }
+    }
+
+    #[test]
+    fn test_return_dates() {
+        // Thursday
+        let received = date("2026-10-15");
+        let cases = [
+            ("I'm out of the office until Monday.", Some("2026-10-19")),
+            ("Back on Thursday", Some("2026-10-22")),
+            ("I will be on leave through Friday", Some("2026-10-17")),
+            ("I will be on leave through Friday, October 23rd", Some("2026-10-24")),
+            ("Returning 2 November 2026 with limited access to email", Some("2026-11-02")),
+            ("Out until the 3rd of January", Some("2027-01-03")),
+            ("On vacation until Oct 20th, 2026", Some("2026-10-20")),
+            ("Returning 2026-10-28", Some("2026-10-28")),
+            ("Away until and including Tuesday", Some("2026-10-21")),
+            ("I will reply when I return. Thanks for your patience", None),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(return_date(text, received), expected.map(date), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_auto_reply_detection() {
+        let ooo = message("a", "2026-10-15T10:00:00Z", "Re: Budget", "I am on vacation with no access to email.", true);
+        assert!(is_out_of_office(&ooo));
+
+        let outlook = message("b", "2026-10-15T10:00:00Z", "Automatic reply: Budget", "Thanks for your message.", false);
+        assert!(is_out_of_office(&outlook));
+
+        let ticket = message("c", "2026-10-15T10:00:00Z", "Ticket #123 received", "We will get back to you.", true);
+        assert!(is_auto_reply(&ticket) && !is_out_of_office(&ticket));
+
+        let person = message("d", "2026-10-15T10:00:00Z", "Re: Budget", "I am out of office, but here is the file.", false);
+        assert!(!is_auto_reply(&person));
+    }
+
+    #[test]
+    fn test_later_mail_means_they_are_back() {
+        let time_service = TimeService::new(chrono_tz::UTC);
+        let ooo = message("ooo", "2026-10-15T10:00:00Z", "Out of office", "Back on Monday.", true);
+        let ticket = message("ticket", "2026-10-16T10:00:00Z", "Request received", "We will reply soon.", true);
+        let reply = message("reply", "2026-10-16T11:00:00Z", "Re: Budget", "Sounds good.", false);
+
+        let (found, back_on) = away_by_auto_reply(&time_service, &[&ticket, &ooo], date("2026-10-16")).unwrap();
+        assert_eq!(found.message_id, "ooo");
+        assert_eq!(back_on, Some(date("2026-10-19")));
+        // A meeting on the day they return is fine
+        assert!(away_by_auto_reply(&time_service, &[&ooo], date("2026-10-19")).is_none());
+        assert!(away_by_auto_reply(&time_service, &[&reply, &ooo], date("2026-10-16")).is_none());
+
+        let undated = message("undated", "2026-10-15T10:00:00Z", "Out of office", "I have limited access to email.", true);
+        assert!(away_by_auto_reply(&time_service, &[&undated], date("2026-10-17")).is_some());
+        assert!(away_by_auto_reply(&time_service, &[&undated], date("2026-10-25")).is_none());
+    }
+
+    fn event(value: serde_json::Value) -> GoogleCalendarEvent {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_only_absences_read_as_away() {
+        let time_service = TimeService::new(chrono_tz::UTC);
+        let meetings: Vec<GoogleCalendarEvent> = (9..18)
+            .map(|hour| {
+                event(serde_json::json!({
+                    "id": format!("m{}", hour),
+                    "start": { "dateTime": format!("2026-10-16T{:02}:00:00Z", hour) },
+                    "end": { "dateTime": format!("2026-10-16T{:02}:00:00Z", hour + 1) },
+                }))
+            })
+            .collect();
+        assert!(meetings.iter().all(|meeting| away_interval(&time_service, meeting).is_none()));
+
+        let ooo = event(serde_json::json!({
+            "id": "ooo",
+            "eventType": "outOfOffice",
+            "start": { "dateTime": "2026-10-19T09:00:00-04:00" },
+            "end": { "dateTime": "2026-10-19T17:00:00-04:00" },
+        }));
+        let holiday = event(serde_json::json!({ "id": "h", "start": { "date": "2026-10-20" }, "end": { "date": "2026-10-22" } }));
+        let birthday = event(serde_json::json!({
+            "id": "b",
+            "transparency": "transparent",
+            "start": { "date": "2026-10-23" },
+            "end": { "date": "2026-10-24" },
+        }));
+        assert_eq!(
+            away_interval(&time_service, &ooo).map(|away| (away.start, away.end)),
+            Some((time("2026-10-19T13:00:00Z"), time("2026-10-19T21:00:00Z")))
+        );
+        assert!(away_interval(&time_service, &birthday).is_none());
+
+        let busy: Vec<BusyInterval> = [&ooo, &holiday].iter().filter_map(|event| away_interval(&time_service, event)).collect();
+        assert_eq!(away_by_calendar(&busy, time("2026-10-20T14:00:00Z")), Some(time("2026-10-22T00:00:00Z")));
+        assert!(away_by_calendar(&busy, time("2026-10-23T09:30:00Z")).is_none());
+
+        let calendars = [
+            CalendarAvailability { email: "sam@example.com".to_string(), visible: true, busy: busy.to_vec() },
+            CalendarAvailability { email: "kim@example.com".to_string(), visible: false, busy: Vec::new() },
+        ];
+        let recipients = [EmailAddress { email: "Sam@example.com".to_string(), name: Some("Lee, Sam".to_string()) }];
+        let advisories = check_calendars(&TimeService::new(chrono_tz::UTC), &recipients, &calendars, time("2026-10-20T14:00:00Z"));
+        assert_eq!(advisories.len(), 1);
+        assert_eq!(advisories[0].source, AwaySource::Calendar);
+        assert_eq!(advisories[0].back_on, Some(date("2026-10-22")));
+        assert!(advisories[0].message.starts_with("Sam"));
+    }
+
+    #[test]
+    fn test_short_name() {
+        assert_eq!(short_name("sam@example.com", Some("Sam Lee")), "Sam");
+        assert_eq!(short_name("sam@example.com", Some("\"Lee, Sam\"")), "Sam");
+        assert_eq!(short_name("sam@example.com", Some(" ")), "sam@example.com");
+        assert_eq!(short_name("sam@example.com", None), "sam@example.com");
+    }
+}
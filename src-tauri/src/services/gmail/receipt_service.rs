@@ -0,0 +1,204 @@
+//! Read receipt and delivery report parsing
+//!
+//! Recipients' mail clients answer `Disposition-Notification-To` with an MDN
+//! (RFC 8098) and servers answer `Return-Receipt-To` with a DSN (RFC 3464).
+//! Both arrive as `multipart/report` messages in the inbox; this module turns
+//! them into [`ReceiptReport`]s keyed by the original Message-ID.
+
+use mailparse::{MailHeaderMap, ParsedMail};
+use serde::{Deserialize, Serialize};
+
+/// Gmail search that finds MDN and DSN reports
+pub const RECEIPT_SEARCH_QUERY: &str =
+    "in:inbox (subject:\"read:\" OR subject:\"delivery status notification\" OR subject:\"return receipt\" OR from:mailer-daemon OR from:postmaster)";
+
+/// Path of the pixel image on a self-hosted tracking server
+pub fn tracking_pixel_url(base_url: &str, token: &str) -> String {
+    format!("{}/t/{}.gif", base_url.trim_end_matches('/'), token)
+}
+
+/// Insert a 1x1 tracking image just before `</body>`, or at the end of the fragment
+pub fn inject_tracking_pixel(html: &str, pixel_url: &str) -> String {
+    let img = format!(
+        "<img src=\"{}\" width=\"1\" height=\"1\" alt=\"\" style=\"display:none\" />",
+        pixel_url
+    );
+    match html.to_ascii_lowercase().rfind("</body>") {
+        Some(index) => format!("{}{}{}", &html[..index], img, &html[index..]),
+        None => format!("{}{}", html, img),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ReceiptReport {
+    /// The recipient's client displayed the message
+    Read {
+        original_message_id: String,
+        reader: Option<String>,
+    },
+    /// A server reported on delivery: `delivered`, `delayed` or `failed`
+    Delivery {
+        original_message_id: String,
+        status: String,
+        recipient: Option<String>,
+    },
+}
+
+impl ReceiptReport {
+    pub fn original_message_id(&self) -> &str {
+        match self {
+            ReceiptReport::Read { original_message_id, .. } => original_message_id,
+            ReceiptReport::Delivery { original_message_id, .. } => original_message_id,
+        }
+    }
+}
+
+/// Whether a message with this Content-Type may be a receipt report
+pub fn is_report(content_type: &str) -> bool {
+    content_type.trim_start().to_ascii_lowercase().starts_with("multipart/report")
+}
+
+/// Parse a raw RFC 822 message into a receipt report, if it is one
+pub fn parse_receipt_report(raw: &[u8]) -> Option<ReceiptReport> {
+    let parsed = mailparse::parse_mail(raw).ok()?;
+    if parsed.ctype.mimetype != "multipart/report" {
+        return None;
+    }
+
+    let report_part = find_part(&parsed, &["message/disposition-notification", "message/delivery-status"])?;
+    let fields = report_fields(&report_part.get_body_raw().ok()?);
+    let field = |name: &str| {
+        fields
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.clone())
+    };
+
+    // DSNs rarely quote the Message-ID in the report itself, so fall back to
+    // the returned headers of the original message
+    let original_message_id = field("Original-Message-ID").or_else(|| returned_message_id(&parsed))?;
+
+    if report_part.ctype.mimetype == "message/disposition-notification" {
+        let disposition = field("Disposition")?.to_lowercase();
+        // "deleted" and "dispatched" dispositions are not reads
+        if !disposition.contains("displayed") {
+            return None;
+        }
+        Some(ReceiptReport::Read {
+            original_message_id,
+            reader: field("Final-Recipient").map(strip_address_type),
+        })
+    } else {
+        let status = match field("Action")?.to_lowercase().as_str() {
+            "delivered" | "relayed" | "expanded" => "delivered",
+            "delayed" => "delayed",
+            "failed" => "failed",
+            _ => return None,
+        };
+        Some(ReceiptReport::Delivery {
+            original_message_id,
+            status: status.to_string(),
+            recipient: field("Final-Recipient").map(strip_address_type),
+        })
+    }
+}
+
+fn find_part<'a>(part: &'a ParsedMail<'a>, mimetypes: &[&str]) -> Option<&'a ParsedMail<'a>> {
+    if mimetypes.contains(&part.ctype.mimetype.as_str()) {
+        return Some(part);
+    }
+    part.subparts.iter().find_map(|subpart| find_part(subpart, mimetypes))
+}
+
+fn returned_message_id(parsed: &ParsedMail) -> Option<String> {
+    let returned = find_part(parsed, &["message/rfc822", "text/rfc822-headers"])?;
+    let body = returned.get_body_raw().ok()?;
+    let (headers, _) = mailparse::parse_headers(&body).ok()?;
+    headers.get_first_value("Message-ID")
+}
+
+/// Report bodies are header-style fields in one or more blank-line separated
+/// groups (per-message, then per-recipient); flatten them in order
+fn report_fields(body: &[u8]) -> Vec<(String, String)> {
+    let text = String::from_utf8_lossy(body).replace("\r\n", "\n");
+    let mut fields: Vec<(String, String)> = Vec::new();
+    for line in text.lines() {
+        if line.starts_with(' ') || line.starts_with('\t') {
+            if let Some((_, value)) = fields.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((key, value)) = line.split_once(':') {
+            fields.push((key.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    fields
+}
+
+/// "rfc822; someone@example.com" -> "someone@example.com"
+fn strip_address_type(value: String) -> String {
+    match value.split_once(';') {
+        Some((_, address)) => address.trim().to_string(),
+        None => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reports_are_recognised_by_content_type() {
+        assert!(is_report("Multipart/Report; report-type=delivery-status"));
+        assert!(!is_report("multipart/mixed"));
+    }
+
+    #[test]
+    fn test_parse_mdn_and_dsn_reports() {
+        let mdn = "From: bob@example.com\r\n\
+            Subject: Read: Hello\r\n\
+            MIME-Version: 1.0\r\n\
+            Content-Type: multipart/report; report-type=disposition-notification; boundary=\"b1\"\r\n\r\n\
+            --b1\r\nContent-Type: text/plain\r\n\r\nYour message was displayed.\r\n\
+            --b1\r\nContent-Type: message/disposition-notification\r\n\r\n\
+            Final-Recipient: rfc822; bob@example.com\r\n\
+            Original-Message-ID: <abc@libreollama.local>\r\n\
+            Disposition: manual-action/MDN-sent-manually; displayed\r\n\
+            --b1--\r\n";
+        assert_eq!(
+            parse_receipt_report(mdn.as_bytes()),
+            Some(ReceiptReport::Read {
+                original_message_id: "<abc@libreollama.local>".to_string(),
+                reader: Some("bob@example.com".to_string()),
+            })
+        );
+
+        let dsn = "From: mailer-daemon@example.com\r\n\
+            Subject: Delivery Status Notification\r\n\
+            MIME-Version: 1.0\r\n\
+            Content-Type: multipart/report; report-type=delivery-status; boundary=\"b2\"\r\n\r\n\
+            --b2\r\nContent-Type: text/plain\r\n\r\nDelivered.\r\n\
+            --b2\r\nContent-Type: message/delivery-status\r\n\r\n\
+            Reporting-MTA: dns; mx.example.com\r\n\r\n\
+            Final-Recipient: rfc822; carol@example.com\r\n\
+            Action: delivered\r\n\
+            Status: 2.0.0\r\n\
+            --b2\r\nContent-Type: text/rfc822-headers\r\n\r\n\
+            Message-ID: <def@libreollama.local>\r\n\
+            Subject: Hello\r\n\
+            --b2--\r\n";
+        assert_eq!(
+            parse_receipt_report(dsn.as_bytes()),
+            Some(ReceiptReport::Delivery {
+                original_message_id: "<def@libreollama.local>".to_string(),
+                status: "delivered".to_string(),
+                recipient: Some("carol@example.com".to_string()),
+            })
+        );
+
+        assert_eq!(parse_receipt_report(b"Subject: hi\r\n\r\nplain"), None);
+        assert!(inject_tracking_pixel("<html><body>Hi</body></html>", "https://t.example/t/x.gif")
+            .ends_with("style=\"display:none\" /></body></html>"));
+    }
+}
@@ -0,0 +1,386 @@
+//! Reply and forward drafts built from an original message
+//!
+//! The original is parsed from its raw RFC 822 form so the threading headers
+//! (Message-ID, References) and attachments are available exactly as sent.
+//! Replies thread through In-Reply-To/References and quote the original under
+//! an attribution line; forwards carry a "Forwarded message" header block and
+//! the original's attachments. Both are written in the app's language; the
+//! "Re:" and "Fwd:" subject prefixes stay as mail clients expect them.
+
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Utc};
+use mailparse::{DispositionType, MailAddr, MailHeaderMap, ParsedMail};
+
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::gmail::api_service::EmailAddress;
+use crate::services::gmail::compose_service::{ComposeAttachment, ComposeRequest, MessageImportance, ReplyType};
+use crate::services::gmail::inline_images;
+use crate::services::i18n;
+
+/// The parts of an original message a reply or forward is built from
+#[derive(Debug, Clone)]
+pub struct OriginalMessage {
+    pub gmail_id: String,
+    pub thread_id: Option<String>,
+    /// Message-ID header value, without angle brackets
+    pub rfc822_message_id: Option<String>,
+    pub references: Vec<String>,
+    pub from: Option<EmailAddress>,
+    pub reply_to: Vec<EmailAddress>,
+    pub to: Vec<EmailAddress>,
+    pub cc: Vec<EmailAddress>,
+    pub subject: String,
+    pub date: Option<DateTime<Utc>>,
+    pub body_text: Option<String>,
+    pub body_html: Option<String>,
+    pub attachments: Vec<ComposeAttachment>,
+}
+
+/// Parse a raw message fetched with `format=raw`
+pub fn parse_original(gmail_id: &str, thread_id: Option<String>, raw: &[u8]) -> Result<OriginalMessage> {
+    let parsed = mailparse::parse_mail(raw).map_err(|e| LibreOllamaError::EmailParsing {
+        message: format!("Failed to parse original message: {}", e),
+    })?;
+
+    let mut original = OriginalMessage {
+        gmail_id: gmail_id.to_string(),
+        thread_id,
+        rfc822_message_id: parsed.headers.get_first_value("Message-ID").and_then(|v| message_ids(&v).into_iter().next()),
+        references: parsed.headers.get_first_value("References").map(|v| message_ids(&v)).unwrap_or_default(),
+        from: addresses(&parsed, "From").into_iter().next(),
+        reply_to: addresses(&parsed, "Reply-To"),
+        to: addresses(&parsed, "To"),
+        cc: addresses(&parsed, "Cc"),
+        subject: parsed.headers.get_first_value("Subject").unwrap_or_default(),
+        date: parsed
+            .headers
+            .get_first_value("Date")
+            .and_then(|v| mailparse::dateparse(&v).ok())
+            .and_then(|ts| DateTime::<Utc>::from_timestamp(ts, 0)),
+        body_text: None,
+        body_html: None,
+        attachments: Vec::new(),
+    };
+    collect_parts(&parsed, &mut original);
+
+    Ok(original)
+}
+
+/// Build a reply or reply-all. `own_address` is the replying account, which
+/// is never addressed and, when it sent the original, keeps its recipients.
+pub fn build_reply(
+    account_id: &str,
+    own_address: Option<&str>,
+    original: &OriginalMessage,
+    mode: &ReplyType,
+    attribution_date: Option<String>,
+) -> ComposeRequest {
+    let is_own = |addr: &EmailAddress| own_address.is_some_and(|own| addr.email.eq_ignore_ascii_case(own));
+    let sent_by_me = original.from.as_ref().is_some_and(is_own);
+
+    let mut to: Vec<EmailAddress> = if sent_by_me {
+        original.to.clone()
+    } else if !original.reply_to.is_empty() {
+        original.reply_to.clone()
+    } else {
+        original.from.clone().into_iter().collect()
+    };
+    let mut cc = Vec::new();
+
+    if matches!(mode, ReplyType::ReplyAll) {
+        if !sent_by_me {
+            to.extend(original.to.iter().cloned());
+        }
+        cc.extend(original.cc.iter().cloned());
+    }
+
+    let mut to = dedupe(to, &[], &is_own);
+    // Replying to yourself with nobody else on the message goes back to you
+    if to.is_empty() && sent_by_me {
+        to = original.from.clone().into_iter().collect();
+    }
+    let cc = dedupe(cc, &to, &is_own);
+
+    let mut references = original.references.clone();
+    if let Some(id) = &original.rfc822_message_id {
+        if !references.contains(id) {
+            references.push(id.clone());
+        }
+    }
+
+    let sender = original.from.as_ref().map(display_address).unwrap_or_else(|| i18n::t("reply-unknown-sender"));
+    let attribution = match attribution_date {
+        Some(date) => i18n::t_args("reply-attribution", &[("date", date.into()), ("sender", sender.into())]),
+        None => i18n::t_args("reply-attribution-undated", &[("sender", sender.into())]),
+    };
+
+    ComposeRequest {
+        account_id: account_id.to_string(),
+        to,
+        cc: if cc.is_empty() { None } else { Some(cc) },
+        bcc: None,
+        subject: prefixed_subject(&original.subject, "Re:", &["re:", "aw:", "sv:"]),
+        body_text: Some(format!("\n\n{}\n{}", attribution, quote_text(original.body_text.as_deref().unwrap_or_default()))),
+        body_html: Some(format!(
+            "<br><br><div class=\"gmail_quote\"><div class=\"gmail_attr\">{}<br></div>\
+             <blockquote class=\"gmail_quote\" style=\"margin:0 0 0 .8ex;border-left:1px #ccc solid;padding-left:1ex\">{}</blockquote></div>",
+            escape_html(&attribution),
+            original_html(original),
+        )),
+        // The quoted HTML still refers to the original's inline images
+        attachments: inline_only(&original.attachments),
+        reply_to_message_id: Some(original.gmail_id.clone()),
+        in_reply_to: original.rfc822_message_id.clone(),
+        references: if references.is_empty() { None } else { Some(references) },
+        thread_id: original.thread_id.clone(),
+        importance: MessageImportance::Normal,
+        delivery_receipt: false,
+        read_receipt: false,
+        schedule_send: None,
+        from_alias: None,
+    }
+}
+
+/// Build a forward with the original's header block, body and attachments
+pub fn build_forward(account_id: &str, original: &OriginalMessage, header_date: Option<String>) -> ComposeRequest {
+    let mut header_lines = vec![
+        (i18n::t("forward-from"), original.from.as_ref().map(display_address).unwrap_or_default()),
+        (i18n::t("forward-date"), header_date.unwrap_or_default()),
+        (i18n::t("forward-subject"), original.subject.clone()),
+        (i18n::t("forward-to"), original.to.iter().map(display_address).collect::<Vec<_>>().join(", ")),
+    ];
+    if !original.cc.is_empty() {
+        header_lines.push((i18n::t("forward-cc"), original.cc.iter().map(display_address).collect::<Vec<_>>().join(", ")));
+    }
+    let forwarded = i18n::t("forward-header");
+
+    let text_header: String = header_lines.iter().map(|(name, value)| format!("{}: {}\n", name, value)).collect();
+    let html_header: String = header_lines
+        .iter()
+        .map(|(name, value)| format!("{}: {}<br>", name, escape_html(value)))
+        .collect();
+
+    ComposeRequest {
+        account_id: account_id.to_string(),
+        to: Vec::new(),
+        cc: None,
+        bcc: None,
+        subject: prefixed_subject(&original.subject, "Fwd:", &["fwd:", "fw:"]),
+        body_text: Some(format!(
+            "\n\n{}\n{}\n{}",
+            forwarded,
+            text_header,
+            original.body_text.as_deref().unwrap_or_default()
+        )),
+        body_html: Some(format!(
+            "<br><br><div class=\"gmail_quote\"><div class=\"gmail_attr\">{}<br>{}</div><br>{}</div>",
+            escape_html(&forwarded),
+            html_header,
+            original_html(original),
+        )),
+        attachments: if original.attachments.is_empty() { None } else { Some(original.attachments.clone()) },
+        reply_to_message_id: None,
+        in_reply_to: None,
+        references: None,
+        // A forward starts a new conversation
+        thread_id: None,
+        importance: MessageImportance::Normal,
+        delivery_receipt: false,
+        read_receipt: false,
+        schedule_send: None,
+        from_alias: None,
+    }
+}
+
+/// Add `prefix` unless the subject already starts with one of `existing`
+pub fn prefixed_subject(subject: &str, prefix: &str, existing: &[&str]) -> String {
+    let trimmed = subject.trim();
+    let lower = trimmed.to_lowercase();
+    if existing.iter().any(|p| lower.starts_with(p)) {
+        trimmed.to_string()
+    } else if trimmed.is_empty() {
+        prefix.to_string()
+    } else {
+        format!("{} {}", prefix, trimmed)
+    }
+}
+
+fn quote_text(text: &str) -> String {
+    text.lines()
+        .map(|line| if line.is_empty() { ">".to_string() } else { format!("> {}", line) })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn original_html(original: &OriginalMessage) -> String {
+    match (&original.body_html, &original.body_text) {
+        (Some(html), _) => html.clone(),
+        (None, Some(text)) => escape_html(text).replace('\n', "<br>"),
+        (None, None) => String::new(),
+    }
+}
+
+fn inline_only(attachments: &[ComposeAttachment]) -> Option<Vec<ComposeAttachment>> {
+    let inline: Vec<ComposeAttachment> = attachments
+        .iter()
+        .filter(|a| a.is_inline && a.content_id.is_some())
+        .cloned()
+        .collect();
+    if inline.is_empty() { None } else { Some(inline) }
+}
+
+/// Drop duplicates, the replying account and anyone already in `exclude`
+fn dedupe(
+    addresses: Vec<EmailAddress>,
+    exclude: &[EmailAddress],
+    is_own: &dyn Fn(&EmailAddress) -> bool,
+) -> Vec<EmailAddress> {
+    let mut kept: Vec<EmailAddress> = Vec::new();
+    for addr in addresses {
+        let seen = kept.iter().chain(exclude).any(|k| k.email.eq_ignore_ascii_case(&addr.email));
+        if !seen && !is_own(&addr) && !addr.email.trim().is_empty() {
+            kept.push(addr);
+        }
+    }
+    kept
+}
+
+fn display_address(addr: &EmailAddress) -> String {
+    match &addr.name {
+        Some(name) if !name.is_empty() => format!("{} <{}>", name, addr.email),
+        _ => addr.email.clone(),
+    }
+}
+
+fn escape_html(value: &str) -> String {
+    value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Message IDs in a Message-ID or References header, without angle brackets
+pub fn message_ids(value: &str) -> Vec<String> {
+    value
+        .split(['<', '>'])
+        .map(str::trim)
+        .filter(|part| part.contains('@') && !part.contains(char::is_whitespace))
+        .map(str::to_string)
+        .collect()
+}
+
+fn addresses(parsed: &ParsedMail, header: &str) -> Vec<EmailAddress> {
+    parsed
+        .headers
+        .get_first_value(header)
+        .and_then(|value| mailparse::addrparse(&value).ok())
+        .map(|list| {
+            list.iter()
+                .flat_map(|addr| match addr {
+                    MailAddr::Single(single) => vec![single.clone()],
+                    MailAddr::Group(group) => group.addrs.clone(),
+                })
+                .map(|single| EmailAddress { email: single.addr, name: single.display_name })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn collect_parts(part: &ParsedMail, original: &mut OriginalMessage) {
+    if !part.subparts.is_empty() {
+        for subpart in &part.subparts {
+            collect_parts(subpart, original);
+        }
+        return;
+    }
+
+    let disposition = part.get_content_disposition();
+    let content_id = part.headers.get_first_value("Content-ID").map(|id| inline_images::normalize_content_id(&id));
+    let filename = disposition.params.get("filename").or_else(|| part.ctype.params.get("name")).cloned();
+    let is_attachment = disposition.disposition == DispositionType::Attachment || filename.is_some() || content_id.is_some();
+
+    match part.ctype.mimetype.as_str() {
+        "text/plain" if !is_attachment && original.body_text.is_none() => original.body_text = part.get_body().ok(),
+        "text/html" if !is_attachment && original.body_html.is_none() => original.body_html = part.get_body().ok(),
+        _ if is_attachment => {
+            if let Ok(data) = part.get_body_raw() {
+                original.attachments.push(ComposeAttachment {
+                    filename: filename.unwrap_or_else(|| format!("attachment{}", original.attachments.len() + 1)),
+                    content_type: part.ctype.mimetype.clone(),
+                    is_inline: disposition.disposition == DispositionType::Inline && content_id.is_some(),
+                    content_id,
+                    size: data.len() as u64,
+                    data: general_purpose::STANDARD.encode(&data),
+                });
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RAW: &str = "From: Alice <alice@example.com>\r\n\
+        To: me@example.com, Bob <bob@example.com>\r\n\
+        Cc: carol@example.com, me@example.com\r\n\
+        Subject: Lunch\r\n\
+        Message-ID: <m2@example.com>\r\n\
+        References: <m1@example.com>\r\n\
+        Date: Tue, 1 Sep 2026 10:00:00 +0000\r\n\
+        MIME-Version: 1.0\r\n\
+        Content-Type: multipart/mixed; boundary=\"b\"\r\n\
+        \r\n\
+        --b\r\n\
+        Content-Type: text/plain; charset=utf-8\r\n\
+        \r\n\
+        Noon?\r\n\
+        --b\r\n\
+        Content-Type: application/pdf; name=\"menu.pdf\"\r\n\
+        Content-Disposition: attachment; filename=\"menu.pdf\"\r\n\
+        Content-Transfer-Encoding: base64\r\n\
+        \r\n\
+        cGRm\r\n\
+        --b--\r\n";
+
+    fn original() -> OriginalMessage {
+        parse_original("gm2", Some("t1".to_string()), RAW.as_bytes()).unwrap()
+    }
+
+    fn emails(addresses: &[EmailAddress]) -> Vec<&str> {
+        addresses.iter().map(|a| a.email.as_str()).collect()
+    }
+
+    #[test]
+    fn reply_all_threads_and_skips_own_address() {
+        let reply = build_reply("acc", Some("me@example.com"), &original(), &ReplyType::ReplyAll, Some("Sep 1".to_string()));
+
+        assert_eq!(emails(&reply.to), vec!["alice@example.com", "bob@example.com"]);
+        assert_eq!(emails(reply.cc.as_deref().unwrap()), vec!["carol@example.com"]);
+        assert_eq!(reply.subject, "Re: Lunch");
+        assert_eq!(reply.in_reply_to.as_deref(), Some("m2@example.com"));
+        assert_eq!(reply.references.unwrap(), vec!["m1@example.com", "m2@example.com"]);
+        assert_eq!(reply.thread_id.as_deref(), Some("t1"));
+        assert!(reply.body_text.unwrap().contains("On Sep 1, Alice <alice@example.com> wrote:\n> Noon?"));
+        assert!(reply.attachments.is_none());
+    }
+
+    #[test]
+    fn forward_carries_attachments_and_starts_a_new_thread() {
+        let forward = build_forward("acc", &original(), None);
+
+        assert_eq!(forward.subject, "Fwd: Lunch");
+        assert!(forward.to.is_empty());
+        assert!(forward.thread_id.is_none());
+        let attachments = forward.attachments.unwrap();
+        assert_eq!(attachments[0].filename, "menu.pdf");
+        assert_eq!(attachments[0].data, "cGRm");
+        let body = forward.body_text.unwrap();
+        assert!(body.contains("---------- Forwarded message ---------\nFrom: Alice <alice@example.com>\n"));
+        assert!(body.contains("Subject: Lunch\n"));
+    }
+
+    #[test]
+    fn existing_prefixes_are_kept() {
+        assert_eq!(prefixed_subject("RE: Lunch", "Re:", &["re:"]), "RE: Lunch");
+        assert_eq!(prefixed_subject("Fw: Lunch", "Fwd:", &["fwd:", "fw:"]), "Fw: Lunch");
+    }
+}
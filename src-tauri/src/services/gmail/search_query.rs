@@ -0,0 +1,390 @@
+//! Gmail search query builder and validator
+//!
+//! Structured filters (sender, subject, dates, size, labels...) are turned
+//! into Gmail's query syntax here so the frontend, saved searches and smart
+//! folders never assemble query strings by hand. Values are quoted when
+//! they need it, label names are written the way Gmail expects, and
+//! contradictory filters (an end date before the start date) are rejected.
+//!
+//! Free text is passed through, but its operators are checked: unknown
+//! operators, bad dates or sizes, and unbalanced quotes or parentheses are
+//! reported so they can be fixed before Gmail silently treats them as text.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{LibreOllamaError, Result};
+
+/// Operators Gmail understands
+const OPERATORS: &[&str] = &[
+    "from", "to", "cc", "bcc", "subject", "label", "has", "is", "in", "after", "before", "older", "newer",
+    "older_than", "newer_than", "larger", "smaller", "size", "filename", "category", "list", "deliveredto",
+    "rfc822msgid", "around",
+];
+const HAS_VALUES: &[&str] = &[
+    "attachment", "drive", "document", "spreadsheet", "presentation", "youtube", "userlabels", "nouserlabels",
+    "yellow-star", "orange-star", "red-star", "purple-star", "blue-star", "green-star", "red-bang",
+    "orange-guillemet", "yellow-bang", "green-check", "blue-info", "purple-question",
+];
+const IS_VALUES: &[&str] = &["read", "unread", "starred", "important", "snoozed", "muted", "chat"];
+const IN_VALUES: &[&str] = &["inbox", "sent", "drafts", "trash", "spam", "anywhere", "snoozed", "chats"];
+const CATEGORY_VALUES: &[&str] = &["primary", "social", "promotions", "updates", "forums", "reservations", "purchases"];
+
+/// Structured search filters; unset fields are left out of the query
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchFilters {
+    /// Free text, may contain Gmail operators
+    pub text: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub subject: Option<String>,
+    pub has_attachment: Option<bool>,
+    pub filename: Option<String>,
+    /// Messages on or after this day
+    pub after: Option<NaiveDate>,
+    /// Messages before this day
+    pub before: Option<NaiveDate>,
+    pub newer_than_days: Option<u32>,
+    pub labels: Vec<String>,
+    pub exclude_labels: Vec<String>,
+    /// Sizes in bytes
+    pub larger_than: Option<u64>,
+    pub smaller_than: Option<u64>,
+    pub is_unread: Option<bool>,
+    pub is_starred: Option<bool>,
+    /// Gmail folder such as inbox, sent or anywhere
+    pub in_folder: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueSeverity {
+    /// Gmail would reject or misread the query
+    Error,
+    /// Gmail would run the query, probably not as intended
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueryIssue {
+    pub severity: IssueSeverity,
+    pub operator: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuiltSearchQuery {
+    pub query: String,
+    /// Warnings about the free text; errors fail the build instead
+    pub issues: Vec<QueryIssue>,
+}
+
+fn invalid(field: &str, message: impl Into<String>) -> LibreOllamaError {
+    LibreOllamaError::InvalidInput { message: message.into(), field: Some(field.to_string()) }
+}
+
+/// A value as Gmail expects it after an operator: quoted when it has
+/// spaces or characters Gmail would read as syntax
+pub fn quote_value(value: &str) -> String {
+    let value = value.trim().replace('"', "");
+    let needs_quotes = value.is_empty()
+        || value.chars().any(|c| c.is_whitespace() || matches!(c, '(' | ')' | '{' | '}' | ':' | '-'));
+    if needs_quotes {
+        format!("\"{}\"", value)
+    } else {
+        value
+    }
+}
+
+/// Gmail writes label names with spaces and slashes as dashes
+pub fn label_term(name: &str) -> String {
+    name.trim()
+        .split(|c: char| c.is_whitespace() || c == '/')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase()
+}
+
+/// Byte count in the shortest form Gmail accepts for larger:/smaller:
+fn size_term(bytes: u64) -> String {
+    const MB: u64 = 1024 * 1024;
+    match bytes {
+        b if b >= MB && b % MB == 0 => format!("{}M", b / MB),
+        b if b >= 1024 && b % 1024 == 0 => format!("{}K", b / 1024),
+        b => b.to_string(),
+    }
+}
+
+/// Build a Gmail query from structured filters
+pub fn build_query(filters: &SearchFilters) -> Result<BuiltSearchQuery> {
+    if let (Some(after), Some(before)) = (filters.after, filters.before) {
+        if after >= before {
+            return Err(invalid("before", "The end date must be after the start date"));
+        }
+    }
+    if let (Some(larger), Some(smaller)) = (filters.larger_than, filters.smaller_than) {
+        if larger >= smaller {
+            return Err(invalid("smaller_than", "The maximum size must be above the minimum size"));
+        }
+    }
+    let non_empty = |value: &Option<String>| value.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string);
+    if let Some(folder) = non_empty(&filters.in_folder) {
+        if !IN_VALUES.contains(&folder.to_lowercase().as_str()) {
+            return Err(invalid("in_folder", format!("Gmail has no \"{}\" folder", folder)));
+        }
+    }
+    if let Some(label) = filters.labels.iter().find(|label| filters.exclude_labels.contains(label)) {
+        return Err(invalid("exclude_labels", format!("\"{}\" is both required and excluded", label)));
+    }
+
+    let mut terms: Vec<String> = Vec::new();
+    let mut issues = Vec::new();
+
+    if let Some(text) = non_empty(&filters.text) {
+        issues = validate_query(&text);
+        if let Some(error) = issues.iter().find(|issue| issue.severity == IssueSeverity::Error) {
+            return Err(invalid("text", error.message.clone()));
+        }
+        terms.push(text);
+    }
+    if let Some(from) = non_empty(&filters.from) {
+        terms.push(format!("from:{}", quote_value(&from)));
+    }
+    if let Some(to) = non_empty(&filters.to) {
+        terms.push(format!("to:{}", quote_value(&to)));
+    }
+    if let Some(subject) = non_empty(&filters.subject) {
+        terms.push(format!("subject:{}", quote_value(&subject)));
+    }
+    match filters.has_attachment {
+        Some(true) => terms.push("has:attachment".to_string()),
+        Some(false) => terms.push("-has:attachment".to_string()),
+        None => {}
+    }
+    if let Some(filename) = non_empty(&filters.filename) {
+        terms.push(format!("filename:{}", quote_value(&filename)));
+    }
+    if let Some(after) = filters.after {
+        terms.push(format!("after:{}", after.format("%Y/%m/%d")));
+    }
+    if let Some(before) = filters.before {
+        terms.push(format!("before:{}", before.format("%Y/%m/%d")));
+    }
+    if let Some(days) = filters.newer_than_days.filter(|days| *days > 0) {
+        terms.push(format!("newer_than:{}d", days));
+    }
+    for label in filters.labels.iter().map(|l| label_term(l)).filter(|l| !l.is_empty()) {
+        terms.push(format!("label:{}", label));
+    }
+    for label in filters.exclude_labels.iter().map(|l| label_term(l)).filter(|l| !l.is_empty()) {
+        terms.push(format!("-label:{}", label));
+    }
+    if let Some(larger) = filters.larger_than {
+        terms.push(format!("larger:{}", size_term(larger)));
+    }
+    if let Some(smaller) = filters.smaller_than {
+        terms.push(format!("smaller:{}", size_term(smaller)));
+    }
+    match filters.is_unread {
+        Some(true) => terms.push("is:unread".to_string()),
+        Some(false) => terms.push("is:read".to_string()),
+        None => {}
+    }
+    match filters.is_starred {
+        Some(true) => terms.push("is:starred".to_string()),
+        Some(false) => terms.push("-is:starred".to_string()),
+        None => {}
+    }
+    if let Some(folder) = non_empty(&filters.in_folder) {
+        terms.push(format!("in:{}", folder.to_lowercase()));
+    }
+
+    Ok(BuiltSearchQuery { query: terms.join(" "), issues })
+}
+
+/// Split a query on whitespace outside quotes, checking quotes and
+/// parentheses balance along the way
+fn tokenize(query: &str) -> (Vec<String>, Vec<QueryIssue>) {
+    let mut tokens = Vec::new();
+    let mut issues = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut depth: i32 = 0;
+
+    for c in query.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '(' | '{' if !in_quotes => depth += 1,
+            ')' | '}' if !in_quotes => depth -= 1,
+            _ => {}
+        }
+        if depth < 0 {
+            issues.push(syntax_error("A closing parenthesis has no opening one"));
+            depth = 0;
+        }
+        if c.is_whitespace() && !in_quotes {
+            if !current.is_empty() {
+                tokens.push(std::mem::take(&mut current));
+            }
+        } else {
+            current.push(c);
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    if in_quotes {
+        issues.push(syntax_error("A quote is not closed"));
+    }
+    if depth > 0 {
+        issues.push(syntax_error("A parenthesis is not closed"));
+    }
+    (tokens, issues)
+}
+
+fn syntax_error(message: &str) -> QueryIssue {
+    QueryIssue { severity: IssueSeverity::Error, operator: None, message: message.to_string() }
+}
+
+fn is_date(value: &str) -> bool {
+    let value = value.replace('-', "/");
+    NaiveDate::parse_from_str(&value, "%Y/%m/%d").is_ok()
+        || NaiveDate::parse_from_str(&value, "%m/%d/%Y").is_ok()
+        || (value.len() >= 9 && value.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn is_relative_age(value: &str) -> bool {
+    let value = value.to_lowercase();
+    value.len() > 1
+        && value.ends_with(['d', 'm', 'y'])
+        && value[..value.len() - 1].chars().all(|c| c.is_ascii_digit())
+}
+
+fn is_size(value: &str) -> bool {
+    let digits = value.trim_end_matches(['k', 'K', 'm', 'M']);
+    !digits.is_empty() && digits.len() + 1 >= value.len() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Check the operators in a query.
+///
+/// Operators are `name:value` terms, optionally negated with `-`. URLs and
+/// times ("10:30") are not operators and are left alone.
+pub fn validate_query(query: &str) -> Vec<QueryIssue> {
+    let (tokens, mut issues) = tokenize(query);
+
+    for token in tokens {
+        let term = token.trim_start_matches(['-', '+', '(', '{']);
+        let Some((operator, value)) = term.split_once(':') else { continue };
+        if operator.is_empty() || !operator.chars().all(|c| c.is_ascii_alphabetic() || c == '_') || value.starts_with("//") {
+            continue;
+        }
+
+        let operator = operator.to_lowercase();
+        let issue = |severity: IssueSeverity, message: String| QueryIssue {
+            severity,
+            operator: Some(operator.clone()),
+            message,
+        };
+        if !OPERATORS.contains(&operator.as_str()) {
+            issues.push(issue(IssueSeverity::Warning, format!("Gmail has no \"{}:\" operator; it will be searched as text", operator)));
+            continue;
+        }
+
+        let value = value.trim_end_matches([')', '}']).trim_matches('"');
+        if value.is_empty() {
+            issues.push(issue(IssueSeverity::Error, format!("\"{}:\" needs a value", operator)));
+            continue;
+        }
+        // Grouped values such as from:(a OR b) are not checked further
+        if value.starts_with(['(', '{']) {
+            continue;
+        }
+
+        let lower = value.to_lowercase();
+        let problem = match operator.as_str() {
+            "has" if !HAS_VALUES.contains(&lower.as_str()) => Some(format!("\"has:{}\" is not a Gmail search", value)),
+            "is" if !IS_VALUES.contains(&lower.as_str()) => Some(format!("\"is:{}\" is not a Gmail search", value)),
+            "in" if !IN_VALUES.contains(&lower.as_str()) => Some(format!("Gmail has no \"{}\" folder", value)),
+            "category" if !CATEGORY_VALUES.contains(&lower.as_str()) => Some(format!("Gmail has no \"{}\" category", value)),
+            "after" | "before" | "older" | "newer" if !is_date(value) => {
+                Some(format!("\"{}\" is not a date; use YYYY/MM/DD", value))
+            }
+            "older_than" | "newer_than" if !is_relative_age(value) => {
+                Some(format!("\"{}\" is not an age; use a number with d, m or y, e.g. 7d", value))
+            }
+            "larger" | "smaller" | "size" if !is_size(value) => {
+                Some(format!("\"{}\" is not a size; use bytes or a number with K or M, e.g. 5M", value))
+            }
+            _ => None,
+        };
+        if let Some(message) = problem {
+            issues.push(issue(IssueSeverity::Error, message));
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_queries_from_filters() {
+        let filters = SearchFilters {
+            from: Some("Jane Doe".to_string()),
+            subject: Some("invoice".to_string()),
+            has_attachment: Some(true),
+            after: NaiveDate::from_ymd_opt(2026, 1, 1),
+            before: NaiveDate::from_ymd_opt(2026, 2, 1),
+            labels: vec!["Clients/Acme Corp".to_string()],
+            exclude_labels: vec!["Archive".to_string()],
+            larger_than: Some(5 * 1024 * 1024),
+            is_unread: Some(true),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            build_query(&filters).unwrap().query,
+            "from:\"Jane Doe\" subject:invoice has:attachment after:2026/01/01 before:2026/02/01 \
+             label:clients-acme-corp -label:archive larger:5M is:unread"
+        );
+    }
+
+    #[test]
+    fn rejects_contradictory_filters() {
+        let dates = SearchFilters {
+            after: NaiveDate::from_ymd_opt(2026, 3, 1),
+            before: NaiveDate::from_ymd_opt(2026, 2, 1),
+            ..Default::default()
+        };
+        assert!(build_query(&dates).is_err());
+
+        let folder = SearchFilters { in_folder: Some("Archive".to_string()), ..Default::default() };
+        assert!(build_query(&folder).is_err());
+
+        let text = SearchFilters { text: Some("after:yesterday".to_string()), ..Default::default() };
+        assert!(build_query(&text).is_err());
+    }
+
+    #[test]
+    fn validates_operators_in_free_text() {
+        assert!(validate_query("from:(alice OR bob) has:attachment older_than:2y larger:10M see https://x.io at 10:30").is_empty());
+
+        let issues = validate_query("form:alice is:shiny subject:");
+        assert_eq!(
+            issues.iter().map(|i| (i.severity, i.operator.as_deref())).collect::<Vec<_>>(),
+            vec![
+                (IssueSeverity::Warning, Some("form")),
+                (IssueSeverity::Error, Some("is")),
+                (IssueSeverity::Error, Some("subject")),
+            ]
+        );
+
+        assert_eq!(validate_query("subject:\"open quote").len(), 1);
+        assert_eq!(validate_query("(a OR b").len(), 1);
+    }
+}
@@ -0,0 +1,325 @@
+//! Package tracking from shipping emails
+//!
+//! Tracking numbers found by entity extraction are stored as shipments. The
+//! status is taken from later shipping emails where possible and otherwise
+//! from the carrier's public tracking page, polled sparingly: a handful of
+//! shipments per tick, a pause between requests, hours between checks of
+//! the same shipment, and exponential backoff on failures. Status changes
+//! are pushed to the frontend as `backend://shipment-updated` events.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::database::operations::shipment_operations::{self, NewShipment, Shipment};
+use crate::database::DatabaseManager;
+use crate::services::events::{BackendEvent, EventBus, ShipmentUpdatedEvent};
+use crate::services::gmail::ProcessedGmailMessage;
+use crate::services::settings;
+use crate::services::task_supervisor::TaskSpec;
+use crate::services::text_processing::{self, EntityKind};
+use crate::utils::http_client::{http_client, TraceRequest};
+
+/// How often the poller looks for due shipments
+const POLL_TICK: Duration = Duration::from_secs(10 * 60);
+/// Most tracking pages fetched per tick
+const CHECKS_PER_TICK: usize = 5;
+/// Pause between two tracking page requests
+const REQUEST_SPACING: Duration = Duration::from_secs(15);
+const CHECK_INTERVAL_SECS: i64 = 4 * 60 * 60;
+const OUT_FOR_DELIVERY_INTERVAL_SECS: i64 = 60 * 60;
+const MAX_BACKOFF_SECS: i64 = 24 * 60 * 60;
+const MAX_CHECK_FAILURES: i64 = 6;
+/// Shipments stop being polled this long after they were detected
+const MAX_TRACKING_AGE_SECS: i64 = 30 * 24 * 60 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShipmentStatus {
+    Unknown,
+    LabelCreated,
+    InTransit,
+    OutForDelivery,
+    Delivered,
+    Exception,
+}
+
+impl ShipmentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ShipmentStatus::Unknown => "unknown",
+            ShipmentStatus::LabelCreated => "label_created",
+            ShipmentStatus::InTransit => "in_transit",
+            ShipmentStatus::OutForDelivery => "out_for_delivery",
+            ShipmentStatus::Delivered => "delivered",
+            ShipmentStatus::Exception => "exception",
+        }
+    }
+
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "label_created" => ShipmentStatus::LabelCreated,
+            "in_transit" => ShipmentStatus::InTransit,
+            "out_for_delivery" => ShipmentStatus::OutForDelivery,
+            "delivered" => ShipmentStatus::Delivered,
+            "exception" => ShipmentStatus::Exception,
+            _ => ShipmentStatus::Unknown,
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            ShipmentStatus::Unknown => 0,
+            ShipmentStatus::LabelCreated => 1,
+            ShipmentStatus::InTransit | ShipmentStatus::Exception => 2,
+            ShipmentStatus::OutForDelivery => 3,
+            ShipmentStatus::Delivered => 4,
+        }
+    }
+
+    /// Whether moving from `self` to `next` is news. Statuses only move
+    /// forward so an old email cannot undo a newer page check; exceptions
+    /// are always reported.
+    pub fn should_advance_to(&self, next: ShipmentStatus) -> bool {
+        next != *self
+            && *self != ShipmentStatus::Delivered
+            && (next == ShipmentStatus::Exception || next.rank() > self.rank())
+    }
+}
+
+/// Guess a shipment status from email or tracking page text
+pub fn classify_status(text: &str) -> Option<ShipmentStatus> {
+    let text = text.to_lowercase();
+    let has = |phrases: &[&str]| phrases.iter().any(|p| text.contains(p));
+
+    if has(&["delivery exception", "delivery attempted", "unable to deliver", "undeliverable", "returned to sender"]) {
+        Some(ShipmentStatus::Exception)
+    } else if has(&["has been delivered", "was delivered", "delivered on", "status: delivered", "your package was left"]) {
+        Some(ShipmentStatus::Delivered)
+    } else if has(&["out for delivery"]) {
+        Some(ShipmentStatus::OutForDelivery)
+    } else if has(&["in transit", "on its way", "has shipped", "arrived at", "departed"]) {
+        Some(ShipmentStatus::InTransit)
+    } else if has(&["label created", "shipping label", "pre-shipment", "awaiting item"]) {
+        Some(ShipmentStatus::LabelCreated)
+    } else {
+        None
+    }
+}
+
+#[derive(Clone)]
+pub struct ShipmentTracker {
+    db_manager: Arc<DatabaseManager>,
+    app: AppHandle,
+}
+
+impl ShipmentTracker {
+    pub fn new(db_manager: Arc<DatabaseManager>, app: AppHandle) -> Self {
+        Self { db_manager, app }
+    }
+
+    /// Record tracking numbers found in messages, and update known shipments
+    /// from what the message says about them. Returns the number of new
+    /// shipments.
+    pub fn detect_in_messages(&self, account_id: &str, messages: &[ProcessedGmailMessage]) -> anyhow::Result<usize> {
+        let conn = self.db_manager.get_connection()?;
+        let now = chrono::Utc::now().timestamp();
+        let today = chrono::Local::now().date_naive();
+        let mut detected = 0;
+
+        for message in messages {
+            let text = message_text(message);
+            let email_status = classify_status(&text);
+
+            for entity in text_processing::extract_entities(&text, today) {
+                let EntityKind::TrackingNumber { carrier, tracking_url } = entity.kind else {
+                    continue;
+                };
+                let new_shipment = NewShipment {
+                    account_id,
+                    carrier: &carrier,
+                    tracking_number: &entity.text,
+                    tracking_url: &tracking_url,
+                    message_id: Some(&message.id),
+                    subject: message.parsed_content.subject.as_deref(),
+                };
+                if shipment_operations::insert_detected(&conn, &new_shipment, now)? {
+                    println!("📦 [SHIPMENTS] Detected {} shipment {}", carrier, entity.text);
+                    detected += 1;
+                }
+
+                if let Some(status) = email_status {
+                    if let Some(shipment) = shipment_operations::find_shipment(&conn, account_id, &carrier, &entity.text)? {
+                        self.apply_status(&conn, &shipment, status, None, now)?;
+                    }
+                }
+            }
+        }
+
+        Ok(detected)
+    }
+
+    /// Poll due shipments in the background for as long as the app runs
+    pub fn task(&self) -> TaskSpec {
+        let tracker = self.clone();
+        TaskSpec::new("shipments", move || {
+            let tracker = tracker.clone();
+            async move {
+                if !tracker.polling_enabled() {
+                    return Ok(());
+                }
+                tracker.poll_due().await
+            }
+        })
+        .every(|| POLL_TICK)
+        .first_run_after(Duration::ZERO)
+        .jitter(0.1)
+    }
+
+    /// Checked every tick, so turning tracking off applies from the next one
+    fn polling_enabled(&self) -> bool {
+        self.db_manager
+            .get_connection()
+            .and_then(|conn| settings::get_bool(&conn, settings::SHIPMENT_TRACKING_SETTING))
+            .unwrap_or(true)
+    }
+
+    async fn poll_due(&self) -> anyhow::Result<()> {
+        let due = {
+            let conn = self.db_manager.get_connection()?;
+            shipment_operations::due_for_check(&conn, chrono::Utc::now().timestamp(), CHECKS_PER_TICK)?
+        };
+
+        for (index, shipment) in due.iter().enumerate() {
+            if index > 0 {
+                tokio::time::sleep(REQUEST_SPACING).await;
+            }
+            let result = fetch_tracking_page(&shipment.tracking_url).await;
+
+            let conn = self.db_manager.get_connection()?;
+            let now = chrono::Utc::now().timestamp();
+            match result {
+                Ok(page) => {
+                    let status = classify_status(&page).unwrap_or(ShipmentStatus::parse(&shipment.status));
+                    self.apply_status(&conn, shipment, status, Some(now), now)?;
+                }
+                Err(e) => {
+                    eprintln!("⚠️ [SHIPMENTS] Failed to check {} {}: {}", shipment.carrier, shipment.tracking_number, e);
+                    let failures = shipment.check_failures + 1;
+                    let next = (failures < MAX_CHECK_FAILURES && !is_stale(shipment, now))
+                        .then(|| now + (CHECK_INTERVAL_SECS << failures.min(8)).min(MAX_BACKOFF_SECS));
+                    shipment_operations::record_check_failure(&conn, shipment.id, now, next)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Store a status and schedule the next check, emitting an event if the
+    /// status moved. `checked_at` is set when the status came from a page check.
+    fn apply_status(
+        &self,
+        conn: &rusqlite::Connection,
+        shipment: &Shipment,
+        status: ShipmentStatus,
+        checked_at: Option<i64>,
+        now: i64,
+    ) -> anyhow::Result<()> {
+        let previous = ShipmentStatus::parse(&shipment.status);
+        let advanced = previous.should_advance_to(status);
+        let current = if advanced { status } else { previous };
+
+        let next_check = match current {
+            ShipmentStatus::Delivered => None,
+            _ if is_stale(shipment, now) => None,
+            ShipmentStatus::OutForDelivery => Some(now + OUT_FOR_DELIVERY_INTERVAL_SECS),
+            _ => Some(now + CHECK_INTERVAL_SECS),
+        };
+
+        if checked_at.is_none() && !advanced {
+            return Ok(());
+        }
+        shipment_operations::update_status(
+            conn,
+            shipment.id,
+            current.as_str(),
+            None,
+            checked_at.or(shipment.last_checked_at).unwrap_or(now),
+            next_check,
+        )?;
+
+        if advanced {
+            println!(
+                "📦 [SHIPMENTS] {} {}: {} -> {}",
+                shipment.carrier, shipment.tracking_number, previous.as_str(), current.as_str()
+            );
+            if let Some(event_bus) = self.app.try_state::<EventBus>() {
+                event_bus.emit(BackendEvent::ShipmentUpdated(ShipmentUpdatedEvent {
+                    account_id: shipment.account_id.clone(),
+                    shipment_id: shipment.id,
+                    carrier: shipment.carrier.clone(),
+                    tracking_number: shipment.tracking_number.clone(),
+                    previous_status: previous.as_str().to_string(),
+                    status: current.as_str().to_string(),
+                }));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn message_text(message: &ProcessedGmailMessage) -> String {
+    let content = &message.parsed_content;
+    let body = content
+        .body_text
+        .clone()
+        .or_else(|| content.body_html.as_deref().map(text_processing::to_plain_text))
+        .or_else(|| message.snippet.clone())
+        .unwrap_or_default();
+    format!("{}\n{}", content.subject.as_deref().unwrap_or_default(), body)
+}
+
+fn is_stale(shipment: &Shipment, now: i64) -> bool {
+    chrono::NaiveDateTime::parse_from_str(&shipment.created_at, "%Y-%m-%d %H:%M:%S")
+        .map(|created| now - created.and_utc().timestamp() > MAX_TRACKING_AGE_SECS)
+        .unwrap_or(false)
+}
+
+async fn fetch_tracking_page(url: &str) -> Result<String, String> {
+    let response = http_client()
+        .get(url)
+        .traced()
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("tracking page returned {}", response.status()));
+    }
+    let body = response.text().await.map_err(|e| e.to_string())?;
+    Ok(text_processing::to_plain_text(&body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_shipping_email_text() {
+        assert_eq!(classify_status("Good news! Your package is out for delivery"), Some(ShipmentStatus::OutForDelivery));
+        assert_eq!(classify_status("Your order has shipped and is on its way"), Some(ShipmentStatus::InTransit));
+        assert_eq!(classify_status("Your package was delivered on Monday"), Some(ShipmentStatus::Delivered));
+        assert_eq!(classify_status("Thanks for your order"), None);
+    }
+
+    #[test]
+    fn status_only_moves_forward() {
+        assert!(ShipmentStatus::InTransit.should_advance_to(ShipmentStatus::Delivered));
+        assert!(!ShipmentStatus::OutForDelivery.should_advance_to(ShipmentStatus::InTransit));
+        assert!(ShipmentStatus::InTransit.should_advance_to(ShipmentStatus::Exception));
+        assert!(!ShipmentStatus::Delivered.should_advance_to(ShipmentStatus::Exception));
+    }
+}
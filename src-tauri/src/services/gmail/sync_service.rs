@@ -1,14 +1,29 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use anyhow::{Result, Context};
 use reqwest::Client;
 use chrono::{DateTime, Utc, Duration};
 use tokio::sync::RwLock;
-use rusqlite::OptionalExtension;
+use rusqlite::{Connection, OptionalExtension};
+use tauri::{AppHandle, Manager};
 
 use crate::database::DatabaseManager;
-use crate::services::gmail::GmailTokens;
+use crate::database::operations::backfill_operations::{self, StoredMessage};
+use crate::database::operations::cache_operations;
+use crate::database::operations::cache_delta_operations::{NewCacheDelta, CHANGE_ADDED, CHANGE_REMOVED, CHANGE_UPDATED};
+use crate::database::operations::sync_policy_operations::{self, LabelSyncPolicy, MessageSyncDecision, SyncContent};
+use crate::config::get_config_manager;
+use crate::errors::LibreOllamaError;
+use crate::services::events::EventBus;
+use crate::services::gmail::api_service::{GmailApiService, MessageSearchQuery};
+use crate::services::gmail::compose_service::GmailComposeService;
+use crate::services::gmail::muted_threads::archive_muted_messages;
+use crate::services::gmail::receipt_service;
+use crate::services::notification::MailNotice;
+use crate::services::task_supervisor::TaskSpec;
+use crate::services::gmail::{GmailTokens, ProcessedGmailMessage};
+use crate::utils::http_client::http_client;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncState {
@@ -28,6 +43,8 @@ pub enum SyncStatus {
     Completed,
     Failed,
     Paused,
+    /// Cut short by shutdown; resumed at the next start
+    Interrupted,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -60,6 +77,17 @@ pub enum SyncType {
     Manual,
 }
 
+impl SyncType {
+    /// The name the sync history records runs under
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SyncType::Full => "full",
+            SyncType::Incremental => "incremental",
+            SyncType::Manual => "manual",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GmailSyncConfig {
     pub client_id: String,
@@ -107,43 +135,36 @@ pub struct AccountSyncState {
 }
 
 
-#[derive(Debug, Deserialize)]
-#[allow(dead_code)]
-struct GmailHistoryResponse {
-    history: Option<Vec<GmailHistoryRecord>>,
-    #[serde(rename = "nextPageToken")]
-    next_page_token: Option<String>,
-    #[serde(rename = "historyId")]
-    history_id: Option<String>,
-}
-
-#[derive(Debug, Deserialize)]
-#[allow(dead_code)]
-struct GmailHistoryRecord {
-    id: String,
-    messages: Option<Vec<GmailHistoryMessage>>,
-    #[serde(rename = "messagesAdded")]
-    messages_added: Option<Vec<GmailHistoryMessage>>,
-    #[serde(rename = "messagesDeleted")]
-    messages_deleted: Option<Vec<GmailHistoryMessage>>,
-    #[serde(rename = "labelsAdded")]
-    labels_added: Option<Vec<GmailLabelChange>>,
-    #[serde(rename = "labelsRemoved")]
-    labels_removed: Option<Vec<GmailLabelChange>>,
-}
-
-#[derive(Debug, Deserialize)]
-struct GmailHistoryMessage {
-    id: String,
-    #[serde(rename = "threadId")]
-    thread_id: String,
+/// One label's share of a full sync, derived from its sync policy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabelSyncPlan {
+    pub label_id: String,
+    /// Gmail search query limiting the age of fetched mail
+    pub query: Option<String>,
+    /// Gmail message format: "full" or "metadata"
+    pub format: String,
 }
 
-#[derive(Debug, Deserialize)]
-struct GmailLabelChange {
-    message: GmailHistoryMessage,
-    #[serde(rename = "labelIds")]
-    label_ids: Vec<String>,
+/// Background incremental sync of every account, run by the task
+/// supervisor at the configured interval
+pub fn background_sync_task(app: AppHandle) -> TaskSpec {
+    TaskSpec::new("gmail_sync", move || {
+        let app = app.clone();
+        async move {
+            match app.try_state::<GmailSyncService>() {
+                Some(service) => service.sync_active_accounts().await,
+                None => Ok(()),
+            }
+        }
+    })
+    .every(|| {
+        let minutes = get_config_manager()
+            .map(|config| config.sync().incremental_sync_interval_minutes)
+            .unwrap_or(15);
+        std::time::Duration::from_secs(minutes.max(1) * 60)
+    })
+    .jitter(0.1)
+    .finish_before_exit()
 }
 
 // Account sync state management
@@ -152,20 +173,34 @@ pub type AccountSyncStates = RwLock<HashMap<String, AccountSyncState>>;
 #[allow(dead_code)]
 pub struct GmailSyncService {
     db_manager: Arc<DatabaseManager>,
+    api_service: Arc<GmailApiService>,
     client: Client,
     sync_states: Arc<AccountSyncStates>,
+    /// None outside the desktop app, e.g. in the CLI: nothing is announced
+    app: Option<AppHandle>,
 }
 
 #[allow(dead_code)]
 impl GmailSyncService {
-    pub fn new(db_manager: Arc<DatabaseManager>) -> Self {
+    pub fn new(db_manager: Arc<DatabaseManager>, api_service: Arc<GmailApiService>, app: AppHandle) -> Self {
+        Self { app: Some(app), ..Self::headless(db_manager, api_service) }
+    }
+
+    /// A sync service with no app to send events to, for the CLI
+    pub fn headless(db_manager: Arc<DatabaseManager>, api_service: Arc<GmailApiService>) -> Self {
         Self {
             db_manager,
-            client: Client::new(),
+            api_service,
+            client: http_client(),
             sync_states: Arc::new(RwLock::new(HashMap::new())),
+            app: None,
         }
     }
 
+    fn app_state<T: Send + Sync + 'static>(&self) -> Option<tauri::State<'_, T>> {
+        self.app.as_ref()?.try_state::<T>()
+    }
+
     /// Initialize sync state for a Gmail account
     pub async fn initialize_sync_state(&self, account_id: &str, config: &SyncConfig) -> Result<SyncState> {
         let conn = self.db_manager.get_connection()
@@ -202,6 +237,40 @@ impl GmailSyncService {
 
     /// Perform full sync of Gmail messages for an account
     pub async fn perform_full_sync(&self, account_id: &str, max_messages: Option<u32>) -> Result<SyncResult> {
+        let result = self.full_sync(account_id, max_messages).await;
+        self.record_run(account_id, SyncType::Full, &result);
+        result
+    }
+
+    /// Perform incremental sync using history API
+    pub async fn perform_incremental_sync(&self, account_id: &str) -> Result<SyncResult> {
+        let result = self.incremental_sync(account_id).await;
+        self.record_run(account_id, SyncType::Incremental, &result);
+        result
+    }
+
+    /// Record a sync in the sync history, under the kind that actually ran
+    fn record_run(&self, account_id: &str, requested: SyncType, result: &Result<SyncResult>) {
+        let Some(event_bus) = self.app_state::<EventBus>() else {
+            return;
+        };
+        match result {
+            Ok(sync) => {
+                let error = match sync.status {
+                    SyncStatus::Failed if sync.errors.is_empty() => {
+                        Some(anyhow::anyhow!("{} messages failed to sync", sync.messages_failed))
+                    }
+                    SyncStatus::Failed => Some(anyhow::anyhow!(sync.errors.join("; "))),
+                    _ => None,
+                };
+                let changed = sync.messages_processed.min(u32::MAX as u64) as u32;
+                event_bus.sync_finished(account_id, "gmail", sync.sync_type.as_str(), changed, error.as_ref());
+            }
+            Err(e) => event_bus.sync_finished(account_id, "gmail", requested.as_str(), 0, Some(e)),
+        }
+    }
+
+    async fn full_sync(&self, account_id: &str, max_messages: Option<u32>) -> Result<SyncResult> {
         let start_time = std::time::Instant::now();
         let mut sync_result = SyncResult {
             account_id: account_id.to_string(),
@@ -214,55 +283,54 @@ impl GmailSyncService {
             status: SyncStatus::InProgress,
         };
 
-        // Update sync state to in-progress
+        self.ensure_sync_state(account_id)?;
         self.update_sync_status(account_id, SyncStatus::InProgress).await?;
 
-        // Get tokens for the account (this would be implemented to get from auth service)
-        // For now, we'll use a placeholder
-        let tokens = GmailTokens {
-            access_token: "placeholder".to_string(),
-            refresh_token: Some("placeholder".to_string()),
-            expires_at: None,
-            token_type: "Bearer".to_string(),
-        };
+        // Taken before listing, so changes made while the sync runs are
+        // picked up by the next incremental sync
+        match self.api_service.get_history_id(account_id).await {
+            Ok(history_id) => sync_result.new_history_id = Some(history_id),
+            Err(e) => {
+                self.update_sync_status(account_id, SyncStatus::Failed).await?;
+                return Err(e.into());
+            }
+        }
+        let policies = self.get_label_policies(account_id).await?;
 
-        let mut page_token: Option<String> = None;
         let mut total_processed = 0u64;
         let mut total_failed = 0u64;
         let batch_size = max_messages.unwrap_or(100).min(500); // Gmail API limit
 
-        loop {
-            match self.sync_message_batch(
-                &tokens.access_token,
-                account_id,
-                batch_size,
-                page_token.clone(),
-            ).await {
-                Ok((processed, failed, next_token, history_id)) => {
-                    total_processed += processed;
-                    total_failed += failed;
-                    
-                    if let Some(hist_id) = history_id {
-                        sync_result.new_history_id = Some(hist_id);
-                    }
-
-                    // Check if we have more pages and haven't hit the limit
-                    if let Some(token) = next_token {
-                        if max_messages.is_none_or(|max| total_processed < max as u64) {
-                            page_token = Some(token);
-                            continue;
+        // Each label is fetched separately so its age limit and body policy apply
+        for plan in self.plan_label_sync(account_id).await? {
+            let mut page_token: Option<String> = None;
+            loop {
+                match self.sync_message_batch(account_id, &policies, &plan, batch_size, page_token.clone()).await {
+                    Ok((processed, failed, next_token)) => {
+                        total_processed += processed;
+                        total_failed += failed;
+
+                        // Check if we have more pages and haven't hit the limit
+                        if let Some(token) = next_token {
+                            if max_messages.is_none_or(|max| total_processed < max as u64) {
+                                page_token = Some(token);
+                                continue;
+                            }
                         }
+                        break;
+                    }
+                    Err(e) => {
+                        sync_result.errors.push(format!("Batch sync failed for {}: {}", plan.label_id, e));
+                        total_failed += batch_size as u64;
+                        break;
                     }
-                    break;
-                }
-                Err(e) => {
-                    sync_result.errors.push(format!("Batch sync failed: {}", e));
-                    total_failed += batch_size as u64;
-                    break;
                 }
             }
         }
 
+        if total_processed > 0 {
+            self.refresh_label_counts(account_id).await;
+        }
         sync_result.messages_processed = total_processed;
         sync_result.messages_failed = total_failed;
         sync_result.duration_ms = start_time.elapsed().as_millis() as u64;
@@ -274,8 +342,7 @@ impl GmailSyncService {
         Ok(sync_result)
     }
 
-    /// Perform incremental sync using history API
-    pub async fn perform_incremental_sync(&self, account_id: &str) -> Result<SyncResult> {
+    async fn incremental_sync(&self, account_id: &str) -> Result<SyncResult> {
         let start_time = std::time::Instant::now();
         let mut sync_result = SyncResult {
             account_id: account_id.to_string(),
@@ -288,31 +355,30 @@ impl GmailSyncService {
             status: SyncStatus::InProgress,
         };
 
-        // Get current sync state
-        let sync_state = self.get_sync_state(account_id).await?
-            .ok_or_else(|| anyhow::anyhow!("No sync state found for account {}", account_id))?;
-
-        let start_history_id = sync_state.history_id
-            .ok_or_else(|| anyhow::anyhow!("No history ID found for incremental sync"))?;
-
-        // Get tokens for the account
-        let tokens = GmailTokens {
-            access_token: "placeholder".to_string(),
-            refresh_token: Some("placeholder".to_string()),
-            expires_at: None,
-            token_type: "Bearer".to_string(),
+        // Without a history ID to start from, only a full sync can catch up
+        let Some(start_history_id) = self.get_sync_state(account_id).await?.and_then(|state| state.history_id) else {
+            return self.full_sync(account_id, None).await;
         };
 
         // Process history changes
-        match self.process_history_changes(&tokens.access_token, account_id, &start_history_id).await {
+        match self.process_history_changes(account_id, &start_history_id).await {
             Ok((processed, failed, new_history_id)) => {
+                if processed > 0 {
+                    self.refresh_label_counts(account_id).await;
+                }
                 sync_result.messages_processed = processed;
                 sync_result.messages_failed = failed;
-                sync_result.new_history_id = new_history_id;
+                sync_result.new_history_id = new_history_id.or(Some(start_history_id));
                 sync_result.status = if failed == 0 { SyncStatus::Completed } else { SyncStatus::Failed };
             }
+            // Gmail keeps about a week of history
+            Err(e) if matches!(e.downcast_ref(), Some(LibreOllamaError::GmailApi { status_code: Some(404), .. })) => {
+                println!("🔄 [GMAIL-SYNC] History for {} expired, running a full sync", account_id);
+                return self.full_sync(account_id, None).await;
+            }
             Err(e) => {
                 sync_result.errors.push(format!("History processing failed: {}", e));
+                sync_result.new_history_id = Some(start_history_id);
                 sync_result.status = SyncStatus::Failed;
             }
         }
@@ -325,6 +391,88 @@ impl GmailSyncService {
         Ok(sync_result)
     }
 
+    /// Label sync policies for an account (defaults if none are stored)
+    pub async fn get_label_policies(&self, account_id: &str) -> Result<Vec<LabelSyncPolicy>> {
+        let conn = self.db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        sync_policy_operations::get_policies(&conn, account_id)
+    }
+
+    /// Replace the label sync policies for an account
+    pub async fn set_label_policies(&self, account_id: &str, policies: &[LabelSyncPolicy]) -> Result<Vec<LabelSyncPolicy>> {
+        let mut conn = self.db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        sync_policy_operations::set_policies(&mut conn, account_id, policies)?;
+        sync_policy_operations::get_policies(&conn, account_id)
+    }
+
+    /// Revert an account to the default label sync policies
+    pub async fn reset_label_policies(&self, account_id: &str) -> Result<Vec<LabelSyncPolicy>> {
+        let conn = self.db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        sync_policy_operations::reset_policies(&conn, account_id)?;
+        Ok(sync_policy_operations::default_policies())
+    }
+
+    /// Turn an account's enabled label policies into per-label fetch plans.
+    ///
+    /// The catch-all `*` policy has no label to list by and only applies to
+    /// messages that arrive through history, so it gets no plan of its own.
+    pub async fn plan_label_sync(&self, account_id: &str) -> Result<Vec<LabelSyncPlan>> {
+        let policies = self.get_label_policies(account_id).await?;
+        let config = self.get_sync_config(account_id)?;
+
+        Ok(policies
+            .into_iter()
+            .filter(|p| p.enabled && p.label_id != sync_policy_operations::DEFAULT_LABEL)
+            .filter(|p| {
+                config.as_ref().is_none_or(|c| {
+                    c.labels_to_sync.as_ref().is_none_or(|labels| labels.contains(&p.label_id))
+                        && !c.exclude_labels.as_ref().is_some_and(|labels| labels.contains(&p.label_id))
+                })
+            })
+            .map(|p| LabelSyncPlan {
+                query: p.max_age_days.map(|days| format!("newer_than:{}d", days)),
+                format: match p.content {
+                    SyncContent::Full => "full".to_string(),
+                    SyncContent::Headers => "metadata".to_string(),
+                },
+                label_id: p.label_id,
+            })
+            .collect())
+    }
+
+    /// Apply label policies to a fetched message.
+    ///
+    /// Returns `None` if the message should not be stored; headers-only
+    /// messages have their bodies and attachment data dropped.
+    pub fn apply_label_policies(
+        policies: &[LabelSyncPolicy],
+        mut message: ProcessedGmailMessage,
+    ) -> Option<ProcessedGmailMessage> {
+        // internalDate is epoch milliseconds
+        let age_days = message
+            .internal_date
+            .as_deref()
+            .and_then(|ms| ms.parse::<i64>().ok())
+            .and_then(DateTime::<Utc>::from_timestamp_millis)
+            .map(|date| (Utc::now() - date).num_days())
+            .unwrap_or(0);
+
+        match sync_policy_operations::decide(policies, &message.labels, age_days) {
+            MessageSyncDecision::Skip => None,
+            MessageSyncDecision::HeadersOnly => {
+                message.parsed_content.body_text = None;
+                message.parsed_content.body_html = None;
+                for attachment in &mut message.parsed_content.attachments {
+                    attachment.data = None;
+                }
+                Some(message)
+            }
+            MessageSyncDecision::Full => Some(message),
+        }
+    }
+
     /// Get sync state for an account
     pub async fn get_sync_state(&self, account_id: &str) -> Result<Option<SyncState>> {
         let conn = self.db_manager.get_connection()
@@ -480,6 +628,98 @@ impl GmailSyncService {
         Ok(())
     }
 
+    /// Write the history IDs held in memory to `sync_states`, and mark
+    /// syncs cut short by shutdown as interrupted. Returns the accounts
+    /// written.
+    pub async fn persist_cursors(&self) -> Result<usize> {
+        let conn = self.db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        let now = Utc::now().to_rfc3339();
+
+        let states = self.sync_states.read().await;
+        let mut written = 0;
+        for (account_id, state) in states.iter() {
+            if let Some(history_id) = &state.last_history_id {
+                written += conn.execute(
+                    "UPDATE sync_states SET history_id = ?1, updated_at = ?2 WHERE account_id = ?3",
+                    rusqlite::params![history_id, &now, account_id],
+                ).context("Failed to persist sync cursor")?;
+            }
+        }
+
+        conn.execute(
+            "UPDATE sync_states SET sync_status = ?1, updated_at = ?2 WHERE sync_status = ?3",
+            rusqlite::params![format!("{:?}", SyncStatus::Interrupted), &now, format!("{:?}", SyncStatus::InProgress)],
+        ).context("Failed to mark interrupted syncs")?;
+
+        Ok(written)
+    }
+
+    /// Sync again the accounts whose sync the last shutdown cut short,
+    /// from their saved history IDs. Accounts paused on purpose stay paused.
+    pub fn resume_interrupted(&self) -> Result<usize> {
+        let account_ids: Vec<String> = {
+            let conn = self.db_manager.get_connection()
+                .context("Failed to get database connection")?;
+            let mut stmt = conn.prepare("SELECT account_id FROM sync_states WHERE sync_status = ?1")?;
+            let ids = stmt
+                .query_map(rusqlite::params![format!("{:?}", SyncStatus::Interrupted)], |row| row.get(0))?
+                .collect::<rusqlite::Result<_>>()
+                .context("Failed to list interrupted syncs")?;
+            ids
+        };
+        if account_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let Some(app) = self.app.clone() else {
+            return Ok(0);
+        };
+        let count = account_ids.len();
+        tauri::async_runtime::spawn(async move {
+            let Some(service) = app.try_state::<GmailSyncService>() else {
+                return;
+            };
+            for account_id in account_ids {
+                if let Err(e) = service.resume_sync(&account_id).await {
+                    eprintln!("❌ Resuming the sync of {} failed: {}", account_id, e);
+                }
+            }
+        });
+        Ok(count)
+    }
+
+    /// Incremental sync of every active account that is set up for sync
+    /// and not paused; one account failing doesn't hold up the others
+    pub async fn sync_active_accounts(&self) -> Result<()> {
+        let account_ids: Vec<String> = {
+            let conn = self.db_manager.get_connection()
+                .context("Failed to get database connection")?;
+            let mut stmt = conn.prepare(
+                "SELECT s.account_id FROM sync_states s
+                 JOIN gmail_accounts_secure a ON a.id = s.account_id
+                 WHERE a.is_active = 1 AND s.sync_status != ?1",
+            )?;
+            let ids = stmt
+                .query_map(rusqlite::params![format!("{:?}", SyncStatus::Paused)], |row| row.get(0))?
+                .collect::<rusqlite::Result<_>>()
+                .context("Failed to list accounts to sync")?;
+            ids
+        };
+
+        let mut failures = Vec::new();
+        for account_id in account_ids {
+            if let Err(e) = self.perform_incremental_sync(&account_id).await {
+                failures.push(format!("{}: {}", account_id, e));
+            }
+        }
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("Sync failed for {}", failures.join("; ")))
+        }
+    }
+
     /// Message operations
     pub async fn mark_as_read(&self, account_id: &str, message_ids: &[String], config: &GmailSyncConfig, tokens: &GmailTokens) -> Result<()> {
         self.create_gmail_client(config, tokens).await?;
@@ -530,27 +770,179 @@ impl GmailSyncService {
         Ok(())
     }
 
+    /// Fetch one page of a label's messages and store the ones not stored
+    /// yet. Returns the stored and failed counts and the next page token.
     async fn sync_message_batch(
         &self,
-        _access_token: &str,
         account_id: &str,
+        policies: &[LabelSyncPolicy],
+        plan: &LabelSyncPlan,
         batch_size: u32,
         page_token: Option<String>,
-    ) -> Result<(u64, u64, Option<String>, Option<String>)> {
-        // Mock implementation
-        println!("Syncing batch of {} messages for account {}", batch_size, account_id);
-        Ok((batch_size as u64, 0, page_token, Some("mock_history_id".to_string())))
+    ) -> Result<(u64, u64, Option<String>)> {
+        let query = MessageSearchQuery {
+            query: plan.query.clone(),
+            label_ids: Some(vec![plan.label_id.clone()]),
+            max_results: Some(batch_size),
+            page_token,
+            include_spam_trash: Some(false),
+        };
+        let page = self.api_service.get_messages(account_id, &query).await?;
+        let message_ids: Vec<String> = page.messages.unwrap_or_default().into_iter().map(|message| message.id).collect();
+
+        let new_ids = unstored_ids(&self.db_manager.get_connection()?, account_id, message_ids)?;
+        let (stored, failed) = self.fetch_and_store(account_id, policies, &new_ids, &HashSet::new()).await?;
+        Ok((stored, failed, page.next_page_token))
     }
 
+    /// Apply the mailbox changes since `start_history_id`: new messages are
+    /// fetched and stored as the label policies allow, label changes are
+    /// written to stored messages and deleted messages are removed.
+    /// Returns the changed and failed counts and the new history ID.
     async fn process_history_changes(
         &self,
-        _access_token: &str,
         account_id: &str,
         start_history_id: &str,
     ) -> Result<(u64, u64, Option<String>)> {
-        // Mock implementation
-        println!("Processing history changes for account {} from history ID {}", account_id, start_history_id);
-        Ok((10, 0, Some("new_history_id".to_string())))
+        let mut added = Vec::new();
+        let mut relabelled: HashMap<String, Vec<String>> = HashMap::new();
+        let mut deleted = Vec::new();
+        let mut history_id = None;
+        let mut page_token: Option<String> = None;
+        loop {
+            let page = self.api_service.list_history(account_id, start_history_id, page_token.as_deref()).await?;
+            history_id = page.history_id.or(history_id);
+            for record in page.history.unwrap_or_default() {
+                for change in record.messages_added {
+                    added.push(change.message.id);
+                }
+                for change in record.labels_added.into_iter().chain(record.labels_removed) {
+                    if let Some(labels) = change.message.label_ids {
+                        relabelled.insert(change.message.id, labels);
+                    }
+                }
+                for change in record.messages_deleted {
+                    deleted.push(change.message.id);
+                }
+            }
+            match page.next_page_token {
+                Some(token) => page_token = Some(token),
+                None => break,
+            }
+        }
+        added.retain(|id| !deleted.contains(id));
+        added.dedup();
+        relabelled.retain(|id, _| !deleted.contains(id) && !added.contains(id));
+        let arrived: HashSet<String> = added.iter().cloned().collect();
+
+        let policies = self.get_label_policies(account_id).await?;
+        let mut deltas = Vec::new();
+        {
+            let conn = self.db_manager.get_connection()?;
+            for id in &deleted {
+                if backfill_operations::delete_messages(&conn, account_id, std::slice::from_ref(id))? > 0 {
+                    deltas.push(NewCacheDelta::message(account_id, id, CHANGE_REMOVED, &[]));
+                }
+            }
+            // A message not stored yet may now fall under a policy that keeps it
+            for (id, labels) in relabelled {
+                if backfill_operations::set_message_labels(&conn, account_id, &id, &labels)? {
+                    deltas.push(NewCacheDelta::message(account_id, &id, CHANGE_UPDATED, &labels));
+                } else {
+                    added.push(id);
+                }
+            }
+        }
+        let changed = deltas.len() as u64;
+        self.announce(&deltas);
+
+        let (stored, failed) = self.fetch_and_store(account_id, &policies, &added, &arrived).await?;
+        Ok((changed + stored, failed, history_id))
+    }
+
+    /// Fetch messages, archive replies on muted threads, apply the label
+    /// policies and store what they allow in one transaction. Unread inbox
+    /// messages among `arrived` are announced as new mail. Returns the
+    /// stored and failed counts.
+    async fn fetch_and_store(
+        &self,
+        account_id: &str,
+        policies: &[LabelSyncPolicy],
+        message_ids: &[String],
+        arrived: &HashSet<String>,
+    ) -> Result<(u64, u64)> {
+        let mut failed = 0;
+        let mut messages = Vec::new();
+        for message_id in message_ids {
+            match self.api_service.get_parsed_message(account_id, message_id).await {
+                Ok(message) => messages.push(message),
+                // Deleted again before it could be fetched
+                Err(LibreOllamaError::GmailApi { status_code: Some(404), .. }) => continue,
+                Err(e) => {
+                    eprintln!("⚠️ [GMAIL-SYNC] Failed to fetch message {}: {}", message_id, e);
+                    failed += 1;
+                }
+            }
+        }
+        archive_muted_messages(&self.api_service, &self.db_manager, account_id, &mut messages, false).await;
+        let new_mail: Vec<MailNotice> = messages
+            .iter()
+            .filter(|message| arrived.contains(&message.id))
+            .filter(|message| message.labels.iter().any(|l| l == "INBOX") && message.labels.iter().any(|l| l == "UNREAD"))
+            .map(MailNotice::from)
+            .collect();
+        let has_reports = messages.iter().any(|message| receipt_service::is_report(&message.parsed_content.content_type));
+
+        let deltas = store_fetched(&mut self.db_manager.get_connection()?, account_id, policies, messages)?;
+        let stored = deltas.len() as u64;
+        self.announce(&deltas);
+        if !new_mail.is_empty() {
+            if let Some(event_bus) = self.app_state::<EventBus>() {
+                event_bus.mail_arrived(account_id, &new_mail);
+            }
+        }
+
+        // Read receipts and delivery reports update tracked sent mail as they arrive
+        if has_reports {
+            if let Some(compose_service) = self.app_state::<Arc<GmailComposeService>>() {
+                if let Err(e) = compose_service.process_receipts(account_id).await {
+                    eprintln!("⚠️ [GMAIL-SYNC] Failed to apply receipts for {}: {}", account_id, e);
+                }
+            }
+        }
+        Ok((stored, failed))
+    }
+
+    /// Refetch label counts once a sync stored or relabelled mail; a
+    /// failure leaves the old counts marked stale
+    async fn refresh_label_counts(&self, account_id: &str) {
+        if let Err(e) = self.api_service.refresh_label_counts(account_id).await {
+            eprintln!("⚠️ [GMAIL-SYNC] Could not refresh label counts for {}: {}", account_id, e);
+        }
+    }
+
+    fn announce(&self, deltas: &[NewCacheDelta]) {
+        if let Some(event_bus) = self.app_state::<EventBus>() {
+            event_bus.cache_changed(deltas);
+        }
+    }
+
+    /// Create an account's sync state row if it has none
+    fn ensure_sync_state(&self, account_id: &str) -> Result<()> {
+        let conn = self.db_manager.get_connection()
+            .context("Failed to get database connection")?;
+        if self.get_sync_state_from_db(account_id, &conn)?.is_none() {
+            self.store_sync_state_in_db(&SyncState {
+                account_id: account_id.to_string(),
+                history_id: None,
+                last_sync_timestamp: None,
+                messages_synced: 0,
+                messages_failed: 0,
+                sync_status: SyncStatus::Idle,
+                errors: Vec::new(),
+            }, &conn)?;
+        }
+        Ok(())
     }
 
     fn get_sync_state_from_db(&self, account_id: &str, conn: &rusqlite::Connection) -> Result<Option<SyncState>> {
@@ -570,6 +962,7 @@ impl GmailSyncService {
                     "Completed" => SyncStatus::Completed,
                     "Failed" => SyncStatus::Failed,
                     "Paused" => SyncStatus::Paused,
+                    "Interrupted" => SyncStatus::Interrupted,
                     _ => SyncStatus::Idle,
                 };
 
@@ -614,6 +1007,30 @@ impl GmailSyncService {
         Ok(())
     }
 
+    fn get_sync_config(&self, account_id: &str) -> Result<Option<SyncConfig>> {
+        let conn = self.db_manager.get_connection()
+            .context("Failed to get database connection")?;
+
+        conn.query_row(
+            "SELECT account_id, max_messages_per_batch, sync_interval_minutes, labels_to_sync,
+             exclude_labels, full_sync_on_startup, enable_incremental_sync
+             FROM sync_configs WHERE account_id = ?1",
+            rusqlite::params![account_id],
+            |row| {
+                let labels = |json: Option<String>| json.and_then(|j| serde_json::from_str::<Vec<String>>(&j).ok());
+                Ok(SyncConfig {
+                    account_id: row.get(0)?,
+                    max_messages_per_batch: row.get(1)?,
+                    sync_interval_minutes: row.get(2)?,
+                    labels_to_sync: labels(row.get(3)?),
+                    exclude_labels: labels(row.get(4)?),
+                    full_sync_on_startup: row.get(5)?,
+                    enable_incremental_sync: row.get(6)?,
+                })
+            },
+        ).optional().context("Failed to get sync config from database")
+    }
+
     fn store_sync_config_in_db(&self, config: &SyncConfig, conn: &rusqlite::Connection) -> Result<()> {
         let now = Utc::now().to_rfc3339();
         let labels_to_sync_json = config.labels_to_sync.as_ref()
@@ -683,4 +1100,57 @@ impl GmailSyncService {
 
         Ok(())
     }
-} 
\ No newline at end of file
+}
+
+/// The IDs among `message_ids` that are not in the account's message
+/// store yet
+pub fn unstored_ids(conn: &Connection, account_id: &str, message_ids: Vec<String>) -> Result<Vec<String>> {
+    let stored = cache_operations::stored_message_ids(conn, account_id, &message_ids)?;
+    Ok(message_ids.into_iter().filter(|id| !stored.contains(id)).collect())
+}
+
+/// Store fetched messages as the label policies allow, in one transaction.
+/// Returns the deltas announcing the stored ones.
+pub fn store_fetched(
+    conn: &mut Connection,
+    account_id: &str,
+    policies: &[LabelSyncPolicy],
+    messages: Vec<ProcessedGmailMessage>,
+) -> Result<Vec<NewCacheDelta>> {
+    let mut batch = Vec::new();
+    let mut deltas = Vec::new();
+    for message in messages {
+        if let Some((message, delta)) = stored_message(account_id, policies, message)? {
+            batch.push(message);
+            deltas.push(delta);
+        }
+    }
+    cache_operations::store_messages(conn, &batch)?;
+    Ok(deltas)
+}
+
+/// The message store row for a message per the label policies, and the
+/// delta announcing it; None if policy excluded the message
+pub(crate) fn stored_message(
+    account_id: &str,
+    policies: &[LabelSyncPolicy],
+    message: ProcessedGmailMessage,
+) -> Result<Option<(StoredMessage, NewCacheDelta)>> {
+    let had_body = message.parsed_content.body_text.is_some() || message.parsed_content.body_html.is_some();
+    let Some(message) = GmailSyncService::apply_label_policies(policies, message) else {
+        return Ok(None);
+    };
+    let has_body = message.parsed_content.body_text.is_some() || message.parsed_content.body_html.is_some();
+
+    let delta = NewCacheDelta::message(account_id, &message.id, CHANGE_ADDED, &message.labels);
+    let stored = StoredMessage {
+        account_id: account_id.to_string(),
+        message_id: message.id.clone(),
+        thread_id: message.thread_id.clone(),
+        label_ids: message.labels.clone(),
+        internal_date: message.internal_date.as_deref().and_then(|d| d.parse().ok()),
+        content: if had_body && !has_body { "headers" } else { "full" }.to_string(),
+        message_data: serde_json::to_value(&message)?,
+    };
+    Ok(Some((stored, delta)))
+}
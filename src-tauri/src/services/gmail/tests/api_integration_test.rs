@@ -9,7 +9,6 @@ mod tests {
     use crate::services::gmail::auth_service::GmailAuthService;
     use crate::commands::rate_limiter::{RateLimiter, RateLimitConfig};
     use std::sync::Arc;
-    use tokio::sync::Mutex;
 
     // Test helpers
     async fn setup_test_services() -> (Arc<GmailAuthService>, Arc<GmailApiService>) {
@@ -24,7 +23,7 @@ mod tests {
         
         let auth_service = Arc::new(GmailAuthService::new(db_manager.clone(), encryption_key).unwrap());
         
-        let rate_limiter = Arc::new(Mutex::new(RateLimiter::new(RateLimitConfig::default())));
+        let rate_limiter = Arc::new(RateLimiter::new(RateLimitConfig::default()));
         let api_service = Arc::new(GmailApiService::new(
             auth_service.clone(),
             db_manager,
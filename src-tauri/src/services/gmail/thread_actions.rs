@@ -0,0 +1,422 @@
+//! Thread-level mail actions
+//!
+//! Archiving, labelling or marking a conversation read applies to every
+//! message in it. A thread's messages come from the local message store, or
+//! from Gmail when the thread has not been synced. Changed messages go to
+//! Gmail in batchModify calls of up to 1000 messages, each thread whole in
+//! one call where it fits. The local store is updated for each call once
+//! Gmail accepted it, so when a call fails the threads sent before it keep
+//! their change, in Gmail and locally, and the action reports the error.
+//!
+//! Each thread's labels before and after are journaled under the
+//! `gmail_thread` entity type, which is what undo replays in reverse.
+//! Label changes and trashing of single messages are journaled under
+//! `gmail_message` as the labels added and removed, with the inverse
+//! change as the previous payload.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::database::operations::{backfill_operations, change_journal_operations};
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::gmail::api_service::{GmailApiService, BATCH_MODIFY_LIMIT};
+
+/// change_journal entity type for thread actions
+pub const THREAD_ENTITY: &str = "gmail_thread";
+
+/// change_journal entity type for label changes to single messages
+pub const MESSAGE_ENTITY: &str = "gmail_message";
+
+/// Label IDs per message ID
+pub type ThreadLabels = BTreeMap<String, Vec<String>>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ThreadAction {
+    Archive,
+    MoveToInbox,
+    MarkRead,
+    MarkUnread,
+    Label { add: Vec<String>, remove: Vec<String> },
+}
+
+impl ThreadAction {
+    /// Labels to add to and remove from every message in the thread
+    pub fn label_changes(&self) -> (Vec<String>, Vec<String>) {
+        let label = |id: &str| vec![id.to_string()];
+        match self {
+            ThreadAction::Archive => (Vec::new(), label("INBOX")),
+            ThreadAction::MoveToInbox => (label("INBOX"), Vec::new()),
+            ThreadAction::MarkRead => (Vec::new(), label("UNREAD")),
+            ThreadAction::MarkUnread => (label("UNREAD"), Vec::new()),
+            ThreadAction::Label { add, remove } => (add.clone(), remove.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadActionResult {
+    /// Threads that had at least one message change
+    pub thread_ids: Vec<String>,
+    pub messages_changed: usize,
+    /// Labels of each changed message after the action
+    pub labels: ThreadLabels,
+}
+
+/// One batchModify call: the same labels added and removed on each message
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelChange {
+    pub message_ids: Vec<String>,
+    pub add: Vec<String>,
+    pub remove: Vec<String>,
+}
+
+/// A message's labels after an action
+pub fn apply_changes(labels: &[String], add: &[String], remove: &[String]) -> Vec<String> {
+    let mut result: Vec<String> = labels.iter().filter(|label| !remove.contains(label)).cloned().collect();
+    for label in add {
+        if !result.contains(label) {
+            result.push(label.clone());
+        }
+    }
+    result
+}
+
+/// The calls that take each message from its labels in `from` to those in
+/// `to`, grouping messages that need the same change
+pub fn diff_groups(from: &ThreadLabels, to: &ThreadLabels) -> Vec<LabelChange> {
+    let mut groups: BTreeMap<(Vec<String>, Vec<String>), Vec<String>> = BTreeMap::new();
+    for (message_id, target) in to {
+        let Some(current) = from.get(message_id) else { continue };
+        let current: BTreeSet<&String> = current.iter().collect();
+        let target: BTreeSet<&String> = target.iter().collect();
+        let add: Vec<String> = target.difference(&current).map(|label| label.to_string()).collect();
+        let remove: Vec<String> = current.difference(&target).map(|label| label.to_string()).collect();
+        if !add.is_empty() || !remove.is_empty() {
+            groups.entry((add, remove)).or_default().push(message_id.clone());
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|((add, remove), message_ids)| LabelChange { message_ids, add, remove })
+        .collect()
+}
+
+/// Split changed threads, kept in order, into runs of at most `limit`
+/// messages; a thread bigger than that gets a run of its own
+fn thread_batches<T>(changes: &[(String, ThreadLabels, T)], limit: usize) -> Vec<&[(String, ThreadLabels, T)]> {
+    let mut batches = Vec::new();
+    let (mut start, mut size) = (0, 0);
+    for (index, (_, before, _)) in changes.iter().enumerate() {
+        if size > 0 && size + before.len() > limit {
+            batches.push(&changes[start..index]);
+            (start, size) = (index, 0);
+        }
+        size += before.len();
+    }
+    if start < changes.len() {
+        batches.push(&changes[start..]);
+    }
+    batches
+}
+
+/// Messages of a thread with their labels, from the store or from Gmail
+async fn thread_labels(
+    api_service: &GmailApiService,
+    db_manager: &DatabaseManager,
+    account_id: &str,
+    thread_id: &str,
+) -> Result<ThreadLabels> {
+    let stored = {
+        let conn = db_manager.get_connection()?;
+        backfill_operations::get_thread_labels(&conn, account_id, thread_id)?
+    };
+    let messages = if stored.is_empty() {
+        api_service.get_thread_labels(account_id, thread_id).await?
+    } else {
+        stored
+    };
+
+    if messages.is_empty() {
+        return Err(LibreOllamaError::NotFound { resource: format!("Gmail thread {}", thread_id) });
+    }
+    Ok(messages.into_iter().collect())
+}
+
+fn labels_in(payload: Option<&Value>) -> ThreadLabels {
+    payload
+        .and_then(|payload| payload.get("labels"))
+        .and_then(|labels| serde_json::from_value(labels.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// Apply an action to every message of the given threads
+pub async fn apply(
+    api_service: &GmailApiService,
+    db_manager: &DatabaseManager,
+    account_id: &str,
+    thread_ids: &[String],
+    action: &ThreadAction,
+) -> Result<ThreadActionResult> {
+    let (add, remove) = action.label_changes();
+    if add.is_empty() && remove.is_empty() {
+        return Err(LibreOllamaError::InvalidInput {
+            message: "A thread action needs at least one label to add or remove".to_string(),
+            field: Some("action".to_string()),
+        });
+    }
+    if add.iter().any(|label| remove.contains(label)) {
+        return Err(LibreOllamaError::InvalidInput {
+            message: "A label cannot be added and removed at once".to_string(),
+            field: Some("action".to_string()),
+        });
+    }
+
+    // Only messages the action actually changes are sent and journaled
+    let mut changes: Vec<(String, ThreadLabels, ThreadLabels)> = Vec::new();
+    for thread_id in thread_ids {
+        let mut before = thread_labels(api_service, db_manager, account_id, thread_id).await?;
+        let mut after = ThreadLabels::new();
+        before.retain(|message_id, labels| {
+            let changed = apply_changes(labels, &add, &remove);
+            let keep = changed != *labels;
+            if keep {
+                after.insert(message_id.clone(), changed);
+            }
+            keep
+        });
+        if !before.is_empty() {
+            changes.push((thread_id.clone(), before, after));
+        }
+    }
+
+    let messages_changed: usize = changes.iter().map(|(_, before, _)| before.len()).sum();
+    if messages_changed == 0 {
+        return Ok(ThreadActionResult { thread_ids: Vec::new(), messages_changed: 0, labels: ThreadLabels::new() });
+    }
+
+    for batch in thread_batches(&changes, BATCH_MODIFY_LIMIT) {
+        let message_ids: Vec<String> = batch.iter().flat_map(|(_, before, _)| before.keys().cloned()).collect();
+        api_service.modify_messages(account_id, message_ids, add.clone(), remove.clone()).await?;
+
+        // Gmail has the change now; local bookkeeping failures are logged so
+        // the next sync can repair the store, but the action still succeeded
+        if let Err(e) = record(db_manager, account_id, batch) {
+            eprintln!("⚠️ [MAIL] Failed to record thread action for {}: {}", account_id, e);
+        }
+    }
+
+    let mut thread_ids = Vec::new();
+    let mut labels = ThreadLabels::new();
+    for (thread_id, _, after) in changes {
+        thread_ids.push(thread_id);
+        labels.extend(after);
+    }
+    Ok(ThreadActionResult { thread_ids, messages_changed, labels })
+}
+
+/// Store the new labels and journal each thread's change
+fn record(db_manager: &DatabaseManager, account_id: &str, changes: &[(String, ThreadLabels, ThreadLabels)]) -> anyhow::Result<()> {
+    let mut conn = db_manager.get_connection()?;
+    let tx = conn.transaction()?;
+    for (thread_id, before, after) in changes {
+        for (message_id, labels) in after {
+            backfill_operations::set_message_labels(&tx, account_id, message_id, labels)?;
+        }
+        change_journal_operations::record_change(
+            &tx,
+            THREAD_ENTITY,
+            thread_id,
+            "update",
+            &json!({ "labels": after }),
+            Some(&json!({ "labels": before })),
+            Some(account_id),
+        )?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Store and journal a label change Gmail accepted for `message_ids`.
+///
+/// For stored messages only the labels the change really added or removed
+/// are journaled, so undoing it leaves labels the message already had;
+/// messages not in the store journal the change as requested.
+pub fn record_message_change(
+    conn: &mut Connection,
+    account_id: &str,
+    message_ids: &[String],
+    add: &[String],
+    remove: &[String],
+) -> anyhow::Result<()> {
+    let tx = conn.transaction()?;
+    for message_id in message_ids {
+        let (added, removed): (Vec<String>, Vec<String>) = match backfill_operations::get_message_labels(&tx, account_id, message_id)? {
+            Some(labels) => {
+                backfill_operations::set_message_labels(&tx, account_id, message_id, &apply_changes(&labels, add, remove))?;
+                (
+                    add.iter().filter(|label| !labels.contains(label)).cloned().collect(),
+                    remove.iter().filter(|label| labels.contains(label)).cloned().collect(),
+                )
+            }
+            None => (add.to_vec(), remove.to_vec()),
+        };
+        if added.is_empty() && removed.is_empty() {
+            continue;
+        }
+        change_journal_operations::record_change(
+            &tx,
+            MESSAGE_ENTITY,
+            message_id,
+            "update",
+            &json!({ "add_label_ids": added, "remove_label_ids": removed }),
+            Some(&json!({ "add_label_ids": removed, "remove_label_ids": added })),
+            Some(account_id),
+        )?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Undo the latest action on each of the given threads.
+///
+/// Only the labels that action changed are put back, so later changes to
+/// other labels survive. Threads with nothing to undo are skipped.
+pub async fn undo(
+    api_service: &GmailApiService,
+    db_manager: &DatabaseManager,
+    account_id: &str,
+    thread_ids: &[String],
+) -> Result<ThreadActionResult> {
+    let mut from = ThreadLabels::new();
+    let mut to = ThreadLabels::new();
+    let mut undone = Vec::new();
+    {
+        let conn = db_manager.get_connection()?;
+        for thread_id in thread_ids {
+            let Some(entry) = change_journal_operations::last_undoable_change(&conn, THREAD_ENTITY, thread_id)? else {
+                continue;
+            };
+            if entry.account_id.as_deref() != Some(account_id) {
+                continue;
+            }
+            from.extend(labels_in(Some(&entry.payload)));
+            to.extend(labels_in(entry.previous_payload.as_ref()));
+            undone.push(thread_id.clone());
+        }
+    }
+
+    // Each group's messages are stored as soon as Gmail accepts them; the
+    // journal entries are only undone once every group went through, so a
+    // failed undo can be retried
+    let groups = diff_groups(&from, &to);
+    for group in &groups {
+        api_service
+            .modify_messages(account_id, group.message_ids.clone(), group.add.clone(), group.remove.clone())
+            .await?;
+        let conn = db_manager.get_connection()?;
+        for message_id in &group.message_ids {
+            backfill_operations::set_message_labels(&conn, account_id, message_id, &to[message_id])?;
+        }
+    }
+
+    let mut conn = db_manager.get_connection()?;
+    let tx = conn.transaction().map_err(anyhow::Error::from)?;
+    for thread_id in &undone {
+        change_journal_operations::undo_last_change(&tx, THREAD_ENTITY, thread_id)?;
+    }
+    tx.commit().map_err(anyhow::Error::from)?;
+
+    let labels: ThreadLabels = groups
+        .iter()
+        .flat_map(|group| group.message_ids.iter())
+        .map(|message_id| (message_id.clone(), to[message_id].clone()))
+        .collect();
+    Ok(ThreadActionResult { thread_ids: undone, messages_changed: labels.len(), labels })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn applies_label_changes_once() {
+        let (add, remove) = ThreadAction::Label { add: labels(&["Label_1"]), remove: labels(&["INBOX"]) }.label_changes();
+        assert_eq!(apply_changes(&labels(&["INBOX", "UNREAD"]), &add, &remove), labels(&["UNREAD", "Label_1"]));
+        assert_eq!(apply_changes(&labels(&["Label_1"]), &add, &remove), labels(&["Label_1"]));
+    }
+
+    #[test]
+    fn groups_undo_by_needed_change() {
+        let from: ThreadLabels = [
+            ("m1".to_string(), labels(&["INBOX"])),
+            ("m2".to_string(), labels(&["INBOX"])),
+            ("m3".to_string(), labels(&["INBOX", "STARRED"])),
+        ]
+        .into();
+        let to: ThreadLabels = [
+            ("m1".to_string(), labels(&["INBOX", "UNREAD"])),
+            ("m2".to_string(), labels(&["INBOX", "UNREAD"])),
+            ("m3".to_string(), labels(&["INBOX", "STARRED"])),
+        ]
+        .into();
+
+        assert_eq!(
+            diff_groups(&from, &to),
+            vec![LabelChange { message_ids: labels(&["m1", "m2"]), add: labels(&["UNREAD"]), remove: Vec::new() }]
+        );
+    }
+
+    #[test]
+    fn batches_keep_threads_whole() {
+        let thread = |id: &str, messages: usize| {
+            let before: ThreadLabels = (0..messages).map(|n| (format!("{}-{}", id, n), labels(&["INBOX"]))).collect();
+            (id.to_string(), before, ())
+        };
+        let changes = vec![thread("t1", 2), thread("t2", 2), thread("t3", 5), thread("t4", 1)];
+
+        let batches: Vec<Vec<&str>> = thread_batches(&changes, 4)
+            .into_iter()
+            .map(|batch| batch.iter().map(|(id, _, _)| id.as_str()).collect())
+            .collect();
+        assert_eq!(batches, vec![vec!["t1", "t2"], vec!["t3"], vec!["t4"]]);
+        assert!(thread_batches::<()>(&[], 4).is_empty());
+    }
+
+    #[test]
+    fn message_changes_journal_what_changed() {
+        use crate::database::operations::backfill_operations::{store_message, StoredMessage};
+
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::database::schema::run_migrations(&conn).unwrap();
+        store_message(&conn, &StoredMessage {
+            account_id: "acc".to_string(),
+            message_id: "m1".to_string(),
+            thread_id: "t1".to_string(),
+            label_ids: labels(&["INBOX", "STARRED"]),
+            internal_date: Some(1),
+            content: "full".to_string(),
+            message_data: json!({ "id": "m1" }),
+        })
+        .unwrap();
+
+        let ids = labels(&["m1", "unsynced"]);
+        record_message_change(&mut conn, "acc", &ids, &labels(&["STARRED", "TRASH"]), &labels(&["INBOX", "UNREAD"])).unwrap();
+
+        assert_eq!(backfill_operations::get_message_labels(&conn, "acc", "m1").unwrap(), Some(labels(&["STARRED", "TRASH"])));
+        let stored = &change_journal_operations::get_entity_history(&conn, MESSAGE_ENTITY, "m1").unwrap()[0];
+        assert_eq!(stored.payload, json!({ "add_label_ids": ["TRASH"], "remove_label_ids": ["INBOX"] }));
+        assert_eq!(stored.previous_payload, Some(json!({ "add_label_ids": ["INBOX"], "remove_label_ids": ["TRASH"] })));
+        let unsynced = &change_journal_operations::get_entity_history(&conn, MESSAGE_ENTITY, "unsynced").unwrap()[0];
+        assert_eq!(unsynced.payload["remove_label_ids"], json!(["INBOX", "UNREAD"]));
+    }
+}
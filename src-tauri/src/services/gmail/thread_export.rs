@@ -0,0 +1,514 @@
+//! Thread export for sharing outside email
+//!
+//! Renders a whole conversation as Markdown, or as a PDF printed from a
+//! standalone HTML page by a headless Chromium-family browser (Chrome,
+//! Chromium, Edge or Brave) found on the machine. Message HTML goes
+//! through an allow-list sanitizer first, so scripts, frames, forms, event
+//! handlers and `javascript:` links are removed, and remote images are
+//! dropped so the renderer never fetches anything; a content security
+//! policy on the page backs that up. Attachments are listed by name, type
+//! and size, not embedded. Exports never replace an existing file.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::gmail::api_service::{EmailAddress, ProcessedGmailMessage};
+use crate::services::google::task_export::{escape_html, file_stem};
+use crate::services::i18n;
+
+/// Longest a PDF print may take before the renderer is stopped
+const RENDER_TIMEOUT: Duration = Duration::from_secs(90);
+/// Nothing is loaded from outside the page itself
+const CONTENT_POLICY: &str = "default-src 'none'; img-src data:; style-src 'unsafe-inline'; font-src data:";
+
+/// Elements removed together with their content
+const DROPPED_ELEMENTS: &[&str] = &[
+    "script", "style", "iframe", "object", "embed", "noscript", "template", "frameset", "applet", "form", "svg", "math",
+    "head", "title", "select", "textarea",
+];
+/// Presentational attributes email HTML lays itself out with
+const LAYOUT_ATTRIBUTES: &[&str] = &[
+    "style", "dir", "align", "valign", "width", "height", "bgcolor", "border", "cellpadding", "cellspacing",
+];
+
+lazy_static::lazy_static! {
+    /// Allow-list sanitizer: anything not listed is removed, so markup the
+    /// browser would parse differently from a pattern cannot slip through
+    static ref SANITIZER: ammonia::Builder<'static> = {
+        let mut builder = ammonia::Builder::default();
+        builder
+            .add_tags(["font"])
+            .add_tag_attributes("font", ["color", "face", "size"])
+            .add_clean_content_tags(DROPPED_ELEMENTS)
+            .add_generic_attributes(LAYOUT_ATTRIBUTES)
+            .url_schemes(["http", "https", "mailto", "tel", "data"].into_iter().collect())
+            .attribute_filter(|element, attribute, value| match (element, attribute) {
+                // Images only from the message itself, never fetched
+                (_, "src") => is_data_image(value).then_some(value.into()),
+                (_, "href" | "cite") if value.trim_start().to_ascii_lowercase().starts_with("data:") => None,
+                // Inline styles that could load something
+                (_, "style") if STYLE_WITH_URL.is_match(value) => None,
+                _ => Some(value.into()),
+            });
+        builder
+    };
+    static ref STYLE_WITH_URL: Regex = Regex::new(r"(?i)url\s*\(|expression\s*\(|@import|\\").unwrap();
+}
+
+fn is_data_image(value: &str) -> bool {
+    value.trim_start().get(..11).is_some_and(|scheme| scheme.eq_ignore_ascii_case("data:image/"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThreadExportFormat {
+    Pdf,
+    Markdown,
+}
+
+impl ThreadExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ThreadExportFormat::Pdf => "pdf",
+            ThreadExportFormat::Markdown => "md",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportStage {
+    Loading,
+    Rendering,
+    Printing,
+    Writing,
+    Done,
+}
+
+/// Where an export has got to; `done` of `total` counts messages while
+/// rendering and steps otherwise
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportProgress {
+    pub stage: ExportStage,
+    pub done: usize,
+    pub total: usize,
+}
+
+impl ExportProgress {
+    pub fn new(stage: ExportStage, done: usize, total: usize) -> Self {
+        Self { stage, done, total }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadExport {
+    pub path: String,
+    pub format: ThreadExportFormat,
+    pub message_count: usize,
+    pub attachment_count: usize,
+    pub bytes: u64,
+}
+
+/// Message HTML reduced to what is safe to show and print offline
+pub fn sanitize_html(html: &str) -> String {
+    SANITIZER.clean(html).to_string().trim().to_string()
+}
+
+fn display_address(address: &EmailAddress) -> String {
+    match address.name.as_deref().filter(|name| !name.trim().is_empty()) {
+        Some(name) => format!("{} <{}>", name, address.email),
+        None => address.email.clone(),
+    }
+}
+
+fn display_addresses(addresses: &[EmailAddress]) -> String {
+    addresses.iter().map(display_address).collect::<Vec<_>>().join(", ")
+}
+
+fn human_size(bytes: usize) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 { format!("{} B", bytes) } else { format!("{:.1} {}", size, UNITS[unit]) }
+}
+
+/// Attachments as "name (type, size)", leaving out inline images
+fn attachment_lines(message: &ProcessedGmailMessage) -> Vec<String> {
+    message
+        .parsed_content
+        .attachments
+        .iter()
+        .filter(|attachment| !attachment.is_inline)
+        .map(|attachment| {
+            let name = attachment
+                .filename
+                .clone()
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| i18n::t("export-attachment"));
+            match attachment.size {
+                Some(size) => format!("{} ({}, {})", name, attachment.content_type, human_size(size)),
+                None => format!("{} ({})", name, attachment.content_type),
+            }
+        })
+        .collect()
+}
+
+pub fn attachment_count(messages: &[ProcessedGmailMessage]) -> usize {
+    messages.iter().map(|message| attachment_lines(message).len()).sum()
+}
+
+/// The thread's subject, from its first message that has one
+pub fn thread_subject(messages: &[ProcessedGmailMessage]) -> String {
+    messages
+        .iter()
+        .find_map(|message| message.parsed_content.subject.clone().filter(|subject| !subject.trim().is_empty()))
+        .unwrap_or_else(|| i18n::t("export-no-subject"))
+}
+
+/// Header lines of a message: date, recipients
+fn header_lines(message: &ProcessedGmailMessage) -> Vec<(String, String)> {
+    let parsed = &message.parsed_content;
+    let mut lines = Vec::new();
+    if let Some(date) = parsed.date.as_deref() {
+        lines.push((i18n::t("export-date"), date.to_string()));
+    }
+    if !parsed.to.is_empty() {
+        lines.push((i18n::t("export-to"), display_addresses(&parsed.to)));
+    }
+    if !parsed.cc.is_empty() {
+        lines.push((i18n::t("export-cc"), display_addresses(&parsed.cc)));
+    }
+    lines
+}
+
+fn message_markdown(message: &ProcessedGmailMessage) -> String {
+    let parsed = &message.parsed_content;
+    let text = parsed.body_text.as_deref().map(str::trim).filter(|text| !text.is_empty());
+    match (text, parsed.body_html.as_deref()) {
+        (Some(text), _) => text.replace("\r\n", "\n"),
+        (None, Some(html)) => html2md::parse_html(&sanitize_html(html)).trim().to_string(),
+        (None, None) => message.snippet.clone().unwrap_or_default(),
+    }
+}
+
+/// Markdown of the whole thread, calling `on_message` after each message
+pub fn render_markdown(messages: &[ProcessedGmailMessage], mut on_message: impl FnMut(usize)) -> String {
+    let mut out = format!("# {}\n\n", thread_subject(messages));
+    out.push_str(&format!("_{}_\n", i18n::t_args("export-message-count", &[("count", messages.len().into())])));
+    for (index, message) in messages.iter().enumerate() {
+        out.push_str("\n---\n\n");
+        out.push_str(&format!("## {}\n\n", display_address(&message.parsed_content.from)));
+        for (label, value) in header_lines(message) {
+            out.push_str(&format!("**{}:** {}  \n", label, value));
+        }
+        out.push('\n');
+        out.push_str(&message_markdown(message));
+        out.push('\n');
+        let attachments = attachment_lines(message);
+        if !attachments.is_empty() {
+            out.push_str(&format!("\n**{}**\n\n", i18n::t("export-attachments")));
+            for attachment in attachments {
+                out.push_str(&format!("- {}\n", attachment));
+            }
+        }
+        on_message(index + 1);
+    }
+    out
+}
+
+fn message_html(message: &ProcessedGmailMessage) -> String {
+    let parsed = &message.parsed_content;
+    let body = match (parsed.body_html.as_deref(), parsed.body_text.as_deref()) {
+        (Some(html), _) if !html.trim().is_empty() => format!("<div class=\"body\">{}</div>", sanitize_html(html)),
+        (_, Some(text)) => format!("<pre class=\"body\">{}</pre>", escape_html(text.trim())),
+        _ => format!("<p class=\"body\">{}</p>", escape_html(message.snippet.as_deref().unwrap_or_default())),
+    };
+    let mut out = format!(
+        "<article>\n<h2>{}</h2>\n<table class=\"headers\">\n",
+        escape_html(&display_address(&parsed.from))
+    );
+    for (label, value) in header_lines(message) {
+        out.push_str(&format!("<tr><td>{}</td><td>{}</td></tr>\n", escape_html(&label), escape_html(&value)));
+    }
+    out.push_str("</table>\n");
+    out.push_str(&body);
+    out.push('\n');
+    let attachments = attachment_lines(message);
+    if !attachments.is_empty() {
+        out.push_str(&format!("<div class=\"attachments\"><strong>{}</strong><ul>\n", escape_html(&i18n::t("export-attachments"))));
+        for attachment in attachments {
+            out.push_str(&format!("<li>{}</li>\n", escape_html(&attachment)));
+        }
+        out.push_str("</ul></div>\n");
+    }
+    out.push_str("</article>\n");
+    out
+}
+
+/// A standalone printable page of the whole thread, calling `on_message`
+/// after each message
+pub fn render_html(messages: &[ProcessedGmailMessage], mut on_message: impl FnMut(usize)) -> String {
+    let subject = thread_subject(messages);
+    let mut body = format!("<h1>{}</h1>\n", escape_html(&subject));
+    for (index, message) in messages.iter().enumerate() {
+        body.push_str(&message_html(message));
+        on_message(index + 1);
+    }
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta http-equiv=\"Content-Security-Policy\" content=\"{}\">\n<title>{}</title>\n<style>\n\
+         body {{ font-family: -apple-system, 'Segoe UI', sans-serif; max-width: 46rem; margin: 2rem auto; color: #111; }}\n\
+         h1 {{ font-size: 1.5rem; }} h2 {{ font-size: 1.05rem; margin: 0 0 0.3rem; }}\n\
+         article {{ border-top: 1px solid #ccc; padding: 1rem 0; }}\n\
+         table.headers td {{ padding: 0 0.75rem 0 0; color: #555; font-size: 0.85em; vertical-align: top; }}\n\
+         .body {{ margin-top: 0.75rem; overflow-wrap: anywhere; }} pre.body {{ white-space: pre-wrap; font-family: inherit; }}\n\
+         .body img {{ max-width: 100%; }} .attachments {{ margin-top: 0.75rem; font-size: 0.9em; color: #444; }}\n\
+         @media print {{ body {{ margin: 0; }} article {{ break-inside: auto; }} }}\n\
+         </style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        CONTENT_POLICY,
+        escape_html(&subject),
+        body
+    )
+}
+
+/// Browsers that can print to PDF headlessly, most likely first
+fn renderer_candidates() -> Vec<PathBuf> {
+    let mut candidates: Vec<PathBuf> = Vec::new();
+    if cfg!(target_os = "macos") {
+        for app in ["Google Chrome", "Chromium", "Microsoft Edge", "Brave Browser"] {
+            candidates.push(PathBuf::from(format!("/Applications/{app}.app/Contents/MacOS/{app}")));
+        }
+    } else if cfg!(windows) {
+        for root in ["ProgramFiles", "ProgramFiles(x86)", "LOCALAPPDATA"].into_iter().filter_map(std::env::var_os) {
+            let root = PathBuf::from(root);
+            candidates.push(root.join("Google").join("Chrome").join("Application").join("chrome.exe"));
+            candidates.push(root.join("Microsoft").join("Edge").join("Application").join("msedge.exe"));
+            candidates.push(root.join("BraveSoftware").join("Brave-Browser").join("Application").join("brave.exe"));
+        }
+    } else {
+        for name in ["chromium", "chromium-browser", "google-chrome", "google-chrome-stable", "microsoft-edge", "brave-browser"] {
+            candidates.push(PathBuf::from(name));
+        }
+    }
+    candidates
+}
+
+/// The headless browser used for PDF exports, if one is installed
+pub fn find_renderer() -> Option<PathBuf> {
+    renderer_candidates().into_iter().find(|candidate| {
+        if candidate.is_absolute() {
+            candidate.is_file()
+        } else {
+            std::process::Command::new(candidate).arg("--version").output().is_ok_and(|output| output.status.success())
+        }
+    })
+}
+
+pub fn renderer_available() -> bool {
+    find_renderer().is_some()
+}
+
+/// Print an HTML page to a PDF file with a headless browser. The page and
+/// a throwaway browser profile live in a temporary directory.
+pub async fn print_pdf(renderer: &Path, html: &str, output: &Path) -> Result<()> {
+    let work_dir = std::env::temp_dir().join(format!("libreollama-export-{}", uuid::Uuid::new_v4()));
+    tokio::fs::create_dir_all(&work_dir).await?;
+    let result = async {
+        let page = work_dir.join("thread.html");
+        tokio::fs::write(&page, html).await?;
+        let page_url = url::Url::from_file_path(&page).map_err(|_| LibreOllamaError::Internal {
+            message: format!("Cannot address {} as a file URL", page.display()),
+        })?;
+
+        let mut command = Command::new(renderer);
+        command
+            .args(["--headless", "--disable-gpu", "--no-first-run", "--no-default-browser-check", "--disable-extensions"])
+            .args(["--no-pdf-header-footer", "--run-all-compositor-stages-before-draw"])
+            .arg(format!("--user-data-dir={}", work_dir.join("profile").display()))
+            .arg(format!("--print-to-pdf={}", output.display()))
+            .arg(page_url.as_str())
+            .kill_on_drop(true);
+        let printed = tokio::time::timeout(RENDER_TIMEOUT, command.output())
+            .await
+            .map_err(|_| LibreOllamaError::Timeout {
+                operation: "printing the thread to PDF".to_string(),
+                duration_ms: Some(RENDER_TIMEOUT.as_millis() as u64),
+            })?
+            .map_err(|e| LibreOllamaError::Internal { message: format!("Failed to start {}: {}", renderer.display(), e) })?;
+
+        let is_pdf = tokio::fs::read(output).await.map(|bytes| bytes.starts_with(b"%PDF")).unwrap_or(false);
+        if !printed.status.success() || !is_pdf {
+            return Err(LibreOllamaError::Internal {
+                message: format!(
+                    "{} did not print the thread: {}",
+                    renderer.display(),
+                    String::from_utf8_lossy(&printed.stderr).lines().last().unwrap_or("no output")
+                ),
+            });
+        }
+        Ok(())
+    }
+    .await;
+    let _ = tokio::fs::remove_dir_all(&work_dir).await;
+    result
+}
+
+/// Where to write the export: the chosen file, or a file named after the
+/// subject when a folder was chosen. An existing file is never replaced;
+/// the export gets a numbered name next to it instead.
+pub async fn output_path(chosen: &Path, subject: &str, format: ThreadExportFormat) -> Result<PathBuf> {
+    let path = if tokio::fs::metadata(chosen).await.is_ok_and(|metadata| metadata.is_dir()) {
+        chosen.join(format!("{}.{}", file_stem(subject), format.extension()))
+    } else {
+        chosen.to_path_buf()
+    };
+    let folder = path.parent().filter(|parent| !parent.as_os_str().is_empty());
+    let folder_exists = match folder {
+        Some(folder) => tokio::fs::metadata(folder).await.is_ok_and(|metadata| metadata.is_dir()),
+        None => true,
+    };
+    if !folder_exists {
+        return Err(LibreOllamaError::InvalidInput {
+            message: format!("Folder of {} does not exist", path.display()),
+            field: Some("path".to_string()),
+        });
+    }
+
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    let extension = path.extension().map(|extension| extension.to_string_lossy().into_owned());
+    let mut candidate = path.clone();
+    for number in 2.. {
+        if !tokio::fs::try_exists(&candidate).await? {
+            break;
+        }
+        let name = match &extension {
+            Some(extension) => format!("{} ({}).{}", stem, number, extension),
+            None => format!("{} ({})", stem, number),
+        };
+        candidate = path.with_file_name(name);
+    }
+    Ok(candidate)
+}
+
+/// Render a thread's messages and write them to `path`, reporting each
+/// step to `progress`
+pub async fn export_messages(
+    messages: &[ProcessedGmailMessage],
+    format: ThreadExportFormat,
+    path: &Path,
+    mut progress: impl FnMut(ExportProgress),
+) -> Result<ThreadExport> {
+    if messages.is_empty() {
+        return Err(LibreOllamaError::InvalidInput {
+            message: "The thread has no messages to export".to_string(),
+            field: Some("thread_id".to_string()),
+        });
+    }
+    let total = messages.len();
+    let output = output_path(path, &thread_subject(messages), format).await?;
+    // Fail before rendering when PDFs cannot be printed at all
+    let renderer = match format {
+        ThreadExportFormat::Pdf => {
+            let found = tokio::task::spawn_blocking(find_renderer).await.map_err(|e| LibreOllamaError::Internal {
+                message: format!("Renderer check failed: {}", e),
+            })?;
+            Some(found.ok_or_else(|| LibreOllamaError::NotSupported {
+                operation: "PDF export needs Chrome, Chromium, Edge or Brave installed".to_string(),
+            })?)
+        }
+        ThreadExportFormat::Markdown => None,
+    };
+
+    match renderer {
+        Some(renderer) => {
+            let html = render_html(messages, |done| progress(ExportProgress::new(ExportStage::Rendering, done, total)));
+            progress(ExportProgress::new(ExportStage::Printing, 0, 1));
+            print_pdf(&renderer, &html, &output).await?;
+        }
+        None => {
+            let markdown = render_markdown(messages, |done| progress(ExportProgress::new(ExportStage::Rendering, done, total)));
+            progress(ExportProgress::new(ExportStage::Writing, 0, 1));
+            // Fails rather than replace a file created since the name was chosen
+            let mut file = tokio::fs::OpenOptions::new().write(true).create_new(true).open(&output).await?;
+            file.write_all(markdown.as_bytes()).await?;
+            file.flush().await?;
+        }
+    }
+
+    let bytes = tokio::fs::metadata(&output).await?.len();
+    progress(ExportProgress::new(ExportStage::Done, 1, 1));
+    Ok(ThreadExport {
+        path: output.to_string_lossy().to_string(),
+        format,
+        message_count: total,
+        attachment_count: attachment_count(messages),
+        bytes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitizes_scripts_handlers_and_remote_content() {
+        let html = r#"<html><head><title>x</title><style>p{}</style></head><body>
+            <p onclick="steal()" style="color: red">Hi <a href="javascript:alert(1)">there</a></p>
+            <script>alert(1)</script><iframe src="https://evil.example"></iframe>
+            <img src="https://tracker.example/pixel.gif" alt="pixel"><img src='data:image/png;base64,AAAA'>
+            <div style="background: url(https://tracker.example/bg)">x</div><!-- note -->
+            <a href="https://example.com">link</a><form action="/x"><input name="q"></form>
+            </body></html>"#;
+        let clean = sanitize_html(html);
+        for gone in ["<script", "alert", "onclick", "<iframe", "tracker.example", "<form", "<input", "note", "<title", "p{}"] {
+            assert!(!clean.contains(gone), "{} survived in {}", gone, clean);
+        }
+        assert!(clean.contains(r#"style="color: red""#));
+        assert!(clean.contains("data:image/png;base64,AAAA"));
+        assert!(clean.contains(r#"href="https://example.com""#));
+        assert!(clean.contains(r#"<img alt="pixel">"#));
+    }
+
+    #[test]
+    fn sanitizes_markup_patterns_miss() {
+        let clean = sanitize_html(
+            r#"<p>Hi</p><img/onerror="alert(1)"/src="https://tracker.example/a.gif"><a/href="javascript:alert(2)">x</a>
+            <p style="background: u\72l(https://tracker.example/bg)">y</p><script>alert(3)"#,
+        );
+        for gone in ["onerror", "tracker.example", "javascript", "alert", "<script", "style="] {
+            assert!(!clean.contains(gone), "{} survived in {}", gone, clean);
+        }
+        assert!(clean.starts_with("<p>Hi</p>"));
+    }
+
+    #[tokio::test]
+    async fn never_replaces_existing_files() {
+        let dir = std::env::temp_dir().join(format!("libreollama_thread_export_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let first = output_path(&dir, "Budget", ThreadExportFormat::Markdown).await.unwrap();
+        assert_eq!(first, dir.join("Budget.md"));
+        std::fs::write(&first, "x").unwrap();
+        assert_eq!(output_path(&dir, "Budget", ThreadExportFormat::Markdown).await.unwrap(), dir.join("Budget (2).md"));
+        assert_eq!(output_path(&first, "Budget", ThreadExportFormat::Markdown).await.unwrap(), dir.join("Budget (2).md"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn formats_sizes() {
+        assert_eq!(human_size(512), "512 B");
+        assert_eq!(human_size(1536), "1.5 KB");
+        assert_eq!(human_size(5 * 1024 * 1024), "5.0 MB");
+    }
+}
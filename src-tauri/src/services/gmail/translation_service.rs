@@ -0,0 +1,65 @@
+//! Message translation with the translation model
+//!
+//! Only the new content of a message is translated: quoted history and
+//! signatures are dropped by the reply-chain parser first, which keeps the
+//! prompt short and stops earlier messages being translated again in every
+//! reply. The source language is detected locally before the model is called,
+//! and a message already in the target language is returned unchanged.
+
+use rusqlite::Connection;
+
+use crate::database::operations::preference_operations;
+use crate::database::DatabaseManager;
+use crate::errors::Result;
+use crate::services::llm_provider::{self, ChatMessage, ChatOptions, LlmFeature};
+use crate::services::prompt_templates;
+use crate::services::text_processing::LanguageInfo;
+
+/// user_preferences key naming the local model used for translation; see
+/// `model_router` for how it combines with the model settings
+pub const TRANSLATION_MODEL_PREFERENCE: &str = "gmail.translation_model";
+pub const DEFAULT_TRANSLATION_MODEL: &str = "llama3.1";
+/// Prompt template feature for translations
+const PROMPT_FEATURE: &str = "mail.translate";
+
+/// The configured translation model, or the default
+pub fn translation_model(conn: &Connection) -> anyhow::Result<String> {
+    Ok(preference_operations::get_preference_value(conn, TRANSLATION_MODEL_PREFERENCE)?
+        .filter(|model| !model.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_TRANSLATION_MODEL.to_string()))
+}
+
+pub fn set_translation_model(conn: &Connection, model: &str) -> anyhow::Result<()> {
+    preference_operations::set_preference_value(conn, TRANSLATION_MODEL_PREFERENCE, model.trim(), "gmail")
+}
+
+/// Whether `target_lang` (an ISO 639-3 code or English language name)
+/// names the detected language
+pub fn is_same_language(source: &LanguageInfo, target_lang: &str) -> bool {
+    let target = target_lang.trim().to_lowercase();
+    source.reliable && (source.code == target || source.name.to_lowercase() == target)
+}
+
+/// Translate text with the translation model, or with `model` when given.
+/// Returns the translation and the model that produced it.
+pub async fn translate_text(
+    db_manager: &DatabaseManager,
+    model: Option<&str>,
+    text: &str,
+    target_lang: &str,
+) -> Result<(String, String)> {
+    let prompt = {
+        let mut values = serde_json::Map::new();
+        values.insert("language".to_string(), target_lang.into());
+        prompt_templates::render_feature_prompt(&db_manager.get_connection()?, PROMPT_FEATURE, &values)?
+    };
+    let options = ChatOptions { model: model.map(str::to_string), temperature: Some(0.1), json: false };
+    let completion = llm_provider::complete_with(
+        db_manager,
+        LlmFeature::Translation,
+        &[ChatMessage::system(prompt), ChatMessage::user(text)],
+        &options,
+    )
+    .await?;
+    Ok((completion.content, completion.model))
+}
@@ -0,0 +1,729 @@
+//! Mail triage sessions
+//!
+//! A triage session takes the messages under one label, oldest first, and
+//! walks them one at a time: archive, label, turn into a task, snooze or
+//! skip. Label changes reach the cache before Gmail confirms them, and are
+//! put back if Gmail refuses. Every action is recorded with the labels it
+//! replaced so the latest one can be undone, and the session's stats are
+//! stored when it ends.
+//!
+//! Snoozed messages leave the inbox and come back, unread, when
+//! `SnoozeWaker` finds them due.
+
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::database::operations::cache_delta_operations::{NewCacheDelta, CHANGE_UPDATED};
+use crate::database::operations::triage_operations::{self, NewTriageAction, TriageActionRow, TriageSessionRow};
+use crate::database::operations::backfill_operations;
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::events::EventBus;
+use crate::services::gmail::api_service::{EmailAddress, GmailApiService, MessageSearchQuery, ProcessedGmailMessage};
+use crate::services::gmail::thread_actions::{apply_changes, diff_groups, ThreadLabels};
+use crate::services::task_supervisor::TaskSpec;
+
+pub const DEFAULT_QUEUE_SIZE: u32 = 50;
+pub const MAX_QUEUE_SIZE: u32 = 200;
+
+/// Longest the snooze waker sleeps, so new snoozes are never missed by much
+const MAX_WAKE_WAIT: Duration = Duration::from_secs(5 * 60);
+/// Shortest, so a snooze that cannot be woken never spins the waker
+const MIN_WAKE_WAIT: Duration = Duration::from_secs(5);
+const SNOOZES_PER_TICK: u32 = 50;
+/// A failed wake is retried after a minute, doubling up to six hours
+const WAKE_RETRY_BASE: Duration = Duration::from_secs(60);
+const WAKE_RETRY_MAX: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// How long to wait before waking a snooze again after `attempts` failures
+fn wake_retry_delay(attempts: u32) -> Duration {
+    WAKE_RETRY_BASE.saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1))).min(WAKE_RETRY_MAX)
+}
+
+/// What the queue shows of a message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriageMessage {
+    pub message_id: String,
+    pub thread_id: String,
+    pub from: EmailAddress,
+    pub subject: Option<String>,
+    pub snippet: Option<String>,
+    pub date: Option<String>,
+    /// Milliseconds since the epoch, as Gmail reports it
+    pub internal_date: Option<i64>,
+    pub unread: bool,
+    pub starred: bool,
+    pub has_attachments: bool,
+    pub labels: Vec<String>,
+}
+
+impl From<&ProcessedGmailMessage> for TriageMessage {
+    fn from(message: &ProcessedGmailMessage) -> Self {
+        let has = |label: &str| message.labels.iter().any(|l| l == label);
+        Self {
+            message_id: message.id.clone(),
+            thread_id: message.thread_id.clone(),
+            from: message.parsed_content.from.clone(),
+            subject: message.parsed_content.subject.clone(),
+            snippet: message.snippet.clone(),
+            date: message.parsed_content.date.clone(),
+            internal_date: message.internal_date.as_deref().and_then(|d| d.parse().ok()),
+            unread: has("UNREAD"),
+            starred: has("STARRED"),
+            has_attachments: !message.parsed_content.attachments.is_empty(),
+            labels: message.labels.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TriageAction {
+    Archive,
+    Label {
+        #[serde(default)]
+        add: Vec<String>,
+        #[serde(default)]
+        remove: Vec<String>,
+        /// Also take the message out of the inbox
+        #[serde(default)]
+        archive: bool,
+    },
+    /// Make a task from the message; title defaults to the subject
+    Task {
+        task_list_id: Option<String>,
+        title: Option<String>,
+        due: Option<String>,
+        #[serde(default = "default_true")]
+        archive: bool,
+    },
+    /// Out of the inbox until `until` (RFC 3339)
+    Snooze { until: String },
+    /// Leave the message as it is and move on
+    Skip,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl TriageAction {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TriageAction::Archive => "archive",
+            TriageAction::Label { .. } => "label",
+            TriageAction::Task { .. } => "task",
+            TriageAction::Snooze { .. } => "snooze",
+            TriageAction::Skip => "skip",
+        }
+    }
+
+    /// Labels to add to and remove from the message
+    pub fn label_changes(&self) -> (Vec<String>, Vec<String>) {
+        let inbox = |archive: bool| if archive { vec!["INBOX".to_string()] } else { Vec::new() };
+        match self {
+            TriageAction::Archive | TriageAction::Snooze { .. } => (Vec::new(), inbox(true)),
+            TriageAction::Label { add, remove, archive } => {
+                let mut remove = remove.clone();
+                if *archive && !remove.iter().any(|label| label == "INBOX") {
+                    remove.push("INBOX".to_string());
+                }
+                (add.clone(), remove)
+            }
+            TriageAction::Task { archive, .. } => (Vec::new(), inbox(*archive)),
+            TriageAction::Skip => (Vec::new(), Vec::new()),
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        let invalid = |message: &str| LibreOllamaError::InvalidInput {
+            message: message.to_string(),
+            field: Some("action".to_string()),
+        };
+        match self {
+            TriageAction::Label { add, remove, archive } => {
+                if add.is_empty() && remove.is_empty() && !archive {
+                    return Err(invalid("A label action needs a label to add or remove"));
+                }
+                if add.iter().any(|label| remove.contains(label)) {
+                    return Err(invalid("A label cannot be added and removed at once"));
+                }
+            }
+            TriageAction::Snooze { until } if snooze_until(until)? <= Utc::now() => {
+                return Err(invalid("A message can only be snoozed until a time in the future"));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn snooze_until(until: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(until)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|e| LibreOllamaError::InvalidInput {
+            message: format!("Invalid snooze time: {}", e),
+            field: Some("until".to_string()),
+        })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriageSession {
+    pub id: i64,
+    pub account_id: String,
+    pub label_id: String,
+    pub queue: Vec<TriageMessage>,
+    pub started_at: String,
+}
+
+/// The result of an action or an undo, with where the queue stands now
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriageOutcome {
+    pub action_id: i64,
+    pub message_id: String,
+    pub action: TriageAction,
+    /// The message's labels after the action, or after undoing it
+    pub labels: Vec<String>,
+    pub task_id: Option<String>,
+    /// Messages in the queue not yet handled
+    pub remaining: usize,
+    /// The next unhandled message, in queue order
+    pub next: Option<TriageMessage>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TriageStats {
+    pub queued: usize,
+    pub processed: usize,
+    pub archived: usize,
+    pub labelled: usize,
+    pub tasks_created: usize,
+    pub snoozed: usize,
+    pub skipped: usize,
+    pub undone: usize,
+    pub remaining: usize,
+    pub duration_seconds: i64,
+    /// Every queued message left the label
+    pub inbox_zero: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriageSessionSummary {
+    pub id: i64,
+    pub label_id: String,
+    pub started_at: String,
+    pub ended_at: Option<String>,
+    pub stats: Option<TriageStats>,
+}
+
+/// The latest action in effect for each message
+fn current_actions(actions: &[TriageActionRow]) -> HashMap<&str, &TriageActionRow> {
+    actions
+        .iter()
+        .filter(|action| action.undone_at.is_none())
+        .map(|action| (action.message_id.as_str(), action))
+        .collect()
+}
+
+fn parse_timestamp(value: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").ok()
+}
+
+/// Stats for a session from the actions taken in it
+pub fn compute_stats(
+    queue: &[TriageMessage],
+    label_id: &str,
+    actions: &[TriageActionRow],
+    started_at: &str,
+    ended_at: &str,
+) -> TriageStats {
+    let current = current_actions(actions);
+    let mut stats = TriageStats {
+        queued: queue.len(),
+        undone: actions.iter().filter(|action| action.undone_at.is_some()).count(),
+        ..Default::default()
+    };
+    let mut left_label = 0;
+    for message in queue {
+        let Some(action) = current.get(message.message_id.as_str()) else {
+            stats.remaining += 1;
+            continue;
+        };
+        match action.kind.as_str() {
+            "archive" => stats.archived += 1,
+            "label" => stats.labelled += 1,
+            "task" => stats.tasks_created += 1,
+            "snooze" => stats.snoozed += 1,
+            _ => stats.skipped += 1,
+        }
+        if action.kind != "skip" {
+            stats.processed += 1;
+        }
+        if !action.labels_after.iter().any(|label| label == label_id) {
+            left_label += 1;
+        }
+    }
+    stats.inbox_zero = !queue.is_empty() && left_label == queue.len();
+    stats.duration_seconds = match (parse_timestamp(started_at), parse_timestamp(ended_at)) {
+        (Some(start), Some(end)) => (end - start).num_seconds().max(0),
+        _ => 0,
+    };
+    stats
+}
+
+fn session_queue(session: &TriageSessionRow) -> Result<Vec<TriageMessage>> {
+    serde_json::from_str(&session.queue_json).map_err(|e| LibreOllamaError::Serialization {
+        message: format!("Failed to read triage queue: {}", e),
+        data_type: "triage_queue".to_string(),
+    })
+}
+
+/// A session that is still running, with its queue
+fn open_session(db_manager: &DatabaseManager, session_id: i64) -> Result<(TriageSessionRow, Vec<TriageMessage>)> {
+    let conn = db_manager.get_connection()?;
+    let session = triage_operations::get_session(&conn, session_id)?
+        .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("Triage session {}", session_id) })?;
+    if session.ended_at.is_some() {
+        return Err(LibreOllamaError::InvalidInput {
+            message: format!("Triage session {} has already ended", session_id),
+            field: Some("session_id".to_string()),
+        });
+    }
+    let queue = session_queue(&session)?;
+    Ok((session, queue))
+}
+
+/// Unhandled messages left and the first of them
+fn progress(db_manager: &DatabaseManager, session_id: i64, queue: &[TriageMessage]) -> Result<(usize, Option<TriageMessage>)> {
+    let actions = {
+        let conn = db_manager.get_connection()?;
+        triage_operations::list_actions(&conn, session_id)?
+    };
+    let handled: HashSet<&str> = current_actions(&actions).into_keys().collect();
+    let pending: Vec<&TriageMessage> = queue.iter().filter(|message| !handled.contains(message.message_id.as_str())).collect();
+    Ok((pending.len(), pending.first().map(|message| (*message).clone())))
+}
+
+/// Start a session over the messages under a label, oldest first
+pub async fn start_session(
+    api_service: &GmailApiService,
+    db_manager: &DatabaseManager,
+    account_id: &str,
+    label_id: &str,
+    limit: Option<u32>,
+) -> Result<TriageSession> {
+    let query = MessageSearchQuery {
+        query: None,
+        label_ids: Some(vec![label_id.to_string()]),
+        max_results: Some(limit.unwrap_or(DEFAULT_QUEUE_SIZE).clamp(1, MAX_QUEUE_SIZE)),
+        page_token: None,
+        include_spam_trash: Some(false),
+    };
+    let found = api_service.search_messages(account_id, &query).await?;
+    let mut queue: Vec<TriageMessage> = found.messages.iter().map(TriageMessage::from).collect();
+    queue.sort_by_key(|message| message.internal_date.unwrap_or(i64::MAX));
+
+    let conn = db_manager.get_connection()?;
+    let session = triage_operations::create_session(&conn, account_id, label_id, &serde_json::to_string(&queue)?)?;
+    println!("✅ [TRIAGE] Started session {} over {} messages in {}", session.id, queue.len(), label_id);
+    Ok(TriageSession { id: session.id, account_id: session.account_id, label_id: session.label_id, queue, started_at: session.started_at })
+}
+
+/// The session's account and the queued message, for callers that need
+/// them before acting, e.g. to create a task from it
+pub fn queued_message(db_manager: &DatabaseManager, session_id: i64, message_id: &str) -> Result<(String, TriageMessage)> {
+    let (session, queue) = open_session(db_manager, session_id)?;
+    let message = queue
+        .into_iter()
+        .find(|message| message.message_id == message_id)
+        .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("Message {} in triage session {}", message_id, session_id) })?;
+    Ok((session.account_id, message))
+}
+
+fn label_delta(account_id: &str, message_id: &str, labels: &[String], data: serde_json::Value) -> NewCacheDelta {
+    NewCacheDelta::message(account_id, message_id, CHANGE_UPDATED, labels).with_data(data)
+}
+
+/// Apply an action to a queued message. `task` is the task already made
+/// from it for a task action, as (task list, task ID).
+pub async fn process_action(
+    api_service: &GmailApiService,
+    db_manager: &DatabaseManager,
+    event_bus: &EventBus,
+    session_id: i64,
+    message_id: &str,
+    action: &TriageAction,
+    task: Option<(&str, &str)>,
+) -> Result<TriageOutcome> {
+    action.validate()?;
+    let (session, queue) = open_session(db_manager, session_id)?;
+    let message = queue
+        .iter()
+        .find(|message| message.message_id == message_id)
+        .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("Message {} in triage session {}", message_id, session_id) })?;
+    let account_id = session.account_id.as_str();
+
+    // Labels as this session last left them, which may differ from the queue
+    let before = {
+        let conn = db_manager.get_connection()?;
+        let actions = triage_operations::list_actions(&conn, session_id)?;
+        current_actions(&actions)
+            .get(message_id)
+            .map(|action| action.labels_after.clone())
+            .unwrap_or_else(|| message.labels.clone())
+    };
+    let (add, remove) = action.label_changes();
+    let after = apply_changes(&before, &add, &remove);
+    let changed = after != before;
+
+    let snooze_id = match action {
+        TriageAction::Snooze { until } => {
+            let conn = db_manager.get_connection()?;
+            Some(triage_operations::snooze_message(&conn, account_id, message_id, &message.thread_id, snooze_until(until)?.timestamp())?)
+        }
+        _ => None,
+    };
+
+    if changed {
+        // The cache moves first; Gmail catches up, or the cache is put back
+        event_bus.cache_changed(&[label_delta(account_id, message_id, &after, serde_json::json!({ "triage_session": session_id, "optimistic": true }))]);
+        if let Err(e) = api_service.modify_messages(account_id, vec![message_id.to_string()], add, remove).await {
+            event_bus.cache_changed(&[label_delta(account_id, message_id, &before, serde_json::json!({ "triage_session": session_id, "reverted": true }))]);
+            if let Some(snooze_id) = snooze_id {
+                let conn = db_manager.get_connection()?;
+                triage_operations::cancel_snooze(&conn, snooze_id)?;
+            }
+            return Err(e);
+        }
+    }
+
+    let recorded = {
+        let conn = db_manager.get_connection()?;
+        if changed {
+            if let Err(e) = backfill_operations::set_message_labels(&conn, account_id, message_id, &after) {
+                eprintln!("⚠️ [TRIAGE] Failed to store labels of {}: {}", message_id, e);
+            }
+        }
+        triage_operations::record_action(&conn, &NewTriageAction {
+            session_id,
+            message_id,
+            kind: action.kind(),
+            action_json: &serde_json::to_string(action)?,
+            labels_before: &before,
+            labels_after: &after,
+            task_id: task.map(|(_, id)| id),
+            task_list_id: task.map(|(list, _)| list),
+            snooze_id,
+        })?
+    };
+
+    let (remaining, next) = progress(db_manager, session_id, &queue)?;
+    Ok(TriageOutcome {
+        action_id: recorded.id,
+        message_id: message_id.to_string(),
+        action: action.clone(),
+        labels: after,
+        task_id: recorded.task_id,
+        remaining,
+        next,
+    })
+}
+
+/// Undo the latest action of a session still in effect.
+///
+/// Only the labels that action changed are put back, so other changes
+/// made to the message since survive. A task the action made is removed
+/// through `remove_task(account_id, task_list_id, task_id)`; the action only counts as
+/// undone once that succeeds, so a failed removal can be retried.
+pub async fn undo_last<F, Fut>(
+    api_service: &GmailApiService,
+    db_manager: &DatabaseManager,
+    event_bus: &EventBus,
+    session_id: i64,
+    remove_task: F,
+) -> Result<Option<TriageOutcome>>
+where
+    F: FnOnce(String, String, String) -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    let (session, queue) = open_session(db_manager, session_id)?;
+    let account_id = session.account_id.as_str();
+    let Some(last) = ({
+        let conn = db_manager.get_connection()?;
+        triage_operations::last_action(&conn, session_id)?
+    }) else {
+        return Ok(None);
+    };
+
+    let from: ThreadLabels = [(last.message_id.clone(), last.labels_after.clone())].into();
+    let to: ThreadLabels = [(last.message_id.clone(), last.labels_before.clone())].into();
+    let groups = diff_groups(&from, &to);
+    if !groups.is_empty() {
+        event_bus.cache_changed(&[label_delta(account_id, &last.message_id, &last.labels_before, serde_json::json!({ "triage_session": session_id, "optimistic": true }))]);
+    }
+    for group in groups {
+        if let Err(e) = api_service.modify_messages(account_id, group.message_ids, group.add, group.remove).await {
+            event_bus.cache_changed(&[label_delta(account_id, &last.message_id, &last.labels_after, serde_json::json!({ "triage_session": session_id, "reverted": true }))]);
+            return Err(e);
+        }
+    }
+
+    {
+        let conn = db_manager.get_connection()?;
+        if let Some(snooze_id) = last.snooze_id {
+            triage_operations::cancel_snooze(&conn, snooze_id)?;
+        }
+        if let Err(e) = backfill_operations::set_message_labels(&conn, account_id, &last.message_id, &last.labels_before) {
+            eprintln!("⚠️ [TRIAGE] Failed to store labels of {}: {}", last.message_id, e);
+        }
+    }
+
+    // Putting the labels back again is harmless, so a retry after a failed
+    // removal starts over
+    if let (Some(task_list_id), Some(task_id)) = (last.task_list_id.clone(), last.task_id.clone()) {
+        remove_task(account_id.to_string(), task_list_id, task_id).await?;
+    }
+    {
+        let conn = db_manager.get_connection()?;
+        triage_operations::mark_undone(&conn, last.id)?;
+    }
+
+    let action: TriageAction = serde_json::from_str(&last.action_json)?;
+    let (remaining, next) = progress(db_manager, session_id, &queue)?;
+    Ok(Some(TriageOutcome {
+        action_id: last.id,
+        message_id: last.message_id,
+        action,
+        labels: last.labels_before,
+        task_id: last.task_id,
+        remaining,
+        next,
+    }))
+}
+
+/// End a session and store its stats
+pub fn end_session(db_manager: &DatabaseManager, session_id: i64) -> Result<TriageStats> {
+    let (session, queue) = open_session(db_manager, session_id)?;
+    let conn = db_manager.get_connection()?;
+    let actions = triage_operations::list_actions(&conn, session_id)?;
+    let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let stats = compute_stats(&queue, &session.label_id, &actions, &session.started_at, &now);
+    triage_operations::end_session(&conn, session_id, &serde_json::to_string(&stats)?)?;
+    println!(
+        "✅ [TRIAGE] Session {} ended: {} of {} processed{}",
+        session_id,
+        stats.processed,
+        stats.queued,
+        if stats.inbox_zero { ", inbox zero" } else { "" }
+    );
+    Ok(stats)
+}
+
+/// Finished sessions of an account, newest first
+pub fn session_history(db_manager: &DatabaseManager, account_id: &str, limit: u32) -> Result<Vec<TriageSessionSummary>> {
+    let conn = db_manager.get_connection()?;
+    Ok(triage_operations::list_ended_sessions(&conn, account_id, limit)?
+        .into_iter()
+        .map(|session| TriageSessionSummary {
+            stats: session.stats_json.as_deref().and_then(|json| serde_json::from_str(json).ok()),
+            id: session.id,
+            label_id: session.label_id,
+            started_at: session.started_at,
+            ended_at: session.ended_at,
+        })
+        .collect())
+}
+
+/// Puts snoozed messages back in the inbox, unread, when they are due
+#[derive(Clone)]
+pub struct SnoozeWaker {
+    db_manager: Arc<DatabaseManager>,
+    app: AppHandle,
+}
+
+impl SnoozeWaker {
+    pub fn new(db_manager: Arc<DatabaseManager>, app: AppHandle) -> Self {
+        Self { db_manager, app }
+    }
+
+    pub fn task(&self) -> TaskSpec {
+        let waker = self.clone();
+        let waiting = self.clone();
+        TaskSpec::new("snoozed_mail", move || {
+            let waker = waker.clone();
+            async move { Ok(waker.wake_due().await?) }
+        })
+        .every(move || waiting.until_due().unwrap_or(MAX_WAKE_WAIT))
+        .first_run_after(Duration::ZERO)
+    }
+
+    fn until_due(&self) -> Result<Duration> {
+        let conn = self.db_manager.get_connection()?;
+        Ok(match triage_operations::next_wake_at(&conn)? {
+            Some(wake_at) => Duration::from_secs((wake_at - Utc::now().timestamp()).max(0) as u64).clamp(MIN_WAKE_WAIT, MAX_WAKE_WAIT),
+            None => MAX_WAKE_WAIT,
+        })
+    }
+
+    async fn wake_due(&self) -> Result<()> {
+        let due = {
+            let conn = self.db_manager.get_connection()?;
+            let orphaned = triage_operations::cancel_snoozes_without_account(&conn)?;
+            if orphaned > 0 {
+                println!("🗑️ [TRIAGE] Dropped {} snoozes of removed accounts", orphaned);
+            }
+            triage_operations::due_snoozes(&conn, Utc::now().timestamp(), SNOOZES_PER_TICK)?
+        };
+        if due.is_empty() {
+            return Ok(());
+        }
+        let Some(api_service) = self.app.try_state::<Arc<GmailApiService>>() else {
+            return Ok(());
+        };
+        let event_bus = self.app.try_state::<EventBus>();
+        let back = vec!["INBOX".to_string(), "UNREAD".to_string()];
+
+        for snooze in due {
+            if let Err(e) = api_service
+                .modify_messages(&snooze.account_id, vec![snooze.message_id.clone()], back.clone(), Vec::new())
+                .await
+            {
+                let conn = self.db_manager.get_connection()?;
+                if is_gone(&e) {
+                    // Deleted from Gmail since it was snoozed
+                    eprintln!("⚠️ [TRIAGE] Snoozed message {} no longer exists: {}", snooze.message_id, e);
+                    triage_operations::cancel_snooze(&conn, snooze.id)?;
+                } else {
+                    let delay = wake_retry_delay(snooze.attempts + 1);
+                    eprintln!("⚠️ [TRIAGE] Failed to unsnooze {}, retrying in {:?}: {}", snooze.message_id, delay, e);
+                    triage_operations::record_wake_failure(&conn, snooze.id, Utc::now().timestamp() + delay.as_secs() as i64)?;
+                }
+                continue;
+            }
+            let conn = self.db_manager.get_connection()?;
+            triage_operations::mark_woken(&conn, snooze.id)?;
+            println!("✅ [TRIAGE] Snoozed message {} is back in the inbox", snooze.message_id);
+
+            let labels = match api_service.get_thread_labels(&snooze.account_id, &snooze.thread_id).await {
+                Ok(messages) => messages.into_iter().find(|(id, _)| *id == snooze.message_id).map(|(_, labels)| labels),
+                Err(e) => {
+                    eprintln!("⚠️ [TRIAGE] Failed to load labels of {}: {}", snooze.message_id, e);
+                    None
+                }
+            };
+            if let Some(labels) = labels {
+                if let Err(e) = backfill_operations::set_message_labels(&conn, &snooze.account_id, &snooze.message_id, &labels) {
+                    eprintln!("⚠️ [TRIAGE] Failed to store labels of {}: {}", snooze.message_id, e);
+                }
+                if let Some(event_bus) = &event_bus {
+                    event_bus.cache_changed(&[label_delta(&snooze.account_id, &snooze.message_id, &labels, serde_json::json!({ "unsnoozed": true }))]);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether Gmail says the message is not there
+fn is_gone(error: &LibreOllamaError) -> bool {
+    matches!(error, LibreOllamaError::NotFound { .. } | LibreOllamaError::GmailApi { status_code: Some(404), .. })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    fn message(id: &str) -> TriageMessage {
+        TriageMessage {
+            message_id: id.to_string(),
+            thread_id: format!("t-{}", id),
+            from: EmailAddress { email: "ada@example.com".to_string(), name: None },
+            subject: None,
+            snippet: None,
+            date: None,
+            internal_date: None,
+            unread: true,
+            starred: false,
+            has_attachments: false,
+            labels: labels(&["INBOX", "UNREAD"]),
+        }
+    }
+
+    fn action(id: i64, message_id: &str, kind: &str, after: &[&str], undone: bool) -> TriageActionRow {
+        TriageActionRow {
+            id,
+            session_id: 1,
+            message_id: message_id.to_string(),
+            kind: kind.to_string(),
+            action_json: "{}".to_string(),
+            labels_before: labels(&["INBOX", "UNREAD"]),
+            labels_after: labels(after),
+            task_id: None,
+            task_list_id: None,
+            snooze_id: None,
+            created_at: "2026-10-16 09:00:00".to_string(),
+            undone_at: undone.then(|| "2026-10-16 09:01:00".to_string()),
+        }
+    }
+
+    #[test]
+    fn wake_retries_back_off() {
+        assert_eq!(wake_retry_delay(1), Duration::from_secs(60));
+        assert_eq!(wake_retry_delay(3), Duration::from_secs(240));
+        assert_eq!(wake_retry_delay(40), WAKE_RETRY_MAX);
+    }
+
+    #[test]
+    fn label_changes_follow_the_action() {
+        let label = TriageAction::Label { add: labels(&["Label_1"]), remove: Vec::new(), archive: true };
+        assert_eq!(label.label_changes(), (labels(&["Label_1"]), labels(&["INBOX"])));
+        let task: TriageAction = serde_json::from_str(r#"{"type":"task"}"#).unwrap();
+        assert_eq!(task.label_changes(), (Vec::new(), labels(&["INBOX"])));
+        assert_eq!(TriageAction::Skip.label_changes(), (Vec::new(), Vec::new()));
+        assert!(TriageAction::Snooze { until: "2020-01-01T00:00:00Z".to_string() }.validate().is_err());
+    }
+
+    #[test]
+    fn stats_count_actions_still_in_effect() {
+        let queue = vec![message("m1"), message("m2"), message("m3")];
+        let actions = vec![
+            action(1, "m1", "archive", &["UNREAD"], false),
+            action(2, "m2", "task", &["UNREAD"], true),
+            action(3, "m2", "skip", &["INBOX", "UNREAD"], false),
+        ];
+        let stats = compute_stats(&queue, "INBOX", &actions, "2026-10-16 09:00:00", "2026-10-16 09:05:30");
+        assert_eq!(
+            stats,
+            TriageStats {
+                queued: 3,
+                processed: 1,
+                archived: 1,
+                skipped: 1,
+                undone: 1,
+                remaining: 1,
+                duration_seconds: 330,
+                inbox_zero: false,
+                ..Default::default()
+            }
+        );
+
+        let actions = vec![
+            action(1, "m1", "archive", &["UNREAD"], false),
+            action(2, "m2", "snooze", &["UNREAD"], false),
+            action(3, "m3", "label", &["Label_1"], false),
+        ];
+        let stats = compute_stats(&queue, "INBOX", &actions, "2026-10-16 09:00:00", "2026-10-16 09:01:00");
+        assert!(stats.inbox_zero);
+        assert_eq!((stats.processed, stats.remaining), (3, 0));
+    }
+}
@@ -0,0 +1,112 @@
+//! Google batch calls
+//!
+//! Gmail and Tasks accept up to a few dozen requests in one
+//! `multipart/mixed` POST. Each part's Content-ID is its index so the
+//! responses, which may come back in any order, can be matched up.
+
+use serde_json::Value;
+
+/// One request inside a batch call
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchPart {
+    pub method: &'static str,
+    /// Full API path, e.g. `/gmail/v1/users/me/labels/INBOX`
+    pub path: String,
+    pub body: Option<Value>,
+}
+
+impl BatchPart {
+    pub fn get(path: String) -> Self {
+        Self { method: "GET", path, body: None }
+    }
+
+    pub fn new(method: &'static str, path: String, body: Option<Value>) -> Self {
+        Self { method, path, body }
+    }
+}
+
+/// Multipart body of a batch call
+pub fn build_batch_body(boundary: &str, parts: &[BatchPart]) -> String {
+    let mut body = String::new();
+    for (index, part) in parts.iter().enumerate() {
+        body.push_str(&format!(
+            "--{}\r\nContent-Type: application/http\r\nContent-ID: <item-{}>\r\n\r\n{} {}\r\n",
+            boundary, index, part.method, part.path
+        ));
+        match &part.body {
+            Some(json) => body.push_str(&format!("Content-Type: application/json\r\n\r\n{}\r\n\r\n", json)),
+            None => body.push_str("\r\n"),
+        }
+    }
+    body.push_str(&format!("--{}--\r\n", boundary));
+    body
+}
+
+/// One response inside a batch call
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchItem {
+    pub index: usize,
+    pub status_code: u16,
+    pub body: String,
+}
+
+/// Split a multipart/mixed batch response into its items. The boundary
+/// comes from the Content-Type header, or the first line of the body if
+/// the header is missing.
+pub fn parse_batch_response(content_type: &str, body: &str) -> Vec<BatchItem> {
+    let boundary = content_type
+        .split(';')
+        .find_map(|param| param.trim().strip_prefix("boundary="))
+        .map(|b| b.trim_matches('"').to_string())
+        .or_else(|| body.trim_start().lines().next().and_then(|line| line.trim().strip_prefix("--")).map(str::to_string));
+    let Some(boundary) = boundary else {
+        return Vec::new();
+    };
+
+    body.replace("\r\n", "\n")
+        .split(&format!("--{}", boundary))
+        .filter_map(|part| {
+            let (part_headers, response) = part.split_once("\n\n")?;
+            let index = part_headers.lines().find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                if !name.trim().eq_ignore_ascii_case("content-id") {
+                    return None;
+                }
+                value.trim().trim_matches(|c| c == '<' || c == '>').rsplit('-').next()?.parse().ok()
+            })?;
+            let status_code = response.lines().next()?.split_whitespace().nth(1)?.parse().ok()?;
+            let body = response.split_once("\n\n").map(|(_, body)| body.trim()).unwrap_or("");
+            Some(BatchItem { index, status_code, body: body.to_string() })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_and_parses_batch_calls() {
+        let body = build_batch_body("b1", &[
+            BatchPart::get("/gmail/v1/users/me/labels/INBOX".to_string()),
+            BatchPart::new("PATCH", "/tasks/v1/lists/l/tasks/t".to_string(), Some(serde_json::json!({ "status": "completed" }))),
+        ]);
+        assert!(body.starts_with("--b1\r\nContent-Type: application/http\r\nContent-ID: <item-0>\r\n\r\nGET /gmail/v1/users/me/labels/INBOX\r\n\r\n"));
+        assert!(body.contains("Content-ID: <item-1>\r\n\r\nPATCH /tasks/v1/lists/l/tasks/t\r\nContent-Type: application/json\r\n\r\n{\"status\":\"completed\"}\r\n"));
+        assert!(body.ends_with("--b1--\r\n"));
+
+        let response = "--batch_x\r\nContent-Type: application/http\r\nContent-ID: <response-item-1>\r\n\r\n\
+            HTTP/1.1 404 Not Found\r\nContent-Type: application/json\r\n\r\n{\"error\": {}}\r\n\
+            --batch_x\r\nContent-Type: application/http\r\nContent-ID: <response-item-0>\r\n\r\n\
+            HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n{\"id\": \"INBOX\", \"messagesUnread\": 4}\r\n\
+            --batch_x--\r\n";
+        let items = parse_batch_response("multipart/mixed; boundary=batch_x", response);
+
+        assert_eq!(items.len(), 2);
+        assert_eq!((items[0].index, items[0].status_code), (1, 404));
+        assert_eq!((items[1].index, items[1].status_code), (0, 200));
+        assert_eq!(items[1].body, "{\"id\": \"INBOX\", \"messagesUnread\": 4}");
+        // Same result when the header is missing
+        assert_eq!(parse_batch_response("", response), items);
+    }
+}
@@ -0,0 +1,148 @@
+//! Google Calendar events for replaying offline edits
+//!
+//! Events are handled as raw JSON objects so a queued edit can carry any
+//! field the Calendar UI changes. Updates send only the edited fields and
+//! are conditional on the event's etag, so an edit made elsewhere between
+//! reading and writing the event is refused instead of overwritten.
+
+use crate::commands::rate_limiter::RequestPriority;
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::gmail::auth_service::GmailAuthService;
+use crate::services::google::GoogleEndpoints;
+use crate::services::request_scheduler;
+use crate::utils::http_client::http_client;
+use reqwest::{Client, Method, StatusCode};
+use serde_json::{Map, Value};
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub struct GoogleCalendarService {
+    client: Client,
+    auth_service: Arc<GmailAuthService>,
+    db_manager: Arc<DatabaseManager>,
+    endpoints: GoogleEndpoints,
+}
+
+impl GoogleCalendarService {
+    pub fn new(auth_service: Arc<GmailAuthService>, db_manager: Arc<DatabaseManager>) -> Self {
+        Self {
+            client: http_client(),
+            auth_service,
+            db_manager,
+            endpoints: GoogleEndpoints::default(),
+        }
+    }
+
+    /// Send requests to `endpoints` instead of Google's production hosts
+    pub fn with_endpoints(mut self, endpoints: GoogleEndpoints) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
+    fn event_url(&self, calendar_id: &str, event_id: Option<&str>) -> String {
+        let mut endpoint = format!("calendars/{}/events", urlencoding::encode(calendar_id));
+        if let Some(event_id) = event_id {
+            endpoint.push_str(&format!("/{}", urlencoding::encode(event_id)));
+        }
+        GoogleEndpoints::url(&self.endpoints.calendar_api, &endpoint)
+    }
+
+    async fn send(
+        &self,
+        account_id: &str,
+        method: Method,
+        url: &str,
+        body: Option<&Map<String, Value>>,
+        if_match: Option<&str>,
+    ) -> Result<reqwest::Response> {
+        let tokens = self
+            .auth_service
+            .validate_and_refresh_tokens(&self.db_manager, account_id)
+            .await?;
+
+        let mut request = self.client.request(method, url).bearer_auth(tokens.access_token);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        if let Some(etag) = if_match {
+            request = request.header(reqwest::header::IF_MATCH, etag);
+        }
+
+        request_scheduler::send(Some(account_id), RequestPriority::Medium, request)
+            .await
+            .map_err(|e| LibreOllamaError::Network {
+                message: format!("Google Calendar API request failed: {}", e),
+                url: Some(url.to_string()),
+            })
+    }
+
+    async fn check_status(response: reqwest::Response) -> Result<reqwest::Response> {
+        if response.status().is_success() {
+            return Ok(response);
+        }
+        let status = response.status().as_u16();
+        let error_text = response.text().await.unwrap_or_default();
+        Err(LibreOllamaError::GoogleCalendarApi {
+            message: format!("Google Calendar API error: {}", error_text),
+            status_code: Some(status),
+        })
+    }
+
+    async fn parse_event(response: reqwest::Response) -> Result<Value> {
+        Self::check_status(response)
+            .await?
+            .json()
+            .await
+            .map_err(|e| LibreOllamaError::Serialization {
+                message: format!("Failed to parse calendar event: {}", e),
+                data_type: "Calendar event".to_string(),
+            })
+    }
+
+    /// The event as Google has it now; None once it has been deleted
+    pub async fn get_event(&self, account_id: &str, calendar_id: &str, event_id: &str) -> Result<Option<Value>> {
+        let response = self
+            .send(account_id, Method::GET, &self.event_url(calendar_id, Some(event_id)), None, None)
+            .await?;
+        if matches!(response.status(), StatusCode::NOT_FOUND | StatusCode::GONE) {
+            return Ok(None);
+        }
+        let event = Self::parse_event(response).await?;
+        Ok((event["status"].as_str() != Some("cancelled")).then_some(event))
+    }
+
+    pub async fn insert_event(&self, account_id: &str, calendar_id: &str, event: &Map<String, Value>) -> Result<Value> {
+        let response = self
+            .send(account_id, Method::POST, &self.event_url(calendar_id, None), Some(event), None)
+            .await?;
+        Self::parse_event(response).await
+    }
+
+    /// Write `fields` onto the event, only if it is still at `etag` when given
+    pub async fn patch_event(
+        &self,
+        account_id: &str,
+        calendar_id: &str,
+        event_id: &str,
+        fields: &Map<String, Value>,
+        etag: Option<&str>,
+    ) -> Result<Value> {
+        let response = self
+            .send(account_id, Method::PATCH, &self.event_url(calendar_id, Some(event_id)), Some(fields), etag)
+            .await?;
+        Self::parse_event(response).await
+    }
+
+    /// Delete the event; one that is already gone counts as deleted
+    pub async fn delete_event(&self, account_id: &str, calendar_id: &str, event_id: &str) -> Result<()> {
+        let response = self
+            .send(account_id, Method::DELETE, &self.event_url(calendar_id, Some(event_id)), None, None)
+            .await?;
+        if matches!(response.status(), StatusCode::NOT_FOUND | StatusCode::GONE) {
+            return Ok(());
+        }
+        Self::check_status(response).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,493 @@
+//! Google Drive file browsing and Drive-backed mail attachments
+//!
+//! Listing and searching only need the `drive.metadata.readonly` scope that
+//! every account already grants. Downloading file contents to attach them to
+//! a message needs `drive.readonly`, which is requested on demand through an
+//! incremental authorization; until then files are attached as links.
+//! Uploading large attachments in the other direction needs `drive.file`,
+//! requested the same way. Files are only downloaded up to the mail
+//! attachment limit; larger ones have to go as links.
+
+use crate::commands::rate_limiter::RequestPriority;
+use crate::config::get_config_manager;
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::gmail::auth_service::GmailAuthService;
+use crate::services::gmail::compose_service::ComposeAttachment;
+use crate::services::request_scheduler;
+use base64::{engine::general_purpose, Engine as _};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::utils::http_client::http_client;
+
+const GOOGLE_DRIVE_API_BASE: &str = "https://www.googleapis.com/drive/v3";
+const GOOGLE_DRIVE_UPLOAD_BASE: &str = "https://www.googleapis.com/upload/drive/v3";
+
+/// Resumable upload chunk size; Drive requires a multiple of 256 KiB
+const UPLOAD_CHUNK_SIZE: usize = 8 * 256 * 1024;
+
+const DRIVE_FILE_FIELDS: &str =
+    "id,name,mimeType,size,modifiedTime,iconLink,thumbnailLink,webViewLink,parents,owners(displayName,emailAddress)";
+
+/// Google Docs/Sheets/Slides have no binary content and must be exported
+const GOOGLE_APPS_MIME_PREFIX: &str = "application/vnd.google-apps.";
+const FOLDER_MIME_TYPE: &str = "application/vnd.google-apps.folder";
+
+/// Largest attachment a message may carry, from the Gmail settings
+pub fn attachment_limit_bytes() -> u64 {
+    get_config_manager()
+        .map(|config| config.gmail().attachment_max_size_mb)
+        .unwrap_or(25)
+        * 1024
+        * 1024
+}
+
+fn too_large(name: &str, max_bytes: u64) -> LibreOllamaError {
+    LibreOllamaError::InvalidInput {
+        message: format!(
+            "{} is larger than the {} MB attachment limit; attach it as a link instead",
+            name,
+            max_bytes / (1024 * 1024)
+        ),
+        field: Some("file_id".to_string()),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriveFileOwner {
+    pub display_name: Option<String>,
+    pub email_address: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriveFile {
+    pub id: String,
+    pub name: String,
+    pub mime_type: String,
+    pub size: Option<String>,
+    pub modified_time: Option<String>,
+    pub icon_link: Option<String>,
+    pub thumbnail_link: Option<String>,
+    pub web_view_link: Option<String>,
+    pub parents: Option<Vec<String>>,
+    pub owners: Option<Vec<DriveFileOwner>>,
+}
+
+impl DriveFile {
+    pub fn is_folder(&self) -> bool {
+        self.mime_type == FOLDER_MIME_TYPE
+    }
+
+    /// Size in bytes; Google Docs formats have none
+    pub fn size_bytes(&self) -> Option<u64> {
+        self.size.as_deref().and_then(|size| size.parse().ok())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriveFilePage {
+    pub files: Vec<DriveFile>,
+    pub next_page_token: Option<String>,
+}
+
+/// A Drive file prepared for a message: either a shareable link to insert
+/// into the body or downloaded content to attach
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DriveAttachment {
+    Link {
+        file_id: String,
+        name: String,
+        mime_type: String,
+        url: String,
+    },
+    File {
+        file_id: String,
+        attachment: ComposeAttachment,
+    },
+}
+
+/// Build a Drive `q` expression for a name/full-text search within an optional folder
+pub fn build_search_query(text: Option<&str>, folder_id: Option<&str>) -> String {
+    let escape = |value: &str| value.replace('\\', "\\\\").replace('\'', "\\'");
+
+    let mut clauses = vec!["trashed = false".to_string()];
+    if let Some(folder_id) = folder_id {
+        clauses.push(format!("'{}' in parents", escape(folder_id)));
+    }
+    if let Some(text) = text.map(str::trim).filter(|t| !t.is_empty()) {
+        let text = escape(text);
+        clauses.push(format!("(name contains '{}' or fullText contains '{}')", text, text));
+    }
+    clauses.join(" and ")
+}
+
+#[derive(Debug, Clone)]
+pub struct DriveService {
+    client: Client,
+    auth_service: Arc<GmailAuthService>,
+    db_manager: Arc<DatabaseManager>,
+}
+
+impl DriveService {
+    pub fn new(auth_service: Arc<GmailAuthService>, db_manager: Arc<DatabaseManager>) -> Self {
+        Self {
+            client: http_client(),
+            auth_service,
+            db_manager,
+        }
+    }
+
+    async fn send_request(&self, account_id: &str, endpoint: &str, query: &[(&str, String)]) -> Result<reqwest::Response> {
+        let tokens = self
+            .auth_service
+            .validate_and_refresh_tokens(&self.db_manager, account_id)
+            .await?;
+
+        let url = format!("{}/{}", GOOGLE_DRIVE_API_BASE, endpoint.trim_start_matches('/'));
+
+        let request = self.client.get(&url).query(query).bearer_auth(tokens.access_token);
+        let response = request_scheduler::send(Some(account_id), RequestPriority::Medium, request)
+            .await
+            .map_err(|e| LibreOllamaError::Network {
+                message: format!("Google Drive API request failed: {}", e),
+                url: Some(url.clone()),
+            })?;
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(LibreOllamaError::GoogleDriveApi {
+                message: format!("Google Drive API error: {}", error_text),
+                status_code: Some(status),
+            });
+        }
+
+        Ok(response)
+    }
+
+    async fn list(&self, account_id: &str, q: String, page_size: Option<u32>, page_token: Option<String>) -> Result<DriveFilePage> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct FilesResponse {
+            files: Option<Vec<DriveFile>>,
+            next_page_token: Option<String>,
+        }
+
+        let mut query = vec![
+            ("q", q),
+            ("fields", format!("nextPageToken,files({})", DRIVE_FILE_FIELDS)),
+            ("pageSize", page_size.unwrap_or(50).min(1000).to_string()),
+            ("orderBy", "folder,modifiedTime desc".to_string()),
+        ];
+        if let Some(token) = page_token {
+            query.push(("pageToken", token));
+        }
+
+        let response: FilesResponse = self
+            .send_request(account_id, "files", &query)
+            .await?
+            .json()
+            .await
+            .map_err(|e| LibreOllamaError::Serialization {
+                message: format!("Failed to parse Google Drive API response: {}", e),
+                data_type: "Google Drive Files".to_string(),
+            })?;
+
+        Ok(DriveFilePage {
+            files: response.files.unwrap_or_default(),
+            next_page_token: response.next_page_token,
+        })
+    }
+
+    /// List the files in a folder (the Drive root when `folder_id` is None)
+    pub async fn list_files(
+        &self,
+        account_id: &str,
+        folder_id: Option<&str>,
+        page_size: Option<u32>,
+        page_token: Option<String>,
+    ) -> Result<DriveFilePage> {
+        let q = build_search_query(None, Some(folder_id.unwrap_or("root")));
+        self.list(account_id, q, page_size, page_token).await
+    }
+
+    /// Search all files by name and content
+    pub async fn search_files(
+        &self,
+        account_id: &str,
+        text: &str,
+        page_size: Option<u32>,
+        page_token: Option<String>,
+    ) -> Result<DriveFilePage> {
+        if text.trim().is_empty() {
+            return Err(LibreOllamaError::InvalidInput {
+                message: "Search text cannot be empty".to_string(),
+                field: Some("query".to_string()),
+            });
+        }
+        self.list(account_id, build_search_query(Some(text), None), page_size, page_token).await
+    }
+
+    pub async fn get_file(&self, account_id: &str, file_id: &str) -> Result<DriveFile> {
+        self.send_request(
+            account_id,
+            &format!("files/{}", urlencoding::encode(file_id)),
+            &[("fields", DRIVE_FILE_FIELDS.to_string())],
+        )
+        .await?
+        .json()
+        .await
+        .map_err(|e| LibreOllamaError::Serialization {
+            message: format!("Failed to parse Google Drive file: {}", e),
+            data_type: "Google Drive File".to_string(),
+        })
+    }
+
+    /// Download a file's content, exporting Google Docs formats to PDF.
+    /// Fails without reading further once the content passes `max_bytes`,
+    /// which is the only check for exports since they have no size.
+    ///
+    /// Requires the `drive.readonly` scope; a 403 means it has not been granted yet.
+    pub async fn download_file(&self, account_id: &str, file: &DriveFile, max_bytes: u64) -> Result<(Vec<u8>, String)> {
+        if file.size_bytes().is_some_and(|size| size > max_bytes) {
+            return Err(too_large(&file.name, max_bytes));
+        }
+
+        let file_id = urlencoding::encode(&file.id);
+        let (endpoint, query, mime_type) = if file.mime_type.starts_with(GOOGLE_APPS_MIME_PREFIX) {
+            (
+                format!("files/{}/export", file_id),
+                vec![("mimeType", "application/pdf".to_string())],
+                "application/pdf".to_string(),
+            )
+        } else {
+            (
+                format!("files/{}", file_id),
+                vec![("alt", "media".to_string())],
+                file.mime_type.clone(),
+            )
+        };
+
+        let mut response = self.send_request(account_id, &endpoint, &query).await?;
+        let mut bytes = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|e| LibreOllamaError::Network {
+            message: format!("Failed to download Drive file: {}", e),
+            url: None,
+        })? {
+            if (bytes.len() + chunk.len()) as u64 > max_bytes {
+                return Err(too_large(&file.name, max_bytes));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+
+        Ok((bytes, mime_type))
+    }
+
+    /// Prepare a Drive file for a message, as a link or as downloaded content
+    pub async fn attachment_from_drive(&self, account_id: &str, file_id: &str, download: bool) -> Result<DriveAttachment> {
+        let file = self.get_file(account_id, file_id).await?;
+
+        if file.is_folder() || !download {
+            let url = file
+                .web_view_link
+                .clone()
+                .unwrap_or_else(|| format!("https://drive.google.com/open?id={}", file.id));
+            return Ok(DriveAttachment::Link {
+                file_id: file.id,
+                name: file.name,
+                mime_type: file.mime_type,
+                url,
+            });
+        }
+
+        // Files over the limit are refused before anything is downloaded
+        let (bytes, mime_type) = self.download_file(account_id, &file, attachment_limit_bytes()).await?;
+        let filename = if mime_type == "application/pdf" && !file.name.to_lowercase().ends_with(".pdf") {
+            format!("{}.pdf", file.name)
+        } else {
+            file.name.clone()
+        };
+
+        Ok(DriveAttachment::File {
+            file_id: file.id,
+            attachment: ComposeAttachment {
+                filename,
+                content_type: mime_type,
+                content_id: None,
+                size: bytes.len() as u64,
+                data: general_purpose::STANDARD.encode(&bytes),
+                is_inline: false,
+            },
+        })
+    }
+
+    async fn access_token(&self, account_id: &str) -> Result<String> {
+        Ok(self
+            .auth_service
+            .validate_and_refresh_tokens(&self.db_manager, account_id)
+            .await?
+            .access_token)
+    }
+
+    async fn check_status(response: reqwest::Response) -> Result<reqwest::Response> {
+        if response.status().is_success() {
+            return Ok(response);
+        }
+        let status = response.status().as_u16();
+        let error_text = response.text().await.unwrap_or_default();
+        Err(LibreOllamaError::GoogleDriveApi {
+            message: format!("Google Drive API error: {}", error_text),
+            status_code: Some(status),
+        })
+    }
+
+    /// Upload a file to the root of the user's Drive with a resumable
+    /// upload, calling `on_progress(bytes_sent, total_bytes)` after each chunk.
+    ///
+    /// Requires the `drive.file` scope; a 403 means it has not been granted yet.
+    pub async fn upload_file(
+        &self,
+        account_id: &str,
+        name: &str,
+        mime_type: &str,
+        bytes: &[u8],
+        on_progress: &(dyn Fn(u64, u64) + Send + Sync),
+    ) -> Result<DriveFile> {
+        let access_token = self.access_token(account_id).await?;
+        let total = bytes.len() as u64;
+        let url = format!("{}/files", GOOGLE_DRIVE_UPLOAD_BASE);
+        let network_error = |e: reqwest::Error| LibreOllamaError::Network {
+            message: format!("Google Drive upload failed: {}", e),
+            url: Some(url.clone()),
+        };
+
+        let request = self
+            .client
+            .post(&url)
+            .query(&[("uploadType", "resumable"), ("fields", DRIVE_FILE_FIELDS)])
+            .bearer_auth(&access_token)
+            .header("X-Upload-Content-Type", mime_type)
+            .header("X-Upload-Content-Length", total)
+            .json(&serde_json::json!({ "name": name, "mimeType": mime_type }));
+        let session = request_scheduler::send(Some(account_id), RequestPriority::Medium, request)
+            .await
+            .map_err(network_error)?;
+        let session_url = Self::check_status(session)
+            .await?
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+            .ok_or_else(|| LibreOllamaError::GoogleDriveApi {
+                message: "Resumable upload session has no Location".to_string(),
+                status_code: None,
+            })?;
+
+        let mut offset = 0usize;
+        loop {
+            let end = (offset + UPLOAD_CHUNK_SIZE).min(bytes.len());
+            let content_range = if bytes.is_empty() {
+                "bytes */0".to_string()
+            } else {
+                format!("bytes {}-{}/{}", offset, end - 1, total)
+            };
+
+            let request = self
+                .client
+                .put(&session_url)
+                .bearer_auth(&access_token)
+                .header(reqwest::header::CONTENT_RANGE, content_range)
+                .body(bytes[offset..end].to_vec());
+            let response = request_scheduler::send(Some(account_id), RequestPriority::Medium, request)
+                .await
+                .map_err(network_error)?;
+
+            on_progress(end as u64, total);
+
+            // 308 means the chunk was stored and more are expected
+            if response.status().as_u16() == 308 && end < bytes.len() {
+                offset = end;
+                continue;
+            }
+
+            return Self::check_status(response)
+                .await?
+                .json()
+                .await
+                .map_err(|e| LibreOllamaError::Serialization {
+                    message: format!("Failed to parse uploaded Drive file: {}", e),
+                    data_type: "Google Drive File".to_string(),
+                });
+        }
+    }
+
+    /// Give each recipient read access to a file.
+    ///
+    /// Drive only allows silent sharing with Google accounts, so recipients
+    /// that fail without a notification are retried with one.
+    pub async fn share_with(&self, account_id: &str, file_id: &str, emails: &[String]) -> Result<()> {
+        let access_token = self.access_token(account_id).await?;
+        let url = format!("{}/files/{}/permissions", GOOGLE_DRIVE_API_BASE, urlencoding::encode(file_id));
+
+        for email in emails {
+            let mut last_error = None;
+            for notify in [false, true] {
+                let request = self
+                    .client
+                    .post(&url)
+                    .query(&[("sendNotificationEmail", notify.to_string())])
+                    .bearer_auth(&access_token)
+                    .json(&serde_json::json!({ "role": "reader", "type": "user", "emailAddress": email }));
+                let response = request_scheduler::send(Some(account_id), RequestPriority::Medium, request)
+                    .await
+                    .map_err(|e| LibreOllamaError::Network {
+                        message: format!("Google Drive share failed: {}", e),
+                        url: Some(url.clone()),
+                    })?;
+                match Self::check_status(response).await {
+                    Ok(_) => {
+                        last_error = None;
+                        break;
+                    }
+                    Err(e) => last_error = Some(e),
+                }
+            }
+            if let Some(e) = last_error {
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_search_query_escapes_and_scopes() {
+        assert_eq!(build_search_query(None, Some("root")), "trashed = false and 'root' in parents");
+        assert_eq!(
+            build_search_query(Some(" Bob's plan "), None),
+            "trashed = false and (name contains 'Bob\\'s plan' or fullText contains 'Bob\\'s plan')"
+        );
+        assert_eq!(build_search_query(Some("  "), None), "trashed = false");
+    }
+
+    #[test]
+    fn test_size_and_limit_message() {
+        let mut file: DriveFile = serde_json::from_value(serde_json::json!({
+            "id": "f1", "name": "Plans.pdf", "mimeType": "application/pdf", "size": "52428800"
+        }))
+        .unwrap();
+        assert_eq!(file.size_bytes(), Some(50 * 1024 * 1024));
+        file.size = None;
+        assert_eq!(file.size_bytes(), None);
+        assert!(too_large(&file.name, 25 * 1024 * 1024).to_string().contains("25 MB"));
+    }
+}
@@ -0,0 +1,50 @@
+//! Base URLs of the Google APIs the services call
+//!
+//! Services default to Google's production hosts. Tests point them at a
+//! local mock server instead, with every API under one base URL.
+
+use crate::services::gmail::auth_service::GMAIL_TOKEN_URL;
+
+/// Where each Google API is served from, without a trailing slash
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoogleEndpoints {
+    pub gmail_api: String,
+    pub gmail_batch: String,
+    pub tasks_api: String,
+    pub tasks_batch: String,
+    pub calendar_api: String,
+    pub oauth_token: String,
+}
+
+impl Default for GoogleEndpoints {
+    fn default() -> Self {
+        Self {
+            gmail_api: "https://www.googleapis.com/gmail/v1".to_string(),
+            gmail_batch: "https://www.googleapis.com/batch/gmail/v1".to_string(),
+            tasks_api: "https://tasks.googleapis.com/tasks/v1".to_string(),
+            tasks_batch: "https://tasks.googleapis.com/batch/tasks/v1".to_string(),
+            calendar_api: "https://www.googleapis.com/calendar/v3".to_string(),
+            oauth_token: GMAIL_TOKEN_URL.to_string(),
+        }
+    }
+}
+
+impl GoogleEndpoints {
+    /// Every API under `base`, keeping Google's paths
+    pub fn rooted_at(base: &str) -> Self {
+        let base = base.trim_end_matches('/');
+        Self {
+            gmail_api: format!("{}/gmail/v1", base),
+            gmail_batch: format!("{}/batch/gmail/v1", base),
+            tasks_api: format!("{}/tasks/v1", base),
+            tasks_batch: format!("{}/batch/tasks/v1", base),
+            calendar_api: format!("{}/calendar/v3", base),
+            oauth_token: format!("{}/token", base),
+        }
+    }
+
+    /// `endpoint` under an API base URL
+    pub fn url(base: &str, endpoint: &str) -> String {
+        format!("{}/{}", base, endpoint.trim_start_matches('/'))
+    }
+}
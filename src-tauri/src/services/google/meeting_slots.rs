@@ -0,0 +1,384 @@
+//! Meeting time suggestions from Google Calendar free/busy data
+//!
+//! The freeBusy endpoint returns busy intervals for the user's primary
+//! calendar and for any attendee calendars the account can see (colleagues
+//! in the same Workspace, shared calendars). Candidate slots are laid out
+//! on a half-hour grid inside the user's working hours; slots where the user
+//! is busy or that run into a break or no-meeting block of their
+//! availability profile are dropped, and the rest are ranked by how many attendees are
+//! free, how soon they are, and whether they would be back to back with
+//! another meeting. Attendees whose calendars are not visible do not count
+//! either way.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::commands::rate_limiter::RequestPriority;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::availability::AvailabilityProfile;
+use crate::services::gmail::campaign_service::render_template;
+use crate::services::google::GoogleEndpoints;
+use crate::services::request_scheduler;
+use crate::utils::http_client::http_client;
+
+/// Candidate slots start on this grid
+const SLOT_STEP_MINUTES: i64 = 30;
+/// Meetings closer than this to another one count as back to back
+const BUFFER_MINUTES: i64 = 15;
+
+pub const DEFAULT_INVITE_SUBJECT: &str = "Meeting: {{title | Catch-up}}";
+pub const DEFAULT_INVITE_BODY: &str = "Hi {{names | all}},\n\n\
+Would {{slot}} ({{duration}} minutes) work for {{title | a quick meeting}}? \
+If not, these times also suit me:\n{{alternatives | (none)}}\n\nThanks!";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BusyInterval {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl BusyInterval {
+    fn overlaps(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> bool {
+        self.start < end && start < self.end
+    }
+}
+
+/// Busy times of one calendar. `visible` is false when Google would not
+/// share the calendar's free/busy information.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarAvailability {
+    pub email: String,
+    pub visible: bool,
+    pub busy: Vec<BusyInterval>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingSlot {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub score: f64,
+    pub available_attendees: Vec<String>,
+    pub busy_attendees: Vec<String>,
+    /// Attendees whose calendars could not be checked
+    pub unknown_attendees: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct FreeBusyCalendar {
+    #[serde(default)]
+    busy: Vec<BusyInterval>,
+    #[serde(default)]
+    errors: Vec<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct FreeBusyResponse {
+    #[serde(default)]
+    calendars: HashMap<String, FreeBusyCalendar>,
+}
+
+/// Query free/busy for the primary calendar and each attendee. The user's
+/// own calendar comes first in the result.
+pub async fn query_free_busy(
+    endpoints: &GoogleEndpoints,
+    account_id: &str,
+    access_token: &str,
+    attendees: &[String],
+    time_min: DateTime<Utc>,
+    time_max: DateTime<Utc>,
+) -> Result<Vec<CalendarAvailability>> {
+    let items: Vec<serde_json::Value> = std::iter::once("primary")
+        .chain(attendees.iter().map(String::as_str))
+        .map(|id| serde_json::json!({ "id": id }))
+        .collect();
+    let request_body = serde_json::json!({
+        "timeMin": time_min.to_rfc3339(),
+        "timeMax": time_max.to_rfc3339(),
+        "items": items,
+    });
+
+    let url = GoogleEndpoints::url(&endpoints.calendar_api, "freeBusy");
+    let request = http_client().post(&url).bearer_auth(access_token).json(&request_body);
+    let response = request_scheduler::send(Some(account_id), RequestPriority::Medium, request)
+        .await
+        .map_err(|e| LibreOllamaError::Network {
+            message: format!("Calendar freeBusy request failed: {}", e),
+            url: Some(url.clone()),
+        })?;
+
+    if !response.status().is_success() {
+        let status = response.status().as_u16();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(LibreOllamaError::GoogleCalendarApi {
+            message: format!("freeBusy failed: {}", error_text),
+            status_code: Some(status),
+        });
+    }
+
+    let mut body: FreeBusyResponse = response.json().await.map_err(|e| LibreOllamaError::Serialization {
+        message: format!("Failed to parse freeBusy response: {}", e),
+        data_type: "Calendar freeBusy".to_string(),
+    })?;
+
+    Ok(std::iter::once("primary")
+        .chain(attendees.iter().map(String::as_str))
+        .map(|id| match body.calendars.remove(id) {
+            Some(calendar) => CalendarAvailability {
+                email: id.to_string(),
+                visible: calendar.errors.is_empty(),
+                busy: calendar.busy,
+            },
+            None => CalendarAvailability { email: id.to_string(), visible: false, busy: Vec::new() },
+        })
+        .collect())
+}
+
+/// What to look for: a meeting of `duration` somewhere in the range
+#[derive(Debug, Clone, Copy)]
+pub struct SlotSearch {
+    pub range_start: DateTime<Utc>,
+    pub range_end: DateTime<Utc>,
+    pub duration: Duration,
+    pub limit: usize,
+}
+
+/// Rank candidate slots laid out in the profile's working hours in time
+/// zone `tz`.
+/// Overlapping candidates are collapsed to the best one so the suggestions
+/// are distinct.
+pub fn rank_slots<Tz: TimeZone>(
+    tz: &Tz,
+    search: &SlotSearch,
+    profile: &AvailabilityProfile,
+    own: &CalendarAvailability,
+    attendees: &[CalendarAvailability],
+) -> Result<Vec<MeetingSlot>> {
+    let SlotSearch { range_start, range_end, duration, limit } = *search;
+    let hours = &profile.working_hours;
+    let (day_start, day_end) = hours.bounds()?;
+    let step = Duration::minutes(SLOT_STEP_MINUTES);
+    let buffer = Duration::minutes(BUFFER_MINUTES);
+    let first_day = range_start.with_timezone(tz).date_naive();
+    let last_day = range_end.with_timezone(tz).date_naive();
+
+    let mut candidates = Vec::new();
+    let mut day = first_day;
+    while day <= last_day {
+        if hours.days.contains(&day.weekday().number_from_monday()) {
+            let window = (
+                tz.from_local_datetime(&day.and_time(day_start)).earliest(),
+                tz.from_local_datetime(&day.and_time(day_end)).latest(),
+            );
+            if let (Some(window_start), Some(window_end)) = window {
+                let window_end = window_end.with_timezone(&Utc);
+                let mut start = window_start.with_timezone(&Utc);
+                while start + duration <= window_end && start + duration <= range_end {
+                    let end = start + duration;
+                    let free = !own.busy.iter().any(|b| b.overlaps(start, end)) && profile.allows_meeting(tz, start, end);
+                    if start >= range_start && free {
+                        let days_out = (day - first_day).num_days() as f64;
+                        let back_to_back = own.busy.iter().any(|b| b.overlaps(start - buffer, end + buffer));
+                        candidates.push(score_slot(start, end, attendees, days_out, back_to_back));
+                    }
+                    start += step;
+                }
+            }
+        }
+        day = match day.succ_opt() {
+            Some(next) => next,
+            None => break,
+        };
+    }
+
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.start.cmp(&b.start)));
+    let mut chosen: Vec<MeetingSlot> = Vec::new();
+    for slot in candidates {
+        if chosen.len() >= limit {
+            break;
+        }
+        if !chosen.iter().any(|c| c.start < slot.end && slot.start < c.end) {
+            chosen.push(slot);
+        }
+    }
+    Ok(chosen)
+}
+
+fn score_slot(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    attendees: &[CalendarAvailability],
+    days_out: f64,
+    back_to_back: bool,
+) -> MeetingSlot {
+    let mut slot = MeetingSlot {
+        start,
+        end,
+        score: 0.0,
+        available_attendees: Vec::new(),
+        busy_attendees: Vec::new(),
+        unknown_attendees: Vec::new(),
+    };
+    for attendee in attendees {
+        let list = if !attendee.visible {
+            &mut slot.unknown_attendees
+        } else if attendee.busy.iter().any(|b| b.overlaps(start, end)) {
+            &mut slot.busy_attendees
+        } else {
+            &mut slot.available_attendees
+        };
+        list.push(attendee.email.clone());
+    }
+
+    let checked = slot.available_attendees.len() + slot.busy_attendees.len();
+    let availability = if checked == 0 { 1.0 } else { slot.available_attendees.len() as f64 / checked as f64 };
+    slot.score = availability * 100.0 - days_out * 2.0 - if back_to_back { 5.0 } else { 0.0 };
+    slot
+}
+
+/// Subject and body of an invite email proposing the best slot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InviteDraft {
+    pub to: Vec<String>,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Render an invite from `{{placeholder}}` templates. Available variables:
+/// `title`, `names`, `slot`, `duration` and `alternatives`.
+pub fn draft_invite<Tz: TimeZone>(
+    tz: &Tz,
+    attendees: &[String],
+    slots: &[MeetingSlot],
+    title: Option<&str>,
+    subject_template: Option<&str>,
+    body_template: Option<&str>,
+) -> Result<Option<InviteDraft>>
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let Some(best) = slots.first() else {
+        return Ok(None);
+    };
+    let format_slot = |slot: &MeetingSlot| {
+        format!(
+            "{} – {}",
+            slot.start.with_timezone(tz).format("%A %-d %B, %H:%M"),
+            slot.end.with_timezone(tz).format("%H:%M %Z")
+        )
+    };
+
+    let variables: HashMap<String, String> = [
+        ("title", title.unwrap_or_default().to_string()),
+        ("names", attendees.join(", ")),
+        ("slot", format_slot(best)),
+        ("duration", (best.end - best.start).num_minutes().to_string()),
+        (
+            "alternatives",
+            slots.iter().skip(1).take(3).map(|s| format!("- {}", format_slot(s))).collect::<Vec<_>>().join("\n"),
+        ),
+    ]
+    .into_iter()
+    .map(|(k, v)| (k.to_string(), v))
+    .collect();
+
+    let render = |template: &str| {
+        render_template(template, &variables, false).map_err(|message| LibreOllamaError::InvalidInput {
+            message,
+            field: Some("template".to_string()),
+        })
+    };
+    Ok(Some(InviteDraft {
+        to: attendees.to_vec(),
+        subject: render(subject_template.unwrap_or(DEFAULT_INVITE_SUBJECT))?,
+        body: render(body_template.unwrap_or(DEFAULT_INVITE_BODY))?,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::availability::{AvailabilityBlock, WorkingHours};
+
+    fn at(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value).unwrap().with_timezone(&Utc)
+    }
+
+    fn calendar(email: &str, busy: &[(&str, &str)]) -> CalendarAvailability {
+        CalendarAvailability {
+            email: email.to_string(),
+            visible: true,
+            busy: busy.iter().map(|(s, e)| BusyInterval { start: at(s), end: at(e) }).collect(),
+        }
+    }
+
+    #[test]
+    fn ranks_slots_inside_working_hours_around_busy_times() {
+        // Monday 2024-06-03
+        let own = calendar("primary", &[("2024-06-03T09:00:00Z", "2024-06-03T12:00:00Z")]);
+        let attendees = vec![
+            calendar("ana@example.com", &[("2024-06-03T13:00:00Z", "2024-06-03T17:00:00Z")]),
+            CalendarAvailability { email: "ext@example.org".to_string(), visible: false, busy: Vec::new() },
+        ];
+
+        let search = SlotSearch {
+            range_start: at("2024-06-03T08:00:00Z"),
+            range_end: at("2024-06-03T23:00:00Z"),
+            duration: Duration::minutes(60),
+            limit: 5,
+        };
+        let slots = rank_slots(&Utc, &search, &AvailabilityProfile::default(), &own, &attendees).unwrap();
+
+        // 12:00 suits everyone who can be checked, even though it is back to back
+        assert_eq!(slots.len(), 5);
+        assert_eq!(slots[0].start, at("2024-06-03T12:00:00Z"));
+        assert_eq!(slots[0].score, 95.0);
+        assert_eq!(slots[0].available_attendees, vec!["ana@example.com"]);
+        assert_eq!(slots[0].unknown_attendees, vec!["ext@example.org"]);
+        assert_eq!(slots[1].start, at("2024-06-03T13:00:00Z"));
+        assert_eq!(slots[1].busy_attendees, vec!["ana@example.com"]);
+        assert!(slots.iter().all(|s| s.end <= at("2024-06-03T17:00:00Z")));
+        for (i, a) in slots.iter().enumerate() {
+            assert!(slots[i + 1..].iter().all(|b| b.start >= a.end || b.end <= a.start));
+        }
+
+        let draft = draft_invite(&Utc, &["ana@example.com".to_string()], &slots, Some("Roadmap"), None, None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(draft.subject, "Meeting: Roadmap");
+        assert!(draft.body.contains("Monday 3 June, 12:00 – 13:00 UTC"));
+    }
+
+    #[test]
+    fn skips_breaks_and_no_meeting_blocks() {
+        let profile = AvailabilityProfile {
+            breaks: vec![AvailabilityBlock { label: None, start: "12:00".to_string(), end: "13:00".to_string(), days: Vec::new() }],
+            no_meeting_blocks: vec![AvailabilityBlock {
+                label: Some("Focus".to_string()),
+                start: "09:00".to_string(),
+                end: "11:00".to_string(),
+                days: vec![1],
+            }],
+            ..Default::default()
+        };
+        let own = calendar("primary", &[]);
+        let search = SlotSearch {
+            range_start: at("2024-06-03T08:00:00Z"),
+            range_end: at("2024-06-03T23:00:00Z"),
+            duration: Duration::minutes(60),
+            limit: 20,
+        };
+        let slots = rank_slots(&Utc, &search, &profile, &own, &[]).unwrap();
+
+        assert_eq!(slots[0].start, at("2024-06-03T11:00:00Z"));
+        assert!(slots.iter().all(|s| s.start >= at("2024-06-03T11:00:00Z")));
+        assert!(slots.iter().all(|s| s.end <= at("2024-06-03T12:00:00Z") || s.start >= at("2024-06-03T13:00:00Z")));
+    }
+
+    #[test]
+    fn rejects_inverted_working_hours() {
+        let hours = WorkingHours { start: "18:00".to_string(), end: "09:00".to_string(), days: vec![1] };
+        assert!(hours.bounds().is_err());
+    }
+}
@@ -1,3 +1,17 @@
+pub mod batch;
+pub mod calendar_service;
+pub mod drive_service;
+pub mod endpoints;
+pub mod meeting_slots;
+pub mod task_activity;
+pub mod task_bulk;
+pub mod task_import;
+pub mod task_export;
+pub mod people_sync;
 pub mod tasks_service;
+pub mod task_recurrence;
+pub mod subtask_sync;
+pub mod travel_buffers;
 
+pub use endpoints::GoogleEndpoints;
 pub use tasks_service::GoogleTasksService;
@@ -0,0 +1,295 @@
+//! Google Contacts sync through the People API
+//!
+//! Mirrors each account's contacts into the local `contacts` tables. The
+//! first run lists every connection and stores the returned sync token;
+//! later runs only fetch what changed, including deletions. When the token
+//! expires (it lives about a week) the local copy is rebuilt from scratch.
+
+use crate::database::operations::contact_operations::{self, ContactEmail, ContactInput};
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::commands::rate_limiter::RequestPriority;
+use crate::services::gmail::auth_service::{GmailAuthService, CONTACTS_READONLY_SCOPE};
+use crate::services::google_backoff;
+use crate::services::request_scheduler;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::utils::http_client::http_client;
+
+const PEOPLE_API_BASE: &str = "https://people.googleapis.com/v1";
+const PERSON_FIELDS: &str = "names,emailAddresses,photos,organizations";
+/// Reasons Google gives when the token lacks the contacts scope
+const MISSING_SCOPE_REASONS: &[&str] = &["ACCESS_TOKEN_SCOPE_INSUFFICIENT", "insufficientPermissions"];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContactSyncSummary {
+    pub full_sync: bool,
+    pub upserted: u32,
+    pub deleted: u32,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct FieldMetadata {
+    #[serde(default)]
+    primary: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PersonMetadata {
+    #[serde(default)]
+    deleted: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PersonName {
+    display_name: Option<String>,
+    given_name: Option<String>,
+    family_name: Option<String>,
+    #[serde(default)]
+    metadata: FieldMetadata,
+}
+
+#[derive(Debug, Deserialize)]
+struct PersonEmail {
+    value: Option<String>,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    #[serde(default)]
+    metadata: FieldMetadata,
+}
+
+#[derive(Debug, Deserialize)]
+struct PersonPhoto {
+    url: Option<String>,
+    #[serde(default)]
+    default: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct PersonOrganization {
+    name: Option<String>,
+    title: Option<String>,
+    #[serde(default)]
+    metadata: FieldMetadata,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Person {
+    resource_name: String,
+    etag: Option<String>,
+    #[serde(default)]
+    metadata: PersonMetadata,
+    #[serde(default)]
+    names: Vec<PersonName>,
+    #[serde(default)]
+    email_addresses: Vec<PersonEmail>,
+    #[serde(default)]
+    photos: Vec<PersonPhoto>,
+    #[serde(default)]
+    organizations: Vec<PersonOrganization>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConnectionsResponse {
+    #[serde(default)]
+    connections: Vec<Person>,
+    next_page_token: Option<String>,
+    next_sync_token: Option<String>,
+}
+
+/// Pick the primary entry of a People API field list, or the first one
+fn primary<T>(items: &[T], is_primary: impl Fn(&T) -> bool) -> Option<&T> {
+    items.iter().find(|i| is_primary(i)).or_else(|| items.first())
+}
+
+/// Whether a 403 body says the token was not granted the contacts scope,
+/// rather than e.g. the People API being disabled
+fn missing_scope(body: &str) -> bool {
+    google_backoff::error_reason(body).is_some_and(|reason| MISSING_SCOPE_REASONS.contains(&reason.as_str()))
+}
+
+impl Person {
+    fn into_contact(self) -> ContactInput {
+        let name = primary(&self.names, |n| n.metadata.primary);
+        let organization = primary(&self.organizations, |o| o.metadata.primary);
+        // Google returns a generated placeholder avatar flagged `default`
+        let photo_url = self.photos.iter().find(|p| !p.default).and_then(|p| p.url.clone());
+
+        ContactInput {
+            display_name: name.and_then(|n| n.display_name.clone()),
+            given_name: name.and_then(|n| n.given_name.clone()),
+            family_name: name.and_then(|n| n.family_name.clone()),
+            photo_url,
+            organization: organization.and_then(|o| o.name.clone()),
+            job_title: organization.and_then(|o| o.title.clone()),
+            emails: self
+                .email_addresses
+                .iter()
+                .filter_map(|e| {
+                    e.value.as_ref().map(|value| ContactEmail {
+                        email: value.clone(),
+                        label: e.kind.clone(),
+                        is_primary: e.metadata.primary,
+                    })
+                })
+                .collect(),
+            resource_name: self.resource_name,
+            etag: self.etag,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PeopleSyncService {
+    client: Client,
+    auth_service: Arc<GmailAuthService>,
+    db_manager: Arc<DatabaseManager>,
+}
+
+impl PeopleSyncService {
+    pub fn new(auth_service: Arc<GmailAuthService>, db_manager: Arc<DatabaseManager>) -> Self {
+        Self {
+            client: http_client(),
+            auth_service,
+            db_manager,
+        }
+    }
+
+    async fn fetch_page(
+        &self,
+        account_id: &str,
+        sync_token: Option<&str>,
+        page_token: Option<&str>,
+    ) -> Result<ConnectionsResponse> {
+        let tokens = self
+            .auth_service
+            .validate_and_refresh_tokens(&self.db_manager, account_id)
+            .await?;
+
+        let url = format!("{}/people/me/connections", PEOPLE_API_BASE);
+        let mut query = vec![
+            ("personFields", PERSON_FIELDS.to_string()),
+            ("pageSize", "1000".to_string()),
+            ("requestSyncToken", "true".to_string()),
+        ];
+        if let Some(token) = sync_token {
+            query.push(("syncToken", token.to_string()));
+        }
+        if let Some(token) = page_token {
+            query.push(("pageToken", token.to_string()));
+        }
+
+        let request = self.client.get(&url).query(&query).bearer_auth(tokens.access_token);
+        let response = request_scheduler::send(Some(account_id), RequestPriority::Low, request)
+            .await
+            .map_err(|e| LibreOllamaError::Network {
+                message: format!("People API request failed: {}", e),
+                url: Some(url.clone()),
+            })?;
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let error_text = response.text().await.unwrap_or_default();
+            if status == 403 && missing_scope(&error_text) {
+                return Err(LibreOllamaError::PermissionDenied {
+                    message: format!("Syncing contacts requires additional access: {}", CONTACTS_READONLY_SCOPE),
+                });
+            }
+            return Err(LibreOllamaError::GooglePeopleApi {
+                message: format!("People API error: {}", error_text),
+                status_code: Some(status),
+            });
+        }
+
+        response.json().await.map_err(|e| LibreOllamaError::Serialization {
+            message: format!("Failed to parse People API response: {}", e),
+            data_type: "People API Connections".to_string(),
+        })
+    }
+
+    fn is_expired_sync_token(error: &LibreOllamaError) -> bool {
+        match error {
+            LibreOllamaError::GooglePeopleApi { status_code: Some(410), .. } => true,
+            LibreOllamaError::GooglePeopleApi { message, .. } => message.contains("EXPIRED_SYNC_TOKEN"),
+            _ => false,
+        }
+    }
+
+    /// Sync an account's contacts, incrementally when a sync token is stored
+    pub async fn sync_contacts(&self, account_id: &str) -> Result<ContactSyncSummary> {
+        let stored_token = {
+            let conn = self.db_manager.get_connection()?;
+            contact_operations::get_sync_token(&conn, account_id)?
+        };
+
+        match self.sync_from(account_id, stored_token.as_deref()).await {
+            Err(e) if stored_token.is_some() && Self::is_expired_sync_token(&e) => {
+                println!("🔄 [PEOPLE-SYNC] Sync token expired for {}, running full sync", account_id);
+                self.sync_from(account_id, None).await
+            }
+            result => result,
+        }
+    }
+
+    async fn sync_from(&self, account_id: &str, sync_token: Option<&str>) -> Result<ContactSyncSummary> {
+        let mut summary = ContactSyncSummary {
+            full_sync: sync_token.is_none(),
+            ..Default::default()
+        };
+
+        // Collect every page first so a failure midway leaves the local copy untouched
+        let mut people = Vec::new();
+        let mut page_token: Option<String> = None;
+        let next_sync_token = loop {
+            let page = self.fetch_page(account_id, sync_token, page_token.as_deref()).await?;
+            people.extend(page.connections);
+            match page.next_page_token {
+                Some(token) => page_token = Some(token),
+                None => break page.next_sync_token,
+            }
+        };
+
+        let mut conn = self.db_manager.get_connection()?;
+        let tx = conn.transaction()?;
+
+        if summary.full_sync {
+            contact_operations::clear_contacts(&tx, account_id)?;
+        }
+
+        for person in people {
+            if person.metadata.deleted {
+                contact_operations::delete_contact(&tx, account_id, &person.resource_name)?;
+                summary.deleted += 1;
+            } else {
+                contact_operations::upsert_contact(&tx, account_id, &person.into_contact())?;
+                summary.upserted += 1;
+            }
+        }
+
+        contact_operations::set_sync_token(&tx, account_id, next_sync_token.as_deref())?;
+        tx.commit()?;
+
+        println!(
+            "✅ [PEOPLE-SYNC] {} contacts updated, {} deleted for {}",
+            summary.upserted, summary.deleted, account_id
+        );
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_scope_is_told_apart() {
+        let scope = r#"{"error":{"code":403,"status":"PERMISSION_DENIED","details":[{"reason":"ACCESS_TOKEN_SCOPE_INSUFFICIENT"}]}}"#;
+        let disabled = r#"{"error":{"code":403,"status":"PERMISSION_DENIED","details":[{"reason":"SERVICE_DISABLED"}]}}"#;
+        assert!(missing_scope(scope));
+        assert!(!missing_scope(disabled));
+    }
+}
@@ -0,0 +1,293 @@
+//! Two-way sync between local subtasks and Google Tasks child tasks
+//!
+//! Google Tasks models subtasks as regular tasks with a `parent` field and a
+//! lexicographic `position`. Local subtasks live in the `subtasks` table and
+//! are linked to their Google counterpart through `subtasks.google_task_id`.
+//!
+//! - Push: local subtasks without a Google ID are created as child tasks
+//!   (using `parent`/`previous`), linked ones are patched and re-ordered.
+//! - Pull: the remote hierarchy is mirrored into the `subtasks` table during
+//!   sync, including re-parenting and remote deletions.
+
+use crate::database::DatabaseManager;
+use crate::errors::Result;
+use crate::services::google::tasks_service::{CreateTaskInput, GoogleTask, GoogleTasksService, UpdateTaskInput};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SubtaskSyncSummary {
+    pub created_remote: u32,
+    pub updated_remote: u32,
+    pub inserted_local: u32,
+    pub updated_local: u32,
+    pub removed_local: u32,
+}
+
+/// Ensure a task_metadata row exists for a parent task and return its ID
+fn ensure_parent_metadata(conn: &Connection, google_task_id: &str, task_list_id: &str) -> rusqlite::Result<i64> {
+    if let Some(id) = conn
+        .query_row(
+            "SELECT id FROM task_metadata WHERE google_task_id = ?1",
+            params![google_task_id],
+            |row| row.get(0),
+        )
+        .optional()?
+    {
+        return Ok(id);
+    }
+
+    conn.execute(
+        "INSERT INTO task_metadata (google_task_id, task_list_id, priority) VALUES (?1, ?2, 'none')",
+        params![google_task_id, task_list_id],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Mirror the remote parent/child hierarchy of one task list into the local
+/// `subtasks` table.
+///
+/// Local subtasks that have not been pushed yet (no Google ID) are preserved.
+pub fn reconcile_remote_hierarchy(
+    conn: &mut Connection,
+    task_list_id: &str,
+    tasks: &[GoogleTask],
+) -> Result<SubtaskSyncSummary> {
+    let mut summary = SubtaskSyncSummary::default();
+
+    let mut children: HashMap<&str, Vec<&GoogleTask>> = HashMap::new();
+    for task in tasks.iter().filter(|t| t.parent.is_some()) {
+        children.entry(task.parent.as_deref().unwrap_or_default()).or_default().push(task);
+    }
+
+    let tx = conn.transaction()?;
+
+    // Parents whose local subtasks may need remote-deletion cleanup: every
+    // top-level task of this list plus any parent that has children.
+    let parent_ids: HashSet<&str> = tasks
+        .iter()
+        .filter(|t| t.parent.is_none())
+        .map(|t| t.id.as_str())
+        .chain(children.keys().copied())
+        .collect();
+
+    // First pass: upsert every remote child under its current parent so
+    // re-parented subtasks are moved rather than deleted and re-created.
+    let mut parents: Vec<(i64, HashSet<&str>)> = Vec::new();
+    for parent_id in parent_ids {
+        let remote_children = children.get(parent_id).cloned().unwrap_or_default();
+
+        let metadata_id: Option<i64> = if remote_children.is_empty() {
+            tx.query_row(
+                "SELECT id FROM task_metadata WHERE google_task_id = ?1",
+                params![parent_id],
+                |row| row.get(0),
+            )
+            .optional()?
+        } else {
+            Some(ensure_parent_metadata(&tx, parent_id, task_list_id)?)
+        };
+
+        let Some(metadata_id) = metadata_id else {
+            continue;
+        };
+
+        let mut ordered = remote_children;
+        ordered.sort_by(|a, b| a.position.cmp(&b.position));
+
+        for (position, child) in ordered.iter().enumerate() {
+            let completed = child.status == "completed";
+            let updated = tx.execute(
+                "UPDATE subtasks SET task_metadata_id = ?1, title = ?2, completed = ?3, position = ?4
+                 WHERE google_task_id = ?5",
+                params![metadata_id, child.title, completed, position as i32, child.id],
+            )?;
+
+            if updated > 0 {
+                summary.updated_local += 1;
+            } else {
+                tx.execute(
+                    "INSERT INTO subtasks (task_metadata_id, title, completed, position, google_task_id)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![metadata_id, child.title, completed, position as i32, child.id],
+                )?;
+                summary.inserted_local += 1;
+            }
+        }
+
+        parents.push((metadata_id, ordered.iter().map(|t| t.id.as_str()).collect()));
+    }
+
+    // Second pass: drop linked subtasks that no longer exist under their parent remotely
+    for (metadata_id, remote_ids) in parents {
+        let linked: Vec<(i64, String)> = {
+            let mut stmt = tx.prepare(
+                "SELECT id, google_task_id FROM subtasks
+                 WHERE task_metadata_id = ?1 AND google_task_id IS NOT NULL",
+            )?;
+            let rows = stmt
+                .query_map(params![metadata_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            rows
+        };
+
+        for (subtask_id, google_id) in linked {
+            if !remote_ids.contains(google_id.as_str()) {
+                tx.execute("DELETE FROM subtasks WHERE id = ?1", params![subtask_id])?;
+                summary.removed_local += 1;
+            }
+        }
+    }
+
+    tx.commit()?;
+    Ok(summary)
+}
+
+/// Push the local subtasks of a task to Google as child tasks, creating
+/// missing children and enforcing local order.
+pub async fn push_local_subtasks(
+    tasks_service: &GoogleTasksService,
+    db_manager: Arc<DatabaseManager>,
+    account_id: &str,
+    task_list_id: &str,
+    parent_task_id: &str,
+) -> Result<SubtaskSyncSummary> {
+    let mut summary = SubtaskSyncSummary::default();
+
+    let local: Vec<(i64, String, bool, Option<String>)> = {
+        let conn = db_manager.get_connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT s.id, s.title, s.completed, s.google_task_id
+             FROM subtasks s JOIN task_metadata tm ON tm.id = s.task_metadata_id
+             WHERE tm.google_task_id = ?1
+             ORDER BY s.position ASC",
+        )?;
+        let rows = stmt
+            .query_map(params![parent_task_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        rows
+    };
+
+    let mut previous: Option<String> = None;
+
+    for (subtask_id, title, completed, google_id) in local {
+        let status = if completed { "completed" } else { "needsAction" }.to_string();
+
+        let remote_id = match google_id {
+            Some(google_id) => {
+                tasks_service
+                    .update_task(
+                        account_id,
+                        task_list_id,
+                        &google_id,
+                        UpdateTaskInput { title: Some(title), notes: None, due: None, status: Some(status) },
+                    )
+                    .await?;
+                tasks_service
+                    .move_task(account_id, task_list_id, &google_id, Some(parent_task_id), previous.as_deref())
+                    .await?;
+                summary.updated_remote += 1;
+                google_id
+            }
+            None => {
+                let created = tasks_service
+                    .create_child_task(
+                        account_id,
+                        task_list_id,
+                        CreateTaskInput { title, notes: None, due: None, status: Some(status) },
+                        parent_task_id,
+                        previous.as_deref(),
+                    )
+                    .await?;
+
+                let conn = db_manager.get_connection()?;
+                conn.execute(
+                    "UPDATE subtasks SET google_task_id = ?1 WHERE id = ?2",
+                    params![created.id, subtask_id],
+                )?;
+                summary.created_remote += 1;
+                created.id
+            }
+        };
+
+        previous = Some(remote_id);
+    }
+
+    Ok(summary)
+}
+
+/// Full two-way sync for the subtasks of a single parent task
+pub async fn sync_task_subtasks(
+    tasks_service: &GoogleTasksService,
+    db_manager: Arc<DatabaseManager>,
+    account_id: &str,
+    task_list_id: &str,
+    parent_task_id: &str,
+) -> Result<SubtaskSyncSummary> {
+    let pushed = push_local_subtasks(tasks_service, db_manager.clone(), account_id, task_list_id, parent_task_id).await?;
+
+    let tasks = tasks_service.get_tasks(account_id, task_list_id).await?;
+    let mut conn = db_manager.get_connection()?;
+    let pulled = reconcile_remote_hierarchy(&mut conn, task_list_id, &tasks)?;
+
+    Ok(SubtaskSyncSummary {
+        created_remote: pushed.created_remote,
+        updated_remote: pushed.updated_remote,
+        ..pulled
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    fn task(id: &str, parent: Option<&str>, position: &str) -> GoogleTask {
+        GoogleTask {
+            id: id.to_string(),
+            title: format!("Task {}", id),
+            notes: None,
+            due: None,
+            status: "needsAction".to_string(),
+            parent: parent.map(|p| p.to_string()),
+            position: Some(position.to_string()),
+            updated: None,
+            completed: None,
+            etag: None,
+        }
+    }
+
+    fn subtask_ids(conn: &Connection, parent: &str) -> Vec<String> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT s.google_task_id FROM subtasks s JOIN task_metadata tm ON tm.id = s.task_metadata_id
+                 WHERE tm.google_task_id = ?1 ORDER BY s.position",
+            )
+            .unwrap();
+        stmt.query_map(params![parent], |row| row.get(0)).unwrap().map(|r| r.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_reconcile_mirrors_remote_children() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let tasks = vec![
+            task("p", None, "001"),
+            task("c2", Some("p"), "002"),
+            task("c1", Some("p"), "001"),
+        ];
+        let summary = reconcile_remote_hierarchy(&mut conn, "list", &tasks).unwrap();
+        assert_eq!(summary.inserted_local, 2);
+        assert_eq!(subtask_ids(&conn, "p"), vec!["c1", "c2"]);
+
+        // c1 deleted remotely, c2 re-parented under q
+        let tasks = vec![task("p", None, "001"), task("q", None, "002"), task("c2", Some("q"), "001")];
+        let summary = reconcile_remote_hierarchy(&mut conn, "list", &tasks).unwrap();
+        assert_eq!(summary.removed_local, 1);
+        assert!(subtask_ids(&conn, "p").is_empty());
+        assert_eq!(subtask_ids(&conn, "q"), vec!["c2"]);
+    }
+}
@@ -0,0 +1,423 @@
+//! Task activity
+//!
+//! A task's activity is its change journal merged with the comments left
+//! on it, oldest first. With `tasks.mirror_activity` on, a compact summary
+//! of the latest entries is kept at the end of the task's Google notes so
+//! it shows up on other devices. The summary block starts with
+//! `ACTIVITY_MARKER` and is rebuilt whenever the task or its comments
+//! change; the notes above it are left as the user wrote them. The block is
+//! written only if the task is unchanged since it was read, so a notes edit
+//! made elsewhere in between is never overwritten. Tasks that are offline or
+//! have no Google ID yet are queued and mirrored by `replay_mirrors`.
+
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::database::operations::change_journal_operations::{self, JournalEntry};
+use crate::database::operations::task_comment_operations::{self, TaskComment};
+use crate::database::operations::task_queue_operations;
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::google::tasks_service::{GoogleTasksService, UpdateTaskInput};
+use crate::services::i18n;
+use crate::services::settings;
+use crate::services::sync::task_queue;
+
+/// Whether comments and changes are summarised into Google Task notes
+pub const MIRROR_ACTIVITY_SETTING: &str = "tasks.mirror_activity";
+/// Start of the mirrored block; not localized so it is found in any locale
+pub const ACTIVITY_MARKER: &str = "━━ ";
+/// Entries kept in the mirrored block
+const MIRRORED_ENTRIES: usize = 10;
+/// Google Tasks rejects longer notes
+const NOTES_LIMIT: usize = 8192;
+/// Comments are cut to this many characters in the mirrored block
+const COMMENT_PREVIEW: usize = 80;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskChange {
+    pub journal_id: i64,
+    pub operation: String,
+    pub payload: Value,
+    pub undone: bool,
+    /// UTC, "YYYY-MM-DD HH:MM:SS"
+    pub created_at: String,
+    /// One line describing the change in the active locale
+    pub summary: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TaskActivity {
+    Comment(TaskComment),
+    Change(TaskChange),
+}
+
+impl TaskActivity {
+    fn created_at(&self) -> &str {
+        match self {
+            TaskActivity::Comment(comment) => &comment.created_at,
+            TaskActivity::Change(change) => &change.created_at,
+        }
+    }
+}
+
+/// Local date a journal or comment timestamp falls on
+fn local_date(timestamp: &str) -> Option<NaiveDate> {
+    let utc = NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S").ok()?;
+    Some(Local.from_utc_datetime(&utc).date_naive())
+}
+
+fn format_date(date: NaiveDate) -> String {
+    date.format_localized(&i18n::t("task-activity-date-format"), i18n::chrono_locale()).to_string()
+}
+
+/// Describe a journaled task change, e.g. "Completed; due Oct 20"
+pub fn summarize_change(entry: &JournalEntry) -> String {
+    match entry.operation.as_str() {
+        "create" => return i18n::t("task-activity-created"),
+        "delete" => return i18n::t("task-activity-deleted"),
+        _ => {}
+    }
+
+    let mut parts = Vec::new();
+    match entry.payload.get("status").and_then(Value::as_str) {
+        Some("completed") => parts.push(i18n::t("task-activity-completed")),
+        Some("needsAction") => parts.push(i18n::t("task-activity-reopened")),
+        _ => {}
+    }
+    if let Some(title) = entry.payload.get("title").and_then(Value::as_str) {
+        parts.push(i18n::t_args("task-activity-renamed", &[("title", title.to_string().into())]));
+    }
+    if let Some(due) = entry.payload.get("due") {
+        let date = due
+            .as_str()
+            .and_then(|due| due.get(..10))
+            .and_then(|day| NaiveDate::parse_from_str(day, "%Y-%m-%d").ok());
+        parts.push(match date {
+            Some(date) => i18n::t_args("task-activity-due", &[("date", format_date(date).into())]),
+            None => i18n::t("task-activity-due-cleared"),
+        });
+    }
+    if entry.payload.get("notes").is_some() {
+        parts.push(i18n::t("task-activity-notes"));
+    }
+    if parts.is_empty() {
+        return i18n::t("task-activity-updated");
+    }
+    parts.join("; ")
+}
+
+/// Comments and journaled changes of a task, oldest first, including those
+/// made while it only had a temporary ID
+pub fn task_activity(conn: &Connection, google_task_id: &str) -> Result<Vec<TaskActivity>> {
+    let mut task_ids = task_queue_operations::former_task_ids(conn, google_task_id)?;
+    task_ids.push(google_task_id.to_string());
+
+    let mut activity = Vec::new();
+    for task_id in &task_ids {
+        for entry in change_journal_operations::get_entity_history(conn, "task", task_id)? {
+            activity.push(TaskActivity::Change(TaskChange {
+                summary: summarize_change(&entry),
+                journal_id: entry.id,
+                operation: entry.operation,
+                payload: entry.payload,
+                undone: entry.undone,
+                created_at: entry.created_at,
+            }));
+        }
+    }
+    activity.extend(task_comment_operations::list_comments(conn, &task_ids)?.into_iter().map(TaskActivity::Comment));
+    activity.sort_by(|a, b| a.created_at().cmp(b.created_at()));
+    Ok(activity)
+}
+
+fn preview(body: &str) -> String {
+    let line = body.lines().map(str::trim).find(|line| !line.is_empty()).unwrap_or_default();
+    if line.chars().count() > COMMENT_PREVIEW {
+        format!("{}…", line.chars().take(COMMENT_PREVIEW - 1).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+/// Summary lines of the latest activity, oldest first; undone changes are left out
+pub fn activity_lines(activity: &[TaskActivity]) -> Vec<String> {
+    let lines: Vec<String> = activity
+        .iter()
+        .filter_map(|item| {
+            let text = match item {
+                TaskActivity::Change(change) if change.undone => return None,
+                TaskActivity::Change(change) => change.summary.clone(),
+                TaskActivity::Comment(comment) => i18n::t_args(
+                    "task-activity-comment",
+                    &[("author", comment.author.clone().into()), ("body", preview(&comment.body).into())],
+                ),
+            };
+            Some(match local_date(item.created_at()) {
+                Some(date) => format!("{} · {}", format_date(date), text),
+                None => text,
+            })
+        })
+        .collect();
+    lines[lines.len().saturating_sub(MIRRORED_ENTRIES)..].to_vec()
+}
+
+/// The notes a user wrote, without a mirrored activity block
+pub fn user_notes(notes: &str) -> &str {
+    let start = if notes.starts_with(ACTIVITY_MARKER) {
+        Some(0)
+    } else {
+        notes.find(&format!("\n{}", ACTIVITY_MARKER))
+    };
+    match start {
+        Some(start) => notes[..start].trim_end(),
+        None => notes,
+    }
+}
+
+/// Notes with the activity block rebuilt from `lines`, dropping the
+/// oldest lines until they fit Google's limit
+pub fn mirrored_notes(notes: &str, lines: &[String]) -> String {
+    let user = user_notes(notes);
+    if lines.is_empty() {
+        return user.to_string();
+    }
+    let heading = format!("{}{}", ACTIVITY_MARKER, i18n::t("task-activity-heading"));
+    let separator = if user.is_empty() { "" } else { "\n\n" };
+    for skip in 0..lines.len() {
+        let notes = format!("{}{}{}\n{}", user, separator, heading, lines[skip..].join("\n"));
+        if notes.chars().count() <= NOTES_LIMIT {
+            return notes;
+        }
+    }
+    user.to_string()
+}
+
+/// Times a mirror is retried when the task changes between read and write
+const MIRROR_ATTEMPTS: usize = 3;
+
+fn mirroring_enabled(conn: &Connection) -> Result<bool> {
+    Ok(settings::get_setting(conn, MIRROR_ACTIVITY_SETTING)?.value.as_bool().unwrap_or(false))
+}
+
+/// Rewrite the activity block in a task's Google notes when mirroring is
+/// on. Returns the new notes, or `None` when nothing had to change; tasks
+/// that have not reached Google yet are left for a later call.
+pub async fn mirror_activity(
+    tasks_service: &GoogleTasksService,
+    db_manager: &DatabaseManager,
+    account_id: &str,
+    task_list_id: &str,
+    google_task_id: &str,
+) -> Result<Option<String>> {
+    if task_queue_operations::is_temp_task_id(google_task_id) {
+        return Ok(None);
+    }
+    let lines = {
+        let conn = db_manager.get_connection()?;
+        if !mirroring_enabled(&conn)? {
+            return Ok(None);
+        }
+        activity_lines(&task_activity(&conn, google_task_id)?)
+    };
+
+    for _ in 0..MIRROR_ATTEMPTS {
+        let task = tasks_service.get_single_task(account_id, task_list_id, google_task_id).await?;
+        let notes = task.notes.unwrap_or_default();
+        let mirrored = mirrored_notes(&notes, &lines);
+        if mirrored == notes {
+            return Ok(None);
+        }
+        let Some(etag) = task.etag else {
+            tasks_service
+                .update_task(account_id, task_list_id, google_task_id, UpdateTaskInput {
+                    title: None,
+                    notes: Some(mirrored.clone()),
+                    due: None,
+                    status: None,
+                })
+                .await?;
+            return Ok(Some(mirrored));
+        };
+        if tasks_service
+            .update_notes_if_unchanged(account_id, task_list_id, google_task_id, &etag, &mirrored)
+            .await?
+        {
+            return Ok(Some(mirrored));
+        }
+    }
+    Err(LibreOllamaError::GoogleTasksApi {
+        message: format!("Task {} kept changing while its activity was mirrored", google_task_id),
+    })
+}
+
+/// Queue a task's activity to be mirrored by `replay_mirrors`, for changes
+/// made while Google is unreachable
+pub fn queue_mirror(db_manager: &DatabaseManager, account_id: &str, task_list_id: &str, google_task_id: &str) {
+    let queue = || -> Result<()> {
+        let conn = db_manager.get_connection()?;
+        if mirroring_enabled(&conn)? {
+            task_comment_operations::queue_mirror(&conn, google_task_id, account_id, task_list_id)?;
+        }
+        Ok(())
+    };
+    if let Err(e) = queue() {
+        eprintln!("⚠️ [TASK_ACTIVITY] Failed to queue activity mirror of {}: {}", google_task_id, e);
+    }
+}
+
+/// Mirror a task's activity, queueing it for `replay_mirrors` when Google
+/// cannot be reached or the task has no Google ID yet. Returns the new
+/// notes if they were written. Failures are logged, never returned: the
+/// change that prompted the mirror has already been made.
+pub async fn mirror_or_queue(
+    tasks_service: &GoogleTasksService,
+    db_manager: &DatabaseManager,
+    account_id: &str,
+    task_list_id: &str,
+    google_task_id: &str,
+) -> Option<String> {
+    if task_queue_operations::is_temp_task_id(google_task_id) {
+        queue_mirror(db_manager, account_id, task_list_id, google_task_id);
+        return None;
+    }
+
+    match mirror_activity(tasks_service, db_manager, account_id, task_list_id, google_task_id).await {
+        Ok(notes) => {
+            let clear = || -> Result<()> {
+                let conn = db_manager.get_connection()?;
+                task_comment_operations::clear_mirror(&conn, google_task_id)?;
+                Ok(())
+            };
+            if let Err(e) = clear() {
+                eprintln!("⚠️ [TASK_ACTIVITY] Failed to clear queued mirror of {}: {}", google_task_id, e);
+            }
+            notes
+        }
+        Err(e) if task_queue::is_offline(&e) => {
+            eprintln!("📴 [TASK_ACTIVITY] Google Tasks unreachable, activity of {} queued", google_task_id);
+            queue_mirror(db_manager, account_id, task_list_id, google_task_id);
+            None
+        }
+        Err(e) => {
+            eprintln!("⚠️ [TASK_ACTIVITY] Failed to mirror activity of {}: {}", google_task_id, e);
+            None
+        }
+    }
+}
+
+/// Mirror the activity of every queued task that has a Google ID by now.
+/// Tasks Google rejects are dropped from the queue rather than retried
+/// forever; those still offline stay queued.
+pub async fn replay_mirrors(tasks_service: &GoogleTasksService, db_manager: &DatabaseManager) -> Result<()> {
+    let queued = {
+        let conn = db_manager.get_connection()?;
+        task_comment_operations::queued_mirrors(&conn)?
+    };
+    for mirror in queued {
+        if task_queue_operations::is_temp_task_id(&mirror.google_task_id) {
+            continue;
+        }
+        match mirror_activity(tasks_service, db_manager, &mirror.account_id, &mirror.task_list_id, &mirror.google_task_id).await {
+            Err(e) if task_queue::is_offline(&e) => return Ok(()),
+            result => {
+                if let Err(e) = result {
+                    eprintln!("⚠️ [TASK_ACTIVITY] Dropping queued mirror of {}: {}", mirror.google_task_id, e);
+                }
+                let conn = db_manager.get_connection()?;
+                task_comment_operations::clear_mirror(&conn, &mirror.google_task_id)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Comment text as stored, rejecting empty comments
+pub fn comment_body(body: &str) -> Result<&str> {
+    let body = body.trim();
+    if body.is_empty() {
+        return Err(LibreOllamaError::InvalidInput {
+            message: "A comment cannot be empty".to_string(),
+            field: Some("body".to_string()),
+        });
+    }
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+    use rusqlite::params;
+    use serde_json::json;
+
+    #[test]
+    fn test_summarize_change() {
+        let entry = |operation: &str, payload: Value| JournalEntry {
+            id: 1,
+            entity_type: "task".to_string(),
+            entity_id: "t".to_string(),
+            operation: operation.to_string(),
+            payload,
+            previous_payload: None,
+            account_id: None,
+            undone: false,
+            synced_at: None,
+            created_at: "2026-10-16 09:00:00".to_string(),
+        };
+        assert_eq!(summarize_change(&entry("create", json!({ "title": "Draft" }))), "Created");
+        assert_eq!(
+            summarize_change(&entry("update", json!({ "status": "completed", "due": "2026-10-20T00:00:00.000Z" }))),
+            "Completed; due Oct 20"
+        );
+        assert_eq!(summarize_change(&entry("update", json!({ "due": null, "notes": "x" }))), "Due date removed; notes edited");
+        assert_eq!(summarize_change(&entry("update", json!({}))), "Updated");
+    }
+
+    #[test]
+    fn test_activity_merges_comments_and_former_ids() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let temp_id = task_queue_operations::new_temp_task_id();
+        task_queue_operations::create_temp_mapping(&conn, &temp_id, "list").unwrap();
+        let created = change_journal_operations::record_change(&conn, "task", &temp_id, "create", &json!({ "title": "Plan" }), None, Some("acc")).unwrap();
+        let comment = task_comment_operations::add_comment(&conn, &temp_id, Some("acc"), "Ada", "Started").unwrap();
+        task_queue_operations::reconcile_task_id(&mut conn, &temp_id, "google-1").unwrap();
+        let done = change_journal_operations::record_change(&conn, "task", "google-1", "update", &json!({ "status": "completed" }), None, Some("acc")).unwrap();
+        // Entries from the same second would tie
+        let at = "UPDATE change_journal SET created_at = ?2 WHERE id = ?1";
+        conn.execute(at, params![created, "2026-10-16 09:00:00"]).unwrap();
+        conn.execute(at, params![done, "2026-10-16 10:00:00"]).unwrap();
+        conn.execute("UPDATE task_comments SET created_at = '2026-10-16 09:30:00' WHERE id = ?1", [comment.id]).unwrap();
+
+        let activity = task_activity(&conn, "google-1").unwrap();
+        assert_eq!(activity.len(), 3);
+        assert!(matches!(&activity[1], TaskActivity::Comment(comment) if comment.google_task_id == "google-1"));
+
+        let lines = activity_lines(&activity);
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].ends_with("Created"));
+        assert!(lines[1].ends_with("Ada: Started"));
+        assert!(lines[2].ends_with("Completed"));
+    }
+
+    #[test]
+    fn test_mirrored_notes_replace_the_previous_block() {
+        let lines = vec!["Oct 16 · Created".to_string(), "Oct 16 · Ada: Looks good".to_string()];
+        let first = mirrored_notes("Buy paint", &lines);
+        assert_eq!(first, "Buy paint\n\n━━ Activity\nOct 16 · Created\nOct 16 · Ada: Looks good");
+        assert_eq!(user_notes(&first), "Buy paint");
+
+        let again = mirrored_notes(&first, &lines[..1]);
+        assert_eq!(again, "Buy paint\n\n━━ Activity\nOct 16 · Created");
+        assert_eq!(mirrored_notes("", &lines[..1]), "━━ Activity\nOct 16 · Created");
+        assert_eq!(mirrored_notes(&again, &[]), "Buy paint");
+
+        let long = vec!["x".repeat(5000), "y".repeat(5000)];
+        assert_eq!(mirrored_notes("", &long), format!("━━ Activity\n{}", "y".repeat(5000)));
+    }
+}
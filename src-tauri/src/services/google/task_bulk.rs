@@ -0,0 +1,448 @@
+//! Bulk task updates
+//!
+//! One set of operations applied to many tasks at once. Status changes and
+//! deletes go to Google in batch calls, then moves to another list in a
+//! second round; priority and label changes are written in a single
+//! transaction. Every task gets its own result, so a task Google rejects
+//! does not stop the others. When Google is unreachable, status changes and
+//! deletes are queued like single edits made offline.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::commands::tasks::metadata_simple::SimpleLabel;
+use crate::database::operations::{change_journal_operations, task_queue_operations};
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::google::batch::BatchPart;
+use crate::services::google::tasks_service::GoogleTasksService;
+use crate::services::sync::task_queue;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskRef {
+    /// Google Task ID, or the temporary ID of a task created offline
+    pub task_id: String,
+    pub task_list_id: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BulkTaskOperations {
+    /// List to move the tasks to
+    pub move_to_list: Option<String>,
+    pub priority: Option<String>,
+    /// Labels to add; one named like an existing label replaces its colour
+    pub add_labels: Vec<SimpleLabel>,
+    /// Names of labels to remove, ignoring case
+    pub remove_labels: Vec<String>,
+    /// Complete the tasks, or reopen them with `false`
+    pub complete: Option<bool>,
+    /// Delete the tasks; cannot be combined with other operations
+    pub delete: bool,
+}
+
+impl BulkTaskOperations {
+    fn changes_metadata(&self) -> bool {
+        self.priority.is_some() || !self.add_labels.is_empty() || !self.remove_labels.is_empty()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let changes_task = self.move_to_list.is_some() || self.complete.is_some() || self.changes_metadata();
+        let message = match (self.delete, changes_task) {
+            (true, true) => "Deleting tasks cannot be combined with other changes",
+            (false, false) => "No changes were given",
+            _ => return Ok(()),
+        };
+        Err(LibreOllamaError::InvalidInput {
+            message: message.to_string(),
+            field: Some("operations".to_string()),
+        })
+    }
+
+    /// Task fields sent to Google and recorded in the journal
+    pub fn status_fields(&self) -> Option<Map<String, Value>> {
+        let status = if self.complete? { "completed" } else { "needsAction" };
+        let mut fields = Map::new();
+        fields.insert("status".to_string(), json!(status));
+        Some(fields)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkTaskResult {
+    pub task_id: String,
+    /// List the task is in afterwards
+    pub task_list_id: String,
+    pub success: bool,
+    /// Queued locally because Google was unreachable or the task has not
+    /// been created there yet
+    pub pending_sync: bool,
+    pub error: Option<String>,
+    /// Whether the task moved to `move_to_list`
+    #[serde(default)]
+    pub moved: bool,
+}
+
+impl BulkTaskResult {
+    fn fail(&mut self, error: impl std::fmt::Display) {
+        self.success = false;
+        self.error = Some(error.to_string());
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkUpdateSummary {
+    pub results: Vec<BulkTaskResult>,
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+/// `existing` with `add` merged in by name and `remove` taken out
+pub fn merge_labels(existing: Vec<SimpleLabel>, add: &[SimpleLabel], remove: &[String]) -> Vec<SimpleLabel> {
+    let same = |a: &str, b: &str| a.trim().eq_ignore_ascii_case(b.trim());
+    let mut labels: Vec<SimpleLabel> = existing
+        .into_iter()
+        .filter(|label| !remove.iter().any(|name| same(name, &label.name)))
+        .collect();
+    for label in add {
+        match labels.iter_mut().find(|existing| same(&existing.name, &label.name)) {
+            Some(existing) => existing.color = label.color.clone(),
+            None => labels.push(label.clone()),
+        }
+    }
+    labels
+}
+
+/// Write priority, label and list changes for `results` that succeeded in
+/// one transaction, journaling each task's metadata like single edits
+pub fn apply_metadata(conn: &mut Connection, results: &[BulkTaskResult], operations: &BulkTaskOperations) -> anyhow::Result<()> {
+    let tx = conn.transaction()?;
+    for result in results.iter().filter(|result| result.success) {
+        if result.moved {
+            tx.execute(
+                "UPDATE task_id_map SET task_list_id = ?1, updated_at = CURRENT_TIMESTAMP WHERE google_task_id = ?2",
+                params![result.task_list_id, result.task_id],
+            )?;
+            tx.execute(
+                "UPDATE task_metadata SET task_list_id = ?1, updated_at = CURRENT_TIMESTAMP WHERE google_task_id = ?2",
+                params![result.task_list_id, result.task_id],
+            )?;
+        }
+        if !operations.changes_metadata() {
+            continue;
+        }
+
+        let current: Option<(String, Option<String>, Option<String>)> = tx
+            .query_row(
+                "SELECT priority, labels_json, time_block FROM task_metadata WHERE google_task_id = ?1",
+                params![result.task_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?;
+        let json_of = |text: &Option<String>| text.as_deref().and_then(|text| serde_json::from_str::<Value>(text).ok());
+        let previous = current.as_ref().map(|(priority, labels, time_block)| {
+            json!({ "priority": priority, "labels": json_of(labels), "time_block": json_of(time_block) })
+        });
+        let (priority, labels_json, time_block) = current.unwrap_or_else(|| ("none".to_string(), None, None));
+
+        let existing = labels_json
+            .as_deref()
+            .and_then(|labels| serde_json::from_str::<Vec<SimpleLabel>>(labels).ok())
+            .unwrap_or_default();
+        let labels = merge_labels(existing, &operations.add_labels, &operations.remove_labels);
+        let priority = operations.priority.clone().unwrap_or(priority);
+        let labels_json = serde_json::to_string(&labels)?;
+
+        tx.execute(
+            "INSERT INTO task_metadata (google_task_id, task_list_id, priority, labels_json)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(google_task_id) DO UPDATE SET
+                task_list_id = excluded.task_list_id, priority = excluded.priority,
+                labels_json = excluded.labels_json, updated_at = CURRENT_TIMESTAMP",
+            params![result.task_id, result.task_list_id, priority, labels_json],
+        )?;
+        let payload = json!({ "priority": priority, "labels": labels, "time_block": json_of(&time_block) });
+        let operation = if previous.is_some() { "update" } else { "create" };
+        change_journal_operations::record_change(&tx, "task_metadata", &result.task_id, operation, &payload, previous.as_ref(), None)?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Remove the local ID mapping and metadata of tasks Google deleted, in one
+/// transaction. Queued deletes keep theirs until the replay worker runs them.
+pub fn remove_deleted(conn: &mut Connection, results: &[BulkTaskResult]) -> anyhow::Result<()> {
+    let tx = conn.transaction()?;
+    for result in results.iter().filter(|result| result.success && !result.pending_sync) {
+        tx.execute("DELETE FROM task_id_map WHERE google_task_id = ?1", params![result.task_id])?;
+        tx.execute("DELETE FROM task_metadata WHERE google_task_id = ?1", params![result.task_id])?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+fn task_path(task_list_id: &str, task_id: &str) -> String {
+    format!("/tasks/v1/lists/{}/tasks/{}", task_list_id, task_id)
+}
+
+/// Queue the status change or delete of one task for the replay worker
+fn queue(conn: &mut Connection, account_id: &str, result: &mut BulkTaskResult, operations: &BulkTaskOperations) {
+    let queued = if operations.delete {
+        task_queue::queue_task_delete(conn, account_id, &result.task_list_id, &result.task_id, None)
+    } else {
+        let fields = operations.status_fields().unwrap_or_default();
+        task_queue::queue_task_update(conn, account_id, &result.task_list_id, &result.task_id, fields, None).map(|_| ())
+    };
+    match queued {
+        Ok(()) => result.pending_sync = true,
+        Err(e) => result.fail(format!("Failed to queue the change: {}", e)),
+    }
+}
+
+/// Apply `operations` to every task in `tasks`
+pub async fn bulk_update(
+    tasks_service: &GoogleTasksService,
+    db_manager: &DatabaseManager,
+    account_id: &str,
+    tasks: &[TaskRef],
+    operations: &BulkTaskOperations,
+) -> Result<BulkUpdateSummary> {
+    operations.validate()?;
+    let mut results: Vec<BulkTaskResult> = tasks
+        .iter()
+        .map(|task| BulkTaskResult {
+            task_id: task.task_id.clone(),
+            task_list_id: task.task_list_id.clone(),
+            success: true,
+            pending_sync: false,
+            error: None,
+            moved: false,
+        })
+        .collect();
+
+    let destination = operations
+        .move_to_list
+        .as_deref()
+        .filter(|list| !list.trim().is_empty());
+    if destination.is_some() {
+        for result in results.iter_mut().filter(|result| task_queue_operations::is_temp_task_id(&result.task_id)) {
+            result.fail("The task has not reached Google yet; move it once it has synced");
+        }
+    }
+
+    // Status changes and deletes
+    let mut offline = false;
+    if operations.delete || operations.complete.is_some() {
+        let (temp, remote): (Vec<usize>, Vec<usize>) = (0..results.len())
+            .filter(|&i| results[i].success)
+            .partition(|&i| task_queue_operations::is_temp_task_id(&results[i].task_id));
+        let parts: Vec<BatchPart> = remote
+            .iter()
+            .map(|&i| {
+                let path = task_path(&results[i].task_list_id, &results[i].task_id);
+                match operations.status_fields() {
+                    Some(fields) if !operations.delete => BatchPart::new("PATCH", path, Some(Value::Object(fields))),
+                    _ => BatchPart::new("DELETE", path, None),
+                }
+            })
+            .collect();
+
+        let mut queued = temp;
+        if !parts.is_empty() {
+            match tasks_service.batch(account_id, &parts).await {
+                Ok(outcomes) => {
+                    for (&i, outcome) in remote.iter().zip(outcomes) {
+                        if let Err(e) = outcome {
+                            results[i].fail(e);
+                        }
+                    }
+                }
+                Err(e) if task_queue::is_offline(&e) => {
+                    eprintln!("📴 Google Tasks unreachable, queueing {} task changes", remote.len());
+                    offline = true;
+                    queued.extend(remote);
+                }
+                Err(e) => {
+                    for &i in &remote {
+                        results[i].fail(&e);
+                    }
+                }
+            }
+        }
+
+        if !queued.is_empty() {
+            let mut conn = db_manager.get_connection()?;
+            for i in queued {
+                queue(&mut conn, account_id, &mut results[i], operations);
+            }
+        }
+    }
+
+    // Moves to another list
+    if let Some(destination) = destination {
+        let moving: Vec<usize> = (0..results.len())
+            .filter(|&i| results[i].success && results[i].task_list_id != destination)
+            .collect();
+        if offline {
+            for &i in &moving {
+                results[i].fail("Google Tasks is unreachable; the task was not moved");
+            }
+        } else if !moving.is_empty() {
+            let parts: Vec<BatchPart> = moving
+                .iter()
+                .map(|&i| {
+                    let path = format!(
+                        "{}/move?destinationTasklist={}",
+                        task_path(&results[i].task_list_id, &results[i].task_id),
+                        urlencoding::encode(destination)
+                    );
+                    BatchPart::new("POST", path, None)
+                })
+                .collect();
+            let outcomes = match tasks_service.batch(account_id, &parts).await {
+                Ok(outcomes) => outcomes.into_iter().map(|outcome| outcome.map(|_| ()).map_err(|e| e.to_string())).collect(),
+                Err(e) => {
+                    let message = e.to_string();
+                    moving.iter().map(|_| Err(message.clone())).collect::<Vec<_>>()
+                }
+            };
+            for (&i, outcome) in moving.iter().zip(outcomes) {
+                match outcome {
+                    Ok(()) => {
+                        results[i].task_list_id = destination.to_string();
+                        results[i].moved = true;
+                    }
+                    Err(e) => results[i].fail(format!("Failed to move the task: {}", e)),
+                }
+            }
+        }
+    }
+
+    if operations.delete {
+        let mut conn = db_manager.get_connection()?;
+        if let Err(e) = remove_deleted(&mut conn, &results) {
+            eprintln!("⚠️ Failed to remove local records of deleted tasks: {}", e);
+        }
+    }
+
+    if !operations.delete && (operations.changes_metadata() || results.iter().any(|result| result.moved)) {
+        let mut conn = db_manager.get_connection()?;
+        if let Err(e) = apply_metadata(&mut conn, &results, operations) {
+            for result in results.iter_mut().filter(|result| result.success) {
+                result.fail(format!("Failed to save priority and labels: {}", e));
+            }
+        }
+    }
+
+    let succeeded = results.iter().filter(|result| result.success).count();
+    Ok(BulkUpdateSummary { failed: results.len() - succeeded, succeeded, results })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    fn label(name: &str, color: &str) -> SimpleLabel {
+        SimpleLabel { name: name.to_string(), color: color.to_string() }
+    }
+
+    fn ok(task_id: &str, task_list_id: &str) -> BulkTaskResult {
+        BulkTaskResult {
+            task_id: task_id.to_string(),
+            task_list_id: task_list_id.to_string(),
+            success: true,
+            pending_sync: false,
+            error: None,
+            moved: false,
+        }
+    }
+
+    #[test]
+    fn test_validate_operations() {
+        assert!(BulkTaskOperations::default().validate().is_err());
+        let delete_and_complete = BulkTaskOperations { delete: true, complete: Some(true), ..Default::default() };
+        assert!(delete_and_complete.validate().is_err());
+        assert!(BulkTaskOperations { delete: true, ..Default::default() }.validate().is_ok());
+        assert!(BulkTaskOperations { remove_labels: vec!["x".to_string()], ..Default::default() }.validate().is_ok());
+    }
+
+    #[test]
+    fn test_merge_labels() {
+        let merged = merge_labels(
+            vec![label("Work", "#111"), label("Home", "#222")],
+            &[label("work", "#333"), label("Urgent", "#444")],
+            &["HOME".to_string()],
+        );
+        assert_eq!(merged.iter().map(|l| (l.name.as_str(), l.color.as_str())).collect::<Vec<_>>(), [("Work", "#333"), ("Urgent", "#444")]);
+    }
+
+    #[test]
+    fn test_apply_metadata_in_one_transaction() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO task_metadata (google_task_id, task_list_id, priority, labels_json) VALUES ('a', 'l1', 'low', ?1)",
+            params![serde_json::to_string(&[label("Home", "#222")]).unwrap()],
+        )
+        .unwrap();
+
+        let operations = BulkTaskOperations {
+            priority: Some("high".to_string()),
+            add_labels: vec![label("Q4", "#f00")],
+            ..Default::default()
+        };
+        let mut moved = ok("a", "l2");
+        moved.moved = true;
+        let mut failed = ok("c", "l1");
+        failed.fail("rejected");
+        apply_metadata(&mut conn, &[moved, ok("b", "l1"), failed], &operations).unwrap();
+
+        let row = |id: &str| -> Option<(String, String, String)> {
+            conn.query_row(
+                "SELECT task_list_id, priority, labels_json FROM task_metadata WHERE google_task_id = ?1",
+                params![id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()
+            .unwrap()
+        };
+        let (list, priority, labels) = row("a").unwrap();
+        assert_eq!((list.as_str(), priority.as_str()), ("l2", "high"));
+        assert_eq!(serde_json::from_str::<Vec<SimpleLabel>>(&labels).unwrap().len(), 2);
+        assert_eq!(row("b").unwrap().1, "high");
+        assert!(row("c").is_none());
+
+        let history = change_journal_operations::get_entity_history(&conn, "task_metadata", "a").unwrap();
+        assert_eq!(history[0].operation, "update");
+        assert_eq!(history[0].previous_payload.as_ref().unwrap()["priority"], "low");
+    }
+
+    #[test]
+    fn test_remove_deleted_keeps_failed_and_queued() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        for id in ["a", "b", "c"] {
+            conn.execute(
+                "INSERT INTO task_id_map (local_id, google_task_id, task_list_id) VALUES (?1, ?1, 'l1')",
+                params![id],
+            )
+            .unwrap();
+            conn.execute("INSERT INTO task_metadata (google_task_id, task_list_id) VALUES (?1, 'l1')", params![id]).unwrap();
+        }
+
+        let mut failed = ok("b", "l1");
+        failed.fail("rejected");
+        let mut queued = ok("c", "l1");
+        queued.pending_sync = true;
+        remove_deleted(&mut conn, &[ok("a", "l1"), failed, queued]).unwrap();
+
+        let remaining = |table: &str| -> Vec<String> {
+            let mut stmt = conn.prepare(&format!("SELECT google_task_id FROM {} ORDER BY google_task_id", table)).unwrap();
+            stmt.query_map([], |row| row.get(0)).unwrap().collect::<rusqlite::Result<_>>().unwrap()
+        };
+        assert_eq!(remaining("task_id_map"), ["b", "c"]);
+        assert_eq!(remaining("task_metadata"), ["b", "c"]);
+    }
+}
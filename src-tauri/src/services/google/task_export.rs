@@ -0,0 +1,423 @@
+//! Task list and agenda export
+//!
+//! Renders a task list, or one day's agenda of calendar events and tasks,
+//! as Markdown (for pasting into chats and notes), CSV (for spreadsheets)
+//! or a standalone HTML page styled for printing. The renderers work on
+//! plain structs so the commands decide where the data comes from.
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::task_metadata::TimeBlock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Markdown,
+    Csv,
+    Html,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Html => "html",
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "text/markdown",
+            ExportFormat::Csv => "text/csv",
+            ExportFormat::Html => "text/html",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    #[serde(default)]
+    pub include_completed: bool,
+    #[serde(default = "default_true")]
+    pub include_labels: bool,
+    #[serde(default = "default_true")]
+    pub include_time_blocks: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportTask {
+    pub title: String,
+    pub notes: Option<String>,
+    /// RFC 3339 due date as returned by Google Tasks
+    pub due: Option<String>,
+    pub completed: bool,
+    pub priority: String,
+    pub labels: Vec<String>,
+    pub time_block: Option<TimeBlock>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgendaEvent {
+    pub title: String,
+    /// Local start and end, "HH:MM"; unset for all-day events
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub location: Option<String>,
+}
+
+/// A rendered export, ready to save or share
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedDocument {
+    pub filename: String,
+    pub mime_type: String,
+    pub content: String,
+}
+
+impl ExportedDocument {
+    pub fn new(name: &str, format: ExportFormat, content: String) -> Self {
+        Self {
+            filename: format!("{}.{}", file_stem(name), format.extension()),
+            mime_type: format.mime_type().to_string(),
+            content,
+        }
+    }
+}
+
+pub fn render_task_list(title: &str, tasks: &[ExportTask], options: &ExportOptions) -> String {
+    let tasks: Vec<&ExportTask> = tasks.iter().filter(|t| options.include_completed || !t.completed).collect();
+    match options.format {
+        ExportFormat::Markdown => {
+            let mut out = format!("# {}\n\n", title);
+            if tasks.is_empty() {
+                out.push_str("_No tasks._\n");
+            }
+            for task in &tasks {
+                out.push_str(&markdown_task(task, options, true));
+            }
+            out
+        }
+        ExportFormat::Csv => {
+            let mut out = csv_row(&task_csv_header(options, false));
+            for task in &tasks {
+                out.push_str(&csv_row(&task_csv_fields(task, options, None)));
+            }
+            out
+        }
+        ExportFormat::Html => {
+            let body = format!("<h1>{}</h1>\n{}", escape_html(title), html_task_list(&tasks, options, true));
+            html_document(title, &body)
+        }
+    }
+}
+
+/// Render one day: events in time order (all-day first), then the tasks
+/// due that day
+pub fn render_agenda(date_label: &str, events: &[AgendaEvent], tasks: &[ExportTask], options: &ExportOptions) -> String {
+    let mut events: Vec<&AgendaEvent> = events.iter().collect();
+    events.sort_by(|a, b| a.start.cmp(&b.start));
+    let tasks: Vec<&ExportTask> = tasks.iter().filter(|t| options.include_completed || !t.completed).collect();
+    let title = format!("Agenda for {}", date_label);
+
+    match options.format {
+        ExportFormat::Markdown => {
+            let mut out = format!("# {}\n\n## Schedule\n\n", title);
+            if events.is_empty() {
+                out.push_str("_Nothing scheduled._\n");
+            }
+            for event in &events {
+                out.push_str(&format!("- **{}** {}", event_time(event), event.title));
+                if let Some(location) = event.location.as_deref().filter(|l| !l.is_empty()) {
+                    out.push_str(&format!(" ({})", location));
+                }
+                out.push('\n');
+            }
+            out.push_str("\n## Tasks\n\n");
+            if tasks.is_empty() {
+                out.push_str("_No tasks due._\n");
+            }
+            for task in &tasks {
+                out.push_str(&markdown_task(task, options, false));
+            }
+            out
+        }
+        ExportFormat::Csv => {
+            let header = task_csv_header(options, true);
+            let mut out = csv_row(&header);
+            for event in &events {
+                let mut fields = vec![
+                    "event".to_string(),
+                    event.title.clone(),
+                    event.start.clone().unwrap_or_else(|| "all day".to_string()),
+                    event.end.clone().unwrap_or_default(),
+                    event.location.clone().unwrap_or_default(),
+                ];
+                fields.resize(header.len(), String::new());
+                out.push_str(&csv_row(&fields));
+            }
+            for task in &tasks {
+                out.push_str(&csv_row(&task_csv_fields(task, options, Some("task"))));
+            }
+            out
+        }
+        ExportFormat::Html => {
+            let mut body = format!("<h1>{}</h1>\n<h2>Schedule</h2>\n", escape_html(&title));
+            if events.is_empty() {
+                body.push_str("<p class=\"empty\">Nothing scheduled.</p>\n");
+            } else {
+                body.push_str("<table class=\"schedule\">\n");
+                for event in &events {
+                    body.push_str(&format!(
+                        "<tr><td class=\"time\">{}</td><td>{}{}</td></tr>\n",
+                        escape_html(&event_time(event)),
+                        escape_html(&event.title),
+                        event
+                            .location
+                            .as_deref()
+                            .filter(|l| !l.is_empty())
+                            .map(|l| format!(" <span class=\"meta\">{}</span>", escape_html(l)))
+                            .unwrap_or_default()
+                    ));
+                }
+                body.push_str("</table>\n");
+            }
+            body.push_str("<h2>Tasks</h2>\n");
+            body.push_str(&html_task_list(&tasks, options, false));
+            html_document(&title, &body)
+        }
+    }
+}
+
+fn event_time(event: &AgendaEvent) -> String {
+    match (&event.start, &event.end) {
+        (Some(start), Some(end)) => format!("{}–{}", start, end),
+        (Some(start), None) => start.clone(),
+        _ => "All day".to_string(),
+    }
+}
+
+fn due_date(task: &ExportTask) -> Option<&str> {
+    task.due.as_deref().map(|due| due.get(..10).unwrap_or(due))
+}
+
+fn time_block_label(block: &TimeBlock) -> String {
+    let short = |value: &str| {
+        chrono::DateTime::parse_from_rfc3339(value)
+            .map(|t| t.format("%H:%M").to_string())
+            .unwrap_or_else(|_| value.to_string())
+    };
+    format!("{}–{}", short(&block.start_time), short(&block.end_time))
+}
+
+/// Extra details shown after a task title: due date, priority, labels, time block
+fn task_details(task: &ExportTask, options: &ExportOptions, with_due: bool) -> Vec<String> {
+    let mut details = Vec::new();
+    if with_due {
+        if let Some(due) = due_date(task) {
+            details.push(format!("due {}", due));
+        }
+    }
+    if !matches!(task.priority.as_str(), "" | "none" | "normal") {
+        details.push(format!("{} priority", task.priority));
+    }
+    if options.include_time_blocks {
+        if let Some(block) = &task.time_block {
+            details.push(time_block_label(block));
+        }
+    }
+    if options.include_labels && !task.labels.is_empty() {
+        details.push(task.labels.iter().map(|l| format!("#{}", l)).collect::<Vec<_>>().join(" "));
+    }
+    details
+}
+
+fn markdown_task(task: &ExportTask, options: &ExportOptions, with_due: bool) -> String {
+    let mut line = format!("- [{}] {}", if task.completed { "x" } else { " " }, task.title);
+    let details = task_details(task, options, with_due);
+    if !details.is_empty() {
+        line.push_str(&format!(" — {}", details.join(" · ")));
+    }
+    line.push('\n');
+    if let Some(notes) = task.notes.as_deref().filter(|n| !n.trim().is_empty()) {
+        for note_line in notes.lines() {
+            line.push_str(&format!("  {}\n", note_line));
+        }
+    }
+    line
+}
+
+fn html_task_list(tasks: &[&ExportTask], options: &ExportOptions, with_due: bool) -> String {
+    if tasks.is_empty() {
+        return "<p class=\"empty\">No tasks.</p>\n".to_string();
+    }
+    let mut out = String::from("<ul class=\"tasks\">\n");
+    for task in tasks {
+        let details = task_details(task, options, with_due);
+        out.push_str(&format!(
+            "<li class=\"{}\"><span class=\"box\">{}</span> {}",
+            if task.completed { "done" } else { "open" },
+            if task.completed { "☑" } else { "☐" },
+            escape_html(&task.title)
+        ));
+        if !details.is_empty() {
+            out.push_str(&format!(" <span class=\"meta\">{}</span>", escape_html(&details.join(" · "))));
+        }
+        if let Some(notes) = task.notes.as_deref().filter(|n| !n.trim().is_empty()) {
+            out.push_str(&format!("<div class=\"notes\">{}</div>", escape_html(notes).replace('\n', "<br>")));
+        }
+        out.push_str("</li>\n");
+    }
+    out.push_str("</ul>\n");
+    out
+}
+
+fn html_document(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n\
+         body {{ font-family: -apple-system, 'Segoe UI', sans-serif; max-width: 42rem; margin: 2rem auto; color: #111; }}\n\
+         h1 {{ font-size: 1.5rem; }} h2 {{ font-size: 1.1rem; margin-top: 1.5rem; border-bottom: 1px solid #ccc; }}\n\
+         ul.tasks {{ list-style: none; padding: 0; }} ul.tasks li {{ padding: 0.3rem 0; break-inside: avoid; }}\n\
+         li.done {{ color: #777; text-decoration: line-through; }}\n\
+         .meta {{ color: #666; font-size: 0.85em; }} .notes {{ margin-left: 1.5rem; color: #444; font-size: 0.9em; }}\n\
+         table.schedule td {{ padding: 0.2rem 0.75rem 0.2rem 0; vertical-align: top; }} td.time {{ white-space: nowrap; font-weight: 600; }}\n\
+         .empty {{ color: #777; font-style: italic; }}\n\
+         @media print {{ body {{ margin: 0; }} }}\n\
+         </style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape_html(title),
+        body
+    )
+}
+
+fn task_csv_header(options: &ExportOptions, with_type: bool) -> Vec<String> {
+    let mut header: Vec<&str> = if with_type {
+        vec!["type", "title", "start", "end", "location", "status", "due", "priority", "notes"]
+    } else {
+        vec!["title", "status", "due", "priority", "notes"]
+    };
+    if options.include_labels {
+        header.push("labels");
+    }
+    if options.include_time_blocks {
+        header.extend(["time_block_start", "time_block_end"]);
+    }
+    header.into_iter().map(str::to_string).collect()
+}
+
+fn task_csv_fields(task: &ExportTask, options: &ExportOptions, row_type: Option<&str>) -> Vec<String> {
+    let mut fields = Vec::new();
+    if let Some(row_type) = row_type {
+        fields.extend([row_type.to_string(), task.title.clone(), String::new(), String::new(), String::new()]);
+    } else {
+        fields.push(task.title.clone());
+    }
+    fields.extend([
+        if task.completed { "completed" } else { "open" }.to_string(),
+        due_date(task).unwrap_or_default().to_string(),
+        task.priority.clone(),
+        task.notes.clone().unwrap_or_default(),
+    ]);
+    if options.include_labels {
+        fields.push(task.labels.join("; "));
+    }
+    if options.include_time_blocks {
+        let block = task.time_block.as_ref();
+        fields.push(block.map(|b| b.start_time.clone()).unwrap_or_default());
+        fields.push(block.map(|b| b.end_time.clone()).unwrap_or_default());
+    }
+    fields
+}
+
+fn csv_row(fields: &[String]) -> String {
+    let mut row = fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(",");
+    row.push_str("\r\n");
+    row
+}
+
+/// Quote a CSV field when needed, and defuse values a spreadsheet would
+/// run as a formula
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+pub(crate) fn escape_html(value: &str) -> String {
+    value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+pub(crate) fn file_stem(name: &str) -> String {
+    let stem: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .collect();
+    let stem = stem.trim_matches('-');
+    if stem.is_empty() { "export".to_string() } else { stem.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(title: &str, completed: bool) -> ExportTask {
+        ExportTask {
+            title: title.to_string(),
+            notes: None,
+            due: Some("2024-06-03T00:00:00.000Z".to_string()),
+            completed,
+            priority: "high".to_string(),
+            labels: vec!["work".to_string()],
+            time_block: None,
+        }
+    }
+
+    fn options(format: ExportFormat) -> ExportOptions {
+        ExportOptions { format, include_completed: false, include_labels: true, include_time_blocks: true }
+    }
+
+    #[test]
+    fn renders_markdown_without_completed_tasks() {
+        let tasks = vec![task("Write report", false), task("Old item", true)];
+        let markdown = render_task_list("Work", &tasks, &options(ExportFormat::Markdown));
+        assert_eq!(markdown, "# Work\n\n- [ ] Write report — due 2024-06-03 · high priority · #work\n");
+    }
+
+    #[test]
+    fn escapes_csv_and_html() {
+        let mut tricky = task("=SUM(A1), \"quoted\"", false);
+        tricky.labels = vec![];
+        let csv = render_task_list("List", &[tricky.clone()], &options(ExportFormat::Csv));
+        assert!(csv.starts_with("title,status,due,priority,notes,labels,time_block_start,time_block_end\r\n"));
+        assert!(csv.contains("\"'=SUM(A1), \"\"quoted\"\"\",open,2024-06-03,high,,,,\r\n"));
+
+        tricky.title = "<script>".to_string();
+        let html = render_agenda("3 June", &[], &[tricky], &options(ExportFormat::Html));
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(html.contains("Nothing scheduled."));
+    }
+
+    #[test]
+    fn agenda_lists_all_day_events_first() {
+        let events = vec![
+            AgendaEvent { title: "Standup".to_string(), start: Some("09:30".to_string()), end: Some("09:45".to_string()), location: None },
+            AgendaEvent { title: "Holiday".to_string(), start: None, end: None, location: None },
+        ];
+        let markdown = render_agenda("2024-06-03", &events, &[], &options(ExportFormat::Markdown));
+        let holiday = markdown.find("Holiday").unwrap();
+        let standup = markdown.find("Standup").unwrap();
+        assert!(holiday < standup);
+        assert!(markdown.contains("- **09:30–09:45** Standup"));
+        assert_eq!(ExportedDocument::new("Agenda 2024/06/03", ExportFormat::Csv, String::new()).filename, "Agenda-2024-06-03.csv");
+    }
+}
@@ -0,0 +1,904 @@
+//! Task import from Todoist and TickTick
+//!
+//! Reads the files those apps export: Todoist project CSVs (alone or in
+//! the backup zip, one CSV per project), Todoist JSON from its API, and
+//! TickTick backup CSVs. Each project becomes a Google Tasks list, reusing
+//! a list with the same name; priorities and labels become task metadata.
+//! Google Tasks nests subtasks one level deep, so deeper subtasks are put
+//! under their top-level ancestor. Recurrence and due times are not
+//! imported; the summary says which tasks lost them.
+
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use chrono_tz::Tz;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::commands::tasks::metadata_simple::{self, SimpleLabel};
+use crate::database::operations::task_import_operations;
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::google::batch::BatchPart;
+use crate::services::google::tasks_service::{CreateTaskInput, GoogleTask, GoogleTasksService, TASKS_BATCH_LIMIT};
+use crate::services::sync::task_queue;
+
+/// Colour of labels that do not exist yet
+const DEFAULT_LABEL_COLOR: &str = "gray";
+/// List name for tasks whose project is unknown
+const DEFAULT_PROJECT: &str = "Imported";
+/// Largest export read, and the most a backup zip may unpack to
+pub const MAX_IMPORT_BYTES: usize = 50 * 1024 * 1024;
+/// Longest parent chain followed before giving up on a malformed file
+const MAX_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskImportFormat {
+    TodoistCsv,
+    TodoistJson,
+    TickTick,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TaskImportOptions {
+    /// Detected from the file when unset
+    pub format: Option<TaskImportFormat>,
+    /// Put every task in this list instead of one list per project
+    pub task_list_id: Option<String>,
+    /// Project name for a single Todoist CSV; the file name by default
+    pub project_name: Option<String>,
+    pub include_completed: bool,
+}
+
+/// A task read from an export file
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceTask {
+    /// ID in the source app, when the file has one
+    pub source_id: Option<String>,
+    pub parent_id: Option<String>,
+    pub project: String,
+    pub title: String,
+    pub notes: Option<String>,
+    pub due: Option<NaiveDate>,
+    /// "high", "medium", "low" or "none"
+    pub priority: String,
+    pub labels: Vec<String>,
+    pub completed: bool,
+    /// Why part of the task could not be imported
+    pub warning: Option<String>,
+}
+
+impl SourceTask {
+    fn new(project: &str, title: &str) -> Self {
+        Self {
+            source_id: None,
+            parent_id: None,
+            project: project.to_string(),
+            title: title.trim().to_string(),
+            notes: None,
+            due: None,
+            priority: "none".to_string(),
+            labels: Vec::new(),
+            completed: false,
+            warning: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkippedItem {
+    pub title: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedImport {
+    pub tasks: Vec<SourceTask>,
+    pub skipped: Vec<SkippedItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedTask {
+    pub task_id: String,
+    pub task_list_id: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskImportSummary {
+    pub format: TaskImportFormat,
+    pub imported: Vec<ImportedTask>,
+    /// Names of the lists created for projects
+    pub lists_created: Vec<String>,
+    pub skipped: Vec<SkippedItem>,
+    /// Imported tasks that lost a recurrence, due time or unreadable date
+    pub warnings: Vec<SkippedItem>,
+    /// Why the import stopped before the last task; importing the file
+    /// again picks up where it stopped
+    pub interrupted: Option<String>,
+}
+
+// =============================================================================
+// Parsing
+// =============================================================================
+
+/// Rows of a CSV file; quoted fields may hold commas, quotes and newlines
+pub fn parse_csv(content: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => row.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows.retain(|row| row.iter().any(|field| !field.trim().is_empty()));
+    rows
+}
+
+/// Guess the format of an export from its content
+pub fn detect_format(content: &str) -> Option<TaskImportFormat> {
+    let trimmed = content.trim_start_matches('\u{feff}').trim_start();
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        return Some(TaskImportFormat::TodoistJson);
+    }
+    let rows = parse_csv(content);
+    let has = |row: &Vec<String>, name: &str| row.iter().any(|field| field.trim() == name);
+    if rows.first().is_some_and(|row| has(row, "TYPE") && has(row, "CONTENT")) {
+        return Some(TaskImportFormat::TodoistCsv);
+    }
+    if rows.iter().any(|row| has(row, "Title") && has(row, "List Name")) {
+        return Some(TaskImportFormat::TickTick);
+    }
+    None
+}
+
+/// Column lookup by header name
+struct Columns(HashMap<String, usize>);
+
+impl Columns {
+    fn new(header: &[String]) -> Self {
+        Self(header.iter().enumerate().map(|(i, name)| (name.trim().to_lowercase(), i)).collect())
+    }
+
+    fn get<'a>(&self, row: &'a [String], name: &str) -> &'a str {
+        self.0.get(name).and_then(|&i| row.get(i)).map(|field| field.trim()).unwrap_or("")
+    }
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    Some(value.trim().to_string()).filter(|value| !value.is_empty())
+}
+
+/// A due date in one of the forms Todoist writes to CSV; relative and
+/// recurring dates like "tomorrow" or "every monday" are not understood
+fn todoist_csv_date(value: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    let date_part = value.split(['T', ' ']).next().unwrap_or(value);
+    if let Ok(date) = NaiveDate::parse_from_str(date_part, "%Y-%m-%d") {
+        return Some(date);
+    }
+    ["%b %d %Y", "%d %b %Y", "%B %d %Y", "%d %B %Y", "%b %d, %Y", "%B %d, %Y"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+}
+
+/// Todoist CSV priorities count from 1 (p1, the highest)
+fn todoist_csv_priority(value: &str) -> String {
+    match value.trim() {
+        "1" => "high",
+        "2" => "medium",
+        "3" => "low",
+        _ => "none",
+    }
+    .to_string()
+}
+
+/// Todoist API priorities count down from 4 (p1, the highest)
+fn todoist_api_priority(value: u8) -> String {
+    match value {
+        4 => "high",
+        3 => "medium",
+        2 => "low",
+        _ => "none",
+    }
+    .to_string()
+}
+
+/// Take `@label` words out of a Todoist task title
+fn split_todoist_labels(content: &str) -> (String, Vec<String>) {
+    let mut labels = Vec::new();
+    let words: Vec<&str> = content
+        .split_whitespace()
+        .filter(|word| match word.strip_prefix('@') {
+            Some(label) if !label.is_empty() => {
+                labels.push(label.to_string());
+                false
+            }
+            _ => true,
+        })
+        .collect();
+    (words.join(" "), labels)
+}
+
+/// One Todoist project CSV. Task rows become tasks, indented rows
+/// subtasks of the task above; note rows are comments, added to the notes
+/// of the task above.
+pub fn parse_todoist_csv(content: &str, project: &str) -> ParsedImport {
+    let rows = parse_csv(content);
+    let mut parsed = ParsedImport::default();
+    let Some((header, rows)) = rows.split_first() else {
+        return parsed;
+    };
+    let columns = Columns::new(header);
+    // Index of the latest task at each indent level
+    let mut by_indent: Vec<usize> = Vec::new();
+
+    for row in rows {
+        match columns.get(row, "type") {
+            "task" => {}
+            "note" => {
+                let text = columns.get(row, "content");
+                if let (Some(&last), false) = (by_indent.last(), text.is_empty()) {
+                    let notes = parsed.tasks[last].notes.get_or_insert_with(String::new);
+                    if !notes.is_empty() {
+                        notes.push_str("\n\n");
+                    }
+                    notes.push_str(text);
+                }
+                continue;
+            }
+            _ => continue,
+        }
+
+        let (title, labels) = split_todoist_labels(columns.get(row, "content"));
+        if title.is_empty() {
+            parsed.skipped.push(SkippedItem { title: String::new(), reason: "The task has no title".to_string() });
+            continue;
+        }
+        let index = parsed.tasks.len();
+        let mut task = SourceTask::new(project, &title);
+        task.source_id = Some(format!("row-{}", index));
+        task.notes = non_empty(columns.get(row, "description"));
+        task.priority = todoist_csv_priority(columns.get(row, "priority"));
+        task.labels = labels;
+
+        let date = columns.get(row, "date");
+        if !date.is_empty() {
+            task.due = todoist_csv_date(date);
+            if task.due.is_none() {
+                task.warning = Some(format!("The due date \"{}\" was not imported", date));
+            }
+        }
+
+        let indent = columns.get(row, "indent").parse::<usize>().unwrap_or(1).max(1);
+        by_indent.truncate(indent - 1);
+        task.parent_id = by_indent.last().and_then(|&parent| parsed.tasks[parent].source_id.clone());
+        by_indent.push(index);
+        parsed.tasks.push(task);
+    }
+    parsed
+}
+
+#[derive(Debug, Deserialize)]
+struct TodoistExport {
+    #[serde(default)]
+    projects: Vec<TodoistProject>,
+    #[serde(default, alias = "tasks")]
+    items: Vec<TodoistItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TodoistProject {
+    id: Value,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TodoistItem {
+    #[serde(default)]
+    id: Value,
+    content: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    project_id: Value,
+    #[serde(default)]
+    parent_id: Value,
+    #[serde(default)]
+    priority: u8,
+    due: Option<TodoistDue>,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default, alias = "is_completed")]
+    checked: bool,
+    #[serde(default)]
+    is_deleted: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct TodoistDue {
+    date: String,
+    #[serde(default)]
+    is_recurring: bool,
+    string: Option<String>,
+}
+
+/// Todoist IDs are strings in newer APIs and numbers in older ones
+fn id_key(id: &Value) -> Option<String> {
+    match id {
+        Value::String(id) if !id.is_empty() => Some(id.clone()),
+        Value::Number(id) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+/// Todoist JSON: `{projects, items}` from the Sync API, `{projects, tasks}`,
+/// or a bare array of tasks
+pub fn parse_todoist_json(content: &str, default_project: &str) -> Result<ParsedImport> {
+    let value: Value = serde_json::from_str(content.trim_start_matches('\u{feff}'))?;
+    let export: TodoistExport = match value {
+        Value::Array(items) => TodoistExport { projects: Vec::new(), items: serde_json::from_value(Value::Array(items))? },
+        value => serde_json::from_value(value)?,
+    };
+    let projects: HashMap<String, String> = export
+        .projects
+        .iter()
+        .filter_map(|project| Some((id_key(&project.id)?, project.name.clone())))
+        .collect();
+
+    let mut parsed = ParsedImport::default();
+    for item in export.items.into_iter().filter(|item| !item.is_deleted) {
+        let project = id_key(&item.project_id)
+            .and_then(|id| projects.get(&id).cloned())
+            .unwrap_or_else(|| default_project.to_string());
+        let (title, mut labels) = split_todoist_labels(&item.content);
+        if title.is_empty() {
+            parsed.skipped.push(SkippedItem { title: String::new(), reason: "The task has no title".to_string() });
+            continue;
+        }
+        labels.extend(item.labels);
+
+        let mut task = SourceTask::new(&project, &title);
+        task.source_id = id_key(&item.id);
+        task.parent_id = id_key(&item.parent_id);
+        task.notes = non_empty(&item.description);
+        task.priority = todoist_api_priority(item.priority);
+        task.labels = labels;
+        task.completed = item.checked;
+        if let Some(due) = item.due {
+            task.due = todoist_csv_date(&due.date);
+            let described = due.string.unwrap_or(due.date);
+            if due.is_recurring {
+                task.warning = Some(format!("The recurrence \"{}\" was not imported", described));
+            } else if task.due.is_none() {
+                task.warning = Some(format!("The due date \"{}\" was not imported", described));
+            }
+        }
+        parsed.tasks.push(task);
+    }
+    Ok(parsed)
+}
+
+/// TickTick priorities: 0 none, 1 low, 3 medium, 5 high
+fn ticktick_priority(value: &str) -> String {
+    match value.trim() {
+        "5" => "high",
+        "3" => "medium",
+        "1" => "low",
+        _ => "none",
+    }
+    .to_string()
+}
+
+/// TickTick writes due dates as UTC instants; all-day dates are midnight in
+/// the task's time zone
+fn ticktick_date(value: &str, zone: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    let instant = DateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%z")
+        .or_else(|_| DateTime::parse_from_rfc3339(value))
+        .ok();
+    match (instant, zone.parse::<Tz>()) {
+        (Some(instant), Ok(zone)) => Some(instant.with_timezone(&zone).date_naive()),
+        (Some(instant), Err(_)) => Some(instant.date_naive()),
+        (None, _) => NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+            .map(|time| time.date())
+            .or_else(|_| NaiveDate::parse_from_str(value, "%Y-%m-%d"))
+            .ok(),
+    }
+}
+
+/// A TickTick backup CSV; the header row follows a few lines describing
+/// the backup
+pub fn parse_ticktick(content: &str) -> ParsedImport {
+    let rows = parse_csv(content);
+    let mut parsed = ParsedImport::default();
+    let Some(header_index) = rows
+        .iter()
+        .position(|row| row.iter().any(|field| field.trim() == "Title") && row.iter().any(|field| field.trim() == "List Name"))
+    else {
+        return parsed;
+    };
+    let columns = Columns::new(&rows[header_index]);
+
+    for row in &rows[header_index + 1..] {
+        let title = columns.get(row, "title");
+        if columns.get(row, "kind").eq_ignore_ascii_case("note") {
+            parsed.skipped.push(SkippedItem { title: title.to_string(), reason: "Notes are not tasks".to_string() });
+            continue;
+        }
+        if title.is_empty() {
+            parsed.skipped.push(SkippedItem { title: String::new(), reason: "The task has no title".to_string() });
+            continue;
+        }
+
+        let project = non_empty(columns.get(row, "list name")).unwrap_or_else(|| DEFAULT_PROJECT.to_string());
+        let mut task = SourceTask::new(&project, title);
+        task.source_id = non_empty(columns.get(row, "taskid"));
+        task.parent_id = non_empty(columns.get(row, "parentid"));
+        task.notes = non_empty(columns.get(row, "content"));
+        task.priority = ticktick_priority(columns.get(row, "priority"));
+        task.labels = columns.get(row, "tags").split(',').filter_map(non_empty).map(|tag| tag.trim_start_matches('#').to_string()).collect();
+        task.completed = matches!(columns.get(row, "status"), "1" | "2");
+        task.due = ticktick_date(columns.get(row, "due date"), columns.get(row, "timezone"));
+
+        let repeat = columns.get(row, "repeat");
+        if !repeat.is_empty() {
+            task.warning = Some(format!("The recurrence \"{}\" was not imported", repeat));
+        } else if task.due.is_none() && !columns.get(row, "due date").is_empty() {
+            task.warning = Some(format!("The due date \"{}\" was not imported", columns.get(row, "due date")));
+        }
+        parsed.tasks.push(task);
+    }
+    parsed
+}
+
+/// All of `reader`, refused past [`MAX_IMPORT_BYTES`]
+fn read_bounded(reader: impl Read) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    // One byte past the limit tells a file at the limit from a larger one
+    reader.take(MAX_IMPORT_BYTES as u64 + 1).read_to_end(&mut bytes)?;
+    if bytes.len() > MAX_IMPORT_BYTES {
+        return Err(too_large());
+    }
+    Ok(bytes)
+}
+
+fn too_large() -> LibreOllamaError {
+    LibreOllamaError::InvalidInput {
+        message: format!("The export is larger than {} MB", MAX_IMPORT_BYTES / (1024 * 1024)),
+        field: Some("path".to_string()),
+    }
+}
+
+/// Parse an export file: a Todoist backup zip, a CSV or a JSON file
+pub fn parse_file(path: &Path, options: &TaskImportOptions) -> Result<(TaskImportFormat, ParsedImport)> {
+    let bytes = read_bounded(std::fs::File::open(path)?)?;
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+    let project = options
+        .project_name
+        .clone()
+        .and_then(|name| non_empty(&name))
+        .or_else(|| non_empty(&stem))
+        .unwrap_or_else(|| DEFAULT_PROJECT.to_string());
+
+    if bytes.starts_with(b"PK") {
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| LibreOllamaError::InvalidInput {
+            message: format!("Could not open the backup: {}", e),
+            field: Some("path".to_string()),
+        })?;
+        let mut parsed = ParsedImport::default();
+        // Unpacked size so far, so a zip bomb is refused
+        let mut total = 0;
+        for index in 0..archive.len() {
+            let mut file = archive.by_index(index).map_err(|e| LibreOllamaError::Internal {
+                message: format!("Could not read the backup: {}", e),
+            })?;
+            let name = file.name().to_string();
+            if !name.to_lowercase().ends_with(".csv") {
+                continue;
+            }
+            let bytes = read_bounded(&mut file)?;
+            total += bytes.len();
+            if total > MAX_IMPORT_BYTES {
+                return Err(too_large());
+            }
+            let content = String::from_utf8_lossy(&bytes);
+            let project = Path::new(&name).file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+            let project = project.split(" [").next().unwrap_or(&project).trim().to_string();
+            let file_parsed = parse_todoist_csv(&content, &project);
+            parsed.tasks.extend(file_parsed.tasks.into_iter().map(|mut task| {
+                // Row IDs restart in every file
+                task.source_id = task.source_id.map(|id| format!("{}/{}", name, id));
+                task.parent_id = task.parent_id.map(|id| format!("{}/{}", name, id));
+                task
+            }));
+            parsed.skipped.extend(file_parsed.skipped);
+        }
+        return Ok((TaskImportFormat::TodoistCsv, parsed));
+    }
+
+    let content = String::from_utf8_lossy(&bytes);
+    let format = options.format.or_else(|| detect_format(&content)).ok_or_else(|| LibreOllamaError::InvalidInput {
+        message: "The file is not a Todoist or TickTick export".to_string(),
+        field: Some("path".to_string()),
+    })?;
+    let parsed = match format {
+        TaskImportFormat::TodoistCsv => parse_todoist_csv(&content, &project),
+        TaskImportFormat::TodoistJson => parse_todoist_json(&content, &project)?,
+        TaskImportFormat::TickTick => parse_ticktick(&content),
+    };
+    Ok((format, parsed))
+}
+
+// =============================================================================
+// Import
+// =============================================================================
+
+/// Each task's parent as an index, lifted to the top-level ancestor since
+/// Google Tasks nests one level deep. Unknown parents leave a task at the
+/// top level.
+pub fn top_level_parents(tasks: &[SourceTask]) -> Vec<Option<usize>> {
+    let by_id: HashMap<&str, usize> = tasks
+        .iter()
+        .enumerate()
+        .filter_map(|(i, task)| Some((task.source_id.as_deref()?, i)))
+        .collect();
+    let parent_of = |i: usize| tasks[i].parent_id.as_deref().and_then(|id| by_id.get(id).copied()).filter(|&p| p != i);
+
+    (0..tasks.len())
+        .map(|i| {
+            let mut top = parent_of(i)?;
+            for _ in 0..MAX_DEPTH {
+                match parent_of(top) {
+                    Some(parent) if parent != i => top = parent,
+                    _ => break,
+                }
+            }
+            Some(top)
+        })
+        .collect()
+}
+
+/// Label colours already in use, by lowercased name
+fn known_label_colors(conn: &Connection) -> anyhow::Result<HashMap<String, String>> {
+    let mut stmt = conn.prepare("SELECT labels_json FROM task_metadata WHERE labels_json IS NOT NULL")?;
+    let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    let mut colors = HashMap::new();
+    for labels in rows {
+        for label in serde_json::from_str::<Vec<SimpleLabel>>(&labels?).unwrap_or_default() {
+            colors.entry(label.name.to_lowercase()).or_insert(label.color);
+        }
+    }
+    Ok(colors)
+}
+
+/// Key a row is recorded under once imported. Rows with an ID in the
+/// source app use it; Todoist CSV rows, numbered only by their place in
+/// the file, use their project, title and due date, counted so repeats of
+/// the same task stay apart.
+pub fn import_keys(format: TaskImportFormat, tasks: &[SourceTask]) -> Vec<String> {
+    let prefix = match format {
+        TaskImportFormat::TodoistCsv => "todoist_csv",
+        TaskImportFormat::TodoistJson => "todoist_json",
+        TaskImportFormat::TickTick => "ticktick",
+    };
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    tasks
+        .iter()
+        .map(|task| match &task.source_id {
+            Some(id) if format != TaskImportFormat::TodoistCsv => format!("{}:{}", prefix, id),
+            _ => {
+                let content = format!(
+                    "{}/{}/{}",
+                    task.project.trim().to_lowercase(),
+                    task.title,
+                    task.due.map(|due| due.to_string()).unwrap_or_default()
+                );
+                let count = seen.entry(content.clone()).or_insert(0);
+                *count += 1;
+                format!("{}:{}#{}", prefix, content, count)
+            }
+        })
+        .collect()
+}
+
+/// Create the tasks of a parsed export in Google Tasks, in batch calls:
+/// top-level tasks first, then subtasks under them. Google may run the
+/// requests of a batch in any order, so tasks can land in a list in a
+/// different order than in the file. Rows imported before are skipped.
+/// When Google becomes unreachable the import stops, and the summary says
+/// so and lists what was created until then.
+pub async fn import_tasks(
+    tasks_service: &GoogleTasksService,
+    db_manager: &std::sync::Arc<DatabaseManager>,
+    account_id: &str,
+    format: TaskImportFormat,
+    parsed: ParsedImport,
+    options: &TaskImportOptions,
+) -> Result<TaskImportSummary> {
+    let ParsedImport { tasks, mut skipped } = parsed;
+    let mut summary = TaskImportSummary {
+        format,
+        imported: Vec::new(),
+        lists_created: Vec::new(),
+        skipped: Vec::new(),
+        warnings: Vec::new(),
+        interrupted: None,
+    };
+
+    let keys = import_keys(format, &tasks);
+    let (previous, label_colors) = {
+        let conn = db_manager.get_connection()?;
+        (task_import_operations::imported_tasks(&conn, account_id)?, known_label_colors(&conn)?)
+    };
+
+    // Google task and list of each row, created now or by an earlier import
+    let mut created: HashMap<usize, (String, String)> = HashMap::new();
+    let mut pending = Vec::new();
+    for (i, task) in tasks.iter().enumerate() {
+        if !options.include_completed && task.completed {
+            skipped.push(SkippedItem { title: task.title.clone(), reason: "The task is completed".to_string() });
+        } else if let Some(existing) = previous.get(&keys[i]) {
+            skipped.push(SkippedItem { title: task.title.clone(), reason: "The task was imported before".to_string() });
+            created.insert(i, existing.clone());
+        } else {
+            pending.push(i);
+        }
+    }
+    let parents = top_level_parents(&tasks);
+
+    // One list per project
+    let mut list_of: HashMap<usize, String> = HashMap::new();
+    match &options.task_list_id {
+        Some(id) => list_of.extend(pending.iter().map(|&i| (i, id.clone()))),
+        None if !pending.is_empty() => {
+            let mut lists: HashMap<String, String> = HashMap::new();
+            for list in tasks_service.get_task_lists(account_id).await? {
+                lists.entry(list.title.trim().to_lowercase()).or_insert(list.id);
+            }
+            for &i in &pending {
+                let task = &tasks[i];
+                let key = task.project.trim().to_lowercase();
+                if let Some(id) = lists.get(&key) {
+                    list_of.insert(i, id.clone());
+                    continue;
+                }
+                match tasks_service.create_task_list(account_id, task.project.trim()).await {
+                    Ok(list) => {
+                        summary.lists_created.push(list.title.clone());
+                        lists.insert(key, list.id.clone());
+                        list_of.insert(i, list.id);
+                    }
+                    Err(e) if task_queue::is_offline(&e) => {
+                        summary.interrupted = Some(format!("Google Tasks became unreachable: {}", e));
+                        break;
+                    }
+                    Err(e) => skipped.push(SkippedItem { title: task.title.clone(), reason: format!("Could not create the list: {}", e) }),
+                }
+            }
+        }
+        None => {}
+    }
+
+    // Google adds a new task above its siblings, so each round creates
+    // its tasks last first
+    let (top_level, subtasks): (Vec<usize>, Vec<usize>) =
+        pending.into_iter().filter(|i| list_of.contains_key(i)).rev().partition(|&i| parents[i].is_none());
+    'rounds: for round in [top_level, subtasks] {
+        if summary.interrupted.is_some() {
+            break;
+        }
+        let mut jobs = Vec::new();
+        for i in round {
+            let task = &tasks[i];
+            let task_list_id = list_of[&i].clone();
+            // A subtask whose parent is in another list or was not created
+            // goes to the top level
+            let parent = parents[i]
+                .and_then(|p| created.get(&p))
+                .filter(|(_, list)| *list == task_list_id)
+                .map(|(id, _)| id.as_str());
+            let input = CreateTaskInput {
+                title: task.title.clone(),
+                notes: task.notes.clone(),
+                due: task.due.map(|due| due.format("%Y-%m-%d").to_string()),
+                status: Some(if task.completed { "completed" } else { "needsAction" }.to_string()),
+            };
+            match tasks_service.create_task_part(&task_list_id, input, parent) {
+                Ok(part) => jobs.push((i, task_list_id, part)),
+                Err(e) => skipped.push(SkippedItem { title: task.title.clone(), reason: e.to_string() }),
+            }
+        }
+
+        for chunk in jobs.chunks(TASKS_BATCH_LIMIT) {
+            let parts: Vec<BatchPart> = chunk.iter().map(|(_, _, part)| part.clone()).collect();
+            let outcomes = match tasks_service.batch(account_id, &parts).await {
+                Ok(outcomes) => outcomes,
+                Err(e) if task_queue::is_offline(&e) => {
+                    summary.interrupted = Some(format!("Google Tasks became unreachable: {}", e));
+                    break 'rounds;
+                }
+                Err(e) => chunk.iter().map(|_| Err(LibreOllamaError::GoogleTasksApi { message: e.to_string() })).collect(),
+            };
+
+            for ((i, task_list_id, _), outcome) in chunk.iter().zip(outcomes) {
+                let task = &tasks[*i];
+                let google_task = match outcome.and_then(|value| Ok(serde_json::from_value::<GoogleTask>(value)?)) {
+                    Ok(google_task) => google_task,
+                    Err(e) => {
+                        skipped.push(SkippedItem { title: task.title.clone(), reason: format!("Google Tasks rejected the task: {}", e) });
+                        continue;
+                    }
+                };
+                if let Err(e) = db_manager
+                    .get_connection()
+                    .and_then(|conn| task_import_operations::record_import(&conn, account_id, &keys[*i], &google_task.id, task_list_id))
+                {
+                    eprintln!("⚠️ [TASK_IMPORT] Failed to record the import of {}: {}", google_task.id, e);
+                }
+
+                if task.priority != "none" || !task.labels.is_empty() {
+                    let labels = task
+                        .labels
+                        .iter()
+                        .map(|name| SimpleLabel {
+                            name: name.clone(),
+                            color: label_colors.get(&name.to_lowercase()).cloned().unwrap_or_else(|| DEFAULT_LABEL_COLOR.to_string()),
+                        })
+                        .collect();
+                    if let Err(e) = metadata_simple::create_or_update_metadata(
+                        google_task.id.clone(),
+                        task_list_id.clone(),
+                        Some(task.priority.clone()),
+                        Some(labels),
+                        None,
+                        db_manager.clone(),
+                    )
+                    .await
+                    {
+                        eprintln!("⚠️ [TASK_IMPORT] Failed to save priority and labels of {}: {}", google_task.id, e);
+                    }
+                }
+
+                if let Some(warning) = &task.warning {
+                    summary.warnings.push(SkippedItem { title: task.title.clone(), reason: warning.clone() });
+                }
+                created.insert(*i, (google_task.id.clone(), task_list_id.clone()));
+                summary.imported.push(ImportedTask { task_id: google_task.id, task_list_id: task_list_id.clone(), title: task.title.clone() });
+            }
+        }
+    }
+
+    summary.skipped = skipped;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv_quotes() {
+        let rows = parse_csv("\u{feff}a,\"b, c\",\"say \"\"hi\"\"\"\r\n\"multi\nline\",,x\r\n\r\n");
+        assert_eq!(rows, vec![
+            vec!["a".to_string(), "b, c".to_string(), "say \"hi\"".to_string()],
+            vec!["multi\nline".to_string(), String::new(), "x".to_string()],
+        ]);
+    }
+
+    #[test]
+    fn test_todoist_csv() {
+        let csv = "TYPE,CONTENT,DESCRIPTION,PRIORITY,INDENT,AUTHOR,RESPONSIBLE,DATE,DATE_LANG,TIMEZONE\n\
+            section,Q4,,,,,,,,\n\
+            task,Plan launch @work @q4,Draft the plan,1,1,Ada,,2026-10-20,en,Europe/Berlin\n\
+            task,Book venue,,4,2,Ada,,every monday,en,Europe/Berlin\n\
+            note,Call first,,,,Ada,,,,\n\
+            task,Deep child,,3,3,Ada,,Oct 22 2026,en,\n\
+            task,Second,,2,1,Ada,,,en,\n";
+        assert_eq!(detect_format(csv), Some(TaskImportFormat::TodoistCsv));
+        let parsed = parse_todoist_csv(csv, "Launch");
+        assert_eq!(parsed.tasks.len(), 4);
+
+        let plan = &parsed.tasks[0];
+        assert_eq!((plan.title.as_str(), plan.priority.as_str()), ("Plan launch", "high"));
+        assert_eq!(plan.labels, ["work", "q4"]);
+        assert_eq!(plan.due, NaiveDate::from_ymd_opt(2026, 10, 20));
+        assert_eq!(plan.project, "Launch");
+
+        let venue = &parsed.tasks[1];
+        assert_eq!(venue.parent_id, plan.source_id);
+        assert_eq!(venue.notes.as_deref(), Some("Call first"));
+        assert!(venue.due.is_none() && venue.warning.is_some());
+        assert_eq!(parsed.tasks[2].due, NaiveDate::from_ymd_opt(2026, 10, 22));
+        assert_eq!(parsed.tasks[3].parent_id, None);
+
+        // Google nests one level deep
+        assert_eq!(top_level_parents(&parsed.tasks), [None, Some(0), Some(0), None]);
+    }
+
+    #[test]
+    fn test_import_keys_survive_a_rerun() {
+        let mut call = SourceTask::new("Home", "Call mom");
+        call.source_id = Some("row-0".to_string());
+        let mut again = call.clone();
+        again.source_id = Some("row-1".to_string());
+        let keys = import_keys(TaskImportFormat::TodoistCsv, &[call.clone(), again]);
+        // Repeats stay apart, and do not depend on row numbers
+        assert_ne!(keys[0], keys[1]);
+        assert_eq!(import_keys(TaskImportFormat::TodoistCsv, &[call.clone()])[0], keys[0]);
+
+        call.source_id = Some("7".to_string());
+        assert_eq!(import_keys(TaskImportFormat::TickTick, &[call]), ["ticktick:7"]);
+    }
+
+    #[test]
+    fn test_todoist_json() {
+        let json = r#"{
+            "projects": [{ "id": "220474322", "name": "Errands" }],
+            "items": [
+                { "id": "1", "content": "Buy milk", "project_id": "220474322", "priority": 4, "labels": ["shop"],
+                  "due": { "date": "2026-10-18", "is_recurring": true, "string": "every sat" } },
+                { "id": 2, "content": "Oat milk", "project_id": 220474322, "parent_id": "1", "checked": true },
+                { "id": "3", "content": "Old", "is_deleted": true }
+            ]
+        }"#;
+        assert_eq!(detect_format(json), Some(TaskImportFormat::TodoistJson));
+        let parsed = parse_todoist_json(json, "Inbox").unwrap();
+        assert_eq!(parsed.tasks.len(), 2);
+        assert_eq!(parsed.tasks[0].project, "Errands");
+        assert_eq!(parsed.tasks[0].priority, "high");
+        assert_eq!(parsed.tasks[0].due, NaiveDate::from_ymd_opt(2026, 10, 18));
+        assert!(parsed.tasks[0].warning.as_deref().unwrap().contains("every sat"));
+        assert_eq!(parsed.tasks[1].parent_id.as_deref(), Some("1"));
+        assert_eq!(parsed.tasks[1].project, "Errands");
+        assert!(parsed.tasks[1].completed);
+    }
+
+    #[test]
+    fn test_ticktick_backup() {
+        let csv = "\"Date: 2026-10-16+0000\"\n\"Version: 7.1\"\n\"Status: \n0 Normal\n1 Completed\n2 Archived\"\n\
+            \"Folder Name\",\"List Name\",\"Title\",\"Kind\",\"Tags\",\"Content\",\"Is Check list\",\"Start Date\",\"Due Date\",\"Reminder\",\"Repeat\",\"Priority\",\"Status\",\"Created Time\",\"Completed Time\",\"Order\",\"Timezone\",\"Is All Day\",\"Is Floating\",\"Column Name\",\"Column Order\",\"View Mode\",\"taskId\",\"parentId\"\n\
+            \"\",\"Home\",\"Fix tap\",\"TEXT\",\"diy, urgent\",\"Washer\",\"N\",\"\",\"2026-10-19T22:00:00+0000\",\"\",\"\",\"5\",\"0\",\"\",\"\",\"1\",\"Europe/Berlin\",\"true\",\"false\",\"\",\"\",\"list\",\"7\",\"\"\n\
+            \"\",\"Home\",\"Buy washer\",\"TEXT\",\"\",\"\",\"N\",\"\",\"\",\"\",\"\",\"0\",\"2\",\"\",\"\",\"2\",\"Europe/Berlin\",\"\",\"\",\"\",\"\",\"list\",\"8\",\"7\"\n\
+            \"\",\"Home\",\"Ideas\",\"NOTE\",\"\",\"\",\"N\",\"\",\"\",\"\",\"\",\"0\",\"0\",\"\",\"\",\"3\",\"\",\"\",\"\",\"\",\"\",\"list\",\"9\",\"\"\n";
+        assert_eq!(detect_format(csv), Some(TaskImportFormat::TickTick));
+        let parsed = parse_ticktick(csv);
+        assert_eq!(parsed.tasks.len(), 2);
+        assert_eq!(parsed.skipped.len(), 1);
+
+        let tap = &parsed.tasks[0];
+        assert_eq!((tap.project.as_str(), tap.priority.as_str()), ("Home", "high"));
+        assert_eq!(tap.labels, ["diy", "urgent"]);
+        // Midnight in Berlin, not the UTC date before it
+        assert_eq!(tap.due, NaiveDate::from_ymd_opt(2026, 10, 20));
+        assert!(parsed.tasks[1].completed);
+        assert_eq!(top_level_parents(&parsed.tasks), [None, Some(0)]);
+    }
+}
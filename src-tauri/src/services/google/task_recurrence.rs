@@ -0,0 +1,546 @@
+//! Local recurrence engine for Google Tasks
+//!
+//! The Google Tasks API has no notion of recurring tasks, so recurrence rules
+//! are stored in `task_metadata.recurrence_rule` and the next occurrence is
+//! created locally when a recurring task is completed or its due date passes.
+//! Completing a task in the app spawns its next occurrence straight away;
+//! `RecurrenceScheduler` catches tasks completed elsewhere and due dates
+//! that pass, checking every account periodically.
+//!
+//! Rules use a subset of RFC 5545 RRULE syntax:
+//! `FREQ=DAILY|WEEKLY|MONTHLY|YEARLY`, `INTERVAL=n`, `BYDAY=MO,WE,FR`,
+//! `COUNT=n` and `UNTIL=YYYYMMDD`.
+
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::google::tasks_service::{CreateTaskInput, GoogleTask, GoogleTasksService};
+use crate::services::task_supervisor::TaskSpec;
+use crate::services::time_service::TimeService;
+use chrono::{Datelike, Duration, Months, NaiveDate, Weekday};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+use tauri::{AppHandle, Manager};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecurrenceFrequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecurrenceRule {
+    pub frequency: RecurrenceFrequency,
+    pub interval: u32,
+    pub by_day: Vec<Weekday>,
+    /// Remaining occurrences including the current one
+    pub count: Option<u32>,
+    pub until: Option<NaiveDate>,
+}
+
+fn parse_weekday(code: &str) -> Option<Weekday> {
+    match code {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn weekday_code(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+impl RecurrenceRule {
+    /// Parse an RRULE-like string, with or without a leading `RRULE:`
+    pub fn parse(input: &str) -> std::result::Result<Self, String> {
+        let body = input.trim();
+        let body = body.strip_prefix("RRULE:").unwrap_or(body);
+
+        let mut frequency = None;
+        let mut interval = 1;
+        let mut by_day = Vec::new();
+        let mut count = None;
+        let mut until = None;
+
+        for part in body.split(';').filter(|p| !p.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid recurrence component '{}'", part))?;
+
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => RecurrenceFrequency::Daily,
+                        "WEEKLY" => RecurrenceFrequency::Weekly,
+                        "MONTHLY" => RecurrenceFrequency::Monthly,
+                        "YEARLY" => RecurrenceFrequency::Yearly,
+                        other => return Err(format!("Unsupported frequency '{}'", other)),
+                    });
+                }
+                "INTERVAL" => {
+                    interval = value
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| format!("Invalid interval '{}'", value))?;
+                }
+                "BYDAY" => {
+                    for code in value.split(',') {
+                        let day = parse_weekday(&code.trim().to_ascii_uppercase())
+                            .ok_or_else(|| format!("Invalid weekday '{}'", code))?;
+                        if !by_day.contains(&day) {
+                            by_day.push(day);
+                        }
+                    }
+                }
+                "COUNT" => {
+                    count = Some(
+                        value
+                            .parse::<u32>()
+                            .ok()
+                            .filter(|n| *n > 0)
+                            .ok_or_else(|| format!("Invalid count '{}'", value))?,
+                    );
+                }
+                "UNTIL" => {
+                    let date_part = value.get(..8).unwrap_or(value);
+                    until = Some(
+                        NaiveDate::parse_from_str(date_part, "%Y%m%d")
+                            .map_err(|_| format!("Invalid until date '{}'", value))?,
+                    );
+                }
+                other => return Err(format!("Unsupported recurrence component '{}'", other)),
+            }
+        }
+
+        let frequency = frequency.ok_or_else(|| "Recurrence rule requires FREQ".to_string())?;
+        if !by_day.is_empty() && frequency != RecurrenceFrequency::Weekly {
+            return Err("BYDAY is only supported with FREQ=WEEKLY".to_string());
+        }
+
+        by_day.sort_by_key(|d| d.num_days_from_monday());
+
+        Ok(Self { frequency, interval, by_day, count, until })
+    }
+
+    /// Serialize back to canonical RRULE-like syntax
+    pub fn to_rule_string(&self) -> String {
+        let freq = match self.frequency {
+            RecurrenceFrequency::Daily => "DAILY",
+            RecurrenceFrequency::Weekly => "WEEKLY",
+            RecurrenceFrequency::Monthly => "MONTHLY",
+            RecurrenceFrequency::Yearly => "YEARLY",
+        };
+
+        let mut parts = vec![format!("FREQ={}", freq)];
+        if self.interval != 1 {
+            parts.push(format!("INTERVAL={}", self.interval));
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<&str> = self.by_day.iter().map(|d| weekday_code(*d)).collect();
+            parts.push(format!("BYDAY={}", days.join(",")));
+        }
+        if let Some(count) = self.count {
+            parts.push(format!("COUNT={}", count));
+        }
+        if let Some(until) = self.until {
+            parts.push(format!("UNTIL={}", until.format("%Y%m%d")));
+        }
+        parts.join(";")
+    }
+
+    /// Compute the occurrence that follows `current`, honoring COUNT and UNTIL
+    pub fn next_after(&self, current: NaiveDate) -> Option<NaiveDate> {
+        if matches!(self.count, Some(n) if n <= 1) {
+            return None;
+        }
+
+        let next = match self.frequency {
+            RecurrenceFrequency::Daily => current + Duration::days(self.interval as i64),
+            RecurrenceFrequency::Weekly if !self.by_day.is_empty() => self.next_weekly_by_day(current),
+            RecurrenceFrequency::Weekly => current + Duration::weeks(self.interval as i64),
+            RecurrenceFrequency::Monthly => current.checked_add_months(Months::new(self.interval))?,
+            RecurrenceFrequency::Yearly => current.checked_add_months(Months::new(self.interval * 12))?,
+        };
+
+        match self.until {
+            Some(until) if next > until => None,
+            _ => Some(next),
+        }
+    }
+
+    fn next_weekly_by_day(&self, current: NaiveDate) -> NaiveDate {
+        // Look for a later matching weekday in the same week first
+        let current_index = current.weekday().num_days_from_monday();
+        if let Some(day) = self.by_day.iter().find(|d| d.num_days_from_monday() > current_index) {
+            return current + Duration::days((day.num_days_from_monday() - current_index) as i64);
+        }
+
+        // Otherwise jump `interval` weeks ahead to the first listed weekday
+        let week_start = current - Duration::days(current_index as i64);
+        let first = self.by_day[0].num_days_from_monday();
+        week_start + Duration::weeks(self.interval as i64) + Duration::days(first as i64)
+    }
+
+    /// Rule to carry over to the next occurrence (COUNT is decremented)
+    pub fn advanced(&self) -> Self {
+        Self {
+            count: self.count.map(|n| n.saturating_sub(1)),
+            ..self.clone()
+        }
+    }
+}
+
+/// Outcome of processing a single recurring task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecurrenceOccurrence {
+    pub source_task_id: String,
+    pub new_task_id: String,
+    pub task_list_id: String,
+    pub due: String,
+}
+
+fn parse_task_due(due: &Option<String>) -> Option<NaiveDate> {
+    due.as_deref()
+        .and_then(|d| d.get(..10))
+        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+}
+
+/// Store or clear the recurrence rule for a task
+pub fn set_task_recurrence_rule(
+    db_manager: &DatabaseManager,
+    google_task_id: &str,
+    task_list_id: &str,
+    rule: Option<&RecurrenceRule>,
+) -> Result<()> {
+    let conn = db_manager.get_connection()?;
+    let rule_string = rule.map(|r| r.to_rule_string());
+
+    let updated = conn.execute(
+        "UPDATE task_metadata SET recurrence_rule = ?1, updated_at = CURRENT_TIMESTAMP WHERE google_task_id = ?2",
+        params![rule_string, google_task_id],
+    )?;
+
+    if updated == 0 && rule_string.is_some() {
+        conn.execute(
+            "INSERT INTO task_metadata (google_task_id, task_list_id, priority, recurrence_rule)
+             VALUES (?1, ?2, 'none', ?3)",
+            params![google_task_id, task_list_id, rule_string],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Read the recurrence rule stored for a task
+pub fn get_task_recurrence_rule(db_manager: &DatabaseManager, google_task_id: &str) -> Result<Option<RecurrenceRule>> {
+    let conn = db_manager.get_connection()?;
+    let rule: Option<String> = conn
+        .query_row(
+            "SELECT recurrence_rule FROM task_metadata WHERE google_task_id = ?1",
+            params![google_task_id],
+            |row| row.get(0),
+        )
+        .optional()?
+        .flatten();
+
+    match rule {
+        Some(rule) => RecurrenceRule::parse(&rule)
+            .map(Some)
+            .map_err(|message| LibreOllamaError::InvalidInput { message, field: Some("recurrence_rule".to_string()) }),
+        None => Ok(None),
+    }
+}
+
+/// Hand the metadata and `next_rule` from `source_id` to the occurrence
+/// `new_id`, in one transaction: the source never keeps its rule once the
+/// occurrence has it, so it cannot spawn a second one.
+fn move_rule(db_manager: &DatabaseManager, source_id: &str, new_id: &str, next_rule: &RecurrenceRule) -> Result<()> {
+    let mut conn = db_manager.get_connection()?;
+    let tx = conn.transaction()?;
+    tx.execute(
+        "INSERT INTO task_metadata (google_task_id, task_list_id, priority, labels_json, recurrence_rule)
+         SELECT ?1, task_list_id, priority, labels_json, ?2 FROM task_metadata WHERE google_task_id = ?3",
+        params![new_id, next_rule.to_rule_string(), source_id],
+    )?;
+    tx.execute(
+        "UPDATE task_metadata SET recurrence_rule = NULL, updated_at = CURRENT_TIMESTAMP WHERE google_task_id = ?1",
+        params![source_id],
+    )?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Creates the next occurrence of a recurring task in Google and moves the
+/// rule (and the rest of the local metadata) onto it.
+///
+/// The rule is cleared from the source task so an occurrence is only ever
+/// spawned once, even if the scheduler runs repeatedly.
+pub async fn spawn_next_occurrence(
+    tasks_service: &GoogleTasksService,
+    db_manager: Arc<DatabaseManager>,
+    account_id: &str,
+    task_list_id: &str,
+    task: &GoogleTask,
+    rule: &RecurrenceRule,
+) -> Result<Option<RecurrenceOccurrence>> {
+    let today = TimeService::from_db(&db_manager)?.today();
+    let anchor = parse_task_due(&task.due).unwrap_or(today);
+
+    // Skip occurrences that are already in the past so an overdue task
+    // does not spawn a backlog of stale copies.
+    let mut next_rule = rule.clone();
+    let mut next_due = match next_rule.next_after(anchor) {
+        Some(date) => date,
+        None => {
+            set_task_recurrence_rule(&db_manager, &task.id, task_list_id, None)?;
+            return Ok(None);
+        }
+    };
+    next_rule = next_rule.advanced();
+    while next_due < today {
+        match next_rule.next_after(next_due) {
+            Some(date) => {
+                next_due = date;
+                next_rule = next_rule.advanced();
+            }
+            None => {
+                set_task_recurrence_rule(&db_manager, &task.id, task_list_id, None)?;
+                return Ok(None);
+            }
+        }
+    }
+
+    let created = tasks_service
+        .create_task(
+            account_id,
+            task_list_id,
+            CreateTaskInput {
+                title: task.title.clone(),
+                notes: task.notes.clone(),
+                due: Some(next_due.format("%Y-%m-%d").to_string()),
+                status: Some("needsAction".to_string()),
+            },
+        )
+        .await?;
+
+    move_rule(&db_manager, &task.id, &created.id, &next_rule)?;
+
+    Ok(Some(RecurrenceOccurrence {
+        source_task_id: task.id.clone(),
+        new_task_id: created.id,
+        task_list_id: task_list_id.to_string(),
+        due: next_due.format("%Y-%m-%d").to_string(),
+    }))
+}
+
+/// Recurring tasks on the lists in `task_list_ids`, with their rule
+fn recurring_tasks(db_manager: &DatabaseManager, task_list_ids: &[String]) -> Result<Vec<(String, String, String)>> {
+    let conn = db_manager.get_connection()?;
+    let mut stmt = conn.prepare(
+        "SELECT google_task_id, task_list_id, recurrence_rule FROM task_metadata
+         WHERE recurrence_rule IS NOT NULL AND task_list_id IN (SELECT value FROM json_each(?1))",
+    )?;
+    let rows = stmt
+        .query_map(params![serde_json::to_string(task_list_ids)?], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(rows)
+}
+
+/// Scan the recurring tasks on an account's lists and spawn the next
+/// occurrence for every task that has been completed or whose due date has
+/// passed.
+pub async fn process_recurring_tasks(
+    tasks_service: &GoogleTasksService,
+    db_manager: Arc<DatabaseManager>,
+    account_id: &str,
+) -> Result<Vec<RecurrenceOccurrence>> {
+    let task_list_ids: Vec<String> = tasks_service
+        .get_task_lists(account_id)
+        .await?
+        .into_iter()
+        .map(|list| list.id)
+        .collect();
+    let recurring = recurring_tasks(&db_manager, &task_list_ids)?;
+
+    let today = TimeService::from_db(&db_manager)?.today();
+    let mut occurrences = Vec::new();
+
+    for (task_id, task_list_id, rule_string) in recurring {
+        let rule = match RecurrenceRule::parse(&rule_string) {
+            Ok(rule) => rule,
+            Err(e) => {
+                eprintln!("⚠️ Skipping task {} with invalid recurrence rule '{}': {}", task_id, rule_string, e);
+                continue;
+            }
+        };
+
+        let task = match tasks_service.get_single_task(account_id, &task_list_id, &task_id).await {
+            Ok(task) => task,
+            Err(e) => {
+                eprintln!("⚠️ Could not load recurring task {}: {}", task_id, e);
+                continue;
+            }
+        };
+
+        let is_completed = task.status == "completed";
+        let is_past_due = parse_task_due(&task.due).map(|due| due < today).unwrap_or(false);
+        if !is_completed && !is_past_due {
+            continue;
+        }
+
+        if let Some(occurrence) =
+            spawn_next_occurrence(tasks_service, db_manager.clone(), account_id, &task_list_id, &task, &rule).await?
+        {
+            occurrences.push(occurrence);
+        }
+    }
+
+    Ok(occurrences)
+}
+
+/// How often every account's recurring tasks are checked
+const RECURRENCE_INTERVAL: StdDuration = StdDuration::from_secs(30 * 60);
+
+/// Spawns the next occurrence of recurring tasks completed outside the app
+/// or past their due date, for every active account
+#[derive(Clone)]
+pub struct RecurrenceScheduler {
+    db_manager: Arc<DatabaseManager>,
+    app: AppHandle,
+}
+
+impl RecurrenceScheduler {
+    pub fn new(db_manager: Arc<DatabaseManager>, app: AppHandle) -> Self {
+        Self { db_manager, app }
+    }
+
+    pub fn task(&self) -> TaskSpec {
+        let scheduler = self.clone();
+        TaskSpec::new("task_recurrence", move || {
+            let scheduler = scheduler.clone();
+            async move { scheduler.run_once().await }
+        })
+        .every(|| RECURRENCE_INTERVAL)
+        .jitter(0.1)
+    }
+
+    async fn run_once(&self) -> anyhow::Result<()> {
+        let Some(tasks_service) = self.app.try_state::<GoogleTasksService>() else {
+            return Ok(());
+        };
+        let accounts: Vec<String> = {
+            let conn = self.db_manager.get_connection()?;
+            let mut stmt = conn.prepare("SELECT id FROM gmail_accounts_secure WHERE is_active = 1")?;
+            let ids = stmt.query_map([], |row| row.get(0))?.collect::<rusqlite::Result<_>>()?;
+            ids
+        };
+
+        let mut failures = Vec::new();
+        for account_id in accounts {
+            match process_recurring_tasks(&tasks_service, self.db_manager.clone(), &account_id).await {
+                Ok(occurrences) if !occurrences.is_empty() => {
+                    println!("🔁 [RECURRENCE] {} new occurrences for {}", occurrences.len(), account_id);
+                }
+                Ok(_) => {}
+                Err(e) => failures.push(format!("{}: {}", account_id, e)),
+            }
+        }
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("Recurring tasks failed for {}", failures.join("; ")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        let rule = RecurrenceRule::parse("RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=FR,MO;COUNT=5").unwrap();
+        assert_eq!(rule.frequency, RecurrenceFrequency::Weekly);
+        assert_eq!(rule.interval, 2);
+        assert_eq!(rule.by_day, vec![Weekday::Mon, Weekday::Fri]);
+        assert_eq!(rule.to_rule_string(), "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR;COUNT=5");
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_rules() {
+        assert!(RecurrenceRule::parse("INTERVAL=2").is_err());
+        assert!(RecurrenceRule::parse("FREQ=HOURLY").is_err());
+        assert!(RecurrenceRule::parse("FREQ=DAILY;BYDAY=MO").is_err());
+        assert!(RecurrenceRule::parse("FREQ=DAILY;INTERVAL=0").is_err());
+    }
+
+    #[test]
+    fn test_next_after_simple_frequencies() {
+        let daily = RecurrenceRule::parse("FREQ=DAILY;INTERVAL=3").unwrap();
+        assert_eq!(daily.next_after(date(2024, 1, 30)), Some(date(2024, 2, 2)));
+
+        let monthly = RecurrenceRule::parse("FREQ=MONTHLY").unwrap();
+        assert_eq!(monthly.next_after(date(2024, 1, 31)), Some(date(2024, 2, 29)));
+
+        let yearly = RecurrenceRule::parse("FREQ=YEARLY").unwrap();
+        assert_eq!(yearly.next_after(date(2024, 3, 1)), Some(date(2025, 3, 1)));
+    }
+
+    #[test]
+    fn test_next_after_weekly_by_day() {
+        let rule = RecurrenceRule::parse("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH").unwrap();
+        // 2024-01-01 is a Monday
+        assert_eq!(rule.next_after(date(2024, 1, 1)), Some(date(2024, 1, 4)));
+        assert_eq!(rule.next_after(date(2024, 1, 4)), Some(date(2024, 1, 15)));
+    }
+
+    #[test]
+    fn test_count_and_until_stop_recurrence() {
+        let counted = RecurrenceRule::parse("FREQ=DAILY;COUNT=2").unwrap();
+        assert!(counted.next_after(date(2024, 1, 1)).is_some());
+        assert!(counted.advanced().next_after(date(2024, 1, 2)).is_none());
+
+        let bounded = RecurrenceRule::parse("FREQ=DAILY;UNTIL=20240102").unwrap();
+        assert_eq!(bounded.next_after(date(2024, 1, 1)), Some(date(2024, 1, 2)));
+        assert!(bounded.next_after(date(2024, 1, 2)).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rules_are_scoped_to_lists_and_moved_once() {
+        let db_path = std::env::temp_dir().join(format!("libreollama_recurrence_{}.db", uuid::Uuid::new_v4()));
+        let db_manager = DatabaseManager::open_at(db_path).unwrap();
+        db_manager.run_migrations().await.unwrap();
+        let weekly = RecurrenceRule::parse("FREQ=WEEKLY").unwrap();
+        set_task_recurrence_rule(&db_manager, "mine", "work", Some(&weekly)).unwrap();
+        set_task_recurrence_rule(&db_manager, "theirs", "other-account", Some(&weekly)).unwrap();
+
+        let found = recurring_tasks(&db_manager, &["work".to_string()]).unwrap();
+        assert_eq!(found.iter().map(|(id, _, _)| id.as_str()).collect::<Vec<_>>(), ["mine"]);
+
+        move_rule(&db_manager, "mine", "next", &weekly.advanced()).unwrap();
+        assert!(get_task_recurrence_rule(&db_manager, "mine").unwrap().is_none());
+        assert_eq!(get_task_recurrence_rule(&db_manager, "next").unwrap(), Some(weekly.advanced()));
+        assert!(get_task_recurrence_rule(&db_manager, "unknown").unwrap().is_none());
+    }
+}
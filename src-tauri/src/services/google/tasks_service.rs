@@ -1,12 +1,18 @@
 use crate::database::DatabaseManager;
 use crate::errors::{LibreOllamaError, Result};
 use crate::services::gmail::auth_service::GmailAuthService;
+use crate::services::google::batch::{build_batch_body, parse_batch_response, BatchPart};
+use crate::commands::rate_limiter::RequestPriority;
+use crate::services::google::GoogleEndpoints;
+use crate::services::request_scheduler;
+use crate::services::time_service::TimeService;
 use reqwest::{Client, Method};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use chrono::Utc;
+use crate::utils::http_client::http_client;
 
-const GOOGLE_TASKS_API_BASE: &str = "https://tasks.googleapis.com/tasks/v1";
+/// Most requests sent in one Tasks batch call
+pub const TASKS_BATCH_LIMIT: usize = 50;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GoogleTaskList {
@@ -21,8 +27,15 @@ pub struct GoogleTask {
     pub notes: Option<String>,
     pub due: Option<String>,
     pub status: String,
+    pub parent: Option<String>,
     pub position: Option<String>,
     pub updated: Option<String>,
+    /// When the task was completed, RFC 3339
+    #[serde(default)]
+    pub completed: Option<String>,
+    /// Version of the task, for updates that must not overwrite a newer edit
+    #[serde(default)]
+    pub etag: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,17 +59,25 @@ pub struct GoogleTasksService {
     client: Client,
     auth_service: Arc<GmailAuthService>,
     db_manager: Arc<DatabaseManager>,
+    endpoints: GoogleEndpoints,
 }
 
 impl GoogleTasksService {
     pub fn new(auth_service: Arc<GmailAuthService>, db_manager: Arc<DatabaseManager>) -> Self {
         Self {
-            client: Client::new(),
+            client: http_client(),
             auth_service,
             db_manager,
+            endpoints: GoogleEndpoints::default(),
         }
     }
 
+    /// Send requests to `endpoints` instead of Google's production hosts
+    pub fn with_endpoints(mut self, endpoints: GoogleEndpoints) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
     async fn make_api_request<T>(&self, account_id: &str, endpoint: &str) -> Result<T>
     where
         T: for<'de> Deserialize<'de>,
@@ -66,13 +87,10 @@ impl GoogleTasksService {
             .validate_and_refresh_tokens(&self.db_manager, account_id)
             .await?;
 
-        let url = format!("{}/{}", GOOGLE_TASKS_API_BASE, endpoint.trim_start_matches('/'));
+        let url = GoogleEndpoints::url(&self.endpoints.tasks_api, endpoint);
 
-        let response = self
-            .client
-            .get(&url)
-            .bearer_auth(tokens.access_token)
-            .send()
+        let request = self.client.get(&url).bearer_auth(tokens.access_token);
+        let response = request_scheduler::send(Some(account_id), RequestPriority::Medium, request)
             .await
             .map_err(|e| LibreOllamaError::Network {
                 message: format!("Google Tasks API request failed: {}", e),
@@ -125,6 +143,11 @@ impl GoogleTasksService {
         Ok(all_lists)
     }
 
+    pub async fn create_task_list(&self, account_id: &str, title: &str) -> Result<GoogleTaskList> {
+        let body = serde_json::json!({ "title": title });
+        self.make_api_request_with_body(account_id, "users/@me/lists", Method::POST, Some(body)).await
+    }
+
     pub async fn get_tasks(&self, account_id: &str, task_list_id: &str) -> Result<Vec<GoogleTask>> {
         #[derive(Deserialize)]
         struct TasksResponse {
@@ -173,7 +196,7 @@ impl GoogleTasksService {
             .validate_and_refresh_tokens(&self.db_manager, account_id)
             .await?;
 
-        let url = format!("{}/{}", GOOGLE_TASKS_API_BASE, endpoint.trim_start_matches('/'));
+        let url = GoogleEndpoints::url(&self.endpoints.tasks_api, endpoint);
 
         let mut request = self
             .client
@@ -185,8 +208,7 @@ impl GoogleTasksService {
             request = request.json(&body_data);
         }
 
-        let response = request
-            .send()
+        let response = request_scheduler::send(Some(account_id), RequestPriority::Medium, request)
             .await
             .map_err(|e| LibreOllamaError::Network {
                 message: format!("Google Tasks API request failed: {}", e),
@@ -206,24 +228,21 @@ impl GoogleTasksService {
         })
     }
 
+    /// A due date or time as the midnight-UTC value Google stores, taking
+    /// times in the user's zone so they keep their local date
+    fn google_due(&self, due: Option<String>) -> Result<Option<String>> {
+        let Some(due) = due else { return Ok(None) };
+        let clock = TimeService::from_db(&self.db_manager)?;
+        clock.task_due(&due).map(Some).ok_or_else(|| LibreOllamaError::InvalidInput {
+            message: format!("Invalid due date '{}'", due),
+            field: Some("due".to_string()),
+        })
+    }
+
     pub async fn create_task(&self, account_id: &str, task_list_id: &str, input: CreateTaskInput) -> Result<GoogleTask> {
         let endpoint = format!("lists/{}/tasks", task_list_id);
         
-        // Convert due date to RFC 3339 format if provided
-        let due_rfc3339 = if let Some(due) = input.due {
-            // If it's already in RFC 3339 format, use as-is
-            if due.contains('T') {
-                Some(due)
-            } else {
-                // For YYYY-MM-DD format, create RFC 3339 timestamp
-                // We use a time that ensures the date remains correct when Google
-                // returns it at midnight UTC. For users in negative UTC offsets
-                // (Americas), we need to use a later time.
-                Some(format!("{}T20:00:00.000Z", due))
-            }
-        } else {
-            None
-        };
+        let due_rfc3339 = self.google_due(input.due)?;
         
         let body = serde_json::json!({
             "title": input.title,
@@ -235,6 +254,86 @@ impl GoogleTasksService {
         self.make_api_request_with_body(account_id, &endpoint, Method::POST, Some(body)).await
     }
 
+    /// Create a task as a child of `parent`, placed after the `previous` sibling
+    pub async fn create_child_task(
+        &self,
+        account_id: &str,
+        task_list_id: &str,
+        input: CreateTaskInput,
+        parent: &str,
+        previous: Option<&str>,
+    ) -> Result<GoogleTask> {
+        let mut endpoint = format!("lists/{}/tasks?parent={}", task_list_id, urlencoding::encode(parent));
+        if let Some(previous) = previous {
+            endpoint.push_str(&format!("&previous={}", urlencoding::encode(previous)));
+        }
+
+        let body = serde_json::json!({
+            "title": input.title,
+            "notes": input.notes,
+            "due": self.google_due(input.due)?,
+            "status": input.status.unwrap_or_else(|| "needsAction".to_string())
+        });
+
+        self.make_api_request_with_body(account_id, &endpoint, Method::POST, Some(body)).await
+    }
+
+    /// A batch part creating a task, as the first child of `parent` when
+    /// given, for [`Self::batch`]
+    pub fn create_task_part(&self, task_list_id: &str, input: CreateTaskInput, parent: Option<&str>) -> Result<BatchPart> {
+        let mut path = format!("/tasks/v1/lists/{}/tasks", task_list_id);
+        if let Some(parent) = parent {
+            path.push_str(&format!("?parent={}", urlencoding::encode(parent)));
+        }
+
+        let body = serde_json::json!({
+            "title": input.title,
+            "notes": input.notes,
+            "due": self.google_due(input.due)?,
+            "status": input.status.unwrap_or_else(|| "needsAction".to_string())
+        });
+
+        Ok(BatchPart::new("POST", path, Some(body)))
+    }
+
+    /// Move a task under a new parent and/or after a sibling. `None` for
+    /// `parent` moves the task to the top level.
+    pub async fn move_task(
+        &self,
+        account_id: &str,
+        task_list_id: &str,
+        task_id: &str,
+        parent: Option<&str>,
+        previous: Option<&str>,
+    ) -> Result<GoogleTask> {
+        let mut query = Vec::new();
+        if let Some(parent) = parent {
+            query.push(format!("parent={}", urlencoding::encode(parent)));
+        }
+        if let Some(previous) = previous {
+            query.push(format!("previous={}", urlencoding::encode(previous)));
+        }
+
+        let mut endpoint = format!("lists/{}/tasks/{}/move", task_list_id, task_id);
+        if !query.is_empty() {
+            endpoint.push('?');
+            endpoint.push_str(&query.join("&"));
+        }
+
+        self.make_api_request_with_body::<GoogleTask, serde_json::Value>(account_id, &endpoint, Method::POST, None).await
+    }
+
+    /// Move a task to the end of another list
+    pub async fn move_task_to_list(&self, account_id: &str, task_list_id: &str, task_id: &str, destination: &str) -> Result<GoogleTask> {
+        let endpoint = format!(
+            "lists/{}/tasks/{}/move?destinationTasklist={}",
+            task_list_id,
+            task_id,
+            urlencoding::encode(destination)
+        );
+        self.make_api_request_with_body::<GoogleTask, serde_json::Value>(account_id, &endpoint, Method::POST, None).await
+    }
+
     pub async fn get_single_task(&self, account_id: &str, task_list_id: &str, task_id: &str) -> Result<GoogleTask> {
         let endpoint = format!("lists/{}/tasks/{}", task_list_id, task_id);
         self.make_api_request(account_id, &endpoint).await
@@ -243,21 +342,7 @@ impl GoogleTasksService {
     pub async fn update_task(&self, account_id: &str, task_list_id: &str, task_id: &str, input: UpdateTaskInput) -> Result<GoogleTask> {
         let endpoint = format!("lists/{}/tasks/{}", task_list_id, task_id);
         
-        // Convert due date to RFC 3339 format if provided
-        let due_rfc3339 = if let Some(due) = input.due {
-            // If it's already in RFC 3339 format, use as-is
-            if due.contains('T') {
-                Some(due)
-            } else {
-                // For YYYY-MM-DD format, create RFC 3339 timestamp
-                // We use a time that ensures the date remains correct when Google
-                // returns it at midnight UTC. For users in negative UTC offsets
-                // (Americas), we need to use a later time.
-                Some(format!("{}T20:00:00.000Z", due))
-            }
-        } else {
-            None
-        };
+        let due_rfc3339 = self.google_due(input.due)?;
         
         // Build body dynamically to only include non-None fields
         // This prevents sending null values that would clear existing data
@@ -282,21 +367,56 @@ impl GoogleTasksService {
         self.make_api_request_with_body(account_id, &endpoint, Method::PATCH, Some(body)).await
     }
 
-    pub async fn delete_task(&self, account_id: &str, task_list_id: &str, task_id: &str) -> Result<()> {
+    /// Replace a task's notes only if it is still at `etag`. Returns
+    /// `false` when someone changed the task in the meantime.
+    pub async fn update_notes_if_unchanged(&self, account_id: &str, task_list_id: &str, task_id: &str, etag: &str, notes: &str) -> Result<bool> {
         let endpoint = format!("lists/{}/tasks/{}", task_list_id, task_id);
-        
+
         let tokens = self
             .auth_service
             .validate_and_refresh_tokens(&self.db_manager, account_id)
             .await?;
 
-        let url = format!("{}/{}", GOOGLE_TASKS_API_BASE, endpoint.trim_start_matches('/'));
+        let url = GoogleEndpoints::url(&self.endpoints.tasks_api, &endpoint);
 
-        let response = self
+        let request = self
             .client
-            .delete(&url)
+            .patch(&url)
             .bearer_auth(tokens.access_token)
-            .send()
+            .header("If-Match", etag)
+            .json(&serde_json::json!({ "id": task_id, "notes": notes }));
+        let response = request_scheduler::send(Some(account_id), RequestPriority::Medium, request)
+            .await
+            .map_err(|e| LibreOllamaError::Network {
+                message: format!("Google Tasks API request failed: {}", e),
+                url: Some(url.clone()),
+            })?;
+
+        if response.status() == reqwest::StatusCode::PRECONDITION_FAILED {
+            return Ok(false);
+        }
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(LibreOllamaError::GoogleTasksApi {
+                message: format!("Google Tasks API error: {}", error_text),
+            });
+        }
+
+        Ok(true)
+    }
+
+    pub async fn delete_task(&self, account_id: &str, task_list_id: &str, task_id: &str) -> Result<()> {
+        let endpoint = format!("lists/{}/tasks/{}", task_list_id, task_id);
+        
+        let tokens = self
+            .auth_service
+            .validate_and_refresh_tokens(&self.db_manager, account_id)
+            .await?;
+
+        let url = GoogleEndpoints::url(&self.endpoints.tasks_api, &endpoint);
+
+        let request = self.client.delete(&url).bearer_auth(tokens.access_token);
+        let response = request_scheduler::send(Some(account_id), RequestPriority::Medium, request)
             .await
             .map_err(|e| LibreOllamaError::Network {
                 message: format!("Google Tasks API request failed: {}", e),
@@ -313,6 +433,73 @@ impl GoogleTasksService {
         Ok(())
     }
 
+    /// Send several requests in Tasks batch calls of up to
+    /// [`TASKS_BATCH_LIMIT`]. Results line up with `parts`; an item Google
+    /// rejected, or left out of the response, is an error of its own.
+    pub async fn batch(&self, account_id: &str, parts: &[BatchPart]) -> Result<Vec<Result<serde_json::Value>>> {
+        let mut results = Vec::with_capacity(parts.len());
+
+        for chunk in parts.chunks(TASKS_BATCH_LIMIT) {
+            let tokens = self
+                .auth_service
+                .validate_and_refresh_tokens(&self.db_manager, account_id)
+                .await?;
+
+            let url = &self.endpoints.tasks_batch;
+            let boundary = format!("batch_{}", uuid::Uuid::new_v4().simple());
+            let request = self
+                .client
+                .post(url)
+                .bearer_auth(tokens.access_token)
+                .header("Content-Type", format!("multipart/mixed; boundary={}", boundary))
+                .body(build_batch_body(&boundary, chunk));
+            // Through the scheduler like single calls, so batches share the
+            // account's rate and back off on 429s
+            let response = request_scheduler::send(Some(account_id), RequestPriority::Medium, request)
+                .await
+                .map_err(|e| LibreOllamaError::Network {
+                    message: format!("Google Tasks batch request failed: {}", e),
+                    url: Some(url.clone()),
+                })?;
+
+            if !response.status().is_success() {
+                let error_text = response.text().await.unwrap_or_default();
+                return Err(LibreOllamaError::GoogleTasksApi {
+                    message: format!("Google Tasks batch error: {}", error_text),
+                });
+            }
+
+            let content_type = response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or("")
+                .to_string();
+            let body = response.text().await.map_err(|e| LibreOllamaError::Network {
+                message: format!("Failed to read Google Tasks batch response: {}", e),
+                url: Some(url.clone()),
+            })?;
+
+            let mut chunk_results: Vec<Result<serde_json::Value>> = chunk
+                .iter()
+                .map(|_| Err(LibreOllamaError::GoogleTasksApi { message: "Missing from the batch response".to_string() }))
+                .collect();
+            for item in parse_batch_response(&content_type, &body) {
+                let Some(slot) = chunk_results.get_mut(item.index) else { continue };
+                *slot = if (200..300).contains(&item.status_code) {
+                    Ok(serde_json::from_str(&item.body).unwrap_or(serde_json::Value::Null))
+                } else {
+                    Err(LibreOllamaError::GoogleTasksApi {
+                        message: format!("Google Tasks API error {}: {}", item.status_code, item.body),
+                    })
+                };
+            }
+            results.extend(chunk_results);
+        }
+
+        Ok(results)
+    }
+
     pub async fn update_task_list(&self, account_id: &str, task_list_id: &str, new_title: String) -> Result<GoogleTaskList> {
         let endpoint = format!("users/@me/lists/{}", task_list_id);
         let body = serde_json::json!({
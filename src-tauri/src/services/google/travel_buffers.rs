@@ -0,0 +1,648 @@
+//! Travel-time buffers around events with a physical location
+//!
+//! An event somewhere you have to get to gets a "Travel to …" block before
+//! it and a "Travel from …" block after it. Trips start at the previous
+//! event's location when it has one and end at the next event's, falling
+//! back to the configured home location. How long a trip takes comes from
+//! fixed minutes or from a routing endpoint the user points us at; when the
+//! endpoint fails or there is no origin to route from, the fixed minutes
+//! stand in. Buffers are ordinary events tagged with a private extended
+//! property naming the event they belong to, so they are found again and
+//! never created twice, and are removed or redone when their event is
+//! deleted or moved. Calendar requests go through the request scheduler;
+//! the routing endpoint is not a Google API and is called directly.
+
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::commands::calendar::{EventDateTime, GoogleCalendarEvent};
+use crate::commands::rate_limiter::RequestPriority;
+use crate::database::operations::preference_operations;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::google::GoogleEndpoints;
+use crate::services::i18n;
+use crate::services::request_scheduler;
+use crate::utils::http_client::http_client;
+
+/// user_preferences key holding the JSON encoded `TravelBufferSettings`
+pub const TRAVEL_BUFFERS_PREFERENCE: &str = "calendar.travel_buffers";
+
+/// Private extended properties on buffer events
+const BUFFER_FOR_PROPERTY: &str = "libreollamaTravelFor";
+const BUFFER_SIDE_PROPERTY: &str = "libreollamaTravelSide";
+
+/// How far around an event to look for where the user comes from and goes next
+const NEIGHBOUR_WINDOW_HOURS: i64 = 6;
+const MAX_TRAVEL_MINUTES: i64 = 240;
+/// Estimates are rounded up to this many minutes
+const ROUND_MINUTES: i64 = 5;
+
+/// Meeting hosts and products; a location mentioning one is not a place
+const VIRTUAL_LOCATION_HINTS: &[&str] = &[
+    "zoom.us", "meet.google.com", "teams.microsoft.com", "teams.live.com", "webex.com", "whereby.com",
+    "gotomeeting.com", "chime.aws", "zoom meeting", "google meet", "microsoft teams", "skype", "webex",
+];
+/// Whole locations that say the event happens nowhere in particular
+const VIRTUAL_LOCATIONS: &[&str] = &[
+    "online", "virtual", "remote", "video call", "phone", "call", "zoom", "teams", "tbd", "tba", "n/a",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TravelTimeProvider {
+    /// The configured before and after minutes, wherever the event is
+    Fixed,
+    /// An HTTP endpoint answering `GET url?origin=…&destination=…` plus
+    /// `arrive_by=…` or `depart_at=…` (RFC 3339) with
+    /// `{"duration_seconds": n}`
+    Routing { url: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TravelBufferSettings {
+    /// Add buffers when an event with a physical location is created
+    pub auto_insert: bool,
+    pub before_minutes: i64,
+    pub after_minutes: i64,
+    pub provider: TravelTimeProvider,
+    /// Where trips start and end when no neighbouring event has a location
+    pub home_location: Option<String>,
+}
+
+impl Default for TravelBufferSettings {
+    fn default() -> Self {
+        Self {
+            auto_insert: false,
+            before_minutes: 30,
+            after_minutes: 30,
+            provider: TravelTimeProvider::Fixed,
+            home_location: None,
+        }
+    }
+}
+
+impl TravelBufferSettings {
+    pub fn validate(&self) -> Result<()> {
+        for (minutes, field) in [(self.before_minutes, "before_minutes"), (self.after_minutes, "after_minutes")] {
+            if !(0..=MAX_TRAVEL_MINUTES).contains(&minutes) {
+                return Err(LibreOllamaError::InvalidInput {
+                    message: format!("Travel buffers must be between 0 and {} minutes", MAX_TRAVEL_MINUTES),
+                    field: Some(field.to_string()),
+                });
+            }
+        }
+        if let TravelTimeProvider::Routing { url } = &self.provider {
+            let valid = url::Url::parse(url).is_ok_and(|url| matches!(url.scheme(), "http" | "https"));
+            if !valid {
+                return Err(LibreOllamaError::InvalidInput {
+                    message: format!("The routing provider needs an http(s) URL, got {}", url),
+                    field: Some("provider.url".to_string()),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn fixed_minutes(&self, side: BufferSide) -> i64 {
+        match side {
+            BufferSide::Before => self.before_minutes,
+            BufferSide::After => self.after_minutes,
+        }
+    }
+}
+
+pub fn load_settings(conn: &Connection) -> anyhow::Result<TravelBufferSettings> {
+    Ok(preference_operations::get_preference_value(conn, TRAVEL_BUFFERS_PREFERENCE)?
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default())
+}
+
+pub fn save_settings(conn: &Connection, settings: &TravelBufferSettings) -> anyhow::Result<()> {
+    preference_operations::set_preference_value(conn, TRAVEL_BUFFERS_PREFERENCE, &serde_json::to_string(settings)?, "calendar")
+}
+
+/// Whether a location is a place to travel to, rather than a meeting link,
+/// a dial-in or a placeholder
+pub fn is_physical_location(location: &str) -> bool {
+    let location = location.trim().to_lowercase();
+    if location.is_empty() || location.contains("://") || location.starts_with("www.") {
+        return false;
+    }
+    if VIRTUAL_LOCATION_HINTS.iter().any(|host| location.contains(host)) {
+        return false;
+    }
+    !VIRTUAL_LOCATIONS.contains(&location.trim_end_matches('.'))
+}
+
+fn same_place(a: &str, b: &str) -> bool {
+    a.trim().eq_ignore_ascii_case(b.trim())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BufferSide {
+    Before,
+    After,
+}
+
+impl BufferSide {
+    fn as_str(self) -> &'static str {
+        match self {
+            BufferSide::Before => "before",
+            BufferSide::After => "after",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EstimateSource {
+    Fixed,
+    Routing,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TravelBufferSuggestion {
+    pub side: BufferSide,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub minutes: i64,
+    pub origin: Option<String>,
+    pub destination: Option<String>,
+    pub source: EstimateSource,
+    pub summary: String,
+    /// Titles of events the buffer would overlap
+    pub conflicts: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TravelBufferSuggestions {
+    pub event_id: String,
+    /// The event's location when it is a physical one
+    pub location: Option<String>,
+    /// Buffer events already created for this event
+    pub existing: Vec<String>,
+    pub suggestions: Vec<TravelBufferSuggestion>,
+}
+
+/// A trip to or from the event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trip {
+    pub side: BufferSide,
+    pub origin: Option<String>,
+    pub destination: Option<String>,
+    /// Arrival for trips before the event, departure for trips after it
+    pub at: DateTime<Utc>,
+}
+
+/// Timed start or end; all-day dates have no travel to plan around
+fn event_time(time: Option<&EventDateTime>) -> Option<DateTime<Utc>> {
+    let value = time?.date_time.as_deref()?;
+    DateTime::parse_from_rfc3339(value).ok().map(|t| t.with_timezone(&Utc))
+}
+
+fn event_span(event: &GoogleCalendarEvent) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let start = event_time(event.start.as_ref())?;
+    let end = event_time(event.end.as_ref())?;
+    (end >= start).then_some((start, end))
+}
+
+fn physical_location(event: &GoogleCalendarEvent) -> Option<&str> {
+    event.location.as_deref().filter(|location| is_physical_location(location)).map(str::trim)
+}
+
+/// Events that say where the user is: timed, not cancelled, busy and not
+/// a buffer themselves
+fn counts_as_neighbour(event: &GoogleCalendarEvent, event_id: &str, buffer_ids: &[String]) -> bool {
+    event.id != event_id
+        && !buffer_ids.contains(&event.id)
+        && event.status.as_deref() != Some("cancelled")
+        && event.transparency.as_deref() != Some("transparent")
+}
+
+/// Trips needed around `event`. There is no trip from the previous event
+/// or to the next one when it is at the same place.
+pub fn plan_trips(
+    event: &GoogleCalendarEvent,
+    neighbours: &[GoogleCalendarEvent],
+    buffer_ids: &[String],
+    settings: &TravelBufferSettings,
+) -> Vec<Trip> {
+    let (Some(location), Some((start, end))) = (physical_location(event), event_span(event)) else {
+        return Vec::new();
+    };
+    let home = settings.home_location.as_deref().map(str::trim).filter(|home| !home.is_empty());
+    let timed: Vec<(&GoogleCalendarEvent, DateTime<Utc>, DateTime<Utc>)> = neighbours
+        .iter()
+        .filter(|other| counts_as_neighbour(other, &event.id, buffer_ids))
+        .filter_map(|other| event_span(other).map(|(s, e)| (other, s, e)))
+        .collect();
+
+    let previous = timed.iter().filter(|(_, _, e)| *e <= start).max_by_key(|(_, _, e)| *e);
+    let next = timed.iter().filter(|(_, s, _)| *s >= end).min_by_key(|(_, s, _)| *s);
+
+    let mut trips = Vec::new();
+    let origin = previous.and_then(|(other, _, _)| physical_location(other)).or(home);
+    if !origin.is_some_and(|origin| same_place(origin, location)) {
+        trips.push(Trip {
+            side: BufferSide::Before,
+            origin: origin.map(str::to_string),
+            destination: Some(location.to_string()),
+            at: start,
+        });
+    }
+    let destination = next.and_then(|(other, _, _)| physical_location(other)).or(home);
+    if !destination.is_some_and(|destination| same_place(destination, location)) {
+        trips.push(Trip {
+            side: BufferSide::After,
+            origin: Some(location.to_string()),
+            destination: destination.map(str::to_string),
+            at: end,
+        });
+    }
+    trips
+}
+
+fn round_up(minutes: i64) -> i64 {
+    ((minutes + ROUND_MINUTES - 1) / ROUND_MINUTES * ROUND_MINUTES).clamp(ROUND_MINUTES, MAX_TRAVEL_MINUTES)
+}
+
+#[derive(Deserialize)]
+struct RouteResponse {
+    duration_seconds: f64,
+}
+
+async fn route_minutes(url: &str, origin: &str, destination: &str, trip: &Trip) -> Result<i64> {
+    let time_param = match trip.side {
+        BufferSide::Before => "arrive_by",
+        BufferSide::After => "depart_at",
+    };
+    let response = http_client()
+        .get(url)
+        .query(&[("origin", origin), ("destination", destination), (time_param, &trip.at.to_rfc3339())])
+        .send()
+        .await
+        .map_err(|e| LibreOllamaError::Network {
+            message: format!("Routing request failed: {}", e),
+            url: Some(url.to_string()),
+        })?;
+    if !response.status().is_success() {
+        return Err(LibreOllamaError::Network {
+            message: format!("Routing provider answered {}", response.status()),
+            url: Some(url.to_string()),
+        });
+    }
+    let route: RouteResponse = response.json().await.map_err(|e| LibreOllamaError::Serialization {
+        message: format!("Failed to parse routing response: {}", e),
+        data_type: "Travel route".to_string(),
+    })?;
+    if !route.duration_seconds.is_finite() || route.duration_seconds < 0.0 {
+        return Err(LibreOllamaError::Serialization {
+            message: format!("Routing provider returned a duration of {} seconds", route.duration_seconds),
+            data_type: "Travel route".to_string(),
+        });
+    }
+    Ok(round_up((route.duration_seconds / 60.0).ceil() as i64))
+}
+
+/// Minutes a trip takes, from the routing provider when it can answer
+pub async fn estimate_minutes(settings: &TravelBufferSettings, trip: &Trip) -> (i64, EstimateSource) {
+    let fixed = (settings.fixed_minutes(trip.side), EstimateSource::Fixed);
+    let TravelTimeProvider::Routing { url } = &settings.provider else {
+        return fixed;
+    };
+    let (Some(origin), Some(destination)) = (&trip.origin, &trip.destination) else {
+        return fixed;
+    };
+    match route_minutes(url, origin, destination, trip).await {
+        Ok(minutes) => (minutes, EstimateSource::Routing),
+        Err(e) => {
+            eprintln!("⚠️ [TRAVEL-BUFFERS] Routing failed, using fixed buffers: {}", e);
+            fixed
+        }
+    }
+}
+
+/// The buffer block for a trip of `minutes`
+pub fn place_buffer(
+    trip: &Trip,
+    minutes: i64,
+    source: EstimateSource,
+    neighbours: &[GoogleCalendarEvent],
+    event_id: &str,
+) -> TravelBufferSuggestion {
+    let (start, end, summary) = match trip.side {
+        BufferSide::Before => (
+            trip.at - Duration::minutes(minutes),
+            trip.at,
+            i18n::t_args("travel-buffer-to", &[("place", trip.destination.clone().unwrap_or_default().into())]),
+        ),
+        BufferSide::After => (
+            trip.at,
+            trip.at + Duration::minutes(minutes),
+            i18n::t_args("travel-buffer-from", &[("place", trip.origin.clone().unwrap_or_default().into())]),
+        ),
+    };
+    let conflicts = neighbours
+        .iter()
+        .filter(|other| counts_as_neighbour(other, event_id, &[]))
+        .filter(|other| event_span(other).is_some_and(|(s, e)| s < end && start < e))
+        .map(|other| other.summary.clone().unwrap_or_else(|| "(No title)".to_string()))
+        .collect();
+    TravelBufferSuggestion {
+        side: trip.side,
+        start,
+        end,
+        minutes,
+        origin: trip.origin.clone(),
+        destination: trip.destination.clone(),
+        source,
+        summary,
+        conflicts,
+    }
+}
+
+fn events_url(endpoints: &GoogleEndpoints, calendar_id: &str) -> String {
+    GoogleEndpoints::url(&endpoints.calendar_api, &format!("calendars/{}/events", urlencoding::encode(calendar_id)))
+}
+
+async fn calendar_get(account_id: &str, access_token: &str, url: &str, query: &[(&str, String)]) -> Result<serde_json::Value> {
+    let request = http_client().get(url).bearer_auth(access_token).query(query);
+    let response = request_scheduler::send(Some(account_id), RequestPriority::Medium, request)
+        .await
+        .map_err(|e| LibreOllamaError::Network {
+            message: format!("Calendar request failed: {}", e),
+            url: Some(url.to_string()),
+        })?;
+    if !response.status().is_success() {
+        let status = response.status().as_u16();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(LibreOllamaError::GoogleCalendarApi {
+            message: format!("Calendar request failed: {}", error_text),
+            status_code: Some(status),
+        });
+    }
+    response.json().await.map_err(|e| LibreOllamaError::Serialization {
+        message: format!("Failed to parse calendar response: {}", e),
+        data_type: "Calendar events".to_string(),
+    })
+}
+
+fn parse_event(value: serde_json::Value) -> Result<GoogleCalendarEvent> {
+    serde_json::from_value(value).map_err(|e| LibreOllamaError::Serialization {
+        message: format!("Failed to parse calendar event: {}", e),
+        data_type: "Calendar event".to_string(),
+    })
+}
+
+/// Buffer events already created for `event_id`, with the side each covers
+async fn existing_buffers(
+    endpoints: &GoogleEndpoints,
+    account_id: &str,
+    access_token: &str,
+    calendar_id: &str,
+    event_id: &str,
+) -> Result<Vec<(String, Option<BufferSide>)>> {
+    let body = calendar_get(
+        account_id,
+        access_token,
+        &events_url(endpoints, calendar_id),
+        &[("privateExtendedProperty", format!("{}={}", BUFFER_FOR_PROPERTY, event_id))],
+    )
+    .await?;
+    Ok(body["items"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|item| item["status"].as_str() != Some("cancelled"))
+        .filter_map(|item| {
+            let side = match item["extendedProperties"]["private"][BUFFER_SIDE_PROPERTY].as_str() {
+                Some("before") => Some(BufferSide::Before),
+                Some("after") => Some(BufferSide::After),
+                _ => None,
+            };
+            Some((item["id"].as_str()?.to_string(), side))
+        })
+        .collect())
+}
+
+/// Suggested buffers for an event, leaving out sides that already have one
+pub async fn suggest(
+    endpoints: &GoogleEndpoints,
+    account_id: &str,
+    access_token: &str,
+    calendar_id: &str,
+    event_id: &str,
+    settings: &TravelBufferSettings,
+) -> Result<TravelBufferSuggestions> {
+    let event_url = format!("{}/{}", events_url(endpoints, calendar_id), urlencoding::encode(event_id));
+    let event = parse_event(calendar_get(account_id, access_token, &event_url, &[]).await?)?;
+    let location = physical_location(&event).map(str::to_string);
+    let mut result = TravelBufferSuggestions {
+        event_id: event_id.to_string(),
+        location,
+        existing: Vec::new(),
+        suggestions: Vec::new(),
+    };
+    let Some((start, end)) = event_span(&event).filter(|_| result.location.is_some()) else {
+        return Ok(result);
+    };
+
+    let existing = existing_buffers(endpoints, account_id, access_token, calendar_id, event_id).await?;
+    let window = Duration::hours(NEIGHBOUR_WINDOW_HOURS);
+    let listed = calendar_get(
+        account_id,
+        access_token,
+        &events_url(endpoints, calendar_id),
+        &[
+            ("timeMin", (start - window).to_rfc3339()),
+            ("timeMax", (end + window).to_rfc3339()),
+            ("singleEvents", "true".to_string()),
+            ("orderBy", "startTime".to_string()),
+        ],
+    )
+    .await?;
+    let neighbours: Vec<GoogleCalendarEvent> = listed["items"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|item| serde_json::from_value(item.clone()).ok())
+        .collect();
+    result.existing = existing.iter().map(|(id, _)| id.clone()).collect();
+
+    for trip in plan_trips(&event, &neighbours, &result.existing, settings) {
+        if existing.iter().any(|(_, side)| side.is_none_or(|side| side == trip.side)) {
+            continue;
+        }
+        let (minutes, source) = estimate_minutes(settings, &trip).await;
+        if minutes > 0 {
+            let neighbours: Vec<GoogleCalendarEvent> =
+                neighbours.iter().filter(|other| !result.existing.contains(&other.id)).cloned().collect();
+            result.suggestions.push(place_buffer(&trip, minutes, source, &neighbours, event_id));
+        }
+    }
+    Ok(result)
+}
+
+/// Create buffer events for `event_id` in the same calendar
+pub async fn insert_buffers(
+    endpoints: &GoogleEndpoints,
+    account_id: &str,
+    access_token: &str,
+    calendar_id: &str,
+    event_id: &str,
+    suggestions: &[TravelBufferSuggestion],
+) -> Result<Vec<GoogleCalendarEvent>> {
+    let url = events_url(endpoints, calendar_id);
+    let mut created = Vec::new();
+    for suggestion in suggestions {
+        if suggestion.end <= suggestion.start {
+            return Err(LibreOllamaError::InvalidInput {
+                message: "A travel buffer must end after it starts".to_string(),
+                field: Some("suggestions".to_string()),
+            });
+        }
+        let description = match (&suggestion.origin, &suggestion.destination) {
+            (Some(origin), Some(destination)) => format!("{} → {}", origin, destination),
+            _ => String::new(),
+        };
+        let body = serde_json::json!({
+            "summary": suggestion.summary,
+            "description": description,
+            "start": { "dateTime": suggestion.start.to_rfc3339() },
+            "end": { "dateTime": suggestion.end.to_rfc3339() },
+            "transparency": "opaque",
+            "reminders": { "useDefault": false },
+            "extendedProperties": { "private": {
+                BUFFER_FOR_PROPERTY: event_id,
+                BUFFER_SIDE_PROPERTY: suggestion.side.as_str(),
+            }},
+        });
+        let request = http_client().post(&url).bearer_auth(access_token).json(&body);
+        let response = request_scheduler::send(Some(account_id), RequestPriority::Medium, request)
+            .await
+            .map_err(|e| LibreOllamaError::Network {
+                message: format!("Failed to create travel buffer: {}", e),
+                url: Some(url.clone()),
+            })?;
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(LibreOllamaError::GoogleCalendarApi {
+                message: format!("Failed to create travel buffer: {}", error_text),
+                status_code: Some(status),
+            });
+        }
+        let value = response.json().await.map_err(|e| LibreOllamaError::Serialization {
+            message: format!("Failed to parse created travel buffer: {}", e),
+            data_type: "Calendar event".to_string(),
+        })?;
+        created.push(parse_event(value)?);
+    }
+    Ok(created)
+}
+
+/// Delete the buffer events created for `event_id`; returns their IDs
+pub async fn remove_buffers(
+    endpoints: &GoogleEndpoints,
+    account_id: &str,
+    access_token: &str,
+    calendar_id: &str,
+    event_id: &str,
+) -> Result<Vec<String>> {
+    let existing = existing_buffers(endpoints, account_id, access_token, calendar_id, event_id).await?;
+    let mut removed = Vec::new();
+    for (buffer_id, _) in existing {
+        let url = format!("{}/{}", events_url(endpoints, calendar_id), urlencoding::encode(&buffer_id));
+        let request = http_client().delete(&url).bearer_auth(access_token);
+        let response = request_scheduler::send(Some(account_id), RequestPriority::Medium, request)
+            .await
+            .map_err(|e| LibreOllamaError::Network {
+                message: format!("Failed to delete travel buffer: {}", e),
+                url: Some(url.clone()),
+            })?;
+        // Gone already is as good as deleted
+        let status = response.status().as_u16();
+        if !response.status().is_success() && status != 404 && status != 410 {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(LibreOllamaError::GoogleCalendarApi {
+                message: format!("Failed to delete travel buffer: {}", error_text),
+                status_code: Some(status),
+            });
+        }
+        removed.push(buffer_id);
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: &str, location: Option<&str>, start: &str, end: &str) -> GoogleCalendarEvent {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "summary": id,
+            "location": location,
+            "start": { "dateTime": start },
+            "end": { "dateTime": end },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn tells_places_from_meeting_links() {
+        assert!(is_physical_location("Café Central, Herrengasse 14, Vienna"));
+        assert!(is_physical_location("Room 4.12"));
+        assert!(!is_physical_location("https://zoom.us/j/123"));
+        assert!(!is_physical_location("meet.google.com/abc-defg-hij"));
+        assert!(!is_physical_location("Microsoft Teams Meeting"));
+        assert!(!is_physical_location("Online"));
+        assert!(!is_physical_location("  "));
+    }
+
+    #[test]
+    fn plans_trips_from_neighbouring_locations() {
+        let settings = TravelBufferSettings { home_location: Some("Home".to_string()), ..Default::default() };
+        let dentist = event("dentist", Some("Dental Clinic"), "2024-05-06T10:00:00Z", "2024-05-06T11:00:00Z");
+        let neighbours = vec![
+            event("standup", Some("Office"), "2024-05-06T08:30:00Z", "2024-05-06T09:00:00Z"),
+            event("call", Some("https://meet.google.com/x"), "2024-05-06T09:00:00Z", "2024-05-06T09:30:00Z"),
+            dentist.clone(),
+            event("checkup", Some("dental clinic"), "2024-05-06T11:00:00Z", "2024-05-06T11:30:00Z"),
+        ];
+
+        let trips = plan_trips(&dentist, &neighbours, &[], &settings);
+        // The call right before has no place, so the trip starts at home;
+        // the next appointment is in the same clinic
+        assert_eq!(trips, vec![Trip {
+            side: BufferSide::Before,
+            origin: Some("Home".to_string()),
+            destination: Some("Dental Clinic".to_string()),
+            at: "2024-05-06T10:00:00Z".parse().unwrap(),
+        }]);
+
+        let suggestion = place_buffer(&trips[0], 45, EstimateSource::Fixed, &neighbours, "dentist");
+        assert_eq!(suggestion.start, "2024-05-06T09:15:00Z".parse::<DateTime<Utc>>().unwrap());
+        assert_eq!(suggestion.conflicts, vec!["call".to_string()]);
+
+        let online = event("sync", Some("Zoom"), "2024-05-06T10:00:00Z", "2024-05-06T11:00:00Z");
+        assert!(plan_trips(&online, &neighbours, &[], &settings).is_empty());
+    }
+
+    #[test]
+    fn validates_settings() {
+        assert!(TravelBufferSettings::default().validate().is_ok());
+        let too_long = TravelBufferSettings { before_minutes: 600, ..Default::default() };
+        assert!(too_long.validate().is_err());
+        let routing = TravelBufferSettings {
+            provider: TravelTimeProvider::Routing { url: "ftp://routes.example".to_string() },
+            ..Default::default()
+        };
+        assert!(routing.validate().is_err());
+        assert_eq!(round_up(1), 5);
+        assert_eq!(round_up(23), 25);
+    }
+}
@@ -0,0 +1,162 @@
+//! Google API backoff guidance
+//!
+//! Google signals throttling with 429, or with 403 and a rate limit reason
+//! in the error body, and asks clients to retry 5xx `backendError`
+//! responses. A Retry-After header says how long to wait when it is sent;
+//! otherwise the guidance is truncated exponential backoff with jitter.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde_json::Value;
+
+use crate::errors::LibreOllamaError;
+
+const BASE_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(64);
+const MAX_JITTER_MS: u64 = 1000;
+
+/// Reasons in a 403 body that mean "slow down" rather than "forbidden"
+const RATE_LIMIT_REASONS: &[&str] = &["rateLimitExceeded", "userRateLimitExceeded", "RATE_LIMIT_EXCEEDED"];
+const BACKEND_ERROR_REASON: &str = "backendError";
+
+/// A response Google wants retried later
+#[derive(Debug, Clone, PartialEq)]
+pub struct Throttle {
+    pub status_code: u16,
+    pub reason: String,
+    /// From Retry-After, when the response had one
+    pub retry_after: Option<Duration>,
+}
+
+impl Throttle {
+    /// The error to report once retries are used up, waiting `delay`
+    pub fn into_error(self, delay: Duration) -> LibreOllamaError {
+        LibreOllamaError::RateLimit {
+            message: format!("Google API returned {} ({})", self.status_code, self.reason),
+            retry_after: Some(delay.as_millis() as u64),
+        }
+    }
+}
+
+/// Retry-After as delta-seconds or an HTTP date
+pub fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?.with_timezone(&Utc);
+    Some((date - now).to_std().unwrap_or(Duration::ZERO))
+}
+
+/// The first reason in a Google error body: `error.errors[].reason` (v1
+/// APIs), `error.details[].reason` (ErrorInfo) or `error.status`
+pub fn error_reason(body: &str) -> Option<String> {
+    let error = serde_json::from_str::<Value>(body).ok()?.get("error")?.clone();
+    let first_reason = |key: &str| {
+        error
+            .get(key)?
+            .as_array()?
+            .iter()
+            .find_map(|item| item.get("reason")?.as_str().map(str::to_string))
+    };
+    first_reason("errors")
+        .or_else(|| first_reason("details"))
+        .or_else(|| error.get("status")?.as_str().map(str::to_string))
+}
+
+/// Whether a response is throttling or a transient backend failure
+pub fn throttle_for(status_code: u16, headers: &HashMap<String, String>, body: &str) -> Option<Throttle> {
+    let reason = error_reason(body);
+    let throttled = match status_code {
+        429 => true,
+        403 => reason.as_deref().is_some_and(|reason| RATE_LIMIT_REASONS.contains(&reason)),
+        503 => true,
+        500..=599 => reason.as_deref() == Some(BACKEND_ERROR_REASON),
+        _ => false,
+    };
+    if !throttled {
+        return None;
+    }
+
+    let retry_after = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("retry-after"))
+        .and_then(|(_, value)| parse_retry_after(value, Utc::now()));
+    let reason = reason.unwrap_or_else(|| match status_code {
+        429 => "rateLimitExceeded".to_string(),
+        _ => BACKEND_ERROR_REASON.to_string(),
+    });
+    Some(Throttle { status_code, reason, retry_after })
+}
+
+/// Whether a throttled `method` request may be sent again. Reads and
+/// idempotent writes always may. A POST or PATCH only may when Google
+/// refused it outright (429 or a rate limit 403): a 5xx can come after the
+/// change was made, and sending it again could, say, deliver a mail twice.
+pub fn retry_is_safe(method: &str, throttle: &Throttle) -> bool {
+    match method.to_ascii_uppercase().as_str() {
+        "POST" | "PATCH" => matches!(throttle.status_code, 429 | 403),
+        _ => true,
+    }
+}
+
+/// Truncated exponential backoff after `failures` throttled attempts in a row
+pub fn exponential_backoff(failures: u32) -> Duration {
+    let exponent = failures.saturating_sub(1).min(6);
+    let delay = (BASE_BACKOFF * 2u32.pow(exponent)).min(MAX_BACKOFF);
+    delay + Duration::from_millis(rand::thread_rng().gen_range(0..MAX_JITTER_MS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_parses_retry_after() {
+        let now = DateTime::parse_from_rfc3339("2024-05-01T10:00:00Z").unwrap().with_timezone(&Utc);
+        assert_eq!(parse_retry_after("30", now), Some(Duration::from_secs(30)));
+        assert_eq!(parse_retry_after("Wed, 01 May 2024 10:01:00 GMT", now), Some(Duration::from_secs(60)));
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn test_classifies_google_errors() {
+        let user_limit = r#"{"error":{"code":403,"message":"User-rate limit exceeded","errors":[{"domain":"usageLimits","reason":"userRateLimitExceeded"}]}}"#;
+        let throttle = throttle_for(403, &headers(&[("retry-after", "5")]), user_limit).unwrap();
+        assert_eq!(throttle.reason, "userRateLimitExceeded");
+        assert_eq!(throttle.retry_after, Some(Duration::from_secs(5)));
+
+        let forbidden = r#"{"error":{"code":403,"errors":[{"reason":"insufficientPermissions"}]}}"#;
+        assert!(throttle_for(403, &HashMap::new(), forbidden).is_none());
+
+        let backend = r#"{"error":{"code":500,"errors":[{"reason":"backendError"}]}}"#;
+        assert_eq!(throttle_for(500, &HashMap::new(), backend).unwrap().reason, "backendError");
+        assert!(throttle_for(500, &HashMap::new(), "{}").is_none());
+
+        let status_only = r#"{"error":{"code":429,"status":"RESOURCE_EXHAUSTED"}}"#;
+        assert_eq!(throttle_for(429, &HashMap::new(), status_only).unwrap().reason, "RESOURCE_EXHAUSTED");
+    }
+
+    #[test]
+    fn test_writes_are_retried_only_when_refused() {
+        let throttle = |status_code| Throttle { status_code, reason: String::new(), retry_after: None };
+        assert!(retry_is_safe("POST", &throttle(429)));
+        assert!(!retry_is_safe("POST", &throttle(503)));
+        assert!(!retry_is_safe("patch", &throttle(500)));
+        assert!(retry_is_safe("GET", &throttle(503)));
+        assert!(retry_is_safe("DELETE", &throttle(500)));
+    }
+
+    #[test]
+    fn test_backoff_is_truncated() {
+        assert!(exponential_backoff(1) < Duration::from_secs(2));
+        assert!(exponential_backoff(20) <= MAX_BACKOFF + Duration::from_millis(MAX_JITTER_MS));
+    }
+}
@@ -0,0 +1,279 @@
+//! Hardware profile for model selection
+//!
+//! System memory comes from `sysinfo`; GPU memory from `nvidia-smi` for
+//! NVIDIA cards and from sysfs for AMD cards on Linux. Apple Silicon GPUs
+//! share system memory, of which macOS lets the GPU use roughly 70%. The
+//! profile is detected at startup and kept in `user_preferences` so the
+//! model router can check a model fits before it is loaded.
+
+use std::path::Path;
+use std::process::Command;
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use sysinfo::System;
+
+use crate::database::operations::preference_operations;
+use crate::errors::Result;
+use crate::services::i18n;
+
+/// user_preferences key holding the last detected profile as JSON
+pub const HARDWARE_PROFILE_PREFERENCE: &str = "system.hardware_profile";
+
+/// Share of unified memory the GPU may use
+const UNIFIED_GPU_SHARE: f64 = 0.7;
+/// Loaded weights take a little more than the file, plus the KV cache
+const MODEL_MEMORY_FACTOR: f64 = 1.2;
+const MODEL_MEMORY_OVERHEAD: u64 = 512 * 1024 * 1024;
+/// Share of system memory a model running on the CPU may take
+const CPU_COMFORTABLE_SHARE: f64 = 0.5;
+const CPU_MAXIMUM_SHARE: f64 = 0.8;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GpuInfo {
+    pub name: String,
+    pub vendor: String,
+    pub vram_total_bytes: u64,
+    pub vram_free_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HardwareProfile {
+    pub total_memory_bytes: u64,
+    pub available_memory_bytes: u64,
+    pub cpu_count: usize,
+    pub gpus: Vec<GpuInfo>,
+    /// GPU and CPU share system memory (Apple Silicon)
+    pub unified_memory: bool,
+    pub detected_at: String,
+}
+
+impl HardwareProfile {
+    /// Memory a model can be loaded into on the GPU, if there is one
+    pub fn accelerator_bytes(&self) -> Option<u64> {
+        if self.unified_memory {
+            return Some((self.total_memory_bytes as f64 * UNIFIED_GPU_SHARE) as u64);
+        }
+        self.gpus.iter().map(|gpu| gpu.vram_total_bytes).max()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FitLevel {
+    Fits,
+    /// Loads, but partly on the CPU or close to the memory limit
+    Tight,
+    TooLarge,
+}
+
+/// How a model of a given size fits this machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceFit {
+    pub level: FitLevel,
+    pub required_bytes: u64,
+    pub available_bytes: u64,
+    pub message: String,
+}
+
+/// Memory needed to run a model whose files take `size_bytes`
+pub fn required_memory(size_bytes: u64) -> u64 {
+    (size_bytes as f64 * MODEL_MEMORY_FACTOR) as u64 + MODEL_MEMORY_OVERHEAD
+}
+
+fn gib(bytes: u64) -> String {
+    format!("{:.1} GB", bytes as f64 / (1024.0 * 1024.0 * 1024.0))
+}
+
+pub fn assess(profile: &HardwareProfile, size_bytes: u64) -> ResourceFit {
+    let required = required_memory(size_bytes);
+    let system = profile.total_memory_bytes;
+    let cpu_comfortable = (system as f64 * CPU_COMFORTABLE_SHARE) as u64;
+    let cpu_maximum = (system as f64 * CPU_MAXIMUM_SHARE) as u64;
+
+    let (level, available, message) = match profile.accelerator_bytes() {
+        Some(gpu) if required <= gpu => (FitLevel::Fits, gpu, i18n::t_args("resource-fits-gpu", &[("gpu", gib(gpu).into())])),
+        Some(gpu) if required <= cpu_maximum => (
+            FitLevel::Tight,
+            gpu,
+            i18n::t_args("resource-tight-gpu", &[("required", gib(required).into()), ("gpu", gib(gpu).into())]),
+        ),
+        None if required <= cpu_comfortable => {
+            (FitLevel::Fits, cpu_comfortable, i18n::t_args("resource-fits-cpu", &[("available", gib(cpu_comfortable).into())]))
+        }
+        None if required <= cpu_maximum => (
+            FitLevel::Tight,
+            cpu_maximum,
+            i18n::t_args("resource-tight-cpu", &[("required", gib(required).into()), ("system", gib(system).into())]),
+        ),
+        _ => (
+            FitLevel::TooLarge,
+            profile.accelerator_bytes().unwrap_or(0).max(cpu_maximum),
+            i18n::t_args("resource-too-large", &[("required", gib(required).into()), ("system", gib(system).into())]),
+        ),
+    };
+    ResourceFit { level, required_bytes: required, available_bytes: available, message }
+}
+
+/// Parse `nvidia-smi --query-gpu=name,memory.total,memory.free --format=csv,noheader,nounits`
+pub fn parse_nvidia_smi(output: &str) -> Vec<GpuInfo> {
+    const MIB: u64 = 1024 * 1024;
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(',').map(str::trim);
+            let name = fields.next().filter(|name| !name.is_empty())?;
+            let total: u64 = fields.next()?.parse().ok()?;
+            let free = fields.next().and_then(|free| free.parse::<u64>().ok());
+            Some(GpuInfo {
+                name: name.to_string(),
+                vendor: "nvidia".to_string(),
+                vram_total_bytes: total * MIB,
+                vram_free_bytes: free.map(|free| free * MIB),
+            })
+        })
+        .collect()
+}
+
+fn nvidia_gpus() -> Vec<GpuInfo> {
+    Command::new("nvidia-smi")
+        .args(["--query-gpu=name,memory.total,memory.free", "--format=csv,noheader,nounits"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| parse_nvidia_smi(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or_default()
+}
+
+/// AMD cards expose their memory through the amdgpu driver in sysfs
+fn amd_gpus() -> Vec<GpuInfo> {
+    let Ok(cards) = std::fs::read_dir("/sys/class/drm") else {
+        return Vec::new();
+    };
+    let read_number = |path: &Path| std::fs::read_to_string(path).ok()?.trim().parse::<u64>().ok();
+    cards
+        .flatten()
+        .filter(|card| card.file_name().to_string_lossy().chars().skip(4).all(|c| c.is_ascii_digit()))
+        .filter_map(|card| {
+            let device = card.path().join("device");
+            let total = read_number(&device.join("mem_info_vram_total"))?;
+            let used = read_number(&device.join("mem_info_vram_used"));
+            let name = std::fs::read_to_string(device.join("product_name"))
+                .map(|name| name.trim().to_string())
+                .unwrap_or_else(|_| "AMD GPU".to_string());
+            Some(GpuInfo {
+                name,
+                vendor: "amd".to_string(),
+                vram_total_bytes: total,
+                vram_free_bytes: used.map(|used| total.saturating_sub(used)),
+            })
+        })
+        .collect()
+}
+
+/// The chip name on Apple Silicon, e.g. "Apple M2 Pro"
+fn apple_silicon() -> Option<String> {
+    if !cfg!(target_os = "macos") {
+        return None;
+    }
+    let output = Command::new("sysctl").args(["-n", "machdep.cpu.brand_string"]).output().ok()?;
+    let brand = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    brand.starts_with("Apple").then_some(brand)
+}
+
+/// Detect memory and GPUs; runs external tools, so call off the async runtime
+pub fn detect() -> HardwareProfile {
+    let mut system = System::new();
+    system.refresh_memory();
+    system.refresh_cpu();
+
+    let apple = apple_silicon();
+    let mut gpus = nvidia_gpus();
+    if gpus.is_empty() && cfg!(target_os = "linux") {
+        gpus = amd_gpus();
+    }
+    if let Some(chip) = &apple {
+        gpus.push(GpuInfo {
+            name: chip.clone(),
+            vendor: "apple".to_string(),
+            vram_total_bytes: (system.total_memory() as f64 * UNIFIED_GPU_SHARE) as u64,
+            vram_free_bytes: None,
+        });
+    }
+
+    HardwareProfile {
+        total_memory_bytes: system.total_memory(),
+        available_memory_bytes: system.available_memory(),
+        cpu_count: system.cpus().len(),
+        gpus,
+        unified_memory: apple.is_some(),
+        detected_at: chrono::Local::now().to_rfc3339(),
+    }
+}
+
+pub fn load_profile(conn: &Connection) -> Result<Option<HardwareProfile>> {
+    Ok(preference_operations::get_preference_value(conn, HARDWARE_PROFILE_PREFERENCE)?
+        .and_then(|json| serde_json::from_str(&json).ok()))
+}
+
+pub fn save_profile(conn: &Connection, profile: &HardwareProfile) -> Result<()> {
+    preference_operations::set_preference_value(
+        conn,
+        HARDWARE_PROFILE_PREFERENCE,
+        &serde_json::to_string(profile)?,
+        "system",
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GIB: u64 = 1024 * 1024 * 1024;
+
+    fn profile(memory_gib: u64, vram_gib: Option<u64>) -> HardwareProfile {
+        HardwareProfile {
+            total_memory_bytes: memory_gib * GIB,
+            available_memory_bytes: memory_gib * GIB / 2,
+            cpu_count: 8,
+            gpus: vram_gib
+                .map(|vram| GpuInfo {
+                    name: "GPU".to_string(),
+                    vendor: "nvidia".to_string(),
+                    vram_total_bytes: vram * GIB,
+                    vram_free_bytes: None,
+                })
+                .into_iter()
+                .collect(),
+            unified_memory: false,
+            detected_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_nvidia_smi() {
+        let gpus = parse_nvidia_smi("NVIDIA GeForce RTX 3060, 12288, 11020\n\n");
+        assert_eq!(gpus.len(), 1);
+        assert_eq!(gpus[0].vram_total_bytes, 12 * GIB);
+        assert_eq!(gpus[0].vram_free_bytes, Some(11020 * 1024 * 1024));
+        assert!(parse_nvidia_smi("No devices were found").is_empty());
+    }
+
+    #[test]
+    fn test_assess() {
+        let gaming = profile(32, Some(12));
+        assert_eq!(assess(&gaming, 4 * GIB).level, FitLevel::Fits);
+        assert_eq!(assess(&gaming, 18 * GIB).level, FitLevel::Tight);
+        assert_eq!(assess(&gaming, 40 * GIB).level, FitLevel::TooLarge);
+
+        let laptop = profile(16, None);
+        assert_eq!(assess(&laptop, 2 * GIB).level, FitLevel::Fits);
+        assert_eq!(assess(&laptop, 9 * GIB).level, FitLevel::Tight);
+        assert_eq!(assess(&laptop, 14 * GIB).level, FitLevel::TooLarge);
+
+        let mac = HardwareProfile { unified_memory: true, ..profile(32, None) };
+        assert_eq!(mac.accelerator_bytes(), Some((32.0 * GIB as f64 * 0.7) as u64));
+        assert_eq!(assess(&mac, 16 * GIB).level, FitLevel::Fits);
+    }
+}
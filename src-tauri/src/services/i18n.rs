@@ -0,0 +1,238 @@
+//! Backend translations
+//!
+//! Strings the backend shows to people (error messages, the daily digest,
+//! model memory checks) come from Fluent files in `locales/`, compiled into
+//! the binary. The locale follows the `appearance.locale` setting; "system"
+//! takes the operating system's language. Anything missing from a
+//! translation falls back to English.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use rusqlite::Connection;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use unic_langid::LanguageIdentifier;
+
+use crate::database::DatabaseManager;
+use crate::services::settings::{self, SettingsService};
+
+pub const LOCALE_SETTING: &str = "appearance.locale";
+/// Setting value that follows the operating system's language
+pub const SYSTEM_LOCALE: &str = "system";
+pub const DEFAULT_LOCALE: &str = "en-US";
+pub const LOCALE_CHOICES: &[&str] = &[SYSTEM_LOCALE, "en-US", "de", "es", "fr"];
+
+/// Shipped locales: id, native name, chrono locale for dates
+const LOCALES: &[(&str, &str, &str)] = &[
+    ("en-US", "English (US)", "en_US"),
+    ("de", "Deutsch", "de_DE"),
+    ("es", "Español", "es_ES"),
+    ("fr", "Français", "fr_FR"),
+];
+
+const SOURCES: &[(&str, &str)] = &[
+    ("en-US", include_str!("../../locales/en-US.ftl")),
+    ("de", include_str!("../../locales/de.ftl")),
+    ("es", include_str!("../../locales/es.ftl")),
+    ("fr", include_str!("../../locales/fr.ftl")),
+];
+
+lazy_static::lazy_static! {
+    static ref BUNDLES: HashMap<&'static str, FluentBundle<FluentResource>> = SOURCES
+        .iter()
+        .map(|(id, source)| (*id, bundle(id, source)))
+        .collect();
+    static ref ACTIVE: RwLock<&'static str> = RwLock::new(DEFAULT_LOCALE);
+}
+
+fn bundle(id: &str, source: &str) -> FluentBundle<FluentResource> {
+    let langid: LanguageIdentifier = id.parse().expect("shipped locale ids are valid");
+    let resource = FluentResource::try_new(source.to_string()).unwrap_or_else(|(resource, errors)| {
+        eprintln!("⚠️ [I18N] {} errors in the {} translations", errors.len(), id);
+        resource
+    });
+    let mut bundle = FluentBundle::new_concurrent(vec![langid]);
+    // Isolation marks would end up in plain-text notifications and email
+    bundle.set_use_isolating(false);
+    if let Err(errors) = bundle.add_resource(resource) {
+        eprintln!("⚠️ [I18N] {} duplicate messages in the {} translations", errors.len(), id);
+    }
+    bundle
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LocaleInfo {
+    pub id: String,
+    pub name: String,
+    pub active: bool,
+}
+
+pub fn available_locales() -> Vec<LocaleInfo> {
+    let active = active();
+    LOCALES
+        .iter()
+        .map(|(id, name, _)| LocaleInfo { id: id.to_string(), name: name.to_string(), active: *id == active })
+        .collect()
+}
+
+/// The shipped locale closest to a BCP 47 tag: an exact match, then the
+/// same language, then English
+pub fn negotiate(requested: &str) -> &'static str {
+    let requested = requested.replace('_', "-");
+    let requested = requested.split('.').next().unwrap_or_default();
+    if let Some((id, _, _)) = LOCALES.iter().find(|(id, _, _)| id.eq_ignore_ascii_case(requested)) {
+        return id;
+    }
+    let language = requested.split('-').next().unwrap_or_default();
+    LOCALES
+        .iter()
+        .find(|(id, _, _)| id.split('-').next().is_some_and(|lang| lang.eq_ignore_ascii_case(language)))
+        .map(|(id, _, _)| *id)
+        .unwrap_or(DEFAULT_LOCALE)
+}
+
+pub fn system_locale() -> &'static str {
+    sys_locale::get_locale().as_deref().map(negotiate).unwrap_or(DEFAULT_LOCALE)
+}
+
+/// The locale a setting value selects
+pub fn resolve(setting: &str) -> &'static str {
+    if setting.is_empty() || setting == SYSTEM_LOCALE {
+        system_locale()
+    } else {
+        negotiate(setting)
+    }
+}
+
+pub fn active() -> &'static str {
+    *ACTIVE.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// English name of the active locale's language, for prompts asking a
+/// model to answer in it
+pub fn language_name() -> &'static str {
+    match active().split('-').next().unwrap_or_default() {
+        "de" => "German",
+        "es" => "Spanish",
+        "fr" => "French",
+        _ => "English",
+    }
+}
+
+pub fn set_active(locale: &'static str) {
+    *ACTIVE.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = locale;
+}
+
+/// Read the locale setting and make it active
+pub fn apply_setting(conn: &Connection) -> anyhow::Result<&'static str> {
+    let setting = settings::get_setting(conn, LOCALE_SETTING)?;
+    let locale = resolve(setting.value.as_str().unwrap_or(SYSTEM_LOCALE));
+    set_active(locale);
+    Ok(locale)
+}
+
+/// Keep the active locale in step with the setting, however it is changed
+pub fn follow_setting(app: AppHandle, db_manager: Arc<DatabaseManager>) {
+    tauri::async_runtime::spawn(async move {
+        let mut changes = app.try_state::<SettingsService>().map(|service| service.subscribe());
+        loop {
+            settings::changed(&mut changes, LOCALE_SETTING).await;
+            match db_manager.get_connection().and_then(|conn| apply_setting(&conn)) {
+                Ok(locale) => println!("🌐 [I18N] Locale set to {}", locale),
+                Err(e) => eprintln!("⚠️ [I18N] Failed to apply the locale setting: {}", e),
+            }
+        }
+    });
+}
+
+/// Format message `id` in `locale`, falling back to English and then to
+/// the id itself
+pub fn translate(locale: &str, id: &str, args: Option<&FluentArgs>) -> String {
+    for locale in [locale, DEFAULT_LOCALE] {
+        let Some(bundle) = BUNDLES.get(locale) else { continue };
+        let Some(pattern) = bundle.get_message(id).and_then(|message| message.value()) else { continue };
+        let mut errors = Vec::new();
+        let text = bundle.format_pattern(pattern, args, &mut errors);
+        if !errors.is_empty() {
+            eprintln!("⚠️ [I18N] Formatting {} in {} failed: {:?}", id, locale, errors);
+        }
+        return text.into_owned();
+    }
+    id.to_string()
+}
+
+/// Message `id` in the active locale
+pub fn t(id: &str) -> String {
+    translate(active(), id, None)
+}
+
+/// Message `id` in the active locale with variables
+pub fn t_args(id: &str, args: &[(&str, FluentValue)]) -> String {
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, value.clone());
+    }
+    translate(active(), id, Some(&fluent_args))
+}
+
+/// Month and weekday names for the active locale
+pub fn chrono_locale() -> chrono::Locale {
+    let active = active();
+    LOCALES
+        .iter()
+        .find(|(id, _, _)| *id == active)
+        .and_then(|(_, _, posix)| chrono::Locale::try_from(*posix).ok())
+        .unwrap_or(chrono::Locale::en_US)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(n: usize) -> FluentArgs<'static> {
+        let mut args = FluentArgs::new();
+        args.set("count", n);
+        args
+    }
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(negotiate("en-US"), "en-US");
+        assert_eq!(negotiate("de-AT"), "de");
+        assert_eq!(negotiate("fr_CA.UTF-8"), "fr");
+        assert_eq!(negotiate("en-GB"), "en-US");
+        assert_eq!(negotiate("ja-JP"), DEFAULT_LOCALE);
+        assert_eq!(resolve("es"), "es");
+    }
+
+    #[test]
+    fn test_translate_plurals_and_fallback() {
+        assert_eq!(translate("en-US", "digest-events", Some(&count(1))), "1 event");
+        assert_eq!(translate("en-US", "digest-events", Some(&count(3))), "3 events");
+        assert_eq!(translate("de", "digest-events", Some(&count(2))), "2 Termine");
+        assert_eq!(translate("ja", "digest-all-day", None), "All day");
+        assert_eq!(translate("fr", "no-such-message", None), "no-such-message");
+    }
+
+    #[test]
+    fn test_every_locale_has_every_message() {
+        let english: Vec<&str> = SOURCES[0]
+            .1
+            .lines()
+            .filter(|line| line.starts_with(|c: char| c.is_ascii_lowercase()))
+            .filter_map(|line| line.split(" =").next())
+            .collect();
+        assert!(english.contains(&"error-unexpected"));
+        for (id, _, _) in LOCALES {
+            for message in &english {
+                assert!(BUNDLES[id].has_message(message), "{} is missing {}", id, message);
+            }
+        }
+        for choice in LOCALE_CHOICES.iter().filter(|choice| **choice != SYSTEM_LOCALE) {
+            assert!(LOCALES.iter().any(|(id, _, _)| id == choice));
+        }
+    }
+}
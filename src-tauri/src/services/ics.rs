@@ -0,0 +1,412 @@
+//! iCalendar import
+//!
+//! ICS files opened with the app and `webcal:` subscriptions are read into
+//! `imported_calendars`. The calendar commands list them and their events
+//! beside the Google calendars, and digests and search see their events
+//! through the search index. A webcal link is an https URL under another
+//! scheme; subscribing to it again refreshes the same calendar, as does
+//! importing the same file again.
+//!
+//! Only what the calendar view shows is read from each VEVENT: times, the
+//! text fields and status. Recurring events keep their RRULE and EXDATE
+//! lines for the frontend to expand. A moved occurrence (RECURRENCE-ID) is
+//! stored as an event of its own and its original time is added to the
+//! series' EXDATEs, so it replaces the occurrence rather than doubling it.
+//! Alarms are ignored.
+
+use std::io::Read;
+use std::path::Path;
+use std::sync::Arc;
+
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::database::operations::imported_calendar_operations::{self, ImportedCalendar, ImportedEvent};
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::time_service::TimeService;
+use crate::utils::http_client::fetch_public;
+
+const ICS_ACCEPT: &str = "text/calendar, text/plain;q=0.5";
+pub const MAX_ICS_BYTES: usize = 10 * 1024 * 1024;
+/// Imported calendar IDs start with this, keeping them apart from Google's
+pub const CALENDAR_ID_PREFIX: &str = "ics:";
+
+/// A calendar as read from an ICS file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedCalendar {
+    /// X-WR-CALNAME, which most publishers set
+    pub name: Option<String>,
+    pub events: Vec<ImportedEvent>,
+}
+
+/// Join folded lines: a line starting with a space or tab continues the
+/// previous one
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.split('\n').map(|line| line.strip_suffix('\r').unwrap_or(line)) {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ if line.is_empty() => {}
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+fn unescape(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => text.push('\n'),
+            Some(other) => text.push(other),
+            None => text.push('\\'),
+        }
+    }
+    text
+}
+
+/// A content line split into its name, parameters and value
+struct Property<'a> {
+    name: String,
+    params: Vec<(String, &'a str)>,
+    value: &'a str,
+}
+
+impl<'a> Property<'a> {
+    fn parse(line: &'a str) -> Option<Self> {
+        // The value starts at the first colon outside a quoted parameter
+        let mut quoted = false;
+        let colon = line.char_indices().find_map(|(i, c)| {
+            if c == '"' {
+                quoted = !quoted;
+            }
+            (c == ':' && !quoted).then_some(i)
+        })?;
+        let mut parts = line[..colon].split(';');
+        let name = parts.next()?.to_ascii_uppercase();
+        let params = parts
+            .filter_map(|param| param.split_once('='))
+            .map(|(key, value)| (key.to_ascii_uppercase(), value.trim_matches('"')))
+            .collect();
+        Some(Self { name, params, value: &line[colon + 1..] })
+    }
+
+    fn param(&self, key: &str) -> Option<&'a str> {
+        self.params.iter().find(|(name, _)| name == key).map(|(_, value)| *value)
+    }
+}
+
+/// An ICS date or date-time in the form Google's event times use
+enum EventTime {
+    Date(String),
+    DateTime { value: String, time_zone: Option<String> },
+}
+
+fn parse_time(property: &Property) -> Option<EventTime> {
+    let value = property.value.trim();
+    let date = chrono::NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok()?;
+    if value.len() == 8 || property.param("VALUE") == Some("DATE") {
+        return Some(EventTime::Date(date.format("%Y-%m-%d").to_string()));
+    }
+    let time = chrono::NaiveTime::parse_from_str(value.get(9..15)?, "%H%M%S").ok()?;
+    let local = date.and_time(time).format("%Y-%m-%dT%H:%M:%S").to_string();
+    Some(if value.ends_with('Z') {
+        EventTime::DateTime { value: format!("{}Z", local), time_zone: None }
+    } else {
+        // A named zone or floating local time
+        EventTime::DateTime { value: local, time_zone: property.param("TZID").map(str::to_string) }
+    })
+}
+
+fn set_start(event: &mut ImportedEvent, time: EventTime) {
+    match time {
+        EventTime::Date(date) => event.start_date = Some(date),
+        EventTime::DateTime { value, time_zone } => {
+            event.start_date_time = Some(value);
+            event.time_zone = time_zone;
+        }
+    }
+}
+
+fn set_end(event: &mut ImportedEvent, time: EventTime) {
+    match time {
+        EventTime::Date(date) => event.end_date = Some(date),
+        EventTime::DateTime { value, time_zone } => {
+            event.end_date_time = Some(value);
+            event.time_zone = event.time_zone.take().or(time_zone);
+        }
+    }
+}
+
+/// The EXDATE line that removes the occurrence a RECURRENCE-ID replaces,
+/// keeping its zone or date-only form
+fn exdate_for(property: &Property) -> String {
+    let params: String = property
+        .params
+        .iter()
+        .filter(|(key, _)| key == "TZID" || key == "VALUE")
+        .map(|(key, value)| format!(";{}={}", key, value))
+        .collect();
+    format!("EXDATE{}:{}", params, property.value.trim())
+}
+
+/// Read the calendar name and events from an ICS file
+pub fn parse_ics(text: &str) -> Result<ParsedCalendar> {
+    // Files saved by Windows tools often start with a byte order mark
+    let lines = unfold(text.trim_start_matches('\u{feff}'));
+    if !lines.first().is_some_and(|line| line.eq_ignore_ascii_case("BEGIN:VCALENDAR")) {
+        return Err(LibreOllamaError::InvalidInput {
+            message: "Not an iCalendar file".to_string(),
+            field: None,
+        });
+    }
+
+    let mut calendar = ParsedCalendar::default();
+    // Components nested inside the current event, such as VALARM
+    let mut nested = 0usize;
+    let mut event: Option<ImportedEvent> = None;
+    let mut recurrence_id: Option<(String, String)> = None;
+    // Series UID and EXDATE line of each moved occurrence
+    let mut overrides: Vec<(String, String)> = Vec::new();
+
+    for property in lines.iter().filter_map(|line| Property::parse(line)) {
+        let component = property.value.trim().to_ascii_uppercase();
+        match (property.name.as_str(), event.as_mut()) {
+            ("BEGIN", None) if component == "VEVENT" => {
+                event = Some(ImportedEvent::default());
+                recurrence_id = None;
+            }
+            ("BEGIN", Some(_)) => nested += 1,
+            ("END", Some(_)) if nested > 0 => nested -= 1,
+            ("END", Some(_)) if component == "VEVENT" => {
+                let mut finished = event.take().unwrap_or_default();
+                if finished.end_date.is_none() && finished.end_date_time.is_none() {
+                    // No DTEND: an all-day event lasts the day, a timed one
+                    // is treated as instantaneous
+                    finished.end_date = finished.start_date.as_deref().and_then(next_day);
+                    finished.end_date_time = finished.start_date_time.clone();
+                }
+                if let Some((recurrence_id, exdate)) = recurrence_id.take() {
+                    overrides.push((finished.uid.clone(), exdate));
+                    finished.uid = format!("{}#{}", finished.uid, recurrence_id);
+                }
+                if !finished.uid.is_empty() {
+                    calendar.events.push(finished);
+                }
+            }
+            ("X-WR-CALNAME", None) => calendar.name = Some(unescape(property.value)),
+            (_, Some(_)) if nested > 0 => {}
+            (name, Some(current)) => match name {
+                "UID" => current.uid = property.value.trim().to_string(),
+                "SUMMARY" => current.summary = Some(unescape(property.value)),
+                "DESCRIPTION" => current.description = Some(unescape(property.value)),
+                "LOCATION" => current.location = Some(unescape(property.value)),
+                "STATUS" => current.status = Some(property.value.trim().to_ascii_lowercase()),
+                "DTSTART" => {
+                    if let Some(time) = parse_time(&property) {
+                        set_start(current, time);
+                    }
+                }
+                "DTEND" => {
+                    if let Some(time) = parse_time(&property) {
+                        set_end(current, time);
+                    }
+                }
+                "RRULE" | "EXDATE" | "RDATE" => current.recurrence.push(format!("{}:{}", name, property.value.trim())),
+                "RECURRENCE-ID" => {
+                    recurrence_id = Some((property.value.trim().to_string(), exdate_for(&property)));
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    for (uid, exdate) in overrides {
+        let series = calendar.events.iter_mut().find(|event| event.uid == uid && !event.recurrence.is_empty());
+        if let Some(series) = series.filter(|series| !series.recurrence.contains(&exdate)) {
+            series.recurrence.push(exdate);
+        }
+    }
+    Ok(calendar)
+}
+
+fn next_day(date: &str) -> Option<String> {
+    let date = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    Some(date.succ_opt()?.format("%Y-%m-%d").to_string())
+}
+
+/// The https URL a `webcal:` or `webcals:` link points at; http and https
+/// URLs are accepted as they are
+pub fn webcal_to_https(url: &str) -> Result<Url> {
+    let url = url.trim();
+    let rewritten = match url.split_once("://") {
+        Some((scheme, rest)) if scheme.eq_ignore_ascii_case("webcal") || scheme.eq_ignore_ascii_case("webcals") => {
+            format!("https://{}", rest)
+        }
+        _ => url.to_string(),
+    };
+    Url::parse(&rewritten)
+        .ok()
+        .filter(|parsed| matches!(parsed.scheme(), "http" | "https"))
+        .ok_or_else(|| LibreOllamaError::InvalidInput {
+            message: format!("Not a calendar link: {}", url),
+            field: Some("url".to_string()),
+        })
+}
+
+/// Stable ID for a calendar's source, so importing it again refreshes it
+fn calendar_id(source_url: &str) -> String {
+    let digest = Sha256::digest(source_url.as_bytes());
+    format!("{}{}", CALENDAR_ID_PREFIX, hex::encode(&digest[..8]))
+}
+
+fn store(
+    db_manager: &Arc<DatabaseManager>,
+    source: &str,
+    source_url: &str,
+    fallback_name: &str,
+    parsed: ParsedCalendar,
+) -> Result<ImportedCalendar> {
+    let id = calendar_id(source_url);
+    let name = parsed.name.filter(|name| !name.trim().is_empty()).unwrap_or_else(|| fallback_name.to_string());
+    let conn = db_manager.get_connection()?;
+    let floating = TimeService::load(&conn)?.zone();
+    let count =
+        imported_calendar_operations::replace_calendar(&conn, &id, &name, source, source_url, &parsed.events, &floating)?;
+    println!("📅 [ICS] Imported {} event(s) into '{}'", count, name);
+    imported_calendar_operations::get_calendar(&conn, &id)?.ok_or_else(|| LibreOllamaError::NotFound {
+        resource: format!("imported calendar {}", id),
+    })
+}
+
+/// Import an ICS file, replacing the events of an earlier import of it.
+/// Callers check the path is one the user chose.
+pub fn import_file(db_manager: &Arc<DatabaseManager>, path: &Path) -> Result<ImportedCalendar> {
+    let path = path.canonicalize()?;
+    // Read one byte past the limit so a file that grew is still caught
+    let mut bytes = Vec::new();
+    std::fs::File::open(&path)?.take(MAX_ICS_BYTES as u64 + 1).read_to_end(&mut bytes)?;
+    if bytes.len() > MAX_ICS_BYTES {
+        return Err(LibreOllamaError::InvalidInput {
+            message: format!("{} is larger than {} MB", path.display(), MAX_ICS_BYTES / (1024 * 1024)),
+            field: Some("path".to_string()),
+        });
+    }
+    let text = String::from_utf8_lossy(&bytes);
+    let fallback = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_else(|| "Calendar".to_string());
+    store(db_manager, "file", &path.display().to_string(), &fallback, parse_ics(&text)?)
+}
+
+fn is_calendar_type(content_type: &str) -> bool {
+    content_type.is_empty()
+        || content_type == "text/calendar"
+        || content_type == "text/plain"
+        || content_type == "application/octet-stream"
+}
+
+/// Subscribe to a webcal or https calendar, or refresh an existing
+/// subscription to it
+pub async fn subscribe(db_manager: &Arc<DatabaseManager>, url: &str) -> Result<ImportedCalendar> {
+    let url = webcal_to_https(url)?;
+    let response = fetch_public(url.clone(), ICS_ACCEPT, MAX_ICS_BYTES, is_calendar_type).await?;
+    if response.truncated {
+        return Err(LibreOllamaError::InvalidInput {
+            message: format!("Calendar at {} is larger than {} MB", url, MAX_ICS_BYTES / (1024 * 1024)),
+            field: Some("url".to_string()),
+        });
+    }
+    let parsed = parse_ics(&String::from_utf8_lossy(&response.body))?;
+    let fallback = url.host_str().unwrap_or("Calendar").to_string();
+    store(db_manager, "webcal", url.as_str(), &fallback, parsed)
+}
+
+/// Fetch a subscription again, or re-read an imported file
+pub async fn refresh(db_manager: &Arc<DatabaseManager>, id: &str) -> Result<ImportedCalendar> {
+    let calendar = {
+        let conn = db_manager.get_connection()?;
+        imported_calendar_operations::get_calendar(&conn, id)?
+    }
+    .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("imported calendar {}", id) })?;
+
+    match calendar.source.as_str() {
+        "webcal" => subscribe(db_manager, &calendar.source_url).await,
+        _ => import_file(db_manager, Path::new(&calendar.source_url)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "BEGIN:VCALENDAR\r\n\
+VERSION:2.0\r\n\
+X-WR-CALNAME:Team\\, shared\r\n\
+BEGIN:VEVENT\r\n\
+UID:standup@example.com\r\n\
+SUMMARY:Daily standup\r\n\
+DESCRIPTION:Agenda:\\nblockers\r\n \x20and wins\r\n\
+DTSTART;TZID=Europe/Berlin:20260112T091500\r\n\
+DTEND;TZID=Europe/Berlin:20260112T093000\r\n\
+RRULE:FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR\r\n\
+BEGIN:VALARM\r\n\
+DESCRIPTION:Reminder\r\n\
+END:VALARM\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:offsite@example.com\r\n\
+SUMMARY:Offsite\r\n\
+DTSTART;VALUE=DATE:20260301\r\n\
+STATUS:TENTATIVE\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:standup@example.com\r\n\
+RECURRENCE-ID;TZID=Europe/Berlin:20260113T091500\r\n\
+DTSTART:20260113T100000Z\r\n\
+DTEND:20260113T101500Z\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    #[test]
+    fn test_parse_ics() {
+        let calendar = parse_ics(SAMPLE).unwrap();
+        assert_eq!(calendar.name.as_deref(), Some("Team, shared"));
+        assert_eq!(calendar.events.len(), 3);
+
+        let standup = &calendar.events[0];
+        assert_eq!(standup.description.as_deref(), Some("Agenda:\nblockers and wins"));
+        assert_eq!(standup.start_date_time.as_deref(), Some("2026-01-12T09:15:00"));
+        assert_eq!(standup.time_zone.as_deref(), Some("Europe/Berlin"));
+        assert_eq!(
+            standup.recurrence,
+            vec!["RRULE:FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR", "EXDATE;TZID=Europe/Berlin:20260113T091500"]
+        );
+
+        let offsite = &calendar.events[1];
+        assert_eq!(offsite.start_date.as_deref(), Some("2026-03-01"));
+        assert_eq!(offsite.end_date.as_deref(), Some("2026-03-02"));
+        assert_eq!(offsite.status.as_deref(), Some("tentative"));
+
+        let moved = &calendar.events[2];
+        assert_eq!(moved.uid, "standup@example.com#20260113T091500");
+        assert_eq!(moved.start_date_time.as_deref(), Some("2026-01-13T10:00:00Z"));
+
+        assert!(parse_ics("BEGIN:VCARD\r\nEND:VCARD").is_err());
+        let with_bom = format!("\u{feff}{}", SAMPLE);
+        assert_eq!(parse_ics(&with_bom).unwrap(), calendar);
+    }
+
+    #[test]
+    fn test_webcal_to_https() {
+        assert_eq!(webcal_to_https("webcal://example.com/cal.ics").unwrap().as_str(), "https://example.com/cal.ics");
+        assert_eq!(webcal_to_https("WEBCALS://example.com/a").unwrap().as_str(), "https://example.com/a");
+        assert!(webcal_to_https("file:///etc/passwd").is_err());
+        assert_eq!(calendar_id("https://example.com/a"), calendar_id("https://example.com/a"));
+    }
+}
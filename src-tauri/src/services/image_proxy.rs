@@ -0,0 +1,219 @@
+//! Remote Image Proxy
+//!
+//! Email bodies reference remote images that the webview should never load
+//! directly: that leaks the user's IP and read status to the sender, and a
+//! crafted message could point the app at addresses on the local network.
+//! Images are fetched here instead, with the destination re-checked on every
+//! redirect, content-type and size limits, and a disk cache under
+//! `cache_dir/images`. Recently shown images are also kept in memory,
+//! within the budget in `resource_budget`.
+
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::config::get_config_manager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::resource_budget;
+use crate::utils::http_client::fetch_public;
+
+/// Largest image the proxy will download
+pub const MAX_IMAGE_BYTES: usize = 10 * 1024 * 1024;
+
+/// Cached images are refetched after a week
+const CACHE_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Raster formats the webview may render. SVG is left out on purpose since it
+/// can carry script and external references of its own.
+const ALLOWED_CONTENT_TYPES: &[&str] = &[
+    "image/png",
+    "image/jpeg",
+    "image/gif",
+    "image/webp",
+    "image/avif",
+    "image/bmp",
+    "image/x-icon",
+    "image/vnd.microsoft.icon",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxiedImage {
+    pub data_url: String,
+    pub content_type: String,
+    pub size: usize,
+    pub from_cache: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntryMeta {
+    url: String,
+    content_type: String,
+}
+
+pub struct ImageProxy {
+    cache_dir: PathBuf,
+    max_bytes: usize,
+}
+
+impl ImageProxy {
+    pub fn new(cache_dir: PathBuf, max_bytes: usize) -> Self {
+        Self { cache_dir, max_bytes }
+    }
+
+    /// Proxy caching under `<cache_dir>/images` from the app config
+    pub fn from_config() -> Result<Self> {
+        let config_manager = get_config_manager().map_err(|e| LibreOllamaError::Configuration {
+            message: format!("Failed to get config manager: {}", e),
+            config_key: None,
+        })?;
+        Ok(Self::new(config_manager.paths().cache_dir.join("images"), MAX_IMAGE_BYTES))
+    }
+
+    /// Fetch a remote image, serving it from the disk cache when possible
+    pub async fn fetch(&self, url: &str) -> Result<ProxiedImage> {
+        let url = Url::parse(url.trim()).map_err(|e| LibreOllamaError::InvalidInput {
+            message: format!("Invalid image URL: {}", e),
+            field: Some("url".to_string()),
+        })?;
+
+        if let Some(image) = resource_budget::IMAGES.get(&url.to_string()) {
+            return Ok(image);
+        }
+        if let Some((content_type, bytes)) = self.read_cache(url.as_str()).await {
+            let image = to_proxied(content_type, &bytes, true);
+            resource_budget::IMAGES.insert(url.to_string(), image.clone());
+            return Ok(image);
+        }
+
+        let (content_type, bytes) = self.fetch_remote(url.clone()).await?;
+        if let Err(e) = self.write_cache(url.as_str(), &content_type, &bytes).await {
+            eprintln!("⚠️ [IMAGE-PROXY] Failed to cache {}: {}", url, e);
+        }
+
+        let image = to_proxied(content_type, &bytes, false);
+        resource_budget::IMAGES.insert(url.to_string(), ProxiedImage { from_cache: true, ..image.clone() });
+        Ok(image)
+    }
+
+    /// Remove every cached image, returning how many were deleted
+    pub async fn clear_cache(&self) -> Result<usize> {
+        resource_budget::IMAGES.clear();
+        let mut entries = match tokio::fs::read_dir(&self.cache_dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(fs_error(&self.cache_dir, e)),
+        };
+
+        let mut removed = 0;
+        while let Some(entry) = entries.next_entry().await.map_err(|e| fs_error(&self.cache_dir, e))? {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "img") {
+                removed += 1;
+            }
+            if let Err(e) = tokio::fs::remove_file(&path).await {
+                eprintln!("⚠️ [IMAGE-PROXY] Failed to remove {}: {}", path.display(), e);
+            }
+        }
+
+        println!("🧹 [IMAGE-PROXY] Cleared {} cached images", removed);
+        Ok(removed)
+    }
+
+    async fn fetch_remote(&self, url: Url) -> Result<(String, Vec<u8>)> {
+        let response = fetch_public(url, "image/*", self.max_bytes, is_allowed_content_type).await?;
+        if response.truncated {
+            return Err(self.too_large());
+        }
+        Ok((response.content_type, response.body))
+    }
+
+    fn too_large(&self) -> LibreOllamaError {
+        LibreOllamaError::InvalidInput {
+            message: format!("Image exceeds the {} byte limit", self.max_bytes),
+            field: Some("url".to_string()),
+        }
+    }
+
+    fn cache_paths(&self, url: &str) -> (PathBuf, PathBuf) {
+        let key = hex::encode(Sha256::digest(url.as_bytes()));
+        (
+            self.cache_dir.join(format!("{}.img", key)),
+            self.cache_dir.join(format!("{}.json", key)),
+        )
+    }
+
+    async fn read_cache(&self, url: &str) -> Option<(String, Vec<u8>)> {
+        let (data_path, meta_path) = self.cache_paths(url);
+
+        let modified = tokio::fs::metadata(&data_path).await.ok()?.modified().ok()?;
+        let age = SystemTime::now().duration_since(modified).unwrap_or_default();
+        if age > CACHE_TTL {
+            return None;
+        }
+
+        let meta: CacheEntryMeta = serde_json::from_slice(&tokio::fs::read(&meta_path).await.ok()?).ok()?;
+        // Guards against a hash collision serving the wrong image
+        if meta.url != url {
+            return None;
+        }
+        let bytes = tokio::fs::read(&data_path).await.ok()?;
+        Some((meta.content_type, bytes))
+    }
+
+    async fn write_cache(&self, url: &str, content_type: &str, bytes: &[u8]) -> Result<()> {
+        tokio::fs::create_dir_all(&self.cache_dir)
+            .await
+            .map_err(|e| fs_error(&self.cache_dir, e))?;
+
+        let (data_path, meta_path) = self.cache_paths(url);
+        let meta = CacheEntryMeta {
+            url: url.to_string(),
+            content_type: content_type.to_string(),
+        };
+        let meta_json = serde_json::to_vec(&meta).map_err(|e| LibreOllamaError::Serialization {
+            message: e.to_string(),
+            data_type: "CacheEntryMeta".to_string(),
+        })?;
+
+        tokio::fs::write(&meta_path, meta_json).await.map_err(|e| fs_error(&meta_path, e))?;
+        tokio::fs::write(&data_path, bytes).await.map_err(|e| fs_error(&data_path, e))?;
+        Ok(())
+    }
+}
+
+pub fn is_allowed_content_type(content_type: &str) -> bool {
+    ALLOWED_CONTENT_TYPES.contains(&content_type)
+}
+
+fn to_proxied(content_type: String, bytes: &[u8], from_cache: bool) -> ProxiedImage {
+    ProxiedImage {
+        data_url: format!("data:{};base64,{}", content_type, general_purpose::STANDARD.encode(bytes)),
+        content_type,
+        size: bytes.len(),
+        from_cache,
+    }
+}
+
+fn fs_error(path: &std::path::Path, e: std::io::Error) -> LibreOllamaError {
+    LibreOllamaError::FileSystem {
+        message: e.to_string(),
+        path: Some(path.display().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::http_client::normalize_content_type;
+
+    #[test]
+    fn only_raster_images_are_allowed() {
+        assert!(is_allowed_content_type(&normalize_content_type("image/PNG; charset=binary")));
+        assert!(!is_allowed_content_type(&normalize_content_type("image/svg+xml")));
+        assert!(!is_allowed_content_type(&normalize_content_type("text/html")));
+    }
+}
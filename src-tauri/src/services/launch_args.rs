@@ -0,0 +1,285 @@
+//! Launch arguments
+//!
+//! Only one copy of the app runs at a time. Launching another focuses the
+//! running window and hands it the new command line, so a `mailto:` or
+//! `webcal:` link, an `.ics` file or `--capture "text"` opened from the OS
+//! reaches the app the user already has open. The first launch's own
+//! arguments go through the same path.
+//!
+//! Arguments become calls to commands in the command registry, with
+//! arguments keyed the way `invoke` expects. They wait in `LaunchQueue`
+//! until the frontend takes them, so none are lost if the frontend is not
+//! listening yet; `backend://launch-requested` says new ones arrived.
+//!
+//! macOS delivers links and opened files as open-URL events rather than
+//! arguments; the deep-link plugin hands those to `handle_urls`.
+//!
+//! The installer registers the link schemes on macOS and Windows. A Linux
+//! AppImage or dev build can only register them at runtime, which would
+//! take over the user's mail client, so that only happens once they turn
+//! on `LINK_HANDLERS_SETTING`.
+
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_fs::FsExt;
+
+use crate::commands::registry;
+use crate::database::DatabaseManager;
+use crate::services::events::{BackendEvent, EventBus, LaunchRequestedEvent};
+use crate::services::settings::{self, SettingsService};
+
+const CAPTURE_FLAG: &str = "--capture";
+/// Whether the app registers itself for `mailto:` and `webcal:` links
+pub const LINK_HANDLERS_SETTING: &str = "system.link_handlers";
+
+/// A registry command to run on the frontend's behalf
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LaunchRequest {
+    pub command: String,
+    /// Keyed as `invoke` expects (camelCase)
+    pub args: Map<String, Value>,
+}
+
+/// Fields of a `mailto:` link, for prefilling the composer
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MailtoFields {
+    pub to: Vec<String>,
+    pub cc: Vec<String>,
+    pub bcc: Vec<String>,
+    pub subject: Option<String>,
+    pub body: Option<String>,
+}
+
+/// Build a call to a registry command, or `None` if the command or one of
+/// its parameters is not registered
+fn invocation(command: &str, args: Vec<(&str, Value)>) -> Option<LaunchRequest> {
+    let metadata = registry::find_command(command)?;
+    let mut keyed = Map::new();
+    for (name, value) in args {
+        metadata.parameters.iter().find(|param| param.name == name)?;
+        keyed.insert(registry::invoke_key(name), value);
+    }
+    Some(LaunchRequest { command: command.to_string(), args: keyed })
+}
+
+fn has_scheme(arg: &str, scheme: &str) -> bool {
+    arg.get(..scheme.len()).is_some_and(|prefix| prefix.eq_ignore_ascii_case(scheme))
+}
+
+/// Commands asked for on a command line; the program path comes first and
+/// unknown arguments are ignored
+pub fn parse_args(argv: &[String]) -> Vec<LaunchRequest> {
+    parse_items(argv.get(1..).unwrap_or_default())
+}
+
+fn parse_items(items: &[String]) -> Vec<LaunchRequest> {
+    let mut requests = Vec::new();
+    let mut args = items.iter();
+    while let Some(arg) = args.next() {
+        let capture = if arg == CAPTURE_FLAG {
+            args.next().cloned()
+        } else {
+            arg.strip_prefix(CAPTURE_FLAG).and_then(|rest| rest.strip_prefix('=')).map(str::to_string)
+        };
+
+        let request = if let Some(text) = capture.filter(|text| !text.trim().is_empty()) {
+            invocation("quick_capture", vec![("request", serde_json::json!({ "text": text }))])
+        } else if has_scheme(arg, "mailto:") {
+            invocation("parse_mailto_link", vec![("uri", Value::String(arg.clone()))])
+        } else if has_scheme(arg, "webcal:") || has_scheme(arg, "webcals:") {
+            invocation("subscribe_webcal_calendar", vec![("url", Value::String(arg.clone()))])
+        } else if let Some(path) = ics_path(arg) {
+            invocation("import_ics_file", vec![("path", Value::String(path))])
+        } else {
+            None
+        };
+        requests.extend(request);
+    }
+    requests
+}
+
+/// The path of an `.ics` file given as a path or a `file:` URL
+fn ics_path(arg: &str) -> Option<String> {
+    let path = match url::Url::parse(arg) {
+        Ok(url) if url.scheme() == "file" => url.to_file_path().ok()?,
+        // Other URLs are not files, but a one-letter scheme is the drive of
+        // a Windows path such as C:\\cal.ics
+        Ok(url) if url.scheme().len() > 1 => return None,
+        _ => std::path::PathBuf::from(arg),
+    };
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("ics"))
+        .then(|| path.display().to_string())
+}
+
+fn decode(text: &str) -> String {
+    urlencoding::decode(text).map(|text| text.into_owned()).unwrap_or_else(|_| text.to_string())
+}
+
+fn addresses(list: &str) -> impl Iterator<Item = String> + '_ {
+    list.split(',').map(|address| decode(address).trim().to_string()).filter(|address| !address.is_empty())
+}
+
+/// Split a `mailto:` link (RFC 6068). `+` stays literal, unlike in form
+/// encoding, so addresses such as `ada+news@example.com` survive.
+pub fn parse_mailto(uri: &str) -> Option<MailtoFields> {
+    let rest = has_scheme(uri, "mailto:").then(|| &uri[7..])?;
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+    let mut fields = MailtoFields { to: addresses(path).collect(), ..Default::default() };
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        match key.to_ascii_lowercase().as_str() {
+            "to" => fields.to.extend(addresses(value)),
+            "cc" => fields.cc.extend(addresses(value)),
+            "bcc" => fields.bcc.extend(addresses(value)),
+            "subject" => fields.subject = Some(decode(value)),
+            "body" => fields.body = Some(decode(value)),
+            _ => {}
+        }
+    }
+    Some(fields)
+}
+
+/// Requests waiting for the frontend, managed as Tauri state
+#[derive(Default)]
+pub struct LaunchQueue {
+    pending: Mutex<Vec<LaunchRequest>>,
+}
+
+impl LaunchQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&self, requests: Vec<LaunchRequest>) -> usize {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.extend(requests);
+        pending.len()
+    }
+
+    pub fn take(&self) -> Vec<LaunchRequest> {
+        std::mem::take(&mut *self.pending.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+/// Queue the requests in a command line and tell the frontend
+pub fn handle_args(app: &AppHandle, argv: &[String]) {
+    queue_requests(app, parse_args(argv));
+}
+
+/// Queue the links and files the OS asked the app to open
+pub fn handle_urls(app: &AppHandle, urls: &[String]) {
+    queue_requests(app, parse_items(urls));
+}
+
+fn queue_requests(app: &AppHandle, requests: Vec<LaunchRequest>) {
+    if requests.is_empty() {
+        return;
+    }
+    // A file the user opened with the app counts as chosen, like one
+    // picked in a dialog; see `services::file_access`
+    if let Some(scope) = app.try_fs_scope() {
+        let paths = requests.iter().filter(|request| request.command == "import_ics_file");
+        for path in paths.filter_map(|request| request.args.get("path").and_then(Value::as_str)) {
+            if let Err(e) = scope.allow_file(path) {
+                eprintln!("⚠️ [LAUNCH] Failed to allow {}: {}", path, e);
+            }
+        }
+    }
+    let Some(queue) = app.try_state::<LaunchQueue>() else {
+        return;
+    };
+    let pending = queue.push(requests);
+    if let Some(event_bus) = app.try_state::<EventBus>() {
+        event_bus.emit(BackendEvent::LaunchRequested(LaunchRequestedEvent { pending }));
+    }
+}
+
+/// Register the app for its link schemes, or drop the registration
+#[cfg(any(windows, target_os = "linux"))]
+fn register_link_handlers(app: &AppHandle, enabled: bool) {
+    use tauri_plugin_deep_link::DeepLinkExt;
+    let result = if enabled {
+        app.deep_link().register_all()
+    } else {
+        ["mailto", "webcal", "webcals"].iter().try_for_each(|scheme| app.deep_link().unregister(scheme))
+    };
+    if let Err(e) = result {
+        eprintln!("⚠️ [LAUNCH] Failed to update link handlers: {}", e);
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+fn register_link_handlers(_app: &AppHandle, _enabled: bool) {}
+
+/// Register the link handlers if the user opted in, and follow the
+/// setting. Turning it off drops the registration; a default of off never
+/// touches what the installer registered.
+pub fn follow_link_handlers(app: AppHandle, db_manager: Arc<DatabaseManager>) {
+    tauri::async_runtime::spawn(async move {
+        let mut changes = app.try_state::<SettingsService>().map(|service| service.subscribe());
+        let mut first = true;
+        loop {
+            match db_manager.get_connection().and_then(|conn| settings::get_bool(&conn, LINK_HANDLERS_SETTING)) {
+                Ok(enabled) if enabled || !first => register_link_handlers(&app, enabled),
+                Ok(_) => {}
+                Err(e) => eprintln!("⚠️ [LAUNCH] Failed to read the link handler setting: {}", e),
+            }
+            first = false;
+            settings::changed(&mut changes, LINK_HANDLERS_SETTING).await;
+        }
+    });
+}
+
+/// Bring the running window forward for a second launch
+pub fn focus_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argv(args: &[&str]) -> Vec<String> {
+        std::iter::once("libreollama").chain(args.iter().copied()).map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let requests = parse_args(&argv(&["--capture", "call Ada tomorrow", "--verbose", "mailto:ada@example.com"]));
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].command, "quick_capture");
+        assert_eq!(requests[0].args["request"]["text"], "call Ada tomorrow");
+        assert_eq!(requests[1].command, "parse_mailto_link");
+        assert_eq!(requests[1].args["uri"], "mailto:ada@example.com");
+
+        let requests = parse_args(&argv(&["webcal://example.com/team.ics", "/tmp/Holidays.ICS", "file:///tmp/trip.ics", "notes.txt"]));
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].command, "subscribe_webcal_calendar");
+        assert_eq!(requests[0].args["url"], "webcal://example.com/team.ics");
+        assert_eq!(requests[1].command, "import_ics_file");
+        assert_eq!(requests[1].args["path"], "/tmp/Holidays.ICS");
+        assert_eq!(requests[2].args["path"], "/tmp/trip.ics");
+
+        assert_eq!(parse_args(&argv(&["--capture=buy milk"]))[0].args["request"]["text"], "buy milk");
+        assert!(parse_args(&argv(&["--capture"])).is_empty());
+    }
+
+    #[test]
+    fn test_parse_mailto() {
+        let fields = parse_mailto("MAILTO:ada+news@example.com,bob@example.com?cc=carol%40example.com&subject=Hello%20there&body=1+1%3D2").unwrap();
+        assert_eq!(fields.to, vec!["ada+news@example.com", "bob@example.com"]);
+        assert_eq!(fields.cc, vec!["carol@example.com"]);
+        assert_eq!(fields.subject.as_deref(), Some("Hello there"));
+        assert_eq!(fields.body.as_deref(), Some("1+1=2"));
+        assert!(parse_mailto("https://example.com").is_none());
+    }
+}
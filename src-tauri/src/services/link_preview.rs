@@ -0,0 +1,307 @@
+//! Link previews
+//!
+//! URLs in notes, tasks and chats are rendered as cards with the page's
+//! title, description and image. Pages are fetched with `fetch_public`, so
+//! links in untrusted content cannot reach the local network, and only the
+//! head of the document is read: Open Graph and Twitter card tags first,
+//! then `<title>` and the description meta tag. Results, failures included,
+//! are cached in `link_previews`.
+
+use std::sync::Arc;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::database::operations::link_preview_operations::{self, LinkPreviewRecord};
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::utils::http_client::fetch_public;
+
+/// Enough for the head of any reasonable page
+const MAX_PAGE_BYTES: usize = 1024 * 1024;
+/// Previews are refetched after a day, failures after an hour
+const CACHE_TTL_SECS: i64 = 24 * 60 * 60;
+const ERROR_TTL_SECS: i64 = 60 * 60;
+const MAX_TITLE_CHARS: usize = 200;
+const MAX_DESCRIPTION_CHARS: usize = 300;
+
+lazy_static::lazy_static! {
+    static ref HEAD_END: Regex = Regex::new(r"(?i)</head\s*>|<body[\s>]").unwrap();
+    static ref META_TAG: Regex = Regex::new(r"(?is)<meta\s([^>]*)>").unwrap();
+    static ref LINK_TAG: Regex = Regex::new(r"(?is)<link\s([^>]*)>").unwrap();
+    static ref ATTRIBUTE: Regex =
+        Regex::new(r#"(?is)([a-z_:-]+)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+))"#).unwrap();
+    static ref TITLE_TAG: Regex = Regex::new(r"(?is)<title[^>]*>(.*?)</title\s*>").unwrap();
+    static ref ENTITY: Regex = Regex::new(r"&(#[xX][0-9a-fA-F]+|#[0-9]+|[a-zA-Z]+);").unwrap();
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PageMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub site_name: Option<String>,
+    pub favicon_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkPreview {
+    pub url: String,
+    /// Where redirects led
+    pub final_url: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub site_name: Option<String>,
+    pub favicon_url: Option<String>,
+    pub content_type: String,
+    pub fetched_at: String,
+    pub from_cache: bool,
+}
+
+impl LinkPreview {
+    fn from_record(record: LinkPreviewRecord, from_cache: bool) -> Self {
+        Self {
+            final_url: record.final_url.unwrap_or_else(|| record.url.clone()),
+            url: record.url,
+            title: record.title,
+            description: record.description,
+            image_url: record.image_url,
+            site_name: record.site_name,
+            favicon_url: record.favicon_url,
+            content_type: record.content_type.unwrap_or_default(),
+            fetched_at: record.fetched_at,
+            from_cache,
+        }
+    }
+}
+
+fn attributes(tag: &str) -> Vec<(String, String)> {
+    ATTRIBUTE
+        .captures_iter(tag)
+        .map(|caps| {
+            let value = caps.get(2).or(caps.get(3)).or(caps.get(4)).map_or("", |m| m.as_str());
+            (caps[1].to_ascii_lowercase(), value.to_string())
+        })
+        .collect()
+}
+
+pub fn attribute<'a>(attributes: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attributes.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+}
+
+/// Decode character references; unknown named entities are left as they are
+pub fn decode_entities(text: &str) -> String {
+    ENTITY
+        .replace_all(text, |caps: &regex::Captures| {
+            let entity = &caps[1];
+            let decoded = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                "ndash" => Some('–'),
+                "mdash" => Some('—'),
+                "hellip" => Some('…'),
+                "rsquo" => Some('’'),
+                "lsquo" => Some('‘'),
+                "rdquo" => Some('”'),
+                "ldquo" => Some('“'),
+                _ if entity.starts_with("#x") || entity.starts_with("#X") => {
+                    u32::from_str_radix(&entity[2..], 16).ok().and_then(char::from_u32)
+                }
+                _ if entity.starts_with('#') => entity[1..].parse().ok().and_then(char::from_u32),
+                _ => None,
+            };
+            decoded.map_or_else(|| caps[0].to_string(), String::from)
+        })
+        .into_owned()
+}
+
+fn clean(text: &str, max_chars: usize) -> Option<String> {
+    let text = decode_entities(text).split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        return None;
+    }
+    if text.chars().count() <= max_chars {
+        return Some(text);
+    }
+    let cut: String = text.chars().take(max_chars - 1).collect();
+    Some(format!("{}…", cut.trim_end()))
+}
+
+/// Absolute http(s) URL for a possibly relative reference on the page
+fn absolute(base: &Url, reference: &str) -> Option<String> {
+    let url = base.join(decode_entities(reference.trim()).as_str()).ok()?;
+    matches!(url.scheme(), "http" | "https").then(|| url.to_string())
+}
+
+/// Attributes of each `<link>` tag in the head of a page
+pub fn head_links(html: &str) -> Vec<Vec<(String, String)>> {
+    let head = HEAD_END.find(html).map_or(html, |end| &html[..end.start()]);
+    LINK_TAG.captures_iter(head).map(|caps| attributes(&caps[1])).collect()
+}
+
+/// Preview metadata from the head of an HTML page fetched from `base`
+pub fn parse_metadata(html: &str, base: &Url) -> PageMetadata {
+    let head = HEAD_END.find(html).map_or(html, |end| &html[..end.start()]);
+
+    let meta: Vec<(String, String)> = META_TAG
+        .captures_iter(head)
+        .filter_map(|caps| {
+            let attrs = attributes(&caps[1]);
+            let key = attribute(&attrs, "property").or(attribute(&attrs, "name"))?.to_ascii_lowercase();
+            let content = attribute(&attrs, "content")?.to_string();
+            Some((key, content))
+        })
+        .collect();
+    let first = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| meta.iter().find(|(name, content)| name == key && !content.trim().is_empty()))
+            .map(|(_, content)| content.as_str())
+    };
+
+    let favicon = head_links(head).into_iter().find_map(|attrs| {
+        let rel = attribute(&attrs, "rel")?.to_ascii_lowercase();
+        let href = attribute(&attrs, "href")?;
+        rel.split_whitespace().any(|token| token == "icon").then(|| href.to_string())
+    });
+
+    PageMetadata {
+        title: first(&["og:title", "twitter:title"])
+            .or_else(|| TITLE_TAG.captures(head).and_then(|caps| caps.get(1)).map(|m| m.as_str()))
+            .and_then(|title| clean(title, MAX_TITLE_CHARS)),
+        description: first(&["og:description", "twitter:description", "description"])
+            .and_then(|description| clean(description, MAX_DESCRIPTION_CHARS)),
+        image_url: first(&["og:image:secure_url", "og:image", "og:image:url", "twitter:image", "twitter:image:src"])
+            .and_then(|image| absolute(base, image)),
+        site_name: first(&["og:site_name", "application-name"])
+            .and_then(|name| clean(name, MAX_TITLE_CHARS))
+            .or_else(|| base.host_str().map(|host| host.trim_start_matches("www.").to_string())),
+        favicon_url: favicon
+            .and_then(|href| absolute(base, &href))
+            .or_else(|| absolute(base, "/favicon.ico")),
+    }
+}
+
+fn is_html(content_type: &str) -> bool {
+    content_type.is_empty() || content_type == "text/html" || content_type == "application/xhtml+xml"
+}
+
+/// Preview of a URL, from the cache unless it has expired or `refresh` is set
+pub async fn unfurl(db_manager: &Arc<DatabaseManager>, url: &str, refresh: bool) -> Result<LinkPreview> {
+    let url = Url::parse(url.trim()).map_err(|e| LibreOllamaError::InvalidInput {
+        message: format!("Invalid URL: {}", e),
+        field: Some("url".to_string()),
+    })?;
+    let key = url.to_string();
+    let now = chrono::Utc::now().timestamp();
+
+    if !refresh {
+        let conn = db_manager.get_connection()?;
+        if let Some(cached) = link_preview_operations::get_fresh_preview(&conn, &key, now)? {
+            return match cached.error {
+                Some(error) => Err(LibreOllamaError::Network { message: error, url: Some(key) }),
+                None => Ok(LinkPreview::from_record(cached, true)),
+            };
+        }
+    }
+
+    let fetched_at = chrono::Utc::now().to_rfc3339();
+    let result = fetch_public(url.clone(), "text/html,application/xhtml+xml;q=0.9,*/*;q=0.5", MAX_PAGE_BYTES, |_| true).await;
+    let record = match &result {
+        Ok(response) => {
+            let metadata = if is_html(&response.content_type) {
+                parse_metadata(&String::from_utf8_lossy(&response.body), &response.url)
+            } else {
+                // Files get their name as the title
+                PageMetadata {
+                    title: response
+                        .url
+                        .path_segments()
+                        .and_then(|mut segments| segments.next_back())
+                        .filter(|name| !name.is_empty())
+                        .map(|name| urlencoding::decode(name).map_or(name.to_string(), |name| name.into_owned())),
+                    site_name: response.url.host_str().map(|host| host.trim_start_matches("www.").to_string()),
+                    ..PageMetadata::default()
+                }
+            };
+            LinkPreviewRecord {
+                url: key.clone(),
+                final_url: Some(response.url.to_string()),
+                title: metadata.title,
+                description: metadata.description,
+                image_url: metadata.image_url,
+                site_name: metadata.site_name,
+                favicon_url: metadata.favicon_url,
+                content_type: Some(response.content_type.clone()),
+                error: None,
+                fetched_at,
+                expires_at: now + CACHE_TTL_SECS,
+            }
+        }
+        Err(e) => LinkPreviewRecord {
+            url: key.clone(),
+            final_url: None,
+            title: None,
+            description: None,
+            image_url: None,
+            site_name: None,
+            favicon_url: None,
+            content_type: None,
+            error: Some(e.to_string()),
+            fetched_at,
+            expires_at: now + ERROR_TTL_SECS,
+        },
+    };
+
+    {
+        let conn = db_manager.get_connection()?;
+        link_preview_operations::save_preview(&conn, &record)?;
+        link_preview_operations::delete_expired(&conn, now)?;
+    }
+
+    result?;
+    Ok(LinkPreview::from_record(record, false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_open_graph() {
+        let html = r#"<!doctype html><html><head>
+            <title>Fallback title</title>
+            <meta content="An article &amp; its   summary" property="og:description">
+            <meta property='og:title' content='Rust &#8211; Release notes'>
+            <meta name="description" content="Plain description">
+            <meta property="og:image" content="/images/card.png">
+            <link rel="shortcut icon" href="/static/icon.png">
+            </head><body><meta property="og:title" content="Body tag"></body></html>"#;
+        let base = Url::parse("https://www.example.com/blog/post").unwrap();
+        let metadata = parse_metadata(html, &base);
+
+        assert_eq!(metadata.title.as_deref(), Some("Rust – Release notes"));
+        assert_eq!(metadata.description.as_deref(), Some("An article & its summary"));
+        assert_eq!(metadata.image_url.as_deref(), Some("https://www.example.com/images/card.png"));
+        assert_eq!(metadata.site_name.as_deref(), Some("example.com"));
+        assert_eq!(metadata.favicon_url.as_deref(), Some("https://www.example.com/static/icon.png"));
+    }
+
+    #[test]
+    fn test_parse_falls_back_to_title() {
+        let base = Url::parse("http://example.org/").unwrap();
+        let metadata = parse_metadata("<html><head><TITLE>\n  Just a\n page </TITLE></head></html>", &base);
+        assert_eq!(metadata.title.as_deref(), Some("Just a page"));
+        assert_eq!(metadata.description, None);
+        assert_eq!(metadata.image_url, None);
+        assert_eq!(metadata.favicon_url.as_deref(), Some("http://example.org/favicon.ico"));
+
+        let javascript = parse_metadata(r#"<meta property="og:image" content="javascript:alert(1)">"#, &base);
+        assert_eq!(javascript.image_url, None);
+    }
+}
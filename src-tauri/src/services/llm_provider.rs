@@ -0,0 +1,896 @@
+//! LLM provider abstraction
+//!
+//! Features that need a model (chat, summarization, embeddings) ask for one
+//! by feature instead of calling Ollama directly. Each feature is mapped to
+//! a provider and model in `llm_feature_models`; a provider is either the
+//! built-in local Ollama server or any endpoint speaking the OpenAI HTTP API
+//! (OpenAI, LM Studio, vLLM, OpenRouter, ...). API keys are encrypted at rest
+//! and only decrypted when a request is made.
+//!
+//! Which model a feature uses is decided by `model_router::resolve_model`:
+//! an explicit choice, else a routing rule over the model registry.
+//!
+//! Chat prompts are fitted to the model's context window before sending:
+//! system messages and the newest turns are kept, older turns dropped.
+//! Token usage of every completion is returned to the caller and recorded
+//! in `performance_metrics`.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::database::models::MetricType;
+use crate::database::operations::llm_provider_operations::{self, LlmProviderRecord};
+use crate::database::operations::model_registry_operations;
+use crate::database::operations::performance_operations;
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::model_router;
+use crate::services::resource_budget;
+use crate::utils::crypto::{decrypt_data, encrypt_data, get_persistent_encryption_key};
+use crate::utils::http_client::http_client;
+use crate::utils::tokens::{self, DEFAULT_CONTEXT_WINDOW, REPLY_PRIMING_TOKENS};
+
+/// The local Ollama server, always present and never deleted
+pub const OLLAMA_PROVIDER_ID: &str = "ollama";
+
+/// Remote endpoints can be slow to produce a full completion
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+/// Tokens of the context window left free for the reply
+const REPLY_RESERVE_TOKENS: usize = 1024;
+/// Ollama allocates the context it is asked for, so requests are capped
+/// well below the largest model windows
+const OLLAMA_MAX_CONTEXT: usize = 16_384;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderKind {
+    Ollama,
+    OpenaiCompatible,
+}
+
+impl ProviderKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProviderKind::Ollama => "ollama",
+            ProviderKind::OpenaiCompatible => "openai_compatible",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Result<Self> {
+        match kind {
+            "ollama" => Ok(ProviderKind::Ollama),
+            "openai_compatible" => Ok(ProviderKind::OpenaiCompatible),
+            other => Err(LibreOllamaError::InvalidInput {
+                message: format!("Unknown provider kind: {}", other),
+                field: Some("kind".to_string()),
+            }),
+        }
+    }
+}
+
+/// What a model is used for; each feature can use a different model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LlmFeature {
+    Chat,
+    Summarization,
+    Classification,
+    Translation,
+    Embeddings,
+}
+
+impl LlmFeature {
+    pub const ALL: [LlmFeature; 5] = [
+        LlmFeature::Chat,
+        LlmFeature::Summarization,
+        LlmFeature::Classification,
+        LlmFeature::Translation,
+        LlmFeature::Embeddings,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LlmFeature::Chat => "chat",
+            LlmFeature::Summarization => "summarization",
+            LlmFeature::Classification => "classification",
+            LlmFeature::Translation => "translation",
+            LlmFeature::Embeddings => "embeddings",
+        }
+    }
+
+    pub fn parse(feature: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|known| known.as_str() == feature)
+            .ok_or_else(|| LibreOllamaError::InvalidInput {
+                message: format!("Unknown LLM feature: {}", feature),
+                field: Some("feature".to_string()),
+            })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: String,
+    pub content: String,
+}
+
+impl ChatMessage {
+    pub fn system(content: impl Into<String>) -> Self {
+        Self { role: "system".to_string(), content: content.into() }
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self { role: "user".to_string(), content: content.into() }
+    }
+}
+
+/// Per-request generation settings
+#[derive(Debug, Clone, Default)]
+pub struct ChatOptions {
+    /// Use this model instead of the one chosen for the feature
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+    /// Ask for a JSON object as the reply
+    pub json: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub prompt_tokens: usize,
+    pub completion_tokens: usize,
+    pub total_tokens: usize,
+    /// The provider did not report counts, so they were estimated
+    pub estimated: bool,
+}
+
+impl TokenUsage {
+    fn new(prompt_tokens: usize, completion_tokens: usize, estimated: bool) -> Self {
+        Self { prompt_tokens, completion_tokens, total_tokens: prompt_tokens + completion_tokens, estimated }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatCompletion {
+    pub content: String,
+    pub model: String,
+    pub usage: TokenUsage,
+    pub latency_ms: u64,
+    pub context_window: usize,
+    /// Oldest messages left out to fit the context window
+    pub dropped_messages: usize,
+    /// The newest message alone was too long and was cut
+    pub truncated: bool,
+}
+
+/// Messages that fit a token budget
+#[derive(Debug, Clone)]
+pub struct FittedPrompt {
+    pub messages: Vec<ChatMessage>,
+    pub prompt_tokens: usize,
+    pub dropped: usize,
+    pub truncated: bool,
+}
+
+/// Tokens a prompt may use in a context window, leaving room for the reply
+pub fn prompt_budget(context_window: usize) -> usize {
+    context_window.saturating_sub(REPLY_RESERVE_TOKENS.min(context_window / 2))
+}
+
+/// Keep every system message and as many of the newest other messages as
+/// fit in `budget` tokens. The newest message is always kept, cut short if
+/// it does not fit on its own.
+pub fn fit_messages(messages: &[ChatMessage], budget: usize) -> FittedPrompt {
+    let (system, turns): (Vec<&ChatMessage>, Vec<&ChatMessage>) =
+        messages.iter().partition(|message| message.role == "system");
+    let mut used = REPLY_PRIMING_TOKENS + system.iter().map(|m| tokens::message_tokens(&m.content)).sum::<usize>();
+
+    let mut kept: Vec<ChatMessage> = Vec::new();
+    let mut truncated = false;
+    for (position, message) in turns.iter().rev().enumerate() {
+        let cost = tokens::message_tokens(&message.content);
+        if used + cost <= budget {
+            used += cost;
+            kept.push((*message).clone());
+        } else if position == 0 {
+            let room = budget.saturating_sub(used + tokens::MESSAGE_OVERHEAD_TOKENS);
+            let content = tokens::truncate_to_tokens(&message.content, room).to_string();
+            used += tokens::message_tokens(&content);
+            kept.push(ChatMessage { role: message.role.clone(), content });
+            truncated = true;
+        } else {
+            break;
+        }
+    }
+    kept.reverse();
+
+    let dropped = turns.len() - kept.len();
+    FittedPrompt {
+        messages: system.into_iter().cloned().chain(kept).collect(),
+        prompt_tokens: used,
+        dropped,
+        truncated,
+    }
+}
+
+/// Provider as shown in settings; the key itself is never sent back
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderInfo {
+    pub id: String,
+    pub name: String,
+    pub kind: ProviderKind,
+    pub base_url: String,
+    pub has_api_key: bool,
+    pub builtin: bool,
+    pub updated_at: String,
+}
+
+impl TryFrom<&LlmProviderRecord> for ProviderInfo {
+    type Error = LibreOllamaError;
+
+    fn try_from(record: &LlmProviderRecord) -> Result<Self> {
+        Ok(Self {
+            id: record.id.clone(),
+            name: record.name.clone(),
+            kind: ProviderKind::parse(&record.kind)?,
+            base_url: record.base_url.clone(),
+            has_api_key: record.api_key_encrypted.is_some(),
+            builtin: record.id == OLLAMA_PROVIDER_ID,
+            updated_at: record.updated_at.clone(),
+        })
+    }
+}
+
+/// A provider to create or update
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProviderInput {
+    pub id: String,
+    pub name: String,
+    pub kind: ProviderKind,
+    /// For OpenAI-compatible endpoints, the URL up to and including `/v1`
+    pub base_url: String,
+    /// `None` keeps the stored key, an empty string removes it
+    pub api_key: Option<String>,
+}
+
+/// Feature → provider and model, `None` when nothing has been chosen
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureModelInfo {
+    pub feature: LlmFeature,
+    pub provider_id: Option<String>,
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderTestResult {
+    pub ok: bool,
+    pub latency_ms: u64,
+    pub models: Vec<String>,
+    /// Reply to a one-line prompt when a model was given
+    pub reply: Option<String>,
+    pub usage: Option<TokenUsage>,
+    pub error: Option<String>,
+}
+
+fn invalid(message: impl Into<String>, field: &str) -> LibreOllamaError {
+    LibreOllamaError::InvalidInput {
+        message: message.into(),
+        field: Some(field.to_string()),
+    }
+}
+
+pub fn list_providers(conn: &Connection) -> Result<Vec<ProviderInfo>> {
+    llm_provider_operations::list_providers(conn)?
+        .iter()
+        .map(ProviderInfo::try_from)
+        .collect()
+}
+
+/// Validate and store a provider, encrypting its API key
+pub fn save_provider(conn: &Connection, input: &ProviderInput) -> Result<ProviderInfo> {
+    let id = input.id.trim();
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_') {
+        return Err(invalid("Provider id must use lowercase letters, digits, '-' or '_'", "id"));
+    }
+    if id == OLLAMA_PROVIDER_ID && input.kind != ProviderKind::Ollama {
+        return Err(invalid("The built-in Ollama provider cannot change kind", "kind"));
+    }
+    let name = input.name.trim();
+    if name.is_empty() {
+        return Err(invalid("Provider name must not be empty", "name"));
+    }
+    let base_url = input.base_url.trim().trim_end_matches('/');
+    if !(base_url.starts_with("http://") || base_url.starts_with("https://")) {
+        return Err(invalid("Base URL must start with http:// or https://", "base_url"));
+    }
+
+    let api_key = input.api_key.as_deref().map(str::trim);
+    if api_key == Some("") {
+        llm_provider_operations::clear_api_key(conn, id)?;
+    }
+    let encrypted = match api_key.filter(|key| !key.is_empty()) {
+        Some(key) => Some(encrypt_data(key, &get_persistent_encryption_key())?),
+        None => None,
+    };
+
+    let record = llm_provider_operations::upsert_provider(
+        conn,
+        id,
+        name,
+        input.kind.as_str(),
+        base_url,
+        encrypted.as_deref(),
+    )?;
+    ProviderInfo::try_from(&record)
+}
+
+pub fn delete_provider(conn: &Connection, id: &str) -> Result<bool> {
+    if id == OLLAMA_PROVIDER_ID {
+        return Err(invalid("The built-in Ollama provider cannot be deleted", "id"));
+    }
+    Ok(llm_provider_operations::delete_provider(conn, id)?)
+}
+
+pub fn feature_models(conn: &Connection) -> Result<Vec<FeatureModelInfo>> {
+    let stored = llm_provider_operations::list_feature_models(conn)?;
+    Ok(LlmFeature::ALL
+        .into_iter()
+        .map(|feature| {
+            let selection = stored.iter().find(|model| model.feature == feature.as_str());
+            FeatureModelInfo {
+                feature,
+                provider_id: selection.map(|model| model.provider_id.clone()),
+                model: selection.map(|model| model.model.clone()),
+            }
+        })
+        .collect())
+}
+
+/// Choose the model a feature uses; an empty model clears the choice
+pub fn set_feature_model(conn: &Connection, feature: LlmFeature, provider_id: &str, model: &str) -> Result<FeatureModelInfo> {
+    let model = model.trim();
+    if model.is_empty() {
+        llm_provider_operations::clear_feature_model(conn, feature.as_str())?;
+        return Ok(FeatureModelInfo { feature, provider_id: None, model: None });
+    }
+    if llm_provider_operations::get_provider(conn, provider_id)?.is_none() {
+        return Err(LibreOllamaError::NotFound { resource: format!("LLM provider {}", provider_id) });
+    }
+    let saved = llm_provider_operations::set_feature_model(conn, feature.as_str(), provider_id, model)?;
+    Ok(FeatureModelInfo {
+        feature,
+        provider_id: Some(saved.provider_id),
+        model: Some(saved.model),
+    })
+}
+
+/// A provider with its key decrypted, ready to make requests
+#[derive(Debug, Clone)]
+pub struct LlmProvider {
+    pub id: String,
+    pub kind: ProviderKind,
+    pub base_url: String,
+    api_key: Option<String>,
+    /// Context lengths reported by the provider, from the model registry
+    context_lengths: HashMap<String, usize>,
+}
+
+/// What a provider reports about one of its models
+#[derive(Debug, Clone, Default)]
+pub struct ModelDetails {
+    pub name: String,
+    pub family: Option<String>,
+    pub parameter_size: Option<String>,
+    pub quantization: Option<String>,
+    pub size_bytes: Option<u64>,
+    pub context_length: Option<usize>,
+    pub capabilities: Vec<String>,
+}
+
+/// Capabilities guessed from a model name when the provider does not say
+fn guess_capabilities(name: &str) -> Vec<String> {
+    let name = name.to_lowercase();
+    if name.contains("embed") || name.contains("bge-") || name.contains("minilm") {
+        vec!["embedding".to_string()]
+    } else {
+        vec!["completion".to_string()]
+    }
+}
+
+impl LlmProvider {
+    pub fn from_record(record: &LlmProviderRecord) -> Result<Self> {
+        let api_key = match &record.api_key_encrypted {
+            Some(encrypted) => Some(decrypt_data(encrypted, &get_persistent_encryption_key())?),
+            None => None,
+        };
+        Ok(Self {
+            id: record.id.clone(),
+            kind: ProviderKind::parse(&record.kind)?,
+            base_url: record.base_url.trim_end_matches('/').to_string(),
+            api_key,
+            context_lengths: HashMap::new(),
+        })
+    }
+
+    pub fn load(conn: &Connection, id: &str) -> Result<Self> {
+        let record = llm_provider_operations::get_provider(conn, id)?
+            .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("LLM provider {}", id) })?;
+        let mut provider = Self::from_record(&record)?;
+        provider.context_lengths = model_registry_operations::list_models(conn, Some(id))?
+            .into_iter()
+            .filter_map(|model| Some((model.name, usize::try_from(model.context_length?).ok()?)))
+            .collect();
+        Ok(provider)
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    async fn post(&self, path: &str, body: &Value) -> Result<Value> {
+        let url = self.url(path);
+        let mut request = http_client().post(&url).timeout(REQUEST_TIMEOUT).json(body);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        Self::read_json(request.send().await, url).await
+    }
+
+    async fn get(&self, path: &str) -> Result<Value> {
+        let url = self.url(path);
+        let mut request = http_client().get(&url).timeout(REQUEST_TIMEOUT);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        Self::read_json(request.send().await, url).await
+    }
+
+    async fn read_json(response: reqwest::Result<reqwest::Response>, url: String) -> Result<Value> {
+        let response = response.map_err(|e| LibreOllamaError::Network {
+            message: format!("Failed to reach LLM provider: {}", e),
+            url: Some(url.clone()),
+        })?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(LibreOllamaError::Network {
+                message: format!("LLM provider error {}: {}", status, error_text),
+                url: Some(url),
+            });
+        }
+        response.json().await.map_err(|e| LibreOllamaError::Serialization {
+            message: format!("Failed to parse LLM provider response: {}", e),
+            data_type: "LLM provider response".to_string(),
+        })
+    }
+
+    fn missing(field: &str) -> LibreOllamaError {
+        LibreOllamaError::Serialization {
+            message: format!("LLM provider response has no {}", field),
+            data_type: "LLM provider response".to_string(),
+        }
+    }
+
+    /// Context window used for `model`, as registered or else from the
+    /// known model table; Ollama requests are capped
+    pub fn context_window(&self, model: &str) -> usize {
+        let window = self
+            .context_lengths
+            .get(model)
+            .copied()
+            .or_else(|| tokens::model_context_window(model))
+            .unwrap_or(DEFAULT_CONTEXT_WINDOW);
+        match self.kind {
+            ProviderKind::Ollama => window.min(OLLAMA_MAX_CONTEXT),
+            ProviderKind::OpenaiCompatible => window,
+        }
+    }
+
+    /// Single, non-streamed chat completion, fitted to the context window
+    pub async fn chat_completion(&self, model: &str, messages: &[ChatMessage], options: &ChatOptions) -> Result<ChatCompletion> {
+        let window = self.context_window(model);
+        let fitted = fit_messages(messages, prompt_budget(window));
+        let started = Instant::now();
+
+        let (content, reported) = match self.kind {
+            ProviderKind::Ollama => {
+                let mut body = json!({
+                    "model": model,
+                    "messages": fitted.messages,
+                    "stream": false,
+                    "options": { "num_ctx": window },
+                });
+                if let Some(temperature) = options.temperature {
+                    body["options"]["temperature"] = json!(temperature);
+                }
+                if options.json {
+                    body["format"] = json!("json");
+                }
+                let response = self.post("/api/chat", &body).await?;
+                (
+                    response["message"]["content"].as_str().map(str::to_string),
+                    (response["prompt_eval_count"].as_u64(), response["eval_count"].as_u64()),
+                )
+            }
+            ProviderKind::OpenaiCompatible => {
+                let mut body = json!({ "model": model, "messages": fitted.messages });
+                if let Some(temperature) = options.temperature {
+                    body["temperature"] = json!(temperature);
+                }
+                if options.json {
+                    body["response_format"] = json!({ "type": "json_object" });
+                }
+                let response = self.post("/chat/completions", &body).await?;
+                (
+                    response["choices"][0]["message"]["content"].as_str().map(str::to_string),
+                    (response["usage"]["prompt_tokens"].as_u64(), response["usage"]["completion_tokens"].as_u64()),
+                )
+            }
+        };
+        let content = content.map(|text| text.trim().to_string()).ok_or_else(|| Self::missing("message content"))?;
+
+        let usage = match reported {
+            (Some(prompt), Some(completion)) => TokenUsage::new(prompt as usize, completion as usize, false),
+            _ => TokenUsage::new(fitted.prompt_tokens, tokens::count_tokens(&content), true),
+        };
+        Ok(ChatCompletion {
+            content,
+            model: model.to_string(),
+            usage,
+            latency_ms: started.elapsed().as_millis() as u64,
+            context_window: window,
+            dropped_messages: fitted.dropped,
+            truncated: fitted.truncated,
+        })
+    }
+
+    /// Reply text of a chat completion
+    pub async fn chat(&self, model: &str, messages: &[ChatMessage]) -> Result<String> {
+        Ok(self.chat_completion(model, messages, &ChatOptions::default()).await?.content)
+    }
+
+    /// One embedding vector per input, in input order
+    pub async fn embed(&self, model: &str, inputs: &[String]) -> Result<Vec<Vec<f32>>> {
+        let vectors = match self.kind {
+            ProviderKind::Ollama => {
+                let response = self.post("/api/embed", &json!({ "model": model, "input": inputs })).await?;
+                serde_json::from_value::<Vec<Vec<f32>>>(response["embeddings"].clone())
+                    .map_err(|_| Self::missing("embeddings"))?
+            }
+            ProviderKind::OpenaiCompatible => {
+                #[derive(Deserialize)]
+                struct Embedding {
+                    index: usize,
+                    embedding: Vec<f32>,
+                }
+                let response = self.post("/embeddings", &json!({ "model": model, "input": inputs })).await?;
+                let mut data = serde_json::from_value::<Vec<Embedding>>(response["data"].clone())
+                    .map_err(|_| Self::missing("embeddings"))?;
+                data.sort_by_key(|embedding| embedding.index);
+                data.into_iter().map(|embedding| embedding.embedding).collect()
+            }
+        };
+        if vectors.len() != inputs.len() {
+            return Err(Self::missing("embedding for every input"));
+        }
+        Ok(vectors)
+    }
+
+    pub async fn list_models(&self) -> Result<Vec<String>> {
+        let (response, list, field) = match self.kind {
+            ProviderKind::Ollama => (self.get("/api/tags").await?, "models", "name"),
+            ProviderKind::OpenaiCompatible => (self.get("/models").await?, "data", "id"),
+        };
+        let models = response[list]
+            .as_array()
+            .ok_or_else(|| Self::missing("model list"))?
+            .iter()
+            .filter_map(|model| model[field].as_str().map(str::to_string))
+            .collect();
+        Ok(models)
+    }
+
+    /// Models with their size, context length and capabilities where the
+    /// provider reports them
+    pub async fn describe_models(&self) -> Result<Vec<ModelDetails>> {
+        match self.kind {
+            ProviderKind::Ollama => {
+                let response = self.get("/api/tags").await?;
+                let listed = response["models"].as_array().ok_or_else(|| Self::missing("model list"))?;
+                let mut models = Vec::with_capacity(listed.len());
+                for entry in listed {
+                    let Some(name) = entry["name"].as_str() else { continue };
+                    let mut details = ModelDetails {
+                        name: name.to_string(),
+                        family: entry["details"]["family"].as_str().map(str::to_string),
+                        parameter_size: entry["details"]["parameter_size"].as_str().map(str::to_string),
+                        quantization: entry["details"]["quantization_level"].as_str().map(str::to_string),
+                        size_bytes: entry["size"].as_u64(),
+                        context_length: None,
+                        capabilities: guess_capabilities(name),
+                    };
+                    // Context length and capabilities need a call per model
+                    match self.post("/api/show", &json!({ "model": name })).await {
+                        Ok(show) => {
+                            details.context_length = show["model_info"].as_object().and_then(|info| {
+                                info.iter()
+                                    .find(|(key, _)| key.ends_with(".context_length"))
+                                    .and_then(|(_, value)| value.as_u64())
+                                    .map(|length| length as usize)
+                            });
+                            if let Some(capabilities) = show["capabilities"].as_array() {
+                                details.capabilities =
+                                    capabilities.iter().filter_map(|c| c.as_str().map(str::to_string)).collect();
+                            }
+                        }
+                        Err(e) => eprintln!("⚠️ [LLM] No details for model {}: {}", name, e),
+                    }
+                    models.push(details);
+                }
+                Ok(models)
+            }
+            ProviderKind::OpenaiCompatible => Ok(self
+                .list_models()
+                .await?
+                .into_iter()
+                .map(|name| ModelDetails {
+                    context_length: tokens::model_context_window(&name),
+                    capabilities: guess_capabilities(&name),
+                    name,
+                    ..Default::default()
+                })
+                .collect()),
+        }
+    }
+
+    /// List models, and with a model also run a one-line prompt, timing both
+    pub async fn test(&self, model: Option<&str>) -> ProviderTestResult {
+        let started = Instant::now();
+        let mut result = ProviderTestResult {
+            ok: false,
+            latency_ms: 0,
+            models: Vec::new(),
+            reply: None,
+            usage: None,
+            error: None,
+        };
+        match self.list_models().await {
+            Ok(models) => result.models = models,
+            Err(e) => result.error = Some(e.to_string()),
+        }
+        if let (None, Some(model)) = (&result.error, model) {
+            let prompt = [ChatMessage::user("Reply with the single word OK.")];
+            match self.chat_completion(model, &prompt, &ChatOptions::default()).await {
+                Ok(completion) => {
+                    result.reply = Some(completion.content);
+                    result.usage = Some(completion.usage);
+                }
+                Err(e) => result.error = Some(e.to_string()),
+            }
+        }
+        result.ok = result.error.is_none();
+        result.latency_ms = started.elapsed().as_millis() as u64;
+        result
+    }
+}
+
+/// Provider and model a feature uses, see `model_router::resolve_model`
+pub fn resolve(conn: &Connection, feature: LlmFeature) -> Result<(LlmProvider, String)> {
+    let resolved = model_router::resolve_model(conn, feature)?;
+    Ok((LlmProvider::load(conn, &resolved.provider_id)?, resolved.model))
+}
+
+/// Base URL of the built-in Ollama provider, as set in Settings
+pub fn ollama_base_url(conn: &Connection) -> Result<String> {
+    let record = llm_provider_operations::get_provider(conn, OLLAMA_PROVIDER_ID)?
+        .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("LLM provider {}", OLLAMA_PROVIDER_ID) })?;
+    Ok(record.base_url.trim_end_matches('/').to_string())
+}
+
+/// Store a completion's token usage and latency for later analysis
+pub fn record_usage(conn: &Connection, feature: LlmFeature, provider_id: &str, completion: &ChatCompletion) -> Result<()> {
+    let metadata = json!({
+        "source": "llm",
+        "feature": feature.as_str(),
+        "provider_id": provider_id,
+        "model": completion.model,
+        "prompt_tokens": completion.usage.prompt_tokens,
+        "completion_tokens": completion.usage.completion_tokens,
+        "estimated": completion.usage.estimated,
+        "latency_ms": completion.latency_ms,
+        "dropped_messages": completion.dropped_messages,
+    });
+    performance_operations::create_performance_metric(
+        conn,
+        MetricType::TokenCount,
+        completion.usage.total_tokens as f64,
+        Some(metadata.to_string()),
+    )?;
+    Ok(())
+}
+
+/// Chat completion with the model configured for `feature`, fitted to its
+/// context window, with usage recorded
+pub async fn complete(db_manager: &DatabaseManager, feature: LlmFeature, messages: &[ChatMessage]) -> Result<ChatCompletion> {
+    complete_with(db_manager, feature, messages, &ChatOptions::default()).await
+}
+
+/// `complete` with generation options; a model given in `options` is used
+/// on the provider the feature resolves to
+pub async fn complete_with(
+    db_manager: &DatabaseManager,
+    feature: LlmFeature,
+    messages: &[ChatMessage],
+    options: &ChatOptions,
+) -> Result<ChatCompletion> {
+    let (provider, model) = {
+        let conn = db_manager.get_connection()?;
+        match options.model.clone().filter(|model| !model.trim().is_empty()) {
+            Some(model) => {
+                // The feature's provider; a named model still works on Ollama
+                // before any model is set up for the feature
+                let provider_id = match model_router::inspect_model(&conn, feature) {
+                    Ok(resolved) => resolved.provider_id,
+                    Err(LibreOllamaError::Configuration { .. }) => OLLAMA_PROVIDER_ID.to_string(),
+                    Err(e) => return Err(e),
+                };
+                (LlmProvider::load(&conn, &provider_id)?, model)
+            }
+            None => resolve(&conn, feature)?,
+        }
+    };
+    let completion = provider.chat_completion(&model, messages, options).await?;
+
+    let recorded = db_manager
+        .get_connection()
+        .map_err(LibreOllamaError::from)
+        .and_then(|conn| record_usage(&conn, feature, &provider.id, &completion));
+    if let Err(e) = recorded {
+        eprintln!("⚠️ [LLM] Failed to record token usage: {}", e);
+    }
+    Ok(completion)
+}
+
+/// Embeddings from the model configured for embeddings; inputs embedded
+/// recently by the same model are not sent again
+pub async fn embed(db_manager: &DatabaseManager, inputs: &[String]) -> Result<Vec<Vec<f32>>> {
+    let (provider, model) = {
+        let conn = db_manager.get_connection()?;
+        resolve(&conn, LlmFeature::Embeddings)?
+    };
+    let key = |input: &str| format!("{}\n{}\n{}", provider.id, model, input);
+    let mut vectors: Vec<Option<Vec<f32>>> = inputs.iter().map(|input| resource_budget::EMBEDDINGS.get(&key(input))).collect();
+
+    let missing: Vec<String> = inputs.iter().zip(&vectors).filter(|(_, vector)| vector.is_none()).map(|(input, _)| input.clone()).collect();
+    if !missing.is_empty() {
+        let mut embedded = provider.embed(&model, &missing).await?.into_iter();
+        for (input, vector) in inputs.iter().zip(vectors.iter_mut()).filter(|(_, vector)| vector.is_none()) {
+            let embedding = embedded.next().ok_or_else(|| LlmProvider::missing("embedding for every input"))?;
+            resource_budget::EMBEDDINGS.insert(key(input), embedding.clone());
+            *vector = Some(embedding);
+        }
+    }
+    Ok(vectors.into_iter().flatten().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        conn
+    }
+
+    fn remote(api_key: Option<&str>) -> ProviderInput {
+        ProviderInput {
+            id: "lm-studio".to_string(),
+            name: "LM Studio".to_string(),
+            kind: ProviderKind::OpenaiCompatible,
+            base_url: "http://localhost:1234/v1/".to_string(),
+            api_key: api_key.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_provider_keys_are_encrypted() {
+        let conn = setup_test_db();
+        let info = save_provider(&conn, &remote(Some("sk-test"))).unwrap();
+        assert!(info.has_api_key && !info.builtin);
+        assert_eq!(info.base_url, "http://localhost:1234/v1");
+
+        let record = llm_provider_operations::get_provider(&conn, "lm-studio").unwrap().unwrap();
+        assert_ne!(record.api_key_encrypted.as_deref(), Some("sk-test"));
+        assert_eq!(LlmProvider::from_record(&record).unwrap().api_key.as_deref(), Some("sk-test"));
+
+        // Saving without a key keeps it, an empty key removes it
+        assert!(save_provider(&conn, &remote(None)).unwrap().has_api_key);
+        assert!(!save_provider(&conn, &remote(Some(""))).unwrap().has_api_key);
+
+        assert_eq!(list_providers(&conn).unwrap()[0].id, OLLAMA_PROVIDER_ID);
+        assert!(delete_provider(&conn, OLLAMA_PROVIDER_ID).is_err());
+        assert!(save_provider(&conn, &ProviderInput { base_url: "localhost:1234".to_string(), ..remote(None) }).is_err());
+
+        // The Ollama commands follow the built-in provider's address
+        assert_eq!(ollama_base_url(&conn).unwrap(), "http://localhost:11434");
+        let gpu_box = ProviderInput {
+            id: OLLAMA_PROVIDER_ID.to_string(),
+            name: "Ollama".to_string(),
+            kind: ProviderKind::Ollama,
+            base_url: "http://gpu-box:11434/".to_string(),
+            api_key: None,
+        };
+        save_provider(&conn, &gpu_box).unwrap();
+        assert_eq!(ollama_base_url(&conn).unwrap(), "http://gpu-box:11434");
+    }
+
+    #[test]
+    fn test_feature_models() {
+        let conn = setup_test_db();
+        assert!(resolve(&conn, LlmFeature::Summarization).is_err());
+        assert!(set_feature_model(&conn, LlmFeature::Chat, "missing", "gpt-4o").is_err());
+
+        save_provider(&conn, &remote(None)).unwrap();
+        set_feature_model(&conn, LlmFeature::Chat, "lm-studio", "qwen2.5-7b").unwrap();
+        set_feature_model(&conn, LlmFeature::Embeddings, OLLAMA_PROVIDER_ID, "nomic-embed-text").unwrap();
+
+        let (provider, model) = resolve(&conn, LlmFeature::Chat).unwrap();
+        assert_eq!((provider.kind, model.as_str()), (ProviderKind::OpenaiCompatible, "qwen2.5-7b"));
+        assert_eq!(provider.url("/chat/completions"), "http://localhost:1234/v1/chat/completions");
+
+        let models = feature_models(&conn).unwrap();
+        assert_eq!(models.len(), LlmFeature::ALL.len());
+        assert!(models.iter().any(|m| m.feature == LlmFeature::Summarization && m.model.is_none()));
+
+        // Deleting a provider drops the features that used it
+        delete_provider(&conn, "lm-studio").unwrap();
+        assert!(resolve(&conn, LlmFeature::Chat).is_err());
+        assert!(resolve(&conn, LlmFeature::Embeddings).is_ok());
+    }
+
+    #[test]
+    fn test_fit_messages() {
+        let long_turn = "word ".repeat(200);
+        let messages = vec![
+            ChatMessage::system("Be brief."),
+            ChatMessage::user(long_turn.clone()),
+            ChatMessage { role: "assistant".to_string(), content: "Sure.".to_string() },
+            ChatMessage::user("And tomorrow?"),
+        ];
+
+        let everything = fit_messages(&messages, 10_000);
+        assert_eq!((everything.messages.len(), everything.dropped), (4, 0));
+
+        // The long early turn is dropped, the system prompt and newest turns kept
+        let fitted = fit_messages(&messages, 60);
+        assert_eq!(fitted.dropped, 1);
+        assert_eq!(fitted.messages[0].role, "system");
+        assert_eq!(fitted.messages.last().unwrap().content, "And tomorrow?");
+        assert!(fitted.prompt_tokens <= 60);
+
+        // A single message longer than the budget is cut
+        let cut = fit_messages(&[ChatMessage::user(long_turn)], 50);
+        assert!(cut.truncated && cut.prompt_tokens <= 50);
+
+        let conn = setup_test_db();
+        let completion = ChatCompletion {
+            content: "OK".to_string(),
+            model: "llama3.1".to_string(),
+            usage: TokenUsage::new(12, 1, false),
+            latency_ms: 340,
+            context_window: 8192,
+            dropped_messages: 0,
+            truncated: false,
+        };
+        record_usage(&conn, LlmFeature::Chat, OLLAMA_PROVIDER_ID, &completion).unwrap();
+        let since = chrono::Local::now().naive_local() - chrono::Duration::hours(1);
+        let summary = performance_operations::get_llm_usage_summary(&conn, since).unwrap();
+        assert_eq!((summary[0].requests, summary[0].prompt_tokens, summary[0].completion_tokens), (1, 12, 1));
+    }
+}
@@ -0,0 +1,587 @@
+//! Mail import from Thunderbird and Outlook
+//!
+//! Imported mail goes into the local message store under an archive
+//! account, so old archives are searched alongside synced Gmail. Each
+//! folder becomes a label; top-level folders with a Gmail counterpart
+//! (Inbox, Sent, Drafts, Trash, Junk) get the Gmail system label.
+//!
+//! Thunderbird keeps every folder as an mbox file, with subfolders in a
+//! `<folder>.sbd` directory beside it. Outlook PST and OST files are first
+//! converted to the same layout with `readpst` from libpst, which must be
+//! installed.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use mailparse::{MailAddr, MailHeaderMap, ParsedMail};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::database::operations::archive_operations::{self, ArchiveAccount};
+use crate::database::operations::backfill_operations::StoredMessage;
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::gmail::attachment_risk;
+use crate::services::gmail::api_service::{EmailAddress, EmailAttachment, ParsedEmail, ProcessedGmailMessage};
+use crate::services::pdf_extract::run_with_timeout;
+use crate::services::text_processing::to_plain_text;
+
+/// Archive account IDs start with this, keeping them apart from Gmail's
+pub const ARCHIVE_ACCOUNT_PREFIX: &str = "archive:";
+/// Label for messages in the root folder of a converted Outlook file
+const ROOT_FOLDER_LABEL: &str = "Imported";
+const MAX_SNIPPET_CHARS: usize = 200;
+/// A damaged data file can leave readpst looping; multi-gigabyte stores
+/// still convert well within this
+const READPST_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+/// Thunderbird marks deleted messages expunged until the folder is compacted
+const MOZILLA_EXPUNGED: u32 = 0x0008;
+const MOZILLA_READ: u32 = 0x0001;
+const MOZILLA_MARKED: u32 = 0x0004;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportSource {
+    Thunderbird,
+    Outlook,
+}
+
+impl ImportSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportSource::Thunderbird => "thunderbird",
+            ImportSource::Outlook => "outlook",
+        }
+    }
+}
+
+/// Outcome of importing one folder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderImport {
+    pub label: String,
+    pub imported: usize,
+    /// Already in the archive from another folder
+    pub duplicates: usize,
+    /// Deleted or unreadable
+    pub skipped: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportSummary {
+    pub account: ArchiveAccount,
+    pub folders: Vec<FolderImport>,
+    pub imported: usize,
+    pub duplicates: usize,
+    pub skipped: usize,
+}
+
+/// Import a Thunderbird profile, mail directory or single mbox file
+/// into a new archive account named `name`
+pub fn import_thunderbird(db: &DatabaseManager, path: &Path, name: &str) -> Result<ImportSummary> {
+    if !path.exists() {
+        return Err(LibreOllamaError::FileSystem {
+            message: "Thunderbird mail folder not found".to_string(),
+            path: Some(path.display().to_string()),
+        });
+    }
+    let mailboxes = mailbox_files(&thunderbird_mail_root(path))?;
+    import_mailboxes(db, ImportSource::Thunderbird, path, name, mailboxes)
+}
+
+/// Convert an Outlook PST or OST file with `readpst` and import it into a
+/// new archive account named `name`
+pub fn import_outlook(db: &DatabaseManager, path: &Path, name: &str) -> Result<ImportSummary> {
+    if !path.is_file() {
+        return Err(LibreOllamaError::FileSystem {
+            message: "Outlook data file not found".to_string(),
+            path: Some(path.display().to_string()),
+        });
+    }
+    if !readpst_available() {
+        return Err(LibreOllamaError::NotSupported {
+            operation: "Outlook import needs readpst from libpst (pst-utils)".to_string(),
+        });
+    }
+
+    let work_dir = std::env::temp_dir().join(format!("libreollama-pst-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&work_dir)?;
+    let result = convert_outlook(path, &work_dir).and_then(|mailboxes| {
+        import_mailboxes(db, ImportSource::Outlook, path, name, mailboxes)
+    });
+    let _ = std::fs::remove_dir_all(&work_dir);
+    result
+}
+
+pub fn readpst_available() -> bool {
+    Command::new("readpst").arg("-V").output().is_ok()
+}
+
+/// Run readpst into `work_dir` and list the mbox files it wrote
+fn convert_outlook(path: &Path, work_dir: &Path) -> Result<Vec<(PathBuf, Vec<String>)>> {
+    // -r: one directory per folder, each with an "mbox" file
+    let converted = run_with_timeout(
+        Command::new("readpst").args(["-r", "-q", "-o"]).arg(work_dir).arg(path),
+        READPST_TIMEOUT,
+    )?;
+    if !converted.status.success() {
+        return Err(LibreOllamaError::Internal {
+            message: format!("readpst failed: {}", String::from_utf8_lossy(&converted.stderr).trim()),
+        });
+    }
+    let mut mailboxes = mailbox_files(work_dir)?;
+    strip_store_root(&mut mailboxes);
+    Ok(mailboxes)
+}
+
+/// readpst names the top directory after the data file's root folder
+/// ("Personal Folders", "Outlook Data File"); it is not a real folder
+fn strip_store_root(mailboxes: &mut [(PathBuf, Vec<String>)]) {
+    let Some(root) = mailboxes.first().and_then(|(_, folder)| folder.first()).cloned() else {
+        return;
+    };
+    if mailboxes.iter().all(|(_, folder)| folder.first() == Some(&root)) {
+        for (_, folder) in mailboxes.iter_mut() {
+            folder.remove(0);
+        }
+    }
+}
+
+/// A profile directory holds its mail under Mail/Local Folders
+fn thunderbird_mail_root(path: &Path) -> PathBuf {
+    let local_folders = path.join("Mail").join("Local Folders");
+    if path.join("prefs.js").is_file() && local_folders.is_dir() {
+        local_folders
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// mbox files under `root` with their folder path: Thunderbird's
+/// extensionless folder files and `.sbd` subfolder directories, and the
+/// `mbox` files readpst writes into per-folder directories
+pub fn mailbox_files(root: &Path) -> Result<Vec<(PathBuf, Vec<String>)>> {
+    let mut mailboxes = Vec::new();
+    if root.is_file() {
+        let name = root.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+        mailboxes.push((root.to_path_buf(), vec![name]));
+    } else {
+        collect_mailboxes(root, &[], &mut mailboxes)?;
+    }
+    Ok(mailboxes)
+}
+
+fn collect_mailboxes(dir: &Path, folder: &[String], mailboxes: &mut Vec<(PathBuf, Vec<String>)>) -> Result<()> {
+    let mut entries = std::fs::read_dir(dir)?.filter_map(|entry| entry.ok()).collect::<Vec<_>>();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') {
+            continue;
+        }
+        let child = |name: &str| [folder, &[name.to_string()][..]].concat();
+        if path.is_dir() {
+            let name = name.strip_suffix(".sbd").unwrap_or(&name);
+            collect_mailboxes(&path, &child(name), mailboxes)?;
+        } else if name == "mbox" {
+            mailboxes.push((path, folder.to_vec()));
+        } else if path.extension().is_none() && is_mbox(&path) {
+            mailboxes.push((path, child(&name)));
+        }
+    }
+    Ok(())
+}
+
+fn is_mbox(path: &Path) -> bool {
+    let mut start = [0u8; 5];
+    File::open(path).and_then(|mut file| std::io::Read::read_exact(&mut file, &mut start)).is_ok() && &start == b"From "
+}
+
+/// The label a folder is stored under
+pub fn folder_label(folder: &[String]) -> String {
+    if let [name] = folder {
+        let system = match name.to_lowercase().as_str() {
+            "inbox" => Some("INBOX"),
+            "sent" | "sent items" | "sent mail" | "sent messages" => Some("SENT"),
+            "drafts" => Some("DRAFT"),
+            "trash" | "deleted items" | "deleted messages" => Some("TRASH"),
+            "junk" | "junk e-mail" | "junk email" | "spam" => Some("SPAM"),
+            _ => None,
+        };
+        if let Some(system) = system {
+            return system.to_string();
+        }
+    }
+    if folder.is_empty() {
+        ROOT_FOLDER_LABEL.to_string()
+    } else {
+        folder.join("/")
+    }
+}
+
+fn import_mailboxes(
+    db: &DatabaseManager,
+    source: ImportSource,
+    path: &Path,
+    name: &str,
+    mailboxes: Vec<(PathBuf, Vec<String>)>,
+) -> Result<ImportSummary> {
+    if mailboxes.is_empty() {
+        return Err(LibreOllamaError::InvalidInput {
+            message: format!("No mail folders found in {}", path.display()),
+            field: Some("path".to_string()),
+        });
+    }
+
+    let conn = db.get_connection()?;
+    let account_id = format!("{}{}", ARCHIVE_ACCOUNT_PREFIX, uuid::Uuid::new_v4());
+    archive_operations::create_archive_account(&conn, &account_id, name, source.as_str(), &path.display().to_string())?;
+
+    let mut folders = Vec::new();
+    for (file, folder) in mailboxes {
+        let label = folder_label(&folder);
+        let mut result = FolderImport { label: label.clone(), imported: 0, duplicates: 0, skipped: 0, error: None };
+        let imported = File::open(&file).map_err(LibreOllamaError::from).and_then(|file| {
+            let tx = conn.unchecked_transaction()?;
+            for_each_message(BufReader::new(file), |raw| {
+                let Some(message) = parse_message(raw, &account_id, &label) else {
+                    result.skipped += 1;
+                    return Ok(());
+                };
+                if archive_operations::store_archived_message(&tx, &message)? {
+                    result.imported += 1;
+                } else {
+                    result.duplicates += 1;
+                }
+                Ok(())
+            })?;
+            tx.commit()?;
+            Ok(())
+        });
+        if let Err(e) = imported {
+            eprintln!("⚠️ [IMPORT] Failed to import folder {}: {}", label, e);
+            result.error = Some(e.to_string());
+        }
+        folders.push(result);
+    }
+
+    archive_operations::finish_import(&conn, &account_id)?;
+    let account = archive_operations::get_archive_account(&conn, &account_id)?.ok_or_else(|| LibreOllamaError::NotFound {
+        resource: format!("Archive account {}", account_id),
+    })?;
+    println!("📥 [IMPORT] Imported {} messages from {} into {}", account.message_count, path.display(), account.name);
+
+    Ok(ImportSummary {
+        imported: folders.iter().map(|f| f.imported).sum(),
+        duplicates: folders.iter().map(|f| f.duplicates).sum(),
+        skipped: folders.iter().map(|f| f.skipped).sum(),
+        account,
+        folders,
+    })
+}
+
+/// Call `f` with each raw message of an mbox, without the "From " line.
+/// A message starts at a "From " line at the start of the file or after
+/// a blank line; ">From " quoting in bodies is undone.
+pub fn for_each_message<R: BufRead>(mut reader: R, mut f: impl FnMut(&[u8]) -> Result<()>) -> Result<()> {
+    let mut message: Vec<u8> = Vec::new();
+    let mut started = false;
+    let mut after_blank = true;
+    let mut line = Vec::new();
+
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        if after_blank && line.starts_with(b"From ") {
+            if started {
+                f(&message)?;
+            }
+            message.clear();
+            started = true;
+            after_blank = false;
+            continue;
+        }
+        after_blank = line == b"\n" || line == b"\r\n";
+        let quoted = line.iter().take_while(|&&b| b == b'>').count();
+        if quoted > 0 && line[quoted..].starts_with(b"From ") {
+            message.extend_from_slice(&line[1..]);
+        } else {
+            message.extend_from_slice(&line);
+        }
+    }
+    if started {
+        f(&message)?;
+    }
+    Ok(())
+}
+
+/// Short stable ID for a Message-ID, so a message filed twice is stored once
+fn short_id(key: &str) -> String {
+    hex::encode(&Sha256::digest(key.as_bytes())[..12])
+}
+
+fn first_message_id(value: &str) -> Option<String> {
+    value
+        .split(['<', '>'])
+        .map(str::trim)
+        .find(|part| part.contains('@'))
+        .map(str::to_string)
+}
+
+fn addresses(parsed: &ParsedMail, header: &str) -> Vec<EmailAddress> {
+    parsed
+        .headers
+        .get_first_value(header)
+        .and_then(|value| mailparse::addrparse(&value).ok())
+        .map(|list| {
+            list.iter()
+                .flat_map(|addr| match addr {
+                    MailAddr::Single(single) => vec![single.clone()],
+                    MailAddr::Group(group) => group.addrs.clone(),
+                })
+                .map(|single| EmailAddress { email: single.addr, name: single.display_name })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn collect_parts(part: &ParsedMail, text: &mut Option<String>, html: &mut Option<String>, attachments: &mut Vec<EmailAttachment>) {
+    if !part.subparts.is_empty() {
+        for subpart in &part.subparts {
+            collect_parts(subpart, text, html, attachments);
+        }
+        return;
+    }
+    let disposition = part.get_content_disposition();
+    let filename = disposition
+        .params
+        .get("filename")
+        .or_else(|| part.ctype.params.get("name"))
+        .cloned();
+    let is_attachment = disposition.disposition == mailparse::DispositionType::Attachment || filename.is_some();
+    match part.ctype.mimetype.as_str() {
+        "text/plain" if text.is_none() && !is_attachment => *text = part.get_body().ok(),
+        "text/html" if html.is_none() && !is_attachment => *html = part.get_body().ok(),
+        _ if is_attachment || !part.ctype.mimetype.starts_with("text/") => {
+            let body = part.get_body_raw().ok();
+            let risk = attachment_risk::assess(filename.as_deref(), &part.ctype.mimetype, body.as_deref());
+            attachments.push(EmailAttachment {
+                id: format!("part-{}", attachments.len() + 1),
+                filename,
+                content_type: part.ctype.mimetype.clone(),
+                size: body.as_ref().map(Vec::len),
+                content_id: part.headers.get_first_value("Content-ID").and_then(|id| first_message_id(&id)),
+                is_inline: disposition.disposition == mailparse::DispositionType::Inline,
+                // Attachment contents stay in the original archive
+                data: None,
+                risk: Some(risk),
+            });
+        }
+        _ => {}
+    }
+}
+
+/// Parse a raw message into the stored form, or `None` if it is unreadable
+/// or was deleted in Thunderbird
+pub fn parse_message(raw: &[u8], account_id: &str, label: &str) -> Option<StoredMessage> {
+    let parsed = mailparse::parse_mail(raw).ok()?;
+    let header = |name: &str| parsed.headers.get_first_value(name);
+
+    let mut labels = vec![label.to_string()];
+    match header("X-Mozilla-Status").and_then(|value| u32::from_str_radix(value.trim(), 16).ok()) {
+        Some(flags) if flags & MOZILLA_EXPUNGED != 0 => return None,
+        Some(flags) => {
+            if flags & MOZILLA_READ == 0 {
+                labels.push("UNREAD".to_string());
+            }
+            if flags & MOZILLA_MARKED != 0 {
+                labels.push("STARRED".to_string());
+            }
+        }
+        // readpst writes "Status: RO" for read messages
+        None => {
+            if header("Status").is_some_and(|status| !status.contains('R')) {
+                labels.push("UNREAD".to_string());
+            }
+        }
+    }
+
+    let message_key = header("Message-ID")
+        .and_then(|id| first_message_id(&id))
+        .unwrap_or_else(|| hex::encode(Sha256::digest(raw)));
+    let thread_key = header("References")
+        .and_then(|refs| first_message_id(&refs))
+        .or_else(|| header("In-Reply-To").and_then(|id| first_message_id(&id)))
+        .unwrap_or_else(|| message_key.clone());
+    let internal_date = header("Date").and_then(|date| mailparse::dateparse(&date).ok()).map(|secs| secs * 1000);
+
+    let (mut body_text, mut body_html, mut attachments) = (None, None, Vec::new());
+    collect_parts(&parsed, &mut body_text, &mut body_html, &mut attachments);
+    if body_text.is_none() {
+        body_text = body_html.as_deref().map(to_plain_text);
+    }
+    let snippet = body_text
+        .as_deref()
+        .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" ").chars().take(MAX_SNIPPET_CHARS).collect::<String>());
+
+    let from = addresses(&parsed, "From").into_iter().next().unwrap_or(EmailAddress { email: String::new(), name: None });
+    let headers: HashMap<String, String> = parsed.headers.iter().map(|h| (h.get_key(), h.get_value())).collect();
+    let message = ProcessedGmailMessage {
+        id: short_id(&message_key),
+        thread_id: short_id(&thread_key),
+        parsed_content: ParsedEmail {
+            message_id: Some(message_key),
+            thread_id: Some(thread_key),
+            subject: header("Subject"),
+            from,
+            to: addresses(&parsed, "To"),
+            cc: addresses(&parsed, "Cc"),
+            bcc: addresses(&parsed, "Bcc"),
+            reply_to: addresses(&parsed, "Reply-To").into_iter().next(),
+            date: header("Date"),
+            body_text,
+            body_html,
+            attachments,
+            headers,
+            is_multipart: !parsed.subparts.is_empty(),
+            content_type: parsed.ctype.mimetype.clone(),
+            size_estimate: Some(raw.len()),
+        },
+        labels: labels.clone(),
+        snippet,
+        internal_date: internal_date.map(|ms| ms.to_string()),
+        size_estimate: i32::try_from(raw.len()).ok(),
+    };
+
+    Some(StoredMessage {
+        account_id: account_id.to_string(),
+        message_id: message.id.clone(),
+        thread_id: message.thread_id.clone(),
+        label_ids: labels,
+        internal_date,
+        content: "full".to_string(),
+        message_data: serde_json::to_value(&message).ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::operations::archive_operations::{list_archive_folders, list_archived_messages};
+
+    const MBOX: &str = "From - Mon Mar 04 09:00:00 2019\n\
+        X-Mozilla-Status: 0001\n\
+        Message-ID: <root@example.com>\n\
+        From: Ada <ada@example.com>\n\
+        To: me@example.com\n\
+        Subject: Quarterly report\n\
+        Date: Mon, 4 Mar 2019 09:00:00 +0000\n\
+        \n\
+        Numbers attached.\n\
+        >From the team\n\
+        \n\
+        From - Tue Mar 05 10:00:00 2019\n\
+        X-Mozilla-Status: 0000\n\
+        Message-ID: <reply@example.com>\n\
+        References: <root@example.com>\n\
+        From: Bob <bob@example.com>\n\
+        Subject: Re: Quarterly report\n\
+        Date: Tue, 5 Mar 2019 10:00:00 +0000\n\
+        \n\
+        Thanks.\n\
+        \n\
+        From - Wed Mar 06 10:00:00 2019\n\
+        X-Mozilla-Status: 0009\n\
+        Message-ID: <deleted@example.com>\n\
+        Subject: Deleted\n\
+        \n\
+        Gone.\n";
+
+    #[test]
+    fn test_for_each_message_splits_and_unquotes() {
+        let mut messages = Vec::new();
+        for_each_message(MBOX.as_bytes(), |raw| {
+            messages.push(String::from_utf8_lossy(raw).into_owned());
+            Ok(())
+        }).unwrap();
+        assert_eq!(messages.len(), 3);
+        assert!(messages[0].starts_with("X-Mozilla-Status: 0001"));
+        assert!(messages[0].contains("\nFrom the team\n"));
+    }
+
+    #[test]
+    fn test_parse_message_flags_and_threads() {
+        let mut parsed = Vec::new();
+        for_each_message(MBOX.as_bytes(), |raw| {
+            parsed.push(parse_message(raw, "archive:test", "Work/Reports"));
+            Ok(())
+        }).unwrap();
+
+        let root = parsed[0].as_ref().unwrap();
+        let reply = parsed[1].as_ref().unwrap();
+        assert!(parsed[2].is_none());
+        assert_eq!(root.thread_id, reply.thread_id);
+        assert_eq!(root.label_ids, vec!["Work/Reports"]);
+        assert_eq!(reply.label_ids, vec!["Work/Reports", "UNREAD"]);
+        assert_eq!(root.internal_date, Some(1_551_690_000_000));
+        assert_eq!(root.message_data["parsed_content"]["from"]["name"], "Ada");
+    }
+
+    #[test]
+    fn test_folder_layout_and_labels() {
+        let dir = std::env::temp_dir().join(format!("libreollama-import-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("Inbox.sbd")).unwrap();
+        std::fs::write(dir.join("Inbox"), MBOX).unwrap();
+        std::fs::write(dir.join("Inbox.msf"), "// index").unwrap();
+        std::fs::write(dir.join("Inbox.sbd").join("Clients"), MBOX).unwrap();
+        std::fs::write(dir.join("Trash"), "").unwrap();
+
+        let mailboxes = mailbox_files(&dir).unwrap();
+        let labels: Vec<String> = mailboxes.iter().map(|(_, folder)| folder_label(folder)).collect();
+        assert_eq!(labels, vec!["INBOX", "Inbox/Clients"]);
+
+        let mut outlook = vec![
+            (PathBuf::from("a"), vec!["Personal Folders".to_string()]),
+            (PathBuf::from("b"), vec!["Personal Folders".to_string(), "Sent Items".to_string()]),
+        ];
+        strip_store_root(&mut outlook);
+        assert_eq!(folder_label(&outlook[0].1), ROOT_FOLDER_LABEL);
+        assert_eq!(folder_label(&outlook[1].1), "SENT");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_store_merges_folders() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::database::schema::run_migrations(&conn).unwrap();
+        archive_operations::create_archive_account(&conn, "archive:test", "Old mail", "thunderbird", "/tmp/profile").unwrap();
+
+        for label in ["INBOX", "Projects"] {
+            for_each_message(MBOX.as_bytes(), |raw| {
+                if let Some(message) = parse_message(raw, "archive:test", label) {
+                    archive_operations::store_archived_message(&conn, &message)?;
+                }
+                Ok(())
+            }).unwrap();
+        }
+        archive_operations::finish_import(&conn, "archive:test").unwrap();
+
+        let account = archive_operations::get_archive_account(&conn, "archive:test").unwrap().unwrap();
+        assert_eq!(account.message_count, 2);
+        let folders = list_archive_folders(&conn, "archive:test").unwrap();
+        assert!(folders.iter().any(|f| f.label == "Projects" && f.message_count == 2));
+        let projects = list_archived_messages(&conn, "archive:test", Some("Projects"), 10, 0).unwrap();
+        assert_eq!(projects[0].subject, "Re: Quarterly report");
+
+        assert!(archive_operations::delete_archive_account(&conn, "archive:test").unwrap());
+        assert!(list_archived_messages(&conn, "archive:test", None, 10, 0).unwrap().is_empty());
+    }
+}
@@ -1,5 +1,55 @@
+pub mod account_detection;
+pub mod activity;
+pub mod app_lock;
+pub mod availability;
+pub mod canvas_assistant;
+pub mod canvas_export;
+pub mod chat_attachments;
+pub mod chat_service;
+pub mod clipboard_watcher;
+pub mod conversation_memory;
+pub mod crash_reports;
+pub mod daily_digest;
+pub mod diagnostics;
+pub mod events;
+pub mod feeds;
+pub mod file_access;
+pub mod global_search;
 pub mod gmail;
 pub mod google;
+pub mod google_backoff;
+pub mod hardware;
+pub mod i18n;
+pub mod ics;
+pub mod image_proxy;
+pub mod launch_args;
+pub mod link_preview;
+pub mod llm_provider;
+pub mod mail_import;
+pub mod model_router;
+pub mod note_templates;
+pub mod notification;
+pub mod ollama_supervisor;
+pub mod onboarding;
+pub mod pdf_extract;
+pub mod prompt_templates;
+pub mod quick_capture;
+pub mod request_scheduler;
+pub mod resource_budget;
+pub mod secure_wipe;
+pub mod service_registry;
+pub mod settings;
+pub mod shutdown;
+pub mod sync;
+pub mod task_supervisor;
+pub mod text_processing;
+pub mod time_service;
+pub mod timeline;
+pub mod transcription_service;
+pub mod updater;
+pub mod vault;
+pub mod web_clipper;
+pub mod workspaces;
 
 // Main export from gmail module
 // Gmail services are managed directly in lib.rs
@@ -19,12 +69,12 @@ pub mod google;
 /// 
 /// Current Services:
 /// - `gmail::GmailAuthService`: Gmail authentication and account management
+/// - `notification::NotificationService`: New-mail alerts, quiet hours and VIP senders
 /// 
 /// Planned Services:
 /// - `database::DatabaseService`: Database operations and migrations
 /// - `sync::SyncService`: Cross-platform synchronization
 /// - `cache::CacheService`: Intelligent caching and offline support
-/// - `notification::NotificationService`: System notifications
 /// - `security::SecurityService`: Encryption and security operations
 /// - `agent::AgentService`: AI agent coordination
 /// - `canvas::CanvasService`: Canvas data management
@@ -0,0 +1,535 @@
+//! Model registry and per-feature routing
+//!
+//! The registry (`llm_models`) records the models each provider offers with
+//! their size, context length and capabilities; it is refreshed from the
+//! providers at startup and on demand. `resolve_model` decides which model
+//! a feature uses, in order:
+//!
+//! 1. the model chosen for the feature in `llm_feature_models`;
+//! 2. for translation and quick-capture classification, the model set in
+//!    their older per-feature settings;
+//! 3. the feature's routing rule, e.g. the smallest local completion model
+//!    for classification and the largest for chat;
+//! 4. the default of the older setting, where there is one.
+//!
+//! With a hardware profile recorded (see `hardware`), rules skip models the
+//! machine cannot hold, and a chosen model that is too large is refused with
+//! models that fit suggested instead. Models that only just fit are resolved
+//! with their fit attached so callers can warn about them.
+
+use std::time::Duration;
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::database::operations::model_registry_operations::{self, ModelRecord, RoutingRuleRecord};
+use crate::database::operations::{llm_provider_operations, preference_operations};
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::events::{BackendEvent, EventBus, ModelResourceWarningEvent};
+use crate::services::gmail::translation_service::{DEFAULT_TRANSLATION_MODEL, TRANSLATION_MODEL_PREFERENCE};
+use crate::services::hardware::{self, FitLevel, HardwareProfile, ResourceFit};
+use crate::services::llm_provider::{LlmFeature, LlmProvider, OLLAMA_PROVIDER_ID};
+use crate::services::quick_capture::{DEFAULT_QUICK_CAPTURE_MODEL, QUICK_CAPTURE_MODEL_PREFERENCE};
+
+pub const CAPABILITIES: &[&str] = &["completion", "embedding", "vision", "tools"];
+
+/// Ollama may still be starting when the app opens
+const STARTUP_REFRESH_DELAY: Duration = Duration::from_secs(10);
+/// Fitting models suggested in place of one that is too large
+const MAX_ALTERNATIVES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SizePreference {
+    Smallest,
+    Largest,
+}
+
+impl SizePreference {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SizePreference::Smallest => "smallest",
+            SizePreference::Largest => "largest",
+        }
+    }
+
+    pub fn parse(prefer: &str) -> Result<Self> {
+        match prefer {
+            "smallest" => Ok(SizePreference::Smallest),
+            "largest" => Ok(SizePreference::Largest),
+            other => Err(LibreOllamaError::InvalidInput {
+                message: format!("Unknown size preference: {}", other),
+                field: Some("prefer".to_string()),
+            }),
+        }
+    }
+}
+
+/// How to pick a model for a feature that has none chosen
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingRule {
+    pub feature: LlmFeature,
+    pub provider_id: String,
+    pub capability: String,
+    pub prefer: SizePreference,
+    pub max_size_bytes: Option<i64>,
+    pub min_context_length: Option<i64>,
+}
+
+impl TryFrom<RoutingRuleRecord> for RoutingRule {
+    type Error = LibreOllamaError;
+
+    fn try_from(record: RoutingRuleRecord) -> Result<Self> {
+        Ok(Self {
+            feature: LlmFeature::parse(&record.feature)?,
+            provider_id: record.provider_id,
+            capability: record.capability,
+            prefer: SizePreference::parse(&record.prefer)?,
+            max_size_bytes: record.max_size_bytes,
+            min_context_length: record.min_context_length,
+        })
+    }
+}
+
+/// A registered model as shown in settings
+#[derive(Debug, Clone, Serialize)]
+pub struct RegisteredModel {
+    pub provider_id: String,
+    pub name: String,
+    pub family: Option<String>,
+    pub parameter_size: Option<String>,
+    pub quantization: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub context_length: Option<i64>,
+    pub capabilities: Vec<String>,
+    pub refreshed_at: String,
+}
+
+impl From<&ModelRecord> for RegisteredModel {
+    fn from(record: &ModelRecord) -> Self {
+        Self {
+            provider_id: record.provider_id.clone(),
+            name: record.name.clone(),
+            family: record.family.clone(),
+            parameter_size: record.parameter_size.clone(),
+            quantization: record.quantization.clone(),
+            size_bytes: record.size_bytes,
+            context_length: record.context_length,
+            capabilities: serde_json::from_str(&record.capabilities).unwrap_or_default(),
+            refreshed_at: record.refreshed_at.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelSource {
+    /// Chosen for the feature in settings
+    Selected,
+    /// The feature's own model setting
+    Setting,
+    /// Picked by the routing rule
+    Rule,
+    /// The feature's built-in default
+    Default,
+}
+
+/// The model a feature will use and why
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedModel {
+    pub feature: LlmFeature,
+    pub provider_id: String,
+    pub model: String,
+    pub source: ModelSource,
+    /// Registry entry, when the model is registered
+    pub details: Option<RegisteredModel>,
+    /// How the model fits this machine, when its size and the hardware are known
+    pub fit: Option<ResourceFit>,
+    /// Registered models that fit, when this one does not fit comfortably
+    pub alternatives: Vec<String>,
+}
+
+impl ResolvedModel {
+    /// Event for a model that is tight on memory or too large, if it is
+    pub fn resource_warning(&self) -> Option<ModelResourceWarningEvent> {
+        let fit = self.fit.as_ref().filter(|fit| fit.level != FitLevel::Fits)?;
+        Some(ModelResourceWarningEvent {
+            feature: self.feature.as_str().to_string(),
+            provider_id: self.provider_id.clone(),
+            model: self.model.clone(),
+            level: fit.level,
+            required_bytes: fit.required_bytes,
+            available_bytes: fit.available_bytes,
+            message: fit.message.clone(),
+            alternatives: self.alternatives.clone(),
+        })
+    }
+}
+
+/// Older setting naming a local model for the feature, with its default
+fn feature_setting(feature: LlmFeature) -> Option<(&'static str, &'static str)> {
+    match feature {
+        LlmFeature::Translation => Some((TRANSLATION_MODEL_PREFERENCE, DEFAULT_TRANSLATION_MODEL)),
+        LlmFeature::Classification => Some((QUICK_CAPTURE_MODEL_PREFERENCE, DEFAULT_QUICK_CAPTURE_MODEL)),
+        _ => None,
+    }
+}
+
+/// Capability a feature needs from its model
+fn feature_capability(feature: LlmFeature) -> &'static str {
+    match feature {
+        LlmFeature::Embeddings => "embedding",
+        _ => "completion",
+    }
+}
+
+fn has_capability(model: &ModelRecord, capability: &str) -> bool {
+    let capabilities: Vec<String> = serde_json::from_str(&model.capabilities).unwrap_or_default();
+    capabilities.iter().any(|c| c == capability)
+}
+
+/// Fit level of a registered model; unknown sizes or hardware count as fitting
+fn fit_level(model: &ModelRecord, profile: Option<&HardwareProfile>) -> FitLevel {
+    match (profile, model.size_bytes) {
+        (Some(profile), Some(size)) => hardware::assess(profile, size.max(0) as u64).level,
+        _ => FitLevel::Fits,
+    }
+}
+
+/// The registered model a rule picks, if any qualifies. Models too large for
+/// `profile` are skipped and ones that fit comfortably rank first.
+pub fn apply_rule<'a>(
+    models: &'a [ModelRecord],
+    rule: &RoutingRule,
+    profile: Option<&HardwareProfile>,
+) -> Option<&'a ModelRecord> {
+    let candidates = models
+        .iter()
+        .filter(|model| {
+            model.provider_id == rule.provider_id
+                && has_capability(model, &rule.capability)
+                && rule.max_size_bytes.is_none_or(|max| model.size_bytes.is_some_and(|size| size <= max))
+                && rule.min_context_length.is_none_or(|min| model.context_length.is_some_and(|length| length >= min))
+        })
+        .map(|model| (fit_level(model, profile), model))
+        .filter(|(level, _)| *level != FitLevel::TooLarge);
+    // Models of unknown size rank after every sized one
+    match rule.prefer {
+        SizePreference::Smallest => candidates
+            .min_by_key(|(level, model)| (*level, model.size_bytes.is_none(), model.size_bytes))
+            .map(|(_, model)| model),
+        SizePreference::Largest => candidates
+            .max_by_key(|(level, model)| (*level == FitLevel::Fits, model.size_bytes.is_some(), model.size_bytes))
+            .map(|(_, model)| model),
+    }
+}
+
+/// Registered models of a provider with `capability` that fit, largest first
+fn fitting_alternatives(
+    conn: &Connection,
+    provider_id: &str,
+    capability: &str,
+    exclude: &str,
+    profile: &HardwareProfile,
+) -> Result<Vec<String>> {
+    let mut models: Vec<ModelRecord> = model_registry_operations::list_models(conn, Some(provider_id))?
+        .into_iter()
+        .filter(|model| {
+            model.name != exclude
+                && has_capability(model, capability)
+                && model.size_bytes.is_some()
+                && fit_level(model, Some(profile)) == FitLevel::Fits
+        })
+        .collect();
+    models.sort_by_key(|model| std::cmp::Reverse(model.size_bytes));
+    Ok(models.into_iter().take(MAX_ALTERNATIVES).map(|model| model.name).collect())
+}
+
+/// Which provider and model `feature` uses, and how it fits this machine.
+/// Unlike `resolve_model` this never refuses a model for its size.
+pub fn inspect_model(conn: &Connection, feature: LlmFeature) -> Result<ResolvedModel> {
+    let profile = hardware::load_profile(conn)?;
+    let resolved = |provider_id: String, model: String, source: ModelSource| -> Result<ResolvedModel> {
+        let details = model_registry_operations::get_model(conn, &provider_id, &model)?;
+        let fit = profile
+            .as_ref()
+            .zip(details.as_ref().and_then(|details| details.size_bytes))
+            .map(|(profile, size)| hardware::assess(profile, size.max(0) as u64));
+        let alternatives = match (&profile, &fit) {
+            (Some(profile), Some(fit)) if fit.level != FitLevel::Fits => {
+                fitting_alternatives(conn, &provider_id, feature_capability(feature), &model, profile)?
+            }
+            _ => Vec::new(),
+        };
+        Ok(ResolvedModel {
+            feature,
+            details: details.as_ref().map(RegisteredModel::from),
+            provider_id,
+            model,
+            source,
+            fit,
+            alternatives,
+        })
+    };
+
+    if let Some(selection) = llm_provider_operations::get_feature_model(conn, feature.as_str())? {
+        return resolved(selection.provider_id, selection.model, ModelSource::Selected);
+    }
+
+    let setting = feature_setting(feature);
+    if let Some((key, _)) = setting {
+        let stored = preference_operations::get_preference_value(conn, key)?;
+        if let Some(model) = stored.map(|model| model.trim().to_string()).filter(|model| !model.is_empty()) {
+            return resolved(OLLAMA_PROVIDER_ID.to_string(), model, ModelSource::Setting);
+        }
+    }
+
+    if let Some(record) = model_registry_operations::get_rule(conn, feature.as_str())? {
+        let rule = RoutingRule::try_from(record)?;
+        let models = model_registry_operations::list_models(conn, Some(&rule.provider_id))?;
+        if let Some(model) = apply_rule(&models, &rule, profile.as_ref()) {
+            return resolved(model.provider_id.clone(), model.name.clone(), ModelSource::Rule);
+        }
+    }
+
+    match setting {
+        Some((_, default)) => resolved(OLLAMA_PROVIDER_ID.to_string(), default.to_string(), ModelSource::Default),
+        None => Err(LibreOllamaError::Configuration {
+            message: format!(
+                "No model available for {}; choose one in Settings or install a matching model",
+                feature.as_str()
+            ),
+            config_key: Some(format!("llm.{}", feature.as_str())),
+        }),
+    }
+}
+
+/// Which provider and model `feature` uses. A model chosen in settings that
+/// is too large for this machine is refused, naming models that fit.
+pub fn resolve_model(conn: &Connection, feature: LlmFeature) -> Result<ResolvedModel> {
+    let resolved = inspect_model(conn, feature)?;
+    match &resolved.fit {
+        Some(fit) if fit.level == FitLevel::TooLarge => {
+            let suggestion = if resolved.alternatives.is_empty() {
+                "choose a smaller model in Settings".to_string()
+            } else {
+                format!("try {}", resolved.alternatives.join(", "))
+            };
+            Err(LibreOllamaError::Configuration {
+                message: format!("{} is too large for this machine ({}); {}", resolved.model, fit.message, suggestion),
+                config_key: Some(format!("llm.{}", feature.as_str())),
+            })
+        }
+        _ => Ok(resolved),
+    }
+}
+
+/// Warnings for every feature whose model is tight on memory or too large
+pub fn resource_warnings(conn: &Connection) -> Vec<ModelResourceWarningEvent> {
+    LlmFeature::ALL
+        .iter()
+        .filter_map(|feature| inspect_model(conn, *feature).ok())
+        .filter_map(|resolved| resolved.resource_warning())
+        .collect()
+}
+
+pub fn list_models(conn: &Connection, provider_id: Option<&str>) -> Result<Vec<RegisteredModel>> {
+    Ok(model_registry_operations::list_models(conn, provider_id)?.iter().map(RegisteredModel::from).collect())
+}
+
+/// Re-read the model list of one provider, or of every provider. A provider
+/// that cannot be reached keeps its previous entries.
+pub async fn refresh_registry(db_manager: &DatabaseManager, provider_id: Option<&str>) -> Result<Vec<RegisteredModel>> {
+    let providers = {
+        let conn = db_manager.get_connection()?;
+        match provider_id {
+            Some(id) => vec![LlmProvider::load(&conn, id)?],
+            None => llm_provider_operations::list_providers(&conn)?
+                .iter()
+                .map(|record| LlmProvider::load(&conn, &record.id))
+                .collect::<Result<Vec<_>>>()?,
+        }
+    };
+
+    for provider in providers {
+        let described = match provider.describe_models().await {
+            Ok(described) => described,
+            Err(e) if provider_id.is_none() => {
+                eprintln!("⚠️ [LLM] Keeping registered models of {}: {}", provider.id, e);
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        let records: Vec<ModelRecord> = described
+            .into_iter()
+            .map(|model| ModelRecord {
+                provider_id: provider.id.clone(),
+                name: model.name,
+                family: model.family,
+                parameter_size: model.parameter_size,
+                quantization: model.quantization,
+                size_bytes: model.size_bytes.map(|size| size as i64),
+                context_length: model.context_length.map(|length| length as i64),
+                capabilities: serde_json::to_string(&model.capabilities).unwrap_or_else(|_| "[]".to_string()),
+                refreshed_at: String::new(),
+            })
+            .collect();
+        let mut conn = db_manager.get_connection()?;
+        model_registry_operations::replace_models(&mut conn, &provider.id, &records)?;
+    }
+
+    list_models(&db_manager.get_connection()?, provider_id)
+}
+
+/// Detect and record the machine's memory and GPUs
+pub async fn detect_hardware(db_manager: &DatabaseManager) -> Result<HardwareProfile> {
+    let profile = tokio::task::spawn_blocking(hardware::detect)
+        .await
+        .map_err(|e| LibreOllamaError::Internal { message: format!("Hardware detection failed: {}", e) })?;
+    hardware::save_profile(&db_manager.get_connection()?, &profile)?;
+    Ok(profile)
+}
+
+/// Detect the hardware, fill the registry shortly after startup and warn
+/// about feature models the machine is unlikely to run well
+pub fn spawn_startup_refresh(app: tauri::AppHandle, db_manager: std::sync::Arc<DatabaseManager>) {
+    use tauri::Manager;
+
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = detect_hardware(&db_manager).await {
+            eprintln!("⚠️ [LLM] Failed to detect hardware: {}", e);
+        }
+        tokio::time::sleep(STARTUP_REFRESH_DELAY).await;
+        if let Err(e) = refresh_registry(&db_manager, None).await {
+            eprintln!("⚠️ [LLM] Failed to refresh the model registry: {}", e);
+        }
+        let warnings = match db_manager.get_connection() {
+            Ok(conn) => resource_warnings(&conn),
+            Err(_) => return,
+        };
+        if let Some(events) = app.try_state::<EventBus>() {
+            for warning in warnings {
+                events.emit(BackendEvent::ModelResourceWarning(warning));
+            }
+        }
+    });
+}
+
+pub fn list_rules(conn: &Connection) -> Result<Vec<RoutingRule>> {
+    model_registry_operations::list_rules(conn)?.into_iter().map(RoutingRule::try_from).collect()
+}
+
+pub fn set_rule(conn: &Connection, rule: &RoutingRule) -> Result<RoutingRule> {
+    if llm_provider_operations::get_provider(conn, &rule.provider_id)?.is_none() {
+        return Err(LibreOllamaError::NotFound { resource: format!("LLM provider {}", rule.provider_id) });
+    }
+    if !CAPABILITIES.contains(&rule.capability.as_str()) {
+        return Err(LibreOllamaError::InvalidInput {
+            message: format!("Unknown capability {}; use one of {}", rule.capability, CAPABILITIES.join(", ")),
+            field: Some("capability".to_string()),
+        });
+    }
+    model_registry_operations::set_rule(
+        conn,
+        rule.feature.as_str(),
+        &rule.provider_id,
+        &rule.capability,
+        rule.prefer.as_str(),
+        rule.max_size_bytes,
+        rule.min_context_length,
+    )?;
+    Ok(rule.clone())
+}
+
+pub fn delete_rule(conn: &Connection, feature: LlmFeature) -> Result<bool> {
+    Ok(model_registry_operations::delete_rule(conn, feature.as_str())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    fn model(name: &str, size: Option<i64>, capabilities: &str) -> ModelRecord {
+        ModelRecord {
+            provider_id: OLLAMA_PROVIDER_ID.to_string(),
+            name: name.to_string(),
+            family: None,
+            parameter_size: None,
+            quantization: None,
+            size_bytes: size,
+            context_length: Some(8192),
+            capabilities: capabilities.to_string(),
+            refreshed_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_routing() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        // Nothing registered: features with an older setting use its default
+        let translation = resolve_model(&conn, LlmFeature::Translation).unwrap();
+        assert_eq!((translation.model.as_str(), translation.source), (DEFAULT_TRANSLATION_MODEL, ModelSource::Default));
+        assert!(resolve_model(&conn, LlmFeature::Chat).is_err());
+
+        let models = vec![
+            model("llama3.2:3b", Some(2_000_000_000), r#"["completion"]"#),
+            model("llama3.1:70b", Some(40_000_000_000), r#"["completion","tools"]"#),
+            model("qwen2.5:7b", Some(4_700_000_000), r#"["completion"]"#),
+            model("nomic-embed-text", Some(270_000_000), r#"["embedding"]"#),
+        ];
+        model_registry_operations::replace_models(&mut conn, OLLAMA_PROVIDER_ID, &models).unwrap();
+
+        assert_eq!(resolve_model(&conn, LlmFeature::Classification).unwrap().model, "llama3.2:3b");
+        assert_eq!(resolve_model(&conn, LlmFeature::Chat).unwrap().model, "llama3.1:70b");
+        assert_eq!(resolve_model(&conn, LlmFeature::Embeddings).unwrap().model, "nomic-embed-text");
+
+        // A size cap keeps chat on a model that fits
+        let rule = RoutingRule {
+            feature: LlmFeature::Chat,
+            provider_id: OLLAMA_PROVIDER_ID.to_string(),
+            capability: "completion".to_string(),
+            prefer: SizePreference::Largest,
+            max_size_bytes: Some(8_000_000_000),
+            min_context_length: None,
+        };
+        set_rule(&conn, &rule).unwrap();
+        let chat = resolve_model(&conn, LlmFeature::Chat).unwrap();
+        assert_eq!((chat.model.as_str(), chat.source), ("qwen2.5:7b", ModelSource::Rule));
+        assert_eq!(chat.details.unwrap().context_length, Some(8192));
+        assert!(set_rule(&conn, &RoutingRule { capability: "telepathy".to_string(), ..rule }).is_err());
+
+        // An explicit choice or the older setting wins over the rule
+        preference_operations::set_preference_value(&conn, QUICK_CAPTURE_MODEL_PREFERENCE, "qwen2.5:7b", "capture").unwrap();
+        assert_eq!(resolve_model(&conn, LlmFeature::Classification).unwrap().source, ModelSource::Setting);
+        llm_provider_operations::set_feature_model(&conn, "chat", OLLAMA_PROVIDER_ID, "llama3.2:3b").unwrap();
+        assert_eq!(resolve_model(&conn, LlmFeature::Chat).unwrap().source, ModelSource::Selected);
+
+        // On a 16 GB machine without a GPU rules skip the 70b model and
+        // choosing it is refused with models that fit
+        assert_eq!(resolve_model(&conn, LlmFeature::Summarization).unwrap().model, "llama3.1:70b");
+        let profile = HardwareProfile {
+            total_memory_bytes: 16 * 1024 * 1024 * 1024,
+            available_memory_bytes: 8 * 1024 * 1024 * 1024,
+            cpu_count: 8,
+            gpus: Vec::new(),
+            unified_memory: false,
+            detected_at: String::new(),
+        };
+        hardware::save_profile(&conn, &profile).unwrap();
+        assert_eq!(resolve_model(&conn, LlmFeature::Summarization).unwrap().model, "qwen2.5:7b");
+        assert_eq!(resolve_model(&conn, LlmFeature::Chat).unwrap().fit.unwrap().level, FitLevel::Fits);
+
+        llm_provider_operations::set_feature_model(&conn, "chat", OLLAMA_PROVIDER_ID, "llama3.1:70b").unwrap();
+        let error = resolve_model(&conn, LlmFeature::Chat).unwrap_err().to_string();
+        assert!(error.contains("qwen2.5:7b, llama3.2:3b"), "{}", error);
+        let inspected = inspect_model(&conn, LlmFeature::Chat).unwrap();
+        assert_eq!(inspected.fit.unwrap().level, FitLevel::TooLarge);
+        let warnings = resource_warnings(&conn);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!((warnings[0].feature.as_str(), warnings[0].level), ("chat", FitLevel::TooLarge));
+    }
+}
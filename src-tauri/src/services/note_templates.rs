@@ -0,0 +1,266 @@
+//! Note templates and the daily note
+//!
+//! Note templates use the same `{{name}}` / `{{name | fallback}}`
+//! placeholders as mail templates. Every note made from a template gets
+//! the date variables: `{{date}}`, `{{weekday}}`, `{{long_date}}`,
+//! `{{time}}`, `{{year}}`, `{{month}}`, `{{month_name}}`, `{{day}}`,
+//! `{{week}}`, `{{yesterday}}` and `{{tomorrow}}`. The daily note also has
+//! `{{agenda}}` and `{{tasks}}`, today's events and due tasks, when
+//! `notes.daily_note` asks for them, and `{{availability}}`, the day's
+//! working hours, breaks and no-meeting blocks.
+//!
+//! The daily note is created the first time it is opened each day, from
+//! the template named in that setting (or a built-in layout) in its
+//! folder, and the same note is returned for the rest of the day.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, Duration};
+use chrono_tz::Tz;
+use regex::Regex;
+use rusqlite::{Connection, TransactionBehavior};
+use serde::{Deserialize, Serialize};
+
+use crate::database::models::Note;
+use crate::database::operations::{folder_operations, note_operations, note_template_operations};
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::{availability, daily_digest};
+use crate::services::gmail::campaign_service::render_template;
+use crate::services::i18n;
+use crate::services::settings;
+
+/// JSON encoded `DailyNoteSettings`
+pub const DAILY_NOTE_SETTING: &str = "notes.daily_note";
+/// Title of daily notes made from the built-in layout
+const DEFAULT_DAILY_TITLE: &str = "{{date}}";
+
+lazy_static::lazy_static! {
+    /// `{{name}}` without a fallback
+    static ref BARE_PLACEHOLDER: Regex = Regex::new(r"\{\{\s*([A-Za-z0-9_.]+)\s*\}\}").unwrap();
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DailyNoteSettings {
+    /// Name of the note template to start from; the built-in layout when unset
+    pub template: Option<String>,
+    /// Folder daily notes are created in
+    pub folder_id: Option<i32>,
+    /// Fill `{{agenda}}` and `{{tasks}}` from the calendar and task lists
+    pub include_agenda: bool,
+}
+
+pub fn load_daily_settings(conn: &Connection) -> anyhow::Result<DailyNoteSettings> {
+    let setting = settings::get_setting(conn, DAILY_NOTE_SETTING)?;
+    Ok(serde_json::from_value(setting.value).unwrap_or_default())
+}
+
+/// The built-in daily note layout in the active locale
+pub fn default_daily_template(include_agenda: bool) -> String {
+    let mut sections = vec!["# {{long_date}}".to_string()];
+    if include_agenda {
+        sections.push(format!(
+            "## {}\n_{{{{availability}}}}_\n\n{{{{agenda | {}}}}}",
+            i18n::t("daily-note-agenda-heading"),
+            i18n::t("daily-note-agenda-empty")
+        ));
+        sections.push(format!("## {}\n{{{{tasks | {}}}}}", i18n::t("daily-note-tasks-heading"), i18n::t("daily-note-tasks-empty")));
+    }
+    sections.push(format!("## {}\n", i18n::t("daily-note-notes-heading")));
+    sections.join("\n\n")
+}
+
+/// Variables describing `now`, in the user's zone (see `TimeService`)
+pub fn date_variables(now: DateTime<Tz>) -> HashMap<String, String> {
+    let locale = i18n::chrono_locale();
+    let day = |date: DateTime<Tz>| date.format("%Y-%m-%d").to_string();
+    HashMap::from([
+        ("date".to_string(), day(now)),
+        ("weekday".to_string(), now.format_localized("%A", locale).to_string()),
+        ("long_date".to_string(), now.format_localized(&i18n::t("note-long-date-format"), locale).to_string()),
+        ("time".to_string(), now.format("%H:%M").to_string()),
+        ("year".to_string(), now.year().to_string()),
+        ("month".to_string(), now.format("%m").to_string()),
+        ("month_name".to_string(), now.format_localized("%B", locale).to_string()),
+        ("day".to_string(), now.day().to_string()),
+        ("week".to_string(), now.iso_week().week().to_string()),
+        ("yesterday".to_string(), day(now - Duration::days(1))),
+        ("tomorrow".to_string(), day(now + Duration::days(1))),
+    ])
+}
+
+/// Render a note template. Known variables that are empty, like an empty
+/// agenda without a fallback, leave nothing behind; unknown ones fail.
+pub fn render(template: &str, variables: &HashMap<String, String>) -> Result<String> {
+    let template = BARE_PLACEHOLDER.replace_all(template, |caps: &regex::Captures| {
+        if variables.contains_key(&caps[1]) {
+            format!("{{{{{} | }}}}", &caps[1])
+        } else {
+            caps[0].to_string()
+        }
+    });
+    render_template(&template, variables, false).map_err(|message| LibreOllamaError::InvalidInput {
+        message,
+        field: Some("template".to_string()),
+    })
+}
+
+/// Folder to create a note in, dropping one that no longer exists
+fn existing_folder(conn: &Connection, folder_id: Option<i32>) -> Result<Option<i32>> {
+    match folder_id {
+        Some(id) if folder_operations::get_folder(conn, id)?.is_none() => {
+            eprintln!("⚠️ [NOTES] Folder {} no longer exists; creating the note at the top level", id);
+            Ok(None)
+        }
+        folder_id => Ok(folder_id),
+    }
+}
+
+/// Create a note from template `template_id`; `variables` add to or
+/// override the date variables
+pub fn create_from_template(
+    conn: &Connection,
+    template_id: i64,
+    user_id: &str,
+    folder_id: Option<i32>,
+    variables: HashMap<String, String>,
+    now: DateTime<Tz>,
+) -> Result<Note> {
+    let template = note_template_operations::get_template(conn, template_id)?.ok_or_else(|| LibreOllamaError::NotFound {
+        resource: format!("note template {}", template_id),
+    })?;
+    let mut all = date_variables(now);
+    all.extend(variables);
+    let title = render(&template.title, &all)?;
+    let content = render(&template.content, &all)?;
+    Ok(note_operations::create_note(conn, &title, &content, user_id, existing_folder(conn, folder_id)?)?)
+}
+
+#[derive(Debug, Clone)]
+pub struct DailyNote {
+    pub note: Note,
+    /// Local date, "YYYY-MM-DD"
+    pub date: String,
+    /// Whether the note was created by this call
+    pub created: bool,
+}
+
+/// The daily note for the local day of `now`, created the first time.
+/// The lookup and creation share a write transaction, so two windows
+/// opening the note at once get the same one.
+pub fn get_or_create_daily_note(conn: &mut Connection, user_id: &str, now: DateTime<Tz>) -> Result<DailyNote> {
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let daily = find_or_create_daily_note(&tx, user_id, now)?;
+    tx.commit()?;
+    Ok(daily)
+}
+
+fn find_or_create_daily_note(conn: &Connection, user_id: &str, now: DateTime<Tz>) -> Result<DailyNote> {
+    let date = now.format("%Y-%m-%d").to_string();
+    if let Some(note_id) = note_template_operations::daily_note_id(conn, &date)? {
+        if let Some(note) = note_operations::get_note(conn, note_id)? {
+            return Ok(DailyNote { note, date, created: false });
+        }
+    }
+
+    let daily = load_daily_settings(conn)?;
+    let (title, content) = match daily.template.as_deref().map(str::trim).filter(|name| !name.is_empty()) {
+        Some(name) => {
+            let template = note_template_operations::get_template_by_name(conn, name)?.ok_or_else(|| {
+                LibreOllamaError::InvalidInput {
+                    message: format!("The daily note template '{}' does not exist", name),
+                    field: Some(DAILY_NOTE_SETTING.to_string()),
+                }
+            })?;
+            (template.title, template.content)
+        }
+        None => (DEFAULT_DAILY_TITLE.to_string(), default_daily_template(daily.include_agenda)),
+    };
+
+    let mut variables = date_variables(now);
+    let (agenda, tasks) = if daily.include_agenda {
+        let (events, due_tasks) = daily_digest::agenda(conn, now)?;
+        (daily_digest::event_lines(&events, now.timezone()), daily_digest::task_lines(&due_tasks, now.date_naive()))
+    } else {
+        (String::new(), String::new())
+    };
+    variables.insert("agenda".to_string(), agenda);
+    variables.insert("tasks".to_string(), tasks);
+    variables.insert("availability".to_string(), availability::day_summary_at(conn, &now)?);
+
+    let note = note_operations::create_note(
+        conn,
+        &render(&title, &variables)?,
+        &render(&content, &variables)?,
+        user_id,
+        existing_folder(conn, daily.folder_id)?,
+    )?;
+    note_template_operations::set_daily_note(conn, &date, note.id)?;
+    Ok(DailyNote { note, date, created: true })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use crate::database::schema::run_migrations;
+
+    fn friday() -> DateTime<Tz> {
+        chrono_tz::Europe::Berlin.with_ymd_and_hms(2026, 10, 16, 8, 30, 0).unwrap()
+    }
+
+    #[test]
+    fn test_date_variables() {
+        let variables = date_variables(friday());
+        let rendered = render("{{date}} {{weekday}} w{{week}} {{yesterday}}..{{tomorrow}} {{time}}", &variables).unwrap();
+        assert_eq!(rendered, "2026-10-16 Friday w42 2026-10-15..2026-10-17 08:30");
+    }
+
+    #[test]
+    fn test_render_empty_and_unknown_variables() {
+        let mut variables = date_variables(friday());
+        variables.insert("agenda".to_string(), String::new());
+        assert_eq!(render("A:{{agenda}}|{{agenda | none}}", &variables).unwrap(), "A:|none");
+        assert!(render("{{project}}", &variables).is_err());
+        assert_eq!(render("{{project | Inbox}}", &variables).unwrap(), "Inbox");
+    }
+
+    #[test]
+    fn test_daily_note_is_created_once_per_day() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        note_template_operations::create_template(&conn, "Daily", "Journal {{date}}", "# {{weekday}}\n{{tasks}}").unwrap();
+        let value = serde_json::json!({ "template": "daily", "include_agenda": true });
+        settings::set_setting(&conn, DAILY_NOTE_SETTING, &value).unwrap();
+
+        let first = get_or_create_daily_note(&mut conn, "user", friday()).unwrap();
+        assert!(first.created);
+        assert_eq!(first.date, "2026-10-16");
+        assert_eq!(first.note.title, "Journal 2026-10-16");
+        assert_eq!(first.note.content, "# Friday\n");
+
+        let again = get_or_create_daily_note(&mut conn, "user", friday() + Duration::hours(6)).unwrap();
+        assert!(!again.created);
+        assert_eq!(again.note.id, first.note.id);
+
+        note_operations::delete_note(&conn, first.note.id).unwrap();
+        let recreated = get_or_create_daily_note(&mut conn, "user", friday()).unwrap();
+        assert!(recreated.created);
+        assert_ne!(recreated.note.id, first.note.id);
+
+        let tomorrow = get_or_create_daily_note(&mut conn, "user", friday() + Duration::days(1)).unwrap();
+        assert!(tomorrow.created);
+        assert_eq!(tomorrow.note.title, "Journal 2026-10-17");
+    }
+
+    #[test]
+    fn test_missing_daily_template_is_reported() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        settings::set_setting(&conn, DAILY_NOTE_SETTING, &serde_json::json!({ "template": "Gone" })).unwrap();
+        assert!(matches!(
+            get_or_create_daily_note(&mut conn, "user", friday()),
+            Err(LibreOllamaError::InvalidInput { .. })
+        ));
+    }
+}
@@ -0,0 +1,433 @@
+//! New-mail notifications
+//!
+//! Decides how each newly arrived message is announced. During quiet hours
+//! nothing is announced, except mail from a VIP when the VIP rules bypass
+//! quiet hours. VIP mail can carry its own sound and always shows a
+//! preview, even when previews are otherwise hidden.
+//!
+//! VIPs belong to a workspace, or to none and so to every workspace; the
+//! active workspace's VIPs and the shared ones count. The VIP rules are the
+//! `notifications.vip` setting, and a workspace can carry its own rules,
+//! which apply while it is active.
+//!
+//! While the app is locked, alerts never carry a preview.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use chrono::NaiveTime;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::database::operations::vip_operations::{self, VipSender};
+use crate::database::operations::workspace_operations;
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::gmail::api_service::ProcessedGmailMessage;
+use crate::services::time_service::TimeService;
+use crate::services::{settings, workspaces};
+
+/// JSON encoded `QuietHours`
+pub const QUIET_HOURS_SETTING: &str = "notifications.quiet_hours";
+/// JSON encoded global `VipRules`
+pub const VIP_RULES_SETTING: &str = "notifications.vip";
+/// Whether notifications show the sender, subject and snippet
+pub const MAIL_PREVIEW_SETTING: &str = "notifications.show_preview";
+
+/// Audio files a VIP sound may be
+const SOUND_EXTENSIONS: [&str; 7] = ["wav", "mp3", "ogg", "oga", "flac", "m4a", "aac"];
+/// Largest sound file accepted
+const MAX_SOUND_BYTES: u64 = 5 * 1024 * 1024;
+/// Longest name of a sound the frontend knows
+const MAX_SOUND_NAME_LEN: usize = 64;
+
+fn parse_clock(value: &str, field: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(value, "%H:%M").map_err(|_| LibreOllamaError::InvalidInput {
+        message: format!("{} must be HH:MM, got {}", field, value),
+        field: Some(field.to_string()),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub enabled: bool,
+    /// Local time quiet hours begin, "HH:MM"
+    pub start: String,
+    /// Local time they end, "HH:MM"; before `start` when they span midnight
+    pub end: String,
+}
+
+impl Default for QuietHours {
+    fn default() -> Self {
+        Self { enabled: false, start: "22:00".to_string(), end: "07:00".to_string() }
+    }
+}
+
+impl QuietHours {
+    pub fn validate(&self) -> Result<()> {
+        parse_clock(&self.start, "start")?;
+        parse_clock(&self.end, "end")?;
+        Ok(())
+    }
+
+    /// Whether a local time of day falls in quiet hours
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        let (Ok(start), Ok(end)) = (parse_clock(&self.start, "start"), parse_clock(&self.end, "end")) else {
+            return false;
+        };
+        if !self.enabled || start == end {
+            return false;
+        }
+        if start < end {
+            start <= time && time < end
+        } else {
+            time >= start || time < end
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VipRules {
+    /// VIP mail is announced during quiet hours
+    pub bypass_quiet_hours: bool,
+    /// Sound played for VIP mail: a sound file, or a name the frontend
+    /// knows; the usual sound when absent
+    pub sound: Option<String>,
+    /// VIP mail shows a preview even when previews are hidden
+    pub always_show_preview: bool,
+}
+
+impl Default for VipRules {
+    fn default() -> Self {
+        Self { bypass_quiet_hours: true, sound: None, always_show_preview: true }
+    }
+}
+
+/// Whether a VIP sound names a file rather than a sound the frontend knows
+pub fn is_sound_file(sound: &str) -> bool {
+    sound.contains('/') || sound.contains('\\')
+}
+
+impl VipRules {
+    pub fn validate(&self) -> Result<()> {
+        let Some(sound) = &self.sound else {
+            return Ok(());
+        };
+        if sound.trim().is_empty() {
+            return Err(LibreOllamaError::InvalidInput {
+                message: "Leave the sound out to use the usual one".to_string(),
+                field: Some("sound".to_string()),
+            });
+        }
+        let invalid = |message: String| LibreOllamaError::InvalidInput { message, field: Some("sound".to_string()) };
+        if !is_sound_file(sound) {
+            let known_name = sound.len() <= MAX_SOUND_NAME_LEN
+                && sound.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !known_name {
+                return Err(invalid(format!("{} is not a sound name", sound)));
+            }
+            return Ok(());
+        }
+        let path = Path::new(sound);
+        let audio = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| SOUND_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str()));
+        if !path.is_absolute() || !audio {
+            return Err(invalid(format!("Sound file {} is not an audio file given by its full path", sound)));
+        }
+        match std::fs::metadata(path) {
+            Ok(metadata) if metadata.is_file() && metadata.len() <= MAX_SOUND_BYTES => Ok(()),
+            Ok(metadata) if metadata.is_file() => Err(invalid(format!("Sound file {} is too large", sound))),
+            _ => Err(invalid(format!("Sound file {} does not exist", sound))),
+        }
+    }
+}
+
+/// How one message is announced
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MailAlert {
+    pub message_id: String,
+    pub vip: bool,
+    /// Sound to play; the usual one when absent
+    pub sound: Option<String>,
+    pub show_preview: bool,
+    /// Sender, subject and snippet; left out when no preview is shown
+    pub from: Option<String>,
+    pub subject: Option<String>,
+    pub snippet: Option<String>,
+}
+
+impl MailAlert {
+    /// An alert under no rules at all: the usual sound, with a preview
+    pub fn plain(notice: &MailNotice) -> Self {
+        Self {
+            message_id: notice.message_id.clone(),
+            vip: false,
+            sound: None,
+            show_preview: true,
+            from: Some(notice.sender()),
+            subject: notice.subject.clone(),
+            snippet: notice.snippet.clone(),
+        }
+    }
+
+    /// The alert with its sender, subject and snippet left out, as while
+    /// the app is locked
+    pub fn without_preview(self) -> Self {
+        Self { show_preview: false, from: None, subject: None, snippet: None, ..self }
+    }
+}
+
+/// The parts of a message an alert is made from
+#[derive(Debug, Clone)]
+pub struct MailNotice {
+    pub message_id: String,
+    pub from_email: String,
+    pub from_name: Option<String>,
+    pub subject: Option<String>,
+    pub snippet: Option<String>,
+}
+
+impl MailNotice {
+    /// The sender's name, or their address when the name is unknown
+    pub fn sender(&self) -> String {
+        self.from_name.clone().unwrap_or_else(|| self.from_email.clone())
+    }
+}
+
+impl From<&ProcessedGmailMessage> for MailNotice {
+    fn from(message: &ProcessedGmailMessage) -> Self {
+        Self {
+            message_id: message.id.clone(),
+            from_email: message.parsed_content.from.email.clone(),
+            from_name: message.parsed_content.from.name.clone(),
+            subject: message.parsed_content.subject.clone(),
+            snippet: message.snippet.clone(),
+        }
+    }
+}
+
+/// Lowercase address, or "@domain" for a whole domain
+pub fn normalize_address(address: &str) -> Result<String> {
+    let address = address.trim().to_lowercase();
+    let valid = match address.split_once('@') {
+        Some((local, domain)) => domain.contains('.') && !domain.contains('@') && (local.is_empty() || !local.contains(' ')),
+        None => false,
+    };
+    if !valid {
+        return Err(LibreOllamaError::InvalidInput {
+            message: format!("{} is neither an email address nor an @domain", address),
+            field: Some("address".to_string()),
+        });
+    }
+    Ok(address)
+}
+
+pub fn is_vip(vips: &[String], email: &str) -> bool {
+    let email = email.trim().to_lowercase();
+    let domain = email.rsplit_once('@').map(|(_, domain)| format!("@{}", domain));
+    vips.iter().any(|vip| *vip == email || Some(vip) == domain.as_ref())
+}
+
+/// What is in effect when mail arrives
+#[derive(Debug, Clone)]
+pub struct AlertPolicy {
+    pub vips: Vec<String>,
+    pub rules: VipRules,
+    pub quiet_now: bool,
+    pub show_preview: bool,
+}
+
+impl AlertPolicy {
+    /// The alert for a message, or `None` when it stays silent
+    pub fn alert(&self, notice: &MailNotice) -> Option<MailAlert> {
+        let vip = is_vip(&self.vips, &notice.from_email);
+        if self.quiet_now && !(vip && self.rules.bypass_quiet_hours) {
+            return None;
+        }
+        let show_preview = self.show_preview || (vip && self.rules.always_show_preview);
+        let preview = |value: &Option<String>| if show_preview { value.clone() } else { None };
+        Some(MailAlert {
+            message_id: notice.message_id.clone(),
+            vip,
+            sound: if vip { self.rules.sound.clone() } else { None },
+            show_preview,
+            from: preview(&Some(notice.sender())),
+            subject: preview(&notice.subject),
+            snippet: preview(&notice.snippet),
+        })
+    }
+}
+
+fn require_workspace(conn: &Connection, workspace_id: i64) -> Result<()> {
+    if workspace_operations::get_workspace(conn, workspace_id)?.is_none() {
+        return Err(LibreOllamaError::NotFound { resource: format!("Workspace {}", workspace_id) });
+    }
+    Ok(())
+}
+
+/// Add a VIP to a workspace's list, or with no workspace to every one
+pub fn add_vip(conn: &Connection, workspace_id: Option<i64>, address: &str, name: Option<&str>) -> Result<VipSender> {
+    if let Some(workspace_id) = workspace_id {
+        require_workspace(conn, workspace_id)?;
+    }
+    let address = normalize_address(address)?;
+    let name = name.map(str::trim).filter(|name| !name.is_empty());
+    Ok(vip_operations::add_vip(conn, workspace_id, &address, name)?)
+}
+
+pub fn global_rules(conn: &Connection) -> anyhow::Result<VipRules> {
+    Ok(serde_json::from_value(settings::get_setting(conn, VIP_RULES_SETTING)?.value).unwrap_or_default())
+}
+
+/// The rules a workspace overrides the global ones with, if any
+pub fn workspace_rules(conn: &Connection, workspace_id: i64) -> anyhow::Result<Option<VipRules>> {
+    Ok(vip_operations::get_workspace_rules(conn, workspace_id)?.and_then(|json| serde_json::from_str(&json).ok()))
+}
+
+pub fn save_workspace_rules(conn: &Connection, workspace_id: i64, rules: Option<&VipRules>) -> Result<()> {
+    require_workspace(conn, workspace_id)?;
+    let json = match rules {
+        Some(rules) => {
+            rules.validate()?;
+            Some(serde_json::to_string(rules)?)
+        }
+        None => None,
+    };
+    Ok(vip_operations::set_workspace_rules(conn, workspace_id, json.as_deref())?)
+}
+
+/// The rules in effect, with the workspace they come from when the active
+/// workspace overrides the global ones
+pub fn effective_rules(conn: &Connection) -> Result<(VipRules, Option<i64>)> {
+    if let Some(workspace) = workspaces::active_workspace(conn)? {
+        if let Some(rules) = workspace_rules(conn, workspace.id)? {
+            return Ok((rules, Some(workspace.id)));
+        }
+    }
+    Ok((global_rules(conn)?, None))
+}
+
+pub fn load_policy(conn: &Connection) -> Result<AlertPolicy> {
+    let active = workspaces::active_workspace(conn)?.map(|workspace| workspace.id);
+    let quiet_hours: QuietHours =
+        serde_json::from_value(settings::get_setting(conn, QUIET_HOURS_SETTING)?.value).unwrap_or_default();
+    let local_time = TimeService::load(conn)?.now().time();
+    Ok(AlertPolicy {
+        vips: vip_operations::vip_addresses(conn, active)?,
+        rules: effective_rules(conn)?.0,
+        quiet_now: quiet_hours.contains(local_time),
+        show_preview: settings::get_bool(conn, MAIL_PREVIEW_SETTING)?,
+    })
+}
+
+/// Turns new messages into alerts, managed as Tauri state
+pub struct NotificationService {
+    db_manager: Arc<DatabaseManager>,
+}
+
+impl NotificationService {
+    pub fn new(db_manager: Arc<DatabaseManager>) -> Self {
+        Self { db_manager }
+    }
+
+    /// Alerts for the messages that should be announced now
+    pub fn alerts(&self, notices: &[MailNotice]) -> Result<Vec<MailAlert>> {
+        let policy = load_policy(&self.db_manager.get_connection()?)?;
+        Ok(notices.iter().filter_map(|notice| policy.alert(notice)).collect())
+    }
+
+    /// The alert mail from a VIP would raise now, ignoring quiet hours so
+    /// the sound and preview can be tried at any time
+    pub fn test_alert(&self, notice: &MailNotice) -> Result<MailAlert> {
+        let mut policy = load_policy(&self.db_manager.get_connection()?)?;
+        policy.quiet_now = false;
+        policy.vips.push(notice.from_email.trim().to_lowercase());
+        policy.alert(notice).ok_or_else(|| LibreOllamaError::Internal {
+            message: "A VIP test alert was left silent".to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notice(from: &str) -> MailNotice {
+        MailNotice {
+            message_id: "m1".to_string(),
+            from_email: from.to_string(),
+            from_name: None,
+            subject: Some("Quarterly numbers".to_string()),
+            snippet: Some("See attached".to_string()),
+        }
+    }
+
+    fn clock(value: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(value, "%H:%M").unwrap()
+    }
+
+    #[test]
+    fn quiet_hours_span_midnight() {
+        let quiet = QuietHours { enabled: true, ..QuietHours::default() };
+        assert!(quiet.contains(clock("23:30")));
+        assert!(quiet.contains(clock("06:59")));
+        assert!(!quiet.contains(clock("07:00")));
+        assert!(!QuietHours::default().contains(clock("23:30")));
+        let afternoon = QuietHours { enabled: true, start: "13:00".to_string(), end: "14:00".to_string() };
+        assert!(afternoon.contains(clock("13:30")) && !afternoon.contains(clock("14:30")));
+    }
+
+    #[test]
+    fn vips_bypass_quiet_hours_with_their_own_sound_and_preview() {
+        let policy = AlertPolicy {
+            vips: vec!["boss@example.com".to_string(), "@family.org".to_string()],
+            rules: VipRules { sound: Some("chime".to_string()), ..VipRules::default() },
+            quiet_now: true,
+            show_preview: false,
+        };
+        assert!(policy.alert(&notice("someone@example.com")).is_none());
+
+        let alert = policy.alert(&notice("Boss@Example.com")).unwrap();
+        assert!(alert.vip && alert.show_preview);
+        assert_eq!(alert.sound.as_deref(), Some("chime"));
+        assert_eq!(alert.subject.as_deref(), Some("Quarterly numbers"));
+        assert!(policy.alert(&notice("mum@family.org")).is_some());
+
+        let daytime = AlertPolicy { quiet_now: false, ..policy };
+        let alert = daytime.alert(&notice("someone@example.com")).unwrap();
+        assert!(!alert.vip && !alert.show_preview);
+        assert!(alert.sound.is_none() && alert.subject.is_none());
+    }
+
+    #[test]
+    fn sounds_are_known_names_or_audio_files() {
+        let rules = |sound: &str| VipRules { sound: Some(sound.to_string()), ..VipRules::default() };
+        assert!(rules("chime").validate().is_ok());
+        assert!(rules("chime; rm").validate().is_err());
+
+        let dir = std::env::temp_dir().join(format!("vip-sound-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let wav = dir.join("ding.wav");
+        std::fs::write(&wav, b"RIFF").unwrap();
+        let text = dir.join("notes.txt");
+        std::fs::write(&text, b"secret").unwrap();
+        assert!(rules(&wav.to_string_lossy()).validate().is_ok());
+        assert!(rules(&text.to_string_lossy()).validate().is_err());
+        assert!(rules(&dir.join("missing.wav").to_string_lossy()).validate().is_err());
+        assert!(rules("sounds/ding.wav").validate().is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let alert = MailAlert::plain(&notice("boss@example.com")).without_preview();
+        assert!(!alert.show_preview && alert.from.is_none() && alert.snippet.is_none());
+    }
+
+    #[test]
+    fn normalizes_addresses_and_domains() {
+        assert_eq!(normalize_address(" Boss@Example.com ").unwrap(), "boss@example.com");
+        assert_eq!(normalize_address("@Family.org").unwrap(), "@family.org");
+        assert!(normalize_address("not an address").is_err());
+        assert!(normalize_address("a@localhost").is_err());
+    }
+}
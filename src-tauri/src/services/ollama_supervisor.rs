@@ -0,0 +1,486 @@
+//! Local Ollama server supervision
+//!
+//! Detects whether an Ollama server answers on the local port and, when it
+//! does not, can launch `ollama serve` as a child process using the model
+//! directory from settings. A monitor loop restarts a child that exited or
+//! stopped answering, backing off between attempts and giving up after
+//! `MAX_RESTARTS` until it is started again by hand. A server the user
+//! started themselves is reported but never stopped or restarted.
+//!
+//! Launching on startup is opt-in through the `ollama.auto_start` setting.
+
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::settings;
+use crate::utils::http_client::http_client;
+
+/// Address the app expects Ollama on
+pub const OLLAMA_HOST: &str = "127.0.0.1:11434";
+const VERSION_URL: &str = "http://127.0.0.1:11434/api/version";
+
+/// How often the monitor checks the server
+const MONITOR_TICK: Duration = Duration::from_secs(15);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+/// How long a launched server gets to start answering
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(20);
+/// Failed health checks in a row before a running child is restarted
+const FAILED_CHECKS_BEFORE_RESTART: u32 = 2;
+/// Automatic restarts before the monitor gives up
+const MAX_RESTARTS: u32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OllamaServerState {
+    NotInstalled,
+    Stopped,
+    Starting,
+    Running,
+    /// The launched server died and could not be brought back
+    Crashed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SupervisorStatus {
+    pub state: OllamaServerState,
+    /// The app launched the running server and will stop it on exit
+    pub managed: bool,
+    pub pid: Option<u32>,
+    pub version: Option<String>,
+    pub binary: Option<String>,
+    pub models_dir: Option<String>,
+    pub restarts: u32,
+    pub last_error: Option<String>,
+}
+
+struct Inner {
+    child: Option<Child>,
+    status: SupervisorStatus,
+    failed_checks: u32,
+    /// Set on app exit; no server is launched after it
+    shut_down: bool,
+}
+
+#[derive(Deserialize)]
+struct VersionResponse {
+    version: String,
+}
+
+fn on_path(binary: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join(binary))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Locate the `ollama` executable: the configured path, then PATH, then
+/// the default install locations
+pub fn find_binary(configured: Option<&Path>) -> Option<PathBuf> {
+    if let Some(path) = configured {
+        return path.is_file().then(|| path.to_path_buf());
+    }
+    if cfg!(target_os = "windows") {
+        on_path("ollama.exe").or_else(|| {
+            let local = std::env::var_os("LOCALAPPDATA")?;
+            let path = PathBuf::from(local).join("Programs").join("Ollama").join("ollama.exe");
+            path.is_file().then_some(path)
+        })
+    } else {
+        on_path("ollama").or_else(|| {
+            let bundled = Path::new("/Applications/Ollama.app/Contents/Resources/ollama");
+            (cfg!(target_os = "macos") && bundled.is_file()).then(|| bundled.to_path_buf())
+        })
+    }
+}
+
+fn configured_path(conn: &Connection, key: &str) -> Option<PathBuf> {
+    settings::get_setting(conn, key)
+        .ok()
+        .and_then(|setting| setting.value.as_str().map(str::to_string))
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+/// The executable to launch, honouring the `ollama.binary_path` setting
+pub fn configured_binary(conn: &Connection) -> Option<PathBuf> {
+    find_binary(configured_path(conn, settings::OLLAMA_BINARY_SETTING).as_deref())
+}
+
+/// Version of the server answering on the local port, if any
+pub async fn probe() -> Option<String> {
+    let response = http_client().get(VERSION_URL).timeout(PROBE_TIMEOUT).send().await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    response.json::<VersionResponse>().await.ok().map(|body| body.version)
+}
+
+/// Starts, watches and stops the local Ollama server, managed as Tauri state
+///
+/// The process and its status sit behind a plain mutex that is never held
+/// across an await, so status reads and shutdown never wait on a launch.
+/// Launches themselves are serialized by a separate async lock.
+#[derive(Clone)]
+pub struct OllamaSupervisor {
+    db_manager: Arc<DatabaseManager>,
+    inner: Arc<Mutex<Inner>>,
+    /// Held while a server is launched and polled until it answers
+    launch: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl OllamaSupervisor {
+    pub fn new(db_manager: Arc<DatabaseManager>) -> Self {
+        Self {
+            db_manager,
+            inner: Arc::new(Mutex::new(Inner {
+                child: None,
+                status: SupervisorStatus {
+                    state: OllamaServerState::Stopped,
+                    managed: false,
+                    pid: None,
+                    version: None,
+                    binary: None,
+                    models_dir: None,
+                    restarts: 0,
+                    last_error: None,
+                },
+                failed_checks: 0,
+                shut_down: false,
+            })),
+            launch: Arc::new(tokio::sync::Mutex::new(())),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Current state, refreshed with a probe of the server
+    pub async fn status(&self) -> SupervisorStatus {
+        let version = probe().await;
+        let mut inner = self.lock();
+        Self::reap_exited(&mut inner);
+        inner.status.version = version.clone();
+        if version.is_some() {
+            inner.status.state = OllamaServerState::Running;
+        } else if inner.status.state == OllamaServerState::Running {
+            inner.status.state = OllamaServerState::Stopped;
+        }
+        inner.status.clone()
+    }
+
+    /// Pid of the server the app launched, if it is still running
+    pub async fn managed_pid(&self) -> Option<u32> {
+        let mut inner = self.lock();
+        Self::reap_exited(&mut inner);
+        inner.child.as_ref().map(Child::id)
+    }
+
+    /// Make sure a server is running, launching one if nothing answers
+    pub async fn start(&self) -> Result<SupervisorStatus> {
+        let _launch = self.launch.lock().await;
+        self.lock().status.restarts = 0;
+        self.ensure_running().await?;
+        Ok(self.lock().status.clone())
+    }
+
+    /// Stop the server the app launched; one started elsewhere is left alone
+    pub async fn stop(&self) -> Result<SupervisorStatus> {
+        let _launch = self.launch.lock().await;
+        let launched = {
+            let mut inner = self.lock();
+            Self::reap_exited(&mut inner);
+            inner.child.is_some()
+        };
+        if !launched {
+            if let Some(version) = probe().await {
+                let mut inner = self.lock();
+                inner.status.version = Some(version);
+                inner.status.state = OllamaServerState::Running;
+                return Err(LibreOllamaError::PermissionDenied {
+                    message: "Ollama was not started by LibreOllama; stop it from where it was started".to_string(),
+                });
+            }
+        }
+        let mut inner = self.lock();
+        Self::kill_child(&mut inner);
+        inner.status.state = OllamaServerState::Stopped;
+        inner.status.version = None;
+        Ok(inner.status.clone())
+    }
+
+    /// Launch on startup when the user opted in, then check health for as
+    /// long as the app runs
+    pub fn start_monitoring(&self) {
+        let supervisor = self.clone();
+        tauri::async_runtime::spawn(async move {
+            if supervisor.auto_start_enabled() {
+                let _launch = supervisor.launch.lock().await;
+                if let Err(e) = supervisor.ensure_running().await {
+                    eprintln!("⚠️ [OLLAMA] Could not start Ollama: {}", e);
+                }
+            }
+            loop {
+                tokio::time::sleep(MONITOR_TICK).await;
+                supervisor.check_health().await;
+            }
+        });
+    }
+
+    /// Kill the launched server and refuse further launches; called when
+    /// the app exits
+    pub fn shutdown(&self) {
+        let mut inner = self.lock();
+        inner.shut_down = true;
+        Self::kill_child(&mut inner);
+    }
+
+    fn auto_start_enabled(&self) -> bool {
+        self.db_manager
+            .get_connection()
+            .and_then(|conn| settings::get_bool(&conn, settings::OLLAMA_AUTO_START_SETTING))
+            .unwrap_or(false)
+    }
+
+    async fn check_health(&self) {
+        // A launch in progress is already watching the server
+        let Ok(_launch) = self.launch.try_lock() else {
+            return;
+        };
+        let version = probe().await;
+        let backoff = {
+            let mut inner = self.lock();
+            if inner.shut_down {
+                return;
+            }
+            let exited = Self::reap_exited(&mut inner);
+            inner.status.version = version.clone();
+
+            if version.is_some() {
+                inner.failed_checks = 0;
+                inner.status.state = OllamaServerState::Running;
+                return;
+            }
+            if inner.child.is_some() {
+                inner.failed_checks += 1;
+                if inner.failed_checks < FAILED_CHECKS_BEFORE_RESTART {
+                    return;
+                }
+            } else if !exited {
+                // Nothing of ours to restart; a server started elsewhere went away
+                if inner.status.state == OllamaServerState::Running {
+                    inner.status.state = OllamaServerState::Stopped;
+                }
+                return;
+            }
+
+            if inner.status.restarts >= MAX_RESTARTS {
+                Self::kill_child(&mut inner);
+                inner.status.state = OllamaServerState::Crashed;
+                inner.status.last_error = Some(format!("Ollama stopped responding {} times; giving up", MAX_RESTARTS));
+                return;
+            }
+
+            inner.status.restarts += 1;
+            Self::kill_child(&mut inner);
+            Duration::from_secs(2u64.pow(inner.status.restarts))
+        };
+
+        eprintln!("⚠️ [OLLAMA] Server is not responding, restarting in {:?}", backoff);
+        tokio::time::sleep(backoff).await;
+        if let Err(e) = self.ensure_running().await {
+            eprintln!("⚠️ [OLLAMA] Restart failed: {}", e);
+        }
+    }
+
+    /// Launch a server unless one answers. Callers hold `launch`.
+    async fn ensure_running(&self) -> Result<()> {
+        if let Some(version) = probe().await {
+            let mut inner = self.lock();
+            inner.status.version = Some(version);
+            inner.status.state = OllamaServerState::Running;
+            inner.status.managed = inner.child.is_some();
+            return Ok(());
+        }
+
+        let (binary, models_dir) = {
+            let conn = self
+                .db_manager
+                .get_connection()
+                .map_err(|e| LibreOllamaError::DatabaseConnection { message: e.to_string() })?;
+            (configured_binary(&conn), configured_path(&conn, settings::OLLAMA_MODELS_DIR_SETTING))
+        };
+
+        {
+            let mut inner = self.lock();
+            if inner.shut_down {
+                return Err(LibreOllamaError::NotSupported { operation: "launching Ollama while LibreOllama shuts down".to_string() });
+            }
+            Self::kill_child(&mut inner);
+            let Some(binary) = binary else {
+                inner.status.state = OllamaServerState::NotInstalled;
+                inner.status.last_error = Some("Ollama is not installed or not on PATH".to_string());
+                return Err(LibreOllamaError::Configuration {
+                    message: "Ollama is not installed. Get it from https://ollama.com/download".to_string(),
+                    config_key: Some(settings::OLLAMA_BINARY_SETTING.to_string()),
+                });
+            };
+
+            let mut command = Command::new(&binary);
+            command
+                .arg("serve")
+                .env("OLLAMA_HOST", OLLAMA_HOST)
+                .env("OLLAMA_ORIGINS", "*")
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null());
+            if let Some(dir) = &models_dir {
+                command.env("OLLAMA_MODELS", dir);
+            }
+            let child = command.spawn().map_err(|e| {
+                let message = format!("Failed to launch {}: {}", binary.display(), e);
+                inner.status.state = OllamaServerState::Crashed;
+                inner.status.last_error = Some(message.clone());
+                LibreOllamaError::FileSystem { message, path: Some(binary.display().to_string()) }
+            })?;
+
+            println!("🦙 [OLLAMA] Launched {} (pid {})", binary.display(), child.id());
+            inner.status.pid = Some(child.id());
+            inner.status.binary = Some(binary.display().to_string());
+            inner.status.models_dir = models_dir.map(|dir| dir.display().to_string());
+            inner.status.state = OllamaServerState::Starting;
+            inner.child = Some(child);
+            inner.failed_checks = 0;
+        }
+
+        let deadline = tokio::time::Instant::now() + STARTUP_TIMEOUT;
+        while tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            {
+                let mut inner = self.lock();
+                if inner.shut_down || Self::reap_exited(&mut inner) {
+                    break;
+                }
+            }
+            if let Some(version) = probe().await {
+                let mut inner = self.lock();
+                inner.status.version = Some(version);
+                inner.status.state = OllamaServerState::Running;
+                inner.status.managed = true;
+                inner.status.last_error = None;
+                return Ok(());
+            }
+        }
+
+        let mut inner = self.lock();
+        Self::kill_child(&mut inner);
+        inner.status.state = OllamaServerState::Crashed;
+        inner.status.last_error = Some("Ollama did not start answering in time".to_string());
+        Err(LibreOllamaError::Timeout {
+            operation: "starting Ollama".to_string(),
+            duration_ms: Some(STARTUP_TIMEOUT.as_millis() as u64),
+        })
+    }
+
+    /// Forget a child that has exited; true when one had
+    fn reap_exited(inner: &mut Inner) -> bool {
+        let exited = match inner.child.as_mut().map(Child::try_wait) {
+            Some(Ok(Some(exit))) => {
+                inner.status.last_error = Some(format!("Ollama exited with {}", exit));
+                true
+            }
+            Some(Err(e)) => {
+                inner.status.last_error = Some(format!("Failed to check the Ollama process: {}", e));
+                true
+            }
+            _ => false,
+        };
+        if exited {
+            inner.child = None;
+            inner.status.pid = None;
+            inner.status.managed = false;
+            inner.status.state = OllamaServerState::Crashed;
+        }
+        exited
+    }
+
+    fn kill_child(inner: &mut Inner) {
+        if let Some(mut child) = inner.child.take() {
+            if let Err(e) = child.kill() {
+                eprintln!("⚠️ [OLLAMA] Failed to stop pid {}: {}", child.id(), e);
+            }
+            let _ = child.wait();
+        }
+        inner.status.pid = None;
+        inner.status.managed = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn supervisor() -> (OllamaSupervisor, PathBuf) {
+        let db_path = std::env::temp_dir().join(format!("libreollama_ollama_{}.db", uuid::Uuid::new_v4()));
+        let db_manager = Arc::new(DatabaseManager::open_at(db_path.clone()).unwrap());
+        db_manager.run_migrations().await.unwrap();
+        (OllamaSupervisor::new(db_manager), db_path)
+    }
+
+    fn cleanup(supervisor: OllamaSupervisor, db_path: PathBuf) {
+        supervisor.db_manager.close();
+        let _ = std::fs::remove_file(db_path);
+    }
+
+    #[tokio::test]
+    async fn test_auto_start_is_opt_in() {
+        let (supervisor, db_path) = supervisor().await;
+        assert!(!supervisor.auto_start_enabled());
+
+        let conn = supervisor.db_manager.get_connection().unwrap();
+        settings::set_setting(&conn, settings::OLLAMA_AUTO_START_SETTING, &serde_json::json!(true)).unwrap();
+        drop(conn);
+        assert!(supervisor.auto_start_enabled());
+        cleanup(supervisor, db_path);
+    }
+
+    #[tokio::test]
+    async fn test_status_does_not_wait_for_a_launch() {
+        let (supervisor, db_path) = supervisor().await;
+        let launch = supervisor.launch.lock().await;
+        let status = tokio::time::timeout(Duration::from_secs(5), supervisor.status()).await;
+        assert!(status.is_ok());
+        drop(launch);
+        cleanup(supervisor, db_path);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_shutdown_kills_the_child_even_during_a_launch() {
+        let (supervisor, db_path) = supervisor().await;
+        let child = Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = child.id();
+        supervisor.lock().child = Some(child);
+
+        let launch = supervisor.launch.lock().await;
+        supervisor.shutdown();
+        assert!(supervisor.lock().child.is_none());
+        assert!(!Path::new(&format!("/proc/{}", pid)).exists());
+        drop(launch);
+
+        // Nothing is launched once the app is exiting
+        let started = supervisor.start().await;
+        if probe().await.is_none() {
+            assert!(started.is_err());
+        }
+        assert!(supervisor.lock().child.is_none());
+        cleanup(supervisor, db_path);
+    }
+}
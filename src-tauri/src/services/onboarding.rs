@@ -0,0 +1,307 @@
+//! First-run onboarding
+//!
+//! Walks a new user through getting Ollama running, pulling a model and
+//! (optionally) connecting Google. Progress lives in `user_onboarding`;
+//! prerequisites are checked live on every call, so the UI can show what
+//! is missing and a hint for fixing it. Steps whose prerequisite is not met
+//! cannot be completed, except the optional Google step.
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::commands::ollama::installed_models;
+use crate::config::get_config_manager;
+use crate::database::operations::onboarding_operations::{self, OnboardingStatus};
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::ollama_supervisor;
+
+/// Step recorded as current once onboarding is over
+const FINISHED_STEP: &str = "finished";
+/// Step recorded as current when the user skipped onboarding
+const SKIPPED_STEP: &str = "skipped";
+
+pub struct StepDefinition {
+    pub id: &'static str,
+    pub title: &'static str,
+    /// Optional steps can be completed even when their prerequisite fails
+    pub optional: bool,
+}
+
+pub static STEPS: &[StepDefinition] = &[
+    StepDefinition { id: "welcome", title: "Welcome", optional: false },
+    StepDefinition { id: "ollama", title: "Set up Ollama", optional: false },
+    StepDefinition { id: "models", title: "Download a model", optional: false },
+    StepDefinition { id: "google_account", title: "Connect Google", optional: true },
+    StepDefinition { id: "tour", title: "Take the tour", optional: false },
+];
+
+/// Live state of everything onboarding depends on
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrerequisiteChecks {
+    pub ollama_installed: bool,
+    pub ollama_running: bool,
+    pub installed_models: Vec<String>,
+    pub google_credentials_configured: bool,
+    pub google_accounts_connected: i64,
+}
+
+impl PrerequisiteChecks {
+    /// Summary kept in `ollama_setup_status`
+    pub fn ollama_setup_status(&self) -> &'static str {
+        match (self.ollama_installed || self.ollama_running, self.ollama_running, !self.installed_models.is_empty()) {
+            (_, true, true) => "ready",
+            (_, true, false) => "running",
+            (true, false, _) => "installed",
+            (false, false, _) => "not_installed",
+        }
+    }
+
+    /// Whether a step's prerequisite is satisfied
+    fn step_ready(&self, step: &str) -> bool {
+        match step {
+            "ollama" => self.ollama_running,
+            "models" => self.ollama_running && !self.installed_models.is_empty(),
+            "google_account" => self.google_accounts_connected > 0,
+            _ => true,
+        }
+    }
+
+    fn hint(&self, step: &str) -> String {
+        match step {
+            "welcome" => "Pick how you plan to use LibreOllama".to_string(),
+            "ollama" if self.ollama_running => "Ollama is running".to_string(),
+            "ollama" if self.ollama_installed => {
+                "Ollama is installed but not running. Start it from here, with `ollama serve` or from the Ollama app".to_string()
+            }
+            "ollama" => "Install Ollama from https://ollama.com/download, then start it".to_string(),
+            "models" if !self.ollama_running => "Start Ollama first".to_string(),
+            "models" if self.installed_models.is_empty() => {
+                "Download a model such as llama3.1 to chat and summarize locally".to_string()
+            }
+            "models" => format!("{} model(s) installed", self.installed_models.len()),
+            "google_account" if !self.google_credentials_configured => {
+                "Set GMAIL_CLIENT_ID and GMAIL_CLIENT_SECRET in .env to connect Google, or skip this step".to_string()
+            }
+            "google_account" if self.google_accounts_connected == 0 => {
+                "Connect a Google account for mail, calendar and tasks, or skip this step".to_string()
+            }
+            "google_account" => format!("{} account(s) connected", self.google_accounts_connected),
+            "tour" => "A quick look around the app".to_string(),
+            _ => String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StepState {
+    pub id: String,
+    pub title: String,
+    pub optional: bool,
+    pub completed: bool,
+    /// The prerequisite is met, so the step can be completed
+    pub ready: bool,
+    pub hint: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OnboardingState {
+    pub user_id: String,
+    pub is_completed: bool,
+    pub skipped: bool,
+    /// First unfinished step; `None` once onboarding is over
+    pub current_step: Option<String>,
+    pub steps: Vec<StepState>,
+    pub checks: PrerequisiteChecks,
+    pub selected_persona: Option<String>,
+    pub selected_models: Vec<String>,
+    pub started_at: String,
+    pub completed_at: Option<String>,
+}
+
+fn parse_list(json: &str) -> Vec<String> {
+    serde_json::from_str(json).unwrap_or_default()
+}
+
+/// Check Ollama, models and Google setup
+pub async fn run_checks(db_manager: &DatabaseManager) -> PrerequisiteChecks {
+    let (google_accounts_connected, ollama_installed) = match db_manager.get_connection() {
+        Ok(conn) => (
+            conn.query_row("SELECT COUNT(*) FROM gmail_accounts_secure WHERE is_active = 1", [], |row| row.get::<_, i64>(0))
+                .unwrap_or(0),
+            ollama_supervisor::configured_binary(&conn).is_some(),
+        ),
+        Err(_) => (0, ollama_supervisor::find_binary(None).is_some()),
+    };
+    let models = installed_models(db_manager).await;
+    let google_credentials_configured = get_config_manager()
+        .map(|config| {
+            let oauth = config.oauth();
+            !oauth.client_id.is_empty() && !oauth.client_secret.is_empty()
+        })
+        .unwrap_or(false);
+
+    PrerequisiteChecks {
+        ollama_installed,
+        ollama_running: models.is_ok(),
+        installed_models: models.map(|models| models.into_iter().map(|m| m.name).collect()).unwrap_or_default(),
+        google_credentials_configured,
+        google_accounts_connected,
+    }
+}
+
+/// Stored progress, or a fresh start at the first step
+pub fn load_status(conn: &Connection, user_id: &str) -> anyhow::Result<OnboardingStatus> {
+    Ok(onboarding_operations::get_onboarding_status(conn, user_id)?.unwrap_or_else(|| OnboardingStatus {
+        is_completed: false,
+        current_step: STEPS[0].id.to_string(),
+        completed_steps: "[]".to_string(),
+        selected_persona: None,
+        ollama_setup_status: "unknown".to_string(),
+        selected_models: "[]".to_string(),
+        sample_data_created: false,
+        tour_progress: "{}".to_string(),
+        started_at: chrono::Utc::now().to_rfc3339(),
+        completed_at: None,
+    }))
+}
+
+pub fn build_state(user_id: &str, status: &OnboardingStatus, checks: PrerequisiteChecks) -> OnboardingState {
+    let completed = parse_list(&status.completed_steps);
+    let steps: Vec<StepState> = STEPS
+        .iter()
+        .map(|step| StepState {
+            id: step.id.to_string(),
+            title: step.title.to_string(),
+            optional: step.optional,
+            completed: completed.iter().any(|done| done == step.id),
+            ready: checks.step_ready(step.id),
+            hint: checks.hint(step.id),
+        })
+        .collect();
+    let current_step = if status.is_completed {
+        None
+    } else {
+        steps.iter().find(|step| !step.completed).map(|step| step.id.clone())
+    };
+
+    OnboardingState {
+        user_id: user_id.to_string(),
+        is_completed: status.is_completed,
+        skipped: status.current_step == SKIPPED_STEP,
+        current_step,
+        steps,
+        checks,
+        selected_persona: status.selected_persona.clone(),
+        selected_models: parse_list(&status.selected_models),
+        started_at: status.started_at.clone(),
+        completed_at: status.completed_at.clone(),
+    }
+}
+
+/// Mark a step done, recording what the user chose on it. Fails with the
+/// step's hint when its prerequisite is not met.
+pub fn complete_step(
+    status: &mut OnboardingStatus,
+    step: &str,
+    checks: &PrerequisiteChecks,
+    persona: Option<String>,
+    models: Option<Vec<String>>,
+) -> Result<()> {
+    let invalid = |message: String, field: &str| LibreOllamaError::InvalidInput { message, field: Some(field.to_string()) };
+    let definition = STEPS
+        .iter()
+        .find(|definition| definition.id == step)
+        .ok_or_else(|| invalid(format!("Unknown onboarding step: {}", step), "step"))?;
+    if !definition.optional && !checks.step_ready(step) {
+        return Err(invalid(checks.hint(step), "step"));
+    }
+
+    if let Some(persona) = persona.filter(|p| !p.trim().is_empty()) {
+        status.selected_persona = Some(persona.trim().to_string());
+    }
+    if let Some(models) = models {
+        let unknown: Vec<&String> = models.iter().filter(|m| !checks.installed_models.contains(m)).collect();
+        if !unknown.is_empty() {
+            return Err(invalid(format!("Models not installed: {:?}", unknown), "selected_models"));
+        }
+        status.selected_models = serde_json::to_string(&models)?;
+    }
+
+    let mut completed = parse_list(&status.completed_steps);
+    if !completed.iter().any(|done| done == step) {
+        completed.push(step.to_string());
+    }
+    status.completed_steps = serde_json::to_string(&completed)?;
+    status.ollama_setup_status = checks.ollama_setup_status().to_string();
+
+    match STEPS.iter().find(|definition| !completed.iter().any(|done| done == definition.id)) {
+        Some(next) => status.current_step = next.id.to_string(),
+        None => {
+            status.current_step = FINISHED_STEP.to_string();
+            status.is_completed = true;
+            status.completed_at = Some(chrono::Utc::now().to_rfc3339());
+        }
+    }
+    Ok(())
+}
+
+/// End onboarding without finishing the remaining steps
+pub fn skip(status: &mut OnboardingStatus) {
+    if status.is_completed {
+        return;
+    }
+    status.current_step = SKIPPED_STEP.to_string();
+    status.is_completed = true;
+    status.completed_at = Some(chrono::Utc::now().to_rfc3339());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    #[test]
+    fn test_steps_follow_prerequisites() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        let mut status = load_status(&conn, "default_user").unwrap();
+        let mut checks = PrerequisiteChecks { ollama_installed: true, ..Default::default() };
+
+        complete_step(&mut status, "welcome", &checks, Some("writer".to_string()), None).unwrap();
+        let state = build_state("default_user", &status, checks.clone());
+        assert_eq!(state.current_step.as_deref(), Some("ollama"));
+        assert!(state.steps[1].hint.contains("not running"));
+        assert!(complete_step(&mut status, "ollama", &checks, None, None).is_err());
+
+        checks.ollama_running = true;
+        checks.installed_models = vec!["llama3.1:latest".to_string()];
+        complete_step(&mut status, "ollama", &checks, None, None).unwrap();
+        assert!(complete_step(&mut status, "models", &checks, None, Some(vec!["mistral".to_string()])).is_err());
+        complete_step(&mut status, "models", &checks, None, Some(vec!["llama3.1:latest".to_string()])).unwrap();
+        assert_eq!(status.ollama_setup_status, "ready");
+
+        // Google is optional, so it completes without an account
+        complete_step(&mut status, "google_account", &checks, None, None).unwrap();
+        complete_step(&mut status, "tour", &checks, None, None).unwrap();
+        assert!(status.is_completed);
+
+        onboarding_operations::update_onboarding_status(&conn, "default_user", &status).unwrap();
+        let state = build_state("default_user", &load_status(&conn, "default_user").unwrap(), checks);
+        assert!(state.is_completed && !state.skipped);
+        assert!(state.current_step.is_none());
+        assert_eq!(state.selected_persona.as_deref(), Some("writer"));
+        assert_eq!(state.selected_models, vec!["llama3.1:latest".to_string()]);
+    }
+
+    #[test]
+    fn test_skip() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        let mut status = load_status(&conn, "default_user").unwrap();
+        skip(&mut status);
+        let state = build_state("default_user", &status, PrerequisiteChecks::default());
+        assert!(state.is_completed && state.skipped);
+        assert!(state.current_step.is_none());
+    }
+}
@@ -0,0 +1,469 @@
+//! Text and structure of PDF files
+//!
+//! Text is read page by page from the PDF's text layer with the
+//! `pdf-extract` crate, and split into headings, paragraphs and list items
+//! by layout heuristics. Scanned pages have no text layer; when `pdftoppm`
+//! (poppler) and `tesseract` are installed those pages are rasterized and
+//! read by OCR, otherwise they are reported as missing text. Used by note
+//! import, chat attachments and anything else that indexes PDF text.
+
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use std::time::{Duration, Instant};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::errors::{LibreOllamaError, Result};
+
+/// Largest PDF read for import
+pub const MAX_PDF_BYTES: usize = 100 * 1024 * 1024;
+/// OCR takes seconds per page, so long scans are only read in part
+pub const MAX_OCR_PAGES: usize = 50;
+const OCR_RESOLUTION_DPI: &str = "300";
+/// pdftoppm or tesseract still running after this long on one page is killed
+const PAGE_TOOL_TIMEOUT: Duration = Duration::from_secs(60);
+const MAX_HEADING_CHARS: usize = 80;
+const MAX_HEADING_WORDS: usize = 12;
+
+lazy_static! {
+    static ref LIST_MARKER: Regex = Regex::new(r"^\s*(?:[•◦▪‣*\-–]|\d{1,3}[.)]|[a-zA-Z][.)])\s+").unwrap();
+    static ref PAGE_NUMBER: Regex = Regex::new(r"^(?i:page\s+)?\d{1,4}(?:\s*(?:/|of)\s*\d{1,4})?$").unwrap();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PageTextSource {
+    TextLayer,
+    Ocr,
+    /// No text layer and OCR was unavailable or found nothing
+    Missing,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockKind {
+    Heading,
+    Paragraph,
+    ListItem,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TextBlock {
+    pub kind: BlockKind,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PdfPage {
+    /// 1-based
+    pub number: usize,
+    pub text: String,
+    pub blocks: Vec<TextBlock>,
+    pub source: PageTextSource,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PdfDocument {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub page_count: usize,
+    pub pages: Vec<PdfPage>,
+    /// Whether pdftoppm and tesseract were found
+    pub ocr_available: bool,
+}
+
+impl PdfDocument {
+    /// Text of every page, pages separated by a blank line
+    pub fn text(&self) -> String {
+        self.pages
+            .iter()
+            .map(|page| page.text.trim())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    /// Pages that still have no text
+    pub fn missing_pages(&self) -> Vec<usize> {
+        self.pages.iter().filter(|page| page.source == PageTextSource::Missing).map(|page| page.number).collect()
+    }
+
+    /// The structured text as HTML, as stored in notes
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        let mut in_list = false;
+        for block in self.pages.iter().flat_map(|page| &page.blocks) {
+            if in_list && block.kind != BlockKind::ListItem {
+                html.push_str("</ul>");
+                in_list = false;
+            }
+            let text = escape_html(&block.text);
+            match block.kind {
+                BlockKind::Heading => html.push_str(&format!("<h2>{}</h2>", text)),
+                BlockKind::Paragraph => html.push_str(&format!("<p>{}</p>", text)),
+                BlockKind::ListItem => {
+                    if !in_list {
+                        html.push_str("<ul>");
+                        in_list = true;
+                    }
+                    html.push_str(&format!("<li>{}</li>", text));
+                }
+            }
+        }
+        if in_list {
+            html.push_str("</ul>");
+        }
+        html
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn has_text(text: &str) -> bool {
+    text.chars().any(char::is_alphanumeric)
+}
+
+/// Join wrapped lines, undoing hyphenation at line ends
+fn join_lines<'a>(lines: impl IntoIterator<Item = &'a str>) -> String {
+    let mut joined = String::new();
+    for line in lines {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if joined.ends_with('-') && line.starts_with(char::is_lowercase) {
+            joined.pop();
+        } else if !joined.is_empty() {
+            joined.push(' ');
+        }
+        joined.push_str(line);
+    }
+    joined
+}
+
+fn looks_like_heading(line: &str) -> bool {
+    let line = line.trim();
+    !line.is_empty()
+        && line.chars().count() <= MAX_HEADING_CHARS
+        && line.split_whitespace().count() <= MAX_HEADING_WORDS
+        && !line.ends_with(['.', ',', ';', ':', '?', '!'])
+        && line.starts_with(|c: char| c.is_uppercase() || c.is_ascii_digit())
+        && has_text(line)
+}
+
+/// Split page text into headings, paragraphs and list items. Paragraphs are
+/// separated by blank lines; a paragraph that is a single short line without
+/// closing punctuation is a heading; lines starting with a bullet or number
+/// start a list item. Bare page numbers are dropped.
+pub fn structure(text: &str) -> Vec<TextBlock> {
+    let mut blocks = Vec::new();
+    for paragraph in text.split("\n\n").map(str::trim).filter(|paragraph| !paragraph.is_empty()) {
+        let lines: Vec<&str> = paragraph.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+        if lines.len() == 1 && PAGE_NUMBER.is_match(lines[0]) {
+            continue;
+        }
+        if lines.len() == 1 && looks_like_heading(lines[0]) && !LIST_MARKER.is_match(lines[0]) {
+            blocks.push(TextBlock { kind: BlockKind::Heading, text: lines[0].to_string() });
+            continue;
+        }
+
+        // Lines before the first list marker form a paragraph; each marker
+        // starts an item that runs until the next one
+        let mut current: Vec<&str> = Vec::new();
+        let mut current_kind = BlockKind::Paragraph;
+        for line in lines {
+            if let Some(marker) = LIST_MARKER.find(line) {
+                if !current.is_empty() {
+                    blocks.push(TextBlock { kind: current_kind, text: join_lines(current.drain(..)) });
+                }
+                current_kind = BlockKind::ListItem;
+                current.push(&line[marker.end()..]);
+            } else {
+                current.push(line);
+            }
+        }
+        if !current.is_empty() {
+            blocks.push(TextBlock { kind: current_kind, text: join_lines(current) });
+        }
+    }
+    blocks
+}
+
+/// Decode a PDF text string: UTF-16BE with a byte order mark, else UTF-8 or Latin-1
+fn decode_pdf_string(bytes: &[u8]) -> String {
+    if let Some(utf16) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        let units: Vec<u16> = utf16.chunks_exact(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]])).collect();
+        return String::from_utf16_lossy(&units);
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => bytes.iter().map(|&byte| byte as char).collect(),
+    }
+}
+
+/// Page count, title and author from the document catalog
+fn metadata(bytes: &[u8]) -> Option<(usize, Option<String>, Option<String>)> {
+    let document = lopdf::Document::load_mem(bytes).ok()?;
+    let page_count = document.get_pages().len();
+    let info = document.trailer.get(b"Info").ok().and_then(|info| match info {
+        lopdf::Object::Reference(id) => document.get_dictionary(*id).ok(),
+        lopdf::Object::Dictionary(dictionary) => Some(dictionary),
+        _ => None,
+    });
+    let field = |key: &[u8]| {
+        info.and_then(|info| info.get(key).ok())
+            .and_then(|value| value.as_str().ok())
+            .map(decode_pdf_string)
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    Some((page_count, field(b"Title"), field(b"Author")))
+}
+
+/// Run `command` to completion, killing it after `timeout`. Blocking.
+pub(crate) fn run_with_timeout(command: &mut Command, timeout: Duration) -> Result<Output> {
+    let mut child = command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+    // Drained on their own threads so a chatty tool cannot stall on a full pipe
+    let drain = |pipe: Option<Box<dyn Read + Send>>| {
+        std::thread::spawn(move || {
+            let mut buffer = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut buffer);
+            }
+            buffer
+        })
+    };
+    let stdout = drain(child.stdout.take().map(|pipe| Box::new(pipe) as Box<dyn Read + Send>));
+    let stderr = drain(child.stderr.take().map(|pipe| Box::new(pipe) as Box<dyn Read + Send>));
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(LibreOllamaError::Timeout {
+                operation: command.get_program().to_string_lossy().into_owned(),
+                duration_ms: Some(timeout.as_millis() as u64),
+            });
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+fn tool_available(tool: &str) -> bool {
+    Command::new(tool).arg("-v").output().is_ok()
+}
+
+pub fn ocr_available() -> bool {
+    tool_available("pdftoppm") && tool_available("tesseract")
+}
+
+/// Whether `render_page` can work, which needs pdftoppm
+pub fn render_available() -> bool {
+    tool_available("pdftoppm")
+}
+
+/// Number of pages, from the document catalog
+pub fn page_count(bytes: &[u8]) -> Option<usize> {
+    metadata(bytes).map(|(count, _, _)| count)
+}
+
+/// Render one 1-based page as a PNG whose longer side is `max_side` pixels.
+/// Blocking.
+pub fn render_page(bytes: &[u8], page: usize, max_side: u32) -> Result<Vec<u8>> {
+    let work_dir = std::env::temp_dir().join(format!("libreollama-render-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&work_dir)?;
+    let pdf_path = work_dir.join("input.pdf");
+    let image_prefix = work_dir.join("page");
+    let result = std::fs::write(&pdf_path, bytes).map_err(LibreOllamaError::from).and_then(|_| {
+        let page_arg = page.to_string();
+        let rendered = run_with_timeout(
+            Command::new("pdftoppm")
+                .args(["-png", "-singlefile", "-f", &page_arg, "-l", &page_arg, "-scale-to", &max_side.to_string()])
+                .arg(&pdf_path)
+                .arg(&image_prefix),
+            PAGE_TOOL_TIMEOUT,
+        )?;
+        if !rendered.status.success() {
+            return Err(LibreOllamaError::Internal {
+                message: format!("pdftoppm failed on page {}: {}", page, String::from_utf8_lossy(&rendered.stderr).trim()),
+            });
+        }
+        Ok(std::fs::read(image_prefix.with_extension("png"))?)
+    });
+    let _ = std::fs::remove_dir_all(&work_dir);
+    result
+}
+
+/// Rasterize one page of the PDF at `pdf_path` and read it with tesseract
+fn ocr_page(pdf_path: &Path, page: usize, work_dir: &Path) -> Result<String> {
+    let image_prefix = work_dir.join(format!("page-{}", page));
+    let page_arg = page.to_string();
+    let rendered = run_with_timeout(
+        Command::new("pdftoppm")
+            .args(["-r", OCR_RESOLUTION_DPI, "-gray", "-png", "-singlefile", "-f", &page_arg, "-l", &page_arg])
+            .arg(pdf_path)
+            .arg(&image_prefix),
+        PAGE_TOOL_TIMEOUT,
+    )?;
+    if !rendered.status.success() {
+        return Err(LibreOllamaError::Internal {
+            message: format!("pdftoppm failed on page {}: {}", page, String::from_utf8_lossy(&rendered.stderr).trim()),
+        });
+    }
+
+    let image = image_prefix.with_extension("png");
+    let recognized = run_with_timeout(Command::new("tesseract").arg(&image).arg("stdout"), PAGE_TOOL_TIMEOUT);
+    let _ = std::fs::remove_file(&image);
+    let recognized = recognized?;
+    if !recognized.status.success() {
+        return Err(LibreOllamaError::Internal {
+            message: format!("tesseract failed on page {}: {}", page, String::from_utf8_lossy(&recognized.stderr).trim()),
+        });
+    }
+    Ok(String::from_utf8_lossy(&recognized.stdout).into_owned())
+}
+
+/// OCR the given 1-based pages; pages that fail are left out
+fn ocr_pages(bytes: &[u8], pages: &[usize]) -> Result<Vec<(usize, String)>> {
+    let work_dir = std::env::temp_dir().join(format!("libreollama-ocr-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&work_dir)?;
+    let pdf_path = work_dir.join("input.pdf");
+    let result = std::fs::write(&pdf_path, bytes).map_err(LibreOllamaError::from).map(|_| {
+        pages
+            .iter()
+            .take(MAX_OCR_PAGES)
+            .filter_map(|&page| match ocr_page(&pdf_path, page, &work_dir) {
+                Ok(text) => Some((page, text)),
+                Err(e) => {
+                    eprintln!("⚠️ [PDF] OCR of page {} failed: {}", page, e);
+                    None
+                }
+            })
+            .collect()
+    });
+    let _ = std::fs::remove_dir_all(&work_dir);
+    result
+}
+
+/// Text layer of each page. pdf-extract panics on some malformed files, so
+/// a panic is treated like a read error.
+fn text_layer(bytes: &[u8]) -> Option<Vec<String>> {
+    match std::panic::catch_unwind(|| ::pdf_extract::extract_text_from_mem_by_pages(bytes)) {
+        Ok(Ok(pages)) => Some(pages),
+        Ok(Err(e)) => {
+            eprintln!("⚠️ [PDF] Could not read the text layer: {}", e);
+            None
+        }
+        Err(_) => {
+            eprintln!("⚠️ [PDF] The text layer reader crashed on this file");
+            None
+        }
+    }
+}
+
+/// Read the text of a PDF, using OCR for pages without a text layer when
+/// `ocr` is set and the tools are installed. Blocking; OCR can take minutes
+/// on long scans.
+pub fn extract(bytes: &[u8], ocr: bool) -> Result<PdfDocument> {
+    let metadata = metadata(bytes);
+    let layer = text_layer(bytes);
+    let page_count = match (&layer, &metadata) {
+        (Some(pages), _) => pages.len(),
+        (None, Some((count, _, _))) => *count,
+        (None, None) => {
+            return Err(LibreOllamaError::InvalidInput {
+                message: "The file is not a readable PDF".to_string(),
+                field: Some("file".to_string()),
+            })
+        }
+    };
+
+    let mut texts: Vec<(String, PageTextSource)> = (0..page_count)
+        .map(|index| match layer.as_ref().and_then(|pages| pages.get(index)) {
+            Some(text) if has_text(text) => (text.replace("\r\n", "\n"), PageTextSource::TextLayer),
+            _ => (String::new(), PageTextSource::Missing),
+        })
+        .collect();
+
+    let missing: Vec<usize> =
+        texts.iter().enumerate().filter(|(_, (_, source))| *source == PageTextSource::Missing).map(|(index, _)| index + 1).collect();
+    let ocr_available = !missing.is_empty() && ocr && ocr_available();
+    if ocr_available {
+        for (page, text) in ocr_pages(bytes, &missing)? {
+            if has_text(&text) {
+                texts[page - 1] = (text, PageTextSource::Ocr);
+            }
+        }
+    }
+
+    let (title, author) = metadata.map(|(_, title, author)| (title, author)).unwrap_or_default();
+    Ok(PdfDocument {
+        title,
+        author,
+        page_count,
+        pages: texts
+            .into_iter()
+            .enumerate()
+            .map(|(index, (text, source))| PdfPage { number: index + 1, blocks: structure(&text), text, source })
+            .collect(),
+        ocr_available,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_structure() {
+        let text = "Quarterly Report\n\nRevenue grew in every re-\ngion this quarter,\nled by Europe.\n\n\
+                    Next steps:\n• Hire two engineers\n• Open the Berlin\noffice\n2. Review pricing\n\n3";
+        let blocks = structure(text);
+        assert_eq!(
+            blocks,
+            vec![
+                TextBlock { kind: BlockKind::Heading, text: "Quarterly Report".to_string() },
+                TextBlock { kind: BlockKind::Paragraph, text: "Revenue grew in every region this quarter, led by Europe.".to_string() },
+                TextBlock { kind: BlockKind::Paragraph, text: "Next steps:".to_string() },
+                TextBlock { kind: BlockKind::ListItem, text: "Hire two engineers".to_string() },
+                TextBlock { kind: BlockKind::ListItem, text: "Open the Berlin office".to_string() },
+                TextBlock { kind: BlockKind::ListItem, text: "Review pricing".to_string() },
+            ]
+        );
+
+        let document = PdfDocument {
+            title: None,
+            author: None,
+            page_count: 1,
+            pages: vec![PdfPage { number: 1, text: text.to_string(), blocks, source: PageTextSource::TextLayer }],
+            ocr_available: false,
+        };
+        assert_eq!(
+            document.to_html(),
+            "<h2>Quarterly Report</h2><p>Revenue grew in every region this quarter, led by Europe.</p>\
+             <p>Next steps:</p><ul><li>Hire two engineers</li><li>Open the Berlin office</li><li>Review pricing</li></ul>"
+        );
+    }
+
+    #[test]
+    fn test_decode_and_invalid_input() {
+        assert_eq!(decode_pdf_string(&[0xFE, 0xFF, 0x00, 0x48, 0x00, 0xE9]), "Hé");
+        assert_eq!(decode_pdf_string(b"Plain"), "Plain");
+        assert_eq!(decode_pdf_string(&[0x43, 0x61, 0x66, 0xE9]), "Café");
+        assert!(extract(b"not a pdf", false).is_err());
+    }
+}
@@ -0,0 +1,789 @@
+//! Prompt template library
+//!
+//! Templates are stored in `chat_templates` with a category (summarize,
+//! reply, classify, general), typed variables and a version history.
+//! Placeholders are written `{{name}}` and every placeholder must be
+//! declared as a variable, so a template can be checked and previewed
+//! against a sample message before it is saved. Each feature that builds a
+//! prompt (mail summaries, reply drafts, translation, capture sorting,
+//! chat memory, canvas summaries...) has a default template of the matching
+//! category. Built-in templates are seeded on startup, and a feature whose
+//! default is cleared falls back to its built-in one.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use regex::{Captures, Regex};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::database::operations::prompt_template_operations::{
+    self, PromptTemplateRecord, TemplateFields, TemplateVersion,
+};
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+
+pub const CATEGORIES: &[&str] = &["summarize", "reply", "classify", "general"];
+
+lazy_static::lazy_static! {
+    static ref PLACEHOLDER: Regex = Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}").unwrap();
+    static ref VARIABLE_NAME: Regex = Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*$").unwrap();
+}
+
+/// A feature that builds prompts from a template of one category
+pub struct PromptFeature {
+    pub id: &'static str,
+    pub category: &'static str,
+    pub description: &'static str,
+}
+
+pub static FEATURES: &[PromptFeature] = &[
+    PromptFeature { id: "mail.summarize", category: "summarize", description: "Summaries of messages and threads" },
+    PromptFeature { id: "mail.reply", category: "reply", description: "Drafted replies" },
+    PromptFeature { id: "mail.classify", category: "classify", description: "Sorting messages into labels" },
+    PromptFeature { id: "capture.classify", category: "classify", description: "Deciding what captured text becomes" },
+    PromptFeature { id: "chat.system", category: "general", description: "System prompt for new chats" },
+    PromptFeature { id: "feeds.digest", category: "summarize", description: "Summary of the daily feed digest" },
+    PromptFeature { id: "chat.memory", category: "summarize", description: "Folding older chat turns into the session summary" },
+    PromptFeature { id: "chat.excerpts", category: "general", description: "Introducing excerpts of files attached to a chat" },
+    PromptFeature { id: "mail.attachment_check", category: "classify", description: "Checking whether an outgoing email promises an attachment" },
+    PromptFeature { id: "mail.translate", category: "general", description: "Translating email text" },
+    PromptFeature { id: "canvas.summarize", category: "summarize", description: "Summaries of canvas selections" },
+    PromptFeature { id: "canvas.diagram", category: "general", description: "Outlines and mind maps from canvas selections" },
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum VariableKind {
+    Text,
+    Number,
+    Boolean,
+    Choice { options: Vec<String> },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateVariable {
+    pub name: String,
+    pub kind: VariableKind,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub default: Option<Value>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl TemplateVariable {
+    /// Text substituted for `value`, or why it does not fit the variable
+    fn format(&self, value: &Value) -> std::result::Result<String, String> {
+        match (&self.kind, value) {
+            (VariableKind::Text, Value::String(text)) => Ok(text.clone()),
+            (VariableKind::Number, Value::Number(number)) => Ok(number.to_string()),
+            (VariableKind::Boolean, Value::Bool(flag)) => Ok(flag.to_string()),
+            (VariableKind::Choice { options }, Value::String(choice)) if options.contains(choice) => Ok(choice.clone()),
+            (VariableKind::Choice { options }, _) => Err(format!("expected one of {}", options.join(", "))),
+            (VariableKind::Text, _) => Err("expected text".to_string()),
+            (VariableKind::Number, _) => Err("expected a number".to_string()),
+            (VariableKind::Boolean, _) => Err("expected true or false".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptTemplate {
+    pub id: i64,
+    pub name: String,
+    pub category: String,
+    pub description: Option<String>,
+    pub content: String,
+    pub variables: Vec<TemplateVariable>,
+    pub version: i64,
+    pub updated_at: Option<String>,
+}
+
+impl From<PromptTemplateRecord> for PromptTemplate {
+    fn from(record: PromptTemplateRecord) -> Self {
+        Self {
+            id: record.id,
+            name: record.name,
+            category: record.category,
+            description: record.description,
+            content: record.content,
+            // Templates created before variables existed have none
+            variables: serde_json::from_str(&record.variables).unwrap_or_default(),
+            version: record.version,
+            updated_at: record.updated_at,
+        }
+    }
+}
+
+/// A template to create, update or preview
+#[derive(Debug, Clone, Deserialize)]
+pub struct TemplateInput {
+    pub name: String,
+    pub category: String,
+    pub description: Option<String>,
+    pub content: String,
+    #[serde(default)]
+    pub variables: Vec<TemplateVariable>,
+}
+
+/// Message used to fill `subject`, `sender`, `recipients`, `date` and
+/// `body` when previewing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SampleMessage {
+    pub subject: String,
+    pub from: String,
+    pub to: String,
+    pub date: String,
+    pub body: String,
+}
+
+impl Default for SampleMessage {
+    fn default() -> Self {
+        Self {
+            subject: "Quarterly planning meeting".to_string(),
+            from: "Dana Reyes <dana@example.com>".to_string(),
+            to: "team@example.com".to_string(),
+            date: "Mon, 6 Oct 2025 09:12:00 +0000".to_string(),
+            body: "Hi all,\n\nCan we move Thursday's planning meeting to Friday at 2pm? \
+                   Please send me your roadmap updates before then.\n\nThanks,\nDana"
+                .to_string(),
+        }
+    }
+}
+
+impl SampleMessage {
+    fn values(&self) -> Map<String, Value> {
+        let mut values = Map::new();
+        values.insert("subject".to_string(), Value::from(self.subject.clone()));
+        values.insert("sender".to_string(), Value::from(self.from.clone()));
+        values.insert("recipients".to_string(), Value::from(self.to.clone()));
+        values.insert("date".to_string(), Value::from(self.date.clone()));
+        values.insert("body".to_string(), Value::from(self.body.clone()));
+        values
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptPreview {
+    pub rendered: String,
+    /// Declared variables the content never uses
+    pub unused_variables: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureDefault {
+    pub feature: String,
+    pub category: String,
+    pub description: String,
+    pub template_id: Option<i64>,
+    pub template_name: Option<String>,
+}
+
+fn invalid(message: impl Into<String>, field: &str) -> LibreOllamaError {
+    LibreOllamaError::InvalidInput {
+        message: message.into(),
+        field: Some(field.to_string()),
+    }
+}
+
+fn not_found(id: i64) -> LibreOllamaError {
+    LibreOllamaError::NotFound { resource: format!("Prompt template {}", id) }
+}
+
+fn find_feature(id: &str) -> Result<&'static PromptFeature> {
+    FEATURES
+        .iter()
+        .find(|feature| feature.id == id)
+        .ok_or_else(|| invalid(format!("Unknown prompt feature: {}", id), "feature"))
+}
+
+/// Names of the placeholders in `content`, in order of first use
+pub fn placeholders(content: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    PLACEHOLDER
+        .captures_iter(content)
+        .map(|captures| captures[1].to_string())
+        .filter(|name| seen.insert(name.clone()))
+        .collect()
+}
+
+/// Check names, category, variable declarations and defaults
+pub fn validate(input: &TemplateInput) -> Result<()> {
+    if input.name.trim().is_empty() {
+        return Err(invalid("Template name must not be empty", "name"));
+    }
+    if !CATEGORIES.contains(&input.category.as_str()) {
+        return Err(invalid(format!("Category must be one of {}", CATEGORIES.join(", ")), "category"));
+    }
+    if input.content.trim().is_empty() {
+        return Err(invalid("Template content must not be empty", "content"));
+    }
+
+    let mut names = HashSet::new();
+    for variable in &input.variables {
+        if !VARIABLE_NAME.is_match(&variable.name) {
+            return Err(invalid(format!("Invalid variable name: {}", variable.name), "variables"));
+        }
+        if !names.insert(variable.name.as_str()) {
+            return Err(invalid(format!("Variable {} is declared twice", variable.name), "variables"));
+        }
+        if matches!(&variable.kind, VariableKind::Choice { options } if options.is_empty()) {
+            return Err(invalid(format!("Variable {} has no choices", variable.name), "variables"));
+        }
+        if let Some(default) = &variable.default {
+            variable
+                .format(default)
+                .map_err(|e| invalid(format!("Default for {}: {}", variable.name, e), "variables"))?;
+        }
+    }
+
+    let undeclared: Vec<String> = placeholders(&input.content)
+        .into_iter()
+        .filter(|name| !names.contains(name.as_str()))
+        .collect();
+    if !undeclared.is_empty() {
+        return Err(invalid(format!("Undeclared variables: {}", undeclared.join(", ")), "content"));
+    }
+    Ok(())
+}
+
+/// Fill the placeholders in `content` from `values`, falling back to each
+/// variable's default. Missing optional variables render as empty text.
+pub fn render(content: &str, variables: &[TemplateVariable], values: &Map<String, Value>) -> Result<String> {
+    let mut formatted = Map::new();
+    for variable in variables {
+        let value = values.get(&variable.name).filter(|value| !value.is_null()).or(variable.default.as_ref());
+        let text = match value {
+            Some(value) => variable
+                .format(value)
+                .map_err(|e| invalid(format!("{}: {}", variable.name, e), &variable.name))?,
+            None if variable.required => {
+                return Err(invalid(format!("Missing value for {}", variable.name), &variable.name))
+            }
+            None => String::new(),
+        };
+        formatted.insert(variable.name.clone(), Value::from(text));
+    }
+
+    Ok(PLACEHOLDER
+        .replace_all(content, |captures: &Captures| {
+            formatted.get(&captures[1]).and_then(Value::as_str).unwrap_or_default().to_string()
+        })
+        .into_owned())
+}
+
+/// Render an unsaved template, taking message variables from `sample`
+/// where `values` does not set them
+pub fn preview(input: &TemplateInput, values: &Map<String, Value>, sample: &SampleMessage) -> Result<PromptPreview> {
+    validate(input)?;
+    let mut merged = sample.values();
+    merged.extend(values.clone());
+
+    let used = placeholders(&input.content);
+    Ok(PromptPreview {
+        rendered: render(&input.content, &input.variables, &merged)?,
+        unused_variables: input
+            .variables
+            .iter()
+            .filter(|variable| !used.contains(&variable.name))
+            .map(|variable| variable.name.clone())
+            .collect(),
+    })
+}
+
+fn fields<'a>(input: &'a TemplateInput, variables: &'a str) -> TemplateFields<'a> {
+    TemplateFields {
+        name: input.name.trim(),
+        category: &input.category,
+        description: input.description.as_deref().filter(|d| !d.trim().is_empty()),
+        content: &input.content,
+        variables,
+    }
+}
+
+pub fn list_templates(conn: &Connection, category: Option<&str>) -> Result<Vec<PromptTemplate>> {
+    Ok(prompt_template_operations::list_templates(conn, category)?
+        .into_iter()
+        .map(PromptTemplate::from)
+        .collect())
+}
+
+pub fn get_template(conn: &Connection, id: i64) -> Result<PromptTemplate> {
+    prompt_template_operations::get_template(conn, id)?
+        .map(PromptTemplate::from)
+        .ok_or_else(|| not_found(id))
+}
+
+pub fn create_template(conn: &Connection, input: &TemplateInput) -> Result<PromptTemplate> {
+    validate(input)?;
+    if prompt_template_operations::get_template_by_name(conn, input.name.trim())?.is_some() {
+        return Err(invalid(format!("A template named {} already exists", input.name.trim()), "name"));
+    }
+    let variables = serde_json::to_string(&input.variables)?;
+    Ok(prompt_template_operations::create_template(conn, &fields(input, &variables))?.into())
+}
+
+/// Save a new version; the template must not move out of the category of
+/// a feature that uses it by default
+pub fn update_template(conn: &mut Connection, id: i64, input: &TemplateInput) -> Result<PromptTemplate> {
+    validate(input)?;
+    if let Some(other) = prompt_template_operations::get_template_by_name(conn, input.name.trim())? {
+        if other.id != id {
+            return Err(invalid(format!("A template named {} already exists", input.name.trim()), "name"));
+        }
+    }
+    for feature in FEATURES.iter().filter(|feature| feature.category != input.category) {
+        if prompt_template_operations::get_default_template_id(conn, feature.id)? == Some(id) {
+            return Err(invalid(
+                format!("Template is the default for {}, which needs a {} template", feature.id, feature.category),
+                "category",
+            ));
+        }
+    }
+    let variables = serde_json::to_string(&input.variables)?;
+    prompt_template_operations::update_template(conn, id, &fields(input, &variables))?
+        .map(PromptTemplate::from)
+        .ok_or_else(|| not_found(id))
+}
+
+pub fn delete_template(conn: &Connection, id: i64) -> Result<bool> {
+    Ok(prompt_template_operations::delete_template(conn, id)?)
+}
+
+pub fn list_versions(conn: &Connection, id: i64) -> Result<Vec<TemplateVersion>> {
+    Ok(prompt_template_operations::list_versions(conn, id)?)
+}
+
+/// Make an archived version current again, as a new version
+pub fn restore_version(conn: &mut Connection, id: i64, version: i64) -> Result<PromptTemplate> {
+    let current = get_template(conn, id)?;
+    let archived = prompt_template_operations::list_versions(conn, id)?
+        .into_iter()
+        .find(|archived| archived.version == version)
+        .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("Version {} of prompt template {}", version, id) })?;
+
+    let input = TemplateInput {
+        name: current.name,
+        category: current.category,
+        description: current.description,
+        content: archived.content,
+        variables: serde_json::from_str(&archived.variables).unwrap_or_default(),
+    };
+    update_template(conn, id, &input)
+}
+
+pub fn feature_defaults(conn: &Connection) -> Result<Vec<FeatureDefault>> {
+    FEATURES
+        .iter()
+        .map(|feature| {
+            let template = match prompt_template_operations::get_default_template_id(conn, feature.id)? {
+                Some(id) => prompt_template_operations::get_template(conn, id)?,
+                None => None,
+            };
+            Ok(FeatureDefault {
+                feature: feature.id.to_string(),
+                category: feature.category.to_string(),
+                description: feature.description.to_string(),
+                template_id: template.as_ref().map(|template| template.id),
+                template_name: template.map(|template| template.name),
+            })
+        })
+        .collect()
+}
+
+/// Set or (with `None`) clear the template a feature uses by default
+pub fn set_feature_default(conn: &Connection, feature_id: &str, template_id: Option<i64>) -> Result<()> {
+    let feature = find_feature(feature_id)?;
+    let Some(template_id) = template_id else {
+        prompt_template_operations::clear_default_template(conn, feature.id)?;
+        return Ok(());
+    };
+    let template = get_template(conn, template_id)?;
+    if template.category != feature.category {
+        return Err(invalid(
+            format!("{} needs a {} template, not {}", feature.id, feature.category, template.category),
+            "template_id",
+        ));
+    }
+    prompt_template_operations::set_default_template(conn, feature.id, template_id)?;
+    Ok(())
+}
+
+/// The feature's default template rendered with `values`; `None` when the
+/// feature has no default
+pub fn render_for_feature(conn: &Connection, feature_id: &str, values: &Map<String, Value>) -> Result<Option<String>> {
+    let feature = find_feature(feature_id)?;
+    let Some(id) = prompt_template_operations::get_default_template_id(conn, feature.id)? else {
+        return Ok(None);
+    };
+    let template = get_template(conn, id)?;
+    render(&template.content, &template.variables, values).map(Some)
+}
+
+/// The feature's prompt: its default template, or the built-in template
+/// when the user cleared the default
+pub fn render_feature_prompt(conn: &Connection, feature_id: &str, values: &Map<String, Value>) -> Result<String> {
+    if let Some(prompt) = render_for_feature(conn, feature_id, values)? {
+        return Ok(prompt);
+    }
+    let (_, input) = builtin_templates()
+        .into_iter()
+        .find(|(id, _)| *id == feature_id)
+        .ok_or_else(|| invalid(format!("{} has no default template", feature_id), "feature"))?;
+    render(&input.content, &input.variables, values)
+}
+
+fn text(name: &str, required: bool) -> TemplateVariable {
+    TemplateVariable { name: name.to_string(), kind: VariableKind::Text, required, default: None, description: None }
+}
+
+fn number(name: &str) -> TemplateVariable {
+    TemplateVariable { name: name.to_string(), kind: VariableKind::Number, required: true, default: None, description: None }
+}
+
+fn choice(name: &str, options: &[&str], default: &str) -> TemplateVariable {
+    TemplateVariable {
+        name: name.to_string(),
+        kind: VariableKind::Choice { options: options.iter().map(|o| o.to_string()).collect() },
+        required: false,
+        default: Some(Value::from(default)),
+        description: None,
+    }
+}
+
+fn builtin_templates() -> Vec<(&'static str, TemplateInput)> {
+    vec![
+        (
+            "mail.summarize",
+            TemplateInput {
+                name: "Summarize message".to_string(),
+                category: "summarize".to_string(),
+                description: Some("Short summary of an email".to_string()),
+                content: "Summarize the following email as {{length}}. Mention any requests or deadlines.\n\n\
+                          From: {{sender}}\nSubject: {{subject}}\n\n{{body}}"
+                    .to_string(),
+                variables: vec![
+                    choice("length", &["one sentence", "three bullet points", "a short paragraph"], "three bullet points"),
+                    text("sender", false),
+                    text("subject", false),
+                    text("body", true),
+                ],
+            },
+        ),
+        (
+            "mail.reply",
+            TemplateInput {
+                name: "Draft reply".to_string(),
+                category: "reply".to_string(),
+                description: Some("Reply to an email in a chosen tone".to_string()),
+                content: "Write a reply to this email in a {{tone}} tone. {{instructions}}\n\
+                          Reply with the email body only.\n\n\
+                          From: {{sender}}\nSubject: {{subject}}\n\n{{body}}"
+                    .to_string(),
+                variables: vec![
+                    choice("tone", &["friendly", "formal", "brief"], "friendly"),
+                    text("instructions", false),
+                    text("sender", false),
+                    text("subject", false),
+                    text("body", true),
+                ],
+            },
+        ),
+        (
+            "mail.classify",
+            TemplateInput {
+                name: "Classify message".to_string(),
+                category: "classify".to_string(),
+                description: Some("Pick one label for an email".to_string()),
+                content: "Classify this email into exactly one of: {{labels}}. Reply with the label only.\n\n\
+                          Subject: {{subject}}\n\n{{body}}"
+                    .to_string(),
+                variables: vec![
+                    TemplateVariable {
+                        default: Some(Value::from("work, personal, newsletter, receipt, spam")),
+                        ..text("labels", false)
+                    },
+                    text("subject", false),
+                    text("body", true),
+                ],
+            },
+        ),
+        (
+            "feeds.digest",
+            TemplateInput {
+                name: "Feed digest".to_string(),
+                category: "summarize".to_string(),
+                description: Some("Daily digest of unread feed items".to_string()),
+                content: "Write a short daily digest of these news items from my feeds, in {{language}}. \
+                          Lead with what matters most, group related items and keep it under 200 words \
+                          of plain text, without a preamble.\n\nToday's items:\n{{items}}"
+                    .to_string(),
+                variables: vec![
+                    TemplateVariable { default: Some(Value::from("English")), ..text("language", false) },
+                    text("items", true),
+                ],
+            },
+        ),
+        (
+            "capture.classify",
+            TemplateInput {
+                name: "Classify capture".to_string(),
+                category: "classify".to_string(),
+                description: Some("Decide whether captured text is a task, note, event or email".to_string()),
+                content: "You sort quick notes typed into a capture box. Today is {{now}}. \
+                          Decide whether the text is a task, a note, a calendar event or an email draft, and reply with JSON only: \
+                          {\"kind\": \"task\" | \"note\" | \"event\" | \"email\", \"title\": string, \"body\": string or null, \
+                          \"date\": \"YYYY-MM-DD\" or null, \"time\": \"HH:MM\" or null, \"duration_minutes\": number or null, \
+                          \"recipients\": [email addresses]}. For an email the title is the subject."
+                    .to_string(),
+                variables: vec![text("now", true)],
+            },
+        ),
+        (
+            "chat.memory",
+            TemplateInput {
+                name: "Conversation memory".to_string(),
+                category: "summarize".to_string(),
+                description: Some("Merge older chat turns into the running summary".to_string()),
+                content: "You maintain the memory of a conversation between a user and an assistant. \
+                          Merge the existing summary with the new turns into one updated summary. Keep facts, decisions, names, numbers \
+                          and open questions; drop small talk. Write in the third person, as short paragraphs or bullet points, and reply \
+                          with the summary only."
+                    .to_string(),
+                variables: Vec::new(),
+            },
+        ),
+        (
+            "chat.excerpts",
+            TemplateInput {
+                name: "Attachment excerpts".to_string(),
+                category: "general".to_string(),
+                description: Some("Numbered excerpts of attached files for the model to cite".to_string()),
+                content: "Excerpts from files attached to this chat. Answer from them when they are relevant and cite each \
+                          excerpt you use by its number in square brackets, e.g. [1].\n\n{{excerpts}}"
+                    .to_string(),
+                variables: vec![text("excerpts", true)],
+            },
+        ),
+        (
+            "mail.attachment_check",
+            TemplateInput {
+                name: "Attachment check".to_string(),
+                category: "classify".to_string(),
+                description: Some("Whether an outgoing email says a file is attached".to_string()),
+                content: "You check outgoing email before it is sent. Decide whether the sender says they are \
+                          attaching a file to this message (not one sent earlier or by someone else). \
+                          Reply with JSON only: {\"expects_attachment\": true | false}."
+                    .to_string(),
+                variables: Vec::new(),
+            },
+        ),
+        (
+            "mail.translate",
+            TemplateInput {
+                name: "Translate email".to_string(),
+                category: "general".to_string(),
+                description: Some("Translate email text, keeping its tone and layout".to_string()),
+                content: "You are a translation engine. Translate the user's email text into {{language}}. \
+                          Keep the meaning, tone, names, numbers, links and line breaks. \
+                          Reply with the translation only, without notes or quotation marks."
+                    .to_string(),
+                variables: vec![text("language", true)],
+            },
+        ),
+        (
+            "canvas.summarize",
+            TemplateInput {
+                name: "Summarize canvas selection".to_string(),
+                category: "summarize".to_string(),
+                description: Some("Gist and key points of selected whiteboard notes".to_string()),
+                content: "You summarize notes from a whiteboard. Reply with a short summary in plain text: \
+                          a one-line gist followed by the key points, without a preamble."
+                    .to_string(),
+                variables: Vec::new(),
+            },
+        ),
+        (
+            "canvas.diagram",
+            TemplateInput {
+                name: "Canvas diagram".to_string(),
+                category: "general".to_string(),
+                description: Some("Topic tree for an outline or mind map".to_string()),
+                content: "You organise notes from a whiteboard into {{shape}}. Reply with only a JSON object of the form \
+                          {\"label\": \"...\", \"children\": [{\"label\": \"...\", \"children\": [...]}]}. \
+                          Use at most {{max_nodes}} nodes in total and at most {{max_depth}} levels below the root. \
+                          Keep every label under eight words."
+                    .to_string(),
+                variables: vec![text("shape", true), number("max_nodes"), number("max_depth")],
+            },
+        ),
+    ]
+}
+
+/// Add the built-in templates that are missing and make them the default
+/// for features that have none
+pub fn seed_builtin_templates(conn: &Connection) -> Result<()> {
+    for (feature_id, input) in builtin_templates() {
+        let template = match prompt_template_operations::get_template_by_name(conn, &input.name)? {
+            Some(existing) => existing.into(),
+            None => create_template(conn, &input)?,
+        };
+        if prompt_template_operations::get_default_template_id(conn, feature_id)?.is_none() && template.category == input.category {
+            prompt_template_operations::set_default_template(conn, feature_id, template.id)?;
+        }
+    }
+    Ok(())
+}
+
+/// Prompt template access for commands, managed as Tauri state
+pub struct PromptTemplateService {
+    db_manager: Arc<DatabaseManager>,
+}
+
+impl PromptTemplateService {
+    pub fn new(db_manager: Arc<DatabaseManager>) -> Self {
+        let service = Self { db_manager };
+        if let Err(e) = service.connection().and_then(|conn| seed_builtin_templates(&conn)) {
+            eprintln!("⚠️ [PROMPTS] Failed to add built-in templates: {}", e);
+        }
+        service
+    }
+
+    fn connection(&self) -> Result<Connection> {
+        Ok(self.db_manager.get_connection()?)
+    }
+
+    pub fn list(&self, category: Option<&str>) -> Result<Vec<PromptTemplate>> {
+        list_templates(&self.connection()?, category)
+    }
+
+    pub fn get(&self, id: i64) -> Result<PromptTemplate> {
+        get_template(&self.connection()?, id)
+    }
+
+    pub fn create(&self, input: &TemplateInput) -> Result<PromptTemplate> {
+        create_template(&self.connection()?, input)
+    }
+
+    pub fn update(&self, id: i64, input: &TemplateInput) -> Result<PromptTemplate> {
+        update_template(&mut self.connection()?, id, input)
+    }
+
+    pub fn delete(&self, id: i64) -> Result<bool> {
+        delete_template(&self.connection()?, id)
+    }
+
+    pub fn versions(&self, id: i64) -> Result<Vec<TemplateVersion>> {
+        list_versions(&self.connection()?, id)
+    }
+
+    pub fn restore(&self, id: i64, version: i64) -> Result<PromptTemplate> {
+        restore_version(&mut self.connection()?, id, version)
+    }
+
+    pub fn defaults(&self) -> Result<Vec<FeatureDefault>> {
+        feature_defaults(&self.connection()?)
+    }
+
+    pub fn set_default(&self, feature: &str, template_id: Option<i64>) -> Result<()> {
+        set_feature_default(&self.connection()?, feature, template_id)
+    }
+
+    /// Prompt for `feature` from its default template, `None` when it has none
+    pub fn render_for(&self, feature: &str, values: &Map<String, Value>) -> Result<Option<String>> {
+        render_for_feature(&self.connection()?, feature, values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        conn
+    }
+
+    fn input(content: &str) -> TemplateInput {
+        TemplateInput {
+            name: "Translate".to_string(),
+            category: "general".to_string(),
+            description: None,
+            content: content.to_string(),
+            variables: vec![text("body", true), choice("language", &["French", "German"], "French")],
+        }
+    }
+
+    #[test]
+    fn test_validate_and_render() {
+        assert!(validate(&input("Translate {{body}} into {{ language }}")).is_ok());
+        assert!(validate(&input("Translate {{body}} for {{audience}}")).is_err());
+
+        let template = input("Translate into {{language}}:\n{{body}}");
+        let mut values = Map::new();
+        assert!(render(&template.content, &template.variables, &values).is_err());
+
+        values.insert("body".to_string(), Value::from("Hello"));
+        assert_eq!(render(&template.content, &template.variables, &values).unwrap(), "Translate into French:\nHello");
+        values.insert("language".to_string(), Value::from("Klingon"));
+        assert!(render(&template.content, &template.variables, &values).is_err());
+
+        let preview = preview(&input("Summarize: {{body}}"), &Map::new(), &SampleMessage::default()).unwrap();
+        assert!(preview.rendered.contains("planning meeting"));
+        assert_eq!(preview.unused_variables, vec!["language".to_string()]);
+    }
+
+    #[test]
+    fn test_versions_and_defaults() {
+        let mut conn = setup_test_db();
+        seed_builtin_templates(&conn).unwrap();
+        seed_builtin_templates(&conn).unwrap();
+        assert_eq!(list_templates(&conn, Some("summarize")).unwrap().len(), 4);
+
+        let mut values = Map::new();
+        values.insert("body".to_string(), Value::from("Lunch on Friday?"));
+        let prompt = render_for_feature(&conn, "mail.summarize", &values).unwrap().unwrap();
+        assert!(prompt.contains("three bullet points") && prompt.contains("Lunch on Friday?"));
+        assert!(render_for_feature(&conn, "chat.system", &values).unwrap().is_none());
+
+        // A feature whose default was cleared falls back to its built-in template
+        set_feature_default(&conn, "feeds.digest", None).unwrap();
+        let mut items = Map::new();
+        items.insert("items".to_string(), Value::from("- [News] Launch"));
+        let prompt = render_feature_prompt(&conn, "feeds.digest", &items).unwrap();
+        assert!(prompt.contains("in English") && prompt.ends_with("- [News] Launch"));
+        assert!(render_feature_prompt(&conn, "chat.system", &values).is_err());
+
+        let mut diagram = Map::new();
+        diagram.insert("shape".to_string(), Value::from("an outline"));
+        diagram.insert("max_nodes".to_string(), Value::from(24));
+        diagram.insert("max_depth".to_string(), Value::from(3));
+        let prompt = render_feature_prompt(&conn, "canvas.diagram", &diagram).unwrap();
+        assert!(prompt.contains("into an outline") && prompt.contains("at most 24 nodes") && prompt.contains("{\"label\""));
+
+        let created = create_template(&conn, &input("Translate {{body}}")).unwrap();
+        assert_eq!(created.version, 1);
+        assert!(create_template(&conn, &input("Again {{body}}")).is_err());
+        assert!(set_feature_default(&conn, "mail.reply", Some(created.id)).is_err());
+        set_feature_default(&conn, "chat.system", Some(created.id)).unwrap();
+
+        let updated = update_template(&mut conn, created.id, &input("Translate {{body}} into {{language}}")).unwrap();
+        assert_eq!(updated.version, 2);
+        assert_eq!(list_versions(&conn, created.id).unwrap()[0].content, "Translate {{body}}");
+        // Moving a default template out of its feature's category is refused
+        let moved = TemplateInput { category: "reply".to_string(), ..input("Translate {{body}}") };
+        assert!(update_template(&mut conn, created.id, &moved).is_err());
+
+        let restored = restore_version(&mut conn, created.id, 1).unwrap();
+        assert_eq!((restored.version, restored.content.as_str()), (3, "Translate {{body}}"));
+
+        delete_template(&conn, created.id).unwrap();
+        let defaults = feature_defaults(&conn).unwrap();
+        assert!(defaults.iter().any(|d| d.feature == "chat.system" && d.template_id.is_none()));
+    }
+}
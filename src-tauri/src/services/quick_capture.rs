@@ -0,0 +1,409 @@
+//! Quick capture classification
+//!
+//! Decides what a line of free text typed into the capture window should
+//! become: a task, a note, a calendar event or an email draft. Cheap
+//! heuristics run first (explicit `task:`/`note:`/`event:`/`email:`
+//! prefixes, dates and times, addresses, leading verbs); when they are not
+//! confident and the caller allows it, the classification model is asked
+//! instead. Only classification lives here; the capture command routes the
+//! result to the right subsystem.
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::llm_provider::{self, ChatMessage, ChatOptions, LlmFeature};
+use crate::services::prompt_templates;
+
+/// user_preferences key naming the local model used to classify captures;
+/// see `model_router` for how it combines with the model settings
+pub const QUICK_CAPTURE_MODEL_PREFERENCE: &str = "quick_capture.model";
+pub const DEFAULT_QUICK_CAPTURE_MODEL: &str = "llama3.1";
+
+/// Below this heuristic confidence the model is consulted, when allowed
+pub const LLM_CONFIDENCE_THRESHOLD: f32 = 0.7;
+/// Captures longer than this (or spanning lines) read as notes
+const LONG_CAPTURE_CHARS: usize = 200;
+const DEFAULT_EVENT_MINUTES: i64 = 60;
+const MAX_TITLE_CHARS: usize = 80;
+/// Prompt template feature for model classification
+const PROMPT_FEATURE: &str = "capture.classify";
+
+lazy_static::lazy_static! {
+    static ref PREFIX: Regex =
+        Regex::new(r"(?i)^\s*(task|todo|note|event|meeting|cal|email|mail|draft)\s*:\s*").unwrap();
+    static ref EMAIL_ADDRESS: Regex = Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}").unwrap();
+    static ref ISO_DATE: Regex = Regex::new(r"(?i)\b(?:(?:on|by|due)\s+)?(\d{4}-\d{2}-\d{2})\b").unwrap();
+    static ref RELATIVE_DAY: Regex = Regex::new(
+        r"(?i)\b(?:(?:on|next|this|by|due)\s+)?(today|tonight|tomorrow|monday|tuesday|wednesday|thursday|friday|saturday|sunday)\b"
+    ).unwrap();
+    static ref TIME_OF_DAY: Regex = Regex::new(
+        r"(?i)(?:\bat\s+|@\s*)?\b(?:(?P<h12>1[0-2]|0?[1-9])(?::(?P<m12>[0-5]\d))?\s*(?P<ampm>am|pm)|(?P<h24>[01]?\d|2[0-3]):(?P<m24>[0-5]\d)|(?P<word>noon|midnight))\b"
+    ).unwrap();
+    static ref DURATION: Regex =
+        Regex::new(r"(?i)\bfor\s+(\d+(?:\.\d+)?)\s*(minutes?|mins?|m|hours?|hrs?|h)\b").unwrap();
+    static ref EVENT_WORDS: Regex = Regex::new(
+        r"(?i)\b(meeting|meet with|call with|lunch|dinner|breakfast|coffee|drinks|appointment|interview|standup|party|class|flight|webinar|demo)\b"
+    ).unwrap();
+    static ref EMAIL_LEAD: Regex =
+        Regex::new(r"(?i)^\s*(?:e-?mail|mail|write to|reply to|message|tell|ask)\b\s*(?:to\s+)?").unwrap();
+    static ref SUBJECT_LEAD: Regex = Regex::new(r"(?i)^(?:about|re|regarding)\b:?\s*").unwrap();
+    static ref TASK_WORDS: Regex = Regex::new(
+        r"(?i)^\s*(remember to|remind me to|don't forget to|need to|buy|pick up|call|pay|send|finish|fix|book|renew|clean|review|submit|check|order|return|read|write)\b"
+    ).unwrap();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureKind {
+    Task,
+    Note,
+    Event,
+    EmailDraft,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClassifiedBy {
+    Prefix,
+    Heuristic,
+    Llm,
+}
+
+/// What a capture should become, with the details pulled out of the text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureIntent {
+    pub kind: CaptureKind,
+    /// Task or event title, note title or email subject
+    pub title: String,
+    /// Note content, task notes or email body
+    pub body: Option<String>,
+    pub date: Option<NaiveDate>,
+    pub time: Option<NaiveTime>,
+    pub duration_minutes: i64,
+    pub recipients: Vec<String>,
+    pub confidence: f32,
+    pub classified_by: ClassifiedBy,
+}
+
+fn prefix_kind(prefix: &str) -> CaptureKind {
+    match prefix.to_lowercase().as_str() {
+        "note" => CaptureKind::Note,
+        "event" | "meeting" | "cal" => CaptureKind::Event,
+        "email" | "mail" | "draft" => CaptureKind::EmailDraft,
+        _ => CaptureKind::Task,
+    }
+}
+
+fn weekday(name: &str) -> Option<Weekday> {
+    name.parse().ok()
+}
+
+/// Pull the first date out of `text`, returning it with the time it
+/// implies ("tonight"). Weekdays mean the next such day, never today.
+fn extract_date(text: &mut String, today: NaiveDate) -> (Option<NaiveDate>, Option<NaiveTime>) {
+    if let Some(caps) = ISO_DATE.captures(text) {
+        let date = NaiveDate::parse_from_str(&caps[1], "%Y-%m-%d").ok();
+        let span = caps.get(0).map(|m| m.range()).unwrap_or_default();
+        if date.is_some() {
+            text.replace_range(span, " ");
+            return (date, None);
+        }
+    }
+
+    let Some(caps) = RELATIVE_DAY.captures(text) else {
+        return (None, None);
+    };
+    let word = caps[1].to_lowercase();
+    let span = caps.get(0).map(|m| m.range()).unwrap_or_default();
+    let resolved = match word.as_str() {
+        "today" => Some((today, None)),
+        "tonight" => Some((today, NaiveTime::from_hms_opt(20, 0, 0))),
+        "tomorrow" => Some((today + Duration::days(1), None)),
+        name => weekday(name).map(|target| {
+            let ahead = (target.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+            let ahead = if ahead == 0 { 7 } else { ahead };
+            (today + Duration::days(ahead as i64), None)
+        }),
+    };
+    match resolved {
+        Some((date, time)) => {
+            text.replace_range(span, " ");
+            (Some(date), time)
+        }
+        None => (None, None),
+    }
+}
+
+fn extract_time(text: &mut String) -> Option<NaiveTime> {
+    let caps = TIME_OF_DAY.captures(text)?;
+    let time = if let Some(hour) = caps.name("h12") {
+        let hour: u32 = hour.as_str().parse().ok()?;
+        let minute: u32 = caps.name("m12").map_or(Some(0), |m| m.as_str().parse().ok())?;
+        let pm = caps.name("ampm").is_some_and(|m| m.as_str().eq_ignore_ascii_case("pm"));
+        NaiveTime::from_hms_opt(hour % 12 + if pm { 12 } else { 0 }, minute, 0)
+    } else if let Some(hour) = caps.name("h24") {
+        let minute = caps.name("m24")?.as_str().parse().ok()?;
+        NaiveTime::from_hms_opt(hour.as_str().parse().ok()?, minute, 0)
+    } else {
+        match caps.name("word")?.as_str().to_lowercase().as_str() {
+            "noon" => NaiveTime::from_hms_opt(12, 0, 0),
+            _ => NaiveTime::from_hms_opt(0, 0, 0),
+        }
+    }?;
+    let span = caps.get(0)?.range();
+    text.replace_range(span, " ");
+    Some(time)
+}
+
+fn extract_duration(text: &mut String) -> Option<i64> {
+    let caps = DURATION.captures(text)?;
+    let amount: f64 = caps[1].parse().ok()?;
+    let minutes = if caps[2].to_lowercase().starts_with('h') { amount * 60.0 } else { amount };
+    let span = caps.get(0)?.range();
+    text.replace_range(span, " ");
+    Some((minutes.round() as i64).clamp(5, 24 * 60))
+}
+
+/// Collapse whitespace, trim dangling punctuation and capitalise
+fn tidy_title(text: &str) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let trimmed = collapsed.trim_matches(|c: char| c.is_whitespace() || ",;:-–".contains(c));
+    let mut chars = trimmed.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn truncate_title(text: &str) -> String {
+    let title: String = text.chars().take(MAX_TITLE_CHARS).collect();
+    if text.chars().count() > MAX_TITLE_CHARS {
+        format!("{}…", title.trim_end())
+    } else {
+        title
+    }
+}
+
+/// Classify a capture with heuristics alone. `now` is local time, used to
+/// resolve relative dates and to move already-past times to tomorrow.
+pub fn classify(text: &str, now: NaiveDateTime) -> CaptureIntent {
+    let text = text.trim();
+    let (explicit, rest) = match PREFIX.captures(text) {
+        Some(caps) => (Some(prefix_kind(&caps[1])), &text[caps.get(0).map_or(0, |m| m.end())..]),
+        None => (None, text),
+    };
+    let mut lines = rest.lines();
+    let first_line = lines.next().unwrap_or_default().trim().to_string();
+    let remainder = lines.collect::<Vec<_>>().join("\n").trim().to_string();
+
+    let mut title = first_line.clone();
+    let (mut date, implied_time) = extract_date(&mut title, now.date());
+    let time = extract_time(&mut title).or(implied_time);
+    let duration_minutes = extract_duration(&mut title).unwrap_or(DEFAULT_EVENT_MINUTES);
+    let recipients: Vec<String> = EMAIL_ADDRESS
+        .find_iter(&first_line)
+        .map(|m| m.as_str().to_lowercase())
+        .collect();
+
+    let length = first_line.chars().count();
+    let email_words = EMAIL_LEAD.is_match(&first_line);
+    let event_words = EVENT_WORDS.is_match(&first_line);
+    let task_words = TASK_WORDS.is_match(&first_line);
+    let (kind, confidence, classified_by) = if let Some(kind) = explicit {
+        (kind, 1.0, ClassifiedBy::Prefix)
+    } else if !recipients.is_empty() && (email_words || time.is_none()) {
+        (CaptureKind::EmailDraft, if email_words { 0.85 } else { 0.6 }, ClassifiedBy::Heuristic)
+    } else if time.is_some() && (event_words || !task_words) {
+        (CaptureKind::Event, if event_words { 0.85 } else { 0.6 }, ClassifiedBy::Heuristic)
+    } else if !remainder.is_empty() || length > LONG_CAPTURE_CHARS {
+        (CaptureKind::Note, 0.7, ClassifiedBy::Heuristic)
+    } else if date.is_some() || task_words {
+        (CaptureKind::Task, 0.75, ClassifiedBy::Heuristic)
+    } else if length <= MAX_TITLE_CHARS {
+        (CaptureKind::Task, 0.4, ClassifiedBy::Heuristic)
+    } else {
+        (CaptureKind::Note, 0.5, ClassifiedBy::Heuristic)
+    };
+
+    // A time with no date is the next time that clock time comes round
+    if let (None, Some(time)) = (date, time) {
+        date = Some(if time <= now.time() { now.date() + Duration::days(1) } else { now.date() });
+    }
+
+    let (title, body) = match kind {
+        CaptureKind::Note => {
+            let title = truncate_title(&first_line);
+            let body = if remainder.is_empty() { first_line.clone() } else { remainder };
+            return CaptureIntent {
+                kind,
+                title: if title.is_empty() { "Quick note".to_string() } else { title },
+                body: Some(body),
+                date: None,
+                time: None,
+                duration_minutes,
+                recipients: Vec::new(),
+                confidence,
+                classified_by,
+            };
+        }
+        CaptureKind::EmailDraft => {
+            let stripped = EMAIL_ADDRESS.replace_all(&title, " ").to_string();
+            let stripped = EMAIL_LEAD.replace(&stripped, "").to_string();
+            let stripped = SUBJECT_LEAD.replace(stripped.trim_start(), "").to_string();
+            (tidy_title(&stripped), remainder)
+        }
+        _ => (tidy_title(&title), remainder),
+    };
+
+    CaptureIntent {
+        kind,
+        title: if title.is_empty() { truncate_title(&first_line) } else { truncate_title(&title) },
+        body: if body.is_empty() { None } else { Some(body) },
+        date,
+        time,
+        duration_minutes,
+        recipients,
+        confidence,
+        classified_by,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct LlmCapture {
+    kind: String,
+    title: Option<String>,
+    body: Option<String>,
+    date: Option<String>,
+    time: Option<String>,
+    duration_minutes: Option<i64>,
+    #[serde(default)]
+    recipients: Vec<String>,
+}
+
+/// Classify a capture with the classification model. The heuristic result
+/// supplies anything the model leaves out.
+pub async fn classify_with_llm(
+    db_manager: &DatabaseManager,
+    text: &str,
+    fallback: &CaptureIntent,
+    now: NaiveDateTime,
+) -> Result<CaptureIntent> {
+    let prompt = {
+        let mut values = serde_json::Map::new();
+        values.insert("now".to_string(), now.format("%A %Y-%m-%d %H:%M").to_string().into());
+        prompt_templates::render_feature_prompt(&db_manager.get_connection()?, PROMPT_FEATURE, &values)?
+    };
+    let options = ChatOptions { temperature: Some(0.0), json: true, ..Default::default() };
+    let completion = llm_provider::complete_with(
+        db_manager,
+        LlmFeature::Classification,
+        &[ChatMessage::system(prompt), ChatMessage::user(text)],
+        &options,
+    )
+    .await?;
+    let capture: LlmCapture = serde_json::from_str(completion.content.trim()).map_err(|e| LibreOllamaError::Serialization {
+        message: format!("Model did not return a capture classification: {}", e),
+        data_type: "quick capture classification".to_string(),
+    })?;
+
+    Ok(merge_llm_capture(capture, fallback))
+}
+
+fn merge_llm_capture(capture: LlmCapture, fallback: &CaptureIntent) -> CaptureIntent {
+    let kind = match capture.kind.to_lowercase().as_str() {
+        "note" => CaptureKind::Note,
+        "event" | "calendar_event" => CaptureKind::Event,
+        "email" | "email_draft" => CaptureKind::EmailDraft,
+        _ => CaptureKind::Task,
+    };
+    let title = capture
+        .title
+        .map(|title| tidy_title(&title))
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| fallback.title.clone());
+    let recipients: Vec<String> = capture
+        .recipients
+        .into_iter()
+        .filter(|r| EMAIL_ADDRESS.is_match(r))
+        .map(|r| r.to_lowercase())
+        .collect();
+
+    CaptureIntent {
+        kind,
+        title: truncate_title(&title),
+        body: capture.body.filter(|b| !b.trim().is_empty()).or_else(|| fallback.body.clone()),
+        date: capture
+            .date
+            .and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok())
+            .or(fallback.date),
+        time: capture
+            .time
+            .and_then(|t| NaiveTime::parse_from_str(&t, "%H:%M").ok())
+            .or(fallback.time),
+        duration_minutes: capture
+            .duration_minutes
+            .map(|m| m.clamp(5, 24 * 60))
+            .unwrap_or(fallback.duration_minutes),
+        recipients: if recipients.is_empty() { fallback.recipients.clone() } else { recipients },
+        confidence: 0.8,
+        classified_by: ClassifiedBy::Llm,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> NaiveDateTime {
+        // A Monday morning
+        NaiveDate::from_ymd_opt(2024, 6, 3).unwrap().and_hms_opt(10, 0, 0).unwrap()
+    }
+
+    fn day(d: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(2024, 6, d)
+    }
+
+    #[test]
+    fn test_prefix_wins() {
+        let intent = classify("task: buy milk tomorrow", now());
+        assert_eq!(intent.kind, CaptureKind::Task);
+        assert_eq!(intent.classified_by, ClassifiedBy::Prefix);
+        assert_eq!(intent.title, "Buy milk");
+        assert_eq!(intent.date, day(4));
+    }
+
+    #[test]
+    fn test_events_with_dates_times_and_durations() {
+        let intent = classify("Lunch with Sam friday at 12:30 for 90 min", now());
+        assert_eq!(intent.kind, CaptureKind::Event);
+        assert_eq!(intent.title, "Lunch with Sam");
+        assert_eq!(intent.date, day(7));
+        assert_eq!(intent.time, NaiveTime::from_hms_opt(12, 30, 0));
+        assert_eq!(intent.duration_minutes, 90);
+
+        // A past time with no date means tomorrow; leading verbs make it a task
+        let intent = classify("Call mom at 9am", now());
+        assert_eq!(intent.kind, CaptureKind::Task);
+        assert_eq!(intent.date, day(4));
+        assert_eq!(intent.time, NaiveTime::from_hms_opt(9, 0, 0));
+    }
+
+    #[test]
+    fn test_email_drafts_and_notes() {
+        let intent = classify("email alice@example.com about the budget", now());
+        assert_eq!(intent.kind, CaptureKind::EmailDraft);
+        assert_eq!(intent.recipients, vec!["alice@example.com"]);
+        assert_eq!(intent.title, "The budget");
+
+        let intent = classify("Ideas for the offsite\n- hike\n- cooking class", now());
+        assert_eq!(intent.kind, CaptureKind::Note);
+        assert_eq!(intent.title, "Ideas for the offsite");
+        assert_eq!(intent.body.as_deref(), Some("- hike\n- cooking class"));
+
+        let intent = classify("something vague", now());
+        assert!(intent.confidence < LLM_CONFIDENCE_THRESHOLD);
+    }
+}
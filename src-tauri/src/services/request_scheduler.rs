@@ -0,0 +1,572 @@
+//! Priority scheduling for the shared Google API request budget
+//!
+//! Interactive requests (opening a message, sending, labelling) and
+//! background work (backfill, campaigns) draw on the same per-minute quota.
+//! Before a request goes out it waits here for a slot. The waiting request
+//! with the highest priority goes first, and every `AGING_STEP` spent
+//! waiting raises a request one level so background work is never starved.
+//! Background requests may only use what is left of the minute's budget
+//! after the interactive reserve, so a busy backfill cannot make a click
+//! wait.
+//!
+//! Grants are also counted per second and written to the database every
+//! few seconds by a background task, off the request path, which lets a
+//! restarted app pick up the rolling minute and the day's total.
+//!
+//! When Google throttles an account, the account backs off as a whole:
+//! waiting requests for it are passed over until the backoff ends, so
+//! concurrent workers do not keep hitting the same limit.
+//!
+//! Gmail requests go through `rate_limiter::send_scheduled`; the Tasks,
+//! Calendar, People and Drive clients build their requests with reqwest
+//! and send them with `send`, on the scheduler installed at startup.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+
+use reqwest::{RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+
+use crate::commands::rate_limiter::{RateLimitConfig, RequestPriority};
+use crate::database::operations::rate_limit_operations::{self, SecondUsage};
+use crate::database::DatabaseManager;
+use crate::services::google_backoff;
+use crate::services::task_supervisor::TaskSpec;
+
+/// Waiting this long raises a request one priority level
+const AGING_STEP: Duration = Duration::from_secs(10);
+/// How often a waiting request re-checks when nothing else wakes it
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+const WINDOW: Duration = Duration::from_secs(60);
+/// Persisted usage older than this is pruned
+const USAGE_RETENTION_SECS: i64 = 2 * 24 * 60 * 60;
+const DAY_SECS: i64 = 24 * 60 * 60;
+/// How often counted grants are written to the database
+const USAGE_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+/// Retries of a throttled request sent with `send`
+const MAX_RETRIES: u32 = 3;
+
+static SHARED: OnceLock<Arc<RequestScheduler>> = OnceLock::new();
+
+tokio::task_local! {
+    static PRIORITY_OVERRIDE: RequestPriority;
+}
+
+/// Run `future` with every request it makes scheduled at `priority`,
+/// whatever priority the requests themselves ask for. Background jobs run
+/// under `RequestPriority::Low`.
+pub async fn with_priority<F: Future>(priority: RequestPriority, future: F) -> F::Output {
+    PRIORITY_OVERRIDE.scope(priority, future).await
+}
+
+/// The priority a request is scheduled at: the task's override, if any
+pub fn effective_priority(requested: &RequestPriority) -> RequestPriority {
+    PRIORITY_OVERRIDE.try_with(|priority| priority.clone()).unwrap_or_else(|_| requested.clone())
+}
+
+/// Make `scheduler` the one `send` waits on
+pub fn install(scheduler: Arc<RequestScheduler>) {
+    if SHARED.set(scheduler).is_err() {
+        eprintln!("⚠️ [RATE-LIMIT] Request scheduler already installed");
+    }
+}
+
+/// Send a Google API request for `account_id` once the installed
+/// scheduler grants a slot. Throttled responses back the account off and
+/// are retried when `google_backoff::retry_is_safe`; the last response is
+/// returned either way, so callers report errors as they always have.
+/// Before a scheduler is installed requests go straight out.
+pub async fn send(account_id: Option<&str>, priority: RequestPriority, request: RequestBuilder) -> reqwest::Result<Response> {
+    let Some(scheduler) = SHARED.get() else {
+        return request.send().await;
+    };
+    let priority = effective_priority(&priority);
+    let method = request.try_clone().and_then(|request| request.build().ok()).map(|request| request.method().to_string());
+
+    let mut retries = 0;
+    loop {
+        scheduler.acquire(account_id, priority.clone()).await;
+        // A streamed body cannot be resent; it goes out once
+        let Some(attempt) = request.try_clone() else {
+            return request.send().await;
+        };
+        let response = attempt.send().await?;
+        let status = response.status();
+        if !status.is_client_error() && !status.is_server_error() {
+            scheduler.succeeded(account_id);
+            return Ok(response);
+        }
+
+        // Error bodies are small; read one to see whether Google is throttling
+        let headers = response.headers().clone();
+        let body = response.bytes().await?;
+        let header_map = headers
+            .iter()
+            .map(|(name, value)| (name.as_str().to_string(), value.to_str().unwrap_or("").to_string()))
+            .collect();
+        let throttle = google_backoff::throttle_for(status.as_u16(), &header_map, &String::from_utf8_lossy(&body));
+        let mut rebuilt = http::Response::new(body);
+        *rebuilt.status_mut() = status;
+        *rebuilt.headers_mut() = headers;
+        let response = Response::from(rebuilt);
+
+        let Some(throttle) = throttle else {
+            return Ok(response);
+        };
+        let delay = scheduler.throttled(account_id, throttle.retry_after);
+        let safe = method.as_deref().is_some_and(|method| google_backoff::retry_is_safe(method, &throttle));
+        if retries >= MAX_RETRIES || !safe {
+            return Ok(response);
+        }
+        retries += 1;
+        eprintln!(
+            "⏳ [RATE-LIMIT] {} {} for {}, retry {}/{} in {}ms",
+            throttle.status_code,
+            throttle.reason,
+            account_id.unwrap_or("app"),
+            retries,
+            MAX_RETRIES,
+            delay.as_millis()
+        );
+    }
+}
+
+fn level(priority: &RequestPriority) -> u64 {
+    match priority {
+        RequestPriority::Low => 0,
+        RequestPriority::Medium => 1,
+        RequestPriority::High => 2,
+        RequestPriority::Critical => 3,
+    }
+}
+
+/// Low priority is background traffic; everything else is interactive
+fn is_background(priority: &RequestPriority) -> bool {
+    matches!(priority, RequestPriority::Low)
+}
+
+struct Waiter {
+    ticket: u64,
+    /// Empty for requests not made for an account
+    account: String,
+    priority: RequestPriority,
+    enqueued: Instant,
+}
+
+impl Waiter {
+    /// Priority level raised by time spent waiting
+    fn rank(&self, now: Instant) -> u64 {
+        level(&self.priority) + (now.duration_since(self.enqueued).as_secs() / AGING_STEP.as_secs())
+    }
+}
+
+struct Sent {
+    at: Instant,
+    background: bool,
+}
+
+struct Backoff {
+    until: Instant,
+    /// Throttled responses in a row
+    failures: u32,
+}
+
+#[derive(Default)]
+struct State {
+    next_ticket: u64,
+    waiting: Vec<Waiter>,
+    /// Grants in the last minute, oldest first
+    sent: VecDeque<Sent>,
+    granted: u64,
+    total_wait_ms: u64,
+    /// Grants before this run, from the persisted usage
+    restored_today: u64,
+    backoff: HashMap<String, Backoff>,
+    /// Grants not written to the usage store yet, per epoch second
+    unrecorded: BTreeMap<i64, SecondUsage>,
+}
+
+impl State {
+    fn prune(&mut self, now: Instant) {
+        while self.sent.front().is_some_and(|sent| now.duration_since(sent.at) >= WINDOW) {
+            self.sent.pop_front();
+        }
+    }
+
+    fn backing_off(&self, account: &str, now: Instant) -> bool {
+        self.backoff.get(account).is_some_and(|backoff| backoff.until > now)
+    }
+}
+
+/// Live view of the scheduler for the settings panel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchedulerMetrics {
+    pub waiting_critical: usize,
+    pub waiting_high: usize,
+    pub waiting_medium: usize,
+    pub waiting_low: usize,
+    /// How long the longest-waiting request has waited
+    pub oldest_wait_ms: u64,
+    pub requests_last_minute: usize,
+    pub background_last_minute: usize,
+    pub requests_per_minute: usize,
+    /// Share of the minute background requests may use
+    pub background_per_minute: usize,
+    /// Requests in the last 24 hours, including before a restart
+    pub requests_last_day: u64,
+    /// Requests granted since the app started
+    pub granted: u64,
+    pub average_wait_ms: u64,
+    /// Accounts waiting out a throttled response
+    pub accounts_backing_off: usize,
+}
+
+pub struct RequestScheduler {
+    requests_per_minute: usize,
+    requests_per_second: usize,
+    background_per_minute: usize,
+    state: Mutex<State>,
+    notify: Notify,
+    usage_store: Option<Arc<DatabaseManager>>,
+}
+
+/// A place in the queue; leaving before the grant (e.g. the request was
+/// cancelled) gives the place up
+struct Ticket<'a> {
+    scheduler: &'a RequestScheduler,
+    id: u64,
+    granted: bool,
+}
+
+impl Drop for Ticket<'_> {
+    fn drop(&mut self) {
+        if !self.granted {
+            self.scheduler.lock().waiting.retain(|waiter| waiter.ticket != self.id);
+            self.scheduler.notify.notify_waiters();
+        }
+    }
+}
+
+impl RequestScheduler {
+    /// A scheduler that keeps usage in memory only
+    pub fn new(config: &RateLimitConfig) -> Self {
+        let per_minute = config.requests_per_minute.max(1) as usize;
+        let reserve = config.interactive_reserve_fraction.clamp(0.0, 1.0);
+        Self {
+            requests_per_minute: per_minute,
+            requests_per_second: config.requests_per_second.max(1) as usize,
+            background_per_minute: ((per_minute as f64) * (1.0 - reserve)).floor().max(1.0) as usize,
+            state: Mutex::new(State::default()),
+            notify: Notify::new(),
+            usage_store: None,
+        }
+    }
+
+    /// A scheduler that persists usage, starting from what was recorded
+    /// in the last minute and day
+    pub fn with_usage_store(config: &RateLimitConfig, db_manager: Arc<DatabaseManager>) -> Self {
+        let mut scheduler = Self::new(config);
+        if let Err(e) = scheduler.restore(&db_manager) {
+            eprintln!("⚠️ [RATE-LIMIT] Could not restore request usage: {}", e);
+        }
+        scheduler.usage_store = Some(db_manager);
+        scheduler
+    }
+
+    fn restore(&mut self, db_manager: &DatabaseManager) -> anyhow::Result<()> {
+        let conn = db_manager.get_connection()?;
+        let now_secs = chrono::Utc::now().timestamp();
+        rate_limit_operations::prune_before(&conn, now_secs - USAGE_RETENTION_SECS)?;
+
+        let now = Instant::now();
+        let state = self.state.get_mut().unwrap_or_else(|e| e.into_inner());
+        for usage in rate_limit_operations::get_usage_since(&conn, now_secs - WINDOW.as_secs() as i64 + 1)? {
+            let Some(at) = now.checked_sub(Duration::from_secs((now_secs - usage.second).max(0) as u64)) else {
+                continue;
+            };
+            let sent = std::iter::repeat_with(|| Sent { at, background: false }).take(usage.interactive as usize);
+            let background = std::iter::repeat_with(|| Sent { at, background: true }).take(usage.background as usize);
+            state.sent.extend(sent.chain(background));
+        }
+        state.restored_today = rate_limit_operations::count_since(&conn, now_secs - DAY_SECS)?;
+        Ok(())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Wait until a request at `priority` for `account_id` may be sent
+    pub async fn acquire(&self, account_id: Option<&str>, priority: RequestPriority) {
+        let mut ticket = {
+            let mut state = self.lock();
+            let id = state.next_ticket;
+            state.next_ticket += 1;
+            state.waiting.push(Waiter {
+                ticket: id,
+                account: account_id.unwrap_or_default().to_string(),
+                priority,
+                enqueued: Instant::now(),
+            });
+            Ticket { scheduler: self, id, granted: false }
+        };
+
+        loop {
+            // Registered before checking, so a grant in between still wakes us
+            let notified = self.notify.notified();
+            if self.try_grant(ticket.id).is_some() {
+                ticket.granted = true;
+                self.notify.notify_waiters();
+                return;
+            }
+            tokio::select! {
+                _ = notified => {}
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            }
+        }
+    }
+
+    /// Grant the slot if the window has room and `ticket` is the best
+    /// waiter allowed to use it
+    fn try_grant(&self, ticket: u64) -> Option<()> {
+        let now = Instant::now();
+        let mut state = self.lock();
+        state.prune(now);
+
+        let last_second = state.sent.iter().rev().take_while(|sent| now.duration_since(sent.at) < Duration::from_secs(1)).count();
+        if state.sent.len() >= self.requests_per_minute || last_second >= self.requests_per_second {
+            return None;
+        }
+        let background_used = state.sent.iter().filter(|sent| sent.background).count();
+
+        let best = state
+            .waiting
+            .iter()
+            .filter(|waiter| !is_background(&waiter.priority) || background_used < self.background_per_minute)
+            .filter(|waiter| !state.backing_off(&waiter.account, now))
+            .max_by_key(|waiter| (waiter.rank(now), std::cmp::Reverse(waiter.ticket)))?;
+        if best.ticket != ticket {
+            return None;
+        }
+
+        let position = state.waiting.iter().position(|waiter| waiter.ticket == ticket)?;
+        let waiter = state.waiting.swap_remove(position);
+        let background = is_background(&waiter.priority);
+        state.sent.push_back(Sent { at: now, background });
+        state.granted += 1;
+        state.total_wait_ms += now.duration_since(waiter.enqueued).as_millis() as u64;
+        if self.usage_store.is_some() {
+            let second = chrono::Utc::now().timestamp();
+            let usage = state.unrecorded.entry(second).or_insert(SecondUsage { second, interactive: 0, background: 0 });
+            if background {
+                usage.background += 1;
+            } else {
+                usage.interactive += 1;
+            }
+        }
+        Some(())
+    }
+
+    /// Put the account into backoff after a throttled response, for
+    /// `retry_after` when Google said how long, and exponentially longer
+    /// for each throttle in a row otherwise. Returns how long until the
+    /// account may send again.
+    pub fn throttled(&self, account_id: Option<&str>, retry_after: Option<Duration>) -> Duration {
+        let account = account_id.unwrap_or_default();
+        let now = Instant::now();
+        let remaining = {
+            let mut state = self.lock();
+            let backoff = state
+                .backoff
+                .entry(account.to_string())
+                .or_insert(Backoff { until: now, failures: 0 });
+            backoff.failures += 1;
+            let delay = retry_after.unwrap_or_else(|| google_backoff::exponential_backoff(backoff.failures));
+            // Another worker may already have backed off for longer
+            backoff.until = backoff.until.max(now + delay);
+            backoff.until.duration_since(now)
+        };
+        self.notify.notify_waiters();
+        eprintln!(
+            "⏳ [RATE-LIMIT] Backing off {} for {}ms",
+            if account.is_empty() { "app" } else { account },
+            remaining.as_millis()
+        );
+        remaining
+    }
+
+    /// Clear the account's backoff after a successful response
+    pub fn succeeded(&self, account_id: Option<&str>) {
+        let now = Instant::now();
+        let mut state = self.lock();
+        let account = account_id.unwrap_or_default();
+        if state.backoff.get(account).is_some_and(|backoff| backoff.until <= now) {
+            state.backoff.remove(account);
+        }
+    }
+
+    /// Write the grants counted since the last flush to the usage store.
+    /// They are kept for the next flush if the write fails.
+    pub fn flush_usage(&self) -> anyhow::Result<()> {
+        let Some(db_manager) = &self.usage_store else {
+            return Ok(());
+        };
+        let unrecorded = std::mem::take(&mut self.lock().unrecorded);
+        if unrecorded.is_empty() {
+            return Ok(());
+        }
+        let usage: Vec<SecondUsage> = unrecorded.values().copied().collect();
+        let result = db_manager.get_connection().and_then(|conn| rate_limit_operations::record_usage(&conn, &usage));
+        if result.is_err() {
+            let mut state = self.lock();
+            for (second, usage) in unrecorded {
+                let kept = state.unrecorded.entry(second).or_insert(SecondUsage { second, interactive: 0, background: 0 });
+                kept.interactive += usage.interactive;
+                kept.background += usage.background;
+            }
+        }
+        result
+    }
+
+    /// Background task writing counted grants to the usage store;
+    /// shutdown writes what is left
+    pub fn usage_task(self: &Arc<Self>) -> TaskSpec {
+        let scheduler = self.clone();
+        TaskSpec::new("request_usage", move || {
+            let scheduler = scheduler.clone();
+            async move { tokio::task::spawn_blocking(move || scheduler.flush_usage()).await? }
+        })
+        .every(|| USAGE_FLUSH_INTERVAL)
+        .first_run_after(USAGE_FLUSH_INTERVAL)
+        .finish_before_exit()
+    }
+
+    /// Requests granted in the last minute
+    pub fn requests_in_last_minute(&self) -> usize {
+        let mut state = self.lock();
+        state.prune(Instant::now());
+        state.sent.len()
+    }
+
+    pub fn metrics(&self) -> SchedulerMetrics {
+        let now = Instant::now();
+        let (mut metrics, since_start, unrecorded) = {
+            let mut state = self.lock();
+            state.prune(now);
+            let unrecorded: u64 = state.unrecorded.values().map(|usage| (usage.interactive + usage.background) as u64).sum();
+            (self.snapshot(&state, now), state.restored_today + state.granted, unrecorded)
+        };
+        // The store is read without holding the state lock
+        metrics.requests_last_day = self.requests_last_day().map_or(since_start, |recorded| recorded + unrecorded);
+        metrics
+    }
+
+    /// Metrics from the in-memory state, counting the day since start only
+    fn snapshot(&self, state: &State, now: Instant) -> SchedulerMetrics {
+        let waiting = |level_wanted: u64| state.waiting.iter().filter(|w| level(&w.priority) == level_wanted).count();
+        SchedulerMetrics {
+            waiting_critical: waiting(3),
+            waiting_high: waiting(2),
+            waiting_medium: waiting(1),
+            waiting_low: waiting(0),
+            oldest_wait_ms: state
+                .waiting
+                .iter()
+                .map(|w| now.duration_since(w.enqueued).as_millis() as u64)
+                .max()
+                .unwrap_or(0),
+            requests_last_minute: state.sent.len(),
+            background_last_minute: state.sent.iter().filter(|sent| sent.background).count(),
+            requests_per_minute: self.requests_per_minute,
+            background_per_minute: self.background_per_minute,
+            requests_last_day: state.restored_today + state.granted,
+            granted: state.granted,
+            average_wait_ms: state.total_wait_ms.checked_div(state.granted).unwrap_or(0),
+            accounts_backing_off: state.backoff.values().filter(|backoff| backoff.until > now).count(),
+        }
+    }
+
+    fn requests_last_day(&self) -> Option<u64> {
+        let conn = self.usage_store.as_ref()?.get_connection().ok()?;
+        rate_limit_operations::count_since(&conn, chrono::Utc::now().timestamp() - DAY_SECS).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduler(per_minute: u32, reserve: f64) -> Arc<RequestScheduler> {
+        Arc::new(RequestScheduler::new(&RateLimitConfig {
+            requests_per_minute: per_minute,
+            requests_per_second: 100,
+            interactive_reserve_fraction: reserve,
+            ..RateLimitConfig::default()
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_background_stays_below_reserve() {
+        let scheduler = scheduler(4, 0.5);
+        scheduler.acquire(None, RequestPriority::Low).await;
+        scheduler.acquire(None, RequestPriority::Low).await;
+
+        // The background share is used up, interactive requests still pass
+        let blocked = tokio::time::timeout(Duration::from_millis(50), scheduler.acquire(None, RequestPriority::Low)).await;
+        assert!(blocked.is_err());
+        scheduler.acquire(None, RequestPriority::High).await;
+
+        let metrics = scheduler.metrics();
+        assert_eq!((metrics.requests_last_minute, metrics.background_last_minute), (3, 2));
+        assert_eq!(metrics.waiting_low, 0, "a cancelled request leaves the queue");
+    }
+
+    #[tokio::test]
+    async fn test_backoff_holds_only_the_throttled_account() {
+        let scheduler = scheduler(100, 0.5);
+        let delay = scheduler.throttled(Some("a@example.com"), Some(Duration::from_secs(30)));
+        assert!(delay > Duration::from_secs(29));
+        // A shorter backoff from a concurrent worker does not cut it short
+        assert!(scheduler.throttled(Some("a@example.com"), Some(Duration::from_secs(1))) > Duration::from_secs(29));
+
+        let blocked =
+            tokio::time::timeout(Duration::from_millis(50), scheduler.acquire(Some("a@example.com"), RequestPriority::Critical)).await;
+        assert!(blocked.is_err());
+        scheduler.acquire(Some("b@example.com"), RequestPriority::Low).await;
+        assert_eq!(scheduler.metrics().accounts_backing_off, 1);
+    }
+
+    #[tokio::test]
+    async fn test_usage_is_saved_in_batches() {
+        let db_path = std::env::temp_dir().join(format!("libreollama_scheduler_{}.db", uuid::Uuid::new_v4()));
+        let db_manager = Arc::new(DatabaseManager::open_at(db_path.clone()).unwrap());
+        db_manager.run_migrations().await.unwrap();
+        let scheduler = RequestScheduler::with_usage_store(&RateLimitConfig::default(), db_manager.clone());
+
+        scheduler.acquire(None, RequestPriority::High).await;
+        scheduler.acquire(None, RequestPriority::Low).await;
+        let conn = db_manager.get_connection().unwrap();
+        assert_eq!(rate_limit_operations::count_since(&conn, 0).unwrap(), 0, "nothing is written per request");
+        assert_eq!(scheduler.metrics().requests_last_day, 2);
+
+        scheduler.flush_usage().unwrap();
+        assert_eq!(rate_limit_operations::count_since(&conn, 0).unwrap(), 2);
+        assert_eq!(scheduler.metrics().requests_last_day, 2);
+
+        drop(conn);
+        db_manager.close();
+        let _ = std::fs::remove_file(db_path);
+    }
+
+    #[test]
+    fn test_waiting_raises_rank() {
+        let now = Instant::now();
+        let waiter = |ticket, priority, enqueued| Waiter { ticket, account: String::new(), priority, enqueued };
+        let old = waiter(0, RequestPriority::Low, now - AGING_STEP * 2);
+        let fresh = waiter(1, RequestPriority::Medium, now);
+        assert!(old.rank(now) > fresh.rank(now));
+    }
+}
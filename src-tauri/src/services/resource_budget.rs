@@ -0,0 +1,366 @@
+//! Memory budget for in-memory caches
+//!
+//! Parsed messages, embeddings and proxied images are kept in memory in
+//! `BudgetedCache`s, each given a share of one total budget. Entries report
+//! an approximate size through `ApproxSize`, and a cache over its share
+//! evicts its least recently used entries. The total comes from
+//! `memory.cache_budget_mb`, or, when that is 0, from the machine's memory,
+//! so low-RAM machines keep less cached. `usage` reports each cache for
+//! `get_resource_usage`. The caches are emptied when the app locks or is
+//! wiped, and an account's entries go when the account is removed.
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::mem::size_of;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::MemoryConfig;
+use crate::services::gmail::api_service::{EmailAddress, ProcessedGmailMessage};
+use crate::services::image_proxy::ProxiedImage;
+
+const MIB: usize = 1024 * 1024;
+/// Share of system memory the caches may use when no budget is configured
+const AUTOMATIC_SHARE: f64 = 0.02;
+const MIN_AUTOMATIC_BUDGET: usize = 32 * MIB;
+const MAX_AUTOMATIC_BUDGET: usize = 512 * MIB;
+
+/// Configured total in bytes; 0 until configured, or when set to automatic
+static CONFIGURED_BUDGET: AtomicUsize = AtomicUsize::new(0);
+
+lazy_static::lazy_static! {
+    static ref AUTOMATIC_BUDGET: usize = {
+        let mut system = sysinfo::System::new();
+        system.refresh_memory();
+        let share = (system.total_memory() as f64 * AUTOMATIC_SHARE) as usize;
+        share.clamp(MIN_AUTOMATIC_BUDGET, MAX_AUTOMATIC_BUDGET)
+    };
+
+    /// Parsed message bodies, by account and message ID
+    pub static ref PARSED_MESSAGES: BudgetedCache<(String, String), ProcessedGmailMessage> =
+        BudgetedCache::new("parsed_messages", 0.5);
+    /// Embedding vectors, by provider, model and input text
+    pub static ref EMBEDDINGS: BudgetedCache<String, Vec<f32>> = BudgetedCache::new("embeddings", 0.2);
+    /// Proxied remote images, by URL, in front of the disk cache
+    pub static ref IMAGES: BudgetedCache<String, ProxiedImage> = BudgetedCache::new("images", 0.3);
+}
+
+/// Approximate heap and inline size of a cached value, in bytes
+pub trait ApproxSize {
+    fn approx_size(&self) -> usize;
+}
+
+impl ApproxSize for String {
+    fn approx_size(&self) -> usize {
+        size_of::<String>() + self.capacity()
+    }
+}
+
+impl ApproxSize for Vec<f32> {
+    fn approx_size(&self) -> usize {
+        size_of::<Vec<f32>>() + self.capacity() * size_of::<f32>()
+    }
+}
+
+fn optional(text: &Option<String>) -> usize {
+    text.as_ref().map_or(0, String::approx_size)
+}
+
+fn address(address: &EmailAddress) -> usize {
+    address.email.approx_size() + optional(&address.name)
+}
+
+impl ApproxSize for ProcessedGmailMessage {
+    fn approx_size(&self) -> usize {
+        let content = &self.parsed_content;
+        let addresses: usize = [&content.to, &content.cc, &content.bcc].into_iter().flatten().map(address).sum();
+        let headers: usize = content.headers.iter().map(|(name, value)| name.approx_size() + value.approx_size()).sum();
+        let attachments: usize = content
+            .attachments
+            .iter()
+            .map(|attachment| {
+                attachment.id.approx_size()
+                    + optional(&attachment.filename)
+                    + attachment.data.as_ref().map_or(0, Vec::len)
+            })
+            .sum();
+        size_of::<Self>()
+            + self.id.approx_size()
+            + self.thread_id.approx_size()
+            + self.labels.iter().map(String::approx_size).sum::<usize>()
+            + optional(&self.snippet)
+            + optional(&content.subject)
+            + optional(&content.body_text)
+            + optional(&content.body_html)
+            + address(&content.from)
+            + addresses
+            + headers
+            + attachments
+    }
+}
+
+impl ApproxSize for ProxiedImage {
+    fn approx_size(&self) -> usize {
+        size_of::<Self>() + self.data_url.approx_size() + self.content_type.approx_size()
+    }
+}
+
+/// What one cache holds, for `get_resource_usage`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheUsage {
+    pub name: String,
+    pub entries: usize,
+    pub bytes: usize,
+    pub budget_bytes: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    pub budget_bytes: usize,
+    /// Whether the budget was sized from system memory
+    pub automatic_budget: bool,
+    pub used_bytes: usize,
+    pub caches: Vec<CacheUsage>,
+}
+
+/// Total budget across all caches, in bytes
+pub fn total_budget() -> usize {
+    match CONFIGURED_BUDGET.load(Ordering::Relaxed) {
+        0 => *AUTOMATIC_BUDGET,
+        configured => configured,
+    }
+}
+
+/// Apply the configured budget, evicting from caches now over their share
+pub fn apply(config: &MemoryConfig) {
+    CONFIGURED_BUDGET.store(config.cache_budget_mb as usize * MIB, Ordering::Relaxed);
+    PARSED_MESSAGES.shrink();
+    EMBEDDINGS.shrink();
+    IMAGES.shrink();
+}
+
+/// Empty every cache, dropping the mail content they hold
+pub fn clear() {
+    PARSED_MESSAGES.clear();
+    EMBEDDINGS.clear();
+    IMAGES.clear();
+}
+
+/// Drop what is cached from an account's mail. Embeddings and images are
+/// not keyed by account, so they are emptied entirely.
+pub fn clear_account(account_id: &str) {
+    PARSED_MESSAGES.retain(|(account, _)| account != account_id);
+    EMBEDDINGS.clear();
+    IMAGES.clear();
+}
+
+pub fn usage() -> ResourceUsage {
+    let caches = vec![PARSED_MESSAGES.usage(), EMBEDDINGS.usage(), IMAGES.usage()];
+    ResourceUsage {
+        budget_bytes: total_budget(),
+        automatic_budget: CONFIGURED_BUDGET.load(Ordering::Relaxed) == 0,
+        used_bytes: caches.iter().map(|cache| cache.bytes).sum(),
+        caches,
+    }
+}
+
+struct Entry<V> {
+    value: V,
+    size: usize,
+    last_used: u64,
+}
+
+/// Least recently used eviction over a byte budget
+struct Lru<K, V> {
+    entries: HashMap<K, Entry<V>>,
+    /// `last_used` tick to key, oldest first
+    order: BTreeMap<u64, K>,
+    tick: u64,
+    bytes: usize,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone + ApproxSize> Lru<K, V> {
+    fn new() -> Self {
+        Self { entries: HashMap::new(), order: BTreeMap::new(), tick: 0, bytes: 0, hits: 0, misses: 0, evictions: 0 }
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn get(&mut self, key: &K) -> Option<V> {
+        let tick = self.next_tick();
+        let Some(entry) = self.entries.get_mut(key) else {
+            self.misses += 1;
+            return None;
+        };
+        self.hits += 1;
+        let key = self.order.remove(&entry.last_used).expect("every entry is ordered");
+        entry.last_used = tick;
+        self.order.insert(tick, key);
+        Some(entry.value.clone())
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let entry = self.entries.remove(key)?;
+        self.order.remove(&entry.last_used);
+        self.bytes -= entry.size;
+        Some(entry.value)
+    }
+
+    /// A value larger than the whole budget is not kept
+    fn insert(&mut self, key: K, value: V, budget: usize) {
+        self.remove(&key);
+        let size = value.approx_size();
+        if size > budget {
+            return;
+        }
+        let tick = self.next_tick();
+        self.order.insert(tick, key.clone());
+        self.entries.insert(key, Entry { value, size, last_used: tick });
+        self.bytes += size;
+        self.shrink(budget);
+    }
+
+    fn shrink(&mut self, budget: usize) {
+        while self.bytes > budget {
+            let Some((_, key)) = self.order.pop_first() else {
+                break;
+            };
+            if let Some(entry) = self.entries.remove(&key) {
+                self.bytes -= entry.size;
+                self.evictions += 1;
+            }
+        }
+    }
+}
+
+/// An in-memory cache held to its share of the total budget
+pub struct BudgetedCache<K, V> {
+    name: &'static str,
+    share: f64,
+    lru: Mutex<Lru<K, V>>,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone + ApproxSize> BudgetedCache<K, V> {
+    pub fn new(name: &'static str, share: f64) -> Self {
+        Self { name, share, lru: Mutex::new(Lru::new()) }
+    }
+
+    fn lru(&self) -> std::sync::MutexGuard<'_, Lru<K, V>> {
+        self.lru.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn budget(&self) -> usize {
+        (total_budget() as f64 * self.share) as usize
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.lru().get(key)
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        let budget = self.budget();
+        self.lru().insert(key, value, budget);
+    }
+
+    pub fn remove(&self, key: &K) -> Option<V> {
+        self.lru().remove(key)
+    }
+
+    pub fn retain(&self, keep: impl Fn(&K) -> bool) {
+        let mut lru = self.lru();
+        let dropped: Vec<K> = lru.entries.keys().filter(|key| !keep(key)).cloned().collect();
+        for key in &dropped {
+            lru.remove(key);
+        }
+    }
+
+    pub fn clear(&self) {
+        self.retain(|_| false);
+    }
+
+    fn shrink(&self) {
+        let budget = self.budget();
+        self.lru().shrink(budget);
+    }
+
+    pub fn usage(&self) -> CacheUsage {
+        let lru = self.lru();
+        CacheUsage {
+            name: self.name.to_string(),
+            entries: lru.entries.len(),
+            bytes: lru.bytes,
+            budget_bytes: self.budget(),
+            hits: lru.hits,
+            misses: lru.misses,
+            evictions: lru.evictions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(bytes: usize) -> String {
+        "x".repeat(bytes - size_of::<String>())
+    }
+
+    #[test]
+    fn test_least_recently_used_is_evicted_first() {
+        let mut lru = Lru::new();
+        lru.insert("a", text(100), 300);
+        lru.insert("b", text(100), 300);
+        lru.insert("c", text(100), 300);
+        assert!(lru.get(&"a").is_some());
+
+        lru.insert("d", text(100), 300);
+        assert!(lru.get(&"b").is_none());
+        assert!(lru.get(&"a").is_some() && lru.get(&"c").is_some() && lru.get(&"d").is_some());
+        assert_eq!((lru.bytes, lru.evictions), (300, 1));
+        assert_eq!((lru.hits, lru.misses), (4, 1));
+    }
+
+    #[test]
+    fn test_budget_is_enforced() {
+        let mut lru = Lru::new();
+        lru.insert("small", text(100), 300);
+        // Larger than the whole budget: not kept, nothing evicted for it
+        lru.insert("huge", text(400), 300);
+        assert!(lru.get(&"huge").is_none());
+        assert_eq!(lru.bytes, 100);
+
+        // Replacing an entry accounts for its new size
+        lru.insert("small", text(200), 300);
+        assert_eq!(lru.bytes, 200);
+
+        lru.insert("other", text(100), 300);
+        lru.shrink(150);
+        assert_eq!(lru.entries.len(), 1);
+        assert!(lru.bytes <= 150);
+        assert!(lru.remove(&"other").is_some());
+        assert_eq!(lru.bytes, 0);
+    }
+
+    #[test]
+    fn test_caches_share_the_total_budget() {
+        let total = total_budget();
+        assert!((MIN_AUTOMATIC_BUDGET..=MAX_AUTOMATIC_BUDGET).contains(&total));
+        let shares = PARSED_MESSAGES.budget() + EMBEDDINGS.budget() + IMAGES.budget();
+        assert!(shares <= total && shares + 3 >= total);
+
+        let usage = usage();
+        assert_eq!(usage.caches.len(), 3);
+        assert_eq!(usage.used_bytes, usage.caches.iter().map(|cache| cache.bytes).sum::<usize>());
+    }
+}
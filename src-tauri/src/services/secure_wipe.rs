@@ -0,0 +1,222 @@
+//! Secure wipe
+//!
+//! Destroys everything the app keeps on this machine, for users handing on
+//! or retiring a computer: the database with its WAL and journal files
+//! (mail, notes, chats, encrypted account tokens, the secure notes vault),
+//! the attachment, cache, log and temp directories, and the configuration
+//! and `.env` files. Background work is stopped and the database closed
+//! first, and the in-memory caches emptied. Each file is overwritten with
+//! random bytes and synced before it is deleted.
+//!
+//! Account tokens are kept encrypted in the database rather than the OS
+//! keyring, so there are no keyring entries to remove. Overwriting cannot
+//! reach copies an SSD's wear levelling or a filesystem snapshot has kept
+//! elsewhere; full-disk encryption is the answer for those.
+//!
+//! Wiping takes two steps: `request` issues a single-use token that
+//! expires after two minutes, and `confirm` accepts only that token with
+//! the exact confirmation phrase.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{LibreOllamaError, Result};
+
+/// Typed by the user to confirm
+pub const CONFIRMATION_PHRASE: &str = "DELETE ALL MY DATA";
+const TOKEN_LIFETIME: Duration = Duration::from_secs(120);
+const CHUNK_BYTES: usize = 1024 * 1024;
+/// Files SQLite keeps beside the database
+const DATABASE_SIDECARS: &[&str] = &["-wal", "-shm", "-journal"];
+
+/// What the user must send back to wipe
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WipeChallenge {
+    pub token: String,
+    pub confirmation_phrase: String,
+    pub expires_in_seconds: u64,
+    /// Files and directories that will be destroyed
+    pub targets: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WipeReport {
+    pub files_shredded: usize,
+    pub bytes_overwritten: u64,
+    /// Paths that could not be destroyed, with the reason
+    pub failures: Vec<String>,
+}
+
+/// Everything a wipe destroys: the database and its sidecar files, then
+/// the data directories and other files
+pub fn wipe_targets(db_path: &Path, others: &[PathBuf]) -> Vec<PathBuf> {
+    let mut targets = vec![db_path.to_path_buf()];
+    for suffix in DATABASE_SIDECARS {
+        let mut sidecar = db_path.as_os_str().to_os_string();
+        sidecar.push(suffix);
+        targets.push(PathBuf::from(sidecar));
+    }
+    for other in others {
+        if !targets.contains(other) {
+            targets.push(other.clone());
+        }
+    }
+    targets
+}
+
+/// Overwrite a file with random bytes, sync it and delete it
+fn shred_file(path: &Path) -> std::io::Result<u64> {
+    let length = std::fs::metadata(path)?.len();
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    let mut chunk = vec![0u8; CHUNK_BYTES];
+    let mut remaining = length;
+    while remaining > 0 {
+        let size = remaining.min(CHUNK_BYTES as u64) as usize;
+        OsRng.fill_bytes(&mut chunk[..size]);
+        file.write_all(&chunk[..size])?;
+        remaining -= size as u64;
+    }
+    file.sync_all()?;
+    drop(file);
+
+    // A random name so the directory entry gives nothing away either
+    let renamed = path.with_file_name(hex::encode(rand::random::<[u8; 8]>()));
+    let removed = std::fs::rename(path, &renamed).map(|_| renamed).unwrap_or_else(|_| path.to_path_buf());
+    std::fs::remove_file(&removed)?;
+    Ok(length)
+}
+
+fn shred_path(path: &Path, report: &mut WipeReport) {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return;
+    };
+    // Links are removed, never followed out of the data directories
+    if metadata.file_type().is_symlink() {
+        if let Err(e) = std::fs::remove_file(path) {
+            report.failures.push(format!("{}: {}", path.display(), e));
+        }
+        return;
+    }
+    if metadata.is_dir() {
+        match std::fs::read_dir(path) {
+            Ok(entries) => {
+                for entry in entries.filter_map(|entry| entry.ok()) {
+                    shred_path(&entry.path(), report);
+                }
+            }
+            Err(e) => report.failures.push(format!("{}: {}", path.display(), e)),
+        }
+        if let Err(e) = std::fs::remove_dir(path) {
+            report.failures.push(format!("{}: {}", path.display(), e));
+        }
+        return;
+    }
+    match shred_file(path) {
+        Ok(bytes) => {
+            report.files_shredded += 1;
+            report.bytes_overwritten += bytes;
+        }
+        Err(e) => report.failures.push(format!("{}: {}", path.display(), e)),
+    }
+}
+
+/// Shred every target that exists
+pub fn shred(targets: &[PathBuf]) -> WipeReport {
+    let mut report = WipeReport::default();
+    for target in targets {
+        shred_path(target, &mut report);
+    }
+    report
+}
+
+/// Pending confirmation token, managed as Tauri state
+#[derive(Default)]
+pub struct SecureWipe {
+    pending: Mutex<Option<(String, Instant)>>,
+}
+
+impl SecureWipe {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Issue a token for `confirm`, replacing any earlier one
+    pub fn request(&self, targets: &[PathBuf]) -> WipeChallenge {
+        let token = hex::encode(rand::random::<[u8; 16]>());
+        *self.pending.lock().unwrap_or_else(|e| e.into_inner()) = Some((token.clone(), Instant::now()));
+        WipeChallenge {
+            token,
+            confirmation_phrase: CONFIRMATION_PHRASE.to_string(),
+            expires_in_seconds: TOKEN_LIFETIME.as_secs(),
+            targets: targets.iter().map(|target| target.display().to_string()).collect(),
+        }
+    }
+
+    /// Use up the pending token; fails unless both token and phrase match
+    pub fn confirm(&self, token: &str, phrase: &str) -> Result<()> {
+        let pending = self.pending.lock().unwrap_or_else(|e| e.into_inner()).take();
+        let valid = pending.is_some_and(|(expected, issued)| expected == token && issued.elapsed() < TOKEN_LIFETIME);
+        if !valid {
+            return Err(LibreOllamaError::PermissionDenied {
+                message: "The wipe token is missing, used or expired; request a new one".to_string(),
+            });
+        }
+        if phrase.trim() != CONFIRMATION_PHRASE {
+            return Err(LibreOllamaError::PermissionDenied {
+                message: format!("Type \"{}\" to confirm", CONFIRMATION_PHRASE),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+
+    #[test]
+    fn test_confirmation_is_single_use() {
+        let wipe = SecureWipe::new();
+        assert!(wipe.confirm("anything", CONFIRMATION_PHRASE).is_err());
+
+        let challenge = wipe.request(&[]);
+        assert!(wipe.confirm(&challenge.token, "delete all my data").is_err());
+        // A failed attempt uses the token up
+        assert!(wipe.confirm(&challenge.token, CONFIRMATION_PHRASE).is_err());
+
+        let challenge = wipe.request(&[]);
+        assert!(wipe.confirm(&challenge.token, CONFIRMATION_PHRASE).is_ok());
+        assert!(wipe.confirm(&challenge.token, CONFIRMATION_PHRASE).is_err());
+    }
+
+    #[test]
+    fn test_shred_removes_files_and_directories() {
+        let root = std::env::temp_dir().join(format!("libreollama-wipe-{}", uuid::Uuid::new_v4()));
+        let attachments = root.join("attachments");
+        std::fs::create_dir_all(attachments.join("nested")).unwrap();
+        let db_path = root.join("database.db");
+        std::fs::write(&db_path, vec![7u8; 3000]).unwrap();
+        std::fs::write(root.join("database.db-wal"), b"wal").unwrap();
+        std::fs::write(attachments.join("nested").join("invoice.pdf"), b"%PDF").unwrap();
+        File::create(root.join("keep.txt")).unwrap();
+
+        let targets = wipe_targets(&db_path, &[attachments.clone(), attachments.clone()]);
+        assert_eq!(targets.len(), 5);
+        let report = shred(&targets);
+
+        assert!(report.failures.is_empty(), "{:?}", report.failures);
+        assert_eq!(report.files_shredded, 3);
+        assert_eq!(report.bytes_overwritten, 3007);
+        assert!(!db_path.exists());
+        assert!(!attachments.exists());
+        assert!(root.join("keep.txt").exists());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,212 @@
+//! Lazy service initialization
+//!
+//! Setup only opens the database and manages the services that commands
+//! and the app lock need from the first frame, so the window appears
+//! without waiting on migrations. A startup task then migrates the
+//! database, starts the background loops and announces `backend://ready`.
+//!
+//! Services that no background loop depends on are built on first use
+//! through `ServiceRegistry`. Each accessor waits for startup to finish, so
+//! nothing reads tables that are still being migrated, and `gate` holds
+//! back every command but the few the first frame needs in the same way.
+//! When migration fails, waiting ends with the migration error instead.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use tauri::ipc::Invoke;
+use tauri::{Manager, Runtime};
+use tokio::sync::{watch, OnceCell};
+
+use crate::database::DatabaseManager;
+use crate::errors::{CommandError, LibreOllamaError, Result};
+use crate::services::canvas_assistant::CanvasAssistant;
+use crate::services::chat_service::ChatService;
+use crate::services::events::BackendReadyEvent;
+use crate::services::prompt_templates::PromptTemplateService;
+
+/// Commands that answer while startup is still running
+const AVAILABLE_DURING_STARTUP: &[&str] = &["get_backend_status", "get_app_lock_status", "unlock_application", "lock_application"];
+
+/// Startup progress, for a frontend that missed `backend://ready`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendStatus {
+    pub ready: bool,
+    pub startup_ms: Option<u64>,
+    pub schema_version: Option<i32>,
+    /// Why migrations failed, if they did
+    pub error: Option<String>,
+}
+
+/// Services built on first use, managed as Tauri state
+pub struct ServiceRegistry {
+    db_manager: Arc<DatabaseManager>,
+    started_at: Instant,
+    ready: watch::Sender<Option<BackendReadyEvent>>,
+    prompt_templates: OnceCell<PromptTemplateService>,
+    chat: OnceCell<ChatService>,
+    canvas_assistant: OnceCell<CanvasAssistant>,
+}
+
+impl ServiceRegistry {
+    /// `started_at` is when setup began, for the reported startup time
+    pub fn new(db_manager: Arc<DatabaseManager>, started_at: Instant) -> Self {
+        Self {
+            db_manager,
+            started_at,
+            ready: watch::channel(None).0,
+            prompt_templates: OnceCell::new(),
+            chat: OnceCell::new(),
+            canvas_assistant: OnceCell::new(),
+        }
+    }
+
+    /// Record that startup finished, returning the event to announce it
+    pub fn mark_ready(&self, schema_version: i32, error: Option<String>) -> BackendReadyEvent {
+        let event = BackendReadyEvent {
+            startup_ms: self.started_at.elapsed().as_millis() as u64,
+            schema_version,
+            error,
+        };
+        self.ready.send_replace(Some(event.clone()));
+        event
+    }
+
+    /// Wait until startup has finished; fails when migration did
+    pub async fn ready(&self) -> Result<()> {
+        let mut receiver = self.ready.subscribe();
+        // The sender lives as long as self, so this cannot fail
+        let finished = receiver.wait_for(|ready| ready.is_some()).await.ok().and_then(|ready| ready.clone());
+        finished.map_or(Ok(()), startup_result)
+    }
+
+    /// Whether startup has finished, and how
+    fn finished(&self) -> Option<Result<()>> {
+        self.ready.borrow().clone().map(startup_result)
+    }
+
+    pub fn status(&self) -> BackendStatus {
+        match self.ready.borrow().as_ref() {
+            Some(event) => BackendStatus {
+                ready: event.error.is_none(),
+                startup_ms: Some(event.startup_ms),
+                schema_version: Some(event.schema_version),
+                error: event.error.clone(),
+            },
+            None => BackendStatus { ready: false, startup_ms: None, schema_version: None, error: None },
+        }
+    }
+
+    async fn get_or_init<'a, T>(&self, cell: &'a OnceCell<T>, init: fn(Arc<DatabaseManager>) -> T) -> Result<&'a T> {
+        self.ready().await?;
+        Ok(cell.get_or_init(|| async { init(self.db_manager.clone()) }).await)
+    }
+
+    /// Prompt templates; the built-in ones are added on first use
+    pub async fn prompt_templates(&self) -> Result<&PromptTemplateService> {
+        self.get_or_init(&self.prompt_templates, PromptTemplateService::new).await
+    }
+
+    pub async fn chat(&self) -> Result<&ChatService> {
+        self.get_or_init(&self.chat, ChatService::new).await
+    }
+
+    pub async fn canvas_assistant(&self) -> Result<&CanvasAssistant> {
+        self.get_or_init(&self.canvas_assistant, CanvasAssistant::new).await
+    }
+}
+
+fn startup_result(event: BackendReadyEvent) -> Result<()> {
+    match event.error {
+        Some(message) => Err(LibreOllamaError::DatabaseMigration { message, version: event.schema_version.to_string() }),
+        None => Ok(()),
+    }
+}
+
+/// Wrap the command handler so commands wait for startup to finish, and
+/// fail with the migration error when it did not
+pub fn gate<R: Runtime>(
+    handler: impl Fn(Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static {
+    let handler = Arc::new(handler);
+    move |invoke| {
+        if AVAILABLE_DURING_STARTUP.contains(&invoke.message.command()) {
+            return handler(invoke);
+        }
+        let webview = invoke.message.webview();
+        let Some(registry) = webview.try_state::<ServiceRegistry>() else {
+            invoke.resolver.reject(CommandError::from("The backend is not running"));
+            return true;
+        };
+        let (finished, mut receiver) = (registry.finished(), registry.ready.subscribe());
+        match finished {
+            Some(Ok(())) => handler(invoke),
+            Some(Err(e)) => {
+                invoke.resolver.reject(CommandError::from(e));
+                true
+            }
+            None => {
+                let handler = handler.clone();
+                tauri::async_runtime::spawn(async move {
+                    let finished = receiver.wait_for(|ready| ready.is_some()).await.ok().and_then(|ready| ready.clone());
+                    match finished.map(startup_result) {
+                        Some(Ok(())) => {
+                            let resolver = invoke.resolver.clone();
+                            let command = invoke.message.command().to_string();
+                            if !handler(invoke) {
+                                resolver.reject(format!("Command {} not found", command));
+                            }
+                        }
+                        Some(Err(e)) => invoke.resolver.reject(CommandError::from(e)),
+                        None => invoke.resolver.reject(CommandError::from("The backend stopped during startup")),
+                    }
+                });
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_db() -> Arc<DatabaseManager> {
+        let path = std::env::temp_dir().join(format!("libreollama_registry_{}", uuid::Uuid::new_v4())).join("test.db");
+        Arc::new(DatabaseManager::open_at(path).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_services_wait_for_ready() {
+        let registry = Arc::new(ServiceRegistry::new(temp_db(), Instant::now()));
+        assert!(!registry.status().ready);
+
+        let waiting = tokio::spawn({
+            let registry = registry.clone();
+            async move { registry.ready().await }
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        assert!(!waiting.is_finished());
+
+        let event = registry.mark_ready(47, None);
+        waiting.await.unwrap().unwrap();
+        let status = registry.status();
+        assert!(status.ready);
+        assert_eq!(status.schema_version, Some(47));
+        assert_eq!(status.startup_ms, Some(event.startup_ms));
+    }
+
+    #[tokio::test]
+    async fn test_failed_migration_is_reported() {
+        let registry = ServiceRegistry::new(temp_db(), Instant::now());
+        registry.mark_ready(12, Some("Database migration failed: disk full".to_string()));
+
+        let status = registry.status();
+        assert!(!status.ready);
+        assert!(status.error.is_some());
+        let error = registry.ready().await.unwrap_err();
+        assert_eq!(error.code(), "database_migration");
+        assert!(registry.prompt_templates().await.is_err());
+    }
+}
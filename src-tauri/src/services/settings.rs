@@ -0,0 +1,751 @@
+//! Typed settings
+//!
+//! `user_preferences` stores plain strings; this module puts a schema over
+//! the keys the app uses so every setting has a type, a default, a category
+//! and validation. Values are stored in the same form the per-feature
+//! helpers always used (plain text for strings, `true`/`false`, JSON for
+//! structured settings), so both paths read each other's writes.
+//!
+//! `SettingsService` is the write path for the settings UI: it validates,
+//! stores, and announces each change both to the frontend
+//! (`backend://setting-changed`) and to background loops that subscribed,
+//! so a new interval or toggle applies without restarting the app.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use anyhow::{bail, Context};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+use tokio::sync::broadcast;
+
+use crate::database::operations::preference_operations;
+use crate::database::DatabaseManager;
+use crate::services::availability::{AvailabilityProfile, AVAILABILITY_SETTING};
+use crate::services::clipboard_watcher::{self, ClipboardPattern, CLIPBOARD_PATTERNS_PREFERENCE};
+use crate::services::daily_digest::{self, DigestSchedule, DEFAULT_DIGEST_TEMPLATE, DIGEST_SCHEDULE_PREFERENCE, DIGEST_TEMPLATE_PREFERENCE};
+use crate::services::events::{BackendEvent, EventBus, SettingChangedEvent};
+use crate::services::gmail::attachment_scan::{ATTACHMENT_SCAN_SETTING, SCANNER_CHOICES};
+use crate::services::gmail::attachment_reminder::ATTACHMENT_REMINDER_LLM_PREFERENCE;
+use crate::services::gmail::drive_offload::DRIVE_OFFLOAD_PREFERENCE;
+use crate::services::gmail::translation_service::{DEFAULT_TRANSLATION_MODEL, TRANSLATION_MODEL_PREFERENCE};
+use crate::services::google::task_activity::MIRROR_ACTIVITY_SETTING;
+use crate::services::i18n::{LOCALE_CHOICES, LOCALE_SETTING, SYSTEM_LOCALE};
+use crate::services::launch_args::LINK_HANDLERS_SETTING;
+use crate::services::note_templates::{DailyNoteSettings, DAILY_NOTE_SETTING};
+use crate::services::notification::{QuietHours, VipRules, MAIL_PREVIEW_SETTING, QUIET_HOURS_SETTING, VIP_RULES_SETTING};
+use crate::services::quick_capture::{DEFAULT_QUICK_CAPTURE_MODEL, QUICK_CAPTURE_MODEL_PREFERENCE};
+use crate::services::time_service::TIME_ZONE_SETTING;
+use crate::services::transcription_service::{TranscriptionSettings, TRANSCRIPTION_SETTINGS_PREFERENCE};
+use crate::services::updater::{AUTO_UPDATE_SETTING, UPDATE_CHANNELS, UPDATE_CHANNEL_SETTING};
+use crate::services::vault::VAULT_AUTO_LOCK_SETTING;
+use crate::utils::time;
+
+/// Seconds between attempts to push changes queued while offline
+pub const REPLAY_INTERVAL_SETTING: &str = "sync.replay_interval_seconds";
+/// Whether shipments found in mail are checked on carrier tracking pages
+pub const SHIPMENT_TRACKING_SETTING: &str = "shipments.tracking_enabled";
+/// Minutes between polls of subscribed RSS/Atom feeds
+pub const FEED_POLL_INTERVAL_SETTING: &str = "feeds.poll_interval_minutes";
+/// Whether newly arrived mail is announced to the frontend
+pub const NEW_MAIL_NOTIFICATIONS_SETTING: &str = "notifications.new_mail";
+pub const THEME_SETTING: &str = "appearance.theme";
+/// Whether copied URLs, addresses and pattern matches are offered for capture
+pub const CLIPBOARD_WATCH_SETTING: &str = "capture.clipboard_watch";
+/// Whether the app launches `ollama serve` when nothing answers on startup
+pub const OLLAMA_AUTO_START_SETTING: &str = "ollama.auto_start";
+pub const OLLAMA_BINARY_SETTING: &str = "ollama.binary_path";
+pub const OLLAMA_MODELS_DIR_SETTING: &str = "ollama.models_dir";
+
+/// Version written into exported settings files
+const EXPORT_VERSION: u32 = 1;
+/// Changes buffered per subscriber before the oldest are dropped
+const CHANGE_CHANNEL_CAPACITY: usize = 32;
+
+#[derive(Debug, Clone, Copy)]
+pub enum SettingKind {
+    Bool,
+    Integer { min: i64, max: i64 },
+    Text,
+    /// Text that must also pass `check`, such as a template whose
+    /// placeholders have to be known
+    CheckedText(fn(&str) -> Result<(), String>),
+    Choice(&'static [&'static str]),
+    /// Absolute filesystem path; empty means the built-in default
+    Path,
+    /// Structured value, checked by deserializing into its Rust type
+    Json(fn(&Value) -> Result<(), String>),
+    /// IANA zone name such as "Europe/Berlin"; empty means the system's
+    TimeZone,
+}
+
+pub struct SettingDefinition {
+    pub key: &'static str,
+    pub category: &'static str,
+    pub description: &'static str,
+    pub kind: SettingKind,
+    pub default: fn() -> Value,
+}
+
+fn check_json<T: serde::de::DeserializeOwned>(value: &Value) -> Result<(), String> {
+    serde_json::from_value::<T>(value.clone()).map(|_| ()).map_err(|e| e.to_string())
+}
+
+fn check_availability(value: &Value) -> Result<(), String> {
+    let profile: AvailabilityProfile = serde_json::from_value(value.clone()).map_err(|e| e.to_string())?;
+    profile.validate().map_err(|e| e.to_string())
+}
+
+fn check_quiet_hours(value: &Value) -> Result<(), String> {
+    let quiet_hours: QuietHours = serde_json::from_value(value.clone()).map_err(|e| e.to_string())?;
+    quiet_hours.validate().map_err(|e| e.to_string())
+}
+
+fn check_vip_rules(value: &Value) -> Result<(), String> {
+    let rules: VipRules = serde_json::from_value(value.clone()).map_err(|e| e.to_string())?;
+    rules.validate().map_err(|e| e.to_string())
+}
+
+fn check_clipboard_patterns(value: &Value) -> Result<(), String> {
+    let patterns: Vec<ClipboardPattern> = serde_json::from_value(value.clone()).map_err(|e| e.to_string())?;
+    clipboard_watcher::validate_patterns(&patterns).map_err(|e| e.to_string())
+}
+
+fn check_digest_schedule(value: &Value) -> Result<(), String> {
+    let schedule: DigestSchedule = serde_json::from_value(value.clone()).map_err(|e| e.to_string())?;
+    schedule.validate().map_err(|e| e.to_string())
+}
+
+fn check_digest_template(template: &str) -> Result<(), String> {
+    daily_digest::check_template(template).map_err(|e| e.to_string())
+}
+
+pub static SETTINGS: &[SettingDefinition] = &[
+    SettingDefinition {
+        key: THEME_SETTING,
+        category: "appearance",
+        description: "Colour scheme of the app",
+        kind: SettingKind::Choice(&["system", "light", "dark"]),
+        default: || Value::from("system"),
+    },
+    SettingDefinition {
+        key: LOCALE_SETTING,
+        category: "appearance",
+        description: "Language of messages, notifications and the daily digest",
+        kind: SettingKind::Choice(LOCALE_CHOICES),
+        default: || Value::from(SYSTEM_LOCALE),
+    },
+    SettingDefinition {
+        key: NEW_MAIL_NOTIFICATIONS_SETTING,
+        category: "notifications",
+        description: "Announce messages arriving in the inbox",
+        kind: SettingKind::Bool,
+        default: || Value::from(true),
+    },
+    SettingDefinition {
+        key: MAIL_PREVIEW_SETTING,
+        category: "notifications",
+        description: "Show the sender, subject and snippet in new-mail notifications",
+        kind: SettingKind::Bool,
+        default: || Value::from(true),
+    },
+    SettingDefinition {
+        key: QUIET_HOURS_SETTING,
+        category: "notifications",
+        description: "Local hours during which new mail is not announced, except from VIPs allowed through",
+        kind: SettingKind::Json(check_quiet_hours),
+        default: || serde_json::to_value(QuietHours::default()).unwrap_or(Value::Null),
+    },
+    SettingDefinition {
+        key: VIP_RULES_SETTING,
+        category: "notifications",
+        description: "How mail from VIP senders is announced: through quiet hours, with its own sound, always with a preview",
+        kind: SettingKind::Json(check_vip_rules),
+        default: || serde_json::to_value(VipRules::default()).unwrap_or(Value::Null),
+    },
+    SettingDefinition {
+        key: REPLAY_INTERVAL_SETTING,
+        category: "sync",
+        description: "Seconds between attempts to push edits made offline",
+        kind: SettingKind::Integer { min: 15, max: 3600 },
+        default: || Value::from(60),
+    },
+    SettingDefinition {
+        key: FEED_POLL_INTERVAL_SETTING,
+        category: "feeds",
+        description: "Minutes between checks of subscribed feeds for new items",
+        kind: SettingKind::Integer { min: 5, max: 1440 },
+        default: || Value::from(60),
+    },
+    SettingDefinition {
+        key: DIGEST_SCHEDULE_PREFERENCE,
+        category: "digest",
+        description: "When the daily digest is sent, and whether as a notification, an email to yourself or both",
+        kind: SettingKind::Json(check_digest_schedule),
+        default: || serde_json::to_value(DigestSchedule::default()).unwrap_or(Value::Null),
+    },
+    SettingDefinition {
+        key: DIGEST_TEMPLATE_PREFERENCE,
+        category: "digest",
+        description: "Template of the daily digest; {{mail}}, {{events}}, {{tasks}} and {{follow_ups}} list each section and {{availability}} gives the day's hours",
+        kind: SettingKind::CheckedText(check_digest_template),
+        default: || Value::from(DEFAULT_DIGEST_TEMPLATE),
+    },
+    SettingDefinition {
+        key: DAILY_NOTE_SETTING,
+        category: "notes",
+        description: "Daily note template name and folder, and whether today's agenda and due tasks are filled in",
+        kind: SettingKind::Json(check_json::<DailyNoteSettings>),
+        default: || serde_json::to_value(DailyNoteSettings::default()).unwrap_or(Value::Null),
+    },
+    SettingDefinition {
+        key: MIRROR_ACTIVITY_SETTING,
+        category: "tasks",
+        description: "Keep a summary of recent comments and changes at the end of each task's Google notes",
+        kind: SettingKind::Bool,
+        default: || Value::from(false),
+    },
+    SettingDefinition {
+        key: SHIPMENT_TRACKING_SETTING,
+        category: "mail",
+        description: "Check carrier tracking pages for shipments found in mail",
+        kind: SettingKind::Bool,
+        default: || Value::from(true),
+    },
+    SettingDefinition {
+        key: DRIVE_OFFLOAD_PREFERENCE,
+        category: "mail",
+        description: "Send attachments over the Gmail size limit as Drive links",
+        kind: SettingKind::Bool,
+        default: || Value::from(true),
+    },
+    SettingDefinition {
+        key: ATTACHMENT_REMINDER_LLM_PREFERENCE,
+        category: "mail",
+        description: "Confirm missing-attachment reminders with the classification model",
+        kind: SettingKind::Bool,
+        default: || Value::from(false),
+    },
+    SettingDefinition {
+        key: ATTACHMENT_SCAN_SETTING,
+        category: "mail",
+        description: "Virus scanner that checks attachments before they open; auto uses ClamAV or Windows Defender when installed",
+        kind: SettingKind::Choice(SCANNER_CHOICES),
+        default: || Value::from("auto"),
+    },
+    SettingDefinition {
+        key: TRANSLATION_MODEL_PREFERENCE,
+        category: "mail",
+        description: "Ollama model used to translate messages",
+        kind: SettingKind::Text,
+        default: || Value::from(DEFAULT_TRANSLATION_MODEL),
+    },
+    SettingDefinition {
+        key: AVAILABILITY_SETTING,
+        category: "calendar",
+        description: "Working days and hours, breaks and no-meeting blocks used for meeting suggestions, time blocks and the daily plan",
+        kind: SettingKind::Json(check_availability),
+        default: || serde_json::to_value(AvailabilityProfile::default()).unwrap_or(Value::Null),
+    },
+    SettingDefinition {
+        key: TIME_ZONE_SETTING,
+        category: "calendar",
+        description: "Time zone for due dates, time blocks and event times; empty follows the system",
+        kind: SettingKind::TimeZone,
+        default: || Value::from(""),
+    },
+    SettingDefinition {
+        key: QUICK_CAPTURE_MODEL_PREFERENCE,
+        category: "capture",
+        description: "Ollama model that classifies captured text",
+        kind: SettingKind::Text,
+        default: || Value::from(DEFAULT_QUICK_CAPTURE_MODEL),
+    },
+    SettingDefinition {
+        key: CLIPBOARD_WATCH_SETTING,
+        category: "capture",
+        description: "Offer copied links, email addresses and pattern matches for capture",
+        kind: SettingKind::Bool,
+        default: || Value::from(false),
+    },
+    SettingDefinition {
+        key: CLIPBOARD_PATTERNS_PREFERENCE,
+        category: "capture",
+        description: "Named regular expressions whose matches in copied text are offered for capture",
+        kind: SettingKind::Json(check_clipboard_patterns),
+        default: || Value::Array(Vec::new()),
+    },
+    SettingDefinition {
+        key: TRANSCRIPTION_SETTINGS_PREFERENCE,
+        category: "transcription",
+        description: "Speech-to-text engine and model",
+        kind: SettingKind::Json(check_json::<TranscriptionSettings>),
+        default: || serde_json::to_value(TranscriptionSettings::default()).unwrap_or(Value::Null),
+    },
+    SettingDefinition {
+        key: OLLAMA_AUTO_START_SETTING,
+        category: "llm",
+        description: "Start Ollama with the app when it is not already running",
+        kind: SettingKind::Bool,
+        default: || Value::from(false),
+    },
+    SettingDefinition {
+        key: OLLAMA_BINARY_SETTING,
+        category: "llm",
+        description: "Ollama executable to launch; empty searches PATH",
+        kind: SettingKind::Path,
+        default: || Value::from(""),
+    },
+    SettingDefinition {
+        key: OLLAMA_MODELS_DIR_SETTING,
+        category: "llm",
+        description: "Directory Ollama keeps models in; empty uses Ollama's default",
+        kind: SettingKind::Path,
+        default: || Value::from(""),
+    },
+    SettingDefinition {
+        key: VAULT_AUTO_LOCK_SETTING,
+        category: "vault",
+        description: "Minutes without use before the secure notes vault locks itself",
+        kind: SettingKind::Integer { min: 1, max: 1440 },
+        default: || Value::from(5),
+    },
+    SettingDefinition {
+        key: UPDATE_CHANNEL_SETTING,
+        category: "updates",
+        description: "Release channel updates come from; beta gets new versions first",
+        kind: SettingKind::Choice(UPDATE_CHANNELS),
+        default: || Value::from("stable"),
+    },
+    SettingDefinition {
+        key: AUTO_UPDATE_SETTING,
+        category: "updates",
+        description: "Check for updates every few hours and download them in the background",
+        kind: SettingKind::Bool,
+        default: || Value::from(true),
+    },
+    SettingDefinition {
+        key: LINK_HANDLERS_SETTING,
+        category: "system",
+        description: "Open mailto: and webcal: links with the app",
+        kind: SettingKind::Bool,
+        default: || Value::from(false),
+    },
+];
+
+pub fn definition(key: &str) -> Option<&'static SettingDefinition> {
+    SETTINGS.iter().find(|setting| setting.key == key)
+}
+
+impl SettingDefinition {
+    /// Check a value against the schema, returning it normalized
+    pub fn validate(&self, value: &Value) -> Result<Value, String> {
+        match self.kind {
+            SettingKind::Bool => value.as_bool().map(Value::from).ok_or_else(|| "expected true or false".to_string()),
+            SettingKind::Integer { min, max } => match value.as_i64() {
+                Some(n) if (min..=max).contains(&n) => Ok(Value::from(n)),
+                Some(n) => Err(format!("{} is outside {}..={}", n, min, max)),
+                None => Err("expected a whole number".to_string()),
+            },
+            SettingKind::Text => match value.as_str().map(str::trim) {
+                Some("") => Err("must not be empty".to_string()),
+                Some(text) => Ok(Value::from(text)),
+                None => Err("expected text".to_string()),
+            },
+            SettingKind::CheckedText(check) => match value.as_str().map(str::trim) {
+                Some("") => Err("must not be empty".to_string()),
+                Some(text) => check(text).map(|_| Value::from(text)),
+                None => Err("expected text".to_string()),
+            },
+            SettingKind::Choice(choices) => match value.as_str() {
+                Some(choice) if choices.contains(&choice) => Ok(Value::from(choice)),
+                _ => Err(format!("expected one of {}", choices.join(", "))),
+            },
+            SettingKind::Path => match value.as_str().map(str::trim) {
+                Some(path) if path.is_empty() || std::path::Path::new(path).is_absolute() => Ok(Value::from(path)),
+                Some(_) => Err("expected an absolute path".to_string()),
+                None => Err("expected a path".to_string()),
+            },
+            SettingKind::Json(check) => check(value).map(|_| value.clone()),
+            SettingKind::TimeZone => match value.as_str().map(str::trim) {
+                Some("") => Ok(Value::from("")),
+                Some(name) => time::parse_zone(name)
+                    .map(|zone| Value::from(zone.name()))
+                    .ok_or_else(|| format!("'{}' is not a known time zone", name)),
+                None => Err("expected a time zone name".to_string()),
+            },
+        }
+    }
+
+    /// Stored form of a validated value
+    fn encode(&self, value: &Value) -> String {
+        match value {
+            Value::String(text) if !matches!(self.kind, SettingKind::Json(_)) => text.clone(),
+            other => other.to_string(),
+        }
+    }
+
+    /// Parse a stored value; `None` when it no longer passes validation
+    fn decode(&self, stored: &str) -> Option<Value> {
+        let value = match self.kind {
+            SettingKind::Bool => Value::from(stored != "false"),
+            SettingKind::Integer { .. } => Value::from(stored.trim().parse::<i64>().ok()?),
+            SettingKind::Text
+            | SettingKind::CheckedText(_)
+            | SettingKind::Choice(_)
+            | SettingKind::Path
+            | SettingKind::TimeZone => Value::from(stored),
+            SettingKind::Json(_) => serde_json::from_str(stored).ok()?,
+        };
+        self.validate(&value).ok()
+    }
+
+    fn kind_name(&self) -> &'static str {
+        match self.kind {
+            SettingKind::Bool => "bool",
+            SettingKind::Integer { .. } => "integer",
+            SettingKind::Text | SettingKind::CheckedText(_) => "text",
+            SettingKind::Choice(_) => "choice",
+            SettingKind::Path => "path",
+            SettingKind::Json(_) => "json",
+            SettingKind::TimeZone => "time_zone",
+        }
+    }
+}
+
+/// One setting as described to the settings UI
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingDescriptor {
+    pub key: String,
+    pub category: String,
+    pub description: String,
+    pub kind: String,
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+    pub choices: Option<Vec<String>>,
+    pub default: Value,
+}
+
+impl From<&SettingDefinition> for SettingDescriptor {
+    fn from(setting: &SettingDefinition) -> Self {
+        let (min, max) = match setting.kind {
+            SettingKind::Integer { min, max } => (Some(min), Some(max)),
+            _ => (None, None),
+        };
+        Self {
+            key: setting.key.to_string(),
+            category: setting.category.to_string(),
+            description: setting.description.to_string(),
+            kind: setting.kind_name().to_string(),
+            min,
+            max,
+            choices: match setting.kind {
+                SettingKind::Choice(choices) => Some(choices.iter().map(|c| c.to_string()).collect()),
+                _ => None,
+            },
+            default: (setting.default)(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingValue {
+    pub key: String,
+    pub category: String,
+    pub value: Value,
+    pub is_default: bool,
+}
+
+/// Contents of an exported settings file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsExport {
+    pub version: u32,
+    pub exported_at: String,
+    pub settings: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportReport {
+    pub applied: Vec<String>,
+    /// Keys left alone, with the reason
+    pub skipped: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingChange {
+    pub key: String,
+    pub category: String,
+    pub value: Value,
+}
+
+fn read_setting(conn: &Connection, setting: &SettingDefinition) -> anyhow::Result<SettingValue> {
+    let stored = preference_operations::get_preference_value(conn, setting.key)?
+        .and_then(|stored| setting.decode(&stored));
+    Ok(SettingValue {
+        key: setting.key.to_string(),
+        category: setting.category.to_string(),
+        is_default: stored.is_none(),
+        value: stored.unwrap_or_else(setting.default),
+    })
+}
+
+pub fn get_setting(conn: &Connection, key: &str) -> anyhow::Result<SettingValue> {
+    let setting = definition(key).with_context(|| format!("Unknown setting {}", key))?;
+    read_setting(conn, setting)
+}
+
+/// Every setting, or those of one category
+pub fn get_settings(conn: &Connection, category: Option<&str>) -> anyhow::Result<Vec<SettingValue>> {
+    SETTINGS
+        .iter()
+        .filter(|setting| category.is_none_or(|c| setting.category == c))
+        .map(|setting| read_setting(conn, setting))
+        .collect()
+}
+
+pub fn get_bool(conn: &Connection, key: &str) -> anyhow::Result<bool> {
+    get_setting(conn, key)?.value.as_bool().with_context(|| format!("Setting {} is not a bool", key))
+}
+
+pub fn get_integer(conn: &Connection, key: &str) -> anyhow::Result<i64> {
+    get_setting(conn, key)?.value.as_i64().with_context(|| format!("Setting {} is not an integer", key))
+}
+
+/// Validate and store a value. Returns the change, or `None` when the
+/// stored value was already the same.
+pub fn set_setting(conn: &Connection, key: &str, value: &Value) -> anyhow::Result<Option<SettingChange>> {
+    let setting = definition(key).with_context(|| format!("Unknown setting {}", key))?;
+    let value = match setting.validate(value) {
+        Ok(value) => value,
+        Err(reason) => bail!("Invalid value for {}: {}", key, reason),
+    };
+
+    let previous = read_setting(conn, setting)?;
+    preference_operations::set_preference_value(conn, key, &setting.encode(&value), setting.category)?;
+    Ok((previous.is_default || previous.value != value).then(|| SettingChange {
+        key: key.to_string(),
+        category: setting.category.to_string(),
+        value,
+    }))
+}
+
+/// Drop the stored value so the default applies again
+pub fn reset_setting(conn: &Connection, key: &str) -> anyhow::Result<Option<SettingChange>> {
+    let setting = definition(key).with_context(|| format!("Unknown setting {}", key))?;
+    let previous = read_setting(conn, setting)?;
+    preference_operations::delete_user_preference_by_key(conn, key)?;
+
+    let value = (setting.default)();
+    Ok((previous.value != value).then(|| SettingChange {
+        key: key.to_string(),
+        category: setting.category.to_string(),
+        value,
+    }))
+}
+
+/// Current value of every setting, defaults included
+pub fn export_settings(conn: &Connection) -> anyhow::Result<SettingsExport> {
+    Ok(SettingsExport {
+        version: EXPORT_VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        settings: get_settings(conn, None)?
+            .into_iter()
+            .map(|setting| (setting.key, setting.value))
+            .collect(),
+    })
+}
+
+/// Apply an exported file in one transaction. Unknown keys and invalid
+/// values are skipped rather than failing the whole import.
+pub fn import_settings(conn: &mut Connection, data: &SettingsExport) -> anyhow::Result<(ImportReport, Vec<SettingChange>)> {
+    if data.version > EXPORT_VERSION {
+        bail!("Settings file version {} is newer than this app supports", data.version);
+    }
+
+    let tx = conn.transaction().context("Failed to start settings import")?;
+    let mut report = ImportReport::default();
+    let mut changes = Vec::new();
+    for (key, value) in &data.settings {
+        let Some(setting) = definition(key) else {
+            report.skipped.push((key.clone(), "unknown setting".to_string()));
+            continue;
+        };
+        if let Err(reason) = setting.validate(value) {
+            report.skipped.push((key.clone(), reason));
+            continue;
+        }
+        changes.extend(set_setting(&tx, key, value)?);
+        report.applied.push(key.clone());
+    }
+    tx.commit().context("Failed to commit settings import")?;
+
+    Ok((report, changes))
+}
+
+/// Resolve when `key` changes (or changes may have been missed). Never
+/// resolves without a subscription, so it can sit in a `select!` as is.
+pub async fn changed(receiver: &mut Option<broadcast::Receiver<SettingChange>>, key: &str) {
+    changed_any(receiver, &[key]).await
+}
+
+/// Like `changed`, for a loop that follows several settings
+pub async fn changed_any(receiver: &mut Option<broadcast::Receiver<SettingChange>>, keys: &[&str]) {
+    let Some(receiver) = receiver else {
+        return std::future::pending().await;
+    };
+    loop {
+        match receiver.recv().await {
+            Ok(change) if keys.contains(&change.key.as_str()) => return,
+            Ok(_) => continue,
+            Err(broadcast::error::RecvError::Lagged(_)) => return,
+            Err(broadcast::error::RecvError::Closed) => return std::future::pending().await,
+        }
+    }
+}
+
+/// Settings writes with change notification, managed as Tauri state
+pub struct SettingsService {
+    db_manager: Arc<DatabaseManager>,
+    app: AppHandle,
+    changes: broadcast::Sender<SettingChange>,
+}
+
+impl SettingsService {
+    pub fn new(db_manager: Arc<DatabaseManager>, app: AppHandle) -> Self {
+        let (changes, _) = broadcast::channel(CHANGE_CHANNEL_CAPACITY);
+        Self { db_manager, app, changes }
+    }
+
+    /// Receive every setting change made through the service
+    pub fn subscribe(&self) -> broadcast::Receiver<SettingChange> {
+        self.changes.subscribe()
+    }
+
+    fn connection(&self) -> anyhow::Result<Connection> {
+        self.db_manager.get_connection()
+    }
+
+    pub fn get(&self, key: &str) -> anyhow::Result<SettingValue> {
+        get_setting(&self.connection()?, key)
+    }
+
+    pub fn get_all(&self, category: Option<&str>) -> anyhow::Result<Vec<SettingValue>> {
+        get_settings(&self.connection()?, category)
+    }
+
+    pub fn set(&self, key: &str, value: &Value) -> anyhow::Result<SettingValue> {
+        let conn = self.connection()?;
+        if let Some(change) = set_setting(&conn, key, value)? {
+            self.announce(change);
+        }
+        get_setting(&conn, key)
+    }
+
+    pub fn reset(&self, key: &str) -> anyhow::Result<SettingValue> {
+        let conn = self.connection()?;
+        if let Some(change) = reset_setting(&conn, key)? {
+            self.announce(change);
+        }
+        get_setting(&conn, key)
+    }
+
+    pub fn export(&self) -> anyhow::Result<SettingsExport> {
+        export_settings(&self.connection()?)
+    }
+
+    pub fn import(&self, data: &SettingsExport) -> anyhow::Result<ImportReport> {
+        let mut conn = self.connection()?;
+        let (report, changes) = import_settings(&mut conn, data)?;
+        for change in changes {
+            self.announce(change);
+        }
+        Ok(report)
+    }
+
+    fn announce(&self, change: SettingChange) {
+        println!("⚙️ [SETTINGS] {} = {}", change.key, change.value);
+        if let Some(event_bus) = self.app.try_state::<EventBus>() {
+            event_bus.emit(BackendEvent::SettingChanged(SettingChangedEvent {
+                key: change.key.clone(),
+                category: change.category.clone(),
+                value: change.value.clone(),
+            }));
+        }
+        // No subscribers is not an error
+        let _ = self.changes.send(change);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_defaults_and_validation() {
+        let conn = setup_test_db();
+        for setting in SETTINGS {
+            assert!(setting.validate(&(setting.default)()).is_ok(), "bad default for {}", setting.key);
+        }
+
+        let interval = get_setting(&conn, REPLAY_INTERVAL_SETTING).unwrap();
+        assert!(interval.is_default);
+        assert_eq!(interval.value, Value::from(60));
+
+        assert!(set_setting(&conn, REPLAY_INTERVAL_SETTING, &Value::from(5)).is_err());
+        assert!(set_setting(&conn, THEME_SETTING, &Value::from("sepia")).is_err());
+        assert!(set_setting(&conn, "no.such.setting", &Value::from(true)).is_err());
+        assert!(set_setting(&conn, OLLAMA_MODELS_DIR_SETTING, &Value::from("models")).is_err());
+        let inverted = serde_json::json!({ "working_hours": {"start": "18:00", "end": "09:00", "days": [1]} });
+        assert!(set_setting(&conn, AVAILABILITY_SETTING, &inverted).is_err());
+
+        let change = set_setting(&conn, REPLAY_INTERVAL_SETTING, &Value::from(120)).unwrap();
+        assert_eq!(change.unwrap().value, Value::from(120));
+        assert!(set_setting(&conn, REPLAY_INTERVAL_SETTING, &Value::from(120)).unwrap().is_none());
+        assert_eq!(get_integer(&conn, REPLAY_INTERVAL_SETTING).unwrap(), 120);
+
+        assert!(reset_setting(&conn, REPLAY_INTERVAL_SETTING).unwrap().is_some());
+        assert!(get_setting(&conn, REPLAY_INTERVAL_SETTING).unwrap().is_default);
+    }
+
+    #[test]
+    fn test_reads_values_written_by_feature_helpers() {
+        let conn = setup_test_db();
+        crate::services::gmail::drive_offload::set_enabled(&conn, false).unwrap();
+        crate::services::gmail::translation_service::set_translation_model(&conn, "qwen2.5").unwrap();
+
+        assert!(!get_bool(&conn, DRIVE_OFFLOAD_PREFERENCE).unwrap());
+        assert_eq!(get_setting(&conn, TRANSLATION_MODEL_PREFERENCE).unwrap().value, Value::from("qwen2.5"));
+
+        set_setting(&conn, TRANSLATION_MODEL_PREFERENCE, &Value::from(" mistral ")).unwrap();
+        assert_eq!(crate::services::gmail::translation_service::translation_model(&conn).unwrap(), "mistral");
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let mut conn = setup_test_db();
+        set_setting(&conn, THEME_SETTING, &Value::from("dark")).unwrap();
+        let mut exported = export_settings(&conn).unwrap();
+        assert_eq!(exported.settings.len(), SETTINGS.len());
+
+        let mut other = setup_test_db();
+        exported.settings.insert("no.such.setting".to_string(), Value::from(1));
+        exported.settings.insert(NEW_MAIL_NOTIFICATIONS_SETTING.to_string(), Value::from("yes"));
+        let (report, changes) = import_settings(&mut other, &exported).unwrap();
+        assert_eq!(report.skipped.len(), 2);
+        assert_eq!(report.applied.len(), SETTINGS.len() - 1);
+        assert_eq!(get_setting(&other, THEME_SETTING).unwrap().value, Value::from("dark"));
+        assert!(changes.iter().any(|change| change.key == THEME_SETTING));
+
+        exported.version = EXPORT_VERSION + 1;
+        assert!(import_settings(&mut conn, &exported).is_err());
+    }
+}
@@ -0,0 +1,163 @@
+//! Graceful shutdown
+//!
+//! When the app exits, `shutdown` stops background jobs from starting new
+//! work and waits, up to a timeout, for work already under way (a campaign
+//! message being sent, a backfill page being stored, queued changes being
+//! pushed) to reach its checkpoint. It then gives queued offline edits one
+//! last push, persists the sync cursors held in memory and closes the
+//! database with a WAL checkpoint.
+//!
+//! Jobs that were stopped keep their `running` status and resume on the
+//! next launch.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Manager};
+use tokio::sync::watch;
+
+use crate::database::DatabaseManager;
+use crate::services::gmail::GmailSyncService;
+use crate::services::request_scheduler::RequestScheduler;
+use crate::services::sync::task_queue::PendingChangeReplayer;
+
+/// How long quitting may wait for running work and the final flush
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Tells background jobs to stop and tracks work they have in flight,
+/// managed as Tauri state
+#[derive(Clone)]
+pub struct ShutdownCoordinator {
+    stopping: watch::Sender<bool>,
+    in_flight: Arc<watch::Sender<usize>>,
+}
+
+/// Held while a unit of work must not be cut short
+pub struct WorkGuard {
+    in_flight: Arc<watch::Sender<usize>>,
+}
+
+impl Drop for WorkGuard {
+    fn drop(&mut self) {
+        self.in_flight.send_modify(|count| *count -= 1);
+    }
+}
+
+impl Default for ShutdownCoordinator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShutdownCoordinator {
+    pub fn new() -> Self {
+        Self {
+            stopping: watch::channel(false).0,
+            in_flight: Arc::new(watch::channel(0).0),
+        }
+    }
+
+    pub fn is_stopping(&self) -> bool {
+        *self.stopping.borrow()
+    }
+
+    /// Resolves once shutdown has begun, for background loops to select on
+    pub async fn stopping(&self) {
+        let mut receiver = self.stopping.subscribe();
+        let _ = receiver.wait_for(|stopping| *stopping).await;
+    }
+
+    /// Register work that should finish before the database closes; `None`
+    /// once shutdown has begun, so no new work starts
+    pub fn begin_work(&self) -> Option<WorkGuard> {
+        if self.is_stopping() {
+            return None;
+        }
+        self.in_flight.send_modify(|count| *count += 1);
+        Some(WorkGuard { in_flight: self.in_flight.clone() })
+    }
+
+    /// Stop new work and wait for running work, up to `timeout`. Returns
+    /// how many units were still running when it gave up.
+    pub async fn drain(&self, timeout: Duration) -> usize {
+        self.stopping.send_replace(true);
+        let mut in_flight = self.in_flight.subscribe();
+        let _ = tokio::time::timeout(timeout, in_flight.wait_for(|count| *count == 0)).await;
+        let remaining = *in_flight.borrow();
+        remaining
+    }
+}
+
+/// Run every shutdown step; called once from the exit handler
+pub async fn shutdown(app: &AppHandle) {
+    let deadline = Instant::now() + SHUTDOWN_TIMEOUT;
+    println!("🛑 [SHUTDOWN] Stopping background work...");
+
+    if let Some(coordinator) = app.try_state::<ShutdownCoordinator>() {
+        let remaining = coordinator.drain(SHUTDOWN_TIMEOUT / 2).await;
+        if remaining > 0 {
+            eprintln!("⚠️ [SHUTDOWN] {} job(s) still running; they resume on next launch", remaining);
+        }
+    }
+
+    let Some(db_manager) = app.try_state::<Arc<DatabaseManager>>().map(|state| state.inner().clone()) else {
+        return;
+    };
+
+    // Edits queued while offline get one last chance to reach Google
+    let replayer = PendingChangeReplayer::new(db_manager.clone(), app.clone());
+    match tokio::time::timeout(deadline.saturating_duration_since(Instant::now()), replayer.replay_once()).await {
+        Ok(Err(e)) => eprintln!("⚠️ [SHUTDOWN] Failed to push queued changes: {}", e),
+        Err(_) => eprintln!("⚠️ [SHUTDOWN] Pushing queued changes timed out; they stay queued"),
+        Ok(Ok(())) => {}
+    }
+
+    if let Some(sync_service) = app.try_state::<GmailSyncService>() {
+        match sync_service.persist_cursors().await {
+            Ok(count) => println!("💾 [SHUTDOWN] Saved sync state for {} account(s)", count),
+            Err(e) => eprintln!("⚠️ [SHUTDOWN] Failed to save sync state: {}", e),
+        }
+    }
+
+    if let Some(scheduler) = app.try_state::<Arc<RequestScheduler>>() {
+        if let Err(e) = scheduler.flush_usage() {
+            eprintln!("⚠️ [SHUTDOWN] Failed to save request usage: {}", e);
+        }
+    }
+
+    if let Some(supervisor) = app.try_state::<crate::services::ollama_supervisor::OllamaSupervisor>() {
+        supervisor.shutdown();
+    }
+
+    db_manager.close();
+    println!("✅ [SHUTDOWN] Database closed");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_drain_waits_for_work_in_flight() {
+        let coordinator = ShutdownCoordinator::new();
+        let work = coordinator.begin_work().unwrap();
+
+        let finishing = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            drop(work);
+        });
+        assert_eq!(coordinator.drain(Duration::from_secs(1)).await, 0);
+        finishing.await.unwrap();
+
+        assert!(coordinator.is_stopping());
+        assert!(coordinator.begin_work().is_none());
+        coordinator.stopping().await;
+    }
+
+    #[tokio::test]
+    async fn test_drain_gives_up_after_timeout() {
+        let coordinator = ShutdownCoordinator::new();
+        let _stuck = coordinator.begin_work().unwrap();
+        assert_eq!(coordinator.drain(Duration::from_millis(10)).await, 1);
+    }
+}
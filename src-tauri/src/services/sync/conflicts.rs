@@ -0,0 +1,512 @@
+//! Conflict detection and resolution for offline edits
+//!
+//! Local edits are queued in `pending_changes` together with the server state
+//! they were based on (`base_payload`, `base_etag`, `base_updated`). When the
+//! queue is flushed, the current server state is fetched and compared:
+//!
+//! - unchanged on the server: the local edit is applied as-is
+//! - changed, but on different fields: a field-level three-way merge is
+//!   applied automatically
+//! - changed on the same fields: a `sync_conflicts` row is recorded and the
+//!   change is held back until it is resolved manually
+//!
+//! Calendar events are merged the same way, with etags as the base version;
+//! their writes are conditional on the etag they were merged against.
+//! Gmail label modifications commute (add/remove sets) and are always safe.
+//! Tasks created offline carry a temporary ID until their create is pushed;
+//! every local reference is then rewritten to the Google ID.
+
+use crate::database::operations::pending_change_operations::{self, PendingChange, SyncConflict};
+use crate::database::operations::task_queue_operations;
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::gmail::api_service::GmailApiService;
+use crate::services::google::calendar_service::GoogleCalendarService;
+use crate::services::google::tasks_service::{CreateTaskInput, GoogleTasksService, UpdateTaskInput};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Result of a field-level three-way merge
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeOutcome {
+    /// Fields to write to the server to apply the local edit on top of remote
+    Merged(Map<String, Value>),
+    /// Fields edited both locally and remotely to different values
+    Conflict(Vec<String>),
+}
+
+/// How a user chose to resolve a conflict
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictResolution {
+    KeepLocal,
+    KeepRemote,
+    Merged,
+}
+
+impl ConflictResolution {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConflictResolution::KeepLocal => "keep_local",
+            ConflictResolution::KeepRemote => "keep_remote",
+            ConflictResolution::Merged => "merged",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FlushSummary {
+    pub applied: u32,
+    pub auto_merged: u32,
+    pub conflicts: u32,
+    pub failed: u32,
+    /// Changes the server refused; they stay `failed` until retried or discarded
+    pub rejected: u32,
+    pub skipped: u32,
+    /// Tasks created offline that now have a Google ID
+    pub reconciled_ids: Vec<ReconciledTaskId>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconciledTaskId {
+    pub temp_id: String,
+    pub google_task_id: String,
+}
+
+/// Has the server copy moved on since the change was recorded?
+///
+/// Etags are authoritative when both sides have one; otherwise the
+/// `updated` timestamps are compared.
+pub fn remote_changed_since_base(change: &PendingChange, remote_etag: Option<&str>, remote_updated: Option<&str>) -> bool {
+    match (change.base_etag.as_deref(), remote_etag) {
+        (Some(base), Some(remote)) => base != remote,
+        _ => match (change.base_updated.as_deref(), remote_updated) {
+            (Some(base), Some(remote)) => base != remote,
+            // Without any base information we cannot prove the server is unchanged
+            (None, Some(_)) => true,
+            _ => false,
+        },
+    }
+}
+
+/// Field-level three-way merge of a local edit against the remote state.
+///
+/// `local` holds only the fields the user edited. A field conflicts when the
+/// server changed it away from `base` to something other than the local value.
+pub fn three_way_merge(base: &Map<String, Value>, local: &Map<String, Value>, remote: &Map<String, Value>) -> MergeOutcome {
+    let mut conflicts = Vec::new();
+    let mut merged = Map::new();
+
+    for (field, local_value) in local {
+        let base_value = base.get(field).unwrap_or(&Value::Null);
+        let remote_value = remote.get(field).unwrap_or(&Value::Null);
+
+        let remote_edited = remote_value != base_value;
+        if remote_edited && remote_value != local_value {
+            conflicts.push(field.clone());
+        } else if remote_value != local_value {
+            merged.insert(field.clone(), local_value.clone());
+        }
+    }
+
+    if conflicts.is_empty() {
+        MergeOutcome::Merged(merged)
+    } else {
+        conflicts.sort();
+        MergeOutcome::Conflict(conflicts)
+    }
+}
+
+/// Entity types and operations the flush knows how to push
+const REPLAYABLE: &[(&str, &[&str])] = &[
+    ("task", &["create", "update", "delete"]),
+    ("calendar_event", &["create", "update", "delete"]),
+    ("gmail_message", &["modify_labels"]),
+];
+
+/// Can a queued change of this kind be pushed by [`flush_pending_changes`]?
+pub fn is_replayable(entity_type: &str, operation: &str) -> bool {
+    REPLAYABLE
+        .iter()
+        .any(|(entity, operations)| *entity == entity_type && operations.contains(&operation))
+}
+
+fn as_object(value: &Value) -> Map<String, Value> {
+    value.as_object().cloned().unwrap_or_default()
+}
+
+fn task_update_from_fields(fields: &Map<String, Value>) -> UpdateTaskInput {
+    let text = |key: &str| fields.get(key).and_then(|v| v.as_str()).map(|s| s.to_string());
+    UpdateTaskInput {
+        title: text("title"),
+        notes: text("notes"),
+        due: text("due"),
+        status: text("status"),
+    }
+}
+
+/// Push all pending changes for an account, merging or recording conflicts
+pub async fn flush_pending_changes(
+    db_manager: Arc<DatabaseManager>,
+    tasks_service: &GoogleTasksService,
+    calendar: &GoogleCalendarService,
+    gmail_api: &GmailApiService,
+    account_id: &str,
+) -> Result<FlushSummary> {
+    let changes = {
+        let conn = db_manager.get_connection()?;
+        pending_change_operations::list_pending_changes(&conn, account_id)?
+    };
+
+    let mut summary = FlushSummary::default();
+    // Temporary IDs reconciled during this flush, for changes loaded before it
+    let mut reconciled: HashMap<String, String> = HashMap::new();
+
+    for mut change in changes {
+        if let Some(google_task_id) = reconciled.get(&change.entity_id) {
+            change.entity_id = google_task_id.clone();
+        }
+
+        let outcome = match (change.entity_type.as_str(), change.operation.as_str()) {
+            ("task", "create") => apply_task_create(&db_manager, tasks_service, &change).await,
+            ("task", "update") => apply_task_update(&db_manager, tasks_service, &change).await,
+            ("task", "delete") => apply_task_delete(&db_manager, tasks_service, &change).await,
+            ("calendar_event", "create") => apply_event_create(calendar, &change).await,
+            ("calendar_event", "update") => apply_event_update(&db_manager, calendar, &change).await,
+            ("calendar_event", "delete") => apply_event_delete(&db_manager, calendar, &change).await,
+            ("gmail_message", "modify_labels") => apply_label_change(gmail_api, &change).await,
+            _ => {
+                summary.skipped += 1;
+                continue;
+            }
+        };
+
+        let conn = db_manager.get_connection()?;
+        match outcome {
+            Ok(ApplyOutcome::Applied) => {
+                pending_change_operations::set_pending_change_status(&conn, change.id, "applied", None)?;
+                summary.applied += 1;
+            }
+            Ok(ApplyOutcome::Created(google_task_id)) => {
+                pending_change_operations::set_pending_change_status(&conn, change.id, "applied", None)?;
+                summary.applied += 1;
+                reconciled.insert(change.entity_id.clone(), google_task_id.clone());
+                summary.reconciled_ids.push(ReconciledTaskId { temp_id: change.entity_id.clone(), google_task_id });
+            }
+            Ok(ApplyOutcome::AutoMerged) => {
+                pending_change_operations::set_pending_change_status(&conn, change.id, "applied", None)?;
+                summary.auto_merged += 1;
+            }
+            Ok(ApplyOutcome::Conflict) => summary.conflicts += 1,
+            Err(e) if e.is_retryable() => {
+                // Leave it pending so the next flush retries it
+                pending_change_operations::set_pending_change_status(&conn, change.id, "pending", Some(&e.to_string()))?;
+                summary.failed += 1;
+            }
+            Err(e) => {
+                pending_change_operations::set_pending_change_status(&conn, change.id, "failed", Some(&e.to_string()))?;
+                summary.failed += 1;
+                summary.rejected += 1;
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+enum ApplyOutcome {
+    Applied,
+    /// A task created offline was given this Google ID
+    Created(String),
+    AutoMerged,
+    Conflict,
+}
+
+async fn apply_task_update(
+    db_manager: &DatabaseManager,
+    tasks_service: &GoogleTasksService,
+    change: &PendingChange,
+) -> Result<ApplyOutcome> {
+    let task_list_id = change.container_id.as_deref().ok_or_else(|| LibreOllamaError::InvalidInput {
+        message: "Task change is missing its task list".to_string(),
+        field: Some("container_id".to_string()),
+    })?;
+
+    let remote = tasks_service.get_single_task(&change.account_id, task_list_id, &change.entity_id).await?;
+    let remote_value = serde_json::to_value(&remote)?;
+    let local = as_object(&change.payload);
+
+    if !remote_changed_since_base(change, None, remote.updated.as_deref()) {
+        tasks_service
+            .update_task(&change.account_id, task_list_id, &change.entity_id, task_update_from_fields(&local))
+            .await?;
+        return Ok(ApplyOutcome::Applied);
+    }
+
+    let base = change.base_payload.as_ref().map(as_object).unwrap_or_default();
+    match three_way_merge(&base, &local, &as_object(&remote_value)) {
+        MergeOutcome::Merged(fields) => {
+            if !fields.is_empty() {
+                tasks_service
+                    .update_task(&change.account_id, task_list_id, &change.entity_id, task_update_from_fields(&fields))
+                    .await?;
+            }
+            Ok(ApplyOutcome::AutoMerged)
+        }
+        MergeOutcome::Conflict(fields) => {
+            let conn = db_manager.get_connection()?;
+            pending_change_operations::record_conflict(&conn, change, &fields, &remote_value)?;
+            Ok(ApplyOutcome::Conflict)
+        }
+    }
+}
+
+async fn apply_task_create(
+    db_manager: &DatabaseManager,
+    tasks_service: &GoogleTasksService,
+    change: &PendingChange,
+) -> Result<ApplyOutcome> {
+    let task_list_id = change.container_id.as_deref().ok_or_else(|| LibreOllamaError::InvalidInput {
+        message: "Task change is missing its task list".to_string(),
+        field: Some("container_id".to_string()),
+    })?;
+
+    let fields = as_object(&change.payload);
+    let update = task_update_from_fields(&fields);
+    let created = tasks_service
+        .create_task(
+            &change.account_id,
+            task_list_id,
+            CreateTaskInput {
+                title: update.title.unwrap_or_default(),
+                notes: update.notes,
+                due: update.due,
+                status: update.status,
+            },
+        )
+        .await?;
+
+    let mut conn = db_manager.get_connection()?;
+    task_queue_operations::reconcile_task_id(&mut conn, &change.entity_id, &created.id)?;
+    println!("🔗 [SYNC] Task {} created as {}", change.entity_id, created.id);
+    Ok(ApplyOutcome::Created(created.id))
+}
+
+async fn apply_task_delete(
+    db_manager: &DatabaseManager,
+    tasks_service: &GoogleTasksService,
+    change: &PendingChange,
+) -> Result<ApplyOutcome> {
+    let task_list_id = change.container_id.as_deref().ok_or_else(|| LibreOllamaError::InvalidInput {
+        message: "Task change is missing its task list".to_string(),
+        field: Some("container_id".to_string()),
+    })?;
+
+    let remote = tasks_service.get_single_task(&change.account_id, task_list_id, &change.entity_id).await?;
+
+    // Deleting a task someone else edited meanwhile would silently drop their work
+    if remote_changed_since_base(change, None, remote.updated.as_deref()) {
+        let conn = db_manager.get_connection()?;
+        let remote_value = serde_json::to_value(&remote)?;
+        pending_change_operations::record_conflict(&conn, change, &["deleted".to_string()], &remote_value)?;
+        return Ok(ApplyOutcome::Conflict);
+    }
+
+    tasks_service.delete_task(&change.account_id, task_list_id, &change.entity_id).await?;
+    Ok(ApplyOutcome::Applied)
+}
+
+fn event_calendar(change: &PendingChange) -> Result<&str> {
+    change.container_id.as_deref().ok_or_else(|| LibreOllamaError::InvalidInput {
+        message: "Calendar change is missing its calendar".to_string(),
+        field: Some("container_id".to_string()),
+    })
+}
+
+async fn apply_event_create(calendar: &GoogleCalendarService, change: &PendingChange) -> Result<ApplyOutcome> {
+    let calendar_id = event_calendar(change)?;
+    calendar.insert_event(&change.account_id, calendar_id, &as_object(&change.payload)).await?;
+    Ok(ApplyOutcome::Applied)
+}
+
+async fn apply_event_update(
+    db_manager: &DatabaseManager,
+    calendar: &GoogleCalendarService,
+    change: &PendingChange,
+) -> Result<ApplyOutcome> {
+    let calendar_id = event_calendar(change)?;
+    let remote = calendar
+        .get_event(&change.account_id, calendar_id, &change.entity_id)
+        .await?
+        .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("calendar event {}", change.entity_id) })?;
+    let remote_etag = remote.get("etag").and_then(|v| v.as_str());
+    let remote_updated = remote.get("updated").and_then(|v| v.as_str());
+    let local = as_object(&change.payload);
+
+    if !remote_changed_since_base(change, remote_etag, remote_updated) {
+        calendar.patch_event(&change.account_id, calendar_id, &change.entity_id, &local, remote_etag).await?;
+        return Ok(ApplyOutcome::Applied);
+    }
+
+    let base = change.base_payload.as_ref().map(as_object).unwrap_or_default();
+    match three_way_merge(&base, &local, &as_object(&remote)) {
+        MergeOutcome::Merged(fields) => {
+            if !fields.is_empty() {
+                calendar.patch_event(&change.account_id, calendar_id, &change.entity_id, &fields, remote_etag).await?;
+            }
+            Ok(ApplyOutcome::AutoMerged)
+        }
+        MergeOutcome::Conflict(fields) => {
+            let conn = db_manager.get_connection()?;
+            pending_change_operations::record_conflict(&conn, change, &fields, &remote)?;
+            Ok(ApplyOutcome::Conflict)
+        }
+    }
+}
+
+async fn apply_event_delete(
+    db_manager: &DatabaseManager,
+    calendar: &GoogleCalendarService,
+    change: &PendingChange,
+) -> Result<ApplyOutcome> {
+    let calendar_id = event_calendar(change)?;
+    let Some(remote) = calendar.get_event(&change.account_id, calendar_id, &change.entity_id).await? else {
+        return Ok(ApplyOutcome::Applied);
+    };
+
+    if remote_changed_since_base(change, remote["etag"].as_str(), remote["updated"].as_str()) {
+        let conn = db_manager.get_connection()?;
+        pending_change_operations::record_conflict(&conn, change, &["deleted".to_string()], &remote)?;
+        return Ok(ApplyOutcome::Conflict);
+    }
+
+    calendar.delete_event(&change.account_id, calendar_id, &change.entity_id).await?;
+    Ok(ApplyOutcome::Applied)
+}
+
+async fn apply_label_change(gmail_api: &GmailApiService, change: &PendingChange) -> Result<ApplyOutcome> {
+    let labels = |key: &str| -> Vec<String> {
+        change.payload
+            .get(key)
+            .and_then(|v| v.as_array())
+            .map(|items| items.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+            .unwrap_or_default()
+    };
+
+    gmail_api
+        .modify_messages(&change.account_id, vec![change.entity_id.clone()], labels("add"), labels("remove"))
+        .await?;
+    Ok(ApplyOutcome::Applied)
+}
+
+/// Resolve a conflict manually.
+///
+/// - `KeepRemote` discards the local change.
+/// - `KeepLocal` re-queues the local payload on top of the current remote state.
+/// - `Merged` re-queues the caller-provided payload on top of the remote state.
+pub fn resolve_conflict(
+    db_manager: &DatabaseManager,
+    conflict_id: i64,
+    resolution: ConflictResolution,
+    merged_payload: Option<Value>,
+) -> Result<SyncConflict> {
+    let conn = db_manager.get_connection()?;
+    let conflict = pending_change_operations::get_conflict(&conn, conflict_id)?
+        .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("sync conflict {}", conflict_id) })?;
+
+    if conflict.resolved_at.is_some() {
+        return Err(LibreOllamaError::InvalidInput {
+            message: format!("Conflict {} is already resolved", conflict_id),
+            field: None,
+        });
+    }
+
+    let remote_updated = conflict.remote_payload.get("updated").and_then(|v| v.as_str());
+    let remote_etag = conflict.remote_payload.get("etag").and_then(|v| v.as_str());
+
+    match resolution {
+        ConflictResolution::KeepRemote => {
+            pending_change_operations::set_pending_change_status(&conn, conflict.pending_change_id, "discarded", None)?;
+        }
+        ConflictResolution::KeepLocal => {
+            pending_change_operations::rebase_pending_change(
+                &conn,
+                conflict.pending_change_id,
+                &conflict.local_payload,
+                &conflict.remote_payload,
+                remote_etag,
+                remote_updated,
+            )?;
+        }
+        ConflictResolution::Merged => {
+            let payload = merged_payload.ok_or_else(|| LibreOllamaError::InvalidInput {
+                message: "A merged payload is required for merged resolution".to_string(),
+                field: Some("merged_payload".to_string()),
+            })?;
+            pending_change_operations::rebase_pending_change(
+                &conn,
+                conflict.pending_change_id,
+                &payload,
+                &conflict.remote_payload,
+                remote_etag,
+                remote_updated,
+            )?;
+        }
+    }
+
+    pending_change_operations::mark_conflict_resolved(&conn, conflict_id, resolution.as_str())?;
+
+    pending_change_operations::get_conflict(&conn, conflict_id)?
+        .ok_or_else(|| LibreOllamaError::NotFound { resource: format!("sync conflict {}", conflict_id) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn map(value: Value) -> Map<String, Value> {
+        value.as_object().cloned().unwrap()
+    }
+
+    #[test]
+    fn test_replayable_changes() {
+        assert!(is_replayable("calendar_event", "update"));
+        assert!(is_replayable("gmail_message", "modify_labels"));
+        assert!(!is_replayable("gmail_message", "delete"));
+        assert!(!is_replayable("note", "update"));
+    }
+
+    #[test]
+    fn test_merge_disjoint_edits() {
+        let base = map(json!({ "title": "Buy milk", "notes": "2%" }));
+        let local = map(json!({ "title": "Buy oat milk" }));
+        let remote = map(json!({ "title": "Buy milk", "notes": "skim" }));
+
+        let outcome = three_way_merge(&base, &local, &remote);
+        assert_eq!(outcome, MergeOutcome::Merged(map(json!({ "title": "Buy oat milk" }))));
+    }
+
+    #[test]
+    fn test_merge_same_field_conflicts() {
+        let base = map(json!({ "title": "Buy milk" }));
+        let local = map(json!({ "title": "Buy oat milk" }));
+        let remote = map(json!({ "title": "Buy soy milk" }));
+
+        assert_eq!(
+            three_way_merge(&base, &local, &remote),
+            MergeOutcome::Conflict(vec!["title".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_merge_identical_edits_are_not_conflicts() {
+        let base = map(json!({ "status": "needsAction" }));
+        let local = map(json!({ "status": "completed" }));
+        let remote = map(json!({ "status": "completed" }));
+
+        assert_eq!(three_way_merge(&base, &local, &remote), MergeOutcome::Merged(Map::new()));
+    }
+}
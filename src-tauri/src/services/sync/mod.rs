@@ -0,0 +1,7 @@
+//! Cross-Domain Sync Services
+//!
+//! This module contains sync infrastructure shared by tasks, calendar and
+//! mail: offline change queuing and conflict resolution.
+
+pub mod conflicts;
+pub mod task_queue;
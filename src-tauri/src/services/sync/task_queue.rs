@@ -0,0 +1,239 @@
+//! Offline task mutations
+//!
+//! When Google Tasks cannot be reached, task creates, updates and deletes
+//! are queued in `pending_changes` and succeed locally straight away. A
+//! task created offline gets a temporary ID (see `task_queue_operations`);
+//! later edits to it are folded into its queued create, and deleting it
+//! simply drops the create. `PendingChangeReplayer` pushes the queue once
+//! Google is reachable again, and failures are reported to the frontend
+//! rather than retried forever.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use rusqlite::Connection;
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Manager};
+
+use crate::database::operations::pending_change_operations::{self, NewPendingChange};
+use crate::database::operations::task_comment_operations;
+use crate::database::operations::task_queue_operations;
+use crate::database::DatabaseManager;
+use crate::errors::LibreOllamaError;
+use crate::services::events::{BackendEvent, ChangesRejectedEvent, ConflictDetectedEvent, EventBus};
+use crate::services::gmail::api_service::GmailApiService;
+use crate::services::google::calendar_service::GoogleCalendarService;
+use crate::services::google::task_activity;
+use crate::services::google::tasks_service::GoogleTasksService;
+use crate::services::settings;
+use crate::services::sync::conflicts::{self, FlushSummary};
+use crate::services::task_supervisor::TaskSpec;
+use crate::utils::http_client::http_client;
+
+/// Answers 204 when the network is up; cheaper than a real API call
+const CONNECTIVITY_PROBE_URL: &str = "https://www.google.com/generate_204";
+
+/// Whether an error means Google could not be reached, so the mutation
+/// should be queued instead of failing
+pub fn is_offline(error: &LibreOllamaError) -> bool {
+    matches!(error, LibreOllamaError::Network { .. } | LibreOllamaError::Timeout { .. })
+}
+
+/// Queue a task create and return the temporary ID it is known by until
+/// the create reaches Google
+pub fn queue_task_create(
+    conn: &Connection,
+    account_id: &str,
+    task_list_id: &str,
+    fields: Map<String, Value>,
+) -> anyhow::Result<String> {
+    let temp_id = task_queue_operations::new_temp_task_id();
+    task_queue_operations::create_temp_mapping(conn, &temp_id, task_list_id)?;
+    pending_change_operations::record_pending_change(conn, &NewPendingChange {
+        account_id: account_id.to_string(),
+        entity_type: "task".to_string(),
+        entity_id: temp_id.clone(),
+        container_id: Some(task_list_id.to_string()),
+        operation: "create".to_string(),
+        payload: Value::Object(fields),
+        base_payload: None,
+        base_etag: None,
+        base_updated: None,
+    })?;
+    Ok(temp_id)
+}
+
+/// Queue a task update. Edits to a task that was itself created offline are
+/// merged into its queued create. Returns the task's fields as queued.
+pub fn queue_task_update(
+    conn: &Connection,
+    account_id: &str,
+    task_list_id: &str,
+    task_id: &str,
+    fields: Map<String, Value>,
+    base_updated: Option<&str>,
+) -> anyhow::Result<Map<String, Value>> {
+    if task_queue_operations::is_temp_task_id(task_id) {
+        let create = pending_change_operations::find_unpushed_create(conn, "task", task_id)?
+            .ok_or_else(|| anyhow::anyhow!("Task {} is not waiting to be created", task_id))?;
+        let mut payload = create.payload.as_object().cloned().unwrap_or_default();
+        payload.extend(fields);
+        pending_change_operations::update_pending_payload(conn, create.id, &Value::Object(payload.clone()))?;
+        return Ok(payload);
+    }
+
+    pending_change_operations::record_pending_change(conn, &NewPendingChange {
+        account_id: account_id.to_string(),
+        entity_type: "task".to_string(),
+        entity_id: task_id.to_string(),
+        container_id: Some(task_list_id.to_string()),
+        operation: "update".to_string(),
+        payload: Value::Object(fields.clone()),
+        base_payload: None,
+        base_etag: None,
+        base_updated: base_updated.map(str::to_string),
+    })?;
+    Ok(fields)
+}
+
+/// Queue a task delete. A task that was created offline never reaches
+/// Google: its queued changes are dropped instead.
+pub fn queue_task_delete(
+    conn: &mut Connection,
+    account_id: &str,
+    task_list_id: &str,
+    task_id: &str,
+    base_updated: Option<&str>,
+) -> anyhow::Result<()> {
+    if task_queue_operations::is_temp_task_id(task_id) {
+        task_queue_operations::discard_unsynced_task(conn, task_id)?;
+        return Ok(());
+    }
+
+    pending_change_operations::record_pending_change(conn, &NewPendingChange {
+        account_id: account_id.to_string(),
+        entity_type: "task".to_string(),
+        entity_id: task_id.to_string(),
+        container_id: Some(task_list_id.to_string()),
+        operation: "delete".to_string(),
+        payload: Value::Object(Map::new()),
+        base_payload: None,
+        base_etag: None,
+        base_updated: base_updated.map(str::to_string),
+    })?;
+    Ok(())
+}
+
+/// Record a flush in the sync history and tell the frontend about
+/// conflicts and rejected changes
+pub fn report_flush(event_bus: &EventBus, account_id: &str, result: &anyhow::Result<FlushSummary>) {
+    match result {
+        Ok(summary) => {
+            event_bus.sync_finished(account_id, "pending_changes", "incremental", summary.applied + summary.auto_merged, None);
+            if summary.conflicts > 0 {
+                event_bus.emit(BackendEvent::ConflictDetected(ConflictDetectedEvent {
+                    account_id: account_id.to_string(),
+                    conflict_count: summary.conflicts,
+                }));
+            }
+            if summary.rejected > 0 {
+                event_bus.emit(BackendEvent::ChangesRejected(ChangesRejectedEvent {
+                    account_id: account_id.to_string(),
+                    rejected_count: summary.rejected,
+                }));
+            }
+        }
+        Err(e) => event_bus.sync_finished(account_id, "pending_changes", "incremental", 0, Some(e)),
+    }
+}
+
+/// Pushes queued changes in the background whenever Google is reachable
+#[derive(Clone)]
+pub struct PendingChangeReplayer {
+    db_manager: Arc<DatabaseManager>,
+    app: AppHandle,
+}
+
+impl PendingChangeReplayer {
+    pub fn new(db_manager: Arc<DatabaseManager>, app: AppHandle) -> Self {
+        Self { db_manager, app }
+    }
+
+    /// Replay on the interval from settings; a changed interval restarts
+    /// the wait straight away. The shutdown flush pushes whatever is
+    /// still queued when the app quits.
+    pub fn task(&self) -> TaskSpec {
+        let replayer = self.clone();
+        let interval = self.clone();
+        TaskSpec::new("pending_changes", move || {
+            let replayer = replayer.clone();
+            async move { replayer.replay_once().await }
+        })
+        .every(move || interval.replay_interval())
+        .first_run_after(self.replay_interval())
+        .wake_on(&[settings::REPLAY_INTERVAL_SETTING])
+        .jitter(0.1)
+        .finish_before_exit()
+    }
+
+    fn replay_interval(&self) -> Duration {
+        let seconds = self
+            .db_manager
+            .get_connection()
+            .and_then(|conn| settings::get_integer(&conn, settings::REPLAY_INTERVAL_SETTING))
+            .unwrap_or(60);
+        Duration::from_secs(seconds.max(1) as u64)
+    }
+
+    /// Push every account's queued changes, then the task activity that
+    /// could not be mirrored, if Google is reachable
+    pub async fn replay_once(&self) -> anyhow::Result<()> {
+        let (accounts, mirrors) = {
+            let conn = self.db_manager.get_connection()?;
+            (
+                pending_change_operations::accounts_with_pending_changes(&conn)?,
+                task_comment_operations::queued_mirrors(&conn)?,
+            )
+        };
+        if (accounts.is_empty() && mirrors.is_empty()) || !is_online().await {
+            return Ok(());
+        }
+
+        let (Some(tasks_service), Some(calendar), Some(gmail_api)) = (
+            self.app.try_state::<GoogleTasksService>(),
+            self.app.try_state::<GoogleCalendarService>(),
+            self.app.try_state::<Arc<GmailApiService>>(),
+        ) else {
+            return Ok(());
+        };
+
+        for account_id in accounts {
+            println!("🔄 [SYNC] Back online, replaying queued changes for {}", account_id);
+            let result = conflicts::flush_pending_changes(self.db_manager.clone(), &tasks_service, &calendar, &gmail_api, &account_id)
+                .await
+                .context("Failed to flush pending changes");
+            if let Some(event_bus) = self.app.try_state::<EventBus>() {
+                report_flush(&event_bus, &account_id, &result);
+            }
+        }
+
+        // After the flush, so tasks created offline have their Google IDs
+        if !mirrors.is_empty() {
+            task_activity::replay_mirrors(&tasks_service, &self.db_manager)
+                .await
+                .context("Failed to mirror queued task activity")?;
+        }
+        Ok(())
+    }
+}
+
+async fn is_online() -> bool {
+    http_client()
+        .get(CONNECTIVITY_PROBE_URL)
+        .timeout(Duration::from_secs(5))
+        .send()
+        .await
+        .map(|response| response.status().is_success())
+        .unwrap_or(false)
+}
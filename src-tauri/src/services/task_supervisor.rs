@@ -0,0 +1,609 @@
+//! Background task supervisor
+//!
+//! Periodic background jobs (pushing queued changes, polling feeds and
+//! shipments, the daily digest) register a `TaskSpec` here instead of each
+//! spawning its own loop. The supervisor runs every job on its schedule,
+//! optionally jittered so jobs started together don't fire together, and
+//! keeps the last run, next run and last error for `get_background_tasks`.
+//!
+//! A task can name tasks it depends on: its first run waits until each of
+//! them has finished a first run. Each run is spawned on its own, so a
+//! panic ends that run only and the task's `RestartPolicy` decides whether
+//! it runs again. An error returned by a run is recorded and the task
+//! carries on. Every loop stops once shutdown begins.
+//!
+//! Heavy tasks (database maintenance, embedding chat attachments) are
+//! marked `when_idle`: once due, they wait for the user to be idle (see
+//! `ActivityMonitor`), for up to a set time, before running.
+//!
+//! Runs are background work, so the Google requests they make are
+//! scheduled at `RequestPriority::Low` and yield to what the user is doing.
+
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, watch};
+
+use crate::commands::rate_limiter::RequestPriority;
+use crate::database::DatabaseManager;
+use crate::services::activity::ActivityMonitor;
+use crate::services::request_scheduler;
+use crate::services::settings::{self, SettingChange};
+use crate::services::shutdown::ShutdownCoordinator;
+
+/// Wait between runs for a task registered without `every`
+const DEFAULT_WAIT: Duration = Duration::from_secs(60);
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+/// Share of free pages at which maintenance vacuums the database
+const VACUUM_FREE_SHARE: f64 = 0.25;
+/// How often `drain` checks whether runs have ended
+const DRAIN_POLL: Duration = Duration::from_millis(50);
+
+type RunFn = Arc<dyn Fn() -> Pin<Box<dyn Future<Output = Result<()>> + Send>> + Send + Sync>;
+type WaitFn = Arc<dyn Fn() -> Duration + Send + Sync>;
+
+/// What happens when a run panics
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartPolicy {
+    /// The task stops and is reported as failed
+    Never,
+    /// Run again after `backoff`, doubled for each panic in a row; the
+    /// task stops after more than `max_restarts` panics in a row
+    OnPanic { max_restarts: u32, backoff: Duration },
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        RestartPolicy::OnPanic { max_restarts: 5, backoff: Duration::from_secs(30) }
+    }
+}
+
+/// A named background job and how it is scheduled
+pub struct TaskSpec {
+    name: &'static str,
+    run: RunFn,
+    wait: WaitFn,
+    initial_delay: Duration,
+    jitter: f64,
+    depends_on: Vec<&'static str>,
+    wake_on: Vec<&'static str>,
+    restart: RestartPolicy,
+    finish_before_exit: bool,
+    idle_defer: Option<Duration>,
+}
+
+impl TaskSpec {
+    pub fn new<F, Fut>(name: &'static str, run: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        Self {
+            name,
+            run: Arc::new(move || Box::pin(run())),
+            wait: Arc::new(|| DEFAULT_WAIT),
+            initial_delay: DEFAULT_WAIT,
+            jitter: 0.0,
+            depends_on: Vec::new(),
+            wake_on: Vec::new(),
+            restart: RestartPolicy::default(),
+            finish_before_exit: false,
+            idle_defer: None,
+        }
+    }
+
+    /// The wait before each run; asked again after every run, so it can
+    /// follow settings
+    pub fn every(mut self, wait: impl Fn() -> Duration + Send + Sync + 'static) -> Self {
+        self.wait = Arc::new(wait);
+        self
+    }
+
+    /// The wait before the first run, instead of the default minute
+    pub fn first_run_after(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    /// Vary each wait by up to this share either way
+    pub fn jitter(mut self, share: f64) -> Self {
+        self.jitter = share.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Hold the first run until each of these tasks has run once
+    pub fn after(mut self, names: &[&'static str]) -> Self {
+        self.depends_on.extend_from_slice(names);
+        self
+    }
+
+    /// Start the wait over, asking `every` again, when one of these
+    /// settings changes
+    pub fn wake_on(mut self, keys: &[&'static str]) -> Self {
+        self.wake_on.extend_from_slice(keys);
+        self
+    }
+
+    pub fn restart(mut self, policy: RestartPolicy) -> Self {
+        self.restart = policy;
+        self
+    }
+
+    /// Quitting waits for a run under way instead of cutting it short
+    pub fn finish_before_exit(mut self) -> Self {
+        self.finish_before_exit = true;
+        self
+    }
+
+    /// Once due, hold each run until the user is idle, or until
+    /// `max_defer` has passed
+    pub fn when_idle(mut self, max_defer: Duration) -> Self {
+        self.idle_defer = Some(max_defer);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+    /// Registered; the supervisor has not started yet
+    Pending,
+    WaitingForDependencies,
+    Scheduled,
+    /// Due, and held until the user is idle
+    WaitingForIdle,
+    Running,
+    /// Stopped after a panic its restart policy does not cover
+    Failed,
+    /// Stopped for shutdown
+    Stopped,
+}
+
+/// What `get_background_tasks` reports for each task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackgroundTaskStatus {
+    pub name: String,
+    pub state: TaskState,
+    pub depends_on: Vec<String>,
+    pub runs: u64,
+    /// Runs started again after a panic
+    pub restarts: u32,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_run_ms: Option<u64>,
+    pub next_run_at: Option<DateTime<Utc>>,
+    /// The error or panic from the latest run that failed
+    pub last_error: Option<String>,
+    pub last_error_at: Option<DateTime<Utc>>,
+}
+
+type Statuses = Arc<Mutex<Vec<BackgroundTaskStatus>>>;
+
+/// Runs the registered background tasks, managed as Tauri state
+pub struct TaskSupervisor {
+    shutdown: ShutdownCoordinator,
+    activity: ActivityMonitor,
+    pending: Mutex<Vec<TaskSpec>>,
+    statuses: Statuses,
+}
+
+impl TaskSupervisor {
+    pub fn new(shutdown: ShutdownCoordinator, activity: ActivityMonitor) -> Self {
+        Self { shutdown, activity, pending: Mutex::new(Vec::new()), statuses: Arc::new(Mutex::new(Vec::new())) }
+    }
+
+    /// Add a task; it runs once `start` is called
+    pub fn register(&self, spec: TaskSpec) {
+        self.statuses.lock().unwrap_or_else(|e| e.into_inner()).push(BackgroundTaskStatus {
+            name: spec.name.to_string(),
+            state: TaskState::Pending,
+            depends_on: spec.depends_on.iter().map(|name| name.to_string()).collect(),
+            runs: 0,
+            restarts: 0,
+            last_run_at: None,
+            last_run_ms: None,
+            next_run_at: None,
+            last_error: None,
+            last_error_at: None,
+        });
+        self.pending.lock().unwrap_or_else(|e| e.into_inner()).push(spec);
+    }
+
+    /// Start every registered task, in dependency order. `settings` wakes
+    /// tasks that follow a setting. Nothing starts if a name is taken
+    /// twice, a dependency is unknown or dependencies form a cycle.
+    ///
+    /// Must be called from within the async runtime.
+    pub fn start(&self, settings: Option<broadcast::Receiver<SettingChange>>) -> Result<()> {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        let order = dependency_order(&pending)?;
+        let offset = self.statuses.lock().unwrap_or_else(|e| e.into_inner()).len() - pending.len();
+
+        let mut first_runs: HashMap<&'static str, watch::Receiver<bool>> = HashMap::new();
+        let mut specs: Vec<Option<TaskSpec>> = pending.drain(..).map(Some).collect();
+        for position in order {
+            let spec = specs[position].take().expect("each task is ordered once");
+            let first_run = watch::channel(false).0;
+            let dependencies = spec.depends_on.iter().map(|name| first_runs[name].clone()).collect();
+            first_runs.insert(spec.name, first_run.subscribe());
+
+            let changes = if spec.wake_on.is_empty() { None } else { settings.as_ref().map(|receiver| receiver.resubscribe()) };
+            let task = Task {
+                spec,
+                index: offset + position,
+                statuses: self.statuses.clone(),
+                shutdown: self.shutdown.clone(),
+                activity: self.activity.clone(),
+            };
+            tokio::spawn(task.supervise(dependencies, first_run, changes));
+        }
+        Ok(())
+    }
+
+    pub fn statuses(&self) -> Vec<BackgroundTaskStatus> {
+        self.statuses.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Stop every task and wait, up to `timeout`, for runs under way to
+    /// end. Returns how many tasks were still running when it gave up.
+    pub async fn drain(&self, timeout: Duration) -> usize {
+        let deadline = Instant::now() + timeout;
+        let remaining = self.shutdown.drain(timeout).await;
+        loop {
+            let running = self.statuses().iter().filter(|status| status.state == TaskState::Running).count();
+            if running == 0 || Instant::now() >= deadline {
+                return running.max(remaining);
+            }
+            tokio::time::sleep(DRAIN_POLL).await;
+        }
+    }
+}
+
+/// Daily database upkeep, run while the user is idle: planner statistics,
+/// a WAL checkpoint and, once enough space is free, a vacuum
+pub fn database_maintenance(db_manager: Arc<DatabaseManager>) -> TaskSpec {
+    TaskSpec::new("database_maintenance", move || {
+        let db_manager = db_manager.clone();
+        async move {
+            tokio::task::spawn_blocking(move || {
+                db_manager.optimize()?;
+                db_manager.vacuum_if_fragmented(VACUUM_FREE_SHARE).map(|_| ())
+            })
+            .await?
+        }
+    })
+    .every(|| MAINTENANCE_INTERVAL)
+    .first_run_after(Duration::from_secs(10 * 60))
+    .jitter(0.1)
+    .when_idle(MAINTENANCE_INTERVAL / 4)
+}
+
+/// Positions of `specs` such that each task comes after its dependencies
+fn dependency_order(specs: &[TaskSpec]) -> Result<Vec<usize>> {
+    let mut positions = HashMap::new();
+    for (position, spec) in specs.iter().enumerate() {
+        if positions.insert(spec.name, position).is_some() {
+            bail!("Background task '{}' is registered twice", spec.name);
+        }
+    }
+    for spec in specs {
+        if let Some(unknown) = spec.depends_on.iter().find(|name| !positions.contains_key(*name)) {
+            bail!("Background task '{}' depends on unknown task '{}'", spec.name, unknown);
+        }
+    }
+
+    let mut order = Vec::with_capacity(specs.len());
+    let mut placed = HashSet::new();
+    while order.len() < specs.len() {
+        let ready: Vec<usize> = specs
+            .iter()
+            .enumerate()
+            .filter(|(position, spec)| {
+                !placed.contains(position) && spec.depends_on.iter().all(|name| placed.contains(&positions[name]))
+            })
+            .map(|(position, _)| position)
+            .collect();
+        if ready.is_empty() {
+            let stuck: Vec<&str> =
+                specs.iter().enumerate().filter(|(position, _)| !placed.contains(position)).map(|(_, spec)| spec.name).collect();
+            bail!("Background tasks depend on each other in a cycle: {}", stuck.join(", "));
+        }
+        placed.extend(ready.iter().copied());
+        order.extend(ready);
+    }
+    Ok(order)
+}
+
+/// `wait` moved by a random amount of up to `share` of it either way
+fn jittered(wait: Duration, share: f64) -> Duration {
+    if share <= 0.0 || wait.is_zero() {
+        return wait;
+    }
+    wait.mul_f64(1.0 + rand::thread_rng().gen_range(-share..=share))
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Box<dyn Any>".to_string())
+}
+
+struct Task {
+    spec: TaskSpec,
+    index: usize,
+    statuses: Statuses,
+    shutdown: ShutdownCoordinator,
+    activity: ActivityMonitor,
+}
+
+impl Task {
+    fn update(&self, change: impl FnOnce(&mut BackgroundTaskStatus)) {
+        change(&mut self.statuses.lock().unwrap_or_else(|e| e.into_inner())[self.index]);
+    }
+
+    fn set_state(&self, state: TaskState) {
+        self.update(|status| {
+            status.state = state;
+            status.next_run_at = None;
+        });
+    }
+
+    async fn supervise(
+        self,
+        dependencies: Vec<watch::Receiver<bool>>,
+        first_run: watch::Sender<bool>,
+        mut changes: Option<broadcast::Receiver<SettingChange>>,
+    ) {
+        if !dependencies.is_empty() {
+            self.set_state(TaskState::WaitingForDependencies);
+            for mut dependency in dependencies {
+                tokio::select! {
+                    _ = dependency.wait_for(|ran| *ran) => {}
+                    _ = self.shutdown.stopping() => return self.set_state(TaskState::Stopped),
+                }
+            }
+        }
+
+        let mut wait = self.spec.initial_delay;
+        let mut panics_in_a_row = 0u32;
+        loop {
+            let wait_now = jittered(wait, self.spec.jitter);
+            self.update(|status| {
+                status.state = TaskState::Scheduled;
+                status.next_run_at = chrono::Duration::from_std(wait_now).ok().map(|wait| Utc::now() + wait);
+            });
+            tokio::select! {
+                _ = tokio::time::sleep(wait_now) => {}
+                _ = settings::changed_any(&mut changes, &self.spec.wake_on) => {
+                    wait = (self.spec.wait)();
+                    continue;
+                }
+                _ = self.shutdown.stopping() => return self.set_state(TaskState::Stopped),
+            }
+            if let Some(max_defer) = self.spec.idle_defer {
+                self.set_state(TaskState::WaitingForIdle);
+                tokio::select! {
+                    _ = self.activity.idle_or_after(max_defer) => {}
+                    _ = self.shutdown.stopping() => return self.set_state(TaskState::Stopped),
+                }
+            }
+
+            let work = if self.spec.finish_before_exit {
+                match self.shutdown.begin_work() {
+                    Some(work) => Some(work),
+                    None => return self.set_state(TaskState::Stopped),
+                }
+            } else {
+                None
+            };
+            self.set_state(TaskState::Running);
+            let started_at = Utc::now();
+            let started = Instant::now();
+            let outcome = tokio::spawn(request_scheduler::with_priority(RequestPriority::Low, (self.spec.run)())).await;
+            drop(work);
+
+            let (error, panicked) = match outcome {
+                Ok(Ok(())) => (None, false),
+                Ok(Err(e)) => (Some(e.to_string()), false),
+                Err(e) if e.is_panic() => (Some(format!("Panicked: {}", panic_message(e.into_panic()))), true),
+                Err(_) => return self.set_state(TaskState::Stopped),
+            };
+            if let Some(error) = &error {
+                eprintln!("⚠️ [TASKS] Background task '{}' failed: {}", self.spec.name, error);
+            }
+            self.update(|status| {
+                status.runs += 1;
+                status.last_run_at = Some(started_at);
+                status.last_run_ms = Some(started.elapsed().as_millis() as u64);
+                if error.is_some() {
+                    status.last_error = error;
+                    status.last_error_at = Some(Utc::now());
+                }
+            });
+            first_run.send_replace(true);
+
+            if !panicked {
+                panics_in_a_row = 0;
+                wait = (self.spec.wait)();
+                continue;
+            }
+            panics_in_a_row += 1;
+            match self.spec.restart {
+                RestartPolicy::OnPanic { max_restarts, backoff } if panics_in_a_row <= max_restarts => {
+                    self.update(|status| status.restarts += 1);
+                    wait = backoff.saturating_mul(1 << (panics_in_a_row - 1).min(16));
+                }
+                _ => {
+                    eprintln!("❌ [TASKS] Background task '{}' stopped after {} panic(s)", self.spec.name, panics_in_a_row);
+                    return self.set_state(TaskState::Failed);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    const TICK: Duration = Duration::from_millis(5);
+
+    fn status(supervisor: &TaskSupervisor, name: &str) -> BackgroundTaskStatus {
+        supervisor.statuses().into_iter().find(|status| status.name == name).unwrap()
+    }
+
+    async fn settle(supervisor: &TaskSupervisor, name: &str, done: impl Fn(&BackgroundTaskStatus) -> bool) -> BackgroundTaskStatus {
+        for _ in 0..200 {
+            let status = status(supervisor, name);
+            if done(&status) {
+                return status;
+            }
+            tokio::time::sleep(TICK).await;
+        }
+        panic!("task '{}' never settled: {:?}", name, status(supervisor, name));
+    }
+
+    #[tokio::test]
+    async fn test_panicking_task_restarts_until_its_policy_runs_out() {
+        let supervisor = TaskSupervisor::new(ShutdownCoordinator::new(), ActivityMonitor::default());
+        let calls = Arc::new(AtomicU32::new(0));
+        let counted = calls.clone();
+        supervisor.register(
+            TaskSpec::new("flaky", move || {
+                let calls = counted.clone();
+                async move {
+                    match calls.fetch_add(1, Ordering::SeqCst) {
+                        0 => bail!("not reachable"),
+                        _ => panic!("bad state"),
+                    }
+                }
+            })
+            .first_run_after(Duration::ZERO)
+            .every(|| TICK)
+            .restart(RestartPolicy::OnPanic { max_restarts: 2, backoff: TICK }),
+        );
+        supervisor.start(None).unwrap();
+
+        let status = settle(&supervisor, "flaky", |status| status.state == TaskState::Failed).await;
+        // An error, then a panic and the two restarts it allows
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+        assert_eq!((status.runs, status.restarts), (4, 2));
+        assert_eq!(status.last_error.as_deref(), Some("Panicked: bad state"));
+        assert!(status.last_run_at.is_some() && status.next_run_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_dependents_wait_for_a_first_run() {
+        let supervisor = TaskSupervisor::new(ShutdownCoordinator::new(), ActivityMonitor::default());
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let record = |name: &'static str| {
+            let order = order.clone();
+            move || {
+                let order = order.clone();
+                async move {
+                    order.lock().unwrap().push(name);
+                    Ok(())
+                }
+            }
+        };
+        // Registered first and due first, but held back by its dependency
+        supervisor.register(TaskSpec::new("digest", record("digest")).first_run_after(Duration::ZERO).after(&["feeds"]).every(|| Duration::from_secs(600)));
+        supervisor.register(TaskSpec::new("feeds", record("feeds")).first_run_after(TICK * 20).every(|| Duration::from_secs(600)));
+        supervisor.start(None).unwrap();
+        settle(&supervisor, "digest", |status| status.state == TaskState::WaitingForDependencies).await;
+
+        settle(&supervisor, "digest", |status| status.runs == 1).await;
+        assert_eq!(*order.lock().unwrap(), ["feeds", "digest"]);
+        assert!(status(&supervisor, "feeds").next_run_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_dependencies_start_nothing() {
+        let task = |name| TaskSpec::new(name, || async { Ok(()) });
+
+        let supervisor = TaskSupervisor::new(ShutdownCoordinator::new(), ActivityMonitor::default());
+        supervisor.register(task("a").after(&["b"]));
+        supervisor.register(task("b").after(&["a"]));
+        assert!(supervisor.start(None).unwrap_err().to_string().contains("cycle: a, b"));
+
+        let supervisor = TaskSupervisor::new(ShutdownCoordinator::new(), ActivityMonitor::default());
+        supervisor.register(task("a").after(&["missing"]));
+        assert!(supervisor.start(None).is_err());
+        assert_eq!(supervisor.statuses()[0].state, TaskState::Pending);
+    }
+
+    #[test]
+    fn test_a_poisoned_status_lock_keeps_working() {
+        let supervisor = TaskSupervisor::new(ShutdownCoordinator::new(), ActivityMonitor::default());
+        let statuses = supervisor.statuses.clone();
+        let _ = std::thread::spawn(move || {
+            let _guard = statuses.lock().unwrap();
+            panic!("poison the status list");
+        })
+        .join();
+        assert!(supervisor.statuses.is_poisoned());
+
+        supervisor.register(TaskSpec::new("after", || async { Ok(()) }));
+        assert_eq!(supervisor.statuses()[0].name, "after");
+    }
+
+    #[tokio::test]
+    async fn test_heavy_tasks_wait_for_idle() {
+        let activity = ActivityMonitor::new(Duration::from_secs(600));
+        let supervisor = TaskSupervisor::new(ShutdownCoordinator::new(), activity.clone());
+        supervisor.register(TaskSpec::new("heavy", || async { Ok(()) }).first_run_after(Duration::ZERO).when_idle(Duration::from_secs(600)));
+        supervisor.start(None).unwrap();
+
+        settle(&supervisor, "heavy", |status| status.state == TaskState::WaitingForIdle).await;
+        activity.set_focused(false);
+        settle(&supervisor, "heavy", |status| status.runs == 1).await;
+    }
+
+    #[tokio::test]
+    async fn test_tasks_stop_for_shutdown() {
+        let shutdown = ShutdownCoordinator::new();
+        let supervisor = TaskSupervisor::new(shutdown.clone(), ActivityMonitor::default());
+        supervisor.register(TaskSpec::new("idle", || async { Ok(()) }));
+        supervisor.start(None).unwrap();
+
+        shutdown.drain(Duration::from_millis(10)).await;
+        settle(&supervisor, "idle", |status| status.state == TaskState::Stopped).await;
+    }
+
+    #[tokio::test]
+    async fn test_drain_waits_for_running_tasks() {
+        let supervisor = TaskSupervisor::new(ShutdownCoordinator::new(), ActivityMonitor::default());
+        supervisor.register(
+            TaskSpec::new("slow", || async {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                Ok(())
+            })
+            .first_run_after(Duration::ZERO),
+        );
+        supervisor.start(None).unwrap();
+
+        settle(&supervisor, "slow", |status| status.state == TaskState::Running).await;
+        assert_eq!(supervisor.drain(Duration::from_secs(2)).await, 0);
+        let status = settle(&supervisor, "slow", |status| status.state == TaskState::Stopped).await;
+        assert_eq!(status.runs, 1);
+    }
+
+    #[test]
+    fn test_jitter_stays_within_its_share() {
+        let wait = Duration::from_secs(100);
+        assert_eq!(jittered(wait, 0.0), wait);
+        for _ in 0..100 {
+            let jittered = jittered(wait, 0.1);
+            assert!(jittered >= Duration::from_secs(90) && jittered <= Duration::from_secs(110));
+        }
+    }
+}
@@ -0,0 +1,414 @@
+//! Text Processing Service
+//!
+//! Analyses email bodies for the reader view: detected language, reading
+//! time and readability, and entities (dates, amounts, tracking numbers and
+//! flight codes) that the UI turns into smart chips such as "add to
+//! calendar" or "track package".
+
+use chrono::{Datelike, NaiveDate};
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+
+/// Average adult silent reading speed
+const WORDS_PER_MINUTE: usize = 230;
+
+lazy_static::lazy_static! {
+    static ref HTML_BLOCK: Regex = Regex::new(r"(?is)<(script|style|head)\b.*?</(script|style|head)>").unwrap();
+    static ref HTML_BREAK: Regex = Regex::new(r"(?i)<(br|/p|/div|/tr|/li|/h[1-6])\b[^>]*>").unwrap();
+    static ref HTML_TAG: Regex = Regex::new(r"<[^>]+>").unwrap();
+
+    static ref ISO_DATE: Regex = Regex::new(r"\b(\d{4})-(\d{2})-(\d{2})\b").unwrap();
+    static ref NUMERIC_DATE: Regex = Regex::new(r"\b(\d{1,2})/(\d{1,2})/(\d{4})\b").unwrap();
+    static ref DAY_MONTH_DATE: Regex = Regex::new(&format!(
+        r"(?i)\b(\d{{1,2}})(?:st|nd|rd|th)?\s+{}\.?(?:,?\s+(\d{{4}}))?\b", MONTH_PATTERN
+    )).unwrap();
+    static ref MONTH_DAY_DATE: Regex = Regex::new(&format!(
+        r"(?i)\b{}\.?\s+(\d{{1,2}})(?:st|nd|rd|th)?\b(?:,?\s+(\d{{4}})\b)?", MONTH_PATTERN
+    )).unwrap();
+
+    static ref PREFIX_AMOUNT: Regex = Regex::new(
+        r"(?:\b(USD|EUR|GBP|JPY|CAD|AUD|CHF)\s?|([$€£¥]))\s?(\d{1,3}(?:[,.\s]\d{3})+(?:[.,]\d{1,2})?|\d+(?:[.,]\d{1,2})?)"
+    ).unwrap();
+    static ref SUFFIX_AMOUNT: Regex = Regex::new(
+        r"(?i)\b(\d{1,3}(?:[,.]\d{3})+(?:[.,]\d{1,2})?|\d+(?:[.,]\d{1,2})?)\s?(USD|EUR|GBP|JPY|CAD|AUD|CHF|dollars|euros|pounds)\b"
+    ).unwrap();
+
+    static ref UPS_TRACKING: Regex = Regex::new(r"\b1Z[0-9A-Z]{16}\b").unwrap();
+    static ref USPS_TRACKING: Regex = Regex::new(r"\b9[1-5]\d{18,20}\b").unwrap();
+    static ref FEDEX_TRACKING: Regex = Regex::new(r"\b(\d{12}|\d{15})\b").unwrap();
+    static ref DHL_TRACKING: Regex = Regex::new(r"\b\d{10}\b").unwrap();
+
+    static ref FLIGHT_CODE: Regex = Regex::new(r"\b([A-Z]{2}|[A-Z]\d|\d[A-Z])\s?(\d{1,4})\b").unwrap();
+}
+
+const MONTH_PATTERN: &str =
+    r"(Jan(?:uary)?|Feb(?:ruary)?|Mar(?:ch)?|Apr(?:il)?|May|June?|July?|Aug(?:ust)?|Sep(?:t(?:ember)?)?|Oct(?:ober)?|Nov(?:ember)?|Dec(?:ember)?)";
+
+/// IATA codes of the airlines flight numbers are recognised for
+const AIRLINES: &[(&str, &str)] = &[
+    ("AA", "American Airlines"),
+    ("AC", "Air Canada"),
+    ("AF", "Air France"),
+    ("AS", "Alaska Airlines"),
+    ("AY", "Finnair"),
+    ("AZ", "ITA Airways"),
+    ("B6", "JetBlue"),
+    ("BA", "British Airways"),
+    ("CX", "Cathay Pacific"),
+    ("DL", "Delta Air Lines"),
+    ("EK", "Emirates"),
+    ("EY", "Etihad Airways"),
+    ("F9", "Frontier Airlines"),
+    ("FR", "Ryanair"),
+    ("IB", "Iberia"),
+    ("JL", "Japan Airlines"),
+    ("KL", "KLM"),
+    ("LH", "Lufthansa"),
+    ("LX", "Swiss"),
+    ("NH", "All Nippon Airways"),
+    ("NK", "Spirit Airlines"),
+    ("QF", "Qantas"),
+    ("QR", "Qatar Airways"),
+    ("SK", "SAS"),
+    ("SQ", "Singapore Airlines"),
+    ("TK", "Turkish Airlines"),
+    ("U2", "easyJet"),
+    ("UA", "United Airlines"),
+    ("VS", "Virgin Atlantic"),
+    ("WN", "Southwest Airlines"),
+];
+
+/// Words that have to appear before bare flight codes are trusted, since
+/// "UA 12" style tokens are common in unrelated text
+const TRAVEL_KEYWORDS: &[&str] = &["flight", "boarding", "itinerary", "departure", "departs", "airline", "gate"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageInfo {
+    /// ISO 639-3 code, e.g. "eng"
+    pub code: String,
+    pub name: String,
+    pub confidence: f64,
+    pub reliable: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadingStats {
+    pub word_count: usize,
+    pub reading_time_minutes: usize,
+    /// Flesch reading ease, only computed for English text
+    pub flesch_reading_ease: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EntityKind {
+    /// `date` is ISO 8601 when the text names a real calendar day
+    Date { date: Option<String> },
+    Amount { value: f64, currency: String },
+    TrackingNumber { carrier: String, tracking_url: String },
+    FlightCode { airline: String, flight_number: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtractedEntity {
+    #[serde(flatten)]
+    pub kind: EntityKind,
+    pub text: String,
+    /// Byte offsets into the plain text the entity was found in
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextAnalysis {
+    pub language: Option<LanguageInfo>,
+    pub reading: ReadingStats,
+    pub entities: Vec<ExtractedEntity>,
+}
+
+/// Run the whole pipeline over an email body, HTML or plain text
+pub fn analyze(body: &str, reference_date: NaiveDate) -> TextAnalysis {
+    let text = to_plain_text(body);
+    let language = detect_language(&text);
+    let is_english = language.as_ref().is_some_and(|l| l.code == "eng");
+
+    TextAnalysis {
+        reading: reading_stats(&text, is_english),
+        entities: extract_entities(&text, reference_date),
+        language,
+    }
+}
+
+/// Strip markup from HTML bodies; plain text is returned unchanged
+pub fn to_plain_text(body: &str) -> String {
+    if !body.contains("</") && !body.to_ascii_lowercase().contains("<br") {
+        return body.to_string();
+    }
+
+    let text = HTML_BLOCK.replace_all(body, " ");
+    let text = HTML_BREAK.replace_all(&text, "\n");
+    let text = HTML_TAG.replace_all(&text, " ");
+    text.replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn detect_language(text: &str) -> Option<LanguageInfo> {
+    whatlang::detect(text).map(|info| LanguageInfo {
+        code: info.lang().code().to_string(),
+        name: info.lang().eng_name().to_string(),
+        confidence: info.confidence(),
+        reliable: info.is_reliable(),
+    })
+}
+
+pub fn reading_stats(text: &str, is_english: bool) -> ReadingStats {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let word_count = words.len();
+
+    let flesch_reading_ease = (is_english && word_count > 0).then(|| {
+        let sentences = text
+            .split(['.', '!', '?'])
+            .filter(|s| s.chars().any(|c| c.is_alphanumeric()))
+            .count()
+            .max(1) as f64;
+        let syllables: usize = words.iter().map(|w| count_syllables(w)).sum();
+        let score = 206.835 - 1.015 * (word_count as f64 / sentences) - 84.6 * (syllables as f64 / word_count as f64);
+        (score * 10.0).round() / 10.0
+    });
+
+    ReadingStats {
+        word_count,
+        reading_time_minutes: word_count.div_ceil(WORDS_PER_MINUTE),
+        flesch_reading_ease,
+    }
+}
+
+/// Rough English syllable count: vowel groups, less a silent final "e"
+fn count_syllables(word: &str) -> usize {
+    let word: String = word.chars().filter(|c| c.is_alphabetic()).collect::<String>().to_lowercase();
+    if word.is_empty() {
+        return 0;
+    }
+
+    let mut count = 0;
+    let mut previous_vowel = false;
+    for c in word.chars() {
+        let vowel = "aeiouy".contains(c);
+        if vowel && !previous_vowel {
+            count += 1;
+        }
+        previous_vowel = vowel;
+    }
+    if word.ends_with('e') && !word.ends_with("le") && count > 1 {
+        count -= 1;
+    }
+    count.max(1)
+}
+
+/// Find dates, amounts, tracking numbers and flight codes in plain text.
+/// Dates without a year are placed in `reference_date`'s year.
+pub fn extract_entities(text: &str, reference_date: NaiveDate) -> Vec<ExtractedEntity> {
+    let mut entities = Vec::new();
+    let lower = text.to_lowercase();
+
+    // Earlier, more specific patterns win where matches overlap
+    for caps in ISO_DATE.captures_iter(text) {
+        let date = ymd(&caps[1], &caps[2], &caps[3]);
+        push_entity(&mut entities, &caps, EntityKind::Date { date });
+    }
+    for caps in DAY_MONTH_DATE.captures_iter(text) {
+        let year = caps.get(3).map_or(reference_date.year().to_string(), |m| m.as_str().to_string());
+        let date = month_number(&caps[2]).and_then(|month| ymd(&year, &month.to_string(), &caps[1]));
+        push_entity(&mut entities, &caps, EntityKind::Date { date });
+    }
+    for caps in MONTH_DAY_DATE.captures_iter(text) {
+        let year = caps.get(3).map_or(reference_date.year().to_string(), |m| m.as_str().to_string());
+        let date = month_number(&caps[1]).and_then(|month| ymd(&year, &month.to_string(), &caps[2]));
+        push_entity(&mut entities, &caps, EntityKind::Date { date });
+    }
+    for caps in NUMERIC_DATE.captures_iter(text) {
+        // Read as month/day first, falling back to day/month when that is
+        // not a valid date
+        let date = ymd(&caps[3], &caps[1], &caps[2]).or_else(|| ymd(&caps[3], &caps[2], &caps[1]));
+        push_entity(&mut entities, &caps, EntityKind::Date { date });
+    }
+
+    for caps in PREFIX_AMOUNT.captures_iter(text) {
+        let currency = caps
+            .get(1)
+            .map(|m| m.as_str().to_string())
+            .or_else(|| caps.get(2).and_then(|m| currency_for_symbol(m.as_str())));
+        if let (Some(currency), Some(value)) = (currency, parse_amount(&caps[3])) {
+            push_entity(&mut entities, &caps, EntityKind::Amount { value, currency });
+        }
+    }
+    for caps in SUFFIX_AMOUNT.captures_iter(text) {
+        let currency = match caps[2].to_lowercase().as_str() {
+            "dollars" => "USD".to_string(),
+            "euros" => "EUR".to_string(),
+            "pounds" => "GBP".to_string(),
+            code => code.to_uppercase(),
+        };
+        if let Some(value) = parse_amount(&caps[1]) {
+            push_entity(&mut entities, &caps, EntityKind::Amount { value, currency });
+        }
+    }
+
+    for m in UPS_TRACKING.find_iter(text) {
+        push_tracking(&mut entities, m, "UPS", "https://www.ups.com/track?tracknum=");
+    }
+    for m in USPS_TRACKING.find_iter(text) {
+        push_tracking(&mut entities, m, "USPS", "https://tools.usps.com/go/TrackConfirmAction?tLabels=");
+    }
+    // Bare digit runs are only tracking numbers when the carrier is named
+    if lower.contains("fedex") {
+        for m in FEDEX_TRACKING.find_iter(text) {
+            push_tracking(&mut entities, m, "FedEx", "https://www.fedex.com/fedextrack/?trknbr=");
+        }
+    }
+    if lower.contains("dhl") {
+        for m in DHL_TRACKING.find_iter(text) {
+            push_tracking(&mut entities, m, "DHL", "https://www.dhl.com/en/express/tracking.html?AWB=");
+        }
+    }
+
+    if TRAVEL_KEYWORDS.iter().any(|keyword| lower.contains(keyword)) {
+        for caps in FLIGHT_CODE.captures_iter(text) {
+            if let Some((code, airline)) = AIRLINES.iter().find(|(code, _)| *code == &caps[1]) {
+                let kind = EntityKind::FlightCode {
+                    airline: airline.to_string(),
+                    flight_number: format!("{}{}", code, &caps[2]),
+                };
+                push_entity(&mut entities, &caps, kind);
+            }
+        }
+    }
+
+    entities.sort_by_key(|e| e.start);
+    entities
+}
+
+fn push_entity(entities: &mut Vec<ExtractedEntity>, caps: &Captures, kind: EntityKind) {
+    let whole = caps.get(0).expect("capture group 0 always matches");
+    push_span(entities, whole.start(), whole.end(), whole.as_str(), kind);
+}
+
+fn push_tracking(entities: &mut Vec<ExtractedEntity>, m: regex::Match, carrier: &str, url_prefix: &str) {
+    let kind = EntityKind::TrackingNumber {
+        carrier: carrier.to_string(),
+        tracking_url: format!("{}{}", url_prefix, m.as_str()),
+    };
+    push_span(entities, m.start(), m.end(), m.as_str(), kind);
+}
+
+fn push_span(entities: &mut Vec<ExtractedEntity>, start: usize, end: usize, text: &str, kind: EntityKind) {
+    if entities.iter().any(|e| start < e.end && e.start < end) {
+        return;
+    }
+    entities.push(ExtractedEntity {
+        kind,
+        text: text.trim().to_string(),
+        start,
+        end,
+    });
+}
+
+fn ymd(year: &str, month: &str, day: &str) -> Option<String> {
+    let date = NaiveDate::from_ymd_opt(year.parse().ok()?, month.parse().ok()?, day.parse().ok()?)?;
+    Some(date.format("%Y-%m-%d").to_string())
+}
+
+fn month_number(name: &str) -> Option<u32> {
+    let prefix = name.get(..3)?.to_lowercase();
+    ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"]
+        .iter()
+        .position(|m| *m == prefix)
+        .map(|index| index as u32 + 1)
+}
+
+fn currency_for_symbol(symbol: &str) -> Option<String> {
+    let code = match symbol {
+        "$" => "USD",
+        "€" => "EUR",
+        "£" => "GBP",
+        "¥" => "JPY",
+        _ => return None,
+    };
+    Some(code.to_string())
+}
+
+/// Parse "1,234.56", "1.234,56" or "1 234" style numbers. The last
+/// separator is the decimal point when at most two digits follow it.
+fn parse_amount(raw: &str) -> Option<f64> {
+    let raw = raw.trim();
+    let decimal_at = raw
+        .rfind(['.', ','])
+        .filter(|&i| raw.len() - i - 1 <= 2);
+
+    let mut normalized = String::with_capacity(raw.len());
+    for (i, c) in raw.char_indices() {
+        if c.is_ascii_digit() {
+            normalized.push(c);
+        } else if Some(i) == decimal_at {
+            normalized.push('.');
+        }
+    }
+    normalized.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 1, 15).unwrap()
+    }
+
+    #[test]
+    fn extracts_dates_and_amounts() {
+        let text = "Your order of $1,299.99 ships on March 3rd. Refund of 45,50 EUR issued 2025-02-01.";
+        let entities = extract_entities(text, reference());
+        let kinds: Vec<_> = entities.iter().map(|e| e.kind.clone()).collect();
+
+        assert_eq!(
+            kinds,
+            vec![
+                EntityKind::Amount { value: 1299.99, currency: "USD".to_string() },
+                EntityKind::Date { date: Some("2025-03-03".to_string()) },
+                EntityKind::Amount { value: 45.5, currency: "EUR".to_string() },
+                EntityKind::Date { date: Some("2025-02-01".to_string()) },
+            ]
+        );
+    }
+
+    #[test]
+    fn extracts_tracking_numbers_and_flights() {
+        let text = "Package 1Z999AA10123456784 is on its way. Your flight UA 857 departs at 10:00.";
+        let entities = extract_entities(text, reference());
+
+        assert_eq!(entities.len(), 2);
+        assert!(matches!(&entities[0].kind, EntityKind::TrackingNumber { carrier, .. } if carrier == "UPS"));
+        assert!(matches!(&entities[1].kind, EntityKind::FlightCode { flight_number, .. } if flight_number == "UA857"));
+    }
+
+    #[test]
+    fn flight_codes_need_travel_context() {
+        assert!(extract_entities("See section BA 12 of the contract.", reference()).is_empty());
+    }
+
+    #[test]
+    fn reading_time_rounds_up() {
+        let text = "word ".repeat(231);
+        assert_eq!(reading_stats(&text, false).reading_time_minutes, 2);
+        assert_eq!(reading_stats("", false).reading_time_minutes, 0);
+    }
+}
@@ -0,0 +1,164 @@
+//! Time zone handling for tasks and the calendar
+//!
+//! Times are stored in UTC. Anything the user enters or sees as a local
+//! time is converted through the zone in the `time.zone` setting, or the
+//! operating system's zone when that is empty, so a trip or a DST change
+//! does not move stored times.
+//!
+//! Google Tasks keeps a due date as midnight UTC of the chosen day; that
+//! form is a calendar date rather than an instant and is never shifted.
+
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
+use rusqlite::Connection;
+use serde::Serialize;
+
+use crate::database::DatabaseManager;
+use crate::models::task_metadata::TimeBlock;
+use crate::services::settings;
+use crate::utils::time;
+
+/// IANA zone name; empty follows the operating system
+pub const TIME_ZONE_SETTING: &str = "time.zone";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimeZoneInfo {
+    /// Zone in use
+    pub zone: String,
+    pub detected: String,
+    pub configured: Option<String>,
+    /// Current offset from UTC, e.g. "+02:00"
+    pub offset: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TimeService {
+    zone: Tz,
+}
+
+impl TimeService {
+    pub fn new(zone: Tz) -> Self {
+        Self { zone }
+    }
+
+    /// The configured zone, or the system's
+    pub fn load(conn: &Connection) -> anyhow::Result<Self> {
+        Ok(Self::new(configured_zone(conn)?.unwrap_or_else(time::system_zone)))
+    }
+
+    /// `load` on a fresh connection
+    pub fn from_db(db_manager: &DatabaseManager) -> anyhow::Result<Self> {
+        Self::load(&db_manager.get_connection()?)
+    }
+
+    pub fn zone(&self) -> Tz {
+        self.zone
+    }
+
+    pub fn now(&self) -> DateTime<Tz> {
+        Utc::now().with_timezone(&self.zone)
+    }
+
+    /// The user's current date, which is what "overdue" and "today" mean
+    pub fn today(&self) -> NaiveDate {
+        self.now().date_naive()
+    }
+
+    pub fn to_local(&self, time: DateTime<Utc>) -> DateTime<Tz> {
+        time.with_timezone(&self.zone)
+    }
+
+    /// An RFC 3339 time, or a local time without an offset, as UTC
+    pub fn to_utc(&self, value: &str) -> Option<DateTime<Utc>> {
+        time::to_utc(value, &self.zone)
+    }
+
+    /// A task due value in Google's form. A bare date, or a value already
+    /// in Google's form, is kept; any other time is reduced to the local
+    /// date it falls on, so a due time late in the evening does not land on
+    /// the next day in UTC.
+    pub fn task_due(&self, value: &str) -> Option<String> {
+        let date = match NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d") {
+            Ok(date) => date,
+            Err(_) => match DateTime::parse_from_rfc3339(value.trim()) {
+                Ok(time) if time.offset().local_minus_utc() == 0 && time.time() == NaiveTime::MIN => time.date_naive(),
+                _ => self.to_local(self.to_utc(value)?).date_naive(),
+            },
+        };
+        Some(format!("{}T00:00:00.000Z", date.format("%Y-%m-%d")))
+    }
+
+    pub fn time_block(&self, block: &TimeBlock) -> TimeBlock {
+        block.in_utc(&self.zone)
+    }
+
+    /// Format a stored UTC time in the user's zone
+    pub fn format(&self, value: &str, format: &str) -> Option<String> {
+        Some(self.to_local(self.to_utc(value)?).format(format).to_string())
+    }
+
+    pub fn info(&self, conn: &Connection) -> anyhow::Result<TimeZoneInfo> {
+        Ok(TimeZoneInfo {
+            zone: self.zone.name().to_string(),
+            detected: time::system_zone().name().to_string(),
+            configured: configured_zone(conn)?.map(|zone| zone.name().to_string()),
+            offset: self.now().format("%:z").to_string(),
+        })
+    }
+}
+
+/// The zone chosen in settings, if any
+pub fn configured_zone(conn: &Connection) -> anyhow::Result<Option<Tz>> {
+    let setting = settings::get_setting(conn, TIME_ZONE_SETTING)?;
+    Ok(setting.value.as_str().and_then(time::parse_zone))
+}
+
+/// Google Tasks due value ("YYYY-MM-DDT00:00:00.000Z") as its date
+pub fn due_date(due: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(due.get(..10)?, "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(zone: &str) -> TimeService {
+        TimeService::new(zone.parse().unwrap())
+    }
+
+    #[test]
+    fn test_local_times_across_dst() {
+        let berlin = service("Europe/Berlin");
+        assert_eq!(time::utc_rfc3339(berlin.to_utc("2026-07-01T09:00").unwrap()), "2026-07-01T07:00:00Z");
+        assert_eq!(time::utc_rfc3339(berlin.to_utc("2026-12-01T09:00").unwrap()), "2026-12-01T08:00:00Z");
+        // 02:30 does not exist on the spring-forward night; 02:30 in autumn happens twice
+        assert_eq!(time::utc_rfc3339(berlin.to_utc("2026-03-29T02:30").unwrap()), "2026-03-29T01:30:00Z");
+        assert_eq!(time::utc_rfc3339(berlin.to_utc("2026-10-25T02:30").unwrap()), "2026-10-25T00:30:00Z");
+        assert_eq!(time::utc_rfc3339(berlin.to_utc("2026-07-01T09:00:00-04:00").unwrap()), "2026-07-01T13:00:00Z");
+    }
+
+    #[test]
+    fn test_task_due_keeps_the_local_date() {
+        let la = service("America/Los_Angeles");
+        assert_eq!(la.task_due("2026-10-16").unwrap(), "2026-10-16T00:00:00.000Z");
+        assert_eq!(la.task_due("2026-10-16T23:00:00-07:00").unwrap(), "2026-10-16T00:00:00.000Z");
+        assert_eq!(la.task_due("2026-10-16T22:30").unwrap(), "2026-10-16T00:00:00.000Z");
+        assert_eq!(la.task_due("2026-10-16T00:00:00.000Z").unwrap(), "2026-10-16T00:00:00.000Z");
+        assert_eq!(due_date("2026-10-16T00:00:00.000Z"), NaiveDate::from_ymd_opt(2026, 10, 16));
+    }
+
+    #[test]
+    fn test_time_blocks_keep_their_zone() {
+        let block = TimeBlock {
+            start_time: "2026-10-16T09:00".to_string(),
+            end_time: "2026-10-16T10:30".to_string(),
+            time_zone: Some("Asia/Tokyo".to_string()),
+        };
+        let stored = service("Europe/London").time_block(&block);
+        assert_eq!(stored.start_time, "2026-10-16T00:00:00Z");
+        assert_eq!(stored.end_time, "2026-10-16T01:30:00Z");
+        assert_eq!(stored.time_zone.as_deref(), Some("Asia/Tokyo"));
+        // Converting again is a no-op
+        assert_eq!(service("UTC").time_block(&stored).start_time, stored.start_time);
+    }
+}
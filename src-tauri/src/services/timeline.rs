@@ -0,0 +1,487 @@
+//! Activity timeline
+//!
+//! A chronological feed of what the user did over a few days, grouped by
+//! day in the user's time zone, for questions like "what did I do on
+//! Tuesday?". It merges:
+//!
+//! - journaled changes to tasks, notes, threads and the rest, leaving out
+//!   changes that were undone
+//! - task completions, from Google's completion time of synced tasks, or
+//!   from the journal for tasks not synced since
+//! - sent mail from the Gmail message store
+//! - notes and chat sessions by the time they were created
+//!
+//! Everything is read from local data, so the timeline works offline but
+//! only shows mail and tasks that have been synced. The active workspace's
+//! scope applies to accounts, task lists and note folders. A busy day shows
+//! its latest `MAX_ITEMS_PER_DAY` items and counts the rest.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::database::operations::change_journal_operations::{self, JournalEntry};
+use crate::database::operations::timeline_operations;
+use crate::database::operations::workspace_operations::WorkspaceKind;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::gmail::thread_actions::THREAD_ENTITY;
+use crate::services::google::task_activity;
+use crate::services::i18n;
+use crate::services::time_service::TimeService;
+use crate::services::workspaces;
+use crate::utils::time;
+
+/// Longest range one request may cover
+pub const MAX_RANGE_DAYS: i64 = 92;
+/// Most items shown for one day; older ones are counted, not listed
+pub const MAX_ITEMS_PER_DAY: usize = 100;
+
+const SQL_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityType {
+    Change,
+    SentMail,
+    TaskCompleted,
+    NoteCreated,
+    ChatSession,
+}
+
+impl ActivityType {
+    pub const ALL: [ActivityType; 5] = [
+        ActivityType::Change,
+        ActivityType::SentMail,
+        ActivityType::TaskCompleted,
+        ActivityType::NoteCreated,
+        ActivityType::ChatSession,
+    ];
+}
+
+/// Days from `start` to `end`, both included, in the user's time zone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelineRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl TimelineRange {
+    pub fn day(date: NaiveDate) -> Self {
+        Self { start: date, end: date }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.end < self.start {
+            return Err(LibreOllamaError::InvalidInput {
+                message: "The timeline range ends before it starts".to_string(),
+                field: Some("range".to_string()),
+            });
+        }
+        if (self.end - self.start).num_days() >= MAX_RANGE_DAYS {
+            return Err(LibreOllamaError::InvalidInput {
+                message: format!("The timeline covers at most {} days at a time", MAX_RANGE_DAYS),
+                field: Some("range".to_string()),
+            });
+        }
+        Ok(())
+    }
+
+    /// Start of the first day and of the day after the last in `zone`
+    fn bounds(&self, zone: &Tz) -> (DateTime<Tz>, DateTime<Tz>) {
+        let midnight = |date: NaiveDate| {
+            let naive = date.and_time(NaiveTime::MIN);
+            time::local_to_utc(naive, zone).unwrap_or_else(|| naive.and_utc()).with_timezone(zone)
+        };
+        (midnight(self.start), midnight(self.end + chrono::Duration::days(1)))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityItem {
+    #[serde(rename = "type")]
+    pub kind: ActivityType,
+    /// Task, note, chat session or message ID, or the journal entry for
+    /// changes to anything else
+    pub id: String,
+    /// For changes, what was changed: "task", "note", "gmail_thread", ...
+    pub entity_type: Option<String>,
+    pub account_id: Option<String>,
+    pub thread_id: Option<String>,
+    pub title: String,
+    /// One line in the active locale, e.g. the recipients of sent mail
+    pub detail: Option<String>,
+    /// RFC 3339, with the offset of the user's time zone
+    pub at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityDay {
+    /// "YYYY-MM-DD"
+    pub date: String,
+    /// The date in the active locale, e.g. "Tuesday, October 13"
+    pub label: String,
+    /// Oldest first
+    pub items: Vec<ActivityItem>,
+    /// Earlier items of the day left out past `MAX_ITEMS_PER_DAY`
+    pub omitted: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityTimeline {
+    pub range: TimelineRange,
+    pub types: Vec<ActivityType>,
+    /// Days with activity, oldest first
+    pub days: Vec<ActivityDay>,
+    /// Items found, including those omitted from busy days
+    pub total: usize,
+}
+
+/// A time stored in the system's local time, "YYYY-MM-DD HH:MM:SS" with
+/// optional fraction, or RFC 3339, in `zone`
+fn parse_local(timestamp: &str, zone: &Tz) -> Option<DateTime<Tz>> {
+    match NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S%.f") {
+        Ok(naive) => Local.from_local_datetime(&naive).earliest().map(|time| time.with_timezone(zone)),
+        Err(_) => DateTime::parse_from_rfc3339(timestamp).ok().map(|time| time.with_timezone(zone)),
+    }
+}
+
+/// A SQLite `CURRENT_TIMESTAMP`, which is UTC, in `zone`
+fn parse_utc(timestamp: &str, zone: &Tz) -> Option<DateTime<Tz>> {
+    NaiveDateTime::parse_from_str(timestamp, SQL_TIME_FORMAT)
+        .ok()
+        .map(|naive| zone.from_utc_datetime(&naive))
+}
+
+fn payload_title(entry: &JournalEntry) -> Option<String> {
+    [Some(&entry.payload), entry.previous_payload.as_ref()]
+        .into_iter()
+        .flatten()
+        .find_map(|payload| payload.get("title").and_then(Value::as_str))
+        .filter(|title| !title.trim().is_empty())
+        .map(str::to_string)
+}
+
+/// Looks up the titles of journaled entities, once each
+struct Titles<'a> {
+    conn: &'a Connection,
+    known: HashMap<(String, String), Option<String>>,
+}
+
+impl<'a> Titles<'a> {
+    fn new(conn: &'a Connection) -> Self {
+        Self { conn, known: HashMap::new() }
+    }
+
+    fn of(&mut self, entry: &JournalEntry) -> Result<String> {
+        let key = (entry.entity_type.clone(), entry.entity_id.clone());
+        if !self.known.contains_key(&key) {
+            let title = match entry.entity_type.as_str() {
+                "task" | "task_metadata" => timeline_operations::task_title(self.conn, &entry.entity_id)?,
+                "note" => match entry.entity_id.parse() {
+                    Ok(id) => timeline_operations::note_title(self.conn, id)?,
+                    Err(_) => None,
+                },
+                THREAD_ENTITY => {
+                    timeline_operations::thread_subject(self.conn, entry.account_id.as_deref(), &entry.entity_id)?
+                }
+                _ => None,
+            };
+            // Entities created here carry their title in the journal
+            let title = match title {
+                Some(title) => Some(title),
+                None => change_journal_operations::replay_entity(self.conn, &entry.entity_type, &entry.entity_id)?
+                    .and_then(|state| state.get("title").and_then(Value::as_str).map(str::to_string))
+                    .filter(|title| !title.trim().is_empty()),
+            };
+            self.known.insert(key.clone(), title);
+        }
+        let title = self.known[&key].clone().or_else(|| payload_title(entry));
+        Ok(title.unwrap_or_else(|| match entry.entity_type.as_str() {
+            THREAD_ENTITY => i18n::t("timeline-no-subject"),
+            _ => i18n::t("timeline-untitled"),
+        }))
+    }
+}
+
+fn is_completion(entry: &JournalEntry) -> bool {
+    entry.entity_type == "task"
+        && entry.operation == "update"
+        && entry.payload.get("status").and_then(Value::as_str) == Some("completed")
+}
+
+/// Describe a journaled change other than a task completion
+fn change_detail(entry: &JournalEntry) -> String {
+    match entry.entity_type.as_str() {
+        "task" => task_activity::summarize_change(entry),
+        THREAD_ENTITY => i18n::t("timeline-labels-changed"),
+        _ => match entry.operation.as_str() {
+            "create" => i18n::t("timeline-created"),
+            "delete" => i18n::t("timeline-deleted"),
+            _ => i18n::t("timeline-edited"),
+        },
+    }
+}
+
+/// An item with the time it is sorted and grouped by
+type DatedItem = (DateTime<Tz>, ActivityItem);
+
+/// Journaled changes and completions in the range, at most
+/// `MAX_ITEMS_PER_DAY` a day, with the number left out per day. Completions
+/// of the tasks in `synced_completions` are left to the synced data.
+fn journal_items(
+    conn: &Connection,
+    (start, end): (DateTime<Tz>, DateTime<Tz>),
+    wanted: &[ActivityType],
+    scope: &workspaces::WorkspaceScope,
+    synced_completions: &HashSet<String>,
+) -> Result<(Vec<DatedItem>, HashMap<NaiveDate, usize>)> {
+    let zone = start.timezone();
+    let utc = |time: DateTime<Tz>| time.with_timezone(&Utc).format(SQL_TIME_FORMAT).to_string();
+    let mut titles = Titles::new(conn);
+    let mut items = Vec::new();
+    let mut per_day: HashMap<NaiveDate, usize> = HashMap::new();
+    let mut omitted: HashMap<NaiveDate, usize> = HashMap::new();
+    // Newest first, so a busy day keeps its latest changes and titles are
+    // only looked up for those
+    for entry in change_journal_operations::get_changes_between(conn, &utc(start), &utc(end))?.into_iter().rev() {
+        // Undone changes and their undo entries cancel out; created notes
+        // come from the notes themselves
+        if entry.undone || (entry.entity_type == "note" && entry.operation == "create") {
+            continue;
+        }
+        if entry.account_id.as_deref().is_some_and(|id| !scope.allows(WorkspaceKind::Account, id)) {
+            continue;
+        }
+        let kind = if is_completion(&entry) { ActivityType::TaskCompleted } else { ActivityType::Change };
+        if !wanted.contains(&kind) || (kind == ActivityType::TaskCompleted && synced_completions.contains(&entry.entity_id)) {
+            continue;
+        }
+        let Some(at) = parse_utc(&entry.created_at, &zone) else { continue };
+        let shown = per_day.entry(at.date_naive()).or_default();
+        if *shown >= MAX_ITEMS_PER_DAY {
+            *omitted.entry(at.date_naive()).or_default() += 1;
+            continue;
+        }
+        *shown += 1;
+        let is_task = matches!(entry.entity_type.as_str(), "task" | "task_metadata");
+        let item = ActivityItem {
+            kind,
+            id: if is_task || entry.entity_type == "note" { entry.entity_id.clone() } else { entry.id.to_string() },
+            entity_type: (kind == ActivityType::Change).then(|| entry.entity_type.clone()),
+            account_id: entry.account_id.clone(),
+            thread_id: (entry.entity_type == THREAD_ENTITY).then(|| entry.entity_id.clone()),
+            title: titles.of(&entry)?,
+            detail: (kind == ActivityType::Change).then(|| change_detail(&entry)),
+            at: at.to_rfc3339(),
+        };
+        items.push((at, item));
+    }
+    Ok((items, omitted))
+}
+
+/// Everything done in `range`, limited to `types` (all of them when
+/// empty), grouped by day in the user's time zone
+pub fn timeline(conn: &Connection, range: TimelineRange, types: &[ActivityType]) -> Result<ActivityTimeline> {
+    range.validate()?;
+    let wanted: Vec<ActivityType> = if types.is_empty() {
+        ActivityType::ALL.to_vec()
+    } else {
+        ActivityType::ALL.into_iter().filter(|kind| types.contains(kind)).collect()
+    };
+    let zone = TimeService::load(conn)?.zone();
+    let (start, end) = range.bounds(&zone);
+    // Notes and chat sessions are stored in the system's local time
+    let local = |time: DateTime<Tz>| time.with_timezone(&Local).naive_local().format(SQL_TIME_FORMAT).to_string();
+    let scope = workspaces::scope(conn)?;
+
+    let mut items = Vec::new();
+    let mut synced_completions = HashSet::new();
+    if wanted.contains(&ActivityType::TaskCompleted) {
+        let rfc3339 = |time: DateTime<Tz>| time::utc_rfc3339(time.with_timezone(&Utc));
+        for task in timeline_operations::tasks_completed_between(conn, &rfc3339(start), &rfc3339(end))? {
+            synced_completions.insert(task.task_id.clone());
+            if !scope.allows(WorkspaceKind::Account, &task.account_id)
+                || !scope.allows(WorkspaceKind::TaskList, &task.task_list_id)
+            {
+                continue;
+            }
+            let Some(at) = DateTime::parse_from_rfc3339(&task.completed_at).ok() else { continue };
+            let at = at.with_timezone(&zone);
+            items.push((at, ActivityItem {
+                kind: ActivityType::TaskCompleted,
+                id: task.task_id,
+                entity_type: None,
+                account_id: Some(task.account_id),
+                thread_id: None,
+                title: Some(task.title).filter(|title| !title.trim().is_empty()).unwrap_or_else(|| i18n::t("timeline-untitled")),
+                detail: None,
+                at: at.to_rfc3339(),
+            }));
+        }
+    }
+    let mut omitted = HashMap::new();
+    if wanted.contains(&ActivityType::Change) || wanted.contains(&ActivityType::TaskCompleted) {
+        let (journaled, left_out) = journal_items(conn, (start, end), &wanted, &scope, &synced_completions)?;
+        items.extend(journaled);
+        omitted = left_out;
+    }
+    if wanted.contains(&ActivityType::SentMail) {
+        for message in timeline_operations::sent_between(conn, start.timestamp_millis(), end.timestamp_millis())? {
+            if !scope.allows(WorkspaceKind::Account, &message.account_id) {
+                continue;
+            }
+            let Some(at) = DateTime::from_timestamp_millis(message.sent_at) else { continue };
+            let at = at.with_timezone(&zone);
+            items.push((at, ActivityItem {
+                kind: ActivityType::SentMail,
+                id: message.message_id,
+                entity_type: None,
+                account_id: Some(message.account_id),
+                thread_id: Some(message.thread_id),
+                title: message.subject.unwrap_or_else(|| i18n::t("timeline-no-subject")),
+                detail: (!message.to.is_empty())
+                    .then(|| i18n::t_args("timeline-sent-to", &[("recipients", message.to.into())])),
+                at: at.to_rfc3339(),
+            }));
+        }
+    }
+    if wanted.contains(&ActivityType::NoteCreated) {
+        for note in timeline_operations::notes_created_between(conn, &local(start), &local(end))? {
+            if !scope.allows_folder(note.folder_id) {
+                continue;
+            }
+            let Some(at) = parse_local(&note.created_at, &zone) else { continue };
+            items.push((at, ActivityItem {
+                kind: ActivityType::NoteCreated,
+                id: note.id.to_string(),
+                entity_type: None,
+                account_id: None,
+                thread_id: None,
+                title: Some(note.title).filter(|title| !title.trim().is_empty()).unwrap_or_else(|| i18n::t("timeline-untitled")),
+                detail: None,
+                at: at.to_rfc3339(),
+            }));
+        }
+    }
+    if wanted.contains(&ActivityType::ChatSession) {
+        for chat in timeline_operations::chats_started_between(conn, &local(start), &local(end))? {
+            let Some(at) = parse_local(&chat.created_at, &zone) else { continue };
+            items.push((at, ActivityItem {
+                kind: ActivityType::ChatSession,
+                id: chat.id.to_string(),
+                entity_type: None,
+                account_id: None,
+                thread_id: None,
+                title: chat.session_name,
+                detail: Some(i18n::t_args("timeline-chat-messages", &[("count", chat.message_count.into())])),
+                at: at.to_rfc3339(),
+            }));
+        }
+    }
+
+    items.sort_by_key(|(at, _)| *at);
+    let total = items.len() + omitted.values().sum::<usize>();
+    let mut days: Vec<ActivityDay> = Vec::new();
+    for (at, item) in items {
+        let date = at.date_naive();
+        let key = date.format("%Y-%m-%d").to_string();
+        match days.last_mut() {
+            Some(day) if day.date == key => day.items.push(item),
+            _ => days.push(ActivityDay {
+                date: key,
+                label: date
+                    .format_localized(&i18n::t("timeline-day-format"), i18n::chrono_locale())
+                    .to_string(),
+                items: vec![item],
+                omitted: omitted.get(&date).copied().unwrap_or_default(),
+            }),
+        }
+    }
+    for day in &mut days {
+        let over = day.items.len().saturating_sub(MAX_ITEMS_PER_DAY);
+        day.items.drain(..over);
+        day.omitted += over;
+    }
+    Ok(ActivityTimeline { range, types: wanted, days, total })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::operations::note_operations;
+    use crate::database::schema::run_migrations;
+    use serde_json::json;
+
+    fn utc(date: NaiveDate, hour: u32) -> String {
+        let local = Local.from_local_datetime(&date.and_hms_opt(hour, 0, 0).unwrap()).earliest().unwrap();
+        local.with_timezone(&Utc).format(SQL_TIME_FORMAT).to_string()
+    }
+
+    #[test]
+    fn test_timeline_groups_by_day() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 12).unwrap();
+        let tuesday = monday.succ_opt().unwrap();
+
+        let journal = |entity_type: &str, id: &str, operation: &str, payload: Value, at: String| {
+            let entry = change_journal_operations::record_change(&conn, entity_type, id, operation, &payload, None, Some("acc")).unwrap();
+            conn.execute("UPDATE change_journal SET created_at = ?1 WHERE id = ?2", rusqlite::params![at, entry]).unwrap();
+        };
+        journal("task", "t1", "create", json!({ "title": "Pay rent" }), utc(monday, 9));
+        journal("task", "t1", "update", json!({ "status": "completed" }), utc(tuesday, 10));
+        journal("task", "t2", "update", json!({ "title": "Call" }), utc(tuesday, 11));
+        let note = note_operations::create_note(&conn, "Minutes", "", "default_user", None).unwrap();
+        conn.execute(
+            "UPDATE notes SET created_at = ?1 WHERE id = ?2",
+            rusqlite::params![format!("{} 08:30:00.25", tuesday), note.id],
+        )
+        .unwrap();
+
+        let week = timeline(&conn, TimelineRange { start: monday, end: tuesday }, &[]).unwrap();
+        assert_eq!(week.total, 4);
+        assert_eq!(week.days.iter().map(|day| day.date.as_str()).collect::<Vec<_>>(), ["2026-10-12", "2026-10-13"]);
+        let tuesday_items = &week.days[1].items;
+        assert_eq!(
+            tuesday_items.iter().map(|item| item.kind).collect::<Vec<_>>(),
+            [ActivityType::NoteCreated, ActivityType::TaskCompleted, ActivityType::Change]
+        );
+        // The completed task is named from the journal it was created in
+        assert_eq!(tuesday_items[1].title, "Pay rent");
+
+        let done = timeline(&conn, TimelineRange::day(tuesday), &[ActivityType::TaskCompleted]).unwrap();
+        assert_eq!(done.total, 1);
+        assert_eq!(done.types, [ActivityType::TaskCompleted]);
+
+        assert!(timeline(&conn, TimelineRange { start: tuesday, end: monday }, &[]).is_err());
+    }
+
+    #[test]
+    fn test_synced_completions_fall_on_the_configured_zone_day() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        crate::services::settings::set_setting(&conn, crate::services::time_service::TIME_ZONE_SETTING, &json!("Pacific/Auckland")).unwrap();
+        conn.execute(
+            "INSERT INTO search_items (domain, account_id, item_id, container_id, title, status, completed_at)
+             VALUES ('task', 'acc', 't1', 'list', 'Pay rent', 'completed', '2026-10-13T20:00:00Z')",
+            [],
+        )
+        .unwrap();
+        // Completed here too; the synced completion stands for both
+        change_journal_operations::record_change(&conn, "task", "t1", "update", &json!({ "status": "completed" }), None, Some("acc")).unwrap();
+        conn.execute("UPDATE change_journal SET created_at = '2026-10-13 20:00:00'", []).unwrap();
+
+        let tuesday = NaiveDate::from_ymd_opt(2026, 10, 13).unwrap();
+        let wednesday = tuesday.succ_opt().unwrap();
+        // 20:00 UTC is 09:00 the next morning in Auckland
+        assert_eq!(timeline(&conn, TimelineRange::day(tuesday), &[ActivityType::TaskCompleted]).unwrap().total, 0);
+        let done = timeline(&conn, TimelineRange::day(wednesday), &[ActivityType::TaskCompleted]).unwrap();
+        assert_eq!(done.total, 1);
+        assert_eq!(done.days[0].items[0].title, "Pay rent");
+        assert!(done.days[0].items[0].at.starts_with("2026-10-14T09:00:00+13:00"));
+    }
+}
@@ -0,0 +1,301 @@
+//! Speech-to-text for voice notes and audio attachments
+//!
+//! Audio is transcribed on this machine by default, with a whisper.cpp
+//! binary and model the user points us at. Audio that is not already WAV is
+//! converted to 16 kHz mono with ffmpeg first, since that is the only input
+//! whisper.cpp reads reliably. Alternatively transcription can go to an HTTP
+//! endpoint speaking the OpenAI `audio/transcriptions` protocol, such as the
+//! whisper.cpp server or faster-whisper-server.
+
+use std::path::{Path, PathBuf};
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::database::operations::preference_operations;
+use crate::errors::{LibreOllamaError, Result};
+use crate::utils::http_client::streaming_http_client;
+
+/// user_preferences key holding the JSON encoded `TranscriptionSettings`
+pub const TRANSCRIPTION_SETTINGS_PREFERENCE: &str = "transcription.settings";
+
+/// Largest audio file accepted, in bytes
+pub const MAX_AUDIO_BYTES: usize = 100 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptionEngine {
+    WhisperCpp,
+    Http,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionSettings {
+    pub engine: TranscriptionEngine,
+    /// whisper.cpp executable, looked up on PATH when not absolute
+    pub whisper_binary: String,
+    /// ggml model file used by whisper.cpp
+    pub whisper_model: Option<String>,
+    /// Base URL of an OpenAI compatible transcription server
+    pub endpoint_url: Option<String>,
+    pub endpoint_model: Option<String>,
+    /// Spoken language as an ISO 639-1 code, detected when unset
+    pub language: Option<String>,
+}
+
+impl Default for TranscriptionSettings {
+    fn default() -> Self {
+        Self {
+            engine: TranscriptionEngine::WhisperCpp,
+            whisper_binary: "whisper-cli".to_string(),
+            whisper_model: None,
+            endpoint_url: None,
+            endpoint_model: None,
+            language: None,
+        }
+    }
+}
+
+impl TranscriptionSettings {
+    /// Short name stored alongside each transcript
+    pub fn engine_name(&self) -> String {
+        match self.engine {
+            TranscriptionEngine::WhisperCpp => "whisper.cpp".to_string(),
+            TranscriptionEngine::Http => format!(
+                "http:{}",
+                self.endpoint_model.as_deref().unwrap_or("whisper-1")
+            ),
+        }
+    }
+}
+
+pub fn load_settings(conn: &Connection) -> anyhow::Result<TranscriptionSettings> {
+    Ok(preference_operations::get_preference_value(conn, TRANSCRIPTION_SETTINGS_PREFERENCE)?
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default())
+}
+
+pub fn save_settings(conn: &Connection, settings: &TranscriptionSettings) -> anyhow::Result<()> {
+    preference_operations::set_preference_value(
+        conn,
+        TRANSCRIPTION_SETTINGS_PREFERENCE,
+        &serde_json::to_string(settings)?,
+        "transcription",
+    )
+}
+
+/// Whether a MIME type or filename denotes audio we can transcribe
+pub fn is_audio(mime_type: &str, filename: &str) -> bool {
+    let mime_type = mime_type.to_lowercase();
+    if mime_type.starts_with("audio/") {
+        return true;
+    }
+    let extension = Path::new(filename)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+        .unwrap_or_default();
+    matches!(extension.as_str(), "mp3" | "m4a" | "wav" | "ogg" | "oga" | "opus" | "flac" | "aac" | "amr" | "webm")
+}
+
+/// Transcribe audio bytes with the configured engine
+pub async fn transcribe(settings: &TranscriptionSettings, filename: &str, mime_type: &str, audio: Vec<u8>) -> Result<String> {
+    if !is_audio(mime_type, filename) {
+        return Err(LibreOllamaError::InvalidInput {
+            message: format!("{} ({}) is not an audio file", filename, mime_type),
+            field: Some("mime_type".to_string()),
+        });
+    }
+    if audio.len() > MAX_AUDIO_BYTES {
+        return Err(LibreOllamaError::InvalidInput {
+            message: format!("{} is too large to transcribe ({} bytes)", filename, audio.len()),
+            field: Some("attachment".to_string()),
+        });
+    }
+
+    let transcript = match settings.engine {
+        TranscriptionEngine::WhisperCpp => transcribe_with_whisper_cpp(settings, filename, &audio).await?,
+        TranscriptionEngine::Http => transcribe_with_endpoint(settings, filename, mime_type, audio).await?,
+    };
+    Ok(normalize_transcript(&transcript))
+}
+
+async fn transcribe_with_whisper_cpp(settings: &TranscriptionSettings, filename: &str, audio: &[u8]) -> Result<String> {
+    let model = settings.whisper_model.as_deref().ok_or_else(|| LibreOllamaError::Configuration {
+        message: "No whisper.cpp model configured for transcription".to_string(),
+        config_key: Some(TRANSCRIPTION_SETTINGS_PREFERENCE.to_string()),
+    })?;
+
+    let work_dir = work_dir()?;
+    let result = run_whisper_cpp(settings, model, filename, audio, &work_dir).await;
+    if let Err(e) = tokio::fs::remove_dir_all(&work_dir).await {
+        eprintln!("⚠️ [TRANSCRIPTION] Failed to clean up {}: {}", work_dir.display(), e);
+    }
+    result
+}
+
+async fn run_whisper_cpp(
+    settings: &TranscriptionSettings,
+    model: &str,
+    filename: &str,
+    audio: &[u8],
+    work_dir: &Path,
+) -> Result<String> {
+    let extension = Path::new(filename)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("audio")
+        .to_lowercase();
+    let input = work_dir.join(format!("input.{}", extension));
+    tokio::fs::write(&input, audio).await.map_err(|e| LibreOllamaError::FileSystem {
+        message: format!("Failed to write audio for transcription: {}", e),
+        path: Some(input.display().to_string()),
+    })?;
+
+    let wav = if extension == "wav" {
+        input
+    } else {
+        let wav = work_dir.join("input.wav");
+        run_tool(
+            "ffmpeg",
+            &["-nostdin", "-y", "-loglevel", "error", "-i", &path_arg(&input), "-ar", "16000", "-ac", "1", &path_arg(&wav)],
+        )
+        .await?;
+        wav
+    };
+
+    println!("🎙️ [TRANSCRIPTION] Running whisper.cpp on {}", filename);
+    let language = settings.language.as_deref().unwrap_or("auto");
+    run_tool(
+        &settings.whisper_binary,
+        &["-m", model, "-f", &path_arg(&wav), "-l", language, "--no-timestamps", "--no-prints"],
+    )
+    .await
+}
+
+async fn run_tool(program: &str, args: &[&str]) -> Result<String> {
+    let output = tokio::process::Command::new(program)
+        .args(args)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| LibreOllamaError::Configuration {
+            message: format!("Failed to run {}: {}", program, e),
+            config_key: Some(TRANSCRIPTION_SETTINGS_PREFERENCE.to_string()),
+        })?;
+
+    if !output.status.success() {
+        return Err(LibreOllamaError::Internal {
+            message: format!(
+                "{} exited with {}: {}",
+                program,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+async fn transcribe_with_endpoint(
+    settings: &TranscriptionSettings,
+    filename: &str,
+    mime_type: &str,
+    audio: Vec<u8>,
+) -> Result<String> {
+    #[derive(Deserialize)]
+    struct TranscriptionResponse {
+        text: String,
+    }
+
+    let base_url = settings.endpoint_url.as_deref().ok_or_else(|| LibreOllamaError::Configuration {
+        message: "No transcription endpoint configured".to_string(),
+        config_key: Some(TRANSCRIPTION_SETTINGS_PREFERENCE.to_string()),
+    })?;
+    let url = format!("{}/v1/audio/transcriptions", base_url.trim_end_matches('/').trim_end_matches("/v1"));
+
+    let file_part = reqwest::multipart::Part::bytes(audio)
+        .file_name(filename.to_string())
+        .mime_str(mime_type)
+        .map_err(|e| LibreOllamaError::InvalidInput {
+            message: format!("Invalid audio MIME type {}: {}", mime_type, e),
+            field: Some("mime_type".to_string()),
+        })?;
+    let mut form = reqwest::multipart::Form::new()
+        .part("file", file_part)
+        .text("model", settings.endpoint_model.clone().unwrap_or_else(|| "whisper-1".to_string()))
+        .text("response_format", "json");
+    if let Some(language) = &settings.language {
+        form = form.text("language", language.clone());
+    }
+
+    println!("🎙️ [TRANSCRIPTION] Sending {} to {}", filename, url);
+    let response = streaming_http_client()
+        .post(&url)
+        .multipart(form)
+        .send()
+        .await
+        .map_err(|e| LibreOllamaError::Network {
+            message: format!("Failed to reach transcription endpoint: {}", e),
+            url: Some(url.clone()),
+        })?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(LibreOllamaError::Network {
+            message: format!("Transcription endpoint error {}: {}", status, error_text),
+            url: Some(url),
+        });
+    }
+
+    let body: TranscriptionResponse = response.json().await.map_err(|e| LibreOllamaError::Serialization {
+        message: format!("Failed to parse transcription response: {}", e),
+        data_type: "transcription response".to_string(),
+    })?;
+    Ok(body.text)
+}
+
+/// Join whisper's per-segment lines into paragraphs and drop the
+/// `[BLANK_AUDIO]`-style markers it emits for silence
+pub fn normalize_transcript(raw: &str) -> String {
+    raw.lines()
+        .map(str::trim)
+        .filter(|line| !(line.is_empty() || (line.starts_with('[') && line.ends_with(']'))))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn work_dir() -> Result<PathBuf> {
+    let base = crate::config::get_config_manager()
+        .map(|config| config.paths().temp_dir.clone())
+        .unwrap_or_else(|_| std::env::temp_dir());
+    let dir = base.join("transcription").join(uuid::Uuid::new_v4().to_string());
+    std::fs::create_dir_all(&dir).map_err(|e| LibreOllamaError::FileSystem {
+        message: format!("Failed to create transcription directory: {}", e),
+        path: Some(dir.display().to_string()),
+    })?;
+    Ok(dir)
+}
+
+fn path_arg(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_audio_by_mime_type_or_extension() {
+        assert!(is_audio("audio/ogg", "voice.ogg"));
+        assert!(is_audio("application/octet-stream", "Voicemail.M4A"));
+        assert!(!is_audio("application/pdf", "invoice.pdf"));
+    }
+
+    #[test]
+    fn normalizes_whisper_output() {
+        let raw = " Hello there.\n[BLANK_AUDIO]\n\n Call me back tomorrow.\n";
+        assert_eq!(normalize_transcript(raw), "Hello there. Call me back tomorrow.");
+    }
+}
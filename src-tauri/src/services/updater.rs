@@ -0,0 +1,397 @@
+//! App updates
+//!
+//! `UpdateService` wraps the Tauri updater. Its background task checks the
+//! release manifest of the chosen channel (`stable` or `beta`) every few
+//! hours while automatic updates are on, downloads a new version in the
+//! background and holds it until the user chooses to restart. Downloads are checked
+//! against the updater public key before they can be installed; builds made
+//! without `LIBREOLLAMA_UPDATER_PUBKEY` cannot update themselves.
+//!
+//! Releases roll out in stages. A manifest may carry a `rollout` percentage,
+//! and each install compares it with a bucket derived from its own random
+//! install ID and the new version, so the same installs get a release first
+//! and widening the rollout only ever adds installs. Checking by hand skips
+//! the rollout.
+//!
+//! Progress is announced on `backend://update-status` whenever the state
+//! changes; `get_update_status` also reports bytes downloaded so far.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_updater::{Update, UpdaterExt};
+use url::Url;
+
+use crate::database::operations::preference_operations;
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::events::{BackendEvent, EventBus, UpdateState, UpdateStatusChangedEvent};
+use crate::services::settings;
+use crate::services::task_supervisor::TaskSpec;
+use crate::utils::http_client::http_client;
+
+pub const UPDATE_CHANNEL_SETTING: &str = "updates.channel";
+/// Whether updates are checked for on a schedule and downloaded in the background
+pub const AUTO_UPDATE_SETTING: &str = "updates.automatic";
+pub const UPDATE_CHANNELS: &[&str] = &["stable", "beta"];
+
+const INSTALL_ID_PREFERENCE: &str = "updates.install_id";
+const STABLE_ENDPOINT: &str = "https://github.com/mtmitchel/LibreOllama/releases/latest/download/latest.json";
+const BETA_ENDPOINT: &str = "https://github.com/mtmitchel/LibreOllama/releases/download/beta/latest.json";
+const RELEASES_API: &str = "https://api.github.com/repos/mtmitchel/LibreOllama/releases/tags";
+const UPDATER_PUBKEY: Option<&str> = option_env!("LIBREOLLAMA_UPDATER_PUBKEY");
+/// The first check waits for startup traffic to settle
+const FIRST_CHECK_DELAY: Duration = Duration::from_secs(60);
+const CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+fn endpoint(channel: &str) -> &'static str {
+    match channel {
+        "beta" => BETA_ENDPOINT,
+        _ => STABLE_ENDPOINT,
+    }
+}
+
+/// Where this install falls in a release's rollout, from 0 to 99
+pub fn rollout_bucket(install_id: &str, version: &str) -> u8 {
+    let digest = Sha256::digest(format!("{}:{}", install_id, version).as_bytes());
+    (u16::from_be_bytes([digest[0], digest[1]]) % 100) as u8
+}
+
+/// Whether a release rolled out to `rollout` percent of installs has
+/// reached this one; a manifest without a rollout goes to everyone
+pub fn in_rollout(rollout: Option<u8>, install_id: &str, version: &str) -> bool {
+    rollout.is_none_or(|percent| rollout_bucket(install_id, version) < percent.min(100))
+}
+
+#[derive(Default)]
+struct UpdateSlot {
+    status: Option<UpdateStatusChangedEvent>,
+    update: Option<Update>,
+    /// The verified installer, once downloaded
+    bytes: Option<Arc<Vec<u8>>>,
+}
+
+#[derive(Deserialize)]
+struct GitHubRelease {
+    body: Option<String>,
+}
+
+/// Checks, downloads and installs updates, managed as Tauri state
+#[derive(Clone)]
+pub struct UpdateService {
+    db_manager: Arc<DatabaseManager>,
+    app: AppHandle,
+    slot: Arc<Mutex<UpdateSlot>>,
+    /// The channel the background task last checked, cleared while
+    /// automatic updates are off
+    checked_channel: Arc<Mutex<Option<String>>>,
+}
+
+impl UpdateService {
+    pub fn new(db_manager: Arc<DatabaseManager>, app: AppHandle) -> Self {
+        Self {
+            db_manager,
+            app,
+            slot: Arc::new(Mutex::new(UpdateSlot::default())),
+            checked_channel: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Check on a schedule while automatic updates are on; changing the
+    /// channel or turning them on checks again straight away
+    pub fn task(&self) -> TaskSpec {
+        let service = self.clone();
+        let schedule = self.clone();
+        TaskSpec::new("updates", move || {
+            let service = service.clone();
+            async move { Ok(service.check_in_background().await?) }
+        })
+        .every(move || schedule.next_check())
+        .first_run_after(FIRST_CHECK_DELAY)
+        .wake_on(&[UPDATE_CHANNEL_SETTING, AUTO_UPDATE_SETTING])
+    }
+
+    /// No wait when the channel changed or automatic updates were turned
+    /// on since the last check
+    fn next_check(&self) -> Duration {
+        let checked = self.checked_channel.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        if self.automatic() && checked.is_none_or(|channel| channel != self.channel()) {
+            Duration::ZERO
+        } else {
+            CHECK_INTERVAL
+        }
+    }
+
+    async fn check_in_background(&self) -> Result<()> {
+        let automatic = self.automatic();
+        *self.checked_channel.lock().unwrap_or_else(|e| e.into_inner()) = automatic.then(|| self.channel());
+        if automatic && self.check(false).await? {
+            self.download().await?;
+        }
+        Ok(())
+    }
+
+    fn automatic(&self) -> bool {
+        self.db_manager
+            .get_connection()
+            .and_then(|conn| settings::get_bool(&conn, AUTO_UPDATE_SETTING))
+            .unwrap_or(true)
+    }
+
+    fn channel(&self) -> String {
+        self.db_manager
+            .get_connection()
+            .and_then(|conn| settings::get_setting(&conn, UPDATE_CHANNEL_SETTING))
+            .ok()
+            .and_then(|setting| setting.value.as_str().map(str::to_string))
+            .unwrap_or_else(|| UPDATE_CHANNELS[0].to_string())
+    }
+
+    /// A random ID kept for the life of the install, only used for rollouts
+    fn install_id(&self) -> Result<String> {
+        let conn = self.db_manager.get_connection()?;
+        if let Some(id) = preference_operations::get_preference_value(&conn, INSTALL_ID_PREFERENCE)? {
+            return Ok(id);
+        }
+        let id = uuid::Uuid::new_v4().to_string();
+        preference_operations::set_preference_value(&conn, INSTALL_ID_PREFERENCE, &id, "updates")?;
+        Ok(id)
+    }
+
+    pub fn status(&self) -> UpdateStatusChangedEvent {
+        let slot = self.slot.lock().unwrap_or_else(|e| e.into_inner());
+        slot.status.clone().unwrap_or_else(|| UpdateStatusChangedEvent {
+            state: UpdateState::Idle,
+            current_version: self.app.package_info().version.to_string(),
+            channel: self.channel(),
+            available_version: None,
+            release_notes: None,
+            release_date: None,
+            downloaded_bytes: 0,
+            total_bytes: None,
+            last_checked_at: None,
+            error: None,
+        })
+    }
+
+    /// Change the status, announcing it when the state moved
+    fn update_status(&self, change: impl FnOnce(&mut UpdateStatusChangedEvent)) {
+        let before = self.status();
+        let mut after = before.clone();
+        change(&mut after);
+        self.slot.lock().unwrap_or_else(|e| e.into_inner()).status = Some(after.clone());
+        if before.state != after.state {
+            if let Some(event_bus) = self.app.try_state::<EventBus>() {
+                event_bus.emit(BackendEvent::UpdateStatusChanged(after));
+            }
+        }
+    }
+
+    fn fail(&self, error: LibreOllamaError) -> LibreOllamaError {
+        let message = error.to_string();
+        self.update_status(|status| {
+            status.state = UpdateState::Error;
+            status.error = Some(message);
+        });
+        error
+    }
+
+    /// Ask the channel's manifest for a newer version. Returns whether one
+    /// is available to this install; `manual` checks ignore the rollout.
+    pub async fn check(&self, manual: bool) -> Result<bool> {
+        let pubkey = UPDATER_PUBKEY.ok_or_else(|| LibreOllamaError::Configuration {
+            message: "This build cannot verify updates, so updating is turned off".to_string(),
+            config_key: Some("LIBREOLLAMA_UPDATER_PUBKEY".to_string()),
+        })?;
+        let channel = self.channel();
+        self.update_status(|status| {
+            status.state = UpdateState::Checking;
+            status.channel = channel.clone();
+            status.error = None;
+        });
+
+        let updater_error = |e: tauri_plugin_updater::Error| LibreOllamaError::Network {
+            message: format!("Update check failed: {}", e),
+            url: Some(endpoint(&channel).to_string()),
+        };
+        let url = Url::parse(endpoint(&channel)).map_err(|e| LibreOllamaError::Internal { message: e.to_string() })?;
+        // The Windows installer exits the app once it starts, so that is
+        // when background work stops; runs on the blocking install thread
+        let app = self.app.clone();
+        let found = async {
+            self.app
+                .updater_builder()
+                .on_before_exit(move || {
+                    tauri::async_runtime::block_on(crate::services::shutdown::shutdown(&app));
+                    app.cleanup_before_exit();
+                })
+                .pubkey(pubkey)
+                .endpoints(vec![url])
+                .map_err(updater_error)?
+                .build()
+                .map_err(updater_error)?
+                .check()
+                .await
+                .map_err(updater_error)
+        }
+        .await
+        .map_err(|e| self.fail(e))?;
+
+        let install_id = self.install_id()?;
+        let found = found.filter(|update| {
+            let rollout = update.raw_json.get("rollout").and_then(|value| value.as_u64()).map(|percent| percent.min(100) as u8);
+            manual || in_rollout(rollout, &install_id, &update.version)
+        });
+        let checked_at = chrono::Utc::now().to_rfc3339();
+        let available = found.is_some();
+
+        let mut slot = self.slot.lock().unwrap_or_else(|e| e.into_inner());
+        let same_version = slot.update.as_ref().map(|update| &update.version) == found.as_ref().map(|update| &update.version);
+        if !same_version {
+            slot.bytes = None;
+        }
+        let ready = slot.bytes.is_some();
+        let (version, notes, date) = found
+            .as_ref()
+            .map(|update| (Some(update.version.clone()), update.body.clone(), update.date.map(|date| date.to_string())))
+            .unwrap_or_default();
+        slot.update = found;
+        drop(slot);
+
+        self.update_status(|status| {
+            status.state = match (available, ready) {
+                (false, _) => UpdateState::UpToDate,
+                (true, true) => UpdateState::Ready,
+                (true, false) => UpdateState::Available,
+            };
+            status.available_version = version;
+            status.release_notes = notes;
+            status.release_date = date;
+            status.last_checked_at = Some(checked_at);
+            if !ready {
+                status.downloaded_bytes = 0;
+                status.total_bytes = None;
+            }
+        });
+        if let Some(version) = self.status().available_version {
+            println!("⬆️ [UPDATER] Version {} is available on the {} channel", version, channel);
+        }
+        Ok(available)
+    }
+
+    /// Download the available update unless it already is; the signature
+    /// is verified before the bytes are kept
+    pub async fn download(&self) -> Result<Arc<Vec<u8>>> {
+        let update = {
+            let slot = self.slot.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(bytes) = &slot.bytes {
+                return Ok(bytes.clone());
+            }
+            slot.update.clone()
+        }
+        .ok_or_else(|| LibreOllamaError::NotFound { resource: "available update".to_string() })?;
+
+        self.update_status(|status| {
+            status.state = UpdateState::Downloading;
+            status.downloaded_bytes = 0;
+        });
+        let progress = self.clone();
+        let bytes = update
+            .download(
+                move |chunk, total| {
+                    let mut slot = progress.slot.lock().unwrap_or_else(|e| e.into_inner());
+                    if let Some(status) = slot.status.as_mut() {
+                        status.downloaded_bytes += chunk as u64;
+                        status.total_bytes = total;
+                    }
+                },
+                || {},
+            )
+            .await
+            .map_err(|e| self.fail(LibreOllamaError::Network {
+                message: format!("Update download failed: {}", e),
+                url: Some(update.download_url.to_string()),
+            }))?;
+
+        let bytes = Arc::new(bytes);
+        self.slot.lock().unwrap_or_else(|e| e.into_inner()).bytes = Some(bytes.clone());
+        self.update_status(|status| status.state = UpdateState::Ready);
+        println!("✅ [UPDATER] Version {} downloaded and verified", update.version);
+        Ok(bytes)
+    }
+
+    /// Install the downloaded update, then shut down cleanly and relaunch.
+    /// A failed install leaves the app running. On Windows the installer
+    /// takes over and exits the app, after the same shutdown.
+    pub async fn install_and_restart(&self) -> Result<()> {
+        let bytes = self.download().await?;
+        let update = self
+            .slot
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .update
+            .clone()
+            .ok_or_else(|| LibreOllamaError::NotFound { resource: "available update".to_string() })?;
+
+        tokio::task::spawn_blocking(move || update.install(bytes.as_slice()))
+            .await
+            .map_err(|e| LibreOllamaError::Internal { message: format!("Update install task failed: {}", e) })?
+            .map_err(|e| self.fail(LibreOllamaError::Internal { message: format!("Failed to install update: {}", e) }))?;
+        crate::services::shutdown::shutdown(&self.app).await;
+        self.app.restart()
+    }
+
+    /// Release notes of a version, by default the available update or else
+    /// the running version
+    pub async fn release_notes(&self, version: Option<String>) -> Result<String> {
+        let status = self.status();
+        let version = version.or(status.available_version.clone()).unwrap_or(status.current_version);
+        if status.available_version.as_deref() == Some(version.as_str()) {
+            if let Some(notes) = status.release_notes.filter(|notes| !notes.trim().is_empty()) {
+                return Ok(notes);
+            }
+        }
+
+        let url = format!("{}/v{}", RELEASES_API, version.trim_start_matches('v'));
+        let response = http_client()
+            .get(&url)
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| LibreOllamaError::Network {
+                message: format!("Failed to fetch release notes: {}", e),
+                url: Some(url.clone()),
+            })?;
+        let release: GitHubRelease = response.json().await.map_err(|e| LibreOllamaError::Network {
+            message: format!("Invalid release response: {}", e),
+            url: Some(url),
+        })?;
+        Ok(release.body.unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rollout_is_stable_and_widens() {
+        let installs: Vec<String> = (0..1000).map(|i| format!("install-{}", i)).collect();
+        let reached = |percent| installs.iter().filter(|id| in_rollout(Some(percent), id, "1.2.0")).count();
+
+        assert_eq!(reached(0), 0);
+        assert_eq!(reached(100), installs.len());
+        let (ten, fifty) = (reached(10), reached(50));
+        assert!((50..=150).contains(&ten), "10% reached {}", ten);
+        assert!(ten < fifty);
+        // Widening keeps every install that already had the release
+        assert!(installs.iter().all(|id| !in_rollout(Some(10), id, "1.2.0") || in_rollout(Some(50), id, "1.2.0")));
+
+        assert!(in_rollout(None, "install-1", "1.2.0"));
+        assert_eq!(rollout_bucket("install-1", "1.2.0"), rollout_bucket("install-1", "1.2.0"));
+    }
+}
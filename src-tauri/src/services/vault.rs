@@ -0,0 +1,408 @@
+//! Secure notes vault
+//!
+//! Secure notes hold 2FA backup codes and other snippets that should stay
+//! unreadable to anyone who gets hold of the database file, which is
+//! itself stored unencrypted, or of the app while it is open. Their
+//! titles and contents are encrypted with a random notes key, which is
+//! itself stored encrypted with a key derived from the vault passphrase
+//! by Argon2id. Nothing about the passphrase is kept outside this vault.
+//!
+//! Unlocking keeps the notes key in memory until `lock` is called or the
+//! vault has gone unused for the auto-lock setting's minutes. Wrong
+//! passphrases are throttled like the app lock's.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use rand::{rngs::OsRng, RngCore};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::database::operations::vault_operations::{self, SecureNoteRecord, VaultRecord};
+use crate::database::DatabaseManager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::app_lock::UnlockThrottle;
+use crate::services::events::{BackendEvent, EventBus, VaultLockedEvent};
+use crate::services::settings;
+use crate::services::task_supervisor::TaskSpec;
+use crate::utils::crypto::{decrypt_data, encrypt_data, generate_encryption_key};
+
+/// Minutes without vault use before it locks itself
+pub const VAULT_AUTO_LOCK_SETTING: &str = "vault.auto_lock_minutes";
+pub const MIN_PASSPHRASE_CHARS: usize = 8;
+const SALT_BYTES: usize = 16;
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Argon2id cost, stored with the vault so it can be raised later
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    /// KiB
+    pub m_cost: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            m_cost: Params::DEFAULT_M_COST,
+            t_cost: Params::DEFAULT_T_COST,
+            p_cost: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecureNote {
+    pub id: i64,
+    pub title: String,
+    pub content: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// A secure note as listed, without its content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecureNoteSummary {
+    pub id: i64,
+    pub title: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultStatus {
+    /// Whether a passphrase has been set
+    pub configured: bool,
+    pub unlocked: bool,
+    pub auto_lock_minutes: i64,
+}
+
+fn crypto_error(message: impl Into<String>) -> LibreOllamaError {
+    LibreOllamaError::Crypto { message: message.into() }
+}
+
+fn check_passphrase(passphrase: &str) -> Result<()> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_CHARS {
+        return Err(LibreOllamaError::InvalidInput {
+            message: format!("The vault passphrase needs at least {} characters", MIN_PASSPHRASE_CHARS),
+            field: Some("passphrase".to_string()),
+        });
+    }
+    Ok(())
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], params: KdfParams) -> Result<[u8; 32]> {
+    let params = Params::new(params.m_cost, params.t_cost, params.p_cost, Some(32))
+        .map_err(|e| crypto_error(format!("Invalid key derivation parameters: {}", e)))?;
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| crypto_error(format!("Key derivation failed: {}", e)))?;
+    Ok(key)
+}
+
+/// A notes key encrypted for one passphrase, as stored
+struct WrappedKey {
+    salt: String,
+    kdf_params: String,
+    wrapped_key: String,
+}
+
+/// Encrypt `notes_key` under a new salt for `passphrase`
+fn wrap_key(passphrase: &str, notes_key: &[u8; 32]) -> Result<WrappedKey> {
+    let mut salt = [0u8; SALT_BYTES];
+    OsRng.fill_bytes(&mut salt);
+    let params = KdfParams::default();
+    let mut passphrase_key = derive_key(passphrase, &salt, params)?;
+    let wrapped = encrypt_data(&STANDARD.encode(notes_key), &passphrase_key);
+    passphrase_key.fill(0);
+    Ok(WrappedKey {
+        salt: STANDARD.encode(salt),
+        kdf_params: serde_json::to_string(&params)?,
+        wrapped_key: wrapped?,
+    })
+}
+
+fn unwrap_key(vault: &VaultRecord, passphrase: &str) -> Result<[u8; 32]> {
+    let salt = STANDARD.decode(&vault.salt).map_err(|e| crypto_error(format!("Invalid vault salt: {}", e)))?;
+    let params: KdfParams = serde_json::from_str(&vault.kdf_params)?;
+    let mut passphrase_key = derive_key(passphrase, &salt, params)?;
+    let unwrapped = decrypt_data(&vault.wrapped_key, &passphrase_key);
+    passphrase_key.fill(0);
+    // AES-GCM rejects the wrong key, so a failure here is a wrong passphrase
+    let encoded = unwrapped.map_err(|_| LibreOllamaError::PermissionDenied {
+        message: "Wrong vault passphrase".to_string(),
+    })?;
+    let bytes = STANDARD.decode(encoded).map_err(|e| crypto_error(format!("Invalid vault key: {}", e)))?;
+    bytes.try_into().map_err(|_| crypto_error("Invalid vault key length"))
+}
+
+fn load_vault(conn: &Connection) -> Result<VaultRecord> {
+    vault_operations::get_vault(conn)?.ok_or_else(|| LibreOllamaError::NotFound {
+        resource: "Secure notes vault".to_string(),
+    })
+}
+
+fn already_set_up() -> LibreOllamaError {
+    LibreOllamaError::InvalidInput {
+        message: "The vault already has a passphrase".to_string(),
+        field: None,
+    }
+}
+
+/// Set the passphrase of a new vault, returning the notes key
+pub fn setup(conn: &Connection, passphrase: &str) -> Result<[u8; 32]> {
+    if vault_operations::get_vault(conn)?.is_some() {
+        return Err(already_set_up());
+    }
+    check_passphrase(passphrase)?;
+    let mut notes_key = generate_encryption_key();
+    let wrapped = wrap_key(passphrase, &notes_key)?;
+    // A setup racing this one may have stored its key since the check above
+    if !vault_operations::create_vault(conn, &wrapped.salt, &wrapped.kdf_params, &wrapped.wrapped_key)? {
+        notes_key.fill(0);
+        return Err(already_set_up());
+    }
+    Ok(notes_key)
+}
+
+/// The notes key, if `passphrase` is right
+pub fn open(conn: &Connection, passphrase: &str) -> Result<[u8; 32]> {
+    unwrap_key(&load_vault(conn)?, passphrase)
+}
+
+/// Re-wrap the notes key for a new passphrase; notes are not re-encrypted
+pub fn change_passphrase(conn: &Connection, current: &str, new: &str) -> Result<()> {
+    check_passphrase(new)?;
+    let mut notes_key = open(conn, current)?;
+    let wrapped = wrap_key(new, &notes_key);
+    notes_key.fill(0);
+    let wrapped = wrapped?;
+    vault_operations::save_vault(conn, &wrapped.salt, &wrapped.kdf_params, &wrapped.wrapped_key)?;
+    Ok(())
+}
+
+fn decrypt_note(record: SecureNoteRecord, key: &[u8; 32]) -> Result<SecureNote> {
+    Ok(SecureNote {
+        id: record.id,
+        title: decrypt_data(&record.title_encrypted, key)?,
+        content: decrypt_data(&record.content_encrypted, key)?,
+        created_at: record.created_at,
+        updated_at: record.updated_at,
+    })
+}
+
+fn note_not_found(id: i64) -> LibreOllamaError {
+    LibreOllamaError::NotFound { resource: format!("Secure note {}", id) }
+}
+
+pub fn create_note(conn: &Connection, key: &[u8; 32], title: &str, content: &str) -> Result<SecureNote> {
+    let record = vault_operations::insert_secure_note(conn, &encrypt_data(title, key)?, &encrypt_data(content, key)?)?;
+    decrypt_note(record, key)
+}
+
+pub fn get_note(conn: &Connection, key: &[u8; 32], id: i64) -> Result<SecureNote> {
+    let record = vault_operations::get_secure_note(conn, id)?.ok_or_else(|| note_not_found(id))?;
+    decrypt_note(record, key)
+}
+
+pub fn list_notes(conn: &Connection, key: &[u8; 32]) -> Result<Vec<SecureNoteSummary>> {
+    vault_operations::list_secure_notes(conn)?
+        .into_iter()
+        .map(|record| {
+            Ok(SecureNoteSummary {
+                id: record.id,
+                title: decrypt_data(&record.title_encrypted, key)?,
+                created_at: record.created_at,
+                updated_at: record.updated_at,
+            })
+        })
+        .collect()
+}
+
+pub fn update_note(conn: &Connection, key: &[u8; 32], id: i64, title: &str, content: &str) -> Result<SecureNote> {
+    if !vault_operations::update_secure_note(conn, id, &encrypt_data(title, key)?, &encrypt_data(content, key)?)? {
+        return Err(note_not_found(id));
+    }
+    get_note(conn, key, id)
+}
+
+/// The notes key while unlocked; wiped when the vault locks
+struct UnlockedVault {
+    key: [u8; 32],
+    last_used: Instant,
+}
+
+impl Drop for UnlockedVault {
+    fn drop(&mut self) {
+        self.key.fill(0);
+    }
+}
+
+/// Lock state of the vault, managed as Tauri state
+#[derive(Clone)]
+pub struct VaultService {
+    db_manager: Arc<DatabaseManager>,
+    app: AppHandle,
+    unlocked: Arc<Mutex<Option<UnlockedVault>>>,
+    /// Held through each passphrase check so attempts cannot race the throttle
+    checking: Arc<Mutex<()>>,
+    throttle: Arc<UnlockThrottle>,
+}
+
+impl VaultService {
+    pub fn new(db_manager: Arc<DatabaseManager>, app: AppHandle) -> Self {
+        Self {
+            db_manager,
+            app,
+            unlocked: Arc::new(Mutex::new(None)),
+            checking: Arc::new(Mutex::new(())),
+            throttle: Arc::new(UnlockThrottle::new("vault")),
+        }
+    }
+
+    fn connection(&self) -> Result<Connection> {
+        Ok(self.db_manager.get_connection()?)
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, Option<UnlockedVault>> {
+        self.unlocked.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn auto_lock_after(&self) -> Duration {
+        let minutes = self
+            .connection()
+            .ok()
+            .and_then(|conn| settings::get_integer(&conn, VAULT_AUTO_LOCK_SETTING).ok())
+            .unwrap_or(5);
+        Duration::from_secs(minutes.max(1) as u64 * 60)
+    }
+
+    /// Lock the vault once it has been idle for the auto-lock time
+    pub fn task(&self) -> TaskSpec {
+        let service = self.clone();
+        TaskSpec::new("vault_auto_lock", move || {
+            let service = service.clone();
+            async move {
+                let idle_for = service.state().as_ref().map(|vault| vault.last_used.elapsed());
+                if idle_for.is_some_and(|idle| idle >= service.auto_lock_after()) {
+                    service.lock("inactivity");
+                }
+                Ok(())
+            }
+        })
+        .every(|| CHECK_INTERVAL)
+        .first_run_after(CHECK_INTERVAL)
+    }
+
+    pub fn status(&self) -> Result<VaultStatus> {
+        let conn = self.connection()?;
+        Ok(VaultStatus {
+            configured: vault_operations::get_vault(&conn)?.is_some(),
+            unlocked: self.state().is_some(),
+            auto_lock_minutes: settings::get_integer(&conn, VAULT_AUTO_LOCK_SETTING)?,
+        })
+    }
+
+    /// Set the passphrase of a new vault and leave it unlocked
+    pub fn setup(&self, passphrase: &str) -> Result<VaultStatus> {
+        let key = setup(&self.connection()?, passphrase)?;
+        *self.state() = Some(UnlockedVault { key, last_used: Instant::now() });
+        self.status()
+    }
+
+    /// Run a passphrase check `f`, refusing it while throttled and counting
+    /// a wrong passphrase against later attempts
+    fn checked<T>(&self, f: impl FnOnce(&Connection) -> Result<T>) -> Result<T> {
+        let _attempt = self.checking.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let conn = self.connection()?;
+        let now = chrono::Utc::now().timestamp();
+        self.throttle.check(&conn, now)?;
+        match f(&conn) {
+            Err(e @ LibreOllamaError::PermissionDenied { .. }) => {
+                self.throttle.record_failure(&conn, now)?;
+                Err(e)
+            }
+            Err(e) => Err(e),
+            Ok(value) => {
+                self.throttle.reset(&conn)?;
+                Ok(value)
+            }
+        }
+    }
+
+    pub fn unlock(&self, passphrase: &str) -> Result<VaultStatus> {
+        let key = self.checked(|conn| open(conn, passphrase))?;
+        *self.state() = Some(UnlockedVault { key, last_used: Instant::now() });
+        println!("🔓 [VAULT] Unlocked");
+        self.status()
+    }
+
+    /// Forget the notes key; returns false if the vault was already locked
+    pub fn lock(&self, reason: &str) -> bool {
+        let was_unlocked = self.state().take().is_some();
+        if was_unlocked {
+            println!("🔒 [VAULT] Locked ({})", reason);
+            if let Some(event_bus) = self.app.try_state::<EventBus>() {
+                event_bus.emit(BackendEvent::VaultLocked(VaultLockedEvent { reason: reason.to_string() }));
+            }
+        }
+        was_unlocked
+    }
+
+    pub fn change_passphrase(&self, current: &str, new: &str) -> Result<()> {
+        self.checked(|conn| change_passphrase(conn, current, new))
+    }
+
+    /// Run `f` with the notes key, counting as vault use
+    pub fn with_key<T>(&self, f: impl FnOnce(&Connection, &[u8; 32]) -> Result<T>) -> Result<T> {
+        let conn = self.connection()?;
+        let mut state = self.state();
+        let vault = state.as_mut().ok_or_else(|| LibreOllamaError::PermissionDenied {
+            message: "The vault is locked".to_string(),
+        })?;
+        vault.last_used = Instant::now();
+        f(&conn, &vault.key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::run_migrations;
+
+    #[test]
+    fn test_vault_round_trip() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        assert!(open(&conn, "correct horse").is_err());
+        assert!(setup(&conn, "short").is_err());
+        let key = setup(&conn, "correct horse").unwrap();
+        assert!(setup(&conn, "another passphrase").is_err());
+        assert!(!vault_operations::create_vault(&conn, "salt", "{}", "wrapped").unwrap());
+
+        let note = create_note(&conn, &key, "GitHub recovery codes", "1234-5678\n9012-3456").unwrap();
+        let stored = vault_operations::get_secure_note(&conn, note.id).unwrap().unwrap();
+        assert!(!stored.content_encrypted.contains("1234"));
+
+        assert!(matches!(open(&conn, "wrong horse"), Err(LibreOllamaError::PermissionDenied { .. })));
+        let reopened = open(&conn, "correct horse").unwrap();
+        assert_eq!(get_note(&conn, &reopened, note.id).unwrap().content, "1234-5678\n9012-3456");
+
+        change_passphrase(&conn, "correct horse", "battery staple").unwrap();
+        assert!(open(&conn, "correct horse").is_err());
+        let rewrapped = open(&conn, "battery staple").unwrap();
+        assert_eq!(rewrapped, key);
+
+        update_note(&conn, &rewrapped, note.id, "GitHub codes", "used up").unwrap();
+        let notes = list_notes(&conn, &rewrapped).unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].title, "GitHub codes");
+        assert!(update_note(&conn, &rewrapped, 999, "x", "y").is_err());
+    }
+}
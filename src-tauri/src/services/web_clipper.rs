@@ -0,0 +1,266 @@
+//! Web clipper
+//!
+//! Saves a web page as a readable Markdown note. The page is downloaded
+//! with `fetch_public`, reduced to its main article by the readability
+//! algorithm and converted to Markdown. Images in the article are
+//! downloaded through the same guarded fetch and embedded as `data:` URLs,
+//! which the webview shows without any file access, so the note still
+//! reads the same once the page is gone and undoing its deletion brings
+//! the images back with it. Clips made before images were embedded kept
+//! them in `attachments_dir/clips/<id>`; `clip_image_dirs` finds those so
+//! they are removed with their note.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use base64::{engine::general_purpose, Engine as _};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::config::get_config_manager;
+use crate::errors::{LibreOllamaError, Result};
+use crate::services::image_proxy::{self, MAX_IMAGE_BYTES};
+use crate::services::link_preview;
+use crate::utils::http_client::fetch_public;
+
+/// Pages larger than this are cut off before extraction
+const MAX_PAGE_BYTES: usize = 5 * 1024 * 1024;
+/// Images beyond this many stay as remote links
+const MAX_IMAGES: usize = 30;
+/// Image bytes embedded in one clip; images past it stay as remote links
+const MAX_EMBEDDED_BYTES: usize = 8 * 1024 * 1024;
+const MAX_TAGS: usize = 20;
+
+lazy_static::lazy_static! {
+    static ref MARKDOWN_IMAGE: Regex = Regex::new(r#"!\[([^\]]*)\]\(\s*<?([^)\s>]+)>?(\s+"[^"]*")?\s*\)"#).unwrap();
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClippedImage {
+    pub source_url: String,
+    pub content_type: String,
+    pub bytes: usize,
+}
+
+/// A page reduced to its article, ready to be stored as a note
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClippedArticle {
+    pub title: String,
+    pub markdown: String,
+    /// The URL that was clipped
+    pub source_url: String,
+    /// Where redirects led
+    pub final_url: String,
+    pub images: Vec<ClippedImage>,
+    /// Images that could not be downloaded and are still remote links
+    pub failed_images: Vec<String>,
+}
+
+/// Trimmed, de-duplicated (case-insensitively) tags without leading `#`
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().trim_start_matches('#').trim();
+        if !tag.is_empty() && !normalized.iter().any(|existing| existing.eq_ignore_ascii_case(tag)) {
+            normalized.push(tag.to_string());
+        }
+    }
+    normalized.truncate(MAX_TAGS);
+    normalized
+}
+
+/// Distinct image URLs referenced by Markdown, in order of appearance
+pub fn image_references(markdown: &str) -> Vec<String> {
+    let mut references: Vec<String> = Vec::new();
+    for caps in MARKDOWN_IMAGE.captures_iter(markdown) {
+        let reference = caps[2].to_string();
+        if !references.contains(&reference) {
+            references.push(reference);
+        }
+    }
+    references
+}
+
+/// Point image references at new targets; unmapped ones are left alone
+pub fn rewrite_images(markdown: &str, targets: &HashMap<String, String>) -> String {
+    MARKDOWN_IMAGE
+        .replace_all(markdown, |caps: &regex::Captures| match targets.get(&caps[2]) {
+            Some(target) => format!("![{}]({})", &caps[1], target),
+            None => caps[0].to_string(),
+        })
+        .into_owned()
+}
+
+fn is_html(content_type: &str) -> bool {
+    content_type.is_empty() || content_type == "text/html" || content_type == "application/xhtml+xml"
+}
+
+fn clips_root() -> Result<PathBuf> {
+    let config_manager = get_config_manager().map_err(|e| LibreOllamaError::Configuration {
+        message: format!("Failed to get config manager: {}", e),
+        config_key: None,
+    })?;
+    Ok(config_manager.paths().attachments_dir.join("clips"))
+}
+
+/// Directories of `clips_root` that a note's images point into
+fn image_dirs_under(content: &str, root: &std::path::Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for reference in image_references(content) {
+        let Some(path) = Url::parse(&reference).ok().and_then(|url| url.to_file_path().ok()) else {
+            continue;
+        };
+        let Some(dir) = path.parent().filter(|dir| dir.parent() == Some(root)) else {
+            continue;
+        };
+        if !dirs.iter().any(|known| known == dir) {
+            dirs.push(dir.to_path_buf());
+        }
+    }
+    dirs
+}
+
+/// Image directories of a note clipped before images were embedded, to be
+/// removed with the note
+pub fn clip_image_dirs(content: &str) -> Result<Vec<PathBuf>> {
+    Ok(image_dirs_under(content, &clips_root()?))
+}
+
+/// Download the article images and embed them, returning what was embedded
+/// and the references that stay remote
+async fn embed_images(markdown: &str, base: &Url) -> (Vec<ClippedImage>, Vec<String>, HashMap<String, String>) {
+    let mut embedded = Vec::new();
+    let mut failed = Vec::new();
+    let mut targets = HashMap::new();
+    let mut budget = MAX_EMBEDDED_BYTES;
+
+    for (index, reference) in image_references(markdown).into_iter().enumerate() {
+        let Some(url) = base.join(&reference).ok().filter(|url| matches!(url.scheme(), "http" | "https")) else {
+            // data: URLs and the like are kept inline
+            continue;
+        };
+        if index >= MAX_IMAGES || budget == 0 {
+            failed.push(url.to_string());
+            continue;
+        }
+
+        let limit = budget.min(MAX_IMAGE_BYTES);
+        let fetched = match fetch_public(url.clone(), "image/*", limit, image_proxy::is_allowed_content_type).await {
+            Ok(response) if !response.truncated => response,
+            _ => {
+                failed.push(url.to_string());
+                continue;
+            }
+        };
+
+        budget -= fetched.body.len();
+        targets.insert(
+            reference,
+            format!("data:{};base64,{}", fetched.content_type, general_purpose::STANDARD.encode(&fetched.body)),
+        );
+        embedded.push(ClippedImage {
+            source_url: url.to_string(),
+            content_type: fetched.content_type,
+            bytes: fetched.body.len(),
+        });
+    }
+
+    (embedded, failed, targets)
+}
+
+/// Fetch a page and reduce it to a Markdown article with embedded images
+pub async fn clip(url: &str) -> Result<ClippedArticle> {
+    let url = Url::parse(url.trim()).map_err(|e| LibreOllamaError::InvalidInput {
+        message: format!("Invalid URL: {}", e),
+        field: Some("url".to_string()),
+    })?;
+    let response = fetch_public(url.clone(), "text/html,application/xhtml+xml", MAX_PAGE_BYTES, is_html).await?;
+    let html = String::from_utf8_lossy(&response.body).into_owned();
+    let base = response.url.clone();
+
+    let (title, article_html) = {
+        let base = base.clone();
+        let html = html.clone();
+        tokio::task::spawn_blocking(move || {
+            readability::extractor::extract(&mut html.as_bytes(), &base)
+                .map(|product| (product.title, product.content))
+                .map_err(|e| LibreOllamaError::Internal {
+                    message: format!("Could not find an article on the page: {}", e),
+                })
+        })
+        .await
+        .map_err(|e| LibreOllamaError::Internal { message: format!("Article extraction failed: {}", e) })??
+    };
+
+    let article = html2md::parse_html(&article_html);
+    if article.trim().is_empty() {
+        return Err(LibreOllamaError::InvalidInput {
+            message: "The page has no readable article text".to_string(),
+            field: Some("url".to_string()),
+        });
+    }
+
+    let metadata = link_preview::parse_metadata(&html, &base);
+    let title = Some(title.trim().to_string())
+        .filter(|title| !title.is_empty())
+        .or(metadata.title)
+        .unwrap_or_else(|| base.host_str().unwrap_or("Clipped page").to_string());
+
+    let (images, failed_images, targets) = embed_images(&article, &base).await;
+    let site = metadata.site_name.unwrap_or_else(|| base.host_str().unwrap_or_default().to_string());
+    let markdown = format!(
+        "> Clipped from [{}]({}) on {}\n\n{}\n",
+        site,
+        url,
+        chrono::Local::now().format("%Y-%m-%d"),
+        rewrite_images(article.trim(), &targets)
+    );
+    println!("✂️ [CLIPPER] Clipped '{}' with {} images", title, images.len());
+
+    Ok(ClippedArticle {
+        title,
+        markdown,
+        source_url: url.to_string(),
+        final_url: base.to_string(),
+        images,
+        failed_images,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_references_and_rewrite() {
+        let markdown = "Intro ![Chart](/img/chart.png \"Sales\")\n\n![](https://cdn.example.com/a.jpg) and ![again](/img/chart.png)";
+        assert_eq!(image_references(markdown), vec!["/img/chart.png", "https://cdn.example.com/a.jpg"]);
+
+        let targets = HashMap::from([("/img/chart.png".to_string(), "file:///clips/image-1.png".to_string())]);
+        let rewritten = rewrite_images(markdown, &targets);
+        assert!(rewritten.starts_with("Intro ![Chart](file:///clips/image-1.png)"));
+        assert!(rewritten.contains("![](https://cdn.example.com/a.jpg)"));
+        assert!(rewritten.ends_with("![again](file:///clips/image-1.png)"));
+    }
+
+    #[test]
+    fn test_image_dirs_under() {
+        let root = std::env::temp_dir().join("clips");
+        let image = |dir: &str, name: &str| Url::from_file_path(root.join(dir).join(name)).unwrap();
+        let content = format!(
+            "![a]({})\n![b]({})\n![c]({})\n![d](https://example.com/d.png)\n![e]({})",
+            image("one", "image-1.png"),
+            image("one", "image-2.png"),
+            image("two", "image-1.png"),
+            Url::from_file_path(std::env::temp_dir().join("elsewhere.png")).unwrap(),
+        );
+        assert_eq!(image_dirs_under(&content, &root), vec![root.join("one"), root.join("two")]);
+    }
+
+    #[test]
+    fn test_normalize_tags() {
+        let tags = ["  rust ".to_string(), "#Reading".to_string(), "RUST".to_string(), "#".to_string()];
+        assert_eq!(normalize_tags(&tags), vec!["rust", "Reading"]);
+    }
+}
@@ -0,0 +1,214 @@
+//! Workspaces
+//!
+//! The active workspace decides what the rest of the app shows. Anything
+//! assigned to another workspace, and not to the active one, is left out of
+//! the account, task list, calendar, folder and note listings, search and
+//! the daily digest; anything assigned to no workspace is visible in all of
+//! them. A hidden folder hides its subfolders and their notes too, unless a
+//! subfolder is itself part of the active workspace. With no workspace
+//! active nothing is hidden.
+//!
+//! Paged and limited queries take the hidden IDs as `HiddenIds` and leave
+//! them out in SQL, so a page is never cut short by items filtered later.
+//! Cache keys carry the active workspace, so a response cached in one
+//! workspace is never served in another.
+
+use std::collections::{HashMap, HashSet};
+
+use rusqlite::Connection;
+
+use crate::database::operations::preference_operations;
+use crate::database::operations::search_operations::SearchDomain;
+use crate::database::operations::workspace_operations::{self, HiddenIds, Workspace, WorkspaceKind};
+use crate::errors::{LibreOllamaError, Result};
+
+/// user_preferences key holding the ID of the active workspace
+pub const ACTIVE_WORKSPACE_PREFERENCE: &str = "workspace.active";
+
+/// What the active workspace hides
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceScope {
+    pub workspace_id: Option<i64>,
+    hidden: HashMap<WorkspaceKind, HashSet<String>>,
+}
+
+impl WorkspaceScope {
+    pub fn allows(&self, kind: WorkspaceKind, id: &str) -> bool {
+        self.hidden.get(&kind).is_none_or(|hidden| !hidden.contains(id))
+    }
+
+    /// Notes outside any folder are always visible
+    pub fn allows_folder(&self, folder_id: Option<i32>) -> bool {
+        folder_id.is_none_or(|id| self.allows(WorkspaceKind::Folder, &id.to_string()))
+    }
+
+    fn hidden_json(&self, kind: WorkspaceKind) -> String {
+        let ids = self.hidden.get(&kind).into_iter().flatten().map(String::as_str);
+        serde_json::Value::from_iter(ids).to_string()
+    }
+
+    /// Hidden accounts, and hidden containers of `container` if given
+    pub fn hidden(&self, container: Option<WorkspaceKind>) -> HiddenIds {
+        HiddenIds {
+            accounts: self.hidden_json(WorkspaceKind::Account),
+            containers: container.map_or_else(|| "[]".to_string(), |kind| self.hidden_json(kind)),
+        }
+    }
+
+    /// What the workspace hides from search hits in `domain`
+    pub fn hidden_in(&self, domain: SearchDomain) -> HiddenIds {
+        match domain {
+            SearchDomain::Mail => self.hidden(None),
+            SearchDomain::Task => self.hidden(Some(WorkspaceKind::TaskList)),
+            SearchDomain::Event => self.hidden(Some(WorkspaceKind::Calendar)),
+            SearchDomain::Note => HiddenIds { accounts: "[]".to_string(), containers: self.hidden_json(WorkspaceKind::Folder) },
+            SearchDomain::Chat => HiddenIds::default(),
+        }
+    }
+
+    /// Drop the items the workspace hides
+    pub fn retain<T>(&self, kind: WorkspaceKind, items: &mut Vec<T>, id: impl Fn(&T) -> &str) {
+        items.retain(|item| self.allows(kind, id(item)));
+    }
+
+    /// `key` namespaced by the active workspace; unchanged when none is
+    pub fn cache_key(&self, key: &str) -> String {
+        match self.workspace_id {
+            Some(id) => format!("workspace:{}:{}", id, key),
+            None => key.to_string(),
+        }
+    }
+}
+
+/// The active workspace; a stored ID whose workspace was deleted counts
+/// as none
+pub fn active_workspace(conn: &Connection) -> Result<Option<Workspace>> {
+    let Some(id) = preference_operations::get_preference_value(conn, ACTIVE_WORKSPACE_PREFERENCE)?
+        .and_then(|value| value.trim().parse::<i64>().ok())
+    else {
+        return Ok(None);
+    };
+    Ok(workspace_operations::get_workspace(conn, id)?)
+}
+
+/// Make a workspace active, or none with `None`
+pub fn switch(conn: &Connection, workspace_id: Option<i64>) -> Result<Option<Workspace>> {
+    let workspace = match workspace_id {
+        Some(id) => Some(workspace_operations::get_workspace(conn, id)?.ok_or_else(|| LibreOllamaError::NotFound {
+            resource: format!("Workspace {}", id),
+        })?),
+        None => None,
+    };
+    match &workspace {
+        Some(workspace) => preference_operations::set_preference_value(
+            conn,
+            ACTIVE_WORKSPACE_PREFERENCE,
+            &workspace.id.to_string(),
+            "workspace",
+        )?,
+        None => preference_operations::delete_user_preference_by_key(conn, ACTIVE_WORKSPACE_PREFERENCE)?,
+    }
+    Ok(workspace)
+}
+
+/// What the active workspace hides
+pub fn scope(conn: &Connection) -> Result<WorkspaceScope> {
+    let Some(workspace) = active_workspace(conn)? else {
+        return Ok(WorkspaceScope::default());
+    };
+
+    let mut hidden: HashMap<WorkspaceKind, HashSet<String>> = HashMap::new();
+    for member in workspace_operations::hidden_members(conn, workspace.id)? {
+        hidden.entry(member.kind).or_default().insert(member.member_id);
+    }
+
+    if let Some(hidden_folders) = hidden.get_mut(&WorkspaceKind::Folder) {
+        let own_folders: HashSet<&str> = workspace
+            .members
+            .iter()
+            .filter(|member| member.kind == WorkspaceKind::Folder)
+            .map(|member| member.member_id.as_str())
+            .collect();
+        let mut stmt = conn.prepare("SELECT id, parent_id FROM folders WHERE parent_id IS NOT NULL")?;
+        let children = stmt
+            .query_map([], |row| Ok((row.get::<_, i64>(0)?.to_string(), row.get::<_, i64>(1)?.to_string())))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        // Hide subfolders of hidden folders until nothing changes
+        loop {
+            let newly_hidden: Vec<String> = children
+                .iter()
+                .filter(|(id, parent)| {
+                    hidden_folders.contains(parent) && !hidden_folders.contains(id) && !own_folders.contains(id.as_str())
+                })
+                .map(|(id, _)| id.clone())
+                .collect();
+            if newly_hidden.is_empty() {
+                break;
+            }
+            hidden_folders.extend(newly_hidden);
+        }
+    }
+
+    Ok(WorkspaceScope { workspace_id: Some(workspace.id), hidden })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::operations::workspace_operations::{create_workspace, set_members, WorkspaceMember};
+    use crate::database::schema::run_migrations;
+
+    fn member(kind: WorkspaceKind, id: &str) -> WorkspaceMember {
+        WorkspaceMember { kind, member_id: id.to_string() }
+    }
+
+    #[test]
+    fn test_scope_hides_other_workspaces() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        for (name, parent) in [("Clients", None), ("Acme", Some(1)), ("Shared", Some(1)), ("Recipes", None)] {
+            conn.execute(
+                "INSERT INTO folders (name, parent_id, user_id, created_at, updated_at)
+                 VALUES (?1, ?2, 'default_user', '2026-10-01 09:00:00', '2026-10-01 09:00:00')",
+                rusqlite::params![name, parent],
+            ).unwrap();
+        }
+
+        let work = create_workspace(&conn, "Work", Some("#3366ff")).unwrap();
+        let personal = create_workspace(&conn, "Personal", None).unwrap();
+        set_members(&mut conn, work.id, &[
+            member(WorkspaceKind::Account, "work@example.com"),
+            member(WorkspaceKind::Folder, "1"),
+        ]).unwrap();
+        set_members(&mut conn, personal.id, &[
+            member(WorkspaceKind::Account, "me@example.com"),
+            member(WorkspaceKind::Folder, "3"),
+            member(WorkspaceKind::Folder, "4"),
+        ]).unwrap();
+
+        let everything = scope(&conn).unwrap();
+        assert!(everything.allows(WorkspaceKind::Account, "work@example.com"));
+        assert_eq!(everything.cache_key("inbox"), "inbox");
+
+        switch(&conn, Some(personal.id)).unwrap();
+        let personal_scope = scope(&conn).unwrap();
+        assert!(!personal_scope.allows(WorkspaceKind::Account, "work@example.com"));
+        assert!(personal_scope.allows(WorkspaceKind::Account, "me@example.com"));
+        assert!(personal_scope.allows(WorkspaceKind::Account, "unassigned@example.com"));
+        // Clients is hidden with its subfolder Acme; Shared is also in Personal
+        assert!(!personal_scope.allows_folder(Some(1)));
+        assert!(!personal_scope.allows_folder(Some(2)));
+        assert!(personal_scope.allows_folder(Some(3)));
+        assert!(personal_scope.allows_folder(None));
+        assert_eq!(personal_scope.cache_key("inbox"), format!("workspace:{}:inbox", personal.id));
+        assert_eq!(personal_scope.hidden(None).accounts, r#"["work@example.com"]"#);
+        assert_eq!(personal_scope.hidden_in(SearchDomain::Chat), HiddenIds::default());
+
+        switch(&conn, Some(work.id)).unwrap();
+        assert!(!scope(&conn).unwrap().allows_folder(Some(4)));
+
+        switch(&conn, None).unwrap();
+        assert!(active_workspace(&conn).unwrap().is_none());
+        assert!(switch(&conn, Some(999)).is_err());
+    }
+}
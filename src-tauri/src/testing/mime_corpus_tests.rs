@@ -0,0 +1,111 @@
+//! Message parsing against a corpus of awkward emails
+//!
+//! Each file in `fixtures/gmail/corpus/` holds a Gmail `format=full`
+//! message and the fields it should parse to. Add a file to cover a new
+//! case; no code change is needed.
+
+use serde_json::Value;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+use super::{fixture, MockGoogle};
+use crate::services::gmail::api_service::{EmailAddress, ProcessedGmailMessage};
+
+const ACCOUNT: &str = "ada";
+
+fn corpus() -> Vec<String> {
+    let dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/testing/fixtures/gmail/corpus");
+    let mut names: Vec<String> = std::fs::read_dir(&dir)
+        .unwrap_or_else(|e| panic!("corpus {}: {}", dir.display(), e))
+        .filter_map(|entry| entry.ok()?.path().file_stem()?.to_str().map(str::to_string))
+        .collect();
+    names.sort();
+    names
+}
+
+fn emails(addresses: &[EmailAddress]) -> Vec<&str> {
+    addresses.iter().map(|address| address.email.as_str()).collect()
+}
+
+fn expected_emails(expected: &Value, field: &str) -> Vec<String> {
+    expected[field]
+        .as_array()
+        .map(|emails| emails.iter().map(|email| email.as_str().unwrap().to_string()).collect())
+        .unwrap_or_default()
+}
+
+fn check(name: &str, message: &ProcessedGmailMessage, expected: &Value) {
+    let parsed = &message.parsed_content;
+    let text = |field: &str| expected[field].as_str();
+
+    assert_eq!(parsed.subject.as_deref(), text("subject"), "{}: subject", name);
+    assert_eq!(parsed.from.email, expected["from"]["email"].as_str().unwrap(), "{}: from", name);
+    assert_eq!(parsed.from.name.as_deref(), expected["from"]["name"].as_str(), "{}: from name", name);
+    assert_eq!(emails(&parsed.to), expected_emails(expected, "to"), "{}: to", name);
+    assert_eq!(emails(&parsed.cc), expected_emails(expected, "cc"), "{}: cc", name);
+    assert_eq!(parsed.reply_to.as_ref().map(|address| address.email.as_str()), text("reply_to"), "{}: reply-to", name);
+
+    if let Some(body) = text("body_text") {
+        assert_eq!(parsed.body_text.as_deref(), Some(body), "{}: body", name);
+    }
+    if let Some(fragment) = text("body_text_contains") {
+        assert!(parsed.body_text.as_deref().unwrap_or_default().contains(fragment), "{}: body {:?}", name, parsed.body_text);
+    }
+    if let Some(fragment) = text("body_html_contains") {
+        assert!(parsed.body_html.as_deref().unwrap_or_default().contains(fragment), "{}: html {:?}", name, parsed.body_html);
+    }
+
+    let attachments: Vec<Value> = parsed
+        .attachments
+        .iter()
+        .map(|attachment| {
+            serde_json::json!({
+                "filename": attachment.filename,
+                "content_type": attachment.content_type,
+                "is_inline": attachment.is_inline,
+            })
+        })
+        .collect();
+    assert_eq!(attachments, expected["attachments"].as_array().cloned().unwrap_or_default(), "{}: attachments", name);
+
+    if let Some(snippet) = text("snippet") {
+        assert_eq!(message.snippet.as_deref(), Some(snippet), "{}: snippet", name);
+    }
+    if let Some(prefix) = text("snippet_starts_with") {
+        assert!(message.snippet.as_deref().unwrap_or_default().starts_with(prefix), "{}: snippet {:?}", name, message.snippet);
+    }
+}
+
+#[tokio::test]
+async fn test_corpus_messages_parse() {
+    let google = MockGoogle::start().await;
+    google.auth.sign_in(ACCOUNT).await;
+
+    let cases: Vec<(String, Value)> = corpus()
+        .into_iter()
+        .map(|name| {
+            let case = fixture(&format!("gmail/corpus/{}", name));
+            (name, case)
+        })
+        .collect();
+    assert!(cases.len() >= 7, "corpus went missing");
+
+    for (_, case) in &cases {
+        let id = case["message"]["id"].as_str().unwrap();
+        Mock::given(method("GET"))
+            .and(path(format!("/gmail/v1/users/me/messages/{}", id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&case["message"]))
+            .mount(&google.server)
+            .await;
+    }
+
+    let gmail = google.gmail();
+    for (name, case) in &cases {
+        let id = case["message"]["id"].as_str().unwrap();
+        let message = gmail
+            .get_parsed_message(ACCOUNT, id)
+            .await
+            .unwrap_or_else(|e| panic!("{}: {}", name, e));
+        check(name, &message, &case["expected"]);
+    }
+}
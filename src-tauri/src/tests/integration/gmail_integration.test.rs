@@ -6,7 +6,6 @@
 #[cfg(test)]
 mod gmail_integration_tests {
     use std::sync::Arc;
-    use tokio::sync::Mutex;
     
     use crate::services::gmail::{
         api_service::{GmailApiService, MessageSearchQuery},
@@ -48,9 +47,9 @@ mod gmail_integration_tests {
     }
 
     /// Create test rate limiter
-    fn create_test_rate_limiter() -> Arc<Mutex<RateLimiter>> {
+    fn create_test_rate_limiter() -> Arc<RateLimiter> {
         let config = RateLimitConfig::default();
-        Arc::new(Mutex::new(RateLimiter::new(config)))
+        Arc::new(RateLimiter::new(config))
     }
 
     #[tokio::test]
@@ -174,6 +173,8 @@ mod gmail_integration_tests {
             body_html: None,
             attachments: vec![],
             reply_to_message_id: None,
+            in_reply_to: None,
+            references: None,
             thread_id: None,
         };
 
@@ -188,12 +189,8 @@ mod gmail_integration_tests {
     async fn test_rate_limiter_integration() {
         let rate_limiter = create_test_rate_limiter();
         
-        // Test that rate limiter can be locked and accessed
-        {
-            let limiter = rate_limiter.lock().await;
-            // Should be able to access the rate limiter
-            assert!(std::ptr::addr_of!(*limiter) as *const _ != std::ptr::null());
-        }
+        // Test that the shared rate limiter can be accessed
+        assert_eq!(rate_limiter.requests_in_last_minute(), 0);
     }
 
     #[tokio::test]
@@ -278,6 +275,8 @@ mod gmail_integration_tests {
             body_html: None,
             attachments: vec![],
             reply_to_message_id: None,
+            in_reply_to: None,
+            references: None,
             thread_id: None,
         };
 
@@ -361,9 +360,9 @@ mod gmail_performance_tests {
         Arc::new(db_manager)
     }
 
-    fn create_test_rate_limiter() -> Arc<Mutex<RateLimiter>> {
+    fn create_test_rate_limiter() -> Arc<RateLimiter> {
         let config = RateLimitConfig::default();
-        Arc::new(Mutex::new(RateLimiter::new(config)))
+        Arc::new(RateLimiter::new(config))
     }
 
     #[tokio::test]
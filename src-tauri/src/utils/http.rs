@@ -1,7 +1,9 @@
 use base64::{engine::general_purpose, Engine as _};
 
+use crate::utils::http_client::http_client;
+
 pub async fn fetch_image_as_base64(url: &str) -> Result<String, reqwest::Error> {
-    let response = reqwest::get(url).await?;
+    let response = http_client().get(url).send().await?;
     let content_type = response
         .headers()
         .get("content-type")
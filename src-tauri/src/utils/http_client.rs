@@ -0,0 +1,374 @@
+//! Shared HTTP client factory
+//!
+//! `reqwest::Client` keeps its own connection pool, so building one per
+//! request or per service throws away keep-alive and TLS session reuse.
+//! Every outgoing request should use a client from here instead: they share
+//! one pool and are configured once from `NetworkConfig` with timeouts,
+//! gzip, and the app's User-Agent. A configuration reload swaps in a
+//! factory built from the new settings; clients already handed out keep
+//! the settings they were built with.
+//!
+//! URLs taken from untrusted content (mail, notes, pasted links) go through
+//! `fetch_public`, which refuses hosts on the local network and re-checks
+//! every redirect.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use reqwest::{redirect, Client, ClientBuilder, RequestBuilder};
+use url::{Host, Url};
+
+use crate::config::{get_config_manager, NetworkConfig};
+use crate::errors::{LibreOllamaError, Result};
+
+static FACTORY: RwLock<Option<Arc<HttpClientFactory>>> = RwLock::new(None);
+
+/// Header carrying the per-request trace id when tracing is enabled
+pub const TRACE_HEADER: &str = "X-Request-Id";
+
+const MAX_REDIRECTS: usize = 5;
+
+pub struct HttpClientFactory {
+    client: Client,
+    streaming_client: Client,
+    config: NetworkConfig,
+    user_agent: String,
+}
+
+impl HttpClientFactory {
+    /// The process-wide factory, built from the global config on first use
+    pub fn global() -> Arc<HttpClientFactory> {
+        if let Some(factory) = FACTORY.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+            return factory.clone();
+        }
+        let mut factory = FACTORY.write().unwrap_or_else(|e| e.into_inner());
+        factory
+            .get_or_insert_with(|| {
+                let config = get_config_manager()
+                    .map(|manager| manager.network())
+                    .unwrap_or_default();
+                Arc::new(HttpClientFactory::from_config(&config))
+            })
+            .clone()
+    }
+
+    /// Rebuild the process-wide factory after the network config changed
+    pub fn reconfigure(config: &NetworkConfig) {
+        let factory = Arc::new(HttpClientFactory::from_config(config));
+        *FACTORY.write().unwrap_or_else(|e| e.into_inner()) = Some(factory);
+        println!("🔧 [HTTP] Clients rebuilt from the updated network config");
+    }
+
+    pub fn from_config(config: &NetworkConfig) -> Self {
+        let user_agent = config
+            .user_agent
+            .clone()
+            .unwrap_or_else(|| format!("LibreOllama/{} ({})", env!("CARGO_PKG_VERSION"), std::env::consts::OS));
+
+        let builder = || Self::base_builder(config, &user_agent);
+
+        // A client that fails to build with custom settings still has to work
+        let client = builder()
+            .timeout(Duration::from_millis(config.request_timeout_ms))
+            .build()
+            .unwrap_or_else(|e| {
+                eprintln!("⚠️ Failed to build configured HTTP client, using defaults: {}", e);
+                Client::new()
+            });
+        let streaming_client = builder().build().unwrap_or_else(|e| {
+            eprintln!("⚠️ Failed to build streaming HTTP client, using defaults: {}", e);
+            Client::new()
+        });
+
+        Self {
+            client,
+            streaming_client,
+            config: config.clone(),
+            user_agent,
+        }
+    }
+
+    fn base_builder(config: &NetworkConfig, user_agent: &str) -> ClientBuilder {
+        Client::builder()
+            .user_agent(user_agent)
+            .gzip(true)
+            .connect_timeout(Duration::from_millis(config.connect_timeout_ms))
+            .pool_idle_timeout(Duration::from_secs(config.pool_idle_timeout_secs))
+            .pool_max_idle_per_host(config.pool_max_idle_per_host)
+    }
+
+    /// Client for ordinary API calls, with the configured request timeout
+    pub fn client(&self) -> Client {
+        self.client.clone()
+    }
+
+    /// Client without an overall request timeout, for streamed or long-running
+    /// responses such as LLM generation and model downloads
+    pub fn streaming_client(&self) -> Client {
+        self.streaming_client.clone()
+    }
+
+    /// One-off client that only connects `host` to `addr` and never follows
+    /// redirects, for URLs taken from untrusted content. Pinning the address
+    /// that was checked stops a second DNS lookup from pointing elsewhere.
+    pub fn pinned_client(&self, host: &str, addr: SocketAddr) -> reqwest::Result<Client> {
+        Self::base_builder(&self.config, &self.user_agent)
+            .timeout(Duration::from_millis(self.config.request_timeout_ms))
+            .redirect(redirect::Policy::none())
+            .resolve(host, addr)
+            .build()
+    }
+
+    pub fn tracing_enabled(&self) -> bool {
+        self.config.enable_request_tracing
+    }
+}
+
+/// Shorthand for `HttpClientFactory::global().client()`
+pub fn http_client() -> Client {
+    HttpClientFactory::global().client()
+}
+
+/// Shorthand for `HttpClientFactory::global().streaming_client()`
+pub fn streaming_http_client() -> Client {
+    HttpClientFactory::global().streaming_client()
+}
+
+/// Adds a trace id header to outgoing requests when tracing is enabled
+pub trait TraceRequest {
+    fn traced(self) -> Self;
+}
+
+impl TraceRequest for RequestBuilder {
+    fn traced(self) -> Self {
+        if !HttpClientFactory::global().tracing_enabled() {
+            return self;
+        }
+        let trace_id = uuid::Uuid::new_v4().to_string();
+        println!("🔎 [HTTP] request {}", trace_id);
+        self.header(TRACE_HEADER, trace_id)
+    }
+}
+
+/// Body of a response fetched by `fetch_public`
+#[derive(Debug, Clone)]
+pub struct PublicResponse {
+    /// Where the last redirect led
+    pub url: Url,
+    pub content_type: String,
+    pub body: Vec<u8>,
+    /// The body was cut off at the byte limit
+    pub truncated: bool,
+}
+
+/// GET a URL taken from untrusted content. Every hop must resolve to public
+/// addresses only, redirects are followed by hand so each one is checked
+/// again, and at most `max_bytes` of the body is read. Responses whose
+/// (lowercased, parameter-free) content type fails `allow_type` are refused
+/// before the body is read.
+pub async fn fetch_public(
+    mut url: Url,
+    accept: &str,
+    max_bytes: usize,
+    allow_type: impl Fn(&str) -> bool,
+) -> Result<PublicResponse> {
+    for _ in 0..=MAX_REDIRECTS {
+        let (host, addr) = resolve_public_addr(&url).await?;
+        let client = HttpClientFactory::global()
+            .pinned_client(&host, addr)
+            .map_err(|e| network_error(&url, format!("Failed to build HTTP client: {}", e)))?;
+
+        let response = client
+            .get(url.clone())
+            .header(reqwest::header::ACCEPT, accept)
+            .send()
+            .await
+            .map_err(|e| network_error(&url, format!("Request failed: {}", e)))?;
+
+        if response.status().is_redirection() {
+            let location = response
+                .headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|v| v.to_str().ok())
+                .ok_or_else(|| network_error(&url, "Redirect without a Location header".to_string()))?;
+            url = url
+                .join(location)
+                .map_err(|e| network_error(&url, format!("Invalid redirect target: {}", e)))?;
+            continue;
+        }
+
+        if !response.status().is_success() {
+            return Err(network_error(&url, format!("Request returned {}", response.status())));
+        }
+
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(normalize_content_type)
+            .unwrap_or_default();
+        if !allow_type(&content_type) {
+            return Err(LibreOllamaError::PermissionDenied {
+                message: format!("Refusing to fetch content type '{}'", content_type),
+            });
+        }
+
+        // Content-Length can be missing or wrong, so the limit is enforced
+        // while reading
+        let mut body = Vec::new();
+        let mut truncated = false;
+        let mut stream = response.bytes_stream();
+        while !truncated {
+            let Some(chunk) = stream.next().await else { break };
+            let chunk = chunk.map_err(|e| network_error(&url, format!("Failed to read response: {}", e)))?;
+            let room = max_bytes - body.len();
+            truncated = chunk.len() > room;
+            body.extend_from_slice(&chunk[..chunk.len().min(room)]);
+        }
+
+        return Ok(PublicResponse { url, content_type, body, truncated });
+    }
+
+    Err(network_error(&url, format!("More than {} redirects", MAX_REDIRECTS)))
+}
+
+/// Resolve the URL's host and return the address to connect to, rejecting
+/// non-HTTP schemes and any host that resolves to a non-public address
+async fn resolve_public_addr(url: &Url) -> Result<(String, SocketAddr)> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(LibreOllamaError::InvalidInput {
+            message: format!("Unsupported URL scheme '{}'", url.scheme()),
+            field: Some("url".to_string()),
+        });
+    }
+
+    let port = url.port_or_known_default().unwrap_or(443);
+    let host = url.host().ok_or_else(|| LibreOllamaError::InvalidInput {
+        message: "URL has no host".to_string(),
+        field: Some("url".to_string()),
+    })?;
+
+    let (host_name, addrs): (String, Vec<SocketAddr>) = match host {
+        Host::Ipv4(ip) => (ip.to_string(), vec![SocketAddr::new(IpAddr::V4(ip), port)]),
+        Host::Ipv6(ip) => (ip.to_string(), vec![SocketAddr::new(IpAddr::V6(ip), port)]),
+        Host::Domain(domain) => {
+            let resolved = tokio::net::lookup_host((domain, port))
+                .await
+                .map_err(|e| network_error(url, format!("Failed to resolve {}: {}", domain, e)))?
+                .collect();
+            (domain.to_string(), resolved)
+        }
+    };
+
+    // Every address has to be public, otherwise a host with one public and
+    // one private record could still reach the private one
+    if let Some(blocked) = addrs.iter().find(|addr| !is_public_ip(&addr.ip())) {
+        eprintln!("🚫 [HTTP] Blocked {} -> {}", host_name, blocked.ip());
+        return Err(LibreOllamaError::PermissionDenied {
+            message: format!("Host {} resolves to a non-public address", host_name),
+        });
+    }
+
+    let addr = addrs
+        .into_iter()
+        .next()
+        .ok_or_else(|| network_error(url, format!("No addresses found for {}", host_name)))?;
+    Ok((host_name, addr))
+}
+
+/// Whether an address is on the public internet. Loopback, private,
+/// link-local, carrier-grade NAT, multicast, documentation and reserved
+/// ranges are all refused.
+pub fn is_public_ip(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => is_public_ipv4(v4),
+        IpAddr::V6(v6) => is_public_ipv6(v6),
+    }
+}
+
+fn is_public_ipv4(ip: &Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0
+        || (a == 100 && (64..128).contains(&b)) // 100.64.0.0/10 carrier-grade NAT
+        || (a == 192 && b == 0 && c == 0) // 192.0.0.0/24 IETF protocol assignments
+        || (a == 198 && (b == 18 || b == 19)) // 198.18.0.0/15 benchmarking
+        || a >= 240) // 240.0.0.0/4 reserved
+}
+
+fn is_public_ipv6(ip: &Ipv6Addr) -> bool {
+    // IPv4-mapped and IPv4-compatible addresses are judged by the IPv4 rules
+    if let Some(v4) = ip.to_ipv4() {
+        if !ip.is_loopback() && !ip.is_unspecified() {
+            return is_public_ipv4(&v4);
+        }
+    }
+
+    let first = ip.segments()[0];
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        || (first & 0xfe00) == 0xfc00 // fc00::/7 unique local
+        || (first & 0xffc0) == 0xfe80 // fe80::/10 link-local
+        || (first & 0xffc0) == 0xfec0 // fec0::/10 site-local
+        || (first == 0x2001 && ip.segments()[1] == 0x0db8)) // 2001:db8::/32 documentation
+}
+
+pub fn normalize_content_type(value: &str) -> String {
+    value.split(';').next().unwrap_or_default().trim().to_ascii_lowercase()
+}
+
+fn network_error(url: &Url, message: String) -> LibreOllamaError {
+    LibreOllamaError::Network {
+        message,
+        url: Some(url.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn public(ip: &str) -> bool {
+        is_public_ip(&ip.parse().unwrap())
+    }
+
+    #[test]
+    fn blocks_private_and_reserved_ranges() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "255.255.255.255",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:10.0.0.1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!public(ip), "{} should be blocked", ip);
+        }
+
+        for ip in ["8.8.8.8", "142.250.72.14", "2607:f8b0:4005:80a::200e", "::ffff:8.8.8.8"] {
+            assert!(public(ip), "{} should be allowed", ip);
+        }
+    }
+
+    #[test]
+    fn normalizes_content_types() {
+        assert_eq!(normalize_content_type("Text/HTML; charset=UTF-8"), "text/html");
+        assert_eq!(normalize_content_type(""), "");
+    }
+}
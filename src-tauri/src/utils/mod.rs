@@ -11,7 +11,9 @@ pub mod cache;
 pub mod crypto;
 pub mod networking;
 pub mod time;
+pub mod tokens;
 pub mod http;
+pub mod http_client;
 
 // Re-export all utilities for convenience
 // Note: These are infrastructure utilities - some are used by current features,
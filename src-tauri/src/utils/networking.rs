@@ -3,6 +3,7 @@
 //! Provides common networking helper functions.
 
 use crate::errors::{LibreOllamaError, Result};
+use crate::utils::http_client::http_client;
 
 /// Check if a port is available
 pub fn is_port_available(port: u16) -> bool {
@@ -16,16 +17,9 @@ pub fn find_available_port(start: u16, end: u16) -> Option<u16> {
 
 /// Basic HTTP client with timeout
 pub async fn http_get_with_timeout(url: &str, timeout_secs: u64) -> Result<String> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(timeout_secs))
-        .build()
-        .map_err(|e| LibreOllamaError::Network {
-            message: format!("Failed to create HTTP client: {}", e),
-            url: None,
-        })?;
-
-    let response = client
+    let response = http_client()
         .get(url)
+        .timeout(std::time::Duration::from_secs(timeout_secs))
         .send()
         .await
         .map_err(|e| LibreOllamaError::Network {
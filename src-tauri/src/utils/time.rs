@@ -2,7 +2,8 @@
 //!
 //! Provides common time handling, formatting, and parsing functions.
 
-use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono::{DateTime, Duration, NaiveDateTime, SecondsFormat, TimeZone, Utc};
+use chrono_tz::Tz;
 
 /// Format timestamp for display
 pub fn format_timestamp(timestamp: &DateTime<Utc>) -> String {
@@ -44,4 +45,52 @@ pub fn time_ago(timestamp: &DateTime<Utc>) -> String {
         format!("{} day{} ago", duration.num_days(),
                 if duration.num_days() == 1 { "" } else { "s" })
     }
-} 
\ No newline at end of file
+} 
+
+/// IANA zone of the operating system, or UTC when it cannot be read
+pub fn system_zone() -> Tz {
+    iana_time_zone::get_timezone().ok().and_then(|name| parse_zone(&name)).unwrap_or(Tz::UTC)
+}
+
+/// Parse an IANA zone name such as "Europe/Berlin"
+pub fn parse_zone(name: &str) -> Option<Tz> {
+    name.trim().parse().ok()
+}
+
+/// Parse a date and time without an offset, as the frontend and ICS files
+/// write local times
+pub fn parse_naive_local(value: &str) -> Option<NaiveDateTime> {
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y%m%dT%H%M%S"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value.trim(), format).ok())
+}
+
+/// A wall-clock time in `zone` as UTC. Times repeated when clocks go back
+/// take the first occurrence; times skipped when they go forward move past
+/// the gap.
+pub fn local_to_utc(time: NaiveDateTime, zone: &Tz) -> Option<DateTime<Utc>> {
+    zone.from_local_datetime(&time)
+        .earliest()
+        .or_else(|| zone.from_local_datetime(&(time + Duration::hours(1))).earliest())
+        .map(|local| local.with_timezone(&Utc))
+}
+
+/// An RFC 3339 time, or a local time read in `zone`, as UTC
+pub fn to_utc(value: &str, zone: &Tz) -> Option<DateTime<Utc>> {
+    match DateTime::parse_from_rfc3339(value.trim()) {
+        Ok(time) => Some(time.with_timezone(&Utc)),
+        Err(_) => local_to_utc(parse_naive_local(value)?, zone),
+    }
+}
+
+/// Like `to_utc` for a time carrying an optional zone name; unknown or
+/// missing zones fall back to `floating`
+pub fn zoned_to_utc(value: &str, time_zone: Option<&str>, floating: &Tz) -> Option<DateTime<Utc>> {
+    let zone = time_zone.and_then(parse_zone).unwrap_or(*floating);
+    to_utc(value, &zone)
+}
+
+/// UTC RFC 3339 with second precision, the form times are stored in
+pub fn utc_rfc3339(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
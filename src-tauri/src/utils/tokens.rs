@@ -0,0 +1,145 @@
+//! Token counting for context budgeting
+//!
+//! An offline estimate of how many tokens a text costs, close to what the
+//! GPT and Llama BPE tokenizers produce for English prose and code. Text is
+//! split the way those tokenizers pre-split it (words with their leading
+//! space, short digit groups, punctuation runs, whitespace) and each piece
+//! is costed by length. Counts reported by the model after a request are
+//! always preferred; this is for deciding what fits before sending.
+
+use regex::Regex;
+
+/// Tokens every chat message costs on top of its content (role, separators)
+pub const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+/// Tokens that prime the assistant's reply
+pub const REPLY_PRIMING_TOKENS: usize = 3;
+/// Window assumed for models missing from the table
+pub const DEFAULT_CONTEXT_WINDOW: usize = 8192;
+
+lazy_static::lazy_static! {
+    static ref PIECE: Regex = Regex::new(
+        r"(?i)'(?:s|t|re|ve|m|ll|d)| ?\p{L}+| ?\p{N}{1,3}| ?[^\s\p{L}\p{N}]+|\s+"
+    ).unwrap();
+}
+
+/// Context windows of common models, matched by name prefix after any
+/// `vendor/` part. Longer prefixes come first.
+static CONTEXT_WINDOWS: &[(&str, usize)] = &[
+    ("gpt-4o", 128_000),
+    ("gpt-4.1", 1_047_576),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-4", 8_192),
+    ("gpt-3.5-turbo", 16_385),
+    ("o1", 200_000),
+    ("o3", 200_000),
+    ("o4", 200_000),
+    ("claude", 200_000),
+    ("gemini", 1_048_576),
+    ("llama3.1", 131_072),
+    ("llama3.2", 131_072),
+    ("llama3.3", 131_072),
+    ("llama3", 8_192),
+    ("llama2", 4_096),
+    ("mistral-nemo", 131_072),
+    ("mistral", 32_768),
+    ("mixtral", 32_768),
+    ("qwen2.5", 32_768),
+    ("qwen3", 40_960),
+    ("gemma3", 131_072),
+    ("gemma2", 8_192),
+    ("phi4", 16_384),
+    ("phi3", 131_072),
+    ("deepseek-r1", 131_072),
+    ("deepseek", 65_536),
+    ("nomic-embed-text", 8_192),
+    ("mxbai-embed-large", 512),
+];
+
+fn piece_tokens(piece: &str) -> usize {
+    let trimmed = piece.trim_start_matches(' ');
+    let chars = trimmed.chars().count();
+    if chars == 0 {
+        // A lone space or run of spaces
+        return 1;
+    }
+    let first = trimmed.chars().next().unwrap_or(' ');
+    if first.is_whitespace() {
+        // Newline runs and indentation are usually one token each
+        return trimmed.matches('\n').count().max(1);
+    }
+    if first.is_alphabetic() {
+        if !trimmed.is_ascii() {
+            // Accented Latin text splits more, CJK close to one token per character
+            let wide = trimmed.chars().filter(|c| (*c as u32) >= 0x2E80).count();
+            return wide + (chars - wide).div_ceil(3).max(usize::from(wide == 0));
+        }
+        // Common words are a single token; long ones split every ~6 characters
+        return if chars <= 8 { 1 } else { chars.div_ceil(6) };
+    }
+    if first.is_numeric() {
+        return 1;
+    }
+    // Punctuation and symbols pair up at best
+    chars.div_ceil(2)
+}
+
+/// Estimated tokens in `text`
+pub fn count_tokens(text: &str) -> usize {
+    PIECE.find_iter(text).map(|piece| piece_tokens(piece.as_str())).sum()
+}
+
+/// Estimated tokens for one chat message, including its overhead
+pub fn message_tokens(content: &str) -> usize {
+    MESSAGE_OVERHEAD_TOKENS + count_tokens(content)
+}
+
+/// The longest prefix of `text` that fits in `max_tokens`, cut at a piece
+/// boundary
+pub fn truncate_to_tokens(text: &str, max_tokens: usize) -> &str {
+    let mut used = 0;
+    for piece in PIECE.find_iter(text) {
+        used += piece_tokens(piece.as_str());
+        if used > max_tokens {
+            return &text[..piece.start()];
+        }
+    }
+    text
+}
+
+/// Known context window of a model, e.g. `llama3.1:8b` or `openai/gpt-4o-mini`
+pub fn model_context_window(model: &str) -> Option<usize> {
+    let name = model.rsplit('/').next().unwrap_or(model).to_lowercase();
+    CONTEXT_WINDOWS
+        .iter()
+        .find(|(prefix, _)| name.starts_with(prefix))
+        .map(|(_, window)| *window)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_tokens() {
+        assert_eq!(count_tokens(""), 0);
+        assert_eq!(count_tokens("Hello world"), 2);
+        assert_eq!(count_tokens("The meeting is at 10:30 tomorrow."), 9);
+        // Long words and numbers split into several tokens
+        assert!(count_tokens("internationalization") > 1);
+        assert_eq!(count_tokens("123456789"), 3);
+        assert!(count_tokens("会议改到星期五") >= 7);
+    }
+
+    #[test]
+    fn test_truncate_and_windows() {
+        let text = "one two three four five";
+        assert_eq!(truncate_to_tokens(text, 2), "one two");
+        assert_eq!(truncate_to_tokens(text, 100), text);
+        assert!(count_tokens(truncate_to_tokens(text, 3)) <= 3);
+
+        assert_eq!(model_context_window("llama3.1:8b"), Some(131_072));
+        assert_eq!(model_context_window("llama3:latest"), Some(8_192));
+        assert_eq!(model_context_window("openai/gpt-4o-mini"), Some(128_000));
+        assert_eq!(model_context_window("my-finetune"), None);
+    }
+}
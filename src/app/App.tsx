@@ -9,6 +9,7 @@ import Sidebar from '../components/navigation/Sidebar';
 import { CommandPalette } from '../components/CommandPalette';
 import { TextSelectionDetector } from '../components/ai/TextSelectionDetector';
 import { useCommandPalette } from '../core/hooks/useCommandPalette';
+import { useUserActivityReporter } from '../core/hooks/useUserActivityReporter';
 import { useInitializeSettings } from '../stores/settingsStore';
 import { MailStoreProvider } from '../features/mail/components/MailStoreProvider';
 import { queryClient } from '../config/queryClient';
@@ -59,6 +60,7 @@ export default function App() {
   const [isSidebarOpen, setSidebarOpen] = useState(true);
   const { isOpen, close } = useCommandPalette();
   const initializeSettings = useInitializeSettings();
+  useUserActivityReporter();
 
   useEffect(() => {
     initializeSettings();
@@ -28,6 +28,7 @@ import { CalendarView, CalendarEvent, DraggedItem } from './calendar/types/calen
 import type { GoogleCalendarEvent } from '../../types/google';
 import { navigateDate, getDateRange, getWeekDays } from './calendar/utils/dateUtils';
 import { useCalendarOperations } from './calendar/hooks/useCalendarOperations';
+import { errorMessage } from '@/core/errors/errorHandler';
 
 // Import styles
 
@@ -482,7 +483,7 @@ export default function CalendarCustom() {
                   await refreshData();
                 } catch (error) {
                   console.error('Failed to duplicate task:', error);
-                  alert('Failed to duplicate task: ' + (error as any).message);
+                  alert('Failed to duplicate task: ' + errorMessage(error));
                 }
               }}
             />
@@ -527,8 +528,7 @@ export default function CalendarCustom() {
               setSelectedEvent(null);
             } catch (error) {
               console.error('Failed to save event:', error);
-              const errorMessage = error instanceof Error ? error.message : 'Unknown error';
-              alert('Failed to save event: ' + errorMessage);
+              alert('Failed to save event: ' + errorMessage(error));
               // Log the full error details for debugging
               console.log('Event data that failed:', eventData);
             }
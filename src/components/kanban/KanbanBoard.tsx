@@ -22,6 +22,7 @@ import '../../styles/asana-core.css';
 import { createTaskList } from "../../api/googleTasksApi";
 import { realtimeSync } from "../../services/realtimeSync";
 import { useFilteredColumns } from "../../hooks/useFilteredColumns";
+import { errorMessage } from '@/core/errors/errorHandler';
 
 type KanbanTask = UnifiedTask;
 
@@ -145,7 +146,7 @@ export const KanbanBoard: React.FC<KanbanBoardProps> = ({
           console.log('Move task completed');
         } catch (error) {
           console.error('Failed to move task:', error);
-          alert(`Failed to move task: ${error}`);
+          alert(`Failed to move task: ${errorMessage(error)}`);
         }
       } else {
         console.log('Same column, not moving');
@@ -52,6 +52,61 @@ export interface ErrorContext {
   userMessage?: string;
 }
 
+/**
+ * Error returned by Tauri commands (CommandError in the backend)
+ */
+export interface CommandError {
+  code: string;
+  category: string;
+  user_message: string;
+  retryable: boolean;
+  retry_after_ms?: number | null;
+  details?: string | null;
+}
+
+export function isCommandError(error: unknown): error is CommandError {
+  return (
+    !!error &&
+    typeof error === 'object' &&
+    typeof (error as CommandError).code === 'string' &&
+    typeof (error as CommandError).user_message === 'string'
+  );
+}
+
+/**
+ * Text to show for anything a command or service threw. Tauri commands
+ * reject with a CommandError object, which prints as "[object Object]" when
+ * interpolated.
+ */
+export function errorMessage(error: unknown): string {
+  if (isCommandError(error)) {
+    return error.user_message;
+  }
+  if (error instanceof Error) {
+    return error.message;
+  }
+  return typeof error === 'string' ? error : JSON.stringify(error);
+}
+
+const COMMAND_ERROR_TYPES: Record<string, ErrorType> = {
+  gmail_auth: ErrorType.AUTH_FAILED,
+  oauth: ErrorType.AUTH_FAILED,
+  gmail_token: ErrorType.TOKEN_EXPIRED,
+  network: ErrorType.NETWORK_ERROR,
+  timeout: ErrorType.TIMEOUT,
+  rate_limit: ErrorType.RATE_LIMIT,
+  gmail_api: ErrorType.API_ERROR,
+  google_tasks_api: ErrorType.API_ERROR,
+  google_calendar_api: ErrorType.API_ERROR,
+  google_drive_api: ErrorType.API_ERROR,
+  google_people_api: ErrorType.API_ERROR,
+  permission_denied: ErrorType.PERMISSION_DENIED,
+  invalid_input: ErrorType.VALIDATION_ERROR,
+  not_found: ErrorType.NOT_FOUND,
+  file_system: ErrorType.STORAGE_ERROR,
+  internal: ErrorType.INTERNAL_ERROR,
+};
+
 export interface ErrorHandlerOptions {
   showNotification?: boolean;
   logToConsole?: boolean;
@@ -109,6 +164,7 @@ class ErrorHandler {
     let message = 'An unknown error occurred';
     let details: any = {};
     let userMessage: string | undefined;
+    let retryable: boolean | undefined;
 
     if (error instanceof Error) {
       message = error.message;
@@ -143,6 +199,12 @@ class ErrorHandler {
         type = ErrorType.TOKEN_EXPIRED;
         userMessage = 'Your session has expired. Please sign in again.';
       }
+    } else if (isCommandError(error)) {
+      type = COMMAND_ERROR_TYPES[error.code] ?? ErrorType.UNKNOWN;
+      message = error.details ?? error.user_message;
+      userMessage = error.user_message;
+      retryable = error.retryable;
+      details = error;
     } else if (typeof error === 'string') {
       message = error;
     } else if (error && typeof error === 'object') {
@@ -156,7 +218,7 @@ class ErrorHandler {
       details,
       timestamp: new Date(),
       source,
-      retryable: options.retryable ?? this.isRetryable(type),
+      retryable: options.retryable ?? retryable ?? this.isRetryable(type),
       userMessage: userMessage || this.getDefaultUserMessage(type),
     };
   }
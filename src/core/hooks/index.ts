@@ -1,6 +1,7 @@
 export { useDebounce } from './useDebounce';
 export { useCommandPalette } from './useCommandPalette';
 export { useTextSelection } from './useTextSelection';
+export { useUserActivityReporter } from './useUserActivityReporter';
 
 // Focus trap hook for modals and dialogs
 export { useFocusTrap } from './useFocusTrap';
@@ -0,0 +1,32 @@
+import { useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+
+/** At most one report per interval; the backend counts idle in minutes */
+const REPORT_INTERVAL_MS = 30 * 1000;
+const INPUT_EVENTS = ['keydown', 'pointerdown', 'wheel'] as const;
+
+/**
+ * Tells the backend the user is at the keyboard or pointer, so idle-time
+ * work (maintenance, embedding attachments) waits. Commands invoked on
+ * timers do not count, so only real input is reported.
+ */
+export function useUserActivityReporter(): void {
+  useEffect(() => {
+    let lastReport = 0;
+    const report = () => {
+      const now = Date.now();
+      if (now - lastReport < REPORT_INTERVAL_MS) {
+        return;
+      }
+      lastReport = now;
+      invoke('report_user_activity').catch(() => {
+        // Activity is a hint; a missed report only lets idle work start sooner
+      });
+    };
+
+    INPUT_EVENTS.forEach((event) => window.addEventListener(event, report, { passive: true, capture: true }));
+    return () => {
+      INPUT_EVENTS.forEach((event) => window.removeEventListener(event, report, { capture: true }));
+    };
+  }, []);
+}
@@ -5,6 +5,7 @@ import { invoke } from '@tauri-apps/api/core';
 import { OllamaService, ModelInfo } from '../../../services/ollamaService';
 import { LLMProviderManager, LLMModel, LLMProvider, LLMMessage } from '../../../services/llmProviders';
 import { logger } from '../../../core/lib/logger';
+import { errorMessage } from '@/core/errors/errorHandler';
 
 // Types that match the UI component interfaces
 export interface ChatMessage {
@@ -265,7 +266,7 @@ export const useChatStore = create<ChatState>()(
             }
           } catch (error) {
             console.error('❌ chatStore deleteConversation error:', error);
-            set({ error: `Failed to delete conversation: ${error}` });
+            set({ error: `Failed to delete conversation: ${errorMessage(error)}` });
           }
         },
 
@@ -1,4 +1,5 @@
 import { invoke } from '@tauri-apps/api/core';
+import { isCommandError } from '@/core/errors/errorHandler';
 import { 
   handleGmailError, 
   retryGmailOperation, 
@@ -132,9 +133,22 @@ export class GmailComposeService {
           throw handleGmailError('Message size exceeds 25MB limit', context);
         }
 
-        return await invoke<SendResponse>('send_gmail_message', {
-          composeRequest: this.toBackendComposeRequest(composeRequest),
-        });
+        const composeRequestForBackend = this.toBackendComposeRequest(composeRequest);
+        try {
+          return await invoke<SendResponse>('send_gmail_message', {
+            composeRequest: composeRequestForBackend,
+          });
+        } catch (error) {
+          // The backend holds back a message that mentions a missing
+          // attachment; its message asks whether to send anyway
+          if (isCommandError(error) && error.code === 'attachment_missing' && window.confirm(error.user_message)) {
+            return await invoke<SendResponse>('send_gmail_message', {
+              composeRequest: composeRequestForBackend,
+              attachmentReminderAcknowledged: true,
+            });
+          }
+          throw error;
+        }
       },
       context,
       { maxRetries: 2 } // Reduce retries for send operations
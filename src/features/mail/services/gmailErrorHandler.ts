@@ -1,4 +1,5 @@
 import { invoke } from '@tauri-apps/api/core';
+import { isCommandError, type CommandError } from '@/core/errors/errorHandler';
 
 // Comprehensive error types for Gmail operations
 export enum GmailErrorType {
@@ -65,6 +66,19 @@ export interface ErrorContext {
   networkStatus?: 'online' | 'offline';
 }
 
+// Backend error codes (CommandError.code) with a Gmail error type of their own
+const COMMAND_ERROR_TYPES: Record<string, GmailErrorType> = {
+  gmail_auth: GmailErrorType.AUTH_ERROR,
+  oauth: GmailErrorType.OAUTH_ERROR,
+  gmail_token: GmailErrorType.TOKEN_EXPIRED,
+  rate_limit: GmailErrorType.RATE_LIMITED,
+  network: GmailErrorType.NETWORK_ERROR,
+  timeout: GmailErrorType.TIMEOUT_ERROR,
+  invalid_input: GmailErrorType.VALIDATION_ERROR,
+  permission_denied: GmailErrorType.PERMISSION_DENIED,
+  not_found: GmailErrorType.NOT_FOUND,
+};
+
 export class GmailErrorHandler {
   private static readonly DEFAULT_RETRY_CONFIG: RetryConfig = {
     maxRetries: 3,
@@ -199,6 +213,11 @@ export class GmailErrorHandler {
       return this.createError(GmailErrorType.OFFLINE_ERROR, '', { context, networkStatus }, timestamp);
     }
 
+    // Handle structured errors from Tauri commands
+    if (isCommandError(error)) {
+      return this.parseCommandError(error, context, timestamp);
+    }
+
     // Handle string errors
     if (typeof error === 'string') {
       return this.parseStringError(error, context, timestamp);
@@ -227,6 +246,14 @@ export class GmailErrorHandler {
     }, timestamp);
   }
 
+  private static parseCommandError(error: CommandError, context: ErrorContext, timestamp: string): GmailError {
+    const type = COMMAND_ERROR_TYPES[error.code];
+    if (!type) {
+      return this.parseStringError(error.user_message, context, timestamp);
+    }
+    return this.createError(type, error.user_message, { context, originalError: error }, timestamp);
+  }
+
   private static parseStringError(error: string, context: ErrorContext, timestamp: string): GmailError {
     const lowerError = error.toLowerCase();
     
@@ -1,4 +1,5 @@
 import { invoke } from '@tauri-apps/api/core';
+import { errorMessage } from '@/core/errors/errorHandler';
 
 export interface ParsedEmailMessage {
   message_id?: string;
@@ -81,7 +82,7 @@ export class GmailParsingService {
       return result;
     } catch (error) {
       console.error('Failed to parse Gmail message:', error);
-      throw new Error(`Failed to parse Gmail message: ${error}`);
+      throw new Error(`Failed to parse Gmail message: ${errorMessage(error)}`);
     }
   }
 
@@ -100,7 +101,7 @@ export class GmailParsingService {
       return result;
     } catch (error) {
       console.error('Failed to parse Gmail thread:', error);
-      throw new Error(`Failed to parse Gmail thread: ${error}`);
+      throw new Error(`Failed to parse Gmail thread: ${errorMessage(error)}`);
     }
   }
 
@@ -117,7 +118,7 @@ export class GmailParsingService {
       return result;
     } catch (error) {
       console.error('Failed to search and parse Gmail messages:', error);
-      throw new Error(`Failed to search and parse Gmail messages: ${error}`);
+      throw new Error(`Failed to search and parse Gmail messages: ${errorMessage(error)}`);
     }
   }
 
@@ -132,7 +133,7 @@ export class GmailParsingService {
       return result;
     } catch (error) {
       console.error('Failed to extract text from HTML:', error);
-      throw new Error(`Failed to extract text from HTML: ${error}`);
+      throw new Error(`Failed to extract text from HTML: ${errorMessage(error)}`);
     }
   }
 
@@ -153,7 +154,7 @@ export class GmailParsingService {
       return result;
     } catch (error) {
       console.error('Failed to sync Gmail messages:', error);
-      throw new Error(`Failed to sync Gmail messages: ${error}`);
+      throw new Error(`Failed to sync Gmail messages: ${errorMessage(error)}`);
     }
   }
 
@@ -174,7 +175,7 @@ export class GmailParsingService {
       return new Uint8Array(result);
     } catch (error) {
       console.error('Failed to get Gmail attachment:', error);
-      throw new Error(`Failed to get Gmail attachment: ${error}`);
+      throw new Error(`Failed to get Gmail attachment: ${errorMessage(error)}`);
     }
   }
 
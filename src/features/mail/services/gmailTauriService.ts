@@ -15,6 +15,7 @@ import type {
   ProcessedGmailMessage
 } from '../types';
 import { logger } from '../../../core/lib/logger';
+import { errorMessage } from '@/core/errors/errorHandler';
 
 // =============================================================================
 // Authentication Functions
@@ -373,7 +374,7 @@ export class GmailTauriService {
       return labels;
     } catch (error) {
       logger.error('❌ [TauriService] Failed to get labels:', error);
-      throw new Error(`Failed to get Gmail labels: ${error}`);
+      throw new Error(`Failed to get Gmail labels: ${errorMessage(error)}`);
     }
   }
 
@@ -401,7 +402,7 @@ export class GmailTauriService {
       return result;
     } catch (error) {
       logger.error('❌ [TauriService] Failed to search messages:', error);
-      throw new Error(`Failed to search Gmail messages: ${error}`);
+      throw new Error(`Failed to search Gmail messages: ${errorMessage(error)}`);
     }
   }
 
@@ -490,7 +491,7 @@ export class GmailTauriService {
       return userInfo;
     } catch (error) {
       logger.error(`❌ [TauriService] Failed to get user profile for account ${this.accountId}:`, error);
-      throw new Error(`Failed to get Gmail user profile: ${error}`);
+      throw new Error(`Failed to get Gmail user profile: ${errorMessage(error)}`);
     }
   }
 
@@ -515,7 +516,7 @@ export class GmailTauriService {
       logger.debug(`[TauriService] Successfully modified ${messageIds.length} messages`);
     } catch (error) {
       logger.error('❌ [TauriService] Failed to modify messages:', error);
-      throw new Error(`Failed to modify Gmail messages: ${error}`);
+      throw new Error(`Failed to modify Gmail messages: ${errorMessage(error)}`);
     }
   }
 
@@ -554,7 +555,7 @@ export class GmailTauriService {
       logger.debug(`[TauriService] Successfully trashed ${messageIds.length} messages`);
     } catch (error) {
       logger.error('❌ [TauriService] Failed to trash messages:', error);
-      throw new Error(`Failed to trash Gmail messages: ${error}`);
+      throw new Error(`Failed to trash Gmail messages: ${errorMessage(error)}`);
     }
   }
 
@@ -575,7 +576,7 @@ export class GmailTauriService {
       return new Uint8Array(data);
     } catch (error) {
       logger.error(`❌ [TauriService] Failed to get attachment ${attachmentId}:`, error);
-      throw new Error(`Failed to get Gmail attachment: ${error}`);
+      throw new Error(`Failed to get Gmail attachment: ${errorMessage(error)}`);
     }
   }
 
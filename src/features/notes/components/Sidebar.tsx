@@ -12,6 +12,7 @@ import { writeTextFile, writeFile } from '@tauri-apps/plugin-fs';
 import { save } from '@tauri-apps/plugin-dialog';
 import { blocksToHtml, blocksToText } from '../utils/blocksToHtml';
 import { useNotifications } from '../hooks/useNotifications';
+import { errorMessage } from '@/core/errors/errorHandler';
 
 interface SidebarProps {
   isOpen?: boolean;
@@ -174,10 +175,10 @@ export const Sidebar: React.FC<SidebarProps> = ({ isOpen = true, onToggle, onSel
           await fetchNotes();
         } catch (error) {
           console.error('❌ Drag & drop move failed:', error);
-          const errorMessage = error instanceof Error ? error.message : String(error);
+          const message = errorMessage(error);
           
           const event = new CustomEvent('app-notification', {
-            detail: { type: 'error', message: `Failed to move note: ${errorMessage}` }
+            detail: { type: 'error', message: `Failed to move note: ${message}` }
           });
           window.dispatchEvent(event);
         }
@@ -360,9 +361,9 @@ export const Sidebar: React.FC<SidebarProps> = ({ isOpen = true, onToggle, onSel
       
     } catch (error) {
       console.error(`❌ Export failed for ${format}:`, error);
-      const errorMessage = error instanceof Error ? error.message : String(error);
+      const message = errorMessage(error);
       const errorEvent = new CustomEvent('app-notification', {
-        detail: { type: 'error', message: `Export failed: ${errorMessage}` }
+        detail: { type: 'error', message: `Export failed: ${message}` }
       });
       window.dispatchEvent(errorEvent);
     }
@@ -497,7 +498,7 @@ export const Sidebar: React.FC<SidebarProps> = ({ isOpen = true, onToggle, onSel
                               window.dispatchEvent(event);
                             })
                             .catch((error) => {
-                              const event = new CustomEvent('app-notification', { detail: { type: 'error', message: `Move failed: ${error}` } });
+                              const event = new CustomEvent('app-notification', { detail: { type: 'error', message: `Move failed: ${errorMessage(error)}` } });
                               window.dispatchEvent(event);
                             });
                         }
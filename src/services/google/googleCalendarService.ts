@@ -7,6 +7,7 @@ import {
   PaginatedResponse,
   GoogleApiError 
 } from '../../types/google';
+import { errorMessage } from '@/core/errors/errorHandler';
 
 // Always use real Tauri invoke - no mock data
 const apiInvoke = invoke;
@@ -16,7 +17,7 @@ class GoogleCalendarService {
     console.error('Google Calendar API Error:', error);
     return {
       code: error.code || 500,
-      message: error.message || 'An error occurred',
+      message: errorMessage(error) || 'An error occurred',
       status: error.status || 'INTERNAL_ERROR'
     };
   }
@@ -8,6 +8,7 @@ import {
   PaginatedResponse,
   GoogleApiError 
 } from '../../types/google';
+import { errorMessage } from '@/core/errors/errorHandler';
 
 // Always use real Tauri invoke - no mock data
 const apiInvoke = invoke;
@@ -17,7 +18,7 @@ class GoogleTasksService {
     console.error('Google Tasks API Error:', error);
     return {
       code: error.code || 500,
-      message: error.message || 'An error occurred',
+      message: errorMessage(error) || 'An error occurred',
       status: error.status || 'INTERNAL_ERROR'
     };
   }
@@ -1,6 +1,7 @@
 import { invoke } from '@tauri-apps/api/core';
 import { OllamaService } from './ollamaService';
 import { logger } from '../core/lib/logger';
+import { errorMessage } from '@/core/errors/errorHandler';
 
 // Provider types
 export type LLMProvider = 'ollama' | 'openai' | 'anthropic' | 'openrouter' | 'deepseek' | 'mistral' | 'gemini'; // Added 'gemini'
@@ -98,7 +99,7 @@ export class OpenAIProvider extends BaseLLMProvider {
       return response as string;
     } catch (error) {
       logger.error('OpenAI chat error:', error);
-      throw new Error(`OpenAI API error: ${error}`);
+      throw new Error(`OpenAI API error: ${errorMessage(error)}`);
     }
   }
 
@@ -122,7 +123,7 @@ export class OpenAIProvider extends BaseLLMProvider {
       }));
     } catch (error) {
       logger.error('Failed to fetch OpenAI models:', error);
-      throw new Error(`Failed to fetch OpenAI models: ${error}`);
+      throw new Error(`Failed to fetch OpenAI models: ${errorMessage(error)}`);
     }
   }
 
@@ -150,7 +151,7 @@ export class AnthropicProvider extends BaseLLMProvider {
       return response as string;
     } catch (error) {
       logger.error('Anthropic chat error:', error);
-      throw new Error(`Anthropic API error: ${error}`);
+      throw new Error(`Anthropic API error: ${errorMessage(error)}`);
     }
   }
 
@@ -174,7 +175,7 @@ export class AnthropicProvider extends BaseLLMProvider {
       }));
     } catch (error) {
       logger.error('Failed to fetch Anthropic models:', error);
-      throw new Error(`Failed to fetch Anthropic models: ${error}`);
+      throw new Error(`Failed to fetch Anthropic models: ${errorMessage(error)}`);
     }
   }
 
@@ -202,7 +203,7 @@ export class OpenRouterProvider extends BaseLLMProvider {
       return response as string;
     } catch (error) {
       logger.error('OpenRouter chat error:', error);
-      throw new Error(`OpenRouter API error: ${error}`);
+      throw new Error(`OpenRouter API error: ${errorMessage(error)}`);
     }
   }
 
@@ -226,7 +227,7 @@ export class OpenRouterProvider extends BaseLLMProvider {
       }));
     } catch (error) {
       logger.error('Failed to fetch OpenRouter models:', error);
-      throw new Error(`Failed to fetch OpenRouter models: ${error}`);
+      throw new Error(`Failed to fetch OpenRouter models: ${errorMessage(error)}`);
     }
   }
 
@@ -254,7 +255,7 @@ export class DeepSeekProvider extends BaseLLMProvider {
       return response as string;
     } catch (error) {
       logger.error('DeepSeek chat error:', error);
-      throw new Error(`DeepSeek API error: ${error}`);
+      throw new Error(`DeepSeek API error: ${errorMessage(error)}`);
     }
   }
 
@@ -278,7 +279,7 @@ export class DeepSeekProvider extends BaseLLMProvider {
       }));
     } catch (error) {
       logger.error('Failed to fetch DeepSeek models:', error);
-      throw new Error(`Failed to fetch DeepSeek models: ${error}`);
+      throw new Error(`Failed to fetch DeepSeek models: ${errorMessage(error)}`);
     }
   }
 
@@ -306,7 +307,7 @@ export class MistralProvider extends BaseLLMProvider {
       return response as string;
     } catch (error) {
       logger.error('Mistral chat error:', error);
-      throw new Error(`Mistral API error: ${error}`);
+      throw new Error(`Mistral API error: ${errorMessage(error)}`);
     }
   }
 
@@ -331,7 +332,7 @@ export class MistralProvider extends BaseLLMProvider {
       }));
     } catch (error) {
       logger.error('Failed to fetch Mistral models:', error);
-      throw new Error(`Failed to fetch Mistral models: ${error}`);
+      throw new Error(`Failed to fetch Mistral models: ${errorMessage(error)}`);
     }
   }
 
@@ -363,7 +364,7 @@ export class GeminiProvider extends BaseLLMProvider {
       return response as string;
     } catch (error) {
       logger.error('Gemini chat error:', error);
-      throw new Error(`Gemini API error: ${error}`);
+      throw new Error(`Gemini API error: ${errorMessage(error)}`);
     }
   }
 
@@ -387,7 +388,7 @@ export class GeminiProvider extends BaseLLMProvider {
       }));
     } catch (error) {
       logger.error('Failed to fetch Gemini models:', error);
-      throw new Error(`Failed to fetch Gemini models: ${error}`);
+      throw new Error(`Failed to fetch Gemini models: ${errorMessage(error)}`);
     }
   }
 
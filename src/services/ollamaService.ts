@@ -1,6 +1,7 @@
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 import { useSettingsStore } from '../stores/settingsStore';
+import { errorMessage } from '@/core/errors/errorHandler';
 
 // Types for Ollama responses
 export interface ModelInfo {
@@ -73,7 +74,7 @@ export class OllamaService {
     try {
       return await invoke<OllamaHealthResponse>('ollama_health_check');
     } catch (error) {
-      throw new Error(`Failed to check Ollama health: ${error}`);
+      throw new Error(`Failed to check Ollama health: ${errorMessage(error)}`);
     }
   }
 
@@ -81,7 +82,7 @@ export class OllamaService {
     try {
       return await invoke<OllamaHealthResponse>('ollama_get_status');
     } catch (error) {
-      throw new Error(`Failed to get Ollama status: ${error}`);
+      throw new Error(`Failed to get Ollama status: ${errorMessage(error)}`);
     }
   }
 
@@ -90,7 +91,7 @@ export class OllamaService {
     try {
       return await invoke<string>('ollama_start_sidecar');
     } catch (error) {
-      throw new Error(`Failed to start Ollama sidecar: ${error}`);
+      throw new Error(`Failed to start Ollama sidecar: ${errorMessage(error)}`);
     }
   }
 
@@ -98,7 +99,7 @@ export class OllamaService {
     try {
       return await invoke<string>('ollama_stop_sidecar');
     } catch (error) {
-      throw new Error(`Failed to stop Ollama sidecar: ${error}`);
+      throw new Error(`Failed to stop Ollama sidecar: ${errorMessage(error)}`);
     }
   }
 
@@ -107,7 +108,7 @@ export class OllamaService {
     try {
       return await invoke<ModelInfo[]>('ollama_list_models');
     } catch (error) {
-      throw new Error(`Failed to list models: ${error}`);
+      throw new Error(`Failed to list models: ${errorMessage(error)}`);
     }
   }
 
@@ -115,7 +116,7 @@ export class OllamaService {
     try {
       return await invoke('ollama_get_model_info', { modelName });
     } catch (error) {
-      throw new Error(`Failed to get model info for ${modelName}: ${error}`);
+      throw new Error(`Failed to get model info for ${modelName}: ${errorMessage(error)}`);
     }
   }
 
@@ -123,7 +124,7 @@ export class OllamaService {
     try {
       return await invoke<string>('ollama_delete_model', { modelName });
     } catch (error) {
-      throw new Error(`Failed to delete model ${modelName}: ${error}`);
+      throw new Error(`Failed to delete model ${modelName}: ${errorMessage(error)}`);
     }
   }
 
@@ -152,7 +153,7 @@ export class OllamaService {
 
       return result;
     } catch (error) {
-      throw new Error(`Failed to pull model ${modelName}: ${error}`);
+      throw new Error(`Failed to pull model ${modelName}: ${errorMessage(error)}`);
     }
   }
 
@@ -165,7 +166,7 @@ export class OllamaService {
         model: modelToUse
       });
     } catch (error) {
-      throw new Error(`Failed to chat with model: ${error}`);
+      throw new Error(`Failed to chat with model: ${errorMessage(error)}`);
     }
   }
 
@@ -196,7 +197,7 @@ export class OllamaService {
 
       return result;
     } catch (error) {
-      throw new Error(`Failed to stream chat with model: ${error}`);
+      throw new Error(`Failed to stream chat with model: ${errorMessage(error)}`);
     }
   }
 
@@ -208,7 +209,7 @@ export class OllamaService {
         model: modelToUse
       });
     } catch (error) {
-      throw new Error(`Failed to generate with model: ${error}`);
+      throw new Error(`Failed to generate with model: ${errorMessage(error)}`);
     }
   }
 